@@ -0,0 +1,222 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversions between the Swim data model and JSON (using [`serde_json`]).
+//!
+//! JSON cannot represent all of the features of the Swim model directly so the following encoding
+//! is used:
+//!
+//! - `Extant` is represented as `null`.
+//! - Numbers, booleans and text are represented by the corresponding JSON values. Big integers that
+//!   cannot be represented as 64 bit integers are written as decimal strings and non-finite floating
+//!   point numbers are written as `null`.
+//! - Blobs are written as Base64 strings.
+//! - A record with no attributes, consisting only of value items, is written as an array.
+//! - A record where every item is a slot with a text key, and the keys are distinct, is written as
+//!   an object. Each attribute of the record is written as a field with the name of the attribute,
+//!   prefixed with `@`, before the slots.
+//! - Any other record is written as an object with the attributes as fields (as above) and the
+//!   items as an array in a field called `$items`. Slots in the array are written as objects with
+//!   two fields, `$key` and `$value`.
+//! - Slot keys that begin with `@` or `$` are written with an additional `$` prefix.
+//!
+//! Converting JSON into the Swim model reverses this encoding so, other than for big integers,
+//! non-finite numbers and blobs, a value will be unchanged by a round trip through JSON.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashSet;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{Map, Number};
+use swimos_form::{read::ReadError, Form};
+use swimos_model::{Attr, Item, Text, Value};
+use thiserror::Error;
+
+const ATTR_PREFIX: char = '@';
+const ESCAPE_PREFIX: char = '$';
+const ITEMS_FIELD: &str = "$items";
+const KEY_FIELD: &str = "$key";
+const VALUE_FIELD: &str = "$value";
+
+/// Error type for failures to interpret a JSON string as a type.
+#[derive(Debug, Error)]
+pub enum JsonError {
+    /// The string was not valid JSON.
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The JSON did not describe a valid instance of the type.
+    #[error("The JSON value was not valid for the type: {0}")]
+    Read(#[from] ReadError),
+}
+
+/// Convert a Swim model value into JSON.
+pub fn value_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Extant => serde_json::Value::Null,
+        Value::Int32Value(n) => (*n).into(),
+        Value::Int64Value(n) => (*n).into(),
+        Value::UInt32Value(n) => (*n).into(),
+        Value::UInt64Value(n) => (*n).into(),
+        Value::Float64Value(x) => Number::from_f64(*x)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Value::BooleanValue(p) => (*p).into(),
+        Value::BigInt(n) => i64::try_from(n)
+            .map(Into::into)
+            .unwrap_or_else(|_| n.to_string().into()),
+        Value::BigUint(n) => u64::try_from(n)
+            .map(Into::into)
+            .unwrap_or_else(|_| n.to_string().into()),
+        Value::Text(text) => text.as_str().into(),
+        Value::Data(blob) => STANDARD.encode(blob).into(),
+        Value::Record(attrs, items) => record_to_json(attrs, items),
+    }
+}
+
+fn record_to_json(attrs: &[Attr], items: &[Item]) -> serde_json::Value {
+    if attrs.is_empty() {
+        let elements = items
+            .iter()
+            .map(|item| match item {
+                Item::ValueItem(value) => Some(value_to_json(value)),
+                Item::Slot(..) => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        if let Some(elements) = elements {
+            return serde_json::Value::Array(elements);
+        }
+    }
+    let mut fields = Map::new();
+    for Attr { name, value } in attrs {
+        fields.insert(format!("{}{}", ATTR_PREFIX, name), value_to_json(value));
+    }
+    if has_distinct_text_keys(items) {
+        for item in items {
+            if let Item::Slot(Value::Text(key), value) = item {
+                fields.insert(escape_key(key), value_to_json(value));
+            }
+        }
+    } else {
+        let items = items
+            .iter()
+            .map(|item| match item {
+                Item::ValueItem(value) => value_to_json(value),
+                Item::Slot(key, value) => {
+                    let mut slot = Map::new();
+                    slot.insert(KEY_FIELD.to_string(), value_to_json(key));
+                    slot.insert(VALUE_FIELD.to_string(), value_to_json(value));
+                    serde_json::Value::Object(slot)
+                }
+            })
+            .collect();
+        fields.insert(ITEMS_FIELD.to_string(), serde_json::Value::Array(items));
+    }
+    serde_json::Value::Object(fields)
+}
+
+fn has_distinct_text_keys(items: &[Item]) -> bool {
+    let mut keys = HashSet::new();
+    items.iter().all(|item| match item {
+        Item::Slot(Value::Text(key), _) => keys.insert(key.as_str()),
+        _ => false,
+    })
+}
+
+fn escape_key(key: &Text) -> String {
+    let key = key.as_str();
+    if key.starts_with(ATTR_PREFIX) || key.starts_with(ESCAPE_PREFIX) {
+        format!("{}{}", ESCAPE_PREFIX, key)
+    } else {
+        key.to_string()
+    }
+}
+
+/// Convert JSON into a Swim model value.
+pub fn json_to_value(json: &serde_json::Value) -> Value {
+    match json {
+        serde_json::Value::Null => Value::Extant,
+        serde_json::Value::Bool(p) => Value::BooleanValue(*p),
+        serde_json::Value::Number(n) => number_to_value(n),
+        serde_json::Value::String(s) => Value::text(s),
+        serde_json::Value::Array(elements) => {
+            Value::from_vec(elements.iter().map(json_to_value).collect::<Vec<_>>())
+        }
+        serde_json::Value::Object(fields) => object_to_value(fields),
+    }
+}
+
+fn number_to_value(n: &Number) -> Value {
+    if let Some(m) = n.as_i64() {
+        i32::try_from(m)
+            .map(Value::Int32Value)
+            .unwrap_or(Value::Int64Value(m))
+    } else if let Some(m) = n.as_u64() {
+        Value::UInt64Value(m)
+    } else {
+        Value::Float64Value(n.as_f64().unwrap_or(f64::NAN))
+    }
+}
+
+fn object_to_value(fields: &Map<String, serde_json::Value>) -> Value {
+    let mut attrs = vec![];
+    let mut items = vec![];
+    for (name, value) in fields {
+        if let Some(attr_name) = name.strip_prefix(ATTR_PREFIX) {
+            attrs.push(Attr::of((attr_name, json_to_value(value))));
+        } else if name == ITEMS_FIELD {
+            match value {
+                serde_json::Value::Array(elements) => {
+                    items.extend(elements.iter().map(json_to_item));
+                }
+                ow => items.push(Item::slot(name.as_str(), json_to_value(ow))),
+            }
+        } else {
+            let key = match name.strip_prefix(ESCAPE_PREFIX) {
+                Some(unescaped)
+                    if unescaped.starts_with(ATTR_PREFIX)
+                        || unescaped.starts_with(ESCAPE_PREFIX) =>
+                {
+                    unescaped
+                }
+                _ => name.as_str(),
+            };
+            items.push(Item::slot(key, json_to_value(value)));
+        }
+    }
+    Value::Record(attrs, items)
+}
+
+fn json_to_item(json: &serde_json::Value) -> Item {
+    if let serde_json::Value::Object(fields) = json {
+        if let (2, Some(key), Some(value)) =
+            (fields.len(), fields.get(KEY_FIELD), fields.get(VALUE_FIELD))
+        {
+            return Item::Slot(json_to_value(key), json_to_value(value));
+        }
+    }
+    Item::ValueItem(json_to_value(json))
+}
+
+/// Print a value, of any type that supports [`Form`], as a JSON string.
+pub fn print_json<T: Form>(value: &T) -> String {
+    value_to_json(&value.as_value()).to_string()
+}
+
+/// Parse a JSON string and interpret it as a type that supports [`Form`].
+pub fn parse_json_as<T: Form>(json: &str) -> Result<T, JsonError> {
+    let json = serde_json::from_str::<serde_json::Value>(json)?;
+    Ok(T::try_convert(json_to_value(&json))?)
+}
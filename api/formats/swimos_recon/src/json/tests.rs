@@ -0,0 +1,160 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde_json::json;
+use swimos_form::Form;
+use swimos_model::{Attr, BigInt, Blob, Item, Value};
+
+use super::{json_to_value, parse_json_as, print_json, value_to_json, JsonError};
+
+fn round_trip(value: Value) {
+    let json = value_to_json(&value);
+    assert_eq!(json_to_value(&json), value);
+}
+
+#[test]
+fn primitives_to_json() {
+    assert_eq!(value_to_json(&Value::Extant), json!(null));
+    assert_eq!(value_to_json(&Value::from(3)), json!(3));
+    assert_eq!(value_to_json(&Value::from(-4i64)), json!(-4));
+    assert_eq!(value_to_json(&Value::from(u64::MAX)), json!(u64::MAX));
+    assert_eq!(value_to_json(&Value::from(1.5)), json!(1.5));
+    assert_eq!(value_to_json(&Value::Float64Value(f64::NAN)), json!(null));
+    assert_eq!(value_to_json(&Value::from(true)), json!(true));
+    assert_eq!(value_to_json(&Value::text("name")), json!("name"));
+    assert_eq!(value_to_json(&Value::BigInt(BigInt::from(12))), json!(12));
+    let big = BigInt::from(i64::MIN) * BigInt::from(2);
+    assert_eq!(
+        value_to_json(&Value::BigInt(big.clone())),
+        json!(big.to_string())
+    );
+    assert_eq!(
+        value_to_json(&Value::Data(Blob::from_vec(vec![1, 2, 3]))),
+        json!("AQID")
+    );
+}
+
+#[test]
+fn primitives_round_trip() {
+    round_trip(Value::Extant);
+    round_trip(Value::from(3));
+    round_trip(Value::from(i64::MAX));
+    round_trip(Value::from(u64::MAX));
+    round_trip(Value::from(-0.5));
+    round_trip(Value::from(false));
+    round_trip(Value::text("text"));
+}
+
+#[test]
+fn records_to_json() {
+    let array = Value::from_vec(vec![1, 2, 3]);
+    assert_eq!(value_to_json(&array), json!([1, 2, 3]));
+
+    let object = Value::from_vec(vec![Item::slot("a", 1), Item::slot("b", "x")]);
+    assert_eq!(value_to_json(&object), json!({"a": 1, "b": "x"}));
+
+    let with_attrs = Value::Record(
+        vec![Attr::of("tag"), Attr::of(("node", "/a"))],
+        vec![Item::slot("a", 1)],
+    );
+    assert_eq!(
+        value_to_json(&with_attrs),
+        json!({"@tag": null, "@node": "/a", "a": 1})
+    );
+
+    let mixed = Value::from_vec(vec![Item::of(1), Item::slot(2, 3)]);
+    assert_eq!(
+        value_to_json(&mixed),
+        json!({"$items": [1, {"$key": 2, "$value": 3}]})
+    );
+
+    let escaped = Value::from_vec(vec![Item::slot("@a", 1), Item::slot("$b", 2)]);
+    assert_eq!(value_to_json(&escaped), json!({"$@a": 1, "$$b": 2}));
+}
+
+#[test]
+fn records_round_trip() {
+    round_trip(Value::empty_record());
+    round_trip(Value::from_vec(vec![1, 2, 3]));
+    round_trip(Value::from_vec(vec![
+        Item::slot("first", 1),
+        Item::slot("second", Value::from_vec(vec![Item::slot("inner", true)])),
+    ]));
+    round_trip(Value::Record(
+        vec![
+            Attr::of(("update", Value::from_vec(vec![Item::slot("key", 4)]))),
+            Attr::of("flag"),
+        ],
+        vec![Item::slot("z", 1), Item::slot("a", 2)],
+    ));
+    round_trip(Value::Record(
+        vec![Attr::of("tag")],
+        vec![Item::of(1), Item::of(2)],
+    ));
+    round_trip(Value::from_vec(vec![
+        Item::slot("a", 1),
+        Item::slot("a", 2),
+    ]));
+    round_trip(Value::from_vec(vec![Item::of("x"), Item::slot(1, "y")]));
+    round_trip(Value::from_vec(vec![
+        Item::slot("@a", 1),
+        Item::slot("$b", 2),
+    ]));
+}
+
+#[derive(Debug, Clone, PartialEq, Form)]
+struct Example {
+    name: String,
+    count: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Form)]
+enum Event {
+    Started,
+    Updated { value: i64 },
+}
+
+#[test]
+fn print_and_parse_forms() {
+    let example = Example {
+        name: "a".to_string(),
+        count: 2,
+    };
+    let json = print_json(&example);
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+        json!({"@Example": null, "name": "a", "count": 2})
+    );
+    assert_eq!(parse_json_as::<Example>(&json).unwrap(), example);
+
+    let event = Event::Updated { value: 7 };
+    let json = print_json(&event);
+    assert_eq!(parse_json_as::<Event>(&json).unwrap(), event);
+    assert_eq!(
+        parse_json_as::<Event>(r#"{"@Started": null}"#).unwrap(),
+        Event::Started
+    );
+}
+
+#[test]
+fn parse_failures() {
+    assert!(matches!(
+        parse_json_as::<Example>("{"),
+        Err(JsonError::Json(_))
+    ));
+    assert!(matches!(
+        parse_json_as::<Example>(r#"{"@Other": null, "name": "a", "count": 2}"#),
+        Err(JsonError::Read(_))
+    ));
+}
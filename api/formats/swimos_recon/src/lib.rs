@@ -21,12 +21,18 @@
 //! - Recon printer that will format types that support the [`swimos_form::Form`] trait to strings.
 //! - Comparator for Recon strings that does not require them to be deserialized.
 //! - Hash function for Recon strings (that will produce the same hash for strings that represent equal values).
+//! - Conversions between the Swim data model and JSON (requires the `json` feature).
+//! - Adapters to read and write types that support `serde` as Recon (requires the `serde` feature).
 
 mod comparator;
 mod encoding;
 mod hasher;
+#[cfg(feature = "json")]
+pub mod json;
 mod printer;
 mod recon_parser;
+#[cfg(feature = "serde")]
+pub mod serde;
 
 pub use comparator::compare_recon_values;
 pub use encoding::{write_recon, WithLenRecognizerDecoder, WithLenReconEncoder};
@@ -38,10 +44,13 @@ pub use printer::{print_recon, print_recon_compact, print_recon_pretty};
 /// - Parser that expects a single recon value, with or without comments.
 /// - Parser for Recon configuration files where the file contains the contents of a Recon record, with or without comments.
 /// - Extractors to parse only the first attribute of a Recon record, leaving the remainder as an uninterpreted string.
+/// - Push-based parser that consumes its input incrementally, as a sequence of byte chunks.
 pub mod parser {
     pub use crate::recon_parser::{
         extract_header, extract_header_str, parse_recognize, parse_text_token, HeaderPeeler,
         MessageExtractError, ParseError, Span,
     };
-    pub use crate::recon_parser::{parse_recon_document, AsyncParseError, RecognizerDecoder};
+    pub use crate::recon_parser::{
+        parse_recon_document, AsyncParseError, RecognizerDecoder, ReconPushParser,
+    };
 }
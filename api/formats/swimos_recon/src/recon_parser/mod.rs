@@ -14,6 +14,7 @@
 
 mod async_parser;
 mod error;
+mod push_parser;
 pub mod record;
 #[cfg(test)]
 mod tests;
@@ -120,3 +121,4 @@ pub fn parse_text_token(input: Span<'_>) -> Result<Cow<'_, str>, ParseError> {
 }
 
 pub use async_parser::{parse_recon_document, AsyncParseError, RecognizerDecoder};
+pub use push_parser::ReconPushParser;
@@ -0,0 +1,127 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(test)]
+mod tests;
+
+use super::async_parser::{AsyncParseError, RecognizerDecoder};
+use bytes::BytesMut;
+use swimos_form::read::Recognizer;
+use tokio_util::codec::Decoder;
+
+/// A push-based Recon parser that accepts its input as a sequence of byte chunks and produces
+/// values (using a [`Recognizer`]) as soon as they are complete. The input does not need to be
+/// split on UTF8 character boundaries.
+///
+/// Tokens are consumed from the internal buffer as they are parsed so only the bytes of any
+/// incomplete token at the end of a chunk are retained between calls. This allows large values to
+/// be read without first collecting the entire input into a single string.
+///
+/// The input may contain any number of values, separated by whitespace. Note that some values
+/// (for example, a number at the end of the input) cannot be known to be complete until either
+/// more input is available or [`ReconPushParser::finish`] is called.
+pub struct ReconPushParser<R> {
+    decoder: RecognizerDecoder<R>,
+    buffer: BytesMut,
+    // Whether any of the input for the next value has been received.
+    pending: bool,
+}
+
+impl<R> ReconPushParser<R> {
+    /// # Arguments
+    /// * `recognizer` - Recognizer to drive with the events produced by the parser.
+    pub fn new(recognizer: R) -> Self {
+        ReconPushParser {
+            decoder: RecognizerDecoder::new(recognizer),
+            buffer: BytesMut::new(),
+            pending: false,
+        }
+    }
+
+    /// Add a chunk of input to the parser. Values can then be retrieved using
+    /// [`ReconPushParser::next_value`].
+    pub fn push(&mut self, chunk: &[u8]) {
+        let ReconPushParser {
+            buffer, pending, ..
+        } = self;
+        *pending = *pending || !is_blank(chunk);
+        buffer.extend_from_slice(chunk);
+    }
+}
+
+impl<R: Recognizer> ReconPushParser<R> {
+    /// Attempt to produce the next value from the input that has been pushed so far. This will
+    /// return `Ok(None)` if more input is required to complete the value. After an error, the
+    /// remaining input is discarded and the parser is returned to its initial state.
+    pub fn next_value(&mut self) -> Result<Option<R::Target>, AsyncParseError> {
+        let ReconPushParser {
+            decoder,
+            buffer,
+            pending,
+        } = self;
+        let result = decoder.decode(buffer);
+        match &result {
+            Ok(Some(_)) => *pending = !is_blank(buffer),
+            Ok(None) => {}
+            Err(_) => {
+                buffer.clear();
+                *pending = false;
+            }
+        }
+        result
+    }
+
+    /// Indicate that there is no more input. This will produce the final value if the remaining
+    /// input completes one (and fail if there is an incomplete value). This should be called
+    /// repeatedly until it returns `Ok(None)` to ensure that all values are consumed. Once it has
+    /// returned `Ok(None)`, or failed, the parser is returned to its initial state and may be reused.
+    pub fn finish(&mut self) -> Result<Option<R::Target>, AsyncParseError> {
+        if !self.pending {
+            self.reset();
+            return Ok(None);
+        }
+        let ReconPushParser {
+            decoder,
+            buffer,
+            pending,
+        } = self;
+        let result = match decoder.decode(buffer) {
+            Ok(None) => decoder.decode_eof(buffer),
+            ow => ow,
+        };
+        if matches!(result, Ok(Some(_))) {
+            *pending = !is_blank(buffer);
+        } else {
+            buffer.clear();
+            *pending = false;
+        }
+        result
+    }
+
+    /// Reset the parser to its initial state, discarding any input that has not been consumed.
+    pub fn reset(&mut self) {
+        let ReconPushParser {
+            decoder,
+            buffer,
+            pending,
+        } = self;
+        decoder.reset();
+        buffer.clear();
+        *pending = false;
+    }
+}
+
+fn is_blank(bytes: &[u8]) -> bool {
+    bytes.iter().all(u8::is_ascii_whitespace)
+}
@@ -0,0 +1,95 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::ReconPushParser;
+use crate::recon_parser::{parse_recognize, AsyncParseError};
+use swimos_form::read::RecognizerReadable;
+use swimos_model::Value;
+
+fn value_parser() -> ReconPushParser<<Value as RecognizerReadable>::Rec> {
+    ReconPushParser::new(Value::make_recognizer())
+}
+
+fn parse_in_chunks(input: &str, chunk_size: usize) -> Vec<Value> {
+    let mut parser = value_parser();
+    let mut values = vec![];
+    for chunk in input.as_bytes().chunks(chunk_size) {
+        parser.push(chunk);
+        while let Some(value) = parser.next_value().expect("Parse failed.") {
+            values.push(value);
+        }
+    }
+    while let Some(value) = parser.finish().expect("Parse failed.") {
+        values.push(value);
+    }
+    values
+}
+
+const RECORD: &str = "@map { {key: 1, value: \"first\"}, {key: 2, value: \"sëcond\"}, {key: 3, value: @inner(a: 1.5) { true, false }} }";
+
+#[test]
+fn parse_single_value_in_chunks() {
+    let expected = parse_recognize::<Value>(RECORD, false).unwrap();
+    for chunk_size in 1..=RECORD.len() {
+        assert_eq!(parse_in_chunks(RECORD, chunk_size), vec![expected.clone()]);
+    }
+}
+
+#[test]
+fn parse_multiple_values() {
+    let input = format!("{} 12 {}\n@done", RECORD, RECORD);
+    let record = parse_recognize::<Value>(RECORD, false).unwrap();
+    let expected = vec![
+        record.clone(),
+        Value::from(12),
+        record,
+        parse_recognize::<Value>("@done", false).unwrap(),
+    ];
+    for chunk_size in [1, 2, 7, input.len()] {
+        assert_eq!(parse_in_chunks(&input, chunk_size), expected);
+    }
+}
+
+#[test]
+fn value_available_before_finish() {
+    let mut parser = value_parser();
+    parser.push(b"{a: 1, b: ");
+    assert!(matches!(parser.next_value(), Ok(None)));
+    parser.push(b"2}");
+    let expected = parse_recognize::<Value>("{a: 1, b: 2}", false).unwrap();
+    assert!(matches!(parser.next_value(), Ok(Some(v)) if v == expected));
+    assert!(matches!(parser.finish(), Ok(None)));
+}
+
+#[test]
+fn parse_error_resets_parser() {
+    let mut parser = value_parser();
+    parser.push(b"{a: 1, $ }");
+    assert!(matches!(
+        parser.next_value(),
+        Err(AsyncParseError::Parser(_))
+    ));
+    parser.push(b"{a: 1}");
+    let expected = parse_recognize::<Value>("{a: 1}", false).unwrap();
+    assert!(matches!(parser.next_value(), Ok(Some(v)) if v == expected));
+}
+
+#[test]
+fn incomplete_at_finish() {
+    let mut parser = value_parser();
+    parser.push(b"{a: 1, b: ");
+    assert!(matches!(parser.next_value(), Ok(None)));
+    assert!(parser.finish().is_err());
+    assert!(matches!(parser.finish(), Ok(None)));
+}
@@ -20,6 +20,7 @@ use super::super::tokens::string_literal;
 use nom::branch::alt;
 use nom::character::complete::{char, multispace0, one_of, space0};
 use nom::combinator::{complete, flat_map, map, map_res, opt, recognize, rest, success};
+use nom::error::ErrorKind;
 use nom::multi::{fold_many0, many0_count, many1_count};
 use nom::sequence::{delimited, pair, preceded, terminated, tuple};
 use nom::{Finish, IResult};
@@ -30,6 +31,10 @@ use crate::parser::Span;
 #[cfg(test)]
 mod tests;
 
+/// The maximum depth of nested records that will be accepted within the header attribute. The
+/// matcher is recursive so this prevents a deeply nested input from overflowing the stack.
+const MAX_NESTING_DEPTH: usize = 64;
+
 /// Implementers of this trait attempt to recognize a particular pattern in the first attribute of
 /// a Recon value. It is necessary for such types to be clonable (for use in different brancehs)
 /// of the parser and cloned instances should behave identically to the original. As clone could
@@ -116,20 +121,30 @@ where
     }
 }
 
-fn value(input: Span<'_>) -> IResult<Span<'_>, Span<'_>> {
+fn value(input: Span<'_>, depth: usize) -> IResult<Span<'_>, Span<'_>> {
     alt((
         recognize(comp_string_literal),
         recognize(identifier),
         recognize(numeric_literal),
         recognize(blob),
-        record,
+        move |input| record(input, depth),
     ))(input)
 }
 
-fn record(input: Span<'_>) -> IResult<Span<'_>, Span<'_>> {
+fn record(input: Span<'_>, depth: usize) -> IResult<Span<'_>, Span<'_>> {
+    if depth >= MAX_NESTING_DEPTH {
+        return Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            ErrorKind::TooLarge,
+        )));
+    }
+    let depth = depth + 1;
     alt((
-        recognize(pair(attrs, body_after_attrs)),
-        recognize(body(('{', '}'))),
+        recognize(pair(
+            move |input| attrs(input, depth),
+            move |input| body_after_attrs(input, depth),
+        )),
+        recognize(body(('{', '}'), depth)),
     ))(input)
 }
 
@@ -137,30 +152,34 @@ fn comp_string_literal(input: Span<'_>) -> IResult<Span<'_>, Cow<'_, str>> {
     complete(string_literal)(input)
 }
 
-fn attrs(input: Span<'_>) -> IResult<Span<'_>, Span<'_>> {
-    recognize(many1_count(pair(attr, space0)))(input)
+fn attrs(input: Span<'_>, depth: usize) -> IResult<Span<'_>, Span<'_>> {
+    recognize(many1_count(pair(move |input| attr(input, depth), space0)))(input)
 }
 
 fn attr_name(input: Span<'_>) -> IResult<Span<'_>, Cow<'_, str>> {
     alt((comp_string_literal, map(identifier, Cow::Borrowed)))(input)
 }
 
-fn attr(input: Span<'_>) -> IResult<Span<'_>, Span<'_>> {
-    recognize(tuple((char('@'), attr_name, opt(body(('(', ')'))))))(input)
+fn attr(input: Span<'_>, depth: usize) -> IResult<Span<'_>, Span<'_>> {
+    recognize(tuple((char('@'), attr_name, opt(body(('(', ')'), depth)))))(input)
 }
 
-fn body(delim: (char, char)) -> impl for<'a> FnMut(Span<'a>) -> IResult<Span<'a>, Span<'a>> {
+fn body(
+    delim: (char, char),
+    depth: usize,
+) -> impl for<'a> FnMut(Span<'a>) -> IResult<Span<'a>, Span<'a>> {
     let (l, r) = delim;
     move |input| {
         delimited(
             pair(char(l), multispace0),
-            items,
+            move |input| items(input, depth),
             pair(multispace0, char(r)),
         )(input)
     }
 }
 
-fn items(input: Span<'_>) -> IResult<Span<'_>, Span<'_>> {
+fn items(input: Span<'_>, depth: usize) -> IResult<Span<'_>, Span<'_>> {
+    let item = move |input| item(input, depth);
     recognize(pair(opt(item), many0_count(preceded(separator, opt(item)))))(input)
 }
 
@@ -172,17 +191,18 @@ fn slot_div(input: Span<'_>) -> IResult<Span<'_>, Span<'_>> {
     recognize(delimited(multispace0, char(':'), multispace0))(input)
 }
 
-fn item(input: Span<'_>) -> IResult<Span<'_>, Span<'_>> {
+fn item(input: Span<'_>, depth: usize) -> IResult<Span<'_>, Span<'_>> {
+    let value = move |input| value(input, depth);
     recognize(pair(value, opt(preceded(slot_div, opt(value)))))(input)
 }
 
-fn body_after_attrs(input: Span<'_>) -> IResult<Span<'_>, Span<'_>> {
+fn body_after_attrs(input: Span<'_>, depth: usize) -> IResult<Span<'_>, Span<'_>> {
     recognize(opt(alt((
         recognize(comp_string_literal),
         recognize(identifier),
         recognize(numeric_literal),
         recognize(blob),
-        body(('{', '}')),
+        body(('{', '}'), depth),
     ))))(input)
 }
 
@@ -209,12 +229,15 @@ enum PeelItem<'a> {
 }
 
 fn peel_value_item(input: Span<'_>) -> IResult<Span<'_>, PeelItem<'_>> {
-    map(recognize(value), PeelItem::ValueItem)(input)
+    map(recognize(|input| value(input, 0)), PeelItem::ValueItem)(input)
 }
 
 fn peel_slot_item(input: Span<'_>) -> IResult<Span<'_>, PeelItem<'_>> {
     map(
-        pair(name, preceded(slot_div, recognize(opt(value)))),
+        pair(
+            name,
+            preceded(slot_div, recognize(opt(|input| value(input, 0)))),
+        ),
         |(name, v)| PeelItem::SlotItem(name, v),
     )(input)
 }
@@ -127,7 +127,11 @@ const RECON_DOCS: &[&str] = &[
 ];
 
 fn complete_value(input: Span<'_>) -> IResult<Span<'_>, Span<'_>> {
-    delimited(multispace0, super::value, preceded(multispace0, eof))(input)
+    delimited(
+        multispace0,
+        |input| super::value(input, 0),
+        preceded(multispace0, eof),
+    )(input)
 }
 
 #[test]
@@ -419,3 +423,21 @@ fn peel_complex() {
     assert_eq!(values, vec![Some("@inner { 2, 7 }")]);
     assert_eq!(body, Some("@other(1) {1,2,3}"));
 }
+
+#[test]
+fn peel_deeply_nested() {
+    let depth = super::MAX_NESTING_DEPTH;
+    let nested = |depth: usize| {
+        format!(
+            "@attr(key: {}{}) {{}}",
+            "{".repeat(depth),
+            "}".repeat(depth)
+        )
+    };
+
+    let within_limit = nested(depth - 1);
+    assert!(super::peel_message(TestPeeler::default())(Span::new(&within_limit)).is_ok());
+
+    let too_deep = nested(depth * 1000);
+    assert!(super::peel_message(TestPeeler::default())(Span::new(&too_deep)).is_err());
+}
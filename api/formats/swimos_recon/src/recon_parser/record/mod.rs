@@ -30,6 +30,11 @@ use swimos_form::read::ReadEvent;
 mod hash;
 pub mod matcher;
 
+/// The maximum number of nested frames (record bodies and attribute bodies) that the parser will
+/// accept. Values are dropped recursively so, without a limit, a short but very deeply nested input
+/// could overflow the stack.
+const MAX_NESTING_DEPTH: usize = 512;
+
 /// Change the state of the parser after producing an event.
 #[derive(Debug)]
 enum StateChange {
@@ -392,6 +397,12 @@ impl<'a> Parser<Span<'a>, ParseEvents<'a>, nom::error::Error<Span<'a>>> for Incr
             state,
             allow_comments,
         } = self;
+        if state.len() > MAX_NESTING_DEPTH {
+            return Err(nom::Err::Failure(nom::error::Error::new(
+                input,
+                ErrorKind::TooLarge,
+            )));
+        }
         if let Some(top) = state.last_mut() {
             let (input, _) = char_str::space0(input)?;
 
@@ -589,15 +600,9 @@ fn parse_after_attr_final(input: Span<'_>) -> IResult<Span<'_>, ParseEvents<'_>>
 fn parse_init(input: Span<'_>) -> IResult<Span<'_>, (ParseEvents<'_>, Option<StateChange>)> {
     alt((
         map(string_literal, |s| (ReadEvent::TextValue(s).single(), None)),
-        map(complete::identifier_or_bool, |v| {
-            (identifier_event(v).single(), None)
-        }),
-        map(complete::numeric_literal, |l| {
-            (ReadEvent::Number(l).single(), None)
-        }),
-        map(complete::blob, |data| {
-            (ReadEvent::Blob(data).single(), None)
-        }),
+        map(identifier_or_bool, |v| (identifier_event(v).single(), None)),
+        map(numeric_literal, |l| (ReadEvent::Number(l).single(), None)),
+        map(blob, |data| (ReadEvent::Blob(data).single(), None)),
         map(secondary_attr, |(e, c)| (e, Some(c))),
         map(char_str::char('{'), |_| {
             (
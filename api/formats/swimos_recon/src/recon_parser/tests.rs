@@ -1608,3 +1608,25 @@ fn attr_with_comments() {
         value_from_string_with_comments(attrs_with_multiple_comments)
     )
 }
+
+#[test]
+fn reject_deeply_nested_records() {
+    let nested = |depth: usize| format!("{}{}", "{".repeat(depth), "}".repeat(depth));
+
+    assert!(value_from_string(&nested(64)).is_ok());
+
+    let result = value_from_string(&nested(100_000));
+    assert!(matches!(
+        result,
+        Err(ParseError::Syntax {
+            kind: nom::error::ErrorKind::TooLarge,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn recognize_bytes() {
+    let blob: bytes::Bytes = super::parse_recognize("%YWJj", false).unwrap();
+    assert_eq!(blob.as_ref(), b"abc");
+}
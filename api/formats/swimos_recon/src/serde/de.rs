@@ -0,0 +1,357 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::de::{
+    value::StringDeserializer, DeserializeSeed, Deserializer, EnumAccess, Error, IntoDeserializer,
+    MapAccess, SeqAccess, Unexpected, VariantAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+use swimos_model::{Attr, Item, Value};
+
+use super::SerdeError;
+
+/// A [`Deserializer`] that reads from a Swim model [`Value`].
+#[derive(Debug, Clone)]
+pub struct ValueDeserializer {
+    value: Value,
+}
+
+impl ValueDeserializer {
+    pub fn new(value: Value) -> Self {
+        ValueDeserializer { value }
+    }
+}
+
+fn unexpected(value: &Value) -> Unexpected<'_> {
+    match value {
+        Value::Extant => Unexpected::Unit,
+        Value::Int32Value(n) => Unexpected::Signed((*n).into()),
+        Value::Int64Value(n) => Unexpected::Signed(*n),
+        Value::UInt32Value(n) => Unexpected::Unsigned((*n).into()),
+        Value::UInt64Value(n) => Unexpected::Unsigned(*n),
+        Value::Float64Value(x) => Unexpected::Float(*x),
+        Value::BooleanValue(p) => Unexpected::Bool(*p),
+        Value::BigInt(_) | Value::BigUint(_) => Unexpected::Other("big integer"),
+        Value::Text(text) => Unexpected::Str(text.as_str()),
+        Value::Data(blob) => Unexpected::Bytes(blob.as_ref()),
+        Value::Record(..) => Unexpected::Other("record"),
+    }
+}
+
+/// The items of a record, without its attributes. Primitive values are treated as records with
+/// a single item.
+fn into_items(value: Value) -> Vec<Item> {
+    match value {
+        Value::Extant => vec![],
+        Value::Record(_, items) => items,
+        ow => vec![Item::ValueItem(ow)],
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value {
+            Value::Extant => visitor.visit_unit(),
+            Value::Int32Value(n) => visitor.visit_i32(n),
+            Value::Int64Value(n) => visitor.visit_i64(n),
+            Value::UInt32Value(n) => visitor.visit_u32(n),
+            Value::UInt64Value(n) => visitor.visit_u64(n),
+            Value::Float64Value(x) => visitor.visit_f64(x),
+            Value::BooleanValue(p) => visitor.visit_bool(p),
+            Value::BigInt(n) => match i128::try_from(&n) {
+                Ok(m) => visitor.visit_i128(m),
+                Err(_) => Err(SerdeError::invalid_value(
+                    Unexpected::Other("big integer"),
+                    &"an integer that fits in 128 bits",
+                )),
+            },
+            Value::BigUint(n) => match u128::try_from(&n) {
+                Ok(m) => visitor.visit_u128(m),
+                Err(_) => Err(SerdeError::invalid_value(
+                    Unexpected::Other("big integer"),
+                    &"an integer that fits in 128 bits",
+                )),
+            },
+            Value::Text(text) => visitor.visit_string(text.to_string()),
+            Value::Data(blob) => visitor.visit_byte_buf(blob.into_vec()),
+            Value::Record(_, items) => {
+                if !items.is_empty() && items.iter().all(|item| matches!(item, Item::Slot(..))) {
+                    visitor.visit_map(Slots::new(items))
+                } else {
+                    visitor.visit_seq(Items::new(items))
+                }
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value {
+            Value::Extant => visitor.visit_none(),
+            value => visitor.visit_some(ValueDeserializer::new(value)),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value {
+            Value::Extant => visitor.visit_unit(),
+            Value::Record(_, items) if items.is_empty() => visitor.visit_unit(),
+            ow => Err(SerdeError::invalid_type(unexpected(&ow), &visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value {
+            value @ (Value::Extant | Value::Record(..)) => {
+                visitor.visit_seq(Items::new(into_items(value)))
+            }
+            ow => Err(SerdeError::invalid_type(unexpected(&ow), &visitor)),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value {
+            value @ (Value::Extant | Value::Record(..)) => {
+                visitor.visit_map(Slots::new(into_items(value)))
+            }
+            ow => Err(SerdeError::invalid_type(unexpected(&ow), &visitor)),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.value {
+            Value::Text(variant) => visitor.visit_enum(Variant {
+                tag: variant.to_string(),
+                body: Value::Extant,
+            }),
+            Value::Record(mut attrs, items) if !attrs.is_empty() => {
+                let Attr { name, .. } = attrs.remove(0);
+                visitor.visit_enum(Variant {
+                    tag: name.to_string(),
+                    body: Value::Record(attrs, items),
+                })
+            }
+            ow => Err(SerdeError::invalid_type(unexpected(&ow), &visitor)),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, SerdeError> for ValueDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Access to the value items of a record as a sequence.
+struct Items {
+    items: std::vec::IntoIter<Item>,
+}
+
+impl Items {
+    fn new(items: Vec<Item>) -> Self {
+        Items {
+            items: items.into_iter(),
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for Items {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        match self.items.next() {
+            Some(Item::ValueItem(value)) => {
+                seed.deserialize(ValueDeserializer::new(value)).map(Some)
+            }
+            Some(Item::Slot(..)) => Err(SerdeError::custom(
+                "Expected a value item but found a slot.",
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+/// Access to the slots of a record as a map.
+struct Slots {
+    items: std::vec::IntoIter<Item>,
+    value: Option<Value>,
+}
+
+impl Slots {
+    fn new(items: Vec<Item>) -> Self {
+        Slots {
+            items: items.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for Slots {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        match self.items.next() {
+            Some(Item::Slot(key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(ValueDeserializer::new(key)).map(Some)
+            }
+            Some(Item::ValueItem(_)) => Err(SerdeError::custom(
+                "Expected a slot but found a value item.",
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(ValueDeserializer::new(value)),
+            None => Err(SerdeError::custom("A value was requested before a key.")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+/// The variant of an enumeration, identified by the tag of a record.
+struct Variant {
+    tag: String,
+    body: Value,
+}
+
+impl<'de> EnumAccess<'de> for Variant {
+    type Error = SerdeError;
+    type Variant = ValueDeserializer;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, ValueDeserializer), SerdeError> {
+        let Variant { tag, body } = self;
+        let tag_de: StringDeserializer<SerdeError> = tag.into_deserializer();
+        let variant = seed.deserialize(tag_de)?;
+        Ok((variant, ValueDeserializer::new(body)))
+    }
+}
+
+impl<'de> VariantAccess<'de> for ValueDeserializer {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.value {
+            Value::Extant => Ok(()),
+            Value::Record(_, items) if items.is_empty() => Ok(()),
+            ow => Err(SerdeError::invalid_type(unexpected(&ow), &"a unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        match self.value {
+            Value::Record(attrs, items) if attrs.is_empty() => match <[Item; 1]>::try_from(items) {
+                Ok([Item::ValueItem(value)]) => seed.deserialize(ValueDeserializer::new(value)),
+                Ok([slot]) => seed.deserialize(ValueDeserializer::new(Value::record(vec![slot]))),
+                Err(items) => seed.deserialize(ValueDeserializer::new(Value::record(items))),
+            },
+            value => seed.deserialize(ValueDeserializer::new(value)),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_map(visitor)
+    }
+}
@@ -0,0 +1,97 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Adapters between [`serde`] and the Swim data model. These allow any type that implements
+//! [`serde::Serialize`] or [`serde::Deserialize`] to be written as, or read from, Recon without
+//! implementing [`swimos_form::Form`]. This is primarily useful for third party types, for which
+//! the `Form` derive macro cannot be used.
+//!
+//! The serde data model is mapped onto the Swim data model so as to be consistent with the
+//! representations generated by the `Form` derive macro:
+//!
+//! - Unit values and `None` are represented as `Extant` and `Some(x)` as the representation of `x`.
+//! - Primitive types are represented by the corresponding primitive values. 128 bit integers are
+//!   represented as big integers.
+//! - Byte arrays are represented as blobs.
+//! - Sequences and tuples are represented as records of value items and maps are represented as
+//!   records of slots.
+//! - Newtype structs are represented by the representation of their field.
+//! - Other structs are represented as records with an attribute with the name of the struct
+//!   (e.g. `@Example{ name: "a", count: 2 }`).
+//! - Enumeration variants are represented in the same way as structs, with the attribute having
+//!   the name of the variant (e.g. `@Started`).
+//!
+//! When reading, the names in the tags of structs are not checked.
+
+mod de;
+mod ser;
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt::Display;
+
+use serde::{de::DeserializeOwned, Serialize};
+use swimos_model::Value;
+use thiserror::Error;
+
+use crate::{parser::ParseError, print_recon_compact};
+
+pub use de::ValueDeserializer;
+pub use ser::ValueSerializer;
+
+/// Error type for failures to convert between serde and the Swim data model.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SerdeError {
+    /// An error reported by a [`serde::Serialize`] or [`serde::Deserialize`] implementation
+    /// or a value that was not valid for the target type.
+    #[error("{0}")]
+    Message(String),
+    /// The input was not valid Recon.
+    #[error("Invalid Recon: {0}")]
+    Parse(#[from] ParseError),
+}
+
+impl serde::ser::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerdeError::Message(msg.to_string())
+    }
+}
+
+impl serde::de::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerdeError::Message(msg.to_string())
+    }
+}
+
+/// Convert any type that implements [`serde::Serialize`] into a Swim model value.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, SerdeError> {
+    value.serialize(ValueSerializer)
+}
+
+/// Interpret a Swim model value as any type that implements [`serde::Deserialize`].
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, SerdeError> {
+    T::deserialize(ValueDeserializer::new(value))
+}
+
+/// Print any type that implements [`serde::Serialize`] as a compact Recon string.
+pub fn to_recon_string<T: Serialize + ?Sized>(value: &T) -> Result<String, SerdeError> {
+    Ok(print_recon_compact(&to_value(value)?).to_string())
+}
+
+/// Parse a Recon string and interpret it as any type that implements [`serde::Deserialize`].
+pub fn from_recon_str<T: DeserializeOwned>(recon: &str) -> Result<T, SerdeError> {
+    let value = crate::parser::parse_recognize::<Value>(recon, false)?;
+    from_value(value)
+}
@@ -0,0 +1,371 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use num_bigint::{BigInt, BigUint};
+use serde::ser::{
+    Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+use swimos_model::{Attr, Blob, Item, Value};
+
+use super::SerdeError;
+
+/// A [`Serializer`] that converts its input into a Swim model [`Value`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ValueSerializer;
+
+fn tagged(tag: &str, items: Vec<Item>) -> Value {
+    Value::Record(vec![Attr::of(tag)], items)
+}
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = SerdeError;
+    type SerializeSeq = SerializeItems;
+    type SerializeTuple = SerializeItems;
+    type SerializeTupleStruct = SerializeItems;
+    type SerializeTupleVariant = SerializeItems;
+    type SerializeMap = SerializeSlots;
+    type SerializeStruct = SerializeSlots;
+    type SerializeStructVariant = SerializeSlots;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, SerdeError> {
+        Ok(Value::BooleanValue(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, SerdeError> {
+        Ok(Value::Int32Value(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, SerdeError> {
+        Ok(Value::Int32Value(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, SerdeError> {
+        Ok(Value::Int32Value(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, SerdeError> {
+        Ok(Value::Int64Value(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, SerdeError> {
+        Ok(Value::BigInt(BigInt::from(v)))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, SerdeError> {
+        Ok(Value::UInt32Value(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, SerdeError> {
+        Ok(Value::UInt32Value(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, SerdeError> {
+        Ok(Value::UInt32Value(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, SerdeError> {
+        Ok(Value::UInt64Value(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, SerdeError> {
+        Ok(Value::BigUint(BigUint::from(v)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, SerdeError> {
+        Ok(Value::Float64Value(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, SerdeError> {
+        Ok(Value::Float64Value(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, SerdeError> {
+        Ok(Value::text(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, SerdeError> {
+        Ok(Value::text(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, SerdeError> {
+        Ok(Value::Data(Blob::from_vec(v.to_vec())))
+    }
+
+    fn serialize_none(self) -> Result<Value, SerdeError> {
+        Ok(Value::Extant)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, SerdeError> {
+        Ok(Value::Extant)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Value, SerdeError> {
+        Ok(tagged(name, vec![]))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, SerdeError> {
+        Ok(tagged(variant, vec![]))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, SerdeError> {
+        Ok(tagged(
+            variant,
+            vec![Item::ValueItem(value.serialize(self)?)],
+        ))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeItems, SerdeError> {
+        Ok(SerializeItems::new(None, len.unwrap_or_default()))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeItems, SerdeError> {
+        Ok(SerializeItems::new(None, len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeItems, SerdeError> {
+        Ok(SerializeItems::new(Some(name), len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeItems, SerdeError> {
+        Ok(SerializeItems::new(Some(variant), len))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeSlots, SerdeError> {
+        Ok(SerializeSlots::new(None, len.unwrap_or_default()))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeSlots, SerdeError> {
+        Ok(SerializeSlots::new(Some(name), len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeSlots, SerdeError> {
+        Ok(SerializeSlots::new(Some(variant), len))
+    }
+}
+
+/// Builds a record consisting of value items, with an optional tag.
+#[derive(Debug)]
+pub struct SerializeItems {
+    tag: Option<&'static str>,
+    items: Vec<Item>,
+}
+
+impl SerializeItems {
+    fn new(tag: Option<&'static str>, len: usize) -> Self {
+        SerializeItems {
+            tag,
+            items: Vec::with_capacity(len),
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.items
+            .push(Item::ValueItem(value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn finish(self) -> Value {
+        let SerializeItems { tag, items } = self;
+        match tag {
+            Some(tag) => tagged(tag, items),
+            None => Value::record(items),
+        }
+    }
+}
+
+impl SerializeSeq for SerializeItems {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTuple for SerializeItems {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTupleStruct for SerializeItems {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTupleVariant for SerializeItems {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+/// Builds a record consisting of slots, with an optional tag.
+#[derive(Debug)]
+pub struct SerializeSlots {
+    tag: Option<&'static str>,
+    items: Vec<Item>,
+    key: Option<Value>,
+}
+
+impl SerializeSlots {
+    fn new(tag: Option<&'static str>, len: usize) -> Self {
+        SerializeSlots {
+            tag,
+            items: Vec::with_capacity(len),
+            key: None,
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, key: Value, value: &T) -> Result<(), SerdeError> {
+        self.items
+            .push(Item::Slot(key, value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn finish(self) -> Value {
+        let SerializeSlots { tag, items, .. } = self;
+        match tag {
+            Some(tag) => tagged(tag, items),
+            None => Value::record(items),
+        }
+    }
+}
+
+impl SerializeMap for SerializeSlots {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.key = Some(key.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        match self.key.take() {
+            Some(key) => self.push(key, value),
+            None => Err(SerdeError::Message(
+                "A map value was serialized without a key.".to_string(),
+            )),
+        }
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeStruct for SerializeSlots {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.push(Value::text(key), value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeStructVariant for SerializeSlots {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.push(Value::text(key), value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(self.finish())
+    }
+}
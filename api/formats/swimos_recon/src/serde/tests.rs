@@ -0,0 +1,200 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use swimos_form::Form;
+use swimos_model::{Attr, Item, Value};
+
+use super::{from_recon_str, from_value, to_recon_string, to_value, SerdeError};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Example {
+    name: String,
+    count: i32,
+    tags: Vec<String>,
+    parent: Option<Box<Example>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Meters(f64);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Point(i32, i32);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum Event {
+    Started,
+    Moved(Point),
+    Resized(u32, u32),
+    Renamed { from: String, to: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Form)]
+struct FormExample {
+    name: String,
+    count: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SerdeExample {
+    name: String,
+    count: i32,
+}
+
+fn round_trip<T>(value: T)
+where
+    T: Serialize + for<'de> Deserialize<'de> + PartialEq + std::fmt::Debug,
+{
+    let recon = to_recon_string(&value).expect("Serialization failed.");
+    let restored = from_recon_str::<T>(&recon).expect("Deserialization failed.");
+    assert_eq!(restored, value);
+}
+
+#[test]
+fn primitives_to_value() {
+    assert_eq!(to_value(&()), Ok(Value::Extant));
+    assert_eq!(to_value(&5u8), Ok(Value::UInt32Value(5)));
+    assert_eq!(to_value(&-5i16), Ok(Value::Int32Value(-5)));
+    assert_eq!(to_value(&5i64), Ok(Value::Int64Value(5)));
+    assert_eq!(to_value(&1.5f32), Ok(Value::Float64Value(1.5)));
+    assert_eq!(to_value(&true), Ok(Value::BooleanValue(true)));
+    assert_eq!(to_value(&'a'), Ok(Value::text("a")));
+    assert_eq!(to_value("text"), Ok(Value::text("text")));
+    assert_eq!(to_value(&None::<i32>), Ok(Value::Extant));
+    assert_eq!(to_value(&Some(3)), Ok(Value::Int32Value(3)));
+}
+
+#[test]
+fn structs_to_value() {
+    let example = Example {
+        name: "a".to_string(),
+        count: 2,
+        tags: vec!["x".to_string()],
+        parent: None,
+    };
+    let expected = Value::Record(
+        vec![Attr::of("Example")],
+        vec![
+            Item::slot("name", "a"),
+            Item::slot("count", 2),
+            Item::slot("tags", Value::from_vec(vec!["x"])),
+            Item::slot("parent", Value::Extant),
+        ],
+    );
+    assert_eq!(to_value(&example), Ok(expected));
+
+    assert_eq!(to_value(&Meters(2.0)), Ok(Value::Float64Value(2.0)));
+    assert_eq!(
+        to_value(&Point(1, 2)),
+        Ok(Value::Record(
+            vec![Attr::of("Point")],
+            vec![Item::of(1), Item::of(2)]
+        ))
+    );
+}
+
+#[test]
+fn enums_to_value() {
+    assert_eq!(to_value(&Event::Started), Ok(Value::of_attr("Started")));
+    assert_eq!(
+        to_value(&Event::Resized(1, 2)),
+        Ok(Value::Record(
+            vec![Attr::of("Resized")],
+            vec![Item::of(1u32), Item::of(2u32)]
+        ))
+    );
+    assert_eq!(
+        to_value(&Event::Renamed {
+            from: "a".to_string(),
+            to: "b".to_string()
+        }),
+        Ok(Value::Record(
+            vec![Attr::of("Renamed")],
+            vec![Item::slot("from", "a"), Item::slot("to", "b")]
+        ))
+    );
+}
+
+#[test]
+fn round_trip_through_recon() {
+    round_trip(Example {
+        name: "child".to_string(),
+        count: -3,
+        tags: vec!["a".to_string(), "b".to_string()],
+        parent: Some(Box::new(Example {
+            name: "parent".to_string(),
+            count: 0,
+            tags: vec![],
+            parent: None,
+        })),
+    });
+    round_trip(Meters(12.5));
+    round_trip(Point(-1, 1));
+    round_trip(Event::Started);
+    round_trip(Event::Moved(Point(3, 4)));
+    round_trip(Event::Resized(10, 20));
+    round_trip(Event::Renamed {
+        from: "a".to_string(),
+        to: "b".to_string(),
+    });
+    round_trip((1, "two".to_string(), 3.0));
+    round_trip(vec![Some(1u64), None, Some(u64::MAX)]);
+    round_trip(
+        [(1, "a".to_string()), (2, "b".to_string())]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>(),
+    );
+    round_trip(i128::MIN);
+    round_trip(u128::MAX);
+}
+
+#[test]
+fn consistent_with_form() {
+    let form = FormExample {
+        name: "a".to_string(),
+        count: 2,
+    };
+    let serde = SerdeExample {
+        name: "a".to_string(),
+        count: 2,
+    };
+    // The tags differ but the bodies of the records are the same.
+    match (to_value(&serde), form.as_value()) {
+        (Ok(Value::Record(serde_attrs, serde_items)), Value::Record(form_attrs, form_items)) => {
+            assert_eq!(serde_attrs, vec![Attr::of("SerdeExample")]);
+            assert_eq!(form_attrs, vec![Attr::of("FormExample")]);
+            assert_eq!(serde_items, form_items);
+        }
+        ow => panic!("Unexpected values: {:?}", ow),
+    }
+    assert_eq!(from_value::<SerdeExample>(form.as_value()), Ok(serde));
+}
+
+#[test]
+fn invalid_input() {
+    assert!(matches!(
+        from_recon_str::<Example>("@Example{"),
+        Err(SerdeError::Parse(_))
+    ));
+    assert!(matches!(
+        from_recon_str::<Example>("@Example{name: 1}"),
+        Err(SerdeError::Message(_))
+    ));
+    assert!(matches!(
+        from_recon_str::<u8>("300"),
+        Err(SerdeError::Message(_))
+    ));
+}
@@ -22,8 +22,12 @@ use swimos_recon::{
 };
 use swimos_utilities::encoding::consume_bounded;
 use tokio_util::codec::{Decoder, Encoder};
+use uuid::Uuid;
 
-use swimos_api::error::{FrameIoError, InvalidFrame};
+use swimos_api::{
+    agent::{LinkAdvice, LinkFailure, NodeStopped, SyncVersion},
+    error::{FrameIoError, InvalidFrame},
+};
 
 #[cfg(test)]
 mod tests;
@@ -35,6 +39,20 @@ const LINKED: u8 = 1;
 const SYNCED: u8 = 2;
 const EVENT: u8 = 3;
 const UNLINKED: u8 = 4;
+const ADVISORY: u8 = 5;
+const NODE_STOPPED: u8 = 6;
+const VERSION: u8 = 7;
+const FAILED: u8 = 8;
+const ORIGIN: u8 = 9;
+
+const VERSION_LEN: usize = 16;
+const ORIGIN_LEN: usize = 16;
+
+const REDUCE_RATE: u8 = 0;
+const UNLINK: u8 = 1;
+
+const NODE_NOT_FOUND: u8 = 0;
+const LANE_NOT_FOUND: u8 = 1;
 
 use crate::{
     model::{DownlinkNotification, DownlinkOperation},
@@ -72,6 +90,40 @@ impl<T: AsRef<[u8]>> Encoder<DownlinkNotification<T>> for DownlinkNotificationEn
                 dst.reserve(TAG_SIZE);
                 dst.put_u8(UNLINKED);
             }
+            DownlinkNotification::Advisory { advice } => {
+                dst.reserve(2 * TAG_SIZE);
+                dst.put_u8(ADVISORY);
+                dst.put_u8(match advice {
+                    LinkAdvice::ReduceRate => REDUCE_RATE,
+                    LinkAdvice::Unlink => UNLINK,
+                });
+            }
+            DownlinkNotification::NodeStopped { notice } => {
+                dst.reserve(2 * TAG_SIZE);
+                dst.put_u8(NODE_STOPPED);
+                dst.put_u8(u8::from(notice.resume));
+            }
+            DownlinkNotification::Failed { failure } => {
+                dst.reserve(2 * TAG_SIZE);
+                dst.put_u8(FAILED);
+                dst.put_u8(match failure {
+                    LinkFailure::NodeNotFound => NODE_NOT_FOUND,
+                    LinkFailure::LaneNotFound => LANE_NOT_FOUND,
+                });
+            }
+            DownlinkNotification::Version {
+                version: SyncVersion { epoch, version },
+            } => {
+                dst.reserve(TAG_SIZE + VERSION_LEN);
+                dst.put_u8(VERSION);
+                dst.put_u64(epoch);
+                dst.put_u64(version);
+            }
+            DownlinkNotification::Origin { origin } => {
+                dst.reserve(TAG_SIZE + ORIGIN_LEN);
+                dst.put_u8(ORIGIN);
+                dst.put_u128(origin.as_u128());
+            }
         }
         Ok(())
     }
@@ -222,6 +274,82 @@ where
                             src.advance(1);
                             break Ok(Some(DownlinkNotification::Unlinked));
                         }
+                        ADVISORY => {
+                            if src.remaining() < 2 * TAG_SIZE {
+                                src.reserve(2 * TAG_SIZE - src.remaining());
+                                break Ok(None);
+                            }
+                            src.advance(1);
+                            let advice = match src.get_u8() {
+                                REDUCE_RATE => LinkAdvice::ReduceRate,
+                                UNLINK => LinkAdvice::Unlink,
+                                code => {
+                                    break Err(FrameIoError::BadFrame(
+                                        InvalidFrame::InvalidHeader {
+                                            problem: Text::from(format!(
+                                                "Invalid link advice: {}",
+                                                code
+                                            )),
+                                        },
+                                    ));
+                                }
+                            };
+                            break Ok(Some(DownlinkNotification::Advisory { advice }));
+                        }
+                        NODE_STOPPED => {
+                            if src.remaining() < 2 * TAG_SIZE {
+                                src.reserve(2 * TAG_SIZE - src.remaining());
+                                break Ok(None);
+                            }
+                            src.advance(1);
+                            let notice = NodeStopped {
+                                resume: src.get_u8() != 0,
+                            };
+                            break Ok(Some(DownlinkNotification::NodeStopped { notice }));
+                        }
+                        FAILED => {
+                            if src.remaining() < 2 * TAG_SIZE {
+                                src.reserve(2 * TAG_SIZE - src.remaining());
+                                break Ok(None);
+                            }
+                            src.advance(1);
+                            let failure = match src.get_u8() {
+                                NODE_NOT_FOUND => LinkFailure::NodeNotFound,
+                                LANE_NOT_FOUND => LinkFailure::LaneNotFound,
+                                code => {
+                                    break Err(FrameIoError::BadFrame(
+                                        InvalidFrame::InvalidHeader {
+                                            problem: Text::from(format!(
+                                                "Invalid link failure: {}",
+                                                code
+                                            )),
+                                        },
+                                    ));
+                                }
+                            };
+                            break Ok(Some(DownlinkNotification::Failed { failure }));
+                        }
+                        VERSION => {
+                            if src.remaining() < TAG_SIZE + VERSION_LEN {
+                                src.reserve(TAG_SIZE + VERSION_LEN - src.remaining());
+                                break Ok(None);
+                            }
+                            src.advance(1);
+                            let epoch = src.get_u64();
+                            let version = src.get_u64();
+                            break Ok(Some(DownlinkNotification::Version {
+                                version: SyncVersion { epoch, version },
+                            }));
+                        }
+                        ORIGIN => {
+                            if src.remaining() < TAG_SIZE + ORIGIN_LEN {
+                                src.reserve(TAG_SIZE + ORIGIN_LEN - src.remaining());
+                                break Ok(None);
+                            }
+                            src.advance(1);
+                            let origin = Uuid::from_u128(src.get_u128());
+                            break Ok(Some(DownlinkNotification::Origin { origin }));
+                        }
                         t => {
                             break Err(FrameIoError::BadFrame(InvalidFrame::InvalidHeader {
                                 problem: Text::from(format!(
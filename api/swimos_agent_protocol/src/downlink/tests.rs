@@ -16,13 +16,18 @@ use crate::downlink::{
     DownlinkOperation, DownlinkOperationDecoder, DownlinkOperationEncoder, ValueNotificationDecoder,
 };
 use bytes::{Buf, Bytes, BytesMut};
+use swimos_api::agent::{LinkAdvice, LinkFailure, NodeStopped, SyncVersion};
 use swimos_form::read::RecognizerReadable;
 use swimos_form::Form;
 use swimos_model::Text;
 use swimos_recon::print_recon_compact;
 use tokio_util::codec::{Decoder, Encoder};
+use uuid::Uuid;
 
-use super::{DownlinkNotification, DownlinkNotificationEncoder, EVENT, LINKED, SYNCED, UNLINKED};
+use super::{
+    DownlinkNotification, DownlinkNotificationEncoder, ADVISORY, EVENT, FAILED, LANE_NOT_FOUND,
+    LINKED, NODE_STOPPED, ORIGIN, SYNCED, UNLINKED, VERSION,
+};
 
 fn encode_notification(notification: DownlinkNotification<&[u8]>) -> Bytes {
     let mut buffer = BytesMut::new();
@@ -103,6 +108,95 @@ fn decode_unlinked_notification() {
     assert_eq!(restored, DownlinkNotification::Unlinked);
 }
 
+#[test]
+fn encode_advisory_notification() {
+    let mut buffer = encode_notification(DownlinkNotification::Advisory {
+        advice: LinkAdvice::Unlink,
+    });
+    assert_eq!(buffer.len(), 2);
+    assert_eq!(buffer.get_u8(), ADVISORY);
+}
+
+#[test]
+fn decode_advisory_notification() {
+    for advice in [LinkAdvice::ReduceRate, LinkAdvice::Unlink] {
+        let restored = round_trip::<Text>(DownlinkNotification::Advisory { advice });
+        assert_eq!(restored, DownlinkNotification::Advisory { advice });
+    }
+}
+
+#[test]
+fn encode_node_stopped_notification() {
+    let mut buffer = encode_notification(DownlinkNotification::NodeStopped {
+        notice: NodeStopped { resume: true },
+    });
+    assert_eq!(buffer.len(), 2);
+    assert_eq!(buffer.get_u8(), NODE_STOPPED);
+    assert_eq!(buffer.get_u8(), 1);
+}
+
+#[test]
+fn decode_node_stopped_notification() {
+    for resume in [true, false] {
+        let notice = NodeStopped { resume };
+        let restored = round_trip::<Text>(DownlinkNotification::NodeStopped { notice });
+        assert_eq!(restored, DownlinkNotification::NodeStopped { notice });
+    }
+}
+
+#[test]
+fn encode_failed_notification() {
+    let mut buffer = encode_notification(DownlinkNotification::Failed {
+        failure: LinkFailure::LaneNotFound,
+    });
+    assert_eq!(buffer.len(), 2);
+    assert_eq!(buffer.get_u8(), FAILED);
+    assert_eq!(buffer.get_u8(), LANE_NOT_FOUND);
+}
+
+#[test]
+fn decode_failed_notification() {
+    for failure in [LinkFailure::NodeNotFound, LinkFailure::LaneNotFound] {
+        let restored = round_trip::<Text>(DownlinkNotification::Failed { failure });
+        assert_eq!(restored, DownlinkNotification::Failed { failure });
+    }
+}
+
+#[test]
+fn encode_version_notification() {
+    let mut buffer = encode_notification(DownlinkNotification::Version {
+        version: SyncVersion::new(3, 17),
+    });
+    assert_eq!(buffer.len(), 17);
+    assert_eq!(buffer.get_u8(), VERSION);
+    assert_eq!(buffer.get_u64(), 3);
+    assert_eq!(buffer.get_u64(), 17);
+}
+
+#[test]
+fn decode_version_notification() {
+    let version = SyncVersion::new(3, 17);
+    let restored = round_trip::<Text>(DownlinkNotification::Version { version });
+    assert_eq!(restored, DownlinkNotification::Version { version });
+}
+
+#[test]
+fn encode_origin_notification() {
+    let mut buffer = encode_notification(DownlinkNotification::Origin {
+        origin: Uuid::from_u128(88),
+    });
+    assert_eq!(buffer.len(), 17);
+    assert_eq!(buffer.get_u8(), ORIGIN);
+    assert_eq!(buffer.get_u128(), 88);
+}
+
+#[test]
+fn decode_origin_notification() {
+    let origin = Uuid::from_u128(88);
+    let restored = round_trip::<Text>(DownlinkNotification::Origin { origin });
+    assert_eq!(restored, DownlinkNotification::Origin { origin });
+}
+
 #[test]
 fn decode_event_notification() {
     let content = "content";
@@ -0,0 +1,177 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::num::NonZeroUsize;
+
+use bytes::{Buf, BufMut, BytesMut};
+use swimos_api::error::{FrameIoError, InvalidFrame};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{FRAGMENT, LEN_SIZE, TAG_LEN};
+
+#[cfg(test)]
+mod tests;
+
+const MORE: u8 = 0;
+const LAST: u8 = 1;
+
+const MARKER_LEN: usize = 1;
+const FRAGMENT_HEADER_LEN: usize = TAG_LEN + MARKER_LEN + LEN_SIZE;
+
+/// Wraps an encoder so that any frame that it produces that is larger than the maximum fragment size
+/// is split into a sequence of fragments. Each fragment is prefixed with the `FRAGMENT` tag, a
+/// continuation marker (indicating whether more fragments follow) and the length of the fragment.
+/// Frames that do not exceed the maximum size are written unaltered.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FragmentingEncoder<E> {
+    max_fragment_size: Option<NonZeroUsize>,
+    inner: E,
+}
+
+impl<E> FragmentingEncoder<E> {
+    pub fn new(inner: E, max_fragment_size: Option<NonZeroUsize>) -> Self {
+        FragmentingEncoder {
+            max_fragment_size,
+            inner,
+        }
+    }
+}
+
+impl<T, E> Encoder<T> for FragmentingEncoder<E>
+where
+    E: Encoder<T>,
+{
+    type Error = E::Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let FragmentingEncoder {
+            max_fragment_size,
+            inner,
+        } = self;
+        match max_fragment_size {
+            Some(max) => {
+                let max = max.get();
+                let mut frame = dst.split_off(dst.len());
+                inner.encode(item, &mut frame)?;
+                if frame.len() <= max {
+                    dst.unsplit(frame);
+                } else {
+                    let num_fragments = frame.len().div_ceil(max);
+                    dst.reserve(frame.len() + num_fragments * FRAGMENT_HEADER_LEN);
+                    while !frame.is_empty() {
+                        let n = frame.len().min(max);
+                        let marker = if n == frame.len() { LAST } else { MORE };
+                        dst.put_u8(FRAGMENT);
+                        dst.put_u8(marker);
+                        dst.put_u64(n as u64);
+                        dst.put_slice(&frame[..n]);
+                        frame.advance(n);
+                    }
+                }
+                Ok(())
+            }
+            None => inner.encode(item, dst),
+        }
+    }
+}
+
+/// Wraps a decoder to reassemble frames that were split into fragments by a [`FragmentingEncoder`]
+/// and to enforce a maximum size for incoming messages. Without a limit, a peer that sends a very
+/// large (or corrupt) frame would cause the decoder to wait, buffering indefinitely. With a limit,
+/// a [`InvalidFrame::TooLarge`] error is returned as soon as it is exceeded.
+#[derive(Debug, Default)]
+pub struct ReassemblingDecoder<D> {
+    max_message_size: Option<NonZeroUsize>,
+    // Indicates that the inner decoder has consumed part of an (unfragmented) frame.
+    mid_frame: bool,
+    assembled: BytesMut,
+    inner: D,
+}
+
+impl<D> ReassemblingDecoder<D> {
+    pub fn new(inner: D, max_message_size: Option<NonZeroUsize>) -> Self {
+        ReassemblingDecoder {
+            max_message_size,
+            mid_frame: false,
+            assembled: BytesMut::new(),
+            inner,
+        }
+    }
+
+    fn check_size(&self, size: usize) -> Result<(), FrameIoError> {
+        match self.max_message_size {
+            Some(limit) if size > limit.get() => {
+                Err(FrameIoError::BadFrame(InvalidFrame::TooLarge {
+                    size,
+                    limit: limit.get(),
+                }))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<D> Decoder for ReassemblingDecoder<D>
+where
+    D: Decoder<Error = FrameIoError>,
+{
+    type Item = D::Item;
+
+    type Error = FrameIoError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            if self.mid_frame || src.first() != Some(&FRAGMENT) {
+                let had_data = !src.is_empty();
+                let result = self.inner.decode(src);
+                self.mid_frame = matches!(result, Ok(None)) && (self.mid_frame || had_data);
+                if matches!(result, Ok(None)) {
+                    self.check_size(src.len())?;
+                }
+                break result;
+            }
+            if src.len() < FRAGMENT_HEADER_LEN {
+                src.reserve(FRAGMENT_HEADER_LEN);
+                break Ok(None);
+            }
+            let mut header = &src.as_ref()[TAG_LEN..FRAGMENT_HEADER_LEN];
+            let marker = header.get_u8();
+            let len = header.get_u64() as usize;
+            let size = self.assembled.len() + len;
+            if let Err(err) = self.check_size(size) {
+                self.assembled.clear();
+                break Err(err);
+            }
+            if marker != MORE && marker != LAST {
+                self.assembled.clear();
+                break Err(FrameIoError::BadFrame(InvalidFrame::InvalidHeader {
+                    problem: format!("Invalid fragment marker: {}", marker).into(),
+                }));
+            }
+            if src.len() < FRAGMENT_HEADER_LEN + len {
+                src.reserve(FRAGMENT_HEADER_LEN + len - src.len());
+                break Ok(None);
+            }
+            src.advance(FRAGMENT_HEADER_LEN);
+            self.assembled.extend_from_slice(&src.split_to(len));
+            if marker == LAST {
+                let mut frame = std::mem::take(&mut self.assembled);
+                break match self.inner.decode_eof(&mut frame)? {
+                    Some(item) if frame.is_empty() => Ok(Some(item)),
+                    _ => Err(FrameIoError::BadFrame(InvalidFrame::Incomplete)),
+                };
+            }
+        }
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::num::NonZeroUsize;
+
+use bytes::{Buf, BytesMut};
+use swimos_api::error::{FrameIoError, InvalidFrame};
+use tokio_util::codec::{Decoder, Encoder};
+use uuid::Uuid;
+
+use crate::{
+    lane::{
+        RawMapLaneRequestDecoder, RawMapLaneRequestEncoder, RawValueLaneRequestDecoder,
+        RawValueLaneRequestEncoder,
+    },
+    LaneRequest, MapMessage, FRAGMENT,
+};
+
+const FRAGMENT_SIZE: NonZeroUsize = match NonZeroUsize::new(16) {
+    Some(n) => n,
+    None => panic!(),
+};
+
+fn body(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+fn decode_all<D: Decoder<Error = FrameIoError>>(
+    decoder: &mut D,
+    buffer: &mut BytesMut,
+) -> Result<Vec<D::Item>, FrameIoError> {
+    let mut results = vec![];
+    while let Some(item) = decoder.decode(buffer)? {
+        results.push(item);
+    }
+    Ok(results)
+}
+
+#[test]
+fn small_frames_are_not_fragmented() {
+    let mut encoder = RawValueLaneRequestEncoder::with_max_fragment_size(FRAGMENT_SIZE);
+    let mut buffer = BytesMut::new();
+    let content = body(4);
+    assert!(encoder
+        .encode(LaneRequest::Command(content.as_slice()), &mut buffer)
+        .is_ok());
+    assert_eq!(buffer.len(), 13);
+    assert_ne!(buffer[0], FRAGMENT);
+}
+
+#[test]
+fn large_frames_are_fragmented() {
+    let mut encoder = RawValueLaneRequestEncoder::with_max_fragment_size(FRAGMENT_SIZE);
+    let mut buffer = BytesMut::new();
+    let content = body(100);
+    assert!(encoder
+        .encode(LaneRequest::Command(content.as_slice()), &mut buffer)
+        .is_ok());
+
+    let mut total = 0;
+    let mut fragments = 0;
+    loop {
+        assert_eq!(buffer.get_u8(), FRAGMENT);
+        let marker = buffer.get_u8();
+        let len = buffer.get_u64() as usize;
+        assert!(len <= FRAGMENT_SIZE.get());
+        buffer.advance(len);
+        total += len;
+        fragments += 1;
+        if marker == super::LAST {
+            break;
+        }
+        assert_eq!(marker, super::MORE);
+    }
+    assert!(buffer.is_empty());
+    assert_eq!(total, 109);
+    assert_eq!(fragments, 7);
+}
+
+#[test]
+fn reassemble_value_request() {
+    let mut encoder = RawValueLaneRequestEncoder::with_max_fragment_size(FRAGMENT_SIZE);
+    let mut decoder = RawValueLaneRequestDecoder::default();
+    let mut buffer = BytesMut::new();
+    let content = body(100);
+    let id = Uuid::from_u128(7);
+    assert!(encoder
+        .encode(LaneRequest::Command(content.as_slice()), &mut buffer)
+        .is_ok());
+    assert!(encoder
+        .encode(LaneRequest::<&[u8]>::Sync(id), &mut buffer)
+        .is_ok());
+    assert!(encoder
+        .encode(
+            LaneRequest::CommandFrom(id, content.as_slice()),
+            &mut buffer
+        )
+        .is_ok());
+
+    let requests = decode_all(&mut decoder, &mut buffer).expect("Decoding failed.");
+    assert_eq!(
+        requests,
+        vec![
+            LaneRequest::Command(BytesMut::from(content.as_slice())),
+            LaneRequest::Sync(id),
+            LaneRequest::CommandFrom(id, BytesMut::from(content.as_slice())),
+        ]
+    );
+}
+
+#[test]
+fn reassemble_map_request_incrementally() {
+    let mut encoder = RawMapLaneRequestEncoder::with_max_fragment_size(FRAGMENT_SIZE);
+    let mut decoder = RawMapLaneRequestDecoder::default();
+    let mut encoded = BytesMut::new();
+    let key = body(10);
+    let value = body(60);
+    let request = LaneRequest::Command(MapMessage::Update {
+        key: key.as_slice(),
+        value: value.as_slice(),
+    });
+    assert!(encoder.encode(request, &mut encoded).is_ok());
+
+    // Deliver the frame a few bytes at a time.
+    let mut buffer = BytesMut::new();
+    let mut result = None;
+    while !encoded.is_empty() {
+        let n = encoded.len().min(5);
+        buffer.extend_from_slice(&encoded.split_to(n));
+        if let Some(request) = decoder.decode(&mut buffer).expect("Decoding failed.") {
+            assert!(result.is_none());
+            result = Some(request);
+        }
+    }
+    assert!(buffer.is_empty());
+    assert_eq!(
+        result,
+        Some(LaneRequest::Command(MapMessage::Update {
+            key: BytesMut::from(key.as_slice()),
+            value: BytesMut::from(value.as_slice()),
+        }))
+    );
+}
+
+#[test]
+fn fragmented_message_too_large() {
+    let mut encoder = RawValueLaneRequestEncoder::with_max_fragment_size(FRAGMENT_SIZE);
+    let mut decoder = RawValueLaneRequestDecoder::with_max_message_size(NonZeroUsize::new(64));
+    let mut buffer = BytesMut::new();
+    let content = body(100);
+    assert!(encoder
+        .encode(LaneRequest::Command(content.as_slice()), &mut buffer)
+        .is_ok());
+
+    let result = decode_all(&mut decoder, &mut buffer);
+    assert!(matches!(
+        result,
+        Err(FrameIoError::BadFrame(InvalidFrame::TooLarge {
+            limit: 64,
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn unfragmented_message_too_large() {
+    let mut encoder = RawValueLaneRequestEncoder::default();
+    let mut decoder = RawValueLaneRequestDecoder::with_max_message_size(NonZeroUsize::new(64));
+    let mut encoded = BytesMut::new();
+    let content = body(100);
+    assert!(encoder
+        .encode(LaneRequest::Command(content.as_slice()), &mut encoded)
+        .is_ok());
+
+    // Only part of the message has arrived but it already exceeds the limit.
+    let mut buffer = encoded.split_to(80);
+    let result = decode_all(&mut decoder, &mut buffer);
+    assert!(matches!(
+        result,
+        Err(FrameIoError::BadFrame(InvalidFrame::TooLarge {
+            limit: 64,
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn message_within_limit() {
+    let mut encoder = RawValueLaneRequestEncoder::with_max_fragment_size(FRAGMENT_SIZE);
+    let mut decoder = RawValueLaneRequestDecoder::with_max_message_size(NonZeroUsize::new(128));
+    let mut buffer = BytesMut::new();
+    let content = body(100);
+    assert!(encoder
+        .encode(LaneRequest::Command(content.as_slice()), &mut buffer)
+        .is_ok());
+
+    let requests = decode_all(&mut decoder, &mut buffer).expect("Decoding failed.");
+    assert_eq!(
+        requests,
+        vec![LaneRequest::Command(BytesMut::from(content.as_slice()))]
+    );
+}
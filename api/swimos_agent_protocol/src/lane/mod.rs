@@ -13,13 +13,18 @@
 // limitations under the License.
 
 use std::fmt::Debug;
+use std::num::NonZeroUsize;
 
 use crate::{
+    fragment::{FragmentingEncoder, ReassemblingDecoder},
     map::{RawMapMessageDecoder, RawMapMessageEncoder},
-    LaneRequest, LaneResponse, MapLaneResponse, MapMessage, MapOperation, COMMAND, EVENT, ID_LEN,
-    INITIALIZED, INIT_DONE, SYNC, SYNC_COMPLETE, TAG_LEN,
+    payload::{Encoded, PayloadCodec},
+    LaneRequest, LaneResponse, MapLaneResponse, MapMessage, MapOperation, COMMAND, COMMAND_FROM,
+    CORRELATED_COMMAND, CORRELATED_COMMAND_FROM, CORRELATION, CORRELATION_LEN, EVENT, EVENT_BATCH,
+    ID_LEN, INITIALIZED, INIT_DONE, LEN_SIZE, ORIGIN, ORIGIN_LEN, RANGE_FLAGS_LEN, SYNC,
+    SYNC_COMPLETE, SYNC_COMPLETE_AT, SYNC_RANGE, SYNC_SINCE, TAG_LEN, VERSION_LEN,
 };
-use bytes::{Buf, BufMut, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use swimos_form::{read::RecognizerReadable, write::StructuralWritable};
 use swimos_model::Text;
 use swimos_recon::{WithLenRecognizerDecoder, WithLenReconEncoder};
@@ -27,7 +32,10 @@ use swimos_utilities::encoding::WithLengthBytesCodec;
 use tokio_util::codec::{Decoder, Encoder};
 use uuid::Uuid;
 
-use swimos_api::error::{FrameIoError, InvalidFrame};
+use swimos_api::{
+    agent::{KeyRange, SyncVersion},
+    error::{FrameIoError, InvalidFrame},
+};
 
 use crate::map::{MapMessageDecoder, MapOperationDecoder};
 
@@ -37,6 +45,80 @@ use super::map::{
 #[cfg(test)]
 mod tests;
 
+fn put_version(version: SyncVersion, dst: &mut BytesMut) {
+    let SyncVersion { epoch, version } = version;
+    dst.put_u64(epoch);
+    dst.put_u64(version);
+}
+
+fn get_version<B: Buf>(src: &mut B) -> SyncVersion {
+    let epoch = src.get_u64();
+    let version = src.get_u64();
+    SyncVersion { epoch, version }
+}
+
+const HAS_FROM: u8 = 0b01;
+const HAS_TO: u8 = 0b10;
+
+// A key range is encoded as a byte of flags, indicating which bounds are present, followed by each
+// of the bounds that is present, prefixed by its length.
+fn put_range(range: &KeyRange<Bytes>, dst: &mut BytesMut) {
+    let KeyRange { from, to } = range;
+    let mut flags = 0;
+    if from.is_some() {
+        flags |= HAS_FROM;
+    }
+    if to.is_some() {
+        flags |= HAS_TO;
+    }
+    let bounds_len = [from, to]
+        .into_iter()
+        .flatten()
+        .map(|b| LEN_SIZE + b.len())
+        .sum::<usize>();
+    dst.reserve(RANGE_FLAGS_LEN + bounds_len);
+    dst.put_u8(flags);
+    for bound in [from, to].into_iter().flatten() {
+        dst.put_u64(bound.len() as u64);
+        dst.put_slice(bound.as_ref());
+    }
+}
+
+// Determine the length of an encoded key range, if enough bytes are available.
+fn range_len(bytes: &[u8]) -> Option<usize> {
+    let (flags, mut rem) = bytes.split_first()?;
+    let mut len = RANGE_FLAGS_LEN;
+    for flag in [HAS_FROM, HAS_TO] {
+        if flags & flag != 0 {
+            if rem.len() < LEN_SIZE {
+                return None;
+            }
+            let bound_len = usize::try_from(rem.get_u64()).ok()?;
+            if rem.len() < bound_len {
+                return None;
+            }
+            rem.advance(bound_len);
+            len += LEN_SIZE + bound_len;
+        }
+    }
+    Some(len)
+}
+
+fn get_range(src: &mut BytesMut) -> KeyRange<Bytes> {
+    let flags = src.get_u8();
+    let mut get_bound = |flag: u8| {
+        if flags & flag != 0 {
+            let len = src.get_u64() as usize;
+            Some(src.split_to(len).freeze())
+        } else {
+            None
+        }
+    };
+    let from = get_bound(HAS_FROM);
+    let to = get_bound(HAS_TO);
+    KeyRange { from, to }
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 struct LaneRequestEncoder<Inner> {
     inner: Inner,
@@ -56,11 +138,45 @@ where
                 dst.put_u8(COMMAND);
                 inner.encode(cmd, dst)?;
             }
+            LaneRequest::CommandFrom(origin, cmd) => {
+                let LaneRequestEncoder { inner, .. } = self;
+                dst.reserve(TAG_LEN + ID_LEN);
+                dst.put_u8(COMMAND_FROM);
+                dst.put_u128(origin.as_u128());
+                inner.encode(cmd, dst)?;
+            }
+            LaneRequest::CorrelatedCommand(correlation_id, None, cmd) => {
+                let LaneRequestEncoder { inner, .. } = self;
+                dst.reserve(TAG_LEN + LEN_SIZE);
+                dst.put_u8(CORRELATED_COMMAND);
+                dst.put_u64(correlation_id);
+                inner.encode(cmd, dst)?;
+            }
+            LaneRequest::CorrelatedCommand(correlation_id, Some(origin), cmd) => {
+                let LaneRequestEncoder { inner, .. } = self;
+                dst.reserve(TAG_LEN + LEN_SIZE + ID_LEN);
+                dst.put_u8(CORRELATED_COMMAND_FROM);
+                dst.put_u64(correlation_id);
+                dst.put_u128(origin.as_u128());
+                inner.encode(cmd, dst)?;
+            }
             LaneRequest::Sync(id) => {
                 dst.reserve(TAG_LEN + ID_LEN);
                 dst.put_u8(SYNC);
                 dst.put_u128(id.as_u128());
             }
+            LaneRequest::SyncSince(id, since) => {
+                dst.reserve(TAG_LEN + ID_LEN + VERSION_LEN);
+                dst.put_u8(SYNC_SINCE);
+                dst.put_u128(id.as_u128());
+                put_version(since, dst);
+            }
+            LaneRequest::SyncRange(id, range) => {
+                dst.reserve(TAG_LEN + ID_LEN);
+                dst.put_u8(SYNC_RANGE);
+                dst.put_u128(id.as_u128());
+                put_range(&range, dst);
+            }
             LaneRequest::InitComplete => {
                 dst.reserve(TAG_LEN);
                 dst.put_u8(INIT_DONE);
@@ -74,7 +190,8 @@ where
 enum LaneRequestDecoderState {
     #[default]
     ReadingHeader,
-    ReadingBody,
+    // The origin and correlation ID of the command, if it was tagged with them.
+    ReadingBody(Option<Uuid>, Option<u64>),
 }
 
 #[derive(Debug, Default)]
@@ -113,7 +230,39 @@ where
                     match src.as_ref()[0] {
                         COMMAND => {
                             src.advance(TAG_LEN);
-                            *state = LaneRequestDecoderState::ReadingBody;
+                            *state = LaneRequestDecoderState::ReadingBody(None, None);
+                        }
+                        COMMAND_FROM => {
+                            if src.remaining() < TAG_LEN + ID_LEN {
+                                src.reserve(TAG_LEN + ID_LEN);
+                                break Ok(None);
+                            }
+                            src.advance(TAG_LEN);
+                            let origin = Uuid::from_u128(src.get_u128());
+                            *state = LaneRequestDecoderState::ReadingBody(Some(origin), None);
+                        }
+                        CORRELATED_COMMAND => {
+                            if src.remaining() < TAG_LEN + LEN_SIZE {
+                                src.reserve(TAG_LEN + LEN_SIZE);
+                                break Ok(None);
+                            }
+                            src.advance(TAG_LEN);
+                            let correlation_id = src.get_u64();
+                            *state =
+                                LaneRequestDecoderState::ReadingBody(None, Some(correlation_id));
+                        }
+                        CORRELATED_COMMAND_FROM => {
+                            if src.remaining() < TAG_LEN + LEN_SIZE + ID_LEN {
+                                src.reserve(TAG_LEN + LEN_SIZE + ID_LEN);
+                                break Ok(None);
+                            }
+                            src.advance(TAG_LEN);
+                            let correlation_id = src.get_u64();
+                            let origin = Uuid::from_u128(src.get_u128());
+                            *state = LaneRequestDecoderState::ReadingBody(
+                                Some(origin),
+                                Some(correlation_id),
+                            );
                         }
                         SYNC => {
                             if src.remaining() < TAG_LEN + ID_LEN {
@@ -124,6 +273,32 @@ where
                             let id = Uuid::from_u128(src.get_u128());
                             break Ok(Some(LaneRequest::Sync(id)));
                         }
+                        SYNC_SINCE => {
+                            if src.remaining() < TAG_LEN + ID_LEN + VERSION_LEN {
+                                src.reserve(TAG_LEN + ID_LEN + VERSION_LEN);
+                                break Ok(None);
+                            }
+                            src.advance(TAG_LEN);
+                            let id = Uuid::from_u128(src.get_u128());
+                            let since = get_version(src);
+                            break Ok(Some(LaneRequest::SyncSince(id, since)));
+                        }
+                        SYNC_RANGE => {
+                            // The range is only decoded once all of its bytes are available.
+                            let complete = src
+                                .as_ref()
+                                .get(TAG_LEN + ID_LEN..)
+                                .and_then(range_len)
+                                .is_some();
+                            if !complete {
+                                src.reserve(TAG_LEN + ID_LEN + RANGE_FLAGS_LEN);
+                                break Ok(None);
+                            }
+                            src.advance(TAG_LEN);
+                            let id = Uuid::from_u128(src.get_u128());
+                            let range = get_range(src);
+                            break Ok(Some(LaneRequest::SyncRange(id, range)));
+                        }
                         INIT_DONE => {
                             src.advance(TAG_LEN);
                             break Ok(Some(LaneRequest::InitComplete));
@@ -136,11 +311,18 @@ where
                         }
                     }
                 }
-                LaneRequestDecoderState::ReadingBody => {
+                LaneRequestDecoderState::ReadingBody(origin, correlation_id) => {
                     break match inner.decode(src) {
                         Ok(Some(value)) => {
+                            let request = match (origin.take(), correlation_id.take()) {
+                                (origin, Some(id)) => {
+                                    LaneRequest::CorrelatedCommand(id, origin, value)
+                                }
+                                (Some(origin), None) => LaneRequest::CommandFrom(origin, value),
+                                (None, None) => LaneRequest::Command(value),
+                            };
                             *state = LaneRequestDecoderState::ReadingHeader;
-                            Ok(Some(LaneRequest::Command(value)))
+                            Ok(Some(request))
                         }
                         Ok(None) => Ok(None),
                         Err(e) => {
@@ -188,6 +370,29 @@ where
                 dst.put_u8(SYNC_COMPLETE);
                 dst.put_u128(id.as_u128());
             }
+            LaneResponse::SyncedAt(id, version) => {
+                dst.reserve(TAG_LEN + ID_LEN + VERSION_LEN);
+                dst.put_u8(SYNC_COMPLETE_AT);
+                dst.put_u128(id.as_u128());
+                put_version(version, dst);
+            }
+            LaneResponse::EventBatch(n) => {
+                dst.reserve(TAG_LEN + LEN_SIZE);
+                dst.put_u8(EVENT_BATCH);
+                dst.put_u64(n);
+            }
+            LaneResponse::Correlated(correlation_id) => {
+                dst.reserve(TAG_LEN + CORRELATION_LEN);
+                dst.put_u8(CORRELATION);
+                dst.put_u8(u8::from(correlation_id.is_some()));
+                dst.put_u64(correlation_id.unwrap_or_default());
+            }
+            LaneResponse::Originated(origin) => {
+                dst.reserve(TAG_LEN + ORIGIN_LEN);
+                dst.put_u8(ORIGIN);
+                dst.put_u8(u8::from(origin.is_some()));
+                dst.put_u128(origin.map(|id| id.as_u128()).unwrap_or_default());
+            }
         }
         Ok(())
     }
@@ -263,6 +468,47 @@ where
                             src.advance(TAG_LEN + ID_LEN);
                             return Ok(Some(LaneResponse::Synced(id)));
                         }
+                        SYNC_COMPLETE_AT => {
+                            if bytes.len() < ID_LEN + VERSION_LEN {
+                                src.reserve(ID_LEN + VERSION_LEN);
+                                return Ok(None);
+                            }
+                            let id = Uuid::from_u128(bytes.get_u128());
+                            let version = get_version(&mut bytes);
+                            src.advance(TAG_LEN + ID_LEN + VERSION_LEN);
+                            return Ok(Some(LaneResponse::SyncedAt(id, version)));
+                        }
+                        EVENT_BATCH => {
+                            if bytes.len() < LEN_SIZE {
+                                src.reserve(LEN_SIZE);
+                                return Ok(None);
+                            }
+                            let n = bytes.get_u64();
+                            src.advance(TAG_LEN + LEN_SIZE);
+                            return Ok(Some(LaneResponse::EventBatch(n)));
+                        }
+                        CORRELATION => {
+                            if bytes.len() < CORRELATION_LEN {
+                                src.reserve(CORRELATION_LEN);
+                                return Ok(None);
+                            }
+                            let present = bytes.get_u8() != 0;
+                            let correlation_id = bytes.get_u64();
+                            src.advance(TAG_LEN + CORRELATION_LEN);
+                            return Ok(Some(LaneResponse::Correlated(
+                                present.then_some(correlation_id),
+                            )));
+                        }
+                        ORIGIN => {
+                            if bytes.len() < ORIGIN_LEN {
+                                src.reserve(ORIGIN_LEN);
+                                return Ok(None);
+                            }
+                            let present = bytes.get_u8() != 0;
+                            let origin = Uuid::from_u128(bytes.get_u128());
+                            src.advance(TAG_LEN + ORIGIN_LEN);
+                            return Ok(Some(LaneResponse::Originated(present.then_some(origin))));
+                        }
                         t => {
                             return Err(FrameIoError::BadFrame(InvalidFrame::InvalidHeader {
                                 problem: Text::from(format!("Invalid lane response tag: {}", t)),
@@ -302,6 +548,56 @@ impl<T: StructuralWritable> Encoder<LaneResponse<T>> for ValueLaneResponseEncode
     }
 }
 
+impl<C: PayloadCodec> Encoder<LaneResponse<Encoded<C>>> for ValueLaneResponseEncoder {
+    type Error = std::io::Error;
+
+    fn encode(
+        &mut self,
+        item: LaneResponse<Encoded<C>>,
+        dst: &mut BytesMut,
+    ) -> Result<(), Self::Error> {
+        LaneResponseEncoder::<EncodedPayloadEncoder>::default().encode(item, dst)
+    }
+}
+
+impl<'a, C: PayloadCodec> Encoder<LaneResponse<&'a Encoded<C>>> for ValueLaneResponseEncoder {
+    type Error = std::io::Error;
+
+    fn encode(
+        &mut self,
+        item: LaneResponse<&'a Encoded<C>>,
+        dst: &mut BytesMut,
+    ) -> Result<(), Self::Error> {
+        LaneResponseEncoder::<EncodedPayloadEncoder>::default().encode(item, dst)
+    }
+}
+
+/// Writes the payload of an [`Encoded`] value, prefixed with its length.
+#[derive(Debug, Clone, Copy, Default)]
+struct EncodedPayloadEncoder;
+
+impl<'a, C: PayloadCodec> Encoder<&'a Encoded<C>> for EncodedPayloadEncoder {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: &'a Encoded<C>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.reserve(LEN_SIZE);
+        let offset = dst.len();
+        dst.put_u64(0);
+        item.encode(dst);
+        let len = (dst.len() - offset - LEN_SIZE) as u64;
+        dst[offset..offset + LEN_SIZE].copy_from_slice(&len.to_be_bytes());
+        Ok(())
+    }
+}
+
+impl<C: PayloadCodec> Encoder<Encoded<C>> for EncodedPayloadEncoder {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: Encoded<C>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.encode(&item, dst)
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct RawValueLaneResponseEncoder {
     inner: LaneResponseEncoder<WithLengthBytesCodec>,
@@ -351,9 +647,18 @@ impl<K: AsRef<[u8]>, V: AsRef<[u8]>> Encoder<MapLaneResponse<K, V>> for RawMapLa
     }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Default)]
 pub struct RawValueLaneResponseDecoder {
-    inner: LaneResponseDecoder<WithLengthBytesCodec>,
+    inner: ReassemblingDecoder<LaneResponseDecoder<WithLengthBytesCodec>>,
+}
+
+impl RawValueLaneResponseDecoder {
+    /// Create a decoder that will fail if it receives a message larger than the specified size.
+    pub fn with_max_message_size(max_message_size: Option<NonZeroUsize>) -> Self {
+        RawValueLaneResponseDecoder {
+            inner: ReassemblingDecoder::new(Default::default(), max_message_size),
+        }
+    }
 }
 
 impl Decoder for RawValueLaneResponseDecoder {
@@ -367,13 +672,16 @@ impl Decoder for RawValueLaneResponseDecoder {
 }
 
 pub struct ValueLaneResponseDecoder<T: RecognizerReadable> {
-    inner: LaneResponseDecoder<WithLenRecognizerDecoder<T::Rec>>,
+    inner: ReassemblingDecoder<LaneResponseDecoder<WithLenRecognizerDecoder<T::Rec>>>,
 }
 
 impl<T: RecognizerReadable> Default for ValueLaneResponseDecoder<T> {
     fn default() -> Self {
         Self {
-            inner: LaneResponseDecoder::new(WithLenRecognizerDecoder::new(T::make_recognizer())),
+            inner: ReassemblingDecoder::new(
+                LaneResponseDecoder::new(WithLenRecognizerDecoder::new(T::make_recognizer())),
+                None,
+            ),
         }
     }
 }
@@ -388,9 +696,18 @@ impl<T: RecognizerReadable> Decoder for ValueLaneResponseDecoder<T> {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Default)]
 pub struct RawMapLaneResponseDecoder {
-    inner: LaneResponseDecoder<RawMapOperationDecoder>,
+    inner: ReassemblingDecoder<LaneResponseDecoder<RawMapOperationDecoder>>,
+}
+
+impl RawMapLaneResponseDecoder {
+    /// Create a decoder that will fail if it receives a message larger than the specified size.
+    pub fn with_max_message_size(max_message_size: Option<NonZeroUsize>) -> Self {
+        RawMapLaneResponseDecoder {
+            inner: ReassemblingDecoder::new(Default::default(), max_message_size),
+        }
+    }
 }
 
 impl Decoder for RawMapLaneResponseDecoder {
@@ -404,7 +721,7 @@ impl Decoder for RawMapLaneResponseDecoder {
 }
 
 pub struct MapLaneResponseDecoder<K: RecognizerReadable, V: RecognizerReadable> {
-    inner: LaneResponseDecoder<MapOperationDecoder<K, V>>,
+    inner: ReassemblingDecoder<LaneResponseDecoder<MapOperationDecoder<K, V>>>,
 }
 
 impl<K: RecognizerReadable, V: RecognizerReadable> Default for MapLaneResponseDecoder<K, V> {
@@ -427,7 +744,16 @@ impl<K: RecognizerReadable, V: RecognizerReadable> Decoder for MapLaneResponseDe
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct RawValueLaneRequestEncoder {
-    inner: LaneRequestEncoder<WithLengthBytesCodec>,
+    inner: FragmentingEncoder<LaneRequestEncoder<WithLengthBytesCodec>>,
+}
+
+impl RawValueLaneRequestEncoder {
+    /// Create an encoder that will split any frame larger than the specified size into fragments.
+    pub fn with_max_fragment_size(max_fragment_size: NonZeroUsize) -> Self {
+        RawValueLaneRequestEncoder {
+            inner: FragmentingEncoder::new(Default::default(), Some(max_fragment_size)),
+        }
+    }
 }
 
 impl<T: AsRef<[u8]>> Encoder<LaneRequest<T>> for RawValueLaneRequestEncoder {
@@ -440,7 +766,16 @@ impl<T: AsRef<[u8]>> Encoder<LaneRequest<T>> for RawValueLaneRequestEncoder {
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct RawMapLaneRequestEncoder {
-    inner: LaneRequestEncoder<RawMapMessageEncoder>,
+    inner: FragmentingEncoder<LaneRequestEncoder<RawMapMessageEncoder>>,
+}
+
+impl RawMapLaneRequestEncoder {
+    /// Create an encoder that will split any frame larger than the specified size into fragments.
+    pub fn with_max_fragment_size(max_fragment_size: NonZeroUsize) -> Self {
+        RawMapLaneRequestEncoder {
+            inner: FragmentingEncoder::new(Default::default(), Some(max_fragment_size)),
+        }
+    }
 }
 
 impl<K: AsRef<[u8]>, V: AsRef<[u8]>> Encoder<LaneRequest<MapMessage<K, V>>>
@@ -491,7 +826,16 @@ impl<K: StructuralWritable, V: StructuralWritable> Encoder<LaneRequest<MapMessag
 
 #[derive(Debug, Default)]
 pub struct RawValueLaneRequestDecoder {
-    inner: LaneRequestDecoder<WithLengthBytesCodec>,
+    inner: ReassemblingDecoder<LaneRequestDecoder<WithLengthBytesCodec>>,
+}
+
+impl RawValueLaneRequestDecoder {
+    /// Create a decoder that will fail if it receives a message larger than the specified size.
+    pub fn with_max_message_size(max_message_size: Option<NonZeroUsize>) -> Self {
+        RawValueLaneRequestDecoder {
+            inner: ReassemblingDecoder::new(Default::default(), max_message_size),
+        }
+    }
 }
 
 impl Decoder for RawValueLaneRequestDecoder {
@@ -505,13 +849,16 @@ impl Decoder for RawValueLaneRequestDecoder {
 }
 
 pub struct ValueLaneRequestDecoder<T: RecognizerReadable> {
-    inner: LaneRequestDecoder<WithLenRecognizerDecoder<T::Rec>>,
+    inner: ReassemblingDecoder<LaneRequestDecoder<WithLenRecognizerDecoder<T::Rec>>>,
 }
 
 impl<T: RecognizerReadable> Default for ValueLaneRequestDecoder<T> {
     fn default() -> Self {
         Self {
-            inner: LaneRequestDecoder::new(WithLenRecognizerDecoder::new(T::make_recognizer())),
+            inner: ReassemblingDecoder::new(
+                LaneRequestDecoder::new(WithLenRecognizerDecoder::new(T::make_recognizer())),
+                None,
+            ),
         }
     }
 }
@@ -528,7 +875,16 @@ impl<T: RecognizerReadable> Decoder for ValueLaneRequestDecoder<T> {
 
 #[derive(Debug, Default)]
 pub struct RawMapLaneRequestDecoder {
-    inner: LaneRequestDecoder<RawMapMessageDecoder>,
+    inner: ReassemblingDecoder<LaneRequestDecoder<RawMapMessageDecoder>>,
+}
+
+impl RawMapLaneRequestDecoder {
+    /// Create a decoder that will fail if it receives a message larger than the specified size.
+    pub fn with_max_message_size(max_message_size: Option<NonZeroUsize>) -> Self {
+        RawMapLaneRequestDecoder {
+            inner: ReassemblingDecoder::new(Default::default(), max_message_size),
+        }
+    }
 }
 
 impl Decoder for RawMapLaneRequestDecoder {
@@ -542,13 +898,16 @@ impl Decoder for RawMapLaneRequestDecoder {
 }
 
 pub struct MapLaneRequestDecoder<K: RecognizerReadable, V: RecognizerReadable> {
-    inner: LaneRequestDecoder<MapMessageDecoder<K, V>>,
+    inner: ReassemblingDecoder<LaneRequestDecoder<MapMessageDecoder<K, V>>>,
 }
 
 impl<K: RecognizerReadable, V: RecognizerReadable> Default for MapLaneRequestDecoder<K, V> {
     fn default() -> Self {
         Self {
-            inner: LaneRequestDecoder::new(MapMessageDecoder::default()),
+            inner: ReassemblingDecoder::new(
+                LaneRequestDecoder::new(MapMessageDecoder::default()),
+                None,
+            ),
         }
     }
 }
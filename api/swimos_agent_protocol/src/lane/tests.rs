@@ -14,6 +14,7 @@
 
 use bytes::{Buf, Bytes, BytesMut};
 use std::fmt::Write;
+use swimos_api::agent::{KeyRange, SyncVersion};
 use swimos_form::{read::RecognizerReadable, write::StructuralWritable, Form};
 use swimos_recon::{print_recon_compact, WithLenRecognizerDecoder};
 use tokio_util::codec::{Decoder, Encoder};
@@ -26,7 +27,7 @@ use crate::{
         ValueLaneResponseEncoder, SYNC,
     },
     map::MapOperationEncoder,
-    MapOperation,
+    DecodePayload, Encoded, MapOperation, PayloadCodec, PayloadDecodeError, RawBytes,
 };
 
 use super::LaneRequest;
@@ -57,6 +58,37 @@ fn encode_command_lane_request() {
     assert_eq!(buffer.as_ref(), content);
 }
 
+#[test]
+fn encode_command_from_lane_request() {
+    let mut encoder = RawValueLaneRequestEncoder::default();
+    let mut buffer = BytesMut::new();
+    let content = b"body";
+    let request = LaneRequest::CommandFrom(Uuid::from_u128(23), content);
+    assert!(encoder.encode(request, &mut buffer).is_ok());
+
+    assert_eq!(buffer.remaining(), 25 + content.len());
+    assert_eq!(buffer.get_u8(), crate::lane::COMMAND_FROM);
+    assert_eq!(buffer.get_u128(), 23);
+    assert_eq!(buffer.get_u64(), content.len() as u64);
+    assert_eq!(buffer.as_ref(), content);
+}
+
+#[test]
+fn encode_correlated_command_lane_request() {
+    let mut encoder = RawValueLaneRequestEncoder::default();
+    let mut buffer = BytesMut::new();
+    let content = b"body";
+    let request = LaneRequest::CorrelatedCommand(99, Some(Uuid::from_u128(23)), content);
+    assert!(encoder.encode(request, &mut buffer).is_ok());
+
+    assert_eq!(buffer.remaining(), 33 + content.len());
+    assert_eq!(buffer.get_u8(), crate::lane::CORRELATED_COMMAND_FROM);
+    assert_eq!(buffer.get_u64(), 99);
+    assert_eq!(buffer.get_u128(), 23);
+    assert_eq!(buffer.get_u64(), content.len() as u64);
+    assert_eq!(buffer.as_ref(), content);
+}
+
 #[derive(Debug, Form, Clone, Copy, PartialEq, Eq)]
 struct Example {
     a: i32,
@@ -66,11 +98,23 @@ struct Example {
 fn round_trip_request(request: LaneRequest<Example>) {
     let with_bytes = match &request {
         LaneRequest::Sync(n) => LaneRequest::Sync(*n),
+        LaneRequest::SyncSince(n, since) => LaneRequest::SyncSince(*n, *since),
+        LaneRequest::SyncRange(n, range) => LaneRequest::SyncRange(*n, range.clone()),
         LaneRequest::Command(value) => {
             let mut buffer = BytesMut::new();
             assert!(write!(buffer, "{}", print_recon_compact(value)).is_ok());
             LaneRequest::Command(buffer.freeze())
         }
+        LaneRequest::CommandFrom(origin, value) => {
+            let mut buffer = BytesMut::new();
+            assert!(write!(buffer, "{}", print_recon_compact(value)).is_ok());
+            LaneRequest::CommandFrom(*origin, buffer.freeze())
+        }
+        LaneRequest::CorrelatedCommand(id, origin, value) => {
+            let mut buffer = BytesMut::new();
+            assert!(write!(buffer, "{}", print_recon_compact(value)).is_ok());
+            LaneRequest::CorrelatedCommand(*id, *origin, buffer.freeze())
+        }
         LaneRequest::InitComplete => LaneRequest::InitComplete,
     };
 
@@ -99,11 +143,79 @@ fn decode_sync_lane_request() {
     round_trip_request(LaneRequest::Sync(Uuid::from_u128(892)));
 }
 
+#[test]
+fn decode_sync_since_lane_request() {
+    round_trip_request(LaneRequest::SyncSince(
+        Uuid::from_u128(892),
+        SyncVersion::new(3, 1234),
+    ));
+}
+
+#[test]
+fn decode_sync_range_lane_request() {
+    let ranges = [
+        KeyRange::new(None, None),
+        KeyRange::new(Some(Bytes::from_static(b"1000")), None),
+        KeyRange::new(None, Some(Bytes::from_static(b"\"key\""))),
+        KeyRange::new(
+            Some(Bytes::from_static(b"1000")),
+            Some(Bytes::from_static(b"2000")),
+        ),
+    ];
+    for range in ranges {
+        round_trip_request(LaneRequest::SyncRange(Uuid::from_u128(892), range));
+    }
+}
+
+#[test]
+fn decode_partial_sync_range_lane_request() {
+    let mut encoder = RawValueLaneRequestEncoder::default();
+    let mut buffer = BytesMut::new();
+    let range = KeyRange::new(Some(Bytes::from_static(b"1000")), None);
+    let request: LaneRequest<&[u8]> = LaneRequest::SyncRange(Uuid::from_u128(7), range.clone());
+    assert!(encoder.encode(request, &mut buffer).is_ok());
+
+    let decoder = WithLenRecognizerDecoder::new(Example::make_recognizer());
+    let mut decoder = LaneRequestDecoder::new(decoder);
+    let mut partial = buffer.split_to(buffer.len() - 2);
+    assert!(matches!(decoder.decode(&mut partial), Ok(None)));
+    partial.unsplit(buffer);
+    match decoder.decode(&mut partial) {
+        Ok(Some(restored)) => {
+            assert_eq!(restored, LaneRequest::SyncRange(Uuid::from_u128(7), range));
+        }
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+    assert!(partial.is_empty());
+}
+
 #[test]
 fn decode_command_lane_request() {
     round_trip_request(LaneRequest::Command(Example { a: 6, b: -56 }));
 }
 
+#[test]
+fn decode_command_from_lane_request() {
+    round_trip_request(LaneRequest::CommandFrom(
+        Uuid::from_u128(4),
+        Example { a: 6, b: -56 },
+    ));
+}
+
+#[test]
+fn decode_correlated_command_lane_request() {
+    round_trip_request(LaneRequest::CorrelatedCommand(
+        12,
+        None,
+        Example { a: 6, b: -56 },
+    ));
+    round_trip_request(LaneRequest::CorrelatedCommand(
+        12,
+        Some(Uuid::from_u128(4)),
+        Example { a: 6, b: -56 },
+    ));
+}
+
 #[test]
 fn encode_sync_value_lane_response() {
     let mut encoder = ValueLaneResponseEncoder::default();
@@ -156,6 +268,80 @@ fn encode_event_value_lane_response() {
     assert_eq!(buffer.as_ref(), b"@Example{a:6,b:234}");
 }
 
+/// A codec that represents a `u32` as 4 big-endian bytes.
+struct U32Codec;
+
+#[derive(Debug, thiserror::Error)]
+#[error("Expected 4 bytes.")]
+struct WrongLength;
+
+impl PayloadCodec for U32Codec {
+    type Item = u32;
+    type Error = WrongLength;
+
+    fn encode(item: &Self::Item, dst: &mut BytesMut) {
+        dst.extend_from_slice(&item.to_be_bytes());
+    }
+
+    fn decode(payload: &[u8]) -> Result<Self::Item, Self::Error> {
+        let bytes = <[u8; 4]>::try_from(payload).map_err(|_| WrongLength)?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+}
+
+#[test]
+fn encode_encoded_event_value_lane_response() {
+    let mut encoder = ValueLaneResponseEncoder::default();
+    let mut buffer = BytesMut::new();
+    let body = Encoded::<RawBytes>::new(Bytes::from_static(&[0, 255, 7, 12]));
+    let request = LaneResponse::event(body);
+    assert!(encoder.encode(request, &mut buffer).is_ok());
+
+    assert_eq!(buffer.get_u8(), crate::lane::EVENT);
+    let len = buffer.get_u64() as usize;
+    assert_eq!(buffer.remaining(), len);
+    assert_eq!(buffer.as_ref(), &[0, 255, 7, 12]);
+}
+
+#[test]
+fn encode_encoded_sync_value_lane_response() {
+    let mut encoder = ValueLaneResponseEncoder::default();
+    let mut buffer = BytesMut::new();
+    let body = Encoded::<U32Codec>::new(0x01020304);
+    let request = LaneResponse::sync_event(Uuid::from_u128(563883), &body);
+    assert!(encoder.encode(request, &mut buffer).is_ok());
+
+    assert_eq!(buffer.get_u8(), crate::lane::SYNC);
+    assert_eq!(buffer.get_u128(), 563883);
+    let len = buffer.get_u64() as usize;
+    assert_eq!(len, 4);
+    assert_eq!(buffer.remaining(), len);
+    assert_eq!(buffer.as_ref(), &[1, 2, 3, 4]);
+}
+
+#[test]
+fn decode_recon_payload() {
+    let mut buffer = BytesMut::from("@Example{a:6,b:234}");
+    let result = Example::decode_payload(&mut buffer);
+    assert!(matches!(result, Ok(Example { a: 6, b: 234 })));
+
+    let mut buffer = BytesMut::from("@Example{a:6,");
+    let result = Example::decode_payload(&mut buffer);
+    assert!(matches!(result, Err(PayloadDecodeError::Recon(_))));
+}
+
+#[test]
+fn decode_encoded_payload() {
+    let mut buffer = BytesMut::from(&[1u8, 2, 3, 4][..]);
+    let result = Encoded::<U32Codec>::decode_payload(&mut buffer);
+    assert_eq!(result.ok(), Some(Encoded::new(0x01020304)));
+    assert!(buffer.is_empty());
+
+    let mut buffer = BytesMut::from(&[1u8, 2, 3][..]);
+    let result = Encoded::<U32Codec>::decode_payload(&mut buffer);
+    assert!(matches!(result, Err(PayloadDecodeError::Codec(_))));
+}
+
 fn to_bytes<T: StructuralWritable>(response: &LaneResponse<T>) -> LaneResponse<BytesMut> {
     match response {
         LaneResponse::StandardEvent(body) => {
@@ -170,6 +356,10 @@ fn to_bytes<T: StructuralWritable>(response: &LaneResponse<T>) -> LaneResponse<B
             LaneResponse::SyncEvent(*id, buffer)
         }
         LaneResponse::Synced(id) => LaneResponse::Synced(*id),
+        LaneResponse::SyncedAt(id, version) => LaneResponse::SyncedAt(*id, *version),
+        LaneResponse::EventBatch(n) => LaneResponse::EventBatch(*n),
+        LaneResponse::Correlated(id) => LaneResponse::Correlated(*id),
+        LaneResponse::Originated(origin) => LaneResponse::Originated(*origin),
     }
 }
 
@@ -300,6 +490,10 @@ fn round_trip_map_response(response: MapLaneResponse<i32, Example>) {
         LaneResponse::Initialized => LaneResponse::Initialized,
         LaneResponse::SyncEvent(id, body) => LaneResponse::SyncEvent(id, map_op_to_bytes(&body)),
         LaneResponse::Synced(id) => LaneResponse::Synced(id),
+        LaneResponse::SyncedAt(id, version) => LaneResponse::SyncedAt(id, version),
+        LaneResponse::EventBatch(n) => LaneResponse::EventBatch(n),
+        LaneResponse::Correlated(id) => LaneResponse::Correlated(id),
+        LaneResponse::Originated(origin) => LaneResponse::Originated(origin),
     };
 
     let mut encoder = MapLaneResponseEncoder::default();
@@ -326,6 +520,38 @@ fn decode_sync_complete_map_lane_response() {
     round_trip_map_response(MapLaneResponse::Synced(Uuid::from_u128(7482)));
 }
 
+#[test]
+fn decode_sync_complete_at_map_lane_response() {
+    round_trip_map_response(MapLaneResponse::SyncedAt(
+        Uuid::from_u128(7482),
+        SyncVersion::new(12, 99),
+    ));
+}
+
+#[test]
+fn encode_event_batch_map_lane_response() {
+    let mut encoder = MapLaneResponseEncoder::default();
+    let mut buffer = BytesMut::new();
+    let request: MapLaneResponse<i32, Example> = MapLaneResponse::EventBatch(3);
+    assert!(encoder.encode(request, &mut buffer).is_ok());
+
+    assert_eq!(buffer.remaining(), 9);
+    assert_eq!(buffer.get_u8(), crate::lane::EVENT_BATCH);
+    assert_eq!(buffer.get_u64(), 3);
+}
+
+#[test]
+fn decode_event_batch_map_lane_response() {
+    round_trip_map_response(MapLaneResponse::EventBatch(3));
+}
+
+#[test]
+fn decode_correlated_lane_responses() {
+    round_trip_value_response(LaneResponse::Correlated(Some(6)));
+    round_trip_value_response(LaneResponse::Correlated(None));
+    round_trip_map_response(MapLaneResponse::Correlated(Some(u64::MAX)));
+}
+
 #[test]
 fn decode_event_map_lane_response() {
     round_trip_map_response(MapLaneResponse::event(MapOperation::Update {
@@ -381,3 +607,12 @@ fn decoder_sequential_value_responses() {
     }
     assert!(buffer.is_empty());
 }
+
+#[test]
+fn decode_originated_lane_responses() {
+    round_trip_value_response(LaneResponse::Originated(Some(Uuid::from_u128(85))));
+    round_trip_value_response(LaneResponse::Originated(None));
+    round_trip_map_response(MapLaneResponse::Originated(Some(Uuid::from_u128(
+        u128::MAX,
+    ))));
+}
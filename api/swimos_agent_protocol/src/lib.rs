@@ -20,21 +20,25 @@
 //! channel between the runtime and the agent. Each message type has encoders and decoders in the [`encoding`]
 //! module. Encoders/decoders whose name starts with 'Raw' send and receive raw arrays of bytes as the content
 //! of the message. Otherwise, the body should be any type that can be serialized to Recon, using the
-//! [`swimos_form::Form`] trait.
+//! [`swimos_form::Form`] trait, or an [`Encoded`] value that uses a custom [`PayloadCodec`] (in which case
+//! the payload is treated as opaque bytes).
 
 mod ad_hoc;
 mod downlink;
+mod fragment;
 mod lane;
 mod map;
 mod store;
 
 mod model;
+mod payload;
 
 pub use model::{
     AdHocCommand, DownlinkNotification, DownlinkOperation, LaneRequest, LaneResponse,
     MapLaneResponse, MapMessage, MapOperation, MapStoreResponse, StoreInitMessage,
     StoreInitialized, StoreResponse,
 };
+pub use payload::{DecodePayload, Encoded, PayloadCodec, PayloadDecodeError, RawBytes};
 
 /// Tokio encoders and decoders for the agent protocols.
 pub mod encoding {
@@ -69,13 +73,41 @@ pub mod encoding {
     ///
     /// During the agent running phase:
     /// 1) [`crate::LaneRequest::Command`] messages are sent by the runtime to lane to inform the lane of commands
-    ///    received, addressed to that lane. The lane is not require to respond.
+    ///    received, addressed to that lane. The lane is not require to respond. If the lane was registered with
+    ///    `track_origin` set in its configuration, [`crate::LaneRequest::CommandFrom`] messages, carrying the ID of
+    ///    the remote that sent the command, are sent instead. If the envelope that carried the command was
+    ///    assigned a correlation ID, [`crate::LaneRequest::CorrelatedCommand`] messages, carrying the ID (and the
+    ///    origin, if it is tracked), are sent instead.
     /// 2) Each time the state of the lane changes (whether in response to a received command or otherwise) it must
     ///    notify the runtime of the change using [`crate::LaneResponse::StandardEvent`] message.
     /// 3) [`crate::LaneRequest::Sync`] messages are sent by the runtime to the lane to request its state. The lane
     ///    must respond with 0 or more [`crate::LaneResponse::SyncEvent`] messages, labelled with the same ID as provided
     ///    in the request. After all such messages are sent, it must send a [`crate::LaneResponse::Synced`] message with
     ///    the same ID.
+    /// 4) [`crate::LaneRequest::SyncSince`] messages are a variant of [`crate::LaneRequest::Sync`], carrying the version
+    ///    of the state of the lane that the remote last synced with. A map lane that recognizes the version may send only
+    ///    the entries that have changed since that version. Map lanes complete all syncs with a
+    ///    [`crate::LaneResponse::SyncedAt`] message, carrying the current version of the lane, instead of
+    ///    [`crate::LaneResponse::Synced`]. All other lanes treat this message exactly as a sync request.
+    /// 5) A map lane may send a [`crate::LaneResponse::EventBatch`] message, carrying a count `n`, to indicate that the
+    ///    next `n` [`crate::LaneResponse::StandardEvent`] messages that it sends form a single batch. The runtime
+    ///    delivers the batch to each uplink as a single event.
+    /// 6) [`crate::LaneRequest::SyncRange`] messages are a variant of [`crate::LaneRequest::Sync`], carrying a range
+    ///    of keys (as Recon). An ordered map lane will only send the entries with keys in the range, in key order.
+    ///    All other lanes treat this message exactly as a sync request.
+    /// 7) A lane may send a [`crate::LaneResponse::Correlated`] message to indicate that the
+    ///    [`crate::LaneResponse::StandardEvent`] messages that follow it were caused by the command with the given
+    ///    correlation ID (or by no correlated command). The runtime echoes the ID on the envelopes for those events.
+    /// 8) A lane that tracks the origins of commands may send a [`crate::LaneResponse::Originated`] message to
+    ///    indicate that the [`crate::LaneResponse::StandardEvent`] messages that follow it were caused by a command
+    ///    from the remote with the given ID (or by no command with a known origin). The runtime attaches the ID to
+    ///    the envelopes for those events so that it can be reported to downlinks.
+    ///
+    /// In either phase, any frame may be split into a sequence of fragments (for example, so that a very large
+    /// command does not exceed the size of the buffer of the channel). Each fragment consists of a tag byte, a
+    /// continuation marker (indicating whether further fragments follow), the length of the fragment (as a `u64`)
+    /// and then the bytes of the fragment. The decoders reassemble the fragments transparently and can be configured
+    /// to reject messages that exceed a maximum size.
     pub mod lane {
         pub use crate::lane::{
             MapLaneRequestDecoder, MapLaneRequestEncoder, MapLaneResponseDecoder,
@@ -162,6 +194,20 @@ const SYNC_COMPLETE: u8 = 2;
 const EVENT: u8 = 3;
 const INIT_DONE: u8 = 4;
 const INITIALIZED: u8 = 5;
+const COMMAND_FROM: u8 = 6;
+const SYNC_SINCE: u8 = 7;
+const SYNC_COMPLETE_AT: u8 = 8;
+const FRAGMENT: u8 = 9;
+const EVENT_BATCH: u8 = 10;
+const SYNC_RANGE: u8 = 11;
+const CORRELATED_COMMAND: u8 = 12;
+const CORRELATED_COMMAND_FROM: u8 = 13;
+const CORRELATION: u8 = 14;
+const ORIGIN: u8 = 15;
 
 const TAG_LEN: usize = 1;
 const ID_LEN: usize = std::mem::size_of::<u128>();
+const VERSION_LEN: usize = 2 * std::mem::size_of::<u64>();
+const RANGE_FLAGS_LEN: usize = 1;
+const CORRELATION_LEN: usize = std::mem::size_of::<u8>() + std::mem::size_of::<u64>();
+const ORIGIN_LEN: usize = std::mem::size_of::<u8>() + ID_LEN;
@@ -12,21 +12,55 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use swimos_api::address::Address;
+use bytes::Bytes;
+use swimos_api::{
+    address::Address,
+    agent::{KeyRange, LinkAdvice, LinkFailure, NodeStopped, SyncVersion},
+};
 use swimos_form::Form;
 use swimos_model::Text;
 use swimos_utilities::encoding::BytesStr;
 use uuid::Uuid;
 
 /// Message type for communication between the agent runtime and agent implementation.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LaneRequest<T> {
     /// A command to alter the state of the lane.
     Command(T),
+    /// A command to alter the state of the lane, tagged with the ID of the remote that sent it.
+    CommandFrom(Uuid, T),
+    /// A command to alter the state of the lane, tagged with the correlation ID of the envelope that
+    /// carried it (and the ID of the remote that sent it, if the lane tracks the origins of commands).
+    CorrelatedCommand(u64, Option<Uuid>, T),
     /// Indicates that the lane initialization phase is complete.
     InitComplete,
     /// Request a synchronization with the lane (responses will be tagged with the provided ID).
     Sync(Uuid),
+    /// Request a synchronization with the lane, starting from a version of its state that the remote
+    /// has previously synced with.
+    SyncSince(Uuid, SyncVersion),
+    /// Request a synchronization with the entries of the lane with keys in a range (the bounds are
+    /// the Recon representations of the keys).
+    SyncRange(Uuid, KeyRange<Bytes>),
+}
+
+impl<T> LaneRequest<T> {
+    /// The ID of the remote that sent a command, if it was tagged with one.
+    pub fn origin(&self) -> Option<Uuid> {
+        match self {
+            LaneRequest::CommandFrom(origin, _) => Some(*origin),
+            LaneRequest::CorrelatedCommand(_, origin, _) => *origin,
+            _ => None,
+        }
+    }
+
+    /// The correlation ID of the envelope that carried a command, if it was tagged with one.
+    pub fn correlation_id(&self) -> Option<u64> {
+        match self {
+            LaneRequest::CorrelatedCommand(id, _, _) => Some(*id),
+            _ => None,
+        }
+    }
 }
 
 /// Message type for communication from the agent implementation to the agent runtime.
@@ -40,6 +74,17 @@ pub enum LaneResponse<T> {
     SyncEvent(Uuid, T),
     /// Signal that an uplink has a consistent view of a lane.
     Synced(Uuid),
+    /// Signal that an uplink has a consistent view of a lane, as of the specified version.
+    SyncedAt(Uuid, SyncVersion),
+    /// Indicates that the next `n` standard events form a single batch which should be delivered
+    /// to uplinks as a single event (so that the changes are never observed partially applied).
+    EventBatch(u64),
+    /// Indicates that the standard events that follow were caused by the command with the specified
+    /// correlation ID (or, if it is absent, by no correlated command).
+    Correlated(Option<u64>),
+    /// Indicates that the standard events that follow were caused by a command from the remote with
+    /// the specified ID (or, if it is absent, by no command with a known origin).
+    Originated(Option<Uuid>),
 }
 
 impl<T> LaneResponse<T> {
@@ -208,8 +253,37 @@ where
 pub enum DownlinkNotification<T> {
     Linked,
     Synced,
-    Event { body: T },
+    Event {
+        body: T,
+    },
     Unlinked,
+    /// The remote lane is approaching the limits of its server and advises that the link should
+    /// back off.
+    Advisory {
+        advice: LinkAdvice,
+    },
+    /// The remote lane was unlinked because its agent stopped. This is always followed by
+    /// [`DownlinkNotification::Unlinked`].
+    NodeStopped {
+        notice: NodeStopped,
+    },
+    /// The link could not be established because the remote node or lane does not exist. This is
+    /// always followed by [`DownlinkNotification::Unlinked`].
+    Failed {
+        failure: LinkFailure,
+    },
+    /// The version of the state of the remote map lane that the downlink has been synced with. This
+    /// is always followed by [`DownlinkNotification::Synced`] and can be presented when the
+    /// downlink is relinked to request only the entries that have changed since.
+    Version {
+        version: SyncVersion,
+    },
+    /// The ID of the remote that sent the command that caused the next event (only reported for
+    /// lanes that track the origins of commands). This is always followed by
+    /// [`DownlinkNotification::Event`].
+    Origin {
+        origin: Uuid,
+    },
 }
 
 /// Message type for communication from a downlink subscriber to the runtime.
@@ -0,0 +1,209 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{convert::Infallible, fmt::Debug, hash::Hash, marker::PhantomData};
+
+use bytes::{Bytes, BytesMut};
+use swimos_api::{
+    error::{FrameIoError, InvalidFrame},
+    limits::{LimitExceeded, Limits},
+};
+use swimos_form::read::RecognizerReadable;
+use swimos_recon::parser::{AsyncParseError, RecognizerDecoder};
+use thiserror::Error;
+use tokio_util::codec::Decoder;
+
+/// A codec for the bodies of lane messages that are not represented as Recon. The runtime treats
+/// the encoded payload as opaque bytes so it is passed, unaltered, between the lane and any remote
+/// clients (which must use the same codec).
+pub trait PayloadCodec {
+    /// The type that is encoded by the codec.
+    type Item;
+    /// The type of errors that can occur when decoding a payload.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Encode an item, appending the payload to a buffer.
+    fn encode(item: &Self::Item, dst: &mut BytesMut);
+
+    /// Attempt to decode an item from a complete payload.
+    fn decode(payload: &[u8]) -> Result<Self::Item, Self::Error>;
+}
+
+/// A [`PayloadCodec`] that passes raw bytes through unaltered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RawBytes;
+
+impl PayloadCodec for RawBytes {
+    type Item = Bytes;
+    type Error = Infallible;
+
+    fn encode(item: &Self::Item, dst: &mut BytesMut) {
+        dst.extend_from_slice(item);
+    }
+
+    fn decode(payload: &[u8]) -> Result<Self::Item, Self::Error> {
+        Ok(Bytes::copy_from_slice(payload))
+    }
+}
+
+/// A value that will be sent and received by a lane using a custom [`PayloadCodec`], rather than
+/// Recon. For example, a value lane of type `ValueLane<Encoded<RawBytes>>` will send and receive
+/// binary payloads.
+pub struct Encoded<C: PayloadCodec> {
+    _codec: PhantomData<fn() -> C>,
+    item: C::Item,
+}
+
+impl<C: PayloadCodec> Encoded<C> {
+    pub fn new(item: C::Item) -> Self {
+        Encoded {
+            _codec: PhantomData,
+            item,
+        }
+    }
+
+    pub fn get(&self) -> &C::Item {
+        &self.item
+    }
+
+    pub fn into_inner(self) -> C::Item {
+        self.item
+    }
+
+    /// Encode the wrapped value, appending the payload to a buffer.
+    pub fn encode(&self, dst: &mut BytesMut) {
+        C::encode(&self.item, dst)
+    }
+}
+
+impl<C> Debug for Encoded<C>
+where
+    C: PayloadCodec,
+    C::Item: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Encoded").field(&self.item).finish()
+    }
+}
+
+impl<C> Clone for Encoded<C>
+where
+    C: PayloadCodec,
+    C::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        Encoded::new(self.item.clone())
+    }
+}
+
+impl<C> Default for Encoded<C>
+where
+    C: PayloadCodec,
+    C::Item: Default,
+{
+    fn default() -> Self {
+        Encoded::new(C::Item::default())
+    }
+}
+
+impl<C> PartialEq for Encoded<C>
+where
+    C: PayloadCodec,
+    C::Item: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.item == other.item
+    }
+}
+
+impl<C> Eq for Encoded<C>
+where
+    C: PayloadCodec,
+    C::Item: Eq,
+{
+}
+
+impl<C> Hash for Encoded<C>
+where
+    C: PayloadCodec,
+    C::Item: Hash,
+{
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.item.hash(state)
+    }
+}
+
+/// Errors that can occur decoding the payload of a lane message.
+#[derive(Debug, Error)]
+pub enum PayloadDecodeError {
+    /// A Recon payload was invalid.
+    #[error("Invalid Recon payload: {0}")]
+    Recon(#[from] AsyncParseError),
+    /// A Recon payload ended before a complete value was read.
+    #[error("The payload was incomplete.")]
+    Incomplete,
+    /// A payload was rejected by a custom [`PayloadCodec`].
+    #[error("Invalid payload: {0}")]
+    Codec(Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+impl From<PayloadDecodeError> for FrameIoError {
+    fn from(err: PayloadDecodeError) -> Self {
+        match err {
+            PayloadDecodeError::Recon(err) => err.into(),
+            PayloadDecodeError::Incomplete => FrameIoError::BadFrame(InvalidFrame::Incomplete),
+            PayloadDecodeError::Codec(err) => {
+                FrameIoError::BadFrame(InvalidFrame::InvalidPayload(err))
+            }
+        }
+    }
+}
+
+/// Types that can be decoded from the complete payload of a lane message. This is implemented for
+/// all types that can be read from Recon and for values using a custom [`PayloadCodec`].
+pub trait DecodePayload: Sized {
+    /// Attempt to decode a value, consuming the contents of the buffer.
+    fn decode_payload(buffer: &mut BytesMut) -> Result<Self, PayloadDecodeError>;
+
+    /// Check that a payload does not violate any of the [`Limits`] on the complexity of Recon
+    /// messages, before it is decoded.
+    fn check_limits(payload: &[u8], limits: &Limits) -> Result<(), LimitExceeded>;
+}
+
+impl<T: RecognizerReadable> DecodePayload for T {
+    fn decode_payload(buffer: &mut BytesMut) -> Result<Self, PayloadDecodeError> {
+        let mut decoder = RecognizerDecoder::new(T::make_recognizer());
+        decoder
+            .decode_eof(buffer)?
+            .ok_or(PayloadDecodeError::Incomplete)
+    }
+
+    fn check_limits(payload: &[u8], limits: &Limits) -> Result<(), LimitExceeded> {
+        limits.check_recon(payload)
+    }
+}
+
+impl<C: PayloadCodec> DecodePayload for Encoded<C> {
+    fn decode_payload(buffer: &mut BytesMut) -> Result<Self, PayloadDecodeError> {
+        let payload = buffer.split();
+        C::decode(payload.as_ref())
+            .map(Encoded::new)
+            .map_err(|e| PayloadDecodeError::Codec(Box::new(e)))
+    }
+
+    /// Payloads with a custom codec are opaque so are not checked.
+    fn check_limits(_payload: &[u8], _limits: &Limits) -> Result<(), LimitExceeded> {
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+};
+
+use super::{StoreKind, WarpLaneKind};
+
+/// The kind of an item of an agent that has persistent state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemStateKind {
+    Lane(WarpLaneKind),
+    Store(StoreKind),
+}
+
+impl ItemStateKind {
+    /// Whether the state of the item is persisted as a map (rather than a single value).
+    pub fn map_like(&self) -> bool {
+        match self {
+            ItemStateKind::Lane(kind) => kind.map_like(),
+            ItemStateKind::Store(kind) => matches!(kind, StoreKind::Map),
+        }
+    }
+}
+
+impl Display for ItemStateKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ItemStateKind::Lane(kind) => write!(f, "{} lane", kind),
+            ItemStateKind::Store(kind) => write!(f, "{} store", kind),
+        }
+    }
+}
+
+/// Describes the items of an agent that have persistent state. An agent declares this to the runtime,
+/// before it registers any of its items, so that the runtime can check it against the items that were
+/// recorded in the store when the agent last ran.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemManifest {
+    /// The kinds of the persistent items, keyed by name.
+    pub items: HashMap<String, ItemStateKind>,
+    /// Maps the previous names of items that have been renamed to their current names.
+    pub renames: HashMap<String, String>,
+}
+
+impl ItemManifest {
+    /// Add a persistent item to the manifest.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the item.
+    /// * `kind` - The kind of the item.
+    pub fn add_item(&mut self, name: impl Into<String>, kind: ItemStateKind) {
+        self.items.insert(name.into(), kind);
+    }
+
+    /// Record that an item was previously known by a different name. If state exists in the store
+    /// under the previous name, it should be moved to the new name.
+    ///
+    /// # Arguments
+    /// * `previous_name` - The name that the item previously had.
+    /// * `name` - The current name of the item.
+    pub fn add_rename(&mut self, previous_name: impl Into<String>, name: impl Into<String>) {
+        self.renames.insert(previous_name.into(), name.into());
+    }
+}
+
+/// A difference between the persistent items that an agent declares and the items that have state in
+/// its store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateInconsistency {
+    /// State exists in the store for an item that the agent no longer has.
+    Orphaned { name: String, kind: ItemStateKind },
+    /// The kind of an item has changed since its state was persisted.
+    KindChanged {
+        name: String,
+        previous: ItemStateKind,
+        current: ItemStateKind,
+    },
+    /// An item was renamed but state exists in the store under both its previous and its current names.
+    RenameConflict { previous_name: String, name: String },
+}
+
+impl Display for StateInconsistency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateInconsistency::Orphaned { name, kind } => {
+                write!(
+                    f,
+                    "the {} '{}' is no longer defined by the agent",
+                    kind, name
+                )
+            }
+            StateInconsistency::KindChanged {
+                name,
+                previous,
+                current,
+            } => write!(
+                f,
+                "the item '{}' was a {} but is now a {}",
+                name, previous, current
+            ),
+            StateInconsistency::RenameConflict {
+                previous_name,
+                name,
+            } => write!(
+                f,
+                "the item '{}' was renamed to '{}' but state exists under both names",
+                previous_name, name
+            ),
+        }
+    }
+}
+
+/// A report of all of the inconsistencies between the persistent items of an agent and the state in its
+/// store.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InconsistentState {
+    pub inconsistencies: Vec<StateInconsistency>,
+}
+
+impl InconsistentState {
+    pub fn is_empty(&self) -> bool {
+        self.inconsistencies.is_empty()
+    }
+}
+
+impl Display for InconsistentState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut it = self.inconsistencies.iter();
+        if let Some(first) = it.next() {
+            write!(f, "{}", first)?;
+            for inconsistency in it {
+                write!(f, "; {}", inconsistency)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -26,7 +26,11 @@ use std::{
 };
 
 use bytes::Bytes;
-use futures::{future::BoxFuture, ready, Future, FutureExt};
+use futures::{
+    future::{self, BoxFuture},
+    ready, Future, FutureExt,
+};
+use swimos_form::Form;
 use swimos_utilities::{
     byte_channel::{ByteReader, ByteWriter},
     future::RetryStrategy,
@@ -37,18 +41,28 @@ use thiserror::Error;
 use tokio::sync::{mpsc, oneshot};
 
 use crate::error::{
-    AgentInitError, AgentRuntimeError, AgentTaskError, DownlinkRuntimeError, OpenStoreError,
+    AgentInitError, AgentRuntimeError, AgentTaskError, DeclareItemsError, DownlinkRuntimeError,
+    OpenStoreError,
 };
 use crate::http::{HttpRequest, HttpResponse};
+use crate::limits::Limits;
 
 mod downlink;
 mod lane;
+mod manifest;
 mod store;
 
 pub use downlink::DownlinkKind;
 pub use lane::{LaneKind, LaneKindParseErr, LaneKindRecognizer, WarpLaneKind};
+pub use manifest::{InconsistentState, ItemManifest, ItemStateKind, StateInconsistency};
 pub use store::StoreKind;
 
+/// The node URI of the built-in agent that holds the feature flags of a plane.
+pub const FEATURE_FLAGS_NODE: &str = "swimos:meta:flags";
+/// The name of the map lane of the feature flags agent. The keys of the map are the names of the flags
+/// and the values are the currently selected variants of the flags.
+pub const FEATURE_FLAGS_LANE: &str = "flags";
+
 /// Indicates the sub-protocol that a lane uses to communicate its state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UplinkKind {
@@ -67,6 +81,161 @@ impl Display for UplinkKind {
     }
 }
 
+/// The reason that an agent is stopping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StopReason {
+    /// The agent was idle for longer than the configured inactivity timeout.
+    InactivityTimeout,
+    /// The agent was stopped from outside of the agent (for example, the server released it) or the
+    /// runtime stopped without giving a reason.
+    ExternalStop,
+    /// The plane that the agent belongs to is shutting down.
+    PlaneShutdown,
+    /// The agent or its runtime failed.
+    Failure,
+    /// An event handler of the agent instructed it to stop.
+    Instructed,
+}
+
+impl Display for StopReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StopReason::InactivityTimeout => f.write_str("Inactivity timeout"),
+            StopReason::ExternalStop => f.write_str("External stop"),
+            StopReason::PlaneShutdown => f.write_str("Plane shutdown"),
+            StopReason::Failure => f.write_str("Failure"),
+            StopReason::Instructed => f.write_str("Instructed"),
+        }
+    }
+}
+
+/// Identifies a version of the state of a map lane. A remote that has previously synced with a lane
+/// can present the version it was given when it syncs again and the lane will only send the entries
+/// that have changed since that version (rather than the entire state of the map).
+///
+/// The epoch changes each time a new instance of the lane is created (for example, when the agent
+/// restarts) so a version is only meaningful for the lane instance that issued it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Form)]
+#[form(tag = "version")]
+pub struct SyncVersion {
+    /// Identifies the instance of the lane that issued the version.
+    pub epoch: u64,
+    /// The number of modifications made to the lane instance.
+    pub version: u64,
+}
+
+impl SyncVersion {
+    pub fn new(epoch: u64, version: u64) -> Self {
+        SyncVersion { epoch, version }
+    }
+}
+
+/// Restricts a sync with an ordered map lane to the entries with keys in a range. The lower bound is
+/// inclusive and the upper bound is exclusive and either may be omitted. The bounds are the Recon
+/// representations of keys of the lane (the runtime does not know the type of the keys so they are
+/// only interpreted by the lane itself).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct KeyRange<T> {
+    /// The (inclusive) lower bound of the range.
+    pub from: Option<T>,
+    /// The (exclusive) upper bound of the range.
+    pub to: Option<T>,
+}
+
+impl<T> KeyRange<T> {
+    pub fn new(from: Option<T>, to: Option<T>) -> Self {
+        KeyRange { from, to }
+    }
+
+    /// True if neither bound is set (so the range includes every key).
+    pub fn is_unbounded(&self) -> bool {
+        self.from.is_none() && self.to.is_none()
+    }
+
+    pub fn as_ref(&self) -> KeyRange<&T> {
+        KeyRange {
+            from: self.from.as_ref(),
+            to: self.to.as_ref(),
+        }
+    }
+
+    pub fn map<U, F>(self, mut f: F) -> KeyRange<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        let KeyRange { from, to } = self;
+        KeyRange {
+            from: from.map(&mut f),
+            to: to.map(f),
+        }
+    }
+}
+
+/// Advice, sent by a server to a remote with a link to one of its lanes, that the server is
+/// approaching its limits and that the link should back off before the server is forced to close
+/// it. The advice is not binding and the remote is free to ignore it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Form)]
+pub enum LinkAdvice {
+    /// The remote is not keeping up with the events from the lane and should reduce the rate at
+    /// which it consumes them.
+    #[form(tag = "reduceRate")]
+    ReduceRate,
+    /// The server has too many links and the remote should unlink, if it can.
+    #[form(tag = "unlink")]
+    Unlink,
+}
+
+impl Display for LinkAdvice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkAdvice::ReduceRate => f.write_str("Reduce rate"),
+            LinkAdvice::Unlink => f.write_str("Unlink"),
+        }
+    }
+}
+
+/// Notification, sent by a server to the remotes with links to the lanes of an agent, that the
+/// links were closed because the agent stopped (rather than because of an error).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Form)]
+#[form(tag = "nodeStopped")]
+pub struct NodeStopped {
+    /// Hint that the agent stopped due to inactivity and will be started again if a remote links to
+    /// it. Otherwise, the agent stopped as part of a shutdown and relinking is unlikely to succeed.
+    #[form(header)]
+    pub resume: bool,
+}
+
+impl Display for NodeStopped {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.resume {
+            f.write_str("Node stopped (resumable)")
+        } else {
+            f.write_str("Node stopped")
+        }
+    }
+}
+
+/// Reason, sent by a server to a remote, that a link could not be established because its target
+/// does not exist. Relinking to the same target is unlikely to succeed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Form)]
+pub enum LinkFailure {
+    /// There is no agent at the requested node URI.
+    #[form(tag = "nodeNotFound")]
+    NodeNotFound,
+    /// The agent exists but does not have the requested lane.
+    #[form(tag = "laneNotFound")]
+    LaneNotFound,
+}
+
+impl Display for LinkFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkFailure::NodeNotFound => f.write_str("Node not found"),
+            LinkFailure::LaneNotFound => f.write_str("Lane not found"),
+        }
+    }
+}
+
 /// Configuration parameters for a lane.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct LaneConfig {
@@ -76,6 +245,16 @@ pub struct LaneConfig {
     pub output_buffer_size: NonZeroUsize,
     /// A transient lane does not have associated persistent storage.
     pub transient: bool,
+    /// Commands delivered to the lane are tagged with the ID of the remote that sent them.
+    pub track_origin: bool,
+    /// The maximum size, in bytes, of a single message sent to or from the lane. Messages that are
+    /// larger than the input buffer are split into fragments so a limit is necessary to prevent a
+    /// single, very large, message from consuming an unbounded amount of memory. If this is not
+    /// set, there is no limit.
+    pub max_message_size: Option<NonZeroUsize>,
+    /// Limits on the complexity of the commands sent to the lane and of the batches of events
+    /// sent by the lane.
+    pub limits: Limits,
 }
 
 /// Configuration parameters for a store.
@@ -93,6 +272,9 @@ impl LaneConfig {
         input_buffer_size: DEFAULT_BUFFER,
         output_buffer_size: DEFAULT_BUFFER,
         transient: false,
+        track_origin: false,
+        max_message_size: None,
+        limits: Limits::DEFAULT,
     };
 }
 
@@ -236,6 +418,45 @@ pub trait AgentContext: Sync {
         name: &str,
         kind: StoreKind,
     ) -> BoxFuture<'static, Result<(ByteWriter, ByteReader), OpenStoreError>>;
+
+    /// Declare the items of the agent that have persistent state. This should be called before any
+    /// items are registered so that the runtime can check that the state in the store is consistent
+    /// with them (and migrate the state of renamed items) before it is restored. Implementations
+    /// that do not support persistence need not check anything.
+    /// # Arguments
+    /// * `manifest` - The persistent items of the agent.
+    fn declare_items(
+        &self,
+        manifest: ItemManifest,
+    ) -> BoxFuture<'static, Result<(), DeclareItemsError>> {
+        let _ = manifest;
+        future::ready(Ok(())).boxed()
+    }
+
+    /// Register an alternative name for a lane (typically the name that the lane had before it was
+    /// renamed). Links and commands that are addressed to the alias will be routed to the lane and
+    /// the runtime may report their use as deprecated. Implementations that do not support aliases
+    /// may ignore them.
+    /// # Arguments
+    /// * `alias` - The alternative name for the lane.
+    /// * `name` - The name of the lane.
+    fn add_lane_alias(
+        &self,
+        alias: &str,
+        name: &str,
+    ) -> BoxFuture<'static, Result<(), AgentRuntimeError>> {
+        let _ = (alias, name);
+        future::ready(Ok(())).boxed()
+    }
+
+    /// A future that completes when the runtime begins to stop the agent, giving the reason that it
+    /// is stopping. If the agent is waiting on this future when the runtime begins to stop, the
+    /// runtime will wait (up to its shutdown timeout) for the agent task to complete before it
+    /// closes the lanes of the agent. Implementations that cannot report a reason may never
+    /// complete.
+    fn stop_signal(&self) -> BoxFuture<'static, StopReason> {
+        future::pending().boxed()
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
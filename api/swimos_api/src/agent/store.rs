@@ -14,8 +14,10 @@
 
 use std::fmt::{Display, Formatter};
 
+use swimos_form::Tag;
+
 /// Kinds of stores that can be persisted in the state of an agent.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Tag, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum StoreKind {
     /// A store containing a single value.
     Value,
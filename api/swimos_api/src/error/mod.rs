@@ -24,7 +24,11 @@ use swimos_utilities::{errors::Recoverable, routing::UnapplyError, trigger::prom
 use thiserror::Error;
 use tokio::sync::{mpsc, oneshot, watch};
 
-use crate::{address::RelativeAddress, agent::StoreKind};
+use crate::{
+    address::RelativeAddress,
+    agent::{InconsistentState, LinkFailure, StoreKind},
+    limits::LimitExceeded,
+};
 
 mod introspection;
 
@@ -56,6 +60,14 @@ pub enum InvalidFrame {
     InvalidHeader { problem: Text },
     #[error("Invalid frame body: {0}")]
     InvalidMessageBody(#[from] AsyncParseError),
+    #[error("Invalid frame payload: {0}")]
+    InvalidPayload(Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("A frame violated a limit: {0}")]
+    LimitExceeded(#[from] LimitExceeded),
+    #[error(
+        "A message of at least {size} bytes exceeds the maximum message size of {limit} bytes."
+    )]
+    TooLarge { size: usize, limit: usize },
 }
 
 /// Possible failure modes for a downlink consumer.
@@ -69,6 +81,8 @@ pub enum DownlinkTaskError {
     BadFrame(#[from] FrameIoError),
     #[error("Failed to deserialize frame body: {0}")]
     DeserializationFailed(#[from] ReadError),
+    #[error("The remote refused the link: {0}")]
+    LinkFailed(LinkFailure),
     #[error("{0:?}")]
     Custom(Box<dyn Error + Send + Sync + 'static>),
 }
@@ -111,6 +125,15 @@ pub enum DownlinkRuntimeError {
     DownlinkConnectionFailed(DownlinkFailureReason),
 }
 
+/// Error type for the declaration of the persistent items of an agent to the runtime.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DeclareItemsError {
+    #[error(transparent)]
+    RuntimeError(#[from] AgentRuntimeError),
+    #[error("The persisted state of the agent is inconsistent with its items: {0}")]
+    InconsistentState(InconsistentState),
+}
+
 /// Error type for requests so the runtime for creating/opening a state for an agent.
 #[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OpenStoreError {
@@ -163,6 +186,18 @@ impl From<oneshot::error::RecvError> for OpenStoreError {
     }
 }
 
+impl<T> From<mpsc::error::SendError<T>> for DeclareItemsError {
+    fn from(_: mpsc::error::SendError<T>) -> Self {
+        DeclareItemsError::RuntimeError(AgentRuntimeError::Terminated)
+    }
+}
+
+impl From<oneshot::error::RecvError> for DeclareItemsError {
+    fn from(_: oneshot::error::RecvError) -> Self {
+        DeclareItemsError::RuntimeError(AgentRuntimeError::Terminated)
+    }
+}
+
 impl<T> From<mpsc::error::SendError<T>> for DownlinkRuntimeError {
     fn from(_: mpsc::error::SendError<T>) -> Self {
         DownlinkRuntimeError::RuntimeError(AgentRuntimeError::Terminated)
@@ -249,6 +284,8 @@ pub enum AgentInitError {
     NodeIntrospection(#[from] NodeIntrospectionError),
     #[error("Failed to initialize introspection for a labe: {0}")]
     LaneIntrospection(#[from] LaneIntrospectionError),
+    #[error("The persisted state of the agent is inconsistent with its items: {0}")]
+    InconsistentState(InconsistentState),
 }
 
 //TODO Make this more sophisticated.
@@ -258,6 +295,17 @@ impl From<AgentRuntimeError> for AgentInitError {
     }
 }
 
+impl From<DeclareItemsError> for AgentInitError {
+    fn from(err: DeclareItemsError) -> Self {
+        match err {
+            DeclareItemsError::RuntimeError(err) => err.into(),
+            DeclareItemsError::InconsistentState(report) => {
+                AgentInitError::InconsistentState(report)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum StoreError {
     /// This implementation does not provide stores.
@@ -21,9 +21,12 @@
 //! - The [`persistence`] module contains the [`persistence::PlanePersistence`] trait that can be implemented
 //!   to add new storage implementations to allow a Swim server to maintain an external persistent state that can
 //!   outlive a single execution of the server process.
+//! - The [`limits`] module contains the [`limits::Limits`] that protect the runtime from peers that send
+//!   messages that are excessively large or complex.
 
 pub mod address;
 pub mod agent;
 pub mod error;
 pub mod http;
+pub mod limits;
 pub mod persistence;
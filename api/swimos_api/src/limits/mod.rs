@@ -0,0 +1,263 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    num::NonZeroUsize,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use swimos_utilities::non_zero_usize;
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+const DEFAULT_MAX_ENVELOPE_SIZE: NonZeroUsize = non_zero_usize!(67108864);
+const DEFAULT_MAX_MAP_BATCH_ENTRIES: NonZeroUsize = non_zero_usize!(1048576);
+const DEFAULT_MAX_RECON_DEPTH: NonZeroUsize = non_zero_usize!(256);
+const DEFAULT_MAX_ATTR_COUNT: NonZeroUsize = non_zero_usize!(65536);
+
+/// Limits that are enforced on the messages received from peers (over remote connections) and
+/// from lanes (over the channels between the agent and the runtime), to protect against peers
+/// that are malicious or faulty. Any limit that is not set is not enforced.
+///
+/// Every violation of a limit is counted in [`LimitMetrics::global`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum size, in bytes, of an envelope received from a remote connection. (Messages
+    /// sent between the runtime and lanes are bounded by
+    /// [`crate::agent::LaneConfig::max_message_size`] instead.)
+    pub max_envelope_size: Option<NonZeroUsize>,
+    /// The maximum number of entries in a single batch of map events sent by a map lane.
+    pub max_map_batch_entries: Option<NonZeroUsize>,
+    /// The maximum nesting depth of records and attribute bodies in a Recon message. For text
+    /// envelopes received from remote connections, this includes the envelope header.
+    pub max_recon_depth: Option<NonZeroUsize>,
+    /// The maximum number of attributes in a Recon message. For text envelopes received from
+    /// remote connections, this includes the envelope header.
+    pub max_attr_count: Option<NonZeroUsize>,
+}
+
+impl Limits {
+    //TODO: Remove this once const impls are stable.
+    pub const DEFAULT: Limits = Limits {
+        max_envelope_size: Some(DEFAULT_MAX_ENVELOPE_SIZE),
+        max_map_batch_entries: Some(DEFAULT_MAX_MAP_BATCH_ENTRIES),
+        max_recon_depth: Some(DEFAULT_MAX_RECON_DEPTH),
+        max_attr_count: Some(DEFAULT_MAX_ATTR_COUNT),
+    };
+
+    /// No limits are enforced.
+    pub const UNLIMITED: Limits = Limits {
+        max_envelope_size: None,
+        max_map_batch_entries: None,
+        max_recon_depth: None,
+        max_attr_count: None,
+    };
+
+    /// Check the size of an envelope received from a remote connection.
+    ///
+    /// # Arguments
+    /// * `size` - The size of the envelope in bytes.
+    pub fn check_envelope_size(&self, size: usize) -> Result<(), LimitExceeded> {
+        match self.max_envelope_size {
+            Some(limit) if size > limit.get() => Err(LimitExceeded::EnvelopeTooLarge {
+                size,
+                limit: limit.get(),
+            }
+            .recorded()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the number of entries announced for a batch of map events.
+    ///
+    /// # Arguments
+    /// * `count` - The number of entries in the batch.
+    pub fn check_map_batch_entries(&self, count: u64) -> Result<(), LimitExceeded> {
+        match self.max_map_batch_entries {
+            Some(limit) if count > limit.get() as u64 => Err(LimitExceeded::TooManyBatchEntries {
+                count,
+                limit: limit.get(),
+            }
+            .recorded()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the nesting depth and number of attributes of a Recon message body. This only scans
+    /// the structure of the body (without parsing it) so the check is cheap enough to apply before
+    /// the body is passed to a parser. It does not validate the body.
+    ///
+    /// # Arguments
+    /// * `body` - The body of the message.
+    pub fn check_recon(&self, body: &[u8]) -> Result<(), LimitExceeded> {
+        let Limits {
+            max_recon_depth,
+            max_attr_count,
+            ..
+        } = *self;
+        if max_recon_depth.is_none() && max_attr_count.is_none() {
+            return Ok(());
+        }
+        let max_depth = max_recon_depth.map(NonZeroUsize::get).unwrap_or(usize::MAX);
+        let max_attrs = max_attr_count.map(NonZeroUsize::get).unwrap_or(usize::MAX);
+        let mut depth = 0usize;
+        let mut attrs = 0usize;
+        let mut bytes = body.iter();
+        while let Some(b) = bytes.next() {
+            match *b {
+                b'"' => {
+                    while let Some(b) = bytes.next() {
+                        match *b {
+                            b'\\' => {
+                                bytes.next();
+                            }
+                            b'"' => break,
+                            _ => {}
+                        }
+                    }
+                }
+                b'@' => {
+                    attrs += 1;
+                    if attrs > max_attrs {
+                        return Err(LimitExceeded::TooManyAttrs { limit: max_attrs }.recorded());
+                    }
+                }
+                b'{' | b'(' | b'[' => {
+                    depth += 1;
+                    if depth > max_depth {
+                        return Err(LimitExceeded::TooDeep { limit: max_depth }.recorded());
+                    }
+                }
+                b'}' | b')' | b']' => {
+                    depth = depth.saturating_sub(1);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits::DEFAULT
+    }
+}
+
+/// The kinds of limit that can be set in [`Limits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitKind {
+    EnvelopeSize,
+    MapBatchEntries,
+    ReconDepth,
+    AttrCount,
+}
+
+/// Indicates that a message violated one of the configured [`Limits`].
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum LimitExceeded {
+    #[error("An envelope of {size} bytes exceeds the maximum envelope size of {limit} bytes.")]
+    EnvelopeTooLarge { size: usize, limit: usize },
+    #[error("A batch of {count} map entries exceeds the maximum batch size of {limit} entries.")]
+    TooManyBatchEntries { count: u64, limit: usize },
+    #[error("A message body exceeds the maximum nesting depth of {limit}.")]
+    TooDeep { limit: usize },
+    #[error("A message body has more than the maximum of {limit} attributes.")]
+    TooManyAttrs { limit: usize },
+}
+
+impl LimitExceeded {
+    /// The kind of limit that was violated.
+    pub fn kind(&self) -> LimitKind {
+        match self {
+            LimitExceeded::EnvelopeTooLarge { .. } => LimitKind::EnvelopeSize,
+            LimitExceeded::TooManyBatchEntries { .. } => LimitKind::MapBatchEntries,
+            LimitExceeded::TooDeep { .. } => LimitKind::ReconDepth,
+            LimitExceeded::TooManyAttrs { .. } => LimitKind::AttrCount,
+        }
+    }
+
+    fn recorded(self) -> Self {
+        LimitMetrics::global().record(&self);
+        self
+    }
+}
+
+/// Counts of the violations of each kind of limit.
+#[derive(Debug, Default)]
+pub struct LimitMetrics {
+    envelope_size: AtomicU64,
+    map_batch_entries: AtomicU64,
+    recon_depth: AtomicU64,
+    attr_count: AtomicU64,
+}
+
+static GLOBAL_METRICS: LimitMetrics = LimitMetrics::new();
+
+impl LimitMetrics {
+    pub const fn new() -> Self {
+        LimitMetrics {
+            envelope_size: AtomicU64::new(0),
+            map_batch_entries: AtomicU64::new(0),
+            recon_depth: AtomicU64::new(0),
+            attr_count: AtomicU64::new(0),
+        }
+    }
+
+    /// The metrics for all violations detected by the checks in [`Limits`], within this process.
+    pub fn global() -> &'static LimitMetrics {
+        &GLOBAL_METRICS
+    }
+
+    /// Record a violation.
+    pub fn record(&self, violation: &LimitExceeded) {
+        self.counter(violation.kind())
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The number of violations of a kind of limit that have been recorded.
+    pub fn violations(&self, kind: LimitKind) -> u64 {
+        self.counter(kind).load(Ordering::Relaxed)
+    }
+
+    /// The total number of violations of all kinds of limit that have been recorded.
+    pub fn total(&self) -> u64 {
+        [
+            LimitKind::EnvelopeSize,
+            LimitKind::MapBatchEntries,
+            LimitKind::ReconDepth,
+            LimitKind::AttrCount,
+        ]
+        .into_iter()
+        .map(|kind| self.violations(kind))
+        .sum()
+    }
+
+    fn counter(&self, kind: LimitKind) -> &AtomicU64 {
+        let LimitMetrics {
+            envelope_size,
+            map_batch_entries,
+            recon_depth,
+            attr_count,
+        } = self;
+        match kind {
+            LimitKind::EnvelopeSize => envelope_size,
+            LimitKind::MapBatchEntries => map_batch_entries,
+            LimitKind::ReconDepth => recon_depth,
+            LimitKind::AttrCount => attr_count,
+        }
+    }
+}
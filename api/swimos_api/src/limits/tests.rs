@@ -0,0 +1,95 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use swimos_utilities::non_zero_usize;
+
+use super::{LimitExceeded, LimitKind, LimitMetrics, Limits};
+
+const LIMITS: Limits = Limits {
+    max_envelope_size: Some(non_zero_usize!(16)),
+    max_map_batch_entries: Some(non_zero_usize!(4)),
+    max_recon_depth: Some(non_zero_usize!(2)),
+    max_attr_count: Some(non_zero_usize!(2)),
+};
+
+#[test]
+fn envelope_size() {
+    assert!(LIMITS.check_envelope_size(16).is_ok());
+    assert_eq!(
+        LIMITS.check_envelope_size(17),
+        Err(LimitExceeded::EnvelopeTooLarge {
+            size: 17,
+            limit: 16
+        })
+    );
+    assert!(Limits::UNLIMITED.check_envelope_size(usize::MAX).is_ok());
+}
+
+#[test]
+fn map_batch_entries() {
+    assert!(LIMITS.check_map_batch_entries(4).is_ok());
+    assert_eq!(
+        LIMITS.check_map_batch_entries(5),
+        Err(LimitExceeded::TooManyBatchEntries { count: 5, limit: 4 })
+    );
+}
+
+#[test]
+fn recon_depth() {
+    assert!(LIMITS.check_recon(b"{a:{b:1}}").is_ok());
+    assert!(LIMITS.check_recon(b"{a:{b:1},c:{d:2}}").is_ok());
+    assert_eq!(
+        LIMITS.check_recon(b"{a:{b:{c:1}}}"),
+        Err(LimitExceeded::TooDeep { limit: 2 })
+    );
+    assert_eq!(
+        LIMITS.check_recon(b"@a(@b([1]))"),
+        Err(LimitExceeded::TooDeep { limit: 2 })
+    );
+}
+
+#[test]
+fn recon_attr_count() {
+    assert!(LIMITS.check_recon(b"@a @b {}").is_ok());
+    assert_eq!(
+        LIMITS.check_recon(b"@a @b @c"),
+        Err(LimitExceeded::TooManyAttrs { limit: 2 })
+    );
+}
+
+#[test]
+fn recon_strings_ignored() {
+    assert!(LIMITS.check_recon(br#""@@@{{{" @a"#).is_ok());
+    assert!(LIMITS.check_recon(br#""\"@@@{{{" @a"#).is_ok());
+}
+
+#[test]
+fn violations_recorded() {
+    let before = LimitMetrics::global().violations(LimitKind::AttrCount);
+    assert!(LIMITS.check_recon(b"@a @b @c").is_err());
+    assert!(LimitMetrics::global().violations(LimitKind::AttrCount) > before);
+}
+
+#[test]
+fn metrics_by_kind() {
+    let metrics = LimitMetrics::new();
+    metrics.record(&LimitExceeded::TooDeep { limit: 2 });
+    metrics.record(&LimitExceeded::TooDeep { limit: 2 });
+    metrics.record(&LimitExceeded::EnvelopeTooLarge { size: 2, limit: 1 });
+
+    assert_eq!(metrics.violations(LimitKind::ReconDepth), 2);
+    assert_eq!(metrics.violations(LimitKind::EnvelopeSize), 1);
+    assert_eq!(metrics.violations(LimitKind::AttrCount), 0);
+    assert_eq!(metrics.total(), 3);
+}
@@ -85,6 +85,13 @@ pub trait PlanePersistence {
 
     ///Attempt to open or create a store for an agent at the specified URI.
     fn node_store(&self, node_uri: &str) -> BoxFuture<'static, Result<Self::Node, StoreError>>;
+
+    /// List the URIs of the agents that have state in the store. This is used to find agents that
+    /// can be recovered when a server starts. Stores that cannot enumerate their contents will
+    /// report no agents.
+    fn node_uris(&self) -> BoxFuture<'static, Result<Vec<String>, StoreError>> {
+        ready(Ok(vec![])).boxed()
+    }
 }
 
 /// A dummy store implementation for when no peristence is required.
@@ -12,16 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::error::Error;
 use std::num::NonZeroUsize;
 
 use futures::future::BoxFuture;
 use swimos_api::address::Address;
-use swimos_api::{agent::DownlinkKind, error::DownlinkTaskError};
+use swimos_api::{
+    agent::{DownlinkKind, SyncVersion},
+    error::DownlinkTaskError,
+};
 use swimos_model::Text;
 use swimos_utilities::{
     byte_channel::{ByteReader, ByteWriter},
+    future::RetryStrategy,
     non_zero_usize,
 };
+use thiserror::Error;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// General downlink configuration parameters.
@@ -33,6 +39,11 @@ pub struct DownlinkConfig {
     pub terminate_on_unlinked: bool,
     /// The size of the channel to send commands to the downlinks.
     pub buffer_size: NonZeroUsize,
+    /// The strategy to use when relinking the downlink after its connection is lost or it is
+    /// unlinked (and would otherwise stop). The downlink will request a new sync each time it is
+    /// relinked. Map downlinks present the version of the state that they hold so that only the
+    /// entries that have changed are sent. (default: never relink).
+    pub relink: RetryStrategy,
 }
 
 const DEFAULT_BUFFER_SIZE: NonZeroUsize = non_zero_usize!(1024);
@@ -43,10 +54,38 @@ impl Default for DownlinkConfig {
             events_when_not_synced: false,
             terminate_on_unlinked: true,
             buffer_size: DEFAULT_BUFFER_SIZE,
+            relink: RetryStrategy::none(),
         }
     }
 }
 
+/// Errors that can occur when a downlink attempts to relink to its remote lane.
+#[derive(Debug, Error)]
+pub enum RelinkError {
+    /// The attempt failed but a later attempt may succeed.
+    #[error("Relinking the downlink failed: {0}")]
+    Failed(Box<dyn Error + Send + Sync + 'static>),
+    /// The runtime has stopped so the downlink can never be relinked.
+    #[error("The downlink runtime has stopped.")]
+    Stopped,
+}
+
+/// Provided by the runtime to a running downlink so that it can be attached to its remote lane
+/// again after its connection is lost.
+pub trait Relink: Send {
+    /// Attempt to relink the downlink, returning new input and output channels to the runtime.
+    ///
+    /// # Arguments
+    /// * `since` - The version of the state of the remote lane held by the downlink (map downlinks
+    ///   only). This will be presented to the lane when the downlink is synced.
+    fn relink(
+        &mut self,
+        since: Option<SyncVersion>,
+    ) -> BoxFuture<'_, Result<(ByteReader, ByteWriter), RelinkError>>;
+}
+
+pub type BoxRelink = Box<dyn Relink + 'static>;
+
 /// Trait to define a consumer of a downlink. Instances of this will be passed to the runtime
 /// to be executed. User code should not generally need to implement this directly. It is
 /// necessary for this trait to be object safe and any changes to it should take that into
@@ -78,6 +117,28 @@ pub trait Downlink {
         input: ByteReader,
         output: ByteWriter,
     ) -> BoxFuture<'static, Result<(), DownlinkTaskError>>;
+
+    /// Create a task, as with [`Downlink::run_boxed`], that may use `relink` to attach the downlink
+    /// to the remote lane again, according to the `relink` strategy in its configuration. The
+    /// default implementation never relinks the downlink.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the lane to which the downlink should be attached.
+    /// * `config` - Configuration parameters for the downlink task.
+    /// * `input` - Byte channel on which updates will be received from the runtime.
+    /// * `output` - Byte channel on which command will be sent to the runtime.
+    /// * `relink` - Requests new channels from the runtime after the connection is lost.
+    fn run_relinking(
+        self: Box<Self>,
+        path: Address<Text>,
+        config: DownlinkConfig,
+        input: ByteReader,
+        output: ByteWriter,
+        relink: BoxRelink,
+    ) -> BoxFuture<'static, Result<(), DownlinkTaskError>> {
+        drop(relink);
+        self.run_boxed(path, config, input, output)
+    }
 }
 
 static_assertions::assert_obj_safe!(Downlink);
@@ -106,4 +167,15 @@ impl<T: Downlink> Downlink for Box<T> {
     ) -> BoxFuture<'static, Result<(), DownlinkTaskError>> {
         (**self).run(path, config, input, output)
     }
+
+    fn run_relinking(
+        self: Box<Self>,
+        path: Address<Text>,
+        config: DownlinkConfig,
+        input: ByteReader,
+        output: ByteWriter,
+        relink: BoxRelink,
+    ) -> BoxFuture<'static, Result<(), DownlinkTaskError>> {
+        (*self).run_relinking(path, config, input, output, relink)
+    }
 }
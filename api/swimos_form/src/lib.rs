@@ -34,6 +34,7 @@ use write::StructuralWritable;
 #[doc(hidden)]
 pub use swimos_model as model;
 
+pub mod schema;
 mod structural;
 pub use structural::{generic, read, write, Tag};
 
@@ -78,6 +79,63 @@ extern crate swimos_form_derive;
 /// ));
 /// ```
 ///
+/// - `#[form(rename_all = "camelCase")]` renames all of the fields of a `struct` (or all of the
+///   variants of an `enum`) according to a case convention. The serde names for the conventions
+///   (`camelCase`, `PascalCase`, `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case` and
+///   `SCREAMING-KEBAB-CASE`) are accepted. Fields that are renamed explicitly keep their names.
+///
+/// ```
+/// use swimos_model::{Attr, Item, Value};
+/// use swimos_form::Form;
+///
+/// #[derive(Form)]
+/// #[form(rename_all = "camelCase")]
+/// #
+/// struct Person {
+///     first_name: String,
+/// }
+///
+/// let person = Person {
+///     first_name: String::from("Dill"),
+/// };
+///
+/// assert_eq!(person.as_value(), Value::Record(
+///     vec![Attr::of("Person")],
+///     vec![Item::Slot(Value::text("firstName"), Value::text("Dill"))]
+/// ));
+/// ```
+///
+/// - `#[form(untagged)]` on an `enum` writes the variants without their tag attributes. Unit
+///   variants are written as `extant`, variants with a single, unnamed field are written as the
+///   value of that field and all other variants are written as records with no tag. When reading,
+///   the variants are attempted in the order in which they are defined and the first that matches
+///   is selected. Variants of untagged enums cannot have header fields.
+///
+/// ```
+/// use swimos_model::{Item, Value};
+/// use swimos_form::Form;
+///
+/// #[derive(Form, Debug, PartialEq)]
+/// #[form(untagged)]
+/// #
+/// enum Reading {
+///     Missing,
+///     Single(f64),
+///     Point { x: f64, y: f64 },
+/// }
+///
+/// let point = Reading::Point { x: 1.0, y: 2.0 };
+/// let value = Value::Record(
+///     Vec::new(),
+///     vec![Item::slot("x", 1.0), Item::slot("y", 2.0)],
+/// );
+///
+/// assert_eq!(point.as_value(), value);
+/// assert_eq!(Reading::try_from_value(&value), Ok(point));
+/// assert_eq!(Reading::try_from_value(&Value::from(3.0)), Ok(Reading::Single(3.0)));
+/// assert_eq!(Reading::try_from_value(&Value::Extant), Ok(Reading::Missing));
+/// ```
+///
 /// ## Variant attributes
 /// Enumeration variant names are used as tags, to use a custom tag the attribute
 /// `#[form(tag = "name")]` on an enumeration variant will transmute the enumeration to a value
@@ -133,6 +191,34 @@ extern crate swimos_form_derive;
 /// assert_eq!(food.as_value(), rec);
 /// ```
 ///
+/// ## Default
+/// If the field is absent when reading the form, use its default value rather than failing. Fields
+/// annotated with this must implement `Default`. This can be combined with the attributes that
+/// specify where the field is written (for example `#[form(attr, default)]`).
+///
+/// ```
+/// use swimos_model::{Attr, Item, Value};
+/// use swimos_form::Form;
+///
+/// #[derive(Form, PartialEq, Debug)]
+/// #
+/// struct Food {
+///     name: String,
+///     #[form(default)]
+///     rating: i32,
+/// }
+///
+/// let rec = Value::Record(
+///     vec![Attr::of("Food")],
+///     vec![Item::Slot(Value::text("name"), Value::text("soup"))],
+/// );
+///
+/// assert_eq!(Food::try_from_value(&rec), Ok(Food {
+///     name: String::from("soup"),
+///     rating: 0,
+/// }));
+/// ```
+///
 /// ## Rename
 /// Rename the field to the provided name. Structures and enumerations that contain unnamed fields
 /// and are renamed will be written as `Item::Slot` in the output record.
@@ -0,0 +1,596 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structural schemas for the values that are written by [`Form`] types.
+//!
+//! The schema of a type can be obtained with [`schema_of`]. For types that derive [`Form`], the
+//! schema describes the layout of the record that the type is written as (its tag, attributes,
+//! slots and items). A schema can be written as Recon (it implements [`StructuralWritable`]) to
+//! publish it and [`validate`] checks that a [`Value`] conforms to it (for example, to reject a
+//! command before it reaches the handler for a lane).
+//!
+//! [`check_round_trip`] is a property for tests of [`Form`] implementations and, with the
+//! `proptest` feature, the [`strategy`] module provides strategies to generate its inputs.
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+
+use num_bigint::Sign;
+use swimos_model::{Attr, BigInt, Item, Text, Value, ValueKind};
+
+use crate::read::ReadError;
+use crate::write::{StructuralWritable, StructuralWriter};
+use crate::Form;
+
+#[cfg(feature = "proptest")]
+pub mod strategy;
+#[cfg(test)]
+mod tests;
+
+/// A description of the structure of a [`Value`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schema {
+    /// Any value.
+    Anything,
+    /// No value.
+    Nothing,
+    /// A value that can be interpreted as the specified kind. Numeric values are accepted by any
+    /// numeric kind that can represent them.
+    OfKind(ValueKind),
+    /// A specific value.
+    Equal(Value),
+    /// Either [`Value::Extant`] or a value matching the schema. Slots and attributes with an
+    /// optional schema may be omitted.
+    Optional(Box<Schema>),
+    /// A value matching at least one of the schemas.
+    Or(Vec<Schema>),
+    /// A record, with no attributes, where every item is a value matching the schema.
+    ArrayOf(Box<Schema>),
+    /// A record, with no attributes, where every item is a slot with a key and value matching
+    /// the schemas.
+    MapOf(Box<Schema>, Box<Schema>),
+    /// A record with a specific layout.
+    Record(RecordSchema),
+}
+
+/// The layout of a record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordSchema {
+    /// The tag of the record (its first attribute), if it must have one.
+    pub tag: Option<TagSchema>,
+    /// Further attributes that the record may have, identified by name.
+    pub attrs: Vec<FieldSchema>,
+    /// The body of the record.
+    pub body: BodySchema,
+}
+
+/// The tag attribute of a record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagSchema {
+    /// The name of the tag (if this is absent, any name is accepted).
+    pub name: Option<Text>,
+    /// The body of the tag.
+    pub body: Box<Schema>,
+}
+
+/// A named attribute or slot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSchema {
+    pub name: Text,
+    pub schema: Schema,
+}
+
+/// The body of a record.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BodySchema {
+    /// A fixed number of values, in order, followed by slots (in any order). The record may have
+    /// no attributes other than those in its schema.
+    Fields {
+        values: Vec<Schema>,
+        slots: Vec<FieldSchema>,
+    },
+    /// The remaining attributes and the items of the record are the representation of another
+    /// value.
+    Delegate(Box<Schema>),
+}
+
+impl Schema {
+    /// A value of the specified kind.
+    pub fn of_kind(kind: ValueKind) -> Self {
+        Schema::OfKind(kind)
+    }
+
+    /// Either [`Value::Extant`] or a value matching the schema.
+    pub fn optional(schema: Schema) -> Self {
+        Schema::Optional(Box::new(schema))
+    }
+
+    /// Determine whether a slot or attribute with this schema may be omitted.
+    pub fn is_optional(&self) -> bool {
+        matches!(self, Schema::Optional(_))
+    }
+
+    /// The representation of the schema in the SwimOS model. This is the schema that is written
+    /// when the schema is published as Recon.
+    pub fn to_value(&self) -> Value {
+        match self {
+            Schema::Anything => Value::of_attr("anything"),
+            Schema::Nothing => Value::of_attr("nothing"),
+            Schema::OfKind(kind) => Value::of_attr(("kind", Value::text(kind_name(*kind)))),
+            Schema::Equal(value) => Value::of_attr(("equal", value.clone())),
+            Schema::Optional(schema) => Value::of_attr(("optional", schema.to_value())),
+            Schema::Or(schemas) => Value::Record(
+                vec![Attr::of("or")],
+                schemas.iter().map(|s| Item::of(s.to_value())).collect(),
+            ),
+            Schema::ArrayOf(schema) => Value::of_attr(("array", schema.to_value())),
+            Schema::MapOf(key, value) => Value::of_attr((
+                "map",
+                Value::record(vec![
+                    Item::slot("key", key.to_value()),
+                    Item::slot("value", value.to_value()),
+                ]),
+            )),
+            Schema::Record(record) => record.to_value(),
+        }
+    }
+}
+
+impl RecordSchema {
+    /// A record with no tag or attributes and a body consisting of the specified items.
+    pub fn fields(values: Vec<Schema>, slots: Vec<FieldSchema>) -> Self {
+        RecordSchema {
+            tag: None,
+            attrs: vec![],
+            body: BodySchema::Fields { values, slots },
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        let RecordSchema { tag, attrs, body } = self;
+        let mut items = vec![];
+        if let Some(TagSchema { name, body }) = tag {
+            let name = name
+                .as_ref()
+                .map(|name| Value::text(name.clone()))
+                .unwrap_or_else(|| Schema::Anything.to_value());
+            items.push(Item::slot("tag", name));
+            items.push(Item::slot("tagBody", body.to_value()));
+        }
+        if !attrs.is_empty() {
+            items.push(Item::slot("attrs", fields_to_value(attrs)));
+        }
+        match body {
+            BodySchema::Fields { values, slots } => {
+                if !values.is_empty() {
+                    let values = values.iter().map(|s| Item::of(s.to_value())).collect();
+                    items.push(Item::slot("values", Value::record(values)));
+                }
+                if !slots.is_empty() {
+                    items.push(Item::slot("slots", fields_to_value(slots)));
+                }
+            }
+            BodySchema::Delegate(schema) => {
+                items.push(Item::slot("body", schema.to_value()));
+            }
+        }
+        Value::Record(vec![Attr::of("record")], items)
+    }
+}
+
+impl FieldSchema {
+    pub fn new(name: impl Into<Text>, schema: Schema) -> Self {
+        FieldSchema {
+            name: name.into(),
+            schema,
+        }
+    }
+}
+
+fn fields_to_value(fields: &[FieldSchema]) -> Value {
+    Value::record(
+        fields
+            .iter()
+            .map(|FieldSchema { name, schema }| Item::slot(name.clone(), schema.to_value()))
+            .collect(),
+    )
+}
+
+fn kind_name(kind: ValueKind) -> &'static str {
+    match kind {
+        ValueKind::Extant => "extant",
+        ValueKind::Int32 => "int32",
+        ValueKind::Int64 => "int64",
+        ValueKind::UInt32 => "uint32",
+        ValueKind::UInt64 => "uint64",
+        ValueKind::Float64 => "float64",
+        ValueKind::Boolean => "boolean",
+        ValueKind::Text => "text",
+        ValueKind::Record => "record",
+        ValueKind::BigInt => "bigint",
+        ValueKind::BigUint => "biguint",
+        ValueKind::Data => "data",
+    }
+}
+
+impl StructuralWritable for Schema {
+    fn num_attributes(&self) -> usize {
+        self.to_value().num_attributes()
+    }
+
+    fn write_with<W: StructuralWriter>(&self, writer: W) -> Result<W::Repr, W::Error> {
+        self.to_value().write_into(writer)
+    }
+
+    fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
+        self.to_value().write_into(writer)
+    }
+}
+
+/// The schema of the values written by a [`Form`] type. Types that do not describe their
+/// structure have the schema [`Schema::Anything`].
+pub fn schema_of<T: Form>() -> Schema {
+    <T as StructuralWritable>::schema()
+}
+
+thread_local! {
+    static IN_PROGRESS: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Used by the derive macro to compute the schema of a type. If the type is recursive, the
+/// schema of the recursive occurrences is [`Schema::Anything`].
+#[doc(hidden)]
+pub fn derived_schema<T: ?Sized>(f: impl FnOnce() -> Schema) -> Schema {
+    let name = std::any::type_name::<T>();
+    let recursive = IN_PROGRESS.with(|in_progress| {
+        let mut guard = in_progress.borrow_mut();
+        if guard.contains(&name) {
+            true
+        } else {
+            guard.push(name);
+            false
+        }
+    });
+    if recursive {
+        Schema::Anything
+    } else {
+        let schema = f();
+        IN_PROGRESS.with(|in_progress| in_progress.borrow_mut().pop());
+        schema
+    }
+}
+
+/// A value that does not conform to a [`Schema`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The location of the invalid part of the value (for example, `$@tag.field[0]`).
+    pub path: String,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid value at {}: {}", self.path, self.message)
+    }
+}
+
+impl Error for ValidationError {}
+
+const ROOT_PATH: &str = "$";
+
+/// Check that a value conforms to a schema.
+pub fn validate(value: &Value, schema: &Schema) -> Result<(), ValidationError> {
+    validate_at(ROOT_PATH, value, schema)
+}
+
+fn invalid(path: &str, message: impl Into<String>) -> ValidationError {
+    ValidationError {
+        path: path.to_string(),
+        message: message.into(),
+    }
+}
+
+fn validate_at(path: &str, value: &Value, schema: &Schema) -> Result<(), ValidationError> {
+    match schema {
+        Schema::Anything => Ok(()),
+        Schema::Nothing => Err(invalid(path, "No value is permitted.")),
+        Schema::OfKind(kind) => {
+            if matches_kind(value, *kind) {
+                Ok(())
+            } else {
+                Err(invalid(
+                    path,
+                    format!("Expected a value of kind {}.", kind_name(*kind)),
+                ))
+            }
+        }
+        Schema::Equal(expected) => {
+            if value == expected {
+                Ok(())
+            } else {
+                Err(invalid(path, format!("Expected {}.", expected)))
+            }
+        }
+        Schema::Optional(schema) => {
+            if matches!(value, Value::Extant) {
+                Ok(())
+            } else {
+                validate_at(path, value, schema)
+            }
+        }
+        Schema::Or(schemas) => {
+            if schemas
+                .iter()
+                .any(|schema| validate_at(path, value, schema).is_ok())
+            {
+                Ok(())
+            } else {
+                Err(invalid(path, "The value matches none of the alternatives."))
+            }
+        }
+        Schema::ArrayOf(schema) => {
+            let items = record_items(path, value)?;
+            for (i, item) in items.iter().enumerate() {
+                let item_path = format!("{}[{}]", path, i);
+                match item {
+                    Item::ValueItem(value) => validate_at(&item_path, value, schema)?,
+                    Item::Slot(..) => return Err(invalid(&item_path, "Expected a value item.")),
+                }
+            }
+            Ok(())
+        }
+        Schema::MapOf(key_schema, value_schema) => {
+            let items = record_items(path, value)?;
+            for (i, item) in items.iter().enumerate() {
+                let item_path = format!("{}[{}]", path, i);
+                match item {
+                    Item::Slot(key, value) => {
+                        validate_at(&item_path, key, key_schema)?;
+                        validate_at(&item_path, value, value_schema)?;
+                    }
+                    Item::ValueItem(_) => return Err(invalid(&item_path, "Expected a slot.")),
+                }
+            }
+            Ok(())
+        }
+        Schema::Record(record) => validate_record(path, value, record),
+    }
+}
+
+fn record_items<'a>(path: &str, value: &'a Value) -> Result<&'a [Item], ValidationError> {
+    match value {
+        Value::Record(attrs, items) if attrs.is_empty() => Ok(items),
+        Value::Record(attrs, _) => Err(invalid(
+            path,
+            format!("Unexpected attribute: '{}'.", attrs[0].name),
+        )),
+        _ => Err(invalid(path, "Expected a record.")),
+    }
+}
+
+fn validate_record(
+    path: &str,
+    value: &Value,
+    schema: &RecordSchema,
+) -> Result<(), ValidationError> {
+    let RecordSchema { tag, attrs, body } = schema;
+    let (value_attrs, items) = match value {
+        Value::Record(value_attrs, items) => (value_attrs.as_slice(), items.as_slice()),
+        // The representation of a record with no attributes or items.
+        Value::Extant => (&[] as &[Attr], &[] as &[Item]),
+        _ => return Err(invalid(path, "Expected a record.")),
+    };
+
+    let mut remaining = value_attrs.iter().collect::<Vec<_>>();
+    if let Some(TagSchema { name, body }) = tag {
+        let Some(first) = value_attrs.first() else {
+            return Err(invalid(path, "The record has no tag."));
+        };
+        if let Some(name) = name {
+            if first.name != *name {
+                return Err(invalid(
+                    path,
+                    format!("Expected tag '{}' but found '{}'.", name, first.name),
+                ));
+            }
+        }
+        validate_at(&format!("{}@{}", path, first.name), &first.value, body)?;
+        remaining.remove(0);
+    }
+
+    for FieldSchema { name, schema } in attrs {
+        match remaining.iter().position(|attr| attr.name == *name) {
+            Some(i) => {
+                let attr = remaining.remove(i);
+                validate_at(&format!("{}@{}", path, name), &attr.value, schema)?;
+            }
+            None if schema.is_optional() => {}
+            None => return Err(invalid(path, format!("Missing attribute: '{}'.", name))),
+        }
+    }
+
+    match body {
+        BodySchema::Fields { values, slots } => {
+            if let Some(attr) = remaining.first() {
+                return Err(invalid(
+                    path,
+                    format!("Unexpected attribute: '{}'.", attr.name),
+                ));
+            }
+            validate_fields(path, items, values, slots)
+        }
+        BodySchema::Delegate(schema) => {
+            let rest = remaining.into_iter().cloned().collect::<Vec<_>>();
+            let mut alternatives = vec![];
+            if rest.is_empty() {
+                match items {
+                    [] => alternatives.push(Value::Extant),
+                    [Item::ValueItem(value)] => alternatives.push(value.clone()),
+                    _ => {}
+                }
+            }
+            let record = Value::Record(rest, items.to_vec());
+            let result = validate_at(path, &record, schema);
+            if result.is_err()
+                && alternatives
+                    .iter()
+                    .any(|value| validate_at(path, value, schema).is_ok())
+            {
+                Ok(())
+            } else {
+                result
+            }
+        }
+    }
+}
+
+fn validate_fields(
+    path: &str,
+    items: &[Item],
+    values: &[Schema],
+    slots: &[FieldSchema],
+) -> Result<(), ValidationError> {
+    if items.len() < values.len() {
+        return Err(invalid(
+            path,
+            format!("Expected at least {} items.", values.len()),
+        ));
+    }
+    let (value_items, slot_items) = items.split_at(values.len());
+    for (i, (item, schema)) in value_items.iter().zip(values).enumerate() {
+        let item_path = format!("{}[{}]", path, i);
+        match item {
+            Item::ValueItem(value) => validate_at(&item_path, value, schema)?,
+            Item::Slot(..) => return Err(invalid(&item_path, "Expected a value item.")),
+        }
+    }
+
+    let mut present = vec![false; slots.len()];
+    for (i, item) in slot_items.iter().enumerate() {
+        let item_path = format!("{}[{}]", path, values.len() + i);
+        let Item::Slot(Value::Text(key), value) = item else {
+            return Err(invalid(&item_path, "Expected a slot with a text key."));
+        };
+        let Some(index) = slots.iter().position(|slot| slot.name == *key) else {
+            return Err(invalid(&item_path, format!("Unexpected slot: '{}'.", key)));
+        };
+        if present[index] {
+            return Err(invalid(&item_path, format!("Duplicate slot: '{}'.", key)));
+        }
+        present[index] = true;
+        validate_at(&format!("{}.{}", path, key), value, &slots[index].schema)?;
+    }
+
+    if let Some(missing) = slots
+        .iter()
+        .zip(present)
+        .find(|(slot, present)| !present && !slot.schema.is_optional())
+        .map(|(slot, _)| &slot.name)
+    {
+        return Err(invalid(path, format!("Missing slot: '{}'.", missing)));
+    }
+    Ok(())
+}
+
+fn as_big_int(value: &Value) -> Option<BigInt> {
+    match value {
+        Value::Int32Value(n) => Some(BigInt::from(*n)),
+        Value::Int64Value(n) => Some(BigInt::from(*n)),
+        Value::UInt32Value(n) => Some(BigInt::from(*n)),
+        Value::UInt64Value(n) => Some(BigInt::from(*n)),
+        Value::BigInt(n) => Some(n.clone()),
+        Value::BigUint(n) => Some(BigInt::from(n.clone())),
+        _ => None,
+    }
+}
+
+/// Determine whether a value can be interpreted as a kind, using the same conversions as the
+/// readers for the primitive types.
+fn matches_kind(value: &Value, kind: ValueKind) -> bool {
+    let as_int = || as_big_int(value);
+    match kind {
+        ValueKind::Int32 => as_int().is_some_and(|n| i32::try_from(&n).is_ok()),
+        ValueKind::Int64 => as_int().is_some_and(|n| i64::try_from(&n).is_ok()),
+        ValueKind::UInt32 => as_int().is_some_and(|n| u32::try_from(&n).is_ok()),
+        ValueKind::UInt64 => as_int().is_some_and(|n| u64::try_from(&n).is_ok()),
+        ValueKind::BigInt => as_int().is_some(),
+        ValueKind::BigUint => as_int().is_some_and(|n| n.sign() != Sign::Minus),
+        ValueKind::Float64 => matches!(value, Value::Float64Value(_)) || as_int().is_some(),
+        _ => value.kind() == kind,
+    }
+}
+
+/// Errors that can occur in [`check_round_trip`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoundTripError {
+    /// The structure of the value does not conform to the schema of its type.
+    Invalid(ValidationError),
+    /// The value could not be read back from its structure.
+    Read(ReadError),
+    /// The value that was read back was not equal to the original.
+    Mismatch { original: String, restored: String },
+}
+
+impl Display for RoundTripError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoundTripError::Invalid(err) => {
+                write!(f, "The structure does not match the schema: {}", err)
+            }
+            RoundTripError::Read(err) => write!(f, "Reading the structure failed: {}", err),
+            RoundTripError::Mismatch { original, restored } => write!(
+                f,
+                "The value read back ({}) differs from the original ({}).",
+                restored, original
+            ),
+        }
+    }
+}
+
+impl Error for RoundTripError {}
+
+impl From<ValidationError> for RoundTripError {
+    fn from(err: ValidationError) -> Self {
+        RoundTripError::Invalid(err)
+    }
+}
+
+impl From<ReadError> for RoundTripError {
+    fn from(err: ReadError) -> Self {
+        RoundTripError::Read(err)
+    }
+}
+
+/// Check that a value can be written to the SwimOS model, that the result conforms to the schema
+/// of its type and that it can be read back to give the original value. This is intended to be
+/// used as the property for property-based tests of [`Form`] implementations (see the
+/// `strategy` module, with the `proptest` feature, for strategies to generate the values).
+pub fn check_round_trip<T>(value: &T) -> Result<(), RoundTripError>
+where
+    T: Form + PartialEq + Debug,
+{
+    let structure = value.as_value();
+    validate(&structure, &schema_of::<T>())?;
+    let restored = T::try_from_value(&structure)?;
+    if restored == *value {
+        Ok(())
+    } else {
+        Err(RoundTripError::Mismatch {
+            original: format!("{:?}", value),
+            restored: format!("{:?}", restored),
+        })
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! [`proptest`] strategies for property-based tests of [`Form`] implementations (requires the
+//! `proptest` feature).
+//!
+//! [`conforming_values`] generates values that conform to a [`Schema`] and [`arbitrary_form`]
+//! generates instances of a [`Form`] type by reading them from the values that conform to its
+//! schema. Together with [`check_round_trip`](super::check_round_trip), this gives a round-trip
+//! test for any type that derives [`Form`]:
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use proptest::test_runner::TestRunner;
+//! use swimos_form::Form;
+//! use swimos_form::schema::check_round_trip;
+//! use swimos_form::schema::strategy::arbitrary_form;
+//!
+//! #[derive(Debug, PartialEq, Form)]
+//! struct Example {
+//!     id: i32,
+//!     name: Option<String>,
+//! }
+//!
+//! let mut runner = TestRunner::default();
+//! runner
+//!     .run(&arbitrary_form::<Example>(), |value| {
+//!         prop_assert_eq!(check_round_trip(&value), Ok(()));
+//!         Ok(())
+//!     })
+//!     .expect("Round trip failed.");
+//! ```
+
+use std::fmt::Debug;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::Union;
+use swimos_model::{Attr, BigInt, BigUint, Blob, Item, Text, Value, ValueKind};
+
+use super::{schema_of, BodySchema, FieldSchema, RecordSchema, Schema, TagSchema};
+use crate::Form;
+
+/// The maximum number of items in generated arrays and maps.
+const MAX_ITEMS: usize = 4;
+
+const TEXT_PATTERN: &str = "\\PC{0,16}";
+const NAME_PATTERN: &str = "[a-z][a-zA-Z0-9]{0,7}";
+
+/// A strategy that generates instances of a [`Form`] type. Values that conform to the schema of
+/// the type are generated and those that can be read as the type are kept.
+pub fn arbitrary_form<T>() -> BoxedStrategy<T>
+where
+    T: Form + Debug + 'static,
+{
+    conforming_values(&schema_of::<T>())
+        .prop_filter_map("The value cannot be read as the type.", |value| {
+            T::try_from_value(&value).ok()
+        })
+        .boxed()
+}
+
+/// A strategy that generates values that conform to a schema (as determined by
+/// [`validate`](super::validate)). No values conform to [`Schema::Nothing`] so the strategy for it
+/// rejects every case.
+pub fn conforming_values(schema: &Schema) -> BoxedStrategy<Value> {
+    match schema {
+        Schema::Anything => primitive_values(),
+        Schema::Nothing => no_values(),
+        Schema::OfKind(kind) => values_of_kind(*kind),
+        Schema::Equal(value) => Just(value.clone()).boxed(),
+        Schema::Optional(schema) => {
+            prop_oneof![Just(Value::Extant), conforming_values(schema)].boxed()
+        }
+        Schema::Or(schemas) => {
+            let alternatives = schemas
+                .iter()
+                .filter(|schema| !matches!(schema, Schema::Nothing))
+                .map(conforming_values)
+                .collect::<Vec<_>>();
+            if alternatives.is_empty() {
+                no_values()
+            } else {
+                Union::new(alternatives).boxed()
+            }
+        }
+        Schema::ArrayOf(schema) => vec(conforming_values(schema), 0..=MAX_ITEMS)
+            .prop_map(Value::from_vec)
+            .boxed(),
+        Schema::MapOf(key, value) => vec(
+            (conforming_values(key), conforming_values(value)),
+            0..=MAX_ITEMS,
+        )
+        .prop_map(|entries| {
+            Value::record(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| Item::Slot(key, value))
+                    .collect(),
+            )
+        })
+        .boxed(),
+        Schema::Record(record) => record_values(record),
+    }
+}
+
+fn no_values() -> BoxedStrategy<Value> {
+    Just(Value::Extant)
+        .prop_filter("No value conforms to the schema.", |_| false)
+        .boxed()
+}
+
+fn primitive_values() -> BoxedStrategy<Value> {
+    prop_oneof![
+        Just(Value::Extant),
+        any::<i32>().prop_map(Value::Int32Value),
+        any::<i64>().prop_map(Value::Int64Value),
+        any::<bool>().prop_map(Value::BooleanValue),
+        TEXT_PATTERN.prop_map(Value::text),
+    ]
+    .boxed()
+}
+
+fn values_of_kind(kind: ValueKind) -> BoxedStrategy<Value> {
+    match kind {
+        ValueKind::Extant => Just(Value::Extant).boxed(),
+        ValueKind::Int32 => any::<i32>().prop_map(Value::Int32Value).boxed(),
+        ValueKind::Int64 => any::<i64>().prop_map(Value::Int64Value).boxed(),
+        ValueKind::UInt32 => any::<u32>().prop_map(Value::UInt32Value).boxed(),
+        ValueKind::UInt64 => any::<u64>().prop_map(Value::UInt64Value).boxed(),
+        // NaN is excluded as it is not equal to itself.
+        ValueKind::Float64 => (prop::num::f64::NORMAL | prop::num::f64::ZERO)
+            .prop_map(Value::Float64Value)
+            .boxed(),
+        ValueKind::Boolean => any::<bool>().prop_map(Value::BooleanValue).boxed(),
+        ValueKind::Text => TEXT_PATTERN.prop_map(Value::text).boxed(),
+        ValueKind::Record => vec(primitive_values(), 0..=MAX_ITEMS)
+            .prop_map(Value::from_vec)
+            .boxed(),
+        ValueKind::BigInt => any::<i128>()
+            .prop_map(|n| Value::BigInt(BigInt::from(n)))
+            .boxed(),
+        ValueKind::BigUint => any::<u128>()
+            .prop_map(|n| Value::BigUint(BigUint::from(n)))
+            .boxed(),
+        ValueKind::Data => vec(any::<u8>(), 0..=16)
+            .prop_map(|data| Value::Data(Blob::from_vec(data)))
+            .boxed(),
+    }
+}
+
+/// Generate the fields of a record, omitting those that are optional at random.
+fn fields(fields: &[FieldSchema]) -> BoxedStrategy<Vec<(Text, Value)>> {
+    fields
+        .iter()
+        .map(|FieldSchema { name, schema }| {
+            let name = name.clone();
+            let value = conforming_values(schema);
+            if schema.is_optional() {
+                proptest::option::of(value)
+                    .prop_map(move |value| value.map(|value| (name.clone(), value)))
+                    .boxed()
+            } else {
+                value
+                    .prop_map(move |value| Some((name.clone(), value)))
+                    .boxed()
+            }
+        })
+        .collect::<Vec<_>>()
+        .prop_map(|fields| fields.into_iter().flatten().collect())
+        .boxed()
+}
+
+fn record_values(schema: &RecordSchema) -> BoxedStrategy<Value> {
+    let RecordSchema { tag, attrs, body } = schema;
+    let tag = match tag {
+        Some(TagSchema { name, body }) => {
+            let name = match name {
+                Some(name) => Just(name.clone()).boxed(),
+                None => NAME_PATTERN.prop_map(Text::from).boxed(),
+            };
+            (name, conforming_values(body))
+                .prop_map(|(name, value)| Some(Attr { name, value }))
+                .boxed()
+        }
+        None => Just(None).boxed(),
+    };
+    let head = (tag, fields(attrs)).prop_map(|(tag, attrs)| {
+        tag.into_iter()
+            .chain(attrs.into_iter().map(|(name, value)| Attr { name, value }))
+            .collect::<Vec<_>>()
+    });
+    match body {
+        BodySchema::Fields { values, slots } => {
+            let values = values.iter().map(conforming_values).collect::<Vec<_>>();
+            // The slots of a record may occur in any order.
+            let slots = fields(slots).prop_shuffle();
+            (head, values, slots)
+                .prop_map(|(attrs, values, slots)| {
+                    let items = values
+                        .into_iter()
+                        .map(Item::ValueItem)
+                        .chain(
+                            slots
+                                .into_iter()
+                                .map(|(name, value)| Item::Slot(Value::Text(name), value)),
+                        )
+                        .collect();
+                    Value::Record(attrs, items)
+                })
+                .boxed()
+        }
+        BodySchema::Delegate(schema) => (head, conforming_values(schema))
+            .prop_map(|(mut attrs, value)| match value {
+                Value::Record(more_attrs, items) => {
+                    attrs.extend(more_attrs);
+                    Value::Record(attrs, items)
+                }
+                Value::Extant => Value::Record(attrs, vec![]),
+                value => Value::Record(attrs, vec![Item::ValueItem(value)]),
+            })
+            .boxed(),
+    }
+}
@@ -0,0 +1,237 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use swimos_model::{Attr, BigInt, Item, Value, ValueKind};
+use swimos_utilities::future::Quantity;
+
+use crate::write::StructuralWritable;
+
+use super::{
+    check_round_trip, schema_of, validate, BodySchema, FieldSchema, RecordSchema, Schema, TagSchema,
+};
+
+fn tagged(name: &str, slots: Vec<FieldSchema>) -> Schema {
+    Schema::Record(RecordSchema {
+        tag: Some(TagSchema {
+            name: Some(name.into()),
+            body: Box::new(Schema::OfKind(ValueKind::Extant)),
+        }),
+        attrs: vec![],
+        body: BodySchema::Fields {
+            values: vec![],
+            slots,
+        },
+    })
+}
+
+#[test]
+fn primitive_schemas() {
+    assert_eq!(schema_of::<i32>(), Schema::OfKind(ValueKind::Int32));
+    assert_eq!(schema_of::<usize>(), Schema::OfKind(ValueKind::UInt64));
+    assert_eq!(schema_of::<String>(), Schema::OfKind(ValueKind::Text));
+    assert_eq!(schema_of::<Vec<u8>>(), Schema::OfKind(ValueKind::Data));
+    assert_eq!(schema_of::<Value>(), Schema::Anything);
+    assert_eq!(
+        schema_of::<Option<bool>>(),
+        Schema::optional(Schema::OfKind(ValueKind::Boolean))
+    );
+    assert_eq!(
+        schema_of::<Vec<i64>>(),
+        Schema::ArrayOf(Box::new(Schema::OfKind(ValueKind::Int64)))
+    );
+    assert_eq!(
+        schema_of::<HashMap<String, f64>>(),
+        Schema::MapOf(
+            Box::new(Schema::OfKind(ValueKind::Text)),
+            Box::new(Schema::OfKind(ValueKind::Float64))
+        )
+    );
+}
+
+#[test]
+fn numeric_kinds() {
+    let schema = Schema::OfKind(ValueKind::Int32);
+    assert!(validate(&Value::Int32Value(-4), &schema).is_ok());
+    assert!(validate(&Value::UInt64Value(4), &schema).is_ok());
+    assert!(validate(&Value::Int64Value(i64::MAX), &schema).is_err());
+    assert!(validate(&Value::Float64Value(1.0), &schema).is_err());
+
+    let schema = Schema::OfKind(ValueKind::UInt32);
+    assert!(validate(&Value::Int32Value(-1), &schema).is_err());
+    assert!(validate(&Value::BigInt(BigInt::from(7)), &schema).is_ok());
+
+    let schema = Schema::OfKind(ValueKind::Float64);
+    assert!(validate(&Value::Int32Value(3), &schema).is_ok());
+    assert!(validate(&Value::text("3"), &schema).is_err());
+}
+
+#[test]
+fn validate_arrays_and_maps() {
+    let schema = schema_of::<Vec<i32>>();
+    assert!(validate(&Value::from_vec(vec![1, 2, 3]), &schema).is_ok());
+
+    let err = validate(&Value::record(vec![Item::of(1), Item::of("two")]), &schema).unwrap_err();
+    assert_eq!(err.path, "$[1]");
+
+    let schema = schema_of::<HashMap<String, i32>>();
+    assert!(validate(&Value::record(vec![Item::slot("a", 1)]), &schema).is_ok());
+    assert!(validate(&Value::record(vec![Item::of(1)]), &schema).is_err());
+}
+
+#[test]
+fn validate_records() {
+    let schema = tagged(
+        "point",
+        vec![
+            FieldSchema::new("x", Schema::OfKind(ValueKind::Int32)),
+            FieldSchema::new("y", Schema::optional(Schema::OfKind(ValueKind::Int32))),
+        ],
+    );
+
+    let good = Value::Record(
+        vec![Attr::of("point")],
+        vec![Item::slot("y", 2), Item::slot("x", 1)],
+    );
+    assert!(validate(&good, &schema).is_ok());
+
+    let optional_omitted = Value::Record(vec![Attr::of("point")], vec![Item::slot("x", 1)]);
+    assert!(validate(&optional_omitted, &schema).is_ok());
+
+    let wrong_tag = Value::Record(vec![Attr::of("other")], vec![Item::slot("x", 1)]);
+    assert!(validate(&wrong_tag, &schema).is_err());
+
+    let missing = Value::Record(vec![Attr::of("point")], vec![Item::slot("y", 1)]);
+    let err = validate(&missing, &schema).unwrap_err();
+    assert_eq!(err.path, "$");
+    assert_eq!(err.message, "Missing slot: 'x'.");
+
+    let wrong_kind = Value::Record(vec![Attr::of("point")], vec![Item::slot("x", "a")]);
+    let err = validate(&wrong_kind, &schema).unwrap_err();
+    assert_eq!(err.path, "$.x");
+
+    let extra = Value::Record(
+        vec![Attr::of("point")],
+        vec![Item::slot("x", 1), Item::slot("z", 1)],
+    );
+    assert!(validate(&extra, &schema).is_err());
+
+    let extra_attr = Value::Record(
+        vec![Attr::of("point"), Attr::of("extra")],
+        vec![Item::slot("x", 1)],
+    );
+    assert!(validate(&extra_attr, &schema).is_err());
+}
+
+#[test]
+fn validate_alternatives() {
+    let schema = schema_of::<Quantity<u32>>();
+    assert!(validate(&Value::UInt32Value(2), &schema).is_ok());
+    assert!(validate(&Value::text("infinite"), &schema).is_ok());
+    assert!(validate(&Value::text("finite"), &schema).is_err());
+
+    assert!(validate(&Value::Extant, &Schema::Nothing).is_err());
+    assert!(validate(&Value::Extant, &Schema::Anything).is_ok());
+}
+
+#[test]
+fn schema_to_recon() {
+    let schema = Schema::optional(Schema::ArrayOf(Box::new(Schema::OfKind(ValueKind::Int32))));
+    let recon = format!("{}", schema.structure());
+    assert_eq!(recon, "@optional(@array(@kind(int32)))");
+
+    let schema = tagged(
+        "point",
+        vec![FieldSchema::new("x", Schema::OfKind(ValueKind::Int32))],
+    );
+    let recon = format!("{}", schema.structure());
+    assert_eq!(
+        recon,
+        "@record{tag:point,tagBody:@kind(extant),slots:{x:@kind(int32)}}"
+    );
+}
+
+#[test]
+fn primitive_round_trips() {
+    assert!(check_round_trip(&5i32).is_ok());
+    assert!(check_round_trip(&"text".to_string()).is_ok());
+    assert!(check_round_trip(&Some(vec![1u64, 2, 3])).is_ok());
+    assert!(check_round_trip(&Duration::from_millis(1500)).is_ok());
+    assert!(check_round_trip(&(1, "a".to_string(), true)).is_ok());
+    assert!(check_round_trip(&Quantity::<u32>::Infinite).is_ok());
+}
+
+#[cfg(feature = "proptest")]
+mod strategies {
+    use proptest::prelude::*;
+    use swimos_model::{Value, ValueKind};
+
+    use super::super::strategy::conforming_values;
+    use super::super::{validate, BodySchema, FieldSchema, RecordSchema, Schema, TagSchema};
+
+    fn composite_schema() -> Schema {
+        Schema::Record(RecordSchema {
+            tag: Some(TagSchema {
+                name: None,
+                body: Box::new(Schema::optional(Schema::OfKind(ValueKind::UInt32))),
+            }),
+            attrs: vec![FieldSchema::new(
+                "meta",
+                Schema::MapOf(
+                    Box::new(Schema::OfKind(ValueKind::Text)),
+                    Box::new(Schema::OfKind(ValueKind::BigInt)),
+                ),
+            )],
+            body: BodySchema::Fields {
+                values: vec![Schema::Or(vec![
+                    Schema::Nothing,
+                    Schema::OfKind(ValueKind::Float64),
+                    Schema::Equal(Value::text("name")),
+                ])],
+                slots: vec![
+                    FieldSchema::new("data", Schema::OfKind(ValueKind::Data)),
+                    FieldSchema::new(
+                        "items",
+                        Schema::optional(Schema::ArrayOf(Box::new(Schema::Anything))),
+                    ),
+                ],
+            },
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn generated_values_conform(value in conforming_values(&composite_schema())) {
+            prop_assert_eq!(validate(&value, &composite_schema()), Ok(()));
+        }
+
+        #[test]
+        fn generated_delegated_values_conform(
+            value in conforming_values(&Schema::Record(RecordSchema {
+                tag: None,
+                attrs: vec![FieldSchema::new("flag", Schema::OfKind(ValueKind::Boolean))],
+                body: BodySchema::Delegate(Box::new(Schema::OfKind(ValueKind::Int64))),
+            }))
+        ) {
+            let schema = Schema::Record(RecordSchema {
+                tag: None,
+                attrs: vec![FieldSchema::new("flag", Schema::OfKind(ValueKind::Boolean))],
+                body: BodySchema::Delegate(Box::new(Schema::OfKind(ValueKind::Int64))),
+            });
+            prop_assert_eq!(validate(&value, &schema), Ok(()));
+        }
+    }
+}
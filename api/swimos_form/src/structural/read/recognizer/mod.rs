@@ -23,6 +23,7 @@ use crate::structural::read::recognizer::primitive::DataRecognizer;
 use crate::structural::read::ReadError;
 use crate::structural::write::StructuralWritable;
 use crate::structural::Tag;
+use bytes::Bytes;
 use std::borrow::Borrow;
 use std::marker::PhantomData;
 use std::num::NonZeroUsize;
@@ -205,6 +206,30 @@ impl RecognizerReadable for Box<[u8]> {
     }
 }
 
+type BytesFromVec = fn(Vec<u8>) -> Bytes;
+
+impl RecognizerReadable for Bytes {
+    type Rec = MappedRecognizer<DataRecognizer, BytesFromVec>;
+    type AttrRec = SimpleAttrBody<Self::Rec>;
+    type BodyRec = SimpleRecBody<Self::Rec>;
+
+    fn make_recognizer() -> Self::Rec {
+        MappedRecognizer::new(DataRecognizer, Bytes::from)
+    }
+
+    fn make_attr_recognizer() -> Self::AttrRec {
+        SimpleAttrBody::new(Self::make_recognizer())
+    }
+
+    fn make_body_recognizer() -> Self::BodyRec {
+        SimpleRecBody::new(Self::make_recognizer())
+    }
+
+    fn is_simple() -> bool {
+        true
+    }
+}
+
 type Selector<Flds> = for<'a> fn(&mut Flds, u32, ReadEvent<'a>) -> Option<Result<(), ReadError>>;
 
 struct Bitset(u32, u64);
@@ -1746,6 +1771,68 @@ where
     }
 }
 
+/// This type is used to encode untagged Rust enums, generated by the derivation macro for
+/// [`RecognizerReadable`] when the `untagged` attribute is applied. The representation is first
+/// read as a [`Value`] and then the variants are attempted, in order of definition, by the
+/// `select` function. It should not generally be necessary to use this type explicitly.
+#[doc(hidden)]
+pub struct UntaggedEnumRecognizer<R, T> {
+    inner: R,
+    select: fn(Value) -> Result<T, ReadError>,
+}
+
+impl<R, T> UntaggedEnumRecognizer<R, T> {
+    /// # Arguments
+    /// * `inner` - Recognizer for the representation of the enum as a [`Value`].
+    /// * `select` - Attempts to read each variant of the enum from the [`Value`].
+    pub fn new(inner: R, select: fn(Value) -> Result<T, ReadError>) -> Self {
+        UntaggedEnumRecognizer { inner, select }
+    }
+}
+
+impl<R, T> Recognizer for UntaggedEnumRecognizer<R, T>
+where
+    R: Recognizer<Target = Value>,
+{
+    type Target = T;
+
+    fn feed_event(&mut self, input: ReadEvent<'_>) -> Option<Result<Self::Target, ReadError>> {
+        let UntaggedEnumRecognizer { inner, select } = self;
+        inner.feed_event(input).map(|r| r.and_then(*select))
+    }
+
+    fn try_flush(&mut self) -> Option<Result<Self::Target, ReadError>> {
+        let UntaggedEnumRecognizer { inner, select } = self;
+        inner.try_flush().map(|r| r.and_then(*select))
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset()
+    }
+}
+
+/// Attempt to read a variant of an untagged enum from a value by adding the tag of the variant to
+/// it and feeding it to the recognizer for the tagged representation of the enum. This returns
+/// [`None`] if the value cannot be the representation of a record variant.
+#[doc(hidden)]
+pub fn read_untagged_variant<R: Recognizer>(
+    recognizer: R,
+    tag: &str,
+    value: &Value,
+) -> Option<Result<R::Target, ReadError>> {
+    let tagged = match value {
+        Value::Extant => Value::of_attr(tag),
+        Value::Record(attrs, items) => {
+            let mut tagged_attrs = Vec::with_capacity(attrs.len() + 1);
+            tagged_attrs.push(swimos_model::Attr::of(tag));
+            tagged_attrs.extend(attrs.iter().cloned());
+            Value::Record(tagged_attrs, items.clone())
+        }
+        _ => return None,
+    };
+    Some(tagged.write_with(RecognizerBridge::new(recognizer)))
+}
+
 #[derive(Clone, Copy)]
 enum UnitStructState {
     Init,
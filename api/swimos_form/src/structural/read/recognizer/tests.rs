@@ -175,3 +175,19 @@ fn hash_map_recognizer() {
 
     assert_eq!(map, expected);
 }
+
+#[test]
+fn bytes_and_cow_str_round_trip() {
+    use crate::structural::read::recognizer::RecognizerReadable;
+    use bytes::Bytes;
+    use std::borrow::Cow;
+    use swimos_model::Value;
+
+    let bytes = Bytes::from_static(b"abc");
+    assert_eq!(Bytes::try_read_from(&bytes), Ok(bytes.clone()));
+    assert_eq!(Vec::<u8>::try_transform(bytes), Ok(b"abc".to_vec()));
+
+    let text: Cow<'_, str> = Cow::Borrowed("text");
+    assert_eq!(Value::try_read_from(&text), Ok(Value::text("text")));
+    assert_eq!(String::try_transform(text), Ok("text".to_string()));
+}
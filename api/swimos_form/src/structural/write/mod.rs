@@ -15,6 +15,7 @@
 //! Contains the [`StructuralWritable`] trait that defines the functionality to serialize a type
 //! that supports the SwimOS model.
 
+use bytes::Bytes;
 use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap};
 use std::convert::Infallible;
@@ -23,7 +24,7 @@ use std::num::NonZeroUsize;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::time::Duration;
-use swimos_model::{Attr, Blob, Item, Text, Value};
+use swimos_model::{Attr, Blob, Item, Text, Value, ValueKind};
 use swimos_model::{BigInt, BigUint};
 use swimos_utilities::future::Quantity;
 
@@ -32,6 +33,7 @@ pub use swimos_form_derive::StructuralWritable;
 use swimos_model::Timestamp;
 use swimos_utilities::routing::RouteUri;
 
+use crate::schema::{BodySchema, FieldSchema, RecordSchema, Schema, TagSchema};
 pub use crate::structural::write::to_model::ValueInterpreter;
 mod impls;
 #[cfg(test)]
@@ -91,6 +93,12 @@ pub trait StructuralWritable {
     fn omit_as_field(&self) -> bool {
         false
     }
+
+    /// The schema of the structure that is written by values of this type. By default, this
+    /// places no constraints on the structure.
+    fn schema() -> Schema {
+        Schema::Anything
+    }
 }
 
 /// Base trait for structural writers that allow for a single, primitive value to be written.
@@ -383,6 +391,10 @@ where
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         T::write_with(self, writer)
     }
+
+    fn schema() -> Schema {
+        T::schema()
+    }
 }
 
 impl<T> StructuralWritable for &mut T
@@ -400,6 +412,10 @@ where
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         T::write_with(self, writer)
     }
+
+    fn schema() -> Schema {
+        T::schema()
+    }
 }
 
 impl StructuralWritable for () {
@@ -414,6 +430,10 @@ impl StructuralWritable for () {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_extant()
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Extant)
+    }
 }
 
 impl StructuralWritable for i32 {
@@ -428,6 +448,10 @@ impl StructuralWritable for i32 {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_i32(self)
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Int32)
+    }
 }
 
 impl StructuralWritable for i64 {
@@ -442,6 +466,10 @@ impl StructuralWritable for i64 {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_i64(self)
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Int64)
+    }
 }
 
 impl StructuralWritable for u32 {
@@ -456,6 +484,10 @@ impl StructuralWritable for u32 {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_u32(self)
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::UInt32)
+    }
 }
 
 impl StructuralWritable for u64 {
@@ -470,6 +502,10 @@ impl StructuralWritable for u64 {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_u64(self)
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::UInt64)
+    }
 }
 
 impl StructuralWritable for usize {
@@ -492,6 +528,10 @@ impl StructuralWritable for usize {
             writer.write_big_uint(BigUint::from(self))
         }
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::UInt64)
+    }
 }
 
 impl StructuralWritable for NonZeroUsize {
@@ -514,6 +554,10 @@ impl StructuralWritable for NonZeroUsize {
             writer.write_big_uint(BigUint::from(self.get()))
         }
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::UInt64)
+    }
 }
 
 impl StructuralWritable for f64 {
@@ -528,6 +572,10 @@ impl StructuralWritable for f64 {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_f64(self)
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Float64)
+    }
 }
 
 impl StructuralWritable for bool {
@@ -542,6 +590,10 @@ impl StructuralWritable for bool {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_bool(self)
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Boolean)
+    }
 }
 
 impl StructuralWritable for BigInt {
@@ -556,6 +608,10 @@ impl StructuralWritable for BigInt {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_big_int(self)
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::BigInt)
+    }
 }
 
 impl StructuralWritable for BigUint {
@@ -570,6 +626,10 @@ impl StructuralWritable for BigUint {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_big_uint(self)
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::BigUint)
+    }
 }
 
 impl StructuralWritable for String {
@@ -584,6 +644,10 @@ impl StructuralWritable for String {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_text(self)
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Text)
+    }
 }
 
 impl<'a> StructuralWritable for &'a str {
@@ -598,6 +662,31 @@ impl<'a> StructuralWritable for &'a str {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_text(self)
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Text)
+    }
+}
+
+impl<'a> StructuralWritable for Cow<'a, str> {
+    fn num_attributes(&self) -> usize {
+        0
+    }
+
+    fn write_with<W: StructuralWriter>(&self, writer: W) -> Result<W::Repr, W::Error> {
+        writer.write_text(self.as_ref())
+    }
+
+    fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
+        match self {
+            Cow::Borrowed(text) => writer.write_text(text),
+            Cow::Owned(text) => writer.write_text(text),
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Text)
+    }
 }
 
 impl StructuralWritable for Text {
@@ -612,6 +701,10 @@ impl StructuralWritable for Text {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_text(self)
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Text)
+    }
 }
 
 impl StructuralWritable for RouteUri {
@@ -632,6 +725,10 @@ impl StructuralWritable for RouteUri {
     ) -> Result<<W as PrimitiveWriter>::Repr, <W as PrimitiveWriter>::Error> {
         writer.write_text(self.as_str())
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Text)
+    }
 }
 
 impl<T: StructuralWritable> StructuralWritable for Arc<T> {
@@ -652,6 +749,10 @@ impl<T: StructuralWritable> StructuralWritable for Arc<T> {
             Err(outer) => outer.write_with(writer),
         }
     }
+
+    fn schema() -> Schema {
+        T::schema()
+    }
 }
 
 impl<T: StructuralWritable> StructuralWritable for Rc<T> {
@@ -672,6 +773,10 @@ impl<T: StructuralWritable> StructuralWritable for Rc<T> {
             Err(outer) => outer.write_with(writer),
         }
     }
+
+    fn schema() -> Schema {
+        T::schema()
+    }
 }
 
 impl StructuralWritable for Blob {
@@ -686,6 +791,10 @@ impl StructuralWritable for Blob {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_blob_vec(self.into_vec())
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Data)
+    }
 }
 
 impl StructuralWritable for Vec<u8> {
@@ -700,6 +809,10 @@ impl StructuralWritable for Vec<u8> {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_blob_vec(self)
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Data)
+    }
 }
 
 impl StructuralWritable for &[u8] {
@@ -714,6 +827,28 @@ impl StructuralWritable for &[u8] {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_blob(self)
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Data)
+    }
+}
+
+impl StructuralWritable for Bytes {
+    fn num_attributes(&self) -> usize {
+        0
+    }
+
+    fn write_with<W: StructuralWriter>(&self, writer: W) -> Result<W::Repr, W::Error> {
+        writer.write_blob(self.as_ref())
+    }
+
+    fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
+        writer.write_blob_vec(self.into())
+    }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Data)
+    }
 }
 
 impl StructuralWritable for Box<[u8]> {
@@ -728,6 +863,10 @@ impl StructuralWritable for Box<[u8]> {
     fn write_into<W: StructuralWriter>(self, writer: W) -> Result<W::Repr, W::Error> {
         writer.write_blob_vec(self.into_vec())
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Data)
+    }
 }
 
 impl StructuralWritable for Value {
@@ -840,6 +979,10 @@ impl<T: StructuralWritable> StructuralWritable for Vec<T> {
             )?
             .done()
     }
+
+    fn schema() -> Schema {
+        Schema::ArrayOf(Box::new(T::schema()))
+    }
 }
 
 impl<T: StructuralWritable> StructuralWritable for Option<T> {
@@ -869,6 +1012,10 @@ impl<T: StructuralWritable> StructuralWritable for Option<T> {
     fn omit_as_field(&self) -> bool {
         self.is_none()
     }
+
+    fn schema() -> Schema {
+        Schema::optional(T::schema())
+    }
 }
 
 macro_rules! map_impl {
@@ -905,6 +1052,10 @@ macro_rules! map_impl {
                     )?
                     .done()
             }
+
+            fn schema() -> Schema {
+                Schema::MapOf(Box::new(K::schema()), Box::new(V::schema()))
+            }
         }
     };
 }
@@ -930,6 +1081,10 @@ impl StructuralWritable for Timestamp {
     fn num_attributes(&self) -> usize {
         0
     }
+
+    fn schema() -> Schema {
+        Schema::OfKind(ValueKind::Int64)
+    }
 }
 
 impl<T: StructuralWritable> StructuralWritable for Quantity<T> {
@@ -950,6 +1105,10 @@ impl<T: StructuralWritable> StructuralWritable for Quantity<T> {
             Quantity::Infinite => writer.write_text("infinite"),
         }
     }
+
+    fn schema() -> Schema {
+        Schema::Or(vec![T::schema(), Schema::Equal(Value::text("infinite"))])
+    }
 }
 
 impl StructuralWritable for Duration {
@@ -978,6 +1137,23 @@ impl StructuralWritable for Duration {
 
         body_writer.done()
     }
+
+    fn schema() -> Schema {
+        Schema::Record(RecordSchema {
+            tag: Some(TagSchema {
+                name: Some(Text::new("duration")),
+                body: Box::new(Schema::OfKind(ValueKind::Extant)),
+            }),
+            attrs: vec![],
+            body: BodySchema::Fields {
+                values: vec![],
+                slots: vec![
+                    FieldSchema::new("secs", Schema::OfKind(ValueKind::UInt64)),
+                    FieldSchema::new("nanos", Schema::OfKind(ValueKind::UInt32)),
+                ],
+            },
+        })
+    }
 }
 
 macro_rules! impl_writable_tuple {
@@ -1014,6 +1190,10 @@ macro_rules! impl_writable_tuple {
             fn num_attributes(&self) -> usize {
                 0
             }
+
+            fn schema() -> Schema {
+                Schema::Record(RecordSchema::fields(vec![$($pname::schema()),+], vec![]))
+            }
         }
     );
 }
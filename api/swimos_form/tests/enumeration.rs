@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt::Debug;
 use swimos_form::Form;
 use swimos_model::{Attr, Item, Value};
 
@@ -675,3 +676,148 @@ fn header_body_replace() {
     assert_eq!(HeaderBodyReplace::try_from_value(&expected), Ok(ex.clone()));
     assert_eq!(HeaderBodyReplace::try_convert(expected), Ok(ex));
 }
+
+#[test]
+fn generic_variant_payloads() {
+    #[derive(Form, Debug, PartialEq, Clone)]
+    enum Payload<A, B = i32>
+    where
+        A: Debug,
+        B: Clone,
+    {
+        Many(Vec<A>),
+        Named {
+            b: Option<B>,
+            #[form(attr)]
+            c: Vec<A>,
+        },
+        #[form(tag = "header")]
+        Header(#[form(header_body)] B, #[form(body)] A),
+        Nothing,
+    }
+
+    #[derive(Form, Debug, PartialEq, Clone)]
+    struct Wrapper<T>
+    where
+        T: Debug,
+    {
+        inner: Payload<T, String>,
+    }
+
+    let header: Payload<i32, String> = Payload::Header("name".to_string(), 3);
+    let expected = Value::Record(
+        vec![Attr::of(("header", Value::text("name")))],
+        vec![Item::of(3)],
+    );
+    assert_eq!(header.as_value(), expected);
+    assert_eq!(Payload::try_from_value(&expected), Ok(header));
+
+    let named: Payload<i64> = Payload::Named {
+        b: Some(1),
+        c: vec![2, 3],
+    };
+    assert_eq!(Payload::try_from_value(&named.as_value()), Ok(named));
+
+    let many: Payload<u32> = Payload::Many(vec![1, 2, 3]);
+    assert_eq!(Payload::try_convert(many.clone().into_value()), Ok(many));
+
+    let wrapper = Wrapper {
+        inner: Payload::<i64, String>::Nothing,
+    };
+    assert_eq!(Wrapper::try_from_value(&wrapper.as_value()), Ok(wrapper));
+}
+
+#[derive(Form, Debug, PartialEq, Clone)]
+#[form(untagged)]
+enum Untagged<T> {
+    Unit,
+    Single(T),
+    Named {
+        value: T,
+    },
+    Pair(T, String),
+    Annotated {
+        #[form(attr)]
+        meta: i32,
+        #[form(body)]
+        body: Vec<T>,
+    },
+}
+
+#[test]
+fn untagged_enum() {
+    let cases = [
+        (Untagged::Unit, Value::Extant),
+        (Untagged::Single(4), Value::Int32Value(4)),
+        (
+            Untagged::Named { value: 5 },
+            Value::record(vec![Item::slot("value", 5)]),
+        ),
+        (
+            Untagged::Pair(6, "a".to_string()),
+            Value::record(vec![Item::of(6), Item::of("a")]),
+        ),
+        (
+            Untagged::Annotated {
+                meta: 7,
+                body: vec![8, 9],
+            },
+            Value::Record(vec![Attr::of(("meta", 7))], vec![Item::of(8), Item::of(9)]),
+        ),
+    ];
+
+    for (untagged, expected) in cases {
+        assert_eq!(untagged.as_value(), expected);
+        assert_eq!(Untagged::try_from_value(&expected), Ok(untagged.clone()));
+        assert_eq!(Untagged::try_convert(expected), Ok(untagged));
+    }
+
+    assert!(Untagged::<i32>::try_from_value(&Value::text("a")).is_err());
+    assert!(Untagged::<i32>::try_from_value(&Value::record(vec![Item::slot("other", 1)])).is_err());
+}
+
+#[test]
+fn untagged_enum_variant_order() {
+    #[derive(Form, Debug, PartialEq, Clone)]
+    #[form(untagged)]
+    enum Number {
+        Int(i32),
+        Float(f64),
+        Text(String),
+    }
+
+    #[derive(Form, Debug, PartialEq, Clone)]
+    struct Measurement {
+        number: Number,
+        #[form(attr)]
+        other: Number,
+    }
+
+    assert_eq!(
+        Number::try_from_value(&Value::Int32Value(1)),
+        Ok(Number::Int(1))
+    );
+    assert_eq!(
+        Number::try_from_value(&Value::Float64Value(1.5)),
+        Ok(Number::Float(1.5))
+    );
+    assert_eq!(
+        Number::try_from_value(&Value::text("1")),
+        Ok(Number::Text("1".to_string()))
+    );
+
+    let measurement = Measurement {
+        number: Number::Float(0.5),
+        other: Number::Text("text".to_string()),
+    };
+    let expected = Value::Record(
+        vec![Attr::of("Measurement"), Attr::of(("other", "text"))],
+        vec![Item::slot("number", 0.5)],
+    );
+    assert_eq!(measurement.as_value(), expected);
+    assert_eq!(
+        Measurement::try_from_value(&expected),
+        Ok(measurement.clone())
+    );
+    assert_eq!(Measurement::try_convert(expected), Ok(measurement));
+}
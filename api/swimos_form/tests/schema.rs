@@ -0,0 +1,297 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use swimos_form::schema::{
+    check_round_trip, schema_of, validate, BodySchema, FieldSchema, RecordSchema, Schema, TagSchema,
+};
+use swimos_form::write::StructuralWritable;
+use swimos_form::Form;
+
+fn schema_of_writable<T: StructuralWritable>() -> Schema {
+    T::schema()
+}
+use swimos_model::{Attr, Item, Value, ValueKind};
+
+#[derive(Form, Debug, PartialEq, Clone)]
+struct Example {
+    a: i32,
+    b: Option<String>,
+}
+
+#[test]
+fn derived_struct_schema() {
+    let expected = Schema::Record(RecordSchema {
+        tag: Some(TagSchema {
+            name: Some("Example".into()),
+            body: Box::new(Schema::OfKind(ValueKind::Extant)),
+        }),
+        attrs: vec![],
+        body: BodySchema::Fields {
+            values: vec![],
+            slots: vec![
+                FieldSchema::new("a", Schema::OfKind(ValueKind::Int32)),
+                FieldSchema::new("b", Schema::optional(Schema::OfKind(ValueKind::Text))),
+            ],
+        },
+    });
+    assert_eq!(schema_of::<Example>(), expected);
+}
+
+#[test]
+fn derived_struct_validation() {
+    let schema = schema_of::<Example>();
+    let example = Example { a: 1, b: None };
+    assert!(validate(&example.as_value(), &schema).is_ok());
+
+    let invalid = Value::Record(
+        vec![Attr::of("Example")],
+        vec![Item::slot("a", "one"), Item::slot("b", "two")],
+    );
+    let err = validate(&invalid, &schema).unwrap_err();
+    assert_eq!(err.path, "$.a");
+
+    let wrong_tag = Value::Record(vec![Attr::of("Other")], vec![Item::slot("a", 1)]);
+    assert!(validate(&wrong_tag, &schema).is_err());
+}
+
+#[derive(Form, Debug, PartialEq, Clone)]
+#[form(tag = "header")]
+struct WithHeader {
+    #[form(header_body)]
+    id: u64,
+    #[form(header)]
+    node: String,
+    #[form(attr)]
+    flag: bool,
+    #[form(body)]
+    body: Vec<i32>,
+}
+
+#[test]
+fn derived_header_and_body_schema() {
+    let expected = Schema::Record(RecordSchema {
+        tag: Some(TagSchema {
+            name: Some("header".into()),
+            body: Box::new(Schema::Record(RecordSchema::fields(
+                vec![Schema::OfKind(ValueKind::UInt64)],
+                vec![FieldSchema::new("node", Schema::OfKind(ValueKind::Text))],
+            ))),
+        }),
+        attrs: vec![FieldSchema::new("flag", Schema::OfKind(ValueKind::Boolean))],
+        body: BodySchema::Delegate(Box::new(schema_of::<Vec<i32>>())),
+    });
+    assert_eq!(schema_of::<WithHeader>(), expected);
+
+    let value = WithHeader {
+        id: 3,
+        node: "node".to_string(),
+        flag: true,
+        body: vec![1, 2, 3],
+    };
+    assert!(check_round_trip(&value).is_ok());
+}
+
+#[derive(Form, Debug, PartialEq, Clone)]
+enum Shape {
+    Circle(f64),
+    Rect { width: f64, height: f64 },
+    Empty,
+}
+
+#[test]
+fn derived_enum_schema() {
+    let schema = schema_of::<Shape>();
+    assert!(matches!(&schema, Schema::Or(variants) if variants.len() == 3));
+
+    for shape in [
+        Shape::Circle(1.0),
+        Shape::Rect {
+            width: 1.0,
+            height: 2.0,
+        },
+        Shape::Empty,
+    ] {
+        assert!(validate(&shape.as_value(), &schema).is_ok());
+        assert!(check_round_trip(&shape).is_ok());
+    }
+
+    let invalid = Value::Record(vec![Attr::of("Rect")], vec![Item::slot("width", 1.0)]);
+    assert!(validate(&invalid, &schema).is_err());
+}
+
+#[derive(Form, Debug, PartialEq, Clone)]
+#[form(newtype)]
+struct Id(u32);
+
+#[test]
+fn derived_newtype_schema() {
+    assert_eq!(schema_of::<Id>(), Schema::OfKind(ValueKind::UInt32));
+    assert!(check_round_trip(&Id(7)).is_ok());
+}
+
+#[derive(StructuralWritable)]
+struct Tree {
+    value: i32,
+    children: Vec<Tree>,
+}
+
+#[test]
+fn recursive_schema() {
+    let schema = schema_of_writable::<Tree>();
+    let Schema::Record(RecordSchema {
+        body: BodySchema::Fields { slots, .. },
+        ..
+    }) = schema
+    else {
+        panic!("Expected a record schema.");
+    };
+    assert_eq!(
+        slots[1],
+        FieldSchema::new("children", Schema::ArrayOf(Box::new(Schema::Anything)))
+    );
+
+    let tree = Tree {
+        value: 1,
+        children: vec![Tree {
+            value: 2,
+            children: vec![],
+        }],
+    };
+    assert!(validate(&tree.structure(), &schema_of_writable::<Tree>()).is_ok());
+}
+
+#[derive(Form, Debug, PartialEq, Clone)]
+struct Generic<T> {
+    inner: T,
+}
+
+#[test]
+fn round_trip_many_values() {
+    for a in [i32::MIN, -1, 0, 1, i32::MAX] {
+        for b in [None, Some(String::new()), Some("text".to_string())] {
+            let example = Example { a, b };
+            assert_eq!(check_round_trip(&example), Ok(()));
+            assert_eq!(check_round_trip(&Generic { inner: example }), Ok(()));
+        }
+    }
+}
+
+#[derive(Form, Debug, PartialEq, Clone)]
+#[form(rename_all = "camelCase")]
+struct WithDefault {
+    required_field: i32,
+    #[form(default)]
+    optional_field: i32,
+}
+
+#[test]
+fn default_fields_are_optional() {
+    let schema = schema_of::<WithDefault>();
+    let value = Value::Record(
+        vec![Attr::of("WithDefault")],
+        vec![Item::slot("requiredField", 1)],
+    );
+    assert!(validate(&value, &schema).is_ok());
+    assert_eq!(
+        WithDefault::try_from_value(&value),
+        Ok(WithDefault {
+            required_field: 1,
+            optional_field: 0
+        })
+    );
+}
+
+#[derive(Form, Debug, PartialEq, Clone)]
+#[form(untagged)]
+enum Untagged {
+    Empty,
+    Number(i32),
+    Point { x: i32, y: i32 },
+}
+
+#[test]
+fn untagged_enum_schema() {
+    let expected = Schema::Or(vec![
+        Schema::OfKind(ValueKind::Extant),
+        Schema::OfKind(ValueKind::Int32),
+        Schema::Record(RecordSchema {
+            tag: None,
+            attrs: vec![],
+            body: BodySchema::Fields {
+                values: vec![],
+                slots: vec![
+                    FieldSchema::new("x", Schema::OfKind(ValueKind::Int32)),
+                    FieldSchema::new("y", Schema::OfKind(ValueKind::Int32)),
+                ],
+            },
+        }),
+    ]);
+    assert_eq!(schema_of::<Untagged>(), expected);
+
+    for untagged in [
+        Untagged::Empty,
+        Untagged::Number(2),
+        Untagged::Point { x: 1, y: 2 },
+    ] {
+        assert!(check_round_trip(&untagged).is_ok());
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod round_trips {
+    use proptest::prelude::*;
+    use swimos_form::schema::strategy::{arbitrary_form, conforming_values};
+    use swimos_form::schema::{check_round_trip, schema_of, validate};
+
+    use super::{Example, Generic, Shape, Untagged, WithDefault, WithHeader};
+
+    proptest! {
+        #[test]
+        fn struct_round_trip(value in arbitrary_form::<Example>()) {
+            prop_assert_eq!(check_round_trip(&value), Ok(()));
+        }
+
+        #[test]
+        fn generic_round_trip(value in arbitrary_form::<Generic<Example>>()) {
+            prop_assert_eq!(check_round_trip(&value), Ok(()));
+        }
+
+        #[test]
+        fn header_and_body_round_trip(value in arbitrary_form::<WithHeader>()) {
+            prop_assert_eq!(check_round_trip(&value), Ok(()));
+        }
+
+        #[test]
+        fn enum_round_trip(value in arbitrary_form::<Shape>()) {
+            prop_assert_eq!(check_round_trip(&value), Ok(()));
+        }
+
+        #[test]
+        fn untagged_enum_round_trip(value in arbitrary_form::<Untagged>()) {
+            prop_assert_eq!(check_round_trip(&value), Ok(()));
+        }
+
+        #[test]
+        fn default_fields_round_trip(value in arbitrary_form::<WithDefault>()) {
+            prop_assert_eq!(check_round_trip(&value), Ok(()));
+        }
+
+        #[test]
+        fn derived_schemas_generate_valid_values(
+            value in conforming_values(&schema_of::<WithHeader>())
+        ) {
+            prop_assert_eq!(validate(&value, &schema_of::<WithHeader>()), Ok(()));
+        }
+    }
+}
@@ -251,6 +251,143 @@ fn test_rename_by_field_convention() {
     assert_eq!(s.into_value(), rec);
 }
 
+#[test]
+fn test_rename_all() {
+    #[derive(Form, Debug, PartialEq, Clone)]
+    #[form(rename_all = "camelCase")]
+    struct MyType {
+        first_field: i32,
+        #[form(name = "second")]
+        second_field: i64,
+    }
+
+    let s = MyType {
+        first_field: 1,
+        second_field: 2,
+    };
+    let rec = Value::Record(
+        vec![Attr::of("MyType")],
+        vec![
+            Item::Slot(Value::text("firstField"), Value::Int32Value(1)),
+            Item::Slot(Value::text("second"), Value::Int64Value(2)),
+        ],
+    );
+    assert_eq!(s.as_value(), rec);
+    assert_eq!(MyType::try_from_value(&rec), Ok(s.clone()));
+    assert_eq!(MyType::try_convert(rec.clone()), Ok(s.clone()));
+    assert_eq!(s.into_value(), rec);
+}
+
+#[test]
+fn test_rename_all_enum() {
+    #[derive(Form, Debug, PartialEq, Clone)]
+    #[form(rename_all = "snake_case")]
+    enum MyEnum {
+        FirstVariant,
+        #[form(rename_all = "SCREAMING_SNAKE_CASE")]
+        SecondVariant {
+            inner_field: i32,
+        },
+    }
+
+    let first = MyEnum::FirstVariant;
+    let rec = Value::of_attr("first_variant");
+    assert_eq!(first.as_value(), rec);
+    assert_eq!(MyEnum::try_from_value(&rec), Ok(first));
+
+    let second = MyEnum::SecondVariant { inner_field: 3 };
+    let rec = Value::Record(
+        vec![Attr::of("second_variant")],
+        vec![Item::Slot(Value::text("INNER_FIELD"), Value::Int32Value(3))],
+    );
+    assert_eq!(second.as_value(), rec);
+    assert_eq!(MyEnum::try_from_value(&rec), Ok(second));
+}
+
+#[test]
+fn test_default_field() {
+    #[derive(Form, Debug, PartialEq, Clone)]
+    struct MyType {
+        required: i32,
+        #[form(default)]
+        count: u32,
+        #[form(default)]
+        names: Vec<String>,
+        #[form(attr, default)]
+        flag: bool,
+    }
+
+    let full = MyType {
+        required: 1,
+        count: 2,
+        names: vec!["a".to_string()],
+        flag: true,
+    };
+    let rec = Value::Record(
+        vec![Attr::of("MyType"), Attr::of(("flag", true))],
+        vec![
+            Item::slot("required", 1),
+            Item::slot("count", 2u32),
+            Item::slot("names", Value::from_vec(vec!["a"])),
+        ],
+    );
+    assert_eq!(full.as_value(), rec);
+    assert_eq!(MyType::try_from_value(&rec), Ok(full));
+
+    let sparse = Value::Record(vec![Attr::of("MyType")], vec![Item::slot("required", 1)]);
+    let expected = MyType {
+        required: 1,
+        count: 0,
+        names: vec![],
+        flag: false,
+    };
+    assert_eq!(MyType::try_from_value(&sparse), Ok(expected.clone()));
+    assert_eq!(MyType::try_convert(sparse), Ok(expected));
+
+    let missing_required = Value::of_attr("MyType");
+    assert!(MyType::try_from_value(&missing_required).is_err());
+}
+
+#[test]
+fn test_default_and_skip_fields() {
+    #[derive(Form, Debug, PartialEq, Clone)]
+    #[form(rename_all = "kebab-case")]
+    struct MyType {
+        #[form(header, default)]
+        node_uri: String,
+        #[form(skip)]
+        cached_len: usize,
+        body_value: i32,
+    }
+
+    let value = MyType {
+        node_uri: "node".to_string(),
+        cached_len: 4,
+        body_value: 5,
+    };
+    let rec = Value::Record(
+        vec![Attr::of((
+            "MyType",
+            Value::record(vec![Item::slot("node-uri", "node")]),
+        ))],
+        vec![Item::slot("body-value", 5)],
+    );
+    assert_eq!(value.as_value(), rec);
+
+    let without_header = Value::Record(
+        vec![Attr::of(("MyType", Value::empty_record()))],
+        vec![Item::slot("body-value", 5)],
+    );
+    assert_eq!(
+        MyType::try_from_value(&without_header),
+        Ok(MyType {
+            node_uri: String::new(),
+            cached_len: 0,
+            body_value: 5,
+        })
+    );
+}
+
 #[test]
 fn body_replaces() {
     #[derive(Form, Debug, PartialEq, Clone)]
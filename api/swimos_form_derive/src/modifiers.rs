@@ -16,7 +16,7 @@ use crate::structural::model::field::FieldSelector;
 use crate::SynValidation;
 use quote::ToTokens;
 use swimos_macro_utilities::attr_names::{
-    CONV_NAME, FIELDS_NAME, NEWTYPE_PATH, SCHEMA_NAME, TAG_NAME,
+    CONV_NAME, FIELDS_NAME, NEWTYPE_PATH, RENAME_ALL_NAME, SCHEMA_NAME, TAG_NAME, UNTAGGED_PATH,
 };
 use swimos_macro_utilities::attributes::{IgnoreConsumer, NestedMetaConsumer};
 use swimos_macro_utilities::{
@@ -76,6 +76,8 @@ pub struct EnumTransform {
     pub variant_rename: TypeLevelNameTransform,
     /// Directive to rename the fields of the variants of the enumeration.
     pub field_rename: TypeLevelNameTransform,
+    /// Directive to omit the tags of the variants and select the variant by structure.
+    pub untagged: bool,
 }
 
 /// Directives to alter the interpretation of a struct definition, extracted from the attributes that
@@ -102,11 +104,13 @@ impl<'a> Default for StructTransform<'a> {
 pub enum EnumTransformPart {
     Variants(CaseConvention),
     Fields(CaseConvention),
+    Untagged,
     Ignored,
 }
 
 pub struct EnumPartConsumer {
     variants: TypeLevelNameTransformConsumer<'static>,
+    rename_all: TypeLevelNameTransformConsumer<'static>,
     fields: TypeLevelNameTransformConsumer<'static>,
     ignore: IgnoreConsumer,
 }
@@ -115,6 +119,7 @@ impl Default for EnumPartConsumer {
     fn default() -> Self {
         Self {
             variants: TypeLevelNameTransformConsumer::new(CONV_NAME),
+            rename_all: TypeLevelNameTransformConsumer::new(RENAME_ALL_NAME),
             fields: TypeLevelNameTransformConsumer::new(FIELDS_NAME),
             ignore: IgnoreConsumer::new(SCHEMA_NAME),
         }
@@ -123,11 +128,20 @@ impl Default for EnumPartConsumer {
 
 impl NestedMetaConsumer<EnumTransformPart> for EnumPartConsumer {
     fn try_consume(&self, meta: &syn::NestedMeta) -> Result<Option<EnumTransformPart>, syn::Error> {
+        if matches!(meta, syn::NestedMeta::Meta(syn::Meta::Path(path)) if path == UNTAGGED_PATH) {
+            return Ok(Some(EnumTransformPart::Untagged));
+        }
         match self.variants.try_consume(meta) {
             Ok(Some(part)) => return Ok(Some(EnumTransformPart::Variants(part))),
             Err(e) => return Err(e),
             _ => {}
         }
+        // As with serde, `rename_all` on an enum applies to the names of the variants.
+        match self.rename_all.try_consume(meta) {
+            Ok(Some(part)) => return Ok(Some(EnumTransformPart::Variants(part))),
+            Err(e) => return Err(e),
+            _ => {}
+        }
         match self.fields.try_consume(meta) {
             Ok(Some(part)) => return Ok(Some(EnumTransformPart::Fields(part))),
             Err(e) => return Err(e),
@@ -154,6 +168,7 @@ pub fn combine_enum_trans_parts<T: ToTokens + ?Sized>(
             let EnumTransform {
                 variant_rename,
                 field_rename,
+                untagged,
             } = &mut acc;
             match part {
                 EnumTransformPart::Variants(conv) => {
@@ -178,6 +193,17 @@ pub fn combine_enum_trans_parts<T: ToTokens + ?Sized>(
                         )))
                     }
                 }
+                EnumTransformPart::Untagged => {
+                    if *untagged {
+                        Validation::fail(Errors::of(syn::Error::new_spanned(
+                            meta,
+                            "'untagged' can only be applied once.",
+                        )))
+                    } else {
+                        *untagged = true;
+                        Validation::valid(acc)
+                    }
+                }
                 EnumTransformPart::Ignored => Validation::valid(acc),
             }
         },
@@ -256,6 +282,7 @@ pub fn combine_struct_trans_parts<T: ToTokens + ?Sized>(
 pub struct StructTransformPartConsumer {
     rename: NameTransformConsumer<'static>,
     field_rename: TypeLevelNameTransformConsumer<'static>,
+    rename_all: TypeLevelNameTransformConsumer<'static>,
     schema_ignore: IgnoreConsumer,
 }
 
@@ -264,6 +291,7 @@ impl Default for StructTransformPartConsumer {
         Self {
             rename: NameTransformConsumer::new(TAG_NAME, CONV_NAME),
             field_rename: TypeLevelNameTransformConsumer::new(FIELDS_NAME),
+            rename_all: TypeLevelNameTransformConsumer::new(RENAME_ALL_NAME),
             schema_ignore: IgnoreConsumer::new(SCHEMA_NAME),
         }
     }
@@ -282,6 +310,7 @@ impl NestedMetaConsumer<StructTransformPart<'static>> for StructTransformPartCon
                 let StructTransformPartConsumer {
                     rename,
                     field_rename,
+                    rename_all,
                     schema_ignore,
                 } = self;
                 match rename.try_consume(meta) {
@@ -294,6 +323,11 @@ impl NestedMetaConsumer<StructTransformPart<'static>> for StructTransformPartCon
                     Err(e) => return Err(e),
                     _ => {}
                 }
+                match rename_all.try_consume(meta) {
+                    Ok(Some(trans)) => return Ok(Some(StructTransformPart::FieldRename(trans))),
+                    Err(e) => return Err(e),
+                    _ => {}
+                }
                 match schema_ignore.try_consume(meta) {
                     Ok(Some(_)) => Ok(Some(StructTransformPart::Ignored)),
                     Err(e) => Err(e),
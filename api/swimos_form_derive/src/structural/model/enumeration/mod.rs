@@ -32,14 +32,22 @@ pub struct EnumModel<'a> {
     pub name: &'a Ident,
     /// Preprocessed descriptions of each variant.
     pub variants: Vec<StructModel<'a>>,
+    /// Whether the variants are written without their tags (and selected by structure).
+    pub untagged: bool,
 }
 
 impl<'a> EnumModel<'a> {
-    pub fn new(root: &'a syn::Path, name: &'a Ident, variants: Vec<StructModel<'a>>) -> Self {
+    pub fn new(
+        root: &'a syn::Path,
+        name: &'a Ident,
+        variants: Vec<StructModel<'a>>,
+        untagged: bool,
+    ) -> Self {
         EnumModel {
             root,
             name,
             variants,
+            untagged,
         }
     }
 }
@@ -92,6 +100,8 @@ impl<'a> EnumDef<'a> {
 
 const VARIANT_WITH_TAG: &str = "Enum variants cannot specify a tag field";
 const NEWTYPE_SPECIFIED_FOR_VARIANT: &str = "Cannot use `newtype` annotation with enum variants";
+const UNTAGGED_WITH_HEADER: &str =
+    "Variants of untagged enums cannot have header fields as they have no tag attribute";
 
 impl<'a> ValidateFrom<EnumDef<'a>> for EnumModel<'a> {
     fn validate(input: EnumDef<'a>) -> SynValidation<Self> {
@@ -131,6 +141,9 @@ impl<'a> ValidateFrom<EnumDef<'a>> for EnumModel<'a> {
                         if model.fields_model.has_tag_field() {
                             let err = syn::Error::new_spanned(variant, VARIANT_WITH_TAG);
                             Validation::Validated(model, err.into())
+                        } else if transform.untagged && model.fields_model.has_header_field() {
+                            let err = syn::Error::new_spanned(variant, UNTAGGED_WITH_HEADER);
+                            Validation::Validated(model, err.into())
                         } else {
                             Validation::valid(model)
                         }
@@ -147,7 +160,7 @@ impl<'a> ValidateFrom<EnumDef<'a>> for EnumModel<'a> {
             },
         );
 
-        variants.and_then(|(_, mut variants)| {
+        variants.and_then(|(transform, mut variants)| {
             let names = variants.iter_mut().validate_fold(
                 Validation::valid(HashSet::new()),
                 false,
@@ -175,7 +188,7 @@ impl<'a> ValidateFrom<EnumDef<'a>> for EnumModel<'a> {
             );
 
             names.and_then(move |_| {
-                let enum_model = EnumModel::new(root, name, variants);
+                let enum_model = EnumModel::new(root, name, variants, transform.untagged);
                 Validation::valid(enum_model)
             })
         })
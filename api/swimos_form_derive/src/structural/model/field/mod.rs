@@ -18,8 +18,8 @@ use quote::{ToTokens, TokenStreamExt};
 use std::borrow::Cow;
 use std::ops::Add;
 use swimos_macro_utilities::attr_names::{
-    ATTR_PATH, BODY_PATH, CONV_NAME, FORM_PATH, HEADER_BODY_PATH, HEADER_PATH, NAME_NAME,
-    SCHEMA_NAME, SKIP_PATH, SLOT_PATH, TAG_PATH,
+    ATTR_PATH, BODY_PATH, CONV_NAME, DEFAULT_PATH, FORM_PATH, HEADER_BODY_PATH, HEADER_PATH,
+    NAME_NAME, SCHEMA_NAME, SKIP_PATH, SLOT_PATH, TAG_PATH,
 };
 use swimos_macro_utilities::attributes::NestedMetaConsumer;
 use swimos_macro_utilities::{
@@ -104,6 +104,8 @@ pub struct FieldModel<'a> {
     pub transform: NameTransform,
     /// The type of the field.
     pub field_ty: &'a Type,
+    /// Whether the field should take its default value if it is absent when reading.
+    pub default: bool,
 }
 
 impl<'a> FieldModel<'a> {
@@ -172,6 +174,8 @@ enum FieldAttr {
     Transform(NameTransform),
     /// Specify where the field should occur in the serialized record.
     Kind(FieldKind),
+    /// Use the default value of the field if it is absent.
+    Default,
     /// Explicitly ignored field attribute.
     Ignored,
 }
@@ -181,6 +185,7 @@ enum FieldAttr {
 struct FieldAttributes {
     transform: NameTransform,
     directive: Option<FieldKind>,
+    default: bool,
 }
 
 impl FieldAttributes {
@@ -189,6 +194,7 @@ impl FieldAttributes {
         let FieldAttributes {
             transform,
             directive,
+            default,
         } = &mut self;
         match attr {
             FieldAttr::Transform(t) => {
@@ -209,6 +215,16 @@ impl FieldAttributes {
                     Validation::valid(self)
                 }
             }
+            FieldAttr::Default => {
+                if *default {
+                    let err =
+                        syn::Error::new_spanned(field, "Field marked as default multiple times");
+                    Validation::Validated(self, err.into())
+                } else {
+                    *default = true;
+                    Validation::valid(self)
+                }
+            }
             FieldAttr::Ignored => Validation::valid(self),
         }
     }
@@ -301,6 +317,7 @@ impl Manifest {
             |FieldAttributes {
                  transform,
                  directive,
+                 default,
              }| {
                 let model = TaggedFieldModel {
                     model: FieldModel {
@@ -311,10 +328,17 @@ impl Manifest {
                         ordinal: i,
                         transform,
                         field_ty: ty,
+                        default,
                     },
                     directive: directive.unwrap_or(FieldKind::Item),
                 };
-                if model.is_valid() {
+                if default && matches!(model.directive, FieldKind::Skip | FieldKind::Tagged) {
+                    let err = syn::Error::new_spanned(
+                        field,
+                        "Skipped and tag fields cannot be marked as default",
+                    );
+                    Validation::Validated(model, err.into())
+                } else if model.is_valid() {
                     Validation::valid(model)
                 } else {
                     let err = syn::Error::new_spanned(
@@ -360,7 +384,11 @@ impl NestedMetaConsumer<FieldAttr> for FieldAttrConsumer {
                         return Ok(Some(FieldAttr::Kind(*kind)));
                     }
                 }
-                Ok(None)
+                if path == DEFAULT_PATH {
+                    Ok(Some(FieldAttr::Default))
+                } else {
+                    Ok(None)
+                }
             }
             NestedMeta::Meta(Meta::List(lst)) if lst.path.is_ident(SCHEMA_NAME) => {
                 Ok(Some(FieldAttr::Ignored))
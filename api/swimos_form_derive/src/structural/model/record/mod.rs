@@ -59,6 +59,32 @@ impl<'a> FieldsModel<'a> {
             .any(|model| model.directive == FieldKind::Tagged)
     }
 
+    /// Determine whether any of the fields are written into the body of the tag attribute.
+    pub fn has_header_field(&self) -> bool {
+        self.fields.iter().any(|model| {
+            matches!(model.directive, FieldKind::Header | FieldKind::HeaderBody)
+                || (model.directive == FieldKind::Item
+                    && self
+                        .fields
+                        .iter()
+                        .any(|other| other.directive == FieldKind::Body))
+        })
+    }
+
+    /// If the fields consist of a single, unlabelled slot, the variant of an untagged enum is
+    /// written as the value of that field alone.
+    pub fn untagged_value_field(&self) -> Option<&FieldModel<'a>> {
+        match self.fields.as_slice() {
+            [field]
+                if field.directive == FieldKind::Item
+                    && self.type_kind != CompoundTypeKind::Labelled =>
+            {
+                Some(&field.model)
+            }
+            _ => None,
+        }
+    }
+
     pub fn newtype_field(&self) -> Result<FieldSelector<'a>, NewtypeFieldError> {
         let mut selector = None;
         for field in &self.fields {
@@ -122,6 +148,7 @@ impl<'a> StructModel<'a> {
         let EnumTransform {
             variant_rename,
             field_rename: super_field_rename,
+            ..
         } = enum_transform;
         let StructModel {
             fields_model: FieldsModel { fields, .. },
@@ -23,7 +23,9 @@ use swimos_macro_utilities::{CompoundTypeKind, FieldKind};
 
 use crate::quote::TokenStreamExt;
 use crate::structural::model::enumeration::SegregatedEnumModel;
-use crate::structural::model::field::{BodyFields, FieldModel, HeaderFields, SegregatedFields};
+use crate::structural::model::field::{
+    BodyFields, FieldModel, FieldSelector, HeaderFields, SegregatedFields,
+};
 use crate::structural::model::record::SegregatedStructModel;
 
 use super::model::record::StructModel;
@@ -316,21 +318,67 @@ impl<'a> ToTokens for DeriveStructuralReadable<'a, SegregatedEnumModel<'a>> {
 
             let recog_ty = quote!(#root::read::TaggedEnumRecognizer<#builder_name #type_gen>);
 
-            tokens.append_all(quote! {
-                const _: () = {
-                    #(#variant_functions)*
-
-                    #state
-
+            let readable_impl = if inner.untagged {
+                let select_untagged_name = select_untagged_name();
+                let select_untagged = SelectUntaggedFn::new(model, &type_gen);
+                let turbofish = type_gen.as_turbofish();
+                let value_ty = quote!(#root::model::Value);
+                let untagged_recog_ty = |rec: TokenStream| {
+                    quote! {
+                        #root::read::UntaggedEnumRecognizer<
+                            <#value_ty as #root::read::RecognizerReadable>::#rec,
+                            #enum_ty,
+                        >
+                    }
+                };
+                let rec_ty = untagged_recog_ty(quote!(Rec));
+                let attr_rec_ty = untagged_recog_ty(quote!(AttrRec));
+                let body_rec_ty = untagged_recog_ty(quote!(BodyRec));
+                quote! {
                     #[automatically_derived]
                     #[allow(non_snake_case)]
-                    #[inline]
-                    fn #select_var_name #impl_gen(name: &str) -> ::core::option::Option<#builder_name #type_gen>
+                    fn #select_untagged_name #impl_gen(value: #value_ty) -> ::core::result::Result<#enum_ty, #root::read::ReadError>
                     #where_clause
                     {
-                         #select_var
+                        #select_untagged
                     }
 
+                    #[automatically_derived]
+                    #[allow(non_snake_case)]
+                    impl #impl_gen #root::read::RecognizerReadable for #name #type_gen
+                    #where_clause
+                    {
+                        type Rec = #rec_ty;
+                        type AttrRec = #attr_rec_ty;
+                        type BodyRec = #body_rec_ty;
+
+                        #[inline]
+                        fn make_recognizer() -> Self::Rec {
+                            #root::read::UntaggedEnumRecognizer::new(
+                                <#value_ty as #root::read::RecognizerReadable>::make_recognizer(),
+                                #select_untagged_name #turbofish,
+                            )
+                        }
+
+                        #[inline]
+                        fn make_attr_recognizer() -> Self::AttrRec {
+                            #root::read::UntaggedEnumRecognizer::new(
+                                <#value_ty as #root::read::RecognizerReadable>::make_attr_recognizer(),
+                                #select_untagged_name #turbofish,
+                            )
+                        }
+
+                        #[inline]
+                        fn make_body_recognizer() -> Self::BodyRec {
+                            #root::read::UntaggedEnumRecognizer::new(
+                                <#value_ty as #root::read::RecognizerReadable>::make_body_recognizer(),
+                                #select_untagged_name #turbofish,
+                            )
+                        }
+                    }
+                }
+            } else {
+                quote! {
                     #[automatically_derived]
                     #[allow(non_snake_case)]
                     impl #impl_gen #root::read::RecognizerReadable for #name #type_gen
@@ -361,6 +409,25 @@ impl<'a> ToTokens for DeriveStructuralReadable<'a, SegregatedEnumModel<'a>> {
                             <Self as #root::read::RecognizerReadable>::make_recognizer()
                         }
                     }
+                }
+            };
+
+            tokens.append_all(quote! {
+                const _: () = {
+                    #(#variant_functions)*
+
+                    #state
+
+                    #[automatically_derived]
+                    #[allow(non_snake_case)]
+                    #[inline]
+                    fn #select_var_name #impl_gen(name: &str) -> ::core::option::Option<#builder_name #type_gen>
+                    #where_clause
+                    {
+                         #select_var
+                    }
+
+                    #readable_impl
                 };
             });
         }
@@ -605,11 +672,16 @@ fn header_on_reset_name() -> syn::Ident {
 }
 
 const SELECT_VAR_NAME: &str = "select_variant";
+const SELECT_UNTAGGED_NAME: &str = "select_untagged";
 
 fn select_var_name() -> syn::Ident {
     syn::Ident::new(SELECT_VAR_NAME, Span::call_site())
 }
 
+fn select_untagged_name() -> syn::Ident {
+    syn::Ident::new(SELECT_UNTAGGED_NAME, Span::call_site())
+}
+
 const BUILDER_NAME: &str = "Builder";
 const HEADER_BUILDER_NAME: &str = "HeaderBuilder";
 
@@ -895,6 +967,17 @@ impl<'a> ToTokens for SelectFeedFn<'a> {
     }
 }
 
+/// The value to use for a field that was absent from the record (this will be `None` if the field
+/// is required).
+fn on_absent(root: &syn::Path, fld: &FieldModel) -> TokenStream {
+    let ty = fld.field_ty;
+    if fld.default {
+        quote!(::core::option::Option::Some(<#ty as ::core::default::Default>::default()))
+    } else {
+        quote!(<#ty as #root::read::RecognizerReadable>::on_absent())
+    }
+}
+
 struct OnDoneFn<'a> {
     fields: &'a SegregatedStructModel<'a>,
     constructor: syn::Path,
@@ -935,11 +1018,11 @@ impl<'a> ToTokens for OnDoneFn<'a> {
                     match tag_body {
                         Some(fld) if header_fields.is_empty() => {
                             let name = fld.resolve_name();
-                            let ty = fld.field_ty;
+                            let on_absent = on_absent(root, fld);
 
                             quote! {
                                 if fields.#idx.is_none() {
-                                    fields.#idx = <#ty as #root::read::RecognizerReadable>::on_absent();
+                                    fields.#idx = #on_absent;
                                     if fields.#idx.is_none() {
                                         missing.push(#root::model::Text::new(#name));
                                     }
@@ -954,11 +1037,11 @@ impl<'a> ToTokens for OnDoneFn<'a> {
                             ow.iter().chain(header_fields.iter()).enumerate().fold(acc, |mut out, (j, fld)| {
                                 let inner_idx = syn::Index::from(j);
                                 let name = fld.resolve_name();
-                                let ty = fld.field_ty;
+                                let on_absent = on_absent(root, fld);
 
                                 out.append_all(quote! {
                                     if header.#inner_idx.is_none() {
-                                        header.#inner_idx = <#ty as #root::read::RecognizerReadable>::on_absent();
+                                        header.#inner_idx = #on_absent;
                                         if header.#inner_idx.is_none() {
                                             missing.push(#root::model::Text::new(#name));
                                         }
@@ -971,11 +1054,11 @@ impl<'a> ToTokens for OnDoneFn<'a> {
                 }
                 FieldGroup::Attribute(fld) | FieldGroup::Item(fld) | FieldGroup::DelegateBody(fld) => {
                     let name = fld.resolve_name();
-                    let ty = fld.field_ty;
+                    let on_absent = on_absent(root, fld);
 
                     quote! {
                         if fields.#idx.is_none() {
-                            fields.#idx = <#ty as #root::read::RecognizerReadable>::on_absent();
+                            fields.#idx = #on_absent;
                             if fields.#idx.is_none() {
                                 missing.push(#root::model::Text::new(#name));
                             }
@@ -1388,6 +1471,72 @@ impl<'a> ToTokens for SelectVariantFn<'a> {
     }
 }
 
+/// Generates the body of the function that selects the variant of an untagged enum from a
+/// [`swimos_model::Value`]. Each variant is attempted in the order in which they are defined.
+struct SelectUntaggedFn<'a> {
+    model: &'a SegregatedEnumModel<'a>,
+    gen_params: &'a TypeGenerics<'a>,
+}
+
+impl<'a> SelectUntaggedFn<'a> {
+    fn new(model: &'a SegregatedEnumModel<'a>, gen_params: &'a TypeGenerics<'a>) -> Self {
+        SelectUntaggedFn { model, gen_params }
+    }
+}
+
+impl<'a> ToTokens for SelectUntaggedFn<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let SelectUntaggedFn {
+            model: SegregatedEnumModel { inner, variants },
+            gen_params,
+        } = self;
+
+        let name = inner.name;
+        let root = inner.root;
+        let builder_name = builder_ident();
+        let select_var_name = select_var_name();
+        let turbofish = gen_params.as_turbofish();
+
+        let attempts = variants.iter().map(|var| {
+            let var_name = var.inner.name;
+            let fields_model = &var.inner.fields_model;
+            match fields_model.untagged_value_field() {
+                Some(field) if fields_model.type_kind != CompoundTypeKind::Unit => {
+                    let ty = field.field_ty;
+                    let constructor = match &field.selector {
+                        FieldSelector::Named(id) => quote!(#name::#var_name { #id: v }),
+                        FieldSelector::Ordinal(_) => quote!(#name::#var_name(v)),
+                    };
+                    quote! {
+                        if let ::core::result::Result::Ok(v) = <#ty as #root::read::RecognizerReadable>::try_interpret_structure(&value) {
+                            return ::core::result::Result::Ok(#constructor);
+                        }
+                    }
+                }
+                _ => {
+                    let lit_name = var.inner.resolve_name();
+                    quote! {
+                        if let ::core::option::Option::Some(::core::result::Result::Ok(v)) = #root::read::read_untagged_variant(
+                            #root::read::TaggedEnumRecognizer::<#builder_name #gen_params>::new(#select_var_name #turbofish),
+                            #lit_name,
+                            &value,
+                        ) {
+                            return ::core::result::Result::Ok(v);
+                        }
+                    }
+                }
+            }
+        });
+
+        tokens.append_all(quote! {
+            #(#attempts)*
+            ::core::result::Result::Err(#root::read::ReadError::Message(
+                #root::model::Text::new("The value does not match any of the variants of the untagged enum."),
+            ))
+        });
+    }
+}
+
 struct EnumState<'a> {
     model: &'a SegregatedEnumModel<'a>,
     gen_params: &'a TypeGenerics<'a>,
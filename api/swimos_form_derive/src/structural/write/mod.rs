@@ -24,6 +24,10 @@ use quote::ToTokens;
 use swimos_macro_utilities::CompoundTypeKind;
 use syn::{Generics, Pat, Path};
 
+mod schema;
+
+use schema::{EnumSchema, StructSchema};
+
 /// Implements the StructuralWritable trait for either of [`SegregatedStructModel`] or
 /// [`SegregatedEnumModel`].
 pub struct DeriveStructuralWritable<'a, S>(pub S, pub &'a Generics);
@@ -48,15 +52,25 @@ impl<'a> Destructure<'a> {
     }
 }
 
-struct WriteWithFn<'a>(&'a SegregatedStructModel<'a>);
-struct WriteIntoFn<'a>(&'a SegregatedStructModel<'a>);
+/// Generates the body of `write_with` for a struct or enum variant. The flag indicates whether
+/// the tag attribute should be written (it is omitted for the variants of untagged enums).
+struct WriteWithFn<'a>(&'a SegregatedStructModel<'a>, bool);
+/// Generates the body of `write_into` for a struct or enum variant. The flag indicates whether
+/// the tag attribute should be written (it is omitted for the variants of untagged enums).
+struct WriteIntoFn<'a>(&'a SegregatedStructModel<'a>, bool);
 
 impl<'a> ToTokens for DeriveStructuralWritable<'a, SegregatedEnumModel<'a>> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let DeriveStructuralWritable(model, generics) = self;
         let SegregatedEnumModel { inner, variants } = model;
-        let EnumModel { root, name, .. } = inner;
+        let EnumModel {
+            root,
+            name,
+            untagged,
+            ..
+        } = inner;
         let writer_trait = make_writer_trait(root);
+        let schema = EnumSchema(model);
 
         let mut new_generics = (*generics).clone();
         super::add_bounds(
@@ -90,30 +104,80 @@ impl<'a> ToTokens for DeriveStructuralWritable<'a, SegregatedEnumModel<'a>> {
                     fn write_into<__W: #writer_trait>(self, _writer: __W) -> ::core::result::Result<__W::Repr, __W::Error> {
                         match self {}
                     }
+
+                    fn schema() -> #root::schema::Schema {
+                        #schema
+                    }
                 }
             }
         } else {
             let name = inner.name;
             let write_with_cases = variants.iter().map(|v| {
                 let destructure = Destructure::variant_match(v.inner);
-                let write_with = WriteWithFn(v);
-                let num_attrs = num_attributes_case(v, true);
-                quote! {
-                    #name::#destructure => {
-                        let num_attrs = #num_attrs;
-                        #write_with
+                match untagged_case(v, *untagged) {
+                    UntaggedCase::Tagged => {
+                        let write_with = WriteWithFn(v, true);
+                        let num_attrs = num_attributes_case(v, true, true);
+                        quote! {
+                            #name::#destructure => {
+                                let num_attrs = #num_attrs;
+                                #write_with
+                            }
+                        }
+                    }
+                    UntaggedCase::Unit => quote! {
+                        #name::#destructure => writer.write_extant(),
+                    },
+                    UntaggedCase::Value(field) => {
+                        let field_index = &field.selector;
+                        quote! {
+                            #name::#destructure => #root::write::StructuralWritable::write_with(#field_index, writer),
+                        }
+                    }
+                    UntaggedCase::Record => {
+                        let write_with = WriteWithFn(v, false);
+                        let num_attrs = num_attributes_case(v, true, false);
+                        quote! {
+                            #name::#destructure => {
+                                let num_attrs = #num_attrs;
+                                #write_with
+                            }
+                        }
                     }
                 }
             });
 
             let write_into_cases = variants.iter().map(|v| {
                 let destructure = Destructure::variant_match(v.inner);
-                let write_into = WriteIntoFn(v);
-                let num_attrs = num_attributes_case(v, false);
-                quote! {
-                    #name::#destructure => {
-                        let num_attrs = #num_attrs;
-                        #write_into
+                match untagged_case(v, *untagged) {
+                    UntaggedCase::Tagged => {
+                        let write_into = WriteIntoFn(v, true);
+                        let num_attrs = num_attributes_case(v, false, true);
+                        quote! {
+                            #name::#destructure => {
+                                let num_attrs = #num_attrs;
+                                #write_into
+                            }
+                        }
+                    }
+                    UntaggedCase::Unit => quote! {
+                        #name::#destructure => writer.write_extant(),
+                    },
+                    UntaggedCase::Value(field) => {
+                        let field_index = &field.selector;
+                        quote! {
+                            #name::#destructure => #root::write::StructuralWritable::write_into(#field_index, writer),
+                        }
+                    }
+                    UntaggedCase::Record => {
+                        let write_into = WriteIntoFn(v, false);
+                        let num_attrs = num_attributes_case(v, false, false);
+                        quote! {
+                            #name::#destructure => {
+                                let num_attrs = #num_attrs;
+                                #write_into
+                            }
+                        }
                     }
                 }
             });
@@ -148,6 +212,10 @@ impl<'a> ToTokens for DeriveStructuralWritable<'a, SegregatedEnumModel<'a>> {
                             #(#write_into_cases)*
                         }
                     }
+
+                    fn schema() -> #root::schema::Schema {
+                        #schema
+                    }
                 }
             }
         };
@@ -172,6 +240,7 @@ impl<'a> ToTokens for DeriveStructuralWritable<'a, SegregatedStructModel<'a>> {
 
         let name = inner.inner.name;
         let writer_trait = make_writer_trait(root);
+        let schema = StructSchema(inner);
 
         let (write_with, write_into, num_attrs) =
             if let Some(selector) = inner.inner.newtype_selector() {
@@ -182,8 +251,8 @@ impl<'a> ToTokens for DeriveStructuralWritable<'a, SegregatedStructModel<'a>> {
                 )
             } else {
                 (
-                    WriteWithFn(inner).to_token_stream(),
-                    WriteIntoFn(inner).to_token_stream(),
+                    WriteWithFn(inner, true).to_token_stream(),
+                    WriteIntoFn(inner, true).to_token_stream(),
                     num_attributes(inner).to_token_stream(),
                 )
             };
@@ -216,6 +285,10 @@ impl<'a> ToTokens for DeriveStructuralWritable<'a, SegregatedStructModel<'a>> {
                     let #destructure = self;
                     #write_into
                 }
+
+                fn schema() -> #root::schema::Schema {
+                    #schema
+                }
             }
         };
 
@@ -299,7 +372,7 @@ fn compute_num_slots(root: &syn::Path, fields: &[&FieldModel], by_ref: bool) ->
 
 impl<'a> ToTokens for WriteWithFn<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let WriteWithFn(model) = self;
+        let WriteWithFn(model, tagged) = self;
         let SegregatedStructModel { inner, fields } = model;
         let StructModel { fields_model, .. } = inner;
         let SegregatedFields { header, body } = fields;
@@ -320,7 +393,9 @@ impl<'a> ToTokens for WriteWithFn<'a> {
             inner.resolve_name().to_token_stream()
         };
 
-        let tag_statement = if header_fields.is_empty() {
+        let tag_statement = if !*tagged {
+            quote!()
+        } else if header_fields.is_empty() {
             if let Some(tag_field) = tag_body.as_ref() {
                 let field_index = &tag_field.selector;
                 quote! {
@@ -421,7 +496,7 @@ fn make_header(
 
 impl<'a> ToTokens for WriteIntoFn<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let WriteIntoFn(model) = self;
+        let WriteIntoFn(model, tagged) = self;
         let SegregatedStructModel { inner, fields } = model;
         let StructModel { fields_model, .. } = inner;
         let SegregatedFields { header, body } = fields;
@@ -442,7 +517,9 @@ impl<'a> ToTokens for WriteIntoFn<'a> {
             inner.resolve_name().to_token_stream()
         };
 
-        let tag_statement = if header_fields.is_empty() {
+        let tag_statement = if !*tagged {
+            quote!()
+        } else if header_fields.is_empty() {
             if let Some(tag_field) = tag_body.as_ref() {
                 let field_index = &tag_field.selector;
                 quote! {
@@ -554,8 +631,12 @@ fn num_attributes<'a>(model: &'a SegregatedStructModel<'a>) -> TokenStream {
     }
 }
 
-fn num_attributes_case<'a>(model: &'a SegregatedStructModel<'a>, by_ref: bool) -> TokenStream {
-    let base_attrs = model.fields.header.attributes.len() + 1;
+fn num_attributes_case<'a>(
+    model: &'a SegregatedStructModel<'a>,
+    by_ref: bool,
+    tagged: bool,
+) -> TokenStream {
+    let base_attrs = model.fields.header.attributes.len() + usize::from(tagged);
     if let BodyFields::ReplacedBody(fld) = model.fields.body {
         let name = &fld.selector;
         let body_fld = if by_ref {
@@ -583,7 +664,20 @@ impl<'a> ToTokens for NumAttrsEnum<'a> {
 
         let cases = variants.iter().map(|v| {
             let var_name = v.inner.name;
-            let base_attrs = v.fields.header.attributes.len() + 1;
+            let case = untagged_case(v, inner.untagged);
+            if let UntaggedCase::Value(fld) = case {
+                let fld_name = &fld.selector;
+                let binder = fld_name.binder();
+                let pat = match fld_name {
+                    FieldSelector::Named(_) => quote!(#enum_name::#var_name { #binder }),
+                    FieldSelector::Ordinal(_) => quote!(#enum_name::#var_name(#binder)),
+                };
+                return quote!(#pat => #root::write::StructuralWritable::num_attributes(#fld_name));
+            }
+            let base_attrs = match case {
+                UntaggedCase::Tagged => v.fields.header.attributes.len() + 1,
+                _ => v.fields.header.attributes.len(),
+            };
             if let BodyFields::ReplacedBody(fld) = v.fields.body {
                 let fld_name = &fld.selector;
                 let binder = fld_name.binder();
@@ -611,3 +705,28 @@ impl<'a> ToTokens for NumAttrsEnum<'a> {
         });
     }
 }
+
+/// How a variant of an enum is written.
+enum UntaggedCase<'a> {
+    /// The variant is written as a record with its tag as the first attribute.
+    Tagged,
+    /// The variant is a unit variant of an untagged enum and is written as extant.
+    Unit,
+    /// The variant of an untagged enum has a single value and is written as that value.
+    Value(&'a FieldModel<'a>),
+    /// The variant of an untagged enum is written as a record with no tag.
+    Record,
+}
+
+fn untagged_case<'a>(model: &'a SegregatedStructModel<'a>, untagged: bool) -> UntaggedCase<'a> {
+    let fields_model = &model.inner.fields_model;
+    if !untagged {
+        UntaggedCase::Tagged
+    } else if fields_model.type_kind == CompoundTypeKind::Unit {
+        UntaggedCase::Unit
+    } else if let Some(field) = fields_model.untagged_value_field() {
+        UntaggedCase::Value(field)
+    } else {
+        UntaggedCase::Record
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{untagged_case, UntaggedCase};
+use crate::quote::TokenStreamExt;
+use crate::structural::model::enumeration::SegregatedEnumModel;
+use crate::structural::model::field::{BodyFields, FieldModel, HeaderFields, SegregatedFields};
+use crate::structural::model::record::{SegregatedStructModel, StructModel};
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use swimos_macro_utilities::{CompoundTypeKind, FieldKind};
+
+/// Generates an expression for the schema of the structure written by the derived
+/// implementation of `StructuralWritable` for a struct.
+pub struct StructSchema<'a>(pub &'a SegregatedStructModel<'a>);
+
+/// Generates an expression for the schema of the structure written by the derived
+/// implementation of `StructuralWritable` for an enum (the union of the schemas of its variants).
+pub struct EnumSchema<'a>(pub &'a SegregatedEnumModel<'a>);
+
+fn field_schema(root: &syn::Path, field: &FieldModel) -> TokenStream {
+    let ty = field.field_ty;
+    quote!(<#ty as #root::write::StructuralWritable>::schema())
+}
+
+fn named_field_schema(root: &syn::Path, field: &FieldModel) -> TokenStream {
+    let literal_name = field.resolve_name();
+    let mut schema = field_schema(root, field);
+    if field.default {
+        // Fields with a default value may be omitted.
+        schema = quote!(#root::schema::Schema::optional(#schema));
+    }
+    quote!(#root::schema::FieldSchema::new(#literal_name, #schema))
+}
+
+fn record_schema(model: &SegregatedStructModel, tagged: bool) -> TokenStream {
+    let SegregatedStructModel { inner, fields } = model;
+    let StructModel {
+        root, fields_model, ..
+    } = inner;
+    let SegregatedFields { header, body } = fields;
+    let HeaderFields {
+        tag_name,
+        tag_body,
+        header_fields,
+        attributes,
+    } = header;
+
+    let tag_name = if tag_name.is_some() {
+        quote!(::core::option::Option::None)
+    } else {
+        let name = inner.resolve_name();
+        quote!(::core::option::Option::Some(#root::model::Text::new(#name)))
+    };
+
+    let tag_body = if header_fields.is_empty() {
+        if let Some(field) = tag_body {
+            field_schema(root, field)
+        } else {
+            quote!(#root::schema::Schema::OfKind(#root::model::ValueKind::Extant))
+        }
+    } else {
+        let values = tag_body.iter().map(|field| field_schema(root, field));
+        let slots = header_fields
+            .iter()
+            .map(|field| named_field_schema(root, field));
+        quote! {
+            #root::schema::Schema::Record(#root::schema::RecordSchema::fields(
+                ::std::vec![#(#values),*],
+                ::std::vec![#(#slots),*],
+            ))
+        }
+    };
+
+    let attrs = attributes
+        .iter()
+        .map(|field| named_field_schema(root, field));
+
+    let body = match body {
+        BodyFields::ReplacedBody(field) => {
+            let schema = field_schema(root, field);
+            quote!(#root::schema::BodySchema::Delegate(::std::boxed::Box::new(#schema)))
+        }
+        BodyFields::StdBody(fields) => {
+            let (values, slots) = if fields_model.body_kind == CompoundTypeKind::Labelled {
+                let slots = fields.iter().map(|field| named_field_schema(root, field));
+                (quote!(), quote!(#(#slots),*))
+            } else {
+                let values = fields.iter().map(|field| field_schema(root, field));
+                (quote!(#(#values),*), quote!())
+            };
+            quote! {
+                #root::schema::BodySchema::Fields {
+                    values: ::std::vec![#values],
+                    slots: ::std::vec![#slots],
+                }
+            }
+        }
+    };
+
+    let tag = if tagged {
+        quote! {
+            ::core::option::Option::Some(#root::schema::TagSchema {
+                name: #tag_name,
+                body: ::std::boxed::Box::new(#tag_body),
+            })
+        }
+    } else {
+        quote!(::core::option::Option::None)
+    };
+
+    quote! {
+        #root::schema::Schema::Record(#root::schema::RecordSchema {
+            tag: #tag,
+            attrs: ::std::vec![#(#attrs),*],
+            body: #body,
+        })
+    }
+}
+
+impl<'a> ToTokens for StructSchema<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let StructSchema(model) = self;
+        let root = model.inner.root;
+        let schema = if model.inner.newtype_selector().is_some() {
+            let field = model
+                .inner
+                .fields_model
+                .fields
+                .iter()
+                .find(|field| field.directive != FieldKind::Skip)
+                .map(|field| &field.model);
+            if let Some(field) = field {
+                field_schema(root, field)
+            } else {
+                quote!(#root::schema::Schema::Anything)
+            }
+        } else {
+            record_schema(model, true)
+        };
+        tokens.append_all(quote! {
+            #root::schema::derived_schema::<Self>(|| #schema)
+        });
+    }
+}
+
+impl<'a> ToTokens for EnumSchema<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let EnumSchema(SegregatedEnumModel { inner, variants }) = self;
+        let root = inner.root;
+        let schema = if variants.is_empty() {
+            quote!(#root::schema::Schema::Nothing)
+        } else {
+            let variants =
+                variants
+                    .iter()
+                    .map(|variant| match untagged_case(variant, inner.untagged) {
+                        UntaggedCase::Tagged => record_schema(variant, true),
+                        UntaggedCase::Unit => {
+                            quote!(#root::schema::Schema::OfKind(#root::model::ValueKind::Extant))
+                        }
+                        UntaggedCase::Value(field) => field_schema(root, field),
+                        UntaggedCase::Record => record_schema(variant, false),
+                    });
+            quote!(#root::schema::Schema::Or(::std::vec![#(#variants),*]))
+        };
+        tokens.append_all(quote! {
+            #root::schema::derived_schema::<Self>(|| #schema)
+        });
+    }
+}
@@ -20,4 +20,6 @@ mod meta;
 
 pub use meta::lane::LaneInfo;
 pub use meta::log::{LogEntry, LogLevel};
-pub use meta::uplink::{LanePulse, NodePulse, WarpUplinkPulse};
+pub use meta::prune::RemotePruned;
+pub use meta::topology::NodeTopology;
+pub use meta::uplink::{LanePulse, MeshPulse, NodePulse, WarpUplinkPulse};
@@ -35,17 +35,36 @@ pub enum LogLevel {
 }
 
 /// A single log entry.
-#[derive(Clone, Debug, Form)]
+#[derive(Clone, Debug, PartialEq, Form)]
 pub struct LogEntry {
     /// Timestamp of when this entry was created.
-    time: Timestamp,
+    pub time: Timestamp,
     /// The body of the entry.
-    message: Value,
+    pub message: Value,
     /// The coarseness of this entry.
     #[form(tag)]
-    level: LogLevel,
+    pub level: LogLevel,
     /// The node URI that produced this entry.
-    node: Text,
+    pub node: Text,
     /// The lane URI that produced this entry.
-    lane: Text,
+    pub lane: Text,
+}
+
+impl LogEntry {
+    /// Create an entry, timestamped with the current time.
+    ///
+    /// # Arguments
+    /// * `level` - The coarseness of the entry.
+    /// * `node` - The node URI that produced the entry.
+    /// * `lane` - The lane URI that produced the entry.
+    /// * `message` - The body of the entry.
+    pub fn new(level: LogLevel, node: Text, lane: Text, message: impl Into<Value>) -> Self {
+        LogEntry {
+            time: Timestamp::now(),
+            message: message.into(),
+            level,
+            node,
+            lane,
+        }
+    }
 }
@@ -14,4 +14,6 @@
 
 pub mod lane;
 pub mod log;
+pub mod prune;
+pub mod topology;
 pub mod uplink;
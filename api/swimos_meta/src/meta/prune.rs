@@ -0,0 +1,27 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use swimos_form::Form;
+use swimos_model::Text;
+
+/// Record of a remote that was pruned from an agent as it had no links.
+#[derive(Form, Clone, PartialEq, Eq, Debug)]
+pub struct RemotePruned {
+    /// The routing ID of the remote.
+    #[form(name = "remoteId")]
+    pub remote_id: Text,
+    /// The time (in milliseconds) for which the remote had no links before it was pruned.
+    #[form(name = "idleTime")]
+    pub idle_time: u64,
+}
@@ -0,0 +1,40 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use swimos_form::Form;
+use swimos_model::Text;
+
+/// An entry in the topology of a server, describing a single running agent. The entries can be
+/// retrieved by syncing with the `topology` lane of the mesh meta-agent (`swimos:meta:mesh`).
+#[derive(Debug, Clone, PartialEq, Eq, Form)]
+pub struct NodeTopology {
+    /// The node URI of the agent.
+    #[form(name = "nodeUri")]
+    pub node_uri: Text,
+    /// The route pattern that the node URI of the agent matched.
+    pub route: Text,
+    /// The time at which the agent started (in milliseconds since the UNIX epoch).
+    pub created: i64,
+    /// The time for which the agent has been running (in milliseconds).
+    pub uptime: u64,
+    /// The number of active uplinks, across all lanes of the agent.
+    #[form(name = "linkCount")]
+    pub link_count: u64,
+    /// The number of lanes of the agent.
+    #[form(name = "laneCount")]
+    pub lane_count: u64,
+    /// An estimate of the memory (in bytes) reserved by the runtime for the buffers of the lanes
+    /// of the agent.
+    pub memory: u64,
+}
@@ -41,10 +41,24 @@ pub struct NodePulse {
     pub uplinks: WarpUplinkPulse,
 }
 
+/// A mesh pulse summarizing the agents that are running on a server.
+#[derive(Default, Form, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct MeshPulse {
+    /// The number of agents that are running.
+    #[form(name = "agentCount")]
+    pub agent_count: u64,
+    /// The total number of uplinks, across all of the running agents.
+    #[form(name = "linkCount")]
+    pub link_count: u64,
+}
+
 /// Accumulated metric associated with a lane.
 #[derive(Default, Form, Clone, PartialEq, Eq, Debug)]
 pub struct LanePulse {
     /// Accumulated WARP uplink pulse.
     #[form(name = "uplinkPulse")]
     pub uplink_pulse: WarpUplinkPulse,
+    /// The time (in milliseconds since the UNIX epoch) at which the lane last generated an event.
+    #[form(name = "lastEvent")]
+    pub last_event: Option<u64>,
 }
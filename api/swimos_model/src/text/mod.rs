@@ -19,12 +19,15 @@ use bytes::Bytes;
 use http::uri::{InvalidUri, Uri};
 use std::borrow::{Borrow, BorrowMut, Cow};
 use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
 use std::convert::{Infallible, TryFrom};
 use std::fmt::{Debug, Display, Formatter};
-use std::hash::{Hash, Hasher};
+use std::hash::{BuildHasher, Hash, Hasher};
 use std::iter::FromIterator;
 use std::str;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
 use swimos_utilities::encoding::TryFromUtf8Bytes;
 use swimos_utilities::routing::{InvalidRouteUri, RouteUri};
 
@@ -33,14 +36,119 @@ const SMALL_SIZE: usize = 3 * std::mem::size_of::<usize>();
 enum TextInner {
     Small(usize, [u8; SMALL_SIZE]),
     Large(String),
+    Shared(Arc<str>),
 }
 
 /// A container for a UTF-8 string that has a small string optimization for strings consisting of
 /// `SMALL_SIZE` bytes (allowing such strings to be held entirely within the object rather than
 /// requiring a separate allocation. This can be used in exactly the same way as [`String`] in
 /// most circumstances.
+///
+/// Larger strings that are used repeatedly (for example, the names of nodes and lanes) can be
+/// interned with [`Text::interned`] so that creating and cloning them does not allocate.
 pub struct Text(TextInner);
 
+const INTERNER_SHARDS: usize = 16;
+const MIN_SWEEP_LEN: usize = 64;
+
+/// Set of interned strings. The interner only holds weak references so the characters are freed
+/// when the last [`Text`] that shares them is dropped. The strings are split between a number of
+/// shards, selected by hash, so that threads interning different strings rarely contend.
+struct Interner {
+    hasher: RandomState,
+    shards: [Mutex<InternerShard>; INTERNER_SHARDS],
+}
+
+/// The interned strings, grouped by hash. Dead references are removed from a group when it is
+/// next accessed and the whole shard is swept whenever the number of references it holds doubles,
+/// so the size of the shard is bounded by twice the number of live strings.
+#[derive(Default)]
+struct InternerShard {
+    atoms: HashMap<u64, Vec<Weak<str>>>,
+    len: usize,
+    sweep_at: usize,
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Interner {
+            hasher: RandomState::new(),
+            shards: std::array::from_fn(|_| Default::default()),
+        }
+    }
+}
+
+impl Interner {
+    fn global() -> &'static Interner {
+        static INTERNER: OnceLock<Interner> = OnceLock::new();
+        INTERNER.get_or_init(Default::default)
+    }
+
+    fn intern(&self, string: &str) -> Arc<str> {
+        let hash = self.hasher.hash_one(string);
+        let mut shard = match self.shards[hash as usize % INTERNER_SHARDS].lock() {
+            Ok(shard) => shard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        shard.intern(hash, string)
+    }
+
+    /// The number of references held by the interner (including any that are dead but have not
+    /// yet been removed).
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| match shard.lock() {
+                Ok(shard) => shard.len,
+                Err(poisoned) => poisoned.into_inner().len,
+            })
+            .sum()
+    }
+}
+
+impl InternerShard {
+    fn intern(&mut self, hash: u64, string: &str) -> Arc<str> {
+        let InternerShard {
+            atoms,
+            len,
+            sweep_at,
+        } = self;
+        let group = atoms.entry(hash).or_default();
+        let before = group.len();
+        group.retain(|atom| atom.strong_count() > 0);
+        *len -= before - group.len();
+        if let Some(atom) = group
+            .iter()
+            .filter_map(Weak::upgrade)
+            .find(|atom| &**atom == string)
+        {
+            return atom;
+        }
+        let atom: Arc<str> = Arc::from(string);
+        group.push(Arc::downgrade(&atom));
+        *len += 1;
+        if *len > *sweep_at {
+            self.sweep();
+        }
+        atom
+    }
+
+    fn sweep(&mut self) {
+        let InternerShard {
+            atoms,
+            len,
+            sweep_at,
+        } = self;
+        atoms.retain(|_, group| {
+            group.retain(|atom| atom.strong_count() > 0);
+            !group.is_empty()
+        });
+        *len = atoms.values().map(Vec::len).sum();
+        *sweep_at = (2 * *len).max(MIN_SWEEP_LEN);
+    }
+}
+
 impl Text {
     /// Create a new [`Text`] instance from UTF-8 characters.
     pub fn new(string: &str) -> Self {
@@ -57,21 +165,36 @@ impl Text {
         string.into()
     }
 
+    /// Create a [`Text`] instance that shares a single, interned copy of its characters with all
+    /// other live instances that were interned with the same characters. Strings that fit in the
+    /// small representation are not interned. The characters are freed when the last instance
+    /// that shares them is dropped.
+    pub fn interned(string: &str) -> Self {
+        if string.len() <= SMALL_SIZE {
+            return small_from_str(string);
+        }
+        Text(TextInner::Shared(Interner::global().intern(string)))
+    }
+
     /// Borrow the characters stored in the [`Text`].
     pub fn as_str(&self) -> &str {
         let Text(inner) = self;
         match inner {
             TextInner::Small(len, bytes) => small_str(*len, bytes),
             TextInner::Large(str) => str.borrow(),
+            TextInner::Shared(str) => str,
         }
     }
 
-    /// Mutably borrow the characters stored in the [`Text`].
+    /// Mutably borrow the characters stored in the [`Text`]. If the characters are shared, they
+    /// will first be copied.
     pub fn as_str_mut(&mut self) -> &mut str {
+        self.make_unique();
         let Text(inner) = self;
         match inner {
             TextInner::Small(len, bytes) => small_str_mut(*len, bytes),
             TextInner::Large(str) => str.borrow_mut(),
+            TextInner::Shared(_) => unreachable!("Text was made unique."),
         }
     }
 
@@ -81,6 +204,7 @@ impl Text {
         match inner {
             TextInner::Small(len, _) => *len,
             TextInner::Large(string) => string.len(),
+            TextInner::Shared(string) => string.len(),
         }
     }
 
@@ -92,14 +216,27 @@ impl Text {
     /// Whether the [`Text`] is contained entirely in this object.
     pub fn is_small(&self) -> bool {
         let Text(inner) = self;
-        match inner {
-            TextInner::Small(_, _) => true,
-            TextInner::Large(_) => false,
+        matches!(inner, TextInner::Small(_, _))
+    }
+
+    /// Whether the characters of the [`Text`] are shared with other instances (see
+    /// [`Text::interned`]).
+    pub fn is_shared(&self) -> bool {
+        let Text(inner) = self;
+        matches!(inner, TextInner::Shared(_))
+    }
+
+    /// Replace a shared representation with an owned copy of the characters.
+    fn make_unique(&mut self) {
+        if let Text(TextInner::Shared(string)) = self {
+            let string = String::from(&**string);
+            *self = Text(TextInner::Large(string));
         }
     }
 
     /// Append a character to this [`Text`].
     pub fn push(&mut self, ch: char) {
+        self.make_unique();
         let Text(inner) = self;
         match inner {
             TextInner::Small(len, arr) => {
@@ -116,11 +253,13 @@ impl Text {
             TextInner::Large(string) => {
                 string.push(ch);
             }
+            TextInner::Shared(_) => unreachable!("Text was made unique."),
         }
     }
 
     /// Append a sequence of characters to this [`Text`].
     pub fn push_str(&mut self, string: &str) {
+        self.make_unique();
         let Text(inner) = self;
         match inner {
             TextInner::Small(len, arr) => {
@@ -137,6 +276,7 @@ impl Text {
             TextInner::Large(large_string) => {
                 large_string.push_str(string);
             }
+            TextInner::Shared(_) => unreachable!("Text was made unique."),
         }
     }
 
@@ -146,6 +286,7 @@ impl Text {
         match inner {
             TextInner::Small(len, arr) => &arr[..*len],
             TextInner::Large(str) => str.as_bytes(),
+            TextInner::Shared(str) => str.as_bytes(),
         }
     }
 
@@ -183,6 +324,7 @@ impl From<Text> for String {
         match inner {
             TextInner::Small(len, arr) => small_str(len, &arr).to_string(),
             TextInner::Large(string) => string,
+            TextInner::Shared(string) => string.to_string(),
         }
     }
 }
@@ -385,6 +527,7 @@ impl Hash for Text {
 enum TextKind {
     Small,
     Large,
+    Shared,
 }
 
 impl Debug for Text {
@@ -401,6 +544,11 @@ impl Debug for Text {
                 .field(&TextKind::Large)
                 .field(string)
                 .finish(),
+            TextInner::Shared(string) => f
+                .debug_tuple("Text")
+                .field(&TextKind::Shared)
+                .field(string)
+                .finish(),
         }
     }
 }
@@ -423,6 +571,7 @@ impl Clone for Text {
         match inner {
             TextInner::Small(len, arr) => Text(TextInner::Small(*len, *arr)),
             TextInner::Large(string) => Text(TextInner::Large(string.clone())),
+            TextInner::Shared(string) => Text(TextInner::Shared(string.clone())),
         }
     }
 
@@ -441,6 +590,9 @@ impl Clone for Text {
             (ref mut ow, TextInner::Large(other)) => {
                 **ow = TextInner::Large(other.clone());
             }
+            (ref mut ow, TextInner::Shared(other)) => {
+                **ow = TextInner::Shared(other.clone());
+            }
         }
     }
 }
@@ -449,6 +601,7 @@ impl Extend<char> for Text {
     fn extend<T: IntoIterator<Item = char>>(&mut self, iter: T) {
         let it = iter.into_iter();
         let (min, max) = it.size_hint();
+        self.make_unique();
         let Text(inner) = self;
         match inner {
             TextInner::Small(len, arr) => {
@@ -471,6 +624,7 @@ impl Extend<char> for Text {
                     string.push(ch);
                 }
             }
+            TextInner::Shared(_) => unreachable!("Text was made unique."),
         }
     }
 }
@@ -558,6 +712,7 @@ impl TryFrom<Text> for Uri {
         match value {
             Text(TextInner::Large(string)) => Uri::try_from(string),
             Text(TextInner::Small(len, bytes)) => Uri::try_from(small_str(len, &bytes)),
+            Text(TextInner::Shared(string)) => Uri::try_from(&*string),
         }
     }
 }
@@ -569,6 +724,7 @@ impl TryFrom<Text> for RouteUri {
         match value {
             Text(TextInner::Large(string)) => RouteUri::try_from(string),
             Text(TextInner::Small(len, bytes)) => RouteUri::try_from(small_str(len, &bytes)),
+            Text(TextInner::Shared(string)) => RouteUri::try_from(&*string),
         }
     }
 }
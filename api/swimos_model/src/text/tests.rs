@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::text::{Text, TextInner, SMALL_SIZE};
+use crate::text::{Interner, Text, TextInner, INTERNER_SHARDS, MIN_SWEEP_LEN, SMALL_SIZE};
 use std::borrow::{Borrow, BorrowMut};
 use std::cmp::Ordering;
 use std::collections::hash_map::DefaultHasher;
@@ -753,3 +753,64 @@ fn collect_chars() {
     let text: Text = chars.iter().collect();
     assert_eq!(text, "extra");
 }
+
+#[test]
+fn interned_text() {
+    let first = Text::interned(LARGE);
+    let second = Text::interned(LARGE);
+    assert!(first.is_shared());
+    assert_eq!(first, LARGE);
+    assert_eq!(first, second);
+    assert!(std::ptr::eq(first.as_str(), second.as_str()));
+    assert!(std::ptr::eq(first.clone().as_str(), first.as_str()));
+
+    let small = Text::interned(SMALL);
+    assert!(small.is_small());
+    assert!(!small.is_shared());
+}
+
+#[test]
+fn modify_interned_text() {
+    let shared = Text::interned(LARGE);
+    let mut modified = shared.clone();
+    modified.push('a');
+    assert!(!modified.is_shared());
+    assert_eq!(modified, format!("{}a", LARGE));
+    assert_eq!(shared, LARGE);
+
+    let mut upper = shared.clone();
+    upper.as_str_mut().make_ascii_uppercase();
+    assert_eq!(upper, LARGE.to_ascii_uppercase());
+    assert_eq!(shared, LARGE);
+    assert_eq!(String::from(shared), LARGE);
+}
+
+#[test]
+fn interned_text_freed() {
+    let interner = Interner::default();
+    let first = interner.intern(LARGE);
+    let second = interner.intern(LARGE);
+    assert!(std::ptr::eq(&*first, &*second));
+    assert_eq!(interner.len(), 1);
+
+    let weak = std::sync::Arc::downgrade(&first);
+    drop(first);
+    drop(second);
+    assert!(weak.upgrade().is_none());
+
+    let third = interner.intern(LARGE);
+    assert_eq!(&*third, LARGE);
+    assert_eq!(interner.len(), 1);
+}
+
+#[test]
+fn interner_size_bounded() {
+    let interner = Interner::default();
+    let live = interner.intern(LARGE);
+    for i in 0..10_000 {
+        let atom = interner.intern(&format!("{} {}", LARGE, i));
+        assert_eq!(*atom, format!("{} {}", LARGE, i));
+    }
+    assert!(interner.len() <= INTERNER_SHARDS * MIN_SWEEP_LEN);
+    assert!(std::ptr::eq(&*interner.intern(LARGE), &*live));
+}
@@ -75,13 +75,13 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     let (task, handle) = server.run();
     let _task = tokio::spawn(task);
 
-    for i in 0..1000 {
-        let route = format!("/cars/{i}");
-        handle
-            .start_agent(RouteUri::from_str(route.as_str())?)
-            .await
-            .expect("Failed to start agent");
-    }
+    let routes = (0..1000)
+        .map(|i| RouteUri::from_str(format!("/cars/{i}").as_str()))
+        .collect::<Result<Vec<_>, _>>()?;
+    handle
+        .start_agents(routes)
+        .await
+        .expect("Failed to start agents");
 
     manage_handle(handle).await;
     println!("Server stopped successfully.");
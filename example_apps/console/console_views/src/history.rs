@@ -22,9 +22,12 @@ use cursive::{
     Cursive, Printer, Rect, Vec2, View,
 };
 
+type CompleteCallback = Box<dyn FnMut(&str) -> Option<String>>;
+
 pub struct HistoryEditView {
     inner: EditView,
     history: History,
+    on_complete: Option<CompleteCallback>,
 }
 
 impl HistoryEditView {
@@ -34,9 +37,28 @@ impl HistoryEditView {
         HistoryEditView {
             inner: EditView::new(),
             history,
+            on_complete: None,
         }
     }
 
+    /// Set a callback that will be invoked when the tab key is pressed. If the callback returns
+    /// a value, it will replace the current content of the view.
+    #[must_use]
+    pub fn on_complete<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&str) -> Option<String> + 'static,
+    {
+        self.set_on_complete(callback);
+        self
+    }
+
+    pub fn set_on_complete<F>(&mut self, callback: F)
+    where
+        F: FnMut(&str) -> Option<String> + 'static,
+    {
+        self.on_complete = Some(Box::new(callback));
+    }
+
     #[must_use]
     pub fn on_submit<F>(mut self, callback: F) -> Self
     where
@@ -215,8 +237,20 @@ impl View for HistoryEditView {
 
     fn on_event(&mut self, event: Event) -> EventResult {
         match event {
+            Event::Key(Key::Tab) => {
+                let HistoryEditView {
+                    inner, on_complete, ..
+                } = self;
+                if let Some(completed) = on_complete
+                    .as_mut()
+                    .and_then(|complete| complete(inner.get_content().as_str()))
+                {
+                    inner.set_content(completed);
+                }
+                EventResult::Consumed(None)
+            }
             Event::Key(Key::Up) => {
-                let HistoryEditView { inner, history } = self;
+                let HistoryEditView { inner, history, .. } = self;
                 if history.incr() {
                     if let Some(text) = history.get() {
                         inner.set_content(text);
@@ -225,14 +259,14 @@ impl View for HistoryEditView {
                 EventResult::Consumed(None)
             }
             Event::Key(Key::Down) => {
-                let HistoryEditView { inner, history } = self;
+                let HistoryEditView { inner, history, .. } = self;
                 if history.decr() {
                     inner.set_content(history.get().unwrap_or(""));
                 }
                 EventResult::Consumed(None)
             }
             Event::Key(Key::Enter) => {
-                let HistoryEditView { inner, history } = self;
+                let HistoryEditView { inner, history, .. } = self;
                 history.push((*inner.get_content()).clone());
                 let result = self.inner.on_event(Event::Key(Key::Enter));
                 self.inner.set_content("");
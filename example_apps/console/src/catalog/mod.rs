@@ -0,0 +1,187 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::model::Endpoint;
+
+#[cfg(test)]
+mod tests;
+
+/// The names of all commands that are understood by the console.
+pub const COMMANDS: &[&str] = &[
+    "assert",
+    "clear",
+    "clear-with",
+    "command",
+    "discover",
+    "help",
+    "link",
+    "list",
+    "map-command",
+    "periodically",
+    "pretty",
+    "query",
+    "quit",
+    "run",
+    "show-with",
+    "sync",
+    "target",
+    "unlink",
+    "with-host",
+    "with-lane",
+    "with-node",
+];
+
+/// The meta lane that is being used to populate an entry in the catalog.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CatalogSource {
+    /// The 'nodes' lane of the mesh meta agent, listing the running agents.
+    Nodes,
+    /// The 'lanes' lane of the meta agent for a node, listing the lanes of that node.
+    Lanes(String),
+}
+
+/// The node URIs and lane names that have been discovered from the meta agents of a server. This is used
+/// to provide completions for commands.
+#[derive(Debug, Default)]
+pub struct Catalog {
+    sources: HashMap<Endpoint, CatalogSource>,
+    nodes: BTreeSet<String>,
+    lanes: HashMap<String, BTreeSet<String>>,
+}
+
+impl Catalog {
+    /// Register a meta lane as the source of entries for the catalog.
+    pub fn watch(&mut self, endpoint: Endpoint, source: CatalogSource) {
+        self.sources.insert(endpoint, source);
+    }
+
+    /// Find the kind of entries that are provided by a lane, if it is registered.
+    pub fn source(&self, endpoint: &Endpoint) -> Option<&CatalogSource> {
+        self.sources.get(endpoint)
+    }
+
+    pub fn insert(&mut self, source: &CatalogSource, key: String) {
+        match source {
+            CatalogSource::Nodes => {
+                self.nodes.insert(key);
+            }
+            CatalogSource::Lanes(node) => {
+                self.lanes.entry(node.clone()).or_default().insert(key);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, source: &CatalogSource, key: &str) {
+        match source {
+            CatalogSource::Nodes => {
+                self.nodes.remove(key);
+            }
+            CatalogSource::Lanes(node) => {
+                if let Some(lanes) = self.lanes.get_mut(node) {
+                    lanes.remove(key);
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self, source: &CatalogSource) {
+        match source {
+            CatalogSource::Nodes => self.nodes.clear(),
+            CatalogSource::Lanes(node) => {
+                self.lanes.remove(node);
+            }
+        }
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.nodes.iter().map(String::as_str)
+    }
+
+    pub fn lanes<'a>(&'a self, node: &str) -> impl Iterator<Item = &'a str> {
+        self.lanes
+            .get(node)
+            .into_iter()
+            .flat_map(|lanes| lanes.iter().map(String::as_str))
+    }
+}
+
+/// Attempt to complete the final token of a partial command.
+///
+/// # Arguments
+/// * `input` - The partial command.
+/// * `catalog` - The known node URIs and lane names.
+/// * `with_node` - The node URI set with the 'with-node' command, if any. This is used to find lane names
+///   when the command does not specify a node explicitly.
+pub fn complete(input: &str, catalog: &Catalog, with_node: Option<&str>) -> Option<String> {
+    let (head, partial) = match input.rfind(char::is_whitespace) {
+        Some(i) => input.split_at(i + 1),
+        None => ("", input),
+    };
+    let previous = head.split_whitespace().collect::<Vec<_>>();
+
+    let candidates: Vec<&str> = match previous.as_slice() {
+        [] | ["help"] => COMMANDS.to_vec(),
+        ["with-node"] => catalog.nodes().collect(),
+        ["with-lane"] => match with_node {
+            Some(node) => catalog.lanes(node).collect(),
+            None => vec![],
+        },
+        [.., "--node" | "-n"] => catalog.nodes().collect(),
+        [.., "--lane" | "-l"] => match node_option(&previous).or(with_node) {
+            Some(node) => catalog.lanes(node).collect(),
+            None => vec![],
+        },
+        _ => vec![],
+    };
+
+    let matching = candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(partial))
+        .collect::<Vec<_>>();
+    match matching.as_slice() {
+        [] => None,
+        [single] => Some(format!("{}{} ", head, single)),
+        [first, rest @ ..] => {
+            let common = rest
+                .iter()
+                .fold(*first, |acc, candidate| common_prefix(acc, candidate));
+            if common.len() > partial.len() {
+                Some(format!("{}{}", head, common))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+fn node_option<'a>(parts: &[&'a str]) -> Option<&'a str> {
+    parts
+        .windows(2)
+        .rev()
+        .find(|pair| matches!(pair[0], "--node" | "-n"))
+        .map(|pair| pair[1])
+}
+
+fn common_prefix<'a>(left: &'a str, right: &str) -> &'a str {
+    let len = left
+        .char_indices()
+        .zip(right.chars())
+        .take_while(|((_, l), r)| l == r)
+        .last()
+        .map(|((i, c), _)| i + c.len_utf8())
+        .unwrap_or(0);
+    &left[..len]
+}
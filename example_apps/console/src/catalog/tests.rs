@@ -0,0 +1,110 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{complete, Catalog, CatalogSource};
+
+fn make_catalog() -> Catalog {
+    let mut catalog = Catalog::default();
+    for node in ["/unit/first", "/unit/second", "/other"] {
+        catalog.insert(&CatalogSource::Nodes, node.to_string());
+    }
+    let lanes = CatalogSource::Lanes("/unit/first".to_string());
+    for lane in ["value", "value_history", "map"] {
+        catalog.insert(&lanes, lane.to_string());
+    }
+    catalog
+}
+
+#[test]
+fn complete_command_name() {
+    let catalog = Catalog::default();
+    assert_eq!(
+        complete("sh", &catalog, None),
+        Some("show-with ".to_string())
+    );
+    assert_eq!(complete("lin", &catalog, None), Some("link ".to_string()));
+    assert_eq!(complete("cl", &catalog, None), Some("clear".to_string()));
+    assert_eq!(
+        complete("map", &catalog, None),
+        Some("map-command ".to_string())
+    );
+    assert_eq!(
+        complete("help with-h", &catalog, None),
+        Some("help with-host ".to_string())
+    );
+    assert_eq!(complete("xyz", &catalog, None), None);
+}
+
+#[test]
+fn ambiguous_command_name() {
+    let catalog = Catalog::default();
+    assert_eq!(complete("c", &catalog, None), None);
+    assert_eq!(complete("li", &catalog, None), None);
+    assert_eq!(complete("with-", &catalog, None), None);
+}
+
+#[test]
+fn complete_node_uri() {
+    let catalog = make_catalog();
+    assert_eq!(
+        complete("link --node /u", &catalog, None),
+        Some("link --node /unit/".to_string())
+    );
+    assert_eq!(
+        complete("link -n /unit/s", &catalog, None),
+        Some("link -n /unit/second ".to_string())
+    );
+    assert_eq!(
+        complete("with-node /o", &catalog, None),
+        Some("with-node /other ".to_string())
+    );
+}
+
+#[test]
+fn complete_lane_name() {
+    let catalog = make_catalog();
+    assert_eq!(
+        complete("link -n /unit/first -l m", &catalog, None),
+        Some("link -n /unit/first -l map ".to_string())
+    );
+    assert_eq!(
+        complete("link -n /unit/first -l v", &catalog, None),
+        Some("link -n /unit/first -l value".to_string())
+    );
+    assert_eq!(complete("link -n /other -l v", &catalog, None), None);
+}
+
+#[test]
+fn complete_lane_name_with_node() {
+    let catalog = make_catalog();
+    assert_eq!(
+        complete("with-lane m", &catalog, Some("/unit/first")),
+        Some("with-lane map ".to_string())
+    );
+    assert_eq!(
+        complete("link --lane m", &catalog, Some("/unit/first")),
+        Some("link --lane map ".to_string())
+    );
+    assert_eq!(complete("with-lane m", &catalog, None), None);
+}
+
+#[test]
+fn remove_from_catalog() {
+    let mut catalog = make_catalog();
+    catalog.remove(&CatalogSource::Nodes, "/other");
+    assert_eq!(complete("with-node /o", &catalog, None), None);
+
+    catalog.clear(&CatalogSource::Lanes("/unit/first".to_string()));
+    assert_eq!(complete("with-lane m", &catalog, Some("/unit/first")), None);
+}
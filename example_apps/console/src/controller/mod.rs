@@ -12,16 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use parking_lot::RwLock;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use swimos_form::Form;
+use swimos_model::Value;
 use swimos_utilities::routing::RouteUri;
 use tokio::sync::mpsc;
 
 use crate::{
+    catalog::{self, CatalogSource},
     model::{
-        ControllerCommand, Endpoint, EndpointOrId, Host, LinkRef, RuntimeCommand, Target, TargetRef,
+        ControllerCommand, Endpoint, EndpointOrId, Host, LinkKind, LinkRef, RuntimeCommand, Target,
+        TargetRef,
     },
     oneshot::{self, ReceiveError},
     shared_state::SharedState,
@@ -29,6 +37,13 @@ use crate::{
 
 const BAD_CHAN: &str = "Command channel dropped.";
 
+const MESH_NODE: &str = "swimos:meta:mesh";
+const NODES_LANE: &str = "nodes";
+const NODE_META_PREFIX: &str = "swimos:meta:node/";
+const LANES_LANE: &str = "lanes";
+
+const ASSERT_RETRY: Duration = Duration::from_millis(50);
+
 pub struct Controller {
     shared_state: Arc<RwLock<SharedState>>,
     command_tx: mpsc::UnboundedSender<RuntimeCommand>,
@@ -240,43 +255,7 @@ impl Controller {
                 kind,
                 sync,
             } => match self.resolve_target(target) {
-                Ok(EndpointOrId::Id(id)) => {
-                    if let Some(name) = name {
-                        self.names.insert(name, id);
-                    }
-                    if sync {
-                        self.command_tx
-                            .send(RuntimeCommand::Sync(id))
-                            .expect(BAD_CHAN);
-                    }
-                    vec!["Already linked.".to_string()]
-                }
-                Ok(EndpointOrId::Endpoint(endpoint)) => {
-                    let (tx, rx) = oneshot::channel();
-                    self.command_tx
-                        .send(RuntimeCommand::Link {
-                            endpoint,
-                            response: tx,
-                            kind,
-                            immediate_sync: sync,
-                        })
-                        .expect(BAD_CHAN);
-                    match rx.recv(self.timeout) {
-                        Ok(Ok(id)) => {
-                            if let Some(name) = name {
-                                self.names.insert(name, id);
-                            }
-                            vec![format!("ID: {}", id)]
-                        }
-                        Ok(Err(e)) => {
-                            vec![format!("Connection failed: {}", e)]
-                        }
-                        Err(ReceiveError::SenderDropped) => panic!("{}", BAD_CHAN),
-                        Err(ReceiveError::TimedOut) => {
-                            vec!["Connection request timed out.".to_string()]
-                        }
-                    }
-                }
+                Ok(resolved) => self.open_link(name, resolved, kind, sync),
                 Err(msg) => vec![msg],
             },
             ControllerCommand::Sync(link) => self.for_link(link, RuntimeCommand::Sync),
@@ -288,6 +267,22 @@ impl Controller {
                 vec![]
             }
             ControllerCommand::Query(link) => self.for_link(link, RuntimeCommand::Query),
+            ControllerCommand::Discover { remote, node } => self.discover(remote, node),
+            ControllerCommand::Pretty(pretty) => {
+                self.shared_state.write().set_pretty(pretty);
+                if pretty {
+                    vec!["Events will be pretty printed.".to_string()]
+                } else {
+                    vec!["Events will be printed compactly.".to_string()]
+                }
+            }
+            ControllerCommand::Assert {
+                link,
+                expected,
+                timeout,
+            } => match self.assert_link(link, expected, timeout) {
+                Ok(msg) | Err(msg) => vec![msg],
+            },
             ControllerCommand::Target { name, target } => {
                 match self.add_target(name.clone(), target) {
                     Ok(endpoint) => {
@@ -318,6 +313,147 @@ impl Controller {
         }
     }
 
+    fn open_link(
+        &mut self,
+        name: Option<String>,
+        target: EndpointOrId,
+        kind: LinkKind,
+        sync: bool,
+    ) -> Vec<String> {
+        match target {
+            EndpointOrId::Id(id) => {
+                if let Some(name) = name {
+                    self.names.insert(name, id);
+                }
+                if sync {
+                    self.command_tx
+                        .send(RuntimeCommand::Sync(id))
+                        .expect(BAD_CHAN);
+                }
+                vec!["Already linked.".to_string()]
+            }
+            EndpointOrId::Endpoint(endpoint) => {
+                let (tx, rx) = oneshot::channel();
+                self.command_tx
+                    .send(RuntimeCommand::Link {
+                        endpoint,
+                        response: tx,
+                        kind,
+                        immediate_sync: sync,
+                    })
+                    .expect(BAD_CHAN);
+                match rx.recv(self.timeout) {
+                    Ok(Ok(id)) => {
+                        if let Some(name) = name {
+                            self.names.insert(name, id);
+                        }
+                        vec![format!("ID: {}", id)]
+                    }
+                    Ok(Err(e)) => {
+                        vec![format!("Connection failed: {}", e)]
+                    }
+                    Err(ReceiveError::SenderDropped) => panic!("{}", BAD_CHAN),
+                    Err(ReceiveError::TimedOut) => {
+                        vec!["Connection request timed out.".to_string()]
+                    }
+                }
+            }
+        }
+    }
+
+    /// Link to the appropriate meta lane to populate the catalog of node URIs (if no node is
+    /// specified) or the lanes of a node.
+    fn discover(&mut self, remote: Option<Host>, node: Option<RouteUri>) -> Vec<String> {
+        let Some(remote) = remote.or_else(|| self.with_host.clone()) else {
+            return vec!["No host specified.".to_string()];
+        };
+        let (meta_node, lane, source) = match node {
+            Some(node) => {
+                let encoded = utf8_percent_encode(node.as_str(), NON_ALPHANUMERIC);
+                let meta_node = format!("{}{}", NODE_META_PREFIX, encoded);
+                (
+                    meta_node,
+                    LANES_LANE,
+                    CatalogSource::Lanes(node.to_string()),
+                )
+            }
+            None => (MESH_NODE.to_string(), NODES_LANE, CatalogSource::Nodes),
+        };
+        let meta_node = match meta_node.parse::<RouteUri>() {
+            Ok(uri) => uri,
+            Err(_) => return vec![format!("Invalid meta node URI: {}", meta_node)],
+        };
+        let endpoint = Endpoint {
+            remote,
+            node: meta_node,
+            lane: lane.to_string(),
+        };
+        self.shared_state
+            .write()
+            .catalog_mut()
+            .watch(endpoint.clone(), source);
+        let target = match self.shared_state.read().get_id(&endpoint) {
+            Some(id) => EndpointOrId::Id(id),
+            None => EndpointOrId::Endpoint(endpoint),
+        };
+        self.open_link(None, target, LinkKind::Map, true)
+    }
+
+    /// Check that the state of a link matches an expected value. If a timeout is specified, the check
+    /// will be retried until it succeeds or the timeout elapses.
+    pub fn assert_link(
+        &mut self,
+        link: LinkRef,
+        expected: Value,
+        timeout: Option<Duration>,
+    ) -> Result<String, String> {
+        let id = self.resolve_link(link)?;
+        let deadline = timeout.map(|t| Instant::now() + t);
+        loop {
+            let (tx, rx) = oneshot::channel();
+            self.command_tx
+                .send(RuntimeCommand::Assert {
+                    id,
+                    expected: expected.clone(),
+                    response: tx,
+                })
+                .expect(BAD_CHAN);
+            let msg = match rx.recv(self.timeout) {
+                Ok(Ok(_)) => return Ok(format!("Assertion passed for link {}.", id)),
+                Ok(Err(msg)) => msg,
+                Err(ReceiveError::SenderDropped) => panic!("{}", BAD_CHAN),
+                Err(ReceiveError::TimedOut) => "Assertion request timed out.".to_string(),
+            };
+            match deadline {
+                Some(t) if Instant::now() < t => std::thread::sleep(ASSERT_RETRY),
+                _ => return Err(format!("Assertion failed for link {}: {}", id, msg)),
+            }
+        }
+    }
+
+    /// Attempt to complete a partially entered command.
+    pub fn complete(&self, input: &str) -> Option<String> {
+        let with_node = self.with_node.as_ref().map(RouteUri::as_str);
+        catalog::complete(input, self.shared_state.read().catalog(), with_node)
+    }
+
+    fn resolve_link(&self, link: LinkRef) -> Result<usize, String> {
+        match link {
+            LinkRef::ById(id) => {
+                if self.shared_state.read().has_id(id) {
+                    Ok(id)
+                } else {
+                    Err(format!("{} is not a valid link ID.", id))
+                }
+            }
+            LinkRef::ByName(name) => match self.names.get(&name) {
+                Some(id) if self.shared_state.read().has_id(*id) => Ok(*id),
+                Some(id) => Err(format!("{} is not a valid link ID.", id)),
+                None => Err(format!("{} is not a valid link name.", name)),
+            },
+        }
+    }
+
     fn for_link(&self, link: LinkRef, f: impl FnOnce(usize) -> RuntimeCommand) -> Vec<String> {
         match link {
             LinkRef::ById(id) => {
@@ -30,6 +30,7 @@ use tokio::runtime::Builder;
 use tokio::sync::mpsc;
 use ui::{CursiveUIUpdater, ViewUpdater};
 
+mod catalog;
 mod controller;
 mod data;
 mod model;
@@ -148,6 +148,16 @@ pub enum ControllerCommand {
     Unlink(LinkRef),
     UnlinkAll,
     Query(LinkRef),
+    Discover {
+        remote: Option<Host>,
+        node: Option<RouteUri>,
+    },
+    Pretty(bool),
+    Assert {
+        link: LinkRef,
+        expected: Value,
+        timeout: Option<Duration>,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -160,6 +170,7 @@ pub enum AppCommand {
     Quit,
     Clear,
     Help { command_name: Option<String> },
+    Run { path: String },
     Controller(ControllerCommand),
 }
 
@@ -177,6 +188,11 @@ pub enum RuntimeCommand {
     Unlink(usize),
     UnlinkAll,
     Query(usize),
+    Assert {
+        id: usize,
+        expected: Value,
+        response: oneshot::Sender<Result<(), String>>,
+    },
     Periodically {
         endpoint: Endpoint,
         delay: Duration,
@@ -103,6 +103,9 @@ pub fn parse_app_command(command: &str) -> Result<AppCommand, Cow<'static, str>>
         }),
         ["quit"] => Ok(AppCommand::Quit),
         ["clear"] => Ok(AppCommand::Clear),
+        ["run", path] => Ok(AppCommand::Run {
+            path: path.to_string(),
+        }),
         _ => Ok(AppCommand::Controller(parse_controller_command(
             command_parts.as_slice(),
         )?)),
@@ -264,6 +267,44 @@ pub fn parse_controller_command(parts: &[&str]) -> Result<ControllerCommand, Cow
             let r = target.parse()?;
             Ok(ControllerCommand::Query(r))
         }
+        ["discover", tail @ ..] => {
+            let (mut options, tail) = parse_options(tail);
+            let Target { remote, node, lane } = options.target()?.unwrap_or_default();
+            if !tail.is_empty() || !options.is_empty() || lane.is_some() {
+                Err(Cow::Borrowed(
+                    "Incorrect parameters to discover. Type 'help discover' for correct usage.",
+                ))
+            } else {
+                Ok(ControllerCommand::Discover { remote, node })
+            }
+        }
+        ["pretty", "on"] => Ok(ControllerCommand::Pretty(true)),
+        ["pretty", "off"] => Ok(ControllerCommand::Pretty(false)),
+        ["assert", tail @ ..] => {
+            let (mut options, tail) = parse_options(tail);
+            let timeout = options
+                .take("timeout", Some('t'))
+                .flatten()
+                .map(parse_duration)
+                .transpose()?;
+            match tail {
+                [link, expected] if options.is_empty() => {
+                    let link = link.parse()?;
+                    if let Ok(expected) = parse_recognize(*expected, false) {
+                        Ok(ControllerCommand::Assert {
+                            link,
+                            expected,
+                            timeout,
+                        })
+                    } else {
+                        Err(Cow::Owned(format!("'{}' is not valid recon.", expected)))
+                    }
+                }
+                _ => Err(Cow::Borrowed(
+                    "Incorrect parameters to assert. Type 'help assert' for correct usage.",
+                )),
+            }
+        }
         _ => Err(Cow::Borrowed(
             "Unknown command. Type 'help' to list valid commands.",
         )),
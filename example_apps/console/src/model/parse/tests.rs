@@ -653,3 +653,76 @@ fn parse_map_command() {
         }
     );
 }
+
+#[test]
+fn parse_discover() {
+    let host: Host = "localhost:8080".parse().unwrap();
+    let node: RouteUri = "/node".parse().unwrap();
+
+    let cmd = to_controller(super::parse_app_command("discover").expect("Should succeed."));
+    assert_eq!(
+        cmd,
+        ControllerCommand::Discover {
+            remote: None,
+            node: None
+        }
+    );
+
+    let cmd = to_controller(
+        super::parse_app_command("discover -h localhost:8080 -n /node").expect("Should succeed."),
+    );
+    assert_eq!(
+        cmd,
+        ControllerCommand::Discover {
+            remote: Some(host),
+            node: Some(node)
+        }
+    );
+
+    assert!(super::parse_app_command("discover --lane my_lane").is_err());
+}
+
+#[test]
+fn parse_pretty() {
+    let cmd = to_controller(super::parse_app_command("pretty on").expect("Should succeed."));
+    assert_eq!(cmd, ControllerCommand::Pretty(true));
+
+    let cmd = to_controller(super::parse_app_command("pretty off").expect("Should succeed."));
+    assert_eq!(cmd, ControllerCommand::Pretty(false));
+
+    assert!(super::parse_app_command("pretty").is_err());
+}
+
+#[test]
+fn parse_assert() {
+    let cmd = to_controller(super::parse_app_command("assert 1 7").expect("Should succeed."));
+    assert_eq!(
+        cmd,
+        ControllerCommand::Assert {
+            link: LinkRef::ById(1),
+            expected: Value::from(7),
+            timeout: None,
+        }
+    );
+
+    let cmd = to_controller(
+        super::parse_app_command("assert --timeout 2s name `{a: 1}`").expect("Should succeed."),
+    );
+    assert_eq!(
+        cmd,
+        ControllerCommand::Assert {
+            link: LinkRef::ByName("name".to_string()),
+            expected: parse_value("{a: 1}"),
+            timeout: Some(Duration::from_secs(2)),
+        }
+    );
+
+    assert!(super::parse_app_command("assert 1").is_err());
+    assert!(super::parse_app_command("assert -t forever 1 7").is_err());
+}
+
+#[test]
+fn parse_run() {
+    let cmd = super::parse_app_command("run script.txt").expect("Should succeed.");
+    assert!(matches!(cmd, AppCommand::Run { path } if path == "script.txt"));
+}
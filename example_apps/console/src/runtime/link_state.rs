@@ -15,7 +15,7 @@
 use std::{collections::BTreeMap, fmt::Debug};
 
 use swimos_agent_protocol::{peeling::extract_header_str, MapMessage};
-use swimos_model::Value;
+use swimos_model::{Item, Value};
 use swimos_recon::{
     parser::{parse_recognize, MessageExtractError, ParseError},
     print_recon,
@@ -42,6 +42,9 @@ pub trait LinkState: Debug + Send + Sync {
     fn sync(&mut self);
 
     fn snapshot(&self) -> Vec<String>;
+
+    /// Check that the state of the link is equal to an expected value.
+    fn check(&self, expected: &Value) -> Result<(), String>;
 }
 
 pub type BoxLinkState = Box<dyn LinkState>;
@@ -80,6 +83,25 @@ impl LinkState for EventLink {
     fn sync(&mut self) {
         self.synced = true;
     }
+
+    fn check(&self, expected: &Value) -> Result<(), String> {
+        match self.state.as_ref() {
+            Some(state) => {
+                let actual = parse_recognize::<Value>(state.as_str(), false)
+                    .map_err(|e| format!("Bad recon: {}", e))?;
+                if &actual == expected {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "Expected {} but the state was {}.",
+                        print_recon(expected),
+                        state
+                    ))
+                }
+            }
+            None => Err("No events have been received.".to_string()),
+        }
+    }
 }
 
 #[derive(Default, Debug)]
@@ -137,4 +159,22 @@ impl LinkState for MapLink {
         );
         messages
     }
+
+    fn check(&self, expected: &Value) -> Result<(), String> {
+        let actual = Value::from_vec(
+            self.state
+                .iter()
+                .map(|(k, v)| Item::Slot(k.clone(), v.clone()))
+                .collect(),
+        );
+        if &actual == expected {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected {} but the state was {}.",
+                print_recon(expected),
+                print_recon(&actual)
+            ))
+        }
+    }
 }
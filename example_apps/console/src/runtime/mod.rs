@@ -27,9 +27,13 @@ use ratchet::{
     CloseCode, CloseReason, ErrorKind, Message, NoExt, NoExtDecoder, NoExtEncoder, NoExtProvider,
     ProtocolRegistry, WebSocket, WebSocketConfig,
 };
+use swimos_agent_protocol::{peeling::extract_header_str, MapMessage};
 use swimos_messages::warp::{peel_envelope_header_str, RawEnvelope};
 use swimos_model::Value;
-use swimos_recon::{parser::MessageExtractError, print_recon_compact};
+use swimos_recon::{
+    parser::{parse_recognize, MessageExtractError},
+    print_recon, print_recon_compact, print_recon_pretty,
+};
 use swimos_utilities::{
     routing::{InvalidRouteUri, RouteUri},
     trigger,
@@ -338,6 +342,17 @@ impl Runtime {
                             }
                         }
                     }
+                    RuntimeCommand::Assert {
+                        id,
+                        expected,
+                        response,
+                    } => {
+                        let result = match state.get_link_state(id) {
+                            Some(link_state) => link_state.check(&expected),
+                            None => Err(format!("{} is not a valid link ID.", id)),
+                        };
+                        response.send(result);
+                    }
                     RuntimeCommand::Periodically {
                         endpoint,
                         delay,
@@ -623,12 +638,13 @@ async fn handle_body(
                 node,
                 lane: lane_uri.to_string(),
             };
+            let message_body = body.trim().to_string();
             if let Some((id, link_state)) = state.get_for_endpoint(&endpoint) {
-                let message_body = body.trim().to_string();
                 link_state.update(&message_body)?;
-                Ok(DisplayResponse::event(id, message_body))
+                state.update_catalog(&endpoint, &message_body)?;
+                Ok(DisplayResponse::event(id, state.format_body(message_body)))
             } else {
-                Ok(DisplayResponse::event(0, body.trim().to_string()))
+                Ok(DisplayResponse::event(0, state.format_body(message_body)))
             }
         }
         _ => Err(BadEnvelope(format!(
@@ -762,6 +778,40 @@ impl State {
     fn endpoints(&self) -> impl Iterator<Item = &Endpoint> {
         self.endpoint_to_id.keys()
     }
+
+    /// If the endpoint is a meta lane that is being used to populate the catalog, apply the
+    /// event to the catalog.
+    fn update_catalog(&self, endpoint: &Endpoint, body: &str) -> Result<(), LinkStateError> {
+        let mut guard = self.shared.write();
+        let catalog = guard.catalog_mut();
+        if let Some(source) = catalog.source(endpoint).cloned() {
+            match extract_header_str(body)? {
+                MapMessage::Update { key, .. } => catalog.insert(&source, catalog_key(key)?),
+                MapMessage::Remove { key } => catalog.remove(&source, &catalog_key(key)?),
+                MapMessage::Clear => catalog.clear(&source),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn format_body(&self, body: String) -> String {
+        if self.shared.read().pretty() {
+            match parse_recognize::<Value>(body.as_str(), false) {
+                Ok(value) => format!("{}", print_recon_pretty(&value)),
+                Err(_) => body,
+            }
+        } else {
+            body
+        }
+    }
+}
+
+fn catalog_key(key: &str) -> Result<String, LinkStateError> {
+    Ok(match parse_recognize::<Value>(key, false)? {
+        Value::Text(text) => text.to_string(),
+        ow => print_recon(&ow).to_string(),
+    })
 }
 
 fn failed() -> CloseReason {
@@ -14,12 +14,14 @@
 
 use std::collections::{BTreeMap, HashMap};
 
-use crate::model::Endpoint;
+use crate::{catalog::Catalog, model::Endpoint};
 
 pub struct SharedState {
     count: usize,
     links: HashMap<Endpoint, usize>,
     rev: BTreeMap<usize, Endpoint>,
+    catalog: Catalog,
+    pretty: bool,
 }
 
 impl Default for SharedState {
@@ -28,13 +30,17 @@ impl Default for SharedState {
             count: 1,
             links: Default::default(),
             rev: Default::default(),
+            catalog: Default::default(),
+            pretty: false,
         }
     }
 }
 
 impl SharedState {
     pub fn insert(&mut self, endpoint: Endpoint) -> usize {
-        let SharedState { count, links, rev } = self;
+        let SharedState {
+            count, links, rev, ..
+        } = self;
         let n = *count;
         *count += 1;
         links.insert(endpoint.clone(), n);
@@ -43,7 +49,9 @@ impl SharedState {
     }
 
     pub fn remove(&mut self, id: usize) {
-        let SharedState { count, links, rev } = self;
+        let SharedState {
+            count, links, rev, ..
+        } = self;
         if let Some(endpoint) = rev.remove(&id) {
             links.remove(&endpoint);
         }
@@ -63,4 +71,20 @@ impl SharedState {
     pub fn get_id(&self, endpoint: &Endpoint) -> Option<usize> {
         self.links.get(endpoint).copied()
     }
+
+    pub fn catalog(&self) -> &Catalog {
+        &self.catalog
+    }
+
+    pub fn catalog_mut(&mut self) -> &mut Catalog {
+        &mut self.catalog
+    }
+
+    pub fn pretty(&self) -> bool {
+        self.pretty
+    }
+
+    pub fn set_pretty(&mut self, pretty: bool) {
+        self.pretty = pretty;
+    }
 }
@@ -13,6 +13,8 @@
 // limitations under the License.
 
 use std::borrow::Cow;
+use std::cell::RefCell;
+use std::rc::Rc;
 
 use console_views::history::HistoryEditView;
 use cursive::theme::Color::{self, TerminalDefault};
@@ -26,7 +28,9 @@ use cursive::{
 };
 use std::time::Duration;
 
-use crate::model::{parse_app_command, AppCommand, DisplayResponse, LogMessageKind};
+use crate::model::{
+    parse_app_command, AppCommand, ControllerCommand, DisplayResponse, LogMessageKind,
+};
 use crate::{controller::Controller, model::UIUpdate};
 
 use self::bounded_append::BoundedAppend;
@@ -132,7 +136,7 @@ const LOG_VIEW: &str = "log";
 const COMMAND_EDIT: &str = "command";
 const HISTORY_LEN: usize = 32;
 
-pub fn create_ui(siv: &mut Cursive, mut controller: Controller, max_lines: usize) {
+pub fn create_ui(siv: &mut Cursive, controller: Controller, max_lines: usize) {
     siv.add_global_callback('q', |s| s.quit());
 
     siv.set_theme(Theme {
@@ -148,6 +152,8 @@ pub fn create_ui(siv: &mut Cursive, mut controller: Controller, max_lines: usize
         }),
     });
     let history_appender = BoundedAppend::new(HISTORY_VIEW, max_lines, Into::into);
+    let controller = Rc::new(RefCell::new(controller));
+    let completer = controller.clone();
     siv.add_layer(
         LinearLayout::horizontal()
             .child(
@@ -155,8 +161,9 @@ pub fn create_ui(siv: &mut Cursive, mut controller: Controller, max_lines: usize
                     .child(Panel::new(
                         HistoryEditView::new(HISTORY_LEN)
                             .on_submit_mut(move |s, text| {
-                                on_command(s, &mut controller, &history_appender, text)
+                                on_command(s, &mut controller.borrow_mut(), &history_appender, text)
                             })
+                            .on_complete(move |text| completer.borrow().complete(text))
                             .with_name(COMMAND_EDIT),
                     ))
                     .child(
@@ -201,13 +208,17 @@ const HELP: &[&str] = &[
     "clear        Clear this display.\n",
     "help         Display this list.\n",
     "quit         Close the application.\n",
+    "run          Run the commands in a script file.\n",
     "\n",
+    "assert       Check that the state of a link is equal to an expected value.\n",
     "clear-with   Clear the values current set with 'with-host', 'with-node' and 'with-lane'.\n",
     "command      Send a command to an existing link, or directly to a specified remote lane.\n",
+    "discover     Discover the nodes of a host, or the lanes of a node, for tab completion.\n",
     "link         Open a link to a remote lane.\n",
     "list         List all active links.\n",
     "map-command  Send a map command to an existing link, or directly to a specified remote lane.\n",
     "periodically Send a stream of commands to a specified target.\n",
+    "pretty       Turn pretty printing of events on or off.\n",
     "query        Query the state of an active link.\n",
     "show-with    Show the values current set with 'with-host', 'with-node' and 'with-lane'.\n",
     "sync         Send a sync frame to an existing link.\n",
@@ -216,6 +227,8 @@ const HELP: &[&str] = &[
     "with-node    Execute subsequent commands against an implicit agent node URI.\n",
     "with-lane    Execute subsequent commands against an implicit lane.\n",
     "\n",
+    "Press tab to complete command names, node URIs and lane names.\n",
+    "\n",
 ];
 
 const CLEAR: &[&str] = &["Clear this display.\n", "\n", "clear\n", "\n"];
@@ -388,6 +401,45 @@ const WITH_LANE: &[&str] = &[
     "\n",
 ];
 
+const DISCOVER: &[&str] = &[
+    "Link to the meta lanes of a host to discover the node URIs and lane names to use for tab completion.\n",
+    "\n",
+    "discover [--host|-h host_name:(string)]\n",
+    "\n",
+    "Discover the node URIs of the agents that are running on the host.\n",
+    "\n",
+    "discover [--host|-h host_name:(string)] --node|-n node_uri:(string)\n",
+    "\n",
+    "Discover the names of the lanes of the specified agent.\n",
+    "\n",
+];
+
+const PRETTY: &[&str] = &[
+    "Turn pretty printing of events on or off.\n",
+    "\n",
+    "pretty on|off\n",
+    "\n",
+];
+
+const ASSERT: &[&str] = &[
+    "Check that the state of an active link is equal to an expected value.\n",
+    "The expected value must be valid Recon. The state of a map link is compared with a record of its entries.\n",
+    "Example: `{a: 1, b: 2}`\n",
+    "\n",
+    "assert ?[--timeout|-t timeout:(duration)] id:(integer) | name:(string) expected:(recon)\n",
+    "\n",
+    "If a timeout is specified the check will be retried until it succeeds or the timeout elapses.\n",
+    "\n",
+];
+
+const RUN: &[&str] = &[
+    "Run the commands in a script file, one per line.\n",
+    "Empty lines and lines starting with '#' are ignored. The script stops at the first failure.\n",
+    "\n",
+    "run path:(string)\n",
+    "\n",
+];
+
 const UNKNOWN: &[&str] = &["Unknown command."];
 
 fn on_command(
@@ -423,6 +475,10 @@ fn on_command(
                 "unlink" => UNLINK,
                 "list" => LIST,
                 "command" => COMMAND,
+                "discover" => DISCOVER,
+                "pretty" => PRETTY,
+                "assert" => ASSERT,
+                "run" => RUN,
                 _ => UNKNOWN,
             };
             Some(help_text.iter().map(|s| Cow::Borrowed(*s)).collect())
@@ -432,6 +488,7 @@ fn on_command(
             Some(vec![])
         }
         Ok(AppCommand::Clear) => None,
+        Ok(AppCommand::Run { path }) => Some(run_script(controller, &path)),
         Ok(AppCommand::Controller(command)) => Some(
             controller
                 .perform_action(command)
@@ -452,6 +509,53 @@ fn on_command(
     }
 }
 
+fn run_script(controller: &mut Controller, path: &str) -> Vec<Cow<'static, str>> {
+    let script = match std::fs::read_to_string(path) {
+        Ok(script) => script,
+        Err(e) => return vec![Cow::Owned(format!("Failed to read {}: {}\n", path, e))],
+    };
+    let mut responses = vec![];
+    for (i, line) in script.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        responses.push(Cow::Owned(format!(">> {}\n", line)));
+        let result = match parse_app_command(line) {
+            Ok(AppCommand::Controller(ControllerCommand::Assert {
+                link,
+                expected,
+                timeout,
+            })) => controller
+                .assert_link(link, expected, timeout)
+                .map(|msg| vec![msg]),
+            Ok(AppCommand::Controller(command)) => Ok(controller.perform_action(command)),
+            Ok(_) => Err("This command cannot be used in a script.".to_string()),
+            Err(msg) => Err(msg.into_owned()),
+        };
+        match result {
+            Ok(messages) => {
+                responses.extend(
+                    messages
+                        .into_iter()
+                        .map(|msg| Cow::Owned(format!("{}\n", msg))),
+                );
+            }
+            Err(msg) => {
+                responses.push(Cow::Owned(format!("{}\n", msg)));
+                responses.push(Cow::Owned(format!(
+                    "Script {} failed at line {}.\n",
+                    path,
+                    i + 1
+                )));
+                return responses;
+            }
+        }
+    }
+    responses.push(Cow::Owned(format!("Script {} completed.\n", path)));
+    responses
+}
+
 fn format_display(display: DisplayResponse) -> StyledString {
     let line = format!("{}\n", display);
     let id = display.id;
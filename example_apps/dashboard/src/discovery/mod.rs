@@ -0,0 +1,171 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use swimos_client::{
+    BasicEventDownlinkLifecycle, BasicMapDownlinkLifecycle, ClientHandle, RemotePath,
+};
+use swimos_meta::{LaneInfo, LanePulse, NodePulse};
+use swimos_model::Value;
+use tokio::sync::mpsc;
+
+use crate::model::DashboardState;
+
+const MESH_NODE: &str = "swimos:meta:mesh";
+const NODES_LANE: &str = "nodes";
+const NODE_META_PREFIX: &str = "swimos:meta:node/";
+const LANES_LANE: &str = "lanes";
+const PULSE_LANE: &str = "pulse";
+
+/// Agents and lanes that have been discovered and need downlinks to be opened to their
+/// meta-agents.
+enum Discovered {
+    Agent(String),
+    Lane { node: String, lane: String },
+}
+
+fn node_meta_uri(node: &str) -> String {
+    format!(
+        "{}{}",
+        NODE_META_PREFIX,
+        utf8_percent_encode(node, NON_ALPHANUMERIC)
+    )
+}
+
+fn lane_meta_uri(node: &str, lane: &str) -> String {
+    format!(
+        "{}/lane/{}",
+        node_meta_uri(node),
+        utf8_percent_encode(lane, NON_ALPHANUMERIC)
+    )
+}
+
+/// Follow the introspection meta-agents of a plane, keeping the dashboard state up to date. A
+/// downlink to the mesh meta-agent discovers the running agents and, for each of these, further
+/// downlinks are opened to discover its lanes and to receive the pulses for the agent and each
+/// of its lanes.
+///
+/// # Arguments
+/// * `handle` - Handle to the client that is used to open the downlinks.
+/// * `host` - The URL of the plane.
+/// * `state` - The state that is displayed by the dashboard.
+pub async fn run(handle: ClientHandle, host: String, state: Arc<Mutex<DashboardState>>) {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let nodes_lifecycle = BasicMapDownlinkLifecycle::<String, Value>::default()
+        .on_update_blocking({
+            let state = state.clone();
+            let tx = tx.clone();
+            move |node, _map, _old, _new| {
+                if state.lock().add_agent(&node) {
+                    let _ = tx.send(Discovered::Agent(node));
+                }
+            }
+        })
+        .on_removed_blocking({
+            let state = state.clone();
+            move |node, _map, _old| state.lock().remove_agent(&node)
+        });
+
+    if let Err(err) = handle
+        .map_downlink::<String, Value>(RemotePath::new(host.as_str(), MESH_NODE, NODES_LANE))
+        .lifecycle(nodes_lifecycle)
+        .open()
+        .await
+    {
+        state.lock().set_error(format!(
+            "Failed to link to the mesh meta-agent (is introspection enabled?): {}",
+            err
+        ));
+        return;
+    }
+
+    // The downlinks to individual agents and lanes can fail to open if the agent stops in the
+    // meantime so errors are ignored.
+    while let Some(discovered) = rx.recv().await {
+        match discovered {
+            Discovered::Agent(node) => {
+                let meta_node = node_meta_uri(&node);
+
+                let lanes_lifecycle = BasicMapDownlinkLifecycle::<String, LaneInfo>::default()
+                    .on_update_blocking({
+                        let state = state.clone();
+                        let tx = tx.clone();
+                        let node = node.clone();
+                        move |lane, _map, _old, info: &LaneInfo| {
+                            if state.lock().add_lane(&node, &lane, info.lane_type) {
+                                let _ = tx.send(Discovered::Lane {
+                                    node: node.clone(),
+                                    lane,
+                                });
+                            }
+                        }
+                    })
+                    .on_removed_blocking({
+                        let state = state.clone();
+                        let node = node.clone();
+                        move |lane, _map, _old| state.lock().remove_lane(&node, &lane)
+                    });
+                let _ = handle
+                    .map_downlink::<String, LaneInfo>(RemotePath::new(
+                        host.as_str(),
+                        meta_node.as_str(),
+                        LANES_LANE,
+                    ))
+                    .lifecycle(lanes_lifecycle)
+                    .open()
+                    .await;
+
+                let pulse_lifecycle = BasicEventDownlinkLifecycle::<NodePulse>::default()
+                    .on_event_blocking({
+                        let state = state.clone();
+                        move |pulse| state.lock().set_agent_pulse(&node, pulse.uplinks)
+                    });
+                let _ = handle
+                    .event_downlink::<NodePulse>(RemotePath::new(
+                        host.as_str(),
+                        meta_node.as_str(),
+                        PULSE_LANE,
+                    ))
+                    .lifecycle(pulse_lifecycle)
+                    .open()
+                    .await;
+            }
+            Discovered::Lane { node, lane } => {
+                let meta_node = lane_meta_uri(&node, &lane);
+                let pulse_lifecycle = BasicEventDownlinkLifecycle::<LanePulse>::default()
+                    .on_event_blocking({
+                        let state = state.clone();
+                        move |pulse| {
+                            state
+                                .lock()
+                                .set_lane_pulse(&node, &lane, pulse.uplink_pulse)
+                        }
+                    });
+                let _ = handle
+                    .event_downlink::<LanePulse>(RemotePath::new(
+                        host.as_str(),
+                        meta_node.as_str(),
+                        PULSE_LANE,
+                    ))
+                    .lifecycle(pulse_lifecycle)
+                    .open()
+                    .await;
+            }
+        }
+    }
+}
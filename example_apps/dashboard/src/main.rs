@@ -0,0 +1,62 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A terminal dashboard for a running SwimOS plane. The dashboard links to the introspection
+//! meta-agents of the plane and displays the running agents, their lanes and the number of
+//! uplinks and event and command rates for each, updating them live. The plane must be started
+//! with introspection enabled.
+
+use std::{error::Error, sync::Arc, time::Duration};
+
+use clap::Parser;
+use parking_lot::Mutex;
+use swimos_client::SwimClientBuilder;
+
+use crate::model::DashboardState;
+
+mod discovery;
+mod model;
+mod ui;
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Params {
+    /// The URL of the plane.
+    #[arg(default_value = "ws://127.0.0.1:8080")]
+    host: String,
+    /// The interval between redraws of the dashboard, in milliseconds.
+    #[arg(short, long, default_value_t = 500)]
+    refresh: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let Params { host, refresh } = Params::parse();
+
+    let (client, task) = SwimClientBuilder::default().build().await;
+    let task_handle = tokio::spawn(task);
+
+    let state: Arc<Mutex<DashboardState>> = Default::default();
+    let discovery_task = tokio::spawn(discovery::run(client.handle(), host.clone(), state.clone()));
+
+    let ui_result =
+        tokio::task::spawn_blocking(move || ui::run(&host, &state, Duration::from_millis(refresh)))
+            .await?;
+
+    discovery_task.abort();
+    client.shutdown().await;
+    task_handle.await?;
+    ui_result?;
+    Ok(())
+}
@@ -0,0 +1,126 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use swimos_api::agent::LaneKind;
+use swimos_meta::WarpUplinkPulse;
+
+#[cfg(test)]
+mod tests;
+
+/// The state of a lane, as reported by its meta-agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LaneEntry {
+    pub kind: LaneKind,
+    pub pulse: Option<WarpUplinkPulse>,
+}
+
+/// The state of an agent, as reported by its meta-agent.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AgentEntry {
+    pub pulse: Option<WarpUplinkPulse>,
+    pub lanes: BTreeMap<String, LaneEntry>,
+}
+
+/// Everything that is known about the agents running in a plane. This is updated by the
+/// downlinks to the meta-agents and read by the UI each time it is drawn.
+#[derive(Debug, Default)]
+pub struct DashboardState {
+    agents: BTreeMap<String, AgentEntry>,
+    error: Option<String>,
+}
+
+impl DashboardState {
+    /// Record that an agent is running. Returns `true` if the agent was not already known.
+    pub fn add_agent(&mut self, node: &str) -> bool {
+        if self.agents.contains_key(node) {
+            false
+        } else {
+            self.agents.insert(node.to_string(), AgentEntry::default());
+            true
+        }
+    }
+
+    pub fn remove_agent(&mut self, node: &str) {
+        self.agents.remove(node);
+    }
+
+    /// Updates for agents that are not known are ignored (the agent may have stopped before the
+    /// last update from its meta-agent was received).
+    pub fn set_agent_pulse(&mut self, node: &str, pulse: WarpUplinkPulse) {
+        if let Some(agent) = self.agents.get_mut(node) {
+            agent.pulse = Some(pulse);
+        }
+    }
+
+    /// Record that an agent has a lane. Returns `true` if the lane was not already known.
+    pub fn add_lane(&mut self, node: &str, lane: &str, kind: LaneKind) -> bool {
+        match self.agents.get_mut(node) {
+            Some(agent) if !agent.lanes.contains_key(lane) => {
+                agent
+                    .lanes
+                    .insert(lane.to_string(), LaneEntry { kind, pulse: None });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn remove_lane(&mut self, node: &str, lane: &str) {
+        if let Some(agent) = self.agents.get_mut(node) {
+            agent.lanes.remove(lane);
+        }
+    }
+
+    pub fn set_lane_pulse(&mut self, node: &str, lane: &str, pulse: WarpUplinkPulse) {
+        if let Some(entry) = self
+            .agents
+            .get_mut(node)
+            .and_then(|agent| agent.lanes.get_mut(lane))
+        {
+            entry.pulse = Some(pulse);
+        }
+    }
+
+    /// Record an error that prevents the dashboard from receiving updates.
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// The known agents, ordered by node URI.
+    pub fn agents(&self) -> impl ExactSizeIterator<Item = (&str, &AgentEntry)> + '_ {
+        self.agents
+            .iter()
+            .map(|(node, agent)| (node.as_str(), agent))
+    }
+
+    /// The sum of the most recent pulses of all of the agents.
+    pub fn totals(&self) -> WarpUplinkPulse {
+        self.agents
+            .values()
+            .filter_map(|agent| agent.pulse.as_ref())
+            .fold(WarpUplinkPulse::default(), |acc, pulse| WarpUplinkPulse {
+                link_count: acc.link_count + pulse.link_count,
+                event_rate: acc.event_rate + pulse.event_rate,
+                event_count: acc.event_count + pulse.event_count,
+                command_rate: acc.command_rate + pulse.command_rate,
+                command_count: acc.command_count + pulse.command_count,
+            })
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use swimos_api::agent::LaneKind;
+use swimos_meta::WarpUplinkPulse;
+
+use super::{DashboardState, LaneEntry};
+
+const NODE: &str = "/node";
+const OTHER: &str = "/other";
+const LANE: &str = "lane";
+
+fn pulse(link_count: u64, event_rate: u64) -> WarpUplinkPulse {
+    WarpUplinkPulse {
+        link_count,
+        event_rate,
+        event_count: event_rate * 10,
+        command_rate: 1,
+        command_count: 10,
+    }
+}
+
+#[test]
+fn add_agent_once() {
+    let mut state = DashboardState::default();
+    assert!(state.add_agent(NODE));
+    assert!(!state.add_agent(NODE));
+    assert!(state.add_agent(OTHER));
+
+    let nodes = state.agents().map(|(node, _)| node).collect::<Vec<_>>();
+    assert_eq!(nodes, vec![NODE, OTHER]);
+}
+
+#[test]
+fn lanes_require_agent() {
+    let mut state = DashboardState::default();
+    assert!(!state.add_lane(NODE, LANE, LaneKind::Value));
+
+    state.add_agent(NODE);
+    assert!(state.add_lane(NODE, LANE, LaneKind::Value));
+    assert!(!state.add_lane(NODE, LANE, LaneKind::Value));
+}
+
+#[test]
+fn apply_pulses() {
+    let mut state = DashboardState::default();
+    state.add_agent(NODE);
+    state.add_lane(NODE, LANE, LaneKind::Map);
+
+    state.set_agent_pulse(NODE, pulse(2, 5));
+    state.set_lane_pulse(NODE, LANE, pulse(1, 3));
+    state.set_lane_pulse(NODE, "missing", pulse(1, 3));
+    state.set_agent_pulse(OTHER, pulse(1, 1));
+
+    let (_, agent) = state.agents().next().expect("Agent missing.");
+    assert_eq!(agent.pulse, Some(pulse(2, 5)));
+    assert_eq!(
+        agent.lanes.get(LANE),
+        Some(&LaneEntry {
+            kind: LaneKind::Map,
+            pulse: Some(pulse(1, 3))
+        })
+    );
+    assert_eq!(agent.lanes.len(), 1);
+    assert_eq!(state.agents().len(), 1);
+}
+
+#[test]
+fn remove_entries() {
+    let mut state = DashboardState::default();
+    state.add_agent(NODE);
+    state.add_agent(OTHER);
+    state.add_lane(NODE, LANE, LaneKind::Value);
+
+    state.remove_lane(NODE, LANE);
+    let (_, agent) = state.agents().next().expect("Agent missing.");
+    assert!(agent.lanes.is_empty());
+
+    state.remove_agent(NODE);
+    let nodes = state.agents().map(|(node, _)| node).collect::<Vec<_>>();
+    assert_eq!(nodes, vec![OTHER]);
+}
+
+#[test]
+fn sum_pulses() {
+    let mut state = DashboardState::default();
+    state.add_agent(NODE);
+    state.add_agent(OTHER);
+    assert_eq!(state.totals(), WarpUplinkPulse::default());
+
+    state.set_agent_pulse(NODE, pulse(2, 5));
+    state.set_agent_pulse(OTHER, pulse(3, 7));
+    assert_eq!(
+        state.totals(),
+        WarpUplinkPulse {
+            link_count: 5,
+            event_rate: 12,
+            event_count: 120,
+            command_rate: 2,
+            command_count: 20,
+        }
+    );
+}
@@ -0,0 +1,211 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{io, time::Duration};
+
+use parking_lot::Mutex;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::Line,
+    widgets::{Block, Row, Table, TableState},
+    DefaultTerminal, Frame,
+};
+use swimos_meta::WarpUplinkPulse;
+
+use crate::model::{AgentEntry, DashboardState};
+
+const AGENT_HEADER: [&str; 7] = [
+    "Node",
+    "Lanes",
+    "Links",
+    "Events/s",
+    "Commands/s",
+    "Events",
+    "Commands",
+];
+const LANE_HEADER: [&str; 7] = [
+    "Lane",
+    "Kind",
+    "Links",
+    "Events/s",
+    "Commands/s",
+    "Events",
+    "Commands",
+];
+const HELP: &str = "↑/↓ select agent   q quit";
+
+/// Run the dashboard until the user quits. The terminal is redrawn from the shared state after
+/// each key press and, otherwise, every `refresh` interval.
+///
+/// # Arguments
+/// * `host` - The URL of the plane (displayed in the title).
+/// * `state` - The state that is kept up to date by the discovery task.
+/// * `refresh` - The interval between redraws.
+pub fn run(host: &str, state: &Mutex<DashboardState>, refresh: Duration) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, host, state, refresh);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    host: &str,
+    state: &Mutex<DashboardState>,
+    refresh: Duration,
+) -> io::Result<()> {
+    let mut selection = TableState::default().with_selected(0);
+    loop {
+        terminal.draw(|frame| render(frame, host, &state.lock(), &mut selection))?;
+        if event::poll(refresh)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Down | KeyCode::Char('j') => selection.select_next(),
+                    KeyCode::Up | KeyCode::Char('k') => selection.select_previous(),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+fn render(frame: &mut Frame, host: &str, state: &DashboardState, selection: &mut TableState) {
+    let [header_area, agents_area, lanes_area, help_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Percentage(50),
+        Constraint::Fill(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let num_agents = state.agents().len();
+    let selected = match selection.selected() {
+        Some(_) if num_agents == 0 => None,
+        Some(i) => Some(i.min(num_agents - 1)),
+        None if num_agents > 0 => Some(0),
+        None => None,
+    };
+    selection.select(selected);
+
+    render_header(frame, header_area, host, state);
+
+    let agent_rows = state.agents().map(|(node, agent)| {
+        let mut cells = vec![node.to_string(), agent.lanes.len().to_string()];
+        cells.extend(pulse_cells(agent.pulse.as_ref()));
+        Row::new(cells)
+    });
+    let agents_table = Table::new(
+        agent_rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(12),
+        ],
+    )
+    .header(Row::new(AGENT_HEADER).bold())
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .block(Block::bordered().title(format!(" Agents ({}) ", num_agents)));
+    frame.render_stateful_widget(agents_table, agents_area, selection);
+
+    let selected_agent = selected.and_then(|i| state.agents().nth(i));
+    render_lanes(frame, lanes_area, selected_agent);
+
+    frame.render_widget(Line::from(HELP).dim(), help_area);
+}
+
+fn render_header(frame: &mut Frame, area: Rect, host: &str, state: &DashboardState) {
+    let line = if let Some(error) = state.error() {
+        Line::from(format!("{}: {}", host, error)).red()
+    } else {
+        let WarpUplinkPulse {
+            link_count,
+            event_rate,
+            command_rate,
+            ..
+        } = state.totals();
+        Line::from(format!(
+            "{}   links: {}   events/s: {}   commands/s: {}",
+            host, link_count, event_rate, command_rate
+        ))
+        .bold()
+    };
+    frame.render_widget(line, area);
+}
+
+fn render_lanes(frame: &mut Frame, area: Rect, agent: Option<(&str, &AgentEntry)>) {
+    let (title, rows) = match agent {
+        Some((node, agent)) => {
+            let rows = agent
+                .lanes
+                .iter()
+                .map(|(name, lane)| {
+                    let mut cells = vec![name.clone(), lane.kind.to_string()];
+                    cells.extend(pulse_cells(lane.pulse.as_ref()));
+                    Row::new(cells)
+                })
+                .collect::<Vec<_>>();
+            (format!(" Lanes of {} ", node), rows)
+        }
+        None => (" Lanes ".to_string(), vec![]),
+    };
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(12),
+        ],
+    )
+    .header(Row::new(LANE_HEADER).bold())
+    .block(Block::bordered().title(title));
+    frame.render_widget(table, area);
+}
+
+/// The cells for the metrics from a pulse. If no pulse has been received yet, placeholders are
+/// displayed.
+fn pulse_cells(pulse: Option<&WarpUplinkPulse>) -> Vec<String> {
+    match pulse {
+        Some(WarpUplinkPulse {
+            link_count,
+            event_rate,
+            event_count,
+            command_rate,
+            command_count,
+        }) => [
+            link_count,
+            event_rate,
+            command_rate,
+            event_count,
+            command_count,
+        ]
+        .into_iter()
+        .map(ToString::to_string)
+        .collect(),
+        None => vec!["-".to_string(); 5],
+    }
+}
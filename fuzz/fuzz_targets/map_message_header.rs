@@ -0,0 +1,26 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![no_main]
+
+use bytes::Bytes;
+use libfuzzer_sys::fuzz_target;
+use swimos_agent_protocol::peeling::{extract_header, extract_header_str};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = extract_header(&Bytes::copy_from_slice(data));
+    if let Ok(message) = std::str::from_utf8(data) {
+        let _ = extract_header_str(message);
+    }
+});
@@ -0,0 +1,43 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![no_main]
+
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+use swimos_messages::protocol::RawRequestMessageDecoder;
+use tokio_util::codec::Decoder;
+
+// The first byte selects how the remaining input is split into chunks so that the decoder is
+// also exercised with frames that arrive in several pieces.
+fuzz_target!(|data: &[u8]| {
+    let Some((chunk, input)) = data.split_first() else {
+        return;
+    };
+    let chunk_size = usize::from(*chunk).max(1);
+
+    let mut decoder = RawRequestMessageDecoder;
+    let mut buffer = BytesMut::new();
+    for part in input.chunks(chunk_size) {
+        buffer.extend_from_slice(part);
+        loop {
+            match decoder.decode(&mut buffer) {
+                Ok(Some(_)) => {}
+                Ok(None) => break,
+                Err(_) => return,
+            }
+        }
+    }
+    let _ = decoder.decode_eof(&mut buffer);
+});
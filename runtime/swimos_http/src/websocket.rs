@@ -63,6 +63,31 @@ pub fn negotiate_upgrade<'a, T, E>(
 ) -> Result<Option<Negotiated<'a, E::Extension>>, UpgradeError<E::Error>>
 where
     E: ExtensionProvider,
+{
+    negotiate_upgrade_with(
+        request,
+        |mut offered| (&mut offered).find_map(|p| protocols.get(p).copied()),
+        extension_provider,
+    )
+}
+
+/// Attempt to negotiate a websocket upgrade on a hyper request, choosing the protocol with a
+/// function. This behaves in the same way as [`negotiate_upgrade`] but, rather than accepting the
+/// first supported protocol offered by the client, the function is passed all of the protocols
+/// offered (in the order in which they were offered) and can select any of them.
+///
+/// # Arguments
+/// * `request` - The HTTP request.
+/// * `select_protocol` - Selects the protocol for the negotiation from those offered.
+/// * `extension_provider` - The extension provider (for example compression support).
+pub fn negotiate_upgrade_with<'a, T, E, F>(
+    request: &Request<T>,
+    select_protocol: F,
+    extension_provider: &E,
+) -> Result<Option<Negotiated<'a, E::Extension>>, UpgradeError<E::Error>>
+where
+    E: ExtensionProvider,
+    F: for<'b> FnOnce(&mut dyn Iterator<Item = &'b str>) -> Option<&'a str>,
 {
     let headers = request.headers();
     let has_conn = headers_contains(headers, http::header::CONNECTION, UPGRADE_STR);
@@ -86,13 +111,13 @@ where
             return Err(UpgradeError::NoKey);
         };
 
-        let protocol = headers
+        let mut offered = headers
             .get_all(http::header::SEC_WEBSOCKET_PROTOCOL)
             .iter()
             .flat_map(|h| h.as_bytes().split(|c| *c == b' ' || *c == b','))
             .map(trim)
-            .filter_map(|b| std::str::from_utf8(b).ok())
-            .find_map(|p| protocols.get(p).copied());
+            .filter_map(|b| std::str::from_utf8(b).ok());
+        let protocol = select_protocol(&mut offered);
 
         let ext_headers = extension_headers(headers);
 
@@ -14,9 +14,13 @@
 
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use std::convert::TryFrom;
-use std::fmt::{Debug, Write};
+use std::fmt::{Debug, Display, Write};
 use std::str::Utf8Error;
-use swimos_api::address::RelativeAddress;
+use std::sync::atomic::{AtomicU64, Ordering};
+use swimos_api::{
+    address::RelativeAddress,
+    agent::{KeyRange, SyncVersion},
+};
 use swimos_form::read::ReadError;
 use swimos_form::read::Recognizer;
 use swimos_form::write::StructuralWritable;
@@ -32,14 +36,79 @@ use uuid::Uuid;
 mod tests;
 
 /// Operations that can be performed on an agent.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Operation<T> {
-    Link,
-    Sync,
+    Link(LinkParams),
+    Sync(LinkParams),
+    /// A sync that is restricted to the entries of an ordered map lane with keys in a range.
+    SyncRange(LinkParams, KeyRange<Bytes>),
+    /// Acknowledges a page of events from a paged sync (see [`LinkParams::window`]).
+    Ack,
     Unlink,
     Command(T),
 }
 
+/// The optional `rate` and `prio` parameters that a remote can attach to a link or sync request
+/// to control how events from the lane are delivered to it. A sync request can additionally carry
+/// the version of the state of the lane that the remote last synced with and request that the
+/// sync is sent in pages.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LinkParams {
+    /// The maximum number of events per second that should be sent to the remote. Events that
+    /// exceed this rate are conflated.
+    pub rate: Option<f32>,
+    /// The priority of the uplink, relative to the other uplinks to the same remote. Uplinks with
+    /// a higher priority are written first (default: 0).
+    pub prio: Option<f32>,
+    /// The version of the state of a map lane that the remote already holds. If the lane recognizes
+    /// the version, only the entries that have changed since will be sent.
+    pub since: Option<SyncVersion>,
+    /// The maximum number of events that the remote will accept, during a sync, before it
+    /// acknowledges them. Once this many events have been sent, no more will be sent until an
+    /// [`Operation::Ack`] is received.
+    pub window: Option<u32>,
+}
+
+impl LinkParams {
+    pub fn new(rate: Option<f32>, prio: Option<f32>) -> Self {
+        LinkParams {
+            rate,
+            prio,
+            since: None,
+            window: None,
+        }
+    }
+
+    /// Attach the version of the state of the lane that the remote already holds.
+    pub fn with_since(mut self, since: Option<SyncVersion>) -> Self {
+        self.since = since;
+        self
+    }
+
+    /// Request that the sync is sent in pages of (at most) the specified number of events.
+    pub fn with_window(mut self, window: Option<u32>) -> Self {
+        self.window = window.filter(|w| *w > 0);
+        self
+    }
+
+    /// True if none of the parameters has been set.
+    pub fn is_empty(&self) -> bool {
+        self.rate.is_none() && self.prio.is_none() && self.since.is_none() && self.window.is_none()
+    }
+}
+
+// The parameters are compared by their bit patterns so that equality is reflexive.
+impl PartialEq for LinkParams {
+    fn eq(&self, other: &Self) -> bool {
+        self.rate.map(f32::to_bits) == other.rate.map(f32::to_bits)
+            && self.prio.map(f32::to_bits) == other.prio.map(f32::to_bits)
+            && self.since == other.since
+            && self.window == other.window
+    }
+}
+
+impl Eq for LinkParams {}
+
 impl<T> Operation<T> {
     pub fn is_command(&self) -> bool {
         matches!(self, Operation::Command(_))
@@ -51,8 +120,13 @@ impl<T> Operation<T> {
 pub enum Notification<T, U> {
     Linked,
     Synced,
+    /// A synced notification with a body (used by map lanes to report the version of their state).
+    SyncedWith(U),
     Unlinked(Option<U>),
     Event(T),
+    /// Advice to the remote that the agent is approaching its limits and that the link should back
+    /// off (the body is a Recon encoded [`swimos_api::agent::LinkAdvice`]).
+    Advisory(U),
 }
 
 impl<T, U> Notification<T, U>
@@ -76,6 +150,17 @@ where
         match self.0 {
             Notification::Linked => write!(f, "Linked"),
             Notification::Synced => write!(f, "Synced"),
+            Notification::SyncedWith(body) => {
+                if let Ok(body_str) = std::str::from_utf8(body.as_ref()) {
+                    f.debug_tuple("SyncedWith")
+                        .field(&format!("Str[{}]", body_str))
+                        .finish()
+                } else {
+                    f.debug_tuple("SyncedWith")
+                        .field(&format!("Bytes[{:?}]", body.as_ref()))
+                        .finish()
+                }
+            }
             Notification::Unlinked(msg) => {
                 if let Some(msg) = msg {
                     if let Ok(msg_str) = std::str::from_utf8(msg.as_ref()) {
@@ -91,6 +176,17 @@ where
                     f.debug_tuple("Unlinked").field(&"Absent").finish()
                 }
             }
+            Notification::Advisory(body) => {
+                if let Ok(body_str) = std::str::from_utf8(body.as_ref()) {
+                    f.debug_tuple("Advisory")
+                        .field(&format!("Str[{}]", body_str))
+                        .finish()
+                } else {
+                    f.debug_tuple("Advisory")
+                        .field(&format!("Bytes[{:?}]", body.as_ref()))
+                        .finish()
+                }
+            }
             Notification::Event(body) => {
                 if let Ok(body_str) = std::str::from_utf8(body.as_ref()) {
                     f.debug_tuple("Event")
@@ -106,12 +202,44 @@ where
     }
 }
 
+/// An identifier that is assigned to an envelope when it is received by the server so that its
+/// progress can be followed through the logs of the tasks that handle it. The ID is passed on to
+/// the lanes and event handlers that handle the envelope and is echoed on the responses that it
+/// causes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CorrelationId(u64);
+
+static NEXT_CORRELATION_ID: AtomicU64 = AtomicU64::new(1);
+
+impl CorrelationId {
+    pub fn new(id: u64) -> Self {
+        CorrelationId(id)
+    }
+
+    /// Generate an ID that is unique within this process.
+    pub fn generate() -> Self {
+        CorrelationId(NEXT_CORRELATION_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Display for CorrelationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
 /// Type of messages that can be sent to an agent/from a downlink..
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RequestMessage<P, T> {
     pub origin: Uuid,
     pub path: RelativeAddress<P>,
     pub envelope: Operation<T>,
+    /// Identifies the envelope in the tracing spans of the tasks that handle it.
+    pub correlation_id: Option<CorrelationId>,
 }
 
 /// Type of messages that can be sent by an agent/received by a downlink.
@@ -120,22 +248,60 @@ pub struct ResponseMessage<P, T, U> {
     pub origin: Uuid,
     pub path: RelativeAddress<P>,
     pub envelope: Notification<T, U>,
+    /// The correlation ID of the request envelope that caused the response to be sent (if it had one).
+    pub correlation_id: Option<CorrelationId>,
+    /// For events from lanes that track the origins of commands, the ID of the remote that sent the
+    /// command that caused the event.
+    pub command_origin: Option<Uuid>,
 }
 
 impl<P, T> RequestMessage<P, T> {
     pub fn link(source: Uuid, path: RelativeAddress<P>) -> Self {
+        Self::link_with_params(source, path, LinkParams::default())
+    }
+
+    pub fn link_with_params(source: Uuid, path: RelativeAddress<P>, params: LinkParams) -> Self {
         RequestMessage {
             origin: source,
             path,
-            envelope: Operation::Link,
+            envelope: Operation::Link(params),
+            correlation_id: None,
         }
     }
 
     pub fn sync(source: Uuid, path: RelativeAddress<P>) -> Self {
+        Self::sync_with_params(source, path, LinkParams::default())
+    }
+
+    pub fn sync_with_params(source: Uuid, path: RelativeAddress<P>, params: LinkParams) -> Self {
         RequestMessage {
             origin: source,
             path,
-            envelope: Operation::Sync,
+            envelope: Operation::Sync(params),
+            correlation_id: None,
+        }
+    }
+
+    pub fn sync_range(
+        source: Uuid,
+        path: RelativeAddress<P>,
+        params: LinkParams,
+        range: KeyRange<Bytes>,
+    ) -> Self {
+        RequestMessage {
+            origin: source,
+            path,
+            envelope: Operation::SyncRange(params, range),
+            correlation_id: None,
+        }
+    }
+
+    pub fn ack(source: Uuid, path: RelativeAddress<P>) -> Self {
+        RequestMessage {
+            origin: source,
+            path,
+            envelope: Operation::Ack,
+            correlation_id: None,
         }
     }
 
@@ -144,14 +310,22 @@ impl<P, T> RequestMessage<P, T> {
             origin: source,
             path,
             envelope: Operation::Unlink,
+            correlation_id: None,
         }
     }
 
+    /// Attach a correlation ID to the message.
+    pub fn with_correlation_id(mut self, id: CorrelationId) -> Self {
+        self.correlation_id = Some(id);
+        self
+    }
+
     pub fn command(source: Uuid, path: RelativeAddress<P>, body: T) -> Self {
         RequestMessage {
             origin: source,
             path,
             envelope: Operation::Command(body),
+            correlation_id: None,
         }
     }
 }
@@ -162,6 +336,8 @@ impl<P, T, U> ResponseMessage<P, T, U> {
             origin: target,
             path,
             envelope: Notification::Linked,
+            correlation_id: None,
+            command_origin: None,
         }
     }
 
@@ -170,6 +346,18 @@ impl<P, T, U> ResponseMessage<P, T, U> {
             origin: target,
             path,
             envelope: Notification::Synced,
+            correlation_id: None,
+            command_origin: None,
+        }
+    }
+
+    pub fn synced_with(target: Uuid, path: RelativeAddress<P>, body: U) -> Self {
+        ResponseMessage {
+            origin: target,
+            path,
+            envelope: Notification::SyncedWith(body),
+            correlation_id: None,
+            command_origin: None,
         }
     }
 
@@ -178,6 +366,8 @@ impl<P, T, U> ResponseMessage<P, T, U> {
             origin: target,
             path,
             envelope: Notification::Unlinked(body),
+            correlation_id: None,
+            command_origin: None,
         }
     }
 
@@ -186,8 +376,32 @@ impl<P, T, U> ResponseMessage<P, T, U> {
             origin: target,
             path,
             envelope: Notification::Event(body),
+            correlation_id: None,
+            command_origin: None,
         }
     }
+
+    pub fn advisory(target: Uuid, path: RelativeAddress<P>, body: U) -> Self {
+        ResponseMessage {
+            origin: target,
+            path,
+            envelope: Notification::Advisory(body),
+            correlation_id: None,
+            command_origin: None,
+        }
+    }
+
+    /// Attach the correlation ID of the request that caused the response to the message.
+    pub fn with_correlation_id(mut self, id: CorrelationId) -> Self {
+        self.correlation_id = Some(id);
+        self
+    }
+
+    /// Attach the ID of the remote that sent the command that caused an event to the message.
+    pub fn with_command_origin(mut self, origin: Uuid) -> Self {
+        self.command_origin = Some(origin);
+        self
+    }
 }
 
 /// An request message where the body is uninterpreted (represented as raw bytes).
@@ -211,6 +425,18 @@ pub struct ResponseMessageEncoder;
 
 const OP_SHIFT: usize = 61;
 const OP_MASK: u64 = 0b111 << OP_SHIFT;
+/// Flag in the header of a request or response frame indicating that a correlation ID follows the
+/// header.
+const CORRELATED: u64 = 1 << (OP_SHIFT - 1);
+/// Flag in the header of a sync frame indicating that the body carries a key range.
+const RANGED: u64 = 1 << (OP_SHIFT - 2);
+/// Flag in the header of an event frame indicating that the ID of the remote that sent the command
+/// that caused the event follows the header (after the correlation ID, if there is one).
+const ORIGINATED: u64 = 1 << (OP_SHIFT - 2);
+const REQUEST_LEN_MASK: u64 = !(OP_MASK | CORRELATED | RANGED);
+const RESPONSE_LEN_MASK: u64 = !(OP_MASK | CORRELATED | ORIGINATED);
+const CORRELATION_ID_LEN: usize = std::mem::size_of::<u64>();
+const ORIGIN_LEN: usize = std::mem::size_of::<u128>();
 
 const LINK: u64 = 0b000;
 const SYNC: u64 = 0b001;
@@ -238,38 +464,45 @@ where
             origin: source,
             path: RelativeAddress { node, lane },
             envelope,
+            correlation_id,
         } = item;
+        let correlation_id = *correlation_id;
         let node_str = node.as_ref();
         let lane_str = lane.as_ref();
-        dst.reserve(HEADER_INIT_LEN + lane_str.len() + node_str.len());
+        dst.reserve(HEADER_INIT_LEN + CORRELATION_ID_LEN + lane_str.len() + node_str.len());
         dst.put_u128(source.as_u128());
         let node_len = u32::try_from(node_str.len()).expect("Node name to long.");
         let lane_len = u32::try_from(lane_str.len()).expect("Lane name to long.");
         dst.put_u32(node_len);
         dst.put_u32(lane_len);
         match envelope {
-            Operation::Link => {
-                dst.put_u64(LINK << OP_SHIFT);
-                dst.put_slice(node_str.as_bytes());
-                dst.put_slice(lane_str.as_bytes());
+            Operation::Link(params) => {
+                encode_with_params(LINK, node_str, lane_str, params, correlation_id, dst);
             }
-            Operation::Sync => {
-                dst.put_u64(SYNC << OP_SHIFT);
+            Operation::Sync(params) => {
+                encode_with_params(SYNC, node_str, lane_str, params, correlation_id, dst);
+            }
+            Operation::SyncRange(params, range) => {
+                encode_ranged(node_str, lane_str, params, range, correlation_id, dst);
+            }
+            Operation::Ack => {
+                put_op(ACK_LEN as u64 | (SYNC << OP_SHIFT), correlation_id, dst);
                 dst.put_slice(node_str.as_bytes());
                 dst.put_slice(lane_str.as_bytes());
+                dst.put_u8(0);
             }
             Operation::Unlink => {
-                dst.put_u64(UNLINK << OP_SHIFT);
+                put_op(UNLINK << OP_SHIFT, correlation_id, dst);
                 dst.put_slice(node_str.as_bytes());
                 dst.put_slice(lane_str.as_bytes());
             }
             Operation::Command(body) => {
                 let body_bytes = body.as_ref();
                 let body_len = body_bytes.len() as u64;
-                if body_len & OP_MASK != 0 {
+                if body_len & !REQUEST_LEN_MASK != 0 {
                     panic!("Body too large.")
                 }
-                dst.put_u64(body_len | (COMMAND << OP_SHIFT));
+                put_op(body_len | (COMMAND << OP_SHIFT), correlation_id, dst);
                 dst.put_slice(node_str.as_bytes());
                 dst.put_slice(lane_str.as_bytes());
                 dst.reserve(body_bytes.len());
@@ -280,6 +513,199 @@ where
     }
 }
 
+/// The length of the body of a link or sync frame that carries [`LinkParams`].
+const PARAMS_LEN: usize = 12;
+/// The length of the body of a link or sync frame that carries [`LinkParams`] including a sync version.
+const PARAMS_WITH_SINCE_LEN: usize = PARAMS_LEN + 16;
+/// All of the operation codes are in use so acknowledgements are encoded as sync frames with a
+/// body of this length (which is distinct from the lengths of the bodies that carry parameters).
+const ACK_LEN: usize = 1;
+
+/// Link and sync frames only have a body if parameters were provided. Absent parameters are
+/// encoded as NaN (or 0 for the window) and the sync version is only included if it is present.
+/// Write the operation code and length of a request or response frame, followed by the correlation
+/// ID (if there is one).
+fn put_op(op: u64, correlation_id: Option<CorrelationId>, dst: &mut BytesMut) {
+    match correlation_id {
+        Some(CorrelationId(id)) => {
+            dst.put_u64(op | CORRELATED);
+            dst.put_u64(id);
+        }
+        None => dst.put_u64(op),
+    }
+}
+
+/// Write the operation code and length of an event frame, followed by the correlation ID and the
+/// origin of the command that caused the event (if they are present).
+fn put_event_op(
+    op: u64,
+    correlation_id: Option<CorrelationId>,
+    command_origin: Option<Uuid>,
+    dst: &mut BytesMut,
+) {
+    match command_origin {
+        Some(origin) => {
+            put_op(op | ORIGINATED, correlation_id, dst);
+            dst.put_u128(origin.as_u128());
+        }
+        None => put_op(op, correlation_id, dst),
+    }
+}
+
+fn encode_with_params(
+    code: u64,
+    node: &str,
+    lane: &str,
+    params: &LinkParams,
+    correlation_id: Option<CorrelationId>,
+    dst: &mut BytesMut,
+) {
+    if params.is_empty() {
+        put_op(code << OP_SHIFT, correlation_id, dst);
+        dst.put_slice(node.as_bytes());
+        dst.put_slice(lane.as_bytes());
+    } else {
+        let LinkParams {
+            rate,
+            prio,
+            since,
+            window,
+        } = params;
+        let len = if since.is_some() {
+            PARAMS_WITH_SINCE_LEN
+        } else {
+            PARAMS_LEN
+        };
+        put_op(len as u64 | (code << OP_SHIFT), correlation_id, dst);
+        dst.put_slice(node.as_bytes());
+        dst.put_slice(lane.as_bytes());
+        dst.reserve(len);
+        dst.put_f32(rate.unwrap_or(f32::NAN));
+        dst.put_f32(prio.unwrap_or(f32::NAN));
+        dst.put_u32(window.unwrap_or(0));
+        if let Some(SyncVersion { epoch, version }) = since {
+            dst.put_u64(*epoch);
+            dst.put_u64(*version);
+        }
+    }
+}
+
+fn decode_params(mut body: &[u8]) -> LinkParams {
+    if body.len() < PARAMS_LEN {
+        LinkParams::default()
+    } else {
+        let rate = Some(body.get_f32()).filter(|r| !r.is_nan());
+        let prio = Some(body.get_f32()).filter(|p| !p.is_nan());
+        let window = Some(body.get_u32()).filter(|w| *w > 0);
+        let since = if body.len() >= PARAMS_WITH_SINCE_LEN - PARAMS_LEN {
+            let epoch = body.get_u64();
+            let version = body.get_u64();
+            Some(SyncVersion { epoch, version })
+        } else {
+            None
+        };
+        LinkParams {
+            rate,
+            prio,
+            since,
+            window,
+        }
+    }
+}
+
+const HAS_SINCE: u8 = 0b001;
+const HAS_FROM: u8 = 0b010;
+const HAS_TO: u8 = 0b100;
+const RANGE_FLAGS_LEN: usize = 1;
+const BOUND_LEN_LEN: usize = std::mem::size_of::<u32>();
+
+/// The body of a ranged sync frame consists of a byte of flags (indicating which of the optional
+/// components are present), the parameters (including the sync version, if present) and then each
+/// bound of the range that is present, prefixed by its length.
+fn encode_ranged(
+    node: &str,
+    lane: &str,
+    params: &LinkParams,
+    range: &KeyRange<Bytes>,
+    correlation_id: Option<CorrelationId>,
+    dst: &mut BytesMut,
+) {
+    let LinkParams {
+        rate,
+        prio,
+        since,
+        window,
+    } = params;
+    let KeyRange { from, to } = range;
+    let mut flags = 0;
+    let mut len = RANGE_FLAGS_LEN + PARAMS_LEN;
+    if since.is_some() {
+        flags |= HAS_SINCE;
+        len += PARAMS_WITH_SINCE_LEN - PARAMS_LEN;
+    }
+    for (flag, bound) in [(HAS_FROM, from), (HAS_TO, to)] {
+        if let Some(bound) = bound {
+            flags |= flag;
+            len += BOUND_LEN_LEN + bound.len();
+        }
+    }
+    put_op(
+        len as u64 | (SYNC << OP_SHIFT) | RANGED,
+        correlation_id,
+        dst,
+    );
+    dst.put_slice(node.as_bytes());
+    dst.put_slice(lane.as_bytes());
+    dst.reserve(len);
+    dst.put_u8(flags);
+    dst.put_f32(rate.unwrap_or(f32::NAN));
+    dst.put_f32(prio.unwrap_or(f32::NAN));
+    dst.put_u32(window.unwrap_or(0));
+    if let Some(SyncVersion { epoch, version }) = since {
+        dst.put_u64(*epoch);
+        dst.put_u64(*version);
+    }
+    for bound in [from, to].into_iter().flatten() {
+        dst.put_u32(u32::try_from(bound.len()).expect("Key too long."));
+        dst.put_slice(bound);
+    }
+}
+
+fn decode_ranged(body: &[u8]) -> Result<(LinkParams, KeyRange<Bytes>), MessageDecodeError> {
+    let (flags, mut rest) = body
+        .split_first()
+        .ok_or(MessageDecodeError::InvalidKeyRange)?;
+    let params_len = if flags & HAS_SINCE != 0 {
+        PARAMS_WITH_SINCE_LEN
+    } else {
+        PARAMS_LEN
+    };
+    if rest.len() < params_len {
+        return Err(MessageDecodeError::InvalidKeyRange);
+    }
+    let params = decode_params(&rest[..params_len]);
+    rest.advance(params_len);
+    let mut get_bound = |flag: u8| {
+        if flags & flag == 0 {
+            Ok(None)
+        } else if rest.len() < BOUND_LEN_LEN {
+            Err(MessageDecodeError::InvalidKeyRange)
+        } else {
+            let len = rest.get_u32() as usize;
+            if rest.len() < len {
+                Err(MessageDecodeError::InvalidKeyRange)
+            } else {
+                let bound = Bytes::copy_from_slice(&rest[..len]);
+                rest.advance(len);
+                Ok(Some(bound))
+            }
+        }
+    };
+    let from = get_bound(HAS_FROM)?;
+    let to = get_bound(HAS_TO)?;
+    Ok((params, KeyRange { from, to }))
+}
+
 impl<P, B> Encoder<RequestMessage<P, B>> for RawRequestMessageEncoder
 where
     P: AsRef<str>,
@@ -313,10 +739,15 @@ where
             origin: source,
             path: RelativeAddress { node, lane },
             envelope,
+            correlation_id,
+            command_origin,
         } = item;
+        let correlation_id = *correlation_id;
         let node_str = node.as_ref();
         let lane_str = lane.as_ref();
-        dst.reserve(HEADER_INIT_LEN + lane_str.len() + node_str.len());
+        dst.reserve(
+            HEADER_INIT_LEN + CORRELATION_ID_LEN + ORIGIN_LEN + lane_str.len() + node_str.len(),
+        );
         dst.put_u128(source.as_u128());
         let node_len = u32::try_from(node_str.len()).expect("Node name to long.");
         let lane_len = u32::try_from(lane_str.len()).expect("Lane name to long.");
@@ -324,18 +755,34 @@ where
         dst.put_u32(lane_len);
         match envelope {
             Notification::Linked => {
-                dst.put_u64(LINKED << OP_SHIFT);
+                put_op(LINKED << OP_SHIFT, correlation_id, dst);
                 dst.put_slice(node_str.as_bytes());
                 dst.put_slice(lane_str.as_bytes());
             }
             Notification::Synced => {
-                dst.put_u64(SYNCED << OP_SHIFT);
+                put_op(SYNCED << OP_SHIFT, correlation_id, dst);
                 dst.put_slice(node_str.as_bytes());
                 dst.put_slice(lane_str.as_bytes());
             }
+            Notification::SyncedWith(body) => {
+                let body_bytes = body.as_ref();
+                put_op(
+                    body_bytes.len() as u64 | (SYNCED << OP_SHIFT),
+                    correlation_id,
+                    dst,
+                );
+                dst.put_slice(node_str.as_bytes());
+                dst.put_slice(lane_str.as_bytes());
+                dst.reserve(body_bytes.len());
+                dst.put_slice(body_bytes);
+            }
             Notification::Unlinked(body) => {
                 let body_len = body.as_ref().map(|b| b.as_ref().len()).unwrap_or_default();
-                dst.put_u64(body_len as u64 | (UNLINKED << OP_SHIFT));
+                put_op(
+                    body_len as u64 | (UNLINKED << OP_SHIFT),
+                    correlation_id,
+                    dst,
+                );
                 dst.put_slice(node_str.as_bytes());
                 dst.put_slice(lane_str.as_bytes());
                 dst.reserve(body_len);
@@ -343,13 +790,30 @@ where
                     dst.put_slice(body.as_ref());
                 }
             }
+            Notification::Advisory(body) => {
+                let body_bytes = body.as_ref();
+                put_op(
+                    body_bytes.len() as u64 | (LINKED << OP_SHIFT),
+                    correlation_id,
+                    dst,
+                );
+                dst.put_slice(node_str.as_bytes());
+                dst.put_slice(lane_str.as_bytes());
+                dst.reserve(body_bytes.len());
+                dst.put_slice(body_bytes);
+            }
             Notification::Event(body) => {
                 let body_bytes = body.as_ref();
                 let body_len = body_bytes.len() as u64;
-                if body_len & OP_MASK != 0 {
+                if body_len & !RESPONSE_LEN_MASK != 0 {
                     panic!("Body too large.")
                 }
-                dst.put_u64(body_len | (EVENT << OP_SHIFT));
+                put_event_op(
+                    body_len | (EVENT << OP_SHIFT),
+                    correlation_id,
+                    *command_origin,
+                    dst,
+                );
                 dst.put_slice(node_str.as_bytes());
                 dst.put_slice(lane_str.as_bytes());
                 dst.reserve(body_bytes.len());
@@ -397,10 +861,14 @@ where
             origin: source,
             path: RelativeAddress { node, lane },
             envelope,
+            correlation_id,
+            command_origin,
         } = item;
         let node_str = node.as_ref();
         let lane_str = lane.as_ref();
-        dst.reserve(HEADER_INIT_LEN + node_str.len() + lane_str.len());
+        dst.reserve(
+            HEADER_INIT_LEN + CORRELATION_ID_LEN + ORIGIN_LEN + node_str.len() + lane_str.len(),
+        );
         dst.put_u128(source.as_u128());
         let node_len = u32::try_from(node_str.len()).expect("Node name to long.");
         let lane_len = u32::try_from(lane_str.len()).expect("Lane name to long.");
@@ -408,18 +876,34 @@ where
         dst.put_u32(lane_len);
         match envelope {
             Notification::Linked => {
-                dst.put_u64(LINKED << OP_SHIFT);
+                put_op(LINKED << OP_SHIFT, correlation_id, dst);
                 dst.put_slice(node_str.as_bytes());
                 dst.put_slice(lane_str.as_bytes());
             }
             Notification::Synced => {
-                dst.put_u64(SYNCED << OP_SHIFT);
+                put_op(SYNCED << OP_SHIFT, correlation_id, dst);
                 dst.put_slice(node_str.as_bytes());
                 dst.put_slice(lane_str.as_bytes());
             }
+            Notification::SyncedWith(body) => {
+                let body_bytes = body.as_ref();
+                put_op(
+                    body_bytes.len() as u64 | (SYNCED << OP_SHIFT),
+                    correlation_id,
+                    dst,
+                );
+                dst.put_slice(node_str.as_bytes());
+                dst.put_slice(lane_str.as_bytes());
+                dst.reserve(body_bytes.len());
+                dst.put_slice(body_bytes);
+            }
             Notification::Unlinked(body) => {
                 let body_len = body.as_ref().map(|b| b.as_ref().len()).unwrap_or_default();
-                dst.put_u64(body_len as u64 | (UNLINKED << OP_SHIFT));
+                put_op(
+                    body_len as u64 | (UNLINKED << OP_SHIFT),
+                    correlation_id,
+                    dst,
+                );
                 dst.put_slice(node_str.as_bytes());
                 dst.put_slice(lane_str.as_bytes());
                 dst.reserve(body_len);
@@ -427,8 +911,28 @@ where
                     dst.put_slice(body.as_ref());
                 }
             }
+            Notification::Advisory(body) => {
+                let body_bytes = body.as_ref();
+                put_op(
+                    body_bytes.len() as u64 | (LINKED << OP_SHIFT),
+                    correlation_id,
+                    dst,
+                );
+                dst.put_slice(node_str.as_bytes());
+                dst.put_slice(lane_str.as_bytes());
+                dst.reserve(body_bytes.len());
+                dst.put_slice(body_bytes);
+            }
             Notification::Event(body) => {
-                put_with_body(node.as_ref(), lane.as_ref(), EVENT, &body, dst);
+                put_with_body(
+                    node.as_ref(),
+                    lane.as_ref(),
+                    EVENT,
+                    &body,
+                    correlation_id,
+                    command_origin,
+                    dst,
+                );
             }
         }
         Ok(())
@@ -440,10 +944,23 @@ fn put_with_body<T: StructuralWritable>(
     lane: &str,
     code: u64,
     body: &T,
+    correlation_id: Option<CorrelationId>,
+    command_origin: Option<Uuid>,
     dst: &mut BytesMut,
 ) {
     let body_len_offset = dst.remaining();
     dst.put_u64(0);
+    let mut flags = match correlation_id {
+        Some(CorrelationId(id)) => {
+            dst.put_u64(id);
+            CORRELATED
+        }
+        None => 0,
+    };
+    if let Some(origin) = command_origin {
+        dst.put_u128(origin.as_u128());
+        flags |= ORIGINATED;
+    }
     dst.put_slice(node.as_bytes());
     dst.put_slice(lane.as_bytes());
     let body_offset = dst.remaining();
@@ -459,11 +976,11 @@ fn put_with_body<T: StructuralWritable>(
         }
     }
     let body_len = (dst.remaining() - body_offset) as u64;
-    if body_len & OP_MASK != 0 {
+    if body_len & !RESPONSE_LEN_MASK != 0 {
         panic!("Body too large.")
     }
     let mut rewound = &mut dst.as_mut()[body_len_offset..];
-    rewound.put_u64(body_len | (code << OP_SHIFT));
+    rewound.put_u64(body_len | (code << OP_SHIFT) | flags);
 }
 
 #[derive(Debug)]
@@ -472,6 +989,7 @@ enum RequestState<P, T> {
     ReadingBody {
         source: Uuid,
         path: RelativeAddress<P>,
+        correlation_id: Option<CorrelationId>,
         remaining: usize,
     },
     AfterBody {
@@ -519,6 +1037,24 @@ impl<T, R> RequestMessageDecoder<T, R> {
 
 const HEADER_INIT_LEN: usize = 32;
 
+/// The lengths in a frame header are not trusted so the decoders will not reserve more than this
+/// much additional space in the buffer before the data has actually arrived.
+const MAX_RESERVE: usize = 64 * 1024;
+
+/// Reserve space in the buffer for the remainder of a frame of the required length.
+fn reserve_for_frame(src: &mut BytesMut, required: usize) {
+    let additional = required.saturating_sub(src.remaining()).min(MAX_RESERVE);
+    src.reserve(additional);
+}
+
+/// Compute the total length of a frame from the lengths in its header.
+fn frame_len(node_len: usize, lane_len: usize, body_len: usize) -> usize {
+    HEADER_INIT_LEN
+        .saturating_add(node_len)
+        .saturating_add(lane_len)
+        .saturating_add(body_len)
+}
+
 /// Error type for the protocol decoders.
 #[derive(Error, Debug)]
 pub enum MessageDecodeError {
@@ -534,6 +1070,9 @@ pub enum MessageDecodeError {
     /// The body of a frame could not be deserialized.
     #[error("Invalid message body: {0}")]
     Body(#[from] AsyncParseError),
+    /// The key range in a sync frame was malformed.
+    #[error("Invalid key range in a sync request.")]
+    InvalidKeyRange,
 }
 
 impl From<ReadError> for MessageDecodeError {
@@ -575,29 +1114,65 @@ where
                     let lane_len = header.get_u32() as usize;
                     let body_len_and_tag = header.get_u64();
                     let tag = (body_len_and_tag & OP_MASK) >> OP_SHIFT;
-                    if src.remaining() < HEADER_INIT_LEN + node_len + lane_len {
-                        src.reserve(node_len + lane_len);
+                    let id_len = correlation_id_len(body_len_and_tag);
+                    let params_len = if matches!(tag, LINK | SYNC) {
+                        (body_len_and_tag & REQUEST_LEN_MASK) as usize
+                    } else {
+                        0
+                    };
+                    let required = frame_len(node_len, lane_len, params_len).saturating_add(id_len);
+                    if src.remaining() < required {
+                        reserve_for_frame(src, required);
                         break Ok(None);
                     }
                     src.advance(HEADER_INIT_LEN);
-                    let node = Text::new(std::str::from_utf8(&src.as_ref()[0..node_len])?);
+                    let correlation_id = get_correlation_id(body_len_and_tag, src);
+                    // The same few node and lane names occur in almost every request so they are
+                    // interned, rather than allocated again for each request.
+                    let node = Text::interned(std::str::from_utf8(&src.as_ref()[0..node_len])?);
                     src.advance(node_len);
-                    let lane = Text::new(std::str::from_utf8(&src.as_ref()[0..lane_len])?);
+                    let lane = Text::interned(std::str::from_utf8(&src.as_ref()[0..lane_len])?);
                     src.advance(lane_len);
                     let path = RelativeAddress::new(node, lane);
                     match tag {
                         LINK => {
+                            let params = decode_params(&src.as_ref()[0..params_len]);
+                            src.advance(params_len);
+                            break Ok(Some(RequestMessage {
+                                origin: id,
+                                path,
+                                envelope: Operation::Link(params),
+                                correlation_id,
+                            }));
+                        }
+                        SYNC if params_len == ACK_LEN => {
+                            src.advance(params_len);
+                            break Ok(Some(RequestMessage {
+                                origin: id,
+                                path,
+                                envelope: Operation::Ack,
+                                correlation_id,
+                            }));
+                        }
+                        SYNC if body_len_and_tag & RANGED != 0 => {
+                            let result = decode_ranged(&src.as_ref()[0..params_len]);
+                            src.advance(params_len);
+                            let (params, range) = result?;
                             break Ok(Some(RequestMessage {
                                 origin: id,
                                 path,
-                                envelope: Operation::Link,
+                                envelope: Operation::SyncRange(params, range),
+                                correlation_id,
                             }));
                         }
                         SYNC => {
+                            let params = decode_params(&src.as_ref()[0..params_len]);
+                            src.advance(params_len);
                             break Ok(Some(RequestMessage {
                                 origin: id,
                                 path,
-                                envelope: Operation::Sync,
+                                envelope: Operation::Sync(params),
+                                correlation_id,
                             }));
                         }
                         UNLINK => {
@@ -605,13 +1180,15 @@ where
                                 origin: id,
                                 path,
                                 envelope: Operation::Unlink,
+                                correlation_id,
                             }));
                         }
                         COMMAND => {
-                            let body_len = (body_len_and_tag & !OP_MASK) as usize;
+                            let body_len = (body_len_and_tag & REQUEST_LEN_MASK) as usize;
                             *state = RequestState::ReadingBody {
                                 source: id,
                                 path,
+                                correlation_id,
                                 remaining: body_len,
                             };
                         }
@@ -623,6 +1200,7 @@ where
                 RequestState::ReadingBody {
                     source,
                     path,
+                    correlation_id,
                     remaining,
                 } => {
                     let to_split = (*remaining).min(src.remaining());
@@ -641,6 +1219,7 @@ where
                                     origin: *source,
                                     path: std::mem::take(path),
                                     envelope: Operation::Command(result),
+                                    correlation_id: *correlation_id,
                                 }),
                                 remaining: *remaining,
                             }
@@ -657,6 +1236,7 @@ where
                                         origin: *source,
                                         path: std::mem::take(path),
                                         envelope: Operation::Command(result),
+                                        correlation_id: *correlation_id,
                                     }))
                                 } else {
                                     Err(MessageDecodeError::incomplete())
@@ -727,13 +1307,17 @@ impl Decoder for RawResponseMessageDecoder {
         let node_len = header.get_u32() as usize;
         let lane_len = header.get_u32() as usize;
         let body_len_and_tag = header.get_u64();
-        let body_len = (body_len_and_tag & !OP_MASK) as usize;
-        let required = HEADER_INIT_LEN + node_len + lane_len + body_len;
+        let body_len = (body_len_and_tag & RESPONSE_LEN_MASK) as usize;
+        let required = frame_len(node_len, lane_len, body_len)
+            .saturating_add(correlation_id_len(body_len_and_tag))
+            .saturating_add(origin_len(body_len_and_tag));
         if src.remaining() < required {
-            src.reserve(required - src.remaining());
+            reserve_for_frame(src, required);
             return Ok(None);
         }
         src.advance(HEADER_INIT_LEN);
+        let correlation_id = get_correlation_id(body_len_and_tag, src);
+        let command_origin = get_origin(body_len_and_tag, src);
         let node_bytes = src.split_to(node_len).freeze();
         let node = BytesStr::try_from(node_bytes)
             .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidData))?;
@@ -744,22 +1328,41 @@ impl Decoder for RawResponseMessageDecoder {
 
         let path = RelativeAddress::new(node, lane);
         let tag = (body_len_and_tag & OP_MASK) >> OP_SHIFT;
-        match tag {
-            LINKED => Ok(Some(BytesResponseMessage::linked(target, path))),
-            SYNCED => Ok(Some(BytesResponseMessage::synced(target, path))),
+        let message = match tag {
+            LINKED => {
+                if body_len == 0 {
+                    BytesResponseMessage::linked(target, path)
+                } else {
+                    let body = src.split_to(body_len).freeze();
+                    BytesResponseMessage::advisory(target, path, body)
+                }
+            }
+            SYNCED => {
+                if body_len == 0 {
+                    BytesResponseMessage::synced(target, path)
+                } else {
+                    let body = src.split_to(body_len).freeze();
+                    BytesResponseMessage::synced_with(target, path, body)
+                }
+            }
             UNLINKED => {
                 let body = if body_len == 0 {
                     None
                 } else {
                     Some(src.split_to(body_len).freeze())
                 };
-                Ok(Some(BytesResponseMessage::unlinked(target, path, body)))
+                BytesResponseMessage::unlinked(target, path, body)
             }
             _ => {
                 let body = src.split_to(body_len).freeze();
-                Ok(Some(BytesResponseMessage::event(target, path, body)))
+                BytesResponseMessage::event(target, path, body)
             }
-        }
+        };
+        Ok(Some(ResponseMessage {
+            correlation_id,
+            command_origin,
+            ..message
+        }))
     }
 }
 
@@ -778,13 +1381,15 @@ impl Decoder for RawRequestMessageDecoder {
         let node_len = header.get_u32() as usize;
         let lane_len = header.get_u32() as usize;
         let body_len_and_tag = header.get_u64();
-        let body_len = (body_len_and_tag & !OP_MASK) as usize;
-        let required = HEADER_INIT_LEN + node_len + lane_len + body_len;
+        let body_len = (body_len_and_tag & REQUEST_LEN_MASK) as usize;
+        let required = frame_len(node_len, lane_len, body_len)
+            .saturating_add(correlation_id_len(body_len_and_tag));
         if src.remaining() < required {
-            src.reserve(required);
+            reserve_for_frame(src, required);
             return Ok(None);
         }
         src.advance(HEADER_INIT_LEN);
+        let correlation_id = get_correlation_id(body_len_and_tag, src);
         let node_bytes = src.split_to(node_len).freeze();
         let node = BytesStr::try_from(node_bytes)
             .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidData))?;
@@ -795,14 +1400,72 @@ impl Decoder for RawRequestMessageDecoder {
 
         let path = RelativeAddress::new(node, lane);
         let tag = (body_len_and_tag & OP_MASK) >> OP_SHIFT;
-        match tag {
-            LINK => Ok(Some(RequestMessage::link(origin, path))),
-            SYNC => Ok(Some(RequestMessage::sync(origin, path))),
-            UNLINK => Ok(Some(RequestMessage::unlink(origin, path))),
+        let message = match tag {
+            LINK => {
+                let params = decode_params(&src.split_to(body_len));
+                RequestMessage::link_with_params(origin, path, params)
+            }
+            SYNC if body_len == ACK_LEN => {
+                src.advance(body_len);
+                RequestMessage::ack(origin, path)
+            }
+            SYNC if body_len_and_tag & RANGED != 0 => {
+                let (params, range) = decode_ranged(&src.split_to(body_len))
+                    .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidData))?;
+                RequestMessage::sync_range(origin, path, params, range)
+            }
+            SYNC => {
+                let params = decode_params(&src.split_to(body_len));
+                RequestMessage::sync_with_params(origin, path, params)
+            }
+            UNLINK => RequestMessage::unlink(origin, path),
             _ => {
                 let body = src.split_to(body_len).freeze();
-                Ok(Some(RequestMessage::command(origin, path, body)))
+                RequestMessage::command(origin, path, body)
             }
-        }
+        };
+        Ok(Some(RequestMessage {
+            correlation_id,
+            ..message
+        }))
+    }
+}
+
+/// The number of bytes occupied by the correlation ID in a request or response frame.
+fn correlation_id_len(body_len_and_tag: u64) -> usize {
+    if body_len_and_tag & CORRELATED != 0 {
+        CORRELATION_ID_LEN
+    } else {
+        0
+    }
+}
+
+/// Read the correlation ID from a request or response frame (the header must already have been
+/// consumed).
+fn get_correlation_id(body_len_and_tag: u64, src: &mut BytesMut) -> Option<CorrelationId> {
+    if body_len_and_tag & CORRELATED != 0 {
+        Some(CorrelationId(src.get_u64()))
+    } else {
+        None
+    }
+}
+
+/// The number of bytes occupied by the origin of the command that caused an event in a response
+/// frame.
+fn origin_len(body_len_and_tag: u64) -> usize {
+    if body_len_and_tag & ORIGINATED != 0 {
+        ORIGIN_LEN
+    } else {
+        0
+    }
+}
+
+/// Read the origin of the command that caused an event from a response frame (the header and the
+/// correlation ID must already have been consumed).
+fn get_origin(body_len_and_tag: u64, src: &mut BytesMut) -> Option<Uuid> {
+    if body_len_and_tag & ORIGINATED != 0 {
+        Some(Uuid::from_u128(src.get_u128()))
+    } else {
+        None
     }
 }
@@ -13,12 +13,13 @@
 // limitations under the License.
 
 use crate::protocol::{
-    BytesResponseMessage, MessageDecodeError, RawRequestMessage, RawRequestMessageEncoder,
-    RawResponseMessageDecoder, RequestMessage, RequestMessageDecoder, ResponseMessage,
-    ResponseMessageEncoder, COMMAND, EVENT, HEADER_INIT_LEN, LINK, LINKED, OP_MASK, OP_SHIFT, SYNC,
-    SYNCED, UNLINK, UNLINKED,
+    BytesResponseMessage, CorrelationId, LinkParams, MessageDecodeError, Notification, Operation,
+    RawRequestMessage, RawRequestMessageDecoder, RawRequestMessageEncoder,
+    RawResponseMessageDecoder, RawResponseMessageEncoder, RequestMessage, RequestMessageDecoder,
+    ResponseMessage, ResponseMessageEncoder, COMMAND, EVENT, HEADER_INIT_LEN, LINK, LINKED,
+    MAX_RESERVE, OP_MASK, OP_SHIFT, SYNC, SYNCED, UNLINK, UNLINKED,
 };
-use bytes::{Buf, Bytes, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use futures::future::join;
 use futures::{SinkExt, StreamExt};
 use std::fmt::Debug;
@@ -26,6 +27,7 @@ use std::fmt::Write;
 use std::io::ErrorKind;
 use std::num::NonZeroUsize;
 use swimos_api::address::RelativeAddress;
+use swimos_api::agent::{KeyRange, SyncVersion};
 use swimos_form::read::RecognizerReadable;
 use swimos_form::write::StructuralWritable;
 use swimos_form::Form;
@@ -272,6 +274,167 @@ fn decode_sync_frame() {
     );
 }
 
+#[test]
+fn decode_link_and_sync_frames_with_params() {
+    let id = make_addr();
+    let node = "my_node";
+    let lane = "lane";
+    let params = LinkParams::new(Some(1.0), Some(2.5));
+    let path = RelativeAddress::new(Text::new(node), Text::new(lane));
+
+    let frame = RawRequestMessage::link_with_params(id, RelativeAddress::new(node, lane), params);
+    let result = round_trip::<_, Example>(frame);
+    check_result(
+        result,
+        RequestMessage::link_with_params(id, path.clone(), params),
+    );
+
+    let rate_only = LinkParams::new(Some(0.5), None);
+    let frame =
+        RawRequestMessage::sync_with_params(id, RelativeAddress::new(node, lane), rate_only);
+    let result = round_trip::<_, Example>(frame);
+    check_result(
+        result,
+        RequestMessage::sync_with_params(id, path, rate_only),
+    );
+}
+
+#[test]
+fn decode_sync_frame_with_version() {
+    let id = make_addr();
+    let node = "my_node";
+    let lane = "lane";
+    let path = RelativeAddress::new(Text::new(node), Text::new(lane));
+
+    let params = LinkParams::default().with_since(Some(SyncVersion::new(7, 12)));
+    let frame = RawRequestMessage::sync_with_params(id, RelativeAddress::new(node, lane), params);
+    let result = round_trip::<_, Example>(frame);
+    check_result(
+        result,
+        RequestMessage::sync_with_params(id, path.clone(), params),
+    );
+
+    let params = LinkParams::new(Some(2.0), None).with_since(Some(SyncVersion::new(1, 0)));
+    let frame = RawRequestMessage::sync_with_params(id, RelativeAddress::new(node, lane), params);
+    let result = round_trip::<_, Example>(frame);
+    check_result(result, RequestMessage::sync_with_params(id, path, params));
+}
+
+#[test]
+fn decode_sync_frame_with_window() {
+    let id = make_addr();
+    let node = "my_node";
+    let lane = "lane";
+    let path = RelativeAddress::new(Text::new(node), Text::new(lane));
+
+    let params = LinkParams::default().with_window(Some(64));
+    let frame = RawRequestMessage::sync_with_params(id, RelativeAddress::new(node, lane), params);
+    let result = round_trip::<_, Example>(frame);
+    check_result(
+        result,
+        RequestMessage::sync_with_params(id, path.clone(), params),
+    );
+
+    let params = LinkParams::new(None, Some(1.0))
+        .with_since(Some(SyncVersion::new(3, 4)))
+        .with_window(Some(16));
+    let frame = RawRequestMessage::sync_with_params(id, RelativeAddress::new(node, lane), params);
+    let result = round_trip::<_, Example>(frame);
+    check_result(result, RequestMessage::sync_with_params(id, path, params));
+}
+
+#[test]
+fn decode_sync_frame_with_range() {
+    let id = make_addr();
+    let node = "my_node";
+    let lane = "lane";
+    let path = RelativeAddress::new(Text::new(node), Text::new(lane));
+
+    let cases = [
+        (
+            LinkParams::default(),
+            KeyRange::new(Some(Bytes::from_static(b"100")), None),
+        ),
+        (
+            LinkParams::new(Some(1.0), None).with_window(Some(8)),
+            KeyRange::new(None, Some(Bytes::from_static(b"\"z\""))),
+        ),
+        (
+            LinkParams::default().with_since(Some(SyncVersion::new(2, 5))),
+            KeyRange::new(
+                Some(Bytes::from_static(b"100")),
+                Some(Bytes::from_static(b"200")),
+            ),
+        ),
+    ];
+
+    for (params, range) in cases {
+        let frame = RawRequestMessage::sync_range(
+            id,
+            RelativeAddress::new(node, lane),
+            params,
+            range.clone(),
+        );
+        let result = round_trip::<_, Example>(frame.clone());
+        check_result(
+            result,
+            RequestMessage::sync_range(id, path.clone(), params, range.clone()),
+        );
+
+        let mut buffer = BytesMut::new();
+        assert!(RawRequestMessageEncoder.encode(frame, &mut buffer).is_ok());
+        let decoded = RawRequestMessageDecoder
+            .decode(&mut buffer)
+            .expect("Decoding failed.")
+            .expect("Incomplete frame.");
+        assert!(buffer.is_empty());
+        assert_eq!(decoded.envelope, Operation::SyncRange(params, range));
+    }
+}
+
+#[test]
+fn decode_ack_frame() {
+    let id = make_addr();
+    let node = "my_node";
+    let lane = "lane";
+    let frame = RawRequestMessage::ack(id, RelativeAddress::new(node, lane));
+    let result = round_trip::<_, Example>(frame);
+    check_result(
+        result,
+        RequestMessage::ack(id, RelativeAddress::new(Text::new(node), Text::new(lane))),
+    );
+
+    let frame = RawRequestMessage::ack(id, RelativeAddress::new(node, lane));
+    let mut buffer = BytesMut::new();
+    assert!(RawRequestMessageEncoder.encode(frame, &mut buffer).is_ok());
+    let decoded = RawRequestMessageDecoder
+        .decode(&mut buffer)
+        .expect("Decoding failed.")
+        .expect("Incomplete frame.");
+    assert!(buffer.is_empty());
+    assert_eq!(decoded.envelope, Operation::Ack);
+}
+
+#[test]
+fn raw_decode_frames_with_params() {
+    let id = make_addr();
+    let params = LinkParams::new(None, Some(-1.0));
+    let frame =
+        RawRequestMessage::link_with_params(id, RelativeAddress::new("my_node", "lane"), params);
+
+    let mut buffer = BytesMut::new();
+    assert!(RawRequestMessageEncoder.encode(frame, &mut buffer).is_ok());
+    let decoded = RawRequestMessageDecoder
+        .decode(&mut buffer)
+        .expect("Decoding failed.")
+        .expect("Incomplete frame.");
+    assert!(buffer.is_empty());
+    assert_eq!(decoded.origin, id);
+    assert_eq!(decoded.path.node.as_str(), "my_node");
+    assert_eq!(decoded.path.lane.as_str(), "lane");
+    assert_eq!(decoded.envelope, Operation::Link(params));
+}
+
 #[test]
 fn decode_unlink_frame() {
     let id = make_addr();
@@ -287,6 +450,31 @@ fn decode_unlink_frame() {
     );
 }
 
+#[test]
+fn decoded_names_are_interned() {
+    let id = make_addr();
+    let node = "/unit/sensors/building_one/floor_three";
+    let lane = "a_lane_with_a_long_descriptive_name";
+
+    let decode = || match round_trip::<_, Example>(RawRequestMessage::unlink(
+        id,
+        RelativeAddress::new(node, lane),
+    )) {
+        Ok(Some(RequestMessage { path, .. })) => path,
+        ow => panic!("Unexpected result: {:?}", ow),
+    };
+
+    let first = decode();
+    let second = decode();
+    assert_eq!(
+        first,
+        RelativeAddress::new(Text::new(node), Text::new(lane))
+    );
+    assert!(first.node.is_shared() && first.lane.is_shared());
+    assert!(std::ptr::eq(first.node.as_str(), second.node.as_str()));
+    assert!(std::ptr::eq(first.lane.as_str(), second.lane.as_str()));
+}
+
 #[test]
 fn decode_command_frame() {
     let id = make_addr();
@@ -314,6 +502,179 @@ fn decode_command_frame() {
     );
 }
 
+#[test]
+fn decode_correlated_command_frame() {
+    let id = make_addr();
+    let correlation_id = CorrelationId::new(0xabcd);
+    let node = "my_node";
+    let lane = "lane";
+
+    let record = Example {
+        first: 1,
+        second: 2,
+    };
+    let as_text = print_recon_compact(&record).to_string();
+
+    let frame =
+        RawRequestMessage::command(id, RelativeAddress::new(node, lane), as_text.as_bytes())
+            .with_correlation_id(correlation_id);
+
+    let result = round_trip::<_, Example>(frame);
+
+    check_result(
+        result,
+        RequestMessage::command(
+            id,
+            RelativeAddress::new(Text::new(node), Text::new(lane)),
+            record,
+        )
+        .with_correlation_id(correlation_id),
+    );
+}
+
+#[test]
+fn raw_decode_correlated_frames_with_params() {
+    let id = make_addr();
+    let correlation_id = CorrelationId::generate();
+    let params = LinkParams::new(None, Some(-1.0));
+    let frame =
+        RawRequestMessage::link_with_params(id, RelativeAddress::new("my_node", "lane"), params)
+            .with_correlation_id(correlation_id);
+
+    let mut buffer = BytesMut::new();
+    assert!(RawRequestMessageEncoder.encode(frame, &mut buffer).is_ok());
+    let decoded = RawRequestMessageDecoder
+        .decode(&mut buffer)
+        .expect("Decoding failed.")
+        .expect("Incomplete frame.");
+    assert!(buffer.is_empty());
+    assert_eq!(decoded.origin, id);
+    assert_eq!(decoded.correlation_id, Some(correlation_id));
+    assert_eq!(decoded.path.node.as_str(), "my_node");
+    assert_eq!(decoded.path.lane.as_str(), "lane");
+    assert_eq!(decoded.envelope, Operation::Link(params));
+}
+
+#[test]
+fn decode_correlated_response_frames() {
+    let id = make_addr();
+    let correlation_id = CorrelationId::new(0x1234);
+    let node = "my_node";
+    let lane = "lane";
+
+    let frame = ResponseMessage::<_, Example, Bytes>::linked(id, RelativeAddress::new(node, lane))
+        .with_correlation_id(correlation_id);
+    let result = round_trip_rawresponse(frame);
+    check_result_rawresponse(
+        result,
+        BytesResponseMessage::linked(id, bytes_path(node, lane))
+            .with_correlation_id(correlation_id),
+    );
+
+    let record = Example {
+        first: 1,
+        second: 2,
+    };
+    let as_text = print_recon_compact(&record).to_string();
+    let frame = ResponseMessage::<_, _, Bytes>::event(id, RelativeAddress::new(node, lane), record)
+        .with_correlation_id(correlation_id);
+    let result = round_trip_rawresponse(frame);
+    check_result_rawresponse(
+        result,
+        BytesResponseMessage::event(id, bytes_path(node, lane), Bytes::from(as_text))
+            .with_correlation_id(correlation_id),
+    );
+}
+
+#[test]
+fn raw_encode_correlated_response_frame() {
+    let id = make_addr();
+    let correlation_id = CorrelationId::new(7);
+    let body = Bytes::from_static(b"@unlinked");
+    let frame: ResponseMessage<&str, Bytes, Bytes> = ResponseMessage::unlinked(
+        id,
+        RelativeAddress::new("my_node", "lane"),
+        Some(body.clone()),
+    )
+    .with_correlation_id(correlation_id);
+
+    let mut buffer = BytesMut::new();
+    assert!(RawResponseMessageEncoder
+        .encode(&frame, &mut buffer)
+        .is_ok());
+    let decoded = RawResponseMessageDecoder
+        .decode(&mut buffer)
+        .expect("Decoding failed.")
+        .expect("Incomplete frame.");
+    assert!(buffer.is_empty());
+    assert_eq!(decoded.correlation_id, Some(correlation_id));
+    assert_eq!(decoded.envelope, Notification::Unlinked(Some(body)));
+}
+
+#[test]
+fn decode_originated_response_frames() {
+    let id = make_addr();
+    let origin = Uuid::from_u128(0x5678);
+    let node = "my_node";
+    let lane = "lane";
+
+    let record = Example {
+        first: 1,
+        second: 2,
+    };
+    let as_text = print_recon_compact(&record).to_string();
+    let frame = ResponseMessage::<_, _, Bytes>::event(id, RelativeAddress::new(node, lane), record)
+        .with_command_origin(origin);
+    let result = round_trip_rawresponse(frame);
+    check_result_rawresponse(
+        result,
+        BytesResponseMessage::event(id, bytes_path(node, lane), Bytes::from(as_text.clone()))
+            .with_command_origin(origin),
+    );
+
+    let correlation_id = CorrelationId::new(0x1234);
+    let frame = ResponseMessage::<_, _, Bytes>::event(id, RelativeAddress::new(node, lane), record)
+        .with_correlation_id(correlation_id)
+        .with_command_origin(origin);
+    let result = round_trip_rawresponse(frame);
+    check_result_rawresponse(
+        result,
+        BytesResponseMessage::event(id, bytes_path(node, lane), Bytes::from(as_text))
+            .with_correlation_id(correlation_id)
+            .with_command_origin(origin),
+    );
+}
+
+#[test]
+fn raw_encode_originated_response_frame() {
+    let id = make_addr();
+    let origin = Uuid::from_u128(0x5678);
+    let body = Bytes::from_static(b"@update(key:1) 2");
+    let frame: ResponseMessage<&str, Bytes, Bytes> =
+        ResponseMessage::event(id, RelativeAddress::new("my_node", "lane"), body.clone())
+            .with_command_origin(origin);
+
+    let mut buffer = BytesMut::new();
+    assert!(RawResponseMessageEncoder
+        .encode(&frame, &mut buffer)
+        .is_ok());
+    let decoded = RawResponseMessageDecoder
+        .decode(&mut buffer)
+        .expect("Decoding failed.")
+        .expect("Incomplete frame.");
+    assert!(buffer.is_empty());
+    assert_eq!(decoded.command_origin, Some(origin));
+    assert_eq!(decoded.envelope, Notification::Event(body));
+}
+
+#[test]
+fn generated_correlation_ids_are_distinct() {
+    let first = CorrelationId::generate();
+    let second = CorrelationId::generate();
+    assert_ne!(first, second);
+    assert_eq!(format!("{}", CorrelationId::new(0xff)), "00000000000000ff");
+}
+
 const CHANNEL_SIZE: NonZeroUsize = non_zero_usize!(16);
 
 #[tokio::test]
@@ -590,6 +951,46 @@ fn decode_synced_frame() {
     );
 }
 
+#[test]
+fn decode_synced_frame_with_body() {
+    let id = make_addr();
+    let node = "my_node";
+    let lane = "lane";
+    let body = Bytes::from_static(b"@version{epoch:1,version:2}");
+
+    let frame = ResponseMessage::<_, Example, Bytes>::synced_with(
+        id,
+        RelativeAddress::new(node, lane),
+        body.clone(),
+    );
+    let result = round_trip_rawresponse::<_, Example>(frame);
+
+    check_result_rawresponse(
+        result,
+        BytesResponseMessage::synced_with(id, bytes_path(node, lane), body),
+    );
+}
+
+#[test]
+fn decode_advisory_frame() {
+    let id = make_addr();
+    let node = "my_node";
+    let lane = "lane";
+    let body = Bytes::from_static(b"@reduceRate");
+
+    let frame = ResponseMessage::<_, Example, Bytes>::advisory(
+        id,
+        RelativeAddress::new(node, lane),
+        body.clone(),
+    );
+    let result = round_trip_rawresponse::<_, Example>(frame);
+
+    check_result_rawresponse(
+        result,
+        BytesResponseMessage::advisory(id, bytes_path(node, lane), body),
+    );
+}
+
 #[test]
 fn decode_unlinked_frame() {
     let id = make_addr();
@@ -686,3 +1087,39 @@ fn decode_command_frame_twice() {
         RequestMessage::command(id, RelativeAddress::text(node, lane), second),
     );
 }
+
+fn oversized_header(node_len: u32, lane_len: u32, body_len: u64) -> BytesMut {
+    let mut buffer = BytesMut::new();
+    buffer.put_u128(make_addr().as_u128());
+    buffer.put_u32(node_len);
+    buffer.put_u32(lane_len);
+    buffer.put_u64((COMMAND << OP_SHIFT) | (body_len & !OP_MASK));
+    buffer
+}
+
+#[test]
+fn decoders_bound_reservation_for_oversized_frames() {
+    let huge_body = oversized_header(4, 4, u64::MAX);
+    let huge_names = oversized_header(u32::MAX, u32::MAX, 0);
+
+    for header in [&huge_body, &huge_names] {
+        let mut buffer = header.clone();
+        assert!(matches!(
+            RawRequestMessageDecoder.decode(&mut buffer),
+            Ok(None)
+        ));
+        assert!(buffer.capacity() <= HEADER_INIT_LEN + MAX_RESERVE);
+
+        let mut buffer = header.clone();
+        assert!(matches!(
+            RawResponseMessageDecoder.decode(&mut buffer),
+            Ok(None)
+        ));
+        assert!(buffer.capacity() <= HEADER_INIT_LEN + MAX_RESERVE);
+    }
+
+    let mut buffer = huge_names.clone();
+    let mut decoder = RequestMessageDecoder::<Example, _>::new(Example::make_recognizer());
+    assert!(matches!(decoder.decode(&mut buffer), Ok(None)));
+    assert!(buffer.capacity() <= HEADER_INIT_LEN + MAX_RESERVE);
+}
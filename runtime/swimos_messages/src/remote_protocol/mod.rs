@@ -120,11 +120,54 @@ impl Display for NoSuchAgent {
 
 impl std::error::Error for NoSuchAgent {}
 
+/// Error type produced when attempting to resolve a lane on an agent that is not running and that
+/// can only be started explicitly (the lane name is kept for producing the response envelope).
+#[derive(Debug)]
+pub struct AgentNotStarted {
+    pub node: Text,
+    pub lane: Option<Text>,
+}
+
+impl Display for AgentNotStarted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let AgentNotStarted { node, lane } = self;
+        write!(f, "Agent '{}' has not been started.", node)?;
+        if let Some(lane_name) = lane {
+            write!(f, " Requested lane was '{}'.", lane_name)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl std::error::Error for AgentNotStarted {}
+
+/// Error type produced when attempting to resolve an agent that is hosted by another server (such
+/// as another member of a cluster). The host is the base URL of that server.
+#[derive(Debug)]
+pub struct AgentHostedByPeer {
+    pub node: Text,
+    pub host: Text,
+}
+
+impl Display for AgentHostedByPeer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let AgentHostedByPeer { node, host } = self;
+        write!(f, "Agent '{}' is hosted by '{}'.", node, host)
+    }
+}
+
+impl std::error::Error for AgentHostedByPeer {}
+
 /// Error type produced when the resolution of an agent fails.
 #[derive(Debug, Error)]
 pub enum AgentResolutionError {
     #[error(transparent)]
     NotFound(#[from] NoSuchAgent),
+    #[error(transparent)]
+    NotStarted(#[from] AgentNotStarted),
+    #[error(transparent)]
+    HostedByPeer(#[from] AgentHostedByPeer),
     #[error("The plane is stopping.")]
     PlaneStopping,
 }
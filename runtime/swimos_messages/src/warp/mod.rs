@@ -12,14 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{borrow::Cow, fmt::Display, num::ParseFloatError};
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    num::{ParseFloatError, ParseIntError},
+};
 
 use smallvec::{smallvec, SmallVec};
 use swimos_recon::parser::{
-    extract_header, extract_header_str, parse_text_token, HeaderPeeler, MessageExtractError, Span,
+    extract_header_str, parse_text_token, HeaderPeeler, MessageExtractError, Span,
 };
 use swimos_utilities::format::comma_sep;
 use thiserror::Error;
+use uuid::Uuid;
+
+use self::scan::scan_header;
+
+mod scan;
 #[cfg(test)]
 mod tests;
 
@@ -29,12 +38,15 @@ enum EnvelopeKind {
     DeAuth,
     Link,
     Sync,
+    Ack,
     Unlink,
     Command,
     Linked,
     Synced,
     Event,
     Unlinked,
+    Advisory,
+    Dispatched,
 }
 
 /// Interpreted form of a warp envelope, taken from a recon encoded string. Wherever possible, string
@@ -56,11 +68,29 @@ pub enum RawEnvelope<'a> {
         lane_uri: Cow<'a, str>,
         rate: Option<f32>,
         prio: Option<f32>,
+        /// If present, the remote requests that the sync is sent in pages of (at most) this many
+        /// events and will acknowledge each page (with an `ack` envelope) before the next is sent.
+        window: Option<u32>,
+        /// If present, the (inclusive) lower bound of the keys of the entries of an ordered map lane
+        /// that the remote wants to sync with, as Recon.
+        from: Option<&'a str>,
+        /// If present, the (exclusive) upper bound of the keys of the entries of an ordered map lane
+        /// that the remote wants to sync with, as Recon.
+        to: Option<&'a str>,
+        body: Span<'a>,
+    },
+    /// Acknowledges the receipt of a page of events from a paged sync.
+    Ack {
+        node_uri: Cow<'a, str>,
+        lane_uri: Cow<'a, str>,
         body: Span<'a>,
     },
     Command {
         node_uri: Cow<'a, str>,
         lane_uri: Cow<'a, str>,
+        /// If present, the remote requests that it is sent a `dispatched` envelope, with the same
+        /// ID, when the command has been dispatched to the lane.
+        id: Option<u64>,
         body: Span<'a>,
     },
     Unlink {
@@ -83,6 +113,9 @@ pub enum RawEnvelope<'a> {
     Event {
         node_uri: Cow<'a, str>,
         lane_uri: Cow<'a, str>,
+        /// For lanes that track the origins of commands, the ID of the remote that sent the
+        /// command that caused the event.
+        origin: Option<Uuid>,
         body: Span<'a>,
     },
     Unlinked {
@@ -90,6 +123,21 @@ pub enum RawEnvelope<'a> {
         lane_uri: Cow<'a, str>,
         body: Span<'a>,
     },
+    /// Advises a remote that the server is approaching its limits and that the link should back
+    /// off (the body describes what the remote should do).
+    Advisory {
+        node_uri: Cow<'a, str>,
+        lane_uri: Cow<'a, str>,
+        body: Span<'a>,
+    },
+    /// Informs a remote that a command that it sent (with the given ID) has been dispatched to the
+    /// lane.
+    Dispatched {
+        node_uri: Cow<'a, str>,
+        lane_uri: Cow<'a, str>,
+        id: u64,
+        body: Span<'a>,
+    },
 }
 
 /// A list of missing slots.
@@ -123,22 +171,31 @@ pub enum HeaderExtractionError {
     UnexpectedHeaderSlot { name: String, value: String },
     #[error("'{0}' cannot be interpreted as a recon string.")]
     InvalidString(String),
+    #[error("'{0}' is not a valid origin ID.")]
+    InvalidOrigin(String),
     #[error("Expecting a floating point number.")]
     InvalidFloat(#[from] ParseFloatError),
+    #[error("Expecting a non-negative integer.")]
+    InvalidInteger(#[from] ParseIntError),
     #[error("The input did not contain an envelope header.")]
     Incomplete,
     #[error("Header had missing slots: {0}")]
     MissingSlots(Missing),
 }
 
-/// Try to interpret an array of bytes as a warp envelope, without allocating.
+/// Try to interpret a string as a warp envelope, without allocating. Envelopes with headers in the
+/// common form are interpreted with a specialised scanner, falling back to the general Recon parser
+/// for anything else.
 pub fn peel_envelope_header_str(input: &str) -> Result<RawEnvelope<'_>, MessageExtractError> {
-    extract_header_str(input, EnvelopeHeaderPeeler::default())
+    match scan_header(input, EnvelopeHeaderPeeler::default()) {
+        Some(envelope) => Ok(envelope),
+        None => extract_header_str(input, EnvelopeHeaderPeeler::default()),
+    }
 }
 
 /// Try to interpret an array of bytes as a warp envelope, without allocating.
 pub fn peel_envelope_header(input: &[u8]) -> Result<RawEnvelope<'_>, MessageExtractError> {
-    extract_header(input, EnvelopeHeaderPeeler::default())
+    peel_envelope_header_str(std::str::from_utf8(input)?)
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -148,6 +205,11 @@ struct EnvelopeHeaderPeeler<'a> {
     lane_uri: Option<&'a str>,
     rate: Option<f32>,
     prio: Option<f32>,
+    window: Option<u32>,
+    from: Option<&'a str>,
+    to: Option<&'a str>,
+    id: Option<u64>,
+    origin: Option<Uuid>,
 }
 
 fn with_path<'a, F>(
@@ -161,12 +223,12 @@ where
 {
     match (node_uri, lane_uri) {
         (Some(node_uri), Some(lane_uri)) => {
-            let node_uri_text = if let Ok(node_uri_text) = parse_text_token(Span::new(node_uri)) {
+            let node_uri_text = if let Some(node_uri_text) = text_token(node_uri) {
                 node_uri_text
             } else {
                 return Err(HeaderExtractionError::InvalidString(node_uri.to_string()));
             };
-            let lane_uri_text = if let Ok(lane_uri_text) = parse_text_token(Span::new(lane_uri)) {
+            let lane_uri_text = if let Some(lane_uri_text) = text_token(lane_uri) {
                 lane_uri_text
             } else {
                 return Err(HeaderExtractionError::InvalidString(node_uri.to_string()));
@@ -186,6 +248,26 @@ where
     }
 }
 
+/// Interpret a Recon string literal or identifier. Literals without escapes and ASCII identifiers
+/// are handled directly, without using the general parser.
+fn text_token(token: &str) -> Option<Cow<'_, str>> {
+    let bytes = token.as_bytes();
+    match bytes {
+        [b'"', inner @ .., b'"'] if !inner.iter().any(|b| *b == b'"' || *b == b'\\') => {
+            Some(Cow::Borrowed(&token[1..token.len() - 1]))
+        }
+        [first, rest @ ..]
+            if (first.is_ascii_alphabetic() || *first == b'_')
+                && rest
+                    .iter()
+                    .all(|b| b.is_ascii_alphanumeric() || *b == b'_' || *b == b'-') =>
+        {
+            Some(Cow::Borrowed(token))
+        }
+        _ => parse_text_token(Span::new(token)).ok(),
+    }
+}
+
 fn with_rate_prio<'a, F>(
     node_uri: Option<&'a str>,
     lane_uri: Option<&'a str>,
@@ -206,17 +288,25 @@ const AUTH_TAG: &str = "auth";
 const DEAUTH_TAG: &str = "deauth";
 const LINK_TAG: &str = "link";
 const SYNC_TAG: &str = "sync";
+const ACK_TAG: &str = "ack";
 const COMMAND_TAG: &str = "command";
 const UNLINK_TAG: &str = "unlink";
 const LINKED_TAG: &str = "linked";
 const SYNCED_TAG: &str = "synced";
 const EVENT_TAG: &str = "event";
 const UNLINKED_TAG: &str = "unlinked";
+const ADVISORY_TAG: &str = "advisory";
+const DISPATCHED_TAG: &str = "dispatched";
 
 const LANE_URI_SLOT: &str = "lane";
 const NODE_URI_SLOT: &str = "node";
 const RATE_SLOT: &str = "rate";
 const PRIO_SLOT: &str = "prio";
+const WINDOW_SLOT: &str = "window";
+const FROM_SLOT: &str = "from";
+const TO_SLOT: &str = "to";
+const ID_SLOT: &str = "id";
+const ORIGIN_SLOT: &str = "origin";
 
 impl<'a> HeaderPeeler<'a> for EnvelopeHeaderPeeler<'a> {
     type Output = RawEnvelope<'a>;
@@ -229,12 +319,15 @@ impl<'a> HeaderPeeler<'a> for EnvelopeHeaderPeeler<'a> {
             DEAUTH_TAG => EnvelopeKind::DeAuth,
             LINK_TAG => EnvelopeKind::Link,
             SYNC_TAG => EnvelopeKind::Sync,
+            ACK_TAG => EnvelopeKind::Ack,
             COMMAND_TAG => EnvelopeKind::Command,
             UNLINK_TAG => EnvelopeKind::Unlink,
             LINKED_TAG => EnvelopeKind::Linked,
             SYNCED_TAG => EnvelopeKind::Synced,
             EVENT_TAG => EnvelopeKind::Event,
             UNLINKED_TAG => EnvelopeKind::Unlinked,
+            ADVISORY_TAG => EnvelopeKind::Advisory,
+            DISPATCHED_TAG => EnvelopeKind::Dispatched,
             ow => {
                 return Err(HeaderExtractionError::InvalidTag(ow.to_string()));
             }
@@ -256,6 +349,24 @@ impl<'a> HeaderPeeler<'a> for EnvelopeHeaderPeeler<'a> {
             PRIO_SLOT => {
                 self.prio = Some(value.parse()?);
             }
+            WINDOW_SLOT => {
+                self.window = Some(value.parse()?);
+            }
+            FROM_SLOT => {
+                self.from = Some(*value);
+            }
+            TO_SLOT => {
+                self.to = Some(*value);
+            }
+            ID_SLOT => {
+                self.id = Some(value.parse()?);
+            }
+            ORIGIN_SLOT => {
+                let origin = text_token(*value)
+                    .and_then(|origin| Uuid::parse_str(&origin).ok())
+                    .ok_or_else(|| HeaderExtractionError::InvalidOrigin(value.to_string()))?;
+                self.origin = Some(origin);
+            }
             _ => {
                 return Err(HeaderExtractionError::UnexpectedHeaderSlot {
                     name: name.to_string(),
@@ -283,6 +394,11 @@ impl<'a> HeaderPeeler<'a> for EnvelopeHeaderPeeler<'a> {
             lane_uri,
             rate,
             prio,
+            window,
+            from,
+            to,
+            id,
+            origin,
         } = self;
 
         if let Some(kind) = kind {
@@ -314,9 +430,21 @@ impl<'a> HeaderPeeler<'a> for EnvelopeHeaderPeeler<'a> {
                         lane_uri,
                         rate,
                         prio,
+                        window,
+                        from,
+                        to,
                         body,
                     },
                 ),
+                EnvelopeKind::Ack => {
+                    with_path(node_uri, lane_uri, body, |node_uri, lane_uri, body| {
+                        RawEnvelope::Ack {
+                            node_uri,
+                            lane_uri,
+                            body,
+                        }
+                    })
+                }
                 EnvelopeKind::Unlink => {
                     with_path(node_uri, lane_uri, body, |node_uri, lane_uri, body| {
                         RawEnvelope::Unlink {
@@ -331,6 +459,7 @@ impl<'a> HeaderPeeler<'a> for EnvelopeHeaderPeeler<'a> {
                         RawEnvelope::Command {
                             node_uri,
                             lane_uri,
+                            id,
                             body,
                         }
                     })
@@ -363,6 +492,7 @@ impl<'a> HeaderPeeler<'a> for EnvelopeHeaderPeeler<'a> {
                         RawEnvelope::Event {
                             node_uri,
                             lane_uri,
+                            origin,
                             body,
                         }
                     })
@@ -376,6 +506,28 @@ impl<'a> HeaderPeeler<'a> for EnvelopeHeaderPeeler<'a> {
                         }
                     })
                 }
+                EnvelopeKind::Advisory => {
+                    with_path(node_uri, lane_uri, body, |node_uri, lane_uri, body| {
+                        RawEnvelope::Advisory {
+                            node_uri,
+                            lane_uri,
+                            body,
+                        }
+                    })
+                }
+                EnvelopeKind::Dispatched => match id {
+                    Some(id) => with_path(node_uri, lane_uri, body, |node_uri, lane_uri, body| {
+                        RawEnvelope::Dispatched {
+                            node_uri,
+                            lane_uri,
+                            id,
+                            body,
+                        }
+                    }),
+                    None => Err(HeaderExtractionError::MissingSlots(Missing::single(
+                        ID_SLOT,
+                    ))),
+                },
             }
         } else {
             Err(HeaderExtractionError::Incomplete)
@@ -0,0 +1,175 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::Range;
+
+use nom::Slice;
+use swimos_recon::parser::{HeaderPeeler, Span};
+
+/// Attempt to interpret the header attribute of an envelope with a specialised scanner that
+/// only understands the common form of envelope headers (for example,
+/// `@command(node:"/node",lane:lane)`), where:
+///
+/// * All names and identifiers are ASCII.
+/// * All items in the attribute are slots, separated by commas.
+/// * The values of the slots are identifiers, string literals without escapes or simple decimal
+///   numbers.
+///
+/// This avoids the overhead of the general Recon parser for the overwhelming majority of
+/// envelopes. If the input is in any other form (or the peeler rejects it), [`None`] is returned
+/// and the general parser must be used instead (which will produce the same result, or an
+/// appropriate error).
+///
+/// # Arguments
+/// * `input` - The envelope.
+/// * `peeler` - The peeler to use to interpret the header attribute.
+pub fn scan_header<'a, P>(input: &'a str, peeler: P) -> Option<P::Output>
+where
+    P: HeaderPeeler<'a>,
+{
+    let span = Span::new(input);
+    let mut scanner = Scanner::new(input.as_bytes());
+    scanner.expect(b'@')?;
+    let tag = scanner.identifier()?;
+    let mut peeler = peeler.tag(&input[tag]).ok()?;
+    if scanner.eat(b'(') {
+        loop {
+            scanner.skip_spaces();
+            let name = scanner.identifier()?;
+            scanner.skip_spaces();
+            scanner.expect(b':')?;
+            scanner.skip_spaces();
+            let value = scanner.value()?;
+            peeler = peeler
+                .feed_header_slot(&input[name], span.slice(value))
+                .ok()?;
+            scanner.skip_spaces();
+            match scanner.next()? {
+                b',' => {}
+                b')' => break,
+                _ => return None,
+            }
+        }
+    }
+    scanner.skip_blanks();
+    peeler.done(span.slice(scanner.offset..)).ok()
+}
+
+fn is_ident_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_'
+}
+
+fn is_ident_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'-'
+}
+
+/// A cursor over the bytes of an envelope.
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Scanner { bytes, offset: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.offset).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let b = self.peek()?;
+        self.offset += 1;
+        Some(b)
+    }
+
+    fn eat(&mut self, expected: u8) -> bool {
+        if self.peek() == Some(expected) {
+            self.offset += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Option<()> {
+        self.eat(expected).then_some(())
+    }
+
+    fn skip_while(&mut self, p: impl Fn(u8) -> bool) {
+        while self.peek().map(&p).unwrap_or(false) {
+            self.offset += 1;
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        self.skip_while(|b| b == b' ');
+    }
+
+    fn skip_blanks(&mut self) {
+        self.skip_while(|b| b == b' ' || b == b'\t');
+    }
+
+    /// Scan an ASCII identifier. This fails if the identifier continues with a non-ASCII character
+    /// (which the general parser might consider to be part of the identifier).
+    fn identifier(&mut self) -> Option<Range<usize>> {
+        let start = self.offset;
+        if !is_ident_start(self.peek()?) {
+            return None;
+        }
+        self.skip_while(is_ident_char);
+        if self.peek().map(|b| !b.is_ascii()).unwrap_or(false) {
+            None
+        } else {
+            Some(start..self.offset)
+        }
+    }
+
+    /// Scan a string literal that contains no escapes (including the quotes).
+    fn string_literal(&mut self) -> Option<Range<usize>> {
+        let start = self.offset;
+        self.expect(b'"')?;
+        self.skip_while(|b| b != b'"' && b != b'\\');
+        self.expect(b'"')?;
+        Some(start..self.offset)
+    }
+
+    /// Scan a decimal number, with an optional sign and fractional part.
+    fn number(&mut self) -> Option<Range<usize>> {
+        let start = self.offset;
+        self.eat(b'-');
+        let digits_start = self.offset;
+        self.skip_while(|b| b.is_ascii_digit());
+        if self.offset == digits_start {
+            return None;
+        }
+        if self.eat(b'.') {
+            let fraction_start = self.offset;
+            self.skip_while(|b| b.is_ascii_digit());
+            if self.offset == fraction_start {
+                return None;
+            }
+        }
+        Some(start..self.offset)
+    }
+
+    fn value(&mut self) -> Option<Range<usize>> {
+        match self.peek()? {
+            b'"' => self.string_literal(),
+            b'-' | b'0'..=b'9' => self.number(),
+            _ => self.identifier(),
+        }
+    }
+}
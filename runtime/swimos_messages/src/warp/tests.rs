@@ -12,7 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::{peel_envelope_header, RawEnvelope};
+use swimos_recon::parser::extract_header_str;
+use uuid::Uuid;
+
+use super::{
+    peel_envelope_header, peel_envelope_header_str, scan::scan_header, EnvelopeHeaderPeeler,
+    RawEnvelope,
+};
 
 #[test]
 fn peel_auth() {
@@ -140,12 +146,18 @@ fn peel_sync() {
             lane_uri,
             rate,
             prio,
+            window,
+            from,
+            to,
             body,
         }) => {
             assert_eq!(node_uri, "/node");
             assert_eq!(lane_uri, "name");
             assert!(rate.is_none());
             assert!(prio.is_none());
+            assert!(window.is_none());
+            assert!(from.is_none());
+            assert!(to.is_none());
             assert_eq!(*body, "@body {a: 1}");
         }
         Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
@@ -161,12 +173,18 @@ fn peel_sync() {
             lane_uri,
             rate,
             prio,
+            window,
+            from,
+            to,
             body,
         }) => {
             assert_eq!(node_uri, "/node");
             assert_eq!(lane_uri, "name");
             assert_eq!(rate, Some(0.5));
             assert!(prio.is_none());
+            assert!(window.is_none());
+            assert!(from.is_none());
+            assert!(to.is_none());
             assert_eq!(*body, "@body {a: 1}");
         }
         Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
@@ -182,12 +200,18 @@ fn peel_sync() {
             lane_uri,
             rate,
             prio,
+            window,
+            from,
+            to,
             body,
         }) => {
             assert_eq!(node_uri, "/node");
             assert_eq!(lane_uri, "name");
             assert!(rate.is_none());
             assert_eq!(prio, Some(1.0));
+            assert!(window.is_none());
+            assert!(from.is_none());
+            assert!(to.is_none());
             assert_eq!(*body, "@body {a: 1}");
         }
         Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
@@ -203,17 +227,92 @@ fn peel_sync() {
             lane_uri,
             rate,
             prio,
+            window,
+            from,
+            to,
             body,
         }) => {
             assert_eq!(node_uri, "/node");
             assert_eq!(lane_uri, "name");
             assert_eq!(rate, Some(0.1));
             assert_eq!(prio, Some(1e-4));
+            assert!(window.is_none());
+            assert!(from.is_none());
+            assert!(to.is_none());
+            assert_eq!(*body, "@body {a: 1}");
+        }
+        Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
+        Err(e) => panic!("Peeling header failed: {}", e),
+    }
+
+    let envelope = b"@sync(node: \"/node\", lane: name, window: 64)@body {a: 1}";
+    let result = peel_envelope_header(envelope);
+
+    match result {
+        Ok(RawEnvelope::Sync {
+            node_uri,
+            lane_uri,
+            rate,
+            prio,
+            window,
+            from,
+            to,
+            body,
+        }) => {
+            assert_eq!(node_uri, "/node");
+            assert_eq!(lane_uri, "name");
+            assert!(rate.is_none());
+            assert!(prio.is_none());
+            assert_eq!(window, Some(64));
+            assert!(from.is_none());
+            assert!(to.is_none());
             assert_eq!(*body, "@body {a: 1}");
         }
         Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
         Err(e) => panic!("Peeling header failed: {}", e),
     }
+
+    let envelope = b"@sync(node: \"/node\", lane: name, from: 1000, to: \"key\")";
+    let result = peel_envelope_header(envelope);
+
+    match result {
+        Ok(RawEnvelope::Sync {
+            node_uri,
+            lane_uri,
+            window,
+            from,
+            to,
+            ..
+        }) => {
+            assert_eq!(node_uri, "/node");
+            assert_eq!(lane_uri, "name");
+            assert!(window.is_none());
+            assert_eq!(from, Some("1000"));
+            assert_eq!(to, Some("\"key\""));
+        }
+        Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
+        Err(e) => panic!("Peeling header failed: {}", e),
+    }
+}
+
+#[test]
+fn peel_ack() {
+    let envelope = b"@ack(node: \"/node\", lane: name)";
+    let result = peel_envelope_header(envelope);
+
+    match result {
+        Ok(RawEnvelope::Ack {
+            node_uri,
+            lane_uri,
+            body,
+        }) => {
+            assert_eq!(node_uri, "/node");
+            assert_eq!(lane_uri, "name");
+            assert!(body.is_empty());
+        }
+        Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
+        Err(e) => panic!("Peeling header failed: {}", e),
+    }
 }
 
 #[test]
@@ -312,10 +411,12 @@ fn peel_command() {
         Ok(RawEnvelope::Command {
             node_uri,
             lane_uri,
+            id,
             body,
         }) => {
             assert_eq!(node_uri, "/node");
             assert_eq!(lane_uri, "name");
+            assert!(id.is_none());
             assert_eq!(*body, "@body {a: 1}");
         }
         Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
@@ -323,6 +424,97 @@ fn peel_command() {
     }
 }
 
+#[test]
+fn peel_command_with_id() {
+    let envelope = b"@command(node: \"/node\", lane: name, id: 12)@body {a: 1}";
+    let result = peel_envelope_header(envelope);
+
+    match result {
+        Ok(RawEnvelope::Command {
+            node_uri,
+            lane_uri,
+            id,
+            body,
+        }) => {
+            assert_eq!(node_uri, "/node");
+            assert_eq!(lane_uri, "name");
+            assert_eq!(id, Some(12));
+            assert_eq!(*body, "@body {a: 1}");
+        }
+        Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
+        Err(e) => panic!("Peeling header failed: {}", e),
+    }
+}
+
+#[test]
+fn peel_event() {
+    let envelope = b"@event(node: \"/node\", lane: name)@body {a: 1}";
+    let result = peel_envelope_header(envelope);
+
+    match result {
+        Ok(RawEnvelope::Event {
+            node_uri,
+            lane_uri,
+            origin,
+            body,
+        }) => {
+            assert_eq!(node_uri, "/node");
+            assert_eq!(lane_uri, "name");
+            assert!(origin.is_none());
+            assert_eq!(*body, "@body {a: 1}");
+        }
+        Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
+        Err(e) => panic!("Peeling header failed: {}", e),
+    }
+}
+
+#[test]
+fn peel_event_with_origin() {
+    let envelope = b"@event(node: \"/node\", lane: name, origin: \"6a1a6e6c-1f3b-4d0e-9a0c-6e4b5b0f2d7a\")@body {a: 1}";
+    let result = peel_envelope_header(envelope);
+
+    match result {
+        Ok(RawEnvelope::Event {
+            node_uri,
+            lane_uri,
+            origin,
+            body,
+        }) => {
+            assert_eq!(node_uri, "/node");
+            assert_eq!(lane_uri, "name");
+            assert_eq!(
+                origin,
+                Some(Uuid::from_u128(0x6a1a6e6c_1f3b_4d0e_9a0c_6e4b5b0f2d7a))
+            );
+            assert_eq!(*body, "@body {a: 1}");
+        }
+        Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
+        Err(e) => panic!("Peeling header failed: {}", e),
+    }
+}
+
+#[test]
+fn peel_dispatched() {
+    let envelope = b"@dispatched(node: \"/node\", lane: name, id: 12)";
+    let result = peel_envelope_header(envelope);
+
+    match result {
+        Ok(RawEnvelope::Dispatched {
+            node_uri,
+            lane_uri,
+            id,
+            body,
+        }) => {
+            assert_eq!(node_uri, "/node");
+            assert_eq!(lane_uri, "name");
+            assert_eq!(id, 12);
+            assert!(body.is_empty());
+        }
+        Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
+        Err(e) => panic!("Peeling header failed: {}", e),
+    }
+}
+
 #[test]
 fn peel_unlink() {
     let envelope = b"@unlink(node: \"/node\", lane: name)@body {a: 1}";
@@ -383,6 +575,26 @@ fn peel_unlinked() {
     }
 }
 
+#[test]
+fn peel_advisory() {
+    let envelope = b"@advisory(node: \"/node\", lane: name)@reduceRate";
+    let result = peel_envelope_header(envelope);
+
+    match result {
+        Ok(RawEnvelope::Advisory {
+            node_uri,
+            lane_uri,
+            body,
+        }) => {
+            assert_eq!(node_uri, "/node");
+            assert_eq!(lane_uri, "name");
+            assert_eq!(*body, "@reduceRate");
+        }
+        Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
+        Err(e) => panic!("Peeling header failed: {}", e),
+    }
+}
+
 #[test]
 fn bad_enevelopes() {
     let envelopes: &[&[u8]] = &[
@@ -392,6 +604,11 @@ fn bad_enevelopes() {
         b"@unlinked(node: \"/node\", lane: 5.6)@body {a: 1}",
         b"@linked(node: \"/node\", lane: name, rate: half)@body {a: 1}",
         b"@linked(node: \"/node\", lane: name, prio: \"max\")@body {a: 1}",
+        b"@sync(node: \"/node\", lane: name, window: -1)@body {a: 1}",
+        b"@ack(lane: name)",
+        b"@command(node: \"/node\", lane: name, id: -3)@body {a: 1}",
+        b"@dispatched(node: \"/node\", lane: name)",
+        b"@event(node: \"/node\", lane: name, origin: \"remote\")@body {a: 1}",
         b"@linked@body {a: 1}",
         b"@linked(7, node: \"/node\", lane: name, rate: 0.5)@body {a: 1}",
         b"@linked(node:node, lane:\"lane);",
@@ -401,3 +618,63 @@ fn bad_enevelopes() {
         assert!(result.is_err());
     }
 }
+
+#[test]
+fn scan_common_envelopes() {
+    let envelopes = [
+        "@link(node:\"/node\",lane:name)",
+        "@sync(node: \"/node\", lane: name, rate: 0.5, prio: -2, window: 10)@body {a: 1}",
+        "@command(node:\"/unit/é\",lane:\"the lane\",id:12) 42",
+        "@event(node:\"/node\",lane:name)\t@update(key: 1) 2",
+        "@event(node:\"/node\",lane:name,origin:\"6a1a6e6c-1f3b-4d0e-9a0c-6e4b5b0f2d7a\") 2",
+        "@unlinked(node:\"\",lane:name)@laneNotFound",
+        "@auth@payload { name: bob }",
+        "@deauth",
+    ];
+    for envelope in envelopes {
+        let scanned = scan_header(envelope, EnvelopeHeaderPeeler::default())
+            .expect("The scanner should handle the envelope.");
+        let parsed = extract_header_str(envelope, EnvelopeHeaderPeeler::default())
+            .expect("Invalid envelope.");
+        // The spans in the output of the scanner are located within the input in the same way.
+        assert_eq!(format!("{:?}", scanned), format!("{:?}", parsed));
+    }
+}
+
+#[test]
+fn scanner_defers_to_parser() {
+    let envelopes = [
+        "@link(node:\"/no\\\"de\",lane:name)",
+        "@link(node:\"/node\"\nlane:name)",
+        "@link(node:\"/node\";lane:name)",
+        "@sync(node:\"/node\",lane:name,from:{a:1},to:2e3)",
+        "@command(\"node\":\"/node\",lane:nämé)",
+        "@command(node:\"/node\",lane:name,)",
+    ];
+    for envelope in envelopes {
+        assert!(scan_header(envelope, EnvelopeHeaderPeeler::default()).is_none());
+        let peeled = peel_envelope_header_str(envelope).map(|env| format!("{:?}", env));
+        let parsed = extract_header_str(envelope, EnvelopeHeaderPeeler::default())
+            .map(|env| format!("{:?}", env));
+        match (peeled, parsed) {
+            (Ok(peeled), Ok(parsed)) => assert_eq!(peeled, parsed),
+            (Err(_), Err(_)) => {}
+            ow => panic!("Results differ: {:?}", ow),
+        }
+    }
+}
+
+#[test]
+fn peel_escaped_uris() {
+    let envelope = b"@command(node: \"/no\\\"de\", lane: \"na\\nme\")@body";
+    match peel_envelope_header(envelope) {
+        Ok(RawEnvelope::Command {
+            node_uri, lane_uri, ..
+        }) => {
+            assert_eq!(node_uri, "/no\"de");
+            assert_eq!(lane_uri, "na\nme");
+        }
+        Ok(ow) => panic!("Unexpected envelope: {:?}", ow),
+        Err(e) => panic!("Peeling header failed: {}", e),
+    }
+}
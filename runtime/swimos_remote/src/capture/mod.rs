@@ -0,0 +1,232 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{Display, Formatter},
+    net::SocketAddr,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
+
+use bytes::Bytes;
+use swimos_model::Timestamp;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod tests;
+
+/// The direction in which a captured frame or envelope was travelling, relative to the server (or
+/// the agent, for envelopes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureDirection {
+    /// The frame was received from the remote.
+    Incoming,
+    /// The frame was sent to the remote.
+    Outgoing,
+}
+
+impl Display for CaptureDirection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptureDirection::Incoming => f.write_str("in"),
+            CaptureDirection::Outgoing => f.write_str("out"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CaptureInner<T> {
+    enabled: AtomicBool,
+    capacity: NonZeroUsize,
+    records: Mutex<VecDeque<T>>,
+}
+
+/// A bounded buffer of records that is used to capture the traffic of a server, for debugging.
+/// Records are only added while the capture is enabled and, when the buffer is full, the oldest
+/// record is discarded to make space for a new one. The capture is disabled when it is created.
+/// This is used both for the raw frames of remotes ([`FrameCapture`]) and for the envelopes of
+/// agents.
+#[derive(Debug)]
+pub struct CaptureBuffer<T> {
+    inner: Arc<CaptureInner<T>>,
+}
+
+impl<T> Clone for CaptureBuffer<T> {
+    fn clone(&self) -> Self {
+        CaptureBuffer {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> CaptureBuffer<T> {
+    /// # Arguments
+    /// * `capacity` - The maximum number of records to retain.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        CaptureBuffer {
+            inner: Arc::new(CaptureInner {
+                enabled: AtomicBool::new(false),
+                capacity,
+                records: Mutex::new(VecDeque::new()),
+            }),
+        }
+    }
+
+    /// Determine whether records are currently being captured.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Start or stop capturing records. Records that have already been captured are retained.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.inner.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Add a record, if the capture is enabled. The record is only created if it will be retained.
+    ///
+    /// # Arguments
+    /// * `make_record` - Creates the record (a record will not be added if this returns nothing).
+    pub fn record_with<F>(&self, make_record: F)
+    where
+        F: FnOnce() -> Option<T>,
+    {
+        let CaptureInner {
+            enabled,
+            capacity,
+            records,
+        } = &*self.inner;
+        if enabled.load(Ordering::Relaxed) {
+            if let Some(record) = make_record() {
+                let mut guard = records.lock().expect("Capture buffer poisoned.");
+                if guard.len() == capacity.get() {
+                    guard.pop_front();
+                }
+                guard.push_back(record);
+            }
+        }
+    }
+
+    /// Take a copy of the records that have been captured, oldest first.
+    pub fn records(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let guard = self.inner.records.lock().expect("Capture buffer poisoned.");
+        guard.iter().cloned().collect()
+    }
+
+    /// Remove the records that have been captured, oldest first.
+    pub fn take(&self) -> Vec<T> {
+        let mut guard = self.inner.records.lock().expect("Capture buffer poisoned.");
+        guard.drain(..).collect()
+    }
+
+    /// Discard all of the records that have been captured.
+    pub fn clear(&self) {
+        let mut guard = self.inner.records.lock().expect("Capture buffer poisoned.");
+        guard.clear();
+    }
+}
+
+/// A raw web-socket frame recorded by a [`FrameCapture`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedFrame {
+    /// The time at which the frame was recorded.
+    pub timestamp: Timestamp,
+    pub direction: CaptureDirection,
+    /// The raw content of the frame.
+    pub frame: Bytes,
+}
+
+/// Records the raw frames sent to and received from a single remote.
+pub type FrameCapture = CaptureBuffer<CapturedFrame>;
+
+impl CaptureBuffer<CapturedFrame> {
+    /// Record a frame, if the capture is enabled.
+    pub fn record(&self, direction: CaptureDirection, frame: &[u8]) {
+        self.record_with(|| {
+            Some(CapturedFrame {
+                timestamp: Timestamp::now(),
+                direction,
+                frame: Bytes::copy_from_slice(frame),
+            })
+        })
+    }
+
+    /// Take a copy of the frames that have been recorded, oldest first.
+    pub fn frames(&self) -> Vec<CapturedFrame> {
+        self.records()
+    }
+}
+
+/// A connected remote with its frame capture.
+#[derive(Debug, Clone)]
+pub struct RemoteCapture {
+    /// The address of the remote.
+    pub addr: SocketAddr,
+    pub capture: FrameCapture,
+}
+
+/// A registry of the [`FrameCapture`]s for all of the remotes that are connected to a server,
+/// keyed by the IDs of the remotes.
+#[derive(Debug, Clone)]
+pub struct RemoteCaptures {
+    capacity: NonZeroUsize,
+    remotes: Arc<RwLock<HashMap<Uuid, RemoteCapture>>>,
+}
+
+impl RemoteCaptures {
+    /// # Arguments
+    /// * `capacity` - The maximum number of frames to retain for each remote.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        RemoteCaptures {
+            capacity,
+            remotes: Default::default(),
+        }
+    }
+
+    /// Create a (disabled) capture for a newly connected remote.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the remote.
+    /// * `addr` - The address of the remote.
+    pub fn register(&self, id: Uuid, addr: SocketAddr) -> FrameCapture {
+        let capture = FrameCapture::new(self.capacity);
+        let mut guard = self.remotes.write().expect("Capture registry poisoned.");
+        guard.insert(
+            id,
+            RemoteCapture {
+                addr,
+                capture: capture.clone(),
+            },
+        );
+        capture
+    }
+
+    /// Remove the capture for a remote that has disconnected.
+    pub fn unregister(&self, id: Uuid) {
+        let mut guard = self.remotes.write().expect("Capture registry poisoned.");
+        guard.remove(&id);
+    }
+
+    /// Get the capture for a connected remote.
+    pub fn get(&self, id: Uuid) -> Option<RemoteCapture> {
+        let guard = self.remotes.read().expect("Capture registry poisoned.");
+        guard.get(&id).cloned()
+    }
+}
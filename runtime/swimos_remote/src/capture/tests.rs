@@ -0,0 +1,102 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use swimos_utilities::non_zero_usize;
+use uuid::Uuid;
+
+use super::{CaptureDirection, FrameCapture, RemoteCaptures};
+
+fn contents(capture: &FrameCapture) -> Vec<(CaptureDirection, Vec<u8>)> {
+    capture
+        .frames()
+        .into_iter()
+        .map(|frame| (frame.direction, frame.frame.to_vec()))
+        .collect()
+}
+
+#[test]
+fn capture_disabled_initially() {
+    let capture = FrameCapture::new(non_zero_usize!(4));
+    assert!(!capture.is_enabled());
+
+    capture.record(CaptureDirection::Incoming, b"@sync(node:a,lane:b)");
+    assert!(capture.frames().is_empty());
+}
+
+#[test]
+fn capture_records_frames_when_enabled() {
+    let capture = FrameCapture::new(non_zero_usize!(4));
+    capture.set_enabled(true);
+
+    capture.record(CaptureDirection::Incoming, b"@sync(node:a,lane:b)");
+    capture.record(CaptureDirection::Outgoing, b"@synced(node:a,lane:b)");
+
+    assert_eq!(
+        contents(&capture),
+        vec![
+            (CaptureDirection::Incoming, b"@sync(node:a,lane:b)".to_vec()),
+            (
+                CaptureDirection::Outgoing,
+                b"@synced(node:a,lane:b)".to_vec()
+            ),
+        ]
+    );
+
+    capture.set_enabled(false);
+    capture.record(CaptureDirection::Incoming, b"@unlink(node:a,lane:b)");
+    assert_eq!(capture.frames().len(), 2);
+
+    capture.clear();
+    assert!(capture.frames().is_empty());
+}
+
+#[test]
+fn capture_discards_oldest_frames() {
+    let capture = FrameCapture::new(non_zero_usize!(2));
+    capture.set_enabled(true);
+
+    capture.record(CaptureDirection::Incoming, b"1");
+    capture.record(CaptureDirection::Incoming, b"2");
+    capture.record(CaptureDirection::Outgoing, b"3");
+
+    assert_eq!(
+        contents(&capture),
+        vec![
+            (CaptureDirection::Incoming, b"2".to_vec()),
+            (CaptureDirection::Outgoing, b"3".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn register_remote_captures() {
+    let captures = RemoteCaptures::new(non_zero_usize!(2));
+    let id = Uuid::from_u128(1);
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8080);
+
+    assert!(captures.get(id).is_none());
+
+    let capture = captures.register(id, addr);
+    capture.set_enabled(true);
+
+    let remote = captures.get(id).expect("Remote not registered.");
+    assert_eq!(remote.addr, addr);
+    // The registry shares the capture with the remote task.
+    assert!(remote.capture.is_enabled());
+
+    captures.unregister(id);
+    assert!(captures.get(id).is_none());
+}
@@ -15,8 +15,10 @@
 use futures::future::BoxFuture;
 use futures::Future;
 use futures::FutureExt;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
 use std::io;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use tokio::net::lookup_host;
 
@@ -75,28 +77,71 @@ impl DnsResolver for GetAddressInfoResolver {
     }
 }
 
+type SharedDnsResolver = Arc<dyn DnsResolver<ResolveFuture = DnsFut> + Send + Sync + 'static>;
+
+/// Adapts a DNS resolver to produce boxed futures.
+struct Boxed<R>(R);
+
+impl<R: DnsResolver> DnsResolver for Boxed<R> {
+    type ResolveFuture = DnsFut;
+
+    fn resolve(&self, host: String, port: u16) -> Self::ResolveFuture {
+        self.0.resolve(host, port).boxed()
+    }
+}
+
+#[derive(Clone)]
+enum ResolverInner {
+    #[cfg(not(feature = "hickory_dns"))]
+    Default(GetAddressInfoResolver),
+    #[cfg(feature = "hickory_dns")]
+    Default(hickory_dns_impl::HickoryDnsResolver),
+    Custom(SharedDnsResolver),
+}
+
+impl Debug for ResolverInner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResolverInner::Default(inner) => f.debug_tuple("Default").field(inner).finish(),
+            ResolverInner::Custom(_) => f.debug_tuple("Custom").finish(),
+        }
+    }
+}
+
 /// The default DNS resolver. If the `hickory_dns` feature flag is enabled, this will use the `hickory_dns`
-/// implementation, otherwise it will use the operating system's built-in DNS support.
+/// implementation, otherwise it will use the operating system's built-in DNS support. Alternatively,
+/// a user provided resolver can be used (see [`Resolver::custom`]).
 #[derive(Debug, Clone)]
 pub struct Resolver {
-    #[cfg(not(feature = "hickory_dns"))]
-    inner: GetAddressInfoResolver,
-    #[cfg(feature = "hickory_dns")]
-    inner: hickory_dns_impl::HickoryDnsResolver,
+    inner: ResolverInner,
 }
 
 impl Resolver {
     #[cfg(feature = "hickory_dns")]
     pub async fn new() -> Resolver {
         Resolver {
-            inner: hickory_dns_impl::HickoryDnsResolver::new().await,
+            inner: ResolverInner::Default(hickory_dns_impl::HickoryDnsResolver::new().await),
         }
     }
 
     #[cfg(not(feature = "hickory_dns"))]
     pub async fn new() -> Resolver {
         Resolver {
-            inner: GetAddressInfoResolver,
+            inner: ResolverInner::Default(GetAddressInfoResolver),
+        }
+    }
+
+    /// Create a resolver that delegates to a user provided implementation (for example, one that
+    /// uses SRV records or a service registry).
+    ///
+    /// # Arguments
+    /// * `resolver` - The resolver to delegate to.
+    pub fn custom<R>(resolver: R) -> Resolver
+    where
+        R: DnsResolver + Send + Sync + 'static,
+    {
+        Resolver {
+            inner: ResolverInner::Custom(Arc::new(Boxed(resolver))),
         }
     }
 }
@@ -105,10 +150,64 @@ impl DnsResolver for Resolver {
     type ResolveFuture = BoxFuture<'static, io::Result<Vec<SocketAddr>>>;
 
     fn resolve(&self, host: String, port: u16) -> Self::ResolveFuture {
-        self.inner.resolve(host, port).boxed()
+        match &self.inner {
+            ResolverInner::Default(inner) => inner.resolve(host, port).boxed(),
+            ResolverInner::Custom(inner) => inner.resolve(host, port),
+        }
+    }
+}
+
+/// A resolver that resolves hosts from a fixed table of addresses. Hosts that are not in the
+/// table are delegated to a fallback resolver, if one is provided, and are otherwise unresolvable.
+#[derive(Debug, Clone, Default)]
+pub struct StaticResolver {
+    entries: HashMap<String, Vec<IpAddr>>,
+    fallback: Option<Resolver>,
+}
+
+impl StaticResolver {
+    /// Add the addresses for a host to the table, replacing any that were already present.
+    pub fn with_entry<I>(mut self, host: impl Into<String>, addrs: I) -> Self
+    where
+        I: IntoIterator<Item = IpAddr>,
+    {
+        self.entries
+            .insert(host.into(), addrs.into_iter().collect());
+        self
+    }
+
+    /// Set a resolver to use for hosts that are not in the table.
+    pub fn with_fallback(mut self, fallback: Resolver) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+}
+
+impl DnsResolver for StaticResolver {
+    type ResolveFuture = DnsFut;
+
+    fn resolve(&self, host: String, port: u16) -> Self::ResolveFuture {
+        match (self.entries.get(&host), &self.fallback) {
+            (Some(addrs), _) => {
+                let addrs = addrs
+                    .iter()
+                    .map(|ip| SocketAddr::new(*ip, port))
+                    .collect::<Vec<_>>();
+                futures::future::ready(Ok(addrs)).boxed()
+            }
+            (None, Some(fallback)) => fallback.resolve(host, port),
+            (None, None) => futures::future::ready(Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No entry for host: {}", host),
+            )))
+            .boxed(),
+        }
     }
 }
 
+#[cfg(test)]
+mod tests;
+
 #[cfg(feature = "hickory_dns")]
 mod hickory_dns_impl {
     use crate::dns::DnsResolver;
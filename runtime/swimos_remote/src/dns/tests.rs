@@ -0,0 +1,60 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::dns::{DnsResolver, Resolver, StaticResolver};
+
+const V4: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+const V6: IpAddr = IpAddr::V6(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 1));
+
+#[tokio::test]
+async fn static_resolver_entries() {
+    let resolver = StaticResolver::default().with_entry("swim.example", [V6, V4]);
+
+    let addrs = resolver
+        .resolve("swim.example".to_string(), 9001)
+        .await
+        .expect("Resolution failed.");
+    assert_eq!(
+        addrs,
+        vec![SocketAddr::new(V6, 9001), SocketAddr::new(V4, 9001)]
+    );
+
+    let err = resolver
+        .resolve("other.example".to_string(), 9001)
+        .await
+        .expect_err("Resolution should fail.");
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+#[tokio::test]
+async fn custom_resolver() {
+    let inner = StaticResolver::default().with_entry("swim.example", [V4]);
+    let resolver = Resolver::custom(inner);
+
+    let addrs = resolver
+        .resolve("swim.example".to_string(), 80)
+        .await
+        .expect("Resolution failed.");
+    assert_eq!(addrs, vec![SocketAddr::new(V4, 80)]);
+
+    let fallback = StaticResolver::default().with_fallback(resolver);
+    let addrs = fallback
+        .resolve("swim.example".to_string(), 80)
+        .await
+        .expect("Resolution failed.");
+    assert_eq!(addrs, vec![SocketAddr::new(V4, 80)]);
+}
@@ -19,27 +19,36 @@
 //!
 //! - An abstraction over DNS to allow for the resolution of remote hosts of Swim agents.
 //! - A networking abstraction with basic implementation for unencrypted traffic over TCP sockets.
+//! - Local implementations of the networking abstraction (in-process and Unix domain sockets).
 //! - An optional implementation of the networking abstraction using TLS encryption.
 //! - Bindings to use the [`ratchet`] web-socket library on top of the networking abstraction.
 //! - A Tokio task to manage a bidirectional web-socket and handle communication with the core SwimOS runtime.
 
+mod capture;
 /// DNS support for resolving remote hosts.
 pub mod dns;
+/// Networking support for clients on the same host, or in the same process, as the server.
+pub mod local;
 mod net;
 
 /// Basic networking support, without TLS support.
 pub mod plain;
+/// Support for opening outgoing connections through SOCKS5 and HTTP proxies.
+pub mod proxy;
 mod task;
 /// Networking support with TLS provided by the [`rustls`] crate.
 #[cfg(feature = "tls")]
 pub mod tls;
 mod ws;
 
-pub use task::RemoteTask;
+pub use capture::{
+    CaptureBuffer, CaptureDirection, CapturedFrame, FrameCapture, RemoteCapture, RemoteCaptures,
+};
+pub use task::{parse_request_envelope, KeepAlive, ReconEncoder, RemoteTask};
 
 pub use net::{
-    BadWarpUrl, ClientConnections, ConnectionError, ExternalConnections, Listener, ListenerError,
-    Scheme, SchemeHostPort, ServerConnections,
+    race_connections, BadWarpUrl, ClientConnections, ConnectionError, ExternalConnections,
+    Listener, ListenerError, Scheme, SchemeHostPort, ServerConnections, CONNECTION_ATTEMPT_DELAY,
 };
 #[doc(hidden)]
 pub use net::{ConnectionResult, ListenerResult};
@@ -48,9 +57,23 @@ pub use net::{ConnectionResult, ListenerResult};
 pub mod websocket {
 
     pub use super::ws::{
-        RatchetClient, RatchetError, WebsocketClient, WebsocketServer, Websockets, WsOpenFuture,
+        DeflateSettings, RatchetClient, RatchetError, ThresholdDeflate, ThresholdDeflateProvider,
+        ThresholdEncoder, WarpEncoding, WarpFeatures, WarpProtocol, WarpVersion, WarpVersions,
+        WebsocketClient, WebsocketServer, Websockets, WsOpenFuture,
     };
 
     /// The name of the Warp protocol for negotiation web-socket connections.
     pub const WARP: &str = "warp0";
+
+    /// The name of the Warp protocol, using binary envelopes, for negotiating web-socket
+    /// connections. See [`WarpEncoding::Binary`] for a description of the format.
+    pub const WARP_BINARY: &str = "warp0-bin";
+
+    /// The name of the Warp protocol for negotiating web-socket connections that carry requests
+    /// that were forwarded by another server. See [`WarpFeatures::FORWARDED`].
+    pub const WARP_FORWARDED: &str = "warp0-fwd";
+
+    /// The name of the Warp protocol, using binary envelopes, for negotiating web-socket
+    /// connections that carry requests that were forwarded by another server.
+    pub const WARP_BINARY_FORWARDED: &str = "warp0-bin-fwd";
 }
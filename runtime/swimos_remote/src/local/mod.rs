@@ -0,0 +1,216 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use futures::future::{ready, BoxFuture};
+use futures::{FutureExt, Stream};
+use tokio::io::{duplex, DuplexStream};
+use tokio::sync::mpsc;
+
+use crate::dns::{BoxDnsResolver, DnsFut, DnsResolver};
+use crate::net::{
+    ClientConnections, ConnectionError, ConnectionResult, Listener, ListenerResult, Scheme,
+    ServerConnections,
+};
+
+#[cfg(unix)]
+mod unix;
+#[cfg(unix)]
+pub use unix::{UnixNetworking, UnixSocketListener};
+
+#[cfg(test)]
+mod tests;
+
+/// The address that is reported for the peers of local connections (which have no network address).
+const LOCAL_PEER: SocketAddr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0));
+
+/// The first port that is assigned when a local transport is bound to port 0.
+const FIRST_ASSIGNED_PORT: u16 = 49152;
+
+const NO_TLS: &str = "TLS connections are not supported by local transports.";
+
+fn loopback(port: u16) -> SocketAddr {
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, port))
+}
+
+fn refused() -> ConnectionError {
+    ConnectionError::ConnectionFailed(io::ErrorKind::ConnectionRefused.into())
+}
+
+/// A DNS resolver that resolves every host to the loopback address. Local transports only
+/// distinguish between servers by port so the host is irrelevant.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoopbackResolver;
+
+impl DnsResolver for LoopbackResolver {
+    type ResolveFuture = DnsFut;
+
+    fn resolve(&self, _host: String, port: u16) -> Self::ResolveFuture {
+        ready(Ok(vec![loopback(port)])).boxed()
+    }
+}
+
+const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Default)]
+struct Registry {
+    listeners: HashMap<u16, mpsc::UnboundedSender<DuplexStream>>,
+}
+
+impl Registry {
+    fn is_bound(&self, port: u16) -> bool {
+        self.listeners
+            .get(&port)
+            .map(|tx| !tx.is_closed())
+            .unwrap_or_default()
+    }
+}
+
+/// An in-process transport, for use when a client runs within the same process as the server
+/// that it connects to. Servers bind to ports of the transport (the address is ignored) and
+/// connections to them are made over in-memory [`DuplexStream`]s, bypassing the network stack
+/// entirely. Every host resolves to the loopback address so, for example, a server bound to port
+/// 9001 can be reached with the URL `ws://localhost:9001`. Clones of the transport share the same
+/// ports.
+#[derive(Debug, Clone)]
+pub struct InProcessNetworking {
+    registry: Arc<Mutex<Registry>>,
+    buffer_size: usize,
+}
+
+impl Default for InProcessNetworking {
+    fn default() -> Self {
+        InProcessNetworking {
+            registry: Default::default(),
+            buffer_size: DEFAULT_BUFFER_SIZE,
+        }
+    }
+}
+
+impl InProcessNetworking {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the size of the buffers for each direction of the connections.
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    fn registry(&self) -> std::sync::MutexGuard<'_, Registry> {
+        self.registry
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl ClientConnections for InProcessNetworking {
+    type ClientSocket = DuplexStream;
+
+    fn try_open(
+        &self,
+        scheme: Scheme,
+        _host: Option<&str>,
+        addr: SocketAddr,
+    ) -> BoxFuture<'_, ConnectionResult<Self::ClientSocket>> {
+        let result = match scheme {
+            Scheme::Ws => {
+                let mut registry = self.registry();
+                match registry.listeners.get(&addr.port()) {
+                    Some(tx) => {
+                        let (client, server) = duplex(self.buffer_size);
+                        if tx.send(server).is_ok() {
+                            Ok(client)
+                        } else {
+                            registry.listeners.remove(&addr.port());
+                            Err(refused())
+                        }
+                    }
+                    None => Err(refused()),
+                }
+            }
+            Scheme::Wss => Err(ConnectionError::BadParameter(NO_TLS.to_string())),
+        };
+        ready(result).boxed()
+    }
+
+    fn dns_resolver(&self) -> BoxDnsResolver {
+        Box::new(LoopbackResolver)
+    }
+
+    fn lookup(&self, host: String, port: u16) -> BoxFuture<'static, io::Result<Vec<SocketAddr>>> {
+        LoopbackResolver.resolve(host, port)
+    }
+}
+
+impl ServerConnections for InProcessNetworking {
+    type ServerSocket = DuplexStream;
+
+    type ListenerType = InProcessListener;
+
+    fn bind(
+        &self,
+        addr: SocketAddr,
+    ) -> BoxFuture<'static, ConnectionResult<(SocketAddr, Self::ListenerType)>> {
+        let mut registry = self.registry();
+        let port = match addr.port() {
+            0 => (FIRST_ASSIGNED_PORT..=u16::MAX).find(|port| !registry.is_bound(*port)),
+            port if !registry.is_bound(port) => Some(port),
+            _ => None,
+        };
+        let result = match port {
+            Some(port) => {
+                let (tx, rx) = mpsc::unbounded_channel();
+                registry.listeners.insert(port, tx);
+                Ok((loopback(port), InProcessListener { rx }))
+            }
+            None => Err(ConnectionError::ConnectionFailed(
+                io::ErrorKind::AddrInUse.into(),
+            )),
+        };
+        ready(result).boxed()
+    }
+}
+
+/// A listener for the connections to a port of an [`InProcessNetworking`] transport. The port is
+/// released when the listener is dropped.
+#[derive(Debug)]
+pub struct InProcessListener {
+    rx: mpsc::UnboundedReceiver<DuplexStream>,
+}
+
+impl Stream for InProcessListener {
+    type Item = ListenerResult<(DuplexStream, Scheme, SocketAddr)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx
+            .poll_recv(cx)
+            .map(|maybe| maybe.map(|stream| Ok((stream, Scheme::Ws, LOCAL_PEER))))
+    }
+}
+
+impl Listener<DuplexStream> for InProcessListener {
+    type AcceptStream = Self;
+
+    fn into_stream(self) -> Self::AcceptStream {
+        self
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::SocketAddr;
+
+use futures::StreamExt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::local::{loopback, InProcessNetworking, FIRST_ASSIGNED_PORT};
+use crate::net::{ClientConnections, ConnectionError, Listener, Scheme, ServerConnections};
+
+async fn exchange<C, S>(client: &mut C, server: &mut S)
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    client.write_all(b"ping").await.expect("Write failed.");
+    let mut buf = [0u8; 4];
+    server.read_exact(&mut buf).await.expect("Read failed.");
+    assert_eq!(&buf, b"ping");
+
+    server.write_all(b"pong").await.expect("Write failed.");
+    client.read_exact(&mut buf).await.expect("Read failed.");
+    assert_eq!(&buf, b"pong");
+}
+
+fn any_addr(port: u16) -> SocketAddr {
+    SocketAddr::from(([0, 0, 0, 0], port))
+}
+
+#[tokio::test]
+async fn in_process_connection() {
+    let networking = InProcessNetworking::new();
+    let (bound, listener) = networking.bind(any_addr(9001)).await.expect("Bind failed.");
+    assert_eq!(bound, loopback(9001));
+    let mut incoming = listener.into_stream();
+
+    let addrs = networking
+        .lookup("swim.example".to_string(), 9001)
+        .await
+        .expect("Lookup failed.");
+    assert_eq!(addrs, vec![bound]);
+
+    let mut client = networking
+        .try_open(Scheme::Ws, Some("swim.example"), bound)
+        .await
+        .expect("Connection failed.");
+    let (mut server, scheme, _) = incoming
+        .next()
+        .await
+        .expect("Listener stopped.")
+        .expect("Accept failed.");
+    assert_eq!(scheme, Scheme::Ws);
+
+    exchange(&mut client, &mut server).await;
+}
+
+#[tokio::test]
+async fn in_process_ports() {
+    let networking = InProcessNetworking::new();
+    let (first, _first_listener) = networking.bind(any_addr(0)).await.expect("Bind failed.");
+    let (second, second_listener) = networking.bind(any_addr(0)).await.expect("Bind failed.");
+    assert_eq!(first.port(), FIRST_ASSIGNED_PORT);
+    assert_eq!(second.port(), FIRST_ASSIGNED_PORT + 1);
+
+    assert!(matches!(
+        networking.bind(first).await,
+        Err(ConnectionError::ConnectionFailed(_))
+    ));
+
+    // Dropping the listener releases the port.
+    drop(second_listener);
+    assert!(matches!(
+        networking.try_open(Scheme::Ws, None, second).await,
+        Err(ConnectionError::ConnectionFailed(_))
+    ));
+    assert!(networking.bind(second).await.is_ok());
+
+    assert!(matches!(
+        networking.try_open(Scheme::Wss, None, first).await,
+        Err(ConnectionError::BadParameter(_))
+    ));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn unix_socket_connection() {
+    use crate::local::UnixNetworking;
+
+    let dir = std::env::temp_dir().join(format!("swimos-local-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("Failed to create directory.");
+    let networking = UnixNetworking::new(&dir);
+
+    let (bound, listener) = networking.bind(any_addr(0)).await.expect("Bind failed.");
+    let path = listener.path().to_owned();
+    assert_eq!(path, networking.socket_path(bound.port()));
+    assert!(path.exists());
+    let mut incoming = listener.into_stream();
+
+    let mut client = networking
+        .try_open(Scheme::Ws, Some("localhost"), bound)
+        .await
+        .expect("Connection failed.");
+    let (mut server, _, _) = incoming
+        .next()
+        .await
+        .expect("Listener stopped.")
+        .expect("Accept failed.");
+
+    exchange(&mut client, &mut server).await;
+
+    drop(incoming);
+    assert!(!path.exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,149 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::future::BoxFuture;
+use futures::{FutureExt, Stream};
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::dns::{BoxDnsResolver, DnsResolver};
+use crate::net::{
+    ClientConnections, ConnectionError, ConnectionResult, Listener, ListenerResult, Scheme,
+    ServerConnections,
+};
+
+use super::{loopback, LoopbackResolver, FIRST_ASSIGNED_PORT, LOCAL_PEER, NO_TLS};
+
+/// A transport over Unix domain sockets, for clients that run on the same host as the server (for
+/// example, a sidecar). Each port of the transport corresponds to a socket file in a directory
+/// that is shared by the server and its clients. As with the [`super::InProcessNetworking`]
+/// transport, every host resolves to the loopback address so a server bound to port 9001 can be
+/// reached with the URL `ws://localhost:9001`.
+#[derive(Debug, Clone)]
+pub struct UnixNetworking {
+    dir: Arc<PathBuf>,
+}
+
+impl UnixNetworking {
+    /// # Arguments
+    /// * `dir` - The directory that contains the socket files.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        UnixNetworking {
+            dir: Arc::new(dir.into()),
+        }
+    }
+
+    /// The path of the socket file for a port.
+    pub fn socket_path(&self, port: u16) -> PathBuf {
+        self.dir.join(format!("swimos-{}.sock", port))
+    }
+}
+
+impl ClientConnections for UnixNetworking {
+    type ClientSocket = UnixStream;
+
+    fn try_open(
+        &self,
+        scheme: Scheme,
+        _host: Option<&str>,
+        addr: SocketAddr,
+    ) -> BoxFuture<'_, ConnectionResult<Self::ClientSocket>> {
+        let path = self.socket_path(addr.port());
+        async move {
+            match scheme {
+                Scheme::Ws => Ok(UnixStream::connect(path).await?),
+                Scheme::Wss => Err(ConnectionError::BadParameter(NO_TLS.to_string())),
+            }
+        }
+        .boxed()
+    }
+
+    fn dns_resolver(&self) -> BoxDnsResolver {
+        Box::new(LoopbackResolver)
+    }
+
+    fn lookup(&self, host: String, port: u16) -> BoxFuture<'static, io::Result<Vec<SocketAddr>>> {
+        LoopbackResolver.resolve(host, port)
+    }
+}
+
+impl ServerConnections for UnixNetworking {
+    type ServerSocket = UnixStream;
+
+    type ListenerType = UnixSocketListener;
+
+    fn bind(
+        &self,
+        addr: SocketAddr,
+    ) -> BoxFuture<'static, ConnectionResult<(SocketAddr, Self::ListenerType)>> {
+        let result = match addr.port() {
+            0 => (FIRST_ASSIGNED_PORT..=u16::MAX)
+                .find(|port| !self.socket_path(*port).exists())
+                .ok_or_else(|| ConnectionError::ConnectionFailed(io::ErrorKind::AddrInUse.into()))
+                .and_then(|port| bind_to(self.socket_path(port), port)),
+            port => bind_to(self.socket_path(port), port),
+        };
+        futures::future::ready(result).boxed()
+    }
+}
+
+fn bind_to(path: PathBuf, port: u16) -> ConnectionResult<(SocketAddr, UnixSocketListener)> {
+    let listener = UnixListener::bind(&path)?;
+    Ok((loopback(port), UnixSocketListener { listener, path }))
+}
+
+/// A listener for the connections to a port of a [`UnixNetworking`] transport. The socket file is
+/// removed when the listener is dropped.
+#[derive(Debug)]
+pub struct UnixSocketListener {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl UnixSocketListener {
+    /// The path of the socket file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for UnixSocketListener {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl Stream for UnixSocketListener {
+    type Item = ListenerResult<(UnixStream, Scheme, SocketAddr)>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.listener
+            .poll_accept(cx)?
+            .map(|(stream, _)| Some(Ok((stream, Scheme::Ws, LOCAL_PEER))))
+    }
+}
+
+impl Listener<UnixStream> for UnixSocketListener {
+    type AcceptStream = Self;
+
+    fn into_stream(self) -> Self::AcceptStream {
+        self
+    }
+}
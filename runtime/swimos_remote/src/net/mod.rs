@@ -14,6 +14,7 @@
 
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use std::{
     fmt::{Display, Formatter},
     str::FromStr,
@@ -23,7 +24,8 @@ use http::{uri::InvalidUri, Uri};
 use thiserror::Error;
 
 use futures::future::BoxFuture;
-use futures::Stream;
+use futures::stream::FuturesUnordered;
+use futures::{Future, FutureExt, Stream, StreamExt};
 use std::io;
 use tokio::io::{AsyncRead, AsyncWrite};
 
@@ -100,6 +102,80 @@ where
     fn into_stream(self) -> Self::AcceptStream;
 }
 
+/// The default delay before an attempt to connect to the next address of a host is started, if
+/// the previous attempt has not completed (as recommended by RFC 8305).
+pub const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Order the addresses of a host so that they alternate between address families, starting with the
+/// family of the first address (RFC 8305, section 4).
+fn interleave_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let first_v6 = addrs.first().map(SocketAddr::is_ipv6).unwrap_or_default();
+    let (preferred, other): (Vec<_>, Vec<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == first_v6);
+    let mut preferred = preferred.into_iter();
+    let mut other = other.into_iter();
+    let mut interleaved = vec![];
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => break interleaved,
+            (first, second) => interleaved.extend(first.into_iter().chain(second)),
+        }
+    }
+}
+
+/// Attempt to open a connection to one of the addresses of a host, racing the attempts in the
+/// manner of RFC 8305 ("happy eyeballs"). The addresses are tried in an order that alternates
+/// between address families. A new attempt is started when the previous attempt fails or if it has
+/// not completed within `attempt_delay`. The first connection to be established is returned and
+/// any attempts that are still in progress are abandoned.
+///
+/// # Arguments
+/// * `addrs` - The addresses of the host.
+/// * `attempt_delay` - The delay before starting the next attempt.
+/// * `open` - Opens a connection to a single address.
+///
+/// If no connection could be established, the errors for each address are returned.
+pub async fn race_connections<S, F, Fut>(
+    addrs: Vec<SocketAddr>,
+    attempt_delay: Duration,
+    mut open: F,
+) -> Result<(SocketAddr, S), Vec<(SocketAddr, ConnectionError)>>
+where
+    F: FnMut(SocketAddr) -> Fut,
+    Fut: Future<Output = ConnectionResult<S>>,
+{
+    let mut remaining = interleave_families(addrs).into_iter();
+    let mut attempts = FuturesUnordered::new();
+    let mut errors = vec![];
+    let mut start_next = |attempts: &mut FuturesUnordered<_>| match remaining.next() {
+        Some(addr) => {
+            attempts.push(open(addr).map(move |result| (addr, result)));
+            true
+        }
+        None => false,
+    };
+    let mut more = start_next(&mut attempts);
+    while !attempts.is_empty() {
+        let delay = tokio::time::sleep(attempt_delay);
+        tokio::select! {
+            Some((addr, result)) = attempts.next() => match result {
+                Ok(socket) => return Ok((addr, socket)),
+                Err(error) => {
+                    errors.push((addr, error));
+                    if more {
+                        more = start_next(&mut attempts);
+                    }
+                }
+            },
+            _ = delay, if more => {
+                more = start_next(&mut attempts);
+            }
+        }
+    }
+    Err(errors)
+}
+
 /// Provides all networking functionality required for a Warp client (DNS resolution and opening sockets).
 pub trait ClientConnections: Clone + Send + Sync + 'static {
     type ClientSocket: AsyncRead + AsyncWrite + Unpin + Send + Sync + 'static;
@@ -12,7 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::net::{BadWarpUrl, Scheme, SchemeHostPort};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures::future::{pending, ready, BoxFuture};
+use futures::FutureExt;
+
+use crate::net::{
+    interleave_families, race_connections, BadWarpUrl, ConnectionError, ConnectionResult, Scheme,
+    SchemeHostPort,
+};
 
 #[test]
 fn parse_insecure_warp_url() {
@@ -59,3 +68,91 @@ fn parse_bad_warp_url_scheme() {
     let result = "ftp://localhost:8080".parse::<SchemeHostPort>();
     assert_eq!(result, Err(BadWarpUrl::BadScheme("ftp".to_string())));
 }
+
+fn addr(s: &str) -> SocketAddr {
+    s.parse().unwrap()
+}
+
+#[test]
+fn interleave_address_families() {
+    let addrs = vec![
+        addr("[fd00::1]:80"),
+        addr("[fd00::2]:80"),
+        addr("[fd00::3]:80"),
+        addr("10.0.0.1:80"),
+        addr("10.0.0.2:80"),
+    ];
+    let expected = vec![
+        addr("[fd00::1]:80"),
+        addr("10.0.0.1:80"),
+        addr("[fd00::2]:80"),
+        addr("10.0.0.2:80"),
+        addr("[fd00::3]:80"),
+    ];
+    assert_eq!(interleave_families(addrs), expected);
+}
+
+const LONG_DELAY: Duration = Duration::from_secs(30);
+
+fn refused() -> ConnectionError {
+    ConnectionError::ConnectionFailed(std::io::ErrorKind::ConnectionRefused.into())
+}
+
+#[tokio::test]
+async fn race_connections_skips_hanging_address() {
+    let v6 = addr("[fd00::1]:80");
+    let v4 = addr("10.0.0.1:80");
+    let result = race_connections(
+        vec![v6, v4],
+        Duration::from_millis(10),
+        |target| -> BoxFuture<'static, ConnectionResult<SocketAddr>> {
+            if target == v6 {
+                pending().boxed()
+            } else {
+                ready(Ok(target)).boxed()
+            }
+        },
+    )
+    .await;
+    assert!(matches!(result, Ok((a, s)) if a == v4 && s == v4));
+}
+
+#[tokio::test]
+async fn race_connections_starts_next_on_failure() {
+    let first = addr("10.0.0.1:80");
+    let second = addr("10.0.0.2:80");
+    // With a long attempt delay, the second attempt must be started as soon as the first fails.
+    let result = tokio::time::timeout(
+        Duration::from_secs(5),
+        race_connections(
+            vec![first, second],
+            LONG_DELAY,
+            |target| -> BoxFuture<'static, ConnectionResult<SocketAddr>> {
+                if target == first {
+                    ready(Err(refused())).boxed()
+                } else {
+                    ready(Ok(target)).boxed()
+                }
+            },
+        ),
+    )
+    .await
+    .expect("Timed out.");
+    assert!(matches!(result, Ok((a, _)) if a == second));
+}
+
+#[tokio::test]
+async fn race_connections_all_fail() {
+    let addrs = vec![addr("[fd00::1]:80"), addr("10.0.0.1:80")];
+    let result = race_connections(addrs.clone(), LONG_DELAY, |_| {
+        ready(Err::<(), _>(refused()))
+    })
+    .await;
+    match result {
+        Err(errors) => {
+            let failed = errors.iter().map(|(a, _)| *a).collect::<Vec<_>>();
+            assert_eq!(failed, addrs);
+        }
+        Ok(_) => panic!("Connection should fail."),
+    }
+}
@@ -18,6 +18,7 @@ use std::pin::Pin;
 use crate::dns::{DnsResolver, Resolver};
 use crate::net::Listener;
 use crate::net::{ConnectionError, Scheme};
+use crate::proxy::{open_tcp, ProxyConfig};
 use futures::future::BoxFuture;
 use futures::task::{Context, Poll};
 use futures::FutureExt;
@@ -34,11 +35,21 @@ use crate::net::{ClientConnections, ConnectionResult, ListenerResult, ServerConn
 #[derive(Debug, Clone)]
 pub struct TokioPlainTextNetworking {
     resolver: Arc<Resolver>,
+    proxy: Option<Arc<ProxyConfig>>,
 }
 
 impl TokioPlainTextNetworking {
     pub fn new(resolver: Arc<Resolver>) -> TokioPlainTextNetworking {
-        TokioPlainTextNetworking { resolver }
+        TokioPlainTextNetworking {
+            resolver,
+            proxy: None,
+        }
+    }
+
+    /// Open outgoing connections through a proxy.
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> TokioPlainTextNetworking {
+        self.proxy = Some(Arc::new(proxy));
+        self
     }
 }
 
@@ -56,12 +67,14 @@ impl ClientConnections for TokioPlainTextNetworking {
     fn try_open(
         &self,
         scheme: Scheme,
-        _host: Option<&str>,
+        host: Option<&str>,
         addr: SocketAddr,
     ) -> BoxFuture<'static, ConnectionResult<Self::ClientSocket>> {
+        let proxy = self.proxy.clone();
+        let host = host.map(str::to_owned);
         async move {
             match scheme {
-                Scheme::Ws => Ok(TcpStream::connect(addr).await?),
+                Scheme::Ws => open_tcp(proxy.as_deref(), host.as_deref(), addr).await,
                 Scheme::Wss => Err(ConnectionError::BadParameter(NO_TLS.to_string())),
             }
         }
@@ -0,0 +1,322 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::{Debug, Formatter};
+use std::net::{IpAddr, SocketAddr};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::net::{ConnectionError, ConnectionResult};
+
+#[cfg(test)]
+mod tests;
+
+/// The protocol used to communicate with a proxy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyKind {
+    /// A SOCKS5 proxy (RFC 1928).
+    Socks5,
+    /// An HTTP proxy that supports the `CONNECT` method.
+    Http,
+}
+
+/// Credentials to authenticate with a proxy.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProxyCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl Debug for ProxyCredentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyCredentials")
+            .field("username", &self.username)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+/// Configuration for a proxy through which outgoing connections are opened. For secure (`wss`)
+/// connections, the TLS session is negotiated through the tunnel provided by the proxy.
+///
+/// The name of the remote host is passed to the proxy so that it can be resolved by the proxy,
+/// although the client will still resolve it locally to identify the connection. If the client
+/// cannot resolve remote hosts, a custom resolver should be provided to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyConfig {
+    pub kind: ProxyKind,
+    /// The host of the proxy.
+    pub host: String,
+    /// The port of the proxy.
+    pub port: u16,
+    pub credentials: Option<ProxyCredentials>,
+}
+
+impl ProxyConfig {
+    /// A SOCKS5 proxy, without authentication.
+    pub fn socks5(host: impl Into<String>, port: u16) -> Self {
+        ProxyConfig {
+            kind: ProxyKind::Socks5,
+            host: host.into(),
+            port,
+            credentials: None,
+        }
+    }
+
+    /// An HTTP proxy, without authentication.
+    pub fn http(host: impl Into<String>, port: u16) -> Self {
+        ProxyConfig {
+            kind: ProxyKind::Http,
+            host: host.into(),
+            port,
+            credentials: None,
+        }
+    }
+
+    /// Authenticate with the proxy using a username and password.
+    pub fn with_credentials(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.credentials = Some(ProxyCredentials {
+            username: username.into(),
+            password: password.into(),
+        });
+        self
+    }
+}
+
+/// Reasons that opening a connection through a proxy can fail.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ProxyError {
+    /// The proxy sent a response that could not be interpreted.
+    #[error("The proxy sent an invalid response.")]
+    InvalidResponse,
+    /// The proxy requires authentication but no credentials were provided.
+    #[error("The proxy requires authentication.")]
+    AuthenticationRequired,
+    /// The proxy rejected the credentials that were provided.
+    #[error("The proxy rejected the credentials.")]
+    AuthenticationFailed,
+    /// The credentials cannot be sent to the proxy (for example, they are too long).
+    #[error("The proxy credentials are invalid.")]
+    InvalidCredentials,
+    /// The proxy refused to connect to the remote host.
+    #[error("The proxy refused the connection: {0}")]
+    Refused(String),
+}
+
+impl From<ProxyError> for ConnectionError {
+    fn from(err: ProxyError) -> Self {
+        ConnectionError::NegotiationFailed(Box::new(err))
+    }
+}
+
+/// Open a TCP connection to a remote host, through a proxy if one is provided.
+///
+/// # Arguments
+/// * `proxy` - The proxy to connect through.
+/// * `host` - The name of the remote host (if known).
+/// * `addr` - The resolved address of the remote host.
+pub async fn open_tcp(
+    proxy: Option<&ProxyConfig>,
+    host: Option<&str>,
+    addr: SocketAddr,
+) -> ConnectionResult<TcpStream> {
+    match proxy {
+        Some(proxy) => {
+            let mut stream = TcpStream::connect((proxy.host.as_str(), proxy.port)).await?;
+            handshake(proxy, &mut stream, host, addr).await?;
+            Ok(stream)
+        }
+        None => Ok(TcpStream::connect(addr).await?),
+    }
+}
+
+/// Perform the handshake with a proxy to open a tunnel to a remote host.
+async fn handshake<S>(
+    proxy: &ProxyConfig,
+    stream: &mut S,
+    host: Option<&str>,
+    addr: SocketAddr,
+) -> ConnectionResult<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let credentials = proxy.credentials.as_ref();
+    match proxy.kind {
+        ProxyKind::Socks5 => socks5_handshake(stream, credentials, host, addr).await,
+        ProxyKind::Http => http_handshake(stream, credentials, host, addr).await,
+    }
+}
+
+const SOCKS_VERSION: u8 = 5;
+const NO_AUTH: u8 = 0;
+const USER_PASS: u8 = 2;
+const USER_PASS_VERSION: u8 = 1;
+const CONNECT: u8 = 1;
+const ATYP_V4: u8 = 1;
+const ATYP_DOMAIN: u8 = 3;
+const ATYP_V6: u8 = 4;
+
+fn socks5_reply_message(code: u8) -> String {
+    match code {
+        1 => "General SOCKS server failure.".to_string(),
+        2 => "Connection not allowed by ruleset.".to_string(),
+        3 => "Network unreachable.".to_string(),
+        4 => "Host unreachable.".to_string(),
+        5 => "Connection refused.".to_string(),
+        6 => "TTL expired.".to_string(),
+        7 => "Command not supported.".to_string(),
+        8 => "Address type not supported.".to_string(),
+        _ => format!("Unknown SOCKS reply code: {}", code),
+    }
+}
+
+/// A host name that can be passed to the proxy (rather than the resolved address).
+fn domain_name(host: Option<&str>) -> Option<&str> {
+    host.filter(|host| host.parse::<IpAddr>().is_err())
+}
+
+async fn socks5_handshake<S>(
+    stream: &mut S,
+    credentials: Option<&ProxyCredentials>,
+    host: Option<&str>,
+    addr: SocketAddr,
+) -> ConnectionResult<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let greeting: &[u8] = if credentials.is_some() {
+        &[SOCKS_VERSION, 2, NO_AUTH, USER_PASS]
+    } else {
+        &[SOCKS_VERSION, 1, NO_AUTH]
+    };
+    stream.write_all(greeting).await?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    match (reply, credentials) {
+        ([SOCKS_VERSION, NO_AUTH], _) => {}
+        ([SOCKS_VERSION, USER_PASS], Some(ProxyCredentials { username, password })) => {
+            let (Ok(user_len), Ok(pass_len)) =
+                (u8::try_from(username.len()), u8::try_from(password.len()))
+            else {
+                return Err(ProxyError::InvalidCredentials.into());
+            };
+            let mut request = vec![USER_PASS_VERSION, user_len];
+            request.extend_from_slice(username.as_bytes());
+            request.push(pass_len);
+            request.extend_from_slice(password.as_bytes());
+            stream.write_all(&request).await?;
+            stream.read_exact(&mut reply).await?;
+            if reply != [USER_PASS_VERSION, 0] {
+                return Err(ProxyError::AuthenticationFailed.into());
+            }
+        }
+        ([SOCKS_VERSION, _], _) => return Err(ProxyError::AuthenticationRequired.into()),
+        _ => return Err(ProxyError::InvalidResponse.into()),
+    }
+
+    let mut request = vec![SOCKS_VERSION, CONNECT, 0];
+    match (domain_name(host), addr.ip()) {
+        (Some(domain), _) if domain.len() <= usize::from(u8::MAX) => {
+            request.push(ATYP_DOMAIN);
+            request.push(domain.len() as u8);
+            request.extend_from_slice(domain.as_bytes());
+        }
+        (_, IpAddr::V4(ip)) => {
+            request.push(ATYP_V4);
+            request.extend_from_slice(&ip.octets());
+        }
+        (_, IpAddr::V6(ip)) => {
+            request.push(ATYP_V6);
+            request.extend_from_slice(&ip.octets());
+        }
+    }
+    request.extend_from_slice(&addr.port().to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).await?;
+    let [version, code, _, atyp] = header;
+    if version != SOCKS_VERSION {
+        return Err(ProxyError::InvalidResponse.into());
+    }
+    if code != 0 {
+        return Err(ProxyError::Refused(socks5_reply_message(code)).into());
+    }
+    // The address that the proxy bound to is not required.
+    let addr_len = match atyp {
+        ATYP_V4 => 4,
+        ATYP_V6 => 16,
+        ATYP_DOMAIN => usize::from(stream.read_u8().await?),
+        _ => return Err(ProxyError::InvalidResponse.into()),
+    };
+    let mut bound = vec![0u8; addr_len + 2];
+    stream.read_exact(&mut bound).await?;
+    Ok(())
+}
+
+const MAX_RESPONSE_HEAD: usize = 8192;
+
+async fn http_handshake<S>(
+    stream: &mut S,
+    credentials: Option<&ProxyCredentials>,
+    host: Option<&str>,
+    addr: SocketAddr,
+) -> ConnectionResult<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let authority = match domain_name(host) {
+        Some(domain) => format!("{}:{}", domain, addr.port()),
+        None => addr.to_string(),
+    };
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", authority);
+    if let Some(ProxyCredentials { username, password }) = credentials {
+        let token = STANDARD.encode(format!("{}:{}", username, password));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // The response is read a byte at a time so that nothing that is sent through the tunnel is
+    // consumed.
+    let mut head = vec![];
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() == MAX_RESPONSE_HEAD {
+            return Err(ProxyError::InvalidResponse.into());
+        }
+        head.push(stream.read_u8().await?);
+    }
+    let status_line = std::str::from_utf8(&head)
+        .ok()
+        .and_then(|head| head.lines().next())
+        .ok_or(ProxyError::InvalidResponse)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or(ProxyError::InvalidResponse)?;
+    match status {
+        200..=299 => Ok(()),
+        407 if credentials.is_some() => Err(ProxyError::AuthenticationFailed.into()),
+        407 => Err(ProxyError::AuthenticationRequired.into()),
+        _ => Err(ProxyError::Refused(status_line.to_string()).into()),
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::SocketAddr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+use crate::net::ConnectionError;
+
+use super::{handshake, ProxyConfig, ProxyError};
+
+const BUFFER_SIZE: usize = 4096;
+
+fn remote_addr() -> SocketAddr {
+    "192.168.0.10:8080".parse().unwrap()
+}
+
+fn proxy_error(err: ConnectionError) -> ProxyError {
+    match err {
+        ConnectionError::NegotiationFailed(err) => {
+            let err = err
+                .downcast::<ProxyError>()
+                .expect("Unexpected error type.");
+            *err
+        }
+        ow => panic!("Unexpected error: {:?}", ow),
+    }
+}
+
+async fn read_http_head(stream: &mut DuplexStream) -> String {
+    let mut head = vec![];
+    while !head.ends_with(b"\r\n\r\n") {
+        head.push(stream.read_u8().await.expect("Reading request failed."));
+    }
+    String::from_utf8(head).expect("Invalid request.")
+}
+
+#[tokio::test]
+async fn socks5_no_auth_with_domain() {
+    let (mut client, mut proxy) = duplex(BUFFER_SIZE);
+    let config = ProxyConfig::socks5("proxy", 1080);
+
+    let proxy_task = async move {
+        let mut greeting = [0u8; 3];
+        proxy.read_exact(&mut greeting).await.unwrap();
+        assert_eq!(greeting, [5, 1, 0]);
+        proxy.write_all(&[5, 0]).await.unwrap();
+
+        let mut request = vec![0u8; 4 + 1 + "example.com".len() + 2];
+        proxy.read_exact(&mut request).await.unwrap();
+        let mut expected = vec![5, 1, 0, 3, 11];
+        expected.extend_from_slice(b"example.com");
+        expected.extend_from_slice(&8080u16.to_be_bytes());
+        assert_eq!(request, expected);
+        proxy
+            .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0x1f, 0x90])
+            .await
+            .unwrap();
+
+        let mut tunnelled = [0u8; 4];
+        proxy.read_exact(&mut tunnelled).await.unwrap();
+        assert_eq!(&tunnelled, b"ping");
+        proxy.write_all(b"pong").await.unwrap();
+    };
+
+    let client_task = async move {
+        handshake(&config, &mut client, Some("example.com"), remote_addr())
+            .await
+            .expect("Handshake failed.");
+        client.write_all(b"ping").await.unwrap();
+        let mut response = [0u8; 4];
+        client.read_exact(&mut response).await.unwrap();
+        assert_eq!(&response, b"pong");
+    };
+
+    tokio::join!(proxy_task, client_task);
+}
+
+#[tokio::test]
+async fn socks5_auth_failure() {
+    let (mut client, mut proxy) = duplex(BUFFER_SIZE);
+    let config = ProxyConfig::socks5("proxy", 1080).with_credentials("user", "secret");
+
+    let proxy_task = async move {
+        let mut greeting = [0u8; 4];
+        proxy.read_exact(&mut greeting).await.unwrap();
+        assert_eq!(greeting, [5, 2, 0, 2]);
+        proxy.write_all(&[5, 2]).await.unwrap();
+
+        let mut request = vec![0u8; 3 + "user".len() + "secret".len()];
+        proxy.read_exact(&mut request).await.unwrap();
+        let mut expected = vec![1, 4];
+        expected.extend_from_slice(b"user");
+        expected.push(6);
+        expected.extend_from_slice(b"secret");
+        assert_eq!(request, expected);
+        proxy.write_all(&[1, 1]).await.unwrap();
+    };
+
+    let client_task = async move {
+        let result = handshake(&config, &mut client, None, remote_addr()).await;
+        assert_eq!(
+            proxy_error(result.expect_err("Handshake should fail.")),
+            ProxyError::AuthenticationFailed
+        );
+    };
+
+    tokio::join!(proxy_task, client_task);
+}
+
+#[tokio::test]
+async fn http_connect_with_auth() {
+    let (mut client, mut proxy) = duplex(BUFFER_SIZE);
+    let config = ProxyConfig::http("proxy", 3128).with_credentials("user", "secret");
+
+    let proxy_task = async move {
+        let head = read_http_head(&mut proxy).await;
+        let mut lines = head.lines();
+        assert_eq!(lines.next(), Some("CONNECT example.com:8080 HTTP/1.1"));
+        let token = STANDARD.encode("user:secret");
+        let auth_header = format!("Proxy-Authorization: Basic {}", token);
+        assert!(head.lines().any(|line| line == auth_header));
+        proxy
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\npong")
+            .await
+            .unwrap();
+    };
+
+    let client_task = async move {
+        handshake(&config, &mut client, Some("example.com"), remote_addr())
+            .await
+            .expect("Handshake failed.");
+        let mut response = [0u8; 4];
+        client.read_exact(&mut response).await.unwrap();
+        assert_eq!(&response, b"pong");
+    };
+
+    tokio::join!(proxy_task, client_task);
+}
+
+#[tokio::test]
+async fn http_connect_auth_required() {
+    let (mut client, mut proxy) = duplex(BUFFER_SIZE);
+    let config = ProxyConfig::http("proxy", 3128);
+
+    let proxy_task = async move {
+        let head = read_http_head(&mut proxy).await;
+        assert!(head.starts_with("CONNECT 192.168.0.10:8080 HTTP/1.1\r\n"));
+        proxy
+            .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n")
+            .await
+            .unwrap();
+    };
+
+    let client_task = async move {
+        let result = handshake(&config, &mut client, None, remote_addr()).await;
+        assert_eq!(
+            proxy_error(result.expect_err("Handshake should fail.")),
+            ProxyError::AuthenticationRequired
+        );
+    };
+
+    tokio::join!(proxy_task, client_task);
+}
@@ -12,16 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use bytes::{BufMut, BytesMut};
+use std::str::Utf8Error;
+
+use bytes::{Buf, BufMut, BytesMut};
 use swimos_api::address::RelativeAddress;
 use swimos_messages::{
     protocol::{
         BytesRequestMessage, BytesResponseMessage, Notification, Operation, RequestMessage,
         ResponseMessage,
     },
-    remote_protocol::NoSuchAgent,
+    remote_protocol::{AgentNotStarted, NoSuchAgent},
 };
-use swimos_model::{identifier::is_identifier, literal::escape_if_needed};
+use swimos_model::{identifier::is_identifier, literal::escape_if_needed, Text};
+use swimos_recon::print_recon_compact;
+use thiserror::Error;
 use tokio_util::codec::Encoder;
 
 #[cfg(test)]
@@ -34,6 +38,7 @@ pub struct ReconEncoder;
 
 const LINK_HEADER: &[u8] = b"@link(";
 const SYNC_HEADER: &[u8] = b"@sync(";
+const ACK_HEADER: &[u8] = b"@ack(";
 const UNLINK_HEADER: &[u8] = b"@unlink(";
 const CMD_HEADER: &[u8] = b"@command(";
 
@@ -41,11 +46,19 @@ const LINKED_HEADER: &[u8] = b"@linked(";
 const SYNCED_HEADER: &[u8] = b"@synced(";
 const UNLINKED_HEADER: &[u8] = b"@unlinked(";
 const EVENT_HEADER: &[u8] = b"@event(";
+const ADVISORY_HEADER: &[u8] = b"@advisory(";
+const DISPATCHED_HEADER: &[u8] = b"@dispatched(";
 
 const NODE_TAG: &[u8] = b"node:";
 const LANE_TAG: &[u8] = b"lane:";
+const WINDOW_TAG: &str = ",window:";
+const FROM_TAG: &[u8] = b",from:";
+const TO_TAG: &[u8] = b",to:";
+const ID_TAG: &str = ",id:";
+const ORIGIN_TAG: &str = ",origin:";
 
 const NODE_NOT_FOUND_TAG: &str = "@nodeNotFound";
+const NODE_NOT_STARTED_TAG: &str = "@nodeNotStarted";
 
 const FIXED_LEN: usize = 12;
 
@@ -59,8 +72,41 @@ impl Encoder<BytesRequestMessage> for ReconEncoder {
             ..
         } = item;
         match envelope {
-            Operation::Link => write_header(LINK_HEADER, node.as_str(), lane.as_str(), dst),
-            Operation::Sync => write_header(SYNC_HEADER, node.as_str(), lane.as_str(), dst),
+            Operation::Link(_) => write_header(LINK_HEADER, node.as_str(), lane.as_str(), dst),
+            Operation::Sync(params) => {
+                if let Some(window) = params.window {
+                    write_path(SYNC_HEADER, node.as_str(), lane.as_str(), dst);
+                    let window_slot = format!("{}{})", WINDOW_TAG, window);
+                    dst.reserve(window_slot.len());
+                    dst.put_slice(window_slot.as_bytes());
+                } else {
+                    write_header(SYNC_HEADER, node.as_str(), lane.as_str(), dst);
+                }
+                if let Some(since) = params.since {
+                    put_body(format!("{}", print_recon_compact(&since)), dst);
+                }
+            }
+            Operation::SyncRange(params, range) => {
+                write_path(SYNC_HEADER, node.as_str(), lane.as_str(), dst);
+                if let Some(window) = params.window {
+                    let window_slot = format!("{}{}", WINDOW_TAG, window);
+                    dst.reserve(window_slot.len());
+                    dst.put_slice(window_slot.as_bytes());
+                }
+                // The bounds are already in Recon so can be written directly.
+                for (tag, bound) in [(FROM_TAG, &range.from), (TO_TAG, &range.to)] {
+                    if let Some(bound) = bound {
+                        dst.reserve(tag.len() + bound.len());
+                        dst.put_slice(tag);
+                        dst.put_slice(bound);
+                    }
+                }
+                dst.put_u8(b')');
+                if let Some(since) = params.since {
+                    put_body(format!("{}", print_recon_compact(&since)), dst);
+                }
+            }
+            Operation::Ack => write_header(ACK_HEADER, node.as_str(), lane.as_str(), dst),
             Operation::Unlink => write_header(UNLINK_HEADER, node.as_str(), lane.as_str(), dst),
             Operation::Command(body) => {
                 write_header(CMD_HEADER, node.as_str(), lane.as_str(), dst);
@@ -95,11 +141,18 @@ impl Encoder<BytesResponseMessage> for ReconEncoder {
         let ResponseMessage {
             path: RelativeAddress { node, lane },
             envelope,
+            command_origin,
             ..
         } = item;
         match envelope {
             Notification::Linked => write_header(LINKED_HEADER, node.as_str(), lane.as_str(), dst),
             Notification::Synced => write_header(SYNCED_HEADER, node.as_str(), lane.as_str(), dst),
+            Notification::SyncedWith(body) => {
+                write_header(SYNCED_HEADER, node.as_str(), lane.as_str(), dst);
+                if !body.is_empty() {
+                    put_body(body, dst);
+                }
+            }
             Notification::Unlinked(body) => {
                 write_header(UNLINKED_HEADER, node.as_str(), lane.as_str(), dst);
                 match body {
@@ -109,8 +162,20 @@ impl Encoder<BytesResponseMessage> for ReconEncoder {
                     _ => {}
                 }
             }
+            Notification::Advisory(body) => {
+                write_header(ADVISORY_HEADER, node.as_str(), lane.as_str(), dst);
+                if !body.is_empty() {
+                    put_body(body, dst);
+                }
+            }
             Notification::Event(body) => {
-                write_header(EVENT_HEADER, node.as_str(), lane.as_str(), dst);
+                if let Some(origin) = command_origin {
+                    write_path(EVENT_HEADER, node.as_str(), lane.as_str(), dst);
+                    let origin_slot = format!("{}\"{}\")", ORIGIN_TAG, origin);
+                    dst.put_slice(origin_slot.as_bytes());
+                } else {
+                    write_header(EVENT_HEADER, node.as_str(), lane.as_str(), dst);
+                }
                 if !body.is_empty() {
                     put_body(body, dst);
                 }
@@ -136,6 +201,42 @@ impl Encoder<NoSuchAgent> for ReconEncoder {
     }
 }
 
+impl Encoder<AgentNotStarted> for ReconEncoder {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: AgentNotStarted, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let AgentNotStarted { node, lane } = item;
+        write_header(
+            UNLINKED_HEADER,
+            node.as_str(),
+            lane.as_ref().map(|s| s.as_str()).unwrap_or(""),
+            dst,
+        );
+        put_body(NODE_NOT_STARTED_TAG, dst);
+        Ok(())
+    }
+}
+
+/// Informs a remote that a command that it sent, with an ID, has been dispatched to the lane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandDispatched {
+    pub node: Text,
+    pub lane: Text,
+    pub id: u64,
+}
+
+impl Encoder<CommandDispatched> for ReconEncoder {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: CommandDispatched, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let CommandDispatched { node, lane, id } = item;
+        write_path(DISPATCHED_HEADER, node.as_str(), lane.as_str(), dst);
+        let id_slot = format!("{}{})", ID_TAG, id);
+        dst.put_slice(id_slot.as_bytes());
+        Ok(())
+    }
+}
+
 fn compute_len(header: &[u8], node: &str, lane: &str, node_ident: bool, lane_ident: bool) -> usize {
     header.len() + len_lit(node, node_ident) + len_lit(lane, lane_ident) + FIXED_LEN
 }
@@ -159,6 +260,13 @@ fn write_lit(lit: &str, ident: bool, dst: &mut BytesMut) {
 }
 
 fn write_header(header: &[u8], node: &str, lane: &str, dst: &mut BytesMut) {
+    write_path(header, node, lane, dst);
+    dst.put_u8(b')');
+}
+
+/// Write the tag and the node and lane slots of an envelope header, leaving the header open so
+/// that further slots can be added.
+fn write_path(header: &[u8], node: &str, lane: &str, dst: &mut BytesMut) {
     let node_ident = is_identifier(node);
     let lane_ident = is_identifier(lane);
 
@@ -180,5 +288,217 @@ fn write_header(header: &[u8], node: &str, lane: &str, dst: &mut BytesMut) {
     dst.put_u8(b',');
     dst.put_slice(LANE_TAG);
     write_lit(lane_str.as_ref(), lane_ident, dst);
-    dst.put_u8(b')');
+}
+
+/// Encoder to write internal request and response messages out as binary envelopes on a
+/// websocket connection (see [`crate::websocket::WarpEncoding::Binary`] for the format).
+#[derive(Debug, Default)]
+pub struct BinaryEncoder;
+
+const LINK_TAG: u8 = 0;
+const SYNC_TAG: u8 = 1;
+const UNLINK_TAG: u8 = 2;
+const CMD_TAG: u8 = 3;
+const LINKED_TAG: u8 = 4;
+const SYNCED_TAG: u8 = 5;
+const UNLINKED_TAG: u8 = 6;
+const EVENT_TAG: u8 = 7;
+const ACK_TAG: u8 = 8;
+const ADVISORY_TAG: u8 = 9;
+
+const BINARY_HEADER_LEN: usize = 1 + 2 * std::mem::size_of::<u32>();
+
+impl Encoder<BytesRequestMessage> for BinaryEncoder {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: BytesRequestMessage, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let RequestMessage {
+            path: RelativeAddress { node, lane },
+            envelope,
+            ..
+        } = item;
+        match envelope {
+            Operation::Link(_) => write_binary(LINK_TAG, node.as_str(), lane.as_str(), b"", dst),
+            // Binary envelopes cannot carry a key range so a ranged sync is sent as a full sync.
+            Operation::Sync(params) | Operation::SyncRange(params, _) => {
+                let body = params
+                    .since
+                    .map(|since| format!("{}", print_recon_compact(&since)))
+                    .unwrap_or_default();
+                write_binary(SYNC_TAG, node.as_str(), lane.as_str(), body.as_bytes(), dst)
+            }
+            Operation::Ack => write_binary(ACK_TAG, node.as_str(), lane.as_str(), b"", dst),
+            Operation::Unlink => write_binary(UNLINK_TAG, node.as_str(), lane.as_str(), b"", dst),
+            Operation::Command(body) => {
+                write_binary(CMD_TAG, node.as_str(), lane.as_str(), body.as_ref(), dst)
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Encoder<BytesResponseMessage> for BinaryEncoder {
+    type Error = std::io::Error;
+
+    fn encode(
+        &mut self,
+        item: BytesResponseMessage,
+        dst: &mut BytesMut,
+    ) -> Result<(), Self::Error> {
+        let ResponseMessage {
+            path: RelativeAddress { node, lane },
+            envelope,
+            ..
+        } = item;
+        match envelope {
+            Notification::Linked => {
+                write_binary(LINKED_TAG, node.as_str(), lane.as_str(), b"", dst)
+            }
+            Notification::Synced => {
+                write_binary(SYNCED_TAG, node.as_str(), lane.as_str(), b"", dst)
+            }
+            Notification::SyncedWith(body) => {
+                write_binary(SYNCED_TAG, node.as_str(), lane.as_str(), body.as_ref(), dst)
+            }
+            Notification::Unlinked(body) => write_binary(
+                UNLINKED_TAG,
+                node.as_str(),
+                lane.as_str(),
+                body.as_ref().map(AsRef::as_ref).unwrap_or_default(),
+                dst,
+            ),
+            Notification::Advisory(body) => write_binary(
+                ADVISORY_TAG,
+                node.as_str(),
+                lane.as_str(),
+                body.as_ref(),
+                dst,
+            ),
+            // Binary envelopes cannot carry the origin of the command that caused an event.
+            Notification::Event(body) => {
+                write_binary(EVENT_TAG, node.as_str(), lane.as_str(), body.as_ref(), dst)
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Encoder<NoSuchAgent> for BinaryEncoder {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: NoSuchAgent, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let NoSuchAgent { node, lane } = item;
+        write_binary(
+            UNLINKED_TAG,
+            node.as_str(),
+            lane.as_ref().map(|s| s.as_str()).unwrap_or(""),
+            NODE_NOT_FOUND_TAG.as_bytes(),
+            dst,
+        );
+        Ok(())
+    }
+}
+
+impl Encoder<AgentNotStarted> for BinaryEncoder {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: AgentNotStarted, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let AgentNotStarted { node, lane } = item;
+        write_binary(
+            UNLINKED_TAG,
+            node.as_str(),
+            lane.as_ref().map(|s| s.as_str()).unwrap_or(""),
+            NODE_NOT_STARTED_TAG.as_bytes(),
+            dst,
+        );
+        Ok(())
+    }
+}
+
+fn write_binary(tag: u8, node: &str, lane: &str, body: &[u8], dst: &mut BytesMut) {
+    dst.reserve(BINARY_HEADER_LEN + node.len() + lane.len() + body.len());
+    dst.put_u8(tag);
+    dst.put_u32(node.len() as u32);
+    dst.put_u32(lane.len() as u32);
+    dst.put_slice(node.as_bytes());
+    dst.put_slice(lane.as_bytes());
+    dst.put_slice(body);
+}
+
+/// The kinds of binary Warp envelopes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryEnvelopeKind {
+    Link,
+    Sync,
+    Unlink,
+    Command,
+    Linked,
+    Synced,
+    Unlinked,
+    Event,
+    Ack,
+    Advisory,
+}
+
+impl TryFrom<u8> for BinaryEnvelopeKind {
+    type Error = BinaryEnvelopeError;
+
+    fn try_from(tag: u8) -> Result<Self, Self::Error> {
+        match tag {
+            LINK_TAG => Ok(BinaryEnvelopeKind::Link),
+            SYNC_TAG => Ok(BinaryEnvelopeKind::Sync),
+            UNLINK_TAG => Ok(BinaryEnvelopeKind::Unlink),
+            CMD_TAG => Ok(BinaryEnvelopeKind::Command),
+            LINKED_TAG => Ok(BinaryEnvelopeKind::Linked),
+            SYNCED_TAG => Ok(BinaryEnvelopeKind::Synced),
+            UNLINKED_TAG => Ok(BinaryEnvelopeKind::Unlinked),
+            EVENT_TAG => Ok(BinaryEnvelopeKind::Event),
+            ACK_TAG => Ok(BinaryEnvelopeKind::Ack),
+            ADVISORY_TAG => Ok(BinaryEnvelopeKind::Advisory),
+            ow => Err(BinaryEnvelopeError::InvalidTag(ow)),
+        }
+    }
+}
+
+/// Interpreted form of a binary Warp envelope. The fields refer back into the original frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BinaryEnvelope<'a> {
+    pub kind: BinaryEnvelopeKind,
+    pub node: &'a str,
+    pub lane: &'a str,
+    pub body: &'a str,
+}
+
+/// Possible errors that can occur when attempting to interpret a binary Warp envelope.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum BinaryEnvelopeError {
+    #[error("The frame was too short to contain a binary envelope.")]
+    Truncated,
+    #[error("Invalid envelope tag: {0}")]
+    InvalidTag(u8),
+    #[error("The envelope contained invalid UTF-8: {0}")]
+    BadUtf8(#[from] Utf8Error),
+}
+
+/// Try to interpret a binary websocket frame as a Warp envelope, without allocating.
+pub fn read_binary_envelope(frame: &[u8]) -> Result<BinaryEnvelope<'_>, BinaryEnvelopeError> {
+    if frame.len() < BINARY_HEADER_LEN {
+        return Err(BinaryEnvelopeError::Truncated);
+    }
+    let mut header = &frame[..BINARY_HEADER_LEN];
+    let kind = BinaryEnvelopeKind::try_from(header.get_u8())?;
+    let node_len = header.get_u32() as usize;
+    let lane_len = header.get_u32() as usize;
+    let rest = &frame[BINARY_HEADER_LEN..];
+    if rest.len() < node_len.saturating_add(lane_len) {
+        return Err(BinaryEnvelopeError::Truncated);
+    }
+    let (node, rest) = rest.split_at(node_len);
+    let (lane, body) = rest.split_at(lane_len);
+    Ok(BinaryEnvelope {
+        kind,
+        node: std::str::from_utf8(node)?,
+        lane: std::str::from_utf8(lane)?,
+        body: std::str::from_utf8(body)?,
+    })
 }
@@ -13,17 +13,25 @@
 // limitations under the License.
 
 use bytes::{Bytes, BytesMut};
-use swimos_api::address::RelativeAddress;
+use swimos_api::{
+    address::RelativeAddress,
+    agent::{KeyRange, SyncVersion},
+};
 use swimos_messages::{
-    protocol::{BytesRequestMessage, BytesResponseMessage, RequestMessage, ResponseMessage},
-    remote_protocol::NoSuchAgent,
+    protocol::{
+        BytesRequestMessage, BytesResponseMessage, LinkParams, RequestMessage, ResponseMessage,
+    },
+    remote_protocol::{AgentNotStarted, NoSuchAgent},
 };
 use swimos_model::Text;
 use swimos_utilities::encoding::BytesStr;
 use tokio_util::codec::Encoder;
 use uuid::Uuid;
 
-use super::ReconEncoder;
+use super::{
+    read_binary_envelope, BinaryEncoder, BinaryEnvelope, BinaryEnvelopeError, BinaryEnvelopeKind,
+    CommandDispatched, ReconEncoder,
+};
 
 const ID: Uuid = Uuid::from_u128(7474834);
 const NODE: &str = "/node";
@@ -68,6 +76,80 @@ fn encode_sync() {
     assert_eq!(envelope_str, "@sync(node:\"/node\",lane:lane)");
 }
 
+#[test]
+fn encode_sync_with_version() {
+    let mut encoder = ReconEncoder;
+    let params = LinkParams::default().with_since(Some(SyncVersion::new(3, 17)));
+    let message: BytesRequestMessage = RequestMessage::sync_with_params(ID, path(), params);
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    let envelope_str = std::str::from_utf8(buffer.as_ref()).expect("Invalid UTF8!");
+
+    assert_eq!(
+        envelope_str,
+        "@sync(node:\"/node\",lane:lane)@version{epoch:3,version:17}"
+    );
+}
+
+#[test]
+fn encode_sync_with_window() {
+    let mut encoder = ReconEncoder;
+    let params = LinkParams::default()
+        .with_since(Some(SyncVersion::new(3, 17)))
+        .with_window(Some(32));
+    let message: BytesRequestMessage = RequestMessage::sync_with_params(ID, path(), params);
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    let envelope_str = std::str::from_utf8(buffer.as_ref()).expect("Invalid UTF8!");
+
+    assert_eq!(
+        envelope_str,
+        "@sync(node:\"/node\",lane:lane,window:32)@version{epoch:3,version:17}"
+    );
+}
+
+#[test]
+fn encode_sync_with_range() {
+    let mut encoder = ReconEncoder;
+    let range = KeyRange::new(
+        Some(Bytes::from_static(b"100")),
+        Some(Bytes::from_static(b"\"key\"")),
+    );
+    let params = LinkParams::default().with_window(Some(8));
+    let message: BytesRequestMessage = RequestMessage::sync_range(ID, path(), params, range);
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    let envelope_str = std::str::from_utf8(buffer.as_ref()).expect("Invalid UTF8!");
+
+    assert_eq!(
+        envelope_str,
+        "@sync(node:\"/node\",lane:lane,window:8,from:100,to:\"key\")"
+    );
+}
+
+#[test]
+fn encode_ack() {
+    let mut encoder = ReconEncoder;
+    let message: BytesRequestMessage = RequestMessage::ack(ID, path());
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    let envelope_str = std::str::from_utf8(buffer.as_ref()).expect("Invalid UTF8!");
+
+    assert_eq!(envelope_str, "@ack(node:\"/node\",lane:lane)");
+}
+
 #[test]
 fn encode_unlink() {
     let mut encoder = ReconEncoder;
@@ -154,6 +236,39 @@ fn encode_synced() {
     assert_eq!(envelope_str, "@synced(node:\"/node\",lane:lane)");
 }
 
+#[test]
+fn encode_synced_with_version() {
+    let mut encoder = ReconEncoder;
+    let body = Bytes::from_static(b"@version{epoch:3,version:17}");
+    let message: BytesResponseMessage = ResponseMessage::synced_with(ID, path(), body);
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    let envelope_str = std::str::from_utf8(buffer.as_ref()).expect("Invalid UTF8!");
+
+    assert_eq!(
+        envelope_str,
+        "@synced(node:\"/node\",lane:lane)@version{epoch:3,version:17}"
+    );
+}
+
+#[test]
+fn encode_advisory() {
+    let mut encoder = ReconEncoder;
+    let body = Bytes::from_static(b"@unlink");
+    let message: BytesResponseMessage = ResponseMessage::advisory(ID, path(), body);
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    let envelope_str = std::str::from_utf8(buffer.as_ref()).expect("Invalid UTF8!");
+
+    assert_eq!(envelope_str, "@advisory(node:\"/node\",lane:lane)@unlink");
+}
+
 #[test]
 fn encode_unlinked_no_body() {
     let mut encoder = ReconEncoder;
@@ -227,6 +342,43 @@ fn encode_event_with_attr() {
     assert_eq!(envelope_str, "@event(node:\"/node\",lane:lane)@body");
 }
 
+#[test]
+fn encode_event_with_origin() {
+    let mut encoder = ReconEncoder;
+    let origin = Uuid::from_u128(0x6a1a6e6c_1f3b_4d0e_9a0c_6e4b5b0f2d7a);
+    let message: BytesResponseMessage =
+        ResponseMessage::event(ID, path(), Bytes::from_static(b"body")).with_command_origin(origin);
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    let envelope_str = std::str::from_utf8(buffer.as_ref()).expect("Invalid UTF8!");
+
+    assert_eq!(
+        envelope_str,
+        "@event(node:\"/node\",lane:lane,origin:\"6a1a6e6c-1f3b-4d0e-9a0c-6e4b5b0f2d7a\") body"
+    );
+}
+
+#[test]
+fn encode_command_dispatched() {
+    let mut encoder = ReconEncoder;
+    let message = CommandDispatched {
+        node: Text::new(NODE),
+        lane: Text::new(LANE),
+        id: 42,
+    };
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    let envelope_str = std::str::from_utf8(buffer.as_ref()).expect("Invalid UTF8!");
+
+    assert_eq!(envelope_str, "@dispatched(node:\"/node\",lane:lane,id:42)");
+}
+
 #[test]
 fn encode_not_found() {
     let mut encoder = ReconEncoder;
@@ -246,3 +398,208 @@ fn encode_not_found() {
         "@unlinked(node:\"/node\",lane:lane)@nodeNotFound"
     );
 }
+
+#[test]
+fn encode_not_started() {
+    let mut encoder = ReconEncoder;
+    let message = AgentNotStarted {
+        node: Text::new(NODE),
+        lane: Some(Text::new(LANE)),
+    };
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    let envelope_str = std::str::from_utf8(buffer.as_ref()).expect("Invalid UTF8!");
+
+    assert_eq!(
+        envelope_str,
+        "@unlinked(node:\"/node\",lane:lane)@nodeNotStarted"
+    );
+}
+
+#[test]
+fn binary_link_round_trip() {
+    let mut encoder = BinaryEncoder;
+    let message: BytesRequestMessage = RequestMessage::link(ID, path());
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    assert_eq!(
+        read_binary_envelope(buffer.as_ref()),
+        Ok(BinaryEnvelope {
+            kind: BinaryEnvelopeKind::Link,
+            node: NODE,
+            lane: LANE,
+            body: "",
+        })
+    );
+}
+
+#[test]
+fn binary_ack_round_trip() {
+    let mut encoder = BinaryEncoder;
+    let message: BytesRequestMessage = RequestMessage::ack(ID, path());
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    assert_eq!(
+        read_binary_envelope(buffer.as_ref()),
+        Ok(BinaryEnvelope {
+            kind: BinaryEnvelopeKind::Ack,
+            node: NODE,
+            lane: LANE,
+            body: "",
+        })
+    );
+}
+
+#[test]
+fn binary_command_round_trip() {
+    let mut encoder = BinaryEncoder;
+    let message: BytesRequestMessage =
+        RequestMessage::command(ID, path(), Bytes::from_static(b"@body"));
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    assert_eq!(
+        read_binary_envelope(buffer.as_ref()),
+        Ok(BinaryEnvelope {
+            kind: BinaryEnvelopeKind::Command,
+            node: NODE,
+            lane: LANE,
+            body: "@body",
+        })
+    );
+}
+
+#[test]
+fn binary_unlinked_round_trip() {
+    let mut encoder = BinaryEncoder;
+    let message: BytesResponseMessage = ResponseMessage::unlinked(ID, path(), None);
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    assert_eq!(
+        read_binary_envelope(buffer.as_ref()),
+        Ok(BinaryEnvelope {
+            kind: BinaryEnvelopeKind::Unlinked,
+            node: NODE,
+            lane: LANE,
+            body: "",
+        })
+    );
+}
+
+#[test]
+fn binary_event_round_trip() {
+    let mut encoder = BinaryEncoder;
+    let message: BytesResponseMessage =
+        ResponseMessage::event(ID, path(), Bytes::from_static(b"body"));
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    assert_eq!(
+        read_binary_envelope(buffer.as_ref()),
+        Ok(BinaryEnvelope {
+            kind: BinaryEnvelopeKind::Event,
+            node: NODE,
+            lane: LANE,
+            body: "body",
+        })
+    );
+}
+
+#[test]
+fn binary_advisory_round_trip() {
+    let mut encoder = BinaryEncoder;
+    let message: BytesResponseMessage =
+        ResponseMessage::advisory(ID, path(), Bytes::from_static(b"@reduceRate"));
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    assert_eq!(
+        read_binary_envelope(buffer.as_ref()),
+        Ok(BinaryEnvelope {
+            kind: BinaryEnvelopeKind::Advisory,
+            node: NODE,
+            lane: LANE,
+            body: "@reduceRate",
+        })
+    );
+}
+
+#[test]
+fn binary_not_found_round_trip() {
+    let mut encoder = BinaryEncoder;
+    let message = NoSuchAgent {
+        node: Text::new(NODE),
+        lane: Some(Text::new(LANE)),
+    };
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    assert_eq!(
+        read_binary_envelope(buffer.as_ref()),
+        Ok(BinaryEnvelope {
+            kind: BinaryEnvelopeKind::Unlinked,
+            node: NODE,
+            lane: LANE,
+            body: "@nodeNotFound",
+        })
+    );
+}
+
+#[test]
+fn binary_invalid_envelopes() {
+    assert_eq!(
+        read_binary_envelope(&[7, 0, 0]),
+        Err(BinaryEnvelopeError::Truncated)
+    );
+    assert_eq!(
+        read_binary_envelope(&[7, 0, 0, 0, 10, 0, 0, 0, 1, b'a']),
+        Err(BinaryEnvelopeError::Truncated)
+    );
+    assert_eq!(
+        read_binary_envelope(&[42, 0, 0, 0, 0, 0, 0, 0, 0]),
+        Err(BinaryEnvelopeError::InvalidTag(42))
+    );
+}
+
+#[test]
+fn binary_not_started_round_trip() {
+    let mut encoder = BinaryEncoder;
+    let message = AgentNotStarted {
+        node: Text::new(NODE),
+        lane: Some(Text::new(LANE)),
+    };
+
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(message, &mut buffer).is_ok());
+
+    assert_eq!(
+        read_binary_envelope(buffer.as_ref()),
+        Ok(BinaryEnvelope {
+            kind: BinaryEnvelopeKind::Unlinked,
+            node: NODE,
+            lane: LANE,
+            body: "@nodeNotStarted",
+        })
+    );
+}
@@ -21,10 +21,10 @@ use std::{
     time::Duration,
 };
 
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use either::Either;
 use futures::{
-    future::{join, join_all, ready, select},
+    future::{join, join_all, pending, ready, select},
     stream::{unfold, FuturesUnordered},
     Future, SinkExt, Stream, StreamExt,
 };
@@ -33,20 +33,24 @@ use ratchet::{
     SplittableExtension, WebSocket, WebSocketStream,
 };
 use smallvec::SmallVec;
-use swimos_api::address::RelativeAddress;
+use swimos_api::{
+    address::RelativeAddress,
+    agent::{KeyRange, SyncVersion},
+    limits::{LimitExceeded, Limits},
+};
 use swimos_messages::warp::{peel_envelope_header_str, RawEnvelope};
 use swimos_messages::{
     protocol::{
-        BytesRequestMessage, BytesResponseMessage, RawRequestMessageDecoder,
-        RawRequestMessageEncoder, RawResponseMessageDecoder, RawResponseMessageEncoder,
-        RequestMessage, ResponseMessage,
+        BytesRequestMessage, BytesResponseMessage, CorrelationId, LinkParams,
+        RawRequestMessageDecoder, RawRequestMessageEncoder, RawResponseMessageDecoder,
+        RawResponseMessageEncoder, RequestMessage, ResponseMessage,
     },
     remote_protocol::{
         AgentResolutionError, AttachClient, FindNode, LinkError, NoSuchAgent, NodeConnectionRequest,
     },
 };
 use swimos_model::Text;
-use swimos_recon::parser::MessageExtractError;
+use swimos_recon::parser::{parse_recognize, MessageExtractError};
 use swimos_utilities::{
     byte_channel::{ByteReader, ByteWriter},
     encoding::BytesStr,
@@ -55,18 +59,36 @@ use swimos_utilities::{
 };
 use thiserror::Error;
 use tokio::sync::{mpsc, oneshot};
+use tokio::time::{interval_at, Instant, Interval, MissedTickBehavior};
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::codec::{Encoder, FramedRead, FramedWrite};
 use uuid::Uuid;
 
 use tracing::{debug, error, info, info_span, trace, warn, Instrument};
 
-use self::envelopes::ReconEncoder;
+use self::envelopes::{
+    read_binary_envelope, BinaryEncoder, BinaryEnvelope, BinaryEnvelopeError, BinaryEnvelopeKind,
+    CommandDispatched,
+};
+use crate::capture::{CaptureDirection, FrameCapture};
+use crate::websocket::{WarpEncoding, WarpProtocol};
 
 mod envelopes;
 #[cfg(test)]
 mod tests;
 
+pub use self::envelopes::ReconEncoder;
+
+/// Keep-alive configuration for a socket connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepAlive {
+    /// The interval at which ping frames are sent to the peer.
+    pub interval: Duration,
+    /// If no frames (including pongs) are received from the peer within this period, it is
+    /// considered to be dead and the connection is dropped.
+    pub timeout: Duration,
+}
+
 /// A task that manages a socket connection. Incoming envelopes are routed to the appropriate
 /// downlink or agent. Agents will be resolved externally where required.
 pub struct RemoteTask<S, E> {
@@ -77,6 +99,10 @@ pub struct RemoteTask<S, E> {
     find_tx: Option<mpsc::Sender<FindNode>>,
     registration_buffer_size: NonZeroUsize,
     close_timeout: Duration,
+    keep_alive: Option<KeepAlive>,
+    capture: Option<FrameCapture>,
+    protocol: WarpProtocol,
+    limits: Limits,
 }
 
 impl<S, E> RemoteTask<S, E> {
@@ -97,8 +123,37 @@ impl<S, E> RemoteTask<S, E> {
             find_tx,
             registration_buffer_size,
             close_timeout,
+            keep_alive: None,
+            capture: None,
+            protocol: WarpProtocol::default(),
+            limits: Limits::default(),
         }
     }
+
+    /// Periodically send pings to the peer and drop the connection if it stops responding.
+    pub fn with_keep_alive(mut self, keep_alive: KeepAlive) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
+    /// Record the raw frames sent to and received from the peer (while the capture is enabled).
+    pub fn with_capture(mut self, capture: FrameCapture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Set the version of the Warp protocol (as negotiated when the connection was opened).
+    pub fn with_protocol(mut self, protocol: WarpProtocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Set the limits that are enforced on the envelopes received from the peer. If an envelope
+    /// violates them, the connection is closed.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -110,6 +165,7 @@ enum IncomingEvent<M> {
 #[derive(Debug)]
 enum OutgoingEvent {
     Message(OutgoingTaskMessage),
+    Ping,
     Request(BytesRequestMessage),
     Response(BytesResponseMessage),
 }
@@ -124,8 +180,14 @@ enum InputError {
     BadUtf8(Utf8Error),
     #[error("A web socket frame did not contain a valid Warp envelope: {0}")]
     InvalidEnvelope(MessageExtractError),
+    #[error("A web socket frame did not contain a valid binary Warp envelope: {0}")]
+    InvalidBinaryEnvelope(BinaryEnvelopeError),
     #[error("The web socket connection was closed.")]
     Closed(Option<CloseReason>),
+    #[error("No frames were received from the peer within {0:?}.")]
+    TimedOut(Duration),
+    #[error("A web socket frame violated a limit: {0}")]
+    LimitExceeded(LimitExceeded),
 }
 
 const STOPPING: &str = "Server is stopping.";
@@ -156,8 +218,13 @@ where
             find_tx,
             registration_buffer_size,
             close_timeout,
+            keep_alive,
+            capture,
+            protocol,
+            limits,
             ..
         } = self;
+        let encoding = protocol.encoding();
 
         let (mut tx, mut rx) = ws.split().unwrap();
 
@@ -169,9 +236,11 @@ where
         let reg = registration_task(attach_rx, incoming_tx, outgoing_tx.clone(), combined_stop)
             .instrument(info_span!("Websocket coordination task."));
 
-        let input = text_frame_stream(&mut rx);
+        let input = frame_stream(&mut rx, keep_alive.map(|ka| ka.timeout), encoding);
 
         let mut incoming = IncomingTask::new(id);
+        incoming.capture.clone_from(&capture);
+        incoming.limits = limits;
 
         let in_task = incoming
             .run(
@@ -183,9 +252,18 @@ where
             )
             .instrument(info_span!("Websocket incoming task", id = %id));
 
-        let mut outgoing = OutgoingTask::default();
+        let mut outgoing = OutgoingTask {
+            capture,
+            encoding,
+            ..Default::default()
+        };
         let out_task = outgoing
-            .run(stop_signal, &mut tx, outgoing_rx)
+            .run(
+                stop_signal,
+                &mut tx,
+                outgoing_rx,
+                keep_alive.map(|ka| ka.interval),
+            )
             .instrument(info_span!("Websocket outgoing task"));
 
         let (_, result) = join(reg, await_io_tasks(in_task, out_task, kill_switch_tx)).await;
@@ -203,10 +281,21 @@ where
                 CloseCode::Protocol,
                 Some(EXPECTED_STR.to_string()),
             )),
-            Err(InputError::InvalidEnvelope(_)) => Some(CloseReason::new(
-                CloseCode::Protocol,
-                Some(BAD_WARP_ENV.to_string()),
-            )),
+            Err(InputError::InvalidEnvelope(_) | InputError::InvalidBinaryEnvelope(_)) => Some(
+                CloseReason::new(CloseCode::Protocol, Some(BAD_WARP_ENV.to_string())),
+            ),
+            Err(InputError::LimitExceeded(error)) => {
+                let code = if matches!(error, LimitExceeded::EnvelopeTooLarge { .. }) {
+                    CloseCode::Overflow
+                } else {
+                    CloseCode::Policy
+                };
+                Some(CloseReason::new(code, Some(error.to_string())))
+            }
+            Err(InputError::TimedOut(period)) => {
+                warn!(id = ?id, period = ?period, "The peer stopped responding; dropping the connection.");
+                None
+            }
             _ => None, //Closed remotely or failed.
         };
         if let Some(reason) = close_reason {
@@ -224,48 +313,92 @@ where
     }
 }
 
-// Converts a websocket reader into a stream of text frames.
-fn text_frame_stream<S, E>(
+/// A frame containing a Warp envelope, received from the peer.
+#[derive(Debug)]
+enum WarpFrame {
+    Text(BytesStr),
+    Binary(Bytes),
+}
+
+impl From<BytesStr> for WarpFrame {
+    fn from(value: BytesStr) -> Self {
+        WarpFrame::Text(value)
+    }
+}
+
+impl WarpFrame {
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            WarpFrame::Text(text) => text.as_str().as_bytes(),
+            WarpFrame::Binary(bytes) => bytes.as_ref(),
+        }
+    }
+}
+
+// Converts a websocket reader into a stream of frames. Binary frames are only permitted if the
+// binary encoding was negotiated for the connection (text frames are always permitted). If a
+// timeout is provided, the stream will fail if no frames of any kind are received within that
+// period.
+fn frame_stream<S, E>(
     rx: &mut ratchet::Receiver<S, E>,
-) -> impl Stream<Item = Result<BytesStr, InputError>> + '_
+    timeout: Option<Duration>,
+    encoding: WarpEncoding,
+) -> impl Stream<Item = Result<WarpFrame, InputError>> + '_
 where
     S: WebSocketStream,
     E: ExtensionDecoder,
 {
-    unfold((Some(rx), BytesMut::new()), |(rx, mut buffer)| async move {
-        if let Some(rx) = rx {
-            match rx.read(&mut buffer).await {
-                Ok(Message::Binary) => {
-                    let item = Some(Err(InputError::BinaryFrame));
-                    Some((item, (None, buffer)))
-                }
-                Ok(Message::Text) => {
-                    let bytes = buffer.split().freeze();
-                    match BytesStr::try_from(bytes) {
-                        Ok(string) => {
-                            let item = Some(Ok(string));
-                            Some((item, (Some(rx), buffer)))
+    unfold(
+        (Some(rx), BytesMut::new()),
+        move |(rx, mut buffer)| async move {
+            if let Some(rx) = rx {
+                let result = match timeout {
+                    Some(t) => match tokio::time::timeout(t, rx.read(&mut buffer)).await {
+                        Ok(result) => result,
+                        Err(_) => {
+                            let item = Some(Err(InputError::TimedOut(t)));
+                            return Some((item, (None, buffer)));
                         }
-                        Err(e) => {
-                            let item = Some(Err(InputError::BadUtf8(e)));
-                            Some((item, (None, buffer)))
+                    },
+                    None => rx.read(&mut buffer).await,
+                };
+                match result {
+                    Ok(Message::Binary) if encoding == WarpEncoding::Binary => {
+                        let item = Some(Ok(WarpFrame::Binary(buffer.split().freeze())));
+                        Some((item, (Some(rx), buffer)))
+                    }
+                    Ok(Message::Binary) => {
+                        let item = Some(Err(InputError::BinaryFrame));
+                        Some((item, (None, buffer)))
+                    }
+                    Ok(Message::Text) => {
+                        let bytes = buffer.split().freeze();
+                        match BytesStr::try_from(bytes) {
+                            Ok(string) => {
+                                let item = Some(Ok(WarpFrame::Text(string)));
+                                Some((item, (Some(rx), buffer)))
+                            }
+                            Err(e) => {
+                                let item = Some(Err(InputError::BadUtf8(e)));
+                                Some((item, (None, buffer)))
+                            }
                         }
                     }
+                    Ok(Message::Close(reason)) => {
+                        let item = Some(Err(InputError::Closed(reason)));
+                        Some((item, (None, buffer)))
+                    }
+                    Err(e) => {
+                        let item = Some(Err(InputError::WsError(e)));
+                        Some((item, (None, buffer)))
+                    }
+                    _ => Some((None, (Some(rx), buffer))),
                 }
-                Ok(Message::Close(reason)) => {
-                    let item = Some(Err(InputError::Closed(reason)));
-                    Some((item, (None, buffer)))
-                }
-                Err(e) => {
-                    let item = Some(Err(InputError::WsError(e)));
-                    Some((item, (None, buffer)))
-                }
-                _ => Some((None, (Some(rx), buffer))),
+            } else {
+                None
             }
-        } else {
-            None
-        }
-    })
+        },
+    )
     .filter_map(ready)
 }
 
@@ -293,6 +426,8 @@ enum OutgoingTaskMessage {
         command_envelope: bool,
         error: AgentResolutionError,
     },
+    /// A command, sent with an ID, has been dispatched to its agent.
+    Dispatched(CommandDispatched),
 }
 
 // The registration task manages requests to attach new clients and serves as the coordinator between
@@ -395,6 +530,8 @@ type ResponseReader = FramedRead<ByteReader, RawResponseMessageDecoder>;
 struct OutgoingTask {
     clients: MultiReader<RequestReader>,
     agents: MultiReader<ResponseReader>,
+    capture: Option<FrameCapture>,
+    encoding: WarpEncoding,
 }
 
 impl OutgoingTask {
@@ -403,13 +540,24 @@ impl OutgoingTask {
         mut stop_signal: trigger::Receiver,
         output: &mut ratchet::Sender<S, E>,
         mut messages_rx: mpsc::Receiver<OutgoingTaskMessage>,
+        ping_interval: Option<Duration>,
     ) where
         S: WebSocketStream,
         E: ExtensionEncoder,
     {
-        let OutgoingTask { clients, agents } = self;
+        let OutgoingTask {
+            clients,
+            agents,
+            capture,
+            encoding,
+        } = self;
+        let encoding = *encoding;
         let mut buffer = BytesMut::new();
-        let mut recon_encoder = ReconEncoder;
+        let mut pings = ping_interval.map(|period| {
+            let mut timer = interval_at(Instant::now() + period, period);
+            timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            timer
+        });
 
         debug!("Outgoing task starting.");
 
@@ -423,6 +571,7 @@ impl OutgoingTask {
                     Some(message) => OutgoingEvent::Message(message),
                     None => break,
                 },
+                _ = next_ping(&mut pings) => OutgoingEvent::Ping,
                 Some(result) = clients.next(), if !clients.is_empty() => {
                     match result {
                         Ok(request) => OutgoingEvent::Request(request),
@@ -472,12 +621,25 @@ impl OutgoingTask {
                 }) => {
                     if !command_envelope {
                         debug!(lane = ?error, "Sending node/lane not found envelope.");
-                        buffer.clear();
-                        recon_encoder
-                            .encode(error, &mut buffer)
-                            .expect("Encoding a frame should be infallible.");
-                        debug_assert!(!buffer.is_empty());
-                        if let Err(error) = output.write(&buffer, PayloadType::Text).await {
+                        encode_envelope(encoding, error, &mut buffer);
+                        if let Err(error) =
+                            write_frame(output, &buffer, encoding, capture.as_ref()).await
+                        {
+                            error!(error = %error, "Writing to the websocket connection failed.");
+                            break;
+                        }
+                    }
+                }
+                OutgoingEvent::Message(OutgoingTaskMessage::NotFound {
+                    error: AgentResolutionError::NotStarted(error),
+                    command_envelope,
+                }) => {
+                    if !command_envelope {
+                        debug!(error = %error, "Sending node not started envelope.");
+                        encode_envelope(encoding, error, &mut buffer);
+                        if let Err(error) =
+                            write_frame(output, &buffer, encoding, capture.as_ref()).await
+                        {
                             error!(error = %error, "Writing to the websocket connection failed.");
                             break;
                         }
@@ -489,26 +651,58 @@ impl OutgoingTask {
                 }) => {
                     info!("Omitting unlinked message as the plane is stopping.");
                 }
-                OutgoingEvent::Request(req) => {
-                    trace!(envelope = ?req, "Sending request envelope.");
+                OutgoingEvent::Message(OutgoingTaskMessage::NotFound {
+                    error: AgentResolutionError::HostedByPeer(error),
+                    ..
+                }) => {
+                    // Links to agents that are hosted by a peer are proxied so this is only
+                    // produced for HTTP requests.
+                    debug!(error = %error, "Omitting unlinked message for an agent hosted by a peer.");
+                }
+                OutgoingEvent::Message(OutgoingTaskMessage::Dispatched(dispatched)) => {
+                    trace!(dispatched = ?dispatched, "Sending command dispatched envelope.");
+                    // Only text envelopes can carry a command ID so the acknowledgement is always
+                    // sent as text.
                     buffer.clear();
-                    recon_encoder
-                        .encode(req, &mut buffer)
+                    ReconEncoder
+                        .encode(dispatched, &mut buffer)
                         .expect("Encoding a frame should be infallible.");
-                    debug_assert!(!buffer.is_empty());
-                    if let Err(error) = output.write(&buffer, PayloadType::Text).await {
+                    if let Err(error) =
+                        write_frame(output, &buffer, WarpEncoding::Text, capture.as_ref()).await
+                    {
+                        error!(error = %error, "Writing to the websocket connection failed.");
+                        break;
+                    }
+                }
+                OutgoingEvent::Ping => {
+                    if let Err(error) = output.write_ping(b"").await {
+                        error!(error = %error, "Writing to the websocket connection failed.");
+                        break;
+                    }
+                }
+                OutgoingEvent::Request(req) => {
+                    trace!(envelope = ?req, "Sending request envelope.");
+                    encode_envelope(encoding, req, &mut buffer);
+                    if let Err(error) =
+                        write_frame(output, &buffer, encoding, capture.as_ref()).await
+                    {
                         error!(error = %error, "Writing to the websocket connection failed.");
                         break;
                     }
                 }
                 OutgoingEvent::Response(res) => {
-                    trace!(envelope = ?res, "Sending response envelope.");
-                    buffer.clear();
-                    recon_encoder
-                        .encode(res, &mut buffer)
-                        .expect("Encoding a frame should be infallible.");
-                    debug_assert!(!buffer.is_empty());
-                    if let Err(error) = output.write(&buffer, PayloadType::Text).await {
+                    // Responses are tagged with the correlation ID of the envelope that caused
+                    // them so that they can be associated with it in the logs.
+                    match res.correlation_id {
+                        Some(id) => {
+                            trace!(correlation_id = %id, envelope = ?res, "Sending response envelope.")
+                        }
+                        None => trace!(envelope = ?res, "Sending response envelope."),
+                    }
+                    encode_envelope(encoding, res, &mut buffer);
+                    if let Err(error) =
+                        write_frame(output, &buffer, encoding, capture.as_ref()).await
+                    {
                         error!(error = %error, "Writing to the websocket connection failed.");
                         break;
                     }
@@ -519,11 +713,61 @@ impl OutgoingTask {
     }
 }
 
+// Encodes an envelope into the buffer (replacing its contents) with the negotiated encoding.
+fn encode_envelope<T>(encoding: WarpEncoding, item: T, buffer: &mut BytesMut)
+where
+    ReconEncoder: Encoder<T, Error = std::io::Error>,
+    BinaryEncoder: Encoder<T, Error = std::io::Error>,
+{
+    buffer.clear();
+    match encoding {
+        WarpEncoding::Text => ReconEncoder.encode(item, buffer),
+        WarpEncoding::Binary => BinaryEncoder.encode(item, buffer),
+    }
+    .expect("Encoding a frame should be infallible.");
+    debug_assert!(!buffer.is_empty());
+}
+
+// Writes a frame to the socket, recording it if a capture is enabled. Envelopes are written as
+// text frames unless binary envelopes were negotiated.
+async fn write_frame<S, E>(
+    output: &mut ratchet::Sender<S, E>,
+    buffer: &BytesMut,
+    encoding: WarpEncoding,
+    capture: Option<&FrameCapture>,
+) -> Result<(), ratchet::Error>
+where
+    S: WebSocketStream,
+    E: ExtensionEncoder,
+{
+    let payload_type = match encoding {
+        WarpEncoding::Text => PayloadType::Text,
+        WarpEncoding::Binary => PayloadType::Binary,
+    };
+    output.write(buffer, payload_type).await?;
+    if let Some(capture) = capture {
+        capture.record(CaptureDirection::Outgoing, buffer);
+    }
+    Ok(())
+}
+
+// Waits for the next tick of the keep-alive timer (never completing if there is no timer).
+async fn next_ping(pings: &mut Option<Interval>) {
+    match pings {
+        Some(timer) => {
+            timer.tick().await;
+        }
+        None => pending().await,
+    }
+}
+
 // The incoming task routes incoming envelopes to agents and downlinks.
 struct IncomingTask {
     id: Uuid,
     client_subscriptions: HashMap<Text, HashMap<Text, ResponseWriters>>,
     agent_routes: HashMap<Text, RequestWriter>,
+    capture: Option<FrameCapture>,
+    limits: Limits,
 }
 
 impl IncomingTask {
@@ -532,12 +776,14 @@ impl IncomingTask {
             id,
             client_subscriptions: Default::default(),
             agent_routes: Default::default(),
+            capture: None,
+            limits: Limits::default(),
         }
     }
 }
 
 impl IncomingTask {
-    async fn run<In>(
+    async fn run<In, F>(
         &mut self,
         mut stop_signal: trigger::Receiver,
         input: In,
@@ -546,17 +792,20 @@ impl IncomingTask {
         outgoing_tx: mpsc::Sender<OutgoingTaskMessage>,
     ) -> Result<(), InputError>
     where
-        In: Stream<Item = Result<BytesStr, InputError>>,
+        In: Stream<Item = Result<F, InputError>>,
+        F: Into<WarpFrame>,
     {
         let IncomingTask {
             id,
             client_subscriptions,
             agent_routes,
+            capture,
+            limits,
         } = self;
         let mut input = pin!(input);
 
         loop {
-            let event: IncomingEvent<BytesStr> = tokio::select! {
+            let event: IncomingEvent<F> = tokio::select! {
                 biased;
                 _ = &mut stop_signal => break Ok(()),
                 maybe_request = attach_rx.recv() => {
@@ -591,19 +840,69 @@ impl IncomingTask {
                     done.trigger();
                 }
                 IncomingEvent::Message(Ok(frame)) => {
-                    trace!(frame = frame.as_str(), "Handling incoming frame.");
-                    let body = frame.as_ref();
-                    match peel_envelope_header_str(body) {
-                        Ok(envelope) => match interpret_envelope(*id, envelope) {
-                            Some(Either::Left(request)) => match &find_tx {
+                    let frame: WarpFrame = frame.into();
+                    trace!(frame = ?frame, "Handling incoming frame.");
+                    if let Some(capture) = capture {
+                        capture.record(CaptureDirection::Incoming, frame.as_bytes());
+                    }
+                    if let Err(error) = check_frame_limits(limits, &frame) {
+                        error!(error = %error, "Received a frame that violates the configured limits.");
+                        break Err(InputError::LimitExceeded(error));
+                    }
+                    // If a command has an ID, the remote expects to be told when it is dispatched.
+                    let mut dispatch_id = None;
+                    let interpreted = match &frame {
+                        WarpFrame::Text(text) => match peel_envelope_header_str(text.as_str()) {
+                            Ok(envelope) => {
+                                if let RawEnvelope::Command { id, .. } = &envelope {
+                                    dispatch_id = *id;
+                                }
+                                interpret_envelope(*id, envelope)
+                            }
+                            Err(error) => {
+                                error!(
+                                    frame = text.as_str(),
+                                    "Received a frame that does not contain a valid Warp envelope."
+                                );
+                                break Err(InputError::InvalidEnvelope(error));
+                            }
+                        },
+                        WarpFrame::Binary(bytes) => match read_binary_envelope(bytes.as_ref()) {
+                            Ok(envelope) => Some(interpret_binary_envelope(*id, envelope)),
+                            Err(error) => {
+                                error!(
+                                    error = %error,
+                                    "Received a frame that does not contain a valid binary Warp envelope."
+                                );
+                                break Err(InputError::InvalidBinaryEnvelope(error));
+                            }
+                        },
+                    };
+                    match interpreted {
+                        Some(Either::Left(request)) => {
+                            // Tag the envelope so that it can be followed through the tasks of the
+                            // agent runtime.
+                            let correlation_id = CorrelationId::generate();
+                            trace!(
+                                correlation_id = %correlation_id,
+                                node = request.path.node.as_ref(),
+                                lane = request.path.lane.as_ref(),
+                                "Assigned a correlation ID to an incoming envelope."
+                            );
+                            let request = request.with_correlation_id(correlation_id);
+                            // Failures to find the agent are not reported for commands unless the
+                            // remote is waiting for an acknowledgement.
+                            let command_envelope =
+                                request.envelope.is_command() && dispatch_id.is_none();
+                            match &find_tx {
                                 Some(find_tx) => {
                                     let node = request.path.node.as_ref();
 
-                                    let dispatched = if let Some(writer) =
+                                    let mut dispatched = if let Some(writer) =
                                         agent_routes.get_mut(node)
                                     {
                                         if let Err(error) = writer.send(&request).await {
-                                            debug!(error = %error, "Forwarding envelope to agent route failed.");
+                                            debug!(error = %error, correlation_id = %correlation_id, "Forwarding envelope to agent route failed.");
                                             agent_routes.remove(node);
                                             false
                                         } else {
@@ -617,7 +916,7 @@ impl IncomingTask {
                                             *id,
                                             Text::new(node),
                                             Text::new(request.path.lane.as_ref()),
-                                            request.envelope.is_command(),
+                                            command_envelope,
                                             find_tx,
                                             &outgoing_tx,
                                         )
@@ -628,8 +927,10 @@ impl IncomingTask {
                                                     .entry(Text::new(node))
                                                     .or_insert_with_key(move |_| writer);
                                                 if let Err(error) = writer.send(&request).await {
-                                                    error!(error = %error, "Envelope not dispatched as agent stopped immediately.");
+                                                    error!(error = %error, correlation_id = %correlation_id, "Envelope not dispatched as agent stopped immediately.");
                                                     agent_routes.remove(node);
+                                                } else {
+                                                    dispatched = true;
                                                 }
                                             }
                                             Err(_) => {
@@ -638,12 +939,24 @@ impl IncomingTask {
                                             _ => {}
                                         }
                                     }
+                                    if let (true, Some(dispatch_id)) = (dispatched, dispatch_id) {
+                                        let RelativeAddress { node, lane } = &request.path;
+                                        let message =
+                                            OutgoingTaskMessage::Dispatched(CommandDispatched {
+                                                node: Text::new(node),
+                                                lane: Text::new(lane),
+                                                id: dispatch_id,
+                                            });
+                                        if outgoing_tx.send(message).await.is_err() {
+                                            break Ok(());
+                                        }
+                                    }
                                 }
                                 None => {
                                     let RequestMessage { path, .. } = request;
                                     if outgoing_tx
                                         .send(OutgoingTaskMessage::NotFound {
-                                            command_envelope: request.envelope.is_command(),
+                                            command_envelope,
                                             error: AgentResolutionError::NotFound(NoSuchAgent {
                                                 node: path.node.into(),
                                                 lane: Some(path.lane.into()),
@@ -655,43 +968,35 @@ impl IncomingTask {
                                         break Ok(());
                                     }
                                 }
-                            },
-                            Some(Either::Right(response)) => {
-                                let RelativeAddress { node, lane } = response.path.clone();
-                                if let Some(node_map) = client_subscriptions.get_mut(node.as_ref())
-                                {
-                                    if let Some(senders) = node_map.get_mut(lane.as_ref()) {
-                                        if !send_response(senders, response).await {
-                                            node_map.remove(lane.as_ref());
-                                            if node_map.is_empty() {
-                                                client_subscriptions.remove(node.as_ref());
-                                            }
+                            }
+                        }
+                        Some(Either::Right(response)) => {
+                            let RelativeAddress { node, lane } = response.path.clone();
+                            if let Some(node_map) = client_subscriptions.get_mut(node.as_ref()) {
+                                if let Some(senders) = node_map.get_mut(lane.as_ref()) {
+                                    if !send_response(senders, response).await {
+                                        node_map.remove(lane.as_ref());
+                                        if node_map.is_empty() {
+                                            client_subscriptions.remove(node.as_ref());
                                         }
-                                    } else {
-                                        info!(
-                                            node = node.as_ref(),
-                                            lane = lane.as_ref(),
-                                            "Envelope received for downlink that does not exist."
-                                        );
-                                    };
+                                    }
                                 } else {
                                     info!(
                                         node = node.as_ref(),
                                         lane = lane.as_ref(),
                                         "Envelope received for downlink that does not exist."
                                     );
-                                }
+                                };
+                            } else {
+                                info!(
+                                    node = node.as_ref(),
+                                    lane = lane.as_ref(),
+                                    "Envelope received for downlink that does not exist."
+                                );
                             }
-                            _ => {
-                                warn!("Auth and Deauth no yet implemented.");
-                            }
-                        },
-                        Err(error) => {
-                            error!(
-                                frame = frame.as_str(),
-                                "Received a frame that does not contain a valid Warp envelope."
-                            );
-                            break Err(InputError::InvalidEnvelope(error));
+                        }
+                        _ => {
+                            warn!("Auth and Deauth no yet implemented.");
                         }
                     }
                 }
@@ -703,6 +1008,15 @@ impl IncomingTask {
     }
 }
 
+fn check_frame_limits(limits: &Limits, frame: &WarpFrame) -> Result<(), LimitExceeded> {
+    let bytes = frame.as_bytes();
+    limits.check_envelope_size(bytes.len())?;
+    match frame {
+        WarpFrame::Text(_) => limits.check_recon(bytes),
+        WarpFrame::Binary(_) => Ok(()),
+    }
+}
+
 // If a message arrives for an unknown agent, attempt to resolve it and open a link.
 async fn connect_agent_route(
     source: Uuid,
@@ -791,6 +1105,20 @@ async fn send_response(senders: &mut ResponseWriters, message: RawResponse<'_>)
 type RawRequest<'a> = RequestMessage<Cow<'a, str>, &'a str>;
 type RawResponse<'a> = ResponseMessage<Cow<'a, str>, &'a str, &'a str>;
 
+/// Interpret a Warp envelope, in Recon, as a request for an agent. If the envelope is valid but is
+/// not a request (for example, it is an event for a downlink), nothing is returned.
+///
+/// # Arguments
+/// * `origin` - The ID of the remote that sent the envelope.
+/// * `envelope` - The envelope.
+pub fn parse_request_envelope(
+    origin: Uuid,
+    envelope: &str,
+) -> Result<Option<RequestMessage<Cow<'_, str>, &str>>, MessageExtractError> {
+    let raw = peel_envelope_header_str(envelope)?;
+    Ok(interpret_envelope(origin, raw).and_then(Either::left))
+}
+
 // Determine whether an envelope is for an agent or a downlink.
 fn interpret_envelope(
     id: Uuid,
@@ -798,14 +1126,42 @@ fn interpret_envelope(
 ) -> Option<Either<RawRequest<'_>, RawResponse<'_>>> {
     match envelope {
         RawEnvelope::Link {
-            node_uri, lane_uri, ..
-        } => Some(Either::Left(RequestMessage::link(
+            node_uri,
+            lane_uri,
+            rate,
+            prio,
+            ..
+        } => Some(Either::Left(RequestMessage::link_with_params(
             id,
             RelativeAddress::new(node_uri, lane_uri),
+            LinkParams::new(rate, prio),
         ))),
         RawEnvelope::Sync {
+            node_uri,
+            lane_uri,
+            rate,
+            prio,
+            window,
+            from,
+            to,
+            body,
+        } => {
+            let path = RelativeAddress::new(node_uri, lane_uri);
+            let params = LinkParams::new(rate, prio)
+                .with_since(sync_version(&body))
+                .with_window(window);
+            let message = if from.is_some() || to.is_some() {
+                let range = KeyRange::new(from, to)
+                    .map(|repr: &str| Bytes::copy_from_slice(repr.as_bytes()));
+                RequestMessage::sync_range(id, path, params, range)
+            } else {
+                RequestMessage::sync_with_params(id, path, params)
+            };
+            Some(Either::Left(message))
+        }
+        RawEnvelope::Ack {
             node_uri, lane_uri, ..
-        } => Some(Either::Left(RequestMessage::sync(
+        } => Some(Either::Left(RequestMessage::ack(
             id,
             RelativeAddress::new(node_uri, lane_uri),
         ))),
@@ -819,6 +1175,7 @@ fn interpret_envelope(
             node_uri,
             lane_uri,
             body,
+            ..
         } => Some(Either::Left(RequestMessage::command(
             id,
             RelativeAddress::new(node_uri, lane_uri),
@@ -831,17 +1188,27 @@ fn interpret_envelope(
             RelativeAddress::new(node_uri, lane_uri),
         ))),
         RawEnvelope::Synced {
-            node_uri, lane_uri, ..
-        } => Some(Either::Right(ResponseMessage::synced(
-            id,
-            RelativeAddress::new(node_uri, lane_uri),
-        ))),
+            node_uri,
+            lane_uri,
+            body,
+        } => {
+            let path = RelativeAddress::new(node_uri, lane_uri);
+            if body.trim().is_empty() {
+                Some(Either::Right(ResponseMessage::synced(id, path)))
+            } else {
+                Some(Either::Right(ResponseMessage::synced_with(id, path, *body)))
+            }
+        }
         RawEnvelope::Unlinked {
             node_uri,
             lane_uri,
             body,
         } => {
-            let unlinked_body = if body.is_empty() { Some(*body) } else { None };
+            let unlinked_body = if body.trim().is_empty() {
+                None
+            } else {
+                Some(*body)
+            };
             Some(Either::Right(ResponseMessage::unlinked(
                 id,
                 RelativeAddress::new(node_uri, lane_uri),
@@ -849,10 +1216,23 @@ fn interpret_envelope(
             )))
         }
         RawEnvelope::Event {
+            node_uri,
+            lane_uri,
+            origin,
+            body,
+        } => {
+            let message =
+                ResponseMessage::event(id, RelativeAddress::new(node_uri, lane_uri), *body);
+            Some(Either::Right(match origin {
+                Some(origin) => message.with_command_origin(origin),
+                None => message,
+            }))
+        }
+        RawEnvelope::Advisory {
             node_uri,
             lane_uri,
             body,
-        } => Some(Either::Right(ResponseMessage::event(
+        } => Some(Either::Right(ResponseMessage::advisory(
             id,
             RelativeAddress::new(node_uri, lane_uri),
             *body,
@@ -861,6 +1241,52 @@ fn interpret_envelope(
     }
 }
 
+// The body of a sync envelope can hold the version of the state of a map lane that the remote already
+// has. A body that is not a valid version is ignored, resulting in a full sync.
+fn sync_version(body: &str) -> Option<SyncVersion> {
+    if body.trim().is_empty() {
+        None
+    } else {
+        parse_recognize::<SyncVersion>(body, false).ok()
+    }
+}
+
+// Determine whether a binary envelope is for an agent or a downlink.
+fn interpret_binary_envelope(
+    id: Uuid,
+    envelope: BinaryEnvelope<'_>,
+) -> Either<RawRequest<'_>, RawResponse<'_>> {
+    let BinaryEnvelope {
+        kind,
+        node,
+        lane,
+        body,
+    } = envelope;
+    let path = RelativeAddress::new(Cow::Borrowed(node), Cow::Borrowed(lane));
+    match kind {
+        BinaryEnvelopeKind::Link => Either::Left(RequestMessage::link(id, path)),
+        BinaryEnvelopeKind::Sync => Either::Left(RequestMessage::sync_with_params(
+            id,
+            path,
+            LinkParams::default().with_since(sync_version(body)),
+        )),
+        BinaryEnvelopeKind::Ack => Either::Left(RequestMessage::ack(id, path)),
+        BinaryEnvelopeKind::Unlink => Either::Left(RequestMessage::unlink(id, path)),
+        BinaryEnvelopeKind::Command => Either::Left(RequestMessage::command(id, path, body)),
+        BinaryEnvelopeKind::Linked => Either::Right(ResponseMessage::linked(id, path)),
+        BinaryEnvelopeKind::Synced if !body.is_empty() => {
+            Either::Right(ResponseMessage::synced_with(id, path, body))
+        }
+        BinaryEnvelopeKind::Synced => Either::Right(ResponseMessage::synced(id, path)),
+        BinaryEnvelopeKind::Unlinked => {
+            let unlinked_body = if body.is_empty() { None } else { Some(body) };
+            Either::Right(ResponseMessage::unlinked(id, path, unlinked_body))
+        }
+        BinaryEnvelopeKind::Event => Either::Right(ResponseMessage::event(id, path, body)),
+        BinaryEnvelopeKind::Advisory => Either::Right(ResponseMessage::advisory(id, path, body)),
+    }
+}
+
 type ResponseWriter = FramedWrite<ByteWriter, RawResponseMessageEncoder>;
 type RequestWriter = FramedWrite<ByteWriter, RawRequestMessageEncoder>;
 
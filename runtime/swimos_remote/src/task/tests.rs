@@ -14,7 +14,8 @@
 
 use std::{num::NonZeroUsize, time::Duration};
 
-use bytes::BytesMut;
+use bytes::{BufMut, BytesMut};
+use either::Either;
 use futures::{
     future::{join, join3, join4},
     Future, SinkExt, StreamExt,
@@ -23,7 +24,11 @@ use ratchet::{
     CloseCode, CloseReason, Message, NegotiatedExtension, NoExt, NoExtDecoder, Receiver, Role,
     WebSocket, WebSocketConfig,
 };
-use swimos_api::address::RelativeAddress;
+use swimos_api::{
+    address::RelativeAddress,
+    agent::SyncVersion,
+    limits::{LimitExceeded, Limits},
+};
 use swimos_messages::{
     protocol::{
         BytesRequestMessage, BytesResponseMessage, Notification, Operation,
@@ -31,8 +36,10 @@ use swimos_messages::{
         RawResponseMessageEncoder, RequestMessage, ResponseMessage,
     },
     remote_protocol::{
-        AgentResolutionError, AttachClient, FindNode, NoSuchAgent, NodeConnectionRequest,
+        AgentNotStarted, AgentResolutionError, AttachClient, FindNode, NoSuchAgent,
+        NodeConnectionRequest,
     },
+    warp::peel_envelope_header_str,
 };
 use swimos_model::Text;
 use swimos_utilities::{
@@ -48,9 +55,15 @@ use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::codec::{FramedRead, FramedWrite};
 use uuid::Uuid;
 
+use crate::capture::{CaptureDirection, FrameCapture};
 use crate::task::OutgoingKind;
 
-use super::{InputError, OutgoingTaskMessage, RegisterIncoming};
+use crate::websocket::{WarpEncoding, WarpProtocol, WarpVersions, WARP_BINARY};
+
+use super::envelopes::{
+    read_binary_envelope, BinaryEnvelope, BinaryEnvelopeKind, CommandDispatched,
+};
+use super::{interpret_envelope, InputError, OutgoingTaskMessage, RegisterIncoming, WarpFrame};
 
 const ID: Uuid = Uuid::from_u128(1484);
 const CHAN_SIZE: NonZeroUsize = non_zero_usize!(8);
@@ -191,6 +204,17 @@ impl IncomingTestContext {
 }
 
 async fn test_incoming_task<F, Fut>(test_case: F) -> (Result<(), InputError>, Fut::Output)
+where
+    F: FnOnce(IncomingTestContext) -> Fut,
+    Fut: Future,
+{
+    test_incoming_task_with_limits(Limits::default(), test_case).await
+}
+
+async fn test_incoming_task_with_limits<F, Fut>(
+    limits: Limits,
+    test_case: F,
+) -> (Result<(), InputError>, Fut::Output)
 where
     F: FnOnce(IncomingTestContext) -> Fut,
     Fut: Future,
@@ -214,6 +238,7 @@ where
     };
 
     let mut incoming = super::IncomingTask::new(ID);
+    incoming.limits = limits;
 
     let incoming_task = incoming.run(
         stop_rx,
@@ -360,6 +385,7 @@ async fn incoming_route_downlink_env() {
             origin,
             path,
             envelope,
+            ..
         } = dl_rx.recv().await;
 
         assert_eq!(origin, ID);
@@ -395,6 +421,7 @@ async fn check_env(n: i32, agent_rx: &mut AgentReader) {
         origin,
         path,
         envelope,
+        ..
     } = agent_rx.recv().await;
 
     assert_eq!(origin, ID);
@@ -584,6 +611,81 @@ async fn incoming_route_not_found_env() {
     assert!(task_result.is_ok());
 }
 
+#[tokio::test]
+async fn incoming_acknowledge_dispatched_command() {
+    let (task_result, _) = test_incoming_task(|mut context| async move {
+        let mut agent_rx = AgentReader::new(context.take_agent_reader());
+
+        let IncomingTestContext {
+            in_tx, outgoing_rx, ..
+        } = &mut context;
+
+        let env = format!("@command(node:\"{}\",lane:{},id:3) {{a:8}}", NODE, LANE);
+        in_tx
+            .send(Ok(BytesStr::from(env)))
+            .await
+            .expect("Task stopped.");
+
+        match outgoing_rx.recv().await {
+            Some(OutgoingTaskMessage::RegisterOutgoing {
+                kind: OutgoingKind::Server,
+                done,
+                ..
+            }) => {
+                assert!(done.send(Ok(())).is_ok());
+            }
+            ow => panic!("Unexpected registration: {:?}", ow),
+        }
+
+        check_env(8, &mut agent_rx).await;
+
+        match outgoing_rx.recv().await {
+            Some(OutgoingTaskMessage::Dispatched(CommandDispatched { node, lane, id })) => {
+                assert_eq!(node, NODE);
+                assert_eq!(lane, LANE);
+                assert_eq!(id, 3);
+            }
+            ow => panic!("Unexpected message: {:?}", ow),
+        }
+
+        context.stop();
+        context
+    })
+    .await;
+    assert!(task_result.is_ok());
+}
+
+#[tokio::test]
+async fn incoming_report_undispatched_command() {
+    let (task_result, _) = test_incoming_task(|mut context| async move {
+        let IncomingTestContext {
+            in_tx, outgoing_rx, ..
+        } = &mut context;
+
+        let env = format!("@command(node:\"{}\",lane:{},id:4) {{a:2}}", OTHER, LANE);
+        in_tx
+            .send(Ok(BytesStr::from(env)))
+            .await
+            .expect("Task stopped.");
+
+        match outgoing_rx.recv().await {
+            Some(OutgoingTaskMessage::NotFound {
+                command_envelope,
+                error: AgentResolutionError::NotFound(NoSuchAgent { node, .. }),
+            }) => {
+                assert!(!command_envelope);
+                assert_eq!(node, OTHER);
+            }
+            ow => panic!("Unexpected message: {:?}", ow),
+        }
+
+        context.stop();
+        context
+    })
+    .await;
+    assert!(task_result.is_ok());
+}
+
 #[tokio::test]
 async fn incoming_terminates_on_input_error() {
     let (task_result, _context) = test_incoming_task(|mut context| async move {
@@ -600,6 +702,74 @@ async fn incoming_terminates_on_input_error() {
     assert!(matches!(task_result, Err(InputError::BinaryFrame)));
 }
 
+const SMALL_LIMITS: Limits = Limits {
+    max_envelope_size: Some(non_zero_usize!(64)),
+    max_map_batch_entries: None,
+    max_recon_depth: Some(non_zero_usize!(3)),
+    max_attr_count: Some(non_zero_usize!(4)),
+};
+
+async fn incoming_limit_exceeded(envelope: String) -> Result<(), InputError> {
+    let (task_result, _context) =
+        test_incoming_task_with_limits(SMALL_LIMITS, |mut context| async move {
+            let IncomingTestContext { in_tx, .. } = &mut context;
+
+            in_tx
+                .send(Ok(BytesStr::from(envelope)))
+                .await
+                .expect("Task stopped.");
+
+            context
+        })
+        .await;
+    task_result
+}
+
+#[tokio::test]
+async fn incoming_terminates_on_oversized_envelope() {
+    let env = format!(
+        "@command(node:\"{}\",lane:{}) \"{}\"",
+        NODE,
+        LANE,
+        "a".repeat(64)
+    );
+    let result = incoming_limit_exceeded(env).await;
+    assert!(matches!(
+        result,
+        Err(InputError::LimitExceeded(LimitExceeded::EnvelopeTooLarge {
+            limit: 64,
+            ..
+        }))
+    ));
+}
+
+#[tokio::test]
+async fn incoming_terminates_on_deep_envelope() {
+    let env = format!(
+        "@command(node:\"{}\",lane:{}) {{{{{{{{1}}}}}}}}",
+        NODE, LANE
+    );
+    let result = incoming_limit_exceeded(env).await;
+    assert!(matches!(
+        result,
+        Err(InputError::LimitExceeded(LimitExceeded::TooDeep {
+            limit: 3
+        }))
+    ));
+}
+
+#[tokio::test]
+async fn incoming_terminates_on_too_many_attrs() {
+    let env = format!("@command(node:\"{}\",lane:{}) @a @b @c @d", NODE, LANE);
+    let result = incoming_limit_exceeded(env).await;
+    assert!(matches!(
+        result,
+        Err(InputError::LimitExceeded(LimitExceeded::TooManyAttrs {
+            limit: 4
+        }))
+    ));
+}
+
 fn make_fake_ws() -> (
     WebSocket<DuplexStream, NoExt>,
     WebSocket<DuplexStream, NoExt>,
@@ -624,12 +794,19 @@ fn make_fake_ws() -> (
     (server, client)
 }
 
+fn frame_text(frame: WarpFrame) -> String {
+    match frame {
+        WarpFrame::Text(body) => body.to_string(),
+        ow => panic!("Unexpected frame: {:?}", ow),
+    }
+}
+
 #[tokio::test]
 async fn messages_from_ws() {
     let (server, mut client) = make_fake_ws();
 
     let (_server_tx, mut server_rx) = server.split().expect("Split failed.");
-    let stream = super::text_frame_stream(&mut server_rx);
+    let stream = super::frame_stream(&mut server_rx, None, WarpEncoding::Text);
 
     client.write_text("first").await.expect("Send failed.");
     client.write_text("second").await.expect("Send failed.");
@@ -640,7 +817,7 @@ async fn messages_from_ws() {
         stream
             .take(3)
             .map(|r| r.expect("Stream failed."))
-            .map(|body| body.to_string())
+            .map(frame_text)
             .collect(),
     )
     .await
@@ -661,7 +838,7 @@ async fn ws_close() {
     let (server, mut client) = make_fake_ws();
 
     let (_server_tx, mut server_rx) = server.split().expect("Split failed.");
-    let stream = super::text_frame_stream(&mut server_rx);
+    let stream = super::frame_stream(&mut server_rx, None, WarpEncoding::Text);
 
     let close_reason = CloseReason::new(CloseCode::GoingAway, Some("gone".to_string()));
     client
@@ -686,7 +863,7 @@ async fn error_on_binary_frame() {
     let (server, mut client) = make_fake_ws();
 
     let (_server_tx, mut server_rx) = server.split().expect("Split failed.");
-    let stream = super::text_frame_stream(&mut server_rx);
+    let stream = super::frame_stream(&mut server_rx, None, WarpEncoding::Text);
 
     client
         .write_binary(&[0, 1, 2, 3])
@@ -700,12 +877,41 @@ async fn error_on_binary_frame() {
     assert!(matches!(frames.as_slice(), [Err(InputError::BinaryFrame)]));
 }
 
+#[tokio::test]
+async fn binary_frames_from_ws() {
+    let (server, mut client) = make_fake_ws();
+
+    let (_server_tx, mut server_rx) = server.split().expect("Split failed.");
+    let stream = super::frame_stream(&mut server_rx, None, WarpEncoding::Binary);
+
+    client
+        .write_binary(&[0, 1, 2, 3])
+        .await
+        .expect("Send failed.");
+    client.write_text("text").await.expect("Send failed.");
+
+    let frames: Vec<_> = tokio::time::timeout(
+        TEST_TIMEOUT,
+        stream.take(2).map(|r| r.expect("Stream failed.")).collect(),
+    )
+    .await
+    .expect("Timed out.");
+
+    match frames.as_slice() {
+        [WarpFrame::Binary(first), WarpFrame::Text(second)] => {
+            assert_eq!(first.as_ref(), &[0, 1, 2, 3]);
+            assert_eq!(second.as_str(), "text");
+        }
+        ow => panic!("Unexpected frames: {:?}", ow),
+    }
+}
+
 #[tokio::test]
 async fn ignore_ping_pong() {
     let (server, mut client) = make_fake_ws();
 
     let (_server_tx, mut server_rx) = server.split().expect("Split failed.");
-    let stream = super::text_frame_stream(&mut server_rx);
+    let stream = super::frame_stream(&mut server_rx, None, WarpEncoding::Text);
 
     client.write_text("first").await.expect("Send failed.");
     client.write_ping("ping!").await.expect("Send failed.");
@@ -717,7 +923,7 @@ async fn ignore_ping_pong() {
         stream
             .take(3)
             .map(|r| r.expect("Stream failed."))
-            .map(|body| body.to_string())
+            .map(frame_text)
             .collect(),
     )
     .await
@@ -738,6 +944,29 @@ async fn ignore_ping_pong() {
     );
 }
 
+#[tokio::test]
+async fn dead_peer_times_out() {
+    let (server, mut client) = make_fake_ws();
+
+    let (_server_tx, mut server_rx) = server.split().expect("Split failed.");
+    let period = Duration::from_millis(50);
+    let stream = super::frame_stream(&mut server_rx, Some(period), WarpEncoding::Text);
+
+    client.write_text("first").await.expect("Send failed.");
+
+    let frames: Vec<_> = tokio::time::timeout(TEST_TIMEOUT, stream.collect())
+        .await
+        .expect("Timed out.");
+
+    match frames.as_slice() {
+        [Ok(first), Err(InputError::TimedOut(t))] => {
+            assert_eq!(first.as_bytes(), b"first");
+            assert_eq!(*t, period);
+        }
+        ow => panic!("Unexpected frames: {:?}", ow),
+    }
+}
+
 struct OutgoingTestContext {
     stop_tx: Option<trigger::Sender>,
     outgoing_tx: mpsc::Sender<OutgoingTaskMessage>,
@@ -754,6 +983,17 @@ impl OutgoingTestContext {
 }
 
 async fn test_outgoing_task<F, Fut>(test_case: F) -> Fut::Output
+where
+    F: FnOnce(OutgoingTestContext) -> Fut,
+    Fut: Future,
+{
+    test_outgoing_task_with_pings(None, test_case).await
+}
+
+async fn test_outgoing_task_with_pings<F, Fut>(
+    ping_interval: Option<Duration>,
+    test_case: F,
+) -> Fut::Output
 where
     F: FnOnce(OutgoingTestContext) -> Fut,
     Fut: Future,
@@ -790,7 +1030,7 @@ where
         _server_rx: server_rx,
     };
 
-    let outgoing_task = outgoing.run(stop_rx, &mut server_tx, outgoing_rx);
+    let outgoing_task = outgoing.run(stop_rx, &mut server_tx, outgoing_rx, ping_interval);
 
     let test_task = test_case(context);
 
@@ -809,6 +1049,21 @@ async fn outgoing_clean_shutdown() {
     .await;
 }
 
+#[tokio::test]
+async fn outgoing_sends_pings() {
+    let _context =
+        test_outgoing_task_with_pings(Some(Duration::from_millis(10)), |mut context| async move {
+            let mut buf = BytesMut::new();
+            for _ in 0..2 {
+                let message = context.client.read(&mut buf).await.expect("Read failed.");
+                assert!(matches!(message, Message::Ping(_)));
+            }
+            context.stop();
+            context
+        })
+        .await;
+}
+
 struct DlSender(FramedWrite<ByteWriter, RawRequestMessageEncoder>);
 
 struct AgentSender(FramedWrite<ByteWriter, RawResponseMessageEncoder>);
@@ -981,6 +1236,43 @@ async fn outgoing_lane_not_found() {
     .await;
 }
 
+#[tokio::test]
+async fn outgoing_agent_not_started() {
+    let _context = test_outgoing_task(|mut context| async move {
+        let OutgoingTestContext {
+            outgoing_tx,
+            client,
+            ..
+        } = &mut context;
+
+        outgoing_tx
+            .send(OutgoingTaskMessage::NotFound {
+                command_envelope: false,
+                error: AgentResolutionError::NotStarted(AgentNotStarted {
+                    node: Text::new(OTHER),
+                    lane: Some(Text::new(LANE)),
+                }),
+            })
+            .await
+            .expect("Channel dropped");
+
+        let mut buf = BytesMut::new();
+        let message = client.read(&mut buf).await.expect("Output stopped.");
+
+        assert_eq!(message, Message::Text);
+
+        let env_str = std::str::from_utf8(buf.as_ref()).expect("Invalid UTF8.");
+
+        let expected = format!("@unlinked(node:\"{}\",lane:{})@nodeNotStarted", OTHER, LANE);
+
+        assert_eq!(env_str, expected);
+
+        context.stop();
+        context
+    })
+    .await;
+}
+
 struct CombinedTestContext {
     stop_tx: Option<trigger::Sender>,
     attach_tx: mpsc::Sender<AttachClient>,
@@ -996,6 +1288,18 @@ impl CombinedTestContext {
 }
 
 async fn test_combined_task<F, Fut>(test_case: F) -> Fut::Output
+where
+    F: FnOnce(CombinedTestContext) -> Fut,
+    Fut: Future,
+{
+    test_combined_task_with(None, WarpProtocol::default(), test_case).await
+}
+
+async fn test_combined_task_with<F, Fut>(
+    capture: Option<FrameCapture>,
+    protocol: WarpProtocol,
+    test_case: F,
+) -> Fut::Output
 where
     F: FnOnce(CombinedTestContext) -> Fut,
     Fut: Future,
@@ -1031,7 +1335,7 @@ where
         client,
     };
 
-    let remote = super::RemoteTask::new(
+    let mut remote = super::RemoteTask::new(
         ID,
         stop_rx,
         server,
@@ -1039,7 +1343,11 @@ where
         Some(find_tx),
         CHAN_SIZE,
         CLOSE_TIMEOUT,
-    );
+    )
+    .with_protocol(protocol);
+    if let Some(capture) = capture {
+        remote = remote.with_capture(capture);
+    }
 
     let remote_task = remote.run();
 
@@ -1075,8 +1383,10 @@ where
 
         while let Some(RequestMessage { path, envelope, .. }) = rx.recv_opt().await {
             let echo = match envelope {
-                Operation::Link => Notification::Linked,
-                Operation::Sync => Notification::Synced,
+                Operation::Link(_) => Notification::Linked,
+                Operation::Sync(_) | Operation::SyncRange(..) | Operation::Ack => {
+                    Notification::Synced
+                }
                 Operation::Unlink => Notification::Unlinked(None),
                 Operation::Command(body) => Notification::Event(body),
             };
@@ -1084,6 +1394,8 @@ where
                 origin: AGENT_ID,
                 path,
                 envelope: echo,
+                correlation_id: None,
+                command_origin: None,
             };
             tx.send_response(response).await;
         }
@@ -1140,6 +1452,50 @@ async fn combined_no_agent() {
     .await;
 }
 
+#[tokio::test]
+async fn combined_capture_frames() {
+    let capture = FrameCapture::new(CHAN_SIZE);
+    capture.set_enabled(true);
+    test_combined_task_with(
+        Some(capture.clone()),
+        WarpProtocol::default(),
+        |mut context| async move {
+            let CombinedTestContext { client, .. } = &mut context;
+
+            let envelope = make_bad_agent_envelope();
+
+            client.write_text(envelope).await.expect("Write failed.");
+
+            let mut buf = BytesMut::new();
+            let message = client.read(&mut buf).await.expect("Channel closed.");
+            assert_eq!(message, Message::Text);
+            context.stop();
+            context
+        },
+    )
+    .await;
+
+    let frames = capture
+        .frames()
+        .into_iter()
+        .map(|frame| {
+            let body = std::str::from_utf8(frame.frame.as_ref()).expect("Invalid UTF8");
+            (frame.direction, body.to_string())
+        })
+        .collect::<Vec<_>>();
+    let expected_out = format!("@unlinked(node:\"{}\",lane:{})@nodeNotFound", OTHER, LANE);
+    assert_eq!(
+        frames,
+        vec![
+            (
+                CaptureDirection::Incoming,
+                make_bad_agent_envelope().as_str().to_string()
+            ),
+            (CaptureDirection::Outgoing, expected_out),
+        ]
+    );
+}
+
 #[tokio::test]
 async fn combined_agent_io() {
     test_combined_task(|mut context| async move {
@@ -1164,6 +1520,44 @@ async fn combined_agent_io() {
     .await;
 }
 
+#[tokio::test]
+async fn combined_agent_io_binary() {
+    let protocol = WarpVersions::default().negotiated(Some(WARP_BINARY));
+    test_combined_task_with(None, protocol, |mut context| async move {
+        let CombinedTestContext { client, .. } = &mut context;
+
+        let mut envelope = BytesMut::new();
+        envelope.put_u8(3);
+        envelope.put_u32(NODE.len() as u32);
+        envelope.put_u32(LANE.len() as u32);
+        envelope.put_slice(NODE.as_bytes());
+        envelope.put_slice(LANE.as_bytes());
+        envelope.put_slice(b"{a:-786}");
+
+        client
+            .write_binary(envelope.as_ref())
+            .await
+            .expect("Write failed.");
+
+        let mut buf = BytesMut::new();
+        let message = client.read(&mut buf).await.expect("Channel closed.");
+        assert_eq!(message, Message::Binary);
+
+        assert_eq!(
+            read_binary_envelope(buf.as_ref()),
+            Ok(BinaryEnvelope {
+                kind: BinaryEnvelopeKind::Event,
+                node: NODE,
+                lane: LANE,
+                body: "{a:-786}",
+            })
+        );
+        context.stop();
+        context
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn combined_downlink_io() {
     test_combined_task(|mut context| async move {
@@ -1197,6 +1591,7 @@ async fn combined_downlink_io() {
             origin,
             path,
             envelope,
+            ..
         } = dl_rx.recv().await;
 
         assert_eq!(origin, ID);
@@ -1223,3 +1618,121 @@ async fn combined_downlink_io() {
     })
     .await;
 }
+
+#[test]
+fn sync_envelope_with_version() {
+    let envelope =
+        peel_envelope_header_str("@sync(node:\"/node\",lane:lane)@version{epoch:3,version:17}")
+            .expect("Invalid envelope.");
+    match interpret_envelope(ID, envelope) {
+        Some(Either::Left(RequestMessage {
+            envelope: Operation::Sync(params),
+            ..
+        })) => {
+            assert_eq!(params.since, Some(SyncVersion::new(3, 17)));
+        }
+        ow => panic!("Unexpected message: {:?}", ow),
+    }
+}
+
+#[test]
+fn sync_envelope_with_invalid_version() {
+    let envelope = peel_envelope_header_str("@sync(node:\"/node\",lane:lane)@version{epoch:3}")
+        .expect("Invalid envelope.");
+    match interpret_envelope(ID, envelope) {
+        Some(Either::Left(RequestMessage {
+            envelope: Operation::Sync(params),
+            ..
+        })) => {
+            assert!(params.since.is_none());
+        }
+        ow => panic!("Unexpected message: {:?}", ow),
+    }
+}
+
+#[test]
+fn sync_envelope_with_window() {
+    let envelope = peel_envelope_header_str("@sync(node:\"/node\",lane:lane,window:100)")
+        .expect("Invalid envelope.");
+    match interpret_envelope(ID, envelope) {
+        Some(Either::Left(RequestMessage {
+            envelope: Operation::Sync(params),
+            ..
+        })) => {
+            assert_eq!(params.window, Some(100));
+        }
+        ow => panic!("Unexpected message: {:?}", ow),
+    }
+}
+
+#[test]
+fn sync_envelope_with_range() {
+    let envelope = peel_envelope_header_str("@sync(node:\"/node\",lane:lane,from:10,to:\"b\")")
+        .expect("Invalid envelope.");
+    match interpret_envelope(ID, envelope) {
+        Some(Either::Left(RequestMessage {
+            envelope: Operation::SyncRange(params, range),
+            ..
+        })) => {
+            assert!(params.since.is_none());
+            assert_eq!(range.from.as_deref(), Some(b"10".as_slice()));
+            assert_eq!(range.to.as_deref(), Some(b"\"b\"".as_slice()));
+        }
+        ow => panic!("Unexpected message: {:?}", ow),
+    }
+}
+
+#[test]
+fn ack_envelope() {
+    let envelope =
+        peel_envelope_header_str("@ack(node:\"/node\",lane:lane)").expect("Invalid envelope.");
+    match interpret_envelope(ID, envelope) {
+        Some(Either::Left(RequestMessage {
+            path,
+            envelope: Operation::Ack,
+            ..
+        })) => {
+            assert_eq!(path.node, "/node");
+            assert_eq!(path.lane, "lane");
+        }
+        ow => panic!("Unexpected message: {:?}", ow),
+    }
+}
+
+#[test]
+fn synced_envelope_with_version() {
+    let envelope =
+        peel_envelope_header_str("@synced(node:\"/node\",lane:lane)@version{epoch:3,version:17}")
+            .expect("Invalid envelope.");
+    match interpret_envelope(ID, envelope) {
+        Some(Either::Right(ResponseMessage {
+            envelope: Notification::SyncedWith(body),
+            ..
+        })) => {
+            assert_eq!(body, "@version{epoch:3,version:17}");
+        }
+        ow => panic!("Unexpected message: {:?}", ow),
+    }
+}
+
+#[test]
+fn event_envelope_with_origin() {
+    let envelope = peel_envelope_header_str(
+        "@event(node:\"/node\",lane:lane,origin:\"6a1a6e6c-1f3b-4d0e-9a0c-6e4b5b0f2d7a\") 5",
+    )
+    .expect("Invalid envelope.");
+    match interpret_envelope(ID, envelope) {
+        Some(Either::Right(ResponseMessage {
+            envelope: Notification::Event(body),
+            command_origin,
+            ..
+        })) => {
+            assert_eq!(body, "5");
+            assert_eq!(
+                command_origin,
+                Some(Uuid::from_u128(0x6a1a6e6c_1f3b_4d0e_9a0c_6e4b5b0f2d7a))
+            );
+        }
+        ow => panic!("Unexpected message: {:?}", ow),
+    }
+}
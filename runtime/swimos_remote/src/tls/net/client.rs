@@ -21,7 +21,7 @@ use rustls::RootCertStore;
 
 use crate::dns::{BoxDnsResolver, DnsResolver, Resolver};
 use crate::net::{ClientConnections, ConnectionError, ConnectionResult, Scheme};
-use tokio::net::TcpStream;
+use crate::proxy::{open_tcp, ProxyConfig};
 use tokio_rustls::{TlsConnector, TlsStream};
 
 use crate::tls::{config::ClientConfig, errors::TlsError, maybe::MaybeTlsStream};
@@ -31,6 +31,7 @@ use crate::tls::{config::ClientConfig, errors::TlsError, maybe::MaybeTlsStream};
 pub struct RustlsClientNetworking {
     resolver: Arc<Resolver>,
     connector: TlsConnector,
+    proxy: Option<Arc<ProxyConfig>>,
 }
 
 impl RustlsClientNetworking {
@@ -38,9 +39,16 @@ impl RustlsClientNetworking {
         RustlsClientNetworking {
             resolver,
             connector,
+            proxy: None,
         }
     }
 
+    /// Open outgoing connections (both secure and insecure) through a proxy.
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(Arc::new(proxy));
+        self
+    }
+
     pub fn build(
         resolver: Arc<Resolver>,
         config: ClientConfig,
@@ -80,9 +88,11 @@ impl ClientConnections for RustlsClientNetworking {
         host: Option<&str>,
         addr: SocketAddr,
     ) -> BoxFuture<'_, ConnectionResult<Self::ClientSocket>> {
+        let proxy = self.proxy.clone();
+        let target_host = host.map(str::to_owned);
         match scheme {
             Scheme::Ws => async move {
-                let stream = TcpStream::connect(addr).await?;
+                let stream = open_tcp(proxy.as_deref(), target_host.as_deref(), addr).await?;
                 Ok(MaybeTlsStream::Plain(stream))
             }
             .boxed(),
@@ -94,8 +104,8 @@ impl ClientConnections for RustlsClientNetworking {
                     Ok(ServerName::IpAddress(addr.ip().into()))
                 };
                 async move {
-                    let stream = TcpStream::connect(addr).await?;
                     let RustlsClientNetworking { connector, .. } = self;
+                    let stream = open_tcp(proxy.as_deref(), target_host.as_deref(), addr).await?;
 
                     let client = connector.connect(domain?, stream).await.map_err(|err| {
                         let tls_err = TlsError::HandshakeFailed(err);
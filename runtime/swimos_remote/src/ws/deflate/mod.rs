@@ -0,0 +1,222 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bytes::BytesMut;
+use ratchet::deflate::{
+    Compression, Deflate, DeflateConfig, DeflateEncoder, DeflateExtProvider, DeflateExtensionError,
+    WindowBits, WindowBitsParseErr,
+};
+use ratchet::{
+    Extension, ExtensionDecoder, ExtensionEncoder, ExtensionProvider, FrameHeader, Header,
+    HeaderMap, HeaderValue, OpCode, ReunitableExtension, RsvBits, SplittableExtension,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Configuration for the per-message deflate web-socket extension.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeflateSettings {
+    /// The configuration that is negotiated with the peer.
+    pub config: DeflateConfig,
+    /// Messages with payloads smaller than this (in bytes) are sent uncompressed. Compressing
+    /// very small messages costs CPU time and often does not reduce their size.
+    pub threshold: usize,
+}
+
+impl DeflateSettings {
+    /// # Arguments
+    /// * `max_window_bits` - The size (as a power of 2) of the LZ77 sliding window for both the
+    ///   client and server. This must be in the range 8 to 15 inclusive.
+    /// * `compression_level` - The compression level, in the range 0 (no compression) to 9 (best
+    ///   compression).
+    /// * `threshold` - Messages with payloads smaller than this (in bytes) are sent uncompressed.
+    pub fn new(
+        max_window_bits: u8,
+        compression_level: u32,
+        threshold: usize,
+    ) -> Result<Self, WindowBitsParseErr> {
+        let window_bits = WindowBits::try_from(max_window_bits)?;
+        let config = DeflateConfig {
+            server_max_window_bits: window_bits,
+            client_max_window_bits: window_bits,
+            compression_level: Compression::new(compression_level.min(9)),
+            ..Default::default()
+        };
+        Ok(DeflateSettings { config, threshold })
+    }
+
+    /// Create an extension provider from these settings.
+    pub fn provider(&self) -> ThresholdDeflateProvider {
+        let DeflateSettings { config, threshold } = *self;
+        ThresholdDeflateProvider {
+            inner: DeflateExtProvider::with_config(config),
+            threshold,
+        }
+    }
+}
+
+impl From<DeflateConfig> for DeflateSettings {
+    fn from(config: DeflateConfig) -> Self {
+        DeflateSettings {
+            config,
+            threshold: 0,
+        }
+    }
+}
+
+/// An [`ExtensionProvider`] that negotiates the per-message deflate extension but will only compress
+/// messages with payloads that meet a size threshold. Peers are permitted to send uncompressed
+/// messages after negotiating the extension so this requires no support from the remote side.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThresholdDeflateProvider {
+    inner: DeflateExtProvider,
+    threshold: usize,
+}
+
+impl ExtensionProvider for ThresholdDeflateProvider {
+    type Extension = ThresholdDeflate;
+    type Error = DeflateExtensionError;
+
+    fn apply_headers(&self, headers: &mut HeaderMap) {
+        self.inner.apply_headers(headers)
+    }
+
+    fn negotiate_client(&self, headers: &[Header]) -> Result<Option<Self::Extension>, Self::Error> {
+        let ThresholdDeflateProvider { inner, threshold } = self;
+        Ok(inner
+            .negotiate_client(headers)?
+            .map(|deflate| ThresholdDeflate::new(deflate, *threshold)))
+    }
+
+    fn negotiate_server(
+        &self,
+        headers: &[Header],
+    ) -> Result<Option<(Self::Extension, HeaderValue)>, Self::Error> {
+        let ThresholdDeflateProvider { inner, threshold } = self;
+        Ok(inner
+            .negotiate_server(headers)?
+            .map(|(deflate, header)| (ThresholdDeflate::new(deflate, *threshold), header)))
+    }
+}
+
+/// Wraps an [`ExtensionEncoder`] so that it is bypassed for messages that are smaller than a
+/// threshold. The decision is made on the first frame of a message and applies to all of its
+/// continuation frames.
+#[derive(Debug)]
+pub struct ThresholdEncoder<E> {
+    inner: E,
+    threshold: usize,
+    bypass: bool,
+}
+
+impl<E> ThresholdEncoder<E> {
+    fn new(inner: E, threshold: usize) -> Self {
+        ThresholdEncoder {
+            inner,
+            threshold,
+            bypass: false,
+        }
+    }
+}
+
+impl<E: ExtensionEncoder> ExtensionEncoder for ThresholdEncoder<E> {
+    type Error = E::Error;
+
+    fn encode(
+        &mut self,
+        payload: &mut BytesMut,
+        header: &mut FrameHeader,
+    ) -> Result<(), Self::Error> {
+        let ThresholdEncoder {
+            inner,
+            threshold,
+            bypass,
+        } = self;
+        if !matches!(header.opcode, OpCode::Continuation) {
+            *bypass = payload.len() < *threshold;
+        }
+        if *bypass {
+            Ok(())
+        } else {
+            inner.encode(payload, header)
+        }
+    }
+}
+
+/// The per-message deflate extension, with a threshold below which messages are not compressed.
+#[derive(Debug)]
+pub struct ThresholdDeflate {
+    inner: ThresholdEncoder<Deflate>,
+}
+
+impl ThresholdDeflate {
+    fn new(deflate: Deflate, threshold: usize) -> Self {
+        ThresholdDeflate {
+            inner: ThresholdEncoder::new(deflate, threshold),
+        }
+    }
+}
+
+impl Extension for ThresholdDeflate {
+    fn bits(&self) -> RsvBits {
+        self.inner.inner.bits()
+    }
+}
+
+impl ExtensionEncoder for ThresholdDeflate {
+    type Error = DeflateExtensionError;
+
+    fn encode(
+        &mut self,
+        payload: &mut BytesMut,
+        header: &mut FrameHeader,
+    ) -> Result<(), Self::Error> {
+        self.inner.encode(payload, header)
+    }
+}
+
+impl ExtensionDecoder for ThresholdDeflate {
+    type Error = DeflateExtensionError;
+
+    fn decode(
+        &mut self,
+        payload: &mut BytesMut,
+        header: &mut FrameHeader,
+    ) -> Result<(), Self::Error> {
+        self.inner.inner.decode(payload, header)
+    }
+}
+
+impl SplittableExtension for ThresholdDeflate {
+    type SplitEncoder = ThresholdEncoder<DeflateEncoder>;
+    type SplitDecoder = <Deflate as SplittableExtension>::SplitDecoder;
+
+    fn split(self) -> (Self::SplitEncoder, Self::SplitDecoder) {
+        let ThresholdEncoder {
+            inner, threshold, ..
+        } = self.inner;
+        let (encoder, decoder) = inner.split();
+        (ThresholdEncoder::new(encoder, threshold), decoder)
+    }
+}
+
+impl ReunitableExtension for ThresholdDeflate {
+    fn reunite(encoder: Self::SplitEncoder, decoder: Self::SplitDecoder) -> Self {
+        let ThresholdEncoder {
+            inner, threshold, ..
+        } = encoder;
+        ThresholdDeflate::new(Deflate::reunite(inner, decoder), threshold)
+    }
+}
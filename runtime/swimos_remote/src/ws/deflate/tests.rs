@@ -0,0 +1,162 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bytes::BytesMut;
+use ratchet::{
+    ExtensionDecoder, ExtensionEncoder, ExtensionProvider, FrameHeader, Header, HeaderMap, OpCode,
+};
+
+use super::{DeflateSettings, ThresholdDeflate, ThresholdEncoder};
+
+const THRESHOLD: usize = 64;
+
+fn header(opcode: OpCode, fin: bool) -> FrameHeader {
+    FrameHeader {
+        fin,
+        rsv1: false,
+        rsv2: false,
+        rsv3: false,
+        opcode,
+    }
+}
+
+#[derive(Debug, Default)]
+struct CountingEncoder(usize);
+
+impl ExtensionEncoder for CountingEncoder {
+    type Error = std::convert::Infallible;
+
+    fn encode(
+        &mut self,
+        _payload: &mut BytesMut,
+        _header: &mut FrameHeader,
+    ) -> Result<(), Self::Error> {
+        self.0 += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn settings_validate_window_bits() {
+    assert!(DeflateSettings::new(7, 6, 0).is_err());
+    assert!(DeflateSettings::new(16, 6, 0).is_err());
+
+    let settings = DeflateSettings::new(10, 6, THRESHOLD).expect("Invalid settings.");
+    assert_eq!(settings.config.server_max_window_bits, 10);
+    assert_eq!(settings.config.client_max_window_bits, 10);
+    assert_eq!(settings.config.compression_level.level(), 6);
+    assert_eq!(settings.threshold, THRESHOLD);
+}
+
+#[test]
+fn threshold_encoder_bypasses_small_messages() {
+    let mut encoder = ThresholdEncoder::new(CountingEncoder::default(), THRESHOLD);
+
+    let mut small = BytesMut::from(&[0u8; THRESHOLD - 1][..]);
+    assert!(encoder
+        .encode(&mut small, &mut header(OpCode::Binary, true))
+        .is_ok());
+    assert_eq!(encoder.inner.0, 0);
+
+    let mut large = BytesMut::from(&[0u8; THRESHOLD][..]);
+    assert!(encoder
+        .encode(&mut large, &mut header(OpCode::Text, true))
+        .is_ok());
+    assert_eq!(encoder.inner.0, 1);
+}
+
+#[test]
+fn threshold_encoder_continuations_follow_first_frame() {
+    let mut encoder = ThresholdEncoder::new(CountingEncoder::default(), THRESHOLD);
+
+    let mut small = BytesMut::from(&[0u8; 1][..]);
+    let mut large = BytesMut::from(&[0u8; THRESHOLD * 2][..]);
+
+    assert!(encoder
+        .encode(&mut small, &mut header(OpCode::Text, false))
+        .is_ok());
+    assert!(encoder
+        .encode(&mut large, &mut header(OpCode::Continuation, true))
+        .is_ok());
+    assert_eq!(encoder.inner.0, 0);
+
+    assert!(encoder
+        .encode(&mut large, &mut header(OpCode::Text, false))
+        .is_ok());
+    assert!(encoder
+        .encode(&mut small, &mut header(OpCode::Continuation, true))
+        .is_ok());
+    assert_eq!(encoder.inner.0, 2);
+}
+
+fn negotiate(settings: DeflateSettings) -> (ThresholdDeflate, ThresholdDeflate) {
+    let provider = settings.provider();
+
+    let mut request = HeaderMap::new();
+    provider.apply_headers(&mut request);
+    let request_headers = request
+        .iter()
+        .map(|(name, value)| Header {
+            name: name.as_str(),
+            value: value.as_bytes(),
+        })
+        .collect::<Vec<_>>();
+
+    let (server, response) = provider
+        .negotiate_server(&request_headers)
+        .expect("Server negotiation failed.")
+        .expect("Extension was not negotiated.");
+
+    let response_headers = [Header {
+        name: "sec-websocket-extensions",
+        value: response.as_bytes(),
+    }];
+    let client = provider
+        .negotiate_client(&response_headers)
+        .expect("Client negotiation failed.")
+        .expect("Extension was not negotiated.");
+    (client, server)
+}
+
+fn round_trip(
+    client: &mut ThresholdDeflate,
+    server: &mut ThresholdDeflate,
+    payload: &[u8],
+) -> bool {
+    let mut buffer = BytesMut::from(payload);
+    let mut frame_header = header(OpCode::Text, true);
+    client
+        .encode(&mut buffer, &mut frame_header)
+        .expect("Encoding failed.");
+    let compressed = frame_header.rsv1;
+    server
+        .decode(&mut buffer, &mut frame_header)
+        .expect("Decoding failed.");
+    assert_eq!(buffer.as_ref(), payload);
+    compressed
+}
+
+#[test]
+fn negotiated_extension_round_trip() {
+    let settings = DeflateSettings::new(12, 9, THRESHOLD).expect("Invalid settings.");
+    let (mut client, mut server) = negotiate(settings);
+
+    let small = b"@event(node:\"/node\",lane:lane)1";
+    assert!(!round_trip(&mut client, &mut server, small));
+
+    let large = "@event(node:\"/node\",lane:lane)".repeat(16);
+    assert!(round_trip(&mut client, &mut server, large.as_bytes()));
+
+    assert!(!round_trip(&mut client, &mut server, small));
+}
@@ -20,12 +20,17 @@ use futures::Stream;
 use swimos_messages::remote_protocol::FindNode;
 use swimos_utilities::errors::Recoverable;
 
-use ratchet::{ExtensionProvider, ProtocolRegistry, WebSocket, WebSocketConfig, WebSocketStream};
+use ratchet::{ExtensionProvider, WebSocket, WebSocketConfig, WebSocketStream};
 use thiserror::Error;
 use tokio::sync::mpsc;
 
 use crate::net::{Listener, ListenerError};
-use crate::websocket::WARP;
+
+mod deflate;
+mod protocol;
+
+pub use deflate::{DeflateSettings, ThresholdDeflate, ThresholdDeflateProvider, ThresholdEncoder};
+pub use protocol::{WarpFeatures, WarpProtocol, WarpVersion, WarpVersions};
 
 #[derive(Debug, Error)]
 #[error("{0}")]
@@ -36,11 +41,45 @@ impl Recoverable for RatchetError {
     }
 }
 
-pub type WsOpenFuture<'l, Sock, Ext, Error> = BoxFuture<'l, Result<WebSocket<Sock, Ext>, Error>>;
+pub type WsOpenFuture<'l, Sock, Ext, Error> =
+    BoxFuture<'l, Result<(WebSocket<Sock, Ext>, WarpProtocol), Error>>;
+
+/// The encoding of the Warp envelopes sent over a web-socket connection. This is determined by the
+/// subprotocol that was negotiated when the connection was opened.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WarpEncoding {
+    /// Envelopes are sent as Recon strings in text frames (negotiated with the
+    /// [`WARP`](crate::websocket::WARP) subprotocol, or with no subprotocol).
+    #[default]
+    Text,
+    /// Envelopes are sent in binary frames (negotiated with the
+    /// [`WARP_BINARY`](crate::websocket::WARP_BINARY) subprotocol).
+    /// Each frame contains a single envelope with the following layout (all integers are
+    /// big-endian):
+    ///
+    /// | Tag (u8) | Node length (u32) | Lane length (u32) | Node (UTF-8) | Lane (UTF-8) | Body |
+    ///
+    /// The tags are: `0` link, `1` sync, `2` unlink, `3` command, `4` linked, `5` synced,
+    /// `6` unlinked, `7` event, `8` ack and `9` advisory. The body consists of the remainder of the
+    /// frame and contains the Recon body of the envelope, as UTF-8. This avoids printing and parsing
+    /// the envelope headers as Recon.
+    Binary,
+}
+
+impl WarpEncoding {
+    /// The features that a version of the Warp protocol must have to use this encoding.
+    pub fn features(&self) -> WarpFeatures {
+        match self {
+            WarpEncoding::Text => WarpFeatures::empty(),
+            WarpEncoding::Binary => WarpFeatures::BINARY_ENVELOPES,
+        }
+    }
+}
 
 /// Trait for adapters that will negotiate a client websocket connection over an duplex connection.
 pub trait WebsocketClient {
-    /// Negotiate a new client connection.
+    /// Negotiate a new client connection. The future returns the connection along with the
+    /// version of the Warp protocol that was negotiated.
     ///
     /// # Arguments
     /// * `socket` - The connection.
@@ -56,16 +95,39 @@ pub trait WebsocketClient {
         Sock: WebSocketStream + Send,
         Provider: ExtensionProvider + Send + Sync + 'static,
         Provider::Extension: Send + Sync + 'static;
+
+    /// Negotiate a new client connection that will carry requests that have been forwarded from
+    /// another connection (see [`WarpFeatures::FORWARDED`]). By default, this is the same as
+    /// [`WebsocketClient::open_connection`] and the connection is not marked as forwarding.
+    ///
+    /// # Arguments
+    /// * `socket` - The connection.
+    /// * `provider` - Provider for websocket extensions.
+    /// * `addr` - The remote host.
+    fn open_forwarding_connection<'a, Sock, Provider>(
+        &self,
+        socket: Sock,
+        provider: &'a Provider,
+        addr: String,
+    ) -> WsOpenFuture<'a, Sock, Provider::Extension, RatchetError>
+    where
+        Sock: WebSocketStream + Send,
+        Provider: ExtensionProvider + Send + Sync + 'static,
+        Provider::Extension: Send + Sync + 'static,
+    {
+        self.open_connection(socket, provider, addr)
+    }
 }
 
 /// Trait for adapters that can negotiate websocket connections for incoming TCP connections.
 pub trait WebsocketServer: Send + Sync {
-    type WsStream<Sock, Ext>: Stream<Item = Result<(WebSocket<Sock, Ext>, SocketAddr), ListenerError>>
+    type WsStream<Sock, Ext>: Stream<Item = Result<(WebSocket<Sock, Ext>, SocketAddr, WarpProtocol), ListenerError>>
         + Send
         + Unpin;
 
     /// Create a stream that will negotiate websocket connections on a stream of incoming duplex connections.
-    /// This will typically be a TCP listener.
+    /// This will typically be a TCP listener. Each connection is accompanied by the version of the
+    /// Warp protocol that was negotiated with the peer.
     ///
     /// # Arguments
     /// * `listener` - The stream of incoming connections.
@@ -90,32 +152,79 @@ pub trait Websockets: WebsocketClient + WebsocketServer {}
 impl<W> Websockets for W where W: WebsocketClient + WebsocketServer {}
 
 /// Standard websocket client implementation.
-pub struct RatchetClient(WebSocketConfig);
+pub struct RatchetClient {
+    config: WebSocketConfig,
+    encoding: WarpEncoding,
+}
+
 impl From<WebSocketConfig> for RatchetClient {
     fn from(config: WebSocketConfig) -> Self {
-        RatchetClient(config)
+        RatchetClient {
+            config,
+            encoding: WarpEncoding::Text,
+        }
     }
 }
 
-impl WebsocketClient for RatchetClient {
-    fn open_connection<'a, Sock, Provider>(
+impl RatchetClient {
+    /// Set the encoding to request for Warp envelopes when opening connections.
+    pub fn with_encoding(mut self, encoding: WarpEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+}
+
+impl RatchetClient {
+    fn open_with<'a, Sock, Provider>(
         &self,
         socket: Sock,
         provider: &'a Provider,
         addr: String,
+        features: WarpFeatures,
     ) -> WsOpenFuture<'a, Sock, Provider::Extension, RatchetError>
     where
         Sock: WebSocketStream + Send,
         Provider: ExtensionProvider + Send + Sync + 'static,
         Provider::Extension: Send + Sync + 'static,
     {
-        let config = self.0;
+        let RatchetClient { config, encoding } = *self;
         Box::pin(async move {
-            let subprotocols = ProtocolRegistry::new([WARP])?;
-            let socket = ratchet::subscribe_with(config, socket, addr, provider, subprotocols)
-                .await?
-                .into_websocket();
-            Ok(socket)
+            let versions = WarpVersions::default();
+            let subprotocols = versions.client_protocols(encoding.features() | features)?;
+            let upgraded =
+                ratchet::subscribe_with(config, socket, addr, provider, subprotocols).await?;
+            let protocol = versions.negotiated(upgraded.subprotocol.as_deref());
+            Ok((upgraded.into_websocket(), protocol))
         })
     }
 }
+
+impl WebsocketClient for RatchetClient {
+    fn open_connection<'a, Sock, Provider>(
+        &self,
+        socket: Sock,
+        provider: &'a Provider,
+        addr: String,
+    ) -> WsOpenFuture<'a, Sock, Provider::Extension, RatchetError>
+    where
+        Sock: WebSocketStream + Send,
+        Provider: ExtensionProvider + Send + Sync + 'static,
+        Provider::Extension: Send + Sync + 'static,
+    {
+        self.open_with(socket, provider, addr, WarpFeatures::empty())
+    }
+
+    fn open_forwarding_connection<'a, Sock, Provider>(
+        &self,
+        socket: Sock,
+        provider: &'a Provider,
+        addr: String,
+    ) -> WsOpenFuture<'a, Sock, Provider::Extension, RatchetError>
+    where
+        Sock: WebSocketStream + Send,
+        Provider: ExtensionProvider + Send + Sync + 'static,
+        Provider::Extension: Send + Sync + 'static,
+    {
+        self.open_with(socket, provider, addr, WarpFeatures::FORWARDED)
+    }
+}
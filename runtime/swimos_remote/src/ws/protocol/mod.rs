@@ -0,0 +1,167 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bitflags::bitflags;
+use ratchet::ProtocolRegistry;
+
+use crate::websocket::{WarpEncoding, WARP, WARP_BINARY, WARP_BINARY_FORWARDED, WARP_FORWARDED};
+
+#[cfg(test)]
+mod tests;
+
+bitflags! {
+    /// Optional features of the Warp protocol. The features that are available on a connection
+    /// are determined by the version of the protocol that was negotiated when it was opened.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub struct WarpFeatures: u32 {
+        /// Envelopes are sent in binary frames (see [`WarpEncoding::Binary`]).
+        const BINARY_ENVELOPES = 0b1;
+        /// The requests sent over the connection have already been forwarded by another server
+        /// (for example, by a member of a cluster to the member that hosts a node) and must not
+        /// be forwarded again. Clients only offer the versions with this feature if they
+        /// forward requests.
+        const FORWARDED = 0b10;
+    }
+}
+
+/// A version of the Warp protocol that can be negotiated, as a web-socket subprotocol, when a
+/// connection is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarpVersion {
+    /// The name of the subprotocol.
+    pub name: &'static str,
+    /// The features that are available when this version is negotiated.
+    pub features: WarpFeatures,
+}
+
+impl WarpVersion {
+    pub const fn new(name: &'static str, features: WarpFeatures) -> Self {
+        WarpVersion { name, features }
+    }
+}
+
+/// All versions of the Warp protocol that are supported, in order of preference. A new envelope
+/// format should be introduced as a new version (with its own subprotocol name) so that peers that
+/// do not recognize it will continue to negotiate an older version.
+const SUPPORTED_VERSIONS: &[WarpVersion] = &[
+    WarpVersion::new(
+        WARP_BINARY_FORWARDED,
+        WarpFeatures::BINARY_ENVELOPES.union(WarpFeatures::FORWARDED),
+    ),
+    WarpVersion::new(WARP_FORWARDED, WarpFeatures::FORWARDED),
+    WarpVersion::new(WARP_BINARY, WarpFeatures::BINARY_ENVELOPES),
+    WarpVersion::new(WARP, WarpFeatures::empty()),
+];
+
+/// A registry of the versions of the Warp protocol that may be negotiated for web-socket
+/// connections. By default, this contains every supported version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarpVersions {
+    versions: &'static [WarpVersion],
+}
+
+impl Default for WarpVersions {
+    fn default() -> Self {
+        WarpVersions {
+            versions: SUPPORTED_VERSIONS,
+        }
+    }
+}
+
+impl WarpVersions {
+    /// Create a registry from a list of versions, in order of preference.
+    pub const fn new(versions: &'static [WarpVersion]) -> Self {
+        WarpVersions { versions }
+    }
+
+    /// The versions in the registry, in order of preference.
+    pub fn versions(&self) -> &'static [WarpVersion] {
+        self.versions
+    }
+
+    /// Find a version by the name of its subprotocol.
+    pub fn find(&self, name: &str) -> Option<&'static WarpVersion> {
+        self.versions.iter().find(|version| version.name == name)
+    }
+
+    /// Select the version to use for an incoming connection from the subprotocols offered by the
+    /// client. The most preferred version in the registry is chosen, irrespective of the order in
+    /// which the client offered them.
+    pub fn select<'a, I>(&self, offered: I) -> Option<&'static WarpVersion>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let versions = self.versions;
+        offered
+            .into_iter()
+            .filter_map(|name| versions.iter().position(|version| version.name == name))
+            .min()
+            .map(|i| &versions[i])
+    }
+
+    /// The subprotocols to offer when opening a client connection. Only the versions that
+    /// require no features other than those specified are offered.
+    pub fn client_protocols(
+        &self,
+        features: WarpFeatures,
+    ) -> Result<ProtocolRegistry, ratchet::Error> {
+        ProtocolRegistry::new(
+            self.versions
+                .iter()
+                .filter(|version| features.contains(version.features))
+                .map(|version| version.name),
+        )
+    }
+
+    /// Determine the protocol for a connection from the subprotocol that was negotiated when it
+    /// was opened. If no subprotocol was negotiated, or it is not in the registry, the peer
+    /// does not support any optional features.
+    pub fn negotiated(&self, subprotocol: Option<&str>) -> WarpProtocol {
+        subprotocol
+            .and_then(|name| self.find(name))
+            .map(|version| WarpProtocol {
+                version: Some(version.name),
+                features: version.features,
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// The version of the Warp protocol that was negotiated for a connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WarpProtocol {
+    version: Option<&'static str>,
+    features: WarpFeatures,
+}
+
+impl WarpProtocol {
+    /// The name of the negotiated subprotocol (if any).
+    pub fn version(&self) -> Option<&'static str> {
+        self.version
+    }
+
+    /// The optional features that are available on the connection.
+    pub fn features(&self) -> WarpFeatures {
+        self.features
+    }
+
+    /// The encoding of the Warp envelopes sent over the connection.
+    pub fn encoding(&self) -> WarpEncoding {
+        if self.features.contains(WarpFeatures::BINARY_ENVELOPES) {
+            WarpEncoding::Binary
+        } else {
+            WarpEncoding::Text
+        }
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::websocket::{WarpEncoding, WARP, WARP_BINARY, WARP_BINARY_FORWARDED, WARP_FORWARDED};
+
+use super::{WarpFeatures, WarpVersion, WarpVersions};
+
+const TEXT_ONLY: &[WarpVersion] = &[WarpVersion::new(WARP, WarpFeatures::empty())];
+
+#[test]
+fn select_preferred_version() {
+    let versions = WarpVersions::default();
+    let selected = versions.select(["warp0", "warp0-bin"]).map(|v| v.name);
+    assert_eq!(selected, Some(WARP_BINARY));
+    let selected = versions.select(["other", "warp0"]).map(|v| v.name);
+    assert_eq!(selected, Some(WARP));
+    assert!(versions.select(["other"]).is_none());
+    assert!(versions.select([]).is_none());
+
+    let text_only = WarpVersions::new(TEXT_ONLY);
+    let selected = text_only.select(["warp0-bin", "warp0"]).map(|v| v.name);
+    assert_eq!(selected, Some(WARP));
+}
+
+#[test]
+fn negotiated_protocol() {
+    let versions = WarpVersions::default();
+
+    let binary = versions.negotiated(Some(WARP_BINARY));
+    assert_eq!(binary.version(), Some(WARP_BINARY));
+    assert_eq!(binary.features(), WarpFeatures::BINARY_ENVELOPES);
+    assert_eq!(binary.encoding(), WarpEncoding::Binary);
+
+    let text = versions.negotiated(Some(WARP));
+    assert_eq!(text.version(), Some(WARP));
+    assert_eq!(text.features(), WarpFeatures::empty());
+    assert_eq!(text.encoding(), WarpEncoding::Text);
+
+    let legacy = versions.negotiated(None);
+    assert_eq!(legacy.version(), None);
+    assert_eq!(legacy.encoding(), WarpEncoding::Text);
+
+    let unknown = WarpVersions::new(TEXT_ONLY).negotiated(Some(WARP_BINARY));
+    assert_eq!(unknown.version(), None);
+    assert_eq!(unknown.encoding(), WarpEncoding::Text);
+}
+
+#[test]
+fn forwarded_versions_are_only_offered_when_forwarding() {
+    let versions = WarpVersions::default();
+    let offered = |features: WarpFeatures| {
+        versions
+            .versions()
+            .iter()
+            .filter(|version| features.contains(version.features))
+            .map(|version| version.name)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        offered(WarpFeatures::BINARY_ENVELOPES),
+        vec![WARP_BINARY, WARP]
+    );
+    assert_eq!(
+        offered(WarpFeatures::BINARY_ENVELOPES | WarpFeatures::FORWARDED),
+        vec![WARP_BINARY_FORWARDED, WARP_FORWARDED, WARP_BINARY, WARP]
+    );
+
+    let selected = versions.select([WARP_FORWARDED, WARP]).map(|v| v.name);
+    assert_eq!(selected, Some(WARP_FORWARDED));
+    let forwarded = versions.negotiated(Some(WARP_BINARY_FORWARDED));
+    assert!(forwarded.features().contains(WarpFeatures::FORWARDED));
+    assert_eq!(forwarded.encoding(), WarpEncoding::Binary);
+}
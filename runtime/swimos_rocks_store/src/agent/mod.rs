@@ -146,6 +146,11 @@ where
         let StoreWrapper(inner) = self;
         ready(Ok(StoreWrapper(inner.node_store(node_uri)))).boxed()
     }
+
+    fn node_uris(&self) -> BoxFuture<'static, Result<Vec<String>, StoreError>> {
+        let StoreWrapper(inner) = self;
+        ready(inner.node_uris()).boxed()
+    }
 }
 
 impl<S> NodePersistence for StoreWrapper<S>
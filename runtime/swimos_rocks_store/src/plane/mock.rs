@@ -54,6 +54,10 @@ impl PlaneStore for MockPlaneStore {
     fn delete_map(&self, _lane_id: u64) -> Result<(), StoreError> {
         Ok(())
     }
+
+    fn node_uris(&self) -> Result<Vec<String>, StoreError> {
+        Ok(vec![])
+    }
 }
 
 impl KeyspaceResolver for MockPlaneStore {
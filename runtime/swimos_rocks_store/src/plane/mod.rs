@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeSet;
 use std::ffi::OsStr;
 use std::fmt::{Debug, Formatter};
 use std::io;
@@ -29,6 +30,8 @@ use swimos_model::Text;
 
 #[cfg(test)]
 pub mod mock;
+#[cfg(test)]
+mod tests;
 
 const STORE_DIR: &str = "store";
 const PLANES_DIR: &str = "planes";
@@ -72,6 +75,9 @@ pub trait PlaneStore: StoreEngine + Sized + Debug + Send + Sync + Clone + 'stati
     fn node_id_of<I>(&self, node: I) -> Result<u64, StoreError>
     where
         I: Into<String>;
+
+    /// List the URIs of the nodes that have lane state in the store.
+    fn node_uris(&self) -> Result<Vec<String>, StoreError>;
 }
 
 /// A store engine for planes.
@@ -136,7 +142,8 @@ where
 {
     type NodeStore = SwimNodeStore<Self>;
 
-    type RangeCon<'a> = PrefixStrippedRangeConsumer<<D as KeyspaceByteEngine>::RangeCon<'a>>
+    type RangeCon<'a>
+        = PrefixStrippedRangeConsumer<<D as KeyspaceByteEngine>::RangeCon<'a>>
     where
         Self: 'a;
 
@@ -171,6 +178,16 @@ where
         self.delegate
             .delete_key_range(KeyspaceName::Map, &start, &ubound)
     }
+
+    fn node_uris(&self) -> Result<Vec<String>, StoreError> {
+        let mut uris = BTreeSet::new();
+        for (node_uri, lane_id) in self.keystore.node_lane_ids()? {
+            if !uris.contains(&node_uri) && self.has_lane_state(lane_id)? {
+                uris.insert(node_uri);
+            }
+        }
+        Ok(uris.into_iter().collect())
+    }
 }
 
 impl<D: Store> StoreEngine for SwimPlaneStore<D> {
@@ -238,4 +255,16 @@ where
             keystore,
         }
     }
+
+    /// Determine whether a lane has a value, or any map entries, in the store.
+    fn has_lane_state(&self, lane_id: u64) -> Result<bool, StoreError> {
+        if self.get(StoreKey::Value { lane_id })?.is_some() {
+            return Ok(true);
+        }
+        let prefix = StoreKey::Map { lane_id, key: None }.serialize_as_bytes();
+        let mut consumer = self
+            .delegate
+            .get_prefix_range_consumer(KeyspaceName::Map, &prefix)?;
+        Ok(matches!(consumer.consume_next()?, Some((key, _)) if key.starts_with(&prefix)))
+    }
 }
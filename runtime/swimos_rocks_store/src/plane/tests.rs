@@ -0,0 +1,143 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::agent::NodeStore;
+use crate::engine::RocksEngine;
+use crate::plane::{open_plane, PlaneStore, SwimPlaneStore};
+use crate::server::rocks::{default_db_opts, default_keyspaces};
+use crate::server::{StoreEngine, StoreKey};
+use tempdir::TempDir;
+
+struct TransientPlane {
+    _dir: TempDir,
+    store: SwimPlaneStore<RocksEngine>,
+}
+
+impl TransientPlane {
+    fn new() -> TransientPlane {
+        let dir = TempDir::new("test").expect("Failed to create temporary directory");
+        let store = open_plane(dir.path(), "plane", default_db_opts(), default_keyspaces())
+            .expect("Failed to open plane store");
+        TransientPlane { _dir: dir, store }
+    }
+}
+
+fn put_value(store: &SwimPlaneStore<RocksEngine>, node_uri: &str, lane: &str) -> u64 {
+    let lane_id = store.node_store(node_uri).lane_id_of(lane).unwrap();
+    store.put(StoreKey::Value { lane_id }, b"value").unwrap();
+    lane_id
+}
+
+fn put_map_entry(store: &SwimPlaneStore<RocksEngine>, node_uri: &str, lane: &str) -> u64 {
+    let lane_id = store.node_store(node_uri).lane_id_of(lane).unwrap();
+    let key = StoreKey::Map {
+        lane_id,
+        key: Some(b"key".to_vec()),
+    };
+    store.put(key, b"value").unwrap();
+    lane_id
+}
+
+#[test]
+fn has_lane_state_for_value() {
+    let TransientPlane { store, .. } = TransientPlane::new();
+    let lane_id = put_value(&store, "/node", "lane");
+
+    assert!(store.has_lane_state(lane_id).unwrap());
+}
+
+#[test]
+fn has_lane_state_for_map_entries() {
+    let TransientPlane { store, .. } = TransientPlane::new();
+    let lane_id = put_map_entry(&store, "/node", "lane");
+
+    assert!(store.has_lane_state(lane_id).unwrap());
+}
+
+#[test]
+fn has_lane_state_empty_lane() {
+    let TransientPlane { store, .. } = TransientPlane::new();
+    let lane_id = store.node_store("/node").lane_id_of("lane").unwrap();
+
+    assert!(!store.has_lane_state(lane_id).unwrap());
+}
+
+#[test]
+fn has_lane_state_ignores_neighbouring_map() {
+    let TransientPlane { store, .. } = TransientPlane::new();
+    let empty_id = store.node_store("/node").lane_id_of("empty").unwrap();
+    put_map_entry(&store, "/node", "full");
+
+    assert!(!store.has_lane_state(empty_id).unwrap());
+}
+
+#[test]
+fn has_lane_state_after_map_deleted() {
+    let TransientPlane { store, .. } = TransientPlane::new();
+    let lane_id = put_map_entry(&store, "/node", "lane");
+    store.delete_map(lane_id).unwrap();
+
+    assert!(!store.has_lane_state(lane_id).unwrap());
+}
+
+#[test]
+fn node_uris_empty_store() {
+    let TransientPlane { store, .. } = TransientPlane::new();
+
+    assert!(store.node_uris().unwrap().is_empty());
+}
+
+#[test]
+fn node_uris_value_and_map_lanes() {
+    let TransientPlane { store, .. } = TransientPlane::new();
+    put_value(&store, "/first", "lane");
+    put_map_entry(&store, "/second", "lane");
+
+    assert_eq!(
+        store.node_uris().unwrap(),
+        vec!["/first".to_string(), "/second".to_string()]
+    );
+}
+
+#[test]
+fn node_uris_skips_nodes_with_only_empty_lanes() {
+    let TransientPlane { store, .. } = TransientPlane::new();
+    put_value(&store, "/full", "lane");
+    store.node_store("/empty").lane_id_of("lane").unwrap();
+    let map_id = put_map_entry(&store, "/cleared", "lane");
+    store.delete_map(map_id).unwrap();
+
+    assert_eq!(store.node_uris().unwrap(), vec!["/full".to_string()]);
+}
+
+#[test]
+fn node_uris_reports_each_node_once() {
+    let TransientPlane { store, .. } = TransientPlane::new();
+    put_value(&store, "/node", "first");
+    put_map_entry(&store, "/node", "second");
+
+    assert_eq!(store.node_uris().unwrap(), vec!["/node".to_string()]);
+}
+
+#[test]
+fn node_uris_with_nested_path() {
+    let TransientPlane { store, .. } = TransientPlane::new();
+    put_value(&store, "/unit/1/sensor", "lane");
+    put_map_entry(&store, "/unit/2", "lane");
+
+    assert_eq!(
+        store.node_uris().unwrap(),
+        vec!["/unit/1/sensor".to_string(), "/unit/2".to_string()]
+    );
+}
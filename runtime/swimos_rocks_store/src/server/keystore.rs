@@ -16,6 +16,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use swimos_api::error::StoreError;
+use swimos_api::persistence::RangeConsumer;
 
 use crate::keyspaces::KeyspaceByteEngine;
 use crate::store::KeyspaceName;
@@ -83,6 +84,25 @@ impl<D: KeyspaceByteEngine> KeyStore<D> {
             }
         }
     }
+
+    /// List the URIs of the nodes that have been assigned lane identifiers, along with each of
+    /// those identifiers.
+    pub fn node_lane_ids(&self) -> Result<Vec<(String, u64)>, StoreError> {
+        let KeyStore { delegate, .. } = self;
+        let prefix = format_key("");
+        let mut consumer =
+            delegate.get_prefix_range_consumer(KeyspaceName::Lane, prefix.as_bytes())?;
+        let mut ids = vec![];
+        while let Some((key, value)) = consumer.consume_next()? {
+            let Some(lane_key) = key.strip_prefix(prefix.as_bytes()) else {
+                break;
+            };
+            let lane_key = std::str::from_utf8(lane_key).map_err(|_| StoreError::InvalidKey)?;
+            let (node_uri, _lane) = lane_key.rsplit_once('/').ok_or(StoreError::InvalidKey)?;
+            ids.push((node_uri.to_string(), deserialize_u64(value)?));
+        }
+        Ok(ids)
+    }
 }
 
 pub fn format_key<I: ToString>(uri: I) -> String {
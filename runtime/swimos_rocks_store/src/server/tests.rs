@@ -12,8 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::engine::RocksEngine;
+use crate::server::keystore::KeyStore;
+use crate::server::rocks::{default_db_opts, default_keyspaces};
 use crate::server::{StoreKey, KEY, MAP_TAG, UBOUND, VAL_TAG};
+use crate::store::StoreBuilder;
 use integer_encoding::FixedInt;
+use std::sync::Arc;
+use tempdir::TempDir;
 
 #[test]
 fn serialize_value_key() {
@@ -70,3 +76,53 @@ fn serialize_map_ubound() {
     assert_eq!(u64::decode_fixed(&bytes[1..9]), Some(lane_id));
     assert_eq!(bytes[9], UBOUND);
 }
+
+fn with_keystore<F>(f: F)
+where
+    F: FnOnce(KeyStore<RocksEngine>),
+{
+    let dir = TempDir::new("test").expect("Failed to create temporary directory");
+    let delegate = default_db_opts()
+        .build(dir.path(), &default_keyspaces())
+        .expect("Failed to build delegate store");
+    f(KeyStore::initialise_with(Arc::new(delegate)));
+}
+
+#[test]
+fn node_lane_ids_empty() {
+    with_keystore(|keystore| {
+        assert!(keystore.node_lane_ids().unwrap().is_empty());
+    });
+}
+
+#[test]
+fn node_lane_ids_lists_assigned_ids() {
+    with_keystore(|keystore| {
+        let first = keystore.id_for("/first/lane".to_string()).unwrap();
+        let second = keystore.id_for("/second/lane".to_string()).unwrap();
+        let other = keystore.id_for("/second/other".to_string()).unwrap();
+
+        let mut ids = keystore.node_lane_ids().unwrap();
+        ids.sort();
+        assert_eq!(
+            ids,
+            vec![
+                ("/first".to_string(), first),
+                ("/second".to_string(), second),
+                ("/second".to_string(), other),
+            ]
+        );
+    });
+}
+
+#[test]
+fn node_lane_ids_nested_node_uri() {
+    with_keystore(|keystore| {
+        let id = keystore.id_for("/unit/1/sensor/lane".to_string()).unwrap();
+
+        assert_eq!(
+            keystore.node_lane_ids().unwrap(),
+            vec![("/unit/1/sensor".to_string(), id)]
+        );
+    });
+}
@@ -0,0 +1,312 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
+
+use swimos_model::{Item, Text, Value};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod tests;
+
+/// Identifies the remote to which an event is being sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteEndpoint {
+    /// The routing ID of the remote. This is assigned when the remote connects so it will differ
+    /// for each connection.
+    pub id: Uuid,
+    /// The network address of the remote, if it is known.
+    pub addr: Option<SocketAddr>,
+}
+
+impl RemoteEndpoint {
+    pub fn new(id: Uuid, addr: Option<SocketAddr>) -> Self {
+        RemoteEndpoint { id, addr }
+    }
+}
+
+/// A hook to transform the bodies of the events sent by the lanes of an agent before they are
+/// written to a remote. This can be used to enrich the events or to redact parts of them, per lane
+/// and per remote. Interceptors are evaluated in the write task of the agent runtime so the
+/// bodies of events are only parsed if [`OutgoingInterceptor::selects`] returns `true`. If the body
+/// of a selected event cannot be parsed, the event is dropped rather than being sent unaltered.
+pub trait OutgoingInterceptor: Debug + Send + Sync {
+    /// Determine whether the events for a lane, sent to a remote, should be transformed.
+    ///
+    /// # Arguments
+    /// * `remote` - The remote to which the events are sent.
+    /// * `lane` - The name of the lane.
+    fn selects(&self, remote: &RemoteEndpoint, lane: &str) -> bool;
+
+    /// Transform the body of an event.
+    ///
+    /// # Arguments
+    /// * `remote` - The remote to which the event is sent.
+    /// * `lane` - The name of the lane.
+    /// * `body` - The body of the event.
+    fn intercept(&self, remote: &RemoteEndpoint, lane: &str, body: &mut Value);
+}
+
+/// Error type for selector expressions that could not be parsed.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("'{0}' is not a valid selector.")]
+pub struct InvalidSelector(String);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Attr(Text),
+    Slot(Text),
+}
+
+/// A compiled selector expression that identifies a field within the body of an event. A selector
+/// consists of a sequence of segments separated by `.` where each segment is either the key of a
+/// slot or, prefixed with `@`, the name of an attribute. For example, `user.email` selects the
+/// `email` slot of the record in the `user` slot and `@update.secret` selects the `secret` slot of
+/// the record in the body of the `update` attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    segments: Vec<Segment>,
+}
+
+impl FromStr for Selector {
+    type Err = InvalidSelector;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let segments = s
+            .split('.')
+            .map(|segment| {
+                let (is_attr, name) = match segment.strip_prefix('@') {
+                    Some(name) => (true, name),
+                    None => (false, segment),
+                };
+                if name.is_empty() {
+                    Err(InvalidSelector(s.to_string()))
+                } else if is_attr {
+                    Ok(Segment::Attr(Text::new(name)))
+                } else {
+                    Ok(Segment::Slot(Text::new(name)))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Selector { segments })
+    }
+}
+
+impl Selector {
+    /// Remove the selected field, if it exists.
+    pub fn remove(&self, value: &mut Value) -> bool {
+        if let Some((last, init)) = self.segments.split_last() {
+            match select_mut(value, init) {
+                Some(Value::Record(attrs, items)) => match last {
+                    Segment::Attr(name) => {
+                        let len = attrs.len();
+                        attrs.retain(|attr| attr.name != *name);
+                        attrs.len() != len
+                    }
+                    Segment::Slot(name) => {
+                        let len = items.len();
+                        items.retain(|item| !is_slot(item, name));
+                        items.len() != len
+                    }
+                },
+                _ => false,
+            }
+        } else {
+            false
+        }
+    }
+
+    /// Set the selected field, replacing it if it exists. The field is only added if the record
+    /// containing it exists.
+    pub fn set(&self, value: &mut Value, replacement: Value) -> bool {
+        if let Some((last, init)) = self.segments.split_last() {
+            let target = match select_mut(value, init) {
+                Some(target @ Value::Record(..)) => target,
+                Some(target @ Value::Extant) if init.is_empty() => {
+                    *target = Value::empty_record();
+                    target
+                }
+                _ => return false,
+            };
+            if let Value::Record(attrs, items) = target {
+                match last {
+                    Segment::Attr(name) => {
+                        if let Some(attr) = attrs.iter_mut().find(|attr| attr.name == *name) {
+                            attr.value = replacement;
+                        } else {
+                            attrs.push((name.clone(), replacement).into());
+                        }
+                    }
+                    Segment::Slot(name) => {
+                        if let Some(Item::Slot(_, slot_value)) =
+                            items.iter_mut().find(|item| is_slot(item, name))
+                        {
+                            *slot_value = replacement;
+                        } else {
+                            items.push(Item::Slot(Value::Text(name.clone()), replacement));
+                        }
+                    }
+                }
+            }
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn is_slot(item: &Item, name: &Text) -> bool {
+    matches!(item, Item::Slot(Value::Text(key), _) if key == name)
+}
+
+fn select_mut<'a>(value: &'a mut Value, segments: &[Segment]) -> Option<&'a mut Value> {
+    let mut current = value;
+    for segment in segments {
+        let Value::Record(attrs, items) = current else {
+            return None;
+        };
+        current = match segment {
+            Segment::Attr(name) => &mut attrs.iter_mut().find(|attr| attr.name == *name)?.value,
+            Segment::Slot(name) => match items.iter_mut().find(|item| is_slot(item, name))? {
+                Item::Slot(_, slot_value) => slot_value,
+                Item::ValueItem(_) => return None,
+            },
+        };
+    }
+    Some(current)
+}
+
+/// Restricts the remotes to which an [`InterceptRule`] applies, by the addresses from which they
+/// connect.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RemoteFilter {
+    /// The rule applies to all remotes.
+    #[default]
+    Any,
+    /// The rule only applies to remotes connecting from the specified addresses. It never applies
+    /// to a remote with an unknown address.
+    Only(HashSet<IpAddr>),
+    /// The rule applies to all remotes except those connecting from the specified addresses. It
+    /// always applies to a remote with an unknown address.
+    Except(HashSet<IpAddr>),
+}
+
+impl RemoteFilter {
+    fn matches(&self, remote: &RemoteEndpoint) -> bool {
+        let ip = remote.addr.map(|addr| addr.ip());
+        match self {
+            RemoteFilter::Any => true,
+            RemoteFilter::Only(addrs) => ip.map(|ip| addrs.contains(&ip)).unwrap_or(false),
+            RemoteFilter::Except(addrs) => ip.map(|ip| !addrs.contains(&ip)).unwrap_or(true),
+        }
+    }
+}
+
+/// A transformation to apply to the body of an event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterceptAction {
+    /// Remove the selected field.
+    Redact(Selector),
+    /// Set the selected field to a value.
+    Enrich(Selector, Value),
+}
+
+/// A rule describing the transformations to apply to the events for a lane (or all lanes) sent to
+/// a set of remotes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InterceptRule {
+    lane: Option<Text>,
+    remotes: RemoteFilter,
+    actions: Vec<InterceptAction>,
+}
+
+impl InterceptRule {
+    /// Restrict the rule to a single lane.
+    pub fn for_lane(mut self, lane: &str) -> Self {
+        self.lane = Some(Text::new(lane));
+        self
+    }
+
+    /// Restrict the remotes to which the rule applies.
+    pub fn for_remotes(mut self, remotes: RemoteFilter) -> Self {
+        self.remotes = remotes;
+        self
+    }
+
+    /// Remove a field from the events.
+    pub fn redact(mut self, selector: Selector) -> Self {
+        self.actions.push(InterceptAction::Redact(selector));
+        self
+    }
+
+    /// Set a field in the events.
+    pub fn enrich(mut self, selector: Selector, value: Value) -> Self {
+        self.actions.push(InterceptAction::Enrich(selector, value));
+        self
+    }
+
+    fn matches(&self, remote: &RemoteEndpoint, lane: &str) -> bool {
+        let InterceptRule {
+            lane: rule_lane,
+            remotes,
+            ..
+        } = self;
+        rule_lane
+            .as_ref()
+            .map(|l| l.as_str() == lane)
+            .unwrap_or(true)
+            && remotes.matches(remote)
+    }
+}
+
+/// An [`OutgoingInterceptor`] that applies a list of [`InterceptRule`]s, in order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InterceptRules {
+    rules: Vec<InterceptRule>,
+}
+
+impl InterceptRules {
+    /// Add a rule.
+    pub fn with_rule(mut self, rule: InterceptRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+}
+
+impl OutgoingInterceptor for InterceptRules {
+    fn selects(&self, remote: &RemoteEndpoint, lane: &str) -> bool {
+        self.rules.iter().any(|rule| rule.matches(remote, lane))
+    }
+
+    fn intercept(&self, remote: &RemoteEndpoint, lane: &str, body: &mut Value) {
+        for rule in self.rules.iter().filter(|rule| rule.matches(remote, lane)) {
+            for action in &rule.actions {
+                match action {
+                    InterceptAction::Redact(selector) => {
+                        selector.remove(body);
+                    }
+                    InterceptAction::Enrich(selector, value) => {
+                        selector.set(body, value.clone());
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use swimos_model::{Attr, Item, Value};
+use uuid::Uuid;
+
+use super::{
+    InterceptRule, InterceptRules, InvalidSelector, OutgoingInterceptor, RemoteEndpoint,
+    RemoteFilter, Selector,
+};
+
+const IP1: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+const IP2: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 2));
+const REMOTE1: RemoteEndpoint = RemoteEndpoint {
+    id: Uuid::from_u128(1),
+    addr: Some(SocketAddr::new(IP1, 50000)),
+};
+const REMOTE2: RemoteEndpoint = RemoteEndpoint {
+    id: Uuid::from_u128(2),
+    addr: Some(SocketAddr::new(IP2, 50000)),
+};
+const UNKNOWN_REMOTE: RemoteEndpoint = RemoteEndpoint {
+    id: Uuid::from_u128(3),
+    addr: None,
+};
+const LANE: &str = "lane";
+const OTHER_LANE: &str = "other";
+
+fn selector(s: &str) -> Selector {
+    s.parse().expect("Invalid selector.")
+}
+
+fn user() -> Value {
+    Value::from_vec(vec![
+        Item::slot("name", "bob"),
+        Item::slot(
+            "contact",
+            Value::from_vec(vec![Item::slot("email", "bob@example.com")]),
+        ),
+    ])
+}
+
+#[test]
+fn parse_selectors() {
+    assert!("name".parse::<Selector>().is_ok());
+    assert!("contact.email".parse::<Selector>().is_ok());
+    assert!("@update.secret".parse::<Selector>().is_ok());
+    assert_eq!(
+        "contact..email".parse::<Selector>(),
+        Err(InvalidSelector("contact..email".to_string()))
+    );
+    assert!("".parse::<Selector>().is_err());
+    assert!("@".parse::<Selector>().is_err());
+}
+
+#[test]
+fn remove_slot() {
+    let mut value = user();
+    assert!(selector("contact.email").remove(&mut value));
+    let expected = Value::from_vec(vec![
+        Item::slot("name", "bob"),
+        Item::slot("contact", Value::empty_record()),
+    ]);
+    assert_eq!(value, expected);
+
+    assert!(!selector("contact.email").remove(&mut value));
+    assert!(!selector("name.first").remove(&mut value));
+}
+
+#[test]
+fn remove_in_attribute() {
+    let mut value = Value::Record(
+        vec![Attr::of((
+            "update",
+            Value::from_vec(vec![Item::slot("key", 1)]),
+        ))],
+        vec![Item::slot("secret", "x")],
+    );
+    assert!(selector("secret").remove(&mut value));
+    assert!(selector("@update.key").remove(&mut value));
+    let expected = Value::Record(vec![Attr::of(("update", Value::empty_record()))], vec![]);
+    assert_eq!(value, expected);
+}
+
+#[test]
+fn set_slot() {
+    let mut value = user();
+    assert!(selector("name").set(&mut value, Value::text("alice")));
+    assert!(selector("contact.phone").set(&mut value, Value::from(123)));
+    assert!(!selector("missing.phone").set(&mut value, Value::from(123)));
+    let expected = Value::from_vec(vec![
+        Item::slot("name", "alice"),
+        Item::slot(
+            "contact",
+            Value::from_vec(vec![
+                Item::slot("email", "bob@example.com"),
+                Item::slot("phone", 123),
+            ]),
+        ),
+    ]);
+    assert_eq!(value, expected);
+}
+
+#[test]
+fn set_on_extant() {
+    let mut value = Value::Extant;
+    assert!(selector("source").set(&mut value, Value::text("server")));
+    assert_eq!(value, Value::from_vec(vec![Item::slot("source", "server")]));
+}
+
+#[test]
+fn rules_select_lanes_and_remotes() {
+    let rules = InterceptRules::default()
+        .with_rule(
+            InterceptRule::default()
+                .for_lane(LANE)
+                .for_remotes(RemoteFilter::Except([IP1].into_iter().collect()))
+                .redact(selector("contact")),
+        )
+        .with_rule(
+            InterceptRule::default()
+                .for_remotes(RemoteFilter::Only([IP1].into_iter().collect()))
+                .enrich(selector("trusted"), Value::from(true)),
+        );
+
+    assert!(rules.selects(&REMOTE1, LANE));
+    assert!(rules.selects(&REMOTE1, OTHER_LANE));
+    assert!(rules.selects(&REMOTE2, LANE));
+    assert!(!rules.selects(&REMOTE2, OTHER_LANE));
+
+    let mut value = user();
+    rules.intercept(&REMOTE2, LANE, &mut value);
+    assert_eq!(value, Value::from_vec(vec![Item::slot("name", "bob")]));
+
+    let mut value = user();
+    rules.intercept(&REMOTE1, LANE, &mut value);
+    let mut expected = user();
+    if let Value::Record(_, items) = &mut expected {
+        items.push(Item::slot("trusted", true));
+    }
+    assert_eq!(value, expected);
+}
+
+#[test]
+fn rules_match_remotes_by_address() {
+    let rules = InterceptRules::default()
+        .with_rule(
+            InterceptRule::default()
+                .for_remotes(RemoteFilter::Except([IP1].into_iter().collect()))
+                .redact(selector("contact")),
+        )
+        .with_rule(
+            InterceptRule::default()
+                .for_remotes(RemoteFilter::Only([IP1].into_iter().collect()))
+                .enrich(selector("trusted"), Value::from(true)),
+        );
+
+    // A new connection from the same address is matched in the same way.
+    let reconnected = RemoteEndpoint::new(Uuid::from_u128(4), Some(SocketAddr::new(IP1, 50001)));
+    let mut value = user();
+    rules.intercept(&reconnected, LANE, &mut value);
+    let mut expected = user();
+    if let Value::Record(_, items) = &mut expected {
+        items.push(Item::slot("trusted", true));
+    }
+    assert_eq!(value, expected);
+
+    // Only redactions apply to a remote with an unknown address.
+    let mut value = user();
+    rules.intercept(&UNKNOWN_REMOTE, LANE, &mut value);
+    assert_eq!(value, Value::from_vec(vec![Item::slot("name", "bob")]));
+}
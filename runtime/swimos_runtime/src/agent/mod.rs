@@ -20,11 +20,11 @@ use swimos_api::{
     address::RelativeAddress,
     agent::{
         Agent, AgentConfig, AgentContext, DownlinkKind, HttpLaneRequest, HttpLaneRequestChannel,
-        LaneConfig, LaneKind, StoreKind, WarpLaneKind,
+        InconsistentState, ItemManifest, LaneConfig, LaneKind, StopReason, StoreKind, WarpLaneKind,
     },
     error::{
-        AgentInitError, AgentRuntimeError, AgentTaskError, DownlinkRuntimeError, OpenStoreError,
-        StoreError,
+        AgentInitError, AgentRuntimeError, AgentTaskError, DeclareItemsError, DownlinkRuntimeError,
+        OpenStoreError, StoreError,
     },
     persistence::NodePersistence,
 };
@@ -45,31 +45,49 @@ use std::{
     collections::HashMap,
     fmt::{Debug, Display},
     future::Future,
+    net::SocketAddr,
     num::NonZeroUsize,
+    sync::Arc,
     time::Duration,
 };
 
-use crate::{downlink::DownlinkOptions, Io};
+use crate::{
+    config::{check_buffer_size, check_timeout, ConfigError},
+    downlink::DownlinkOptions,
+    Io,
+};
 
 use self::{
-    reporting::{UplinkReportReader, UplinkReporter},
+    intercept::OutgoingInterceptor,
+    prune::PrunePolicy,
+    recording::EnvelopeRecording,
+    reporting::{
+        DeprecationReportReader, DeprecationReporter, PruneReportReader, PruneReporter,
+        UplinkReportReader, UplinkReporter,
+    },
     store::{StoreInitError, StorePersistence},
     task::{
-        AdHocChannelRequest, AgentInitTask, AgentRuntimeTask, HttpLaneRuntimeSpec, InitTaskConfig,
-        LaneRuntimeSpec, LinksTaskConfig, NodeDescriptor, StoreRuntimeSpec,
+        stop_notification, AdHocChannelRequest, AgentInitTask, AgentRuntimeTask, DeclareItemsSpec,
+        HttpLaneRuntimeSpec, InitTaskConfig, LaneAliasSpec, LaneRuntimeSpec, LinksTaskConfig,
+        NodeDescriptor, StopSignal, StoreRuntimeSpec,
     },
 };
 
 /// Describes the metrics the agent runtime task reports as it runs. These are subscribed to by the
 /// introspection API to report on the internal state of server application.
+pub mod intercept;
+pub mod prune;
+pub mod recording;
 pub mod reporting;
 mod store;
 mod task;
 #[cfg(test)]
 mod tests;
 
+pub use store::{decode_item_kind, MANIFEST_NAME};
+
 use task::AgentRuntimeRequest;
-use tracing::{error, info_span, Instrument};
+use tracing::{error, info_span, warn, Instrument};
 
 /// A message type that can be sent to the agent runtime to request a link to one of its lanes.
 #[derive(Debug)]
@@ -178,11 +196,12 @@ impl DownlinkRequest {
 #[derive(Clone)]
 struct AgentRuntimeContext {
     tx: mpsc::Sender<AgentRuntimeRequest>,
+    stop_signal: StopSignal,
 }
 
 impl AgentRuntimeContext {
-    fn new(tx: mpsc::Sender<AgentRuntimeRequest>) -> Self {
-        AgentRuntimeContext { tx }
+    fn new(tx: mpsc::Sender<AgentRuntimeRequest>, stop_signal: StopSignal) -> Self {
+        AgentRuntimeContext { tx, stop_signal }
     }
 }
 
@@ -293,6 +312,47 @@ impl AgentContext for AgentRuntimeContext {
         }
         .boxed()
     }
+
+    fn declare_items(
+        &self,
+        manifest: ItemManifest,
+    ) -> BoxFuture<'static, Result<(), DeclareItemsError>> {
+        let sender = self.tx.clone();
+        async move {
+            let (tx, rx) = oneshot::channel();
+            sender
+                .send(AgentRuntimeRequest::DeclareItems(DeclareItemsSpec::new(
+                    manifest, tx,
+                )))
+                .await?;
+            rx.await?
+        }
+        .boxed()
+    }
+
+    fn add_lane_alias(
+        &self,
+        alias: &str,
+        name: &str,
+    ) -> BoxFuture<'static, Result<(), AgentRuntimeError>> {
+        let alias = Text::new(alias);
+        let name = Text::new(name);
+        let sender = self.tx.clone();
+        async move {
+            let (tx, rx) = oneshot::channel();
+            sender
+                .send(AgentRuntimeRequest::AddLaneAlias(LaneAliasSpec::new(
+                    alias, name, tx,
+                )))
+                .await?;
+            rx.await?
+        }
+        .boxed()
+    }
+
+    fn stop_signal(&self) -> BoxFuture<'static, StopReason> {
+        self.stop_signal.wait().boxed()
+    }
 }
 
 /// Reasons that a remote connected to an agent runtime task could be disconnected.
@@ -359,6 +419,8 @@ pub enum AgentAttachmentRequest {
         on_attached: Option<trigger::Sender>,
         /// A promise that will be satisfied when the agent runtime task closes the remote.
         completion: promise::Sender<DisconnectionReason>,
+        /// The network address of the remote endpoint, if it is known.
+        remote_addr: Option<SocketAddr>,
     },
 }
 
@@ -396,6 +458,9 @@ impl UplinkReporterRegistration {
 pub struct NodeReporting {
     agent_id: Uuid,
     aggregate_reporter: UplinkReporter,
+    prune_reporter: PruneReporter,
+    deprecation_reporter: DeprecationReporter,
+    recording: Option<EnvelopeRecording>,
     lane_registrations: mpsc::Sender<UplinkReporterRegistration>,
 }
 
@@ -412,10 +477,35 @@ impl NodeReporting {
         NodeReporting {
             agent_id,
             aggregate_reporter,
+            prune_reporter: Default::default(),
+            deprecation_reporter: Default::default(),
+            recording: None,
             lane_registrations,
         }
     }
 
+    /// Allow the envelopes received and sent by the agent to be recorded.
+    pub fn with_recording(mut self, recording: EnvelopeRecording) -> Self {
+        self.recording = Some(recording);
+        self
+    }
+
+    /// The recording of the envelopes received and sent by the agent, if recording is allowed.
+    /// This is disabled until recording is requested.
+    pub fn recording(&self) -> Option<EnvelopeRecording> {
+        self.recording.clone()
+    }
+
+    /// Create a reader for the records of the remotes that are pruned from the agent.
+    pub fn prune_reader(&self) -> PruneReportReader {
+        self.prune_reporter.reader()
+    }
+
+    /// Create a reader for the records of remotes that address lanes by deprecated aliases.
+    pub fn deprecation_reader(&self) -> DeprecationReportReader {
+        self.deprecation_reporter.reader()
+    }
+
     /// Register a new lane for reporting.
     async fn register(&self, name: Text, kind: WarpLaneKind) -> Option<UplinkReporter> {
         let NodeReporting {
@@ -442,6 +532,16 @@ impl NodeReporting {
     fn aggregate(&self) -> UplinkReporter {
         self.aggregate_reporter.clone()
     }
+
+    /// Get a reporter for the remotes that are pruned from the agent.
+    fn prune(&self) -> PruneReporter {
+        self.prune_reporter.clone()
+    }
+
+    /// Get a reporter for the use of deprecated lane aliases.
+    fn deprecation(&self) -> DeprecationReporter {
+        self.deprecation_reporter.clone()
+    }
 }
 
 impl AgentAttachmentRequest {
@@ -463,9 +563,22 @@ impl AgentAttachmentRequest {
             io,
             completion,
             on_attached: Some(on_attached),
+            remote_addr: None,
         }
     }
 
+    /// Attach the network address of the remote endpoint to a two way request. This is used to
+    /// identify the remote to any [outgoing interceptor](`intercept::OutgoingInterceptor`).
+    ///
+    /// # Arguments
+    /// * `addr` - The address of the remote endpoint.
+    pub fn with_remote_addr(mut self, addr: Option<SocketAddr>) -> Self {
+        if let AgentAttachmentRequest::TwoWay { remote_addr, .. } = &mut self {
+            *remote_addr = addr;
+        }
+        self
+    }
+
     /// Constructs a request to open a one way channel to send commands to the agent.
     ///
     /// # Arguments
@@ -495,7 +608,8 @@ pub struct AgentRuntimeConfig {
     /// deregistered.
     pub prune_remote_delay: Duration,
     /// If the clean-shutdown mechanism for the task takes longer than this, it will be
-    /// terminated.
+    /// terminated. This also bounds the time that the runtime will wait for the agent to complete
+    /// after notifying it that it is stopping.
     pub shutdown_timeout: Duration,
     /// If initializing an item from the store takes longer than this, the agent will fail.
     pub item_init_timeout: Duration,
@@ -507,6 +621,109 @@ pub struct AgentRuntimeConfig {
     pub ad_hoc_buffer_size: NonZeroUsize,
     /// The size of the channel used by the agent to pass requests to an HTTP lane.
     pub lane_http_request_channel_size: NonZeroUsize,
+    /// What to do if the store for the agent cannot be opened when it starts.
+    pub store_failure: StoreFailureAction,
+    /// How events for a remote are handled when the remote is not keeping up with the agent.
+    pub uplink_backpressure: UplinkBackpressure,
+    /// When the agent should advise low priority links to back off.
+    pub link_advisory: LinkAdvisoryConfig,
+    /// What to do if the state in the store for the agent is inconsistent with the items that
+    /// the agent declares.
+    pub state_migration: StateMigrationPolicy,
+}
+
+/// How the agent runtime handles events for a remote that cannot keep up with the rate at which
+/// they are generated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UplinkBackpressure {
+    /// Events that are waiting to be written are conflated: events for value lanes collapse to the
+    /// latest value and events for map lanes collapse to the latest operation for each key. This
+    /// bounds the memory used for each remote by the size of the state of the lanes.
+    #[default]
+    Conflate,
+    /// Every event is queued and written, in order, until the queue for an uplink reaches its capacity.
+    /// Once the queue is full, events for the uplink are handled according to the overflow policy.
+    Queue {
+        /// The maximum number of events that will be queued for each uplink.
+        capacity: NonZeroUsize,
+        /// What to do with events for an uplink when its queue is full.
+        overflow: QueueOverflow,
+    },
+}
+
+impl UplinkBackpressure {
+    /// Queue events, in order, up to the specified capacity for each uplink and conflate them after that.
+    pub fn queue(capacity: NonZeroUsize) -> Self {
+        UplinkBackpressure::Queue {
+            capacity,
+            overflow: QueueOverflow::default(),
+        }
+    }
+}
+
+/// What the agent runtime does with an event for an uplink when its queue is full (see
+/// [`UplinkBackpressure::Queue`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum QueueOverflow {
+    /// Events for the uplink are conflated (as with [`UplinkBackpressure::Conflate`]) until the events
+    /// that are already queued have been written.
+    #[default]
+    Conflate,
+    /// The oldest event in the queue for the uplink is discarded. The remote will miss events so this is
+    /// only appropriate where each event is independent of the others.
+    DropOldest,
+}
+
+/// Soft limits at which the agent runtime will advise remotes that hold low priority links to back
+/// off (see [`swimos_api::agent::LinkAdvice`]). The runtime never closes a link itself when these
+/// limits are reached; the advice gives well behaved clients the opportunity to relieve the agent
+/// before it is overwhelmed. Each link is advised at most once for each episode of pressure.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LinkAdvisoryConfig {
+    /// If the agent already has at least this many links when a low priority link is opened, the
+    /// remote will be advised to close the new link.
+    pub link_limit: Option<NonZeroUsize>,
+    /// If at least this many writes are waiting for a remote, low priority uplinks with events
+    /// pending for that remote will be advised to reduce their rate.
+    pub queue_limit: Option<NonZeroUsize>,
+    /// Links with a priority, requested by the remote, at or below this value are low priority.
+    /// Links for which no priority was requested have a priority of 0.
+    pub low_priority: f32,
+}
+
+impl LinkAdvisoryConfig {
+    /// Determine whether a link with the specified priority is low priority.
+    pub fn is_low_priority(&self, prio: f32) -> bool {
+        prio <= self.low_priority
+    }
+}
+
+/// The action to take if a persistence store cannot be opened.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StoreFailureAction {
+    /// Treat the failure as fatal.
+    #[default]
+    Fail,
+    /// Log a warning and continue without persistence (the state of all lanes will be transient).
+    Transient,
+}
+
+/// The action to take if the state in the store for an agent is inconsistent with the persistent
+/// items that the agent declares when it starts (for example, if a lane has been renamed or its
+/// kind has changed). By default, no persisted state is discarded: any inconsistency that cannot be
+/// resolved by renaming an item is fatal.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StateMigrationPolicy {
+    /// Log a warning for each inconsistency and leave the store unaltered.
+    Ignore,
+    /// Move the state of renamed items to their new names and discard the state of items whose kind
+    /// has changed. A warning is logged for any state that is left orphaned. This must be selected
+    /// explicitly as the discarded state cannot be recovered.
+    Migrate,
+    /// Move the state of renamed items to their new names but treat any other inconsistency as fatal,
+    /// failing with a report of all of them.
+    #[default]
+    Fail,
 }
 
 const DEFAULT_BUFFER_SIZE: NonZeroUsize = non_zero_usize!(4096);
@@ -527,10 +744,150 @@ impl Default for AgentRuntimeConfig {
             ad_hoc_output_retry: RetryStrategy::none(),
             ad_hoc_buffer_size: DEFAULT_BUFFER_SIZE,
             lane_http_request_channel_size: DEFAULT_CHANNEL_SIZE,
+            store_failure: StoreFailureAction::Fail,
+            uplink_backpressure: UplinkBackpressure::Conflate,
+            link_advisory: LinkAdvisoryConfig::default(),
+            state_migration: StateMigrationPolicy::Fail,
         }
     }
 }
 
+impl AgentRuntimeConfig {
+    /// Create a builder, starting from the default configuration, that will check the values of
+    /// the parameters when the configuration is built.
+    pub fn builder() -> AgentRuntimeConfigBuilder {
+        AgentRuntimeConfigBuilder::default()
+    }
+
+    /// Check that all of the parameters are within their permitted ranges.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let AgentRuntimeConfig {
+            inactive_timeout,
+            prune_remote_delay,
+            shutdown_timeout,
+            item_init_timeout,
+            ad_hoc_output_timeout,
+            ad_hoc_buffer_size,
+            ..
+        } = self;
+        check_timeout("AgentRuntimeConfig::inactive_timeout", *inactive_timeout)?;
+        check_timeout(
+            "AgentRuntimeConfig::prune_remote_delay",
+            *prune_remote_delay,
+        )?;
+        check_timeout("AgentRuntimeConfig::shutdown_timeout", *shutdown_timeout)?;
+        check_timeout("AgentRuntimeConfig::item_init_timeout", *item_init_timeout)?;
+        check_timeout(
+            "AgentRuntimeConfig::ad_hoc_output_timeout",
+            *ad_hoc_output_timeout,
+        )?;
+        check_buffer_size(
+            "AgentRuntimeConfig::ad_hoc_buffer_size",
+            ad_hoc_buffer_size.get(),
+        )
+    }
+}
+
+/// Builder for [`AgentRuntimeConfig`] that validates the parameters when the configuration is
+/// built. Any parameters that are not set will take their default values.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AgentRuntimeConfigBuilder {
+    config: AgentRuntimeConfig,
+}
+
+impl AgentRuntimeConfigBuilder {
+    /// Set the size of the queue for handling requests to attach remotes to the task.
+    pub fn attachment_queue_size(mut self, size: NonZeroUsize) -> Self {
+        self.config.attachment_queue_size = size;
+        self
+    }
+
+    /// Set the size of the channel used by the server runtime to pass HTTP requests to an agent.
+    pub fn agent_http_request_channel_size(mut self, size: NonZeroUsize) -> Self {
+        self.config.agent_http_request_channel_size = size;
+        self
+    }
+
+    /// Set the period of inactivity after which the agent will stop.
+    pub fn inactive_timeout(mut self, timeout: Duration) -> Self {
+        self.config.inactive_timeout = timeout;
+        self
+    }
+
+    /// Set the period of inactivity after which a remote, with no links, will be deregistered.
+    pub fn prune_remote_delay(mut self, delay: Duration) -> Self {
+        self.config.prune_remote_delay = delay;
+        self
+    }
+
+    /// Set the maximum time allowed for the clean-shutdown mechanism of the task.
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.config.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Set the maximum time allowed to initialize an item from the store.
+    pub fn item_init_timeout(mut self, timeout: Duration) -> Self {
+        self.config.item_init_timeout = timeout;
+        self
+    }
+
+    /// Set the timeout for outgoing channels to send ad hoc commands.
+    pub fn ad_hoc_output_timeout(mut self, timeout: Duration) -> Self {
+        self.config.ad_hoc_output_timeout = timeout;
+        self
+    }
+
+    /// Set the retry strategy for opening outgoing channels for ad hoc commands.
+    pub fn ad_hoc_output_retry(mut self, retry: RetryStrategy) -> Self {
+        self.config.ad_hoc_output_retry = retry;
+        self
+    }
+
+    /// Set the size of the buffer used by the agent to send ad hoc commands to the runtime.
+    pub fn ad_hoc_buffer_size(mut self, size: NonZeroUsize) -> Self {
+        self.config.ad_hoc_buffer_size = size;
+        self
+    }
+
+    /// Set the size of the channel used by the agent to pass requests to an HTTP lane.
+    pub fn lane_http_request_channel_size(mut self, size: NonZeroUsize) -> Self {
+        self.config.lane_http_request_channel_size = size;
+        self
+    }
+
+    /// Set what to do if the store for the agent cannot be opened when it starts.
+    pub fn store_failure(mut self, action: StoreFailureAction) -> Self {
+        self.config.store_failure = action;
+        self
+    }
+
+    /// Set how events for a remote are handled when the remote is not keeping up with the agent.
+    pub fn uplink_backpressure(mut self, backpressure: UplinkBackpressure) -> Self {
+        self.config.uplink_backpressure = backpressure;
+        self
+    }
+
+    /// Set the limits at which low priority links are advised to back off.
+    pub fn link_advisory(mut self, advisory: LinkAdvisoryConfig) -> Self {
+        self.config.link_advisory = advisory;
+        self
+    }
+
+    /// Set what to do if the state in the store for the agent is inconsistent with its items.
+    pub fn state_migration(mut self, policy: StateMigrationPolicy) -> Self {
+        self.config.state_migration = policy;
+        self
+    }
+
+    /// Build the configuration, failing if any of the parameters are out of range.
+    pub fn build(self) -> Result<AgentRuntimeConfig, ConfigError> {
+        let AgentRuntimeConfigBuilder { config } = self;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
 /// Ways in which the agent runtime task can fail.
 #[derive(Debug, Error)]
 pub enum AgentExecError {
@@ -554,6 +911,8 @@ pub enum AgentExecError {
     },
     #[error("Persisting a change to the state of a lane failed: {0}")]
     PersistenceFailure(#[from] StoreError),
+    #[error("The persisted state of the agent is inconsistent with its items: {0}")]
+    InconsistentState(InconsistentState),
 }
 
 /// Descriptor of an agent route.
@@ -612,6 +971,8 @@ pub struct AgentRouteTask<'a, A> {
     agent_config: AgentConfig,
     runtime_config: AgentRuntimeConfig,
     reporting: Option<NodeReporting>,
+    interceptor: Option<Arc<dyn OutgoingInterceptor>>,
+    prune_policy: Option<Arc<dyn PrunePolicy>>,
 }
 
 impl<'a, A: Agent + 'static> AgentRouteTask<'a, A> {
@@ -644,9 +1005,25 @@ impl<'a, A: Agent + 'static> AgentRouteTask<'a, A> {
             agent_config: config.agent_config,
             runtime_config: config.runtime_config,
             reporting,
+            interceptor: None,
+            prune_policy: None,
         }
     }
 
+    /// Transform the bodies of the events sent by the lanes of the agent, before they are
+    /// written to remotes.
+    pub fn with_interceptor(mut self, interceptor: Option<Arc<dyn OutgoingInterceptor>>) -> Self {
+        self.interceptor = interceptor;
+        self
+    }
+
+    /// Customize when remotes with no links to the lanes of the agent are pruned. By default, they
+    /// are pruned as soon as the prune delay has elapsed.
+    pub fn with_prune_policy(mut self, prune_policy: Option<Arc<dyn PrunePolicy>>) -> Self {
+        self.prune_policy = prune_policy;
+        self
+    }
+
     /// Run the agent task without persistence.
     pub fn run_agent(self) -> impl Future<Output = Result<(), AgentExecError>> + Send + 'static {
         let AgentRouteTask {
@@ -661,6 +1038,8 @@ impl<'a, A: Agent + 'static> AgentRouteTask<'a, A> {
             agent_config,
             runtime_config,
             reporting,
+            interceptor,
+            prune_policy,
         } = self;
         let node_uri = route.to_string().into();
         let (runtime_tx, runtime_rx) = mpsc::channel(runtime_config.attachment_queue_size.get());
@@ -682,10 +1061,12 @@ impl<'a, A: Agent + 'static> AgentRouteTask<'a, A> {
                 item_init_timeout: runtime_config.item_init_timeout,
                 external_links: ad_hoc_config,
                 http_lane_channel_size: runtime_config.lane_http_request_channel_size,
+                state_migration: runtime_config.state_migration,
             },
             reporting,
         );
-        let context = Box::new(AgentRuntimeContext::new(runtime_tx));
+        let (stop_notifier, stop_signal) = stop_notification();
+        let context = Box::new(AgentRuntimeContext::new(runtime_tx, stop_signal));
 
         let agent_init = agent.run(route, route_params, agent_config, context);
 
@@ -703,13 +1084,16 @@ impl<'a, A: Agent + 'static> AgentRouteTask<'a, A> {
             let (initial_state, _) = initial_state_result?;
 
             let runtime_task = AgentRuntimeTask::new(
-                NodeDescriptor::new(identity, node_uri),
+                NodeDescriptor::new(identity, node_uri)
+                    .with_interceptor(interceptor)
+                    .with_prune_policy(prune_policy),
                 initial_state,
                 attachment_rx,
                 http_rx,
                 stopping,
                 runtime_config,
-            );
+            )
+            .with_stop_notifier(stop_notifier);
 
             let (runtime_result, agent_result) = join(runtime_task.run(), agent_task).await;
             runtime_result?;
@@ -742,12 +1126,15 @@ impl<'a, A: Agent + 'static> AgentRouteTask<'a, A> {
             agent_config,
             runtime_config,
             reporting,
+            interceptor,
+            prune_policy,
         } = self;
         let node_uri: Text = route.to_string().into();
         let (runtime_tx, runtime_rx) = mpsc::channel(runtime_config.attachment_queue_size.get());
         let (init_tx, init_rx) = trigger::trigger();
 
-        let context = Box::new(AgentRuntimeContext::new(runtime_tx));
+        let (stop_notifier, stop_signal) = stop_notification();
+        let context = Box::new(AgentRuntimeContext::new(runtime_tx, stop_signal));
 
         let agent_init = agent
             .run(route, route_params, agent_config, context)
@@ -762,7 +1149,14 @@ impl<'a, A: Agent + 'static> AgentRouteTask<'a, A> {
         };
 
         async move {
-            let store = store_fut.await?;
+            let store = match store_fut.await {
+                Ok(store) => Some(StorePersistence(store)),
+                Err(error) if runtime_config.store_failure == StoreFailureAction::Transient => {
+                    warn!(id = %identity, route = %node_uri, error = %error, "Failed to open the store for the agent. The state of its lanes will be transient.");
+                    None
+                }
+                Err(error) => return Err(error.into()),
+            };
             let runtime_init_task = AgentInitTask::with_store(
                 identity,
                 runtime_rx,
@@ -773,9 +1167,10 @@ impl<'a, A: Agent + 'static> AgentRouteTask<'a, A> {
                     item_init_timeout: runtime_config.item_init_timeout,
                     external_links: ad_hoc_config,
                     http_lane_channel_size: runtime_config.lane_http_request_channel_size,
+                    state_migration: runtime_config.state_migration,
                 },
                 reporting,
-                StorePersistence(store),
+                store,
             );
 
             let agent_init_task = async move {
@@ -793,7 +1188,9 @@ impl<'a, A: Agent + 'static> AgentRouteTask<'a, A> {
             );
 
             let runtime_task = AgentRuntimeTask::with_store(
-                NodeDescriptor::new(identity, node_uri.clone()),
+                NodeDescriptor::new(identity, node_uri.clone())
+                    .with_interceptor(interceptor)
+                    .with_prune_policy(prune_policy),
                 initial_state,
                 attachment_rx,
                 http_rx,
@@ -801,6 +1198,7 @@ impl<'a, A: Agent + 'static> AgentRouteTask<'a, A> {
                 runtime_config,
                 store_per,
             )
+            .with_stop_notifier(stop_notifier)
             .run()
             .instrument(info_span!("Agent runtime task.", id = %identity, route = %node_uri));
 
@@ -0,0 +1,86 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt::Debug, time::Duration};
+
+use uuid::Uuid;
+
+#[cfg(test)]
+mod tests;
+
+/// Description of a remote that has had no links for the configured prune delay (see
+/// [`crate::agent::AgentRuntimeConfig::prune_remote_delay`]) and is being considered for pruning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleRemote {
+    /// The routing ID of the remote.
+    pub remote_id: Uuid,
+    /// The time since the last link from the remote was closed.
+    pub idle_for: Duration,
+    /// The time since the remote last attempted to link to a lane of the agent, if it ever did.
+    pub since_link_attempt: Option<Duration>,
+}
+
+/// The decision of a [`PrunePolicy`] for an idle remote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneDecision {
+    /// Remove the remote from the agent.
+    Prune,
+    /// Keep the remote and consider it again after the specified delay (if it is still idle).
+    Defer(Duration),
+}
+
+/// A hook to customize when remotes, with no links to the lanes of an agent, are pruned. Policies
+/// are evaluated in the write task of the agent runtime so should not block.
+pub trait PrunePolicy: Debug + Send + Sync {
+    /// Decide whether an idle remote should be pruned.
+    ///
+    /// # Arguments
+    /// * `remote` - Description of the idle remote.
+    fn decide(&self, remote: &IdleRemote) -> PruneDecision;
+}
+
+/// The default policy: prune all remotes as soon as the prune delay has elapsed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AlwaysPrune;
+
+impl PrunePolicy for AlwaysPrune {
+    fn decide(&self, _remote: &IdleRemote) -> PruneDecision {
+        PruneDecision::Prune
+    }
+}
+
+/// Retain remotes that have attempted to link to a lane within a window of time. Remotes that
+/// are relinking frequently (for example, clients that are paging between views) are then not
+/// repeatedly pruned and reattached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepRecentlyLinked {
+    window: Duration,
+}
+
+impl KeepRecentlyLinked {
+    /// # Arguments
+    /// * `window` - Remotes that attempted to link within this window are retained.
+    pub fn new(window: Duration) -> Self {
+        KeepRecentlyLinked { window }
+    }
+}
+
+impl PrunePolicy for KeepRecentlyLinked {
+    fn decide(&self, remote: &IdleRemote) -> PruneDecision {
+        match remote.since_link_attempt {
+            Some(since) if since < self.window => PruneDecision::Defer(self.window - since),
+            _ => PruneDecision::Prune,
+        }
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use super::{AlwaysPrune, IdleRemote, KeepRecentlyLinked, PruneDecision, PrunePolicy};
+
+const REMOTE_ID: Uuid = Uuid::from_u128(84);
+const WINDOW: Duration = Duration::from_secs(30);
+
+fn idle_remote(since_link_attempt: Option<Duration>) -> IdleRemote {
+    IdleRemote {
+        remote_id: REMOTE_ID,
+        idle_for: Duration::from_secs(60),
+        since_link_attempt,
+    }
+}
+
+#[test]
+fn always_prune() {
+    let policy = AlwaysPrune;
+    assert_eq!(policy.decide(&idle_remote(None)), PruneDecision::Prune);
+    assert_eq!(
+        policy.decide(&idle_remote(Some(Duration::ZERO))),
+        PruneDecision::Prune
+    );
+}
+
+#[test]
+fn keep_recently_linked_without_attempts() {
+    let policy = KeepRecentlyLinked::new(WINDOW);
+    assert_eq!(policy.decide(&idle_remote(None)), PruneDecision::Prune);
+}
+
+#[test]
+fn keep_recently_linked_defers_recent_attempts() {
+    let policy = KeepRecentlyLinked::new(WINDOW);
+    assert_eq!(
+        policy.decide(&idle_remote(Some(Duration::from_secs(10)))),
+        PruneDecision::Defer(Duration::from_secs(20))
+    );
+}
+
+#[test]
+fn keep_recently_linked_prunes_old_attempts() {
+    let policy = KeepRecentlyLinked::new(WINDOW);
+    assert_eq!(
+        policy.decide(&idle_remote(Some(WINDOW))),
+        PruneDecision::Prune
+    );
+    assert_eq!(
+        policy.decide(&idle_remote(Some(Duration::from_secs(45)))),
+        PruneDecision::Prune
+    );
+}
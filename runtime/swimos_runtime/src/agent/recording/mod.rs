@@ -0,0 +1,270 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fmt::{Display, Formatter},
+    num::NonZeroUsize,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bytes::{Bytes, BytesMut};
+use swimos_api::address::RelativeAddress;
+use swimos_messages::protocol::{
+    BytesRequestMessage, BytesResponseMessage, Notification, Operation, RequestMessage,
+    ResponseMessage,
+};
+use swimos_model::Text;
+use swimos_remote::{CaptureBuffer, ReconEncoder};
+use swimos_utilities::{encoding::BytesStr, non_zero_usize};
+use thiserror::Error;
+use tokio_util::codec::Encoder;
+use uuid::Uuid;
+
+mod replay;
+#[cfg(test)]
+mod tests;
+
+pub use replay::{EnvelopeReplay, ReplayError, ReplayTiming};
+pub use swimos_remote::CaptureDirection;
+
+/// The default maximum number of envelopes that are retained by the recording for an agent.
+pub const DEFAULT_RECORDING_CAPACITY: NonZeroUsize = non_zero_usize!(1024);
+
+const INCOMING: &str = "in";
+const OUTGOING: &str = "out";
+
+/// Error type for lines of a recording that could not be parsed.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("'{0}' is not a valid recorded envelope.")]
+pub struct InvalidRecord(String);
+
+/// An envelope recorded by an [`EnvelopeRecording`]. A record is written as a single line,
+/// consisting of the time at which it was recorded (in milliseconds since the UNIX epoch), the
+/// direction, the ID of the remote and then the Warp envelope in Recon. For example:
+///
+/// ```text
+/// 1700000000000 in 9a0e66b4-3c8b-4d0c-8a43-2bd3e5e6f0b1 @command(node:"/node",lane:lane) 42
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedEnvelope {
+    /// The time at which the envelope was recorded.
+    pub timestamp: SystemTime,
+    pub direction: CaptureDirection,
+    /// The routing ID of the remote that sent or received the envelope.
+    pub remote_id: Uuid,
+    /// The envelope, in Recon.
+    pub envelope: Text,
+}
+
+impl Display for RecordedEnvelope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let RecordedEnvelope {
+            timestamp,
+            direction,
+            remote_id,
+            envelope,
+        } = self;
+        let millis = timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        write!(f, "{} {} {} {}", millis, direction, remote_id, envelope)
+    }
+}
+
+impl FromStr for RecordedEnvelope {
+    type Err = InvalidRecord;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidRecord(s.to_string());
+        let mut parts = s.trim().splitn(4, ' ');
+        let mut next = || parts.next().ok_or_else(invalid);
+        let millis = next()?.parse::<u64>().map_err(|_| invalid())?;
+        let direction = match next()? {
+            INCOMING => CaptureDirection::Incoming,
+            OUTGOING => CaptureDirection::Outgoing,
+            _ => return Err(invalid()),
+        };
+        let remote_id = Uuid::parse_str(next()?).map_err(|_| invalid())?;
+        let envelope = Text::new(next()?);
+        Ok(RecordedEnvelope {
+            timestamp: UNIX_EPOCH + Duration::from_millis(millis),
+            direction,
+            remote_id,
+            envelope,
+        })
+    }
+}
+
+/// Records the envelopes received and sent by the lanes of an agent, to reproduce issues that
+/// depend on the order in which envelopes were handled. The envelopes are held in a
+/// [`CaptureBuffer`] so the recording is bounded and is disabled when it is created.
+#[derive(Debug, Clone)]
+pub struct EnvelopeRecording {
+    buffer: CaptureBuffer<RecordedEnvelope>,
+}
+
+impl Default for EnvelopeRecording {
+    fn default() -> Self {
+        EnvelopeRecording::new(DEFAULT_RECORDING_CAPACITY)
+    }
+}
+
+impl EnvelopeRecording {
+    /// # Arguments
+    /// * `capacity` - The maximum number of envelopes to retain.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        EnvelopeRecording {
+            buffer: CaptureBuffer::new(capacity),
+        }
+    }
+
+    /// Determine whether envelopes are currently being recorded.
+    pub fn is_enabled(&self) -> bool {
+        self.buffer.is_enabled()
+    }
+
+    /// Start or stop recording envelopes. Envelopes that have already been recorded are retained.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.buffer.set_enabled(enabled);
+    }
+
+    /// Record an envelope received from a remote, if the recording is enabled.
+    pub fn record_request<P, T>(&self, message: &RequestMessage<P, T>)
+    where
+        P: AsRef<str>,
+        T: AsRef<[u8]>,
+    {
+        if self.is_enabled() {
+            let RequestMessage {
+                origin,
+                path,
+                envelope,
+                correlation_id,
+            } = message;
+            let envelope = match envelope {
+                Operation::Link(params) => Operation::Link(*params),
+                Operation::Sync(params) => Operation::Sync(*params),
+                Operation::SyncRange(params, range) => Operation::SyncRange(*params, range.clone()),
+                Operation::Ack => Operation::Ack,
+                Operation::Unlink => Operation::Unlink,
+                Operation::Command(body) => Operation::Command(copy_body(body)),
+            };
+            let message: BytesRequestMessage = RequestMessage {
+                origin: *origin,
+                path: copy_path(path),
+                envelope,
+                correlation_id: *correlation_id,
+            };
+            self.record(CaptureDirection::Incoming, *origin, message);
+        }
+    }
+
+    /// Record an envelope sent to a remote, if the recording is enabled.
+    ///
+    /// # Arguments
+    /// * `remote_id` - The ID of the remote.
+    /// * `message` - The envelope.
+    pub fn record_response<P, T, U>(&self, remote_id: Uuid, message: &ResponseMessage<P, T, U>)
+    where
+        P: AsRef<str>,
+        T: AsRef<[u8]>,
+        U: AsRef<[u8]>,
+    {
+        if self.is_enabled() {
+            let ResponseMessage {
+                origin,
+                path,
+                envelope,
+                correlation_id,
+                command_origin,
+            } = message;
+            let envelope = match envelope {
+                Notification::Linked => Notification::Linked,
+                Notification::Synced => Notification::Synced,
+                Notification::SyncedWith(body) => Notification::SyncedWith(copy_body(body)),
+                Notification::Unlinked(body) => {
+                    Notification::Unlinked(body.as_ref().map(copy_body))
+                }
+                Notification::Event(body) => Notification::Event(copy_body(body)),
+                Notification::Advisory(body) => Notification::Advisory(copy_body(body)),
+            };
+            let message: BytesResponseMessage = ResponseMessage {
+                origin: *origin,
+                path: copy_path(path),
+                envelope,
+                correlation_id: *correlation_id,
+                command_origin: *command_origin,
+            };
+            self.record(CaptureDirection::Outgoing, remote_id, message);
+        }
+    }
+
+    fn record<M>(&self, direction: CaptureDirection, remote_id: Uuid, message: M)
+    where
+        ReconEncoder: Encoder<M>,
+    {
+        self.buffer
+            .record_with(|| make_record(direction, remote_id, message));
+    }
+
+    /// Take a copy of the envelopes that have been recorded, oldest first.
+    pub fn envelopes(&self) -> Vec<RecordedEnvelope> {
+        self.buffer.records()
+    }
+
+    /// Remove the envelopes that have been recorded, oldest first.
+    pub fn take(&self) -> Vec<RecordedEnvelope> {
+        self.buffer.take()
+    }
+
+    /// Discard all of the envelopes that have been recorded.
+    pub fn clear(&self) {
+        self.buffer.clear();
+    }
+}
+
+/// Write a message as a Warp envelope, in Recon, and timestamp it.
+fn make_record<M>(
+    direction: CaptureDirection,
+    remote_id: Uuid,
+    message: M,
+) -> Option<RecordedEnvelope>
+where
+    ReconEncoder: Encoder<M>,
+{
+    let mut buffer = BytesMut::new();
+    ReconEncoder.encode(message, &mut buffer).ok()?;
+    // Each envelope must occupy a single line of the recording. Line breaks can only occur in the
+    // whitespace between the items of an envelope, so they can be replaced safely.
+    let envelope = String::from_utf8_lossy(buffer.as_ref()).replace(['\r', '\n'], " ");
+    Some(RecordedEnvelope {
+        timestamp: SystemTime::now(),
+        direction,
+        remote_id,
+        envelope: Text::from(envelope),
+    })
+}
+
+fn copy_path<P: AsRef<str>>(path: &RelativeAddress<P>) -> RelativeAddress<BytesStr> {
+    RelativeAddress::new(
+        BytesStr::from(path.node.as_ref()),
+        BytesStr::from(path.lane.as_ref()),
+    )
+}
+
+fn copy_body<T: AsRef<[u8]>>(body: &T) -> Bytes {
+    Bytes::copy_from_slice(body.as_ref())
+}
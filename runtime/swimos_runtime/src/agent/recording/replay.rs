@@ -0,0 +1,250 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    num::NonZeroUsize,
+    pin::pin,
+    time::{Duration, SystemTime},
+};
+
+use futures::{future::join, stream::SelectAll, SinkExt, StreamExt};
+use swimos_messages::protocol::{RawRequestMessageEncoder, RawResponseMessageDecoder};
+use swimos_model::Text;
+use swimos_remote::parse_request_envelope;
+use swimos_utilities::{
+    byte_channel::byte_channel,
+    non_zero_usize,
+    trigger::{self, promise},
+};
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio_util::codec::{FramedRead, FramedWrite};
+
+use crate::agent::AgentAttachmentRequest;
+
+use super::{make_record, CaptureDirection, InvalidRecord, RecordedEnvelope};
+
+const REPLAY_BUFFER_SIZE: NonZeroUsize = non_zero_usize!(4096);
+const DEFAULT_QUIET_PERIOD: Duration = Duration::from_millis(100);
+
+/// Controls the intervals between the envelopes sent by an [`EnvelopeReplay`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayTiming {
+    /// Send each envelope as soon as the previous envelope has been sent.
+    #[default]
+    Immediate,
+    /// Preserve the intervals between the envelopes in the recording.
+    Recorded,
+}
+
+/// Errors that can occur when replaying a recording.
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    /// A line of the recording could not be parsed.
+    #[error("Line {line} of the recording is invalid: {error}")]
+    InvalidRecord { line: usize, error: InvalidRecord },
+    /// An incoming envelope in the recording is not a valid request for an agent.
+    #[error("'{0}' is not a valid request envelope.")]
+    InvalidEnvelope(Text),
+    /// The agent stopped before all of the remotes in the recording could be attached to it.
+    #[error("The agent stopped accepting remotes.")]
+    AgentStopped,
+    /// Sending an envelope to the agent failed.
+    #[error("Sending an envelope to the agent failed: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Replays the incoming envelopes of a recording (see [`super::EnvelopeRecording`]) against an
+/// agent, in the order in which they were recorded. A remote is attached to the agent for each
+/// remote in the recording and the envelopes that the agent sends in response are collected so
+/// that they can be compared with the outgoing envelopes of the recording.
+#[derive(Debug, Clone)]
+pub struct EnvelopeReplay {
+    records: Vec<RecordedEnvelope>,
+    timing: ReplayTiming,
+    quiet_period: Duration,
+}
+
+impl EnvelopeReplay {
+    /// # Arguments
+    /// * `records` - The recorded envelopes, oldest first.
+    pub fn new(records: Vec<RecordedEnvelope>) -> Self {
+        EnvelopeReplay {
+            records,
+            timing: ReplayTiming::default(),
+            quiet_period: DEFAULT_QUIET_PERIOD,
+        }
+    }
+
+    /// Parse a recording with one envelope per line (see [`RecordedEnvelope`] for the format).
+    /// Blank lines are ignored.
+    pub fn parse(recording: &str) -> Result<Self, ReplayError> {
+        let records = recording
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                line.parse::<RecordedEnvelope>()
+                    .map_err(|error| ReplayError::InvalidRecord { line: i + 1, error })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(EnvelopeReplay::new(records))
+    }
+
+    /// Set the intervals between the envelopes that are sent to the agent.
+    pub fn with_timing(mut self, timing: ReplayTiming) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Set how long the replay waits for further envelopes from the agent after the last
+    /// envelope has been sent to it.
+    pub fn with_quiet_period(mut self, quiet_period: Duration) -> Self {
+        self.quiet_period = quiet_period;
+        self
+    }
+
+    /// The recorded envelopes.
+    pub fn records(&self) -> &[RecordedEnvelope] {
+        &self.records
+    }
+
+    /// The envelopes that were sent by the agent in the recording.
+    pub fn recorded_outgoing(&self) -> impl Iterator<Item = &RecordedEnvelope> + '_ {
+        self.records
+            .iter()
+            .filter(|record| record.direction == CaptureDirection::Outgoing)
+    }
+
+    /// Replay the incoming envelopes against an agent. This completes when all of the envelopes
+    /// have been sent and the agent has then sent nothing for the quiet period, returning the
+    /// envelopes that the agent sent.
+    ///
+    /// # Arguments
+    /// * `attachments` - Channel to attach remotes to the agent.
+    pub async fn run(
+        &self,
+        attachments: &mpsc::Sender<AgentAttachmentRequest>,
+    ) -> Result<Vec<RecordedEnvelope>, ReplayError> {
+        let EnvelopeReplay {
+            records,
+            timing,
+            quiet_period,
+        } = self;
+
+        let mut requests = vec![];
+        for record in records
+            .iter()
+            .filter(|record| record.direction == CaptureDirection::Incoming)
+        {
+            match parse_request_envelope(record.remote_id, record.envelope.as_str()) {
+                Ok(Some(request)) => requests.push((record.timestamp, request)),
+                _ => return Err(ReplayError::InvalidEnvelope(record.envelope.clone())),
+            }
+        }
+
+        let mut writers = HashMap::new();
+        let mut readers = SelectAll::new();
+        let mut completions = vec![];
+        for (_, request) in &requests {
+            let remote_id = request.origin;
+            if writers.contains_key(&remote_id) {
+                continue;
+            }
+            let (in_tx, in_rx) = byte_channel(REPLAY_BUFFER_SIZE);
+            let (out_tx, out_rx) = byte_channel(REPLAY_BUFFER_SIZE);
+            let (completion_tx, completion_rx) = promise::promise();
+            let (attached_tx, attached_rx) = trigger::trigger();
+            let attach = AgentAttachmentRequest::with_confirmation(
+                remote_id,
+                (out_tx, in_rx),
+                completion_tx,
+                attached_tx,
+            );
+            if attachments.send(attach).await.is_err() || attached_rx.await.is_err() {
+                return Err(ReplayError::AgentStopped);
+            }
+            writers.insert(remote_id, FramedWrite::new(in_tx, RawRequestMessageEncoder));
+            readers.push(
+                FramedRead::new(out_rx, RawResponseMessageDecoder)
+                    .map(move |result| (remote_id, result)),
+            );
+            completions.push(completion_rx);
+        }
+
+        let (done_tx, done_rx) = trigger::trigger();
+        let send = async move {
+            let mut previous: Option<SystemTime> = None;
+            for (timestamp, request) in requests {
+                if *timing == ReplayTiming::Recorded {
+                    if let Some(gap) = previous.and_then(|prev| timestamp.duration_since(prev).ok())
+                    {
+                        tokio::time::sleep(gap).await;
+                    }
+                    previous = Some(timestamp);
+                }
+                if let Some(writer) = writers.get_mut(&request.origin) {
+                    writer.send(request).await?;
+                }
+            }
+            done_tx.trigger();
+            // The remotes are kept open until the responses have been collected.
+            Ok::<_, ReplayError>(writers)
+        };
+
+        let receive = async move {
+            let mut done_rx = pin!(done_rx);
+            let mut done = false;
+            let mut outgoing = vec![];
+            loop {
+                let next = if done {
+                    match tokio::time::timeout(*quiet_period, readers.next()).await {
+                        Ok(next) => next,
+                        Err(_) => break,
+                    }
+                } else {
+                    tokio::select! {
+                        next = readers.next() => next,
+                        _ = &mut done_rx => {
+                            done = true;
+                            continue;
+                        }
+                    }
+                };
+                match next {
+                    Some((remote_id, Ok(message))) => {
+                        outgoing.extend(make_record(
+                            CaptureDirection::Outgoing,
+                            remote_id,
+                            message,
+                        ));
+                    }
+                    Some((_, Err(_))) => {}
+                    None if done => break,
+                    None => {
+                        (&mut done_rx).await.ok();
+                        done = true;
+                    }
+                }
+            }
+            outgoing
+        };
+
+        let (send_result, outgoing) = join(send, receive).await;
+        drop(send_result?);
+        drop(completions);
+        Ok(outgoing)
+    }
+}
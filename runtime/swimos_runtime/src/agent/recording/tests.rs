@@ -0,0 +1,214 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use futures::{future::join, SinkExt, StreamExt};
+use swimos_api::address::RelativeAddress;
+use swimos_messages::protocol::{
+    BytesResponseMessage, Notification, Operation, RawRequestMessageDecoder,
+    RawResponseMessageEncoder, RequestMessage, ResponseMessage,
+};
+use swimos_model::Text;
+use swimos_utilities::non_zero_usize;
+use tokio::sync::mpsc;
+use tokio_util::codec::{FramedRead, FramedWrite};
+use uuid::Uuid;
+
+use crate::agent::AgentAttachmentRequest;
+
+use super::{CaptureDirection, EnvelopeRecording, EnvelopeReplay, RecordedEnvelope, ReplayError};
+
+const AGENT_ID: Uuid = Uuid::from_u128(1);
+const RID: Uuid = Uuid::from_u128(2);
+const NODE: &str = "/node";
+const LANE: &str = "lane";
+
+fn command(body: &str) -> RequestMessage<&str, &[u8]> {
+    RequestMessage::command(RID, RelativeAddress::new(NODE, LANE), body.as_bytes())
+}
+
+fn event(body: &str) -> ResponseMessage<&str, &[u8], &[u8]> {
+    ResponseMessage::event(AGENT_ID, RelativeAddress::new(NODE, LANE), body.as_bytes())
+}
+
+#[test]
+fn recording_disabled_initially() {
+    let recording = EnvelopeRecording::default();
+    assert!(!recording.is_enabled());
+    recording.record_request(&command("1"));
+    recording.record_response(RID, &event("1"));
+    assert!(recording.envelopes().is_empty());
+}
+
+#[test]
+fn record_envelopes() {
+    let recording = EnvelopeRecording::default();
+    recording.set_enabled(true);
+    recording.record_request(&command("1"));
+    recording.record_response(RID, &event("2"));
+
+    let records = recording.envelopes();
+    assert_eq!(records.len(), 2);
+
+    let first = &records[0];
+    assert_eq!(first.direction, CaptureDirection::Incoming);
+    assert_eq!(first.remote_id, RID);
+    assert_eq!(first.envelope, "@command(node:\"/node\",lane:lane) 1");
+
+    let second = &records[1];
+    assert_eq!(second.direction, CaptureDirection::Outgoing);
+    assert_eq!(second.remote_id, RID);
+    assert_eq!(second.envelope, "@event(node:\"/node\",lane:lane) 2");
+}
+
+#[test]
+fn recording_evicts_oldest() {
+    let recording = EnvelopeRecording::new(non_zero_usize!(2));
+    recording.set_enabled(true);
+    for i in 0..4 {
+        recording.record_request(&command(&i.to_string()));
+    }
+    let envelopes = recording
+        .envelopes()
+        .into_iter()
+        .map(|record| record.envelope)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        envelopes,
+        vec![
+            Text::new("@command(node:\"/node\",lane:lane) 2"),
+            Text::new("@command(node:\"/node\",lane:lane) 3"),
+        ]
+    );
+}
+
+#[test]
+fn take_and_clear_recording() {
+    let recording = EnvelopeRecording::default();
+    recording.set_enabled(true);
+    recording.record_request(&command("1"));
+    assert_eq!(recording.take().len(), 1);
+    assert!(recording.envelopes().is_empty());
+
+    recording.record_request(&command("2"));
+    recording.clear();
+    assert!(recording.envelopes().is_empty());
+}
+
+#[test]
+fn record_round_trip() {
+    let record = RecordedEnvelope {
+        timestamp: UNIX_EPOCH + Duration::from_millis(1700000000123),
+        direction: CaptureDirection::Outgoing,
+        remote_id: RID,
+        envelope: Text::new("@event(node:\"/node\",lane:lane) {a: 1, b: 2}"),
+    };
+    let line = record.to_string();
+    assert_eq!(
+        line,
+        "1700000000123 out 00000000-0000-0000-0000-000000000002 @event(node:\"/node\",lane:lane) {a: 1, b: 2}"
+    );
+    assert_eq!(line.parse::<RecordedEnvelope>(), Ok(record));
+}
+
+#[test]
+fn parse_invalid_recording() {
+    let recording = "1700000000000 in 00000000-0000-0000-0000-000000000002 @command(node:\"/node\",lane:lane) 1\n\n1700000000001 sideways 00000000-0000-0000-0000-000000000002 @command(node:\"/node\",lane:lane) 2";
+    match EnvelopeReplay::parse(recording) {
+        Err(ReplayError::InvalidRecord { line, .. }) => assert_eq!(line, 3),
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+}
+
+#[tokio::test]
+async fn replay_invalid_envelope() {
+    let replay = EnvelopeReplay::parse(
+        "1700000000000 in 00000000-0000-0000-0000-000000000002 @event(node:\"/node\",lane:lane) 1",
+    )
+    .expect("Invalid recording.");
+    let (tx, _rx) = mpsc::channel(8);
+    assert!(matches!(
+        replay.run(&tx).await,
+        Err(ReplayError::InvalidEnvelope(_))
+    ));
+}
+
+/// Attaches a single remote and echoes the bodies of the commands that it sends back as events.
+async fn echo_agent(mut rx: mpsc::Receiver<AgentAttachmentRequest>) {
+    let Some(AgentAttachmentRequest::TwoWay {
+        io: (tx, rx),
+        on_attached,
+        ..
+    }) = rx.recv().await
+    else {
+        panic!("Expected a remote.");
+    };
+    if let Some(on_attached) = on_attached {
+        on_attached.trigger();
+    }
+    let mut reader = FramedRead::new(rx, RawRequestMessageDecoder);
+    let mut writer = FramedWrite::new(tx, RawResponseMessageEncoder);
+    while let Some(Ok(RequestMessage { path, envelope, .. })) = reader.next().await {
+        if let Operation::Command(body) = envelope {
+            let response: BytesResponseMessage = ResponseMessage {
+                origin: AGENT_ID,
+                path,
+                envelope: Notification::Event(body),
+                correlation_id: None,
+                command_origin: None,
+            };
+            assert!(writer.send(response).await.is_ok());
+        }
+    }
+}
+
+#[tokio::test]
+async fn replay_against_agent() {
+    let recording = EnvelopeRecording::default();
+    recording.set_enabled(true);
+    recording.record_request(&command("1"));
+    recording.record_response(RID, &event("1"));
+    recording.record_request(&command("2"));
+    recording.record_response(RID, &event("2"));
+
+    let lines = recording
+        .envelopes()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let replay = EnvelopeReplay::parse(&lines)
+        .expect("Invalid recording.")
+        .with_quiet_period(Duration::from_millis(50));
+
+    let (tx, rx) = mpsc::channel(8);
+    let (result, _) = tokio::time::timeout(
+        Duration::from_secs(5),
+        join(replay.run(&tx), echo_agent(rx)),
+    )
+    .await
+    .expect("Test timed out.");
+
+    let replayed = result.expect("Replay failed.");
+    let expected = replay
+        .recorded_outgoing()
+        .map(|record| (record.remote_id, record.envelope.clone()))
+        .collect::<Vec<_>>();
+    let actual = replayed
+        .into_iter()
+        .map(|record| (record.remote_id, record.envelope))
+        .collect::<Vec<_>>();
+    assert_eq!(actual, expected);
+}
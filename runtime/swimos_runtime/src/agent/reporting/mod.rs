@@ -13,14 +13,18 @@
 // limitations under the License.
 
 use std::{
+    collections::VecDeque,
     sync::{
         atomic::{AtomicU64, Ordering},
         Arc, Weak,
     },
-    time::Duration,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+use parking_lot::Mutex;
 use swimos_meta::WarpUplinkPulse;
+use swimos_model::Text;
+use uuid::Uuid;
 
 #[cfg(test)]
 mod tests;
@@ -32,11 +36,14 @@ mod tests;
 /// * The number of active uplinks for the lane/agent.
 /// * The number of events that were generated by the lane/agent since the last snapshot was taken.
 /// * The number of commands received by the lane/agent since the last snapshot was taken.
+/// * The time (in milliseconds since the UNIX epoch) at which the lane/agent last generated an
+///   event (0 if no events have been generated).
 #[derive(Default, Debug)]
 struct UplinkCounters {
     link_count: AtomicU64,
     event_count: AtomicU64,
     command_count: AtomicU64,
+    last_event: AtomicU64,
 }
 
 /// A snapshot taken from the uplink counters.
@@ -115,10 +122,23 @@ fn snapshot_value(n: &AtomicU64) -> u64 {
     }
 }
 
+fn epoch_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|t| u64::try_from(t.as_millis()).unwrap_or(u64::MAX))
+        .unwrap_or_default()
+}
+
 impl UplinkReporter {
-    /// Increment the count of events by the given amount (this will saturate).
+    /// Increment the count of events by the given amount (this will saturate). If any events are
+    /// counted, the time of the most recent event is also updated.
     pub fn count_events(&self, n: u64) {
-        saturating_add(&self.counters.event_count, n)
+        saturating_add(&self.counters.event_count, n);
+        if n > 0 {
+            self.counters
+                .last_event
+                .fetch_max(epoch_millis(), Ordering::Relaxed);
+        }
     }
 
     /// Increment the count of commands by the given amount (this will saturate).
@@ -145,6 +165,24 @@ impl UplinkReportReader {
         self.counters.upgrade().is_some()
     }
 
+    /// Read the number of active uplinks without consuming the counts of events and commands. If
+    /// the reporter to which this reader is attached has been dropped, this will return nothing.
+    pub fn link_count(&self) -> Option<u64> {
+        self.counters
+            .upgrade()
+            .map(|counters| counters.link_count.load(Ordering::Relaxed))
+    }
+
+    /// Read the time (in milliseconds since the UNIX epoch) at which the most recent event was
+    /// counted. If no events have been counted or the reporter to which this reader is attached
+    /// has been dropped, this will return nothing.
+    pub fn last_event(&self) -> Option<u64> {
+        self.counters
+            .upgrade()
+            .map(|counters| counters.last_event.load(Ordering::Relaxed))
+            .filter(|t| *t > 0)
+    }
+
     /// Create a snapshot of the current state. This will read the value of the number of uplinks
     /// and consume the counts of events and commands (setting the new values back to 0). If
     /// the reporter to which this reader is attached has been dropped, this will return nothing.
@@ -161,3 +199,159 @@ impl UplinkReportReader {
         })
     }
 }
+
+/// The maximum number of prune events that are retained until they are consumed by a reader. If
+/// more events occur, the oldest are discarded.
+const MAX_PRUNE_EVENTS: usize = 256;
+
+/// Record of a remote that was pruned from an agent as it had no links.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PruneEvent {
+    /// The routing ID of the remote.
+    pub remote_id: Uuid,
+    /// The time for which the remote had no links before it was pruned.
+    pub idle_for: Duration,
+}
+
+#[derive(Default, Debug)]
+struct PruneRecords {
+    pruned_count: AtomicU64,
+    deferred_count: AtomicU64,
+    events: Mutex<VecDeque<PruneEvent>>,
+}
+
+/// A snapshot taken from the prune counters.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PruneSnapshot {
+    /// The total number of remotes that have been pruned.
+    pub pruned_count: u64,
+    /// The total number of times the pruning of a remote was deferred by the prune policy.
+    pub deferred_count: u64,
+}
+
+/// Allows an agent to report the pruning of idle remotes to the metrics reporting system.
+#[derive(Default, Debug, Clone)]
+pub struct PruneReporter {
+    records: Arc<PruneRecords>,
+}
+
+/// A consumer attached to a [`PruneReporter`]. When the corresponding reporter is dropped, this
+/// will become invalidated and all future calls will return nothing.
+#[derive(Default, Debug, Clone)]
+pub struct PruneReportReader {
+    records: Weak<PruneRecords>,
+}
+
+impl PruneReporter {
+    /// Record that a remote was pruned.
+    pub fn report_pruned(&self, event: PruneEvent) {
+        let PruneRecords {
+            pruned_count,
+            events,
+            ..
+        } = &*self.records;
+        saturating_add(pruned_count, 1);
+        let mut guard = events.lock();
+        if guard.len() == MAX_PRUNE_EVENTS {
+            guard.pop_front();
+        }
+        guard.push_back(event);
+    }
+
+    /// Record that the pruning of a remote was deferred.
+    pub fn report_deferred(&self) {
+        saturating_add(&self.records.deferred_count, 1);
+    }
+
+    /// Create a reader attached to this reporter.
+    pub fn reader(&self) -> PruneReportReader {
+        PruneReportReader {
+            records: Arc::downgrade(&self.records),
+        }
+    }
+}
+
+impl PruneReportReader {
+    /// This will return true if and only if the corresponding reporter has not yet been dropped.
+    pub fn is_active(&self) -> bool {
+        self.records.upgrade().is_some()
+    }
+
+    /// Read the prune counters. If the reporter to which this reader is attached has been dropped,
+    /// this will return nothing.
+    pub fn snapshot(&self) -> Option<PruneSnapshot> {
+        self.records.upgrade().map(|records| PruneSnapshot {
+            pruned_count: records.pruned_count.load(Ordering::Relaxed),
+            deferred_count: records.deferred_count.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Consume the events that have been reported since the last call. If the reporter to which
+    /// this reader is attached has been dropped, this will return nothing.
+    pub fn take_events(&self) -> Option<Vec<PruneEvent>> {
+        self.records
+            .upgrade()
+            .map(|records| records.events.lock().drain(..).collect())
+    }
+}
+
+/// The maximum number of deprecation events that are retained until they are consumed by a reader.
+/// If more events occur, the oldest are discarded.
+const MAX_DEPRECATION_EVENTS: usize = 256;
+
+/// Record of a remote addressing a lane of an agent by a deprecated alias.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DeprecationEvent {
+    /// The routing ID of the remote.
+    pub remote_id: Uuid,
+    /// The alias that the remote used.
+    pub alias: Text,
+    /// The name of the lane to which the alias refers.
+    pub lane: Text,
+}
+
+/// Allows an agent to report the use of deprecated lane aliases to the metrics reporting system.
+#[derive(Default, Debug, Clone)]
+pub struct DeprecationReporter {
+    events: Arc<Mutex<VecDeque<DeprecationEvent>>>,
+}
+
+/// A consumer attached to a [`DeprecationReporter`]. When the corresponding reporter is dropped,
+/// this will become invalidated and all future calls will return nothing.
+#[derive(Default, Debug, Clone)]
+pub struct DeprecationReportReader {
+    events: Weak<Mutex<VecDeque<DeprecationEvent>>>,
+}
+
+impl DeprecationReporter {
+    /// Record that a remote used a deprecated alias.
+    pub fn report_deprecated(&self, event: DeprecationEvent) {
+        let mut guard = self.events.lock();
+        if guard.len() == MAX_DEPRECATION_EVENTS {
+            guard.pop_front();
+        }
+        guard.push_back(event);
+    }
+
+    /// Create a reader attached to this reporter.
+    pub fn reader(&self) -> DeprecationReportReader {
+        DeprecationReportReader {
+            events: Arc::downgrade(&self.events),
+        }
+    }
+}
+
+impl DeprecationReportReader {
+    /// This will return true if and only if the corresponding reporter has not yet been dropped.
+    pub fn is_active(&self) -> bool {
+        self.events.upgrade().is_some()
+    }
+
+    /// Consume the events that have been reported since the last call. If the reporter to which
+    /// this reader is attached has been dropped, this will return nothing.
+    pub fn take_events(&self) -> Option<Vec<DeprecationEvent>> {
+        self.events
+            .upgrade()
+            .map(|events| events.lock().drain(..).collect())
+    }
+}
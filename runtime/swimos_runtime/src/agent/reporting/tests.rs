@@ -12,11 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use swimos_meta::WarpUplinkPulse;
 
-use super::{UplinkReporter, UplinkSnapshot};
+use swimos_model::Text;
+use uuid::Uuid;
+
+use super::{
+    DeprecationEvent, DeprecationReporter, PruneEvent, PruneReporter, PruneSnapshot,
+    UplinkReporter, UplinkSnapshot, MAX_DEPRECATION_EVENTS, MAX_PRUNE_EVENTS,
+};
 
 #[test]
 fn empty_snapshot() {
@@ -130,6 +136,55 @@ fn set_uplinks() {
     );
 }
 
+#[test]
+fn link_count_does_not_consume() {
+    let reporter = UplinkReporter::default();
+    let reader = reporter.reader();
+
+    reporter.count_events(2);
+    reporter.set_uplinks(3);
+
+    assert_eq!(reader.link_count(), Some(3));
+
+    let snapshot = reader.snapshot();
+    assert_eq!(
+        snapshot,
+        Some(UplinkSnapshot {
+            link_count: 3,
+            event_count: 2,
+            command_count: 0
+        })
+    );
+
+    drop(reporter);
+    assert!(reader.link_count().is_none());
+}
+
+#[test]
+fn last_event_time() {
+    let reporter = UplinkReporter::default();
+    let reader = reporter.reader();
+
+    reporter.count_commands(1);
+    reporter.count_events(0);
+    assert!(reader.last_event().is_none());
+
+    let before = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Clock before epoch.")
+        .as_millis() as u64;
+    reporter.count_events(2);
+    let last_event = reader.last_event().expect("Expected a time.");
+    assert!(last_event >= before);
+
+    // The time is not consumed when a snapshot is taken.
+    assert!(reader.snapshot().is_some());
+    assert!(reader.last_event().is_some_and(|t| t >= last_event));
+
+    drop(reporter);
+    assert!(reader.last_event().is_none());
+}
+
 #[test]
 fn snapshot_resets_command_event_counts() {
     let reporter = UplinkReporter::default();
@@ -275,3 +330,109 @@ fn sub_microsecond_duration_saturates_rates() {
     assert_eq!(command_count, 67);
     assert_eq!(command_rate, u64::MAX);
 }
+
+fn prune_event(n: u128) -> PruneEvent {
+    PruneEvent {
+        remote_id: Uuid::from_u128(n),
+        idle_for: Duration::from_secs(1),
+    }
+}
+
+#[test]
+fn report_prune_events() {
+    let reporter = PruneReporter::default();
+    let reader = reporter.reader();
+
+    reporter.report_pruned(prune_event(1));
+    reporter.report_deferred();
+    reporter.report_pruned(prune_event(2));
+
+    assert_eq!(
+        reader.snapshot(),
+        Some(PruneSnapshot {
+            pruned_count: 2,
+            deferred_count: 1
+        })
+    );
+    assert_eq!(
+        reader.take_events(),
+        Some(vec![prune_event(1), prune_event(2)])
+    );
+    assert_eq!(reader.take_events(), Some(vec![]));
+}
+
+#[test]
+fn prune_events_bounded() {
+    let reporter = PruneReporter::default();
+    let reader = reporter.reader();
+
+    let n = MAX_PRUNE_EVENTS as u128;
+    for i in 0..(n + 2) {
+        reporter.report_pruned(prune_event(i));
+    }
+
+    let events = reader.take_events().expect("Reporter dropped.");
+    let expected = (2..(n + 2)).map(prune_event).collect::<Vec<_>>();
+    assert_eq!(events, expected);
+}
+
+#[test]
+fn drop_prune_reporter() {
+    let reporter = PruneReporter::default();
+    let reader = reporter.reader();
+    assert!(reader.is_active());
+
+    drop(reporter);
+    assert!(!reader.is_active());
+    assert!(reader.snapshot().is_none());
+    assert!(reader.take_events().is_none());
+}
+
+fn deprecation_event(n: u128) -> DeprecationEvent {
+    DeprecationEvent {
+        remote_id: Uuid::from_u128(n),
+        alias: Text::new("old"),
+        lane: Text::new("new"),
+    }
+}
+
+#[test]
+fn report_deprecation_events() {
+    let reporter = DeprecationReporter::default();
+    let reader = reporter.reader();
+
+    reporter.report_deprecated(deprecation_event(1));
+    reporter.report_deprecated(deprecation_event(2));
+
+    assert_eq!(
+        reader.take_events(),
+        Some(vec![deprecation_event(1), deprecation_event(2)])
+    );
+    assert_eq!(reader.take_events(), Some(vec![]));
+}
+
+#[test]
+fn deprecation_events_bounded() {
+    let reporter = DeprecationReporter::default();
+    let reader = reporter.reader();
+
+    let n = MAX_DEPRECATION_EVENTS as u128;
+    for i in 0..(n + 2) {
+        reporter.report_deprecated(deprecation_event(i));
+    }
+
+    let events = reader.take_events().expect("Reporter dropped.");
+    let expected = (2..(n + 2)).map(deprecation_event).collect::<Vec<_>>();
+    assert_eq!(events, expected);
+}
+
+#[test]
+fn drop_deprecation_reporter() {
+    let reporter = DeprecationReporter::default();
+    let reader = reporter.reader();
+    assert!(reader.is_active());
+
+    drop(reporter);
+    assert!(!reader.is_active());
+    assert!(reader.take_events().is_none());
+}
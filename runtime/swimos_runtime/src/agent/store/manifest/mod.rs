@@ -0,0 +1,280 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+use bytes::BytesMut;
+use swimos_api::{
+    agent::{
+        InconsistentState, ItemManifest, ItemStateKind, StateInconsistency, StoreKind, WarpLaneKind,
+    },
+    error::StoreError,
+};
+use swimos_recon::{parser::parse_recognize, print_recon_compact};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::agent::StateMigrationPolicy;
+
+use super::AgentPersistence;
+
+#[cfg(test)]
+mod tests;
+
+/// The name under which the kinds of the persistent items of an agent are recorded in its store. No
+/// item of an agent may have this name.
+pub const MANIFEST_NAME: &str = "$manifest";
+
+const LANE_SUFFIX: &str = "Lane";
+const STORE_SUFFIX: &str = "Store";
+
+fn encode_kind(kind: ItemStateKind) -> String {
+    match kind {
+        ItemStateKind::Lane(kind) => format!("{}{}", kind.as_ref(), LANE_SUFFIX),
+        ItemStateKind::Store(kind) => format!("{}{}", kind.as_ref(), STORE_SUFFIX),
+    }
+}
+
+/// Decode the kind of an item from its tag in the manifest.
+pub fn decode_item_kind(tag: &str) -> Option<ItemStateKind> {
+    if let Some(kind) = tag.strip_suffix(LANE_SUFFIX) {
+        WarpLaneKind::from_str(kind).ok().map(ItemStateKind::Lane)
+    } else if let Some(kind) = tag.strip_suffix(STORE_SUFFIX) {
+        StoreKind::from_str(kind).ok().map(ItemStateKind::Store)
+    } else {
+        None
+    }
+}
+
+/// Errors that can occur when checking the persistent items of an agent against its store.
+#[derive(Debug, Error)]
+pub enum ManifestError {
+    #[error("Failed to read or update the manifest of the agent items: {0}")]
+    Store(#[from] StoreError),
+    #[error("The persisted state of the agent is inconsistent with its items: {0}")]
+    Inconsistent(InconsistentState),
+}
+
+fn read_manifest<S: AgentPersistence>(
+    store: &S,
+    id: S::StoreId,
+) -> Result<Option<HashMap<String, ItemStateKind>>, StoreError> {
+    let mut buffer = BytesMut::new();
+    if store.get_value(id, &mut buffer)?.is_none() {
+        return Ok(None);
+    }
+    let body = std::str::from_utf8(buffer.as_ref())
+        .map_err(|err| StoreError::Decoding(err.to_string()))?;
+    let tags = parse_recognize::<HashMap<String, String>>(body, false)
+        .map_err(|err| StoreError::Decoding(err.to_string()))?;
+    tags.into_iter()
+        .map(|(name, tag)| match decode_item_kind(&tag) {
+            Some(kind) => Ok((name, kind)),
+            None => Err(StoreError::Decoding(format!(
+                "Invalid kind '{}' for item '{}'.",
+                tag, name
+            ))),
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+fn write_manifest<S: AgentPersistence>(
+    store: &mut S,
+    id: S::StoreId,
+    items: &HashMap<String, ItemStateKind>,
+) -> Result<(), StoreError> {
+    let tags = items
+        .iter()
+        .map(|(name, kind)| (name.clone(), encode_kind(*kind)))
+        .collect::<HashMap<_, _>>();
+    let body = format!("{}", print_recon_compact(&tags));
+    store.put_value(id, body.as_bytes())
+}
+
+/// A change to the state in a store, required to make it consistent with the items of the agent.
+#[derive(Debug, PartialEq, Eq)]
+enum Migration {
+    /// Move the state of an item that has been renamed.
+    Rename {
+        previous_name: String,
+        name: String,
+        map_like: bool,
+    },
+    /// Discard the state of an item that can no longer be restored.
+    Discard { name: String, map_like: bool },
+}
+
+/// The result of comparing the items declared by an agent with those recorded in its store.
+#[derive(Debug, Default)]
+struct MigrationPlan {
+    migrations: Vec<Migration>,
+    inconsistencies: Vec<StateInconsistency>,
+    manifest: HashMap<String, ItemStateKind>,
+}
+
+/// Compare the persistent items declared by an agent against those recorded in its store and
+/// determine how the state in the store must be changed.
+fn plan_migration(
+    stored: HashMap<String, ItemStateKind>,
+    declared: ItemManifest,
+    policy: StateMigrationPolicy,
+) -> MigrationPlan {
+    let ItemManifest { items, renames } = declared;
+    let mut remaining = stored;
+    let mut plan = MigrationPlan::default();
+    let mut conflicted = HashSet::new();
+
+    let mut renames = renames.into_iter().collect::<Vec<_>>();
+    renames.sort();
+
+    if policy != StateMigrationPolicy::Ignore {
+        for (previous_name, name) in renames {
+            if items.contains_key(&previous_name) {
+                continue;
+            }
+            let (Some(previous), Some(current)) = (remaining.get(&previous_name), items.get(&name))
+            else {
+                continue;
+            };
+            let (previous, current) = (*previous, *current);
+            if remaining.contains_key(&name) {
+                conflicted.insert(previous_name.clone());
+                plan.inconsistencies
+                    .push(StateInconsistency::RenameConflict {
+                        previous_name,
+                        name,
+                    });
+            } else if previous != current {
+                remaining.remove(&previous_name);
+                plan.migrations.push(Migration::Discard {
+                    name: previous_name,
+                    map_like: previous.map_like(),
+                });
+                plan.inconsistencies.push(StateInconsistency::KindChanged {
+                    name,
+                    previous,
+                    current,
+                });
+            } else {
+                remaining.remove(&previous_name);
+                plan.migrations.push(Migration::Rename {
+                    previous_name,
+                    name,
+                    map_like: previous.map_like(),
+                });
+            }
+        }
+    }
+
+    let mut names = remaining.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    for name in names {
+        let previous = remaining[&name];
+        match items.get(&name) {
+            Some(current) if *current != previous => {
+                remaining.remove(&name);
+                if policy != StateMigrationPolicy::Ignore {
+                    plan.migrations.push(Migration::Discard {
+                        name: name.clone(),
+                        map_like: previous.map_like(),
+                    });
+                }
+                plan.inconsistencies.push(StateInconsistency::KindChanged {
+                    name,
+                    previous,
+                    current: *current,
+                });
+            }
+            Some(_) => {}
+            None if !conflicted.contains(&name) => {
+                plan.inconsistencies.push(StateInconsistency::Orphaned {
+                    name,
+                    kind: previous,
+                });
+            }
+            None => {}
+        }
+    }
+
+    // Orphaned state is kept in the manifest so that it will be reported again (and can still be
+    // migrated if a rename is added later).
+    remaining.retain(|name, _| !items.contains_key(name));
+    remaining.extend(items);
+    plan.manifest = remaining;
+    plan
+}
+
+/// Check the persistent items declared by an agent against the items that were recorded in its store
+/// when it last ran, applying the migration policy to any inconsistencies. The manifest in the store
+/// is then updated to record the declared items. If the agent has no store, there is nothing to check.
+///
+/// # Arguments
+/// * `store` - The store for the agent.
+/// * `manifest` - The persistent items declared by the agent.
+/// * `policy` - How to handle inconsistencies between the items and the store.
+pub fn reconcile_items<S: AgentPersistence>(
+    store: &mut S,
+    manifest: ItemManifest,
+    policy: StateMigrationPolicy,
+) -> Result<(), ManifestError> {
+    let manifest_id = match store.store_id(MANIFEST_NAME) {
+        Ok(id) => id,
+        Err(StoreError::NoStoreAvailable) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    let Some(stored) = read_manifest(store, manifest_id)? else {
+        write_manifest(store, manifest_id, &manifest.items)?;
+        return Ok(());
+    };
+
+    let MigrationPlan {
+        migrations,
+        inconsistencies,
+        manifest,
+    } = plan_migration(stored, manifest, policy);
+
+    if policy == StateMigrationPolicy::Fail && !inconsistencies.is_empty() {
+        return Err(ManifestError::Inconsistent(InconsistentState {
+            inconsistencies,
+        }));
+    }
+
+    for migration in migrations {
+        match migration {
+            Migration::Rename {
+                previous_name,
+                name,
+                map_like,
+            } => {
+                info!(previous_name, name, "Moving the state of a renamed item.");
+                let from = store.store_id(&previous_name)?;
+                let to = store.store_id(&name)?;
+                store.move_state(from, to, map_like)?;
+            }
+            Migration::Discard { name, map_like } => {
+                let id = store.store_id(&name)?;
+                store.clear_state(id, map_like)?;
+            }
+        }
+    }
+    for inconsistency in inconsistencies {
+        warn!(%inconsistency, "The persisted state of the agent is inconsistent with its items.");
+    }
+    write_manifest(store, manifest_id, &manifest)?;
+    Ok(())
+}
@@ -0,0 +1,326 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bytes::{BufMut, BytesMut};
+use parking_lot::Mutex;
+use swimos_api::{
+    agent::{
+        InconsistentState, ItemManifest, ItemStateKind, StateInconsistency, StoreKind, WarpLaneKind,
+    },
+    error::StoreError,
+    persistence::{KeyValue, NodePersistence, RangeConsumer, StoreDisabled},
+};
+
+use crate::agent::{
+    store::{AgentPersistence, StorePersistence},
+    StateMigrationPolicy,
+};
+
+use super::{reconcile_items, ManifestError, MANIFEST_NAME};
+
+#[derive(Default)]
+struct MemStore {
+    ids: Mutex<HashMap<String, u64>>,
+    values: HashMap<u64, Vec<u8>>,
+    maps: HashMap<u64, BTreeMap<Vec<u8>, Vec<u8>>>,
+}
+
+struct MemConsumer(
+    std::vec::IntoIter<(Vec<u8>, Vec<u8>)>,
+    Option<(Vec<u8>, Vec<u8>)>,
+);
+
+impl RangeConsumer for MemConsumer {
+    fn consume_next(&mut self) -> Result<Option<KeyValue<'_>>, StoreError> {
+        let MemConsumer(it, current) = self;
+        Ok(it.next().map(|entry| {
+            let (k, v) = current.insert(entry);
+            (k.as_slice(), v.as_slice())
+        }))
+    }
+}
+
+impl NodePersistence for MemStore {
+    type MapCon<'a>
+        = MemConsumer
+    where
+        Self: 'a;
+
+    type LaneId = u64;
+
+    fn id_for(&self, name: &str) -> Result<Self::LaneId, StoreError> {
+        let mut guard = self.ids.lock();
+        let next = guard.len() as u64;
+        Ok(*guard.entry(name.to_string()).or_insert(next))
+    }
+
+    fn get_value(
+        &self,
+        id: Self::LaneId,
+        buffer: &mut BytesMut,
+    ) -> Result<Option<usize>, StoreError> {
+        Ok(self.values.get(&id).map(|value| {
+            buffer.put_slice(value);
+            value.len()
+        }))
+    }
+
+    fn put_value(&mut self, id: Self::LaneId, value: &[u8]) -> Result<(), StoreError> {
+        self.values.insert(id, value.to_vec());
+        Ok(())
+    }
+
+    fn delete_value(&mut self, id: Self::LaneId) -> Result<(), StoreError> {
+        self.values.remove(&id);
+        Ok(())
+    }
+
+    fn update_map(&mut self, id: Self::LaneId, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        self.maps
+            .entry(id)
+            .or_default()
+            .insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn remove_map(&mut self, id: Self::LaneId, key: &[u8]) -> Result<(), StoreError> {
+        if let Some(map) = self.maps.get_mut(&id) {
+            map.remove(key);
+        }
+        Ok(())
+    }
+
+    fn clear_map(&mut self, id: Self::LaneId) -> Result<(), StoreError> {
+        self.maps.remove(&id);
+        Ok(())
+    }
+
+    fn read_map(&self, id: Self::LaneId) -> Result<Self::MapCon<'_>, StoreError> {
+        let entries = self
+            .maps
+            .get(&id)
+            .map(|map| {
+                map.iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        Ok(MemConsumer(entries.into_iter(), None))
+    }
+}
+
+const VALUE_LANE: ItemStateKind = ItemStateKind::Lane(WarpLaneKind::Value);
+const MAP_LANE: ItemStateKind = ItemStateKind::Lane(WarpLaneKind::Map);
+const VALUE_STORE: ItemStateKind = ItemStateKind::Store(StoreKind::Value);
+
+fn manifest(items: &[(&str, ItemStateKind)], renames: &[(&str, &str)]) -> ItemManifest {
+    let mut manifest = ItemManifest::default();
+    for (name, kind) in items {
+        manifest.add_item(*name, *kind);
+    }
+    for (previous_name, name) in renames {
+        manifest.add_rename(*previous_name, *name);
+    }
+    manifest
+}
+
+fn value_of(store: &StorePersistence<MemStore>, name: &str) -> Option<Vec<u8>> {
+    let id = store.store_id(name).expect("No ID.");
+    let mut buffer = BytesMut::new();
+    store
+        .get_value(id, &mut buffer)
+        .expect("Read failed.")
+        .map(|_| buffer.to_vec())
+}
+
+fn map_of(store: &StorePersistence<MemStore>, name: &str) -> BTreeMap<Vec<u8>, Vec<u8>> {
+    let StorePersistence(inner) = store;
+    let id = inner.id_for(name).expect("No ID.");
+    inner.maps.get(&id).cloned().unwrap_or_default()
+}
+
+fn put_value(store: &mut StorePersistence<MemStore>, name: &str, value: &[u8]) {
+    let id = store.store_id(name).expect("No ID.");
+    store.put_value(id, value).expect("Write failed.");
+}
+
+fn put_entry(store: &mut StorePersistence<MemStore>, name: &str, key: &[u8], value: &[u8]) {
+    let StorePersistence(inner) = store;
+    let id = inner.id_for(name).expect("No ID.");
+    inner.update_map(id, key, value).expect("Write failed.");
+}
+
+/// Create a store as it would be after an agent with the specified items last ran.
+fn previous_run(items: &[(&str, ItemStateKind)]) -> StorePersistence<MemStore> {
+    let mut store = StorePersistence(MemStore::default());
+    reconcile_items(&mut store, manifest(items, &[]), StateMigrationPolicy::Fail)
+        .expect("Initial reconciliation failed.");
+    store
+}
+
+#[test]
+fn manifest_recorded_on_first_run() {
+    let mut store = StorePersistence(MemStore::default());
+    let items = manifest(&[("value", VALUE_LANE), ("map", MAP_LANE)], &[]);
+    assert!(reconcile_items(&mut store, items.clone(), StateMigrationPolicy::Fail).is_ok());
+    assert!(value_of(&store, MANIFEST_NAME).is_some());
+
+    //Restarting with the same items is consistent.
+    assert!(reconcile_items(&mut store, items, StateMigrationPolicy::Fail).is_ok());
+}
+
+#[test]
+fn new_items_are_consistent() {
+    let mut store = previous_run(&[("value", VALUE_LANE)]);
+    let items = manifest(&[("value", VALUE_LANE), ("other", VALUE_STORE)], &[]);
+    assert!(reconcile_items(&mut store, items, StateMigrationPolicy::Fail).is_ok());
+}
+
+#[test]
+fn renamed_value_lane_migrated() {
+    let mut store = previous_run(&[("old", VALUE_LANE)]);
+    put_value(&mut store, "old", b"5");
+
+    let items = manifest(&[("new", VALUE_LANE)], &[("old", "new")]);
+    assert!(reconcile_items(&mut store, items.clone(), StateMigrationPolicy::Migrate).is_ok());
+
+    assert_eq!(value_of(&store, "new"), Some(b"5".to_vec()));
+    assert_eq!(value_of(&store, "old"), None);
+
+    //The rename has been recorded so the items are now consistent.
+    assert!(reconcile_items(&mut store, items, StateMigrationPolicy::Fail).is_ok());
+}
+
+#[test]
+fn renamed_map_lane_migrated() {
+    let mut store = previous_run(&[("old", MAP_LANE)]);
+    put_entry(&mut store, "old", b"a", b"1");
+    put_entry(&mut store, "old", b"b", b"2");
+
+    let items = manifest(&[("new", MAP_LANE)], &[("old", "new")]);
+    assert!(reconcile_items(&mut store, items, StateMigrationPolicy::Migrate).is_ok());
+
+    let expected = [
+        (b"a".to_vec(), b"1".to_vec()),
+        (b"b".to_vec(), b"2".to_vec()),
+    ]
+    .into_iter()
+    .collect::<BTreeMap<_, _>>();
+    assert_eq!(map_of(&store, "new"), expected);
+    assert!(map_of(&store, "old").is_empty());
+}
+
+#[test]
+fn fail_policy_applies_renames() {
+    let mut store = previous_run(&[("old", VALUE_LANE)]);
+    put_value(&mut store, "old", b"5");
+
+    let items = manifest(&[("new", VALUE_LANE)], &[("old", "new")]);
+    assert!(reconcile_items(&mut store, items, StateMigrationPolicy::Fail).is_ok());
+    assert_eq!(value_of(&store, "new"), Some(b"5".to_vec()));
+}
+
+#[test]
+fn kind_change_discards_state() {
+    let mut store = previous_run(&[("lane", VALUE_LANE)]);
+    put_value(&mut store, "lane", b"5");
+
+    let items = manifest(&[("lane", MAP_LANE)], &[]);
+    assert!(reconcile_items(&mut store, items.clone(), StateMigrationPolicy::Migrate).is_ok());
+    assert_eq!(value_of(&store, "lane"), None);
+
+    assert!(reconcile_items(&mut store, items, StateMigrationPolicy::Fail).is_ok());
+}
+
+#[test]
+fn orphaned_state_is_retained() {
+    let mut store = previous_run(&[("lane", VALUE_LANE), ("gone", VALUE_LANE)]);
+    put_value(&mut store, "gone", b"5");
+
+    let items = manifest(&[("lane", VALUE_LANE)], &[]);
+    assert!(reconcile_items(&mut store, items, StateMigrationPolicy::Migrate).is_ok());
+    assert_eq!(value_of(&store, "gone"), Some(b"5".to_vec()));
+
+    //The orphan is still recorded so a rename can be added later.
+    let items = manifest(
+        &[("lane", VALUE_LANE), ("found", VALUE_LANE)],
+        &[("gone", "found")],
+    );
+    assert!(reconcile_items(&mut store, items, StateMigrationPolicy::Fail).is_ok());
+    assert_eq!(value_of(&store, "found"), Some(b"5".to_vec()));
+}
+
+#[test]
+fn fail_policy_reports_inconsistencies() {
+    let mut store = previous_run(&[
+        ("gone", VALUE_LANE),
+        ("lane", VALUE_LANE),
+        ("old", MAP_LANE),
+        ("new", MAP_LANE),
+    ]);
+    put_value(&mut store, "lane", b"5");
+
+    let items = manifest(&[("lane", MAP_LANE), ("new", MAP_LANE)], &[("old", "new")]);
+    let result = reconcile_items(&mut store, items, StateMigrationPolicy::Fail);
+    let expected = InconsistentState {
+        inconsistencies: vec![
+            StateInconsistency::RenameConflict {
+                previous_name: "old".to_string(),
+                name: "new".to_string(),
+            },
+            StateInconsistency::Orphaned {
+                name: "gone".to_string(),
+                kind: VALUE_LANE,
+            },
+            StateInconsistency::KindChanged {
+                name: "lane".to_string(),
+                previous: VALUE_LANE,
+                current: MAP_LANE,
+            },
+        ],
+    };
+    match result {
+        Err(ManifestError::Inconsistent(report)) => assert_eq!(report, expected),
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+    //The store is left unaltered.
+    assert_eq!(value_of(&store, "lane"), Some(b"5".to_vec()));
+}
+
+#[test]
+fn ignore_policy_leaves_store_unaltered() {
+    let mut store = previous_run(&[("old", VALUE_LANE), ("lane", VALUE_LANE)]);
+    put_value(&mut store, "old", b"5");
+    put_value(&mut store, "lane", b"6");
+
+    let items = manifest(
+        &[("new", VALUE_LANE), ("lane", MAP_LANE)],
+        &[("old", "new")],
+    );
+    assert!(reconcile_items(&mut store, items, StateMigrationPolicy::Ignore).is_ok());
+    assert_eq!(value_of(&store, "old"), Some(b"5".to_vec()));
+    assert_eq!(value_of(&store, "new"), None);
+    assert_eq!(value_of(&store, "lane"), Some(b"6".to_vec()));
+}
+
+#[test]
+fn no_store_is_consistent() {
+    let items = manifest(&[("lane", VALUE_LANE)], &[]);
+    assert!(reconcile_items(&mut StoreDisabled, items, StateMigrationPolicy::Fail).is_ok());
+    let mut absent: Option<StorePersistence<MemStore>> = None;
+    let items = manifest(&[("lane", VALUE_LANE)], &[]);
+    assert!(reconcile_items(&mut absent, items, StateMigrationPolicy::Fail).is_ok());
+}
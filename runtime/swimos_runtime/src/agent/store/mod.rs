@@ -30,9 +30,12 @@ use tokio_util::codec::FramedWrite;
 
 use super::AgentExecError;
 
+mod manifest;
 #[cfg(test)]
 mod tests;
 
+pub use manifest::{decode_item_kind, reconcile_items, ManifestError, MANIFEST_NAME};
+
 #[derive(Debug, Error)]
 #[error("Failed to initialize item named {name}.")]
 pub struct AgentItemInitError {
@@ -168,6 +171,25 @@ pub trait AgentPersistence {
         store_id: Self::StoreId,
         op: &MapOperation<B, B>,
     ) -> Result<(), StoreError>;
+
+    /// Read the value of a value store from the store, appending it to the buffer and returning
+    /// the number of bytes read (if it exists).
+    fn get_value(
+        &self,
+        store_id: Self::StoreId,
+        buffer: &mut BytesMut,
+    ) -> Result<Option<usize>, StoreError>;
+
+    /// Move all of the state of one item to another, replacing any state that it had.
+    fn move_state(
+        &mut self,
+        from: Self::StoreId,
+        to: Self::StoreId,
+        map_like: bool,
+    ) -> Result<(), StoreError>;
+
+    /// Remove all of the state of an item.
+    fn clear_state(&mut self, store_id: Self::StoreId, map_like: bool) -> Result<(), StoreError>;
 }
 
 impl AgentPersistence for StoreDisabled {
@@ -196,6 +218,101 @@ impl AgentPersistence for StoreDisabled {
     ) -> Result<(), StoreError> {
         Err(StoreError::NoStoreAvailable)
     }
+
+    fn get_value(
+        &self,
+        _store_id: Self::StoreId,
+        _buffer: &mut BytesMut,
+    ) -> Result<Option<usize>, StoreError> {
+        Err(StoreError::NoStoreAvailable)
+    }
+
+    fn move_state(
+        &mut self,
+        _from: Self::StoreId,
+        _to: Self::StoreId,
+        _map_like: bool,
+    ) -> Result<(), StoreError> {
+        Err(StoreError::NoStoreAvailable)
+    }
+
+    fn clear_state(&mut self, _store_id: Self::StoreId, _map_like: bool) -> Result<(), StoreError> {
+        Err(StoreError::NoStoreAvailable)
+    }
+}
+
+/// An optional store. If the store is absent, all items of the agent are transient (as with
+/// [`StoreDisabled`]).
+impl<P> AgentPersistence for Option<P>
+where
+    P: AgentPersistence,
+{
+    type StoreId = P::StoreId;
+
+    fn store_id(&self, name: &str) -> Result<Self::StoreId, StoreError> {
+        match self {
+            Some(store) => store.store_id(name),
+            None => Err(StoreError::NoStoreAvailable),
+        }
+    }
+
+    fn init_value_store(&self, store_id: Self::StoreId) -> Option<BoxInitializer<'_>> {
+        self.as_ref()
+            .and_then(|store| store.init_value_store(store_id))
+    }
+
+    fn init_map_store(&self, store_id: Self::StoreId) -> Option<BoxInitializer<'_>> {
+        self.as_ref()
+            .and_then(|store| store.init_map_store(store_id))
+    }
+
+    fn put_value(&mut self, store_id: Self::StoreId, bytes: &[u8]) -> Result<(), StoreError> {
+        match self {
+            Some(store) => store.put_value(store_id, bytes),
+            None => Err(StoreError::NoStoreAvailable),
+        }
+    }
+
+    fn apply_map<B: AsRef<[u8]>>(
+        &mut self,
+        store_id: Self::StoreId,
+        op: &MapOperation<B, B>,
+    ) -> Result<(), StoreError> {
+        match self {
+            Some(store) => store.apply_map(store_id, op),
+            None => Err(StoreError::NoStoreAvailable),
+        }
+    }
+
+    fn get_value(
+        &self,
+        store_id: Self::StoreId,
+        buffer: &mut BytesMut,
+    ) -> Result<Option<usize>, StoreError> {
+        match self {
+            Some(store) => store.get_value(store_id, buffer),
+            None => Err(StoreError::NoStoreAvailable),
+        }
+    }
+
+    fn move_state(
+        &mut self,
+        from: Self::StoreId,
+        to: Self::StoreId,
+        map_like: bool,
+    ) -> Result<(), StoreError> {
+        match self {
+            Some(store) => store.move_state(from, to, map_like),
+            None => Err(StoreError::NoStoreAvailable),
+        }
+    }
+
+    fn clear_state(&mut self, store_id: Self::StoreId, map_like: bool) -> Result<(), StoreError> {
+        match self {
+            Some(store) => store.clear_state(store_id, map_like),
+            None => Err(StoreError::NoStoreAvailable),
+        }
+    }
 }
 
 /// Binding to use an implementation of [`NodePersistence`] as an implementation of
@@ -245,6 +362,54 @@ where
         let init = MapInit { store, store_id };
         Some(Box::new(init))
     }
+
+    fn get_value(
+        &self,
+        store_id: Self::StoreId,
+        buffer: &mut BytesMut,
+    ) -> Result<Option<usize>, StoreError> {
+        let StorePersistence(store) = self;
+        store.get_value(store_id, buffer)
+    }
+
+    fn move_state(
+        &mut self,
+        from: Self::StoreId,
+        to: Self::StoreId,
+        map_like: bool,
+    ) -> Result<(), StoreError> {
+        let StorePersistence(store) = self;
+        if map_like {
+            let mut entries = vec![];
+            let mut it = store.read_map(from)?;
+            while let Some((key, value)) = it.consume_next()? {
+                entries.push((key.to_vec(), value.to_vec()));
+            }
+            drop(it);
+            store.clear_map(to)?;
+            for (key, value) in entries {
+                store.update_map(to, &key, &value)?;
+            }
+            store.clear_map(from)
+        } else {
+            let mut buffer = BytesMut::new();
+            if store.get_value(from, &mut buffer)?.is_some() {
+                store.put_value(to, &buffer)?;
+            } else {
+                store.delete_value(to)?;
+            }
+            store.delete_value(from)
+        }
+    }
+
+    fn clear_state(&mut self, store_id: Self::StoreId, map_like: bool) -> Result<(), StoreError> {
+        let StorePersistence(store) = self;
+        if map_like {
+            store.clear_map(store_id)
+        } else {
+            store.delete_value(store_id)
+        }
+    }
 }
 
 struct NoValueInit;
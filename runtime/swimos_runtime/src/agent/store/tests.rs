@@ -164,7 +164,8 @@ impl NodePersistence for FakeStore {
         }
     }
 
-    type MapCon<'a> = FakeConsumer
+    type MapCon<'a>
+        = FakeConsumer
     where
         Self: 'a;
 
@@ -347,3 +348,36 @@ fn clear_map() {
     let map = &store.inner.lock().map;
     assert!(map.is_empty());
 }
+
+#[test]
+fn absent_store_is_disabled() {
+    let mut persistence: Option<StorePersistence<FakeStore>> = None;
+
+    assert!(matches!(
+        persistence.store_id("lane"),
+        Err(StoreError::NoStoreAvailable)
+    ));
+    assert!(matches!(
+        persistence.put_value(Id::Value, &[1, 2, 3]),
+        Err(StoreError::NoStoreAvailable)
+    ));
+    assert!(matches!(
+        persistence.apply_map::<&[u8]>(Id::Map, &MapOperation::Clear),
+        Err(StoreError::NoStoreAvailable)
+    ));
+    assert!(persistence.init_value_store(Id::Value).is_none());
+    assert!(persistence.init_map_store(Id::Map).is_none());
+}
+
+#[test]
+fn present_store_delegates() {
+    let store = FakeStore::new(None, Default::default());
+
+    let mut persistence = Some(StorePersistence(store.clone()));
+
+    assert_eq!(persistence.store_id(VALUE_NAME).ok(), Some(Id::Value));
+    let data = &[1, 2, 3];
+    assert!(persistence.put_value(Id::Value, data).is_ok());
+
+    assert_eq!(&store.inner.lock().value, &Some(data.to_vec()));
+}
@@ -252,6 +252,7 @@ async fn get_msg(reader: &mut RequestReader, target: usize) -> Vec<u8> {
         origin,
         path,
         envelope,
+        ..
     } = reader
         .next()
         .await
@@ -277,6 +278,7 @@ fn get_msgs(
             origin,
             path: RelativeAddress { node, lane },
             envelope,
+            ..
         } = reader.next().await?.expect("Reader failed.");
         assert_eq!(origin, ID);
         let item = match envelope {
@@ -539,6 +541,7 @@ async fn output_replace_writer_pending_record() {
             origin,
             path: RelativeAddress { node, lane },
             envelope: Operation::Command(body),
+            ..
         }] => {
             assert_eq!(*origin, ID);
             assert_eq!(node, "/node");
@@ -594,6 +597,7 @@ async fn output_overwrite_record() {
             origin,
             path: RelativeAddress { node, lane },
             envelope: Operation::Command(body),
+            ..
         }] => {
             assert_eq!(*origin, ID);
             assert_eq!(node, "/node");
@@ -630,6 +634,7 @@ async fn output_queue_records() {
                 origin,
                 path: RelativeAddress { node, lane },
                 envelope: Operation::Command(body),
+                ..
             } = r1
             {
                 assert_eq!(*origin, ID);
@@ -644,6 +649,7 @@ async fn output_queue_records() {
                 origin,
                 path: RelativeAddress { node, lane },
                 envelope: Operation::Command(body),
+                ..
             } = r2
             {
                 assert_eq!(*origin, ID);
@@ -658,6 +664,7 @@ async fn output_queue_records() {
                 origin,
                 path: RelativeAddress { node, lane },
                 envelope: Operation::Command(body),
+                ..
             } = r3
             {
                 assert_eq!(*origin, ID);
@@ -937,6 +944,7 @@ fn check_pending_single(mut pending: PendingWrites, target: usize, expected_valu
                 origin,
                 path,
                 envelope,
+                ..
             } = decoder
                 .decode_eof(buffer)
                 .expect("Decoding failed.")
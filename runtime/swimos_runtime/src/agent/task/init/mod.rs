@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{convert::identity, num::NonZeroUsize, time::Duration};
+use std::{convert::identity, num::NonZeroUsize, pin::Pin, time::Duration};
 
 use futures::{
     future::{join, Either},
@@ -22,7 +22,7 @@ use futures::{
 use swimos_agent_protocol::encoding::store::StoreInitializedCodec;
 use swimos_api::{
     agent::{LaneConfig, StoreConfig, StoreKind, UplinkKind, WarpLaneKind},
-    error::{AgentRuntimeError, OpenStoreError, StoreError},
+    error::{AgentRuntimeError, DeclareItemsError, OpenStoreError, StoreError},
     persistence::StoreDisabled,
 };
 use swimos_model::Text;
@@ -37,20 +37,20 @@ use uuid::Uuid;
 
 use crate::agent::{
     store::{
-        no_map_init, no_value_init, AgentItemInitError, AgentPersistence, BoxInitializer,
-        StoreInitError,
+        no_map_init, no_value_init, reconcile_items, AgentItemInitError, AgentPersistence,
+        BoxInitializer, ManifestError, StoreInitError,
     },
-    AgentExecError, AgentRuntimeRequest, Io, LinkRequest, NodeReporting,
+    AgentExecError, AgentRuntimeRequest, Io, LinkRequest, NodeReporting, StateMigrationPolicy,
 };
 
 use super::{
     external_links::{external_links_task, LinksTaskConfig, LinksTaskState, NoReport},
-    Endpoints, ExternalLinkRequest, HttpLaneEndpoint, HttpLaneRuntimeSpec, InitialEndpoints,
-    ItemEndpoint, ItemInitTask, LaneEndpoint, LaneResult, LaneRuntimeSpec, StoreEndpoint,
-    StoreResult, StoreRuntimeSpec,
+    DeclareItemsSpec, Endpoints, ExternalLinkRequest, FrameLimits, HttpLaneEndpoint,
+    HttpLaneRuntimeSpec, InitialEndpoints, ItemEndpoint, ItemInitTask, LaneAlias, LaneAliasSpec,
+    LaneEndpoint, LaneResult, LaneRuntimeSpec, StoreEndpoint, StoreResult, StoreRuntimeSpec,
 };
 
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 #[cfg(test)]
 mod tests;
@@ -73,6 +73,7 @@ pub struct InitTaskConfig {
     pub item_init_timeout: Duration,
     pub external_links: LinksTaskConfig,
     pub http_lane_channel_size: NonZeroUsize,
+    pub state_migration: StateMigrationPolicy,
 }
 
 impl AgentInitTask {
@@ -143,7 +144,7 @@ impl<Store: AgentPersistence + Send + Sync> AgentInitTask<Store> {
             requests,
             init_complete,
             link_requests,
-            store,
+            mut store,
             config,
             reporting,
         } = self;
@@ -152,10 +153,32 @@ impl<Store: AgentPersistence + Send + Sync> AgentInitTask<Store> {
             item_init_timeout,
             external_links,
             http_lane_channel_size,
+            state_migration,
         } = config;
         let initialization = Initialization::new(reporting, item_init_timeout);
         let mut request_stream = ReceiverStream::new(requests);
-        let terminated = (&mut request_stream).take_until(init_complete);
+        let mut terminated = (&mut request_stream).take_until(init_complete).peekable();
+
+        // If the agent declares its persistent items, it must do so before it registers any of them
+        // so that the store can be checked (and migrated) before any state is restored from it.
+        if let Some(AgentRuntimeRequest::DeclareItems(DeclareItemsSpec { manifest, promise })) =
+            Pin::new(&mut terminated)
+                .next_if(|request| matches!(request, AgentRuntimeRequest::DeclareItems(_)))
+                .await
+        {
+            match reconcile_items(&mut store, manifest, state_migration) {
+                Ok(()) => {
+                    let _ = promise.send(Ok(()));
+                }
+                Err(ManifestError::Inconsistent(report)) => {
+                    let _ = promise.send(Err(DeclareItemsError::InconsistentState(report.clone())));
+                    return Err(AgentExecError::InconsistentState(report));
+                }
+                Err(ManifestError::Store(err)) => {
+                    return Err(AgentExecError::PersistenceFailure(err));
+                }
+            }
+        }
 
         let mut endpoints = Endpoints::default();
 
@@ -304,7 +327,15 @@ impl Initialization {
             input_buffer_size,
             output_buffer_size,
             transient,
+            track_origin,
+            max_message_size,
+            limits,
         } = config;
+        let limits = FrameLimits {
+            max_fragment_size: Some(input_buffer_size),
+            max_message_size,
+            max_batch_entries: limits.max_map_batch_entries,
+        };
 
         let (in_tx, in_rx) = byte_channel::byte_channel(input_buffer_size);
         let (out_tx, out_rx) = byte_channel::byte_channel(output_buffer_size);
@@ -337,7 +368,7 @@ impl Initialization {
                         _ => (None, None),
                     };
                     if let Some(initializer) = maybe_initializer {
-                        let endpoint = lane_initialization(
+                        let mut endpoint = lane_initialization(
                             name.clone(),
                             kind,
                             *item_init_timeout,
@@ -347,6 +378,8 @@ impl Initialization {
                             initializer,
                         )
                         .await?;
+                        endpoint.track_origin = track_origin;
+                        endpoint.limits = limits;
                         Ok((endpoint, maybe_store_id))
                     } else {
                         let reporter = if let Some(node_reporter) = reporting {
@@ -358,8 +391,10 @@ impl Initialization {
                             name,
                             kind: kind.uplink_kind(),
                             transient,
+                            track_origin,
                             io: (in_tx, out_rx),
                             reporter,
+                            limits,
                         };
                         Ok((endpoint, None))
                     }
@@ -409,9 +444,11 @@ async fn lane_initialization(
         let endpoint = LaneEndpoint {
             name: lane_name,
             transient: false,
+            track_origin: false,
             kind,
             io: (in_tx, out_rx),
             reporter,
+            limits: Default::default(),
         };
         Ok(endpoint)
     })
@@ -461,6 +498,7 @@ where
         lane_endpoints,
         http_lane_endpoints,
         store_endpoints,
+        lane_aliases,
     } = endpoints;
     let mut initializers: FuturesUnordered<ItemInitTask<'_>> = FuturesUnordered::new();
     loop {
@@ -532,6 +570,21 @@ where
                         break Err(AgentExecError::FailedDownlinkRequest);
                     }
                 }
+                AgentRuntimeRequest::DeclareItems(DeclareItemsSpec { promise, .. }) => {
+                    warn!("The agent declared its persistent items after registering items. They will not be checked against the store.");
+                    let _ = promise.send(Ok(()));
+                }
+                AgentRuntimeRequest::AddLaneAlias(LaneAliasSpec {
+                    alias,
+                    name,
+                    promise,
+                }) => {
+                    info!("Registering alias '{}' for lane '{}'.", alias, name);
+                    // The lane may still be initializing so the alias is checked when the runtime
+                    // starts.
+                    let _ = promise.send(Ok(()));
+                    lane_aliases.push(LaneAlias::new(alias, name));
+                }
                 AgentRuntimeRequest::AddHttpLane(HttpLaneRuntimeSpec { name, promise }) => {
                     let (tx, rx) = mpsc::channel(http_channel_size.get());
                     if promise.send(Ok(rx)).is_err() {
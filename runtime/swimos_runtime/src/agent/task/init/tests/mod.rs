@@ -25,6 +25,7 @@ use swimos_agent_protocol::{LaneRequest, LaneResponse};
 use swimos_api::{
     agent::{LaneConfig, LaneKind, UplinkKind, WarpLaneKind},
     error::StoreError,
+    limits::Limits,
     persistence::StoreDisabled,
 };
 use swimos_model::Text;
@@ -41,7 +42,7 @@ use crate::agent::{
     reporting::UplinkReporter,
     store::{AgentPersistence, InitFut, Initializer, StoreInitError},
     task::{InitialEndpoints, LaneEndpoint, LinksTaskConfig},
-    AgentExecError, AgentRuntimeRequest, Io, LinkRequest, NodeReporting,
+    AgentExecError, AgentRuntimeRequest, Io, LinkRequest, NodeReporting, StateMigrationPolicy,
     UplinkReporterRegistration,
 };
 
@@ -80,18 +81,25 @@ const INIT_CONFIG: InitTaskConfig = InitTaskConfig {
         timeout_delay: AD_HOC_TIMEOUT,
     },
     http_lane_channel_size: HTTP_CHAN_SIZE,
+    state_migration: StateMigrationPolicy::Migrate,
 };
 
 const TRANSIENT: LaneConfig = LaneConfig {
     input_buffer_size: BUFFER_SIZE,
     output_buffer_size: BUFFER_SIZE,
     transient: true,
+    track_origin: false,
+    max_message_size: None,
+    limits: Limits::DEFAULT,
 };
 
 const PERSISTENT: LaneConfig = LaneConfig {
     input_buffer_size: BUFFER_SIZE,
     output_buffer_size: BUFFER_SIZE,
     transient: false,
+    track_origin: false,
+    max_message_size: None,
+    limits: Limits::DEFAULT,
 };
 
 const CONFIGS: &[LaneConfig] = &[TRANSIENT, PERSISTENT];
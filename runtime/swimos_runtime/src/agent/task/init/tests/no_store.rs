@@ -215,6 +215,7 @@ async fn single_lane() {
             mut io,
             transient,
             reporter,
+            ..
         } = lane_endpoints.pop().unwrap();
         assert_eq!(name, "my_lane");
         assert_eq!(kind, UplinkKind::Value);
@@ -254,6 +255,7 @@ async fn single_lane_with_reporting() {
             mut io,
             transient,
             reporter,
+            ..
         } = lane_endpoints.pop().unwrap();
         assert_eq!(name, "my_lane");
         assert_eq!(kind, UplinkKind::Value);
@@ -387,6 +389,7 @@ async fn two_lanes() {
             mut io,
             transient,
             reporter,
+            ..
         } in lane_endpoints
         {
             match kind {
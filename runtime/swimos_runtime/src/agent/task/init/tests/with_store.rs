@@ -24,7 +24,9 @@ use swimos_agent_protocol::{
     LaneRequest, LaneResponse, MapMessage, MapOperation, StoreInitMessage, StoreInitialized,
 };
 use swimos_api::{
-    agent::{LaneConfig, StoreConfig, StoreKind, UplinkKind, WarpLaneKind},
+    agent::{
+        ItemManifest, ItemStateKind, LaneConfig, StoreConfig, StoreKind, UplinkKind, WarpLaneKind,
+    },
     error::StoreError,
     persistence::NodePersistence,
 };
@@ -37,14 +39,14 @@ use tokio::sync::{mpsc, oneshot};
 use tokio_util::codec::{FramedRead, FramedWrite};
 
 use crate::agent::{
-    store::{StoreInitError, StorePersistence},
+    store::{reconcile_items, AgentPersistence, StoreInitError, StorePersistence, MANIFEST_NAME},
     task::{
         fake_store::FakeStore,
         init::tests::{INIT_STOPPED, NO_LANE, NO_RESPONSE, NO_STORE, TRANSIENT},
-        AgentRuntimeRequest, Endpoints, InitialEndpoints, LaneEndpoint, LaneRuntimeSpec,
-        StoreEndpoint, StoreRuntimeSpec,
+        AgentRuntimeRequest, DeclareItemsSpec, Endpoints, InitialEndpoints, LaneEndpoint,
+        LaneRuntimeSpec, StoreEndpoint, StoreRuntimeSpec,
     },
-    AgentExecError, Io, LinkRequest,
+    AgentExecError, Io, LinkRequest, StateMigrationPolicy,
 };
 
 use super::{check_connected, run_test, TestInit, PERSISTENT};
@@ -233,6 +235,94 @@ async fn failed_value_lane_init_from_store() {
     }
 }
 
+struct DeclaredValueLane {
+    manifest: ItemManifest,
+    expected: i32,
+}
+
+impl TestInit for DeclaredValueLane {
+    type Output = Io;
+
+    fn run_test(
+        self,
+        requests: mpsc::Sender<AgentRuntimeRequest>,
+        link_requests: mpsc::Receiver<LinkRequest>,
+        init_complete: trigger::Sender,
+    ) -> BoxFuture<'static, Self::Output> {
+        async move {
+            let DeclaredValueLane { manifest, expected } = self;
+            let _link_requests = link_requests;
+            let (declare_tx, declare_rx) = oneshot::channel();
+            requests
+                .send(AgentRuntimeRequest::DeclareItems(DeclareItemsSpec::new(
+                    manifest, declare_tx,
+                )))
+                .await
+                .expect("Declaring items failed.");
+            declare_rx
+                .await
+                .expect("Request dropped.")
+                .expect("Items were inconsistent.");
+
+            let (promise_tx, promise_rx) = oneshot::channel();
+            requests
+                .send(AgentRuntimeRequest::AddLane(LaneRuntimeSpec::new(
+                    Text::new(VAL_LANE),
+                    WarpLaneKind::Value,
+                    PERSISTENT,
+                    promise_tx,
+                )))
+                .await
+                .expect("Requesting new lane failed.");
+            let mut lane_io = promise_rx
+                .await
+                .expect("Request dropped.")
+                .expect("Opening new lane failed.");
+            let (output, input) = &mut lane_io;
+            with_store_init_value(input, output, expected).await;
+            init_complete.trigger();
+            lane_io
+        }
+        .boxed()
+    }
+}
+
+#[tokio::test]
+async fn init_renamed_value_lane_from_store() {
+    const OLD_NAME: &str = "old_value";
+    let store = FakeStore::new(vec![VAL_LANE, OLD_NAME, MANIFEST_NAME]);
+    let mut persistence = StorePersistence(store);
+
+    let mut previous = ItemManifest::default();
+    previous.add_item(OLD_NAME, ItemStateKind::Lane(WarpLaneKind::Value));
+    reconcile_items(&mut persistence, previous, StateMigrationPolicy::Fail)
+        .expect("Recording items failed.");
+    let id = persistence.store_id(OLD_NAME).expect("Invalid key.");
+    persistence.put_value(id, b"56").expect("Invalid ID");
+
+    let mut manifest = ItemManifest::default();
+    manifest.add_item(VAL_LANE, ItemStateKind::Lane(WarpLaneKind::Value));
+    manifest.add_rename(OLD_NAME, VAL_LANE);
+    let init = DeclaredValueLane {
+        manifest,
+        expected: 56,
+    };
+    let (initial_result, mut agent_io) = run_test(init, persistence).await;
+
+    let initial = initial_result.expect("No lanes were registered.");
+    let InitialEndpoints {
+        endpoints: Endpoints {
+            mut lane_endpoints, ..
+        },
+        ..
+    } = initial;
+
+    assert_eq!(lane_endpoints.len(), 1);
+    let LaneEndpoint { name, mut io, .. } = lane_endpoints.pop().unwrap();
+    assert_eq!(name, VAL_LANE);
+    check_connected(&mut agent_io, &mut io);
+}
+
 struct SingleMapLane {
     config: LaneConfig,
     expected: Expectation<HashMap<i32, i32>>,
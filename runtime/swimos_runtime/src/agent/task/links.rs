@@ -128,6 +128,11 @@ impl Links {
         self.forward.entry(lane_id).or_default().reporter = Some(reporter);
     }
 
+    /// The total number of links between lanes and remotes.
+    pub fn count(&self) -> u64 {
+        self.total_count
+    }
+
     /// Create a new link from a lane to a remote.
     pub fn insert(&mut self, lane_id: u64, remote_id: Uuid) {
         let Links {
@@ -12,12 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::future::Future;
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
 use std::pin::{pin, Pin};
+use std::sync::Arc;
 use std::time::Duration;
 
+use crate::agent::intercept::OutgoingInterceptor;
+use crate::agent::prune::{IdleRemote, PruneDecision, PrunePolicy};
 use crate::agent::store::StoreInitError;
 use crate::agent::task::links::TriggerUnlink;
 use crate::agent::task::sender::LaneSendError;
@@ -34,34 +39,40 @@ use self::remotes::{RemoteSender, RemoteTracker, UplinkResponse};
 use self::sender::LaneSender;
 use self::write_fut::{WriteResult, WriteTask};
 
-use super::reporting::UplinkReporter;
+use super::recording::EnvelopeRecording;
+use super::reporting::{
+    DeprecationEvent, DeprecationReporter, PruneEvent, PruneReporter, UplinkReporter,
+};
 use super::store::{AgentItemInitError, AgentPersistence};
 use super::{
     AgentAttachmentRequest, AgentRuntimeConfig, DisconnectionReason, DownlinkRequest, Io,
-    NodeReporting,
+    LinkAdvisoryConfig, NodeReporting, UplinkBackpressure,
 };
 use bytes::{Bytes, BytesMut};
-use futures::future::{join4, BoxFuture};
+use futures::future::{self, join4, BoxFuture};
 use futures::stream::FuturesUnordered;
 use futures::{
     future::{join, select, Either},
-    stream::SelectAll,
-    Stream, StreamExt,
+    stream::{self, SelectAll},
+    FutureExt, Stream, StreamExt,
 };
 use swimos_api::address::RelativeAddress;
 use swimos_api::agent::{
-    HttpLaneRequest, HttpLaneRequestChannel, HttpResponseSender, LaneConfig, StoreConfig,
+    HttpLaneRequest, HttpLaneRequestChannel, HttpResponseSender, ItemManifest, LaneConfig,
+    NodeStopped, StopReason, StoreConfig,
 };
-use swimos_api::error::{DownlinkRuntimeError, OpenStoreError, StoreError};
+use swimos_api::error::{DeclareItemsError, DownlinkRuntimeError, OpenStoreError, StoreError};
 use swimos_api::persistence::StoreDisabled;
 use swimos_api::{
     agent::{StoreKind, UplinkKind, WarpLaneKind},
     error::AgentRuntimeError,
     http::{Header, HttpResponse, StandardHeaderName, StatusCode, Version},
 };
-use swimos_messages::protocol::{Operation, RawRequestMessageDecoder, RequestMessage};
+use swimos_messages::protocol::{
+    CorrelationId, LinkParams, Operation, RawRequestMessageDecoder, RequestMessage,
+};
 use swimos_model::Text;
-use swimos_recon::parser::MessageExtractError;
+use swimos_recon::{parser::MessageExtractError, print_recon_compact};
 use swimos_utilities::byte_channel::{ByteReader, ByteWriter};
 use swimos_utilities::encoding::BytesStr;
 use swimos_utilities::future::{immediate_or_join, StopAfterError};
@@ -74,16 +85,18 @@ mod prune;
 mod receiver;
 mod remotes;
 mod sender;
+mod stop;
 mod uri_params;
 mod write_fut;
 
 pub use external_links::LinksTaskConfig;
 pub use init::{AgentInitTask, InitTaskConfig};
+pub use stop::{stop_notification, StopNotifier, StopSignal};
 use tokio::sync::{mpsc, oneshot};
 use tokio::time::{sleep, timeout, Instant, Sleep};
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::codec::FramedRead;
-use tracing::{debug, error, info, info_span, trace, warn, Instrument};
+use tracing::{debug, debug_span, error, info, info_span, trace, warn, Instrument, Span};
 use uuid::Uuid;
 #[cfg(test)]
 mod fake_store;
@@ -153,6 +166,42 @@ impl StoreRuntimeSpec {
     }
 }
 
+#[derive(Debug)]
+pub struct DeclareItemsSpec {
+    pub manifest: ItemManifest,
+    pub promise: oneshot::Sender<Result<(), DeclareItemsError>>,
+}
+
+impl DeclareItemsSpec {
+    pub fn new(
+        manifest: ItemManifest,
+        promise: oneshot::Sender<Result<(), DeclareItemsError>>,
+    ) -> Self {
+        DeclareItemsSpec { manifest, promise }
+    }
+}
+
+#[derive(Debug)]
+pub struct LaneAliasSpec {
+    pub alias: Text,
+    pub name: Text,
+    pub promise: oneshot::Sender<Result<(), AgentRuntimeError>>,
+}
+
+impl LaneAliasSpec {
+    pub fn new(
+        alias: Text,
+        name: Text,
+        promise: oneshot::Sender<Result<(), AgentRuntimeError>>,
+    ) -> Self {
+        LaneAliasSpec {
+            alias,
+            name,
+            promise,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct AdHocChannelRequest {
     pub promise: oneshot::Sender<Result<ByteWriter, DownlinkRuntimeError>>,
@@ -183,6 +232,26 @@ pub enum AgentRuntimeRequest {
     AddStore(StoreRuntimeSpec),
     /// Attempt to open a downlink to a lane on another agent.
     OpenDownlink(DownlinkRequest),
+    /// Declare the persistent items of the agent, before any are registered.
+    DeclareItems(DeclareItemsSpec),
+    /// Register an alternative name for a lane of the agent.
+    AddLaneAlias(LaneAliasSpec),
+}
+
+/// An alternative name by which a lane of the agent can be addressed. Aliases that clash with the
+/// name of a lane, or that refer to a lane that does not exist, are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaneAlias {
+    /// The alternative name.
+    alias: Text,
+    /// The name of the lane.
+    name: Text,
+}
+
+impl LaneAlias {
+    fn new(alias: Text, name: Text) -> Self {
+        LaneAlias { alias, name }
+    }
 }
 
 /// A labelled channel endpoint (or pair) for a lane.
@@ -194,10 +263,25 @@ pub struct LaneEndpoint<T> {
     kind: UplinkKind,
     /// Whether the lane state should be persisted.
     transient: bool,
+    /// Whether commands sent to the lane should be tagged with the ID of the remote that sent them.
+    track_origin: bool,
     /// The channel endpoint/s.
     io: T,
     /// Metadata reporter for the lane.
     reporter: Option<UplinkReporter>,
+    /// Limits on the sizes of the frames exchanged with the lane.
+    limits: FrameLimits,
+}
+
+/// Limits on the sizes of the frames exchanged with a lane.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FrameLimits {
+    /// Requests to the lane that are larger than this are split into fragments.
+    pub max_fragment_size: Option<NonZeroUsize>,
+    /// Responses from the lane that are larger than this are rejected.
+    pub max_message_size: Option<NonZeroUsize>,
+    /// Batches of map events from the lane with more entries than this are rejected.
+    pub max_batch_entries: Option<NonZeroUsize>,
 }
 
 #[derive(Debug)]
@@ -220,15 +304,19 @@ impl<T> LaneEndpoint<T> {
         name: Text,
         kind: UplinkKind,
         transient: bool,
+        track_origin: bool,
         io: T,
         reporter: Option<UplinkReporter>,
+        limits: FrameLimits,
     ) -> Self {
         LaneEndpoint {
             name,
             kind,
             transient,
+            track_origin,
             io,
             reporter,
+            limits,
         }
     }
 }
@@ -240,13 +328,23 @@ impl LaneEndpoint<Io> {
             name,
             kind,
             transient,
+            track_origin,
             io: (tx, rx),
             reporter,
+            limits,
         } = self;
 
-        let read = LaneEndpoint::new(name.clone(), kind, transient, rx, reporter.clone());
+        let read = LaneEndpoint::new(
+            name.clone(),
+            kind,
+            transient,
+            track_origin,
+            rx,
+            reporter.clone(),
+            limits,
+        );
 
-        let write = LaneEndpoint::new(name, kind, transient, tx, reporter);
+        let write = LaneEndpoint::new(name, kind, transient, track_origin, tx, reporter, limits);
 
         (write, read)
     }
@@ -264,13 +362,29 @@ impl LaneEndpoint<ByteReader> {
             kind,
             io: reader,
             reporter,
+            limits:
+                FrameLimits {
+                    max_message_size,
+                    max_batch_entries,
+                    ..
+                },
             ..
         } = self;
         let id = state.register_lane(name, reporter);
         match kind {
-            UplinkKind::Value => ResponseReceiver::value_like_lane(id, store_id, reader),
-            UplinkKind::Supply => ResponseReceiver::supply_lane(id, store_id, reader),
-            UplinkKind::Map => ResponseReceiver::map_lane(id, store_id, reader),
+            UplinkKind::Value => {
+                ResponseReceiver::value_like_lane(id, store_id, reader, max_message_size)
+            }
+            UplinkKind::Supply => {
+                ResponseReceiver::supply_lane(id, store_id, reader, max_message_size)
+            }
+            UplinkKind::Map => ResponseReceiver::map_lane(
+                id,
+                store_id,
+                reader,
+                max_message_size,
+                max_batch_entries,
+            ),
         }
     }
 }
@@ -280,11 +394,15 @@ impl LaneEndpoint<ByteWriter> {
         let LaneEndpoint {
             name,
             kind,
+            track_origin,
             io: tx,
             reporter,
+            limits: FrameLimits {
+                max_fragment_size, ..
+            },
             ..
         } = self;
-        let sender = LaneSender::new(tx, kind, reporter);
+        let sender = LaneSender::new(tx, kind, track_origin, reporter, max_fragment_size);
         ReadTaskMessage::Lane { name, sender }
     }
 }
@@ -321,6 +439,7 @@ pub struct Endpoints {
     lane_endpoints: Vec<LaneEndpoint<Io>>,
     http_lane_endpoints: Vec<HttpLaneEndpoint>,
     store_endpoints: Vec<StoreEndpoint>,
+    lane_aliases: Vec<LaneAlias>,
 }
 
 /// Result of the agent initialization task (detailing the lanes that were created during initialization).
@@ -352,11 +471,30 @@ impl InitialEndpoints {
 pub struct NodeDescriptor {
     identity: Uuid,
     node_uri: Text,
+    interceptor: Option<Arc<dyn OutgoingInterceptor>>,
+    prune_policy: Option<Arc<dyn PrunePolicy>>,
 }
 
 impl NodeDescriptor {
     pub fn new(identity: Uuid, node_uri: Text) -> Self {
-        NodeDescriptor { identity, node_uri }
+        NodeDescriptor {
+            identity,
+            node_uri,
+            interceptor: None,
+            prune_policy: None,
+        }
+    }
+
+    /// Transform the bodies of the events sent by the agent with an interceptor.
+    pub fn with_interceptor(mut self, interceptor: Option<Arc<dyn OutgoingInterceptor>>) -> Self {
+        self.interceptor = interceptor;
+        self
+    }
+
+    /// Customize when remotes with no links to the agent are pruned.
+    pub fn with_prune_policy(mut self, prune_policy: Option<Arc<dyn PrunePolicy>>) -> Self {
+        self.prune_policy = prune_policy;
+        self
     }
 }
 
@@ -374,6 +512,7 @@ pub struct AgentRuntimeTask<Store = StoreDisabled> {
     stopping: trigger::Receiver,
     config: AgentRuntimeConfig,
     store: Store,
+    stop_notifier: Option<StopNotifier>,
 }
 
 /// Message type used by the read and write tasks to communicate with each other.
@@ -383,6 +522,7 @@ enum RwCoordinationMessage {
     UnknownLane {
         origin: Uuid,
         path: RelativeAddress<Text>,
+        correlation_id: Option<CorrelationId>,
     },
     /// An envelope that was invalid for the subprotocol used by the specified lane was received.
     BadEnvelope {
@@ -391,9 +531,38 @@ enum RwCoordinationMessage {
         error: MessageExtractError,
     },
     /// Instruct the write task to create an uplink from the specified lane to the specified remote.
-    Link { origin: Uuid, lane: Text },
+    Link {
+        origin: Uuid,
+        lane: Text,
+        params: LinkParams,
+        correlation_id: Option<CorrelationId>,
+    },
+    /// Instruct the write task to update the rate and priority of the uplink from the specified lane
+    /// to the specified remote.
+    Params {
+        origin: Uuid,
+        lane: Text,
+        params: LinkParams,
+    },
     /// Instruct the write task to remove an uplink from the specified lane to the specified remote.
-    Unlink { origin: Uuid, lane: Text },
+    Unlink {
+        origin: Uuid,
+        lane: Text,
+        correlation_id: Option<CorrelationId>,
+    },
+    /// Inform the write task of the correlation ID of a sync request from the specified remote so
+    /// that it can be echoed on the responses to the sync.
+    Correlate {
+        origin: Uuid,
+        lane: Text,
+        correlation_id: CorrelationId,
+    },
+    /// Instruct the write task that the remote has acknowledged a page of a paged sync from the
+    /// specified lane.
+    Ack { origin: Uuid, lane: Text },
+    /// Inform the write task that the remote addressed the specified lane by an alias, so that the
+    /// envelopes sent to the remote for an implicit link use the same name.
+    LinkName { origin: Uuid, lane: Text },
 }
 
 impl AgentRuntimeTask {
@@ -421,6 +590,7 @@ impl AgentRuntimeTask {
             stopping,
             config,
             store: StoreDisabled,
+            stop_notifier: None,
         }
     }
 }
@@ -455,8 +625,16 @@ where
             stopping,
             config,
             store,
+            stop_notifier: None,
         }
     }
+
+    /// Notify the agent of the reason that the runtime is stopping before the lanes of the agent
+    /// are closed.
+    pub fn with_stop_notifier(mut self, stop_notifier: StopNotifier) -> Self {
+        self.stop_notifier = Some(stop_notifier);
+        self
+    }
 }
 
 impl<Store> AgentRuntimeTask<Store>
@@ -465,7 +643,13 @@ where
 {
     pub async fn run(self) -> Result<(), StoreError> {
         let AgentRuntimeTask {
-            node: NodeDescriptor { identity, node_uri },
+            node:
+                NodeDescriptor {
+                    identity,
+                    node_uri,
+                    interceptor,
+                    prune_policy,
+                },
             init:
                 InitialEndpoints {
                     reporting,
@@ -475,6 +659,7 @@ where
                             lane_endpoints,
                             http_lane_endpoints,
                             store_endpoints,
+                            lane_aliases,
                         },
                     ext_link_state,
                 },
@@ -483,6 +668,7 @@ where
             stopping,
             config,
             store,
+            stop_notifier,
         } = self;
 
         let (write_endpoints, read_endpoints): (Vec<_>, Vec<_>) =
@@ -496,12 +682,36 @@ where
             timeout_coord::agent_timeout_coordinator();
 
         let (kill_switch_tx, kill_switch_rx) = trigger::trigger();
+        // Instructs the IO tasks to begin a clean shutdown.
+        let (shutdown_tx, shutdown_rx) = trigger::trigger();
+        // Instructs the attachment task to stop.
+        let (halt_tx, halt_rx) = trigger::trigger();
+        // Indicates that the agent has been released by the server (no more remotes can attach).
+        let (released_tx, released_rx) = trigger::trigger();
+
+        let combined_stop = select(select(shutdown_rx.clone(), kill_switch_rx.clone()), halt_rx);
+
+        let stop_coordinator = coordinate_stop(
+            StopSignals {
+                stopping,
+                vote_waiter,
+                kill_switch: kill_switch_rx,
+                released: released_rx,
+            },
+            stop_notifier,
+            config.shutdown_timeout,
+            shutdown_tx,
+            halt_tx,
+        );
 
-        let combined_stop = select(select(stopping.clone(), kill_switch_rx), vote_waiter);
+        let attachments = ReceiverStream::new(attachment_rx).chain(stream::once(async move {
+            released_tx.trigger();
+            future::pending().await
+        }));
 
         let att = attachment_task(
             rx,
-            attachment_rx,
+            attachments,
             read_tx.clone(),
             write_tx.clone(),
             http_tx,
@@ -513,18 +723,21 @@ where
         let read = read_task(
             config,
             write_endpoints,
+            lane_aliases.clone(),
             read_rx,
             write_tx,
             read_vote,
-            stopping.clone(),
-            reporting.as_ref().map(NodeReporting::aggregate),
+            shutdown_rx.clone(),
+            reporting.clone(),
         )
         .instrument(info_span!("Agent Runtime Read Task", %identity, %node_uri));
 
         let write = write_task(
-            WriteTaskConfiguration::new(identity, node_uri.clone(), config),
-            WriteTaskEndpoints::new(read_endpoints, store_endpoints),
-            ReceiverStream::new(write_rx).take_until(stopping.clone()),
+            WriteTaskConfiguration::new(identity, node_uri.clone(), config)
+                .with_interceptor(interceptor)
+                .with_prune_policy(prune_policy),
+            WriteTaskEndpoints::new(read_endpoints, store_endpoints).with_aliases(lane_aliases),
+            ReceiverStream::new(write_rx).take_until(shutdown_rx.clone()),
             read_tx,
             write_vote,
             reporting,
@@ -533,7 +746,7 @@ where
         .instrument(info_span!("Agent Runtime Write Task", %identity, %node_uri));
 
         let http_task = http_task(
-            stopping,
+            shutdown_rx,
             config,
             http_requests,
             http_lane_endpoints,
@@ -556,16 +769,85 @@ where
         )
         .instrument(info_span!("Agent Ad Hoc Command Task", %identity, %node_uri));
 
-        let io = await_io_tasks(read, write, kill_switch_tx);
+        let io = async move {
+            let io = pin!(await_io_tasks(read, write, kill_switch_tx));
+            match select(io, pin!(stop_coordinator)).await {
+                Either::Left((result, _)) => result,
+                Either::Right((_, io)) => io.await,
+            }
+        };
         let (_, _, _, result) = join4(att, ext_links, http_task, io).await;
         result
     }
 }
 
+/// The events that can cause the agent runtime to stop.
+struct StopSignals<V> {
+    /// The plane is shutting down.
+    stopping: trigger::Receiver,
+    /// Completes when all parts of the runtime have voted to stop due to inactivity.
+    vote_waiter: V,
+    /// Triggered when the read or write task stops.
+    kill_switch: trigger::Receiver,
+    /// Triggered when the server releases the agent.
+    released: trigger::Receiver,
+}
+
+/// Wait for the runtime to be instructed to stop and notify the agent of the reason before the
+/// remaining parts of the runtime are stopped. This will never complete normally and should be
+/// dropped when the IO tasks have completed.
+///
+/// # Arguments
+/// * `signals` - The events that can cause the runtime to stop.
+/// * `stop_notifier` - Notifies the agent of the reason that it is stopping.
+/// * `grace_period` - The maximum time to wait for the agent to complete after it has been notified.
+/// * `shutdown_tx` - Instructs the IO tasks to begin a clean shutdown.
+/// * `halt_tx` - Instructs the attachment task to stop.
+async fn coordinate_stop<V>(
+    signals: StopSignals<V>,
+    stop_notifier: Option<StopNotifier>,
+    grace_period: Duration,
+    shutdown_tx: trigger::Sender,
+    halt_tx: trigger::Sender,
+) where
+    V: Future,
+{
+    let StopSignals {
+        mut stopping,
+        vote_waiter,
+        kill_switch,
+        released,
+    } = signals;
+    let reason = tokio::select! {
+        biased;
+        _ = kill_switch => StopReason::Failure,
+        _ = &mut stopping => StopReason::PlaneShutdown,
+        _ = vote_waiter => StopReason::InactivityTimeout,
+        _ = released => StopReason::ExternalStop,
+    };
+    debug!(reason = %reason, "Agent runtime stopping.");
+    if let Some(notifier) = stop_notifier {
+        notifier.notify(reason, grace_period).await;
+    }
+    if reason == StopReason::PlaneShutdown {
+        shutdown_tx.trigger();
+    } else {
+        halt_tx.trigger();
+        // If the plane stops while the runtime is still stopping, the IO tasks must still be
+        // instructed to shut down.
+        if stopping.await.is_ok() {
+            shutdown_tx.trigger();
+        }
+    }
+    future::pending().await
+}
+
 /// Control messages consumed by the read task.
 enum ReadTaskMessage {
     /// Create a new lane endpoint.
     Lane { name: Text, sender: LaneSender },
+    /// Register an alias for a lane.
+    Alias(LaneAlias),
     /// Attach a new remote.
     Remote {
         reader: ByteReader,
@@ -582,9 +864,12 @@ enum WriteTaskMessage {
     Lane(LaneRuntimeSpec),
     /// Create a new store endpoint.
     Store(StoreRuntimeSpec),
+    /// Register an alias for a lane.
+    Alias(LaneAliasSpec),
     /// Attach a new remote.
     Remote {
         id: Uuid,
+        addr: Option<SocketAddr>,
         writer: ByteWriter,
         completion: promise::Sender<DisconnectionReason>,
         on_attached: Option<trigger::Sender>,
@@ -614,17 +899,19 @@ impl WriteTaskMessage {
 /// * `combined_stop` - The task will stop when this future completes. This should combined the overall
 ///    shutdown-signal with latch that ensures this task will stop if the read/write tasks stop (to avoid
 ///    deadlocks).
-async fn attachment_task<F>(
+async fn attachment_task<A, F>(
     mut runtime: mpsc::Receiver<AgentRuntimeRequest>,
-    mut attachment: mpsc::Receiver<AgentAttachmentRequest>,
+    attachment: A,
     read_tx: mpsc::Sender<ReadTaskMessage>,
     write_tx: mpsc::Sender<WriteTaskMessage>,
     http_tx: mpsc::Sender<HttpLaneRuntimeSpec>,
     ext_link_tx: mpsc::Sender<ExternalLinkRequest>,
     mut combined_stop: F,
 ) where
+    A: Stream<Item = AgentAttachmentRequest>,
     F: Future + Unpin,
 {
+    let mut attachment = pin!(attachment);
     let mut attachments = FuturesUnordered::new();
 
     loop {
@@ -634,7 +921,7 @@ async fn attachment_task<F>(
             _ = attachments.next(), if !attachments.is_empty() => {},
             maybe_event = async { tokio::select! {
                 maybe_runtime = runtime.recv() => maybe_runtime.map(Either::Left),
-                maybe_att = attachment.recv() => maybe_att.map(Either::Right),
+                maybe_att = attachment.next() => maybe_att.map(Either::Right),
             }} => {
                 if let Some(event) = maybe_event {
                     match event {
@@ -643,8 +930,14 @@ async fn attachment_task<F>(
                                 AgentRuntimeRequest::AddLane(req) => write_tx.send(WriteTaskMessage::Lane(req)).await.is_ok(),
                                 AgentRuntimeRequest::AddHttpLane(req) => http_tx.send(req).await.is_ok(),
                                 AgentRuntimeRequest::AddStore(req) => write_tx.send(WriteTaskMessage::Store(req)).await.is_ok(),
+                                AgentRuntimeRequest::AddLaneAlias(req) => write_tx.send(WriteTaskMessage::Alias(req)).await.is_ok(),
                                 AgentRuntimeRequest::AdHoc(request) => ext_link_tx.send(ExternalLinkRequest::AdHoc(request)).await.is_ok(),
                                 AgentRuntimeRequest::OpenDownlink(req) => ext_link_tx.send(ExternalLinkRequest::Downlink(req)).await.is_ok(),
+                                // The state of the agent has already been restored so there is nothing to check.
+                                AgentRuntimeRequest::DeclareItems(DeclareItemsSpec { promise, .. }) => {
+                                    let _ = promise.send(Ok(()));
+                                    true
+                                }
                             };
                             if !succeeded {
                                 break;
@@ -695,6 +988,7 @@ where
             io: (tx, rx),
             completion,
             on_attached,
+            remote_addr,
         } => {
             info!(
                 "Attaching a new remote endpoint with ID {id} to the agent.",
@@ -728,6 +1022,7 @@ where
             });
             write_permit.send(WriteTaskMessage::Remote {
                 id,
+                addr: remote_addr,
                 writer: tx,
                 completion,
                 on_attached: write_on_attached,
@@ -794,20 +1089,27 @@ enum ReadTaskEvent {
 /// # Arguments
 /// * `config` - Configuration parameters for the task.
 /// * `initial_endpoints` - Initial lane endpoints that were created in the agent initialization phase.
+/// * `initial_aliases` - Aliases for the lanes that were registered in the agent initialization phase.
 /// * `reg_rx` - Channel for registering new lanes and remotes.
 /// * `write_tx` - Channel to communicate with the write task.
 /// * `stop_vote` - Votes to stop if this task becomes inactive (unanimity with the write task is required).
 /// * `stopping` - Initiates the clean shutdown procedure.
-/// * `aggregate_reporter` - Aggregated uplink reporter for all lanes of the agent.
+/// * `reporting` - Reporting context for the agent (to count commands, record envelopes and report
+///   the use of deprecated aliases).
+#[allow(clippy::too_many_arguments)]
 async fn read_task(
     config: AgentRuntimeConfig,
     initial_endpoints: Vec<LaneEndpoint<ByteWriter>>,
+    initial_aliases: Vec<LaneAlias>,
     reg_rx: mpsc::Receiver<ReadTaskMessage>,
     write_tx: mpsc::Sender<WriteTaskMessage>,
     stop_vote: timeout_coord::Voter,
     stopping: trigger::Receiver,
-    aggregate_reporter: Option<UplinkReporter>,
+    reporting: Option<NodeReporting>,
 ) {
+    let aggregate_reporter = reporting.as_ref().map(NodeReporting::aggregate);
+    let recording = reporting.as_ref().and_then(NodeReporting::recording);
+    let deprecation_reporter = reporting.as_ref().map(NodeReporting::deprecation);
     let mut remotes = SelectAll::new();
 
     let mut reg_stream = ReceiverStream::new(reg_rx).take_until(stopping);
@@ -821,6 +1123,7 @@ async fn read_task(
     };
 
     let mut name_mapping = HashMap::new();
+    let mut aliases = LaneAliases::default();
     let mut lanes = HashMap::new();
     let mut needs_flush = None;
     let mut voted = false;
@@ -828,14 +1131,25 @@ async fn read_task(
     for LaneEndpoint {
         name,
         kind,
+        track_origin,
         io,
         reporter,
+        limits: FrameLimits {
+            max_fragment_size, ..
+        },
         ..
     } in initial_endpoints.into_iter()
     {
         let i = next_id();
         name_mapping.insert(name, i);
-        lanes.insert(i, LaneSender::new(io, kind, reporter));
+        lanes.insert(
+            i,
+            LaneSender::new(io, kind, track_origin, reporter, max_fragment_size),
+        );
+    }
+
+    for alias in initial_aliases {
+        aliases.add(&mut name_mapping, alias);
     }
 
     loop {
@@ -884,6 +1198,9 @@ async fn read_task(
                     name_mapping.insert(name, id);
                     lanes.insert(id, sender);
                 }
+                ReadTaskMessage::Alias(alias) => {
+                    aliases.add(&mut name_mapping, alias);
+                }
                 ReadTaskMessage::Remote {
                     reader,
                     on_attached,
@@ -908,131 +1225,234 @@ async fn read_task(
                         voted = false;
                     }
                 }
-                debug!(message = ?msg, "Processing envelope.");
+                // All of the events that are logged while the envelope is handled are associated
+                // with the ID assigned to it when it was received.
+                let span = match msg.correlation_id {
+                    Some(id) => debug_span!("Envelope", correlation_id = %id),
+                    None => Span::none(),
+                };
+                span.in_scope(|| debug!(message = ?msg, "Processing envelope."));
+                if let Some(recording) = &recording {
+                    recording.record_request(&msg);
+                }
                 let RequestMessage {
                     path,
                     origin,
                     envelope,
+                    correlation_id,
                 } = msg;
-
-                if let Some(id) = name_mapping.get(path.lane.as_str()) {
-                    if matches!(&needs_flush, Some(i) if i != id) {
-                        trace!(
-                            "Flushing lane '{name}' (id = {id})",
-                            name = path.lane,
-                            id = id
+                // A sync that is restricted to a range of keys is handled in the same way as any
+                // other sync until the request is passed to the lane.
+                let sync_range = match &envelope {
+                    Operation::SyncRange(_, range) => Some(range.clone()),
+                    _ => None,
+                };
+                let keep_running = async {
+                    if let Some(id) = name_mapping.get(path.lane.as_str()) {
+                        let by_alias = aliases.check_deprecated(
+                            origin,
+                            path.lane.as_str(),
+                            deprecation_reporter.as_ref(),
                         );
-                        flush_lane(&mut lanes, &mut needs_flush).await;
-                    }
-                    if let Some(lane_tx) = lanes.get_mut(id) {
-                        let RelativeAddress { lane, .. } = path;
-                        let origin: Uuid = origin;
-                        match envelope {
-                            Operation::Link => {
-                                debug!(
-                                    "Attempting to set up link to {} from lane '{}'.",
-                                    origin, lane
-                                );
-                                if write_tx
-                                    .send(WriteTaskMessage::Coord(RwCoordinationMessage::Link {
-                                        origin,
-                                        lane: Text::new(lane.as_str()),
-                                    }))
-                                    .await
-                                    .is_err()
-                                {
-                                    error!(TASK_COORD_ERR);
-                                    break;
-                                }
-                            }
-                            Operation::Sync => {
-                                debug!(
-                                    "Attempting to synchronize {} with lane '{}'.",
-                                    origin, lane
-                                );
-                                if lane_tx.start_sync(origin).await.is_err() {
-                                    error!(
-                                        "Failed to communicate with lane '{}'. Removing handle.",
-                                        lane
+                        if matches!(&needs_flush, Some(i) if i != id) {
+                            trace!(
+                                "Flushing lane '{name}' (id = {id})",
+                                name = path.lane,
+                                id = id
+                            );
+                            flush_lane(&mut lanes, &mut needs_flush).await;
+                        }
+                        if let Some(lane_tx) = lanes.get_mut(id) {
+                            let RelativeAddress { lane, .. } = path;
+                            let origin: Uuid = origin;
+                            match envelope {
+                                Operation::Link(params) => {
+                                    debug!(
+                                        "Attempting to set up link to {} from lane '{}'.",
+                                        origin, lane
                                     );
-                                    if let Some(id) = name_mapping.remove(lane.as_str()) {
-                                        lanes.remove(&id);
+                                    if write_tx
+                                        .send(WriteTaskMessage::Coord(RwCoordinationMessage::Link {
+                                            origin,
+                                            lane: Text::new(lane.as_str()),
+                                            params,
+                                            correlation_id,
+                                        }))
+                                        .await
+                                        .is_err()
+                                    {
+                                        error!(TASK_COORD_ERR);
+                                        return false;
                                     }
-                                };
-                            }
-                            Operation::Command(body) => {
-                                trace!(body = ?body, "Dispatching command envelope from {} to lane '{}'.", origin, lane);
-                                if let Some(reporter) = &aggregate_reporter {
-                                    reporter.count_commands(1);
                                 }
-                                match lane_tx.feed_frame(body).await {
-                                    Err(LaneSendError::Io(_)) => {
-                                        error!("Failed to communicate with lane '{}'. Removing handle.", lane);
-                                        if let Some(id) = name_mapping.remove(lane.as_str()) {
-                                            lanes.remove(&id);
-                                        }
+                                Operation::Sync(params) | Operation::SyncRange(params, _) => {
+                                    debug!(
+                                        "Attempting to synchronize {} with lane '{}'.",
+                                        origin, lane
+                                    );
+                                    let since = params.since;
+                                    let params = params.with_since(None);
+                                    if by_alias
+                                        && write_tx
+                                            .send(WriteTaskMessage::Coord(
+                                                RwCoordinationMessage::LinkName {
+                                                    origin,
+                                                    lane: Text::new(lane.as_str()),
+                                                },
+                                            ))
+                                            .await
+                                            .is_err()
+                                    {
+                                        error!(TASK_COORD_ERR);
+                                        return false;
                                     }
-                                    Err(LaneSendError::Extraction(error)) => {
-                                        error!(error = ?error, "Received invalid envelope from {} for lane '{}'", origin, lane);
+                                    if !params.is_empty()
+                                        && write_tx
+                                            .send(WriteTaskMessage::Coord(
+                                                RwCoordinationMessage::Params {
+                                                    origin,
+                                                    lane: Text::new(lane.as_str()),
+                                                    params,
+                                                },
+                                            ))
+                                            .await
+                                            .is_err()
+                                    {
+                                        error!(TASK_COORD_ERR);
+                                        return false;
+                                    }
+                                    if let Some(correlation_id) = correlation_id {
                                         if write_tx
                                             .send(WriteTaskMessage::Coord(
-                                                RwCoordinationMessage::BadEnvelope {
+                                                RwCoordinationMessage::Correlate {
                                                     origin,
                                                     lane: Text::new(lane.as_str()),
-                                                    error,
+                                                    correlation_id,
                                                 },
                                             ))
                                             .await
                                             .is_err()
                                         {
                                             error!(TASK_COORD_ERR);
-                                            break;
+                                            return false;
                                         }
                                     }
-                                    _ => {
-                                        let _ = lane_tx.flush().await;
-                                        needs_flush = Some(*id);
+                                    let result = match sync_range {
+                                        Some(range) => {
+                                            lane_tx.start_sync_range(origin, range).await
+                                        }
+                                        None => lane_tx.start_sync(origin, since).await,
+                                    };
+                                    if result.is_err() {
+                                        error!(
+                                            "Failed to communicate with lane '{}'. Removing handle.",
+                                            lane
+                                        );
+                                        if let Some(id) = name_mapping.remove(lane.as_str()) {
+                                            lanes.remove(&id);
+                                        }
+                                    };
+                                }
+                                Operation::Command(body) => {
+                                    trace!(body = ?body, "Dispatching command envelope from {} to lane '{}'.", origin, lane);
+                                    if let Some(reporter) = &aggregate_reporter {
+                                        reporter.count_commands(1);
+                                    }
+                                    match lane_tx.feed_frame(origin, correlation_id, body).await {
+                                        Err(LaneSendError::Io(_)) => {
+                                            error!("Failed to communicate with lane '{}'. Removing handle.", lane);
+                                            if let Some(id) = name_mapping.remove(lane.as_str()) {
+                                                lanes.remove(&id);
+                                            }
+                                        }
+                                        Err(LaneSendError::Extraction(error)) => {
+                                            error!(error = ?error, "Received invalid envelope from {} for lane '{}'", origin, lane);
+                                            if write_tx
+                                                .send(WriteTaskMessage::Coord(
+                                                    RwCoordinationMessage::BadEnvelope {
+                                                        origin,
+                                                        lane: Text::new(lane.as_str()),
+                                                        error,
+                                                    },
+                                                ))
+                                                .await
+                                                .is_err()
+                                            {
+                                                error!(TASK_COORD_ERR);
+                                                return false;
+                                            }
+                                        }
+                                        _ => {
+                                            let _ = lane_tx.flush().await;
+                                            needs_flush = Some(*id);
+                                        }
                                     }
                                 }
-                            }
-                            Operation::Unlink => {
-                                debug!(
-                                    "Attempting to stop the link to {} from lane '{}'.",
-                                    origin, lane
-                                );
-                                if write_tx
-                                    .send(WriteTaskMessage::Coord(RwCoordinationMessage::Unlink {
+                                Operation::Unlink => {
+                                    debug!(
+                                        "Attempting to stop the link to {} from lane '{}'.",
+                                        origin, lane
+                                    );
+                                    if write_tx
+                                        .send(WriteTaskMessage::Coord(RwCoordinationMessage::Unlink {
+                                            origin,
+                                            lane: Text::new(lane.as_str()),
+                                            correlation_id,
+                                        }))
+                                        .await
+                                        .is_err()
+                                    {
+                                        error!(TASK_COORD_ERR);
+                                        return false;
+                                    }
+                                }
+                                Operation::Ack => {
+                                    trace!(
+                                        "Received acknowledgement of a sync page from {} for lane '{}'.",
                                         origin,
-                                        lane: Text::new(lane.as_str()),
-                                    }))
-                                    .await
-                                    .is_err()
-                                {
-                                    error!(TASK_COORD_ERR);
-                                    break;
+                                        lane
+                                    );
+                                    if write_tx
+                                        .send(WriteTaskMessage::Coord(RwCoordinationMessage::Ack {
+                                            origin,
+                                            lane: Text::new(lane.as_str()),
+                                        }))
+                                        .await
+                                        .is_err()
+                                    {
+                                        error!(TASK_COORD_ERR);
+                                        return false;
+                                    }
                                 }
                             }
                         }
-                    }
-                } else {
-                    info!("Received envelope for non-existent lane '{}'.", path.lane);
-                    let flush = flush_lane(&mut lanes, &mut needs_flush);
-                    let result = if envelope.is_command() {
-                        flush.await;
-                        Ok(())
                     } else {
-                        let send_err = write_tx.send(WriteTaskMessage::Coord(
-                            RwCoordinationMessage::UnknownLane {
-                                origin,
-                                path: RelativeAddress::text(path.node.as_str(), path.lane.as_str()),
-                            },
-                        ));
-                        join(flush, send_err).await.1
-                    };
-                    if result.is_err() {
-                        error!(TASK_COORD_ERR);
-                        break;
+                        info!("Received envelope for non-existent lane '{}'.", path.lane);
+                        let flush = flush_lane(&mut lanes, &mut needs_flush);
+                        let result = if envelope.is_command() {
+                            flush.await;
+                            Ok(())
+                        } else {
+                            let send_err = write_tx.send(WriteTaskMessage::Coord(
+                                RwCoordinationMessage::UnknownLane {
+                                    origin,
+                                    path: RelativeAddress::text(path.node.as_str(), path.lane.as_str()),
+                                    correlation_id,
+                                },
+                            ));
+                            join(flush, send_err).await.1
+                        };
+                        if result.is_err() {
+                            error!(TASK_COORD_ERR);
+                            return false;
+                        }
                     }
+                    true
+                }
+                .instrument(span)
+                .await;
+                if !keep_running {
+                    break;
                 }
             }
             ReadTaskEvent::Timeout => {
@@ -1050,6 +1470,67 @@ async fn read_task(
     }
 }
 
+/// Keeps track of the aliases of the lanes of the agent in the read task.
+#[derive(Debug, Default)]
+struct LaneAliases {
+    /// Map from each alias to the name of the lane to which it refers.
+    targets: HashMap<Text, Text>,
+    /// The aliases that have already been reported as used by each remote.
+    reported: HashSet<(Uuid, Text)>,
+}
+
+impl LaneAliases {
+    /// Register an alias, mapping it to the ID of its lane. Aliases never replace the names of lanes.
+    fn add(&mut self, name_mapping: &mut HashMap<Text, u64>, alias: LaneAlias) {
+        let LaneAlias { alias, name } = alias;
+        if name_mapping.contains_key(alias.as_str()) {
+            warn!(
+                "Ignoring alias '{}' for lane '{}' as it clashes with another lane.",
+                alias, name
+            );
+        } else if let Some(id) = name_mapping.get(name.as_str()).copied() {
+            info!("Lane '{}' can be addressed as '{}'.", name, alias);
+            name_mapping.insert(alias.clone(), id);
+            self.targets.insert(alias, name);
+        } else {
+            warn!(
+                "Ignoring alias '{}' for non-existent lane '{}'.",
+                alias, name
+            );
+        }
+    }
+
+    /// Determine whether a lane was addressed by an alias. The first time that a remote uses an
+    /// alias, it is reported as deprecated.
+    fn check_deprecated(
+        &mut self,
+        origin: Uuid,
+        lane_name: &str,
+        reporter: Option<&DeprecationReporter>,
+    ) -> bool {
+        let LaneAliases { targets, reported } = self;
+        if let Some((alias, lane)) = targets.get_key_value(lane_name) {
+            if !reported.contains(&(origin, alias.clone())) {
+                warn!(
+                    "Remote {} addressed lane '{}' by its deprecated alias '{}'.",
+                    origin, lane, alias
+                );
+                reported.insert((origin, alias.clone()));
+                if let Some(reporter) = reporter {
+                    reporter.report_deprecated(DeprecationEvent {
+                        remote_id: origin,
+                        alias: alias.clone(),
+                        lane: lane.clone(),
+                    });
+                }
+            }
+            true
+        } else {
+            false
+        }
+    }
+}
+
 async fn flush_lane(lanes: &mut HashMap<u64, LaneSender>, needs_flush: &mut Option<u64>) {
     if let Some(id) = needs_flush.take() {
         if let Some(tx) = lanes.get_mut(&id) {
@@ -1074,7 +1555,12 @@ enum WriteTaskEvent<I> {
     /// Reading from a store failed.
     StoreFailed(u64),
     /// A remote may have been without active links beyond the configured timeout.
-    PruneRemote(Uuid),
+    PruneRemote {
+        remote_id: Uuid,
+        idle_since: Instant,
+    },
+    /// The delay for a rate limited uplink, from a lane to a remote, has elapsed.
+    ReleaseUplink { remote_id: Uuid, lane_id: u64 },
     /// The task timed out due to inactivity.
     Timeout,
     /// The stop signal was received.
@@ -1087,6 +1573,8 @@ struct WriteTaskConfiguration {
     identity: Uuid,
     node_uri: Text,
     runtime_config: AgentRuntimeConfig,
+    interceptor: Option<Arc<dyn OutgoingInterceptor>>,
+    prune_policy: Option<Arc<dyn PrunePolicy>>,
 }
 
 impl WriteTaskConfiguration {
@@ -1095,8 +1583,20 @@ impl WriteTaskConfiguration {
             identity,
             node_uri,
             runtime_config,
+            interceptor: None,
+            prune_policy: None,
         }
     }
+
+    fn with_interceptor(mut self, interceptor: Option<Arc<dyn OutgoingInterceptor>>) -> Self {
+        self.interceptor = interceptor;
+        self
+    }
+
+    fn with_prune_policy(mut self, prune_policy: Option<Arc<dyn PrunePolicy>>) -> Self {
+        self.prune_policy = prune_policy;
+        self
+    }
 }
 
 /// Manages the timeout for the write task. This can be disabled (to prevent it from firing repeatedly
@@ -1120,6 +1620,16 @@ type StoreResult<I> = InitResult<(StoreEndpoint, I)>;
 
 type ItemInitTask<'a> = BoxFuture<'a, InitResult<ItemEndpoint>>;
 
+/// The maximum number of consecutive events that the write task will handle from sources other
+/// than the lanes and stores (messages, completed writes etc.) before the lanes and stores are
+/// given priority.
+const LANE_STARVATION_LIMIT: usize = 32;
+
+/// The maximum number of consecutive writes to a single remote in each write cycle. Once a remote
+/// has used its budget, its next write is deferred until the next cycle (which starts when the lanes
+/// and stores are polled or when no other work is immediately available).
+const REMOTE_WRITE_BUDGET: usize = 16;
+
 /// Aggregates all of the streams of events for the write task.
 #[derive(Debug)]
 struct WriteTaskEvents<'a, S, W, I> {
@@ -1129,6 +1639,10 @@ struct WriteTaskEvents<'a, S, W, I> {
     message_stream: S,
     lanes_and_stores: SelectAll<StopAfterError<ResponseReceiver<I>>>,
     pending_writes: FuturesUnordered<W>,
+    deferred_writes: VecDeque<W>, //Writes for remotes that have used their budget for the current write cycle.
+    cycle_writes: HashMap<Uuid, usize>, //The number of consecutive writes to each remote in the current write cycle.
+    pending_releases: FuturesUnordered<BoxFuture<'static, (Uuid, u64)>>,
+    since_lane_event: usize, //The number of consecutive events that did not come from the lanes and stores.
 }
 
 impl<'a, S, W, I> WriteTaskEvents<'a, S, W, I>
@@ -1162,6 +1676,10 @@ where
 
             lanes_and_stores: Default::default(),
             pending_writes: Default::default(),
+            deferred_writes: Default::default(),
+            cycle_writes: Default::default(),
+            pending_releases: Default::default(),
+            since_lane_event: 0,
         }
     }
 
@@ -1180,6 +1698,24 @@ where
         self.pending_writes.push(write);
     }
 
+    /// Schedule the write that follows a completed write to a remote. If the remote has used its
+    /// budget for the current write cycle, the write is deferred until the next cycle.
+    fn schedule_next_write(&mut self, remote_id: Uuid, write: W) {
+        let WriteTaskEvents {
+            pending_writes,
+            deferred_writes,
+            cycle_writes,
+            ..
+        } = self;
+        let count = cycle_writes.entry(remote_id).or_default();
+        *count += 1;
+        if *count < REMOTE_WRITE_BUDGET {
+            pending_writes.push(write);
+        } else {
+            deferred_writes.push_back(write);
+        }
+    }
+
     /// Schedule a remote to be pruned (after a period of inactivity).
     fn schedule_prune(&mut self, remote_id: Uuid) {
         let WriteTaskEvents {
@@ -1190,6 +1726,20 @@ where
         prune_remotes.push(remote_id, *remote_timeout);
     }
 
+    /// Consider a remote for pruning again, after a delay, if the prune policy deferred it.
+    fn defer_prune(&mut self, remote_id: Uuid, idle_since: Instant, delay: Duration) {
+        self.prune_remotes.defer(remote_id, idle_since, delay);
+    }
+
+    /// Schedule a rate limited uplink to be released at the specified time.
+    fn schedule_release(&mut self, remote_id: Uuid, lane_id: u64, at: Instant) {
+        self.pending_releases.push(
+            tokio::time::sleep_until(at)
+                .map(move |_| (remote_id, lane_id))
+                .boxed(),
+        );
+    }
+
     /// Disable the agent timeout (if the stop vote has been made and not yet rescinded).
     fn disable_timeout(&mut self) {
         self.inactive_timeout.enabled = false;
@@ -1208,14 +1758,22 @@ where
     I: Unpin + Copy,
 {
     /// Select the next of any type of event. This is biased and will try to clear existing work
-    /// before adding more work.
+    /// before adding more work. To prevent the lanes and stores from being starved when there is a
+    /// lot of other work, if [`LANE_STARVATION_LIMIT`] consecutive events have come from other
+    /// sources, any event that is immediately available from the lanes and stores is taken first.
+    /// Similarly, a remote that always has more to write cannot delay the lanes and stores (and so
+    /// the events for other remotes) by more than [`REMOTE_WRITE_BUDGET`] writes.
     async fn select_next(&mut self) -> WriteTaskEvent<I> {
         let WriteTaskEvents {
             inactive_timeout,
             message_stream,
             lanes_and_stores,
             pending_writes,
+            deferred_writes,
+            cycle_writes,
             prune_remotes,
+            pending_releases,
+            since_lane_event,
             ..
         } = self;
 
@@ -1227,12 +1785,24 @@ where
 
         let mut delay = timeout_delay.as_mut();
 
-        loop {
+        if *since_lane_event >= LANE_STARVATION_LIMIT && !lanes_and_stores.is_empty() {
+            *since_lane_event = 0;
+            if let Some(event) = lanes_and_stores
+                .next()
+                .now_or_never()
+                .and_then(|maybe_result| lane_event(maybe_result, delay.as_mut(), *timeout))
+            {
+                start_write_cycle(pending_writes, deferred_writes, cycle_writes);
+                return event;
+            }
+        }
+
+        let event = loop {
             tokio::select! {
                 biased;
                 maybe_remote = prune_remotes.next(), if !prune_remotes.is_empty() => {
-                    if let Some(remote_id) = maybe_remote {
-                        break WriteTaskEvent::PruneRemote(remote_id);
+                    if let Some((remote_id, idle_since)) = maybe_remote {
+                        break WriteTaskEvent::PruneRemote { remote_id, idle_since };
                     }
                 }
                 maybe_msg = message_stream.next() => {
@@ -1255,27 +1825,20 @@ where
                         break WriteTaskEvent::WriteDone(result);
                     }
                 }
+                maybe_release = pending_releases.next(), if !pending_releases.is_empty() => {
+                    if let Some((remote_id, lane_id)) = maybe_release {
+                        break WriteTaskEvent::ReleaseUplink { remote_id, lane_id };
+                    }
+                }
                 maybe_result = lanes_and_stores.next(), if !lanes_and_stores.is_empty() => {
-                    match maybe_result {
-                        Some(Ok(response)) =>  {
-                            if response.is_lane() {
-                                delay.as_mut().reset(
-                                    Instant::now()
-                                        .checked_add(*timeout)
-                                        .expect("Timer overflow."),
-                                );
-                            }
-                            break WriteTaskEvent::Event(response);
-                        },
-                        Some(Err(Failed::Lane(item_id))) => {
-                            break WriteTaskEvent::LaneFailed(item_id);
-                        },
-                        Some(Err(Failed::Store(item_id))) => {
-                            break WriteTaskEvent::StoreFailed(item_id);
-                        },
-                        _ => {}
+                    if let Some(event) = lane_event(maybe_result, delay.as_mut(), *timeout) {
+                        break event;
                     }
                 },
+                _ = future::ready(()), if !deferred_writes.is_empty() => {
+                    // Nothing else is immediately available so the deferred writes can proceed.
+                    start_write_cycle(pending_writes, deferred_writes, cycle_writes);
+                }
                 _ = &mut delay, if *timeout_enabled => {
                     break if lanes_and_stores.is_empty() {
                         trace!("Stopping as there are no active lanes.");
@@ -1285,12 +1848,30 @@ where
                     };
                 }
             };
+        };
+        if matches!(
+            event,
+            WriteTaskEvent::Event(_)
+                | WriteTaskEvent::LaneFailed(_)
+                | WriteTaskEvent::StoreFailed(_)
+        ) {
+            *since_lane_event = 0;
+            start_write_cycle(pending_writes, deferred_writes, cycle_writes);
+        } else {
+            *since_lane_event += 1;
         }
+        event
     }
 
     /// Select only from pending writes (used in the shutdown process).
     async fn next_write(&mut self) -> Option<WriteResult> {
-        let WriteTaskEvents { pending_writes, .. } = self;
+        let WriteTaskEvents {
+            pending_writes,
+            deferred_writes,
+            cycle_writes,
+            ..
+        } = self;
+        start_write_cycle(pending_writes, deferred_writes, cycle_writes);
         if pending_writes.is_empty() {
             None
         } else {
@@ -1299,6 +1880,39 @@ where
     }
 }
 
+/// Start a new write cycle, resetting the budgets of the remotes and resuming any deferred writes.
+fn start_write_cycle<W>(
+    pending_writes: &mut FuturesUnordered<W>,
+    deferred_writes: &mut VecDeque<W>,
+    cycle_writes: &mut HashMap<Uuid, usize>,
+) {
+    cycle_writes.clear();
+    pending_writes.extend(deferred_writes.drain(..));
+}
+
+/// Interpret the result of polling the lanes and stores of the agent, resetting the agent timeout
+/// if a lane produced an event.
+fn lane_event<I>(
+    maybe_result: Option<Result<ItemResponse<I>, Failed>>,
+    delay: Pin<&mut Sleep>,
+    timeout: Duration,
+) -> Option<WriteTaskEvent<I>> {
+    match maybe_result? {
+        Ok(response) => {
+            if response.is_lane() {
+                delay.reset(
+                    Instant::now()
+                        .checked_add(timeout)
+                        .expect("Timer overflow."),
+                );
+            }
+            Some(WriteTaskEvent::Event(response))
+        }
+        Err(Failed::Lane(item_id)) => Some(WriteTaskEvent::LaneFailed(item_id)),
+        Err(Failed::Store(item_id)) => Some(WriteTaskEvent::StoreFailed(item_id)),
+    }
+}
+
 /// The internal state of the write task.
 #[derive(Debug)]
 struct WriteTaskState {
@@ -1307,6 +1921,13 @@ struct WriteTaskState {
     /// Manages writes to remotes (particularly backpressure relief).
     remote_tracker: RemoteTracker,
     store_counter: u64,
+    /// The most recent link attempt from each remote.
+    link_attempts: HashMap<Uuid, Instant>,
+    /// Decides when idle remotes are pruned (all are pruned if absent).
+    prune_policy: Option<Arc<dyn PrunePolicy>>,
+    prune_reporter: Option<PruneReporter>,
+    /// The correlation IDs of the outstanding sync requests, keyed by remote and lane ID.
+    sync_correlations: HashMap<(Uuid, u64), CorrelationId>,
 }
 
 /// Possible results of handling a message from the coordination/read tasks.
@@ -1317,6 +1938,8 @@ enum TaskMessageResult<I> {
     AddLane(LaneEndpoint<Io>, Option<I>),
     /// Register a new store.
     AddStore(StoreEndpoint, I),
+    /// Register an alias for a lane with the read task.
+    AddAlias(LaneAlias),
     /// Schedule a write to one or all remotes (if no ID is specified).
     ScheduleWrite {
         write: WriteTask,
@@ -1394,14 +2017,43 @@ impl<W> Iterator for Writes<W> {
 }
 
 impl WriteTaskState {
-    fn new(identity: Uuid, node_uri: Text, aggregate_reporter: Option<UplinkReporter>) -> Self {
+    fn new(
+        identity: Uuid,
+        node_uri: Text,
+        aggregate_reporter: Option<UplinkReporter>,
+        interceptor: Option<Arc<dyn OutgoingInterceptor>>,
+        backpressure: UplinkBackpressure,
+        advisory: LinkAdvisoryConfig,
+    ) -> Self {
         WriteTaskState {
             links: Links::new(aggregate_reporter),
-            remote_tracker: RemoteTracker::new(identity, node_uri),
+            remote_tracker: RemoteTracker::new(identity, node_uri)
+                .with_interceptor(interceptor)
+                .with_backpressure(backpressure)
+                .with_advisory(advisory),
             store_counter: 0,
+            link_attempts: Default::default(),
+            prune_policy: None,
+            prune_reporter: None,
+            sync_correlations: Default::default(),
         }
     }
 
+    fn with_recording(mut self, recording: Option<EnvelopeRecording>) -> Self {
+        self.remote_tracker = self.remote_tracker.with_recording(recording);
+        self
+    }
+
+    fn with_pruning(
+        mut self,
+        prune_policy: Option<Arc<dyn PrunePolicy>>,
+        prune_reporter: Option<PruneReporter>,
+    ) -> Self {
+        self.prune_policy = prune_policy;
+        self.prune_reporter = prune_reporter;
+        self
+    }
+
     /// Register a new lane with the state, assigning it a unique ID.
     fn register_lane(&mut self, name: Text, reporter: Option<UplinkReporter>) -> u64 {
         let WriteTaskState {
@@ -1436,6 +2088,8 @@ impl WriteTaskState {
         let WriteTaskState {
             links,
             remote_tracker,
+            link_attempts,
+            sync_correlations,
             ..
         } = self;
         match reg {
@@ -1470,26 +2124,58 @@ impl WriteTaskState {
                     _ => TaskMessageResult::Nothing,
                 }
             }
+            WriteTaskMessage::Alias(LaneAliasSpec {
+                alias,
+                name,
+                promise,
+            }) => {
+                let _ = promise.send(Ok(()));
+                let alias = LaneAlias::new(alias, name);
+                if remote_tracker.lane_registry().add_alias(&alias) {
+                    TaskMessageResult::AddAlias(alias)
+                } else {
+                    warn!(
+                        "Ignoring alias '{}' for lane '{}' as the lane does not exist or the alias clashes with another lane.",
+                        alias.alias, alias.name
+                    );
+                    TaskMessageResult::Nothing
+                }
+            }
             WriteTaskMessage::Remote {
                 id,
+                addr,
                 writer,
                 completion,
                 on_attached,
             } => {
-                remote_tracker.insert(id, writer, completion);
+                remote_tracker.insert(id, addr, writer, completion);
                 if let Some(on_attached) = on_attached {
                     on_attached.trigger();
                 }
                 TaskMessageResult::AddPruneTimeout(id)
             }
-            WriteTaskMessage::Coord(RwCoordinationMessage::Link { origin, lane }) => {
+            WriteTaskMessage::Coord(RwCoordinationMessage::Link {
+                origin,
+                lane,
+                params,
+                correlation_id,
+            }) => {
                 info!("Attempting to set up link from '{}' to {}.", lane, origin);
+                link_attempts.insert(origin, Instant::now());
                 match remote_tracker.lane_registry().id_for(lane.as_str()) {
                     Some(id) if remote_tracker.has_remote(origin) => {
                         links.insert(id, origin);
-                        remote_tracker
-                            .push_special(SpecialAction::Linked(id), &origin)
-                            .into()
+                        remote_tracker.set_params(origin, id, params);
+                        remote_tracker.set_link_name(origin, id, lane);
+                        let linked = remote_tracker.push_correlated_special(
+                            SpecialAction::Linked(id),
+                            correlation_id,
+                            &origin,
+                        );
+                        // The linked message always takes the writer first so any advice is
+                        // queued behind it.
+                        let advised = remote_tracker.advise_on_link(origin, id, links.count());
+                        linked.or(advised).into()
                     }
                     Some(_) => {
                         error!("No remote with ID {}.", origin);
@@ -1505,7 +2191,47 @@ impl WriteTaskState {
                     }
                 }
             }
-            WriteTaskMessage::Coord(RwCoordinationMessage::Unlink { origin, lane }) => {
+            WriteTaskMessage::Coord(RwCoordinationMessage::Params {
+                origin,
+                lane,
+                params,
+            }) => {
+                debug!(params = ?params, "Updating the parameters of the uplink from '{}' to {}.", lane, origin);
+                if let Some(id) = remote_tracker.lane_registry().id_for(lane.as_str()) {
+                    remote_tracker.set_params(origin, id, params);
+                }
+                TaskMessageResult::Nothing
+            }
+            WriteTaskMessage::Coord(RwCoordinationMessage::LinkName { origin, lane }) => {
+                debug!("Lane '{}' was addressed by an alias by {}.", lane, origin);
+                if let Some(id) = remote_tracker.lane_registry().id_for(lane.as_str()) {
+                    remote_tracker.set_link_name(origin, id, lane);
+                }
+                TaskMessageResult::Nothing
+            }
+            WriteTaskMessage::Coord(RwCoordinationMessage::Ack { origin, lane }) => {
+                trace!("Sync page from '{}' acknowledged by {}.", lane, origin);
+                if let Some(id) = remote_tracker.lane_registry().id_for(lane.as_str()) {
+                    remote_tracker.ack(origin, id).into()
+                } else {
+                    TaskMessageResult::Nothing
+                }
+            }
+            WriteTaskMessage::Coord(RwCoordinationMessage::Correlate {
+                origin,
+                lane,
+                correlation_id,
+            }) => {
+                if let Some(id) = remote_tracker.lane_registry().id_for(lane.as_str()) {
+                    sync_correlations.insert((origin, id), correlation_id);
+                }
+                TaskMessageResult::Nothing
+            }
+            WriteTaskMessage::Coord(RwCoordinationMessage::Unlink {
+                origin,
+                lane,
+                correlation_id,
+            }) => {
                 info!(
                     "Attempting to close any link from '{}' to {}.",
                     lane, origin
@@ -1514,8 +2240,11 @@ impl WriteTaskState {
                     if links.is_linked(origin, lane_id) {
                         let schedule_prune = links.remove(lane_id, origin).into_option();
                         let message = Text::new("\"Link closed.\"");
-                        let maybe_write = remote_tracker
-                            .push_special(SpecialAction::unlinked(lane_id, message), &origin);
+                        let maybe_write = remote_tracker.push_correlated_special(
+                            SpecialAction::unlinked(lane_id, message),
+                            correlation_id,
+                            &origin,
+                        );
                         if let Some(write) = maybe_write {
                             TaskMessageResult::ScheduleWrite {
                                 write,
@@ -1535,13 +2264,21 @@ impl WriteTaskState {
                     TaskMessageResult::Nothing
                 }
             }
-            WriteTaskMessage::Coord(RwCoordinationMessage::UnknownLane { origin, path }) => {
+            WriteTaskMessage::Coord(RwCoordinationMessage::UnknownLane {
+                origin,
+                path,
+                correlation_id,
+            }) => {
                 info!(
                     "Received envelope for non-existent lane '{}' from {}.",
                     path.lane, origin
                 );
                 remote_tracker
-                    .push_special(SpecialAction::lane_not_found(path.lane), &origin)
+                    .push_correlated_special(
+                        SpecialAction::lane_not_found(path.lane),
+                        correlation_id,
+                        &origin,
+                    )
                     .into()
             }
             WriteTaskMessage::Coord(RwCoordinationMessage::BadEnvelope {
@@ -1565,13 +2302,28 @@ impl WriteTaskState {
         let WriteTaskState {
             links,
             remote_tracker: write_tracker,
+            sync_correlations,
             ..
         } = self;
 
         use either::Either;
 
-        let LaneData { target, response } = response;
+        let LaneData {
+            target,
+            response,
+            correlation_id,
+            origin,
+        } = response;
         if let Some(remote_id) = target {
+            // The responses to a sync are correlated with the sync request.
+            let correlation_id = if matches!(
+                response,
+                UplinkResponse::Synced(_) | UplinkResponse::SyncedAt(_)
+            ) {
+                sync_correlations.remove(&(remote_id, id))
+            } else {
+                sync_correlations.get(&(remote_id, id)).copied()
+            };
             trace!(response = ?response, "Routing response to {}.", remote_id);
             links.count_single(id);
             let write = if !links.is_linked(remote_id, id) {
@@ -1579,13 +2331,13 @@ impl WriteTaskState {
                 links.insert(id, remote_id);
                 let write1 = write_tracker.push_special(SpecialAction::Linked(id), &remote_id);
                 let write2 = write_tracker
-                    .push_write(id, response, &remote_id)
+                    .push_write(id, response, correlation_id, None, &remote_id)
                     .unwrap_or_else(discard_error);
                 Writes::from((write1, write2))
             } else {
                 Writes::from(
                     write_tracker
-                        .push_write(id, response, &remote_id)
+                        .push_write(id, response, correlation_id, None, &remote_id)
                         .unwrap_or_else(discard_error),
                 )
             };
@@ -1596,7 +2348,7 @@ impl WriteTaskState {
             Either::Right(targets.iter().zip(std::iter::repeat(response)).flat_map(
                 move |(remote_id, response)| {
                     write_tracker
-                        .push_write(id, response, remote_id)
+                        .push_write(id, response, correlation_id, origin, remote_id)
                         .unwrap_or_else(discard_error)
                 },
             ))
@@ -1610,13 +2362,65 @@ impl WriteTaskState {
     fn remove_remote(&mut self, remote_id: Uuid, reason: DisconnectionReason) {
         info!("Removing remote connection {}.", remote_id);
         self.links.remove_remote(remote_id);
+        self.link_attempts.remove(&remote_id);
+        self.sync_correlations
+            .retain(|(origin, _), _| *origin != remote_id);
         self.remote_tracker.remove_remote(remote_id, reason);
     }
 
-    /// Remove a registered remote only if it has no links.
-    fn remove_remote_if_idle(&mut self, remote_id: Uuid) {
-        if self.links.linked_to(remote_id).is_none() {
-            self.remove_remote(remote_id, DisconnectionReason::RemoteTimedOut);
+    /// Remove a registered remote only if it has no links and the prune policy permits it. If the
+    /// policy defers the pruning, the delay after which it should be considered again is returned.
+    ///
+    /// # Arguments
+    /// * `remote_id` - The ID of the remote.
+    /// * `idle_since` - The instant at which the remote stopped having any links.
+    fn remove_remote_if_idle(&mut self, remote_id: Uuid, idle_since: Instant) -> Option<Duration> {
+        let WriteTaskState {
+            links,
+            remote_tracker,
+            link_attempts,
+            prune_policy,
+            prune_reporter,
+            ..
+        } = self;
+        if links.linked_to(remote_id).is_some() || !remote_tracker.has_remote(remote_id) {
+            return None;
+        }
+        let now = Instant::now();
+        let idle_for = now.saturating_duration_since(idle_since);
+        let decision = if let Some(policy) = prune_policy {
+            let idle_remote = IdleRemote {
+                remote_id,
+                idle_for,
+                since_link_attempt: link_attempts
+                    .get(&remote_id)
+                    .map(|at| now.saturating_duration_since(*at)),
+            };
+            policy.decide(&idle_remote)
+        } else {
+            PruneDecision::Prune
+        };
+        match decision {
+            PruneDecision::Prune => {
+                if let Some(reporter) = prune_reporter {
+                    reporter.report_pruned(PruneEvent {
+                        remote_id,
+                        idle_for,
+                    });
+                }
+                self.remove_remote(remote_id, DisconnectionReason::RemoteTimedOut);
+                None
+            }
+            PruneDecision::Defer(delay) => {
+                debug!(
+                    "Pruning of remote {} deferred for {:?} by the prune policy.",
+                    remote_id, delay
+                );
+                if let Some(reporter) = prune_reporter {
+                    reporter.report_deferred();
+                }
+                Some(delay)
+            }
         }
     }
 
@@ -1638,7 +2442,7 @@ impl WriteTaskState {
                 "Unlinking remote {} connected to lane with id {}.",
                 remote_id, lane_id
             );
-            let task = write_tracker.unlink_lane(remote_id, lane_id);
+            let task = write_tracker.unlink_lane(remote_id, lane_id, Text::empty());
             (unlink, task)
         })
     }
@@ -1654,21 +2458,43 @@ impl WriteTaskState {
         remote_tracker.replace_and_pop(writer, buffer)
     }
 
+    /// Release a rate limited uplink after its delay has elapsed. This will trigger a new task if
+    /// the writer for the remote is available and events were held for the uplink.
+    fn release_uplink(&mut self, remote_id: Uuid, lane_id: u64) -> Option<WriteTask> {
+        trace!(
+            "Releasing rate limited uplink from lane {} to {}.",
+            lane_id,
+            remote_id
+        );
+        self.remote_tracker.release(remote_id, lane_id)
+    }
+
+    /// Take the rate limited uplinks that need to be released at a later time.
+    fn take_releases(&mut self) -> impl Iterator<Item = (Uuid, u64, Instant)> + '_ {
+        self.remote_tracker.take_releases()
+    }
+
     fn has_remotes(&self) -> bool {
         !self.remote_tracker.is_empty()
     }
 
-    /// Unlink all open links.
-    fn unlink_all(&mut self) -> impl Iterator<Item = WriteTask> + '_ {
+    /// Unlink all open links, notifying the remotes that the agent has stopped.
+    ///
+    /// # Arguments
+    /// * `notice` - Describes why the agent stopped.
+    fn unlink_all(&mut self, notice: NodeStopped) -> impl Iterator<Item = WriteTask> + '_ {
         info!("Unlinking all open links for shutdown.");
         let WriteTaskState {
             links,
             remote_tracker,
             ..
         } = self;
+        let message = Text::from(print_recon_compact(&notice).to_string());
         links
             .remove_all_links()
-            .flat_map(move |(lane_id, remote_id)| remote_tracker.unlink_lane(remote_id, lane_id))
+            .flat_map(move |(lane_id, remote_id)| {
+                remote_tracker.unlink_lane(remote_id, lane_id, message.clone())
+            })
     }
 
     /// Close all open remotes with the reason the agent is stopping.
@@ -1682,6 +2508,7 @@ impl WriteTaskState {
 struct WriteTaskEndpoints {
     lane_endpoints: Vec<LaneEndpoint<ByteReader>>,
     store_endpoints: Vec<StoreEndpoint>,
+    lane_aliases: Vec<LaneAlias>,
 }
 
 impl WriteTaskEndpoints {
@@ -1692,8 +2519,15 @@ impl WriteTaskEndpoints {
         WriteTaskEndpoints {
             lane_endpoints,
             store_endpoints,
+            lane_aliases: vec![],
         }
     }
+
+    /// Add aliases for the lanes.
+    fn with_aliases(mut self, lane_aliases: Vec<LaneAlias>) -> Self {
+        self.lane_aliases = lane_aliases;
+        self
+    }
 }
 
 /// The write task of the agent runtime. This receives messages from the agent lanes and forwards them
@@ -1722,11 +2556,15 @@ where
     Store: AgentPersistence + Send + Sync,
 {
     let aggregate_reporter = reporting.as_ref().map(NodeReporting::aggregate);
+    let prune_reporter = reporting.as_ref().map(NodeReporting::prune);
+    let recording = reporting.as_ref().and_then(NodeReporting::recording);
 
     let WriteTaskConfiguration {
         identity,
         node_uri,
         runtime_config,
+        interceptor,
+        prune_policy,
     } = configuration;
 
     let initialization = Initialization::new(reporting, runtime_config.item_init_timeout);
@@ -1741,13 +2579,23 @@ where
         remote_prune_delay,
         message_stream,
     );
-    let mut state = WriteTaskState::new(identity, node_uri, aggregate_reporter);
+    let mut state = WriteTaskState::new(
+        identity,
+        node_uri,
+        aggregate_reporter,
+        interceptor,
+        runtime_config.uplink_backpressure,
+        runtime_config.link_advisory,
+    )
+    .with_recording(recording)
+    .with_pruning(prune_policy, prune_reporter);
 
     info!(endpoints = ?initial_endpoints, "Adding initial endpoints.");
 
     let WriteTaskEndpoints {
         lane_endpoints,
         store_endpoints,
+        lane_aliases,
     } = initial_endpoints;
 
     for endpoint in lane_endpoints {
@@ -1770,11 +2618,19 @@ where
         streams.add_receiver(store_stream);
     }
 
+    // The read task registers the initial aliases itself.
+    for alias in &lane_aliases {
+        state.remote_tracker.lane_registry().add_alias(alias);
+    }
+
     let mut voted = false;
 
     let mut remote_reason = DisconnectionReason::AgentStoppedExternally;
 
     loop {
+        for (remote_id, lane_id, at) in state.take_releases() {
+            streams.schedule_release(remote_id, lane_id, at);
+        }
         let next = streams.select_next().await;
         trace!(event = ?next, "Processing write task event");
         match next {
@@ -1797,6 +2653,16 @@ where
                 TaskMessageResult::AddStore(store, store_id) => {
                     streams.add_receiver(store.into_store_stream(store_id, &mut state));
                 }
+                TaskMessageResult::AddAlias(alias) => {
+                    if read_task_tx
+                        .send(ReadTaskMessage::Alias(alias))
+                        .await
+                        .is_err()
+                    {
+                        error!("Could not communicate with read task.");
+                        break;
+                    }
+                }
                 TaskMessageResult::ScheduleWrite {
                     write,
                     schedule_prune,
@@ -1852,8 +2718,9 @@ where
                 }
             }
             WriteTaskEvent::WriteDone((writer, buffer, Ok(_))) => {
+                let remote_id = writer.remote_id();
                 if let Some(write) = state.replace(writer, buffer) {
-                    streams.schedule_write(write.into_future());
+                    streams.schedule_next_write(remote_id, write.into_future());
                 }
             }
             WriteTaskEvent::WriteDone((writer, _, Err(err))) => {
@@ -1886,8 +2753,18 @@ where
             WriteTaskEvent::StoreFailed(item_id) => {
                 error!("Store with ID {} failed.", item_id);
             }
-            WriteTaskEvent::PruneRemote(remote_id) => {
-                state.remove_remote_if_idle(remote_id);
+            WriteTaskEvent::PruneRemote {
+                remote_id,
+                idle_since,
+            } => {
+                if let Some(delay) = state.remove_remote_if_idle(remote_id, idle_since) {
+                    streams.defer_prune(remote_id, idle_since, delay);
+                }
+            }
+            WriteTaskEvent::ReleaseUplink { remote_id, lane_id } => {
+                if let Some(write) = state.release_uplink(remote_id, lane_id) {
+                    streams.schedule_write(write.into_future());
+                }
             }
             WriteTaskEvent::Timeout => {
                 info!(
@@ -1915,7 +2792,11 @@ where
     let cleanup_result = timeout(runtime_config.shutdown_timeout, async move {
         info!("Unlinking all links on shutdown.");
         streams.clear_lanes_and_stores();
-        for write in state.unlink_all() {
+        // Remotes may relink to restart an agent that stopped due to inactivity.
+        let notice = NodeStopped {
+            resume: matches!(remote_reason, DisconnectionReason::AgentTimedOut),
+        };
+        for write in state.unlink_all(notice) {
             streams.schedule_write(write.into_future());
         }
         while let Some((writer, buffer, result)) = streams.next_write().await {
@@ -1986,6 +2867,12 @@ where
                 ..
             })
             | ResponseData::Store(StoreData::Map(body)) => store.apply_map(*store_id, body),
+            ResponseData::Lane(LaneData {
+                response: UplinkResponse::MapBatch(operations),
+                ..
+            }) => operations
+                .iter()
+                .try_for_each(|operation| store.apply_map(*store_id, operation)),
             _ => Ok(()),
         }
     } else {
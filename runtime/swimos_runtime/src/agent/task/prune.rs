@@ -23,64 +23,104 @@ use futures::{ready, FutureExt, Stream};
 use tokio::time::{Instant, Sleep};
 use uuid::Uuid;
 
-/// A queue of remotes to be pruned if they have no links within the timeout period.
+/// A remote that is waiting to be pruned.
+#[derive(Debug, Clone, Copy)]
+struct PendingPrune {
+    remote_id: Uuid,
+    idle_since: Instant,
+    timeout_at: Instant,
+}
+
+/// A queue of remotes to be pruned if they have no links within the timeout period. The stream
+/// yields the IDs of the remotes along with the instant from which they have been idle.
 #[derive(Debug)]
 pub struct PruneRemotes<'a> {
-    next_id: Option<Uuid>,                 //Next ID to prune.
-    delay: Pin<&'a mut Sleep>,             //Delay future (held on the stack of the write task).
-    remote_ids: VecDeque<(Uuid, Instant)>, //Queue of future IDs to be pruned.
+    next: Option<PendingPrune>,         //Next remote to prune.
+    delay: Pin<&'a mut Sleep>,          //Delay future (held on the stack of the write task).
+    remote_ids: VecDeque<PendingPrune>, //Queue of future remotes to be pruned, ordered by timeout.
 }
 
 impl<'a> PruneRemotes<'a> {
     pub fn new(delay: Pin<&'a mut Sleep>) -> Self {
         PruneRemotes {
-            next_id: None,
+            next: None,
             delay,
             remote_ids: VecDeque::new(),
         }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.next_id.is_none()
+        self.next.is_none()
     }
 
+    /// Schedule a remote, that has just become idle, to be pruned after a timeout.
     pub fn push(&mut self, id: Uuid, timeout: Duration) {
+        let now = Instant::now();
+        self.insert(PendingPrune {
+            remote_id: id,
+            idle_since: now,
+            timeout_at: now.checked_add(timeout).expect("Timer overflow."),
+        });
+    }
+
+    /// Schedule a remote to be considered for pruning again, after its pruning was deferred.
+    pub fn defer(&mut self, id: Uuid, idle_since: Instant, delay: Duration) {
+        self.insert(PendingPrune {
+            remote_id: id,
+            idle_since,
+            timeout_at: Instant::now().checked_add(delay).expect("Timer overflow."),
+        });
+    }
+
+    fn insert(&mut self, entry: PendingPrune) {
         let PruneRemotes {
-            next_id,
+            next,
             delay,
             remote_ids,
         } = self;
-        let timeout_at = Instant::now()
-            .checked_add(timeout)
-            .expect("Timer overflow.");
-        if next_id.is_some() {
-            remote_ids.push_back((id, timeout_at));
-        } else {
-            *next_id = Some(id);
-            delay.as_mut().reset(timeout_at);
+        match next {
+            Some(current) if current.timeout_at <= entry.timeout_at => {
+                let index =
+                    remote_ids.partition_point(|other| other.timeout_at <= entry.timeout_at);
+                remote_ids.insert(index, entry);
+            }
+            Some(current) => {
+                remote_ids.push_front(std::mem::replace(current, entry));
+                delay.as_mut().reset(entry.timeout_at);
+            }
+            None => {
+                *next = Some(entry);
+                delay.as_mut().reset(entry.timeout_at);
+            }
         }
     }
 }
 
 impl<'a> Stream for PruneRemotes<'a> {
-    type Item = Uuid;
+    type Item = (Uuid, Instant);
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         if self.is_empty() {
             Poll::Ready(None)
         } else {
             let PruneRemotes {
-                next_id,
+                next,
                 delay,
                 remote_ids,
             } = self.get_mut();
             ready!(delay.poll_unpin(cx));
-            let result = next_id.take();
-            if let Some((id, timeout_at)) = remote_ids.pop_front() {
-                *next_id = Some(id);
-                delay.as_mut().reset(timeout_at);
+            let result = next.take();
+            if let Some(entry) = remote_ids.pop_front() {
+                delay.as_mut().reset(entry.timeout_at);
+                *next = Some(entry);
             }
-            Poll::Ready(result)
+            Poll::Ready(result.map(
+                |PendingPrune {
+                     remote_id,
+                     idle_since,
+                     ..
+                 }| (remote_id, idle_since),
+            ))
         }
     }
 }
@@ -103,11 +143,14 @@ mod tests {
         assert!(prune_remotes.is_empty());
         assert!(prune_remotes.next().await.is_none());
 
+        let start = Instant::now();
         prune_remotes.push(Uuid::from_u128(7473), TIMEOUT);
         assert!(!prune_remotes.is_empty());
 
         let result = prune_remotes.next().await;
-        assert_eq!(result, Some(Uuid::from_u128(7473)));
+        assert!(
+            matches!(result, Some((id, idle_since)) if id == Uuid::from_u128(7473) && idle_since >= start)
+        );
         assert!(prune_remotes.is_empty());
         assert!(prune_remotes.next().await.is_none());
     }
@@ -130,10 +173,35 @@ mod tests {
             assert!(!prune_remotes.is_empty());
         }
 
-        let results = (&mut prune_remotes).collect::<Vec<_>>().await;
+        let results = (&mut prune_remotes)
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>()
+            .await;
         let between = Instant::now().duration_since(start);
         assert!(prune_remotes.is_empty());
         assert_eq!(results, ids);
         assert!(between >= TIMEOUT);
     }
+
+    #[tokio::test]
+    async fn deferred_ids_ordered_by_timeout() {
+        let delay = pin!(tokio::time::sleep(Duration::ZERO));
+        let mut prune_remotes = PruneRemotes::new(delay);
+
+        let first = Uuid::from_u128(1);
+        let second = Uuid::from_u128(2);
+        let third = Uuid::from_u128(3);
+
+        let idle_since = Instant::now();
+        prune_remotes.defer(first, idle_since, TIMEOUT * 3);
+        prune_remotes.push(second, TIMEOUT);
+        prune_remotes.defer(third, idle_since, TIMEOUT * 2);
+
+        let results = (&mut prune_remotes).collect::<Vec<_>>().await;
+        assert_eq!(results.len(), 3);
+        let ids = results.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![second, third, first]);
+        assert_eq!(results[1].1, idle_since);
+        assert_eq!(results[2].1, idle_since);
+    }
 }
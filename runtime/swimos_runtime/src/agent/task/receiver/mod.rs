@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::{
+    num::NonZeroUsize,
     pin::Pin,
     task::{Context, Poll},
 };
@@ -24,13 +25,21 @@ use swimos_agent_protocol::{
     encoding::store::{RawMapStoreResponseDecoder, RawValueStoreResponseDecoder},
     LaneResponse, MapLaneResponse, MapOperation, StoreResponse,
 };
-use swimos_api::agent::UplinkKind;
+use swimos_api::{
+    agent::{SyncVersion, UplinkKind},
+    limits::Limits,
+};
+use swimos_messages::protocol::CorrelationId;
 use swimos_utilities::byte_channel::ByteReader;
 use tokio_util::codec::FramedRead;
+use tracing::error;
 use uuid::Uuid;
 
 use super::remotes::UplinkResponse;
 
+#[cfg(test)]
+mod tests;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ValueOrSupply {
     Value,
@@ -70,11 +79,21 @@ impl<I> ItemResponse<I> {
 pub struct LaneData {
     pub target: Option<Uuid>,
     pub response: UplinkResponse,
+    /// The correlation ID of the command that caused the event (only for broadcast events).
+    pub correlation_id: Option<CorrelationId>,
+    /// The ID of the remote that sent the command that caused the event (only for broadcast
+    /// events from lanes that track the origins of commands).
+    pub origin: Option<Uuid>,
 }
 
 impl LaneData {
     pub fn new(target: Option<Uuid>, response: UplinkResponse) -> Self {
-        LaneData { target, response }
+        LaneData {
+            target,
+            response,
+            correlation_id: None,
+            origin: None,
+        }
     }
 }
 
@@ -143,6 +162,18 @@ impl<I> ItemResponse<I> {
         }
     }
 
+    pub fn map_lane_batch(
+        item_id: u64,
+        store_id: Option<I>,
+        body: Vec<MapOperation<BytesMut, BytesMut>>,
+    ) -> Self {
+        ItemResponse {
+            item_id,
+            store_id,
+            body: ResponseData::Lane(LaneData::new(None, UplinkResponse::MapBatch(body))),
+        }
+    }
+
     pub fn lane_synced(item_id: u64, target: Uuid, kind: UplinkKind) -> Self {
         ItemResponse {
             item_id,
@@ -151,6 +182,17 @@ impl<I> ItemResponse<I> {
         }
     }
 
+    pub fn map_lane_synced_at(item_id: u64, target: Uuid, version: SyncVersion) -> Self {
+        ItemResponse {
+            item_id,
+            store_id: None,
+            body: ResponseData::Lane(LaneData::new(
+                Some(target),
+                UplinkResponse::SyncedAt(version),
+            )),
+        }
+    }
+
     pub fn value_store(item_id: u64, store_id: I, body: Bytes) -> Self {
         ItemResponse {
             item_id,
@@ -169,6 +211,34 @@ impl<I> ItemResponse<I> {
 }
 
 impl<I> ItemResponse<I> {
+    /// Attach the correlation ID of the command that caused an event. This only applies to events
+    /// that are broadcast to all uplinks (the responses to syncs are not affected).
+    fn correlated(mut self, id: Option<CorrelationId>) -> Self {
+        if let ResponseData::Lane(LaneData {
+            target: None,
+            correlation_id,
+            ..
+        }) = &mut self.body
+        {
+            *correlation_id = id;
+        }
+        self
+    }
+
+    /// Attach the ID of the remote that sent the command that caused an event. As with
+    /// correlation IDs, this only applies to events that are broadcast to all uplinks.
+    fn originated(mut self, id: Option<Uuid>) -> Self {
+        if let ResponseData::Lane(LaneData {
+            target: None,
+            origin,
+            ..
+        }) = &mut self.body
+        {
+            *origin = id;
+        }
+        self
+    }
+
     pub fn into_uplink_response(self) -> Option<(u64, LaneData)> {
         let ItemResponse { item_id, body, .. } = self;
         if let ResponseData::Lane(resp) = body {
@@ -187,11 +257,23 @@ pub enum ResponseReceiver<I> {
         item_id: u64,
         store_id: Option<I>,
         uplink: ValueOrSupply,
+        // The correlation ID of the command that caused the events currently being received.
+        correlation: Option<CorrelationId>,
+        // The remote that sent the command that caused the events currently being received.
+        origin: Option<Uuid>,
         reader: FramedRead<ByteReader, RawValueLaneResponseDecoder>,
     },
     MapLane {
         item_id: u64,
         store_id: Option<I>,
+        // The correlation ID of the command that caused the events currently being received.
+        correlation: Option<CorrelationId>,
+        // The remote that sent the command that caused the events currently being received.
+        origin: Option<Uuid>,
+        // The number of events expected for the current batch and those that have been received.
+        batch: Option<(u64, Vec<MapOperation<BytesMut, BytesMut>>)>,
+        // The maximum number of events that will be accepted in a batch.
+        max_batch_entries: Option<NonZeroUsize>,
         reader: FramedRead<ByteReader, RawMapLaneResponseDecoder>,
     },
     ValueStore {
@@ -207,29 +289,62 @@ pub enum ResponseReceiver<I> {
 }
 
 impl<I> ResponseReceiver<I> {
-    pub fn value_like_lane(item_id: u64, store_id: Option<I>, rx: ByteReader) -> Self {
+    pub fn value_like_lane(
+        item_id: u64,
+        store_id: Option<I>,
+        rx: ByteReader,
+        max_message_size: Option<NonZeroUsize>,
+    ) -> Self {
         ResponseReceiver::ValueLikeLane {
             item_id,
             store_id,
             uplink: ValueOrSupply::Value,
-            reader: FramedRead::new(rx, Default::default()),
+            correlation: None,
+            origin: None,
+            reader: FramedRead::new(
+                rx,
+                RawValueLaneResponseDecoder::with_max_message_size(max_message_size),
+            ),
         }
     }
 
-    pub fn supply_lane(item_id: u64, store_id: Option<I>, rx: ByteReader) -> Self {
+    pub fn supply_lane(
+        item_id: u64,
+        store_id: Option<I>,
+        rx: ByteReader,
+        max_message_size: Option<NonZeroUsize>,
+    ) -> Self {
         ResponseReceiver::ValueLikeLane {
             item_id,
             store_id,
             uplink: ValueOrSupply::Supply,
-            reader: FramedRead::new(rx, Default::default()),
+            correlation: None,
+            origin: None,
+            reader: FramedRead::new(
+                rx,
+                RawValueLaneResponseDecoder::with_max_message_size(max_message_size),
+            ),
         }
     }
 
-    pub fn map_lane(item_id: u64, store_id: Option<I>, rx: ByteReader) -> Self {
+    pub fn map_lane(
+        item_id: u64,
+        store_id: Option<I>,
+        rx: ByteReader,
+        max_message_size: Option<NonZeroUsize>,
+        max_batch_entries: Option<NonZeroUsize>,
+    ) -> Self {
         ResponseReceiver::MapLane {
             item_id,
             store_id,
-            reader: FramedRead::new(rx, Default::default()),
+            correlation: None,
+            origin: None,
+            batch: None,
+            max_batch_entries,
+            reader: FramedRead::new(
+                rx,
+                RawMapLaneResponseDecoder::with_max_message_size(max_message_size),
+            ),
         }
     }
 
@@ -259,17 +374,25 @@ impl<I: Copy + Unpin> Stream for ResponseReceiver<I> {
                 item_id,
                 store_id,
                 uplink,
+                correlation,
+                origin,
                 reader,
             } => {
                 let next = loop {
                     let maybe_result = ready!(reader.poll_next_unpin(cx));
 
                     match maybe_result {
+                        Some(Ok(LaneResponse::Correlated(id))) => {
+                            *correlation = id.map(CorrelationId::new);
+                        }
+                        Some(Ok(LaneResponse::Originated(id))) => {
+                            *origin = id;
+                        }
                         Some(Ok(r)) => {
                             if let Some(resp) =
                                 value_or_supply_raw_response(*item_id, r, *uplink, *store_id)
                             {
-                                break Some(Ok(resp));
+                                break Some(Ok(resp.correlated(*correlation).originated(*origin)));
                             }
                         }
                         Some(Err(_)) => break Some(Err(Failed::Lane(*item_id))),
@@ -281,14 +404,49 @@ impl<I: Copy + Unpin> Stream for ResponseReceiver<I> {
             ResponseReceiver::MapLane {
                 item_id,
                 store_id,
+                correlation,
+                origin,
+                batch,
+                max_batch_entries,
                 reader,
             } => {
                 let next = loop {
                     let maybe_result = ready!(reader.poll_next_unpin(cx));
                     match maybe_result {
+                        Some(Ok(LaneResponse::EventBatch(n))) => {
+                            let limits = Limits {
+                                max_map_batch_entries: *max_batch_entries,
+                                ..Limits::UNLIMITED
+                            };
+                            if let Err(error) = limits.check_map_batch_entries(n) {
+                                error!(error = %error, item_id, "A map lane sent an oversized batch of events.");
+                                break Some(Err(Failed::Lane(*item_id)));
+                            }
+                            *batch = Some((n, vec![]));
+                        }
+                        Some(Ok(LaneResponse::Correlated(id))) => {
+                            *correlation = id.map(CorrelationId::new);
+                        }
+                        Some(Ok(LaneResponse::Originated(id))) => {
+                            *origin = id;
+                        }
+                        Some(Ok(LaneResponse::StandardEvent(body))) if batch.is_some() => {
+                            if let Some((n, mut operations)) = batch.take() {
+                                operations.push(body);
+                                if operations.len() as u64 >= n {
+                                    break Some(Ok(ItemResponse::map_lane_batch(
+                                        *item_id, *store_id, operations,
+                                    )
+                                    .correlated(*correlation)
+                                    .originated(*origin)));
+                                } else {
+                                    *batch = Some((n, operations));
+                                }
+                            }
+                        }
                         Some(Ok(r)) => {
                             if let Some(resp) = map_raw_response(*item_id, r, *store_id) {
-                                break Some(Ok(resp));
+                                break Some(Ok(resp.correlated(*correlation).originated(*origin)));
                             }
                         }
                         Some(Err(_)) => break Some(Err(Failed::Lane(*item_id))),
@@ -359,9 +517,12 @@ fn value_or_supply_raw_response<I>(
                 body.freeze(),
             )),
         },
-        LaneResponse::Synced(id) => {
+        LaneResponse::Synced(id) | LaneResponse::SyncedAt(id, _) => {
             Some(ItemResponse::lane_synced(item_id, id, uplink.uplink_kind()))
         }
+        LaneResponse::EventBatch(_) | LaneResponse::Correlated(_) | LaneResponse::Originated(_) => {
+            None
+        }
     }
 }
 
@@ -379,5 +540,12 @@ fn map_raw_response<I>(
             Some(ItemResponse::map_lane(item_id, store_id, Some(id), body))
         }
         LaneResponse::Synced(id) => Some(ItemResponse::lane_synced(item_id, id, UplinkKind::Map)),
+        LaneResponse::SyncedAt(id, version) => {
+            Some(ItemResponse::map_lane_synced_at(item_id, id, version))
+        }
+        // Batches, correlation IDs and origins are handled by the receiver.
+        LaneResponse::EventBatch(_) | LaneResponse::Correlated(_) | LaneResponse::Originated(_) => {
+            None
+        }
     }
 }
@@ -0,0 +1,141 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::num::NonZeroUsize;
+
+use futures::{SinkExt, StreamExt};
+use swimos_agent_protocol::{
+    encoding::lane::MapLaneResponseEncoder, LaneResponse, MapLaneResponse, MapOperation,
+};
+use swimos_messages::protocol::CorrelationId;
+use swimos_utilities::{byte_channel::byte_channel, non_zero_usize};
+use tokio_util::codec::FramedWrite;
+use uuid::Uuid;
+
+use super::{Failed, LaneData, ResponseData, ResponseReceiver};
+
+const BUFFER_SIZE: NonZeroUsize = non_zero_usize!(4096);
+const LANE_ID: u64 = 7;
+const MAX_BATCH: NonZeroUsize = non_zero_usize!(2);
+
+async fn receive_batch(n: u64) -> Option<Result<ResponseData, Failed>> {
+    let (tx, rx) = byte_channel(BUFFER_SIZE);
+    let mut writer = FramedWrite::new(tx, MapLaneResponseEncoder::default());
+    let mut receiver = ResponseReceiver::<()>::map_lane(LANE_ID, None, rx, None, Some(MAX_BATCH));
+
+    let mut responses: Vec<MapLaneResponse<i32, i32>> = vec![LaneResponse::EventBatch(n)];
+    for i in 0..n {
+        let i = i as i32;
+        responses.push(LaneResponse::StandardEvent(MapOperation::Update {
+            key: i,
+            value: i,
+        }));
+    }
+    for response in responses {
+        writer.send(response).await.expect("Channel closed.");
+    }
+    drop(writer);
+
+    receiver
+        .next()
+        .await
+        .map(|result| result.map(|response| response.body))
+}
+
+#[tokio::test]
+async fn map_lane_batch_within_limit() {
+    let result = receive_batch(2).await;
+    assert!(matches!(result, Some(Ok(ResponseData::Lane(_)))));
+}
+
+#[tokio::test]
+async fn map_lane_batch_exceeds_limit() {
+    let result = receive_batch(3).await;
+    assert!(matches!(result, Some(Err(Failed::Lane(LANE_ID)))));
+}
+
+#[tokio::test]
+async fn map_lane_correlated_events() {
+    let (tx, rx) = byte_channel(BUFFER_SIZE);
+    let mut writer = FramedWrite::new(tx, MapLaneResponseEncoder::default());
+    let receiver = ResponseReceiver::<()>::map_lane(LANE_ID, None, rx, None, None);
+
+    let remote_id = Uuid::from_u128(1);
+    let responses: Vec<MapLaneResponse<i32, i32>> = vec![
+        LaneResponse::Correlated(Some(5)),
+        LaneResponse::StandardEvent(MapOperation::Update { key: 1, value: 1 }),
+        LaneResponse::SyncEvent(remote_id, MapOperation::Update { key: 1, value: 1 }),
+        LaneResponse::Correlated(None),
+        LaneResponse::StandardEvent(MapOperation::Remove { key: 1 }),
+    ];
+    for response in responses {
+        writer.send(response).await.expect("Channel closed.");
+    }
+    drop(writer);
+
+    let correlation_ids = receiver
+        .map(|result| match result.expect("Receive failed.").body {
+            ResponseData::Lane(LaneData {
+                target,
+                correlation_id,
+                ..
+            }) => (target, correlation_id),
+            ow => panic!("Unexpected response: {:?}", ow),
+        })
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(
+        correlation_ids,
+        vec![
+            (None, Some(CorrelationId::new(5))),
+            (Some(remote_id), None),
+            (None, None),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn map_lane_originated_events() {
+    let (tx, rx) = byte_channel(BUFFER_SIZE);
+    let mut writer = FramedWrite::new(tx, MapLaneResponseEncoder::default());
+    let receiver = ResponseReceiver::<()>::map_lane(LANE_ID, None, rx, None, None);
+
+    let remote_id = Uuid::from_u128(1);
+    let origin = Uuid::from_u128(2);
+    let responses: Vec<MapLaneResponse<i32, i32>> = vec![
+        LaneResponse::Originated(Some(origin)),
+        LaneResponse::StandardEvent(MapOperation::Update { key: 1, value: 1 }),
+        LaneResponse::SyncEvent(remote_id, MapOperation::Update { key: 1, value: 1 }),
+        LaneResponse::Originated(None),
+        LaneResponse::StandardEvent(MapOperation::Remove { key: 1 }),
+    ];
+    for response in responses {
+        writer.send(response).await.expect("Channel closed.");
+    }
+    drop(writer);
+
+    let origins = receiver
+        .map(|result| match result.expect("Receive failed.").body {
+            ResponseData::Lane(LaneData { target, origin, .. }) => (target, origin),
+            ow => panic!("Unexpected response: {:?}", ow),
+        })
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(
+        origins,
+        vec![(None, Some(origin)), (Some(remote_id), None), (None, None)]
+    );
+}
@@ -13,14 +13,24 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
 
 use bytes::BytesMut;
+use swimos_messages::protocol::{CorrelationId, LinkParams};
 use swimos_model::Text;
 use swimos_utilities::{byte_channel::ByteWriter, trigger::promise};
+use tokio::time::Instant;
 use tracing::debug;
 use uuid::Uuid;
 
-use crate::{agent::DisconnectionReason, backpressure::InvalidKey};
+use crate::{
+    agent::{
+        intercept::OutgoingInterceptor, recording::EnvelopeRecording, DisconnectionReason,
+        LinkAdvisoryConfig, UplinkBackpressure,
+    },
+    backpressure::InvalidKey,
+};
 pub use sender::RemoteSender;
 pub use uplink::UplinkResponse;
 
@@ -44,6 +54,11 @@ pub struct RemoteTracker {
     identity: Uuid,
     registry: LaneRegistry,
     remotes: HashMap<Uuid, Uplinks>,
+    interceptor: Option<Arc<dyn OutgoingInterceptor>>,
+    recording: Option<EnvelopeRecording>,
+    backpressure: UplinkBackpressure,
+    advisory: LinkAdvisoryConfig,
+    releases: Vec<(Uuid, u64, Instant)>,
 }
 
 impl RemoteTracker {
@@ -56,9 +71,38 @@ impl RemoteTracker {
             identity,
             registry: Default::default(),
             remotes: Default::default(),
+            interceptor: None,
+            recording: None,
+            backpressure: Default::default(),
+            advisory: Default::default(),
+            releases: Default::default(),
         }
     }
 
+    /// Transform the bodies of the events sent to all remotes with an interceptor.
+    pub fn with_interceptor(mut self, interceptor: Option<Arc<dyn OutgoingInterceptor>>) -> Self {
+        self.interceptor = interceptor;
+        self
+    }
+
+    /// Record the envelopes sent to all remotes.
+    pub fn with_recording(mut self, recording: Option<EnvelopeRecording>) -> Self {
+        self.recording = recording;
+        self
+    }
+
+    /// Set how events are handled for remotes that are not keeping up with the agent.
+    pub fn with_backpressure(mut self, backpressure: UplinkBackpressure) -> Self {
+        self.backpressure = backpressure;
+        self
+    }
+
+    /// Set the limits at which low priority links are advised to back off.
+    pub fn with_advisory(mut self, advisory: LinkAdvisoryConfig) -> Self {
+        self.advisory = advisory;
+        self
+    }
+
     /// Remove a remote, giving the specified reason.
     pub fn remove_remote(&mut self, remote_id: Uuid, reason: DisconnectionReason) {
         if let Some(existing) = self.remotes.remove(&remote_id) {
@@ -84,6 +128,7 @@ impl RemoteTracker {
     pub fn insert(
         &mut self,
         remote_id: Uuid,
+        addr: Option<SocketAddr>,
         writer: ByteWriter,
         completion: promise::Sender<DisconnectionReason>,
     ) {
@@ -92,12 +137,19 @@ impl RemoteTracker {
             identity,
             node,
             remotes,
+            interceptor,
+            recording,
+            backpressure,
+            advisory,
             ..
         } = self;
-        if let Some(existing) = remotes.insert(
-            remote_id,
-            Uplinks::new(node.clone(), *identity, remote_id, writer, completion),
-        ) {
+        let uplinks = Uplinks::new(node.clone(), *identity, remote_id, writer, completion)
+            .with_remote_addr(addr)
+            .with_interceptor(interceptor.clone())
+            .with_recording(recording.clone())
+            .with_backpressure(*backpressure)
+            .with_advisory(*advisory);
+        if let Some(existing) = remotes.insert(remote_id, uplinks) {
             existing.complete(DisconnectionReason::DuplicateRegistration(remote_id));
         }
     }
@@ -105,39 +157,160 @@ impl RemoteTracker {
     /// Push a special action into the queue for the specified remote.
     #[must_use]
     pub fn push_special(&mut self, response: SpecialAction, target: &Uuid) -> Option<WriteTask> {
+        self.push_correlated_special(response, None, target)
+    }
+
+    /// Push a special action, caused by a request with a correlation ID, into the queue for the
+    /// specified remote.
+    #[must_use]
+    pub fn push_correlated_special(
+        &mut self,
+        response: SpecialAction,
+        correlation_id: Option<CorrelationId>,
+        target: &Uuid,
+    ) -> Option<WriteTask> {
         let RemoteTracker {
             registry, remotes, ..
         } = self;
         remotes
             .get_mut(target)
-            .and_then(|uplink| uplink.push_special(response, registry))
+            .and_then(|uplink| uplink.push_correlated_special(response, correlation_id, registry))
     }
 
-    /// Push an event for a lane into the queue for the specified remote.
+    /// Push an event for a lane (caused by the command with the specified correlation ID and
+    /// origin, if any) into the queue for the specified remote.
     pub fn push_write(
         &mut self,
         lane_id: u64,
         response: UplinkResponse,
+        correlation_id: Option<CorrelationId>,
+        command_origin: Option<Uuid>,
         target: &Uuid,
     ) -> Result<Option<WriteTask>, InvalidKey> {
         let RemoteTracker {
-            registry, remotes, ..
+            registry,
+            remotes,
+            releases,
+            ..
         } = self;
         if let Some(uplink) = remotes.get_mut(target) {
-            uplink.push(lane_id, response, registry)
+            let result =
+                uplink.push_originated(lane_id, response, correlation_id, command_origin, registry);
+            uplink.advise_if_congested(lane_id);
+            collect_releases(*target, uplink, releases);
+            result
         } else {
             Ok(None)
         }
     }
 
+    /// Set the rate and priority, requested by a remote, for its uplink from a lane.
+    pub fn set_params(&mut self, remote_id: Uuid, lane_id: u64, params: LinkParams) {
+        if let Some(uplinks) = self.remotes.get_mut(&remote_id) {
+            uplinks.set_params(lane_id, params);
+        }
+    }
+
+    /// Record the name by which a remote addressed a lane when linking to it. If this is an alias of
+    /// the lane, the envelopes for the lane will be sent to the remote with the alias.
+    pub fn set_link_name(&mut self, remote_id: Uuid, lane_id: u64, name: Text) {
+        let RemoteTracker {
+            registry, remotes, ..
+        } = self;
+        if let Some(uplinks) = remotes.get_mut(&remote_id) {
+            let is_alias = registry
+                .name_for(lane_id)
+                .map(|lane_name| lane_name != name.as_str())
+                .unwrap_or(false);
+            uplinks.set_link_name(lane_id, is_alias.then_some(name));
+        }
+    }
+
+    /// Release a rate limited uplink from a lane to the specified remote, after its delay has elapsed.
+    #[must_use]
+    pub fn release(&mut self, remote_id: Uuid, lane_id: u64) -> Option<WriteTask> {
+        let RemoteTracker {
+            registry,
+            remotes,
+            releases,
+            ..
+        } = self;
+        remotes.get_mut(&remote_id).and_then(|uplinks| {
+            let write = uplinks.release(lane_id, registry);
+            collect_releases(remote_id, uplinks, releases);
+            write
+        })
+    }
+
+    /// Handle an acknowledgement from a remote for the current page of a paged sync of a lane.
+    #[must_use]
+    pub fn ack(&mut self, remote_id: Uuid, lane_id: u64) -> Option<WriteTask> {
+        let RemoteTracker {
+            registry,
+            remotes,
+            releases,
+            ..
+        } = self;
+        remotes.get_mut(&remote_id).and_then(|uplinks| {
+            let write = uplinks.ack(lane_id, registry);
+            collect_releases(remote_id, uplinks, releases);
+            write
+        })
+    }
+
+    /// Take the rate limited uplinks that are waiting to be released (consisting of the ID of the remote,
+    /// the ID of the lane and the time at which the uplink should be released).
+    pub fn take_releases(&mut self) -> impl Iterator<Item = (Uuid, u64, Instant)> + '_ {
+        self.releases.drain(..)
+    }
+
+    /// Advise a remote to close a newly opened link to a lane if the agent now has more links than
+    /// the configured limit and the link is low priority.
+    /// # Arguments
+    /// * `remote_id` - The ID of the remote.
+    /// * `lane_id` - The ID of the lane.
+    /// * `link_count` - The total number of links to the agent, including the new link.
+    #[must_use]
+    pub fn advise_on_link(
+        &mut self,
+        remote_id: Uuid,
+        lane_id: u64,
+        link_count: u64,
+    ) -> Option<WriteTask> {
+        let RemoteTracker {
+            registry,
+            remotes,
+            advisory,
+            ..
+        } = self;
+        let limit = advisory.link_limit?;
+        if link_count > limit.get() as u64 {
+            remotes
+                .get_mut(&remote_id)
+                .and_then(|uplinks| uplinks.advise_unlink(lane_id, registry))
+        } else {
+            None
+        }
+    }
+
     /// Unlink a lane from the specified remote.
+    ///
+    /// # Arguments
+    /// * `remote_id` - The ID of the remote.
+    /// * `lane_id` - The ID of the lane.
+    /// * `message` - The body of the unlinked envelope.
     #[must_use]
-    pub fn unlink_lane(&mut self, remote_id: Uuid, lane_id: u64) -> Option<WriteTask> {
+    pub fn unlink_lane(
+        &mut self,
+        remote_id: Uuid,
+        lane_id: u64,
+        message: Text,
+    ) -> Option<WriteTask> {
         let RemoteTracker {
             registry, remotes, ..
         } = self;
         remotes.get_mut(&remote_id).and_then(|uplinks| {
-            uplinks.push_special(SpecialAction::unlinked(lane_id, Text::empty()), registry)
+            uplinks.push_special(SpecialAction::unlinked(lane_id, message), registry)
         })
     }
 
@@ -145,12 +318,17 @@ impl RemoteTracker {
     #[must_use]
     pub fn replace_and_pop(&mut self, writer: RemoteSender, buffer: BytesMut) -> Option<WriteTask> {
         let RemoteTracker {
-            registry, remotes, ..
+            registry,
+            remotes,
+            releases,
+            ..
         } = self;
         let id = writer.remote_id();
-        remotes
-            .get_mut(&id)
-            .and_then(|uplinks| uplinks.replace_and_pop(writer, buffer, registry))
+        remotes.get_mut(&id).and_then(|uplinks| {
+            let write = uplinks.replace_and_pop(writer, buffer, registry);
+            collect_releases(id, uplinks, releases);
+            write
+        })
     }
 
     pub fn is_empty(&self) -> bool {
@@ -165,3 +343,15 @@ impl RemoteTracker {
             .for_each(|(_, uplinks)| uplinks.complete(reason));
     }
 }
+
+fn collect_releases(
+    remote_id: Uuid,
+    uplinks: &mut Uplinks,
+    releases: &mut Vec<(Uuid, u64, Instant)>,
+) {
+    releases.extend(
+        uplinks
+            .take_releases()
+            .map(|(lane_id, at)| (remote_id, lane_id, at)),
+    );
+}
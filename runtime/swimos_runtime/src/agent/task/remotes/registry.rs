@@ -17,6 +17,8 @@ use std::collections::HashMap;
 use swimos_model::Text;
 use tracing::debug;
 
+use crate::agent::task::LaneAlias;
+
 /// Assigns integer IDs to each lane of an agent. This is to avoid moving copies of the string name
 /// into futures.
 #[derive(Debug, Default)]
@@ -24,6 +26,7 @@ pub struct LaneRegistry {
     lane_id_counter: u64,
     lane_names: HashMap<Text, u64>,
     lane_names_rev: HashMap<u64, Text>,
+    aliases: HashMap<Text, u64>,
 }
 
 impl LaneRegistry {
@@ -48,8 +51,31 @@ impl LaneRegistry {
         id
     }
 
+    /// Add an alias for a lane. Aliases never replace the names of lanes so this will fail if the
+    /// alias clashes with the name of a lane or if the lane does not exist.
+    pub fn add_alias(&mut self, alias: &LaneAlias) -> bool {
+        let LaneRegistry {
+            lane_names,
+            aliases,
+            ..
+        } = self;
+        let LaneAlias { alias, name } = alias;
+        match lane_names.get(name) {
+            Some(id) if !lane_names.contains_key(alias) => {
+                debug!("Adding alias '{}' for lane with ID {}.", alias, id);
+                aliases.insert(alias.clone(), *id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Get the ID of a lane from its name or one of its aliases.
     pub fn id_for(&self, name: &str) -> Option<u64> {
-        self.lane_names.get(name).copied()
+        self.lane_names
+            .get(name)
+            .or_else(|| self.aliases.get(name))
+            .copied()
     }
 
     pub fn name_for(&self, id: u64) -> Option<&str> {
@@ -61,6 +87,8 @@ impl LaneRegistry {
 mod tests {
     use swimos_model::Text;
 
+    use crate::agent::task::LaneAlias;
+
     use super::LaneRegistry;
 
     #[test]
@@ -71,4 +99,21 @@ mod tests {
         assert_eq!(registry.id_for("lane"), Some(id));
         assert_eq!(registry.name_for(id), Some("lane"));
     }
+
+    #[test]
+    fn add_alias() {
+        let mut registry = LaneRegistry::default();
+        let id = registry.add_endpoint(Text::new("lane"));
+        let other = registry.add_endpoint(Text::new("other"));
+
+        assert!(registry.add_alias(&LaneAlias::new(Text::new("old"), Text::new("lane"))));
+        assert_eq!(registry.id_for("old"), Some(id));
+        assert_eq!(registry.name_for(id), Some("lane"));
+
+        assert!(!registry.add_alias(&LaneAlias::new(Text::new("other"), Text::new("lane"))));
+        assert_eq!(registry.id_for("other"), Some(other));
+
+        assert!(!registry.add_alias(&LaneAlias::new(Text::new("older"), Text::new("missing"))));
+        assert_eq!(registry.id_for("older"), None);
+    }
 }
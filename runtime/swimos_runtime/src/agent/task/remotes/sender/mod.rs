@@ -12,17 +12,25 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::{net::SocketAddr, sync::Arc};
+
 use bytes::BytesMut;
 use futures::SinkExt;
 use swimos_api::address::RelativeAddress;
 use swimos_messages::protocol::RawResponseMessageEncoder;
-use swimos_messages::protocol::{Notification, ResponseMessage};
-use swimos_model::Text;
+use swimos_messages::protocol::{CorrelationId, Notification, ResponseMessage};
+use swimos_model::{Text, Value};
+use swimos_recon::{parser::parse_recognize, write_recon};
 use swimos_utilities::byte_channel::ByteWriter;
 use tokio_util::codec::FramedWrite;
-use tracing::trace;
+use tracing::{error, trace};
 use uuid::Uuid;
 
+use crate::agent::{
+    intercept::{OutgoingInterceptor, RemoteEndpoint},
+    recording::EnvelopeRecording,
+};
+
 #[cfg(test)]
 mod tests;
 
@@ -32,8 +40,14 @@ pub struct RemoteSender {
     sender: FramedWrite<ByteWriter, RawResponseMessageEncoder>,
     identity: Uuid,
     remote_id: Uuid,
+    remote_addr: Option<SocketAddr>,
     node: Text,
     pub lane: String,
+    pub correlation_id: Option<CorrelationId>,
+    pub command_origin: Option<Uuid>,
+    interceptor: Option<Arc<dyn OutgoingInterceptor>>,
+    intercept_buffer: BytesMut,
+    recording: Option<EnvelopeRecording>,
 }
 
 impl RemoteSender {
@@ -47,29 +61,79 @@ impl RemoteSender {
             sender: FramedWrite::new(writer, Default::default()),
             identity,
             remote_id,
+            remote_addr: None,
             node,
             lane: Default::default(),
+            correlation_id: None,
+            command_origin: None,
+            interceptor: None,
+            intercept_buffer: Default::default(),
+            recording: None,
         }
     }
 
+    /// Set the network address of the remote, used to identify it to an interceptor.
+    pub fn with_remote_addr(mut self, addr: Option<SocketAddr>) -> Self {
+        self.remote_addr = addr;
+        self
+    }
+
+    /// Transform the bodies of events with an interceptor before they are sent.
+    pub fn with_interceptor(mut self, interceptor: Option<Arc<dyn OutgoingInterceptor>>) -> Self {
+        self.interceptor = interceptor;
+        self
+    }
+
+    /// Record the envelopes that are sent (after they have been intercepted).
+    pub fn with_recording(mut self, recording: Option<EnvelopeRecording>) -> Self {
+        self.recording = recording;
+        self
+    }
+
     pub fn remote_id(&self) -> Uuid {
         self.remote_id
     }
 
     /// Set the name of the lane for the next message that is sent. This is done separately from
     /// the actual write to avoid needing to move a copy of the name into the future that performs
-    /// the write.
+    /// the write. This also clears the correlation ID and command origin of the previous message.
     ///
     /// # Arguments
     /// * `lane_name` - The name of the lane.
     pub fn update_lane(&mut self, lane_name: &str) {
-        let RemoteSender { lane, .. } = self;
+        let RemoteSender {
+            lane,
+            correlation_id,
+            command_origin,
+            ..
+        } = self;
         lane.clear();
         lane.push_str(lane_name);
+        *correlation_id = None;
+        *command_origin = None;
+    }
+
+    /// Set the correlation ID of the request that caused the next message to be sent. This must be
+    /// called after [`RemoteSender::update_lane`].
+    ///
+    /// # Arguments
+    /// * `id` - The correlation ID, if the request had one.
+    pub fn update_correlation_id(&mut self, id: Option<CorrelationId>) {
+        self.correlation_id = id;
+    }
+
+    /// Set the ID of the remote that sent the command that caused the next event to be sent. This
+    /// must be called after [`RemoteSender::update_lane`].
+    ///
+    /// # Arguments
+    /// * `origin` - The ID of the remote, if the lane tracks the origins of commands.
+    pub fn update_command_origin(&mut self, origin: Option<Uuid>) {
+        self.command_origin = origin;
     }
 
     /// Construct a [`ResponseMessage`] for the provided notification and send it on the
-    /// channel.
+    /// channel. If the notification is an event that is selected by the interceptor but its
+    /// body cannot be transformed, it is dropped.
     ///
     /// # Arguments
     /// * `notification` - The content of the frame.
@@ -81,19 +145,60 @@ impl RemoteSender {
             sender,
             identity,
             remote_id,
+            remote_addr,
             node,
             lane,
-            ..
+            correlation_id,
+            command_origin,
+            interceptor,
+            intercept_buffer,
+            recording,
         } = self;
 
-        trace!(identity = %identity, remote_id = %remote_id, node = %node, lane = %lane, notification = ?notification.debug_formatter(), "Sending notification.");
+        let remote = RemoteEndpoint::new(*remote_id, *remote_addr);
+        let notification = match (interceptor, notification) {
+            (Some(interceptor), Notification::Event(body))
+                if interceptor.selects(&remote, lane) =>
+            {
+                match intercept_body(interceptor.as_ref(), &remote, lane, body, intercept_buffer) {
+                    Ok(()) => Notification::Event(&*intercept_buffer),
+                    Err(msg) => {
+                        error!(remote_id = %remote_id, node = %node, lane = %lane, error = msg, "Failed to intercept the body of an event. The event will not be sent.");
+                        return Ok(());
+                    }
+                }
+            }
+            (_, notification) => notification,
+        };
+
+        trace!(identity = %identity, remote_id = %remote_id, node = %node, lane = %lane, correlation_id = ?correlation_id, notification = ?notification.debug_formatter(), "Sending notification.");
 
         let message: ResponseMessage<&str, &BytesMut, &[u8]> = ResponseMessage {
             origin: *identity,
             path: RelativeAddress::new(node.as_str(), lane.as_str()),
             envelope: notification,
+            correlation_id: *correlation_id,
+            command_origin: *command_origin,
         };
+        if let Some(recording) = recording {
+            recording.record_response(*remote_id, &message);
+        }
         sender.send(message).await?;
         Ok(())
     }
 }
+
+fn intercept_body(
+    interceptor: &dyn OutgoingInterceptor,
+    remote: &RemoteEndpoint,
+    lane: &str,
+    body: &BytesMut,
+    buffer: &mut BytesMut,
+) -> Result<(), String> {
+    let body_str = std::str::from_utf8(body.as_ref()).map_err(|e| e.to_string())?;
+    let mut value = parse_recognize::<Value>(body_str, false).map_err(|e| e.to_string())?;
+    interceptor.intercept(remote, lane, &mut value);
+    buffer.clear();
+    write_recon(buffer, &value);
+    Ok(())
+}
@@ -12,9 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use bytes::{BufMut, BytesMut};
 use futures::StreamExt;
-use swimos_messages::protocol::{Notification, RawResponseMessageDecoder, ResponseMessage};
+use swimos_messages::protocol::{
+    CorrelationId, Notification, RawResponseMessageDecoder, ResponseMessage,
+};
 use swimos_model::Text;
 use swimos_utilities::{
     byte_channel::{byte_channel, ByteReader},
@@ -23,6 +27,8 @@ use swimos_utilities::{
 use tokio_util::codec::FramedRead;
 use uuid::Uuid;
 
+use crate::agent::intercept::{InterceptRule, InterceptRules};
+
 use super::RemoteSender;
 
 const ID: Uuid = Uuid::from_u128(1);
@@ -61,6 +67,7 @@ async fn send_record() {
                 origin,
                 path,
                 envelope,
+                ..
             } = resp;
             assert_eq!(origin, ID);
             assert_eq!(&path.node, NODE);
@@ -95,6 +102,7 @@ async fn send_records_same_lane() {
                 origin,
                 path,
                 envelope,
+                ..
             } = resp;
             assert_eq!(origin, ID);
             assert_eq!(&path.node, NODE);
@@ -110,6 +118,7 @@ async fn send_records_same_lane() {
                 origin,
                 path,
                 envelope,
+                ..
             } = resp;
             assert_eq!(origin, ID);
             assert_eq!(&path.node, NODE);
@@ -151,6 +160,7 @@ async fn send_records_switch_lane() {
                 origin,
                 path,
                 envelope,
+                ..
             } = resp;
             assert_eq!(origin, ID);
             assert_eq!(&path.node, NODE);
@@ -166,6 +176,7 @@ async fn send_records_switch_lane() {
                 origin,
                 path,
                 envelope,
+                ..
             } = resp;
             assert_eq!(origin, ID);
             assert_eq!(&path.node, NODE);
@@ -180,3 +191,102 @@ async fn send_records_switch_lane() {
         ow => panic!("Unexpected result: {:?}", ow),
     }
 }
+
+#[tokio::test]
+async fn send_intercepted_event() {
+    let (sender, mut receiver) = make_sender();
+    let rules = InterceptRules::default().with_rule(
+        InterceptRule::default()
+            .for_lane("my_lane")
+            .redact("secret".parse().expect("Invalid selector.")),
+    );
+    let mut sender = sender.with_interceptor(Some(Arc::new(rules)));
+
+    let mut data = BytesMut::new();
+    data.put(b"{name:a,secret:b}".as_ref());
+
+    for lane in ["my_lane", "other"] {
+        sender.update_lane(lane);
+        let write_result = sender.send_notification(Notification::Event(&data)).await;
+        assert!(write_result.is_ok());
+    }
+
+    for expected in ["{name:a}", "{name:a,secret:b}"] {
+        match receiver.next().await {
+            Some(Ok(ResponseMessage {
+                envelope: Notification::Event(content),
+                ..
+            })) => {
+                assert_eq!(content.as_ref(), expected.as_bytes());
+            }
+            ow => panic!("Unexpected result: {:?}", ow),
+        }
+    }
+}
+
+#[tokio::test]
+async fn drop_event_that_cannot_be_intercepted() {
+    let (sender, mut receiver) = make_sender();
+    let rules = InterceptRules::default()
+        .with_rule(InterceptRule::default().redact("secret".parse().expect("Invalid selector.")));
+    let mut sender = sender.with_interceptor(Some(Arc::new(rules)));
+    sender.update_lane("my_lane");
+
+    let mut invalid = BytesMut::new();
+    invalid.put(b"{name:a,secret:".as_ref());
+    let write_result = sender
+        .send_notification(Notification::Event(&invalid))
+        .await;
+    assert!(write_result.is_ok());
+
+    let mut data = BytesMut::new();
+    data.put(b"{name:a,secret:b}".as_ref());
+    let write_result = sender.send_notification(Notification::Event(&data)).await;
+    assert!(write_result.is_ok());
+
+    match receiver.next().await {
+        Some(Ok(ResponseMessage {
+            envelope: Notification::Event(content),
+            ..
+        })) => {
+            assert_eq!(content.as_ref(), b"{name:a}");
+        }
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+}
+
+#[tokio::test]
+async fn send_correlated_notification() {
+    let (mut sender, mut receiver) = make_sender();
+
+    sender.update_lane("my_lane");
+    sender.update_correlation_id(Some(CorrelationId::new(5)));
+    let write_result = sender.send_notification(Notification::Linked).await;
+    assert!(write_result.is_ok());
+
+    // Setting the lane for the next message clears the correlation ID.
+    sender.update_lane("my_lane");
+    let write_result = sender.send_notification(Notification::Synced).await;
+    assert!(write_result.is_ok());
+
+    match receiver.next().await {
+        Some(Ok(ResponseMessage {
+            envelope: Notification::Linked,
+            correlation_id,
+            ..
+        })) => {
+            assert_eq!(correlation_id, Some(CorrelationId::new(5)));
+        }
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+    match receiver.next().await {
+        Some(Ok(ResponseMessage {
+            envelope: Notification::Synced,
+            correlation_id,
+            ..
+        })) => {
+            assert!(correlation_id.is_none());
+        }
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+}
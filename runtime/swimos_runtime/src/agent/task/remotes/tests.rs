@@ -30,7 +30,7 @@ use uuid::Uuid;
 
 use crate::agent::{
     task::write_fut::{SpecialAction, WriteTask},
-    DisconnectionReason,
+    DisconnectionReason, LinkAdvisoryConfig,
 };
 
 use super::{RemoteSender, RemoteTracker, UplinkResponse};
@@ -56,7 +56,7 @@ fn insert_remote() {
     assert!(remotes.is_empty());
     assert!(!remotes.has_remote(RID1));
 
-    remotes.insert(RID1, tx, comp_tx);
+    remotes.insert(RID1, None, tx, comp_tx);
 
     assert!(!remotes.is_empty());
     assert!(remotes.has_remote(RID1));
@@ -79,8 +79,8 @@ fn setup() -> TestData {
     let mut remotes = RemoteTracker::new(ADDR, Text::new(NODE));
     let lane_id = remotes.lane_registry().add_endpoint(Text::new(LANE));
 
-    remotes.insert(RID1, tx1, comp_tx1);
-    remotes.insert(RID2, tx2, comp_tx2);
+    remotes.insert(RID1, None, tx1, comp_tx1);
+    remotes.insert(RID2, None, tx2, comp_tx2);
 
     TestData {
         remotes,
@@ -125,6 +125,32 @@ async fn dispatch_special() {
     }
 }
 
+#[tokio::test]
+async fn advise_link_over_limit() {
+    let (tx, mut rx) = byte_channel(BUFFER_SIZE);
+    let (comp_tx, _comp_rx) = promise::promise();
+    let mut remotes = RemoteTracker::new(ADDR, Text::new(NODE)).with_advisory(LinkAdvisoryConfig {
+        link_limit: Some(non_zero_usize!(1)),
+        ..Default::default()
+    });
+    let lane_id = remotes.lane_registry().add_endpoint(Text::new(LANE));
+    remotes.insert(RID1, None, tx, comp_tx);
+
+    assert!(remotes.advise_on_link(RID1, lane_id, 1).is_none());
+
+    if let Some(write) = remotes.advise_on_link(RID1, lane_id, 2) {
+        let expected =
+            BytesResponseMessage::advisory(ADDR, make_path(), Bytes::from_static(b"@unlink"));
+        let (writer, buffer) = expect_message(write, &mut rx, expected).await;
+        assert!(remotes.replace_and_pop(writer, buffer).is_none());
+    } else {
+        panic!("Expected a write task.");
+    }
+
+    // The link has already been advised.
+    assert!(remotes.advise_on_link(RID1, lane_id, 3).is_none());
+}
+
 const BODY: &[u8] = b"body";
 
 #[tokio::test]
@@ -139,6 +165,8 @@ async fn dispatch_normal() {
     if let Ok(Some(write)) = remotes.push_write(
         lane_id,
         UplinkResponse::Value(Bytes::from_static(BODY)),
+        None,
+        None,
         &RID1,
     ) {
         let expected = BytesResponseMessage::event(ADDR, make_path(), Bytes::from_static(BODY));
@@ -165,6 +193,8 @@ fn setup_with_pending(queue_write: bool) -> (TestData, WriteTask) {
             remotes.push_write(
                 lane_id,
                 UplinkResponse::Value(Bytes::from_static(BODY)),
+                None,
+                None,
                 &RID1
             ),
             Ok(None)
@@ -13,23 +13,31 @@
 // limitations under the License.
 
 use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::{BufMut, Bytes, BytesMut};
 use swimos_agent_protocol::MapOperation;
-use swimos_api::agent::UplinkKind;
+use swimos_api::agent::{LinkAdvice, SyncVersion, UplinkKind};
+use swimos_messages::protocol::{CorrelationId, LinkParams};
 use swimos_model::Text;
 use swimos_utilities::{byte_channel::ByteWriter, trigger::promise};
+use tokio::time::Instant;
 use tokio_util::codec::Encoder;
 use uuid::Uuid;
 
 use crate::{
     agent::{
+        intercept::OutgoingInterceptor,
+        recording::EnvelopeRecording,
         task::write_fut::{SpecialAction, WriteAction, WriteTask},
-        DisconnectionReason,
+        DisconnectionReason, LinkAdvisoryConfig, QueueOverflow, UplinkBackpressure,
     },
     backpressure::{
-        recon::MapOperationReconEncoder, BackpressureStrategy, InvalidKey, MapBackpressure,
-        SupplyBackpressure, ValueBackpressure,
+        check_key,
+        recon::{MapBatchReconEncoder, MapOperationReconEncoder},
+        BackpressureStrategy, InvalidKey, MapBackpressure, SupplyBackpressure, ValueBackpressure,
     },
 };
 
@@ -45,14 +53,51 @@ use super::{LaneRegistry, RemoteSender};
 /// backpressure relief mechanism for the lane. To pop from the queue, the writer is returned. If there is
 /// more work to be done, it will be popped and returned as a new future (once again removing the writer). If
 /// no work is pending, the writer is stored within the queue and nothing is returned.
+///
+/// If backpressure relief is disabled (see [`UplinkBackpressure::Queue`]), events are encoded
+/// immediately and held, in order, in a bounded queue for each uplink instead. The uplinks with queued
+/// events take turns to be written so that an uplink with a high rate of events cannot delay the
+/// events of the other uplinks for the remote indefinitely. When the queue for an uplink is full, either
+/// its oldest event is discarded or further events are passed to the backpressure relief mechanism
+/// until the queue has been drained (see [`QueueOverflow`]).
+///
+/// A remote can request that the events for an uplink are limited to a maximum rate. Events for such
+/// an uplink that arrive too soon after the previous write are held in its backpressure relief
+/// mechanism (regardless of the relief mode) until the uplink is released (see [`Uplinks::release`]).
+/// When more than one uplink has work pending, the uplink with the highest priority is written first.
+///
+/// If the request that caused a write carried a correlation ID, the ID is echoed on the envelope
+/// that is sent to the remote. When events are conflated by the backpressure relief mechanism,
+/// the envelope carries the ID of the most recent event. Similarly, for lanes that track the
+/// origins of commands, the envelope for an event reports the remote that sent the command that
+/// caused it.
+///
+/// A remote can also request that a sync is sent in pages. The events for such an uplink are held in its
+/// backpressure relief mechanism and, once a full window of events has been written, no more are written
+/// until the remote acknowledges them (see [`Uplinks::ack`]). The window is discarded when the synced
+/// message is written.
+///
+/// If link advisories are enabled (see [`LinkAdvisoryConfig`]), low priority uplinks are advised to
+/// back off when too many writes are waiting for the remote (see [`Uplinks::advise_if_congested`]).
+/// Each uplink receives a given piece of advice at most once until the pressure on the remote is
+/// relieved.
 #[derive(Debug)]
 pub struct Uplinks {
     writer: Option<(RemoteSender, BytesMut)>, //Holds the sender and associated buffer when it has not been leant out.
+    relief: UplinkBackpressure, //Determines whether events are conflated when the remote is slow.
+    event_queue: EventQueues,   //Queues of encoded events, used when relief is disabled.
     value_uplinks: HashMap<u64, Uplink<ValueBackpressure>>, //Uplinks for value lanes.
     supply_uplinks: HashMap<u64, Uplink<SupplyBackpressure>>, //Uplinks for supply lanes.
     map_uplinks: HashMap<u64, Uplink<MapBackpressure>>, //Uplinks for map lanes.
     write_queue: VecDeque<(UplinkKind, u64)>, //Queue tracking which uplink should be written next.
-    special_queue: VecDeque<SpecialAction>, //Queue of special actions (primarily link/unlink messages) which take precedence over uplinks.
+    special_queue: VecDeque<(SpecialAction, Option<CorrelationId>)>, //Queue of special actions (primarily link/unlink messages) which take precedence over uplinks, with the correlation IDs of the requests that caused them.
+    rate_limits: HashMap<u64, RateLimit>, //Rate limits requested by the remote for its uplinks.
+    priorities: HashMap<u64, f32>, //Priorities requested by the remote for its uplinks (absent entries have priority 0).
+    releases: Vec<(u64, Instant)>, //Rate limited uplinks that need to be released at the specified times.
+    sync_windows: HashMap<u64, SyncWindow>, //Windows for uplinks that are being synced in pages.
+    advisory: LinkAdvisoryConfig,  //Limits at which low priority uplinks are advised to back off.
+    advised: HashMap<u64, LinkAdvice>, //Advice that has already been sent for each uplink.
+    link_names: HashMap<u64, Text>, //Aliases by which the remote addressed lanes (these are used in place of the names of the lanes).
     completion: promise::Sender<DisconnectionReason>, //Promise to be satisfied when the remote is closed.
 }
 
@@ -61,16 +106,40 @@ pub struct Uplinks {
 pub enum UplinkResponse {
     /// A synced message.
     Synced(UplinkKind),
+    /// A synced message for a map lane, reporting the version of the state of the lane.
+    SyncedAt(SyncVersion),
     /// An event message for a value type lane.
     Value(Bytes),
     /// An event message for a supply value type lane.
     Supply(Bytes),
     /// An event message for a map type lane.
     Map(MapOperation<BytesMut, BytesMut>),
+    /// A batch of operations for a map type lane that must be delivered as a single event.
+    MapBatch(Vec<MapOperation<BytesMut, BytesMut>>),
 }
 
 const UNREGISTERED_LANE: &str = "Unregistered lane ID.";
 
+/// Get the name to use for a lane in the envelopes sent to the remote.
+fn lane_name<'a>(
+    registry: &'a LaneRegistry,
+    link_names: &'a HashMap<u64, Text>,
+    lane_id: u64,
+) -> &'a str {
+    link_names
+        .get(&lane_id)
+        .map(Text::as_str)
+        .or_else(|| registry.name_for(lane_id))
+        .expect(UNREGISTERED_LANE)
+}
+
+/// Once the unlinked message for a lane is written, the alias used by the remote no longer applies.
+fn clear_link_name(action: &SpecialAction, link_names: &mut HashMap<u64, Text>) {
+    if let SpecialAction::Unlinked { lane_id, .. } = action {
+        link_names.remove(lane_id);
+    }
+}
+
 impl Uplinks {
     /// # Arguments
     /// * `node` - The node URI to attach to the outgoing messages.
@@ -88,15 +157,178 @@ impl Uplinks {
         let sender = RemoteSender::new(writer, identity, remote_id, node);
         Uplinks {
             writer: Some((sender, Default::default())),
+            relief: UplinkBackpressure::Conflate,
+            event_queue: Default::default(),
             value_uplinks: Default::default(),
             supply_uplinks: Default::default(),
             map_uplinks: Default::default(),
             write_queue: Default::default(),
             special_queue: Default::default(),
+            rate_limits: Default::default(),
+            priorities: Default::default(),
+            releases: Default::default(),
+            sync_windows: Default::default(),
+            advisory: Default::default(),
+            advised: Default::default(),
+            link_names: Default::default(),
             completion,
         }
     }
 
+    /// Set the network address of the remote, used to identify it to an interceptor.
+    pub fn with_remote_addr(mut self, addr: Option<SocketAddr>) -> Self {
+        if let Some((sender, buffer)) = self.writer.take() {
+            self.writer = Some((sender.with_remote_addr(addr), buffer));
+        }
+        self
+    }
+
+    /// Transform the bodies of the events sent to the remote with an interceptor.
+    pub fn with_interceptor(mut self, interceptor: Option<Arc<dyn OutgoingInterceptor>>) -> Self {
+        if let Some((sender, buffer)) = self.writer.take() {
+            self.writer = Some((sender.with_interceptor(interceptor), buffer));
+        }
+        self
+    }
+
+    /// Record the envelopes that are sent to the remote.
+    pub fn with_recording(mut self, recording: Option<EnvelopeRecording>) -> Self {
+        if let Some((sender, buffer)) = self.writer.take() {
+            self.writer = Some((sender.with_recording(recording), buffer));
+        }
+        self
+    }
+
+    /// Set how events are handled when the remote is not keeping up with the agent.
+    pub fn with_backpressure(mut self, relief: UplinkBackpressure) -> Self {
+        self.relief = relief;
+        self
+    }
+
+    /// Set the limits at which low priority uplinks are advised to back off.
+    pub fn with_advisory(mut self, advisory: LinkAdvisoryConfig) -> Self {
+        self.advisory = advisory;
+        self
+    }
+
+    /// Set the alias by which the remote addressed a lane. Envelopes for the lane will be sent to
+    /// the remote with the alias until the lane is unlinked.
+    /// # Arguments
+    /// * `lane_id` - ID of the lane.
+    /// * `alias` - The alias or nothing if the remote used the name of the lane.
+    pub fn set_link_name(&mut self, lane_id: u64, alias: Option<Text>) {
+        if let Some(alias) = alias {
+            self.link_names.insert(lane_id, alias);
+        } else {
+            self.link_names.remove(&lane_id);
+        }
+    }
+
+    /// Apply the parameters provided by the remote when linking to or syncing with a lane. This
+    /// replaces any rate and priority that were previously set for the uplink. If a window is
+    /// provided, the sync will be sent in pages of that size.
+    /// # Arguments
+    /// * `lane_id` - ID of the lane.
+    /// * `params` - The requested rate, priority and sync window for the uplink.
+    pub fn set_params(&mut self, lane_id: u64, params: LinkParams) {
+        let Uplinks {
+            rate_limits,
+            priorities,
+            sync_windows,
+            ..
+        } = self;
+        let LinkParams {
+            rate, prio, window, ..
+        } = params;
+        if let Some(size) = window.filter(|w| *w > 0) {
+            sync_windows.insert(lane_id, SyncWindow::new(size));
+        }
+        match rate.filter(|r| r.is_finite() && *r > 0.0) {
+            Some(rate) => {
+                let interval = Duration::from_secs_f64(1.0 / f64::from(rate));
+                rate_limits
+                    .entry(lane_id)
+                    .and_modify(|limit| limit.interval = interval)
+                    .or_insert_with(|| RateLimit::new(interval));
+            }
+            None => {
+                rate_limits.remove(&lane_id);
+            }
+        }
+        match prio.filter(|p| p.is_finite() && *p != 0.0) {
+            Some(prio) => {
+                priorities.insert(lane_id, prio);
+            }
+            None => {
+                priorities.remove(&lane_id);
+            }
+        }
+    }
+
+    /// Take the rate limited uplinks that have events waiting, along with the times at which they
+    /// should be released.
+    pub fn take_releases(&mut self) -> impl Iterator<Item = (u64, Instant)> + '_ {
+        self.releases.drain(..)
+    }
+
+    /// Release a rate limited uplink, after its delay has elapsed, so that any events that were held
+    /// for it can be written.
+    /// # Arguments
+    /// * `lane_id` - ID of the lane.
+    /// * `registry` - Registry mapping lane IDs to lane names.
+    pub fn release(&mut self, lane_id: u64, registry: &LaneRegistry) -> Option<WriteTask> {
+        self.rate_limits.get_mut(&lane_id)?.release_scheduled = false;
+        if is_window_closed(&self.sync_windows, lane_id) {
+            // The uplink will be queued again when the remote acknowledges the current page.
+            return self.pop_if_idle(registry);
+        }
+        let Uplinks {
+            value_uplinks,
+            supply_uplinks,
+            map_uplinks,
+            write_queue,
+            ..
+        } = self;
+        let pending = if let Some(uplink) = value_uplinks.get_mut(&lane_id) {
+            Some((
+                UplinkKind::Value,
+                uplink.backpressure.has_data(),
+                &mut uplink.queued,
+            ))
+        } else if let Some(uplink) = supply_uplinks.get_mut(&lane_id) {
+            Some((
+                UplinkKind::Supply,
+                uplink.backpressure.has_data(),
+                &mut uplink.queued,
+            ))
+        } else {
+            map_uplinks.get_mut(&lane_id).map(|uplink| {
+                (
+                    UplinkKind::Map,
+                    uplink.backpressure.has_data(),
+                    &mut uplink.queued,
+                )
+            })
+        };
+        if let Some((kind, true, queued)) = pending {
+            if !*queued {
+                write_queue.push_back((kind, lane_id));
+                *queued = true;
+            }
+        }
+        self.pop_if_idle(registry)
+    }
+
+    /// If the writer is not in use but there is work queued, start the next write.
+    fn pop_if_idle(&mut self, registry: &LaneRegistry) -> Option<WriteTask> {
+        if self.write_queue.is_empty() {
+            None
+        } else {
+            let (sender, buffer) = self.writer.take()?;
+            self.replace_and_pop(sender, buffer, registry)
+        }
+    }
+
     /// Push a special action into the queue. Special actions are not subject to backpressure relief and
     /// are always popped before other entries.
     /// # Arguments
@@ -106,6 +338,21 @@ impl Uplinks {
         &mut self,
         action: SpecialAction,
         registry: &LaneRegistry,
+    ) -> Option<WriteTask> {
+        self.push_correlated_special(action, None, registry)
+    }
+
+    /// Push a special action, caused by a request with a correlation ID, into the queue. The ID
+    /// will be echoed on the envelope that is sent to the remote.
+    /// # Arguments
+    /// * `actions` - The special action.
+    /// * `correlation_id` - The correlation ID of the request.
+    /// * `registry` - Registry mapping lane IDs to lane names.
+    pub fn push_correlated_special(
+        &mut self,
+        action: SpecialAction,
+        correlation_id: Option<CorrelationId>,
+        registry: &LaneRegistry,
     ) -> Option<WriteTask> {
         let Uplinks {
             writer,
@@ -113,32 +360,65 @@ impl Uplinks {
             supply_uplinks,
             map_uplinks,
             special_queue,
+            event_queue,
+            rate_limits,
+            priorities,
+            sync_windows,
+            advised,
+            link_names,
             ..
         } = self;
+        if let SpecialAction::Unlinked { lane_id, .. } = &action {
+            rate_limits.remove(lane_id);
+            priorities.remove(lane_id);
+            sync_windows.remove(lane_id);
+            advised.remove(lane_id);
+        }
         if let Some((mut writer, buffer)) = writer.take() {
-            let lane_name = action.lane_name(registry);
+            let lane_name = action.lane_name(registry, link_names);
             writer.update_lane(lane_name);
+            writer.update_correlation_id(correlation_id);
+            clear_link_name(&action, link_names);
             Some(WriteTask::new(writer, buffer, WriteAction::Special(action)))
         } else {
             if let SpecialAction::Unlinked { lane_id, .. } = &action {
                 value_uplinks.remove(lane_id);
                 supply_uplinks.remove(lane_id);
                 map_uplinks.remove(lane_id);
+                event_queue.remove_lane(*lane_id);
             }
-            special_queue.push_back(action);
+            special_queue.push_back((action, correlation_id));
             None
         }
     }
 
-    /// Push an event into the queue.
+    /// Push an event, with no known origin, into the queue.
+    #[cfg(test)]
+    pub fn push(
+        &mut self,
+        lane_id: u64,
+        event: UplinkResponse,
+        correlation_id: Option<CorrelationId>,
+        registry: &LaneRegistry,
+    ) -> Result<Option<WriteTask>, InvalidKey> {
+        self.push_originated(lane_id, event, correlation_id, None, registry)
+    }
+
+    /// Push an event, caused by a command from a known remote, into the queue.
     /// # Arguments
     /// * `lane_id` - ID of the lane to which the event refers.
     /// * `event` - The event.
+    /// * `correlation_id` - The correlation ID of the command that caused the event (if any).
+    /// * `command_origin` - The ID of the remote that sent the command that caused the event (only
+    ///   for lanes that track the origins of commands). This will be reported on the envelope that
+    ///   is sent to the remote.
     /// * `registry` - Registry mapping lane IDs to lane names.
-    pub fn push(
+    pub fn push_originated(
         &mut self,
         lane_id: u64,
         event: UplinkResponse,
+        correlation_id: Option<CorrelationId>,
+        command_origin: Option<Uuid>,
         registry: &LaneRegistry,
     ) -> Result<Option<WriteTask>, InvalidKey> {
         let Uplinks {
@@ -147,89 +427,161 @@ impl Uplinks {
             supply_uplinks,
             map_uplinks,
             write_queue,
+            relief,
+            event_queue,
+            rate_limits,
+            releases,
+            sync_windows,
+            link_names,
             ..
         } = self;
-        if let Some((mut writer, mut buffer)) = writer.take() {
+        let now = Instant::now();
+        let throttled = rate_limits
+            .get(&lane_id)
+            .map(|limit| limit.is_throttled(now))
+            .unwrap_or(false);
+        // Events for a paged sync must always be counted against the window.
+        let paged = sync_windows.contains_key(&lane_id);
+        let available = if throttled || paged {
+            None
+        } else {
+            writer.take()
+        };
+        if let Some((mut writer, mut buffer)) = available {
             let action = write_to_buffer(event, &mut buffer)?;
-            let lane_name = registry.name_for(lane_id).expect(UNREGISTERED_LANE);
+            let lane_name = lane_name(registry, link_names, lane_id);
             writer.update_lane(lane_name);
+            writer.update_correlation_id(correlation_id);
+            writer.update_command_origin(command_origin);
+            if let Some(limit) = rate_limits.get_mut(&lane_id) {
+                limit.written(now);
+            }
             Ok(Some(WriteTask::new(writer, buffer, action)))
+        } else if !rate_limits.contains_key(&lane_id)
+            && !paged
+            && !relief_pending(value_uplinks, supply_uplinks, map_uplinks, lane_id)
+            && make_room(*relief, event_queue, lane_id)
+        {
+            let mut body = BytesMut::new();
+            let action = write_to_buffer(event, &mut body)?;
+            event_queue.push(lane_id, action, body, correlation_id, command_origin);
+            Ok(None)
         } else {
-            match event {
+            let (kind, is_synced, queued) = match event {
                 UplinkResponse::Value(body) => {
                     let Uplink {
                         queued,
                         backpressure,
+                        correlation,
+                        origin,
                         ..
                     } = value_uplinks.entry(lane_id).or_default();
                     backpressure.push_bytes(body);
-                    if !*queued {
-                        write_queue.push_back((UplinkKind::Value, lane_id));
-                        *queued = true;
-                    }
+                    *correlation = correlation_id;
+                    *origin = command_origin;
+                    (UplinkKind::Value, false, queued)
                 }
                 UplinkResponse::Supply(body) => {
                     let Uplink {
                         queued,
                         backpressure,
+                        correlation,
+                        origin,
                         ..
                     } = supply_uplinks.entry(lane_id).or_default();
                     backpressure.push_bytes(body);
-                    if !*queued {
-                        write_queue.push_back((UplinkKind::Supply, lane_id));
-                        *queued = true;
-                    }
+                    *correlation = correlation_id;
+                    *origin = command_origin;
+                    (UplinkKind::Supply, false, queued)
                 }
                 UplinkResponse::Map(operation) => {
                     let Uplink {
                         queued,
                         backpressure,
+                        correlation,
+                        origin,
                         ..
                     } = map_uplinks.entry(lane_id).or_default();
                     backpressure.push(operation)?;
-                    if !*queued {
-                        write_queue.push_back((UplinkKind::Map, lane_id));
-                        *queued = true;
-                    }
+                    *correlation = correlation_id;
+                    *origin = command_origin;
+                    (UplinkKind::Map, false, queued)
                 }
-                UplinkResponse::Synced(UplinkKind::Value) => {
+                UplinkResponse::MapBatch(operations) => {
                     let Uplink {
                         queued,
-                        send_synced,
+                        backpressure,
+                        correlation,
+                        origin,
                         ..
-                    } = value_uplinks.entry(lane_id).or_default();
-                    *send_synced = true;
-                    if !*queued {
-                        write_queue.push_back((UplinkKind::Value, lane_id));
-                        *queued = true;
-                    }
+                    } = map_uplinks.entry(lane_id).or_default();
+                    backpressure.push_batch(operations)?;
+                    *correlation = correlation_id;
+                    *origin = command_origin;
+                    (UplinkKind::Map, false, queued)
                 }
-                UplinkResponse::Synced(UplinkKind::Supply) => {
-                    let Uplink {
-                        queued,
-                        send_synced,
-                        ..
-                    } = supply_uplinks.entry(lane_id).or_default();
+                UplinkResponse::Synced(kind) => {
+                    let (queued, send_synced, correlation) = match kind {
+                        UplinkKind::Value => {
+                            let Uplink {
+                                queued,
+                                send_synced,
+                                correlation,
+                                ..
+                            } = value_uplinks.entry(lane_id).or_default();
+                            (queued, send_synced, correlation)
+                        }
+                        UplinkKind::Supply => {
+                            let Uplink {
+                                queued,
+                                send_synced,
+                                correlation,
+                                ..
+                            } = supply_uplinks.entry(lane_id).or_default();
+                            (queued, send_synced, correlation)
+                        }
+                        UplinkKind::Map => {
+                            let Uplink {
+                                queued,
+                                send_synced,
+                                correlation,
+                                ..
+                            } = map_uplinks.entry(lane_id).or_default();
+                            (queued, send_synced, correlation)
+                        }
+                    };
                     *send_synced = true;
-                    if !*queued {
-                        write_queue.push_back((UplinkKind::Supply, lane_id));
-                        *queued = true;
-                    }
+                    *correlation = correlation_id;
+                    (kind, true, queued)
                 }
-                UplinkResponse::Synced(UplinkKind::Map) => {
+                UplinkResponse::SyncedAt(sync_version) => {
                     let Uplink {
                         queued,
                         send_synced,
+                        version,
+                        correlation,
                         ..
                     } = map_uplinks.entry(lane_id).or_default();
                     *send_synced = true;
-                    if !*queued {
-                        write_queue.push_back((UplinkKind::Map, lane_id));
-                        *queued = true;
-                    }
+                    *version = Some(sync_version);
+                    *correlation = correlation_id;
+                    (UplinkKind::Map, true, queued)
                 }
+            };
+            if *queued {
+                // The uplink is already waiting to be written.
+            } else if !is_synced && is_window_closed(sync_windows, lane_id) {
+                // The uplink will be queued when the remote acknowledges the current page.
+            } else if throttled && !is_synced {
+                if let Some(limit) = rate_limits.get_mut(&lane_id) {
+                    limit.schedule_release(lane_id, releases);
+                }
+            } else {
+                write_queue.push_back((kind, lane_id));
+                *queued = true;
             }
-            Ok(None)
+            // Synced messages are not subject to the rate limit so the writer could be idle.
+            Ok(self.pop_if_idle(registry))
         }
     }
 
@@ -252,38 +604,68 @@ impl Uplinks {
             map_uplinks,
             write_queue,
             special_queue,
+            event_queue,
+            rate_limits,
+            priorities,
+            releases,
+            sync_windows,
+            advised,
+            link_names,
             ..
         } = self;
         debug_assert!(writer.is_none());
-        if let Some(special) = special_queue.pop_front() {
-            sender.update_lane(special.lane_name(registry));
+        let now = Instant::now();
+        if let Some((special, correlation_id)) = special_queue.pop_front() {
+            sender.update_lane(special.lane_name(registry, link_names));
+            sender.update_correlation_id(correlation_id);
+            clear_link_name(&special, link_names);
             Some(WriteTask::new(
                 sender,
                 buffer,
                 WriteAction::Special(special),
             ))
+        } else if let Some((lane_id, (action, mut body, correlation_id, origin))) =
+            event_queue.pop(priorities)
+        {
+            std::mem::swap(&mut buffer, &mut body);
+            let lane_name = lane_name(registry, link_names, lane_id);
+            sender.update_lane(lane_name);
+            sender.update_correlation_id(correlation_id);
+            sender.update_command_origin(origin);
+            Some(WriteTask::new(sender, buffer, action))
         } else {
             loop {
-                if let Some((kind, lane_id)) = write_queue.pop_front() {
+                if let Some((kind, lane_id)) =
+                    pop_by_priority(write_queue, priorities, |(_, lane_id)| *lane_id)
+                {
+                    let mut limit = rate_limits.get_mut(&lane_id);
+                    if let Some(limit) = limit.as_mut() {
+                        limit.written(now);
+                    }
                     match kind {
                         UplinkKind::Value => {
                             if let Some(Uplink {
                                 queued,
                                 send_synced,
                                 backpressure,
+                                correlation,
+                                origin,
+                                ..
                             }) = value_uplinks.get_mut(&lane_id)
                             {
                                 *queued = false;
                                 let synced = std::mem::replace(send_synced, false);
                                 backpressure.prepare_write(&mut buffer);
                                 let action = if synced {
+                                    sync_windows.remove(&lane_id);
                                     WriteAction::ValueSynced(true)
                                 } else {
                                     WriteAction::Event
                                 };
-                                let lane_name =
-                                    registry.name_for(lane_id).expect(UNREGISTERED_LANE);
+                                let lane_name = lane_name(registry, link_names, lane_id);
                                 sender.update_lane(lane_name);
+                                sender.update_correlation_id(correlation.take());
+                                sender.update_command_origin(origin.take());
                                 break Some(WriteTask::new(sender, buffer, action));
                             }
                         }
@@ -292,18 +674,25 @@ impl Uplinks {
                                 queued,
                                 send_synced,
                                 backpressure,
+                                correlation,
+                                origin,
+                                ..
                             }) = supply_uplinks.get_mut(&lane_id)
                             {
                                 let synced = std::mem::replace(send_synced, false);
 
                                 let had_data = backpressure.has_data();
                                 backpressure.prepare_write(&mut buffer);
-                                if backpressure.has_data() {
-                                    write_queue.push_back((UplinkKind::Supply, lane_id));
-                                } else {
+                                if !backpressure.has_data() {
                                     *queued = false;
+                                } else if let Some(limit) = limit {
+                                    *queued = false;
+                                    limit.schedule_release(lane_id, releases);
+                                } else {
+                                    write_queue.push_back((UplinkKind::Supply, lane_id));
                                 }
                                 let maybe_action = if synced {
+                                    sync_windows.remove(&lane_id);
                                     Some(WriteAction::ValueSynced(had_data))
                                 } else if had_data {
                                     Some(WriteAction::Event)
@@ -311,9 +700,10 @@ impl Uplinks {
                                     None
                                 };
                                 if let Some(action) = maybe_action {
-                                    let lane_name =
-                                        registry.name_for(lane_id).expect(UNREGISTERED_LANE);
+                                    let lane_name = lane_name(registry, link_names, lane_id);
                                     sender.update_lane(lane_name);
+                                    sender.update_correlation_id(correlation.take());
+                                    sender.update_command_origin(origin.take());
                                     break Some(WriteTask::new(sender, buffer, action));
                                 }
                             }
@@ -322,32 +712,58 @@ impl Uplinks {
                             if let Some(Uplink {
                                 queued,
                                 send_synced,
+                                version,
                                 backpressure,
+                                correlation,
+                                origin,
                             }) = map_uplinks.get_mut(&lane_id)
                             {
                                 let synced = std::mem::replace(send_synced, false);
-                                let write = if synced {
+                                let window = sync_windows.get_mut(&lane_id);
+                                // For a paged sync, the synced message is held until all of the
+                                // events have been written.
+                                let write_synced =
+                                    synced && (window.is_none() || !backpressure.has_data());
+                                let write = if write_synced {
                                     *queued = false;
-                                    let lane_name =
-                                        registry.name_for(lane_id).expect(UNREGISTERED_LANE);
+                                    sync_windows.remove(&lane_id);
+                                    let lane_name = lane_name(registry, link_names, lane_id);
                                     sender.update_lane(lane_name);
+                                    sender.update_correlation_id(correlation.take());
+                                    sender.update_command_origin(origin.take());
                                     WriteTask::new(
                                         sender,
                                         buffer,
-                                        WriteAction::MapSynced(Some(Box::new(std::mem::take(
-                                            backpressure,
-                                        )))),
+                                        WriteAction::MapSynced(
+                                            Some(Box::new(std::mem::take(backpressure))),
+                                            version.take(),
+                                        ),
                                     )
                                 } else {
+                                    *send_synced = synced;
                                     backpressure.prepare_write(&mut buffer);
-                                    if backpressure.has_data() {
+                                    let window_closed = window
+                                        .map(|window| {
+                                            window.written();
+                                            window.is_closed()
+                                        })
+                                        .unwrap_or(false);
+                                    if !backpressure.has_data() && !*send_synced {
+                                        *queued = false;
+                                    } else if !backpressure.has_data() {
                                         write_queue.push_back((UplinkKind::Map, lane_id));
-                                    } else {
+                                    } else if window_closed {
+                                        *queued = false;
+                                    } else if let Some(limit) = limit {
                                         *queued = false;
+                                        limit.schedule_release(lane_id, releases);
+                                    } else {
+                                        write_queue.push_back((UplinkKind::Map, lane_id));
                                     }
-                                    let lane_name =
-                                        registry.name_for(lane_id).expect(UNREGISTERED_LANE);
+                                    let lane_name = lane_name(registry, link_names, lane_id);
                                     sender.update_lane(lane_name);
+                                    sender.update_correlation_id(*correlation);
+                                    sender.update_command_origin(*origin);
                                     WriteTask::new(sender, buffer, WriteAction::Event)
                                 };
                                 break Some(write);
@@ -355,6 +771,9 @@ impl Uplinks {
                         }
                     }
                 } else {
+                    // The remote has caught up so uplinks can be advised to reduce their rate again
+                    // if it falls behind in future.
+                    advised.retain(|_, advice| *advice != LinkAdvice::ReduceRate);
                     *writer = Some((sender, buffer));
                     break None;
                 }
@@ -362,6 +781,92 @@ impl Uplinks {
         }
     }
 
+    /// If the number of writes waiting for the remote has reached the configured limit, advise the
+    /// remote to reduce the rate of the uplink for a lane, if it is low priority. The advice is
+    /// queued behind any other special actions and so this never starts a write.
+    /// # Arguments
+    /// * `lane_id` - ID of the lane.
+    pub fn advise_if_congested(&mut self, lane_id: u64) {
+        let Uplinks {
+            writer,
+            event_queue,
+            write_queue,
+            special_queue,
+            priorities,
+            advisory,
+            advised,
+            ..
+        } = self;
+        let congested = advisory
+            .queue_limit
+            .map(|limit| event_queue.len() + write_queue.len() >= limit.get())
+            .unwrap_or(false);
+        let prio = priorities.get(&lane_id).copied().unwrap_or_default();
+        if writer.is_none()
+            && congested
+            && advisory.is_low_priority(prio)
+            && !advised.contains_key(&lane_id)
+        {
+            advised.insert(lane_id, LinkAdvice::ReduceRate);
+            special_queue.push_back((
+                SpecialAction::advisory(lane_id, LinkAdvice::ReduceRate),
+                None,
+            ));
+        }
+    }
+
+    /// Advise the remote to close the link to a lane, if it is low priority. This is used when
+    /// the agent has more links than the configured limit.
+    /// # Arguments
+    /// * `lane_id` - ID of the lane.
+    /// * `registry` - Registry mapping lane IDs to lane names.
+    pub fn advise_unlink(&mut self, lane_id: u64, registry: &LaneRegistry) -> Option<WriteTask> {
+        let Uplinks {
+            priorities,
+            advisory,
+            advised,
+            ..
+        } = self;
+        let prio = priorities.get(&lane_id).copied().unwrap_or_default();
+        if advisory.is_low_priority(prio) && advised.get(&lane_id) != Some(&LinkAdvice::Unlink) {
+            advised.insert(lane_id, LinkAdvice::Unlink);
+            self.push_special(
+                SpecialAction::advisory(lane_id, LinkAdvice::Unlink),
+                registry,
+            )
+        } else {
+            None
+        }
+    }
+
+    /// Handle an acknowledgement from the remote for the current page of a paged sync. This opens
+    /// the window again so that the next page of events can be written.
+    /// # Arguments
+    /// * `lane_id` - ID of the lane.
+    /// * `registry` - Registry mapping lane IDs to lane names.
+    pub fn ack(&mut self, lane_id: u64, registry: &LaneRegistry) -> Option<WriteTask> {
+        let Uplinks {
+            map_uplinks,
+            write_queue,
+            sync_windows,
+            ..
+        } = self;
+        sync_windows.get_mut(&lane_id)?.reopen();
+        if let Some(Uplink {
+            queued,
+            send_synced,
+            backpressure,
+            ..
+        }) = map_uplinks.get_mut(&lane_id)
+        {
+            if !*queued && (backpressure.has_data() || *send_synced) {
+                write_queue.push_back((UplinkKind::Map, lane_id));
+                *queued = true;
+            }
+        }
+        self.pop_if_idle(registry)
+    }
+
     /// Dispose of the uplinks, providing the specified reason.
     pub fn complete(self, reason: DisconnectionReason) {
         let _ = self.completion.provide(reason);
@@ -371,9 +876,222 @@ impl Uplinks {
 /// The state of a single uplink within an [`Uplinks`] instance for a remote.
 #[derive(Debug, Default)]
 struct Uplink<B> {
-    queued: bool,      //Indicates that this uplink is currently in the queue.
+    queued: bool,                 //Indicates that this uplink is currently in the queue.
     send_synced: bool, //Indicates that a synced message needs to be emitted for this uplink.
-    backpressure: B,   //Backpressure relief queue (varying implementation based on uplink kind).
+    version: Option<SyncVersion>, //The version of the lane to report in the synced message (map lanes only).
+    backpressure: B, //Backpressure relief queue (varying implementation based on uplink kind).
+    correlation: Option<CorrelationId>, //The correlation ID of the request that caused the most recent event (echoed on the next write).
+    origin: Option<Uuid>, //The remote that sent the command that caused the most recent event (reported on the next write).
+}
+
+/// Determine whether events for an uplink are held in its backpressure relief mechanism. While they are,
+/// new events must also be added to it to preserve their order.
+fn relief_pending(
+    value_uplinks: &HashMap<u64, Uplink<ValueBackpressure>>,
+    supply_uplinks: &HashMap<u64, Uplink<SupplyBackpressure>>,
+    map_uplinks: &HashMap<u64, Uplink<MapBackpressure>>,
+    lane_id: u64,
+) -> bool {
+    value_uplinks.get(&lane_id).is_some_and(|u| u.queued)
+        || supply_uplinks.get(&lane_id).is_some_and(|u| u.queued)
+        || map_uplinks.get(&lane_id).is_some_and(|u| u.queued)
+}
+
+/// Determine whether a new event for an uplink should be added to its queue, discarding the oldest
+/// event in the queue if it is full and the overflow policy requires it.
+fn make_room(relief: UplinkBackpressure, event_queue: &mut EventQueues, lane_id: u64) -> bool {
+    match relief {
+        UplinkBackpressure::Conflate => false,
+        UplinkBackpressure::Queue { capacity, overflow } => {
+            if event_queue.lane_len(lane_id) < capacity.get() {
+                true
+            } else {
+                match overflow {
+                    QueueOverflow::Conflate => false,
+                    QueueOverflow::DropOldest => event_queue.drop_oldest_event(lane_id),
+                }
+            }
+        }
+    }
+}
+
+/// An encoded event, with the correlation ID of the request that caused it and the remote that
+/// sent that request.
+type QueuedEvent = (WriteAction, BytesMut, Option<CorrelationId>, Option<Uuid>);
+
+/// The queues of encoded events for the uplinks within an [`Uplinks`] instance (used when
+/// backpressure relief is disabled). The uplinks with events waiting are served in turn, in order
+/// of priority.
+#[derive(Debug, Default)]
+struct EventQueues {
+    queues: HashMap<u64, VecDeque<QueuedEvent>>, //The events waiting for each uplink (with no empty entries).
+    turns: VecDeque<u64>, //The uplinks with events waiting, in the order in which they will be served.
+    len: usize,           //The total number of events waiting.
+}
+
+impl EventQueues {
+    /// The total number of events waiting, across all uplinks.
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn push(
+        &mut self,
+        lane_id: u64,
+        action: WriteAction,
+        body: BytesMut,
+        correlation_id: Option<CorrelationId>,
+        origin: Option<Uuid>,
+    ) {
+        let EventQueues { queues, turns, len } = self;
+        let queue = queues.entry(lane_id).or_default();
+        if queue.is_empty() {
+            turns.push_back(lane_id);
+        }
+        queue.push_back((action, body, correlation_id, origin));
+        *len += 1;
+    }
+
+    /// The number of events waiting for an uplink.
+    fn lane_len(&self, lane_id: u64) -> usize {
+        self.queues.get(&lane_id).map(VecDeque::len).unwrap_or(0)
+    }
+
+    /// Discard the oldest event (that is not a synced message) waiting for an uplink. Returns false if
+    /// there was no such event.
+    fn drop_oldest_event(&mut self, lane_id: u64) -> bool {
+        let EventQueues { queues, len, .. } = self;
+        let Some(queue) = queues.get_mut(&lane_id) else {
+            return false;
+        };
+        match queue
+            .iter()
+            .position(|(action, ..)| matches!(action, WriteAction::Event))
+        {
+            Some(i) => {
+                queue.remove(i);
+                *len -= 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Discard all events waiting for an uplink.
+    fn remove_lane(&mut self, lane_id: u64) {
+        let EventQueues { queues, turns, len } = self;
+        if let Some(queue) = queues.remove(&lane_id) {
+            *len -= queue.len();
+            turns.retain(|id| *id != lane_id);
+        }
+    }
+
+    /// Take the next event for the uplink with the highest priority. Of the uplinks with the same
+    /// priority, the one that was written least recently is chosen.
+    fn pop(&mut self, priorities: &HashMap<u64, f32>) -> Option<(u64, QueuedEvent)> {
+        let EventQueues { queues, turns, len } = self;
+        let lane_id = pop_by_priority(turns, priorities, |lane_id| *lane_id)?;
+        let queue = queues.get_mut(&lane_id)?;
+        let event = queue.pop_front()?;
+        *len -= 1;
+        if queue.is_empty() {
+            queues.remove(&lane_id);
+        } else {
+            turns.push_back(lane_id);
+        }
+        Some((lane_id, event))
+    }
+}
+
+/// The window for an uplink that is being synced in pages within an [`Uplinks`] instance.
+#[derive(Debug)]
+struct SyncWindow {
+    size: u32,      //The number of events that can be written for each acknowledgement.
+    remaining: u32, //The number of events that can be written before the next acknowledgement.
+}
+
+impl SyncWindow {
+    fn new(size: u32) -> Self {
+        SyncWindow {
+            size,
+            remaining: size,
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.remaining == 0
+    }
+
+    fn written(&mut self) {
+        self.remaining = self.remaining.saturating_sub(1);
+    }
+
+    fn reopen(&mut self) {
+        self.remaining = self.size;
+    }
+}
+
+fn is_window_closed(sync_windows: &HashMap<u64, SyncWindow>, lane_id: u64) -> bool {
+    sync_windows
+        .get(&lane_id)
+        .map(SyncWindow::is_closed)
+        .unwrap_or(false)
+}
+
+/// The rate limit for a single uplink within an [`Uplinks`] instance.
+#[derive(Debug)]
+struct RateLimit {
+    interval: Duration,          //The minimum time between writes for the uplink.
+    next_write: Option<Instant>, //The earliest time at which the uplink can next be written.
+    release_scheduled: bool,     //Indicates that the uplink is already waiting to be released.
+}
+
+impl RateLimit {
+    fn new(interval: Duration) -> Self {
+        RateLimit {
+            interval,
+            next_write: None,
+            release_scheduled: false,
+        }
+    }
+
+    fn is_throttled(&self, now: Instant) -> bool {
+        matches!(self.next_write, Some(t) if now < t)
+    }
+
+    fn written(&mut self, now: Instant) {
+        self.next_write = now.checked_add(self.interval);
+    }
+
+    fn schedule_release(&mut self, lane_id: u64, releases: &mut Vec<(u64, Instant)>) {
+        if let (false, Some(t)) = (self.release_scheduled, self.next_write) {
+            self.release_scheduled = true;
+            releases.push((lane_id, t));
+        }
+    }
+}
+
+/// Remove the entry for the uplink with the highest priority from a queue. The earliest entry is
+/// chosen if there are several with the same priority.
+fn pop_by_priority<T, F>(
+    queue: &mut VecDeque<T>,
+    priorities: &HashMap<u64, f32>,
+    lane_id: F,
+) -> Option<T>
+where
+    F: Fn(&T) -> u64,
+{
+    if priorities.is_empty() {
+        return queue.pop_front();
+    }
+    let mut selected: Option<(usize, f32)> = None;
+    for (i, entry) in queue.iter().enumerate() {
+        let prio = priorities.get(&lane_id(entry)).copied().unwrap_or(0.0);
+        if selected.map(|(_, p)| prio > p).unwrap_or(true) {
+            selected = Some((i, prio));
+        }
+    }
+    selected.and_then(|(i, _)| queue.remove(i))
 }
 
 /// Write the body of a response directly into a buffer (used when backpressure relief is not
@@ -386,7 +1104,8 @@ fn write_to_buffer(
         UplinkResponse::Synced(UplinkKind::Value | UplinkKind::Supply) => {
             WriteAction::ValueSynced(false)
         }
-        UplinkResponse::Synced(UplinkKind::Map) => WriteAction::MapSynced(None),
+        UplinkResponse::Synced(UplinkKind::Map) => WriteAction::MapSynced(None, None),
+        UplinkResponse::SyncedAt(version) => WriteAction::MapSynced(None, Some(version)),
         UplinkResponse::Value(body) | UplinkResponse::Supply(body) => {
             buffer.clear();
             buffer.reserve(body.len());
@@ -395,14 +1114,7 @@ fn write_to_buffer(
         }
         UplinkResponse::Map(operation) => {
             //Validating the key is valid UTF8 for consistency with the backpressure relief case.
-            match &operation {
-                MapOperation::Update { key, .. } | MapOperation::Remove { key } => {
-                    if let Err(e) = std::str::from_utf8(key.as_ref()) {
-                        return Err(InvalidKey::new(key.clone().freeze(), e));
-                    }
-                }
-                _ => {}
-            }
+            check_key(&operation)?;
             let mut encoder = MapOperationReconEncoder;
             buffer.clear();
             encoder
@@ -410,6 +1122,17 @@ fn write_to_buffer(
                 .expect("Wiritng map operations is infallible.");
             WriteAction::Event
         }
+        UplinkResponse::MapBatch(operations) => {
+            for operation in &operations {
+                check_key(operation)?;
+            }
+            let mut encoder = MapBatchReconEncoder;
+            buffer.clear();
+            encoder
+                .encode(operations, buffer)
+                .expect("Wiritng map operations is infallible.");
+            WriteAction::Event
+        }
     };
     Ok(action)
 }
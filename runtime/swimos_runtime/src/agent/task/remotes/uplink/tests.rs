@@ -13,16 +13,19 @@
 // limitations under the License.
 
 use std::num::NonZeroUsize;
+use std::time::Duration;
 
 use bytes::{Bytes, BytesMut};
 use swimos_agent_protocol::MapOperation;
-use swimos_api::agent::UplinkKind;
+use swimos_api::agent::{LinkAdvice, SyncVersion, UplinkKind};
+use swimos_messages::protocol::{CorrelationId, LinkParams};
 use swimos_model::Text;
 use swimos_utilities::{
     byte_channel::{byte_channel, ByteReader},
     non_zero_usize,
     trigger::promise,
 };
+use tokio::time::Instant;
 use uuid::Uuid;
 
 use crate::agent::{
@@ -30,7 +33,7 @@ use crate::agent::{
         remotes::{LaneRegistry, UplinkResponse},
         write_fut::WriteTask,
     },
-    DisconnectionReason,
+    DisconnectionReason, LinkAdvisoryConfig, QueueOverflow, UplinkBackpressure,
 };
 
 use super::{RemoteSender, SpecialAction, Uplinks, WriteAction};
@@ -179,7 +182,7 @@ fn push_value_event() {
         buffer,
         action,
     } = uplinks
-        .push(0, UplinkResponse::Value(content), &lane_names)
+        .push(0, UplinkResponse::Value(content), None, &lane_names)
         .expect("Action was invalid.")
         .expect("Expected immediate write.");
 
@@ -194,7 +197,12 @@ fn push_value_synced() {
     let (mut uplinks, _reader, ..) = make_uplinks();
 
     let WriteTask { sender, action, .. } = uplinks
-        .push(0, UplinkResponse::Synced(UplinkKind::Value), &lane_names)
+        .push(
+            0,
+            UplinkResponse::Synced(UplinkKind::Value),
+            None,
+            &lane_names,
+        )
         .expect("Action was invalid.")
         .expect("Expected immediate write.");
 
@@ -208,12 +216,32 @@ fn push_map_synced() {
     let (mut uplinks, _reader, ..) = make_uplinks();
 
     let WriteTask { sender, action, .. } = uplinks
-        .push(0, UplinkResponse::Synced(UplinkKind::Map), &lane_names)
+        .push(
+            0,
+            UplinkResponse::Synced(UplinkKind::Map),
+            None,
+            &lane_names,
+        )
+        .expect("Action was invalid.")
+        .expect("Expected immediate write.");
+
+    assert_eq!(&sender.lane, LANE_NAME);
+    assert!(matches!(action, WriteAction::MapSynced(None, None)));
+}
+
+#[test]
+fn push_map_synced_at_version() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, ..) = make_uplinks();
+
+    let version = SyncVersion::new(5, 87);
+    let WriteTask { sender, action, .. } = uplinks
+        .push(0, UplinkResponse::SyncedAt(version), None, &lane_names)
         .expect("Action was invalid.")
         .expect("Expected immediate write.");
 
     assert_eq!(&sender.lane, LANE_NAME);
-    assert!(matches!(action, WriteAction::MapSynced(None)));
+    assert!(matches!(action, WriteAction::MapSynced(None, Some(v)) if v == version));
 }
 
 const KEY: i32 = 6;
@@ -234,6 +262,7 @@ fn push_good_map_event() {
             UplinkResponse::Map(MapOperation::Remove {
                 key: BytesMut::from(KEY_STR),
             }),
+            None,
             &lane_names,
         )
         .expect("Action was invalid.")
@@ -245,6 +274,77 @@ fn push_good_map_event() {
     assert_eq!(buffer.as_ref(), expected.as_bytes());
 }
 
+#[test]
+fn push_map_event_batch() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, ..) = make_uplinks();
+
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = uplinks
+        .push(
+            0,
+            UplinkResponse::MapBatch(vec![
+                MapOperation::Remove {
+                    key: BytesMut::from(KEY_STR),
+                },
+                MapOperation::Clear,
+            ]),
+            None,
+            &lane_names,
+        )
+        .expect("Action was invalid.")
+        .expect("Expected immediate write.");
+
+    assert_eq!(&sender.lane, LANE_NAME);
+    assert!(matches!(action, WriteAction::Event));
+    let expected = format!("@batch{{@remove(key:{}),@clear}}", KEY);
+    assert_eq!(buffer.as_ref(), expected.as_bytes());
+}
+
+#[test]
+fn queued_map_event_batch() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, _, sender, buffer) = make_uplinks_writing();
+
+    let events = [
+        UplinkResponse::Map(MapOperation::Update {
+            key: BytesMut::from(KEY1_STR),
+            value: BytesMut::from(VAL1),
+        }),
+        UplinkResponse::MapBatch(vec![MapOperation::Update {
+            key: BytesMut::from(KEY2_STR),
+            value: BytesMut::from(VAL2),
+        }]),
+    ];
+
+    for event in events {
+        let result = uplinks
+            .push(0, event, None, &lane_names)
+            .expect("Action was invalid.");
+        assert!(result.is_none());
+    }
+
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+
+    assert!(matches!(action, WriteAction::Event));
+    assert_eq!(
+        buffer.as_ref(),
+        b"@batch{@update(key:78) value1,@update(key:567) value2}"
+    );
+
+    let result = uplinks.replace_and_pop(sender, buffer, &lane_names);
+    assert!(result.is_none());
+}
+
 const BAD_UTF8: &[u8] = &[0xf0, 0x28, 0x8c, 0x28, 0x00, 0x00, 0x00];
 
 #[test]
@@ -257,6 +357,7 @@ fn push_bad_map_event() {
         UplinkResponse::Map(MapOperation::Remove {
             key: BytesMut::from(BAD_UTF8),
         }),
+        None,
         &lane_names,
     );
 
@@ -295,7 +396,7 @@ fn push_multiple_value_events_same_lane() {
 
     for (id, event) in events {
         let result = uplinks
-            .push(id, event, &lane_names)
+            .push(id, event, None, &lane_names)
             .expect("Action was invalid.");
         assert!(result.is_none());
     }
@@ -318,6 +419,141 @@ fn push_multiple_value_events_same_lane() {
         .is_none());
 }
 
+#[test]
+fn push_correlated_value_event() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, ..) = make_uplinks();
+
+    let content = Bytes::from_static(BODY1);
+    let correlation_id = CorrelationId::new(7);
+
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = uplinks
+        .push(
+            0,
+            UplinkResponse::Value(content),
+            Some(correlation_id),
+            &lane_names,
+        )
+        .expect("Action was invalid.")
+        .expect("Expected immediate write.");
+
+    assert_eq!(&sender.lane, LANE_NAME);
+    assert_eq!(sender.correlation_id, Some(correlation_id));
+    assert!(matches!(action, WriteAction::Event));
+    assert_eq!(buffer.as_ref(), BODY1);
+}
+
+#[test]
+fn queued_correlated_value_events() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, _, sender, buffer) = make_uplinks_writing();
+
+    let events = [
+        (
+            UplinkResponse::Value(Bytes::from_static(BODY1)),
+            Some(CorrelationId::new(1)),
+        ),
+        (
+            UplinkResponse::Value(Bytes::from_static(BODY2)),
+            Some(CorrelationId::new(2)),
+        ),
+    ];
+
+    for (event, correlation_id) in events {
+        let result = uplinks
+            .push(0, event, correlation_id, &lane_names)
+            .expect("Action was invalid.");
+        assert!(result.is_none());
+    }
+
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+
+    // The conflated event carries the ID of the command that caused the most recent value.
+    assert_eq!(&sender.lane, LANE_NAME);
+    assert_eq!(sender.correlation_id, Some(CorrelationId::new(2)));
+    assert!(matches!(action, WriteAction::Event));
+    assert_eq!(buffer.as_ref(), BODY2);
+
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+}
+
+#[test]
+fn queued_originated_value_events() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, _, sender, buffer) = make_uplinks_writing();
+
+    let origin = Uuid::from_u128(83);
+
+    let result = uplinks
+        .push_originated(
+            0,
+            UplinkResponse::Value(Bytes::from_static(BODY1)),
+            None,
+            Some(origin),
+            &lane_names,
+        )
+        .expect("Action was invalid.");
+    assert!(result.is_none());
+
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+
+    assert_eq!(&sender.lane, LANE_NAME);
+    assert_eq!(sender.command_origin, Some(origin));
+    assert!(matches!(action, WriteAction::Event));
+    assert_eq!(buffer.as_ref(), BODY1);
+
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+}
+
+#[test]
+fn queue_correlated_special() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, _, sender, buffer) = make_uplinks_writing();
+
+    assert!(uplinks
+        .push_correlated_special(
+            SpecialAction::Linked(0),
+            Some(CorrelationId::new(3)),
+            &lane_names
+        )
+        .is_none());
+    assert!(uplinks
+        .push_special(SpecialAction::Linked(1), &lane_names)
+        .is_none());
+
+    let WriteTask { sender, buffer, .. } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    assert_eq!(&sender.lane, LANE_NAME);
+    assert_eq!(sender.correlation_id, Some(CorrelationId::new(3)));
+
+    let WriteTask { sender, .. } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    assert_eq!(&sender.lane, OTHER_LANE_NAME);
+    assert_eq!(sender.correlation_id, None);
+}
+
 #[test]
 fn delayed_synced_message() {
     let lane_names = lane_names();
@@ -331,7 +567,7 @@ fn delayed_synced_message() {
 
     for (id, event) in events {
         let result = uplinks
-            .push(id, event, &lane_names)
+            .push(id, event, None, &lane_names)
             .expect("Action was invalid.");
         assert!(result.is_none());
     }
@@ -366,7 +602,7 @@ fn push_multiple_value_events_multiple_lanes() {
 
     for (id, event) in events {
         let result = uplinks
-            .push(id, event, &lane_names)
+            .push(id, event, None, &lane_names)
             .expect("Action was invalid.");
         assert!(result.is_none());
     }
@@ -405,6 +641,7 @@ fn special_before_events() {
         .push(
             0,
             UplinkResponse::Value(Bytes::from_static(BODY1)),
+            None,
             &lane_names,
         )
         .expect("Action was invalid.");
@@ -454,6 +691,7 @@ fn unlink_clears_pending() {
         .push(
             0,
             UplinkResponse::Value(Bytes::from_static(BODY1)),
+            None,
             &lane_names,
         )
         .expect("Action was invalid.");
@@ -463,6 +701,7 @@ fn unlink_clears_pending() {
         .push(
             1,
             UplinkResponse::Value(Bytes::from_static(BODY2)),
+            None,
             &lane_names,
         )
         .expect("Action was invalid.");
@@ -526,13 +765,18 @@ fn map_lane_sync_consumes_buffer() {
 
     for op in ops {
         let result = uplinks
-            .push(0, UplinkResponse::Map(op), &lane_names)
+            .push(0, UplinkResponse::Map(op), None, &lane_names)
             .expect("Action was invalid.");
         assert!(result.is_none());
     }
 
     let result = uplinks
-        .push(0, UplinkResponse::Synced(UplinkKind::Map), &lane_names)
+        .push(
+            0,
+            UplinkResponse::Synced(UplinkKind::Map),
+            None,
+            &lane_names,
+        )
         .expect("Action was invalid.");
     assert!(result.is_none());
 
@@ -546,7 +790,7 @@ fn map_lane_sync_consumes_buffer() {
 
     assert_eq!(&sender.lane, LANE_NAME);
     match action {
-        WriteAction::MapSynced(Some(mut queue)) => {
+        WriteAction::MapSynced(Some(mut queue), None) => {
             let first = queue.pop();
             let second = queue.pop();
             let third = queue.pop();
@@ -565,3 +809,663 @@ fn map_lane_sync_consumes_buffer() {
     let result = uplinks.replace_and_pop(sender, buffer, &lane_names);
     assert!(result.is_none());
 }
+
+const CAPACITY: NonZeroUsize = non_zero_usize!(128);
+
+#[test]
+fn queued_value_events_are_not_conflated() {
+    let lane_names = lane_names();
+    let (uplinks, _reader, _, sender, buffer) = make_uplinks_writing();
+    let mut uplinks = uplinks.with_backpressure(UplinkBackpressure::queue(CAPACITY));
+
+    let events = [
+        (0, UplinkResponse::Value(Bytes::from_static(BODY1))),
+        (1, UplinkResponse::Value(Bytes::from_static(BODY2))),
+        (0, UplinkResponse::Value(Bytes::from_static(BODY2))),
+        (0, UplinkResponse::Synced(UplinkKind::Value)),
+    ];
+
+    for (id, event) in events {
+        let result = uplinks
+            .push(id, event, None, &lane_names)
+            .expect("Action was invalid.");
+        assert!(result.is_none());
+    }
+
+    let mut sender = sender;
+    let mut buffer = buffer;
+    for (lane, body) in [
+        (LANE_NAME, BODY1),
+        (OTHER_LANE_NAME, BODY2),
+        (LANE_NAME, BODY2),
+    ] {
+        let task = uplinks
+            .replace_and_pop(sender, buffer, &lane_names)
+            .expect("Expected queued result.");
+        assert_eq!(&task.sender.lane, lane);
+        assert!(matches!(task.action, WriteAction::Event));
+        assert_eq!(task.buffer.as_ref(), body);
+        sender = task.sender;
+        buffer = task.buffer;
+    }
+
+    let WriteTask {
+        sender,
+        action,
+        buffer,
+    } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    assert_eq!(&sender.lane, LANE_NAME);
+    assert!(matches!(action, WriteAction::ValueSynced(false)));
+
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+}
+
+#[test]
+fn queued_map_events_are_not_conflated() {
+    let lane_names = lane_names();
+    let (uplinks, _reader, _, sender, buffer) = make_uplinks_writing();
+    let mut uplinks = uplinks.with_backpressure(UplinkBackpressure::queue(CAPACITY));
+
+    let ops = [
+        MapOperation::Update {
+            key: BytesMut::from(KEY1_STR),
+            value: BytesMut::from(VAL1),
+        },
+        MapOperation::Update {
+            key: BytesMut::from(KEY1_STR),
+            value: BytesMut::from(VAL2),
+        },
+    ];
+
+    for op in ops {
+        let result = uplinks
+            .push(0, UplinkResponse::Map(op), None, &lane_names)
+            .expect("Action was invalid.");
+        assert!(result.is_none());
+    }
+
+    let WriteTask { sender, buffer, .. } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    assert_eq!(buffer.as_ref(), b"@update(key:78) value1");
+
+    let WriteTask { sender, buffer, .. } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    assert_eq!(buffer.as_ref(), b"@update(key:78) value2");
+
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+}
+
+fn expect_event(
+    uplinks: &mut Uplinks,
+    sender: RemoteSender,
+    buffer: BytesMut,
+    lane_names: &LaneRegistry,
+    expected: &[u8],
+) -> (RemoteSender, BytesMut) {
+    let WriteTask {
+        sender,
+        action,
+        buffer,
+    } = uplinks
+        .replace_and_pop(sender, buffer, lane_names)
+        .expect("Expected queued result.");
+    assert!(matches!(action, WriteAction::Event));
+    assert_eq!(buffer.as_ref(), expected);
+    (sender, buffer)
+}
+
+#[test]
+fn full_queue_overflows_into_conflation() {
+    let lane_names = lane_names();
+    let (uplinks, _reader, _, sender, buffer) = make_uplinks_writing();
+    let mut uplinks = uplinks.with_backpressure(UplinkBackpressure::queue(non_zero_usize!(2)));
+
+    for body in [BODY1, BODY2, BODY1, BODY2] {
+        let result = uplinks
+            .push(
+                0,
+                UplinkResponse::Value(Bytes::from_static(body)),
+                None,
+                &lane_names,
+            )
+            .expect("Action was invalid.");
+        assert!(result.is_none());
+    }
+    assert_eq!(uplinks.event_queue.len(), 2);
+
+    let (sender, buffer) = expect_event(&mut uplinks, sender, buffer, &lane_names, BODY1);
+    // The queue has space but the events that overflowed have not been written yet.
+    let result = uplinks
+        .push(
+            0,
+            UplinkResponse::Value(Bytes::from_static(BODY1)),
+            None,
+            &lane_names,
+        )
+        .expect("Action was invalid.");
+    assert!(result.is_none());
+    assert_eq!(uplinks.event_queue.len(), 1);
+
+    let (sender, buffer) = expect_event(&mut uplinks, sender, buffer, &lane_names, BODY2);
+    let (sender, buffer) = expect_event(&mut uplinks, sender, buffer, &lane_names, BODY1);
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+}
+
+#[test]
+fn full_queue_drops_oldest_event() {
+    let lane_names = lane_names();
+    let (uplinks, _reader, _, sender, buffer) = make_uplinks_writing();
+    let mut uplinks = uplinks.with_backpressure(UplinkBackpressure::Queue {
+        capacity: non_zero_usize!(2),
+        overflow: QueueOverflow::DropOldest,
+    });
+
+    for body in [BODY1, BODY2, BODY1] {
+        let result = uplinks
+            .push(
+                0,
+                UplinkResponse::Value(Bytes::from_static(body)),
+                None,
+                &lane_names,
+            )
+            .expect("Action was invalid.");
+        assert!(result.is_none());
+    }
+    assert_eq!(uplinks.event_queue.len(), 2);
+
+    let (sender, buffer) = expect_event(&mut uplinks, sender, buffer, &lane_names, BODY2);
+    let (sender, buffer) = expect_event(&mut uplinks, sender, buffer, &lane_names, BODY1);
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+}
+
+#[test]
+fn unlink_discards_queued_events() {
+    let lane_names = lane_names();
+    let (uplinks, _reader, _, sender, buffer) = make_uplinks_writing();
+    let mut uplinks = uplinks.with_backpressure(UplinkBackpressure::queue(CAPACITY));
+
+    let events = [
+        (0, UplinkResponse::Value(Bytes::from_static(BODY1))),
+        (1, UplinkResponse::Value(Bytes::from_static(BODY2))),
+    ];
+
+    for (id, event) in events {
+        let result = uplinks
+            .push(id, event, None, &lane_names)
+            .expect("Action was invalid.");
+        assert!(result.is_none());
+    }
+
+    assert!(uplinks
+        .push_special(SpecialAction::unlinked(0, Text::new("Gone")), &lane_names)
+        .is_none());
+
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    assert_eq!(&sender.lane, LANE_NAME);
+    assert!(matches!(
+        action,
+        WriteAction::Special(SpecialAction::Unlinked { lane_id: 0, .. })
+    ));
+
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    assert_eq!(&sender.lane, OTHER_LANE_NAME);
+    assert!(matches!(action, WriteAction::Event));
+    assert_eq!(buffer.as_ref(), BODY2);
+
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+}
+
+#[test]
+fn queued_events_for_uplinks_take_turns() {
+    let lane_names = lane_names();
+    let (uplinks, _reader, _, sender, buffer) = make_uplinks_writing();
+    let mut uplinks = uplinks.with_backpressure(UplinkBackpressure::queue(CAPACITY));
+
+    // The first uplink produces many events before the second produces one.
+    for _ in 0..100 {
+        let result = uplinks
+            .push(
+                0,
+                UplinkResponse::Value(Bytes::from_static(BODY1)),
+                None,
+                &lane_names,
+            )
+            .expect("Action was invalid.");
+        assert!(result.is_none());
+    }
+    let result = uplinks
+        .push(
+            1,
+            UplinkResponse::Value(Bytes::from_static(BODY2)),
+            None,
+            &lane_names,
+        )
+        .expect("Action was invalid.");
+    assert!(result.is_none());
+    assert_eq!(uplinks.event_queue.len(), 101);
+
+    let mut sender = sender;
+    let mut buffer = buffer;
+    for (lane, body) in [
+        (LANE_NAME, BODY1),
+        (OTHER_LANE_NAME, BODY2),
+        (LANE_NAME, BODY1),
+    ] {
+        let task = uplinks
+            .replace_and_pop(sender, buffer, &lane_names)
+            .expect("Expected queued result.");
+        assert_eq!(&task.sender.lane, lane);
+        assert_eq!(task.buffer.as_ref(), body);
+        sender = task.sender;
+        buffer = task.buffer;
+    }
+
+    let mut remaining = 0;
+    while let Some(task) = uplinks.replace_and_pop(sender, buffer, &lane_names) {
+        assert_eq!(&task.sender.lane, LANE_NAME);
+        remaining += 1;
+        sender = task.sender;
+        buffer = task.buffer;
+    }
+    assert_eq!(remaining, 98);
+    assert_eq!(uplinks.event_queue.len(), 0);
+}
+
+#[test]
+fn supply_uplink_does_not_starve_other_uplinks() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, _, sender, buffer) = make_uplinks_writing();
+
+    // Supply uplinks are not conflated so a busy supply uplink always has more to write.
+    for _ in 0..100 {
+        let result = uplinks
+            .push(
+                0,
+                UplinkResponse::Supply(Bytes::from_static(BODY1)),
+                None,
+                &lane_names,
+            )
+            .expect("Action was invalid.");
+        assert!(result.is_none());
+    }
+    let result = uplinks
+        .push(
+            1,
+            UplinkResponse::Value(Bytes::from_static(BODY2)),
+            None,
+            &lane_names,
+        )
+        .expect("Action was invalid.");
+    assert!(result.is_none());
+
+    let WriteTask { sender, buffer, .. } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    assert_eq!(&sender.lane, LANE_NAME);
+
+    let WriteTask { sender, buffer, .. } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    assert_eq!(&sender.lane, OTHER_LANE_NAME);
+    assert_eq!(buffer.as_ref(), BODY2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limited_events_are_conflated() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, ..) = make_uplinks();
+    uplinks.set_params(0, LinkParams::new(Some(10.0), None));
+
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = uplinks
+        .push(
+            0,
+            UplinkResponse::Value(Bytes::from_static(BODY1)),
+            None,
+            &lane_names,
+        )
+        .expect("Action was invalid.")
+        .expect("Expected immediate write.");
+    assert!(matches!(action, WriteAction::Event));
+    assert_eq!(buffer.as_ref(), BODY1);
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+
+    let start = Instant::now();
+    for body in [BODY2, BODY1] {
+        let result = uplinks
+            .push(
+                0,
+                UplinkResponse::Value(Bytes::from_static(body)),
+                None,
+                &lane_names,
+            )
+            .expect("Action was invalid.");
+        assert!(result.is_none());
+    }
+
+    // Only a single release should be scheduled, for when the interval has elapsed.
+    let releases = uplinks.take_releases().collect::<Vec<_>>();
+    assert_eq!(releases, vec![(0, start + Duration::from_millis(100))]);
+
+    tokio::time::advance(Duration::from_millis(100)).await;
+
+    let WriteTask { buffer, action, .. } = uplinks
+        .release(0, &lane_names)
+        .expect("Expected a write on release.");
+    assert!(matches!(action, WriteAction::Event));
+    assert_eq!(buffer.as_ref(), BODY1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn synced_bypasses_rate_limit() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, ..) = make_uplinks();
+    uplinks.set_params(0, LinkParams::new(Some(1.0), None));
+
+    let WriteTask { sender, buffer, .. } = uplinks
+        .push(
+            0,
+            UplinkResponse::Value(Bytes::from_static(BODY1)),
+            None,
+            &lane_names,
+        )
+        .expect("Action was invalid.")
+        .expect("Expected immediate write.");
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+
+    let result = uplinks
+        .push(
+            0,
+            UplinkResponse::Value(Bytes::from_static(BODY2)),
+            None,
+            &lane_names,
+        )
+        .expect("Action was invalid.");
+    assert!(result.is_none());
+
+    // The held event is written, along with the synced message, without waiting for the release.
+    let WriteTask { buffer, action, .. } = uplinks
+        .push(
+            0,
+            UplinkResponse::Synced(UplinkKind::Value),
+            None,
+            &lane_names,
+        )
+        .expect("Action was invalid.")
+        .expect("Expected immediate write.");
+    assert!(matches!(action, WriteAction::ValueSynced(true)));
+    assert_eq!(buffer.as_ref(), BODY2);
+}
+
+#[test]
+fn higher_priority_uplinks_written_first() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, _, sender, buffer) = make_uplinks_writing();
+    uplinks.set_params(1, LinkParams::new(None, Some(1.0)));
+
+    let events = [
+        (0, UplinkResponse::Value(Bytes::from_static(BODY1))),
+        (1, UplinkResponse::Value(Bytes::from_static(BODY2))),
+    ];
+
+    for (id, event) in events {
+        let result = uplinks
+            .push(id, event, None, &lane_names)
+            .expect("Action was invalid.");
+        assert!(result.is_none());
+    }
+
+    let WriteTask { sender, buffer, .. } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    assert_eq!(&sender.lane, OTHER_LANE_NAME);
+    assert_eq!(buffer.as_ref(), BODY2);
+
+    let WriteTask { sender, buffer, .. } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    assert_eq!(&sender.lane, LANE_NAME);
+    assert_eq!(buffer.as_ref(), BODY1);
+
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+}
+
+#[test]
+fn paged_map_sync_waits_for_ack() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, ..) = make_uplinks();
+    uplinks.set_params(0, LinkParams::default().with_window(Some(2)));
+
+    let ops = [
+        MapOperation::Clear,
+        MapOperation::Update {
+            key: BytesMut::from(KEY1_STR),
+            value: BytesMut::from(VAL1),
+        },
+        MapOperation::Update {
+            key: BytesMut::from(KEY2_STR),
+            value: BytesMut::from(VAL2),
+        },
+    ];
+
+    let mut writes = vec![];
+    for op in ops {
+        let result = uplinks
+            .push(0, UplinkResponse::Map(op), None, &lane_names)
+            .expect("Action was invalid.");
+        writes.extend(result);
+    }
+    let result = uplinks
+        .push(
+            0,
+            UplinkResponse::Synced(UplinkKind::Map),
+            None,
+            &lane_names,
+        )
+        .expect("Action was invalid.");
+    assert!(result.is_none());
+
+    // The first event is written as soon as it is pushed.
+    assert_eq!(writes.len(), 1);
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = writes.pop().unwrap();
+    assert!(matches!(action, WriteAction::Event));
+    assert_eq!(buffer.as_ref(), b"@clear");
+
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    assert!(matches!(action, WriteAction::Event));
+    assert_eq!(buffer.as_ref(), b"@update(key:78) value1");
+
+    // The window is exhausted so nothing more is written until the remote acknowledges the page.
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = uplinks
+        .ack(0, &lane_names)
+        .expect("Expected write after acknowledgement.");
+    assert!(matches!(action, WriteAction::Event));
+    assert_eq!(buffer.as_ref(), b"@update(key:567) value2");
+
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    match action {
+        WriteAction::MapSynced(Some(mut queue), None) => assert!(queue.pop().is_none()),
+        ow => panic!("Unexpected action {:?}.", ow),
+    }
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+
+    // Once the sync is complete, events are no longer held.
+    let result = uplinks
+        .push(
+            0,
+            UplinkResponse::Map(MapOperation::Clear),
+            None,
+            &lane_names,
+        )
+        .expect("Action was invalid.");
+    assert!(result.is_some());
+}
+
+#[test]
+fn ack_without_window_is_ignored() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, ..) = make_uplinks();
+
+    assert!(uplinks.ack(0, &lane_names).is_none());
+    assert!(uplinks.writer.is_some());
+}
+
+#[test]
+fn advise_congested_low_priority_uplink() {
+    let lane_names = lane_names();
+    let (uplinks, _reader, _, sender, buffer) = make_uplinks_writing();
+    let mut uplinks = uplinks
+        .with_backpressure(UplinkBackpressure::queue(CAPACITY))
+        .with_advisory(LinkAdvisoryConfig {
+            queue_limit: Some(non_zero_usize!(2)),
+            ..Default::default()
+        });
+    uplinks.set_params(1, LinkParams::new(None, Some(1.0)));
+
+    let events = [
+        (0, UplinkResponse::Value(Bytes::from_static(BODY1))),
+        (1, UplinkResponse::Value(Bytes::from_static(BODY2))),
+        (1, UplinkResponse::Value(Bytes::from_static(BODY1))),
+        (0, UplinkResponse::Value(Bytes::from_static(BODY2))),
+        (0, UplinkResponse::Value(Bytes::from_static(BODY1))),
+    ];
+
+    for (id, event) in events {
+        let result = uplinks
+            .push(id, event, None, &lane_names)
+            .expect("Action was invalid.");
+        assert!(result.is_none());
+        uplinks.advise_if_congested(id);
+    }
+
+    // Only the low priority uplink is advised, and only once.
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .expect("Expected queued result.");
+    assert_eq!(&sender.lane, LANE_NAME);
+    assert!(matches!(
+        action,
+        WriteAction::Special(SpecialAction::Advisory {
+            lane_id: 0,
+            advice: LinkAdvice::ReduceRate
+        })
+    ));
+
+    let mut sender = sender;
+    let mut buffer = buffer;
+    for _ in 0..5 {
+        let task = uplinks
+            .replace_and_pop(sender, buffer, &lane_names)
+            .expect("Expected queued result.");
+        assert!(matches!(task.action, WriteAction::Event));
+        sender = task.sender;
+        buffer = task.buffer;
+    }
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+    assert!(uplinks.advised.is_empty());
+}
+
+#[test]
+fn advise_unlink_low_priority_uplink() {
+    let lane_names = lane_names();
+    let (mut uplinks, _reader, ..) = make_uplinks();
+    uplinks.set_params(1, LinkParams::new(None, Some(1.0)));
+
+    let WriteTask {
+        sender,
+        buffer,
+        action,
+    } = uplinks
+        .advise_unlink(0, &lane_names)
+        .expect("Expected immediate write.");
+    assert_eq!(&sender.lane, LANE_NAME);
+    assert!(matches!(
+        action,
+        WriteAction::Special(SpecialAction::Advisory {
+            lane_id: 0,
+            advice: LinkAdvice::Unlink
+        })
+    ));
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+
+    assert!(uplinks.advise_unlink(0, &lane_names).is_none());
+    assert!(uplinks.advise_unlink(1, &lane_names).is_none());
+
+    // Unlinking clears the advice.
+    let WriteTask { sender, buffer, .. } = uplinks
+        .push_special(SpecialAction::unlinked(0, Text::new("Gone")), &lane_names)
+        .expect("Expected immediate write.");
+    assert!(uplinks
+        .replace_and_pop(sender, buffer, &lane_names)
+        .is_none());
+    assert!(uplinks.advise_unlink(0, &lane_names).is_some());
+}
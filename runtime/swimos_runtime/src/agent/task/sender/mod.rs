@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::num::NonZeroUsize;
+
 use bytes::Bytes;
 use futures::SinkExt;
 use swimos_agent_protocol::{
@@ -19,7 +21,8 @@ use swimos_agent_protocol::{
     peeling::extract_header,
     LaneRequest, MapMessage,
 };
-use swimos_api::agent::UplinkKind;
+use swimos_api::agent::{KeyRange, SyncVersion, UplinkKind};
+use swimos_messages::protocol::CorrelationId;
 use swimos_recon::parser::MessageExtractError;
 use swimos_utilities::byte_channel::ByteWriter;
 use thiserror::Error;
@@ -55,47 +58,126 @@ enum LaneSenderWriter {
 
 pub struct LaneSender {
     writer: LaneSenderWriter,
+    track_origin: bool,
     reporter: Option<UplinkReporter>,
 }
 
 impl LaneSender {
-    pub fn new(tx: ByteWriter, kind: UplinkKind, reporter: Option<UplinkReporter>) -> Self {
+    pub fn new(
+        tx: ByteWriter,
+        kind: UplinkKind,
+        track_origin: bool,
+        reporter: Option<UplinkReporter>,
+        max_fragment_size: Option<NonZeroUsize>,
+    ) -> Self {
         let writer = match kind {
             UplinkKind::Value | UplinkKind::Supply => LaneSenderWriter::Value {
-                sender: FramedWrite::new(tx, RawValueLaneRequestEncoder::default()),
+                sender: FramedWrite::new(
+                    tx,
+                    max_fragment_size
+                        .map(RawValueLaneRequestEncoder::with_max_fragment_size)
+                        .unwrap_or_default(),
+                ),
             },
             UplinkKind::Map => LaneSenderWriter::Map {
-                sender: FramedWrite::new(tx, RawMapLaneRequestEncoder::default()),
+                sender: FramedWrite::new(
+                    tx,
+                    max_fragment_size
+                        .map(RawMapLaneRequestEncoder::with_max_fragment_size)
+                        .unwrap_or_default(),
+                ),
             },
         };
-        LaneSender { writer, reporter }
+        LaneSender {
+            writer,
+            track_origin,
+            reporter,
+        }
+    }
+
+    /// Request that the lane sync with a remote.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the remote.
+    /// * `since` - The version of the state of the lane that the remote already holds. This is only
+    ///   passed on to map lanes.
+    pub async fn start_sync(
+        &mut self,
+        id: Uuid,
+        since: Option<SyncVersion>,
+    ) -> Result<(), std::io::Error> {
+        match &mut self.writer {
+            LaneSenderWriter::Value { sender } => {
+                let req: LaneRequest<Bytes> = LaneRequest::Sync(id);
+                sender.send(req).await
+            }
+            LaneSenderWriter::Map { sender } => {
+                let req: LaneRequest<MapMessage<Bytes, Bytes>> = match since {
+                    Some(since) => LaneRequest::SyncSince(id, since),
+                    None => LaneRequest::Sync(id),
+                };
+                sender.send(req).await
+            }
+        }
     }
 
-    pub async fn start_sync(&mut self, id: Uuid) -> Result<(), std::io::Error> {
+    /// Request that the lane sync with a remote, restricted to the entries with keys in a range.
+    /// The range is only meaningful for map lanes; value-like lanes will perform a full sync.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the remote.
+    /// * `range` - The Recon representations of the bounds of the range of keys.
+    pub async fn start_sync_range(
+        &mut self,
+        id: Uuid,
+        range: KeyRange<Bytes>,
+    ) -> Result<(), std::io::Error> {
         match &mut self.writer {
             LaneSenderWriter::Value { sender } => {
                 let req: LaneRequest<Bytes> = LaneRequest::Sync(id);
                 sender.send(req).await
             }
             LaneSenderWriter::Map { sender } => {
-                let req: LaneRequest<MapMessage<Bytes, Bytes>> = LaneRequest::Sync(id);
+                let req: LaneRequest<MapMessage<Bytes, Bytes>> = LaneRequest::SyncRange(id, range);
                 sender.send(req).await
             }
         }
     }
 
-    pub async fn feed_frame(&mut self, data: Bytes) -> Result<(), LaneSendError> {
-        let LaneSender { writer, reporter } = self;
+    /// Forward a command to the lane.
+    ///
+    /// # Arguments
+    /// * `origin` - The ID of the remote that sent the command. This is only passed on to the lane if
+    ///   it requested that commands be tagged with their origin.
+    /// * `correlation_id` - The correlation ID of the envelope that contained the command. This is
+    ///   passed on to the lane so that it can be echoed on the events that the command causes.
+    /// * `data` - The body of the command.
+    pub async fn feed_frame(
+        &mut self,
+        origin: Uuid,
+        correlation_id: Option<CorrelationId>,
+        data: Bytes,
+    ) -> Result<(), LaneSendError> {
+        let LaneSender {
+            writer,
+            track_origin,
+            reporter,
+        } = self;
         if let Some(reporter) = reporter {
             reporter.count_commands(1);
         }
+        let origin = if *track_origin { Some(origin) } else { None };
         match writer {
             LaneSenderWriter::Value { sender } => {
-                sender.feed(LaneRequest::Command(data)).await?;
+                sender
+                    .feed(command_request(origin, correlation_id, data))
+                    .await?;
             }
             LaneSenderWriter::Map { sender } => {
                 let message = extract_header(&data)?;
-                sender.send(LaneRequest::Command(message)).await?;
+                sender
+                    .send(command_request(origin, correlation_id, message))
+                    .await?;
             }
         }
         Ok(())
@@ -109,6 +191,18 @@ impl LaneSender {
     }
 }
 
+fn command_request<T>(
+    origin: Option<Uuid>,
+    correlation_id: Option<CorrelationId>,
+    body: T,
+) -> LaneRequest<T> {
+    match (origin, correlation_id) {
+        (origin, Some(id)) => LaneRequest::CorrelatedCommand(id.get(), origin, body),
+        (Some(origin), None) => LaneRequest::CommandFrom(origin, body),
+        (None, None) => LaneRequest::Command(body),
+    }
+}
+
 async fn flush_sender_val<T>(sender: &mut FramedWrite<ByteWriter, T>) -> Result<(), T::Error>
 where
     T: Encoder<LaneRequest<Bytes>>,
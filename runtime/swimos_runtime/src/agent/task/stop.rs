@@ -0,0 +1,96 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use swimos_api::agent::StopReason;
+use tokio::sync::watch;
+use tracing::warn;
+
+/// Create a linked [`StopNotifier`] and [`StopSignal`] pair.
+pub fn stop_notification() -> (StopNotifier, StopSignal) {
+    let (tx, rx) = watch::channel(None);
+    let listening = Arc::new(AtomicBool::new(false));
+    (
+        StopNotifier {
+            tx,
+            listening: listening.clone(),
+        },
+        StopSignal { rx, listening },
+    )
+}
+
+/// Held by the agent runtime task to notify the agent of the reason that it is stopping.
+#[derive(Debug)]
+pub struct StopNotifier {
+    tx: watch::Sender<Option<StopReason>>,
+    listening: Arc<AtomicBool>,
+}
+
+/// Held by the agent to wait for the runtime to notify it that it is stopping. The agent task is
+/// considered to have completed when all copies of the signal have been dropped.
+#[derive(Debug, Clone)]
+pub struct StopSignal {
+    rx: watch::Receiver<Option<StopReason>>,
+    listening: Arc<AtomicBool>,
+}
+
+impl StopSignal {
+    /// Wait for the runtime to indicate that the agent is stopping. If the runtime stops without
+    /// giving a reason, this will never complete.
+    pub fn wait(&self) -> impl Future<Output = StopReason> + Send + 'static {
+        self.listening.store(true, Ordering::Release);
+        let mut rx = self.rx.clone();
+        async move {
+            loop {
+                if let Some(reason) = *rx.borrow_and_update() {
+                    break reason;
+                }
+                if rx.changed().await.is_err() {
+                    break futures::future::pending().await;
+                }
+            }
+        }
+    }
+}
+
+impl StopNotifier {
+    /// Notify the agent that it is stopping. If the agent is listening for the signal, this will
+    /// wait for the agent task to complete before returning.
+    ///
+    /// # Arguments
+    /// * `reason` - The reason that the agent is stopping.
+    /// * `grace_period` - The maximum time to wait for the agent task to complete.
+    pub async fn notify(self, reason: StopReason, grace_period: Duration) {
+        let StopNotifier { tx, listening } = self;
+        tx.send_replace(Some(reason));
+        if listening.load(Ordering::Acquire)
+            && tokio::time::timeout(grace_period, tx.closed())
+                .await
+                .is_err()
+        {
+            warn!(
+                reason = %reason,
+                "The agent did not complete within the shutdown timeout after being notified that it is stopping."
+            );
+        }
+    }
+}
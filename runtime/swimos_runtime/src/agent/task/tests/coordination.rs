@@ -171,8 +171,10 @@ impl FakeAgent {
                 name,
                 kind,
                 transient: false,
+                track_origin: false,
                 io: io_rx,
                 reporter: None,
+                limits: Default::default(),
             }));
         }
         let mut create_stream = UnboundedReceiverStream::new(create_rx).take_until(stopping);
@@ -187,12 +189,12 @@ impl FakeAgent {
                                         LaneRequest::InitComplete => {
                                             panic!("Unexpected InitComplete");
                                         }
-                                        LaneRequest::Command(v) => {
+                                        LaneRequest::Command(v) | LaneRequest::CommandFrom(_, v) | LaneRequest::CorrelatedCommand(_, _, v) => {
                                             assert!(event_tx.send(Event::ValueCommand { name: name.clone(), n: v }).is_ok());
                                             *value = v;
                                             sender.event(v).await;
                                         },
-                                        LaneRequest::Sync(id) => {
+                                        LaneRequest::Sync(id) | LaneRequest::SyncSince(id, _) | LaneRequest::SyncRange(id, _) => {
                                             sender.synced(id, *value).await;
                                         }
                                     }
@@ -204,7 +206,7 @@ impl FakeAgent {
                                         LaneRequest::InitComplete => {
                                             panic!("Unexpected InitComplete.");
                                         }
-                                        LaneRequest::Command(msg) => {
+                                        LaneRequest::Command(msg) | LaneRequest::CommandFrom(_, msg) | LaneRequest::CorrelatedCommand(_, _, msg) => {
                                             assert!(event_tx.send(Event::MapCommand { name: name.clone(), cmd: msg.clone() }).is_ok());
                                             match msg {
                                                 MapMessage::Update { key, value } => {
@@ -240,7 +242,7 @@ impl FakeAgent {
                                                 },
                                             }
                                         },
-                                        LaneRequest::Sync(id) => {
+                                        LaneRequest::Sync(id) | LaneRequest::SyncSince(id, _) | LaneRequest::SyncRange(id, _) => {
                                             for (k, v) in map {
                                                 sender.sync_event(id, k.clone(), *v).await;
                                             }
@@ -292,7 +294,7 @@ impl FakeAgent {
                                 panic!("Unexpected supply uplink.");
                             }
                         }
-                        lanes.push(LaneReader::new(LaneEndpoint { name, kind: uplink_kind, transient: false, io: io_rx, reporter: None }));
+                        lanes.push(LaneReader::new(LaneEndpoint { name, kind: uplink_kind, transient: false, track_origin: false, io: io_rx, reporter: None, limits: Default::default() }));
                     } else {
                         break;
                     }
@@ -415,30 +417,38 @@ where
         Text::new(VAL_LANE),
         UplinkKind::Value,
         false,
+        false,
         (tx_in_val, rx_out_val),
         None,
+        Default::default(),
     ));
     runtime_endpoints.push(LaneEndpoint::new(
         Text::new(MAP_LANE),
         UplinkKind::Map,
         false,
+        false,
         (tx_in_map, rx_out_map),
         None,
+        Default::default(),
     ));
 
     agent_endpoints.push(LaneEndpoint::new(
         Text::new(VAL_LANE),
         UplinkKind::Value,
         false,
+        false,
         (tx_out_val, rx_in_val),
         None,
+        Default::default(),
     ));
     agent_endpoints.push(LaneEndpoint::new(
         Text::new(MAP_LANE),
         UplinkKind::Map,
         false,
+        false,
         (tx_out_map, rx_in_map),
         None,
+        Default::default(),
     ));
 
     let (tx_http, rx_http) = mpsc::channel(QUEUE_SIZE.get());
@@ -450,6 +460,7 @@ where
         lane_endpoints: runtime_endpoints,
         http_lane_endpoints: http_endpoints,
         store_endpoints: vec![],
+        lane_aliases: vec![],
     };
     let init = InitialEndpoints::new(
         None,
@@ -533,6 +544,7 @@ impl AgentAttachmentRequest {
             io,
             completion,
             on_attached: None,
+            remote_addr: None,
         }
     }
 }
@@ -30,7 +30,11 @@ use swimos_agent_protocol::{
     encoding::store::{MapStoreResponseEncoder, ValueStoreResponseEncoder},
     LaneRequest, LaneResponse, MapLaneResponse, MapMessage, MapOperation, StoreResponse,
 };
-use swimos_api::{address::RelativeAddress, agent::UplinkKind, error::FrameIoError};
+use swimos_api::{
+    address::RelativeAddress,
+    agent::{NodeStopped, UplinkKind},
+    error::FrameIoError,
+};
 use swimos_messages::protocol::{
     Notification, RawRequestMessageEncoder, RawResponseMessageDecoder, RequestMessage,
     ResponseMessage,
@@ -49,8 +53,10 @@ use tokio_util::codec::{FramedRead, FramedWrite};
 use uuid::Uuid;
 
 use crate::agent::{
-    reporting::{UplinkReportReader, UplinkSnapshot},
-    AgentRuntimeConfig, DisconnectionReason, UplinkReporterRegistration,
+    recording::EnvelopeRecording,
+    reporting::{DeprecationReportReader, PruneReportReader, UplinkReportReader, UplinkSnapshot},
+    AgentRuntimeConfig, DisconnectionReason, StateMigrationPolicy, StoreFailureAction,
+    UplinkBackpressure, UplinkReporterRegistration,
 };
 
 use super::{LaneEndpoint, RwCoordinationMessage};
@@ -58,6 +64,7 @@ use super::{LaneEndpoint, RwCoordinationMessage};
 mod coordination;
 mod http;
 mod read;
+mod stop;
 mod write;
 
 const QUEUE_SIZE: NonZeroUsize = non_zero_usize!(8);
@@ -85,6 +92,10 @@ fn make_prune_config(
         ad_hoc_output_retry: RetryStrategy::none(),
         ad_hoc_buffer_size: non_zero_usize!(4096),
         lane_http_request_channel_size: non_zero_usize!(8),
+        store_failure: StoreFailureAction::Fail,
+        state_migration: StateMigrationPolicy::Migrate,
+        uplink_backpressure: UplinkBackpressure::Conflate,
+        link_advisory: Default::default(),
     }
 }
 
@@ -455,6 +466,7 @@ impl RemoteReceiver {
                 origin,
                 path,
                 envelope,
+                ..
             })) => {
                 assert_eq!(origin, self.expected_agent);
                 assert_eq!(
@@ -579,14 +591,25 @@ impl RemoteReceiver {
             expected_node,
             completion_rx,
         } = self;
+        let expected_reason =
+            expected_reason.unwrap_or(DisconnectionReason::AgentStoppedExternally);
+        let expected_notice = NodeStopped {
+            resume: expected_reason == DisconnectionReason::AgentTimedOut,
+        };
         let results = inner.collect::<Vec<_>>().await;
         for result in results {
             match result {
                 Ok(ResponseMessage {
                     origin,
                     path,
-                    envelope: Notification::Unlinked(_),
+                    envelope: Notification::Unlinked(body),
+                    ..
                 }) => {
+                    let body = body.expect("Unlinked body missing.");
+                    let body = std::str::from_utf8(body.as_ref()).expect("Invalid UTF8.");
+                    let notice = parse_recognize::<NodeStopped>(body, false)
+                        .expect("Invalid unlinked body.");
+                    assert_eq!(notice, expected_notice);
                     assert_eq!(origin, expected_agent);
                     assert_eq!(&path.node, &expected_node);
                     let lane = &path.lane;
@@ -602,10 +625,7 @@ impl RemoteReceiver {
         }
         let reason = completion_rx.await.unwrap_or(DisconnectionReason::Failed);
 
-        assert_eq!(
-            reason,
-            expected_reason.unwrap_or(DisconnectionReason::AgentStoppedExternally)
-        );
+        assert_eq!(reason, expected_reason);
     }
 }
 
@@ -666,6 +686,9 @@ struct ReportReaders {
     _reg_rx: mpsc::Receiver<UplinkReporterRegistration>,
     aggregate: UplinkReportReader,
     lanes: HashMap<&'static str, UplinkReportReader>,
+    prune: PruneReportReader,
+    deprecation: DeprecationReportReader,
+    recording: EnvelopeRecording,
 }
 
 struct Snapshots {
@@ -31,13 +31,15 @@ use tokio_stream::wrappers::ReceiverStream;
 use uuid::Uuid;
 
 use crate::agent::{
-    reporting::{UplinkReporter, UplinkSnapshot},
+    recording::{CaptureDirection, EnvelopeRecording},
+    reporting::{DeprecationEvent, UplinkReporter, UplinkSnapshot},
     task::{
         read_task,
         tests::{RemoteSender, BUFFER_SIZE, DEFAULT_TIMEOUT, INACTIVE_TEST_TIMEOUT},
         timeout_coord::{self, VoteResult},
-        LaneEndpoint, ReadTaskMessage, RwCoordinationMessage, WriteTaskMessage,
+        LaneAlias, LaneEndpoint, ReadTaskMessage, RwCoordinationMessage, WriteTaskMessage,
     },
+    NodeReporting,
 };
 
 use super::{
@@ -133,19 +135,30 @@ where
     let (stop_tx, stop_rx) = trigger::trigger();
     let config = make_config(inactive_timeout);
 
-    let (agg_rep, val_rep, map_rep, reporting) = if with_reporting {
+    let (node_rep, val_rep, map_rep, reporting) = if with_reporting {
         let agg_rep = UplinkReporter::default();
         let val_rep = UplinkReporter::default();
         let map_rep = UplinkReporter::default();
-        let (_, reg_rx) = mpsc::channel(QUEUE_SIZE.get());
+        let (reg_tx, reg_rx) = mpsc::channel(QUEUE_SIZE.get());
+        let aggregate = agg_rep.reader();
+        let node_rep = NodeReporting::new(AGENT_ID, agg_rep, reg_tx)
+            .with_recording(EnvelopeRecording::default());
         let reporting = ReportReaders {
             _reg_rx: reg_rx,
-            aggregate: agg_rep.reader(),
+            aggregate,
             lanes: [(VAL_LANE, val_rep.reader()), (MAP_LANE, map_rep.reader())]
                 .into_iter()
                 .collect(),
+            prune: node_rep.prune_reader(),
+            deprecation: node_rep.deprecation_reader(),
+            recording: node_rep.recording().expect("Recording not allowed."),
         };
-        (Some(agg_rep), Some(val_rep), Some(map_rep), Some(reporting))
+        (
+            Some(node_rep),
+            Some(val_rep),
+            Some(map_rep),
+            Some(reporting),
+        )
     } else {
         (None, None, None, None)
     };
@@ -155,15 +168,19 @@ where
             name: Text::new(VAL_LANE),
             kind: UplinkKind::Value,
             transient: false,
+            track_origin: false,
             io: byte_channel(BUFFER_SIZE),
             reporter: val_rep,
+            limits: Default::default(),
         },
         LaneEndpoint {
             name: Text::new(MAP_LANE),
             kind: UplinkKind::Map,
             transient: false,
+            track_origin: false,
             io: byte_channel(BUFFER_SIZE),
             reporter: map_rep,
+            limits: Default::default(),
         },
     ];
 
@@ -177,14 +194,17 @@ where
 
     let (vote1, vote2, vote3, vote_rx) = timeout_coord::agent_timeout_coordinator();
 
+    let aliases = vec![LaneAlias::new(Text::new(OLD_VAL_LANE), Text::new(VAL_LANE))];
+
     let read = read_task(
         config,
         endpoints_tx,
+        aliases,
         reg_rx,
         coord_tx,
         vote1,
         stop_rx,
-        agg_rep,
+        node_rep,
     );
 
     let context = TestContext {
@@ -224,9 +244,11 @@ async fn shutdown_no_remotes() {
     assert!(events.is_empty());
 }
 
+const AGENT_ID: Uuid = Uuid::from_u128(1);
 const RID: Uuid = Uuid::from_u128(0);
 const RID2: Uuid = Uuid::from_u128(1);
 const NODE: &str = "node";
+const OLD_VAL_LANE: &str = "old_value_lane";
 
 async fn attach_remote_with(rid: Uuid, reg_tx: &mpsc::Sender<ReadTaskMessage>) -> RemoteSender {
     let (tx, rx) = byte_channel(BUFFER_SIZE);
@@ -259,7 +281,7 @@ async fn attach_remote_and_link() {
         sender.link(VAL_LANE).await;
         let event = event_rx.recv().await;
         match event {
-            Some(Event::Coord(RwCoordinationMessage::Link { origin, lane })) => {
+            Some(Event::Coord(RwCoordinationMessage::Link { origin, lane, .. })) => {
                 assert_eq!(origin, RID);
                 assert_eq!(lane, VAL_LANE);
             }
@@ -359,6 +381,112 @@ async fn attach_remote_and_map_command() {
     assert_eq!(events.len(), 1);
 }
 
+#[tokio::test]
+async fn attach_remote_and_link_by_alias() {
+    let (events, deprecated) = run_test_case(DEFAULT_TIMEOUT, true, |context| async move {
+        let TestContext {
+            stop_sender,
+            reg_tx,
+            write_voter: _write_voter,
+            http_voter: _http_voter,
+            vote_rx: _vote_rx,
+            mut event_rx,
+            readers,
+        } = context;
+        let readers = readers.expect("Reporting not enabled.");
+        let mut sender = attach_remote(&reg_tx).await;
+        sender.link(OLD_VAL_LANE).await;
+        sender.link(OLD_VAL_LANE).await;
+        for _ in 0..2 {
+            match event_rx.recv().await {
+                Some(Event::Coord(RwCoordinationMessage::Link { origin, lane, .. })) => {
+                    assert_eq!(origin, RID);
+                    assert_eq!(lane, OLD_VAL_LANE);
+                }
+                ow => panic!("Unexpected event: {:?}", ow),
+            }
+        }
+        stop_sender.trigger();
+        readers.deprecation.take_events()
+    })
+    .await;
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        deprecated,
+        Some(vec![DeprecationEvent {
+            remote_id: RID,
+            alias: Text::new(OLD_VAL_LANE),
+            lane: Text::new(VAL_LANE),
+        }])
+    );
+}
+
+#[tokio::test]
+async fn attach_remote_and_sync_by_alias() {
+    let (events, _) = run_test_case(DEFAULT_TIMEOUT, false, |context| async move {
+        let TestContext {
+            stop_sender,
+            reg_tx,
+            write_voter: _write_voter,
+            http_voter: _http_voter,
+            vote_rx: _vote_rx,
+            mut event_rx,
+            ..
+        } = context;
+        let mut sender = attach_remote(&reg_tx).await;
+        sender.sync(OLD_VAL_LANE).await;
+        let mut named = false;
+        let mut synced = false;
+        //The lane and the write task are read independently so the order is not fixed.
+        for _ in 0..2 {
+            match event_rx.recv().await {
+                Some(Event::Coord(RwCoordinationMessage::LinkName { origin, lane })) => {
+                    assert_eq!(origin, RID);
+                    assert_eq!(lane, OLD_VAL_LANE);
+                    named = true;
+                }
+                Some(Event::Sync { name, id }) => {
+                    assert_eq!(id, RID);
+                    assert_eq!(name, VAL_LANE);
+                    synced = true;
+                }
+                ow => panic!("Unexpected event: {:?}", ow),
+            }
+        }
+        assert!(named && synced);
+        stop_sender.trigger();
+    })
+    .await;
+    assert_eq!(events.len(), 2);
+}
+
+#[tokio::test]
+async fn attach_remote_and_value_command_by_alias() {
+    let (events, _) = run_test_case(DEFAULT_TIMEOUT, false, |context| async move {
+        let TestContext {
+            stop_sender,
+            reg_tx,
+            write_voter: _write_voter,
+            http_voter: _http_voter,
+            vote_rx: _vote_rx,
+            mut event_rx,
+            ..
+        } = context;
+        let mut sender = attach_remote(&reg_tx).await;
+        sender.value_command(OLD_VAL_LANE, 77).await;
+        match event_rx.recv().await {
+            Some(Event::ValueCommand { name, n }) => {
+                assert_eq!(name, VAL_LANE);
+                assert_eq!(n, 77);
+            }
+            ow => panic!("Unexpected event: {:?}", ow),
+        }
+        stop_sender.trigger();
+    })
+    .await;
+    assert_eq!(events.len(), 1);
+}
+
 #[tokio::test]
 async fn votes_to_stop() {
     let (events, _stop_sender) =
@@ -429,7 +557,7 @@ async fn attach_two_remotes_and_link() {
         let event2 = event_rx.recv().await;
         let seen;
         match event1 {
-            Some(Event::Coord(RwCoordinationMessage::Link { origin, lane })) => {
+            Some(Event::Coord(RwCoordinationMessage::Link { origin, lane, .. })) => {
                 assert!(origin == RID || origin == RID2);
                 seen = origin;
                 assert_eq!(lane, VAL_LANE);
@@ -437,7 +565,7 @@ async fn attach_two_remotes_and_link() {
             ow => panic!("Unexpected event: {:?}", ow),
         }
         match event2 {
-            Some(Event::Coord(RwCoordinationMessage::Link { origin, lane })) => {
+            Some(Event::Coord(RwCoordinationMessage::Link { origin, lane, .. })) => {
                 assert!(origin == RID || origin == RID2);
                 assert_ne!(origin, seen);
                 assert_eq!(lane, VAL_LANE);
@@ -595,3 +723,49 @@ async fn reports_command_counts() {
     .await;
     assert_eq!(events.len(), 2);
 }
+
+#[tokio::test]
+async fn records_envelopes_when_enabled() {
+    let (events, _) = run_test_case(DEFAULT_TIMEOUT, true, |context| async move {
+        let TestContext {
+            stop_sender,
+            reg_tx,
+            write_voter: _write_voter,
+            http_voter: _http_voter,
+            vote_rx: _vote_rx,
+            mut event_rx,
+            readers,
+        } = context;
+
+        let recording = readers
+            .as_ref()
+            .map(|readers| readers.recording.clone())
+            .expect("Report readers not initialized.");
+
+        let mut sender = attach_remote(&reg_tx).await;
+
+        sender.value_command(VAL_LANE, 1).await;
+        assert!(matches!(
+            event_rx.recv().await,
+            Some(Event::ValueCommand { n: 1, .. })
+        ));
+
+        recording.set_enabled(true);
+        sender.value_command(VAL_LANE, 2).await;
+        assert!(matches!(
+            event_rx.recv().await,
+            Some(Event::ValueCommand { n: 2, .. })
+        ));
+
+        let records = recording.envelopes();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.direction, CaptureDirection::Incoming);
+        assert_eq!(record.remote_id, RID);
+        assert_eq!(record.envelope, "@command(node:node,lane:value_lane) 2");
+
+        stop_sender.trigger();
+    })
+    .await;
+    assert_eq!(events.len(), 2);
+}
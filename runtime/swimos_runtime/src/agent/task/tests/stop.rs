@@ -0,0 +1,74 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use futures::future::join;
+use swimos_api::agent::StopReason;
+
+use crate::agent::task::stop_notification;
+
+use super::TEST_TIMEOUT;
+
+const GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+#[tokio::test]
+async fn notify_without_listener() {
+    let (notifier, _signal) = stop_notification();
+    // As the agent is not waiting on the signal, this should not wait for it to be dropped.
+    tokio::time::timeout(
+        TEST_TIMEOUT,
+        notifier.notify(StopReason::PlaneShutdown, GRACE_PERIOD),
+    )
+    .await
+    .expect("Test timed out.");
+}
+
+#[tokio::test]
+async fn notify_listening_agent() {
+    let (notifier, signal) = stop_notification();
+    let wait = signal.wait();
+
+    let agent = async move {
+        let reason = wait.await;
+        drop(signal);
+        reason
+    };
+
+    let (_, reason) = tokio::time::timeout(
+        TEST_TIMEOUT,
+        join(
+            notifier.notify(StopReason::InactivityTimeout, GRACE_PERIOD),
+            agent,
+        ),
+    )
+    .await
+    .expect("Test timed out.");
+    assert_eq!(reason, StopReason::InactivityTimeout);
+}
+
+#[tokio::test]
+async fn notify_times_out_if_agent_does_not_stop() {
+    let (notifier, signal) = stop_notification();
+    let wait = signal.wait();
+
+    tokio::time::timeout(
+        TEST_TIMEOUT,
+        notifier.notify(StopReason::ExternalStop, Duration::from_millis(50)),
+    )
+    .await
+    .expect("Test timed out.");
+
+    assert_eq!(wait.await, StopReason::ExternalStop);
+}
@@ -12,12 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashMap, time::Duration};
+use std::{collections::HashMap, pin::pin, sync::Arc, time::Duration};
 
 use bytes::BytesMut;
 use futures::{
-    future::{join, join3},
-    Future, StreamExt,
+    future::{self, join, join3, BoxFuture},
+    stream, Future, FutureExt, StreamExt,
 };
 use swimos_api::{
     agent::{StoreKind, UplinkKind},
@@ -34,21 +34,27 @@ use tokio_stream::wrappers::{ReceiverStream, UnboundedReceiverStream};
 use uuid::Uuid;
 
 use crate::agent::{
-    reporting::{UplinkReporter, UplinkSnapshot},
+    prune::{KeepRecentlyLinked, PrunePolicy},
+    recording::EnvelopeRecording,
+    reporting::{PruneEvent, PruneSnapshot, UplinkReporter, UplinkSnapshot},
     store::{AgentPersistence, StorePersistence},
     task::{
         fake_store::FakeStore,
+        receiver::ResponseReceiver,
+        remotes::RemoteSender,
         tests::RemoteReceiver,
         timeout_coord::{self, VoteResult},
-        write_task, LaneEndpoint, ReadTaskMessage, RwCoordinationMessage, StoreEndpoint,
-        WriteTaskConfiguration, WriteTaskEndpoints, WriteTaskMessage,
+        write_fut::WriteResult,
+        write_task, LaneAlias, LaneEndpoint, ReadTaskMessage, RwCoordinationMessage, StoreEndpoint,
+        WriteTaskConfiguration, WriteTaskEndpoints, WriteTaskEvent, WriteTaskEvents,
+        WriteTaskMessage, LANE_STARVATION_LIMIT, REMOTE_WRITE_BUDGET,
     },
-    DisconnectionReason, NodeReporting,
+    AgentRuntimeConfig, DisconnectionReason, NodeReporting,
 };
 
 use super::{
-    make_config, Instruction, Instructions, MapLaneSender, MapStoreSender, ReportReaders,
-    Snapshots, ValueLikeLaneSender, ValueStoreSender, BUFFER_SIZE, DEFAULT_TIMEOUT,
+    make_config, make_prune_config, Instruction, Instructions, MapLaneSender, MapStoreSender,
+    ReportReaders, Snapshots, ValueLikeLaneSender, ValueStoreSender, BUFFER_SIZE, DEFAULT_TIMEOUT,
     INACTIVE_TEST_TIMEOUT, MAP_LANE, MAP_STORE, QUEUE_SIZE, SUPPLY_LANE, TEST_TIMEOUT, VAL_LANE,
     VAL_STORE,
 };
@@ -209,6 +215,7 @@ struct TestContext {
 
 const AGENT_ID: Uuid = Uuid::from_u128(1);
 const NODE: &str = "/node";
+const OLD_VAL_LANE: &str = "old_value_lane";
 
 use std::fmt::Debug;
 
@@ -233,6 +240,20 @@ where
     run_test_case_with_store(inactive_timeout, true, StoreDisabled, false, test_case).await
 }
 
+async fn run_test_case_with_pruning<F, Fut>(
+    prune_remote_delay: Duration,
+    prune_policy: Option<Arc<dyn PrunePolicy>>,
+    test_case: F,
+) -> Fut::Output
+where
+    F: FnOnce(TestContext) -> Fut,
+    Fut: Future + Send,
+    Fut::Output: Debug,
+{
+    let config = make_prune_config(DEFAULT_TIMEOUT, prune_remote_delay);
+    run_test_case_with_config(config, true, StoreDisabled, false, prune_policy, test_case).await
+}
+
 async fn run_test_case_with_store<F, Fut, Store>(
     inactive_timeout: Duration,
     with_reporting: bool,
@@ -246,8 +267,33 @@ where
     Fut::Output: Debug,
     Store: AgentPersistence + Clone + Send + Sync,
 {
-    let (stop_tx, stop_rx) = trigger::trigger();
     let config = make_config(inactive_timeout);
+    run_test_case_with_config(
+        config,
+        with_reporting,
+        store,
+        register_stores,
+        None,
+        test_case,
+    )
+    .await
+}
+
+async fn run_test_case_with_config<F, Fut, Store>(
+    config: AgentRuntimeConfig,
+    with_reporting: bool,
+    store: Store,
+    register_stores: bool,
+    prune_policy: Option<Arc<dyn PrunePolicy>>,
+    test_case: F,
+) -> Fut::Output
+where
+    F: FnOnce(TestContext) -> Fut,
+    Fut: Future + Send,
+    Fut::Output: Debug,
+    Store: AgentPersistence + Clone + Send + Sync,
+{
+    let (stop_tx, stop_rx) = trigger::trigger();
 
     let (val_rep, map_rep, sup_rep, node_rep, reporting) = if with_reporting {
         let val_rep = UplinkReporter::default();
@@ -257,9 +303,13 @@ where
 
         let (reg_tx, reg_rx) = mpsc::channel(QUEUE_SIZE.get());
 
+        let aggregate = agg_rep.reader();
+        let node_rep = NodeReporting::new(AGENT_ID, agg_rep, reg_tx)
+            .with_recording(EnvelopeRecording::default());
+
         let reporting = ReportReaders {
             _reg_rx: reg_rx,
-            aggregate: agg_rep.reader(),
+            aggregate,
             lanes: [
                 (VAL_LANE, val_rep.reader()),
                 (MAP_LANE, map_rep.reader()),
@@ -267,9 +317,11 @@ where
             ]
             .into_iter()
             .collect(),
+            prune: node_rep.prune_reader(),
+            deprecation: node_rep.deprecation_reader(),
+            recording: node_rep.recording().expect("Recording not allowed."),
         };
 
-        let node_rep = NodeReporting::new(AGENT_ID, agg_rep, reg_tx);
         (
             Some(val_rep),
             Some(map_rep),
@@ -286,22 +338,28 @@ where
             name: Text::new(VAL_LANE),
             kind: UplinkKind::Value,
             transient: false,
+            track_origin: false,
             io: byte_channel(BUFFER_SIZE),
             reporter: val_rep,
+            limits: Default::default(),
         },
         LaneEndpoint {
             name: Text::new(SUPPLY_LANE),
             kind: UplinkKind::Supply,
             transient: true,
+            track_origin: false,
             io: byte_channel(BUFFER_SIZE),
             reporter: sup_rep,
+            limits: Default::default(),
         },
         LaneEndpoint {
             name: Text::new(MAP_LANE),
             kind: UplinkKind::Map,
             transient: false,
+            track_origin: false,
             io: byte_channel(BUFFER_SIZE),
             reporter: map_rep,
+            limits: Default::default(),
         },
     ];
 
@@ -332,12 +390,16 @@ where
     let (messages_tx, messages_rx) = mpsc::channel(QUEUE_SIZE.get());
 
     let fake_agent = FakeAgent::new(endpoints_tx, fake_stores, stop_rx.clone(), instr_rx);
-    let write_config = WriteTaskConfiguration::new(AGENT_ID, Text::new(NODE), config);
+    let write_config = WriteTaskConfiguration::new(AGENT_ID, Text::new(NODE), config)
+        .with_prune_policy(prune_policy);
 
     let (read_tx, read_rx) = mpsc::channel(QUEUE_SIZE.get());
     let write = write_task(
         write_config,
-        WriteTaskEndpoints::new(endpoints_rx, store_endpoints),
+        WriteTaskEndpoints::new(endpoints_rx, store_endpoints).with_aliases(vec![LaneAlias::new(
+            Text::new(OLD_VAL_LANE),
+            Text::new(VAL_LANE),
+        )]),
         ReceiverStream::new(messages_rx).take_until(stop_rx),
         read_tx,
         vote1,
@@ -392,6 +454,7 @@ async fn attach_remote(
     assert!(messages_tx
         .send(WriteTaskMessage::Remote {
             id: remote_id,
+            addr: None,
             writer: tx,
             completion: completion_tx,
             on_attached: None,
@@ -411,6 +474,7 @@ async fn attach_remote_and_wait(
     assert!(messages_tx
         .send(WriteTaskMessage::Remote {
             id: remote_id,
+            addr: None,
             writer: tx,
             completion: completion_tx,
             on_attached: Some(attach_tx),
@@ -425,6 +489,8 @@ async fn link_remote(remote_id: Uuid, lane: &str, messages_tx: &mpsc::Sender<Wri
     let msg = RwCoordinationMessage::Link {
         origin: remote_id,
         lane: Text::new(lane),
+        params: Default::default(),
+        correlation_id: None,
     };
     assert!(messages_tx.send(WriteTaskMessage::Coord(msg)).await.is_ok());
 }
@@ -433,6 +499,7 @@ async fn unlink_remote(remote_id: Uuid, lane: &str, messages_tx: &mpsc::Sender<W
     let msg = RwCoordinationMessage::Unlink {
         origin: remote_id,
         lane: Text::new(lane),
+        correlation_id: None,
     };
     assert!(messages_tx.send(WriteTaskMessage::Coord(msg)).await.is_ok());
 }
@@ -440,6 +507,8 @@ async fn unlink_remote(remote_id: Uuid, lane: &str, messages_tx: &mpsc::Sender<W
 const RID1: Uuid = Uuid::from_u128(1);
 const RID2: Uuid = Uuid::from_u128(2);
 
+const PRUNE_DELAY: Duration = Duration::from_millis(20);
+
 #[tokio::test]
 async fn attach_remote_no_link() {
     run_test_case(DEFAULT_TIMEOUT, |context| async move {
@@ -510,6 +579,32 @@ async fn receive_value_message_when_linked_remote() {
     .await;
 }
 
+#[tokio::test]
+async fn receive_value_message_when_linked_by_alias() {
+    run_test_case(DEFAULT_TIMEOUT, |context| async move {
+        let TestContext {
+            stop_sender,
+            messages_tx,
+            read_voter: _read_voter,
+            http_voter: _http_voter,
+            vote_rx: _vote_rx,
+            instr_tx,
+            ..
+        } = context;
+
+        let mut reader = attach_remote(RID1, &messages_tx).await;
+        link_remote(RID1, OLD_VAL_LANE, &messages_tx).await;
+        reader.expect_linked(OLD_VAL_LANE).await;
+
+        instr_tx.value_event(VAL_LANE, 747);
+        reader.expect_value_like_event(OLD_VAL_LANE, 747).await;
+
+        stop_sender.trigger();
+        reader.expect_clean_shutdown(vec![OLD_VAL_LANE], None).await;
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn receive_supply_message_when_linked_remote() {
     run_test_case(DEFAULT_TIMEOUT, |context| async move {
@@ -1511,3 +1606,201 @@ async fn map_store_events_persisted() {
     expected.insert(b"a".to_vec(), b"22".to_vec());
     assert_eq!(store_map, expected);
 }
+
+#[tokio::test]
+async fn prune_idle_remote() {
+    run_test_case_with_pruning(PRUNE_DELAY, None, |context| async move {
+        let TestContext {
+            stop_sender: _stop_sender,
+            messages_tx,
+            read_voter: _read_voter,
+            http_voter: _http_voter,
+            vote_rx: _vote_rx,
+            instr_tx: _instr_tx,
+            reporters,
+            read_rx: _read_rx,
+        } = context;
+
+        let mut reader = attach_remote(RID1, &messages_tx).await;
+        link_remote(RID1, VAL_LANE, &messages_tx).await;
+        reader.expect_linked(VAL_LANE).await;
+
+        let start = Instant::now();
+        unlink_remote(RID1, VAL_LANE, &messages_tx).await;
+        reader.expect_unlinked(VAL_LANE).await;
+
+        reader
+            .expect_clean_shutdown(vec![], Some(DisconnectionReason::RemoteTimedOut))
+            .await;
+        assert!(start.elapsed() >= PRUNE_DELAY);
+
+        let prune = &reporters
+            .as_ref()
+            .expect("Reporting not initialized.")
+            .prune;
+        assert_eq!(
+            prune.snapshot(),
+            Some(PruneSnapshot {
+                pruned_count: 1,
+                deferred_count: 0
+            })
+        );
+        let events = prune.take_events().expect("Reporting dropped.");
+        assert!(matches!(
+            events.as_slice(),
+            [PruneEvent { remote_id, idle_for }] if *remote_id == RID1 && *idle_for >= PRUNE_DELAY
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn prune_policy_defers_pruning() {
+    let window = PRUNE_DELAY * 5;
+    let policy: Arc<dyn PrunePolicy> = Arc::new(KeepRecentlyLinked::new(window));
+    run_test_case_with_pruning(PRUNE_DELAY, Some(policy), |context| async move {
+        let TestContext {
+            stop_sender: _stop_sender,
+            messages_tx,
+            read_voter: _read_voter,
+            http_voter: _http_voter,
+            vote_rx: _vote_rx,
+            instr_tx: _instr_tx,
+            reporters,
+            read_rx: _read_rx,
+        } = context;
+
+        let mut reader = attach_remote(RID1, &messages_tx).await;
+        let start = Instant::now();
+        link_remote(RID1, VAL_LANE, &messages_tx).await;
+        reader.expect_linked(VAL_LANE).await;
+
+        unlink_remote(RID1, VAL_LANE, &messages_tx).await;
+        reader.expect_unlinked(VAL_LANE).await;
+
+        reader
+            .expect_clean_shutdown(vec![], Some(DisconnectionReason::RemoteTimedOut))
+            .await;
+        assert!(start.elapsed() >= window);
+
+        let prune = &reporters
+            .as_ref()
+            .expect("Reporting not initialized.")
+            .prune;
+        let PruneSnapshot {
+            pruned_count,
+            deferred_count,
+        } = prune.snapshot().expect("Reporting dropped.");
+        assert_eq!(pruned_count, 1);
+        assert!(deferred_count >= 1);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn lane_events_not_starved_by_messages() {
+    let timeout_delay = pin!(tokio::time::sleep(DEFAULT_TIMEOUT));
+    let prune_delay = pin!(tokio::time::sleep(Duration::default()));
+
+    // The message stream always has another message available and takes priority over the lanes.
+    let messages = stream::repeat_with(|| WriteTaskMessage::Stop);
+    let mut events = WriteTaskEvents::<_, BoxFuture<'static, WriteResult>, ()>::new(
+        DEFAULT_TIMEOUT,
+        DEFAULT_TIMEOUT,
+        timeout_delay,
+        prune_delay,
+        messages,
+    );
+
+    let (tx, rx) = byte_channel(BUFFER_SIZE);
+    events.add_receiver(ResponseReceiver::value_like_lane(0, None, rx, None));
+    let mut sender = ValueLikeLaneSender::new(tx);
+    sender.event(1).await;
+
+    for _ in 0..LANE_STARVATION_LIMIT {
+        assert!(matches!(
+            events.select_next().await,
+            WriteTaskEvent::Message(WriteTaskMessage::Stop)
+        ));
+    }
+    match events.select_next().await {
+        WriteTaskEvent::Event(response) => {
+            assert!(response.is_lane());
+            assert_eq!(response.item_id, 0);
+        }
+        ow => panic!("Unexpected event: {:?}", ow),
+    }
+    assert!(matches!(
+        events.select_next().await,
+        WriteTaskEvent::Message(WriteTaskMessage::Stop)
+    ));
+}
+
+fn completed_write(sender: RemoteSender) -> BoxFuture<'static, WriteResult> {
+    future::ready((sender, BytesMut::new(), Ok(()))).boxed()
+}
+
+#[tokio::test]
+async fn flooding_remote_limited_to_write_budget() {
+    let timeout_delay = pin!(tokio::time::sleep(DEFAULT_TIMEOUT));
+    let prune_delay = pin!(tokio::time::sleep(Duration::default()));
+
+    let mut events = WriteTaskEvents::<_, BoxFuture<'static, WriteResult>, ()>::new(
+        DEFAULT_TIMEOUT,
+        DEFAULT_TIMEOUT,
+        timeout_delay,
+        prune_delay,
+        stream::pending(),
+    );
+
+    let flooding_id = Uuid::from_u128(1);
+    let other_id = Uuid::from_u128(2);
+    let (flooding_tx, _flooding_rx) = byte_channel(BUFFER_SIZE);
+    let (other_tx, _other_rx) = byte_channel(BUFFER_SIZE);
+    let flooding = RemoteSender::new(flooding_tx, Uuid::nil(), flooding_id, Text::new(NODE));
+    let mut other = Some(RemoteSender::new(
+        other_tx,
+        Uuid::nil(),
+        other_id,
+        Text::new(NODE),
+    ));
+
+    // The flooding remote always has another write ready when its previous write completes.
+    events.schedule_write(completed_write(flooding));
+
+    let (tx, rx) = byte_channel(BUFFER_SIZE);
+    events.add_receiver(ResponseReceiver::value_like_lane(0, None, rx, None));
+    let mut sender = ValueLikeLaneSender::new(tx);
+    sender.event(1).await;
+
+    let mut flooding_writes = 0;
+    loop {
+        match events.select_next().await {
+            WriteTaskEvent::WriteDone((writer, _, Ok(_))) if writer.remote_id() == flooding_id => {
+                flooding_writes += 1;
+                assert!(flooding_writes <= REMOTE_WRITE_BUDGET);
+                events.schedule_next_write(flooding_id, completed_write(writer));
+            }
+            WriteTaskEvent::WriteDone((writer, _, Ok(_))) if writer.remote_id() == other_id => {
+                break;
+            }
+            WriteTaskEvent::Event(response) => {
+                assert_eq!(response.item_id, 0);
+                // The event from the lane results in a write to the other remote.
+                let writer = other.take().expect("Event received twice.");
+                events.schedule_write(completed_write(writer));
+                flooding_writes = 0;
+            }
+            ow => panic!("Unexpected event: {:?}", ow),
+        }
+    }
+    assert!(other.is_none());
+
+    // The flooding remote resumes once the other remote has been served.
+    match events.select_next().await {
+        WriteTaskEvent::WriteDone((writer, _, Ok(_))) => {
+            assert_eq!(writer.remote_id(), flooding_id);
+        }
+        ow => panic!("Unexpected event: {:?}", ow),
+    }
+}
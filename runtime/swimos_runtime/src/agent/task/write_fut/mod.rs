@@ -12,9 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+
 use bytes::BytesMut;
+use swimos_api::agent::{LinkAdvice, SyncVersion};
 use swimos_messages::protocol::Notification;
 use swimos_model::Text;
+use swimos_recon::print_recon_compact;
 
 use crate::backpressure::{BackpressureStrategy, MapBackpressure};
 
@@ -35,6 +39,7 @@ pub enum SpecialAction {
     Linked(u64),
     Unlinked { lane_id: u64, message: Text },
     LaneNotFound { lane_name: Text },
+    Advisory { lane_id: u64, advice: LinkAdvice },
 }
 
 impl SpecialAction {
@@ -46,11 +51,30 @@ impl SpecialAction {
         SpecialAction::LaneNotFound { lane_name }
     }
 
-    pub fn lane_name<'a>(&'a self, registry: &'a LaneRegistry) -> &'a str {
+    pub fn advisory(lane_id: u64, advice: LinkAdvice) -> Self {
+        SpecialAction::Advisory { lane_id, advice }
+    }
+
+    /// Get the name of the lane to which the action refers.
+    /// # Arguments
+    /// * `registry` - Registry mapping lane IDs to lane names.
+    /// * `link_names` - Aliases by which the remote addressed lanes (these take precedence).
+    pub fn lane_name<'a>(
+        &'a self,
+        registry: &'a LaneRegistry,
+        link_names: &'a HashMap<u64, Text>,
+    ) -> &'a str {
+        let name_for = |id: &u64| {
+            link_names
+                .get(id)
+                .map(Text::as_str)
+                .or_else(|| registry.name_for(*id))
+                .unwrap_or_default()
+        };
         match self {
-            SpecialAction::Linked(id) => registry.name_for(*id).unwrap_or_default(),
-            SpecialAction::Unlinked { lane_id, .. } => {
-                registry.name_for(*lane_id).unwrap_or_default()
+            SpecialAction::Linked(id) => name_for(id),
+            SpecialAction::Unlinked { lane_id, .. } | SpecialAction::Advisory { lane_id, .. } => {
+                name_for(lane_id)
             }
             SpecialAction::LaneNotFound { lane_name } => lane_name.as_str(),
         }
@@ -64,8 +88,9 @@ pub enum WriteAction {
     Event,
     // A value lane synced message.
     ValueSynced(bool),
-    // A queue of map lan events, to be followed by a synced message (the contents of the buffer are irrelevant).
-    MapSynced(Option<Box<MapBackpressure>>),
+    // A queue of map lan events, to be followed by a synced message, optionally reporting the version of the
+    // lane (the contents of the buffer are irrelevant).
+    MapSynced(Option<Box<MapBackpressure>>, Option<SyncVersion>),
     // A special action (the body will be stored in the associated buffer, where appropriate).
     Special(SpecialAction),
 }
@@ -118,7 +143,7 @@ async fn perform_write(
             }
             writer.send_notification(Notification::Synced).await?;
         }
-        WriteAction::MapSynced(maybe_queue) => {
+        WriteAction::MapSynced(maybe_queue, maybe_version) => {
             if let Some(mut queue) = maybe_queue {
                 while queue.has_data() {
                     queue.prepare_write(buffer);
@@ -127,7 +152,14 @@ async fn perform_write(
                         .await?;
                 }
             }
-            writer.send_notification(Notification::Synced).await?;
+            if let Some(version) = maybe_version {
+                let body = format!("{}", print_recon_compact(&version));
+                writer
+                    .send_notification(Notification::SyncedWith(body.as_bytes()))
+                    .await?;
+            } else {
+                writer.send_notification(Notification::Synced).await?;
+            }
         }
         WriteAction::Special(SpecialAction::Linked(_)) => {
             writer.send_notification(Notification::Linked).await?;
@@ -142,6 +174,12 @@ async fn perform_write(
                 .send_notification(Notification::Unlinked(Some(LANE_NOT_FOUND_BODY)))
                 .await?;
         }
+        WriteAction::Special(SpecialAction::Advisory { advice, .. }) => {
+            let body = format!("{}", print_recon_compact(&advice));
+            writer
+                .send_notification(Notification::Advisory(body.as_bytes()))
+                .await?;
+        }
     }
 
     Ok(())
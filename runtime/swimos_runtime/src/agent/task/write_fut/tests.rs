@@ -17,7 +17,10 @@ use std::num::NonZeroUsize;
 use bytes::{BufMut, BytesMut};
 use futures::StreamExt;
 use swimos_agent_protocol::MapOperation;
-use swimos_api::address::RelativeAddress;
+use swimos_api::{
+    address::RelativeAddress,
+    agent::{LinkAdvice, SyncVersion},
+};
 use swimos_messages::protocol::{Notification, RawResponseMessageDecoder, ResponseMessage};
 use swimos_model::Text;
 use swimos_utilities::{
@@ -73,6 +76,7 @@ async fn write_event() {
             origin,
             path,
             envelope: Notification::Event(body),
+            ..
         })) => {
             assert_eq!(origin, ADDR);
             assert_eq!(path, make_path());
@@ -94,6 +98,7 @@ async fn write_event_with_synced() {
             origin,
             path,
             envelope: Notification::Event(body),
+            ..
         })) => {
             assert_eq!(origin, ADDR);
             assert_eq!(path, make_path());
@@ -108,6 +113,7 @@ async fn write_event_with_synced() {
             origin,
             path,
             envelope: Notification::Synced,
+            ..
         })) => {
             assert_eq!(origin, ADDR);
             assert_eq!(path, make_path());
@@ -138,7 +144,7 @@ async fn write_map_queue_with_synced() {
         .is_ok());
 
     let (task, mut reader) = make_task(
-        WriteAction::MapSynced(Some(Box::new(map_backpressure))),
+        WriteAction::MapSynced(Some(Box::new(map_backpressure)), None),
         None,
     );
 
@@ -157,6 +163,7 @@ async fn write_map_queue_with_synced() {
                 origin,
                 path,
                 envelope: Notification::Event(body),
+                ..
             })) => {
                 assert_eq!(origin, ADDR);
                 assert_eq!(path, make_path());
@@ -173,9 +180,36 @@ async fn write_map_queue_with_synced() {
             origin,
             path,
             envelope: Notification::Synced,
+            ..
+        })) => {
+            assert_eq!(origin, ADDR);
+            assert_eq!(path, make_path());
+        }
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+}
+
+#[tokio::test]
+async fn write_map_synced_with_version() {
+    let (task, mut reader) = make_task(
+        WriteAction::MapSynced(None, Some(SyncVersion::new(2, 30))),
+        None,
+    );
+
+    assert!(task.into_future().await.2.is_ok());
+
+    let result = reader.next().await;
+    match result {
+        Some(Ok(ResponseMessage {
+            origin,
+            path,
+            envelope: Notification::SyncedWith(body),
+            ..
         })) => {
             assert_eq!(origin, ADDR);
             assert_eq!(path, make_path());
+            let body_str = std::str::from_utf8(body.as_ref()).unwrap();
+            assert_eq!(body_str, "@version{epoch:2,version:30}");
         }
         ow => panic!("Unexpected result: {:?}", ow),
     }
@@ -196,6 +230,7 @@ async fn write_linked() {
             origin,
             path,
             envelope: Notification::Linked,
+            ..
         })) => {
             assert_eq!(origin, ADDR);
             assert_eq!(path, make_path());
@@ -221,6 +256,7 @@ async fn write_unlinked() {
             origin,
             path,
             envelope: Notification::Unlinked(Some(message)),
+            ..
         })) => {
             assert_eq!(origin, ADDR);
             assert_eq!(path, make_path());
@@ -246,6 +282,7 @@ async fn write_lane_not_found() {
             origin,
             path,
             envelope: Notification::Unlinked(Some(message)),
+            ..
         })) => {
             assert_eq!(origin, ADDR);
             assert_eq!(path, make_path());
@@ -254,3 +291,29 @@ async fn write_lane_not_found() {
         ow => panic!("Unexpected result: {:?}", ow),
     }
 }
+
+#[tokio::test]
+async fn write_advisory() {
+    let (task, mut reader) = make_task(
+        WriteAction::Special(SpecialAction::advisory(0, LinkAdvice::ReduceRate)),
+        Some(BODY_BYTES),
+    );
+
+    assert!(task.into_future().await.2.is_ok());
+
+    let result = reader.next().await;
+    match result {
+        Some(Ok(ResponseMessage {
+            origin,
+            path,
+            envelope: Notification::Advisory(body),
+            ..
+        })) => {
+            assert_eq!(origin, ADDR);
+            assert_eq!(path, make_path());
+            let body_str = std::str::from_utf8(body.as_ref()).unwrap();
+            assert_eq!(body_str, "@reduceRate");
+        }
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::{
+    collections::VecDeque,
     convert::Infallible,
     fmt::{Display, Formatter},
 };
@@ -28,7 +29,7 @@ mod key;
 mod map_queue;
 pub mod recon;
 
-use recon::MapOperationReconEncoder;
+use recon::{MapBatchReconEncoder, MapOperationReconEncoder};
 
 type RawMapOperation = MapOperation<Bytes, BytesMut>;
 type RawMapOperationMut = MapOperation<BytesMut, BytesMut>;
@@ -104,10 +105,12 @@ impl SupplyBackpressure {
 }
 
 /// Backpressure implementation for map-like uplinks/downlinks. Map updates are pushed into a
-/// [`MapOperationQueue`] that relieves backpressure on a per-key basis.
+/// [`MapOperationQueue`] that relieves backpressure on a per-key basis. Batches of updates, that
+/// must be applied together, are not conflated and are encoded as soon as they are pushed.
 #[derive(Debug, Default)]
 pub struct MapBackpressure {
     queue: MapOperationQueue,
+    batches: VecDeque<BytesMut>,
     encoder: MapOperationReconEncoder,
 }
 
@@ -116,6 +119,33 @@ impl MapBackpressure {
         self.queue.push(operation)
     }
 
+    /// Push a batch of operations. Any operations that are already queued are written at the
+    /// start of the batch so that the order of the operations is preserved.
+    pub fn push_batch(&mut self, operations: Vec<RawMapOperationMut>) -> Result<(), InvalidKey> {
+        let MapBackpressure { queue, batches, .. } = self;
+        for operation in &operations {
+            check_key(operation)?;
+        }
+        let mut batch = vec![];
+        while let Some(operation) = queue.pop() {
+            batch.push(operation);
+        }
+        batch.extend(operations.into_iter().map(|operation| match operation {
+            MapOperation::Update { key, value } => MapOperation::Update {
+                key: key.freeze(),
+                value,
+            },
+            MapOperation::Remove { key } => MapOperation::Remove { key: key.freeze() },
+            MapOperation::Clear => MapOperation::Clear,
+        }));
+        let mut body = BytesMut::new();
+        MapBatchReconEncoder
+            .encode(batch, &mut body)
+            .expect("Encoding should be unfallible.");
+        batches.push_back(body);
+        Ok(())
+    }
+
     pub fn pop(&mut self) -> Option<RawMapOperation> {
         self.queue.pop()
     }
@@ -192,7 +222,7 @@ impl BackpressureStrategy for MapBackpressure {
     }
 
     fn has_data(&self) -> bool {
-        !self.queue.is_empty()
+        !self.queue.is_empty() || !self.batches.is_empty()
     }
 
     fn write_direct(&mut self, op: Self::Operation, buffer: &mut BytesMut) {
@@ -206,7 +236,9 @@ impl BackpressureStrategy for MapBackpressure {
 
     fn prepare_write(&mut self, buffer: &mut BytesMut) {
         buffer.clear();
-        if let Some(head) = self.pop() {
+        if let Some(batch) = self.batches.pop_front() {
+            buffer.put(batch);
+        } else if let Some(head) = self.pop() {
             // Encoding the operation cannot fail.
             self.encoder
                 .encode(head, buffer)
@@ -215,6 +247,19 @@ impl BackpressureStrategy for MapBackpressure {
     }
 }
 
+/// Check that the key of a map operation (if it has one) is valid UTF8.
+pub fn check_key(operation: &RawMapOperationMut) -> Result<(), InvalidKey> {
+    match operation {
+        MapOperation::Update { key, .. } | MapOperation::Remove { key } => {
+            if let Err(e) = std::str::from_utf8(key.as_ref()) {
+                return Err(InvalidKey::new(key.clone().freeze(), e));
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 /// Error indicating that the key for a map message contained invalid UTF8.
 #[derive(Debug, Error)]
 pub struct InvalidKey {
@@ -23,6 +23,9 @@ const CLEAR: &[u8] = b"@clear";
 const UPDATE: &[u8] = b"@update(key:) ";
 const REMOVE: &[u8] = b"@remove(key:)";
 const KEY_OFFSET: usize = 12;
+const BATCH_START: &[u8] = b"@batch{";
+const BATCH_END: &[u8] = b"}";
+const BATCH_SEP: &[u8] = b",";
 
 #[derive(Debug, Default)]
 pub struct MapOperationReconEncoder;
@@ -53,3 +56,29 @@ impl<K: Buf, V: Buf> Encoder<MapOperation<K, V>> for MapOperationReconEncoder {
         Ok(())
     }
 }
+
+/// Encodes a sequence of map operations, that must be applied together, as a single Recon record of
+/// the form `@batch{op1,op2,...}` where each item is encoded as by [`MapOperationReconEncoder`].
+#[derive(Debug, Default)]
+pub struct MapBatchReconEncoder;
+
+impl<K: Buf, V: Buf> Encoder<Vec<MapOperation<K, V>>> for MapBatchReconEncoder {
+    type Error = std::io::Error;
+
+    fn encode(
+        &mut self,
+        item: Vec<MapOperation<K, V>>,
+        dst: &mut BytesMut,
+    ) -> Result<(), Self::Error> {
+        dst.reserve(BATCH_START.len() + BATCH_END.len());
+        dst.put(BATCH_START);
+        for (i, op) in item.into_iter().enumerate() {
+            if i > 0 {
+                dst.put(BATCH_SEP);
+            }
+            MapOperationReconEncoder.encode(op, dst)?;
+        }
+        dst.put(BATCH_END);
+        Ok(())
+    }
+}
@@ -14,7 +14,7 @@
 
 use crate::backpressure::{RawMapOperation, RawMapOperationMut};
 
-use super::MapOperationReconEncoder;
+use super::{MapBatchReconEncoder, MapOperationReconEncoder};
 use bytes::{Bytes, BytesMut};
 use swimos_agent_protocol::MapOperation;
 use tokio_util::codec::Encoder;
@@ -54,3 +54,26 @@ fn recon_encode_update() {
 
     assert_eq!(buffer.as_ref(), b"@update(key:5) data");
 }
+
+#[test]
+fn recon_encode_batch() {
+    let ops: Vec<RawMapOperation> = vec![
+        MapOperation::Clear,
+        MapOperation::Update {
+            key: Bytes::from_static(b"5"),
+            value: BytesMut::from(b"data".as_slice()),
+        },
+        MapOperation::Remove {
+            key: Bytes::from_static(b"6"),
+        },
+    ];
+    let mut encoder = MapBatchReconEncoder;
+    let mut buffer = BytesMut::new();
+
+    assert!(encoder.encode(ops, &mut buffer).is_ok());
+
+    assert_eq!(
+        buffer.as_ref(),
+        b"@batch{@clear,@update(key:5) data,@remove(key:6)}"
+    );
+}
@@ -13,9 +13,9 @@
 // limitations under the License.
 
 use bytes::{Bytes, BytesMut};
-use swimos_agent_protocol::DownlinkOperation;
+use swimos_agent_protocol::{DownlinkOperation, MapOperation};
 
-use crate::backpressure::{BackpressureStrategy, SupplyBackpressure};
+use crate::backpressure::{BackpressureStrategy, MapBackpressure, SupplyBackpressure};
 
 use super::ValueBackpressure;
 
@@ -162,3 +162,43 @@ fn supply_backpressure_push_multiple() {
     assert_eq!(buffer.as_ref(), &[7, 8, 9]);
     assert!(!BackpressureStrategy::has_data(&backpressure));
 }
+
+#[test]
+fn map_backpressure_push_batch() {
+    let mut backpressure = MapBackpressure::default();
+
+    assert!(backpressure
+        .push(MapOperation::Remove {
+            key: BytesMut::from("1"),
+        })
+        .is_ok());
+    assert!(backpressure
+        .push_batch(vec![
+            MapOperation::Update {
+                key: BytesMut::from("2"),
+                value: BytesMut::from("a"),
+            },
+            MapOperation::Update {
+                key: BytesMut::from("3"),
+                value: BytesMut::from("b"),
+            },
+        ])
+        .is_ok());
+    assert!(backpressure
+        .push(MapOperation::Update {
+            key: BytesMut::from("2"),
+            value: BytesMut::from("c"),
+        })
+        .is_ok());
+
+    let mut buffer = BytesMut::new();
+    backpressure.prepare_write(&mut buffer);
+    assert_eq!(
+        buffer.as_ref(),
+        b"@batch{@remove(key:1),@update(key:2) a,@update(key:3) b}"
+    );
+
+    backpressure.prepare_write(&mut buffer);
+    assert_eq!(buffer.as_ref(), b"@update(key:2) c");
+    assert!(!BackpressureStrategy::has_data(&backpressure));
+}
@@ -0,0 +1,146 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use thiserror::Error;
+
+/// The shortest permitted value for any timeout in the runtime configuration.
+pub const MIN_TIMEOUT: Duration = Duration::from_millis(1);
+/// The smallest permitted size for any byte buffer in the runtime configuration.
+pub const MIN_BUFFER_SIZE: usize = 64;
+
+/// Error type for configuration parameters that are outside of their permitted range. The
+/// parameter is identified by the name of the configuration type and the name of the field.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    /// A timeout was shorter than the permitted minimum.
+    #[error("The timeout `{parameter}` was {value:?} but must be at least {min:?}.")]
+    TimeoutTooShort {
+        parameter: &'static str,
+        value: Duration,
+        min: Duration,
+    },
+    /// A buffer size was less than the permitted minimum.
+    #[error("The buffer size `{parameter}` was {value} bytes but must be at least {min} bytes.")]
+    BufferTooSmall {
+        parameter: &'static str,
+        value: usize,
+        min: usize,
+    },
+}
+
+/// Check that a timeout is no shorter than [`MIN_TIMEOUT`].
+///
+/// # Arguments
+/// * `parameter` - The name of the parameter, used in the error.
+/// * `value` - The value of the timeout.
+pub fn check_timeout(parameter: &'static str, value: Duration) -> Result<(), ConfigError> {
+    if value < MIN_TIMEOUT {
+        Err(ConfigError::TimeoutTooShort {
+            parameter,
+            value,
+            min: MIN_TIMEOUT,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Check that a buffer size is no smaller than [`MIN_BUFFER_SIZE`].
+///
+/// # Arguments
+/// * `parameter` - The name of the parameter, used in the error.
+/// * `value` - The size of the buffer.
+pub fn check_buffer_size(parameter: &'static str, value: usize) -> Result<(), ConfigError> {
+    if value < MIN_BUFFER_SIZE {
+        Err(ConfigError::BufferTooSmall {
+            parameter,
+            value,
+            min: MIN_BUFFER_SIZE,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use swimos_utilities::non_zero_usize;
+
+    use super::{ConfigError, MIN_BUFFER_SIZE, MIN_TIMEOUT};
+    use crate::{agent::AgentRuntimeConfig, downlink::DownlinkRuntimeConfig};
+
+    #[test]
+    fn default_configs_are_valid() {
+        assert!(AgentRuntimeConfig::default().validate().is_ok());
+        assert!(DownlinkRuntimeConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn agent_runtime_config_builder() {
+        let config = AgentRuntimeConfig::builder()
+            .inactive_timeout(Duration::from_secs(5))
+            .ad_hoc_buffer_size(non_zero_usize!(1024))
+            .build()
+            .expect("Valid config rejected.");
+        assert_eq!(config.inactive_timeout, Duration::from_secs(5));
+        assert_eq!(config.ad_hoc_buffer_size, non_zero_usize!(1024));
+    }
+
+    #[test]
+    fn agent_runtime_config_zero_timeout() {
+        let result = AgentRuntimeConfig::builder()
+            .shutdown_timeout(Duration::ZERO)
+            .build();
+        assert_eq!(
+            result.err(),
+            Some(ConfigError::TimeoutTooShort {
+                parameter: "AgentRuntimeConfig::shutdown_timeout",
+                value: Duration::ZERO,
+                min: MIN_TIMEOUT,
+            })
+        );
+    }
+
+    #[test]
+    fn downlink_runtime_config_small_buffer() {
+        let result = DownlinkRuntimeConfig::builder()
+            .remote_buffer_size(non_zero_usize!(8))
+            .build();
+        assert_eq!(
+            result.err(),
+            Some(ConfigError::BufferTooSmall {
+                parameter: "DownlinkRuntimeConfig::remote_buffer_size",
+                value: 8,
+                min: MIN_BUFFER_SIZE,
+            })
+        );
+    }
+
+    #[test]
+    fn config_error_display() {
+        let err = ConfigError::BufferTooSmall {
+            parameter: "DownlinkRuntimeConfig::remote_buffer_size",
+            value: 8,
+            min: 64,
+        };
+        assert_eq!(
+            err.to_string(),
+            "The buffer size `DownlinkRuntimeConfig::remote_buffer_size` was 8 bytes but must be at least 64 bytes."
+        );
+    }
+}
@@ -17,7 +17,9 @@ use std::convert::Infallible;
 use bytes::{BufMut, Bytes, BytesMut};
 use swimos_agent_protocol::encoding::map::RawMapMessageEncoder;
 use swimos_agent_protocol::peeling::extract_header;
-use swimos_recon::parser::MessageExtractError;
+use swimos_model::{Item, Value};
+use swimos_recon::parser::{parse_recognize, MessageExtractError};
+use swimos_recon::print_recon_compact;
 use tokio_util::codec::Encoder;
 
 /// A possible transformation to apply to an incoming event body, before passing it on
@@ -37,6 +39,12 @@ pub trait DownlinkInterpretation {
         frame: Bytes,
         buffer: &mut BytesMut,
     ) -> Result<(), Self::Error>;
+
+    /// If the frame carries a batch of events, that were applied together by the remote lane, split it
+    /// into the bodies of the individual events. By default, every frame carries a single event.
+    fn split_batch(&self, _frame: &Bytes) -> Option<Vec<Bytes>> {
+        None
+    }
 }
 
 pub struct FnMutInterpretation<F>(F);
@@ -92,8 +100,32 @@ impl DownlinkInterpretation for MapInterpretation {
             .expect("Encoding a raw message into a BytesMut is infallible.");
         Ok(())
     }
+
+    fn split_batch(&self, frame: &Bytes) -> Option<Vec<Bytes>> {
+        if !frame.starts_with(BATCH_TAG) {
+            return None;
+        }
+        let body = std::str::from_utf8(frame.as_ref()).ok()?;
+        // Batches that cannot be parsed are left to fail as invalid map messages.
+        match parse_recognize::<Value>(body, false).ok()? {
+            Value::Record(_, items) => Some(
+                items
+                    .into_iter()
+                    .filter_map(|item| match item {
+                        Item::ValueItem(value) => {
+                            Some(Bytes::from(format!("{}", print_recon_compact(&value))))
+                        }
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
 }
 
+const BATCH_TAG: &[u8] = b"@batch{";
+
 /// Interpretation for map downlinks that does not interpret the events at all and passes through
 /// the frame unmodified.
 pub struct NoInterpretation;
@@ -18,13 +18,14 @@ use std::fmt::Debug;
 use std::num::NonZeroUsize;
 use std::time::Duration;
 
+use crate::config::{check_buffer_size, check_timeout, ConfigError};
 use crate::downlink::failure::BadFrameResponse;
 use crate::timeout_coord::{VoteResult, Voter};
 use crate::Io;
 use backpressure::DownlinkBackpressure;
 use bitflags::bitflags;
 use bytes::{Bytes, BytesMut};
-use futures::future::{join, select, Either};
+use futures::future::{join, join_all, poll_fn, select, Either};
 use futures::stream::SelectAll;
 use futures::{Future, FutureExt, Sink, SinkExt, Stream, StreamExt};
 use interpretation::MapInterpretation;
@@ -32,11 +33,13 @@ use swimos_agent_protocol::{
     encoding::downlink::DownlinkNotificationEncoder, DownlinkNotification,
 };
 use swimos_api::address::RelativeAddress;
+use swimos_api::agent::{LinkAdvice, LinkFailure, NodeStopped, SyncVersion};
 use swimos_messages::protocol::{
-    Notification, Operation, RawRequestMessage, RawRequestMessageEncoder,
+    LinkParams, Notification, Operation, RawRequestMessage, RawRequestMessageEncoder,
     RawResponseMessageDecoder, ResponseMessage,
 };
 use swimos_model::Text;
+use swimos_recon::parser::parse_recognize;
 use swimos_utilities::byte_channel::{ByteReader, ByteWriter};
 use swimos_utilities::encoding::BytesStr;
 use swimos_utilities::future::{immediate_or_join, immediate_or_start, SecondaryResult};
@@ -85,13 +88,29 @@ bitflags! {
 }
 
 impl WriteTaskState {
-    /// If a new consumer needs to be synced, set the appropriate state bit.
+    /// If a new consumer needs to be synced, set the appropriate state bit and record the version
+    /// of the state of the lane that it already holds. If several consumers are waiting to be
+    /// synced and they do not hold the same version, a full sync is requested.
     ///
     /// # Arguments
     /// * `options` - The option flags.
-    pub fn set_needs_sync(&mut self, options: DownlinkOptions) {
+    /// * `since` - The version of the state of the lane held by the consumer.
+    /// * `pending_since` - The version to present in the pending sync request.
+    pub fn set_needs_sync(
+        &mut self,
+        options: DownlinkOptions,
+        since: Option<SyncVersion>,
+        pending_since: &mut Option<SyncVersion>,
+    ) {
         if options.contains(DownlinkOptions::SYNC) {
-            *self |= WriteTaskState::NEEDS_SYNC;
+            if self.contains(WriteTaskState::NEEDS_SYNC) {
+                if *pending_since != since {
+                    *pending_since = None;
+                }
+            } else {
+                *self |= WriteTaskState::NEEDS_SYNC;
+                *pending_since = since;
+            }
         }
     }
 }
@@ -100,6 +119,7 @@ impl WriteTaskState {
 pub struct AttachAction {
     io: Io,
     options: DownlinkOptions,
+    since: Option<SyncVersion>,
 }
 
 impl AttachAction {
@@ -107,7 +127,19 @@ impl AttachAction {
     /// * `io` - Bidirectional channel to communicate with the downlink runtime.
     /// * `options` - Option flags for the downlink.
     pub fn new(io: Io, options: DownlinkOptions) -> Self {
-        AttachAction { io, options }
+        AttachAction {
+            io,
+            options,
+            since: None,
+        }
+    }
+
+    /// Attach the version of the state of a map lane that the consumer already holds (from
+    /// a previous link). If the lane recognizes the version, it will only send the entries that
+    /// have changed since, rather than its entire state, when the consumer is synced.
+    pub fn with_since(mut self, since: Option<SyncVersion>) -> Self {
+        self.since = since;
+        self
     }
 }
 
@@ -126,6 +158,79 @@ pub struct DownlinkRuntimeConfig {
     pub downlink_buffer_size: NonZeroUsize,
 }
 
+impl DownlinkRuntimeConfig {
+    /// Create a builder, starting from the default configuration, that will check the values of
+    /// the parameters when the configuration is built.
+    pub fn builder() -> DownlinkRuntimeConfigBuilder {
+        DownlinkRuntimeConfigBuilder::default()
+    }
+
+    /// Check that all of the parameters are within their permitted ranges.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let DownlinkRuntimeConfig {
+            empty_timeout,
+            remote_buffer_size,
+            downlink_buffer_size,
+            ..
+        } = self;
+        check_timeout("DownlinkRuntimeConfig::empty_timeout", *empty_timeout)?;
+        check_buffer_size(
+            "DownlinkRuntimeConfig::remote_buffer_size",
+            remote_buffer_size.get(),
+        )?;
+        check_buffer_size(
+            "DownlinkRuntimeConfig::downlink_buffer_size",
+            downlink_buffer_size.get(),
+        )
+    }
+}
+
+/// Builder for [`DownlinkRuntimeConfig`] that validates the parameters when the configuration is
+/// built. Any parameters that are not set will take their default values.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DownlinkRuntimeConfigBuilder {
+    config: DownlinkRuntimeConfig,
+}
+
+impl DownlinkRuntimeConfigBuilder {
+    /// Set the period with no consumers after which the runtime will stop.
+    pub fn empty_timeout(mut self, timeout: Duration) -> Self {
+        self.config.empty_timeout = timeout;
+        self
+    }
+
+    /// Set the size of the queue for accepting new subscribers to a downlink.
+    pub fn attachment_queue_size(mut self, size: NonZeroUsize) -> Self {
+        self.config.attachment_queue_size = size;
+        self
+    }
+
+    /// Set whether the downlink should abort on receiving invalid frames.
+    pub fn abort_on_bad_frames(mut self, abort: bool) -> Self {
+        self.config.abort_on_bad_frames = abort;
+        self
+    }
+
+    /// Set the size of the buffers to communicate with the socket.
+    pub fn remote_buffer_size(mut self, size: NonZeroUsize) -> Self {
+        self.config.remote_buffer_size = size;
+        self
+    }
+
+    /// Set the size of the buffers to communicate with the downlink implementation.
+    pub fn downlink_buffer_size(mut self, size: NonZeroUsize) -> Self {
+        self.config.downlink_buffer_size = size;
+        self
+    }
+
+    /// Build the configuration, failing if any of the parameters are out of range.
+    pub fn build(self) -> Result<DownlinkRuntimeConfig, ConfigError> {
+        let DownlinkRuntimeConfigBuilder { config } = self;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
 impl Default for DownlinkRuntimeConfig {
     fn default() -> Self {
         DownlinkRuntimeConfig {
@@ -139,6 +244,12 @@ impl Default for DownlinkRuntimeConfig {
 }
 
 /// The runtime component for a value type downlink (i.e. value downlink, event downlink, etc.).
+/// All consumers share a single link to the remote lane. If the runtime is conflating (see
+/// [`ValueDownlinkRuntime::conflating`]), a consumer that cannot keep up with the events from the
+/// lane skips to the latest value rather than delaying the other consumers. Otherwise, every
+/// consumer receives every event, preceded by the ID of the remote that sent the command that
+/// caused it (for lanes that track the origins of commands). As a conflated event can stand in for
+/// several, the origins of events are not reported when conflating.
 pub struct ValueDownlinkRuntime {
     requests: mpsc::Receiver<AttachAction>,
     input: ByteReader,
@@ -147,9 +258,12 @@ pub struct ValueDownlinkRuntime {
     identity: Uuid,
     path: RelativeAddress<Text>,
     config: DownlinkRuntimeConfig,
+    conflate: bool,
 }
 
-/// The runtime component for a map type downlink.
+/// The runtime component for a map type downlink. All consumers share a single link to the remote
+/// lane. As the state of a map is built from a sequence of events, every consumer receives every
+/// event and a consumer that cannot keep up will delay the others.
 pub struct MapDownlinkRuntime<H, I> {
     requests: mpsc::Receiver<AttachAction>,
     input: ByteReader,
@@ -212,9 +326,18 @@ impl ValueDownlinkRuntime {
             identity,
             path,
             config,
+            conflate: false,
         }
     }
 
+    /// Allow consumers that are lagging to skip to the latest value. This is only valid when the
+    /// consumers are only interested in the state of the lane (i.e. for value downlinks) and must
+    /// not be used for event downlinks, where every event must be delivered.
+    pub fn conflating(mut self) -> Self {
+        self.conflate = true;
+        self
+    }
+
     /// Run the downlink task.
     pub async fn run(self) {
         let ValueDownlinkRuntime {
@@ -225,6 +348,7 @@ impl ValueDownlinkRuntime {
             identity,
             path,
             config,
+            conflate,
         } = self;
 
         let (producer_tx, producer_rx) = mpsc::channel(config.attachment_queue_size.get());
@@ -239,6 +363,7 @@ impl ValueDownlinkRuntime {
             input,
             consumer_rx,
             config,
+            conflate,
             value_interpretation(),
             InfallibleStrategy,
             read_vote,
@@ -380,6 +505,7 @@ where
             input,
             consumer_rx,
             config,
+            false,
             interpretation,
             failure_handler,
             read_vote,
@@ -411,7 +537,7 @@ where
 /// Communicates with the read and write tasks to add new consumers.
 async fn attach_task<F>(
     rx: mpsc::Receiver<AttachAction>,
-    producer_tx: mpsc::Sender<(ByteReader, DownlinkOptions)>,
+    producer_tx: mpsc::Sender<(ByteReader, DownlinkOptions, Option<SyncVersion>)>,
     consumer_tx: mpsc::Sender<(ByteWriter, DownlinkOptions)>,
     combined_stop: F,
 ) where
@@ -421,12 +547,13 @@ async fn attach_task<F>(
     while let Some(AttachAction {
         io: (output, input),
         options,
+        since,
     }) = stream.next().await
     {
         if consumer_tx.send((output, options)).await.is_err() {
             break;
         }
-        if producer_tx.send((input, options)).await.is_err() {
+        if producer_tx.send((input, options, since)).await.is_err() {
             break;
         }
     }
@@ -445,6 +572,8 @@ enum ReadTaskDlState {
 struct DownlinkSender {
     sender: FramedWrite<ByteWriter, DownlinkNotificationEncoder>,
     options: DownlinkOptions,
+    // The subscriber has fallen behind and has missed at least one event.
+    stale: bool,
 }
 
 impl DownlinkSender {
@@ -452,7 +581,44 @@ impl DownlinkSender {
         DownlinkSender {
             sender: FramedWrite::new(writer, DownlinkNotificationEncoder),
             options,
+            stale: false,
+        }
+    }
+
+    /// Attempt to feed a message to the subscriber without waiting for it to make space for it.
+    /// If the subscriber is lagging, the message is discarded and the sender is marked as stale.
+    fn try_feed(&mut self, message: DownlinkNotification<&BytesMut>) -> Result<(), std::io::Error> {
+        let DownlinkSender { sender, stale, .. } = self;
+        let ready = poll_fn(|cx| poll_ready_notification(sender, cx)).now_or_never();
+        match ready {
+            Some(Ok(_)) => sender.start_send_unpin(message),
+            Some(Err(err)) => Err(err),
+            None => {
+                *stale = true;
+                Ok(())
+            }
+        }
+    }
+
+    /// If the subscriber is stale, bring it up to date with the current value, and then flush it.
+    /// The progress is recorded in the sender so this can be safely abandoned part way through
+    /// and resumed later without sending any value more than once.
+    fn poll_catch_up(
+        &mut self,
+        cx: &mut Context<'_>,
+        current: &BytesMut,
+    ) -> Poll<Result<(), std::io::Error>> {
+        let DownlinkSender { sender, stale, .. } = self;
+        if *stale {
+            ready!(poll_ready_notification(sender, cx))?;
+            sender.start_send_unpin(DownlinkNotification::Event { body: current })?;
+            *stale = false;
         }
+        poll_flush_notification(sender, cx)
+    }
+
+    async fn catch_up(&mut self, current: &BytesMut) -> Result<(), std::io::Error> {
+        poll_fn(|cx| self.poll_catch_up(cx, current)).await
     }
 
     async fn feed(
@@ -474,6 +640,26 @@ impl DownlinkSender {
     }
 }
 
+fn poll_ready_notification<T>(
+    sender: &mut FramedWrite<ByteWriter, T>,
+    cx: &mut Context<'_>,
+) -> Poll<Result<(), T::Error>>
+where
+    T: Encoder<DownlinkNotification<&'static BytesMut>>,
+{
+    sender.poll_ready_unpin(cx)
+}
+
+fn poll_flush_notification<T>(
+    sender: &mut FramedWrite<ByteWriter, T>,
+    cx: &mut Context<'_>,
+) -> Poll<Result<(), T::Error>>
+where
+    T: Encoder<DownlinkNotification<&'static BytesMut>>,
+{
+    sender.poll_flush_unpin(cx)
+}
+
 async fn flush_sender_notification<T>(
     sender: &mut FramedWrite<ByteWriter, T>,
 ) -> Result<(), T::Error>
@@ -549,6 +735,7 @@ async fn read_task<I, H>(
     input: ByteReader,
     consumers: mpsc::Receiver<(ByteWriter, DownlinkOptions)>,
     config: DownlinkRuntimeConfig,
+    conflate: bool,
     mut interpretation: I,
     mut failure_handler: H,
     stop_voter: Voter,
@@ -558,6 +745,8 @@ where
     H: BadFrameStrategy<I::Error>,
 {
     let mut messages = FramedRead::new(input, RawResponseMessageDecoder);
+    // Conflation is only possible if the state of the lane is carried by a single frame.
+    let conflate = conflate && I::SINGLE_FRAME_STATE;
 
     let mut flushed = true;
     let mut voted = false;
@@ -576,7 +765,7 @@ where
     // lane and a downlink syncing with a lane that has a type which may be optional.
     let mut sync_event = false;
 
-    let result: Result<(), H::Report> = loop {
+    let result: Result<(), H::Report> = 'read: loop {
         let (event, is_active) = match task_state.as_mut().as_pin_mut() {
             Some(sleep) if !voted => (
                 tokio::select! {
@@ -624,6 +813,36 @@ where
                 if flushed {
                     trace!("Waiting without flush.");
                     (get_next.await, true)
+                } else if conflate {
+                    // Subscribers to value downlinks only need the latest value so a subscriber
+                    // that is lagging must not prevent the others from receiving events. The
+                    // flush is abandoned if a new event arrives before it completes (catching up
+                    // is cancel safe).
+                    trace!("Waiting with non-blocking flush.");
+                    let mut get_next = pin!(get_next);
+                    let flush = join(
+                        flush_all(&mut awaiting_synced),
+                        catch_up_all(&mut registered, &current),
+                    );
+                    let (next, flush_result) = tokio::select! {
+                        biased;
+                        next = &mut get_next => (next, None),
+                        flush_result = flush => (get_next.await, Some(flush_result)),
+                    };
+                    let is_active = if flush_result.is_some() {
+                        trace!("Flush completed.");
+                        flushed = true;
+                        if registered.is_empty() && awaiting_synced.is_empty() {
+                            trace!("Number of subscribers dropped to 0.");
+                            task_state.set(Some(make_timeout()));
+                            false
+                        } else {
+                            true
+                        }
+                    } else {
+                        true
+                    };
+                    (next, is_active)
                 } else {
                     trace!("Waiting with flush.");
                     let flush = join(flush_all(&mut awaiting_synced), flush_all(&mut registered));
@@ -657,7 +876,11 @@ where
                     voted = true;
                 }
             }
-            ReadTaskEvent::Message(ResponseMessage { envelope, .. }) => match envelope {
+            ReadTaskEvent::Message(ResponseMessage {
+                envelope,
+                command_origin,
+                ..
+            }) => match envelope {
                 Notification::Linked => {
                     trace!("Entering Linked state.");
                     dl_state = ReadTaskDlState::Linked;
@@ -669,8 +892,14 @@ where
                         }
                     }
                 }
-                Notification::Synced => {
+                envelope @ (Notification::Synced | Notification::SyncedWith(_)) => {
                     trace!("Entering Synced state.");
+                    // Map lanes report the version of their state which is passed on to the
+                    // consumers so that they can present it if they need to sync again.
+                    let version = match &envelope {
+                        Notification::SyncedWith(body) => read_sync_version(body.as_ref()),
+                        _ => None,
+                    };
                     dl_state = ReadTaskDlState::Synced;
                     if is_active {
                         // `sync_event` will be false if we're communicating with a stateless lane
@@ -684,9 +913,10 @@ where
                         // fail due to reading an extant read event. Therefore, delegate the operation to
                         // `sync_only` which will not send an event notification.
                         if I::SINGLE_FRAME_STATE && sync_event {
-                            sync_current(&mut awaiting_synced, &mut registered, &current).await;
+                            sync_current(&mut awaiting_synced, &mut registered, &current, version)
+                                .await;
                         } else {
-                            sync_only(&mut awaiting_synced, &mut registered).await;
+                            sync_only(&mut awaiting_synced, &mut registered, version).await;
                         }
                         if registered.is_empty() {
                             trace!("Number of subscribers dropped to 0.");
@@ -696,30 +926,64 @@ where
                 }
                 Notification::Unlinked(message) => {
                     trace!("Stopping after unlinked: {msg:?}", msg = message);
+                    if let Some(notice) = message.as_deref().and_then(read_node_stopped) {
+                        info!(notice = %notice, "The remote agent stopped.");
+                        node_stopped(&mut awaiting_synced, notice).await;
+                        node_stopped(&mut registered, notice).await;
+                    } else if let Some(failure) = message.as_deref().and_then(read_link_failure) {
+                        info!(failure = %failure, "The remote refused the link.");
+                        link_failed(&mut awaiting_linked, failure).await;
+                        link_failed(&mut awaiting_synced, failure).await;
+                        link_failed(&mut registered, failure).await;
+                    }
                     break Ok(());
                 }
+                Notification::Advisory(body) => match read_advice(body.as_ref()) {
+                    Some(advice) => {
+                        info!(advice = %advice, "The remote lane advised the downlink to back off.");
+                        if is_active {
+                            advise(&mut awaiting_synced, advice).await;
+                            advise(&mut registered, advice).await;
+                        }
+                    }
+                    _ => {
+                        warn!(body = ?body, "Ignoring an invalid advisory from the remote lane.");
+                    }
+                },
                 Notification::Event(bytes) => {
                     sync_event = true;
 
-                    trace!("Updating the current value.");
-                    current.clear();
-
-                    if let Err(e) = interpretation.interpret_frame_data(bytes, &mut current) {
-                        if let BadFrameResponse::Abort(report) = failure_handler.failed_with(e) {
-                            break Err(report);
-                        }
-                    }
-                    if is_active {
-                        send_current(&mut registered, &current).await;
-                        if !I::SINGLE_FRAME_STATE {
-                            send_current(&mut awaiting_synced, &current).await;
+                    // A batch of events is passed on as a sequence of individual events.
+                    let bodies = interpretation
+                        .split_batch(&bytes)
+                        .unwrap_or_else(|| vec![bytes]);
+                    for body in bodies {
+                        trace!("Updating the current value.");
+                        current.clear();
+
+                        if let Err(e) = interpretation.interpret_frame_data(body, &mut current) {
+                            if let BadFrameResponse::Abort(report) = failure_handler.failed_with(e)
+                            {
+                                break 'read Err(report);
+                            }
                         }
-                        if registered.is_empty() && awaiting_synced.is_empty() {
-                            trace!("Number of subscribers dropped to 0.");
-                            task_state.set(Some(make_timeout()));
-                            flushed = true;
-                        } else {
-                            flushed = false;
+                        if is_active {
+                            if conflate {
+                                conflate_current(&mut registered, &current);
+                            } else {
+                                send_current(&mut registered, &current, command_origin).await;
+                                if !I::SINGLE_FRAME_STATE {
+                                    send_current(&mut awaiting_synced, &current, command_origin)
+                                        .await;
+                                }
+                            }
+                            if registered.is_empty() && awaiting_synced.is_empty() {
+                                trace!("Number of subscribers dropped to 0.");
+                                task_state.set(Some(make_timeout()));
+                                flushed = true;
+                            } else {
+                                flushed = false;
+                            }
                         }
                     }
                 }
@@ -776,10 +1040,11 @@ async fn sync_current(
     awaiting_synced: &mut Vec<DownlinkSender>,
     registered: &mut Vec<DownlinkSender>,
     current: &BytesMut,
+    version: Option<SyncVersion>,
 ) {
     let event = DownlinkNotification::Event { body: current };
     for mut tx in awaiting_synced.drain(..) {
-        if tx.feed(event).await.is_ok() && tx.send(DownlinkNotification::Synced).await.is_ok() {
+        if tx.feed(event).await.is_ok() && send_synced(&mut tx, version).await.is_ok() {
             registered.push(tx);
         }
     }
@@ -788,25 +1053,113 @@ async fn sync_current(
 async fn sync_only(
     awaiting_synced: &mut Vec<DownlinkSender>,
     registered: &mut Vec<DownlinkSender>,
+    version: Option<SyncVersion>,
 ) {
     for mut tx in awaiting_synced.drain(..) {
-        if tx.send(DownlinkNotification::Synced).await.is_ok() {
+        if send_synced(&mut tx, version).await.is_ok() {
             registered.push(tx);
         }
     }
 }
 
-async fn send_current(senders: &mut Vec<DownlinkSender>, current: &BytesMut) {
+/// Send a synced notification to a consumer, preceded by the version of the state of the lane,
+/// if it was reported.
+async fn send_synced(
+    tx: &mut DownlinkSender,
+    version: Option<SyncVersion>,
+) -> Result<(), std::io::Error> {
+    if let Some(version) = version {
+        tx.feed(DownlinkNotification::Version { version }).await?;
+    }
+    tx.send(DownlinkNotification::Synced).await
+}
+
+/// Send the current value to each subscriber, preceded by the ID of the remote that sent the
+/// command that caused it, if it is known.
+async fn send_current(senders: &mut Vec<DownlinkSender>, current: &BytesMut, origin: Option<Uuid>) {
+    let event = DownlinkNotification::Event { body: current };
+    let mut failed = HashSet::<usize>::default();
+    for (i, tx) in senders.iter_mut().enumerate() {
+        let result = match origin {
+            Some(origin) => match tx.feed(DownlinkNotification::Origin { origin }).await {
+                Ok(_) => tx.feed(event).await,
+                Err(err) => Err(err),
+            },
+            None => tx.feed(event).await,
+        };
+        if result.is_err() {
+            failed.insert(i);
+        }
+    }
+    clear_failed(senders, &failed);
+}
+
+/// Send the current value to each subscriber that is able to receive it immediately. Any
+/// subscriber that is lagging is skipped and will receive the latest value when it catches up.
+fn conflate_current(senders: &mut Vec<DownlinkSender>, current: &BytesMut) {
     let event = DownlinkNotification::Event { body: current };
     let mut failed = HashSet::<usize>::default();
     for (i, tx) in senders.iter_mut().enumerate() {
-        if tx.feed(event).await.is_err() {
+        if !tx.stale && tx.try_feed(event).is_err() {
             failed.insert(i);
         }
     }
     clear_failed(senders, &failed);
 }
 
+async fn advise(senders: &mut Vec<DownlinkSender>, advice: LinkAdvice) {
+    let event = DownlinkNotification::Advisory { advice };
+    let mut failed = HashSet::<usize>::default();
+    for (i, tx) in senders.iter_mut().enumerate() {
+        if tx.send(event).await.is_err() {
+            failed.insert(i);
+        }
+    }
+    clear_failed(senders, &failed);
+}
+
+fn read_advice(body: &[u8]) -> Option<LinkAdvice> {
+    let body_str = std::str::from_utf8(body).ok()?;
+    parse_recognize::<LinkAdvice>(body_str, false).ok()
+}
+
+async fn node_stopped(senders: &mut Vec<DownlinkSender>, notice: NodeStopped) {
+    let event = DownlinkNotification::NodeStopped { notice };
+    let mut failed = HashSet::<usize>::default();
+    for (i, tx) in senders.iter_mut().enumerate() {
+        if tx.send(event).await.is_err() {
+            failed.insert(i);
+        }
+    }
+    clear_failed(senders, &failed);
+}
+
+fn read_node_stopped(body: &[u8]) -> Option<NodeStopped> {
+    let body_str = std::str::from_utf8(body).ok()?;
+    parse_recognize::<NodeStopped>(body_str, false).ok()
+}
+
+async fn link_failed(senders: &mut Vec<DownlinkSender>, failure: LinkFailure) {
+    let event = DownlinkNotification::Failed { failure };
+    let mut failed = HashSet::<usize>::default();
+    for (i, tx) in senders.iter_mut().enumerate() {
+        if tx.send(event).await.is_err() {
+            failed.insert(i);
+        }
+    }
+    clear_failed(senders, &failed);
+}
+
+fn read_link_failure(body: &[u8]) -> Option<LinkFailure> {
+    let body_str = std::str::from_utf8(body).ok()?;
+    parse_recognize::<LinkFailure>(body_str, false).ok()
+}
+
+fn read_sync_version(body: &[u8]) -> Option<SyncVersion> {
+    let body_str = std::str::from_utf8(body).ok()?;
+    parse_recognize::<SyncVersion>(body_str, false).ok()
+}
+
 async fn link(
     awaiting_linked: &mut Vec<DownlinkSender>,
     awaiting_synced: &mut Vec<DownlinkSender>,
@@ -851,14 +1204,25 @@ fn clear_failed(senders: &mut Vec<DownlinkSender>, failed: &HashSet<usize>) {
     }
 }
 
+/// Flush all of the subscribers concurrently so that a slow subscriber does not delay the others.
 async fn flush_all(senders: &mut Vec<DownlinkSender>) {
-    let mut failed = HashSet::<usize>::default();
-    for (i, tx) in senders.iter_mut().enumerate() {
-        if tx.flush().await.is_err() {
-            failed.insert(i);
-        }
-    }
-    clear_failed(senders, &failed);
+    let results = join_all(senders.iter_mut().map(DownlinkSender::flush)).await;
+    clear_failed(senders, &failed_indices(results));
+}
+
+/// Flush all of the subscribers concurrently, sending the current value to any that are stale.
+async fn catch_up_all(senders: &mut Vec<DownlinkSender>, current: &BytesMut) {
+    let results = join_all(senders.iter_mut().map(|tx| tx.catch_up(current))).await;
+    clear_failed(senders, &failed_indices(results));
+}
+
+fn failed_indices<E>(results: Vec<Result<(), E>>) -> HashSet<usize> {
+    results
+        .iter()
+        .enumerate()
+        .filter(|(_, result)| result.is_err())
+        .map(|(i, _)| i)
+        .collect()
 }
 
 #[derive(Debug)]
@@ -886,12 +1250,13 @@ impl RequestSender {
         let message = RawRequestMessage {
             origin: *identity,
             path: path.clone(),
-            envelope: Operation::Link,
+            envelope: Operation::Link(LinkParams::default()),
+            correlation_id: None,
         };
         sender.send(message).await
     }
 
-    async fn send_sync(&mut self) -> Result<(), std::io::Error> {
+    async fn send_sync(&mut self, since: Option<SyncVersion>) -> Result<(), std::io::Error> {
         let RequestSender {
             sender,
             identity,
@@ -900,7 +1265,8 @@ impl RequestSender {
         let message = RawRequestMessage {
             origin: *identity,
             path: path.clone(),
-            envelope: Operation::Sync,
+            envelope: Operation::Sync(LinkParams::default().with_since(since)),
+            correlation_id: None,
         };
         sender.send(message).await
     }
@@ -915,6 +1281,7 @@ impl RequestSender {
             origin: *identity,
             path: path.clone(),
             envelope: Operation::Command(body),
+            correlation_id: None,
         };
         sender.feed(message).await
     }
@@ -941,7 +1308,7 @@ enum WriteState<F> {
 }
 
 enum WriteKind {
-    Sync,
+    Sync(Option<SyncVersion>),
     Data,
 }
 
@@ -957,7 +1324,7 @@ async fn do_flush(
 /// If commands are received faster than the channel can send them, some records will be dropped.
 async fn write_task<B: DownlinkBackpressure>(
     output: ByteWriter,
-    producers: mpsc::Receiver<(ByteReader, DownlinkOptions)>,
+    producers: mpsc::Receiver<(ByteReader, DownlinkOptions, Option<SyncVersion>)>,
     identity: Uuid,
     path: RelativeAddress<Text>,
     config: DownlinkRuntimeConfig,
@@ -987,11 +1354,13 @@ async fn write_task<B: DownlinkBackpressure>(
     };
 
     let mut task_state = WriteTaskState::INIT;
+    // The version to present in the pending sync request (if the NEEDS_SYNC flag is set).
+    let mut pending_since: Option<SyncVersion> = None;
 
     let suspend_write = |mut message_writer: RequestSender, buffer: BytesMut, kind: WriteKind| async move {
         let result = match kind {
             WriteKind::Data => message_writer.feed_command(buffer.as_ref()).await,
-            WriteKind::Sync => message_writer.send_sync().await,
+            WriteKind::Sync(since) => message_writer.send_sync(since).await,
         };
         (result.map(move |_| message_writer), buffer)
     };
@@ -1066,7 +1435,7 @@ async fn write_task<B: DownlinkBackpressure>(
                         req_result
                     };
                     match req_result {
-                        Ok(Some((reader, options))) => {
+                        Ok(Some((reader, options, since))) => {
                             if voted {
                                 if stop_voter.rescind() == VoteResult::Unanimous {
                                     info!("Attempted to rescind vote to stop but shutdown had already started.");
@@ -1081,7 +1450,8 @@ async fn write_task<B: DownlinkBackpressure>(
                             registered.push(receiver);
                             if options.contains(DownlinkOptions::SYNC) {
                                 trace!("Sending a Sync message.");
-                                let write = suspend_write(message_writer, buffer, WriteKind::Sync);
+                                let write =
+                                    suspend_write(message_writer, buffer, WriteKind::Sync(since));
                                 state = WriteState::Writing(Either::Left(write));
                             } else {
                                 state = WriteState::Idle {
@@ -1129,7 +1499,7 @@ async fn write_task<B: DownlinkBackpressure>(
                         (discard(next_op), flush_outcome)
                     };
                     match next_op {
-                        Either::Left(Some((reader, options))) => {
+                        Either::Left(Some((reader, options, since))) => {
                             let receiver =
                                 DownlinkReceiver::new(reader, next_id(), B::make_decoder());
                             registered.push(receiver);
@@ -1137,8 +1507,11 @@ async fn write_task<B: DownlinkBackpressure>(
                                 Either::Left(message_writer) => {
                                     if options.contains(DownlinkOptions::SYNC) {
                                         trace!("Sending a Sync message.");
-                                        let write =
-                                            suspend_write(message_writer, buffer, WriteKind::Sync);
+                                        let write = suspend_write(
+                                            message_writer,
+                                            buffer,
+                                            WriteKind::Sync(since),
+                                        );
                                         state = WriteState::Writing(Either::Left(write));
                                     } else {
                                         state = WriteState::Idle {
@@ -1149,7 +1522,7 @@ async fn write_task<B: DownlinkBackpressure>(
                                 }
                                 Either::Right(flush) => {
                                     trace!("Waiting on the completion of a flush.");
-                                    task_state.set_needs_sync(options);
+                                    task_state.set_needs_sync(options, since, &mut pending_since);
                                     state =
                                         WriteState::Writing(Either::Right(do_flush(flush, buffer)));
                                 }
@@ -1233,7 +1606,11 @@ async fn write_task<B: DownlinkBackpressure>(
                                 trace!("Sending a Sync message.");
                                 task_state
                                     .remove(WriteTaskState::FLUSHED | WriteTaskState::NEEDS_SYNC);
-                                let write = suspend_write(message_writer, buffer, WriteKind::Sync);
+                                let write = suspend_write(
+                                    message_writer,
+                                    buffer,
+                                    WriteKind::Sync(pending_since.take()),
+                                );
                                 state = WriteState::Writing(Either::Left(write));
                             } else if backpressure.has_data() {
                                 trace!("Dispatching the updated buffer.");
@@ -1268,12 +1645,12 @@ async fn write_task<B: DownlinkBackpressure>(
                                 rx.terminate();
                             }
                         }
-                        SuspendedResult::NewRegistration(Some((reader, options))) => {
+                        SuspendedResult::NewRegistration(Some((reader, options, since))) => {
                             trace!("Registering a new subscriber.");
                             let receiver =
                                 DownlinkReceiver::new(reader, next_id(), B::make_decoder());
                             registered.push(receiver);
-                            task_state.set_needs_sync(options);
+                            task_state.set_needs_sync(options, since, &mut pending_since);
                         }
                         SuspendedResult::NewRegistration(_) => {
                             info!("Instructed to stop.");
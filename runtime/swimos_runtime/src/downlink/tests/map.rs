@@ -24,14 +24,16 @@ use swimos_agent_protocol::{
 };
 use swimos_api::{
     address::RelativeAddress,
+    agent::SyncVersion,
     error::{DownlinkTaskError, FrameIoError, InvalidFrame},
 };
-use swimos_form::{read::RecognizerReadable, Form};
+use swimos_form::{read::RecognizerReadable, write::StructuralWritable, Form};
 use swimos_messages::protocol::{
-    MessageDecodeError, Operation, RequestMessage, RequestMessageDecoder, ResponseMessage,
-    ResponseMessageEncoder,
+    LinkParams, MessageDecodeError, Operation, RequestMessage, RequestMessageDecoder,
+    ResponseMessage, ResponseMessageEncoder,
 };
-use swimos_model::Text;
+use swimos_model::{Attr, Item, Text, Value};
+use swimos_recon::print_recon_compact;
 use swimos_utilities::{
     byte_channel::{self, ByteReader, ByteWriter},
     trigger::{self, promise},
@@ -66,6 +68,7 @@ const FAIL_KEY: i32 = -2;
 async fn run_fake_downlink(
     sub: mpsc::Sender<AttachAction>,
     options: DownlinkOptions,
+    since: Option<SyncVersion>,
     start: trigger::Receiver,
     event_tx: mpsc::UnboundedSender<Event>,
     send_rx: mpsc::UnboundedReceiver<MapOperation<i32, Record>>,
@@ -77,7 +80,7 @@ async fn run_fake_downlink(
     let (tx_in, rx_in) = byte_channel::byte_channel(BUFFER_SIZE);
     let (tx_out, rx_out) = byte_channel::byte_channel(BUFFER_SIZE);
     if sub
-        .send(AttachAction::new((tx_in, rx_out), options))
+        .send(AttachAction::new((tx_in, rx_out), options).with_since(since))
         .await
         .is_err()
     {
@@ -221,6 +224,17 @@ impl TestSender {
         .await;
     }
 
+    async fn sync_with_version(&mut self, version: SyncVersion) {
+        let body = format!("{}", print_recon_compact(&version));
+        let message: ResponseMessage<&str, MapMessage<i32, Record>, &[u8]> =
+            ResponseMessage::synced_with(
+                REMOTE_ADDR,
+                RelativeAddress::new(REMOTE_NODE, REMOTE_LANE),
+                body.as_bytes(),
+            );
+        self.send(message).await;
+    }
+
     async fn send(&mut self, message: ResponseMessage<&str, MapMessage<i32, Record>, &[u8]>) {
         assert!(self.0.send(message).await.is_ok());
     }
@@ -270,6 +284,22 @@ impl TestSender {
         self.send(message).await;
     }
 
+    async fn batch(&mut self, messages: Vec<MapMessage<i32, Record>>) {
+        let body = Value::Record(
+            vec![Attr::of("batch")],
+            messages
+                .iter()
+                .map(|message| Item::ValueItem(message.structure()))
+                .collect(),
+        );
+        let message: ResponseMessage<&str, Value, &[u8]> = ResponseMessage::event(
+            REMOTE_ADDR,
+            RelativeAddress::new(REMOTE_NODE, REMOTE_LANE),
+            body,
+        );
+        assert!(self.0.send(message).await.is_ok());
+    }
+
     async fn update_text(&mut self, message: Text) {
         let message: ResponseMessage<&str, Text, &[u8]> = ResponseMessage::event(
             REMOTE_ADDR,
@@ -341,6 +371,21 @@ async fn run_test_with_config<F, Fut, H>(
     failure_strategy: H,
     test_block: F,
 ) -> (Fut::Output, Result<(), DownlinkTaskError>)
+where
+    F: FnOnce(TestContext) -> Fut,
+    Fut: Future + Send + 'static,
+    H: BadFrameStrategy<<MapInterpretation as DownlinkInterpretation>::Error>,
+{
+    run_test_with_since(options, None, config, failure_strategy, test_block).await
+}
+
+async fn run_test_with_since<F, Fut, H>(
+    options: DownlinkOptions,
+    since: Option<SyncVersion>,
+    config: DownlinkRuntimeConfig,
+    failure_strategy: H,
+    test_block: F,
+) -> (Fut::Output, Result<(), DownlinkTaskError>)
 where
     F: FnOnce(TestContext) -> Fut,
     Fut: Future + Send + 'static,
@@ -352,7 +397,14 @@ where
     let (event_tx, event_rx) = mpsc::unbounded_channel();
     let (send_tx, send_rx) = mpsc::unbounded_channel();
 
-    let downlink = run_fake_downlink(attach_tx.clone(), options, start_rx, event_tx, send_rx);
+    let downlink = run_fake_downlink(
+        attach_tx.clone(),
+        options,
+        since,
+        start_rx,
+        event_tx,
+        send_rx,
+    );
 
     let (in_tx, in_rx) = byte_channel::byte_channel(BUFFER_SIZE);
     let (out_tx, out_rx) = byte_channel::byte_channel(BUFFER_SIZE);
@@ -428,7 +480,7 @@ async fn shutdowm_none_attached() {
              events,
              ..
          }| async move {
-            expect_message(rx.recv().await, Operation::Link);
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
             stop.trigger();
             events.collect::<Vec<_>>().await
         },
@@ -450,7 +502,7 @@ async fn shutdowm_after_attached() {
              mut events,
              ..
          }| async move {
-            expect_message(rx.recv().await, Operation::Link);
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
 
             start_client.trigger();
             tx.link().await;
@@ -484,7 +536,7 @@ async fn shutdowm_after_corrupted_frame() {
             mut events,
             ..
         } = context;
-        expect_message(rx.recv().await, Operation::Link);
+        expect_message(rx.recv().await, Operation::Link(Default::default()));
 
         start_client.trigger();
         tx.link().await;
@@ -522,7 +574,7 @@ async fn sync_from_nothing() {
              mut events,
              ..
          }| async move {
-            expect_message(rx.recv().await, Operation::Link);
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
 
             start_client.trigger();
             tx.link().await;
@@ -532,7 +584,7 @@ async fn sync_from_nothing() {
                 State::Unlinked,
                 DownlinkNotification::Linked,
             );
-            expect_message(rx.recv().await, Operation::Sync);
+            expect_message(rx.recv().await, Operation::Sync(Default::default()));
 
             tx.update(2, rec(1, 2)).await;
             tx.update(6, rec(3, 4)).await;
@@ -576,6 +628,81 @@ async fn sync_from_nothing() {
     );
 }
 
+#[tokio::test]
+async fn sync_since_version() {
+    let since = SyncVersion::new(1, 5);
+    let reported = SyncVersion::new(1, 7);
+    let config = DownlinkRuntimeConfig {
+        empty_timeout: EMPTY_TIMEOUT,
+        attachment_queue_size: ATT_QUEUE_SIZE,
+        abort_on_bad_frames: true,
+        remote_buffer_size: BUFFER_SIZE,
+        downlink_buffer_size: BUFFER_SIZE,
+    };
+    let (events, result) = run_test_with_since(
+        DownlinkOptions::SYNC,
+        Some(since),
+        config,
+        AlwaysAbortStrategy,
+        |TestContext {
+             mut tx,
+             mut rx,
+             start_client,
+             stop,
+             mut events,
+             ..
+         }| async move {
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
+
+            start_client.trigger();
+            tx.link().await;
+
+            expect_event(
+                events.next().await,
+                State::Unlinked,
+                DownlinkNotification::Linked,
+            );
+            expect_message(
+                rx.recv().await,
+                Operation::Sync(LinkParams::default().with_since(Some(since))),
+            );
+
+            tx.update(2, rec(1, 2)).await;
+            tx.sync_with_version(reported).await;
+
+            expect_event(
+                events.next().await,
+                State::Linked,
+                DownlinkNotification::Event {
+                    body: MapMessage::Update {
+                        key: 2,
+                        value: rec(1, 2),
+                    },
+                },
+            );
+            expect_event(
+                events.next().await,
+                State::Linked,
+                DownlinkNotification::Version { version: reported },
+            );
+            expect_event(
+                events.next().await,
+                State::Linked,
+                DownlinkNotification::Synced,
+            );
+
+            stop.trigger();
+            events.collect::<Vec<_>>().await
+        },
+    )
+    .await;
+    assert!(result.is_ok());
+    assert_eq!(
+        events,
+        vec![(State::Synced, DownlinkNotification::Unlinked)]
+    );
+}
+
 async fn sync_client_then<F, Fut>(context: TestContext, f: F) -> Vec<Event>
 where
     F: FnOnce(SyncedTestContext) -> Fut,
@@ -589,7 +716,7 @@ where
         mut events,
         send_tx,
     } = context;
-    expect_message(rx.recv().await, Operation::Link);
+    expect_message(rx.recv().await, Operation::Link(Default::default()));
 
     start_client.trigger();
     tx.link().await;
@@ -599,7 +726,7 @@ where
         State::Unlinked,
         DownlinkNotification::Linked,
     );
-    expect_message(rx.recv().await, Operation::Sync);
+    expect_message(rx.recv().await, Operation::Sync(Default::default()));
 
     tx.sync().await;
 
@@ -740,6 +867,66 @@ async fn send_all_message_kinds() {
     );
 }
 
+#[tokio::test]
+async fn receive_batched_events() {
+    let (events, result) = run_test(DownlinkOptions::SYNC, |context| {
+        sync_client_then(context, |context| async move {
+            let SyncedTestContext {
+                mut tx,
+                rx: _rx,
+                stop,
+                mut events,
+                send_tx: _,
+            } = context;
+
+            tx.batch(vec![
+                MapMessage::Update {
+                    key: 1,
+                    value: rec(2, 3),
+                },
+                MapMessage::Remove { key: 4 },
+                MapMessage::Clear,
+            ])
+            .await;
+
+            expect_event(
+                events.next().await,
+                State::Synced,
+                DownlinkNotification::Event {
+                    body: MapMessage::Update {
+                        key: 1,
+                        value: rec(2, 3),
+                    },
+                },
+            );
+            expect_event(
+                events.next().await,
+                State::Synced,
+                DownlinkNotification::Event {
+                    body: MapMessage::Remove { key: 4 },
+                },
+            );
+            expect_event(
+                events.next().await,
+                State::Synced,
+                DownlinkNotification::Event {
+                    body: MapMessage::Clear,
+                },
+            );
+
+            stop.trigger();
+            events
+        })
+    })
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(
+        events,
+        vec![(State::Synced, DownlinkNotification::Unlinked)]
+    );
+}
+
 #[tokio::test]
 async fn shutdowm_after_timeout_with_no_subscribers() {
     let ((_stop, events), result) = run_test_with_config(
@@ -760,7 +947,7 @@ async fn shutdowm_after_timeout_with_no_subscribers() {
              start_client,
              send_tx: _,
          }| async move {
-            expect_message(rx.recv().await, Operation::Link);
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
             drop(start_client);
             (stop, events)
         },
@@ -812,7 +999,7 @@ async fn use_bad_message_strategy() {
                   mut events,
                   ..
               }| async move {
-            expect_message(rx.recv().await, Operation::Link);
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
 
             start_client.trigger();
             tx.link().await;
@@ -822,7 +1009,7 @@ async fn use_bad_message_strategy() {
                 State::Unlinked,
                 DownlinkNotification::Linked,
             );
-            expect_message(rx.recv().await, Operation::Sync);
+            expect_message(rx.recv().await, Operation::Sync(Default::default()));
 
             tx.update_text(Text::new("invalid")).await;
 
@@ -1101,7 +1288,7 @@ async fn sync_both(context: &mut TwoConsumerTestContext) {
         events: second_events,
     } = second_consumer;
 
-    expect_message(rx.recv().await, Operation::Link);
+    expect_message(rx.recv().await, Operation::Link(Default::default()));
 
     if let Some(start) = start_first.take() {
         start.trigger();
@@ -1121,8 +1308,8 @@ async fn sync_both(context: &mut TwoConsumerTestContext) {
         State::Unlinked,
         DownlinkNotification::Linked,
     );
-    expect_message(rx.recv().await, Operation::Sync);
-    expect_message(rx.recv().await, Operation::Sync);
+    expect_message(rx.recv().await, Operation::Sync(Default::default()));
+    expect_message(rx.recv().await, Operation::Sync(Default::default()));
 
     tx.update(1, rec(0, 1)).await;
     tx.sync().await;
@@ -89,6 +89,7 @@ async fn read_task_votes_to_stop_no_subscribers() {
         msg_rx,
         consumers_rx,
         config,
+        false,
         DummyInterp,
         InfallibleStrategy,
         read_voter,
@@ -124,6 +125,7 @@ async fn read_task_rescinds_vote_to_stop() {
         msg_rx,
         consumers_rx,
         config,
+        false,
         DummyInterp,
         InfallibleStrategy,
         read_vote,
@@ -192,7 +194,7 @@ async fn write_task_votes_to_stop_no_subscribers() {
             .await
             .expect("Message channel dropped.")
             .expect("Receive failed.");
-        assert!(matches!(envelope, Operation::Link));
+        assert!(matches!(envelope, Operation::Link(_)));
         vote_rx.await;
     };
 
@@ -232,11 +234,11 @@ async fn write_task_rescinds_stop_vote() {
             .await
             .expect("Message channel dropped.")
             .expect("Receive failed.");
-        assert!(matches!(envelope, Operation::Link));
+        assert!(matches!(envelope, Operation::Link(_)));
         tokio::time::sleep(2 * EMPTY_TIMEOUT).await;
         let (tx, rx) = byte_channel(BUFFER_SIZE);
         producers_tx
-            .send((rx, DownlinkOptions::empty()))
+            .send((rx, DownlinkOptions::empty(), None))
             .await
             .expect("Send failed.");
         let mut sender = FramedWrite::new(tx, DownlinkOperationEncoder::default());
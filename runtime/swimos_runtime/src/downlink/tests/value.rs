@@ -28,6 +28,7 @@ use swimos_agent_protocol::encoding::downlink::{
 use swimos_agent_protocol::{DownlinkNotification, DownlinkOperation};
 use swimos_api::{
     address::RelativeAddress,
+    agent::{LinkFailure, NodeStopped},
     error::{DownlinkTaskError, FrameIoError, InvalidFrame},
 };
 use swimos_form::read::RecognizerReadable;
@@ -265,6 +266,16 @@ impl TestSender {
         self.send(message).await;
     }
 
+    async fn update_from(&mut self, message: Message, origin: Uuid) {
+        let message = ResponseMessage::event(
+            REMOTE_ADDR,
+            RelativeAddress::new(REMOTE_NODE, REMOTE_LANE),
+            message,
+        )
+        .with_command_origin(origin);
+        self.send(message).await;
+    }
+
     async fn update_text(&mut self, message: Text) {
         let message: ResponseMessage<&str, Text, &[u8]> = ResponseMessage::event(
             REMOTE_ADDR,
@@ -347,7 +358,7 @@ async fn shutdowm_none_attached() {
              events,
              ..
          }| async move {
-            expect_message(rx.recv().await, Operation::Link);
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
             stop.trigger();
             events.collect::<Vec<_>>().await
         },
@@ -369,7 +380,7 @@ async fn shutdowm_after_attached() {
              mut events,
              ..
          }| async move {
-            expect_message(rx.recv().await, Operation::Link);
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
 
             start_client.trigger();
             tx.link().await;
@@ -403,7 +414,7 @@ async fn shutdown_after_corrupted_frame() {
             mut events,
             ..
         } = context;
-        expect_message(rx.recv().await, Operation::Link);
+        expect_message(rx.recv().await, Operation::Link(Default::default()));
 
         start_client.trigger();
         tx.link().await;
@@ -437,7 +448,7 @@ async fn sync_from_nothing() {
              mut events,
              ..
          }| async move {
-            expect_message(rx.recv().await, Operation::Link);
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
 
             start_client.trigger();
             tx.link().await;
@@ -447,7 +458,7 @@ async fn sync_from_nothing() {
                 State::Unlinked,
                 DownlinkNotification::Linked,
             );
-            expect_message(rx.recv().await, Operation::Sync);
+            expect_message(rx.recv().await, Operation::Sync(Default::default()));
 
             let message = Message::CurrentValue(Text::new("A"));
             tx.update(message.clone()).await;
@@ -476,6 +487,117 @@ async fn sync_from_nothing() {
     );
 }
 
+#[tokio::test]
+async fn node_stopped_before_unlinked() {
+    let (events, result) = run_test(
+        DownlinkOptions::SYNC,
+        |TestContext {
+             mut tx,
+             mut rx,
+             start_client,
+             stop,
+             mut events,
+             ..
+         }| async move {
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
+
+            start_client.trigger();
+            tx.link().await;
+
+            expect_event(
+                events.next().await,
+                State::Unlinked,
+                DownlinkNotification::Linked,
+            );
+            expect_message(rx.recv().await, Operation::Sync(Default::default()));
+
+            let message = Message::CurrentValue(Text::new("A"));
+            tx.update(message.clone()).await;
+            tx.sync().await;
+
+            expect_event(
+                events.next().await,
+                State::Linked,
+                DownlinkNotification::Event { body: message },
+            );
+            expect_event(
+                events.next().await,
+                State::Linked,
+                DownlinkNotification::Synced,
+            );
+
+            tx.send(ResponseMessage::unlinked(
+                REMOTE_ADDR,
+                RelativeAddress::new(REMOTE_NODE, REMOTE_LANE),
+                Some(b"@nodeStopped(resume:true)".as_slice()),
+            ))
+            .await;
+            // The downlink should stop after being unlinked, without being stopped explicitly.
+            let events = events.collect::<Vec<_>>().await;
+            drop(stop);
+            events
+        },
+    )
+    .await;
+    assert!(result.is_ok());
+    assert_eq!(
+        events,
+        vec![
+            (
+                State::Synced,
+                DownlinkNotification::NodeStopped {
+                    notice: NodeStopped { resume: true }
+                }
+            ),
+            (State::Synced, DownlinkNotification::Unlinked),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn lane_not_found_before_unlinked() {
+    let (events, result) = run_test(
+        DownlinkOptions::SYNC,
+        |TestContext {
+             mut tx,
+             mut rx,
+             start_client,
+             stop,
+             events,
+             ..
+         }| async move {
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
+
+            start_client.trigger();
+
+            tx.send(ResponseMessage::unlinked(
+                REMOTE_ADDR,
+                RelativeAddress::new(REMOTE_NODE, REMOTE_LANE),
+                Some(b"@laneNotFound".as_slice()),
+            ))
+            .await;
+            // The downlink should stop after being unlinked, without being stopped explicitly.
+            let events = events.collect::<Vec<_>>().await;
+            drop(stop);
+            events
+        },
+    )
+    .await;
+    assert!(result.is_ok());
+    assert_eq!(
+        events,
+        vec![
+            (
+                State::Unlinked,
+                DownlinkNotification::Failed {
+                    failure: LinkFailure::LaneNotFound
+                }
+            ),
+            (State::Unlinked, DownlinkNotification::Unlinked),
+        ]
+    );
+}
+
 #[tokio::test]
 async fn sync_after_value() {
     let (events, result) = run_test(
@@ -488,7 +610,7 @@ async fn sync_after_value() {
              mut events,
              ..
          }| async move {
-            expect_message(rx.recv().await, Operation::Link);
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
 
             tx.link().await;
 
@@ -502,7 +624,7 @@ async fn sync_after_value() {
                 State::Unlinked,
                 DownlinkNotification::Linked,
             );
-            expect_message(rx.recv().await, Operation::Sync);
+            expect_message(rx.recv().await, Operation::Sync(Default::default()));
 
             let message2 = Message::CurrentValue(Text::new("B"));
             tx.update(message2.clone()).await;
@@ -543,7 +665,7 @@ where
         stop,
         mut events,
     } = context;
-    expect_message(rx.recv().await, Operation::Link);
+    expect_message(rx.recv().await, Operation::Link(Default::default()));
 
     start_client.trigger();
     tx.link().await;
@@ -553,7 +675,7 @@ where
         State::Unlinked,
         DownlinkNotification::Linked,
     );
-    expect_message(rx.recv().await, Operation::Sync);
+    expect_message(rx.recv().await, Operation::Sync(Default::default()));
 
     let message = Message::CurrentValue(Text::new("A"));
     tx.update(message.clone()).await;
@@ -638,6 +760,56 @@ async fn receive_commands() {
     );
 }
 
+#[tokio::test]
+async fn receive_originated_events() {
+    let (events, result) = run_test(DownlinkOptions::SYNC, |context| {
+        sync_client_then(
+            context,
+            |SyncedTestContext {
+                 mut tx,
+                 stop,
+                 mut events,
+                 ..
+             }| async move {
+                let origin = Uuid::from_u128(77);
+                tx.update_from(Message::CurrentValue(Text::new("B")), origin)
+                    .await;
+                expect_event(
+                    events.next().await,
+                    State::Synced,
+                    DownlinkNotification::Origin { origin },
+                );
+                expect_event(
+                    events.next().await,
+                    State::Synced,
+                    DownlinkNotification::Event {
+                        body: Message::CurrentValue(Text::new("B")),
+                    },
+                );
+
+                tx.update(Message::CurrentValue(Text::new("C"))).await;
+                expect_event(
+                    events.next().await,
+                    State::Synced,
+                    DownlinkNotification::Event {
+                        body: Message::CurrentValue(Text::new("C")),
+                    },
+                );
+                stop.trigger();
+
+                events
+            },
+        )
+    })
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(
+        events,
+        vec![(State::Synced, DownlinkNotification::Unlinked)]
+    );
+}
+
 #[tokio::test]
 async fn handle_failed_consumer() {
     let (events, result) = run_test(DownlinkOptions::SYNC, |context| {
@@ -757,7 +929,7 @@ async fn shutdown_after_timeout_with_no_subscribers() {
              events,
              start_client,
          }| async move {
-            expect_message(rx.recv().await, Operation::Link);
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
             drop(start_client);
             (stop, events)
         },
@@ -937,7 +1109,7 @@ async fn sync_both(context: &mut TwoConsumerTestContext) {
         events: second_events,
     } = second_consumer;
 
-    expect_message(rx.recv().await, Operation::Link);
+    expect_message(rx.recv().await, Operation::Link(Default::default()));
 
     if let Some(start) = start_first.take() {
         start.trigger();
@@ -949,8 +1121,8 @@ async fn sync_both(context: &mut TwoConsumerTestContext) {
 
     expect_text_event(first_events.next().await, DownlinkNotification::Linked);
     expect_text_event(second_events.next().await, DownlinkNotification::Linked);
-    expect_message(rx.recv().await, Operation::Sync);
-    expect_message(rx.recv().await, Operation::Sync);
+    expect_message(rx.recv().await, Operation::Sync(Default::default()));
+    expect_message(rx.recv().await, Operation::Sync(Default::default()));
 
     let content = Text::new("Content");
     tx.update_text(content.clone()).await;
@@ -1101,7 +1273,7 @@ async fn sync_no_event() {
              mut events,
              ..
          }| async move {
-            expect_message(rx.recv().await, Operation::Link);
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
 
             tx.link().await;
 
@@ -1112,7 +1284,7 @@ async fn sync_no_event() {
                 State::Unlinked,
                 DownlinkNotification::Linked,
             );
-            expect_message(rx.recv().await, Operation::Sync);
+            expect_message(rx.recv().await, Operation::Sync(Default::default()));
 
             tx.sync().await;
 
@@ -1146,7 +1318,7 @@ async fn sync_empty_event() {
              mut events,
              ..
          }| async move {
-            expect_message(rx.recv().await, Operation::Link);
+            expect_message(rx.recv().await, Operation::Link(Default::default()));
 
             tx.link().await;
 
@@ -1157,7 +1329,7 @@ async fn sync_empty_event() {
                 State::Unlinked,
                 DownlinkNotification::Linked,
             );
-            expect_message(rx.recv().await, Operation::Sync);
+            expect_message(rx.recv().await, Operation::Sync(Default::default()));
 
             let content = Message::CurrentValue(Text::new(""));
             tx.update(content.clone()).await;
@@ -1185,3 +1357,196 @@ async fn sync_empty_event() {
         vec![(State::Synced, DownlinkNotification::Unlinked)]
     );
 }
+
+const NUM_UPDATES: usize = 2000;
+
+// Reads values from a consumer until the expected final value is received, checking that the
+// values are received in order.
+async fn read_until_final(
+    events: &mut FramedRead<ByteReader, ValueNotificationDecoder<Text>>,
+    final_value: &str,
+) -> usize {
+    let mut count = 0;
+    let mut prev: Option<usize> = None;
+    loop {
+        match events.next().await {
+            Some(Ok(DownlinkNotification::Event { body })) => {
+                count += 1;
+                let i = body.as_str().parse::<usize>().unwrap();
+                if let Some(j) = prev {
+                    assert!(j < i);
+                }
+                prev = Some(i);
+                if body == final_value {
+                    break count;
+                }
+            }
+            ow => panic!("Unexpected result: {:?}", ow),
+        }
+    }
+}
+
+#[tokio::test]
+async fn lagging_consumer_does_not_block_others() {
+    let (attach_tx, attach_rx) = mpsc::channel(CHANNEL_SIZE);
+    let (stop_tx, stop_rx) = trigger::trigger();
+
+    let (in_tx, in_rx) = byte_channel::byte_channel(BUFFER_SIZE);
+    let (out_tx, out_rx) = byte_channel::byte_channel(BUFFER_SIZE);
+
+    let management_task = ValueDownlinkRuntime::new(
+        attach_rx,
+        (out_tx, in_rx),
+        stop_rx,
+        IdentifiedAddress {
+            identity: Uuid::from_u128(1),
+            address: RelativeAddress::text("/node", "lane"),
+        },
+        DownlinkRuntimeConfig {
+            empty_timeout: EMPTY_TIMEOUT,
+            attachment_queue_size: ATT_QUEUE_SIZE,
+            abort_on_bad_frames: true,
+            remote_buffer_size: BUFFER_SIZE,
+            downlink_buffer_size: BUFFER_SIZE,
+        },
+    )
+    .conflating()
+    .run();
+
+    let test_task = async move {
+        let mut tx = TestSender::new(in_tx);
+        let mut rx = TestReceiver::<Text>::new(out_rx);
+
+        let mut consumers = vec![];
+        for _ in 0..2 {
+            let (tx_in, rx_in) = byte_channel::byte_channel(BUFFER_SIZE);
+            let (tx_out, rx_out) = byte_channel::byte_channel(BUFFER_SIZE);
+            assert!(attach_tx
+                .send(AttachAction::new((tx_in, rx_out), DownlinkOptions::SYNC))
+                .await
+                .is_ok());
+            consumers.push((
+                FramedRead::new(rx_in, ValueNotificationDecoder::<Text>::default()),
+                tx_out,
+            ));
+        }
+        let (mut lagging, _lagging_tx) = consumers.pop().unwrap();
+        let (mut active, _active_tx) = consumers.pop().unwrap();
+
+        expect_message(rx.recv().await, Operation::Link(Default::default()));
+        tx.link().await;
+        expect_message(rx.recv().await, Operation::Sync(Default::default()));
+        expect_message(rx.recv().await, Operation::Sync(Default::default()));
+        tx.sync().await;
+        for expected in [DownlinkNotification::Linked, DownlinkNotification::Synced] {
+            assert_eq!(active.next().await.transpose().unwrap(), Some(expected));
+        }
+
+        // The lagging consumer never reads while the updates are sent so, if it were able to
+        // block the runtime, this would never complete.
+        let final_value = (NUM_UPDATES - 1).to_string();
+        let send_updates = async {
+            for i in 0..NUM_UPDATES {
+                tx.update_text(Text::from(i.to_string())).await;
+            }
+        };
+        let (_, active_count) = join(
+            send_updates,
+            read_until_final(&mut active, final_value.as_str()),
+        )
+        .await;
+        assert!(active_count > 0);
+
+        // When the lagging consumer resumes reading it skips to the latest value.
+        for expected in [DownlinkNotification::Linked, DownlinkNotification::Synced] {
+            assert_eq!(lagging.next().await.transpose().unwrap(), Some(expected));
+        }
+        let lagging_count = read_until_final(&mut lagging, final_value.as_str()).await;
+        assert!(lagging_count < NUM_UPDATES);
+
+        stop_tx.trigger();
+        for mut consumer in [active, lagging] {
+            assert!(matches!(
+                consumer.next().await,
+                Some(Ok(DownlinkNotification::Unlinked))
+            ));
+        }
+    };
+
+    timeout(TEST_TIMEOUT, join(management_task, test_task))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn non_conflating_runtime_delivers_every_event() {
+    let (attach_tx, attach_rx) = mpsc::channel(CHANNEL_SIZE);
+    let (stop_tx, stop_rx) = trigger::trigger();
+
+    let (in_tx, in_rx) = byte_channel::byte_channel(BUFFER_SIZE);
+    let (out_tx, out_rx) = byte_channel::byte_channel(BUFFER_SIZE);
+
+    let management_task = ValueDownlinkRuntime::new(
+        attach_rx,
+        (out_tx, in_rx),
+        stop_rx,
+        IdentifiedAddress {
+            identity: Uuid::from_u128(1),
+            address: RelativeAddress::text("/node", "lane"),
+        },
+        DownlinkRuntimeConfig {
+            empty_timeout: EMPTY_TIMEOUT,
+            attachment_queue_size: ATT_QUEUE_SIZE,
+            abort_on_bad_frames: true,
+            remote_buffer_size: BUFFER_SIZE,
+            downlink_buffer_size: BUFFER_SIZE,
+        },
+    )
+    .run();
+
+    let test_task = async move {
+        let mut tx = TestSender::new(in_tx);
+        let mut rx = TestReceiver::<Text>::new(out_rx);
+
+        let (tx_in, rx_in) = byte_channel::byte_channel(BUFFER_SIZE);
+        let (tx_out, rx_out) = byte_channel::byte_channel(BUFFER_SIZE);
+        assert!(attach_tx
+            .send(AttachAction::new((tx_in, rx_out), DownlinkOptions::empty()))
+            .await
+            .is_ok());
+        let mut consumer = FramedRead::new(rx_in, ValueNotificationDecoder::<Text>::default());
+
+        expect_message(rx.recv().await, Operation::Link(Default::default()));
+        tx.link().await;
+        assert_eq!(
+            consumer.next().await.transpose().unwrap(),
+            Some(DownlinkNotification::Linked)
+        );
+
+        // The consumer falls behind the updates so, if the runtime were conflating, it would
+        // skip some of them.
+        let final_value = (NUM_UPDATES - 1).to_string();
+        let send_updates = async {
+            for i in 0..NUM_UPDATES {
+                tx.update_text(Text::from(i.to_string())).await;
+            }
+        };
+        let (_, count) = join(
+            send_updates,
+            read_until_final(&mut consumer, final_value.as_str()),
+        )
+        .await;
+        assert_eq!(count, NUM_UPDATES);
+
+        stop_tx.trigger();
+        assert!(matches!(
+            consumer.next().await,
+            Some(Ok(DownlinkNotification::Unlinked))
+        ));
+        drop(tx_out);
+    };
+
+    timeout(TEST_TIMEOUT, join(management_task, test_task))
+        .await
+        .unwrap();
+}
@@ -23,6 +23,8 @@ use swimos_utilities::byte_channel::{ByteReader, ByteWriter};
 /// The agent runtime task.
 pub mod agent;
 mod backpressure;
+/// Validation of runtime configuration parameters.
+pub mod config;
 /// The downlink runtime task.
 pub mod downlink;
 mod timeout_coord;
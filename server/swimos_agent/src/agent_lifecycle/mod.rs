@@ -23,9 +23,9 @@ pub mod on_init;
 /// The `on_start` event handler is executed when an agent starts. The signature of the event is
 /// described by the [`on_start::OnStart`] trait.
 pub mod on_start;
-/// The `on_stop` event handler is executed when an agent stops. No more event handlers will be executed
-/// after execution of this handler stops. The signature of the event is described by the
-/// [`on_stop::OnStop`] trait.
+/// The `on_stop` event handler is executed when an agent stops and is passed the reason that the agent
+/// is stopping. No more event handlers will be executed after execution of this handler stops. The
+/// signature of the event is described by the [`on_stop::OnStop`] trait.
 pub mod on_stop;
 mod stateful;
 mod utility;
@@ -45,4 +45,5 @@ impl<L, Context> AgentLifecycle<Context> for L where
 }
 
 pub use stateful::StatefulAgentLifecycle;
+pub use swimos_api::agent::StopReason;
 pub use utility::*;
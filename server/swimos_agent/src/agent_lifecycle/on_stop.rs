@@ -12,9 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use swimos_api::agent::StopReason;
 use swimos_utilities::handlers::{FnHandler, NoHandler};
 
-use crate::event_handler::{EventHandler, HandlerFn0, UnitHandler};
+use crate::event_handler::{EventConsumeFn, EventHandler, HandlerFn0, UnitHandler};
 
 use super::utility::HandlerContext;
 
@@ -24,7 +25,9 @@ pub trait OnStop<Context>: Send {
     where
         Self: 'a;
 
-    fn on_stop(&self) -> Self::OnStopHandler<'_>;
+    /// # Arguments
+    /// * `reason` - The reason that the agent is stopping.
+    fn on_stop(&self, reason: StopReason) -> Self::OnStopHandler<'_>;
 }
 
 /// Lifecycle event for the `on_stop` event of an agent where the event handler
@@ -38,19 +41,26 @@ pub trait OnStopShared<Context, Shared>: Send {
     /// # Arguments
     /// * `shared` - The shared state.
     /// * `handler_context` - Utility for constructing event handlers.
+    /// * `reason` - The reason that the agent is stopping.
     fn on_stop<'a>(
         &'a self,
         shared: &'a Shared,
         handler_context: HandlerContext<Context>,
+        reason: StopReason,
     ) -> Self::OnStopHandler<'a>;
 }
 
+/// Wraps a function that creates an `on_stop` event handler from the reason that the agent is
+/// stopping. (A function wrapped in [`FnHandler`] ignores the reason.)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WithReason<F>(pub F);
+
 impl<Context> OnStop<Context> for NoHandler {
     type OnStopHandler<'a> = UnitHandler
     where
         Self: 'a;
 
-    fn on_stop(&self) -> Self::OnStopHandler<'_> {
+    fn on_stop(&self, _reason: StopReason) -> Self::OnStopHandler<'_> {
         Default::default()
     }
 }
@@ -65,6 +75,7 @@ impl<Context, Shared> OnStopShared<Context, Shared> for NoHandler {
         &'a self,
         _shared: &'a Shared,
         _handler_context: HandlerContext<Context>,
+        _reason: StopReason,
     ) -> Self::OnStopHandler<'a> {
         Default::default()
     }
@@ -79,12 +90,27 @@ where
     where
         Self: 'a;
 
-    fn on_stop(&self) -> Self::OnStopHandler<'_> {
+    fn on_stop(&self, _reason: StopReason) -> Self::OnStopHandler<'_> {
         let FnHandler(f) = self;
         f()
     }
 }
 
+impl<Context, F, H> OnStop<Context> for WithReason<F>
+where
+    F: Fn(StopReason) -> H + Send,
+    H: EventHandler<Context> + 'static,
+{
+    type OnStopHandler<'a> = H
+    where
+        Self: 'a;
+
+    fn on_stop(&self, reason: StopReason) -> Self::OnStopHandler<'_> {
+        let WithReason(f) = self;
+        f(reason)
+    }
+}
+
 impl<Context, F, Shared> OnStopShared<Context, Shared> for FnHandler<F>
 where
     F: for<'a> HandlerFn0<'a, Context, Shared> + Send,
@@ -98,8 +124,29 @@ where
         &'a self,
         shared: &'a Shared,
         handler_context: HandlerContext<Context>,
+        _reason: StopReason,
     ) -> Self::OnStopHandler<'a> {
         let FnHandler(f) = self;
         f.make_handler(shared, handler_context)
     }
 }
+
+impl<Context, F, Shared> OnStopShared<Context, Shared> for WithReason<F>
+where
+    F: for<'a> EventConsumeFn<'a, Context, Shared, StopReason> + Send,
+{
+    type OnStopHandler<'a> = <F as EventConsumeFn<'a, Context, Shared, StopReason>>::Handler
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_stop<'a>(
+        &'a self,
+        shared: &'a Shared,
+        handler_context: HandlerContext<Context>,
+        reason: StopReason,
+    ) -> Self::OnStopHandler<'a> {
+        let WithReason(f) = self;
+        f.make_handler(shared, handler_context, reason)
+    }
+}
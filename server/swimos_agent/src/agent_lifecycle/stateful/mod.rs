@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use static_assertions::assert_impl_all;
+use swimos_api::agent::StopReason;
 use swimos_utilities::handlers::{FnHandler, NoHandler};
 
 use crate::{agent_lifecycle::AgentLifecycle, event_handler::ActionContext, meta::AgentMetadata};
@@ -21,7 +22,7 @@ use super::{
     item_event::{ItemEvent, ItemEventShared},
     on_init::{OnInit, OnInitShared},
     on_start::{OnStart, OnStartShared},
-    on_stop::{OnStop, OnStopShared},
+    on_stop::{OnStop, OnStopShared, WithReason},
     utility::HandlerContext,
 };
 
@@ -136,14 +137,14 @@ where
     where
         Self: 'a;
 
-    fn on_stop(&self) -> Self::OnStopHandler<'_> {
+    fn on_stop(&self, reason: StopReason) -> Self::OnStopHandler<'_> {
         let StatefulAgentLifecycle {
             state,
             handler_context,
             on_stop,
             ..
         } = self;
-        on_stop.on_stop(state, *handler_context)
+        on_stop.on_stop(state, *handler_context, reason)
     }
 }
 
@@ -230,6 +231,33 @@ impl<Context, State, FInit, FStart, FStop, ItemEv>
         }
     }
 
+    /// Replace the `on_stop` handler with another defined using a closure that is passed the
+    /// reason that the agent is stopping.
+    pub fn on_stop_with_reason<F>(
+        self,
+        f: F,
+    ) -> StatefulAgentLifecycle<Context, State, FInit, FStart, WithReason<F>, ItemEv>
+    where
+        WithReason<F>: OnStopShared<Context, State>,
+    {
+        let StatefulAgentLifecycle {
+            handler_context,
+            state,
+            on_init,
+            on_start,
+            item_event,
+            ..
+        } = self;
+        StatefulAgentLifecycle {
+            handler_context,
+            state,
+            on_init,
+            on_start,
+            on_stop: WithReason(f),
+            item_event,
+        }
+    }
+
     /// Replace the `on_stop` handler with another defined using a closure.
     pub fn on_stop<F>(
         self,
@@ -16,42 +16,78 @@ use std::any::Any;
 use std::borrow::Borrow;
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::ops::{Add, RangeBounds};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use std::{collections::HashMap, marker::PhantomData};
+use std::{
+    collections::{BTreeMap, HashMap},
+    marker::PhantomData,
+};
 
 use futures::stream::unfold;
 use futures::{Future, FutureExt, Stream, StreamExt};
 use tokio::time::Instant;
+use uuid::Uuid;
 
+use swimos_agent_protocol::{MapMessage, MapOperation};
 use swimos_api::address::Address;
+use swimos_api::agent::{LaneConfig, WarpLaneKind};
+use swimos_api::error::DownlinkRuntimeError;
 use swimos_form::read::RecognizerReadable;
 use swimos_form::write::StructuralWritable;
 use swimos_form::Form;
+use swimos_model::Text;
 use swimos_utilities::routing::RouteUri;
 
-use crate::agent_model::downlink::{EventDownlinkHandle, MapDownlinkHandle, ValueDownlinkHandle};
 use crate::agent_model::downlink::{
-    OpenEventDownlinkAction, OpenMapDownlinkAction, OpenValueDownlinkAction,
+    CommandDownlinkHandle, EventDownlinkHandle, MapDownlinkHandle, ValueDownlinkHandle,
+};
+use crate::agent_model::downlink::{
+    OpenCommandDownlinkAction, OpenEventDownlinkAction, OpenMapDownlinkAction,
+    OpenValueDownlinkAction,
 };
-use crate::config::{MapDownlinkConfig, SimpleDownlinkConfig};
+use crate::config::{CommandDownlinkConfig, MapDownlinkConfig, SimpleDownlinkConfig};
+use crate::derived::{Ewma, HasChanged, RateMeter, SampleRate};
 use crate::downlink_lifecycle::ValueDownlinkLifecycle;
-use crate::downlink_lifecycle::{EventDownlinkLifecycle, MapDownlinkLifecycle};
+use crate::downlink_lifecycle::{
+    downlink_event_stream, DownlinkEventStream, EventDownlinkEvent, EventDownlinkLifecycle,
+    MapDownlinkEvent, MapDownlinkLifecycle, ValueDownlinkEvent,
+};
 use crate::event_handler::{
-    run_after, run_schedule, run_schedule_async, ConstHandler, EventHandler, GetParameter,
-    HandlerActionExt, SendCommand, Sequentially, Stop, Suspend, UnitHandler,
+    run_after, run_in_batches, run_schedule, run_schedule_async, suspend_with_timeout,
+    CancellationHandle, CommandAckError, ConstHandler, EventHandler, GetParameter,
+    HandlerActionExt, LaneSpawnError, OpenLane, SendCommand, SendCommandWithAck, Sequentially,
+    Stop, Suspend, SuspendCancellable, UnitHandler,
 };
-use crate::event_handler::{GetAgentUri, HandlerAction, SideEffect};
+use crate::event_handler::{
+    GetAgentUri, GetCommandOrigin, GetCorrelationId, HandlerAction, SideEffect,
+};
+use crate::feature_flags::GetFeatureFlag;
 use crate::item::{
-    InspectableMapLikeItem, JoinLikeItem, MapLikeItem, MutableMapLikeItem, MutableValueLikeItem,
-    ValueLikeItem,
+    EventCount, InspectableMapLikeItem, JoinLikeItem, MapLikeItem, MutableMapLikeItem,
+    MutableValueLikeItem, ValueLikeItem,
 };
 use crate::lanes::command::{CommandLane, DoCommand};
 use crate::lanes::demand::{Cue, DemandLane};
 use crate::lanes::demand_map::CueKey;
+use crate::lanes::dynamic::{
+    AttachDynamicLane, DynamicMapLaneApply, DynamicMapLaneGet, DynamicValueLaneGet,
+    DynamicValueLaneSet,
+};
+use crate::lanes::history::{
+    HistoryLane, HistoryLanePush, HistoryLaneRange, HistoryLaneSetRetention, HistoryRetention,
+};
 use crate::lanes::join_map::JoinMapAddDownlink;
 use crate::lanes::join_value::{JoinValueAddDownlink, JoinValueLane};
+use crate::lanes::map::MapLaneTransaction;
+use crate::lanes::ordered_map::{OrderedMapLane, OrderedMapLaneGet, OrderedMapLaneGetMap};
+use crate::lanes::stats::{Stats, StatsLane, StatsLaneGet, StatsLanePush, StatsLaneSetWindow};
 use crate::lanes::supply::{Supply, SupplyLane};
-use crate::lanes::{DemandMapLane, JoinMapLane};
+use crate::lanes::value::{
+    TransactionLanes, ValueLane, ValueLaneCompareAndSet, ValueLaneModify, ValueLaneTransaction,
+};
+use crate::lanes::{DemandMapLane, DynamicMapLanes, DynamicValueLanes, JoinMapLane, MapLane};
 
 pub use self::downlink_builder::event::{
     StatefulEventDownlinkBuilder, StatelessEventDownlinkBuilder,
@@ -139,6 +175,36 @@ impl<Agent: 'static> HandlerContext<Agent> {
         SendCommand::new(addr, command, true)
     }
 
+    /// Send a command to a lane (either on a remote host or locally to an agent on the same plane)
+    /// and run another event handler when the command has been passed to the runtime. Unlike
+    /// [`Self::send_command`], the command will not be overwritten if another command is sent to the
+    /// same lane before it has been sent.
+    ///
+    /// # Arguments
+    /// * `host` - The target remote host or [`None`] for an agent in the same plane.
+    /// * `node` - The target node hosting the lane.
+    /// * `lane` - The name of the target lane.
+    /// * `command` - The value to send.
+    /// * `on_ack` - Creates an event handler to run when the command has been passed to the runtime
+    ///   (or failed to be).
+    pub fn send_command_with_ack<'a, S, T, F, H>(
+        &self,
+        host: Option<S>,
+        node: S,
+        lane: S,
+        command: T,
+        on_ack: F,
+    ) -> impl EventHandler<Agent> + 'a
+    where
+        S: AsRef<str> + 'a,
+        T: StructuralWritable + 'a,
+        F: FnOnce(Result<(), CommandAckError>) -> H + Send + 'static,
+        H: EventHandler<Agent> + Send + 'static,
+    {
+        let addr = Address::new(host, node, lane);
+        SendCommandWithAck::new(addr, command, on_ack)
+    }
+
     /// Create an event handler that will fetch the metadata of the agent instance.
     pub fn get_agent_uri(
         &self,
@@ -146,6 +212,28 @@ impl<Agent: 'static> HandlerContext<Agent> {
         GetAgentUri::default()
     }
 
+    /// Create an event handler that will get the ID of the remote that sent the command that is
+    /// currently being handled. This will only be available in handlers that run as a consequence of
+    /// the command (such as the `on_update` handler of a map lane) and only for lanes that are
+    /// configured to track the origins of commands (see [`swimos_api::agent::LaneConfig`]). The
+    /// event handlers of a downlink to such a lane can also get the ID of the remote that sent
+    /// the command that caused the event that they are handling.
+    pub fn command_origin(
+        &self,
+    ) -> impl HandlerAction<Agent, Completion = Option<Uuid>> + Send + 'static {
+        GetCommandOrigin::default()
+    }
+
+    /// Create an event handler that will get the correlation ID of the envelope that contained
+    /// the command that is currently being handled. This will only be available in handlers that
+    /// run as a consequence of the command. The events that the command causes are tagged with
+    /// the same ID when they are sent to the remotes that are linked to the lane.
+    pub fn correlation_id(
+        &self,
+    ) -> impl HandlerAction<Agent, Completion = Option<u64>> + Send + 'static {
+        GetCorrelationId::default()
+    }
+
     /// Get the value of a parameter extracted from the route URI of the agent instance.
     /// # Arguments
     /// * `name` - The name of the parameter.
@@ -208,6 +296,130 @@ impl<Agent: 'static> HandlerContext<Agent> {
             .and_then(move |v| Item::set_handler(item, v))
     }
 
+    /// Create an event handler that will set a new value into a value lane of the agent, if its current
+    /// value is equal to an expected value. The check and the update are made in a single step so no
+    /// other handler can modify the lane in between. The handler completes with whether the lane was
+    /// modified and an event is only emitted if it was.
+    ///
+    /// #Arguments
+    /// * `lane` - Projection to the value lane.
+    /// * `expected` - The value that the lane must have for it to be modified.
+    /// * `new` - The value to set.
+    pub fn compare_and_set<T>(
+        &self,
+        lane: fn(&Agent) -> &ValueLane<T>,
+        expected: T,
+        new: T,
+    ) -> impl HandlerAction<Agent, Completion = bool> + Send + 'static
+    where
+        Agent: 'static,
+        T: PartialEq + Send + 'static,
+    {
+        ValueLaneCompareAndSet::new(lane, expected, new)
+    }
+
+    /// Create an event handler that will add an amount to the value of a numeric value lane of the agent,
+    /// in a single step, emitting a single event. The handler completes with the new value of the lane.
+    ///
+    /// #Arguments
+    /// * `lane` - Projection to the value lane.
+    /// * `amount` - The amount to add.
+    pub fn add_value<T>(
+        &self,
+        lane: fn(&Agent) -> &ValueLane<T>,
+        amount: T,
+    ) -> impl HandlerAction<Agent, Completion = T> + Send + 'static
+    where
+        Agent: 'static,
+        T: Add<Output = T> + Clone + Send + 'static,
+    {
+        ValueLaneModify::new(lane, move |current: &T| Some(current.clone() + amount))
+    }
+
+    /// Create an event handler that will set the value of a value lane of the agent to the maximum of its
+    /// current value and another value, in a single step. An event is only emitted if the value of the lane
+    /// changes. The handler completes with the new value of the lane.
+    ///
+    /// #Arguments
+    /// * `lane` - Projection to the value lane.
+    /// * `value` - The value to compare with the value of the lane.
+    pub fn max_value<T>(
+        &self,
+        lane: fn(&Agent) -> &ValueLane<T>,
+        value: T,
+    ) -> impl HandlerAction<Agent, Completion = T> + Send + 'static
+    where
+        Agent: 'static,
+        T: PartialOrd + Clone + Send + 'static,
+    {
+        ValueLaneModify::new(lane, move |current: &T| (value > *current).then_some(value))
+    }
+
+    /// Create an event handler that will set the value of a value lane of the agent to the minimum of its
+    /// current value and another value, in a single step. An event is only emitted if the value of the lane
+    /// changes. The handler completes with the new value of the lane.
+    ///
+    /// #Arguments
+    /// * `lane` - Projection to the value lane.
+    /// * `value` - The value to compare with the value of the lane.
+    pub fn min_value<T>(
+        &self,
+        lane: fn(&Agent) -> &ValueLane<T>,
+        value: T,
+    ) -> impl HandlerAction<Agent, Completion = T> + Send + 'static
+    where
+        Agent: 'static,
+        T: PartialOrd + Clone + Send + 'static,
+    {
+        ValueLaneModify::new(lane, move |current: &T| (value < *current).then_some(value))
+    }
+
+    /// Create an event handler that will update several value lanes of the agent as a single step. The
+    /// new values are computed from the current values of the lanes and, if this succeeds, all of the
+    /// lanes are set before the lifecycle events of any of them are triggered. If the computation
+    /// fails, none of the lanes are modified and the handler fails with the error.
+    ///
+    /// #Arguments
+    /// * `lanes` - A tuple of between two and four projections to value lanes.
+    /// * `f` - A closure that produces the new values of the lanes from their current values.
+    pub fn transaction<L, F, E>(
+        &self,
+        lanes: L,
+        f: F,
+    ) -> impl HandlerAction<Agent, Completion = ()> + Send + 'static
+    where
+        Agent: 'static,
+        L: TransactionLanes<Agent> + Send + 'static,
+        F: FnOnce(L::Values) -> Result<L::Values, E> + Send + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        ValueLaneTransaction::new(lanes, f)
+    }
+
+    /// Create an event handler that will apply several operations to a map lane of the agent as a single
+    /// step. The operations are computed from the current state of the map and, if this succeeds, they are
+    /// all applied together and sent to remotes linked to the lane as a single batch. The lifecycle event
+    /// handlers of the lane are not run for the operations. If the computation fails, the lane is not
+    /// modified and the handler fails with the error.
+    ///
+    /// #Arguments
+    /// * `lane` - Projection to the map lane.
+    /// * `f` - A closure that produces the operations to apply from the current state of the map.
+    pub fn map_transaction<K, V, F, E>(
+        &self,
+        lane: fn(&Agent) -> &MapLane<K, V>,
+        f: F,
+    ) -> impl HandlerAction<Agent, Completion = ()> + Send + 'static
+    where
+        Agent: 'static,
+        K: Clone + Eq + Hash + Send + 'static,
+        V: Send + 'static,
+        F: FnOnce(&HashMap<K, V>) -> Result<Vec<MapOperation<K, V>>, E> + Send + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        MapLaneTransaction::new(lane, f)
+    }
+
     /// Create an event handler that will inspect the value of a value lane or store and generate a result from it.
     /// This differs from using [`Self::get_value`] in that it does not require a clone to be made of the existing value.
     ///
@@ -455,6 +667,131 @@ impl<Agent: 'static> HandlerContext<Agent> {
         Supply::new(lane, value)
     }
 
+    /// Create an event handler that will add a value to a history lane. The handler will complete
+    /// with the timestamp that was assigned to the new entry.
+    ///
+    /// # Arguments
+    /// * `lane` - Projection to the history lane.
+    /// * `value` - The value to add.
+    pub fn push_history<T>(
+        &self,
+        lane: fn(&Agent) -> &HistoryLane<T>,
+        value: T,
+    ) -> impl HandlerAction<Agent, Completion = u64> + Send + 'static
+    where
+        T: Send + 'static,
+    {
+        HistoryLanePush::new(lane, value)
+    }
+
+    /// Create an event handler that will get the entries of a history lane with timestamps (in
+    /// milliseconds since the UNIX epoch) in a range, ordered from oldest to newest.
+    ///
+    /// # Arguments
+    /// * `lane` - Projection to the history lane.
+    /// * `range` - The range of timestamps.
+    pub fn history_range<T, R>(
+        &self,
+        lane: fn(&Agent) -> &HistoryLane<T>,
+        range: R,
+    ) -> impl HandlerAction<Agent, Completion = Vec<(u64, T)>> + Send + 'static
+    where
+        T: Clone + Send + 'static,
+        R: RangeBounds<u64>,
+    {
+        HistoryLaneRange::new(lane, range)
+    }
+
+    /// Create an event handler that will replace the retention policy of a history lane, evicting
+    /// any entries that do not satisfy the new policy. This will typically be executed from the
+    /// `on_start` handler of the agent.
+    ///
+    /// # Arguments
+    /// * `lane` - Projection to the history lane.
+    /// * `retention` - The new retention policy.
+    pub fn set_history_retention<T>(
+        &self,
+        lane: fn(&Agent) -> &HistoryLane<T>,
+        retention: HistoryRetention,
+    ) -> impl EventHandler<Agent> + Send + 'static
+    where
+        T: Send + 'static,
+    {
+        HistoryLaneSetRetention::new(lane, retention)
+    }
+
+    /// Create an event handler that will get the value of an entry of an ordered map lane.
+    ///
+    /// # Arguments
+    /// * `lane` - Projection to the ordered map lane.
+    /// * `key` - The key to fetch.
+    pub fn get_ordered_entry<K, V>(
+        &self,
+        lane: fn(&Agent) -> &OrderedMapLane<K, V>,
+        key: K,
+    ) -> impl HandlerAction<Agent, Completion = Option<V>> + Send + 'static
+    where
+        K: Clone + Ord + Hash + Send + 'static,
+        V: Clone + Send + 'static,
+    {
+        OrderedMapLaneGet::new(lane, key)
+    }
+
+    /// Create an event handler that will get the entire state of an ordered map lane.
+    ///
+    /// # Arguments
+    /// * `lane` - Projection to the ordered map lane.
+    pub fn get_ordered_map<K, V>(
+        &self,
+        lane: fn(&Agent) -> &OrderedMapLane<K, V>,
+    ) -> impl HandlerAction<Agent, Completion = BTreeMap<K, V>> + Send + 'static
+    where
+        K: Clone + Ord + Hash + Send + 'static,
+        V: Clone + Send + 'static,
+    {
+        OrderedMapLaneGetMap::new(lane)
+    }
+
+    /// Create an event handler that will add a sample to a stats lane. The handler will complete
+    /// with the statistics for the updated window.
+    ///
+    /// # Arguments
+    /// * `lane` - Projection to the stats lane.
+    /// * `sample` - The sample to add.
+    pub fn push_sample(
+        &self,
+        lane: fn(&Agent) -> &StatsLane,
+        sample: f64,
+    ) -> impl HandlerAction<Agent, Completion = Stats> + Send + 'static {
+        StatsLanePush::new(lane, sample)
+    }
+
+    /// Create an event handler that will get the statistics for the current window of a stats lane.
+    ///
+    /// # Arguments
+    /// * `lane` - Projection to the stats lane.
+    pub fn get_stats(
+        &self,
+        lane: fn(&Agent) -> &StatsLane,
+    ) -> impl HandlerAction<Agent, Completion = Stats> + Send + 'static {
+        StatsLaneGet::new(lane)
+    }
+
+    /// Create an event handler that will replace the window over which a stats lane computes its
+    /// statistics, discarding any samples that are no longer included. This will typically be
+    /// executed from the `on_start` handler of the agent.
+    ///
+    /// # Arguments
+    /// * `lane` - Projection to the stats lane.
+    /// * `window` - The new window.
+    pub fn set_stats_window(
+        &self,
+        lane: fn(&Agent) -> &StatsLane,
+        window: HistoryRetention,
+    ) -> impl EventHandler<Agent> + Send + 'static {
+        StatsLaneSetWindow::new(lane, window)
+    }
+
     /// Suspend a future to be executed by the agent task. The future must result in another
     /// event handler that will be executed by the agent upon completion.
     pub fn suspend<Fut, H>(&self, future: Fut) -> impl EventHandler<Agent> + Send + 'static
@@ -465,6 +802,28 @@ impl<Agent: 'static> HandlerContext<Agent> {
         Suspend::new(future)
     }
 
+    /// Suspend a future, that can be cancelled, to be executed by the agent task. The handler
+    /// completes with a [`CancellationHandle`] that can be used to cancel the future. If it is
+    /// cancelled before it completes (which will happen automatically when the agent stops), the
+    /// future is discarded and the `on_cancelled` handler is executed instead. When the agent stops,
+    /// the `on_cancelled` handlers are executed before the `on_stop` handler of the agent.
+    ///
+    /// # Arguments
+    /// * `future` - The future to be suspended.
+    /// * `on_cancelled` - The handler to run if the future is cancelled.
+    pub fn suspend_cancellable<Fut, H, H2>(
+        &self,
+        future: Fut,
+        on_cancelled: H2,
+    ) -> impl HandlerAction<Agent, Completion = CancellationHandle> + Send + 'static
+    where
+        Fut: Future<Output = H> + Send + 'static,
+        H: EventHandler<Agent> + 'static,
+        H2: EventHandler<Agent> + Send + 'static,
+    {
+        SuspendCancellable::new(future, on_cancelled)
+    }
+
     /// Suspend an [`EventHandler`] to be executed after a fixed duration.
     ///
     /// # Note
@@ -486,6 +845,28 @@ impl<Agent: 'static> HandlerContext<Agent> {
         run_after(delay, handler)
     }
 
+    /// Suspend a future to be executed by the agent task, bounding the time that it may take. If the
+    /// future completes in time, the event handler that it results in will be executed by the agent.
+    /// Otherwise, the future is discarded and the `on_timeout` handler will be executed instead.
+    ///
+    /// # Arguments
+    /// * `timeout` - The maximum time to wait for the future.
+    /// * `future` - The future to be suspended.
+    /// * `on_timeout` - The handler to run if the future does not complete in time.
+    pub fn suspend_with_timeout<Fut, H, H2>(
+        &self,
+        timeout: Duration,
+        future: Fut,
+        on_timeout: H2,
+    ) -> impl EventHandler<Agent> + Send + 'static
+    where
+        Fut: Future<Output = H> + Send + 'static,
+        H: EventHandler<Agent> + 'static,
+        H2: EventHandler<Agent> + Send + 'static,
+    {
+        suspend_with_timeout(timeout, future, on_timeout)
+    }
+
     /// Run a (potentially infinite) sequence of [`EventHandler`]s on a schedule. For each pair of a duration
     /// and handler produced by the iterator the handler will be scheduled to run after the delay.
     /// This is the most general scheduling handler and it will often be possible to achieve simpler
@@ -549,6 +930,31 @@ impl<Agent: 'static> HandlerContext<Agent> {
         self.run_schedule(handlers.into_iter().map(move |h| (delay, h)))
     }
 
+    /// Run a (potentially very long) sequence of [`EventHandler`]s in batches. Between each batch, the
+    /// agent will yield to allow other events to be processed, so that a long sequence of handlers does
+    /// not prevent the agent from responding to its other lanes.
+    ///
+    /// # Note
+    ///
+    /// Both the iterator and the handlers must be [`Send`] as the task running the agent could be moved
+    /// to another thread while they are still in use.
+    ///
+    /// # Arguments
+    /// * `handlers` - An iterator returning a sequence of handlers.
+    /// * `batch_size` - The maximum number of handlers to run before yielding.
+    pub fn run_in_batches<I, H>(
+        &self,
+        handlers: I,
+        batch_size: NonZeroUsize,
+    ) -> impl EventHandler<Agent> + Send + 'static
+    where
+        I: IntoIterator<Item = H> + 'static,
+        I::IntoIter: Send + 'static,
+        H: EventHandler<Agent> + Send + 'static,
+    {
+        run_in_batches(handlers, batch_size)
+    }
+
     /// Schedule a (potentially infinite) sequence of futures (each resulting in an [`EventHandler`]) to
     /// run with a fixed delay between them. The delay is computed when the future starts executing so if
     /// a futures takes longer than the delay to complete, the next will start immediately.
@@ -635,6 +1041,107 @@ impl<Agent: 'static> HandlerContext<Agent> {
         self.suspend_handlers_with_delay(delay, std::iter::from_fn(f))
     }
 
+    /// Periodically compute the rate (in events per second) at which a lane or store is modified
+    /// and write it into a value lane or store. The rate is smoothed by an exponentially weighted
+    /// moving average. The source item maintains a running count of its events so no event handler
+    /// needs to be attached to it. This will typically be started from the `on_start` handler of
+    /// the agent.
+    ///
+    /// # Arguments
+    /// * `source` - Projection to the lane or store to monitor.
+    /// * `target` - Projection to the value lane or store that will hold the rate.
+    /// * `half_life` - The half-life of the moving average applied to the rate.
+    /// * `period` - The interval between samples of the source.
+    pub fn track_rate<Source, Item>(
+        &self,
+        source: fn(&Agent) -> &Source,
+        target: fn(&Agent) -> &Item,
+        half_life: Duration,
+        period: Duration,
+    ) -> impl EventHandler<Agent> + Send + 'static
+    where
+        Source: EventCount + 'static,
+        Item: MutableValueLikeItem<f64> + 'static,
+    {
+        let meter = Arc::new(Mutex::new(RateMeter::new(half_life)));
+        let start = SampleRate::new(source, meter.clone()).discard();
+        let samples = self.schedule_repeatedly(period, move || {
+            let handler = SampleRate::new(source, meter.clone())
+                .and_then(move |rate: Option<f64>| rate.map(|r| Item::set_handler(target, r)))
+                .discard();
+            Some(handler)
+        });
+        start.followed_by(samples)
+    }
+
+    /// Periodically sample a numeric value lane or store and write an exponentially weighted moving
+    /// average of its value into another value lane or store. This will typically be started from
+    /// the `on_start` handler of the agent.
+    ///
+    /// # Arguments
+    /// * `source` - Projection to the value lane or store to sample.
+    /// * `target` - Projection to the value lane or store that will hold the average.
+    /// * `half_life` - The period over which the weight of a sample decays by half.
+    /// * `period` - The interval between samples of the source.
+    pub fn track_ewma<Source, T, Item>(
+        &self,
+        source: fn(&Agent) -> &Source,
+        target: fn(&Agent) -> &Item,
+        half_life: Duration,
+        period: Duration,
+    ) -> impl EventHandler<Agent> + Send + 'static
+    where
+        Source: ValueLikeItem<T> + 'static,
+        T: Clone + Into<f64> + Send + 'static,
+        Item: MutableValueLikeItem<f64> + 'static,
+    {
+        let average = Arc::new(Mutex::new(Ewma::new(half_life)));
+        self.schedule_repeatedly(period, move || {
+            let average = average.clone();
+            let handler = Source::get_handler::<Agent>(source)
+                .map(move |value: T| {
+                    let mut guard = average.lock().expect("Moving average poisoned.");
+                    guard.update(value.into(), Instant::now())
+                })
+                .and_then(move |avg: f64| Item::set_handler(target, avg));
+            Some(handler)
+        })
+    }
+
+    /// Periodically sample a value lane or store and add its value to a history lane, if it has
+    /// changed since the previous sample. The source item maintains a running count of its events so
+    /// no event handler needs to be attached to it. Several changes to the source between samples
+    /// will result in a single entry. This will typically be started from the `on_start` handler of
+    /// the agent.
+    ///
+    /// # Arguments
+    /// * `source` - Projection to the value lane or store to sample.
+    /// * `history` - Projection to the history lane.
+    /// * `period` - The interval between samples of the source.
+    pub fn track_history<Source, T>(
+        &self,
+        source: fn(&Agent) -> &Source,
+        history: fn(&Agent) -> &HistoryLane<T>,
+        period: Duration,
+    ) -> impl EventHandler<Agent> + Send + 'static
+    where
+        Source: ValueLikeItem<T> + EventCount + 'static,
+        T: Send + 'static,
+    {
+        let last_count = Arc::new(Mutex::new(None));
+        self.schedule_repeatedly(period, move || {
+            let handler = HasChanged::new(source, last_count.clone())
+                .and_then(move |changed: bool| {
+                    changed.then(|| {
+                        Source::get_handler::<Agent>(source)
+                            .and_then(move |value: T| HistoryLanePush::new(history, value))
+                    })
+                })
+                .discard();
+            Some(handler)
+        })
+    }
+
     /// Suspend a future to be executed by the agent task.
     /// # Note
     ///
@@ -721,6 +1228,386 @@ impl<Agent: 'static> HandlerContext<Agent> {
         OpenMapDownlinkAction::new(Address::text(host, node, lane), lifecycle, config)
     }
 
+    /// Open a value downlink to a lane on another agent, delivering its events as a stream
+    /// rather than to a lifecycle. The stream could, for example, be consumed by a future that is
+    /// suspended with [`HandlerContext::suspend`]. As the agent cannot wait for the consumer of
+    /// the stream, events are discarded if its buffer is full (see [`DownlinkEventStream::dropped`]).
+    ///
+    /// # Arguments
+    /// * `host` - The remote host at which the agent resides (a local agent if not specified).
+    /// * `node` - The node URI of the agent.
+    /// * `lane` - The lane to downlink from.
+    /// * `config` - Configuration parameters for the downlink.
+    /// * `buffer_size` - The number of events that can be buffered by the stream.
+    pub fn open_value_downlink_stream<T>(
+        &self,
+        host: Option<&str>,
+        node: &str,
+        lane: &str,
+        config: SimpleDownlinkConfig,
+        buffer_size: NonZeroUsize,
+    ) -> impl HandlerAction<
+        Agent,
+        Completion = (
+            ValueDownlinkHandle<T>,
+            DownlinkEventStream<ValueDownlinkEvent<T>>,
+        ),
+    > + Send
+           + 'static
+    where
+        T: Form + Clone + Send + Sync + 'static,
+        T::Rec: Send,
+    {
+        let (sender, events) = downlink_event_stream(buffer_size);
+        self.open_value_downlink(host, node, lane, sender, config)
+            .map(move |handle| (handle, events))
+    }
+
+    /// Open an event downlink to a lane on another agent, delivering its events as a stream
+    /// rather than to a lifecycle. As the agent cannot wait for the consumer of the stream,
+    /// events are discarded if its buffer is full (see [`DownlinkEventStream::dropped`]).
+    ///
+    /// # Arguments
+    /// * `host` - The remote host at which the agent resides (a local agent if not specified).
+    /// * `node` - The node URI of the agent.
+    /// * `lane` - The lane to downlink from.
+    /// * `config` - Configuration parameters for the downlink.
+    /// * `buffer_size` - The number of events that can be buffered by the stream.
+    pub fn open_event_downlink_stream<T>(
+        &self,
+        host: Option<&str>,
+        node: &str,
+        lane: &str,
+        config: SimpleDownlinkConfig,
+        buffer_size: NonZeroUsize,
+    ) -> impl HandlerAction<
+        Agent,
+        Completion = (
+            EventDownlinkHandle,
+            DownlinkEventStream<EventDownlinkEvent<T>>,
+        ),
+    > + Send
+           + 'static
+    where
+        T: Form + Send + Sync + 'static,
+        T::Rec: Send,
+    {
+        let (sender, events) = downlink_event_stream(buffer_size);
+        self.open_event_downlink(host, node, lane, sender, config)
+            .map(move |handle| (handle, events))
+    }
+
+    /// Open a map downlink to a lane on another agent, delivering its events as a stream
+    /// rather than to a lifecycle. As the agent cannot wait for the consumer of the stream,
+    /// events are discarded if its buffer is full (see [`DownlinkEventStream::dropped`]).
+    ///
+    /// # Arguments
+    /// * `host` - The remote host at which the agent resides (a local agent if not specified).
+    /// * `node` - The node URI of the agent.
+    /// * `lane` - The lane to downlink from.
+    /// * `config` - Configuration parameters for the downlink.
+    /// * `buffer_size` - The number of events that can be buffered by the stream.
+    pub fn open_map_downlink_stream<K, V>(
+        &self,
+        host: Option<&str>,
+        node: &str,
+        lane: &str,
+        config: MapDownlinkConfig,
+        buffer_size: NonZeroUsize,
+    ) -> impl HandlerAction<
+        Agent,
+        Completion = (
+            MapDownlinkHandle<K, V>,
+            DownlinkEventStream<MapDownlinkEvent<K, V>>,
+        ),
+    > + Send
+           + 'static
+    where
+        K: Form + Hash + Eq + Ord + Clone + Send + Sync + 'static,
+        V: Form + Clone + Send + Sync + 'static,
+        K::Rec: Send,
+        V::Rec: Send,
+    {
+        let (sender, events) = downlink_event_stream(buffer_size);
+        self.open_map_downlink(host, node, lane, sender, config)
+            .map(move |handle| (handle, events))
+    }
+
+    /// Open a value downlink to a lane at a full address. If the address has a host (for example
+    /// `warp://host:port`), the downlink will be opened over the client connections of the server,
+    /// sharing a single connection with any other downlinks to the same remote host.
+    ///
+    /// # Arguments
+    /// * `address` - The address of the lane to downlink from.
+    /// * `lifecycle` - Lifecycle events for the downlink.
+    /// * `config` - Configuration parameters for the downlink.
+    /// * `on_failed` - Creates an event handler to run if the downlink cannot be opened.
+    pub fn open_value_downlink_at<T, LC, S, F, H>(
+        &self,
+        address: Address<S>,
+        lifecycle: LC,
+        config: SimpleDownlinkConfig,
+        on_failed: F,
+    ) -> impl HandlerAction<Agent, Completion = ValueDownlinkHandle<T>> + Send + 'static
+    where
+        T: Form + Send + Sync + 'static,
+        LC: ValueDownlinkLifecycle<T, Agent> + Send + 'static,
+        T::Rec: Send,
+        S: AsRef<str>,
+        F: FnOnce(DownlinkRuntimeError) -> H + Send + 'static,
+        H: EventHandler<Agent> + Send + 'static,
+    {
+        OpenValueDownlinkAction::new(address.to_text(), lifecycle, config).on_failed(on_failed)
+    }
+
+    /// Open a map downlink to a lane at a full address. If the address has a host (for example
+    /// `warp://host:port`), the downlink will be opened over the client connections of the server,
+    /// sharing a single connection with any other downlinks to the same remote host.
+    ///
+    /// # Arguments
+    /// * `address` - The address of the lane to downlink from.
+    /// * `lifecycle` - Lifecycle events for the downlink.
+    /// * `config` - Configuration parameters for the downlink.
+    /// * `on_failed` - Creates an event handler to run if the downlink cannot be opened.
+    pub fn open_map_downlink_at<K, V, LC, S, F, H>(
+        &self,
+        address: Address<S>,
+        lifecycle: LC,
+        config: MapDownlinkConfig,
+        on_failed: F,
+    ) -> impl HandlerAction<Agent, Completion = MapDownlinkHandle<K, V>> + Send + 'static
+    where
+        K: Form + Hash + Eq + Ord + Clone + Send + Sync + 'static,
+        V: Form + Send + Sync + 'static,
+        LC: MapDownlinkLifecycle<K, V, Agent> + Send + 'static,
+        K::Rec: Send,
+        V::Rec: Send,
+        S: AsRef<str>,
+        F: FnOnce(DownlinkRuntimeError) -> H + Send + 'static,
+        H: EventHandler<Agent> + Send + 'static,
+    {
+        OpenMapDownlinkAction::new(address.to_text(), lifecycle, config).on_failed(on_failed)
+    }
+
+    /// Open a command downlink to a lane at a full address. The downlink can only be used to send
+    /// commands to the lane. The commands are sent through the ad hoc command channel of the agent
+    /// so no link is made to the lane and it will not send any events back. Commands are queued
+    /// and the handle will refuse further commands while the queue is full.
+    ///
+    /// # Arguments
+    /// * `address` - The address of the lane to send commands to.
+    /// * `config` - Configuration parameters for the downlink.
+    pub fn open_command_downlink<T, S>(
+        &self,
+        address: Address<S>,
+        config: CommandDownlinkConfig,
+    ) -> impl HandlerAction<Agent, Completion = CommandDownlinkHandle<T>> + Send + 'static
+    where
+        T: StructuralWritable + Send + 'static,
+        S: AsRef<str>,
+    {
+        OpenCommandDownlinkAction::new(address.to_text(), config)
+    }
+
+    /// Open a new lane for the agent after it has started (for example, one lane for each sensor
+    /// that is discovered). When the lane has been registered, `on_done` is called with the ID that
+    /// was assigned to it. The agent specification must be able to dispatch to the lane, by name,
+    /// using that ID for it to receive commands and produce events. Lanes opened in this way are
+    /// always transient. For agents that derive [`AgentLaneModel`](crate::AgentLaneModel), use
+    /// [`Self::open_value_lane`] or [`Self::open_map_lane`] instead.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the lane.
+    /// * `kind` - The kind of the lane.
+    /// * `config` - Configuration parameters for the lane.
+    /// * `on_done` - Creates an event handler to run when the lane has been opened (or failed to open).
+    pub fn open_lane<F, H>(
+        &self,
+        name: &str,
+        kind: WarpLaneKind,
+        config: LaneConfig,
+        on_done: F,
+    ) -> impl EventHandler<Agent> + Send + 'static
+    where
+        F: FnOnce(Result<u64, LaneSpawnError>) -> H + Send + 'static,
+        H: EventHandler<Agent> + Send + 'static,
+    {
+        OpenLane::new(Text::new(name), kind, config, on_done)
+    }
+
+    /// Open a new value lane for the agent after it has started and add it to a collection of
+    /// dynamic value lanes. The lane starts with the default value for its type. If the collection
+    /// is a field of an agent that derives [`AgentLaneModel`](crate::AgentLaneModel), commands,
+    /// sync requests and events for the lane will be dispatched to it by name.
+    ///
+    /// # Arguments
+    /// * `lanes` - Projection to the collection of dynamic value lanes.
+    /// * `name` - The name of the lane.
+    /// * `config` - Configuration parameters for the lane.
+    /// * `on_done` - Creates an event handler to run when the lane has been opened (or failed to open).
+    pub fn open_value_lane<T, F, H>(
+        &self,
+        lanes: fn(&Agent) -> &DynamicValueLanes<T>,
+        name: &str,
+        config: LaneConfig,
+        on_done: F,
+    ) -> impl EventHandler<Agent> + Send + 'static
+    where
+        T: Default + Send + 'static,
+        F: FnOnce(Result<u64, LaneSpawnError>) -> H + Send + 'static,
+        H: EventHandler<Agent> + Send + 'static,
+    {
+        let lane_name = Text::new(name);
+        OpenLane::new(
+            lane_name.clone(),
+            WarpLaneKind::Value,
+            config,
+            move |result: Result<u64, LaneSpawnError>| {
+                let lane = result
+                    .as_ref()
+                    .ok()
+                    .map(|id| (lane_name, ValueLane::new(*id, T::default())));
+                AttachDynamicLane::new(lanes, lane).followed_by(on_done(result))
+            },
+        )
+    }
+
+    /// Open a new map lane for the agent after it has started and add it to a collection of
+    /// dynamic map lanes. The lane starts empty. If the collection is a field of an agent that
+    /// derives [`AgentLaneModel`](crate::AgentLaneModel), commands, sync requests and events for the
+    /// lane will be dispatched to it by name.
+    ///
+    /// # Arguments
+    /// * `lanes` - Projection to the collection of dynamic map lanes.
+    /// * `name` - The name of the lane.
+    /// * `config` - Configuration parameters for the lane.
+    /// * `on_done` - Creates an event handler to run when the lane has been opened (or failed to open).
+    pub fn open_map_lane<K, V, F, H>(
+        &self,
+        lanes: fn(&Agent) -> &DynamicMapLanes<K, V>,
+        name: &str,
+        config: LaneConfig,
+        on_done: F,
+    ) -> impl EventHandler<Agent> + Send + 'static
+    where
+        K: Send + 'static,
+        V: Send + 'static,
+        F: FnOnce(Result<u64, LaneSpawnError>) -> H + Send + 'static,
+        H: EventHandler<Agent> + Send + 'static,
+    {
+        let lane_name = Text::new(name);
+        OpenLane::new(
+            lane_name.clone(),
+            WarpLaneKind::Map,
+            config,
+            move |result: Result<u64, LaneSpawnError>| {
+                let lane = result
+                    .as_ref()
+                    .ok()
+                    .map(|id| (lane_name, MapLane::new(*id, HashMap::new())));
+                AttachDynamicLane::new(lanes, lane).followed_by(on_done(result))
+            },
+        )
+    }
+
+    /// Create an event handler that will get the value of a dynamic value lane of the agent. The
+    /// handler will fail if the lane does not exist.
+    ///
+    /// # Arguments
+    /// * `lanes` - Projection to the collection of dynamic value lanes.
+    /// * `name` - The name of the lane.
+    pub fn get_dynamic_value<T>(
+        &self,
+        lanes: fn(&Agent) -> &DynamicValueLanes<T>,
+        name: &str,
+    ) -> impl HandlerAction<Agent, Completion = T> + Send + 'static
+    where
+        T: Clone + Send + 'static,
+    {
+        DynamicValueLaneGet::new(lanes, Text::new(name))
+    }
+
+    /// Create an event handler that will set a new value into a dynamic value lane of the agent.
+    /// The handler will fail if the lane does not exist.
+    ///
+    /// # Arguments
+    /// * `lanes` - Projection to the collection of dynamic value lanes.
+    /// * `name` - The name of the lane.
+    /// * `value` - The value to set.
+    pub fn set_dynamic_value<T>(
+        &self,
+        lanes: fn(&Agent) -> &DynamicValueLanes<T>,
+        name: &str,
+        value: T,
+    ) -> impl HandlerAction<Agent, Completion = ()> + Send + 'static
+    where
+        T: Send + 'static,
+    {
+        DynamicValueLaneSet::new(lanes, Text::new(name), value)
+    }
+
+    /// Create an event handler that will get an entry from a dynamic map lane of the agent. The
+    /// handler will fail if the lane does not exist.
+    ///
+    /// # Arguments
+    /// * `lanes` - Projection to the collection of dynamic map lanes.
+    /// * `name` - The name of the lane.
+    /// * `key` - The key to fetch.
+    pub fn get_dynamic_entry<K, V>(
+        &self,
+        lanes: fn(&Agent) -> &DynamicMapLanes<K, V>,
+        name: &str,
+        key: K,
+    ) -> impl HandlerAction<Agent, Completion = Option<V>> + Send + 'static
+    where
+        K: Send + Clone + Eq + Hash + 'static,
+        V: Send + Clone + 'static,
+    {
+        DynamicMapLaneGet::new(lanes, Text::new(name), key)
+    }
+
+    /// Create an event handler that will update an entry in a dynamic map lane of the agent. The
+    /// handler will fail if the lane does not exist.
+    ///
+    /// # Arguments
+    /// * `lanes` - Projection to the collection of dynamic map lanes.
+    /// * `name` - The name of the lane.
+    /// * `key` - The key to update.
+    /// * `value` - The new value.
+    pub fn update_dynamic<K, V>(
+        &self,
+        lanes: fn(&Agent) -> &DynamicMapLanes<K, V>,
+        name: &str,
+        key: K,
+        value: V,
+    ) -> impl HandlerAction<Agent, Completion = ()> + Send + 'static
+    where
+        K: Send + Clone + Eq + Hash + 'static,
+        V: Send + 'static,
+    {
+        DynamicMapLaneApply::new(lanes, Text::new(name), MapMessage::Update { key, value })
+    }
+
+    /// Create an event handler that will remove an entry from a dynamic map lane of the agent. The
+    /// handler will fail if the lane does not exist.
+    ///
+    /// # Arguments
+    /// * `lanes` - Projection to the collection of dynamic map lanes.
+    /// * `name` - The name of the lane.
+    /// * `key` - The key to remove.
+    pub fn remove_dynamic<K, V>(
+        &self,
+        lanes: fn(&Agent) -> &DynamicMapLanes<K, V>,
+        name: &str,
+        key: K,
+    ) -> impl HandlerAction<Agent, Completion = ()> + Send + 'static
+    where
+        K: Send + Clone + Eq + Hash + 'static,
+        V: Send + 'static,
+    {
+        DynamicMapLaneApply::new(lanes, Text::new(name), MapMessage::Remove { key })
+    }
+
     /// Create a builder to construct a request to open an event downlink.
     /// # Arguments
     /// * `host` - The remote host at which the agent resides (a local agent if not specified).
@@ -855,6 +1742,21 @@ impl<Agent: 'static> HandlerContext<Agent> {
         JoinMapAddDownlink::new(lane, link_key, address)
     }
 
+    /// Read the current variant of a feature flag of the plane (see `ServerBuilder::enable_feature_flags`).
+    /// The flags are cached by the agent and the cache is kept up to date by a downlink to the feature
+    /// flags meta-agent, which is opened the first time a flag is requested. Until that downlink has
+    /// synchronized, or if the flag is not set or cannot be converted to the requested type, this will
+    /// resolve to [`None`].
+    ///
+    /// # Arguments
+    /// * `name` - The name of the flag.
+    pub fn feature_flag<T>(&self, name: &str) -> impl HandlerAction<Agent, Completion = Option<T>>
+    where
+        T: Form,
+    {
+        GetFeatureFlag::new(name)
+    }
+
     /// Causes the agent to stop. If this is encountered during the `on_start` event of an agent it will
     /// fail to start at all. Otherwise, execution of the event handler will terminate and the agent will
     /// begin to shutdown. The 'on_stop' handler will still be run. If a stop is requested in
@@ -22,8 +22,9 @@ use swimos_utilities::routing::RouteUri;
 
 use crate::{
     event_handler::{ActionContext, HandlerAction, LocalBoxEventHandler, StepResult},
+    lanes::ValueLane,
     meta::AgentMetadata,
-    test_context::{no_downlink, DummyAgentContext},
+    test_context::{dummy_context, no_downlink, DummyAgentContext},
 };
 
 use super::HandlerContext;
@@ -129,3 +130,68 @@ async fn suspend_repeatedly() {
     let expected: Vec<i32> = (0..10).collect();
     assert_eq!(values, expected);
 }
+
+struct Counter {
+    lane: ValueLane<i32>,
+}
+
+impl Counter {
+    const LANE: fn(&Counter) -> &ValueLane<i32> = |agent| &agent.lane;
+}
+
+fn run_to_completion<H>(agent: &Counter, mut handler: H) -> (H::Completion, bool)
+where
+    H: HandlerAction<Counter>,
+{
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let mut action_context = dummy_context(&mut join_lane_init, &mut ad_hoc_buffer);
+    match handler.step(&mut action_context, meta, agent) {
+        StepResult::Complete {
+            modified_item,
+            result,
+        } => (result, modified_item.is_some()),
+        _ => panic!("Handler did not complete."),
+    }
+}
+
+#[test]
+fn numeric_value_lane_handlers() {
+    let agent = Counter {
+        lane: ValueLane::new(0, 10),
+    };
+    let context: HandlerContext<Counter> = HandlerContext::default();
+
+    assert_eq!(
+        run_to_completion(&agent, context.add_value(Counter::LANE, 5)),
+        (15, true)
+    );
+    assert_eq!(
+        run_to_completion(&agent, context.max_value(Counter::LANE, 12)),
+        (15, false)
+    );
+    assert_eq!(
+        run_to_completion(&agent, context.max_value(Counter::LANE, 20)),
+        (20, true)
+    );
+    assert_eq!(
+        run_to_completion(&agent, context.min_value(Counter::LANE, 30)),
+        (20, false)
+    );
+    assert_eq!(
+        run_to_completion(&agent, context.min_value(Counter::LANE, 3)),
+        (3, true)
+    );
+    assert_eq!(
+        run_to_completion(&agent, context.compare_and_set(Counter::LANE, 4, 7)),
+        (false, false)
+    );
+    assert_eq!(
+        run_to_completion(&agent, context.compare_and_set(Counter::LANE, 3, 7)),
+        (true, true)
+    );
+    assert_eq!(agent.lane.read(|n| *n), 7);
+}
@@ -0,0 +1,219 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{atomic::AtomicU8, Arc};
+
+use futures::FutureExt;
+use swimos_api::address::Address;
+use swimos_form::write::StructuralWritable;
+use swimos_model::Text;
+use swimos_utilities::trigger;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tracing::{debug, info, trace};
+
+use crate::{
+    event_handler::{
+        ActionContext, HandlerAction, LocalBoxEventHandler, Spawner, StepResult, UnitHandler,
+    },
+    meta::AgentMetadata,
+};
+
+use super::{DlState, DlStateObserver, DlStateTracker};
+
+#[cfg(test)]
+mod tests;
+
+/// Drains the queue of commands for a command downlink into the ad hoc command channel of the
+/// agent. No link is opened to the remote lane so it does not need to sync or send events back
+/// to the agent. The pump is suspended into the agent task and resumes each time a command is
+/// queued, until the downlink is stopped or all of its handles are dropped.
+pub struct CommandPump<T> {
+    address: Address<Text>,
+    commands_rx: mpsc::Receiver<T>,
+    stop_rx: trigger::Receiver,
+    dl_state: DlStateTracker,
+}
+
+impl<T> CommandPump<T>
+where
+    T: StructuralWritable + Send + 'static,
+{
+    pub fn new(
+        address: Address<Text>,
+        stop_rx: trigger::Receiver,
+        commands_rx: mpsc::Receiver<T>,
+        dl_state: Arc<AtomicU8>,
+    ) -> Self {
+        let dl_state = DlStateTracker::new(dl_state);
+        dl_state.set(DlState::Linked);
+        CommandPump {
+            address,
+            commands_rx,
+            stop_rx,
+            dl_state,
+        }
+    }
+
+    /// Suspend the pump into the agent task to wait for the next command.
+    pub fn schedule<Context: 'static>(self, action_context: &mut ActionContext<Context>) {
+        let fut = self
+            .next_command()
+            .map(|maybe_command| {
+                let handler: LocalBoxEventHandler<'static, Context> = match maybe_command {
+                    Some((pump, command)) => Box::new(SendQueuedCommand::new(pump, command)),
+                    None => Box::new(UnitHandler::default()),
+                };
+                handler
+            })
+            .boxed();
+        action_context.spawn_suspend(fut);
+    }
+
+    async fn next_command(mut self) -> Option<(Self, T)> {
+        let CommandPump {
+            address,
+            commands_rx,
+            stop_rx,
+            dl_state,
+        } = &mut self;
+        let maybe_command = tokio::select! {
+            biased;
+            stopped = stop_rx => match stopped {
+                Ok(()) => {
+                    info!(address = %address, "Command downlink stopped by trigger.");
+                    None
+                }
+                Err(_) => commands_rx.recv().await,
+            },
+            maybe_command = commands_rx.recv() => maybe_command,
+        };
+        if let Some(command) = maybe_command {
+            Some((self, command))
+        } else {
+            debug!(address = %address, "Command downlink stopped.");
+            dl_state.set(DlState::Stopped);
+            None
+        }
+    }
+}
+
+/// [`HandlerAction`] that writes a command, taken from the queue of a command downlink, into
+/// the ad hoc command channel and then suspends the pump to wait for the next command.
+struct SendQueuedCommand<T> {
+    inner: Option<(CommandPump<T>, T)>,
+}
+
+impl<T> SendQueuedCommand<T> {
+    fn new(pump: CommandPump<T>, command: T) -> Self {
+        SendQueuedCommand {
+            inner: Some((pump, command)),
+        }
+    }
+}
+
+impl<T, Context> HandlerAction<Context> for SendQueuedCommand<T>
+where
+    Context: 'static,
+    T: StructuralWritable + Send + 'static,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<Context>,
+        _meta: AgentMetadata,
+        _context: &Context,
+    ) -> StepResult<Self::Completion> {
+        if let Some((pump, command)) = self.inner.take() {
+            trace!(address = %pump.address, "Sending a command through a command downlink.");
+            action_context.send_command(pump.address.clone(), command, false);
+            pump.schedule(action_context);
+            StepResult::done(())
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+/// Error type for commands that could not be queued to be sent by a command downlink. The
+/// command is returned so that it can be retried.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum CommandDownlinkError<T> {
+    /// The queue of commands waiting to be sent is full.
+    #[error("The command queue of the downlink is full.")]
+    Full(T),
+    /// The downlink has stopped (either because it failed or it was stopped explicitly).
+    #[error("The command downlink has stopped.")]
+    Stopped(T),
+}
+
+impl<T> CommandDownlinkError<T> {
+    /// Take back the command that could not be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            CommandDownlinkError::Full(command) => command,
+            CommandDownlinkError::Stopped(command) => command,
+        }
+    }
+}
+
+/// A handle which can be used to send commands to a lane through a command downlink or to stop
+/// the downlink.
+#[derive(Debug)]
+pub struct CommandDownlinkHandle<T> {
+    address: Address<Text>,
+    sender: mpsc::Sender<T>,
+    stop_tx: Option<trigger::Sender>,
+    observer: DlStateObserver,
+}
+
+impl<T> CommandDownlinkHandle<T> {
+    pub fn new(
+        address: Address<Text>,
+        sender: mpsc::Sender<T>,
+        stop_tx: trigger::Sender,
+        state: &Arc<AtomicU8>,
+    ) -> Self {
+        CommandDownlinkHandle {
+            address,
+            sender,
+            stop_tx: Some(stop_tx),
+            observer: DlStateObserver::new(state),
+        }
+    }
+
+    /// Instruct the downlink to stop. Any commands that have not yet been sent will be discarded.
+    pub fn stop(&mut self) {
+        trace!(address = %self.address, "Stopping a command downlink.");
+        if let Some(tx) = self.stop_tx.take() {
+            tx.trigger();
+        }
+    }
+
+    /// True if the downlink has stopped (regardless of whether it stopped cleanly or failed.)
+    pub fn is_stopped(&self) -> bool {
+        self.observer.get() == DlState::Stopped
+    }
+
+    /// Queue a command to be sent to the remote lane. This will fail, returning the command, if
+    /// the queue of pending commands is full or the downlink has stopped.
+    pub fn send(&self, command: T) -> Result<(), CommandDownlinkError<T>> {
+        trace!(address = %self.address, "Queueing a command for a command downlink.");
+        self.sender.try_send(command).map_err(|err| match err {
+            mpsc::error::TrySendError::Full(command) => CommandDownlinkError::Full(command),
+            mpsc::error::TrySendError::Closed(command) => CommandDownlinkError::Stopped(command),
+        })
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bytes::BytesMut;
+use futures::{stream::FuturesUnordered, StreamExt};
+use swimos_agent_protocol::{encoding::ad_hoc::AdHocCommandDecoder, AdHocCommand};
+use swimos_api::address::Address;
+use swimos_model::Text;
+use swimos_utilities::{encoding::BytesStr, trigger};
+use tokio::sync::mpsc;
+use tokio_util::codec::Decoder;
+
+use super::{CommandDownlinkError, CommandDownlinkHandle, CommandPump, SendQueuedCommand};
+use crate::event_handler::HandlerFuture;
+
+use super::super::test_support::run_handler_with;
+
+struct FakeAgent;
+
+const NODE: &str = "/node";
+const LANE: &str = "lane";
+
+fn make_pump(queue_size: usize) -> (CommandPump<i32>, CommandDownlinkHandle<i32>) {
+    let address = Address::new(None, Text::new(NODE), Text::new(LANE));
+    let (stop_tx, stop_rx) = trigger::trigger();
+    let (tx, rx) = mpsc::channel(queue_size);
+    let dl_state = Default::default();
+    let handle = CommandDownlinkHandle::new(address.clone(), tx, stop_tx, &dl_state);
+    let pump = CommandPump::new(address, stop_rx, rx, dl_state);
+    (pump, handle)
+}
+
+fn read_commands(buffer: &mut BytesMut) -> Vec<i32> {
+    let mut decoder = AdHocCommandDecoder::<BytesStr, i32>::default();
+    let mut commands = vec![];
+    while let Some(AdHocCommand {
+        address,
+        command,
+        overwrite_permitted,
+    }) = decoder.decode(buffer).expect("Decoding failed.")
+    {
+        assert_eq!(address, Address::new(None, NODE, LANE));
+        assert!(!overwrite_permitted);
+        commands.push(command);
+    }
+    assert!(buffer.is_empty());
+    commands
+}
+
+#[tokio::test]
+async fn send_commands() {
+    let agent = FakeAgent;
+    let (pump, handle) = make_pump(8);
+
+    assert!(handle.send(1).is_ok());
+    assert!(handle.send(2).is_ok());
+    assert!(handle.send(3).is_ok());
+    drop(handle);
+
+    let (pump, first) = pump.next_command().await.expect("Expected a command.");
+    assert_eq!(first, 1);
+
+    let mut spawner = FuturesUnordered::<HandlerFuture<FakeAgent>>::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    run_handler_with(
+        Box::new(SendQueuedCommand::new(pump, first)),
+        &agent,
+        &spawner,
+        &mut ad_hoc_buffer,
+    );
+    while let Some(handler) = spawner.next().await {
+        run_handler_with(handler, &agent, &spawner, &mut ad_hoc_buffer);
+    }
+
+    assert_eq!(read_commands(&mut ad_hoc_buffer), vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn send_commands_queue_full() {
+    let (pump, handle) = make_pump(1);
+
+    assert!(handle.send(1).is_ok());
+    assert!(matches!(handle.send(2), Err(CommandDownlinkError::Full(2))));
+
+    let (_pump, command) = pump.next_command().await.expect("Expected a command.");
+    assert_eq!(command, 1);
+
+    assert!(handle.send(3).is_ok());
+    assert!(!handle.is_stopped());
+}
+
+#[tokio::test]
+async fn stop_command_downlink() {
+    let (pump, mut handle) = make_pump(8);
+
+    assert!(handle.send(1).is_ok());
+    assert!(!handle.is_stopped());
+    handle.stop();
+
+    assert!(pump.next_command().await.is_none());
+    assert!(handle.is_stopped());
+    assert!(matches!(
+        handle.send(2),
+        Err(CommandDownlinkError::Stopped(2))
+    ));
+}
+
+#[tokio::test]
+async fn pending_commands_sent_after_handle_dropped() {
+    let (pump, handle) = make_pump(8);
+
+    assert!(handle.send(1).is_ok());
+    drop(handle);
+
+    let (pump, command) = pump.next_command().await.expect("Expected a command.");
+    assert_eq!(command, 1);
+    assert!(pump.next_command().await.is_none());
+}
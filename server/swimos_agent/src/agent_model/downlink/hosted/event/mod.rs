@@ -32,6 +32,7 @@ use swimos_utilities::{
 };
 use tokio_util::codec::FramedRead;
 use tracing::{debug, error, info, trace};
+use uuid::Uuid;
 
 use crate::{
     agent_model::downlink::{
@@ -42,7 +43,7 @@ use crate::{
     event_handler::{HandlerActionExt, LocalBoxEventHandler},
 };
 
-use super::{DlState, DlStateObserver, DlStateTracker};
+use super::{DlState, DlStateObserver, DlStateTracker, Originated};
 
 #[cfg(test)]
 mod tests;
@@ -97,6 +98,7 @@ where
             address,
             receiver: Some(FramedRead::new(receiver, Default::default())),
             next: None,
+            origin: None,
             lifecycle,
             config,
             dl_state: DlStateTracker::new(dl_state),
@@ -118,6 +120,8 @@ pub struct HostedEventDownlink<T: RecognizerReadable, LC> {
     address: Address<Text>,
     receiver: Option<FramedRead<ByteReader, ValueNotificationDecoder<T>>>,
     next: Option<Result<DownlinkNotification<T>, FrameIoError>>,
+    // The origin of the command that caused the next event (if it was reported).
+    origin: Option<Uuid>,
     lifecycle: LC,
     config: SimpleDownlinkConfig,
     dl_state: DlStateTracker,
@@ -235,6 +239,7 @@ where
             address,
             receiver,
             next,
+            origin,
             lifecycle,
             dl_state,
             config:
@@ -260,9 +265,10 @@ where
                 }
                 Ok(DownlinkNotification::Event { body }) => {
                     trace!(address = %address, "Event received for downlink.");
+                    let origin = origin.take();
                     let handler = if dl_state.get() == DlState::Synced || *events_when_not_synced {
-                        let handler = lifecycle.on_event(body).boxed_local();
-                        Some(handler)
+                        let handler = lifecycle.on_event(body);
+                        Some(Originated::new(origin, handler).boxed_local())
                     } else {
                         None
                     };
@@ -278,6 +284,24 @@ where
                     }
                     Some(lifecycle.on_unlinked().boxed_local())
                 }
+                Ok(DownlinkNotification::Advisory { advice }) => {
+                    debug!(address = %address, advice = %advice, "Downlink advised to back off by the remote lane.");
+                    None
+                }
+                Ok(DownlinkNotification::NodeStopped { notice }) => {
+                    debug!(address = %address, notice = %notice, "The remote agent stopped.");
+                    None
+                }
+                Ok(DownlinkNotification::Failed { failure }) => {
+                    debug!(address = %address, failure = %failure, "The remote refused the link.");
+                    None
+                }
+                // Agent downlinks do not retain their state when they are relinked.
+                Ok(DownlinkNotification::Version { .. }) => None,
+                Ok(DownlinkNotification::Origin { origin: id }) => {
+                    *origin = Some(id);
+                    None
+                }
                 Err(_) => {
                     debug!(address = %address, "Downlink failed.");
                     if *terminate_on_unlinked {
@@ -298,11 +322,13 @@ where
         let HostedEventDownlink {
             receiver,
             next,
+            origin,
             dl_state,
             write_terminated,
             ..
         } = self;
         *next = None;
+        *origin = None;
         dl_state.set(DlState::Unlinked);
         *write_terminated = false;
         *receiver = Some(FramedRead::new(input, Default::default()));
@@ -278,6 +278,13 @@ fn to_bytes(not: DownlinkNotification<i32>) -> DownlinkNotification<Vec<u8>> {
             }
         }
         DownlinkNotification::Unlinked => DownlinkNotification::Unlinked,
+        DownlinkNotification::Advisory { advice } => DownlinkNotification::Advisory { advice },
+        DownlinkNotification::NodeStopped { notice } => {
+            DownlinkNotification::NodeStopped { notice }
+        }
+        DownlinkNotification::Failed { failure } => DownlinkNotification::Failed { failure },
+        DownlinkNotification::Version { version } => DownlinkNotification::Version { version },
+        DownlinkNotification::Origin { origin } => DownlinkNotification::Origin { origin },
     }
 }
 
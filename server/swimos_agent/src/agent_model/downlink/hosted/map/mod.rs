@@ -14,7 +14,7 @@
 
 use std::{
     cell::RefCell,
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeSet, HashMap, VecDeque},
     pin::{pin, Pin},
     sync::{atomic::AtomicU8, Arc},
     task::{Context, Poll},
@@ -44,6 +44,7 @@ use swimos_utilities::{
 use tokio::sync::mpsc;
 use tokio_util::codec::{FramedRead, FramedWrite};
 use tracing::{debug, error, info, trace};
+use uuid::Uuid;
 
 use crate::event_handler::LocalBoxEventHandler;
 use crate::{
@@ -56,7 +57,9 @@ use crate::{
     event_queue::EventQueue,
 };
 
-use super::{DlState, DlStateObserver, DlStateTracker, OutputWriter, RestartableOutput};
+use super::{
+    DlState, DlStateObserver, DlStateTracker, Originated, OutputWriter, RestartableOutput,
+};
 
 #[cfg(test)]
 mod tests;
@@ -108,6 +111,7 @@ impl<K, V> MapDlState<K, V> {
         key: K,
         value: V,
         lifecycle: Option<&'a LC>,
+        origin: Option<Uuid>,
     ) -> Option<LocalBoxEventHandler<'a, Context>>
     where
         K: Eq + Hash + Clone + Ord,
@@ -123,9 +127,8 @@ impl<K, V> MapDlState<K, V> {
             }
             let new_value = &map[&key];
             lifecycle.map(|lifecycle| {
-                lifecycle
-                    .on_update(key, &*map, old, new_value)
-                    .boxed_local()
+                let handler = lifecycle.on_update(key, &*map, old, new_value);
+                Originated::new(origin, handler).boxed_local()
             })
         })
     }
@@ -134,6 +137,7 @@ impl<K, V> MapDlState<K, V> {
         &self,
         key: K,
         lifecycle: Option<&'a LC>,
+        origin: Option<Uuid>,
     ) -> Option<LocalBoxEventHandler<'a, Context>>
     where
         K: Eq + Hash + Ord,
@@ -144,16 +148,27 @@ impl<K, V> MapDlState<K, V> {
                 if let Some(ord) = order {
                     ord.remove(&key);
                 }
-                lifecycle.map(|lifecycle| lifecycle.on_remove(key, &*map, old).boxed_local())
+                lifecycle.map(|lifecycle| {
+                    let handler = lifecycle.on_remove(key, &*map, old);
+                    Originated::new(origin, handler).boxed_local()
+                })
             })
         })
     }
 
+    /// Drop the first `n` entries of the map. If the drop is decomposed into a sequence of removals,
+    /// at most `budget` removals will be performed and the remainder of the operation (if any)
+    /// will be returned.
     fn drop<'a, LC, Context>(
         &self,
         n: usize,
         lifecycle: Option<&'a LC>,
-    ) -> Option<LocalBoxEventHandler<'a, Context>>
+        budget: usize,
+        origin: Option<Uuid>,
+    ) -> (
+        Option<LocalBoxEventHandler<'a, Context>>,
+        Option<MapMessage<K, V>>,
+    )
     where
         K: Eq + Hash + Ord + Clone,
         LC: MapDownlinkLifecycle<K, V, Context>,
@@ -162,15 +177,20 @@ impl<K, V> MapDlState<K, V> {
             if n >= map.len() {
                 *order = None;
                 let old = std::mem::take(map);
-                lifecycle.map(move |lifecycle| lifecycle.on_clear(old).boxed_local())
+                (
+                    lifecycle.map(move |lifecycle| {
+                        Originated::new(origin, lifecycle.on_clear(old)).boxed_local()
+                    }),
+                    None,
+                )
             } else {
                 let ord = order.get_or_insert_with(|| map.keys().cloned().collect());
 
                 //Decompose the take into a sequence of removals.
-                let to_remove: Vec<_> = ord.iter().take(n).cloned().collect();
                 if let Some(lifecycle) = lifecycle {
-                    let expected = n.min(map.len());
-                    let mut removed = Vec::with_capacity(expected);
+                    let count = n.min(budget);
+                    let to_remove: Vec<_> = ord.iter().take(count).cloned().collect();
+                    let mut removed = Vec::with_capacity(count);
 
                     for k in to_remove {
                         ord.remove(&k);
@@ -178,27 +198,42 @@ impl<K, V> MapDlState<K, V> {
                             removed.push(lifecycle.on_remove(k, map, v));
                         }
                     }
-                    if removed.is_empty() {
+                    let remainder = if n > count {
+                        Some(MapMessage::Drop((n - count) as u64))
+                    } else {
                         None
+                    };
+                    if removed.is_empty() {
+                        (None, remainder)
                     } else {
-                        Some(Sequentially::new(removed).boxed_local())
+                        let handler = Originated::new(origin, Sequentially::new(removed));
+                        (Some(handler.boxed_local()), remainder)
                     }
                 } else {
+                    let to_remove: Vec<_> = ord.iter().take(n).cloned().collect();
                     for k in to_remove {
                         ord.remove(&k);
                         map.remove(&k);
                     }
-                    None
+                    (None, None)
                 }
             }
         })
     }
 
+    /// Retain only the first `n` entries of the map. If the take is decomposed into a sequence of
+    /// removals, at most `budget` removals will be performed and the remainder of the operation
+    /// (if any) will be returned.
     fn take<'a, LC, Context>(
         &self,
         n: usize,
         lifecycle: Option<&'a LC>,
-    ) -> Option<LocalBoxEventHandler<'a, Context>>
+        budget: usize,
+        origin: Option<Uuid>,
+    ) -> (
+        Option<LocalBoxEventHandler<'a, Context>>,
+        Option<MapMessage<K, V>>,
+    )
     where
         K: Eq + Hash + Ord + Clone,
         LC: MapDownlinkLifecycle<K, V, Context>,
@@ -209,30 +244,38 @@ impl<K, V> MapDlState<K, V> {
                 let ord = order.get_or_insert_with(|| map.keys().cloned().collect());
 
                 //Decompose the drop into a sequence of removals.
-                let to_remove: Vec<_> = ord.iter().rev().take(to_drop).cloned().collect();
                 if let Some(lifecycle) = lifecycle {
-                    let mut removed = Vec::with_capacity(to_drop);
+                    let count = to_drop.min(budget);
+                    let to_remove: Vec<_> = ord.iter().skip(n).take(count).cloned().collect();
+                    let mut removed = Vec::with_capacity(count);
 
-                    for k in to_remove.into_iter().rev() {
+                    for k in to_remove {
                         ord.remove(&k);
                         if let Some(v) = map.remove(&k) {
                             removed.push(lifecycle.on_remove(k, map, v));
                         }
                     }
-                    if removed.is_empty() {
+                    let remainder = if to_drop > count {
+                        Some(MapMessage::Take(n as u64))
+                    } else {
                         None
+                    };
+                    if removed.is_empty() {
+                        (None, remainder)
                     } else {
-                        Some(Sequentially::new(removed).boxed_local())
+                        let handler = Originated::new(origin, Sequentially::new(removed));
+                        (Some(handler.boxed_local()), remainder)
                     }
                 } else {
+                    let to_remove: Vec<_> = ord.iter().skip(n).cloned().collect();
                     for k in to_remove {
                         ord.remove(&k);
                         map.remove(&k);
                     }
-                    None
+                    (None, None)
                 }
             } else {
-                None
+                (None, None)
             }
         })
     }
@@ -245,7 +288,7 @@ pub struct MapDownlinkFactory<K, V, LC> {
     config: MapDownlinkConfig,
     dl_state: Arc<AtomicU8>,
     stop_rx: trigger::Receiver,
-    op_rx: mpsc::UnboundedReceiver<MapOperation<K, V>>,
+    op_rx: mpsc::UnboundedReceiver<MapWrite<K, V>>,
 }
 
 impl<K, V, LC> MapDownlinkFactory<K, V, LC>
@@ -260,7 +303,7 @@ where
         lifecycle: LC,
         config: MapDownlinkConfig,
         stop_rx: trigger::Receiver,
-        op_rx: mpsc::UnboundedReceiver<MapOperation<K, V>>,
+        op_rx: mpsc::UnboundedReceiver<MapWrite<K, V>>,
     ) -> Self {
         MapDownlinkFactory {
             address,
@@ -297,6 +340,7 @@ where
             write_stream: Writes::Inactive(op_rx),
             state,
             next: None,
+            origin: None,
             lifecycle,
             config,
             dl_state: DlStateTracker::new(dl_state),
@@ -321,6 +365,8 @@ pub struct HostedMapDownlink<K: RecognizerReadable, V: RecognizerReadable, LC> {
     write_stream: Writes<K, V>,
     state: MapDlState<K, V>,
     next: Option<Result<DownlinkNotification<MapMessage<K, V>>, FrameIoError>>,
+    // The origin of the command that caused the next event (if it was reported).
+    origin: Option<Uuid>,
     lifecycle: LC,
     config: MapDownlinkConfig,
     dl_state: DlStateTracker,
@@ -350,6 +396,10 @@ where
             dl_state,
             ..
         } = self;
+        if next.is_some() {
+            // The previous message was only partially handled.
+            return Some(Ok(DownlinkChannelEvent::HandlerReady));
+        }
         let select_next = pin!(async {
             tokio::select! {
                 maybe_result = OptionFuture::from(receiver.as_mut().map(|rx| rx.next())) => {
@@ -453,13 +503,14 @@ where
             receiver,
             state,
             next,
+            origin,
             lifecycle,
             dl_state,
             config:
                 MapDownlinkConfig {
                     events_when_not_synced,
                     terminate_on_unlinked,
-                    ..
+                    removal_budget,
                 },
             ..
         } = self;
@@ -486,38 +537,56 @@ where
                         };
                     trace!(address = %address, "Event received for downlink.");
 
-                    match body {
+                    let event_origin = *origin;
+                    let handler = match body {
                         MapMessage::Update { key, value } => {
                             trace!("Updating an entry.");
-                            state.update(key, value, maybe_lifecycle)
+                            state.update(key, value, maybe_lifecycle, event_origin)
                         }
                         MapMessage::Remove { key } => {
                             trace!("Removing an entry.");
-                            state.remove(key, maybe_lifecycle)
+                            state.remove(key, maybe_lifecycle, event_origin)
                         }
                         MapMessage::Clear => {
                             trace!("Clearing the map.");
                             let old_map = state.clear();
-                            maybe_lifecycle
-                                .map(|lifecycle| lifecycle.on_clear(old_map).boxed_local())
+                            maybe_lifecycle.map(|lifecycle| {
+                                let handler = lifecycle.on_clear(old_map);
+                                Originated::new(event_origin, handler).boxed_local()
+                            })
                         }
                         MapMessage::Take(n) => {
                             trace!("Retaining the first {} items.", n);
-                            state.take(
+                            let (handler, remainder) = state.take(
                                 n.try_into()
                                     .expect("number to take does not fit into usize"),
                                 maybe_lifecycle,
-                            )
+                                removal_budget.get(),
+                                event_origin,
+                            );
+                            // If the removals exceeded the budget, the remainder is handled after
+                            // the agent has had the opportunity to process other events.
+                            *next = remainder.map(|body| Ok(DownlinkNotification::Event { body }));
+                            handler
                         }
                         MapMessage::Drop(n) => {
                             trace!("Dropping the first {} items.", n);
-                            state.drop(
+                            let (handler, remainder) = state.drop(
                                 n.try_into()
                                     .expect("number to drop does not fit into usize"),
                                 maybe_lifecycle,
-                            )
+                                removal_budget.get(),
+                                event_origin,
+                            );
+                            *next = remainder.map(|body| Ok(DownlinkNotification::Event { body }));
+                            handler
                         }
+                    };
+                    // The remainder of a partially applied event has the same origin.
+                    if next.is_none() {
+                        *origin = None;
                     }
+                    handler
                 }
                 Ok(DownlinkNotification::Unlinked) => {
                     debug!(address = %address, "Downlink unlinked.");
@@ -530,6 +599,24 @@ where
                     state.clear();
                     Some(lifecycle.on_unlinked().boxed_local())
                 }
+                Ok(DownlinkNotification::Advisory { advice }) => {
+                    debug!(address = %address, advice = %advice, "Downlink advised to back off by the remote lane.");
+                    None
+                }
+                Ok(DownlinkNotification::NodeStopped { notice }) => {
+                    debug!(address = %address, notice = %notice, "The remote agent stopped.");
+                    None
+                }
+                Ok(DownlinkNotification::Failed { failure }) => {
+                    debug!(address = %address, failure = %failure, "The remote refused the link.");
+                    None
+                }
+                // Agent downlinks do not retain their state when they are relinked.
+                Ok(DownlinkNotification::Version { .. }) => None,
+                Ok(DownlinkNotification::Origin { origin: id }) => {
+                    *origin = Some(id);
+                    None
+                }
                 Err(_) => {
                     debug!(address = %address, "Downlink failed.");
                     if *terminate_on_unlinked {
@@ -553,6 +640,7 @@ where
             write_stream,
             state,
             next,
+            origin,
             dl_state,
             ..
         } = self;
@@ -560,6 +648,7 @@ where
         write_stream.restart(output);
         state.clear();
         *next = None;
+        *origin = None;
         dl_state.set(DlState::Unlinked);
     }
 
@@ -580,11 +669,26 @@ where
     }
 }
 
+/// Writes that are sent from a [`MapDownlinkHandle`] to the write stream of the downlink.
+#[derive(Debug)]
+pub enum MapWrite<K, V> {
+    /// A single operation that may be conflated with other pending operations on the same key.
+    Single(MapOperation<K, V>),
+    /// A batch of operations that will be written contiguously, in order, without conflation.
+    Batch(Vec<MapOperation<K, V>>),
+}
+
+impl<K, V> From<MapOperation<K, V>> for MapWrite<K, V> {
+    fn from(op: MapOperation<K, V>) -> Self {
+        MapWrite::Single(op)
+    }
+}
+
 /// A handle which can be used to modify the state of a map lane through a downlink.
 #[derive(Debug)]
 pub struct MapDownlinkHandle<K, V> {
     address: Address<Text>,
-    sender: mpsc::UnboundedSender<MapOperation<K, V>>,
+    sender: mpsc::UnboundedSender<MapWrite<K, V>>,
     stop_tx: Option<trigger::Sender>,
     observer: DlStateObserver,
 }
@@ -592,7 +696,7 @@ pub struct MapDownlinkHandle<K, V> {
 impl<K, V> MapDownlinkHandle<K, V> {
     pub fn new(
         address: Address<Text>,
-        sender: mpsc::UnboundedSender<MapOperation<K, V>>,
+        sender: mpsc::UnboundedSender<MapWrite<K, V>>,
         stop_tx: trigger::Sender,
         state: &Arc<AtomicU8>,
     ) -> Self {
@@ -630,19 +734,48 @@ where
 {
     pub fn update(&self, key: K, value: V) -> Result<(), AgentRuntimeError> {
         trace!(address = %self.address, "Updating an entry on a map downlink.");
-        self.sender.send(MapOperation::Update { key, value })?;
+        self.sender
+            .send(MapOperation::Update { key, value }.into())?;
         Ok(())
     }
 
     pub fn remove(&self, key: K) -> Result<(), AgentRuntimeError> {
         trace!(address = %self.address, "Removing an entry on a map downlink.");
-        self.sender.send(MapOperation::Remove { key })?;
+        self.sender.send(MapOperation::Remove { key }.into())?;
         Ok(())
     }
 
     pub fn clear(&self) -> Result<(), AgentRuntimeError> {
         trace!(address = %self.address, "Clearing a map downlink.");
-        self.sender.send(MapOperation::Clear)?;
+        self.sender.send(MapOperation::Clear.into())?;
+        Ok(())
+    }
+
+    /// Update several entries on the map. The updates will be sent to the remote lane together and
+    /// will not be interleaved with any other operations from this handle.
+    pub fn update_many<I>(&self, entries: I) -> Result<(), AgentRuntimeError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        self.apply(
+            entries
+                .into_iter()
+                .map(|(key, value)| MapOperation::Update { key, value }),
+        )
+    }
+
+    /// Apply a batch of operations to the map. The operations will be sent to the remote lane
+    /// together, in order, and will not be interleaved with any other operations from this handle.
+    /// Unlike individual operations, operations within a batch are never conflated.
+    pub fn apply<I>(&self, batch: I) -> Result<(), AgentRuntimeError>
+    where
+        I: IntoIterator<Item = MapOperation<K, V>>,
+    {
+        let ops = batch.into_iter().collect::<Vec<_>>();
+        if !ops.is_empty() {
+            trace!(address = %self.address, num_ops = ops.len(), "Applying a batch of operations on a map downlink.");
+            self.sender.send(MapWrite::Batch(ops))?;
+        }
         Ok(())
     }
 }
@@ -662,23 +795,25 @@ pub struct MapWriteStream<K, V, S = ReconWriter> {
     #[pin]
     write: S,
     #[pin]
-    op_rx: mpsc::UnboundedReceiver<MapOperation<K, V>>,
+    op_rx: mpsc::UnboundedReceiver<MapWrite<K, V>>,
     queue: EventQueue<K, V>,
+    batch: VecDeque<MapOperation<K, V>>, //Operations that must be written contiguously, before any new operations are received.
     state: MapWriteStreamState,
 }
 
 impl<K, V> MapWriteStream<K, V> {
-    pub fn new(writer: ByteWriter, op_rx: mpsc::UnboundedReceiver<MapOperation<K, V>>) -> Self {
+    pub fn new(writer: ByteWriter, op_rx: mpsc::UnboundedReceiver<MapWrite<K, V>>) -> Self {
         Self::with_sink(FramedWrite::new(writer, Default::default()), op_rx)
     }
 }
 
 impl<K, V, S> MapWriteStream<K, V, S> {
-    pub fn with_sink(sink: S, op_rx: mpsc::UnboundedReceiver<MapOperation<K, V>>) -> Self {
+    pub fn with_sink(sink: S, op_rx: mpsc::UnboundedReceiver<MapWrite<K, V>>) -> Self {
         MapWriteStream {
             write: sink,
             op_rx,
             queue: Default::default(),
+            batch: Default::default(),
             state: Default::default(),
         }
     }
@@ -706,35 +841,56 @@ where
         loop {
             match projected.state {
                 MapWriteStreamState::Active => {
-                    let received = match projected.op_rx.as_mut().poll_recv(cx) {
-                        Poll::Ready(Some(op)) => {
-                            projected.queue.push(op);
-                            true
-                        }
-                        Poll::Ready(None) => {
-                            *projected.state = MapWriteStreamState::Stopping;
-                            continue;
-                        }
-                        Poll::Pending => {
-                            if projected.queue.is_empty() {
-                                let result = ready!(Sink::<MapOperation<K, V>>::poll_flush(
-                                    projected.write.as_mut(),
-                                    cx
-                                ));
-                                break if result.is_err() {
-                                    *projected.state = MapWriteStreamState::Stopped;
-                                    Poll::Ready(Some(result))
-                                } else {
-                                    Poll::Pending
-                                };
+                    let received = if !projected.batch.is_empty() {
+                        // Operations from a batch must be written before anything else is received.
+                        false
+                    } else {
+                        match projected.op_rx.as_mut().poll_recv(cx) {
+                            Poll::Ready(Some(MapWrite::Single(op))) => {
+                                projected.queue.push(op);
+                                true
+                            }
+                            Poll::Ready(Some(MapWrite::Batch(ops))) => {
+                                // Any pending operations were received before the batch and so must be
+                                // written before it.
+                                while let Some(op) = projected.queue.pop() {
+                                    projected.batch.push_back(op);
+                                }
+                                projected.batch.extend(ops);
+                                if projected.batch.is_empty() {
+                                    continue;
+                                }
+                                true
+                            }
+                            Poll::Ready(None) => {
+                                *projected.state = MapWriteStreamState::Stopping;
+                                continue;
+                            }
+                            Poll::Pending => {
+                                if projected.queue.is_empty() {
+                                    let result = ready!(Sink::<MapOperation<K, V>>::poll_flush(
+                                        projected.write.as_mut(),
+                                        cx
+                                    ));
+                                    break if result.is_err() {
+                                        *projected.state = MapWriteStreamState::Stopped;
+                                        Poll::Ready(Some(result))
+                                    } else {
+                                        Poll::Pending
+                                    };
+                                }
+                                false
                             }
-                            false
                         }
                     };
                     break match Sink::<MapOperation<K, V>>::poll_ready(projected.write.as_mut(), cx)
                     {
                         Poll::Ready(Ok(_)) => {
-                            let op = projected.queue.pop().expect("Queue should be non-empty.");
+                            let op = projected
+                                .batch
+                                .pop_front()
+                                .or_else(|| projected.queue.pop())
+                                .expect("Queue should be non-empty.");
                             let result = projected.write.start_send(op);
                             if result.is_err() {
                                 *projected.state = MapWriteStreamState::Stopped;
@@ -753,7 +909,7 @@ where
                     };
                 }
                 MapWriteStreamState::Stopping => {
-                    if projected.queue.is_empty() {
+                    if projected.queue.is_empty() && projected.batch.is_empty() {
                         let result = ready!(Sink::<MapOperation<K, V>>::poll_close(
                             projected.write.as_mut(),
                             cx
@@ -768,7 +924,11 @@ where
                             cx
                         )) {
                             Ok(_) => {
-                                let op = projected.queue.pop().expect("Queue should be non-empty.");
+                                let op = projected
+                                    .batch
+                                    .pop_front()
+                                    .or_else(|| projected.queue.pop())
+                                    .expect("Queue should be non-empty.");
                                 let result = projected.write.start_send(op);
                                 if result.is_err() {
                                     *projected.state = MapWriteStreamState::Stopped;
@@ -789,7 +949,7 @@ where
 }
 
 impl<K, V> RestartableOutput for MapWriteStream<K, V> {
-    type Source = mpsc::UnboundedReceiver<MapOperation<K, V>>;
+    type Source = mpsc::UnboundedReceiver<MapWrite<K, V>>;
 
     fn make_inactive(self) -> Self::Source {
         self.op_rx
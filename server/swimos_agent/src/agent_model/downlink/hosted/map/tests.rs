@@ -57,7 +57,7 @@ use crate::{
     event_handler::{HandlerActionExt, LocalBoxEventHandler, SideEffect},
 };
 
-use super::{MapDownlinkFactory, MapWriteStream};
+use super::{MapDownlinkFactory, MapWrite, MapWriteStream};
 
 struct FakeAgent;
 
@@ -262,6 +262,13 @@ impl Writer {
                 }
             }
             DownlinkNotification::Unlinked => DownlinkNotification::Unlinked,
+            DownlinkNotification::Advisory { advice } => DownlinkNotification::Advisory { advice },
+            DownlinkNotification::NodeStopped { notice } => {
+                DownlinkNotification::NodeStopped { notice }
+            }
+            DownlinkNotification::Failed { failure } => DownlinkNotification::Failed { failure },
+            DownlinkNotification::Version { version } => DownlinkNotification::Version { version },
+            DownlinkNotification::Origin { origin } => DownlinkNotification::Origin { origin },
         };
         sender.send(bytes).await
     }
@@ -271,7 +278,7 @@ struct TestContext {
     channel: BoxDownlinkChannel<FakeAgent>,
     events: Events,
     sender: Option<Writer>,
-    output_tx: Option<mpsc::UnboundedSender<MapOperation<i32, Text>>>,
+    output_tx: Option<mpsc::UnboundedSender<MapWrite<i32, Text>>>,
     out_rx: ByteReader,
     stop_tx: Option<trigger::Sender>,
 }
@@ -405,6 +412,8 @@ enum Instruction {
         notification: DownlinkNotification<MapMessage<i32, Text>>,
         expected: Option<Vec<Event>>,
     },
+    // Continue handling a message that was only partially handled.
+    Resume(Vec<Event>),
     Outgoing(MapOperation<i32, Text>),
     DropOutgoing,
 }
@@ -456,11 +465,20 @@ async fn run_with_expectations(
                     assert!(next.is_none());
                 }
             }
+            Instruction::Resume(expected) => {
+                assert!(matches!(
+                    channel.await_ready().await,
+                    Some(Ok(DownlinkChannelEvent::HandlerReady))
+                ));
+                let handler = channel.next_event(agent).expect("Expected handler.");
+                run_handler(handler, agent);
+                assert_eq!(take_events(events), expected);
+            }
             Instruction::Outgoing(op) => {
                 output_tx
                     .as_ref()
                     .expect("Output dropped.")
-                    .send(op)
+                    .send(op.into())
                     .expect("Channel dropped");
                 assert!(matches!(
                     channel.await_ready().await,
@@ -761,6 +779,70 @@ async fn emit_drop_handlers() {
     clean_shutdown(&mut context, &agent, true).await;
 }
 
+#[tokio::test]
+async fn take_and_drop_within_budget() {
+    let agent = FakeAgent;
+    let config = MapDownlinkConfig {
+        removal_budget: non_zero_usize!(2),
+        ..Default::default()
+    };
+    let mut context = make_hosted_input(&agent, config);
+
+    run_with_expectations(
+        &mut context,
+        &agent,
+        vec![
+            incoming(DownlinkNotification::Linked, Some(vec![Event::Linked])),
+            incoming(upd(1, "a"), None),
+            incoming(upd(2, "b"), None),
+            incoming(upd(3, "c"), None),
+            incoming(upd(4, "d"), None),
+            incoming(upd(5, "e"), None),
+            incoming(upd(6, "f"), None),
+            incoming(upd(7, "g"), None),
+            incoming(
+                DownlinkNotification::Synced,
+                Some(vec![Event::synced([
+                    (1, "a"),
+                    (2, "b"),
+                    (3, "c"),
+                    (4, "d"),
+                    (5, "e"),
+                    (6, "f"),
+                    (7, "g"),
+                ])]),
+            ),
+            incoming(
+                drp(3),
+                Some(vec![
+                    Event::removed(
+                        1,
+                        "a",
+                        [(2, "b"), (3, "c"), (4, "d"), (5, "e"), (6, "f"), (7, "g")],
+                    ),
+                    Event::removed(2, "b", [(3, "c"), (4, "d"), (5, "e"), (6, "f"), (7, "g")]),
+                ]),
+            ),
+            Instruction::Resume(vec![Event::removed(
+                3,
+                "c",
+                [(4, "d"), (5, "e"), (6, "f"), (7, "g")],
+            )]),
+            incoming(
+                tke(1),
+                Some(vec![
+                    Event::removed(5, "e", [(4, "d"), (6, "f"), (7, "g")]),
+                    Event::removed(6, "f", [(4, "d"), (7, "g")]),
+                ]),
+            ),
+            Instruction::Resume(vec![Event::removed(7, "g", [(4, "d")])]),
+        ],
+    )
+    .await;
+
+    clean_shutdown(&mut context, &agent, true).await;
+}
+
 #[tokio::test]
 async fn emit_drop_all_handlers() {
     let agent = FakeAgent;
@@ -839,7 +921,7 @@ async fn revive_unlinked_downlink() {
 
 #[tokio::test]
 async fn map_downlink_writer() {
-    let (op_tx, op_rx) = mpsc::unbounded_channel::<MapOperation<i32, Text>>();
+    let (op_tx, op_rx) = mpsc::unbounded_channel::<MapWrite<i32, Text>>();
     let (tx, rx) = byte_channel::byte_channel(BUFFER_SIZE);
     let (stop_tx, _stop_rx) = trigger::trigger();
     let mut stream = MapWriteStream::new(tx, op_rx);
@@ -911,6 +993,81 @@ async fn map_downlink_writer() {
     assert_eq!(key1, Some(Text::new("j")));
 }
 
+#[tokio::test]
+async fn map_downlink_writer_batches() {
+    let (op_tx, op_rx) = mpsc::unbounded_channel::<MapWrite<i32, Text>>();
+    let (tx, rx) = byte_channel::byte_channel(BUFFER_SIZE);
+    let (stop_tx, _stop_rx) = trigger::trigger();
+    let mut stream = MapWriteStream::new(tx, op_rx);
+
+    let receiver = FramedRead::new(rx, MapOperationDecoder::<i32, Text>::default());
+
+    let driver = async move {
+        while let Some(result) = stream.next().await {
+            assert!(result.is_ok());
+        }
+    };
+
+    let read = async move { receiver.collect::<Vec<_>>().await };
+
+    let write = async move {
+        let handle = MapDownlinkHandle::new(
+            Address::text(None, NODE, LANE),
+            op_tx,
+            stop_tx,
+            &Default::default(),
+        );
+        assert!(handle
+            .update_many([
+                (1, Text::new("a")),
+                (1, Text::new("b")),
+                (2, Text::new("c"))
+            ])
+            .is_ok());
+        assert!(handle.apply([]).is_ok());
+        assert!(handle
+            .apply([
+                MapOperation::Remove { key: 1 },
+                MapOperation::Clear,
+                MapOperation::Update {
+                    key: 2,
+                    value: Text::new("d")
+                },
+            ])
+            .is_ok());
+    };
+
+    let (_, received, r) = join3(driver, read, tokio::spawn(write)).await;
+    assert!(r.is_ok());
+
+    let received = received
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("Decoding failed.");
+
+    let expected = vec![
+        MapOperation::Update {
+            key: 1,
+            value: Text::new("a"),
+        },
+        MapOperation::Update {
+            key: 1,
+            value: Text::new("b"),
+        },
+        MapOperation::Update {
+            key: 2,
+            value: Text::new("c"),
+        },
+        MapOperation::Remove { key: 1 },
+        MapOperation::Clear,
+        MapOperation::Update {
+            key: 2,
+            value: Text::new("d"),
+        },
+    ];
+    assert_eq!(received, expected);
+}
+
 #[derive(Debug, Default)]
 struct TestWaker {
     woken: AtomicBool,
@@ -1060,7 +1217,7 @@ impl Sink<MapOperation<i32, Text>> for TestSink {
 }
 
 struct WriteStreamContext {
-    set_tx: Option<mpsc::UnboundedSender<MapOperation<i32, Text>>>,
+    set_tx: Option<mpsc::UnboundedSender<MapWrite<i32, Text>>>,
     sink: Arc<Mutex<TestSinkInner>>,
     wake_state: Arc<TestWaker>,
     waker: Waker,
@@ -1083,7 +1240,15 @@ impl WriteStreamContext {
         self.set_tx
             .as_mut()
             .expect("Sender closed.")
-            .send(operation)
+            .send(operation.into())
+            .expect("Channel dropped.");
+    }
+
+    fn send_batch(&mut self, operations: Vec<MapOperation<i32, Text>>) {
+        self.set_tx
+            .as_mut()
+            .expect("Sender closed.")
+            .send(MapWrite::Batch(operations))
             .expect("Channel dropped.");
     }
 
@@ -1095,7 +1260,7 @@ impl WriteStreamContext {
 fn init_write_test(
     sink: Option<TestSinkInner>,
 ) -> (WriteStreamContext, MapWriteStream<i32, Text, TestSink>) {
-    let (set_tx, set_rx) = mpsc::unbounded_channel::<MapOperation<i32, Text>>();
+    let (set_tx, set_rx) = mpsc::unbounded_channel::<MapWrite<i32, Text>>();
 
     let inner = Arc::new(Mutex::new(sink.unwrap_or_default()));
     let sink = TestSink {
@@ -1216,6 +1381,49 @@ fn writer_data_available_no_capacity() {
     assert_eq!(operations, &[op]);
 }
 
+#[test]
+fn writer_batch_written_contiguously() {
+    let (mut context, stream) = init_write_test(Some(TestSinkInner::full()));
+    let mut stream = pin!(stream);
+
+    let upd = |key: i32, value: &str| MapOperation::Update {
+        key,
+        value: Text::new(value),
+    };
+
+    context.send(upd(1, "a"));
+    context.send(upd(2, "x"));
+    context.send_batch(vec![upd(1, "b"), upd(3, "y"), upd(1, "c")]);
+    context.send(upd(1, "d"));
+
+    for _ in 0..4 {
+        assert!(stream
+            .as_mut()
+            .poll_next(&mut context.future_context())
+            .is_pending());
+    }
+    assert!(context.sink_data().operations.is_empty());
+
+    context.free_capacity();
+    for _ in 0..6 {
+        let poll = stream.as_mut().poll_next(&mut context.future_context());
+        assert!(matches!(poll, Poll::Ready(Some(Ok(_)))));
+    }
+
+    let TestSinkInner { operations, .. } = &*context.sink_data();
+    assert_eq!(
+        operations,
+        &[
+            upd(1, "a"),
+            upd(2, "x"),
+            upd(1, "b"),
+            upd(3, "y"),
+            upd(1, "c"),
+            upd(1, "d")
+        ]
+    );
+}
+
 #[test]
 fn writer_stop_no_data() {
     let (mut context, stream) = init_write_test(None);
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod command;
 mod event;
 mod map;
 mod value;
@@ -21,12 +22,20 @@ use std::sync::{
     Arc, Weak,
 };
 
+pub use command::{CommandDownlinkError, CommandDownlinkHandle, CommandPump};
 pub use event::{EventDownlinkFactory, EventDownlinkHandle};
-pub use map::{MapDownlinkFactory, MapDownlinkHandle};
+pub use map::{MapDownlinkFactory, MapDownlinkHandle, MapWrite};
 use swimos_utilities::byte_channel::ByteWriter;
+use tokio::sync::watch;
+use uuid::Uuid;
 pub use value::{ValueDownlinkFactory, ValueDownlinkHandle};
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+use crate::{
+    event_handler::{ActionContext, HandlerAction, StepResult},
+    meta::AgentMetadata,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum DlState {
     Unlinked,
     Linked,
@@ -70,11 +79,25 @@ impl From<u8> for DlState {
 #[derive(Debug)]
 pub(super) struct DlStateTracker {
     state: Arc<AtomicU8>,
+    changes: Option<watch::Sender<DlState>>,
 }
 
 impl DlStateTracker {
     pub fn new(state: Arc<AtomicU8>) -> Self {
-        let tracker = DlStateTracker { state };
+        let tracker = DlStateTracker {
+            state,
+            changes: None,
+        };
+        tracker.set(DlState::Unlinked);
+        tracker
+    }
+
+    /// Create a tracker that also publishes changes in the state to a watch channel.
+    fn with_changes(state: Arc<AtomicU8>, changes: watch::Sender<DlState>) -> Self {
+        let tracker = DlStateTracker {
+            state,
+            changes: Some(changes),
+        };
         tracker.set(DlState::Unlinked);
         tracker
     }
@@ -95,7 +118,14 @@ impl DlStateObserver {
 
 impl DlStateTracker {
     fn set(&self, state: DlState) {
-        self.state.store(state.into(), Ordering::Release)
+        self.state.store(state.into(), Ordering::Release);
+        if let Some(changes) = &self.changes {
+            changes.send_if_modified(|current| {
+                let modified = *current != state;
+                *current = state;
+                modified
+            });
+        }
     }
 
     fn get(&self) -> DlState {
@@ -112,6 +142,37 @@ impl DlStateObserver {
     }
 }
 
+/// Runs the handlers for an event received by a downlink with the ID of the remote that sent the
+/// command that caused the event attached to the agent metadata (this is only reported by lanes
+/// that track the origins of commands).
+struct Originated<H> {
+    origin: Option<Uuid>,
+    handler: H,
+}
+
+impl<H> Originated<H> {
+    fn new(origin: Option<Uuid>, handler: H) -> Self {
+        Originated { origin, handler }
+    }
+}
+
+impl<Context, H> HandlerAction<Context> for Originated<H>
+where
+    H: HandlerAction<Context>,
+{
+    type Completion = H::Completion;
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<Context>,
+        meta: AgentMetadata,
+        context: &Context,
+    ) -> StepResult<Self::Completion> {
+        let Originated { origin, handler } = self;
+        handler.step(action_context, meta.with_command_origin(*origin), context)
+    }
+}
+
 enum OutputWriter<W: RestartableOutput> {
     Active(W),
     Inactive(W::Source),
@@ -269,23 +330,28 @@ mod test_support {
         AgentMetadata::new(uri, route_params, &CONFIG)
     }
 
-    pub fn run_handler<FakeAgent>(
+    pub fn run_handler<FakeAgent>(handler: LocalBoxEventHandler<'_, FakeAgent>, agent: &FakeAgent) {
+        let mut ad_hoc_buffer = BytesMut::new();
+        run_handler_with(handler, agent, &NoSpawn, &mut ad_hoc_buffer);
+    }
+
+    pub fn run_handler_with<FakeAgent>(
         mut handler: LocalBoxEventHandler<'_, FakeAgent>,
         agent: &FakeAgent,
+        spawner: &dyn Spawner<FakeAgent>,
+        ad_hoc_buffer: &mut BytesMut,
     ) {
         let uri = make_uri();
         let route_params = HashMap::new();
         let meta = make_meta(&uri, &route_params);
-        let no_spawn = NoSpawn;
         let no_runtime = NoAgentRuntime;
         let mut join_lane_init = HashMap::new();
-        let mut ad_hoc_buffer = BytesMut::new();
         let mut context = ActionContext::new(
-            &no_spawn,
+            spawner,
             &no_runtime,
-            &no_spawn,
+            &NoSpawn,
             &mut join_lane_init,
-            &mut ad_hoc_buffer,
+            ad_hoc_buffer,
         );
         loop {
             match handler.step(&mut context, meta, agent) {
@@ -19,8 +19,9 @@ use futures::{
 use pin_project::pin_project;
 use std::{
     cell::RefCell,
+    future::Future,
     pin::{pin, Pin},
-    sync::{atomic::AtomicU8, Arc},
+    sync::{atomic::AtomicU8, Arc, Mutex, MutexGuard, PoisonError, Weak},
     task::{Context, Poll},
 };
 use swimos_agent_protocol::encoding::downlink::ValueNotificationDecoder;
@@ -37,8 +38,10 @@ use swimos_utilities::{
     byte_channel::{ByteReader, ByteWriter},
     circular_buffer, trigger,
 };
+use tokio::sync::watch;
 use tokio_util::codec::{FramedRead, FramedWrite};
 use tracing::{debug, error, info, trace};
+use uuid::Uuid;
 
 use crate::{
     agent_model::downlink::{
@@ -49,7 +52,7 @@ use crate::{
     event_handler::{HandlerActionExt, LocalBoxEventHandler},
 };
 
-use super::{DlState, DlStateObserver, DlStateTracker, OutputWriter, RestartableOutput};
+use super::{DlState, DlStateTracker, Originated, OutputWriter, RestartableOutput};
 
 #[cfg(test)]
 mod tests;
@@ -86,12 +89,37 @@ impl<T: Send> ValueDlState<T> for RefCell<Option<T>> {
     }
 }
 
+/// A store that is shared between a value downlink and its handle, allowing the handle to read
+/// the current value of the downlink.
+impl<T: Send> ValueDlState<T> for Arc<Mutex<Option<T>>> {
+    fn take_current(&self) -> Option<T> {
+        lock(self).take()
+    }
+
+    fn replace(&self, value: T) {
+        *lock(self) = Some(value);
+    }
+
+    fn with<R, Op: FnOnce(Option<&T>) -> R>(&self, op: Op) -> R {
+        op(lock(self).as_ref())
+    }
+
+    fn clear(&self) {
+        *lock(self) = None;
+    }
+}
+
+fn lock<T>(value: &Mutex<Option<T>>) -> MutexGuard<'_, Option<T>> {
+    value.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
 pub struct ValueDownlinkFactory<T: RecognizerReadable, LC, State> {
     address: Address<Text>,
     state: State,
     lifecycle: LC,
     config: SimpleDownlinkConfig,
     dl_state: Arc<AtomicU8>,
+    changes: watch::Sender<DlState>,
     stop_rx: trigger::Receiver,
     watch_rx: circular_buffer::Receiver<T>,
 }
@@ -115,6 +143,7 @@ where
             lifecycle,
             config,
             dl_state: Default::default(),
+            changes: watch::channel(DlState::Unlinked).0,
             stop_rx,
             watch_rx,
         }
@@ -136,6 +165,7 @@ where
             lifecycle,
             config,
             dl_state,
+            changes,
             stop_rx,
             watch_rx,
         } = self;
@@ -145,17 +175,27 @@ where
             write_stream: Writes::Inactive(watch_rx),
             state,
             next: None,
+            origin: None,
             lifecycle,
             config,
-            dl_state: DlStateTracker::new(dl_state),
+            dl_state: DlStateTracker::with_changes(dl_state, changes),
             stop_rx: Some(stop_rx),
         };
         chan.connect(context, sender, receiver);
         Box::new(chan)
     }
+}
 
-    pub fn dl_state(&self) -> &Arc<AtomicU8> {
-        &self.dl_state
+impl<T, LC> ValueDownlinkFactory<T, LC, Arc<Mutex<Option<T>>>>
+where
+    T: RecognizerReadable,
+{
+    /// Create an observer that allows a handle to inspect the state and value of the downlink.
+    pub fn observer(&self) -> ValueDlObserver<T> {
+        ValueDlObserver {
+            value: Arc::downgrade(&self.state),
+            changes: self.changes.subscribe(),
+        }
     }
 }
 
@@ -167,6 +207,8 @@ pub struct HostedValueDownlink<T: RecognizerReadable, LC, State> {
     write_stream: Writes<T>,
     state: State,
     next: Option<Result<DownlinkNotification<T>, FrameIoError>>,
+    // The origin of the command that caused the next event (if it was reported).
+    origin: Option<Uuid>,
     lifecycle: LC,
     config: SimpleDownlinkConfig,
     dl_state: DlStateTracker,
@@ -295,6 +337,7 @@ where
             receiver,
             state,
             next,
+            origin,
             lifecycle,
             dl_state,
             config:
@@ -321,12 +364,12 @@ where
                 Ok(DownlinkNotification::Event { body }) => {
                     trace!(address = %address, "Event received for downlink.");
                     let prev = state.take_current();
+                    let origin = origin.take();
                     let handler = if dl_state.get() == DlState::Synced || *events_when_not_synced {
                         let handler = lifecycle
                             .on_event(&body)
-                            .followed_by(lifecycle.on_set(prev, &body))
-                            .boxed_local();
-                        Some(handler)
+                            .followed_by(lifecycle.on_set(prev, &body));
+                        Some(Originated::new(origin, handler).boxed_local())
                     } else {
                         None
                     };
@@ -344,6 +387,24 @@ where
                     }
                     Some(lifecycle.on_unlinked().boxed_local())
                 }
+                Ok(DownlinkNotification::Advisory { advice }) => {
+                    debug!(address = %address, advice = %advice, "Downlink advised to back off by the remote lane.");
+                    None
+                }
+                Ok(DownlinkNotification::NodeStopped { notice }) => {
+                    debug!(address = %address, notice = %notice, "The remote agent stopped.");
+                    None
+                }
+                Ok(DownlinkNotification::Failed { failure }) => {
+                    debug!(address = %address, failure = %failure, "The remote refused the link.");
+                    None
+                }
+                // Agent downlinks do not retain their state when they are relinked.
+                Ok(DownlinkNotification::Version { .. }) => None,
+                Ok(DownlinkNotification::Origin { origin: id }) => {
+                    *origin = Some(id);
+                    None
+                }
                 Err(_) => {
                     debug!(address = %address, "Downlink failed.");
                     state.clear();
@@ -367,6 +428,7 @@ where
             write_stream,
             state,
             next,
+            origin,
             dl_state,
             ..
         } = self;
@@ -374,6 +436,7 @@ where
         write_stream.restart(output);
         state.clear();
         *next = None;
+        *origin = None;
         dl_state.set(DlState::Unlinked);
     }
 
@@ -394,14 +457,62 @@ where
     }
 }
 
-/// A handle which can be used to set the value of a lane through a value downlink or stop the
-/// downlink.
+/// Allows the handle of a value downlink to observe the state and the current value of the
+/// downlink. The default observer is detached and will always report that the downlink has
+/// stopped.
+#[derive(Debug)]
+pub struct ValueDlObserver<T> {
+    value: Weak<Mutex<Option<T>>>,
+    changes: watch::Receiver<DlState>,
+}
+
+impl<T> Default for ValueDlObserver<T> {
+    fn default() -> Self {
+        ValueDlObserver {
+            value: Weak::new(),
+            changes: watch::channel(DlState::Stopped).1,
+        }
+    }
+}
+
+impl<T> ValueDlObserver<T> {
+    fn get(&self) -> DlState {
+        if self.changes.has_changed().is_err() {
+            DlState::Stopped
+        } else {
+            *self.changes.borrow()
+        }
+    }
+
+    fn wait_for(
+        &self,
+        f: fn(DlState) -> bool,
+    ) -> impl Future<Output = Result<(), DownlinkRuntimeError>> + Send + 'static {
+        let mut changes = self.changes.clone();
+        async move {
+            let reached = changes
+                .wait_for(|state| f(*state) || *state == DlState::Stopped)
+                .await
+                .is_ok_and(|state| f(*state));
+            if reached {
+                Ok(())
+            } else {
+                Err(DownlinkRuntimeError::DownlinkConnectionFailed(
+                    DownlinkFailureReason::DownlinkStopped,
+                ))
+            }
+        }
+    }
+}
+
+/// A handle which can be used to set the value of a lane through a value downlink, inspect its
+/// current value or stop the downlink.
 #[derive(Debug)]
 pub struct ValueDownlinkHandle<T> {
     address: Address<Text>,
     inner: circular_buffer::Sender<T>,
     stop_tx: Option<trigger::Sender>,
-    observer: DlStateObserver,
+    observer: ValueDlObserver<T>,
 }
 
 impl<T> ValueDownlinkHandle<T> {
@@ -409,13 +520,13 @@ impl<T> ValueDownlinkHandle<T> {
         address: Address<Text>,
         inner: circular_buffer::Sender<T>,
         stop_tx: trigger::Sender,
-        state: &Arc<AtomicU8>,
+        observer: ValueDlObserver<T>,
     ) -> Self {
         ValueDownlinkHandle {
             address,
             inner,
             stop_tx: Some(stop_tx),
-            observer: DlStateObserver::new(state),
+            observer,
         }
     }
 }
@@ -438,6 +549,35 @@ impl<T> ValueDownlinkHandle<T> {
     pub fn is_linked(&self) -> bool {
         matches!(self.observer.get(), DlState::Linked | DlState::Synced)
     }
+
+    /// Returns a future that completes when the downlink has linked to the remote lane. The
+    /// future will fail if the downlink stops before it links. Event handlers can wait for it
+    /// using [`crate::agent_lifecycle::HandlerContext::suspend`].
+    pub fn await_linked(
+        &self,
+    ) -> impl Future<Output = Result<(), DownlinkRuntimeError>> + Send + 'static {
+        self.observer.wait_for(|state| state.is_linked())
+    }
+
+    /// Returns a future that completes when the downlink has synced with the remote lane, after
+    /// which [`Self::get`] will return its value. The future will fail if the downlink stops
+    /// before it syncs.
+    pub fn await_synced(
+        &self,
+    ) -> impl Future<Output = Result<(), DownlinkRuntimeError>> + Send + 'static {
+        self.observer.wait_for(|state| state == DlState::Synced)
+    }
+
+    /// The last value received by the downlink, if it is linked.
+    pub fn get(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.observer
+            .value
+            .upgrade()
+            .and_then(|value| lock(&value).clone())
+    }
 }
 
 impl<T> ValueDownlinkHandle<T>
@@ -44,6 +44,7 @@ use swimos_utilities::{
 };
 use tokio::{io::AsyncWriteExt, task::yield_now};
 use tokio_util::codec::{FramedRead, FramedWrite};
+use uuid::Uuid;
 
 use super::{SimpleDownlinkConfig, ValueDownlinkFactory};
 use crate::{
@@ -52,9 +53,10 @@ use crate::{
         BoxDownlinkChannel, DownlinkChannelEvent,
     },
     downlink_lifecycle::{
-        OnDownlinkEvent, OnDownlinkSet, OnFailed, OnLinked, OnSynced, OnUnlinked,
+        downlink_event_stream, OnDownlinkEvent, OnDownlinkSet, OnFailed, OnLinked, OnSynced,
+        OnUnlinked, ValueDownlinkEvent,
     },
-    event_handler::{HandlerActionExt, LocalBoxEventHandler, SideEffect},
+    event_handler::{GetCommandOrigin, HandlerActionExt, LocalBoxEventHandler, SideEffect},
 };
 
 use std::time::Duration;
@@ -84,6 +86,7 @@ enum TestEvent {
     Linked,
     Synced(i32),
     Event(i32),
+    Origin(Uuid),
     Set(Option<i32>, i32),
     Unlinked,
     Failed,
@@ -159,10 +162,19 @@ impl OnDownlinkEvent<i32, FakeAgent> for FakeLifecycle {
     fn on_event<'a>(&'a self, value: &i32) -> Self::OnEventHandler<'a> {
         let state = self.inner.clone();
         let n = *value;
-        SideEffect::from(move || {
-            state.lock().push(TestEvent::Event(n));
-        })
-        .boxed_local()
+        let handler = HandlerActionExt::<FakeAgent>::and_then(
+            GetCommandOrigin::default(),
+            move |origin: Option<Uuid>| {
+                SideEffect::from(move || {
+                    let mut guard = state.lock();
+                    if let Some(origin) = origin {
+                        guard.push(TestEvent::Origin(origin));
+                    }
+                    guard.push(TestEvent::Event(n));
+                })
+            },
+        );
+        handler.boxed_local()
     }
 }
 
@@ -307,6 +319,13 @@ fn to_bytes(not: DownlinkNotification<i32>) -> DownlinkNotification<Vec<u8>> {
             }
         }
         DownlinkNotification::Unlinked => DownlinkNotification::Unlinked,
+        DownlinkNotification::Advisory { advice } => DownlinkNotification::Advisory { advice },
+        DownlinkNotification::NodeStopped { notice } => {
+            DownlinkNotification::NodeStopped { notice }
+        }
+        DownlinkNotification::Failed { failure } => DownlinkNotification::Failed { failure },
+        DownlinkNotification::Version { version } => DownlinkNotification::Version { version },
+        DownlinkNotification::Origin { origin } => DownlinkNotification::Origin { origin },
     }
 }
 
@@ -504,6 +523,191 @@ async fn emit_event_handlers() {
     clean_shutdown(&mut context, &agent, true).await;
 }
 
+#[tokio::test]
+async fn emit_originated_event_handlers() {
+    let agent = FakeAgent;
+    let mut context = make_hosted_input(&agent, SimpleDownlinkConfig::default());
+    let origin = Uuid::from_u128(584);
+
+    run_with_expectations(
+        &mut context,
+        &agent,
+        vec![
+            incoming(DownlinkNotification::Linked, Some(vec![TestEvent::Linked])),
+            incoming(DownlinkNotification::Event { body: 13 }, None),
+            incoming(
+                DownlinkNotification::Synced,
+                Some(vec![TestEvent::Synced(13)]),
+            ),
+            incoming(DownlinkNotification::Origin { origin }, None),
+            incoming(
+                DownlinkNotification::Event { body: 15 },
+                Some(vec![
+                    TestEvent::Origin(origin),
+                    TestEvent::Event(15),
+                    TestEvent::Set(Some(13), 15),
+                ]),
+            ),
+            incoming(
+                DownlinkNotification::Event { body: 16 },
+                Some(vec![TestEvent::Event(16), TestEvent::Set(Some(15), 16)]),
+            ),
+        ],
+    )
+    .await;
+
+    clean_shutdown(&mut context, &agent, true).await;
+}
+
+async fn deliver_notifications(
+    channel: &mut BoxDownlinkChannel<FakeAgent>,
+    sender: &mut FramedWrite<ByteWriter, DownlinkNotificationEncoder>,
+    agent: &FakeAgent,
+    notifications: Vec<DownlinkNotification<i32>>,
+) {
+    for notification in notifications {
+        assert!(sender.send(to_bytes(notification)).await.is_ok());
+        assert!(matches!(channel.await_ready().await, Some(Ok(_))));
+        if let Some(handler) = channel.next_event(agent) {
+            run_handler(handler, agent);
+        }
+    }
+}
+
+#[tokio::test]
+async fn forward_events_to_stream() {
+    let agent = FakeAgent;
+    let (lc, mut events) = downlink_event_stream::<ValueDownlinkEvent<i32>>(non_zero_usize!(2));
+
+    let (in_tx, in_rx) = byte_channel::byte_channel(BUFFER_SIZE);
+    let (out_tx, _out_rx) = byte_channel::byte_channel(BUFFER_SIZE);
+    let address = Address::new(None, Text::new("/node"), Text::new("lane"));
+    let (_stop_tx, stop_rx) = trigger::trigger();
+    let (_write_tx, write_rx) = circular_buffer::channel(OUT_CHAN_SIZE);
+    let fac = ValueDownlinkFactory::new(
+        address,
+        lc,
+        State::default(),
+        SimpleDownlinkConfig::default(),
+        stop_rx,
+        write_rx,
+    );
+    let mut channel = fac.create(&agent, out_tx, in_rx);
+    let mut sender = FramedWrite::new(in_tx, DownlinkNotificationEncoder);
+
+    // The buffer has space for two events so the third is discarded.
+    deliver_notifications(
+        &mut channel,
+        &mut sender,
+        &agent,
+        vec![
+            DownlinkNotification::Linked,
+            DownlinkNotification::Event { body: 13 },
+            DownlinkNotification::Synced,
+            DownlinkNotification::Event { body: 15 },
+        ],
+    )
+    .await;
+
+    assert_eq!(events.next().await, Some(ValueDownlinkEvent::Linked));
+    assert_eq!(events.next().await, Some(ValueDownlinkEvent::Synced(13)));
+    assert_eq!(events.dropped(), 1);
+
+    deliver_notifications(
+        &mut channel,
+        &mut sender,
+        &agent,
+        vec![DownlinkNotification::Event { body: 16 }],
+    )
+    .await;
+    assert_eq!(
+        events.next().await,
+        Some(ValueDownlinkEvent::Set {
+            previous: Some(15),
+            value: 16
+        })
+    );
+}
+
+type SharedState = Arc<std::sync::Mutex<Option<i32>>>;
+
+#[tokio::test]
+async fn handle_observes_downlink() {
+    let agent = FakeAgent;
+    let lc = FakeLifecycle {
+        inner: Default::default(),
+    };
+
+    let (in_tx, in_rx) = byte_channel::byte_channel(BUFFER_SIZE);
+    let (out_tx, _out_rx) = byte_channel::byte_channel(BUFFER_SIZE);
+    let address = Address::new(None, Text::new("/node"), Text::new("lane"));
+    let (stop_tx, stop_rx) = trigger::trigger();
+    let (write_tx, write_rx) = circular_buffer::channel(OUT_CHAN_SIZE);
+    let fac = ValueDownlinkFactory::new(
+        address.clone(),
+        lc,
+        SharedState::default(),
+        SimpleDownlinkConfig::default(),
+        stop_rx,
+        write_rx,
+    );
+    let handle = ValueDownlinkHandle::new(address, write_tx, stop_tx, fac.observer());
+    let mut channel = fac.create(&agent, out_tx, in_rx);
+    let mut sender = FramedWrite::new(in_tx, DownlinkNotificationEncoder);
+
+    let linked = handle.await_linked();
+    let synced = handle.await_synced();
+    assert!(!handle.is_linked());
+    assert_eq!(handle.get(), None);
+
+    deliver_notifications(
+        &mut channel,
+        &mut sender,
+        &agent,
+        vec![DownlinkNotification::Linked],
+    )
+    .await;
+    assert!(linked.await.is_ok());
+    assert!(handle.is_linked());
+
+    deliver_notifications(
+        &mut channel,
+        &mut sender,
+        &agent,
+        vec![
+            DownlinkNotification::Event { body: 5 },
+            DownlinkNotification::Synced,
+        ],
+    )
+    .await;
+    assert!(synced.await.is_ok());
+    assert_eq!(handle.get(), Some(5));
+
+    deliver_notifications(
+        &mut channel,
+        &mut sender,
+        &agent,
+        vec![DownlinkNotification::Event { body: 6 }],
+    )
+    .await;
+    assert_eq!(handle.get(), Some(6));
+
+    deliver_notifications(
+        &mut channel,
+        &mut sender,
+        &agent,
+        vec![DownlinkNotification::Unlinked],
+    )
+    .await;
+    assert!(!handle.is_linked());
+    assert_eq!(handle.get(), None);
+
+    let synced = handle.await_synced();
+    drop(channel);
+    assert!(handle.is_stopped());
+    assert!(synced.await.is_err());
+}
+
 #[tokio::test]
 async fn emit_events_before_synced() {
     let agent = FakeAgent;
@@ -629,7 +833,7 @@ async fn value_downlink_writer() {
 
     let write = async move {
         let address = Address::new(None, Text::new("/node"), Text::new("lane"));
-        let mut handle = ValueDownlinkHandle::new(address, set_tx, stop_tx, &Default::default());
+        let mut handle = ValueDownlinkHandle::new(address, set_tx, stop_tx, Default::default());
         for i in 0..=10 {
             assert!(handle.set(i).is_ok());
             if i % 2 == 0 {
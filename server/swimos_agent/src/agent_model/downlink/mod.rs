@@ -16,13 +16,15 @@ mod hosted;
 #[cfg(test)]
 mod tests;
 
-use std::{cell::RefCell, marker::PhantomData};
+use std::{
+    marker::PhantomData,
+    sync::{atomic::AtomicU8, Arc, Mutex},
+};
 
 use futures::future::BoxFuture;
 use std::hash::Hash;
-use swimos_agent_protocol::MapOperation;
-use swimos_api::{address::Address, agent::DownlinkKind};
-use swimos_form::{read::RecognizerReadable, Form};
+use swimos_api::{address::Address, agent::DownlinkKind, error::DownlinkRuntimeError};
+use swimos_form::{read::RecognizerReadable, write::StructuralWritable, Form};
 use swimos_model::Text;
 use swimos_utilities::byte_channel::{ByteReader, ByteWriter};
 use swimos_utilities::{circular_buffer, trigger};
@@ -32,26 +34,40 @@ use tracing::error;
 
 use crate::event_handler::LocalBoxEventHandler;
 use crate::{
-    config::{MapDownlinkConfig, SimpleDownlinkConfig},
+    config::{CommandDownlinkConfig, MapDownlinkConfig, SimpleDownlinkConfig},
     downlink_lifecycle::{EventDownlinkLifecycle, MapDownlinkLifecycle, ValueDownlinkLifecycle},
-    event_handler::{ActionContext, HandlerAction, StepResult, UnitHandler},
+    event_handler::{ActionContext, Either, EventHandler, HandlerAction, StepResult, UnitHandler},
     meta::AgentMetadata,
 };
 
-use self::hosted::{EventDownlinkFactory, MapDownlinkFactory, ValueDownlinkFactory};
-pub use self::hosted::{EventDownlinkHandle, MapDownlinkHandle, ValueDownlinkHandle};
+pub use self::hosted::{
+    CommandDownlinkError, CommandDownlinkHandle, EventDownlinkHandle, MapDownlinkHandle,
+    ValueDownlinkHandle,
+};
+use self::hosted::{
+    CommandPump, EventDownlinkFactory, MapDownlinkFactory, MapWrite, ValueDownlinkFactory,
+};
 
 struct Inner<LC> {
     address: Address<Text>,
     lifecycle: LC,
 }
 
+/// The default callback for when a downlink cannot be opened. The failure is always logged so
+/// this does nothing further.
+pub type IgnoreFailure = fn(DownlinkRuntimeError) -> UnitHandler;
+
+fn ignore_failure(_error: DownlinkRuntimeError) -> UnitHandler {
+    UnitHandler::default()
+}
+
 /// [`HandlerAction`] that attempts to open a value downlink to a remote lane and results in
 /// a handle to the downlink.
-pub struct OpenValueDownlinkAction<T, LC> {
+pub struct OpenValueDownlinkAction<T, LC, F = IgnoreFailure> {
     _type: PhantomData<fn(T) -> T>,
     inner: Option<Inner<LC>>,
     config: SimpleDownlinkConfig,
+    on_failed: Option<F>,
 }
 
 /// [`HandlerAction`] that attempts to open an event downlink to a remote lane.
@@ -66,10 +82,20 @@ type KvInvariant<K, V> = fn(K, V) -> (K, V);
 
 /// [`HandlerAction`] that attempts to open a map downlink to a remote lane and results in
 /// a handle to the downlink.
-pub struct OpenMapDownlinkAction<K, V, LC> {
+pub struct OpenMapDownlinkAction<K, V, LC, F = IgnoreFailure> {
     _type: PhantomData<KvInvariant<K, V>>,
     inner: Option<Inner<LC>>,
     config: MapDownlinkConfig,
+    on_failed: Option<F>,
+}
+
+/// [`HandlerAction`] that opens a command downlink to a remote lane and results in a handle that
+/// can be used to send commands to it. The commands are sent through the ad hoc command channel of
+/// the agent so no link is made to the remote lane.
+pub struct OpenCommandDownlinkAction<T> {
+    _type: PhantomData<fn(T) -> T>,
+    address: Option<Address<Text>>,
+    config: CommandDownlinkConfig,
 }
 
 impl<T, LC> OpenValueDownlinkAction<T, LC> {
@@ -78,6 +104,21 @@ impl<T, LC> OpenValueDownlinkAction<T, LC> {
             _type: PhantomData,
             inner: Some(Inner { address, lifecycle }),
             config,
+            on_failed: Some(ignore_failure),
+        }
+    }
+}
+
+impl<T, LC, F> OpenValueDownlinkAction<T, LC, F> {
+    /// Specify a callback to create an event handler that will be run if the downlink cannot be
+    /// opened (for example, if the host could not be resolved or a connection to it failed).
+    pub fn on_failed<F2>(self, on_failed: F2) -> OpenValueDownlinkAction<T, LC, F2> {
+        let OpenValueDownlinkAction { inner, config, .. } = self;
+        OpenValueDownlinkAction {
+            _type: PhantomData,
+            inner,
+            config,
+            on_failed: Some(on_failed),
         }
     }
 }
@@ -104,16 +145,43 @@ impl<K, V, LC> OpenMapDownlinkAction<K, V, LC> {
             _type: PhantomData,
             inner: Some(Inner { address, lifecycle }),
             config,
+            on_failed: Some(ignore_failure),
         }
     }
 }
 
-impl<T, LC, Context> HandlerAction<Context> for OpenValueDownlinkAction<T, LC>
+impl<K, V, LC, F> OpenMapDownlinkAction<K, V, LC, F> {
+    /// Specify a callback to create an event handler that will be run if the downlink cannot be
+    /// opened (for example, if the host could not be resolved or a connection to it failed).
+    pub fn on_failed<F2>(self, on_failed: F2) -> OpenMapDownlinkAction<K, V, LC, F2> {
+        let OpenMapDownlinkAction { inner, config, .. } = self;
+        OpenMapDownlinkAction {
+            _type: PhantomData,
+            inner,
+            config,
+            on_failed: Some(on_failed),
+        }
+    }
+}
+
+impl<T> OpenCommandDownlinkAction<T> {
+    pub fn new(address: Address<Text>, config: CommandDownlinkConfig) -> Self {
+        OpenCommandDownlinkAction {
+            _type: PhantomData,
+            address: Some(address),
+            config,
+        }
+    }
+}
+
+impl<T, LC, F, H, Context> HandlerAction<Context> for OpenValueDownlinkAction<T, LC, F>
 where
     Context: 'static,
     T: Form + Send + 'static,
     LC: ValueDownlinkLifecycle<T, Context> + Send + 'static,
     T::Rec: Send,
+    F: FnOnce(DownlinkRuntimeError) -> H + Send + 'static,
+    H: EventHandler<Context> + Send + 'static,
 {
     type Completion = ValueDownlinkHandle<T>;
 
@@ -123,13 +191,21 @@ where
         _meta: AgentMetadata,
         _context: &Context,
     ) -> StepResult<Self::Completion> {
-        let OpenValueDownlinkAction { inner, config, .. } = self;
-        if let Some(Inner {
-            address: path,
-            lifecycle,
-        }) = inner.take()
+        let OpenValueDownlinkAction {
+            inner,
+            config,
+            on_failed,
+            ..
+        } = self;
+        if let (
+            Some(Inner {
+                address: path,
+                lifecycle,
+            }),
+            Some(on_failed),
+        ) = (inner.take(), on_failed.take())
         {
-            let state: RefCell<Option<T>> = Default::default();
+            let state: Arc<Mutex<Option<T>>> = Default::default();
             let (tx, rx) = circular_buffer::watch_channel();
             let (stop_tx, stop_rx) = trigger::trigger();
 
@@ -137,17 +213,18 @@ where
 
             let fac =
                 ValueDownlinkFactory::new(path.clone(), lifecycle, state, config, stop_rx, rx);
-            let handle = ValueDownlinkHandle::new(path.clone(), tx, stop_tx, fac.dl_state());
+            let handle = ValueDownlinkHandle::new(path.clone(), tx, stop_tx, fac.observer());
 
             action_context.start_downlink(
                 path,
                 DownlinkKind::Value,
                 move |con, writer, reader| fac.create(con, writer, reader),
-                |result| {
-                    if let Err(err) = result {
+                |result| match result {
+                    Ok(()) => Either::Left(UnitHandler::default()),
+                    Err(err) => {
                         error!(error = %err, "Registering value downlink failed.");
+                        Either::Right(on_failed(err))
                     }
-                    UnitHandler::default()
                 },
             );
 
@@ -209,7 +286,7 @@ where
     }
 }
 
-impl<K, V, LC, Context> HandlerAction<Context> for OpenMapDownlinkAction<K, V, LC>
+impl<K, V, LC, F, H, Context> HandlerAction<Context> for OpenMapDownlinkAction<K, V, LC, F>
 where
     Context: 'static,
     K: Form + Hash + Eq + Ord + Clone + Send + Sync + 'static,
@@ -217,6 +294,8 @@ where
     LC: MapDownlinkLifecycle<K, V, Context> + Send + 'static,
     K::Rec: Send,
     V::Rec: Send,
+    F: FnOnce(DownlinkRuntimeError) -> H + Send + 'static,
+    H: EventHandler<Context> + Send + 'static,
 {
     type Completion = MapDownlinkHandle<K, V>;
 
@@ -226,9 +305,16 @@ where
         _meta: AgentMetadata,
         _context: &Context,
     ) -> StepResult<Self::Completion> {
-        let OpenMapDownlinkAction { inner, config, .. } = self;
-        if let Some(Inner { address, lifecycle }) = inner.take() {
-            let (tx, rx) = mpsc::unbounded_channel::<MapOperation<K, V>>();
+        let OpenMapDownlinkAction {
+            inner,
+            config,
+            on_failed,
+            ..
+        } = self;
+        if let (Some(Inner { address, lifecycle }), Some(on_failed)) =
+            (inner.take(), on_failed.take())
+        {
+            let (tx, rx) = mpsc::unbounded_channel::<MapWrite<K, V>>();
             let (stop_tx, stop_rx) = trigger::trigger();
             let config = *config;
             let fac = MapDownlinkFactory::new(address.clone(), lifecycle, config, stop_rx, rx);
@@ -238,11 +324,12 @@ where
                 address,
                 DownlinkKind::Map,
                 move |con, writer, reader| fac.create(con, writer, reader),
-                |result| {
-                    if let Err(err) = result {
+                |result| match result {
+                    Ok(()) => Either::Left(UnitHandler::default()),
+                    Err(err) => {
                         error!(error = %err, "Registering map downlink failed.");
+                        Either::Right(on_failed(err))
                     }
-                    UnitHandler::default()
                 },
             );
 
@@ -253,6 +340,35 @@ where
     }
 }
 
+impl<T, Context> HandlerAction<Context> for OpenCommandDownlinkAction<T>
+where
+    Context: 'static,
+    T: StructuralWritable + Send + 'static,
+{
+    type Completion = CommandDownlinkHandle<T>;
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<Context>,
+        _meta: AgentMetadata,
+        _context: &Context,
+    ) -> StepResult<Self::Completion> {
+        let OpenCommandDownlinkAction {
+            address, config, ..
+        } = self;
+        if let Some(address) = address.take() {
+            let (tx, rx) = mpsc::channel(config.queue_size.get());
+            let (stop_tx, stop_rx) = trigger::trigger();
+            let dl_state: Arc<AtomicU8> = Default::default();
+            let handle = CommandDownlinkHandle::new(address.clone(), tx, stop_tx, &dl_state);
+            CommandPump::new(address, stop_rx, rx, dl_state).schedule(action_context);
+            StepResult::done(handle)
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
 /// Indication that the downlink task has completed some unit of work.
 #[derive(Debug, PartialEq, Eq)]
 pub enum DownlinkChannelEvent {
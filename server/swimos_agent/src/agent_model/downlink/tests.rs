@@ -21,32 +21,37 @@ use futures::{
     FutureExt, StreamExt,
 };
 use parking_lot::Mutex;
+use swimos_agent_protocol::{encoding::ad_hoc::AdHocCommandDecoder, AdHocCommand};
 use swimos_api::{
     address::Address,
     agent::DownlinkKind,
     agent::{
         AgentConfig, AgentContext, HttpLaneRequestChannel, LaneConfig, StoreKind, WarpLaneKind,
     },
-    error::{AgentRuntimeError, DownlinkRuntimeError, OpenStoreError},
+    error::{AgentRuntimeError, DownlinkFailureReason, DownlinkRuntimeError, OpenStoreError},
 };
 use swimos_model::Text;
 use swimos_utilities::{
     byte_channel::{byte_channel, ByteReader, ByteWriter},
+    encoding::BytesStr,
     non_zero_usize,
     routing::RouteUri,
 };
+use tokio_util::codec::Decoder;
 
 use crate::{
-    config::{MapDownlinkConfig, SimpleDownlinkConfig},
+    config::{CommandDownlinkConfig, MapDownlinkConfig, SimpleDownlinkConfig},
     downlink_lifecycle::{StatefulMapDownlinkLifecycle, StatefulValueDownlinkLifecycle},
     event_handler::{
-        ActionContext, BoxJoinLaneInit, DownlinkSpawner, HandlerAction, HandlerFuture, Spawner,
-        StepResult,
+        ActionContext, BoxJoinLaneInit, DownlinkSpawner, HandlerAction, HandlerFuture, SideEffect,
+        Spawner, StepResult,
     },
     meta::AgentMetadata,
 };
 
-use super::{BoxDownlinkChannel, OpenMapDownlinkAction, OpenValueDownlinkAction};
+use super::{
+    BoxDownlinkChannel, OpenCommandDownlinkAction, OpenMapDownlinkAction, OpenValueDownlinkAction,
+};
 
 struct TestAgent;
 
@@ -63,6 +68,7 @@ struct TestSpawner {
 
 struct ContextInner {
     io: Option<(ByteWriter, ByteReader)>,
+    fail: bool,
 }
 
 struct TestContext {
@@ -74,7 +80,20 @@ impl TestContext {
     fn new(expected_kind: DownlinkKind, io: (ByteWriter, ByteReader)) -> Self {
         TestContext {
             expected_kind,
-            inner: Arc::new(Mutex::new(ContextInner { io: Some(io) })),
+            inner: Arc::new(Mutex::new(ContextInner {
+                io: Some(io),
+                fail: false,
+            })),
+        }
+    }
+
+    fn failing(expected_kind: DownlinkKind) -> Self {
+        TestContext {
+            expected_kind,
+            inner: Arc::new(Mutex::new(ContextInner {
+                io: None,
+                fail: true,
+            })),
         }
     }
 }
@@ -126,7 +145,14 @@ impl AgentContext for TestContext {
         assert_eq!(node, NODE);
         assert_eq!(lane, LANE);
         assert_eq!(kind, self.expected_kind);
-        let io = self.inner.lock().io.take().expect("IO taken twice.");
+        let mut guard = self.inner.lock();
+        if guard.fail {
+            return ready(Err(DownlinkRuntimeError::DownlinkConnectionFailed(
+                DownlinkFailureReason::RemoteStopped,
+            )))
+            .boxed();
+        }
+        let io = guard.io.take().expect("IO taken twice.");
         ready(Ok(io)).boxed()
     }
 
@@ -163,17 +189,33 @@ fn make_meta<'a>(
 }
 
 async fn run_all_and_check(
-    mut spawner: TestSpawner,
+    spawner: TestSpawner,
     context: TestContext,
     meta: AgentMetadata<'_>,
     join_lane_init: &mut HashMap<u64, BoxJoinLaneInit<'static, TestAgent>>,
     agent: &TestAgent,
 ) {
+    let spawner = run_all(spawner, &context, meta, join_lane_init, agent).await;
+    spawner
+        .inner
+        .lock()
+        .downlink
+        .take()
+        .expect("Downlink was not registered.");
+}
+
+async fn run_all(
+    mut spawner: TestSpawner,
+    context: &TestContext,
+    meta: AgentMetadata<'_>,
+    join_lane_init: &mut HashMap<u64, BoxJoinLaneInit<'static, TestAgent>>,
+    agent: &TestAgent,
+) -> TestSpawner {
     let mut ad_hoc_buffer = BytesMut::new();
     while let Some(handler) = spawner.futures.next().await {
         let mut action_context = ActionContext::new(
             &spawner,
-            &context,
+            context,
             &spawner,
             join_lane_init,
             &mut ad_hoc_buffer,
@@ -183,11 +225,6 @@ async fn run_all_and_check(
     assert!(join_lane_init.is_empty());
     assert!(ad_hoc_buffer.is_empty());
     spawner
-        .inner
-        .lock()
-        .downlink
-        .take()
-        .expect("Downlink was not registered.");
 }
 
 fn run_handler<H>(
@@ -281,3 +318,154 @@ async fn open_map_downlink() {
 
     run_all_and_check(spawner, context, meta, &mut join_lane_init, &agent).await;
 }
+
+#[tokio::test]
+async fn open_value_downlink_failed() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let lifecycle = StatefulValueDownlinkLifecycle::<TestAgent, _, i32>::new(());
+
+    let failure = Arc::new(Mutex::new(None));
+    let failure_cpy = failure.clone();
+    let handler = OpenValueDownlinkAction::<i32, _>::new(
+        Address::text(Some(HOST), NODE, LANE),
+        lifecycle,
+        SimpleDownlinkConfig::default(),
+    )
+    .on_failed(move |err| {
+        SideEffect::from(move || {
+            *failure_cpy.lock() = Some(err);
+        })
+    });
+
+    let spawner = TestSpawner::default();
+    let context = TestContext::failing(DownlinkKind::Value);
+
+    let agent = TestAgent;
+    let mut action_context = ActionContext::new(
+        &spawner,
+        &context,
+        &spawner,
+        &mut join_lane_init,
+        &mut ad_hoc_buffer,
+    );
+    let _handle = run_handler(handler, &mut action_context, &agent, meta);
+
+    let spawner = run_all(spawner, &context, meta, &mut join_lane_init, &agent).await;
+    assert!(spawner.inner.lock().downlink.is_none());
+    assert!(matches!(
+        failure.lock().take(),
+        Some(DownlinkRuntimeError::DownlinkConnectionFailed(
+            DownlinkFailureReason::RemoteStopped
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn open_map_downlink_failed() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let lifecycle = StatefulMapDownlinkLifecycle::<TestAgent, _, i32, Text>::new(());
+
+    let failure = Arc::new(Mutex::new(None));
+    let failure_cpy = failure.clone();
+    let handler = OpenMapDownlinkAction::<i32, Text, _>::new(
+        Address::text(Some(HOST), NODE, LANE),
+        lifecycle,
+        MapDownlinkConfig::default(),
+    )
+    .on_failed(move |err| {
+        SideEffect::from(move || {
+            *failure_cpy.lock() = Some(err);
+        })
+    });
+
+    let spawner = TestSpawner::default();
+    let context = TestContext::failing(DownlinkKind::Map);
+
+    let agent = TestAgent;
+    let mut action_context = ActionContext::new(
+        &spawner,
+        &context,
+        &spawner,
+        &mut join_lane_init,
+        &mut ad_hoc_buffer,
+    );
+    let _handle = run_handler(handler, &mut action_context, &agent, meta);
+
+    let spawner = run_all(spawner, &context, meta, &mut join_lane_init, &agent).await;
+    assert!(spawner.inner.lock().downlink.is_none());
+    assert!(matches!(
+        failure.lock().take(),
+        Some(DownlinkRuntimeError::DownlinkConnectionFailed(
+            DownlinkFailureReason::RemoteStopped
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn open_command_downlink() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+
+    let handler = OpenCommandDownlinkAction::<i32>::new(
+        Address::text(Some(HOST), NODE, LANE),
+        CommandDownlinkConfig::default(),
+    );
+
+    let mut spawner = TestSpawner::default();
+    // Opening a link to the lane would fail so this checks that the commands are not sent over one.
+    let context = TestContext::failing(DownlinkKind::Event);
+
+    let agent = TestAgent;
+    let mut action_context = ActionContext::new(
+        &spawner,
+        &context,
+        &spawner,
+        &mut join_lane_init,
+        &mut ad_hoc_buffer,
+    );
+    let handle = run_handler(handler, &mut action_context, &agent, meta);
+    assert!(handle.send(5).is_ok());
+    assert!(handle.send(6).is_ok());
+    drop(handle);
+
+    while let Some(handler) = spawner.futures.next().await {
+        let mut action_context = ActionContext::new(
+            &spawner,
+            &context,
+            &spawner,
+            &mut join_lane_init,
+            &mut ad_hoc_buffer,
+        );
+        run_handler(handler, &mut action_context, &agent, meta);
+    }
+    assert!(spawner.inner.lock().downlink.is_none());
+
+    let mut decoder = AdHocCommandDecoder::<BytesStr, i32>::default();
+    let mut commands = vec![];
+    while let Some(command) = decoder
+        .decode(&mut ad_hoc_buffer)
+        .expect("Decoding failed.")
+    {
+        commands.push(command);
+    }
+    assert!(ad_hoc_buffer.is_empty());
+    let address = Address::new(Some(HOST), NODE, LANE);
+    assert_eq!(
+        commands,
+        vec![
+            AdHocCommand::new(address, 5, false),
+            AdHocCommand::new(address, 6, false),
+        ]
+    );
+}
@@ -0,0 +1,101 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+
+use swimos_api::{agent::WarpLaneKind, error::AgentRuntimeError};
+use swimos_model::Text;
+use swimos_utilities::byte_channel::{ByteReader, ByteWriter};
+
+use crate::event_handler::{LaneSpawnError, LaneSpawner};
+
+#[cfg(test)]
+mod tests;
+
+/// A lane that was opened by an event handler after the agent started and is waiting to be
+/// attached to the agent task.
+#[derive(Debug)]
+pub struct DynamicLane {
+    pub id: u64,
+    pub name: Text,
+    pub kind: WarpLaneKind,
+    pub io: (ByteWriter, ByteReader),
+}
+
+/// Tracks the lanes that are opened by event handlers after the agent has started. Names are
+/// reserved when a lane is requested so that duplicates are rejected before the runtime is
+/// asked to register them. The channels for the lanes are held until the agent task takes them
+/// (after the handler that registered them has completed).
+#[derive(Debug)]
+pub struct DynamicLanes {
+    next_id: Cell<u64>,                 //The ID to assign to the next lane.
+    names: RefCell<HashSet<Text>>, //The names of all lanes of the agent, including reserved names.
+    pending: RefCell<Vec<DynamicLane>>, //Lanes that have not yet been attached to the agent task.
+}
+
+impl DynamicLanes {
+    /// # Arguments
+    /// * `names` - The names of the lanes that the agent was started with.
+    /// * `next_id` - The first ID that is not used by any item of the agent.
+    pub fn new<'a, I>(names: I, next_id: u64) -> Self
+    where
+        I: IntoIterator<Item = &'a Text>,
+    {
+        DynamicLanes {
+            next_id: Cell::new(next_id),
+            names: RefCell::new(names.into_iter().cloned().collect()),
+            pending: Default::default(),
+        }
+    }
+
+    /// Take the lanes that have been registered since this was last called.
+    pub fn take_pending(&self) -> Vec<DynamicLane> {
+        std::mem::take(&mut *self.pending.borrow_mut())
+    }
+}
+
+impl LaneSpawner for DynamicLanes {
+    fn reserve_lane(&self, name: &str) -> Result<(), LaneSpawnError> {
+        let mut guard = self.names.borrow_mut();
+        if guard.contains(name) {
+            Err(LaneSpawnError::DuplicateLane(Text::new(name)))
+        } else {
+            guard.insert(Text::new(name));
+            Ok(())
+        }
+    }
+
+    fn spawn_lane(
+        &self,
+        name: Text,
+        kind: WarpLaneKind,
+        io: Result<(ByteWriter, ByteReader), AgentRuntimeError>,
+    ) -> Result<u64, LaneSpawnError> {
+        match io {
+            Ok(io) => {
+                let id = self.next_id.get();
+                self.next_id.set(id + 1);
+                self.pending
+                    .borrow_mut()
+                    .push(DynamicLane { id, name, kind, io });
+                Ok(id)
+            }
+            Err(err) => {
+                self.names.borrow_mut().remove(&name);
+                Err(err.into())
+            }
+        }
+    }
+}
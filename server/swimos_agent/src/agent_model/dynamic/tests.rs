@@ -0,0 +1,107 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::num::NonZeroUsize;
+
+use swimos_api::{agent::WarpLaneKind, error::AgentRuntimeError};
+use swimos_model::Text;
+use swimos_utilities::{
+    byte_channel::{byte_channel, ByteReader, ByteWriter},
+    non_zero_usize,
+};
+
+use crate::event_handler::{LaneSpawnError, LaneSpawner};
+
+use super::{DynamicLane, DynamicLanes};
+
+const EXISTING: &str = "existing";
+const NEW_LANE: &str = "sensor";
+const FIRST_ID: u64 = 3;
+const BUFFER_SIZE: NonZeroUsize = non_zero_usize!(64);
+
+fn make_lanes() -> DynamicLanes {
+    DynamicLanes::new(&[Text::new(EXISTING)], FIRST_ID)
+}
+
+fn make_io() -> (ByteWriter, ByteReader) {
+    let (tx, _) = byte_channel(BUFFER_SIZE);
+    let (_, rx) = byte_channel(BUFFER_SIZE);
+    (tx, rx)
+}
+
+#[test]
+fn reserve_existing_name() {
+    let lanes = make_lanes();
+    assert_eq!(
+        lanes.reserve_lane(EXISTING),
+        Err(LaneSpawnError::DuplicateLane(Text::new(EXISTING)))
+    );
+}
+
+#[test]
+fn reserve_name_twice() {
+    let lanes = make_lanes();
+    assert!(lanes.reserve_lane(NEW_LANE).is_ok());
+    assert_eq!(
+        lanes.reserve_lane(NEW_LANE),
+        Err(LaneSpawnError::DuplicateLane(Text::new(NEW_LANE)))
+    );
+}
+
+#[test]
+fn spawned_lanes_are_pending() {
+    let lanes = make_lanes();
+    assert!(lanes.reserve_lane(NEW_LANE).is_ok());
+    assert!(lanes.reserve_lane("other").is_ok());
+
+    let first = lanes.spawn_lane(Text::new(NEW_LANE), WarpLaneKind::Map, Ok(make_io()));
+    let second = lanes.spawn_lane(Text::new("other"), WarpLaneKind::Value, Ok(make_io()));
+    assert_eq!(first, Ok(FIRST_ID));
+    assert_eq!(second, Ok(FIRST_ID + 1));
+
+    let pending = lanes.take_pending();
+    let summary = pending
+        .iter()
+        .map(|DynamicLane { id, name, kind, .. }| (*id, name.as_str(), *kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (FIRST_ID, NEW_LANE, WarpLaneKind::Map),
+            (FIRST_ID + 1, "other", WarpLaneKind::Value)
+        ]
+    );
+    assert!(lanes.take_pending().is_empty());
+
+    // The names remain reserved after the lanes have been attached.
+    assert!(lanes.reserve_lane(NEW_LANE).is_err());
+}
+
+#[test]
+fn failed_lane_releases_name() {
+    let lanes = make_lanes();
+    assert!(lanes.reserve_lane(NEW_LANE).is_ok());
+
+    let result = lanes.spawn_lane(
+        Text::new(NEW_LANE),
+        WarpLaneKind::Value,
+        Err(AgentRuntimeError::Stopping),
+    );
+    assert_eq!(
+        result,
+        Err(LaneSpawnError::RuntimeError(AgentRuntimeError::Stopping))
+    );
+    assert!(lanes.take_pending().is_empty());
+    assert!(lanes.reserve_lane(NEW_LANE).is_ok());
+}
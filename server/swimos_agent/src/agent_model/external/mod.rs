@@ -0,0 +1,198 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, error::Error, hash::Hash, time::Duration};
+
+use futures::{future::BoxFuture, Future, FutureExt};
+use swimos_utilities::routing::RouteUri;
+use thiserror::Error;
+use tracing::warn;
+
+use crate::item::{AgentItem, MapItem, ValueItem};
+
+use super::init::InitFn;
+
+type BoxError = Box<dyn Error + Send + Sync + 'static>;
+
+/// Error type for failures to load the initial state of an item from an external source.
+#[derive(Debug, Error)]
+pub enum LaneInitError {
+    /// The state was not loaded within the timeout.
+    #[error("Loading the initial state of an item timed out after {0:?}.")]
+    TimedOut(Duration),
+    /// The loader failed.
+    #[error("Loading the initial state of an item failed: {0}")]
+    Failed(#[source] BoxError),
+}
+
+/// A hook that loads the initial state of an item of an agent from an external source (for
+/// example, a database or an HTTP service). The initializers of an agent are run, concurrently,
+/// after any persisted state has been restored and before the `on_start` handler of the agent
+/// runs. An initializer is only run if no state was restored for its item so that the external
+/// source is only used the first time the agent starts and will never overwrite the persisted
+/// state. No links to the lanes of the agent are admitted until they have all completed so remotes
+/// will never observe the lanes before they have been populated. If an initializer fails, the
+/// agent will fail to start.
+pub trait LaneInitializer<ItemModel>: Send + Sync {
+    /// The ID of the item that is initialized.
+    ///
+    /// # Arguments
+    /// * `item_model` - The instance of the agent.
+    fn item_id(&self, item_model: &ItemModel) -> u64;
+
+    /// Load the initial state of the item.
+    ///
+    /// # Arguments
+    /// * `route` - The node URI of the agent instance.
+    /// * `route_params` - Parameters extracted from the route URI of the agent instance.
+    fn load(
+        &self,
+        route: &RouteUri,
+        route_params: &HashMap<String, String>,
+    ) -> BoxFuture<'static, Result<InitFn<ItemModel>, LaneInitError>>;
+}
+
+/// A [`LaneInitializer`] that sets the state of a value-like or map-like item (lane or store) to
+/// the result of an asynchronous function. Optionally, the load can be given a timeout and a
+/// fallback value to use if the load fails or times out. The state is set without triggering any
+/// lifecycle events for the item.
+pub struct ExternalInitializer<ItemModel, Item, T, F> {
+    projection: fn(&ItemModel) -> &Item,
+    id: fn(&Item) -> u64,
+    init: fn(&Item, T),
+    load: F,
+    timeout: Option<Duration>,
+    fallback: Option<T>,
+}
+
+impl<ItemModel, Item, T, F> ExternalInitializer<ItemModel, Item, T, F> {
+    /// Initialize a value-like item from an external source.
+    ///
+    /// # Arguments
+    /// * `projection` - Projection from the agent to the item.
+    /// * `load` - Asynchronous function to load the value.
+    pub fn value(projection: fn(&ItemModel) -> &Item, load: F) -> Self
+    where
+        Item: ValueItem<T>,
+    {
+        ExternalInitializer {
+            projection,
+            id: <Item as AgentItem>::id,
+            init: <Item as ValueItem<T>>::init,
+            load,
+            timeout: None,
+            fallback: None,
+        }
+    }
+
+    /// Set a timeout for the load.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        ExternalInitializer {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Set a fallback value to use if the load fails or times out.
+    pub fn with_fallback(self, fallback: T) -> Self {
+        ExternalInitializer {
+            fallback: Some(fallback),
+            ..self
+        }
+    }
+}
+
+impl<ItemModel, Item, K, V, F> ExternalInitializer<ItemModel, Item, HashMap<K, V>, F> {
+    /// Initialize a map-like item from an external source.
+    ///
+    /// # Arguments
+    /// * `projection` - Projection from the agent to the item.
+    /// * `load` - Asynchronous function to load the contents of the map.
+    pub fn map(projection: fn(&ItemModel) -> &Item, load: F) -> Self
+    where
+        Item: MapItem<K, V>,
+        K: Eq + Hash,
+    {
+        ExternalInitializer {
+            projection,
+            id: <Item as AgentItem>::id,
+            init: <Item as MapItem<K, V>>::init,
+            load,
+            timeout: None,
+            fallback: None,
+        }
+    }
+}
+
+impl<ItemModel, Item, T, F, Fut, E> LaneInitializer<ItemModel>
+    for ExternalInitializer<ItemModel, Item, T, F>
+where
+    ItemModel: 'static,
+    Item: 'static,
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&RouteUri, &HashMap<String, String>) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<T, E>> + Send + 'static,
+    E: Error + Send + Sync + 'static,
+{
+    fn item_id(&self, item_model: &ItemModel) -> u64 {
+        (self.id)((self.projection)(item_model))
+    }
+
+    fn load(
+        &self,
+        route: &RouteUri,
+        route_params: &HashMap<String, String>,
+    ) -> BoxFuture<'static, Result<InitFn<ItemModel>, LaneInitError>> {
+        let ExternalInitializer {
+            projection,
+            init,
+            id: _,
+            load,
+            timeout,
+            fallback,
+        } = self;
+        let projection = *projection;
+        let init = *init;
+        let timeout = *timeout;
+        let fallback = fallback.clone();
+        let load_fut = load(route, route_params);
+        async move {
+            let result = if let Some(t) = timeout {
+                match tokio::time::timeout(t, load_fut).await {
+                    Ok(result) => result.map_err(|e| LaneInitError::Failed(Box::new(e))),
+                    Err(_) => Err(LaneInitError::TimedOut(t)),
+                }
+            } else {
+                load_fut
+                    .await
+                    .map_err(|e| LaneInitError::Failed(Box::new(e)))
+            };
+            let value = match (result, fallback) {
+                (Ok(value), _) => value,
+                (Err(error), Some(fallback)) => {
+                    warn!(error = %error, "Failed to load the initial state of an item. Using the fallback value.");
+                    fallback
+                }
+                (Err(error), None) => return Err(error),
+            };
+            let f_init: InitFn<ItemModel> =
+                Box::new(move |agent: &ItemModel| init(projection(agent), value));
+            Ok(f_init)
+        }
+        .boxed()
+    }
+}
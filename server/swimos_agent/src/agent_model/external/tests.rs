@@ -0,0 +1,138 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, time::Duration};
+
+use futures::future::{pending, ready};
+use swimos_utilities::routing::RouteUri;
+use thiserror::Error;
+
+use crate::lanes::{MapLane, ValueLane};
+
+use super::{ExternalInitializer, LaneInitError, LaneInitializer};
+
+const VAL_ID: u64 = 0;
+const MAP_ID: u64 = 1;
+const NODE_URI: &str = "/node";
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+struct TestAgent {
+    value: ValueLane<i32>,
+    map: MapLane<i32, String>,
+}
+
+impl Default for TestAgent {
+    fn default() -> Self {
+        TestAgent {
+            value: ValueLane::new(VAL_ID, 0),
+            map: MapLane::new(MAP_ID, HashMap::new()),
+        }
+    }
+}
+
+impl TestAgent {
+    const VALUE: fn(&TestAgent) -> &ValueLane<i32> = |agent| &agent.value;
+    const MAP: fn(&TestAgent) -> &MapLane<i32, String> = |agent| &agent.map;
+}
+
+#[derive(Debug, Error)]
+#[error("Source unavailable.")]
+struct Unavailable;
+
+fn make_uri() -> RouteUri {
+    RouteUri::try_from(NODE_URI).expect("Bad URI.")
+}
+
+async fn run_init<I>(initializer: I, agent: &TestAgent) -> Result<(), LaneInitError>
+where
+    I: LaneInitializer<TestAgent>,
+{
+    let init_fn = initializer.load(&make_uri(), &HashMap::new()).await?;
+    init_fn(agent);
+    Ok(())
+}
+
+#[tokio::test]
+async fn load_value_lane() {
+    let agent = TestAgent::default();
+    let initializer = ExternalInitializer::value(TestAgent::VALUE, |route: &RouteUri, _: &_| {
+        assert_eq!(route.as_str(), NODE_URI);
+        ready(Ok::<_, Unavailable>(56))
+    });
+    assert_eq!(initializer.item_id(&agent), VAL_ID);
+    assert!(run_init(initializer, &agent).await.is_ok());
+    assert_eq!(agent.value.read(|n| *n), 56);
+}
+
+#[tokio::test]
+async fn load_map_lane() {
+    let agent = TestAgent::default();
+    let initializer = ExternalInitializer::map(TestAgent::MAP, |_: &_, _: &_| {
+        let map = [(1, "a".to_string()), (2, "b".to_string())]
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        ready(Ok::<_, Unavailable>(map))
+    });
+    assert_eq!(initializer.item_id(&agent), MAP_ID);
+    assert!(run_init(initializer, &agent).await.is_ok());
+    let expected = [(1, "a".to_string()), (2, "b".to_string())]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    assert_eq!(agent.map.get_map(Clone::clone), expected);
+}
+
+#[tokio::test]
+async fn failed_load() {
+    let agent = TestAgent::default();
+    let initializer = ExternalInitializer::value(TestAgent::VALUE, |_: &_, _: &_| {
+        ready(Err::<i32, _>(Unavailable))
+    });
+    let result = run_init(initializer, &agent).await;
+    assert!(matches!(result, Err(LaneInitError::Failed(_))));
+    assert_eq!(agent.value.read(|n| *n), 0);
+}
+
+#[tokio::test]
+async fn failed_load_with_fallback() {
+    let agent = TestAgent::default();
+    let initializer = ExternalInitializer::value(TestAgent::VALUE, |_: &_, _: &_| {
+        ready(Err::<i32, _>(Unavailable))
+    })
+    .with_fallback(-1);
+    assert!(run_init(initializer, &agent).await.is_ok());
+    assert_eq!(agent.value.read(|n| *n), -1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn load_timeout() {
+    let agent = TestAgent::default();
+    let initializer = ExternalInitializer::value(TestAgent::VALUE, |_: &_, _: &_| {
+        pending::<Result<i32, Unavailable>>()
+    })
+    .with_timeout(TIMEOUT);
+    let result = run_init(initializer, &agent).await;
+    assert!(matches!(result, Err(LaneInitError::TimedOut(t)) if t == TIMEOUT));
+}
+
+#[tokio::test(start_paused = true)]
+async fn load_timeout_with_fallback() {
+    let agent = TestAgent::default();
+    let initializer = ExternalInitializer::value(TestAgent::VALUE, |_: &_, _: &_| {
+        pending::<Result<i32, Unavailable>>()
+    })
+    .with_timeout(TIMEOUT)
+    .with_fallback(-1);
+    assert!(run_init(initializer, &agent).await.is_ok());
+    assert_eq!(agent.value.read(|n| *n), -1);
+}
@@ -14,6 +14,7 @@
 
 use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use bytes::BytesMut;
 use futures::{
@@ -22,7 +23,8 @@ use futures::{
     FutureExt, SinkExt, Stream, StreamExt,
 };
 use swimos_agent_protocol::{
-    encoding::store::StoreInitializedCodec, MapMessage, StoreInitMessage, StoreInitialized,
+    encoding::store::StoreInitializedCodec, DecodePayload, MapMessage, PayloadDecodeError,
+    StoreInitMessage, StoreInitialized,
 };
 use swimos_api::error::FrameIoError;
 use swimos_form::read::{ReadError, RecognizerReadable};
@@ -32,7 +34,7 @@ use swimos_utilities::future::try_last;
 use tokio_util::codec::{Decoder, FramedRead, FramedWrite};
 
 use crate::item::{MapItem, ValueItem};
-use crate::lanes::{MapLane, ValueLane};
+use crate::lanes::{MapLane, OrderedMapLane, ValueLane};
 use crate::stores::value::ValueStore;
 use crate::stores::MapStore;
 
@@ -86,6 +88,8 @@ pub struct InitializedItem<'a, Agent> {
     pub name: &'a str,
     // Function to initialize the state of the lane in the agent.
     pub init_fn: InitFn<Agent>,
+    // Whether any state for the lane was restored from the store.
+    pub restored: bool,
     // Channels for communication with the runtime.
     pub io: (ByteWriter, ByteReader),
 }
@@ -95,12 +99,14 @@ impl<'a, Agent> InitializedItem<'a, Agent> {
         item_kind: ItemKind,
         name: &'a str,
         init_fn: InitFn<Agent>,
+        restored: bool,
         io: (ByteWriter, ByteReader),
     ) -> Self {
         InitializedItem {
             item_kind,
             name,
             init_fn,
+            restored,
             io,
         }
     }
@@ -126,16 +132,18 @@ where
     FrameIoError: From<D::Error>,
 {
     let (mut tx, mut rx) = io;
-    let stream = init_stream(&mut rx, decoder);
+    let restored = AtomicBool::new(false);
+    let stream = init_stream(&mut rx, decoder).inspect(|_| restored.store(true, Ordering::Relaxed));
     match init.initialize(stream.boxed()).await {
         Err(e) => Err(e),
         Ok(init_fn) => {
+            let restored = restored.load(Ordering::Relaxed);
             let mut writer = FramedWrite::new(&mut tx, StoreInitializedCodec);
             writer
                 .send(StoreInitialized)
                 .await
                 .map_err(FrameIoError::Io)
-                .map(move |_| InitializedItem::new(item_kind, name, init_fn, (tx, rx)))
+                .map(move |_| InitializedItem::new(item_kind, name, init_fn, restored, (tx, rx)))
         }
     }
 }
@@ -184,24 +192,39 @@ impl<Agent, K, V> MapStoreInitializer<Agent, K, V> {
     }
 }
 
+/// [`ItemInitializer`] to construct the state of an ordered map lane.
+pub struct OrderedMapLaneInitializer<Agent, K, V> {
+    projection: fn(&Agent) -> &OrderedMapLane<K, V>,
+}
+
+impl<Agent, K, V> OrderedMapLaneInitializer<Agent, K, V> {
+    pub fn new(projection: fn(&Agent) -> &OrderedMapLane<K, V>) -> Self {
+        OrderedMapLaneInitializer { projection }
+    }
+}
+
 async fn value_like_init<Agent, F, T>(
     stream: BoxStream<'_, Result<BytesMut, FrameIoError>>,
     init: F,
 ) -> Result<InitFn<Agent>, FrameIoError>
 where
     Agent: 'static,
-    T: RecognizerReadable + Send + 'static,
+    T: DecodePayload + Send + 'static,
     F: FnOnce(&Agent, T) + Send + 'static,
 {
     let body = try_last(stream).await?;
     if let Some(mut body) = body {
-        let mut decoder = RecognizerDecoder::new(T::make_recognizer());
-        if let Some(value) = decoder.decode_eof(&mut body)? {
-            let f = move |agent: &Agent| init(agent, value);
-            let f_init: InitFn<Agent> = Box::new(f);
-            Ok(f_init)
-        } else {
-            Err(AsyncParseError::Parser(ParseError::Structure(ReadError::IncompleteRecord)).into())
+        match T::decode_payload(&mut body) {
+            Ok(value) => {
+                let f = move |agent: &Agent| init(agent, value);
+                let f_init: InitFn<Agent> = Box::new(f);
+                Ok(f_init)
+            }
+            Err(PayloadDecodeError::Incomplete) => Err(AsyncParseError::Parser(
+                ParseError::Structure(ReadError::IncompleteRecord),
+            )
+            .into()),
+            Err(err) => Err(err.into()),
         }
     } else {
         let f = move |_: &Agent| {};
@@ -213,7 +236,7 @@ where
 impl<Agent, T> ItemInitializer<Agent, BytesMut> for ValueLaneInitializer<Agent, T>
 where
     Agent: 'static,
-    T: RecognizerReadable + Send + 'static,
+    T: DecodePayload + Send + 'static,
 {
     fn initialize(
         self: Box<Self>,
@@ -227,7 +250,7 @@ where
 impl<Agent, T> ItemInitializer<Agent, BytesMut> for ValueStoreInitializer<Agent, T>
 where
     Agent: 'static,
-    T: RecognizerReadable + Send + 'static,
+    T: DecodePayload + Send + 'static,
 {
     fn initialize(
         self: Box<Self>,
@@ -238,16 +261,15 @@ where
     }
 }
 
-async fn map_like_init<Agent, Item, K, V, F>(
+async fn map_like_init<Agent, K, V, F>(
     mut stream: BoxStream<'_, Result<MapMessage<BytesMut, BytesMut>, FrameIoError>>,
-    projection: F,
+    init: F,
 ) -> Result<InitFn<Agent>, FrameIoError>
 where
     Agent: 'static,
-    K: Eq + Hash + Ord + Clone + RecognizerReadable + Send + 'static,
+    K: Ord + Clone + RecognizerReadable + Send + 'static,
     V: RecognizerReadable + Send + 'static,
-    Item: MapItem<K, V> + 'static,
-    F: Fn(&Agent) -> &Item + Send + 'static,
+    F: FnOnce(&Agent, BTreeMap<K, V>) + Send + 'static,
 {
     let mut key_decoder = RecognizerDecoder::new(K::make_recognizer());
     let mut value_decoder = RecognizerDecoder::new(V::make_recognizer());
@@ -293,8 +315,7 @@ where
             }
         }
     }
-    let map_init = map.into_iter().collect::<HashMap<_, _>>();
-    let f = move |agent: &Agent| projection(agent).init(map_init);
+    let f = move |agent: &Agent| init(agent, map);
     let f_init: InitFn<Agent> = Box::new(f);
     Ok(f_init)
 }
@@ -313,7 +334,10 @@ where
         stream: BoxStream<'_, Result<MapMessage<BytesMut, BytesMut>, FrameIoError>>,
     ) -> BoxFuture<'_, Result<InitFn<Agent>, FrameIoError>> {
         let MapLaneInitializer { projection } = *self;
-        map_like_init(stream, projection).boxed()
+        map_like_init(stream, move |agent, map: BTreeMap<K, V>| {
+            projection(agent).init(map.into_iter().collect::<HashMap<_, _>>())
+        })
+        .boxed()
     }
 }
 
@@ -331,7 +355,28 @@ where
         stream: BoxStream<'_, Result<MapMessage<BytesMut, BytesMut>, FrameIoError>>,
     ) -> BoxFuture<'_, Result<InitFn<Agent>, FrameIoError>> {
         let MapStoreInitializer { projection } = *self;
-        map_like_init(stream, projection).boxed()
+        map_like_init(stream, move |agent, map: BTreeMap<K, V>| {
+            projection(agent).init(map.into_iter().collect::<HashMap<_, _>>())
+        })
+        .boxed()
+    }
+}
+
+impl<Agent, K, V> ItemInitializer<Agent, MapMessage<BytesMut, BytesMut>>
+    for OrderedMapLaneInitializer<Agent, K, V>
+where
+    Agent: 'static,
+    K: RecognizerReadable + Hash + Eq + Ord + Clone + Send + 'static,
+    K::Rec: Send,
+    V: RecognizerReadable + Send + 'static,
+    V::Rec: Send,
+{
+    fn initialize(
+        self: Box<Self>,
+        stream: BoxStream<'_, Result<MapMessage<BytesMut, BytesMut>, FrameIoError>>,
+    ) -> BoxFuture<'_, Result<InitFn<Agent>, FrameIoError>> {
+        let OrderedMapLaneInitializer { projection } = *self;
+        map_like_init(stream, move |agent, map| projection(agent).init(map)).boxed()
     }
 }
 
@@ -106,9 +106,12 @@ async fn init_value_lane() {
         item_kind,
         name,
         init_fn,
+        restored,
         io: _io,
     } = result.expect("Initialization failed.");
 
+    assert!(restored);
+
     assert_eq!(item_kind, ItemKind::VALUE_LANE);
     assert_eq!(name, "value_lane");
 
@@ -161,9 +164,12 @@ async fn init_value_store() {
         item_kind,
         name,
         init_fn,
+        restored,
         io: _io,
     } = result.expect("Initialization failed.");
 
+    assert!(restored);
+
     assert_eq!(item_kind, ItemKind::VALUE_STORE);
     assert_eq!(name, "value_store");
 
@@ -212,9 +218,12 @@ async fn init_value_lane_no_data() {
         item_kind,
         name,
         init_fn,
+        restored,
         io: _io,
     } = result.expect("Initialization failed.");
 
+    assert!(!restored);
+
     assert_eq!(item_kind, ItemKind::VALUE_LANE);
     assert_eq!(name, "value_lane");
 
@@ -263,9 +272,12 @@ async fn init_value_store_no_data() {
         item_kind,
         name,
         init_fn,
+        restored,
         io: _io,
     } = result.expect("Initialization failed.");
 
+    assert!(!restored);
+
     assert_eq!(item_kind, ItemKind::VALUE_STORE);
     assert_eq!(name, "value_store");
 
@@ -335,9 +347,12 @@ async fn init_map_lane() {
         item_kind,
         name,
         init_fn,
+        restored,
         io: _io,
     } = result.expect("Initialization failed.");
 
+    assert!(restored);
+
     assert_eq!(item_kind, ItemKind::MAP_LANE);
     assert_eq!(name, "map_lane");
 
@@ -412,9 +427,12 @@ async fn init_map_store() {
         item_kind,
         name,
         init_fn,
+        restored,
         io: _io,
     } = result.expect("Initialization failed.");
 
+    assert!(restored);
+
     assert_eq!(item_kind, ItemKind::MAP_STORE);
     assert_eq!(name, "map_store");
 
@@ -468,9 +486,12 @@ async fn init_map_lane_no_data() {
         item_kind,
         name,
         init_fn,
+        restored,
         io: _io,
     } = result.expect("Initialization failed.");
 
+    assert!(!restored);
+
     assert_eq!(item_kind, ItemKind::MAP_LANE);
     assert_eq!(name, "map_lane");
 
@@ -520,9 +541,12 @@ async fn init_map_store_no_data() {
         item_kind,
         name,
         init_fn,
+        restored,
         io: _io,
     } = result.expect("Initialization failed.");
 
+    assert!(!restored);
+
     assert_eq!(item_kind, ItemKind::MAP_STORE);
     assert_eq!(name, "map_store");
 
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::{
+    num::NonZeroUsize,
     pin::Pin,
     task::{Context, Poll},
 };
@@ -20,13 +21,20 @@ use std::{
 use bytes::BytesMut;
 use futures::{ready, SinkExt, Stream, StreamExt};
 use swimos_agent_protocol::{
-    encoding::lane::{RawMapLaneRequestDecoder, RawValueLaneRequestDecoder},
-    LaneRequest, MapMessage,
+    encoding::lane::{
+        RawMapLaneRequestDecoder, RawValueLaneRequestDecoder, RawValueLaneResponseEncoder,
+    },
+    LaneRequest, LaneResponse, MapMessage,
+};
+use swimos_api::{
+    agent::HttpLaneRequest,
+    error::{FrameIoError, InvalidFrame},
+    limits::Limits,
 };
-use swimos_api::{agent::HttpLaneRequest, error::FrameIoError};
 use swimos_utilities::byte_channel::{ByteReader, ByteWriter};
 use tokio::sync::mpsc;
-use tokio_util::codec::{BytesCodec, FramedRead, FramedWrite};
+use tokio_util::codec::{BytesCodec, Encoder, FramedRead, FramedWrite};
+use uuid::Uuid;
 
 type ValueLaneReader = FramedRead<ByteReader, RawValueLaneRequestDecoder>;
 type MapLaneReader = FramedRead<ByteReader, RawMapLaneRequestDecoder>;
@@ -36,11 +44,18 @@ pub struct ItemWriter {
     id: u64,
     writer: FramedWrite<ByteWriter, BytesCodec>,
     pub buffer: BytesMut,
+    // Whether the item is a lane (and so the correlation IDs of the commands that caused events
+    // should be reported).
+    is_lane: bool,
+    // The correlation ID that was most recently reported to the runtime.
+    correlated: Option<u64>,
+    // The command origin that was most recently reported to the runtime.
+    originated: Option<Uuid>,
 }
 
 enum LaneReaderInner {
     Value(ValueLaneReader),
-    Map(MapLaneReader),
+    Map(MapLaneReader, Limits),
     Http(mpsc::Receiver<HttpLaneRequest>),
 }
 
@@ -51,17 +66,36 @@ pub struct LaneReader {
 }
 
 impl LaneReader {
-    pub fn value(id: u64, reader: ByteReader) -> Self {
+    /// Fragmented requests are reassembled and requests larger than `max_message_size` will
+    /// result in an error.
+    pub fn value(id: u64, reader: ByteReader, max_message_size: Option<NonZeroUsize>) -> Self {
         LaneReader {
             id,
-            inner: LaneReaderInner::Value(FramedRead::new(reader, Default::default())),
+            inner: LaneReaderInner::Value(FramedRead::new(
+                reader,
+                RawValueLaneRequestDecoder::with_max_message_size(max_message_size),
+            )),
         }
     }
 
-    pub fn map(id: u64, reader: ByteReader) -> Self {
+    /// Fragmented requests are reassembled and requests larger than `max_message_size` will
+    /// result in an error. Map lanes always use Recon so the keys and values of commands that
+    /// violate the Recon `limits` will also result in an error.
+    pub fn map(
+        id: u64,
+        reader: ByteReader,
+        max_message_size: Option<NonZeroUsize>,
+        limits: Limits,
+    ) -> Self {
         LaneReader {
             id,
-            inner: LaneReaderInner::Map(FramedRead::new(reader, Default::default())),
+            inner: LaneReaderInner::Map(
+                FramedRead::new(
+                    reader,
+                    RawMapLaneRequestDecoder::with_max_message_size(max_message_size),
+                ),
+                limits,
+            ),
         }
     }
 
@@ -79,6 +113,60 @@ impl ItemWriter {
             id,
             writer: FramedWrite::new(tx, BytesCodec::default()),
             buffer: Default::default(),
+            is_lane: false,
+            correlated: None,
+            originated: None,
+        }
+    }
+
+    /// A writer for a lane that will report the correlation IDs of the commands that caused the
+    /// events that it writes.
+    pub fn lane(id: u64, tx: ByteWriter) -> Self {
+        ItemWriter {
+            is_lane: true,
+            ..ItemWriter::new(id, tx)
+        }
+    }
+
+    /// Inform the runtime of the correlation ID of the command that caused the events that are
+    /// about to be written into the buffer. This does nothing for stores or if the ID has not
+    /// changed since it was last reported.
+    pub fn correlate(&mut self, correlation_id: Option<u64>) {
+        let ItemWriter {
+            buffer,
+            is_lane,
+            correlated,
+            ..
+        } = self;
+        if *is_lane && *correlated != correlation_id {
+            let response: LaneResponse<&[u8]> = LaneResponse::Correlated(correlation_id);
+            if RawValueLaneResponseEncoder::default()
+                .encode(response, buffer)
+                .is_ok()
+            {
+                *correlated = correlation_id;
+            }
+        }
+    }
+
+    /// Inform the runtime of the ID of the remote that sent the command that caused the events
+    /// that are about to be written into the buffer. This does nothing for stores or if the origin
+    /// has not changed since it was last reported.
+    pub fn originate(&mut self, origin: Option<Uuid>) {
+        let ItemWriter {
+            buffer,
+            is_lane,
+            originated,
+            ..
+        } = self;
+        if *is_lane && *originated != origin {
+            let response: LaneResponse<&[u8]> = LaneResponse::Originated(origin);
+            if RawValueLaneResponseEncoder::default()
+                .encode(response, buffer)
+                .is_ok()
+            {
+                *originated = origin;
+            }
         }
     }
 
@@ -110,9 +198,14 @@ impl Stream for LaneReader {
                 let result = ready!(reader.poll_next_unpin(cx));
                 Poll::Ready(result.map(|r| (*id, r.map(LaneReadEvent::Value))))
             }
-            LaneReaderInner::Map(reader) => {
-                let result = ready!(reader.poll_next_unpin(cx));
-                Poll::Ready(result.map(|r| (*id, r.map(LaneReadEvent::Map))))
+            LaneReaderInner::Map(reader, limits) => {
+                let result = ready!(reader.poll_next_unpin(cx)).map(|r| {
+                    r.and_then(|request| {
+                        check_map_request(&request, limits)?;
+                        Ok(LaneReadEvent::Map(request))
+                    })
+                });
+                Poll::Ready(result.map(|r| (*id, r)))
             }
             LaneReaderInner::Http(rx) => {
                 let result = ready!(rx.poll_recv(cx));
@@ -121,3 +214,23 @@ impl Stream for LaneReader {
         }
     }
 }
+
+fn check_map_request(
+    request: &LaneRequest<MapMessage<BytesMut, BytesMut>>,
+    limits: &Limits,
+) -> Result<(), FrameIoError> {
+    let body = match request {
+        LaneRequest::Command(body)
+        | LaneRequest::CommandFrom(_, body)
+        | LaneRequest::CorrelatedCommand(_, _, body) => body,
+        _ => return Ok(()),
+    };
+    let result = match body {
+        MapMessage::Update { key, value } => limits
+            .check_recon(key.as_ref())
+            .and_then(|_| limits.check_recon(value.as_ref())),
+        MapMessage::Remove { key } => limits.check_recon(key.as_ref()),
+        _ => Ok(()),
+    };
+    result.map_err(|err| FrameIoError::BadFrame(InvalidFrame::LimitExceeded(err)))
+}
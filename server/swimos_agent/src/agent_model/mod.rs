@@ -21,15 +21,18 @@ use std::sync::Arc;
 use bytes::{Bytes, BytesMut};
 use futures::future::{Fuse, OptionFuture};
 use futures::{
-    future::{BoxFuture, Either, FusedFuture},
+    future::{ready, BoxFuture, Either, FusedFuture},
     stream::{FuturesUnordered, SelectAll},
     StreamExt,
 };
-use futures::{Future, FutureExt};
+use futures::{Future, FutureExt, TryFutureExt};
 use swimos_agent_protocol::encoding::store::{RawMapStoreInitDecoder, RawValueStoreInitDecoder};
 use swimos_agent_protocol::{LaneRequest, MapMessage};
 use swimos_api::agent::DownlinkKind;
-use swimos_api::agent::{HttpLaneRequest, LaneConfig, RawHttpLaneResponse};
+use swimos_api::agent::{
+    HttpLaneRequest, ItemManifest, ItemStateKind, KeyRange, LaneConfig, RawHttpLaneResponse,
+    StopReason, SyncVersion,
+};
 use swimos_api::error::{AgentRuntimeError, DownlinkRuntimeError, OpenStoreError};
 use swimos_api::{
     address::Address,
@@ -42,23 +45,30 @@ use swimos_utilities::byte_channel::{ByteReader, ByteWriter};
 use swimos_utilities::future::RetryStrategy;
 use swimos_utilities::routing::RouteUri;
 use tokio::io::AsyncWriteExt;
-use tokio::sync::mpsc;
-use tracing::{debug, error, info, trace};
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, debug_span, error, info, trace};
 use uuid::Uuid;
 
 use crate::agent_lifecycle::item_event::ItemEvent;
 use crate::agent_model::io::LaneReadEvent;
 use crate::event_handler::{
-    ActionContext, BoxJoinLaneInit, HandlerFuture, LocalBoxEventHandler, ModificationFlags,
+    ActionContext, BoxJoinLaneInit, CommandAckTracker, HandlerFuture, LocalBoxEventHandler,
+    ModificationFlags,
 };
+use crate::feature_flags::FeatureFlags;
 use crate::{
     agent_lifecycle::AgentLifecycle,
-    event_handler::{EventHandler, EventHandlerError, HandlerAction, StepResult},
+    event_handler::{
+        reentrancy, EventHandler, EventHandlerError, HandlerAction, ReentrancyPolicy, StepResult,
+    },
     meta::AgentMetadata,
 };
 
 /// Support for executing downlink lifecycles within agents.
 pub mod downlink;
+mod dynamic;
+mod external;
 mod init;
 mod io;
 #[cfg(test)]
@@ -69,10 +79,12 @@ use io::{ItemWriter, LaneReader};
 use bitflags::bitflags;
 
 use self::downlink::{BoxDownlinkChannel, DownlinkChannelError, DownlinkChannelEvent};
+use self::dynamic::{DynamicLane, DynamicLanes};
 use self::init::{run_item_initializer, InitializedItem};
+pub use external::{ExternalInitializer, LaneInitError, LaneInitializer};
 pub use init::{
-    ItemInitializer, MapLaneInitializer, MapStoreInitializer, ValueLaneInitializer,
-    ValueStoreInitializer,
+    ItemInitializer, MapLaneInitializer, MapStoreInitializer, OrderedMapLaneInitializer,
+    ValueLaneInitializer, ValueStoreInitializer,
 };
 pub use swimos_api::agent::{StoreKind, WarpLaneKind};
 
@@ -126,6 +138,9 @@ bitflags! {
     pub struct ItemFlags: u8 {
         /// The state of the item should not be persisted.
         const TRANSIENT = 0b01;
+        /// The IDs of the remotes that send commands to the lane should be tracked and reported
+        /// with the events that they cause (this has no effect for stores).
+        const TRACK_ORIGIN = 0b10;
     }
 }
 
@@ -152,6 +167,12 @@ pub struct ItemSpec {
     pub lifecycle_name: &'static str,
     /// Type information for the item.
     pub descriptor: ItemDescriptor,
+    /// Names that the item previously had. If the store has state for the item under one of
+    /// these names, it will be moved to the current name.
+    pub previous_names: &'static [&'static str],
+    /// Deprecated names by which a lane can still be addressed. Links and commands that use one of
+    /// these names are routed to the lane.
+    pub aliases: &'static [&'static str],
 }
 
 impl ItemSpec {
@@ -160,8 +181,53 @@ impl ItemSpec {
             id,
             lifecycle_name,
             descriptor,
+            previous_names: &[],
+            aliases: &[],
         }
     }
+
+    /// Specify the names that the item previously had.
+    pub fn with_previous_names(mut self, previous_names: &'static [&'static str]) -> Self {
+        self.previous_names = previous_names;
+        self
+    }
+
+    /// Specify the deprecated names by which the item can still be addressed.
+    pub fn with_aliases(mut self, aliases: &'static [&'static str]) -> Self {
+        self.aliases = aliases;
+        self
+    }
+}
+
+/// Describe the items of an agent that have persistent state so that the runtime can check them
+/// against the state in the store.
+///
+/// # Arguments
+/// * `item_specs` - Specifications of all of the items of the agent.
+/// * `transient_lanes` - Whether the default lane configuration makes all lanes transient.
+fn item_manifest(
+    item_specs: &HashMap<&'static str, ItemSpec>,
+    transient_lanes: bool,
+) -> ItemManifest {
+    let mut manifest = ItemManifest::default();
+    for (name, spec) in item_specs {
+        let kind = match spec.descriptor {
+            ItemDescriptor::WarpLane { kind, flags }
+                if !transient_lanes && !flags.contains(ItemFlags::TRANSIENT) =>
+            {
+                ItemStateKind::Lane(kind)
+            }
+            ItemDescriptor::Store { kind, flags } if !flags.contains(ItemFlags::TRANSIENT) => {
+                ItemStateKind::Store(kind)
+            }
+            _ => continue,
+        };
+        manifest.add_item(*name, kind);
+        for previous_name in spec.previous_names {
+            manifest.add_rename(*previous_name, *name);
+        }
+    }
+    manifest
 }
 
 type MapLikeInitializer<T> =
@@ -212,6 +278,18 @@ pub trait AgentSpec: Sized + Send {
     where
         Self: 'static;
 
+    /// Start the timers for any lanes that have been configured with a time-to-live. This is called
+    /// once, immediately before the `on_start` event handler of the agent is executed.
+    ///
+    /// # Arguments
+    /// * `action_context` - The context in which to suspend the timers.
+    fn start_expiry_timers(&self, action_context: &mut ActionContext<Self>)
+    where
+        Self: 'static,
+    {
+        let _ = action_context;
+    }
+
     /// Create a handler that will update the state of the agent when a command is received
     /// for a map lane. There will be no handler if the lane does not exist or does not
     /// accept commands.
@@ -232,6 +310,44 @@ pub trait AgentSpec: Sized + Send {
     /// * `id` - The ID of the remote that requested the sync.
     fn on_sync(&self, lane: &str, id: Uuid) -> Option<Self::OnSyncHandler>;
 
+    /// Create a handler that will update the state of an agent when a remote, that has previously
+    /// synced with a lane, requests to sync again and only receive the changes since the version
+    /// it already holds. Lanes that do not support this should perform a normal sync (which is the
+    /// default). There will be no handler if the lane does not exist.
+    ///
+    /// # Arguments
+    /// * `lane` - The name of the lane.
+    /// * `id` - The ID of the remote that requested the sync.
+    /// * `since` - The version of the state of the lane that the remote holds.
+    fn on_sync_since(
+        &self,
+        lane: &str,
+        id: Uuid,
+        since: SyncVersion,
+    ) -> Option<Self::OnSyncHandler> {
+        let _ = since;
+        self.on_sync(lane, id)
+    }
+
+    /// Create a handler that will update the state of an agent when a remote requests to sync with
+    /// only those entries of a lane that have keys within a range. Lanes that do not support this
+    /// should perform a normal sync (which is the default). There will be no handler if the lane
+    /// does not exist.
+    ///
+    /// # Arguments
+    /// * `lane` - The name of the lane.
+    /// * `id` - The ID of the remote that requested the sync.
+    /// * `range` - The Recon representations of the bounds of the range of keys.
+    fn on_sync_range(
+        &self,
+        lane: &str,
+        id: Uuid,
+        range: KeyRange<Bytes>,
+    ) -> Option<Self::OnSyncHandler> {
+        let _ = range;
+        self.on_sync(lane, id)
+    }
+
     /// Create a handler that will update the state of the agent when an HTTP request is
     /// made to a lane. If no HTTP lane exists with the specified name the request will
     /// be returned as an error (so that the caller can handle it will a 404 response).
@@ -278,6 +394,12 @@ pub trait LifecycleFactory<ItemModel>: Send + Sync {
 
     /// Create an instance of the agent type.
     fn create(&self) -> Self::LifecycleType;
+
+    /// Attempt to create an instance of the agent type. If this fails, the agent instance will not
+    /// start. By default, this delegates to [`LifecycleFactory::create`] and cannot fail.
+    fn try_create(&self) -> Result<Self::LifecycleType, AgentInitError> {
+        Ok(self.create())
+    }
 }
 
 /// A lifecycle factory that creates clones of a provided instance.
@@ -308,12 +430,89 @@ where
     }
 }
 
+/// The factory that an [`AgentModel`] uses to create its lifecycles. This allows for lifecycles that
+/// are created asynchronously, in addition to those created by a [`LifecycleFactory`].
+trait AsyncLifecycleFactory<ItemModel>: Send + Sync {
+    type LifecycleType: AgentLifecycle<ItemModel> + Send;
+
+    /// Create an instance of the agent type. If this fails, the agent instance will not start.
+    fn create(&self) -> BoxFuture<'static, Result<Self::LifecycleType, AgentInitError>>;
+}
+
+/// Adapts a [`LifecycleFactory`] to be used by an [`AgentModel`].
+struct SyncLifecycleFac<Fac>(Fac);
+
+impl<ItemModel, Fac> AsyncLifecycleFactory<ItemModel> for SyncLifecycleFac<Fac>
+where
+    Fac: LifecycleFactory<ItemModel>,
+    Fac::LifecycleType: 'static,
+{
+    type LifecycleType = Fac::LifecycleType;
+
+    fn create(&self) -> BoxFuture<'static, Result<Self::LifecycleType, AgentInitError>> {
+        ready(self.0.try_create()).boxed()
+    }
+}
+
+/// A lifecycle factory that runs a fallible function to create each instance.
+struct TryFnLifecycleFac<F>(F);
+
+impl<ItemModel, F, LC, E> AsyncLifecycleFactory<ItemModel> for TryFnLifecycleFac<F>
+where
+    F: Fn() -> Result<LC, E> + Send + Sync,
+    LC: Send + AgentLifecycle<ItemModel> + 'static,
+    E: std::error::Error + Send + 'static,
+{
+    type LifecycleType = LC;
+
+    fn create(&self) -> BoxFuture<'static, Result<Self::LifecycleType, AgentInitError>> {
+        ready(self.0().map_err(|e| AgentInitError::UserCodeError(Box::new(e)))).boxed()
+    }
+}
+
+/// A lifecycle factory that runs an asynchronous function to create each instance.
+struct AsyncFnLifecycleFac<F>(F);
+
+impl<ItemModel, F, Fut, LC> AsyncLifecycleFactory<ItemModel> for AsyncFnLifecycleFac<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = LC> + Send + 'static,
+    LC: Send + AgentLifecycle<ItemModel> + 'static,
+{
+    type LifecycleType = LC;
+
+    fn create(&self) -> BoxFuture<'static, Result<Self::LifecycleType, AgentInitError>> {
+        self.0().map(Ok).boxed()
+    }
+}
+
+/// A lifecycle factory that runs a fallible, asynchronous function to create each instance.
+struct TryAsyncFnLifecycleFac<F>(F);
+
+impl<ItemModel, F, Fut, LC, E> AsyncLifecycleFactory<ItemModel> for TryAsyncFnLifecycleFac<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<LC, E>> + Send + 'static,
+    LC: Send + AgentLifecycle<ItemModel> + 'static,
+    E: std::error::Error + Send + 'static,
+{
+    type LifecycleType = LC;
+
+    fn create(&self) -> BoxFuture<'static, Result<Self::LifecycleType, AgentInitError>> {
+        self.0()
+            .map_err(|e| AgentInitError::UserCodeError(Box::new(e)))
+            .boxed()
+    }
+}
+
 /// The complete model for an agent consisting of an implementation of [`AgentSpec`] to describe the lanes
 /// of the agent and an implementation of [`AgentLifecycle`] to describe the lifecycle events that will trigger,
 /// for  example, when the agent starts or stops or when the state of a lane changes.
 pub struct AgentModel<ItemModel, Lifecycle> {
     item_model_fac: Arc<dyn ItemModelFactory<ItemModel = ItemModel>>,
-    lifecycle_fac: Arc<dyn LifecycleFactory<ItemModel, LifecycleType = Lifecycle>>,
+    lifecycle_fac: Arc<dyn AsyncLifecycleFactory<ItemModel, LifecycleType = Lifecycle>>,
+    lane_initializers: Vec<Arc<dyn LaneInitializer<ItemModel>>>,
+    reentrancy: ReentrancyPolicy,
 }
 
 impl<ItemModel, Lifecycle> Clone for AgentModel<ItemModel, Lifecycle> {
@@ -321,10 +520,31 @@ impl<ItemModel, Lifecycle> Clone for AgentModel<ItemModel, Lifecycle> {
         Self {
             item_model_fac: self.item_model_fac.clone(),
             lifecycle_fac: self.lifecycle_fac.clone(),
+            lane_initializers: self.lane_initializers.clone(),
+            reentrancy: self.reentrancy,
         }
     }
 }
 
+impl<ItemModel, Lifecycle> AgentModel<ItemModel, Lifecycle> {
+    /// Add a hook to load the initial state of an item of the agent from an external source
+    /// before the agent starts.
+    pub fn with_initializer<I>(mut self, initializer: I) -> Self
+    where
+        I: LaneInitializer<ItemModel> + 'static,
+    {
+        self.lane_initializers.push(Arc::new(initializer));
+        self
+    }
+
+    /// Enable diagnostics for event handlers that write to lanes or stores that are already
+    /// borrowed. By default, this will panic (see [`ReentrancyPolicy`]).
+    pub fn with_reentrancy(mut self, reentrancy: ReentrancyPolicy) -> Self {
+        self.reentrancy = reentrancy;
+        self
+    }
+}
+
 impl<ItemModel, Lifecycle> AgentModel<ItemModel, Lifecycle>
 where
     Lifecycle: Send + Sync + Clone + AgentLifecycle<ItemModel> + 'static,
@@ -335,7 +555,9 @@ where
     {
         AgentModel {
             item_model_fac: Arc::new(item_model_fac),
-            lifecycle_fac: Arc::new(CloneableLifecycle(lifecycle)),
+            lifecycle_fac: Arc::new(SyncLifecycleFac(CloneableLifecycle(lifecycle))),
+            lane_initializers: vec![],
+            reentrancy: ReentrancyPolicy::Panic,
         }
     }
 
@@ -345,7 +567,9 @@ where
     ) -> Self {
         AgentModel {
             item_model_fac,
-            lifecycle_fac: Arc::new(CloneableLifecycle(lifecycle)),
+            lifecycle_fac: Arc::new(SyncLifecycleFac(CloneableLifecycle(lifecycle))),
+            lane_initializers: vec![],
+            reentrancy: ReentrancyPolicy::Panic,
         }
     }
 }
@@ -361,7 +585,93 @@ where
     {
         AgentModel {
             item_model_fac: Arc::new(item_model_fac),
-            lifecycle_fac: Arc::new(FnLifecycleFac(lifecycle_fn)),
+            lifecycle_fac: Arc::new(SyncLifecycleFac(FnLifecycleFac(lifecycle_fn))),
+            lane_initializers: vec![],
+            reentrancy: ReentrancyPolicy::Panic,
+        }
+    }
+
+    /// Create an agent model where the lifecycle for each agent instance is created by a fallible
+    /// function. If the function fails, the agent instance will fail to start with
+    /// [`AgentInitError::UserCodeError`].
+    ///
+    /// # Arguments
+    /// * `item_model_fac` - Factory for the items of the agent.
+    /// * `lifecycle_fn` - Function to create the lifecycle for an agent instance.
+    pub fn try_from_fn<F, G, E>(item_model_fac: F, lifecycle_fn: G) -> Self
+    where
+        F: ItemModelFactory<ItemModel = ItemModel> + Sized + 'static,
+        G: Fn() -> Result<Lifecycle, E> + Send + Sync + 'static,
+        E: std::error::Error + Send + 'static,
+    {
+        AgentModel {
+            item_model_fac: Arc::new(item_model_fac),
+            lifecycle_fac: Arc::new(TryFnLifecycleFac(lifecycle_fn)),
+            lane_initializers: vec![],
+            reentrancy: ReentrancyPolicy::Panic,
+        }
+    }
+
+    /// Create an agent model where the lifecycle for each agent instance is created by a
+    /// [`LifecycleFactory`]. If [`LifecycleFactory::try_create`] fails, the agent instance will
+    /// not start.
+    ///
+    /// # Arguments
+    /// * `item_model_fac` - Factory for the items of the agent.
+    /// * `lifecycle_fac` - Factory for the lifecycle of each agent instance.
+    pub fn from_factory<F, Fac>(item_model_fac: F, lifecycle_fac: Fac) -> Self
+    where
+        F: ItemModelFactory<ItemModel = ItemModel> + Sized + 'static,
+        Fac: LifecycleFactory<ItemModel, LifecycleType = Lifecycle> + 'static,
+    {
+        AgentModel {
+            item_model_fac: Arc::new(item_model_fac),
+            lifecycle_fac: Arc::new(SyncLifecycleFac(lifecycle_fac)),
+            lane_initializers: vec![],
+            reentrancy: ReentrancyPolicy::Panic,
+        }
+    }
+
+    /// Create an agent model where the lifecycle for each agent instance is created by an
+    /// asynchronous function. This allows for lifecycles that require asynchronous setup (for
+    /// example, opening a client for an external service).
+    ///
+    /// # Arguments
+    /// * `item_model_fac` - Factory for the items of the agent.
+    /// * `lifecycle_fn` - Asynchronous function to create the lifecycle for an agent instance.
+    pub fn from_async_fn<F, G, Fut>(item_model_fac: F, lifecycle_fn: G) -> Self
+    where
+        F: ItemModelFactory<ItemModel = ItemModel> + Sized + 'static,
+        G: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Lifecycle> + Send + 'static,
+    {
+        AgentModel {
+            item_model_fac: Arc::new(item_model_fac),
+            lifecycle_fac: Arc::new(AsyncFnLifecycleFac(lifecycle_fn)),
+            lane_initializers: vec![],
+            reentrancy: ReentrancyPolicy::Panic,
+        }
+    }
+
+    /// Create an agent model where the lifecycle for each agent instance is created by a fallible,
+    /// asynchronous function. If the function fails, the agent instance will fail to start with
+    /// [`AgentInitError::UserCodeError`].
+    ///
+    /// # Arguments
+    /// * `item_model_fac` - Factory for the items of the agent.
+    /// * `lifecycle_fn` - Asynchronous function to create the lifecycle for an agent instance.
+    pub fn try_from_async_fn<F, G, Fut, E>(item_model_fac: F, lifecycle_fn: G) -> Self
+    where
+        F: ItemModelFactory<ItemModel = ItemModel> + Sized + 'static,
+        G: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Lifecycle, E>> + Send + 'static,
+        E: std::error::Error + Send + 'static,
+    {
+        AgentModel {
+            item_model_fac: Arc::new(item_model_fac),
+            lifecycle_fac: Arc::new(TryAsyncFnLifecycleFac(lifecycle_fn)),
+            lane_initializers: vec![],
+            reentrancy: ReentrancyPolicy::Panic,
         }
     }
 }
@@ -589,9 +899,11 @@ where
         let AgentModel {
             item_model_fac,
             lifecycle_fac,
+            lane_initializers,
+            reentrancy,
         } = self;
 
-        let lifecycle = lifecycle_fac.create();
+        let lifecycle = lifecycle_fac.create().await?;
 
         let meta = AgentMetadata::new(&route, &route_params, &config);
 
@@ -605,21 +917,43 @@ where
             .map(|spec| (spec.id, Text::new(spec.lifecycle_name)))
             .collect();
 
-        let external_item_ids = item_specs
+        let external_item_ids: HashMap<Text, u64> = item_specs
             .iter()
             .map(|(name, spec)| (Text::new(name), spec.id))
             .collect();
 
+        // Lanes that are opened after the agent has started are assigned IDs after those of the
+        // items of the agent.
+        let next_id = item_specs
+            .values()
+            .map(|spec| spec.id + 1)
+            .max()
+            .unwrap_or_default();
+        let dynamic_lanes = DynamicLanes::new(external_item_ids.keys(), next_id);
+
         let suspended = FuturesUnordered::new();
         let downlink_channels = RefCell::new(vec![]);
         let mut join_lane_init = HashMap::new();
+        let feature_flags = FeatureFlags::default();
         let mut ad_hoc_buffer = BytesMut::new();
+        let command_acks = CommandAcks::default();
+        let stop_token = CancellationToken::new();
 
         let item_model = item_model_fac.create();
 
+        // The IDs of the items for which state was restored from the store.
+        let mut restored_item_ids = HashSet::new();
+
         {
             let mut lane_init_tasks = FuturesUnordered::new();
             let default_lane_config = config.default_lane_config.unwrap_or_default();
+
+            // The persistent items must be declared before any are registered so that the runtime
+            // can migrate the state in the store before it is restored.
+            context
+                .declare_items(item_manifest(&item_specs, default_lane_config.transient))
+                .await?;
+
             macro_rules! with_init {
                 ($init:ident => $body:block) => {{
                     let mut $init = InitContext::new(&mut lane_io, &item_model, &lane_init_tasks);
@@ -640,7 +974,13 @@ where
                             if flags.contains(ItemFlags::TRANSIENT) {
                                 lane_conf.transient = true;
                             }
+                            if flags.contains(ItemFlags::TRACK_ORIGIN) {
+                                lane_conf.track_origin = true;
+                            }
                             let io = context.add_lane(name, kind, lane_conf).await?;
+                            for alias in spec.aliases {
+                                context.add_lane_alias(alias, name).await?;
+                            }
                             with_init!(init => {
                                 init.init_map_lane(name, kind,lane_conf, io);
                             })
@@ -649,7 +989,13 @@ where
                             if flags.contains(ItemFlags::TRANSIENT) {
                                 lane_conf.transient = true;
                             }
+                            if flags.contains(ItemFlags::TRACK_ORIGIN) {
+                                lane_conf.track_origin = true;
+                            }
                             let io = context.add_lane(name, kind, lane_conf).await?;
+                            for alias in spec.aliases {
+                                context.add_lane_alias(alias, name).await?;
+                            }
                             with_init!(init => {
                                 init.init_value_lane(name, kind, lane_conf, io);
                             })
@@ -697,9 +1043,13 @@ where
                     item_kind,
                     name,
                     init_fn,
+                    restored,
                     io,
                 } = result.map_err(AgentInitError::LaneInitializationFailure)?;
                 init_fn(&item_model);
+                if restored {
+                    restored_item_ids.insert(external_item_ids[name]);
+                }
                 match item_kind {
                     ItemKind::Lane(kind) => {
                         lane_io.insert((Text::new(name), kind), io);
@@ -713,6 +1063,20 @@ where
             }
         }
 
+        // Load the states of any items that are initialized from external sources. This must
+        // complete before the agent starts so that no remote can observe the unpopulated state.
+        // Items with persisted state are not loaded again as that would overwrite the state.
+        let external_init = lane_initializers
+            .iter()
+            .filter(|initializer| !restored_item_ids.contains(&initializer.item_id(&item_model)))
+            .map(|initializer| initializer.load(&route, &route_params));
+        for init_fn in futures::future::try_join_all(external_init)
+            .await
+            .map_err(|e| AgentInitError::UserCodeError(Box::new(e)))?
+        {
+            init_fn(&item_model);
+        }
+
         lifecycle.initialize(
             &mut ActionContext::new(
                 &suspended,
@@ -720,11 +1084,31 @@ where
                 &downlink_channels,
                 &mut join_lane_init,
                 &mut ad_hoc_buffer,
-            ),
+            )
+            .with_feature_flags(&feature_flags)
+            .with_lane_spawner(&dynamic_lanes)
+            .with_command_acks(&command_acks)
+            .with_stop_token(&stop_token)
+            .with_reentrancy(reentrancy),
             meta,
             &item_model,
         );
 
+        item_model.start_expiry_timers(
+            &mut ActionContext::new(
+                &suspended,
+                &*context,
+                &downlink_channels,
+                &mut join_lane_init,
+                &mut ad_hoc_buffer,
+            )
+            .with_feature_flags(&feature_flags)
+            .with_lane_spawner(&dynamic_lanes)
+            .with_command_acks(&command_acks)
+            .with_stop_token(&stop_token)
+            .with_reentrancy(reentrancy),
+        );
+
         // Run the agent's `on_start` event handler.
         let on_start_handler = lifecycle.on_start();
 
@@ -735,7 +1119,12 @@ where
                 &downlink_channels,
                 &mut join_lane_init,
                 &mut ad_hoc_buffer,
-            ),
+            )
+            .with_feature_flags(&feature_flags)
+            .with_lane_spawner(&dynamic_lanes)
+            .with_command_acks(&command_acks)
+            .with_stop_token(&stop_token)
+            .with_reentrancy(reentrancy),
             meta,
             &item_model,
             &lifecycle,
@@ -761,7 +1150,12 @@ where
             suspended,
             downlink_channels: downlink_channels.into_inner(),
             ad_hoc_buffer,
+            command_acks,
+            stop_token,
+            reentrancy,
             join_lane_init,
+            feature_flags,
+            dynamic_lanes,
         };
         Ok(agent_task.run_agent(context).boxed())
     }
@@ -923,8 +1317,13 @@ struct AgentTask<ItemModel, Lifecycle> {
     http_lane_rxs: HashMap<Text, mpsc::Receiver<HttpLaneRequest>>,
     suspended: FuturesUnordered<HandlerFuture<ItemModel>>,
     join_lane_init: HashMap<u64, BoxJoinLaneInit<'static, ItemModel>>,
+    feature_flags: FeatureFlags,
     ad_hoc_buffer: BytesMut,
+    command_acks: CommandAcks,
+    stop_token: CancellationToken,
+    reentrancy: ReentrancyPolicy,
     downlink_channels: Vec<BoxDownlinkChannel<ItemModel>>,
+    dynamic_lanes: DynamicLanes,
 }
 
 impl<ItemModel, Lifecycle> AgentTask<ItemModel, Lifecycle>
@@ -947,15 +1346,20 @@ where
             route,
             route_params,
             config,
-            lifecycle_item_ids,
+            mut lifecycle_item_ids,
             external_item_ids,
             lane_io,
             store_io,
             http_lane_rxs,
             mut suspended,
             mut join_lane_init,
+            feature_flags,
             mut ad_hoc_buffer,
+            command_acks,
+            stop_token,
+            reentrancy,
             downlink_channels,
+            dynamic_lanes,
         } = self;
         let meta = AgentMetadata::new(&route, &route_params, &config);
 
@@ -965,7 +1369,7 @@ where
         let mut downlinks = FuturesUnordered::new();
         let mut external_item_ids_rev = HashMap::new();
         for (name, id) in external_item_ids.iter() {
-            external_item_ids_rev.insert(*id, name);
+            external_item_ids_rev.insert(*id, name.clone());
         }
 
         let mut cmd_writer = if let Ok(cmd_tx) = context.ad_hoc_commands().await {
@@ -984,15 +1388,20 @@ where
             downlinks.push(Either::Left(dl.wait_on_downlink()));
         }
 
+        let LaneConfig {
+            max_message_size,
+            limits,
+            ..
+        } = config.default_lane_config.unwrap_or_default();
         for ((name, kind), (tx, rx)) in lane_io {
             if kind.map_like() {
                 let id = external_item_ids[&name];
-                lane_readers.push(LaneReader::map(id, rx));
-                item_writers.insert(id, ItemWriter::new(id, tx));
+                lane_readers.push(LaneReader::map(id, rx, max_message_size, limits));
+                item_writers.insert(id, ItemWriter::lane(id, tx));
             } else {
                 let id = external_item_ids[&name];
-                lane_readers.push(LaneReader::value(id, rx));
-                item_writers.insert(id, ItemWriter::new(id, tx));
+                lane_readers.push(LaneReader::value(id, rx, max_message_size));
+                item_writers.insert(id, ItemWriter::lane(id, tx));
             }
         }
 
@@ -1010,6 +1419,7 @@ where
         // initialisation process called lifecycle::on_start and that may have sent commands.
         check_cmds(
             &mut ad_hoc_buffer,
+            &command_acks,
             &mut cmd_writer,
             &mut cmd_send_fut,
             CommandWriter::write,
@@ -1017,8 +1427,16 @@ where
 
         // This set keeps track of which items have data to be written (according to executed event handlers).
         let mut dirty_items: HashSet<u64> = HashSet::new();
+        // The correlation IDs of the commands that most recently made lanes dirty.
+        let mut item_correlations: HashMap<u64, u64> = HashMap::new();
+        // The remotes that sent the commands that most recently made lanes dirty (only for lanes
+        // that track the origins of commands).
+        let mut item_origins: HashMap<u64, Uuid> = HashMap::new();
+
+        // Resolves if the runtime is about to stop the agent, before the lanes are torn down.
+        let mut stop_signal = context.stop_signal().fuse();
 
-        loop {
+        let loop_result = loop {
             let select_event = async {
                 tokio::select! {
                     maybe_suspended = suspended.next(), if !suspended.is_empty() => {
@@ -1065,11 +1483,12 @@ where
                         continue;
                     }
                 }
+                reason = &mut stop_signal => break Ok(reason),
                 maybe_event = select_event => {
                     if let Some(event) = maybe_event {
                         event
                     } else {
-                        break Ok(());
+                        break Ok(StopReason::ExternalStop);
                     }
                 }
             };
@@ -1093,7 +1512,12 @@ where
                                         &add_downlink,
                                         &mut join_lane_init,
                                         &mut ad_hoc_buffer,
-                                    ),
+                                    )
+                                    .with_feature_flags(&feature_flags)
+                                    .with_lane_spawner(&dynamic_lanes)
+                                    .with_command_acks(&command_acks)
+                                    .with_stop_token(&stop_token)
+                                    .with_reentrancy(reentrancy),
                                     meta,
                                     &item_model,
                                     &lifecycle,
@@ -1101,12 +1525,15 @@ where
                                     &lifecycle_item_ids,
                                     &mut dirty_items,
                                 ) {
-                                    Err(EventHandlerError::StopInstructed) => break Ok(()),
+                                    Err(EventHandlerError::StopInstructed) => {
+                                        break Ok(StopReason::Instructed)
+                                    }
                                     Err(e) => {
                                         break Err(AgentTaskError::UserCodeError(Box::new(e)))
                                     }
                                     Ok(_) => check_cmds(
                                         &mut ad_hoc_buffer,
+                                        &command_acks,
                                         &mut cmd_writer,
                                         &mut cmd_send_fut,
                                         CommandWriter::write,
@@ -1114,7 +1541,7 @@ where
                                 }
                             }
                         }
-                        Err(_) => break Ok(()), //Failing to write indicates that the runtime has stopped so we can exit without an error.
+                        Err(_) => break Ok(StopReason::ExternalStop), //Failing to write indicates that the runtime has stopped so we can exit without an error.
                         _ => {}
                     }
                     item_writers.insert(writer.lane_id(), writer);
@@ -1127,7 +1554,12 @@ where
                             &add_downlink,
                             &mut join_lane_init,
                             &mut ad_hoc_buffer,
-                        ),
+                        )
+                        .with_feature_flags(&feature_flags)
+                        .with_lane_spawner(&dynamic_lanes)
+                        .with_command_acks(&command_acks)
+                        .with_stop_token(&stop_token)
+                        .with_reentrancy(reentrancy),
                         meta,
                         &item_model,
                         &lifecycle,
@@ -1135,10 +1567,11 @@ where
                         &lifecycle_item_ids,
                         &mut dirty_items,
                     ) {
-                        Err(EventHandlerError::StopInstructed) => break Ok(()),
+                        Err(EventHandlerError::StopInstructed) => break Ok(StopReason::Instructed),
                         Err(e) => break Err(AgentTaskError::UserCodeError(Box::new(e))),
                         Ok(_) => check_cmds(
                             &mut ad_hoc_buffer,
+                            &command_acks,
                             &mut cmd_writer,
                             &mut cmd_send_fut,
                             CommandWriter::write,
@@ -1173,7 +1606,12 @@ where
                                     &add_downlink,
                                     &mut join_lane_init,
                                     &mut ad_hoc_buffer,
-                                ),
+                                )
+                                .with_feature_flags(&feature_flags)
+                                .with_lane_spawner(&dynamic_lanes)
+                                .with_command_acks(&command_acks)
+                                .with_stop_token(&stop_token)
+                                .with_reentrancy(reentrancy),
                                 meta,
                                 &item_model,
                                 &lifecycle,
@@ -1181,10 +1619,13 @@ where
                                 &lifecycle_item_ids,
                                 &mut dirty_items,
                             ) {
-                                Err(EventHandlerError::StopInstructed) => break Ok(()),
+                                Err(EventHandlerError::StopInstructed) => {
+                                    break Ok(StopReason::Instructed)
+                                }
                                 Err(e) => break Err(AgentTaskError::UserCodeError(Box::new(e))),
                                 Ok(_) => check_cmds(
                                     &mut ad_hoc_buffer,
+                                    &command_acks,
                                     &mut cmd_writer,
                                     &mut cmd_send_fut,
                                     CommandWriter::write,
@@ -1228,9 +1669,15 @@ where
                 },
                 TaskEvent::ValueRequest { id, request } => {
                     let name = &external_item_ids_rev[&id];
+                    let origin = request.origin();
+                    let correlation_id = request.correlation_id();
                     match request {
-                        LaneRequest::Command(body) => {
-                            trace!(name = %name, "Received a command for a value-like lane.");
+                        LaneRequest::Command(body)
+                        | LaneRequest::CommandFrom(_, body)
+                        | LaneRequest::CorrelatedCommand(_, _, body) => {
+                            let _span = correlation_id
+                                .map(|id| debug_span!("Command", correlation_id = id).entered());
+                            trace!(name = %name, origin = ?origin, "Received a command for a value-like lane.");
                             if let Some(handler) = item_model.on_value_command(name.as_str(), body)
                             {
                                 let result = run_handler(
@@ -1240,16 +1687,30 @@ where
                                         &add_downlink,
                                         &mut join_lane_init,
                                         &mut ad_hoc_buffer,
-                                    ),
-                                    meta,
+                                    )
+                                    .with_feature_flags(&feature_flags)
+                                    .with_lane_spawner(&dynamic_lanes)
+                                    .with_command_acks(&command_acks)
+                                    .with_stop_token(&stop_token)
+                                    .with_reentrancy(reentrancy),
+                                    meta.with_command_origin(origin)
+                                        .with_correlation_id(correlation_id),
                                     &item_model,
                                     &lifecycle,
                                     handler,
                                     &lifecycle_item_ids,
-                                    &mut dirty_items,
+                                    &mut Correlating {
+                                        ids: &mut dirty_items,
+                                        correlations: &mut item_correlations,
+                                        correlation_id,
+                                        origins: &mut item_origins,
+                                        origin,
+                                    },
                                 );
                                 match result {
-                                    Err(EventHandlerError::StopInstructed) => break Ok(()),
+                                    Err(EventHandlerError::StopInstructed) => {
+                                        break Ok(StopReason::Instructed)
+                                    }
                                     Err(
                                         e @ (EventHandlerError::RuntimeError(_)
                                         | EventHandlerError::SteppedAfterComplete),
@@ -1264,6 +1725,7 @@ where
                                     }
                                     _ => check_cmds(
                                         &mut ad_hoc_buffer,
+                                        &command_acks,
                                         &mut cmd_writer,
                                         &mut cmd_send_fut,
                                         CommandWriter::write,
@@ -1271,7 +1733,9 @@ where
                                 }
                             }
                         }
-                        LaneRequest::Sync(remote_id) => {
+                        LaneRequest::Sync(remote_id)
+                        | LaneRequest::SyncSince(remote_id, _)
+                        | LaneRequest::SyncRange(remote_id, _) => {
                             trace!(name = %name, remote_id = %remote_id, "Received a sync request for a value-like lane.");
                             if let Some(handler) = item_model.on_sync(name.as_str(), remote_id) {
                                 match run_handler(
@@ -1281,7 +1745,12 @@ where
                                         &add_downlink,
                                         &mut join_lane_init,
                                         &mut ad_hoc_buffer,
-                                    ),
+                                    )
+                                    .with_feature_flags(&feature_flags)
+                                    .with_lane_spawner(&dynamic_lanes)
+                                    .with_command_acks(&command_acks)
+                                    .with_stop_token(&stop_token)
+                                    .with_reentrancy(reentrancy),
                                     meta,
                                     &item_model,
                                     &lifecycle,
@@ -1289,12 +1758,15 @@ where
                                     &lifecycle_item_ids,
                                     &mut dirty_items,
                                 ) {
-                                    Err(EventHandlerError::StopInstructed) => break Ok(()),
+                                    Err(EventHandlerError::StopInstructed) => {
+                                        break Ok(StopReason::Instructed)
+                                    }
                                     Err(e) => {
                                         break Err(AgentTaskError::UserCodeError(Box::new(e)))
                                     }
                                     Ok(_) => check_cmds(
                                         &mut ad_hoc_buffer,
+                                        &command_acks,
                                         &mut cmd_writer,
                                         &mut cmd_send_fut,
                                         CommandWriter::write,
@@ -1307,9 +1779,15 @@ where
                 }
                 TaskEvent::MapRequest { id, request } => {
                     let name = &external_item_ids_rev[&id];
+                    let origin = request.origin();
+                    let correlation_id = request.correlation_id();
                     match request {
-                        LaneRequest::Command(body) => {
-                            trace!(name = %name, "Received a command for a map-like lane.");
+                        LaneRequest::Command(body)
+                        | LaneRequest::CommandFrom(_, body)
+                        | LaneRequest::CorrelatedCommand(_, _, body) => {
+                            let _span = correlation_id
+                                .map(|id| debug_span!("Command", correlation_id = id).entered());
+                            trace!(name = %name, origin = ?origin, "Received a command for a map-like lane.");
                             if let Some(handler) = item_model.on_map_command(name.as_str(), body) {
                                 let result = run_handler(
                                     &mut ActionContext::new(
@@ -1318,16 +1796,30 @@ where
                                         &add_downlink,
                                         &mut join_lane_init,
                                         &mut ad_hoc_buffer,
-                                    ),
-                                    meta,
+                                    )
+                                    .with_feature_flags(&feature_flags)
+                                    .with_lane_spawner(&dynamic_lanes)
+                                    .with_command_acks(&command_acks)
+                                    .with_stop_token(&stop_token)
+                                    .with_reentrancy(reentrancy),
+                                    meta.with_command_origin(origin)
+                                        .with_correlation_id(correlation_id),
                                     &item_model,
                                     &lifecycle,
                                     handler,
                                     &lifecycle_item_ids,
-                                    &mut dirty_items,
+                                    &mut Correlating {
+                                        ids: &mut dirty_items,
+                                        correlations: &mut item_correlations,
+                                        correlation_id,
+                                        origins: &mut item_origins,
+                                        origin,
+                                    },
                                 );
                                 match result {
-                                    Err(EventHandlerError::StopInstructed) => break Ok(()),
+                                    Err(EventHandlerError::StopInstructed) => {
+                                        break Ok(StopReason::Instructed)
+                                    }
                                     Err(
                                         e @ (EventHandlerError::RuntimeError(_)
                                         | EventHandlerError::SteppedAfterComplete),
@@ -1342,6 +1834,7 @@ where
                                     }
                                     _ => check_cmds(
                                         &mut ad_hoc_buffer,
+                                        &command_acks,
                                         &mut cmd_writer,
                                         &mut cmd_send_fut,
                                         CommandWriter::write,
@@ -1349,9 +1842,20 @@ where
                                 }
                             }
                         }
-                        LaneRequest::Sync(remote_id) => {
+                        LaneRequest::Sync(remote_id)
+                        | LaneRequest::SyncSince(remote_id, _)
+                        | LaneRequest::SyncRange(remote_id, _) => {
                             trace!(name = %name, remote_id = %remote_id, "Received a sync request for a map-like lane.");
-                            if let Some(handler) = item_model.on_sync(name.as_str(), remote_id) {
+                            let handler = match request {
+                                LaneRequest::SyncSince(_, since) => {
+                                    item_model.on_sync_since(name.as_str(), remote_id, since)
+                                }
+                                LaneRequest::SyncRange(_, range) => {
+                                    item_model.on_sync_range(name.as_str(), remote_id, range)
+                                }
+                                _ => item_model.on_sync(name.as_str(), remote_id),
+                            };
+                            if let Some(handler) = handler {
                                 match run_handler(
                                     &mut ActionContext::new(
                                         &suspended,
@@ -1359,7 +1863,12 @@ where
                                         &add_downlink,
                                         &mut join_lane_init,
                                         &mut ad_hoc_buffer,
-                                    ),
+                                    )
+                                    .with_feature_flags(&feature_flags)
+                                    .with_lane_spawner(&dynamic_lanes)
+                                    .with_command_acks(&command_acks)
+                                    .with_stop_token(&stop_token)
+                                    .with_reentrancy(reentrancy),
                                     meta,
                                     &item_model,
                                     &lifecycle,
@@ -1367,12 +1876,15 @@ where
                                     &lifecycle_item_ids,
                                     &mut dirty_items,
                                 ) {
-                                    Err(EventHandlerError::StopInstructed) => break Ok(()),
+                                    Err(EventHandlerError::StopInstructed) => {
+                                        break Ok(StopReason::Instructed)
+                                    }
                                     Err(e) => {
                                         break Err(AgentTaskError::UserCodeError(Box::new(e)))
                                     }
                                     Ok(_) => check_cmds(
                                         &mut ad_hoc_buffer,
+                                        &command_acks,
                                         &mut cmd_writer,
                                         &mut cmd_send_fut,
                                         CommandWriter::write,
@@ -1395,7 +1907,12 @@ where
                                     &add_downlink,
                                     &mut join_lane_init,
                                     &mut ad_hoc_buffer,
-                                ),
+                                )
+                                .with_feature_flags(&feature_flags)
+                                .with_lane_spawner(&dynamic_lanes)
+                                .with_command_acks(&command_acks)
+                                .with_stop_token(&stop_token)
+                                .with_reentrancy(reentrancy),
                                 meta,
                                 &item_model,
                                 &lifecycle,
@@ -1403,10 +1920,13 @@ where
                                 &lifecycle_item_ids,
                                 &mut dirty_items,
                             ) {
-                                Err(EventHandlerError::StopInstructed) => break Ok(()),
+                                Err(EventHandlerError::StopInstructed) => {
+                                    break Ok(StopReason::Instructed)
+                                }
                                 Err(e) => break Err(AgentTaskError::UserCodeError(Box::new(e))),
                                 Ok(_) => check_cmds(
                                     &mut ad_hoc_buffer,
+                                    &command_acks,
                                     &mut cmd_writer,
                                     &mut cmd_send_fut,
                                     CommandWriter::write,
@@ -1423,7 +1943,7 @@ where
                 TaskEvent::CommandSendComplete { result: Ok(writer) } => {
                     cmd_send_fut.set(None.into());
                     if !ad_hoc_buffer.is_empty() {
-                        let fut = writer.write(&mut ad_hoc_buffer);
+                        let fut = writer.write(&mut ad_hoc_buffer, command_acks.take());
                         cmd_send_fut.set(Some(fut.fuse()).into());
                     } else {
                         cmd_writer = Some(writer);
@@ -1433,16 +1953,37 @@ where
                     break Err(AgentTaskError::OutputFailed(err));
                 }
             }
+            // Attach any lanes that were opened by the event handlers. These are marked as dirty so
+            // that any state that was set when they were created is written.
+            for DynamicLane { id, name, kind, io } in dynamic_lanes.take_pending() {
+                info!(name = %name, kind = ?kind, id, "Attaching a lane opened by an event handler.");
+                let (tx, rx) = io;
+                if kind.map_like() {
+                    lane_readers.push(LaneReader::map(id, rx, max_message_size, limits));
+                } else {
+                    lane_readers.push(LaneReader::value(id, rx, max_message_size));
+                }
+                item_writers.insert(id, ItemWriter::lane(id, tx));
+                lifecycle_item_ids.insert(id, name.clone());
+                external_item_ids_rev.insert(id, name);
+                dirty_items.insert(id);
+            }
             // Attempt to write to the outgoing buffers for any items with data.
             dirty_items.retain(|id| {
                 if let Some(mut tx) = item_writers.remove(id) {
                     let name = &external_item_ids_rev[id];
+                    tx.correlate(item_correlations.get(id).copied());
+                    tx.originate(item_origins.get(id).copied());
                     match item_model.write_event(name.as_str(), &mut tx.buffer) {
                         Some(WriteResult::Done) => {
+                            item_correlations.remove(id);
+                            item_origins.remove(id);
                             pending_writes.push(do_write(tx, false));
                             false
                         }
                         Some(WriteResult::RequiresEvent) => {
+                            item_correlations.remove(id);
+                            item_origins.remove(id);
                             pending_writes.push(do_write(tx, true));
                             false
                         }
@@ -1450,20 +1991,57 @@ where
                             pending_writes.push(do_write(tx, false));
                             true
                         }
-                        _ => false,
+                        _ => {
+                            item_correlations.remove(id);
+                            item_origins.remove(id);
+                            false
+                        }
                     }
                 } else {
                     true
                 }
             });
-        }?;
-        // Try to run the `on_stop` handler before we stop.
-        let on_stop_handler = lifecycle.on_stop();
+        };
+        let (reason, mut result) = match loop_result {
+            Ok(reason) => (reason, Ok(())),
+            Err(err) => (StopReason::Failure, Err(err)),
+        };
         let discard = |_| {
             Err(DownlinkRuntimeError::RuntimeError(
                 AgentRuntimeError::Stopping,
             ))
         };
+        // Cancel any suspended futures that can be cancelled and run their `on_cancelled` handlers
+        // (along with the handlers of any other suspended futures that have already completed).
+        stop_token.cancel();
+        while let Some(Some(handler)) = suspended.next().now_or_never() {
+            match run_handler(
+                &mut ActionContext::new(
+                    &suspended,
+                    &*context,
+                    &discard,
+                    &mut join_lane_init,
+                    &mut ad_hoc_buffer,
+                )
+                .with_feature_flags(&feature_flags)
+                .with_stop_token(&stop_token)
+                .with_reentrancy(reentrancy),
+                meta,
+                &item_model,
+                &lifecycle,
+                handler,
+                &lifecycle_item_ids,
+                &mut Discard,
+            ) {
+                Ok(_) | Err(EventHandlerError::StopInstructed) => {}
+                Err(e) => {
+                    result = result.and(Err(AgentTaskError::UserCodeError(Box::new(e))));
+                    break;
+                }
+            }
+        }
+        // Try to run the `on_stop` handler before we stop.
+        let on_stop_handler = lifecycle.on_stop(reason);
         match run_handler(
             &mut ActionContext::new(
                 &suspended,
@@ -1471,7 +2049,8 @@ where
                 &discard,
                 &mut join_lane_init,
                 &mut ad_hoc_buffer,
-            ),
+            )
+            .with_feature_flags(&feature_flags),
             meta,
             &item_model,
             &lifecycle,
@@ -1479,8 +2058,8 @@ where
             &lifecycle_item_ids,
             &mut Discard,
         ) {
-            Ok(_) | Err(EventHandlerError::StopInstructed) => Ok(()),
-            Err(e) => Err(AgentTaskError::UserCodeError(Box::new(e))),
+            Ok(_) | Err(EventHandlerError::StopInstructed) => result,
+            Err(e) => result.and(Err(AgentTaskError::UserCodeError(Box::new(e)))),
         }
     }
 }
@@ -1531,6 +2110,40 @@ impl IdCollector for HashSet<u64> {
     }
 }
 
+/// Collects the IDs of the items modified by the handler for a command, recording the correlation
+/// ID and origin of the command so that they can be reported with the events that are written for
+/// the items.
+struct Correlating<'a> {
+    ids: &'a mut HashSet<u64>,
+    correlations: &'a mut HashMap<u64, u64>,
+    correlation_id: Option<u64>,
+    origins: &'a mut HashMap<u64, Uuid>,
+    origin: Option<Uuid>,
+}
+
+impl IdCollector for Correlating<'_> {
+    fn add_id(&mut self, id: u64) {
+        let Correlating {
+            ids,
+            correlations,
+            correlation_id,
+            origins,
+            origin,
+        } = self;
+        ids.insert(id);
+        if let Some(correlation_id) = correlation_id {
+            correlations.insert(id, *correlation_id);
+        } else {
+            correlations.remove(&id);
+        }
+        if let Some(origin) = origin {
+            origins.insert(id, *origin);
+        } else {
+            origins.remove(&id);
+        }
+    }
+}
+
 /// Run an event handler within the context of the lifecycle of an agent. If the event handler causes another
 /// event to trigger, it is suspended while that other event handler is executed. When an event handler changes
 /// the state of a lane, that is recorded by the collector so that the change can be written out after the chain
@@ -1555,6 +2168,9 @@ impl IdCollector for HashSet<u64> {
 /// * `items` - Mapping between item IDs (returned by the handler to indicate that it has changed the state of
 ///    an item) and the item names (which are used by the lifecycle to identify the items).
 /// * `collector` - Collects the IDs of lanes with state changes.
+///
+/// If the policy of the action context enables reentrancy diagnostics, any write to an item that
+/// was already borrowed will cause the handler to fail with [`EventHandlerError::ReentrantAccess`].
 fn run_handler<Context, Lifecycle, Handler, Collector>(
     action_context: &mut ActionContext<Context>,
     meta: AgentMetadata,
@@ -1569,8 +2185,16 @@ where
     Handler: EventHandler<Context>,
     Collector: IdCollector,
 {
+    let _scope = reentrancy::enter(action_context.reentrancy());
     loop {
-        match handler.step(action_context, meta, context) {
+        let result = handler.step(action_context, meta, context);
+        if let Some(item_id) = reentrancy::take_violation() {
+            break Err(EventHandlerError::ReentrantAccess {
+                item_id,
+                item_name: items.get(&item_id).cloned(),
+            });
+        }
+        match result {
             StepResult::Continue { modified_item } => {
                 if let Some((modification, lane)) = modified_item.and_then(|modification| {
                     items
@@ -1649,31 +2273,54 @@ impl CommandWriter {
     fn write(
         mut self,
         content: &mut BytesMut,
+        acks: Vec<oneshot::Sender<()>>,
     ) -> impl Future<Output = Result<Self, std::io::Error>> + 'static {
         self.buffer.clear();
         std::mem::swap(&mut self.buffer, content);
         async move {
             let CommandWriter { tx, buffer } = &mut self;
             tx.write_all(buffer).await?;
+            for ack in acks {
+                // The handler waiting on the acknowledgement may have been discarded.
+                let _ = ack.send(());
+            }
             Ok(self)
         }
     }
 }
 
+/// Acknowledgements for the commands in the ad hoc command buffer of the agent.
+#[derive(Debug, Default)]
+struct CommandAcks(RefCell<Vec<oneshot::Sender<()>>>);
+
+impl CommandAcks {
+    /// Take the acknowledgements for the current contents of the buffer.
+    fn take(&self) -> Vec<oneshot::Sender<()>> {
+        std::mem::take(&mut *self.0.borrow_mut())
+    }
+}
+
+impl CommandAckTracker for CommandAcks {
+    fn track(&self, ack: oneshot::Sender<()>) {
+        self.0.borrow_mut().push(ack);
+    }
+}
+
 /// Check of an event handler wrote into the ad-hoc commands buffer and schedule a
 /// write to the command channel.
 #[inline]
 fn check_cmds<Fut>(
     ad_hoc_buffer: &mut BytesMut,
+    command_acks: &CommandAcks,
     cmd_writer: &mut Option<CommandWriter>,
     cmd_send_fut: &mut Pin<&mut OptionFuture<Fuse<Fut>>>,
-    write: fn(CommandWriter, &mut BytesMut) -> Fut,
+    write: fn(CommandWriter, &mut BytesMut, Vec<oneshot::Sender<()>>) -> Fut,
 ) where
     Fut: Future,
 {
     if !ad_hoc_buffer.is_empty() {
         if let Some(writer) = cmd_writer.take() {
-            let fut = write(writer, ad_hoc_buffer);
+            let fut = write(writer, ad_hoc_buffer, command_acks.take());
             cmd_send_fut.set(Some(fut.fuse()).into());
         }
     }
@@ -38,7 +38,8 @@ use crate::{
 };
 
 use super::{
-    TestEvent, CMD_ID, CMD_LANE, HTTP_ID, HTTP_LANE, MAP_ID, MAP_LANE, SYNC_VALUE, VAL_ID, VAL_LANE,
+    TestEvent, CMD_ID, CMD_LANE, HTTP_ID, HTTP_LANE, MAP_ID, MAP_LANE, SYNC_VALUE, VAL_ALIAS,
+    VAL_ID, VAL_LANE,
 };
 
 #[derive(Debug)]
@@ -156,7 +157,8 @@ impl AgentSpec for TestAgent {
                     kind: WarpLaneKind::Value,
                     flags: ItemFlags::TRANSIENT,
                 },
-            ),
+            )
+            .with_aliases(&[VAL_ALIAS]),
         );
         lanes.insert(
             CMD_LANE,
@@ -228,6 +230,7 @@ impl AgentSpec for TestAgent {
             Some(
                 TestEvent::Map {
                     body: interpret_map_op(body),
+                    origin: None,
                 }
                 .into(),
             )
@@ -313,7 +316,7 @@ impl HandlerAction<TestAgent> for TestHandler {
     fn step(
         &mut self,
         _action_context: &mut ActionContext<TestAgent>,
-        _meta: AgentMetadata,
+        meta: AgentMetadata,
         context: &TestAgent,
     ) -> StepResult<Self::Completion> {
         let TestHandler { event } = self;
@@ -328,7 +331,8 @@ impl HandlerAction<TestAgent> for TestHandler {
                     *cmd = Some(*body);
                     Some(Modification::of(CMD_ID))
                 }
-                TestEvent::Map { body } => {
+                TestEvent::Map { body, origin } => {
+                    *origin = meta.command_origin();
                     context.stage_map(to_op(*body));
                     Some(Modification::of(MAP_ID))
                 }
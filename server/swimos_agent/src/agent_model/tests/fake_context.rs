@@ -15,15 +15,15 @@
 use std::{num::NonZeroUsize, sync::Arc};
 
 use futures::{
-    future::{ready, BoxFuture},
+    future::{pending, ready, BoxFuture},
     FutureExt,
 };
 use parking_lot::Mutex;
 use swimos_api::{
     agent::DownlinkKind,
     agent::{
-        AgentContext, HttpLaneRequest, HttpLaneRequestChannel, LaneConfig, StoreKind, UplinkKind,
-        WarpLaneKind,
+        AgentContext, HttpLaneRequest, HttpLaneRequestChannel, LaneConfig, StopReason, StoreKind,
+        UplinkKind, WarpLaneKind,
     },
     error::{AgentRuntimeError, DownlinkRuntimeError, OpenStoreError},
 };
@@ -66,6 +66,14 @@ impl TestAgentContext {
     pub fn take_http_io(&self) -> Option<mpsc::Sender<HttpLaneRequest>> {
         self.inner.lock().http_sender.take()
     }
+
+    pub fn set_stop_signal(&self, rx: oneshot::Receiver<StopReason>) {
+        self.inner.lock().stop_rx = Some(rx);
+    }
+
+    pub fn lane_aliases(&self) -> Vec<(String, String)> {
+        self.inner.lock().lane_aliases.clone()
+    }
 }
 
 type Io = (ByteWriter, ByteReader);
@@ -78,6 +86,8 @@ struct Inner {
     http_sender: Option<mpsc::Sender<HttpLaneRequest>>,
     ad_hoc_consumer: Option<oneshot::Sender<ByteReader>>,
     ad_hoc_rx: Option<ByteReader>,
+    stop_rx: Option<oneshot::Receiver<StopReason>>,
+    lane_aliases: Vec<(String, String)>,
 }
 
 const CHAN_SIZE: NonZeroUsize = non_zero_usize!(8);
@@ -127,6 +137,18 @@ impl AgentContext for TestAgentContext {
         }
     }
 
+    fn add_lane_alias(
+        &self,
+        alias: &str,
+        name: &str,
+    ) -> BoxFuture<'static, Result<(), AgentRuntimeError>> {
+        self.inner
+            .lock()
+            .lane_aliases
+            .push((alias.to_string(), name.to_string()));
+        ready(Ok(())).boxed()
+    }
+
     fn open_downlink(
         &self,
         _host: Option<&str>,
@@ -159,4 +181,18 @@ impl AgentContext for TestAgentContext {
             panic!("Unexpected lane registration: {:?}", name);
         }
     }
+
+    fn stop_signal(&self) -> BoxFuture<'static, StopReason> {
+        if let Some(rx) = self.inner.lock().stop_rx.take() {
+            async move {
+                match rx.await {
+                    Ok(reason) => reason,
+                    Err(_) => pending().await,
+                }
+            }
+            .boxed()
+        } else {
+            pending().boxed()
+        }
+    }
 }
@@ -12,8 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use futures::FutureExt;
+use futures::{future::pending, FutureExt};
 use swimos_api::address::Address;
+use swimos_api::agent::StopReason;
 use swimos_model::Text;
 use tokio::sync::mpsc;
 
@@ -21,6 +22,7 @@ use crate::{
     agent_lifecycle::{item_event::ItemEvent, on_init::OnInit, on_start::OnStart, on_stop::OnStop},
     event_handler::{
         ActionContext, HandlerAction, LocalBoxEventHandler, SideEffect, Spawner, StepResult,
+        SuspendCancellable, UnitHandler,
     },
     meta::AgentMetadata,
 };
@@ -34,22 +36,37 @@ pub enum LifecycleEvent {
     Lane(Text),
     RanSuspended(i32),
     RanSuspendedConsequence,
-    Stop,
+    Cancelled,
+    Stop(StopReason),
 }
 
 pub struct LifecycleHandler {
     sender: mpsc::UnboundedSender<LifecycleEvent>,
     event: Option<LifecycleEvent>,
+    suspend_cancellable: bool,
 }
 
 #[derive(Clone)]
 pub struct TestLifecycle {
     sender: mpsc::UnboundedSender<LifecycleEvent>,
+    suspend_cancellable: bool,
 }
 
 impl TestLifecycle {
     pub fn new(tx: mpsc::UnboundedSender<LifecycleEvent>) -> TestLifecycle {
-        TestLifecycle { sender: tx }
+        TestLifecycle {
+            sender: tx,
+            suspend_cancellable: false,
+        }
+    }
+
+    /// The `on_start` handler will suspend a future that never completes and that reports when
+    /// it is cancelled.
+    pub fn suspending_cancellable(tx: mpsc::UnboundedSender<LifecycleEvent>) -> TestLifecycle {
+        TestLifecycle {
+            sender: tx,
+            suspend_cancellable: true,
+        }
     }
 }
 
@@ -58,6 +75,7 @@ impl TestLifecycle {
         LifecycleHandler {
             sender: self.sender.clone(),
             event: Some(event),
+            suspend_cancellable: self.suspend_cancellable,
         }
     }
 }
@@ -86,8 +104,8 @@ impl OnStop<TestAgent> for TestLifecycle {
     where
         Self: 'a;
 
-    fn on_stop(&self) -> Self::OnStopHandler<'_> {
-        self.make_handler(LifecycleEvent::Stop)
+    fn on_stop(&self, reason: StopReason) -> Self::OnStopHandler<'_> {
+        self.make_handler(LifecycleEvent::Stop(reason))
     }
 }
 
@@ -111,11 +129,26 @@ impl HandlerAction<TestAgent> for LifecycleHandler {
     fn step(
         &mut self,
         action_context: &mut ActionContext<TestAgent>,
-        _meta: AgentMetadata,
+        meta: AgentMetadata,
         context: &TestAgent,
     ) -> StepResult<Self::Completion> {
-        let LifecycleHandler { sender, event } = self;
+        let LifecycleHandler {
+            sender,
+            event,
+            suspend_cancellable,
+        } = self;
         if let Some(event) = event.take() {
+            if *suspend_cancellable && event == LifecycleEvent::Start {
+                let tx = sender.clone();
+                let on_cancelled = SideEffect::from(move || {
+                    tx.send(LifecycleEvent::Cancelled).expect("Channel closed.");
+                });
+                let mut suspend = SuspendCancellable::new(pending::<UnitHandler>(), on_cancelled);
+                assert!(matches!(
+                    suspend.step(action_context, meta, context),
+                    StepResult::Complete { .. }
+                ));
+            }
             if let LifecycleEvent::Lane(name) = &event {
                 match name.as_str() {
                     CMD_LANE => {
@@ -53,6 +53,26 @@ impl ValueLaneSender {
             .expect("Sending to value lane failed.");
     }
 
+    pub async fn correlated_command(&mut self, correlation_id: u64, n: i32) {
+        let ValueLaneSender { buffer, inner } = self;
+        write!(buffer, "{}", n).expect("Writing to buffer failed.");
+        let bytes = buffer.split();
+        inner
+            .send(LaneRequest::CorrelatedCommand(correlation_id, None, bytes))
+            .await
+            .expect("Sending to value lane failed.");
+    }
+
+    pub async fn command_from(&mut self, origin: Uuid, n: i32) {
+        let ValueLaneSender { buffer, inner } = self;
+        write!(buffer, "{}", n).expect("Writing to buffer failed.");
+        let bytes = buffer.split();
+        inner
+            .send(LaneRequest::CommandFrom(origin, bytes))
+            .await
+            .expect("Sending to value lane failed.");
+    }
+
     pub async fn sync(&mut self, id: Uuid) {
         let ValueLaneSender { inner, .. } = self;
         let req: LaneRequest<BytesMut> = LaneRequest::Sync(id);
@@ -89,6 +109,24 @@ impl ValueLaneReceiver {
         }
     }
 
+    pub async fn expect_correlation(&mut self, expected: Option<u64>) {
+        let response = self.get_response().await;
+        if let LaneResponse::Correlated(correlation_id) = response {
+            assert_eq!(correlation_id, expected);
+        } else {
+            panic!("Unexpected response.");
+        }
+    }
+
+    pub async fn expect_origin(&mut self, expected: Option<Uuid>) {
+        let response = self.get_response().await;
+        if let LaneResponse::Originated(origin) = response {
+            assert_eq!(origin, expected);
+        } else {
+            panic!("Unexpected response.");
+        }
+    }
+
     pub async fn expect_sync_event(&mut self, id: Uuid, expected: i32) {
         let first = self.get_response().await;
         let second = self.get_response().await;
@@ -137,6 +175,17 @@ impl MapLaneSender {
             .await
             .expect("Sending to map lane failed.");
     }
+
+    pub async fn command_from(&mut self, origin: Uuid, key: i32, value: i32) {
+        let MapLaneSender { inner } = self;
+        inner
+            .send(LaneRequest::CommandFrom(
+                origin,
+                MapMessage::Update { key, value },
+            ))
+            .await
+            .expect("Sending to map lane failed.");
+    }
 }
 
 impl MapLaneReceiver {
@@ -165,6 +214,15 @@ impl MapLaneReceiver {
             ow => panic!("Unexpected response: {:?}", ow),
         }
     }
+
+    pub async fn expect_origin(&mut self, expected: Option<Uuid>) {
+        let response = self.get_response().await;
+
+        match response {
+            MapLaneResponse::Originated(origin) => assert_eq!(origin, expected),
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+    }
 }
 
 fn read_op(operation: MapOperation<BytesMut, BytesMut>) -> MapOperation<i32, i32> {
@@ -25,7 +25,11 @@ use swimos_agent_protocol::{
 };
 use swimos_api::{
     address::Address,
-    agent::{AgentConfig, AgentTask, DownlinkKind, HttpLaneRequest},
+    agent::{
+        AgentConfig, AgentTask, DownlinkKind, HttpLaneRequest, ItemManifest, ItemStateKind,
+        StopReason, StoreKind, WarpLaneKind,
+    },
+    error::AgentInitError,
     http::{HttpRequest, Method, StatusCode, Version},
 };
 use swimos_model::Text;
@@ -53,7 +57,9 @@ use self::{
 
 use super::{
     downlink::{DownlinkChannel, DownlinkChannelError, DownlinkChannelEvent},
-    AgentModel, HostedDownlink, ItemModelFactory,
+    init::InitFn,
+    item_manifest, AgentModel, HostedDownlink, ItemDescriptor, ItemFlags, ItemModelFactory,
+    ItemSpec, LaneInitError, LaneInitializer, LifecycleFactory,
 };
 
 mod fake_agent;
@@ -75,10 +81,19 @@ where
 
 #[derive(Debug, Clone)]
 pub enum TestEvent {
-    Value { body: i32 },
-    Cmd { body: i32 },
-    Map { body: MapMessage<i32, i32> },
-    Sync { id: Uuid },
+    Value {
+        body: i32,
+    },
+    Cmd {
+        body: i32,
+    },
+    Map {
+        body: MapMessage<i32, i32>,
+        origin: Option<Uuid>,
+    },
+    Sync {
+        id: Uuid,
+    },
 }
 
 const VAL_ID: u64 = 0;
@@ -87,6 +102,7 @@ const CMD_ID: u64 = 2;
 const HTTP_ID: u64 = 3;
 
 const VAL_LANE: &str = "first";
+const VAL_ALIAS: &str = "zeroth";
 const MAP_LANE: &str = "second";
 const CMD_LANE: &str = "third";
 const HTTP_LANE: &str = "fourth";
@@ -223,6 +239,193 @@ async fn run_agent_init_task() {
     .await
 }
 
+#[tokio::test]
+async fn lane_aliases_registered() {
+    with_timeout(async {
+        let context = Box::<TestAgentContext>::default();
+        let _ = init_agent(context.clone()).await;
+
+        assert_eq!(
+            context.lane_aliases(),
+            vec![(VAL_ALIAS.to_string(), VAL_LANE.to_string())]
+        );
+    })
+    .await
+}
+
+struct FailingInitializer;
+
+impl LaneInitializer<TestAgent> for FailingInitializer {
+    fn item_id(&self, _item_model: &TestAgent) -> u64 {
+        VAL_ID
+    }
+
+    fn load(
+        &self,
+        _route: &RouteUri,
+        _route_params: &HashMap<String, String>,
+    ) -> BoxFuture<'static, Result<InitFn<TestAgent>, LaneInitError>> {
+        ready(Err(LaneInitError::TimedOut(TIMEOUT))).boxed()
+    }
+}
+
+#[tokio::test]
+async fn failed_lane_initializer_prevents_start() {
+    with_timeout(async {
+        let context = Box::<TestAgentContext>::default();
+        let lane_model_fac = Fac::new(TestAgent::default());
+
+        let (lc_event_tx, lc_event_rx) = mpsc::unbounded_channel();
+        let lifecycle = TestLifecycle::new(lc_event_tx);
+
+        let model = AgentModel::<TestAgent, TestLifecycle>::new(lane_model_fac, lifecycle)
+            .with_initializer(FailingInitializer);
+
+        let result = model
+            .initialize_agent(make_uri(), HashMap::new(), CONFIG, context)
+            .await;
+        assert!(matches!(result, Err(AgentInitError::UserCodeError(_))));
+
+        //The `on_start` event must not have fired.
+        let events = UnboundedReceiverStream::new(lc_event_rx)
+            .collect::<Vec<_>>()
+            .await;
+        assert!(events.is_empty());
+    })
+    .await
+}
+
+#[tokio::test]
+async fn lifecycle_from_async_fn() {
+    with_timeout(async {
+        let context = Box::<TestAgentContext>::default();
+        let lane_model_fac = Fac::new(TestAgent::default());
+
+        let (lc_event_tx, lc_event_rx) = mpsc::unbounded_channel();
+        let model =
+            AgentModel::<TestAgent, TestLifecycle>::from_async_fn(lane_model_fac, move || {
+                let tx = lc_event_tx.clone();
+                async move {
+                    tokio::task::yield_now().await;
+                    TestLifecycle::new(tx)
+                }
+            });
+
+        let task = model
+            .initialize_agent(make_uri(), HashMap::new(), CONFIG, context)
+            .await
+            .expect("Initialization failed.");
+        drop(task);
+
+        let events = UnboundedReceiverStream::new(lc_event_rx)
+            .collect::<Vec<_>>()
+            .await;
+        assert!(matches!(
+            events.as_slice(),
+            [LifecycleEvent::Init, LifecycleEvent::Start]
+        ));
+    })
+    .await
+}
+
+#[tokio::test]
+async fn failed_sync_lifecycle_fn_prevents_start() {
+    with_timeout(async {
+        let context = Box::<TestAgentContext>::default();
+        let lane_model_fac = Fac::new(TestAgent::default());
+
+        let model = AgentModel::<TestAgent, TestLifecycle>::try_from_fn(lane_model_fac, || {
+            Err(std::io::Error::from(ErrorKind::NotFound))
+        });
+
+        let result = model
+            .initialize_agent(make_uri(), HashMap::new(), CONFIG, context)
+            .await;
+        assert!(matches!(result, Err(AgentInitError::UserCodeError(_))));
+    })
+    .await
+}
+
+#[tokio::test]
+async fn lifecycle_from_sync_try_fn() {
+    with_timeout(async {
+        let context = Box::<TestAgentContext>::default();
+        let lane_model_fac = Fac::new(TestAgent::default());
+
+        let (lc_event_tx, lc_event_rx) = mpsc::unbounded_channel();
+        let model =
+            AgentModel::<TestAgent, TestLifecycle>::try_from_fn(lane_model_fac, move || {
+                Ok::<_, std::io::Error>(TestLifecycle::new(lc_event_tx.clone()))
+            });
+
+        let task = model
+            .initialize_agent(make_uri(), HashMap::new(), CONFIG, context)
+            .await
+            .expect("Initialization failed.");
+        drop(task);
+
+        let events = UnboundedReceiverStream::new(lc_event_rx)
+            .collect::<Vec<_>>()
+            .await;
+        assert!(matches!(
+            events.as_slice(),
+            [LifecycleEvent::Init, LifecycleEvent::Start]
+        ));
+    })
+    .await
+}
+
+#[tokio::test]
+async fn failed_lifecycle_fn_prevents_start() {
+    with_timeout(async {
+        let context = Box::<TestAgentContext>::default();
+        let lane_model_fac = Fac::new(TestAgent::default());
+
+        let model =
+            AgentModel::<TestAgent, TestLifecycle>::try_from_async_fn(lane_model_fac, || {
+                ready(Err(std::io::Error::from(ErrorKind::NotFound)))
+            });
+
+        let result = model
+            .initialize_agent(make_uri(), HashMap::new(), CONFIG, context)
+            .await;
+        assert!(matches!(result, Err(AgentInitError::UserCodeError(_))));
+    })
+    .await
+}
+
+struct FailingLifecycleFac;
+
+impl LifecycleFactory<TestAgent> for FailingLifecycleFac {
+    type LifecycleType = TestLifecycle;
+
+    fn create(&self) -> Self::LifecycleType {
+        panic!("Only try_create should be called.");
+    }
+
+    fn try_create(&self) -> Result<Self::LifecycleType, AgentInitError> {
+        Err(AgentInitError::UserCodeError(Box::new(
+            std::io::Error::from(ErrorKind::NotFound),
+        )))
+    }
+}
+
+#[tokio::test]
+async fn failed_lifecycle_factory_prevents_start() {
+    with_timeout(async {
+        let context = Box::<TestAgentContext>::default();
+        let lane_model_fac = Fac::new(TestAgent::default());
+
+        let model = AgentModel::from_factory(lane_model_fac, FailingLifecycleFac);
+
+        let result = model
+            .initialize_agent(make_uri(), HashMap::new(), CONFIG, context)
+            .await;
+        assert!(matches!(result, Err(AgentInitError::UserCodeError(_))));
+    })
+    .await
+}
+
 #[tokio::test]
 async fn stops_if_all_lanes_stop() {
     with_timeout(async move {
@@ -269,7 +472,10 @@ async fn stops_if_all_lanes_stop() {
         let events = lc_event_rx.collect::<Vec<_>>().await;
 
         //Check that the `on_stop` event fired.
-        assert!(matches!(events.as_slice(), [LifecycleEvent::Stop]));
+        assert!(matches!(
+            events.as_slice(),
+            [LifecycleEvent::Stop(StopReason::ExternalStop)]
+        ));
 
         let lane_events = test_event_rx.collect::<Vec<_>>().await;
         assert!(lane_events.is_empty());
@@ -277,6 +483,105 @@ async fn stops_if_all_lanes_stop() {
     .await
 }
 
+#[tokio::test]
+async fn stops_with_reason_when_signalled() {
+    with_timeout(async move {
+        let context = Box::<TestAgentContext>::default();
+        let (stop_tx, stop_rx) = oneshot::channel();
+        context.set_stop_signal(stop_rx);
+        let (
+            task,
+            TestContext {
+                test_event_rx,
+                http_request_rx: _http_request_rx,
+                mut lc_event_rx,
+                val_lane_io,
+                map_lane_io,
+                cmd_lane_io,
+                http_lane_tx,
+            },
+        ) = init_agent(context).await;
+
+        let test_case = async move {
+            assert_eq!(
+                lc_event_rx.next().await.expect("Expected init event."),
+                LifecycleEvent::Init
+            );
+            assert_eq!(
+                lc_event_rx.next().await.expect("Expected start event."),
+                LifecycleEvent::Start
+            );
+
+            //The lanes remain open but the runtime indicates that the agent is about to be stopped.
+            assert!(stop_tx.send(StopReason::InactivityTimeout).is_ok());
+
+            (
+                lc_event_rx,
+                val_lane_io,
+                map_lane_io,
+                cmd_lane_io,
+                http_lane_tx,
+            )
+        };
+
+        let (result, (lc_event_rx, _val_lane_io, _map_lane_io, _cmd_lane_io, _http_lane_tx)) =
+            join(task, test_case).await;
+        assert!(result.is_ok());
+
+        let events = lc_event_rx.collect::<Vec<_>>().await;
+
+        //Check that the `on_stop` event fired with the reason provided by the runtime.
+        assert!(matches!(
+            events.as_slice(),
+            [LifecycleEvent::Stop(StopReason::InactivityTimeout)]
+        ));
+
+        let lane_events = test_event_rx.collect::<Vec<_>>().await;
+        assert!(lane_events.is_empty());
+    })
+    .await
+}
+
+#[tokio::test]
+async fn suspended_futures_cancelled_on_stop() {
+    with_timeout(async move {
+        let context = Box::<TestAgentContext>::default();
+        let (stop_tx, stop_rx) = oneshot::channel();
+        context.set_stop_signal(stop_rx);
+
+        let lane_model_fac = Fac::new(TestAgent::default());
+        let (lc_event_tx, lc_event_rx) = mpsc::unbounded_channel();
+        let lifecycle = TestLifecycle::suspending_cancellable(lc_event_tx);
+        let model = AgentModel::<TestAgent, TestLifecycle>::new(lane_model_fac, lifecycle);
+
+        let task = model
+            .initialize_agent(make_uri(), HashMap::new(), CONFIG, context.clone())
+            .await
+            .expect("Initialization failed.");
+        let _lane_io = context.take_lane_io();
+        let _http_tx = context.take_http_io();
+
+        assert!(stop_tx.send(StopReason::InactivityTimeout).is_ok());
+        assert!(task.await.is_ok());
+
+        let events = UnboundedReceiverStream::new(lc_event_rx)
+            .collect::<Vec<_>>()
+            .await;
+
+        //The `on_cancelled` handler of the suspended future runs before the `on_stop` event.
+        assert!(matches!(
+            events.as_slice(),
+            [
+                LifecycleEvent::Init,
+                LifecycleEvent::Start,
+                LifecycleEvent::Cancelled,
+                LifecycleEvent::Stop(StopReason::InactivityTimeout)
+            ]
+        ));
+    })
+    .await
+}
+
 #[tokio::test]
 async fn command_to_value_lane() {
     with_timeout(async {
@@ -335,7 +640,173 @@ async fn command_to_value_lane() {
         let events = lc_event_rx.collect::<Vec<_>>().await;
 
         //Check that the `on_stop` event fired.
-        assert!(matches!(events.as_slice(), [LifecycleEvent::Stop]));
+        assert!(matches!(
+            events.as_slice(),
+            [LifecycleEvent::Stop(StopReason::ExternalStop)]
+        ));
+
+        let lane_events = test_event_rx.collect::<Vec<_>>().await;
+        assert!(lane_events.is_empty());
+    })
+    .await
+}
+
+#[tokio::test]
+async fn correlated_command_to_value_lane() {
+    with_timeout(async {
+        let context = Box::<TestAgentContext>::default();
+        let (
+            task,
+            TestContext {
+                mut test_event_rx,
+                http_request_rx: _http_request_rx,
+                mut lc_event_rx,
+                val_lane_io,
+                map_lane_io,
+                cmd_lane_io,
+                http_lane_tx,
+            },
+        ) = init_agent(context).await;
+
+        let test_case = async move {
+            assert_eq!(
+                lc_event_rx.next().await.expect("Expected init event."),
+                LifecycleEvent::Init
+            );
+            assert_eq!(
+                lc_event_rx.next().await.expect("Expected start event."),
+                LifecycleEvent::Start
+            );
+            let (mut sender, mut receiver) = val_lane_io;
+
+            sender.correlated_command(12, 56).await;
+
+            assert!(matches!(
+                test_event_rx.next().await.expect("Expected command event."),
+                TestEvent::Value { body: 56 }
+            ));
+            assert_eq!(
+                lc_event_rx.next().await.expect("Expected command event."),
+                LifecycleEvent::Lane(Text::new(VAL_LANE))
+            );
+
+            // The outgoing event is tagged with the correlation ID of the command...
+            receiver.expect_correlation(Some(12)).await;
+            receiver.expect_event(56).await;
+
+            sender.command(57).await;
+
+            assert!(matches!(
+                test_event_rx.next().await.expect("Expected command event."),
+                TestEvent::Value { body: 57 }
+            ));
+            assert_eq!(
+                lc_event_rx.next().await.expect("Expected command event."),
+                LifecycleEvent::Lane(Text::new(VAL_LANE))
+            );
+
+            //... and the tag is cleared for an uncorrelated command.
+            receiver.expect_correlation(None).await;
+            receiver.expect_event(57).await;
+
+            drop(sender);
+            drop(map_lane_io);
+            drop(cmd_lane_io);
+            drop(http_lane_tx);
+            (test_event_rx, lc_event_rx)
+        };
+
+        let (result, (test_event_rx, lc_event_rx)) = join(task, test_case).await;
+        assert!(result.is_ok());
+
+        let events = lc_event_rx.collect::<Vec<_>>().await;
+
+        assert!(matches!(
+            events.as_slice(),
+            [LifecycleEvent::Stop(StopReason::ExternalStop)]
+        ));
+
+        let lane_events = test_event_rx.collect::<Vec<_>>().await;
+        assert!(lane_events.is_empty());
+    })
+    .await
+}
+
+#[tokio::test]
+async fn originated_command_to_value_lane() {
+    with_timeout(async {
+        let context = Box::<TestAgentContext>::default();
+        let (
+            task,
+            TestContext {
+                mut test_event_rx,
+                http_request_rx: _http_request_rx,
+                mut lc_event_rx,
+                val_lane_io,
+                map_lane_io,
+                cmd_lane_io,
+                http_lane_tx,
+            },
+        ) = init_agent(context).await;
+
+        let test_case = async move {
+            assert_eq!(
+                lc_event_rx.next().await.expect("Expected init event."),
+                LifecycleEvent::Init
+            );
+            assert_eq!(
+                lc_event_rx.next().await.expect("Expected start event."),
+                LifecycleEvent::Start
+            );
+            let (mut sender, mut receiver) = val_lane_io;
+
+            let origin = Uuid::from_u128(8573);
+            sender.command_from(origin, 56).await;
+
+            assert!(matches!(
+                test_event_rx.next().await.expect("Expected command event."),
+                TestEvent::Value { body: 56 }
+            ));
+            assert_eq!(
+                lc_event_rx.next().await.expect("Expected command event."),
+                LifecycleEvent::Lane(Text::new(VAL_LANE))
+            );
+
+            // The outgoing event is tagged with the origin of the command...
+            receiver.expect_origin(Some(origin)).await;
+            receiver.expect_event(56).await;
+
+            sender.command(57).await;
+
+            assert!(matches!(
+                test_event_rx.next().await.expect("Expected command event."),
+                TestEvent::Value { body: 57 }
+            ));
+            assert_eq!(
+                lc_event_rx.next().await.expect("Expected command event."),
+                LifecycleEvent::Lane(Text::new(VAL_LANE))
+            );
+
+            //... and the tag is cleared for a command with no known origin.
+            receiver.expect_origin(None).await;
+            receiver.expect_event(57).await;
+
+            drop(sender);
+            drop(map_lane_io);
+            drop(cmd_lane_io);
+            drop(http_lane_tx);
+            (test_event_rx, lc_event_rx)
+        };
+
+        let (result, (test_event_rx, lc_event_rx)) = join(task, test_case).await;
+        assert!(result.is_ok());
+
+        let events = lc_event_rx.collect::<Vec<_>>().await;
+
+        assert!(matches!(
+            events.as_slice(),
+            [LifecycleEvent::Stop(StopReason::ExternalStop)]
+        ));
 
         let lane_events = test_event_rx.collect::<Vec<_>>().await;
         assert!(lane_events.is_empty());
@@ -419,7 +890,10 @@ async fn request_to_http_lane() {
         let events = lc_event_rx.collect::<Vec<_>>().await;
 
         //Check that the `on_stop` event fired.
-        assert!(matches!(events.as_slice(), [LifecycleEvent::Stop]));
+        assert!(matches!(
+            events.as_slice(),
+            [LifecycleEvent::Stop(StopReason::ExternalStop)]
+        ));
 
         let lane_events = test_event_rx.collect::<Vec<_>>().await;
         assert!(lane_events.is_empty());
@@ -481,7 +955,10 @@ async fn sync_with_lane() {
         let events = lc_event_rx.collect::<Vec<_>>().await;
 
         //Check that the `on_stop` event fired.
-        assert!(matches!(events.as_slice(), [LifecycleEvent::Stop]));
+        assert!(matches!(
+            events.as_slice(),
+            [LifecycleEvent::Stop(StopReason::ExternalStop)]
+        ));
 
         let lane_events = test_event_rx.collect::<Vec<_>>().await;
         assert!(lane_events.is_empty());
@@ -526,7 +1003,8 @@ async fn command_to_map_lane() {
                     body: MapMessage::Update {
                         key: 83,
                         value: 9282
-                    }
+                    },
+                    origin: None,
                 }
             ));
 
@@ -557,7 +1035,92 @@ async fn command_to_map_lane() {
         let events = lc_event_rx.collect::<Vec<_>>().await;
 
         //Check that the `on_stop` event fired.
-        assert!(matches!(events.as_slice(), [LifecycleEvent::Stop]));
+        assert!(matches!(
+            events.as_slice(),
+            [LifecycleEvent::Stop(StopReason::ExternalStop)]
+        ));
+
+        let lane_events = test_event_rx.collect::<Vec<_>>().await;
+        assert!(lane_events.is_empty());
+    })
+    .await
+}
+
+#[tokio::test]
+async fn command_with_origin_to_map_lane() {
+    with_timeout(async {
+        let context = Box::<TestAgentContext>::default();
+        let (
+            task,
+            TestContext {
+                mut test_event_rx,
+                http_request_rx: _http_request_rx,
+                mut lc_event_rx,
+                val_lane_io,
+                map_lane_io,
+                cmd_lane_io,
+                http_lane_tx,
+            },
+        ) = init_agent(context).await;
+
+        let test_case = async move {
+            assert_eq!(
+                lc_event_rx.next().await.expect("Expected init event."),
+                LifecycleEvent::Init
+            );
+            assert_eq!(
+                lc_event_rx.next().await.expect("Expected start event."),
+                LifecycleEvent::Start
+            );
+            let (mut sender, mut receiver) = map_lane_io;
+
+            let origin = Uuid::from_u128(77);
+            sender.command_from(origin, 83, 9282).await;
+
+            // The agent should receive the command, along with its origin...
+            assert!(matches!(
+                test_event_rx.next().await.expect("Expected command event."),
+                TestEvent::Map {
+                    body: MapMessage::Update {
+                        key: 83,
+                        value: 9282
+                    },
+                    origin: Some(id),
+                } if id == origin
+            ));
+
+            //... ,trigger the `on_command` event...
+            assert_eq!(
+                lc_event_rx.next().await.expect("Expected command event."),
+                LifecycleEvent::Lane(Text::new(MAP_LANE))
+            );
+
+            //... and then generate an outgoing event, tagged with the origin.
+            receiver.expect_origin(Some(origin)).await;
+            receiver
+                .expect_event(MapOperation::Update {
+                    key: 83,
+                    value: 9282,
+                })
+                .await;
+
+            drop(sender);
+            drop(val_lane_io);
+            drop(cmd_lane_io);
+            drop(http_lane_tx);
+            (test_event_rx, lc_event_rx)
+        };
+
+        let (result, (test_event_rx, lc_event_rx)) = join(task, test_case).await;
+        assert!(result.is_ok());
+
+        let events = lc_event_rx.collect::<Vec<_>>().await;
+
+        //Check that the `on_stop` event fired.
+        assert!(matches!(
+            events.as_slice(),
+            [LifecycleEvent::Stop(StopReason::ExternalStop)]
+        ));
 
         let lane_events = test_event_rx.collect::<Vec<_>>().await;
         assert!(lane_events.is_empty());
@@ -641,7 +1204,10 @@ async fn suspend_future() {
         let events = lc_event_rx.collect::<Vec<_>>().await;
 
         //Check that the `on_stop` event fired.
-        assert!(matches!(events.as_slice(), [LifecycleEvent::Stop]));
+        assert!(matches!(
+            events.as_slice(),
+            [LifecycleEvent::Stop(StopReason::ExternalStop)]
+        ));
 
         let lane_events = test_event_rx.collect::<Vec<_>>().await;
         assert!(lane_events.is_empty());
@@ -733,7 +1299,10 @@ async fn trigger_ad_hoc_command() {
     let events = lc_event_rx.collect::<Vec<_>>().await;
 
     //Check that the `on_stop` event fired.
-    assert!(matches!(events.as_slice(), [LifecycleEvent::Stop]));
+    assert!(matches!(
+        events.as_slice(),
+        [LifecycleEvent::Stop(StopReason::ExternalStop)]
+    ));
 
     let lane_events = test_event_rx.collect::<Vec<_>>().await;
     assert!(lane_events.is_empty());
@@ -1007,3 +1576,54 @@ async fn hosted_downlink_in_out_interleaved() {
 }
 
 const TEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[test]
+fn item_manifest_includes_persistent_items() {
+    let mut specs = HashMap::new();
+    specs.insert(
+        "value",
+        ItemSpec::new(
+            0,
+            "value",
+            ItemDescriptor::WarpLane {
+                kind: WarpLaneKind::Value,
+                flags: ItemFlags::empty(),
+            },
+        )
+        .with_previous_names(&["old_value"]),
+    );
+    specs.insert(
+        "transient",
+        ItemSpec::new(
+            1,
+            "transient",
+            ItemDescriptor::WarpLane {
+                kind: WarpLaneKind::Map,
+                flags: ItemFlags::TRANSIENT,
+            },
+        ),
+    );
+    specs.insert(
+        "store",
+        ItemSpec::new(
+            2,
+            "store",
+            ItemDescriptor::Store {
+                kind: StoreKind::Map,
+                flags: ItemFlags::empty(),
+            },
+        ),
+    );
+    specs.insert("http", ItemSpec::new(3, "http", ItemDescriptor::Http));
+
+    let mut expected = ItemManifest::default();
+    expected.add_item("value", ItemStateKind::Lane(WarpLaneKind::Value));
+    expected.add_item("store", ItemStateKind::Store(StoreKind::Map));
+    expected.add_rename("old_value", "value");
+    assert_eq!(item_manifest(&specs, false), expected);
+
+    //If all lanes are transient, only the stores are persistent.
+    let mut expected = ItemManifest::default();
+    expected.add_item("store", ItemStateKind::Store(StoreKind::Map));
+    assert_eq!(item_manifest(&specs, true), expected);
+}
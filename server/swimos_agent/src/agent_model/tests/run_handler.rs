@@ -24,10 +24,13 @@ use swimos_model::Text;
 use swimos_utilities::routing::RouteUri;
 
 use crate::{
-    agent_lifecycle::item_event::ItemEvent,
+    agent_lifecycle::item_event::{HLeaf, ItemEvent},
     agent_model::run_handler,
-    event_handler::{ActionContext, HandlerAction, Modification, StepResult},
+    event_handler::{
+        ActionContext, EventHandlerError, HandlerAction, Modification, ReentrancyPolicy, StepResult,
+    },
     meta::AgentMetadata,
+    stores::ValueStore,
     test_context::dummy_context,
 };
 
@@ -289,3 +292,101 @@ fn multi_hop_event_trigger() {
     assert_eq!(ids, expected_ids);
     agent.check_lanes(expected_lanes);
 }
+
+struct StoreAgent {
+    store: ValueStore<i32>,
+}
+
+impl Default for StoreAgent {
+    fn default() -> Self {
+        StoreAgent {
+            store: ValueStore::new(Lane::A.id(), 0),
+        }
+    }
+}
+
+/// Sets the value of the store from within a read of the same store.
+struct ReentrantSet;
+
+impl HandlerAction<StoreAgent> for ReentrantSet {
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<StoreAgent>,
+        _meta: AgentMetadata,
+        context: &StoreAgent,
+    ) -> StepResult<Self::Completion> {
+        let StoreAgent { store } = context;
+        store.read(|n| {
+            store.set(*n + 1);
+            store.set(*n + 2);
+        });
+        StepResult::Complete {
+            modified_item: Some(Modification::no_trigger(Lane::A.id())),
+            result: (),
+        }
+    }
+}
+
+fn run_reentrant_handler(
+    agent: &StoreAgent,
+    policy: ReentrancyPolicy,
+) -> Result<(), EventHandlerError> {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let mut lanes = HashMap::new();
+    lanes.insert(Lane::A.id(), Text::new(Lane::A.name()));
+
+    let mut collector = HashSet::new();
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let mut action_context =
+        dummy_context(&mut join_lane_init, &mut ad_hoc_buffer).with_reentrancy(policy);
+
+    run_handler(
+        &mut action_context,
+        meta,
+        agent,
+        &HLeaf,
+        ReentrantSet,
+        &lanes,
+        &mut collector,
+    )
+}
+
+#[test]
+fn diagnose_reentrant_write() {
+    let agent = StoreAgent::default();
+    let result = run_reentrant_handler(&agent, ReentrancyPolicy::Diagnose);
+
+    match result {
+        Err(EventHandlerError::ReentrantAccess { item_id, item_name }) => {
+            assert_eq!(item_id, Lane::A.id());
+            assert_eq!(item_name, Some(Text::new(Lane::A.name())));
+        }
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+    assert_eq!(agent.store.read(|n| *n), 0);
+}
+
+#[test]
+fn queue_reentrant_write() {
+    let agent = StoreAgent::default();
+    let result = run_reentrant_handler(&agent, ReentrancyPolicy::Queue);
+
+    assert!(result.is_ok());
+    assert_eq!(agent.store.read(|n| *n), 2);
+    assert_eq!(
+        agent.store.read_with_prev(|prev, n| (prev, *n)),
+        (Some(1), 2)
+    );
+}
+
+#[test]
+#[should_panic]
+fn reentrant_write_panics_by_default() {
+    let agent = StoreAgent::default();
+    let _ = run_reentrant_handler(&agent, ReentrancyPolicy::Panic);
+}
@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::num::NonZeroUsize;
+
+use swimos_utilities::non_zero_usize;
+
 /// Configuration parameters for hosted value and event downlinks.
 #[derive(Debug, Clone, Copy)]
 pub struct SimpleDownlinkConfig {
@@ -39,13 +43,38 @@ pub struct MapDownlinkConfig {
     pub events_when_not_synced: bool,
     /// If this is set, the downlink will stop if it enters the unlinked state (default: true).
     pub terminate_on_unlinked: bool,
+    /// Take and drop operations are decomposed into a sequence of removals, each of which will
+    /// trigger the `on_remove` event handler. This is the maximum number of removals that will be
+    /// handled at once, before yielding to allow the agent to handle other events (default: 256).
+    pub removal_budget: NonZeroUsize,
 }
 
+const DEFAULT_REMOVAL_BUDGET: NonZeroUsize = non_zero_usize!(256);
+
 impl Default for MapDownlinkConfig {
     fn default() -> Self {
         Self {
             events_when_not_synced: false,
             terminate_on_unlinked: true,
+            removal_budget: DEFAULT_REMOVAL_BUDGET,
+        }
+    }
+}
+
+const DEFAULT_COMMAND_QUEUE_SIZE: NonZeroUsize = non_zero_usize!(16);
+
+/// Configuration parameters for command downlinks.
+#[derive(Debug, Clone, Copy)]
+pub struct CommandDownlinkConfig {
+    /// The maximum number of commands that can be waiting to be sent. Attempting to send
+    /// further commands will fail until there is space in the queue (default: 16).
+    pub queue_size: NonZeroUsize,
+}
+
+impl Default for CommandDownlinkConfig {
+    fn default() -> Self {
+        Self {
+            queue_size: DEFAULT_COMMAND_QUEUE_SIZE,
         }
     }
 }
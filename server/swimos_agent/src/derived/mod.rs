@@ -0,0 +1,215 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::{
+    event_handler::{ActionContext, EventHandlerError, HandlerAction, StepResult},
+    item::EventCount,
+    meta::AgentMetadata,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// An exponentially weighted moving average where the weight of each sample decays with time.
+/// A sample that was observed one half-life ago contributes half as much to the average as a sample
+/// observed now, irrespective of how frequently samples are taken.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ewma {
+    half_life: Duration,
+    state: Option<(f64, Instant)>,
+}
+
+impl Ewma {
+    /// # Arguments
+    /// * `half_life` - The period over which the weight of a sample decays by half. If this is zero,
+    ///   the average will simply track the most recent sample.
+    pub fn new(half_life: Duration) -> Self {
+        Ewma {
+            half_life,
+            state: None,
+        }
+    }
+
+    /// The half-life of the average.
+    pub fn half_life(&self) -> Duration {
+        self.half_life
+    }
+
+    /// The current value of the average, if any samples have been observed.
+    pub fn value(&self) -> Option<f64> {
+        self.state.map(|(value, _)| value)
+    }
+
+    /// Add a new sample to the average, returning the updated value. The first sample initializes
+    /// the average.
+    ///
+    /// # Arguments
+    /// * `sample` - The value of the sample.
+    /// * `now` - The time at which the sample was observed.
+    pub fn update(&mut self, sample: f64, now: Instant) -> f64 {
+        let Ewma { half_life, state } = self;
+        let value = match *state {
+            Some((prev, last)) if !half_life.is_zero() => {
+                let elapsed = now.saturating_duration_since(last).as_secs_f64();
+                let decay = 0.5f64.powf(elapsed / half_life.as_secs_f64());
+                sample + (prev - sample) * decay
+            }
+            _ => sample,
+        };
+        *state = Some((value, now));
+        value
+    }
+}
+
+/// Tracks the rate (in events per second) at which a monotonically increasing event count grows,
+/// smoothed by an [`Ewma`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateMeter {
+    average: Ewma,
+    last: Option<(u64, Instant)>,
+}
+
+impl RateMeter {
+    /// # Arguments
+    /// * `half_life` - The half-life of the moving average applied to the rate.
+    pub fn new(half_life: Duration) -> Self {
+        RateMeter {
+            average: Ewma::new(half_life),
+            last: None,
+        }
+    }
+
+    /// The current (smoothed) rate, if it has been possible to compute one.
+    pub fn rate(&self) -> Option<f64> {
+        self.average.value()
+    }
+
+    /// Observe the value of the event count. The first observation only establishes a baseline so
+    /// no rate is produced until the count has been observed at least twice.
+    ///
+    /// # Arguments
+    /// * `count` - The total number of events that have occurred.
+    /// * `now` - The time at which the count was observed.
+    pub fn update(&mut self, count: u64, now: Instant) -> Option<f64> {
+        let RateMeter { average, last } = self;
+        match *last {
+            Some((prev_count, prev_time)) => {
+                let elapsed = now.saturating_duration_since(prev_time).as_secs_f64();
+                if elapsed > 0.0 {
+                    *last = Some((count, now));
+                    let instantaneous = count.saturating_sub(prev_count) as f64 / elapsed;
+                    Some(average.update(instantaneous, now))
+                } else {
+                    average.value()
+                }
+            }
+            _ => {
+                *last = Some((count, now));
+                None
+            }
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that samples the event count of an item
+/// and feeds it to a shared [`RateMeter`], completing with the updated rate.
+pub struct SampleRate<C, Item> {
+    projection: fn(&C) -> &Item,
+    meter: Option<Arc<Mutex<RateMeter>>>,
+}
+
+impl<C, Item> SampleRate<C, Item> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the item.
+    /// * `meter` - The meter tracking the rate for the item.
+    pub fn new(projection: fn(&C) -> &Item, meter: Arc<Mutex<RateMeter>>) -> Self {
+        SampleRate {
+            projection,
+            meter: Some(meter),
+        }
+    }
+}
+
+impl<C, Item: EventCount> HandlerAction<C> for SampleRate<C, Item> {
+    type Completion = Option<f64>;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let SampleRate { projection, meter } = self;
+        if let Some(meter) = meter.take() {
+            let count = projection(context).event_count();
+            let mut guard = meter.lock().expect("Rate meter poisoned.");
+            StepResult::done(guard.update(count, Instant::now()))
+        } else {
+            StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that completes with whether the event
+/// count of an item has changed since it was last observed. The last observed count is shared
+/// between instances of the handler so that it can be run repeatedly. The first observation always
+/// counts as a change.
+pub struct HasChanged<C, Item> {
+    projection: fn(&C) -> &Item,
+    last_count: Option<Arc<Mutex<Option<u64>>>>,
+}
+
+impl<C, Item> HasChanged<C, Item> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the item.
+    /// * `last_count` - The event count of the item when it was last observed.
+    pub fn new(projection: fn(&C) -> &Item, last_count: Arc<Mutex<Option<u64>>>) -> Self {
+        HasChanged {
+            projection,
+            last_count: Some(last_count),
+        }
+    }
+}
+
+impl<C, Item: EventCount> HandlerAction<C> for HasChanged<C, Item> {
+    type Completion = bool;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let HasChanged {
+            projection,
+            last_count,
+        } = self;
+        if let Some(last_count) = last_count.take() {
+            let count = projection(context).event_count();
+            let mut guard = last_count.lock().expect("Event count poisoned.");
+            let changed = *guard != Some(count);
+            *guard = Some(count);
+            StepResult::done(changed)
+        } else {
+            StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+        }
+    }
+}
@@ -0,0 +1,275 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bytes::BytesMut;
+use swimos_api::agent::AgentConfig;
+use swimos_utilities::routing::RouteUri;
+use tokio::time::Instant;
+
+use crate::{
+    event_handler::{EventHandlerError, HandlerAction, StepResult},
+    item::EventCount,
+    lanes::{CommandLane, MapLane, ValueLane},
+    meta::AgentMetadata,
+    stores::{MapStore, ValueStore},
+    test_context::dummy_context,
+};
+
+use super::{Ewma, HasChanged, RateMeter, SampleRate};
+
+const HALF_LIFE: Duration = Duration::from_secs(10);
+const EPSILON: f64 = 1e-9;
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < EPSILON,
+        "{} is not close to {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn ewma_first_sample_initializes() {
+    let mut ewma = Ewma::new(HALF_LIFE);
+    assert!(ewma.value().is_none());
+
+    let now = Instant::now();
+    assert_close(ewma.update(4.0, now), 4.0);
+    assert_eq!(ewma.value(), Some(4.0));
+}
+
+#[test]
+fn ewma_decays_by_half_life() {
+    let mut ewma = Ewma::new(HALF_LIFE);
+    let start = Instant::now();
+    ewma.update(0.0, start);
+
+    let value = ewma.update(8.0, start + HALF_LIFE);
+    assert_close(value, 4.0);
+
+    let value = ewma.update(8.0, start + 2 * HALF_LIFE);
+    assert_close(value, 6.0);
+}
+
+#[test]
+fn ewma_independent_of_sample_frequency() {
+    let start = Instant::now();
+
+    let mut coarse = Ewma::new(HALF_LIFE);
+    coarse.update(0.0, start);
+    let coarse_value = coarse.update(10.0, start + HALF_LIFE);
+
+    let mut fine = Ewma::new(HALF_LIFE);
+    fine.update(0.0, start);
+    let mut fine_value = 0.0;
+    for i in 1..=10 {
+        fine_value = fine.update(10.0, start + HALF_LIFE * i / 10);
+    }
+
+    assert_close(coarse_value, fine_value);
+}
+
+#[test]
+fn ewma_zero_half_life_tracks_latest() {
+    let mut ewma = Ewma::new(Duration::ZERO);
+    let start = Instant::now();
+    ewma.update(1.0, start);
+    assert_close(ewma.update(7.0, start + Duration::from_secs(1)), 7.0);
+}
+
+#[test]
+fn rate_meter_requires_baseline() {
+    let mut meter = RateMeter::new(HALF_LIFE);
+    let start = Instant::now();
+    assert!(meter.update(5, start).is_none());
+    assert!(meter.rate().is_none());
+
+    let rate = meter.update(25, start + Duration::from_secs(2));
+    assert_eq!(rate, Some(10.0));
+    assert_eq!(meter.rate(), Some(10.0));
+}
+
+#[test]
+fn rate_meter_smooths_rate() {
+    let mut meter = RateMeter::new(HALF_LIFE);
+    let start = Instant::now();
+    meter.update(0, start);
+    meter.update(100, start + HALF_LIFE);
+
+    let rate = meter
+        .update(100, start + 2 * HALF_LIFE)
+        .expect("Expected a rate.");
+    assert_close(rate, 5.0);
+}
+
+#[test]
+fn rate_meter_ignores_zero_interval() {
+    let mut meter = RateMeter::new(HALF_LIFE);
+    let start = Instant::now();
+    meter.update(0, start);
+    meter.update(10, start + Duration::from_secs(1));
+
+    assert_eq!(meter.update(20, start + Duration::from_secs(1)), Some(10.0));
+    assert_eq!(meter.update(20, start + Duration::from_secs(2)), Some(10.0));
+}
+
+#[test]
+fn items_count_events() {
+    let value_lane = ValueLane::new(0, 0);
+    value_lane.set(1);
+    value_lane.replace(|n| n + 1);
+    assert_eq!(value_lane.event_count(), 2);
+
+    let value_store = ValueStore::new(1, 0);
+    value_store.init(5);
+    value_store.set(1);
+    assert_eq!(value_store.event_count(), 1);
+
+    let map_lane = MapLane::new(2, HashMap::new());
+    map_lane.update(1, 1);
+    map_lane.update(2, 2);
+    map_lane.remove(&1);
+    map_lane.remove(&3);
+    map_lane.clear();
+    assert_eq!(map_lane.event_count(), 4);
+
+    let map_store = MapStore::new(3, HashMap::new());
+    map_store.update(1, 1);
+    map_store.transform_entry(1, |_| None);
+    map_store.transform_entry(1, |_| None);
+    assert_eq!(map_store.event_count(), 2);
+
+    let command_lane = CommandLane::new(4);
+    command_lane.command(1);
+    command_lane.command(2);
+    assert_eq!(command_lane.event_count(), 2);
+}
+
+struct TestAgent {
+    lane: ValueLane<i32>,
+}
+
+impl TestAgent {
+    const LANE: fn(&TestAgent) -> &ValueLane<i32> = |agent| &agent.lane;
+}
+
+const CONFIG: AgentConfig = AgentConfig::DEFAULT;
+const NODE_URI: &str = "/node";
+
+#[tokio::test(start_paused = true)]
+async fn sample_rate_handler() {
+    let uri = RouteUri::try_from(NODE_URI).expect("Bad URI.");
+    let route_params = HashMap::new();
+    let meta = AgentMetadata::new(&uri, &route_params, &CONFIG);
+    let agent = TestAgent {
+        lane: ValueLane::new(0, 0),
+    };
+    let meter = Arc::new(Mutex::new(RateMeter::new(HALF_LIFE)));
+
+    let mut handler = SampleRate::new(TestAgent::LANE, meter.clone());
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Complete {
+            modified_item: None,
+            result: None
+        }
+    ));
+
+    for i in 0..6 {
+        agent.lane.set(i);
+    }
+    tokio::time::advance(Duration::from_secs(3)).await;
+
+    let mut handler = SampleRate::new(TestAgent::LANE, meter.clone());
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    match result {
+        StepResult::Complete {
+            modified_item: None,
+            result: Some(rate),
+        } => assert_close(rate, 2.0),
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+    ));
+}
+
+fn run_has_changed(agent: &TestAgent, last_count: &Arc<Mutex<Option<u64>>>) -> bool {
+    let uri = RouteUri::try_from(NODE_URI).expect("Bad URI.");
+    let route_params = HashMap::new();
+    let meta = AgentMetadata::new(&uri, &route_params, &CONFIG);
+
+    let mut handler = HasChanged::new(TestAgent::LANE, last_count.clone());
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        agent,
+    );
+    let changed = match result {
+        StepResult::Complete {
+            modified_item: None,
+            result,
+        } => result,
+        ow => panic!("Unexpected result: {:?}", ow),
+    };
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        agent,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+    ));
+    changed
+}
+
+#[test]
+fn has_changed_handler() {
+    let agent = TestAgent {
+        lane: ValueLane::new(0, 0),
+    };
+    let last_count = Arc::new(Mutex::new(None));
+
+    assert!(run_has_changed(&agent, &last_count));
+    assert!(!run_has_changed(&agent, &last_count));
+
+    agent.lane.set(1);
+    assert!(run_has_changed(&agent, &last_count));
+    assert!(!run_has_changed(&agent, &last_count));
+}
@@ -18,6 +18,7 @@ mod on_failed;
 mod on_linked;
 mod on_synced;
 mod on_unlinked;
+mod stream;
 mod value;
 
 pub use event::*;
@@ -26,4 +27,5 @@ pub use on_failed::*;
 pub use on_linked::*;
 pub use on_synced::*;
 pub use on_unlinked::*;
+pub use stream::*;
 pub use value::*;
@@ -0,0 +1,413 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+
+use futures::Stream;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::{
+    event_handler::{ActionContext, HandlerAction, StepResult},
+    meta::AgentMetadata,
+};
+
+use super::{
+    OnConsumeEvent, OnDownlinkClear, OnDownlinkEvent, OnDownlinkRemove, OnDownlinkSet,
+    OnDownlinkUpdate, OnFailed, OnLinked, OnSynced, OnUnlinked,
+};
+
+/// The events of a value downlink, hosted by an agent, as delivered by a [`DownlinkEventStream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueDownlinkEvent<T> {
+    Linked,
+    Synced(T),
+    /// The value of the downlink has changed.
+    Set {
+        previous: Option<T>,
+        value: T,
+    },
+    Unlinked,
+    Failed,
+}
+
+/// The events of a map downlink, hosted by an agent, as delivered by a [`DownlinkEventStream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapDownlinkEvent<K: Eq + std::hash::Hash, V> {
+    Linked,
+    Synced(HashMap<K, V>),
+    Updated {
+        key: K,
+        previous: Option<V>,
+        value: V,
+    },
+    Removed {
+        key: K,
+        value: V,
+    },
+    Cleared(HashMap<K, V>),
+    Unlinked,
+    Failed,
+}
+
+/// The events of an event downlink, hosted by an agent, as delivered by a [`DownlinkEventStream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventDownlinkEvent<T> {
+    Linked,
+    Synced,
+    Event(T),
+    Unlinked,
+    Failed,
+}
+
+/// The events that are common to all kinds of downlink.
+pub trait DownlinkStreamEvent: Send + 'static {
+    fn linked() -> Self;
+
+    fn unlinked() -> Self;
+
+    fn failed() -> Self;
+}
+
+impl<T: Send + 'static> DownlinkStreamEvent for ValueDownlinkEvent<T> {
+    fn linked() -> Self {
+        ValueDownlinkEvent::Linked
+    }
+
+    fn unlinked() -> Self {
+        ValueDownlinkEvent::Unlinked
+    }
+
+    fn failed() -> Self {
+        ValueDownlinkEvent::Failed
+    }
+}
+
+impl<K, V> DownlinkStreamEvent for MapDownlinkEvent<K, V>
+where
+    K: Eq + std::hash::Hash + Send + 'static,
+    V: Send + 'static,
+{
+    fn linked() -> Self {
+        MapDownlinkEvent::Linked
+    }
+
+    fn unlinked() -> Self {
+        MapDownlinkEvent::Unlinked
+    }
+
+    fn failed() -> Self {
+        MapDownlinkEvent::Failed
+    }
+}
+
+impl<T: Send + 'static> DownlinkStreamEvent for EventDownlinkEvent<T> {
+    fn linked() -> Self {
+        EventDownlinkEvent::Linked
+    }
+
+    fn unlinked() -> Self {
+        EventDownlinkEvent::Unlinked
+    }
+
+    fn failed() -> Self {
+        EventDownlinkEvent::Failed
+    }
+}
+
+/// Create a downlink lifecycle that forwards the events of a downlink, hosted by an agent, to a
+/// stream rather than handling them with event handlers.
+///
+/// The agent task cannot wait for the consumer of the stream so, if the buffer is full when an
+/// event occurs, the event is discarded. The number of discarded events can be observed with
+/// [`DownlinkEventStream::dropped`]. If the stream is dropped, any subsequent events are also
+/// discarded.
+///
+/// # Arguments
+/// * `buffer_size` - The number of events that can be buffered before events are discarded.
+pub fn downlink_event_stream<E>(
+    buffer_size: NonZeroUsize,
+) -> (DownlinkEventSender<E>, DownlinkEventStream<E>) {
+    let (tx, rx) = mpsc::channel(buffer_size.get());
+    let dropped = Arc::new(AtomicU64::new(0));
+    (
+        DownlinkEventSender {
+            tx,
+            dropped: dropped.clone(),
+        },
+        DownlinkEventStream { rx, dropped },
+    )
+}
+
+/// A downlink lifecycle that forwards the events of the downlink to a [`DownlinkEventStream`].
+/// See [`downlink_event_stream`].
+#[derive(Debug)]
+pub struct DownlinkEventSender<E> {
+    tx: mpsc::Sender<E>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl<E> DownlinkEventSender<E> {
+    fn forward(&self, event: E) -> ForwardEvent<E> {
+        ForwardEvent {
+            event: Some(event),
+            tx: self.tx.clone(),
+            dropped: self.dropped.clone(),
+        }
+    }
+}
+
+/// A stream of the events of a downlink that is hosted by an agent. See [`downlink_event_stream`].
+#[derive(Debug)]
+pub struct DownlinkEventStream<E> {
+    rx: mpsc::Receiver<E>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl<E> DownlinkEventStream<E> {
+    /// The number of events that have been discarded as the buffer of the stream was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<E> Stream for DownlinkEventStream<E> {
+    type Item = E;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().rx.poll_recv(cx)
+    }
+}
+
+/// An event handler that forwards a single event to a [`DownlinkEventStream`].
+pub struct ForwardEvent<E> {
+    event: Option<E>,
+    tx: mpsc::Sender<E>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl<E, Context> HandlerAction<Context> for ForwardEvent<E> {
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<Context>,
+        _meta: AgentMetadata,
+        _context: &Context,
+    ) -> StepResult<Self::Completion> {
+        let ForwardEvent { event, tx, dropped } = self;
+        if let Some(event) = event.take() {
+            match tx.try_send(event) {
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    warn!(
+                        "The buffer of a downlink event stream is full. The event was discarded."
+                    );
+                    dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                // If the stream has been dropped there is nobody to observe the event.
+                Err(mpsc::error::TrySendError::Closed(_)) | Ok(_) => {}
+            }
+            StepResult::done(())
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+impl<E, Context> OnLinked<Context> for DownlinkEventSender<E>
+where
+    E: DownlinkStreamEvent,
+{
+    type OnLinkedHandler<'a> = ForwardEvent<E>
+    where
+        Self: 'a;
+
+    fn on_linked(&self) -> Self::OnLinkedHandler<'_> {
+        self.forward(E::linked())
+    }
+}
+
+impl<E, Context> OnUnlinked<Context> for DownlinkEventSender<E>
+where
+    E: DownlinkStreamEvent,
+{
+    type OnUnlinkedHandler<'a> = ForwardEvent<E>
+    where
+        Self: 'a;
+
+    fn on_unlinked(&self) -> Self::OnUnlinkedHandler<'_> {
+        self.forward(E::unlinked())
+    }
+}
+
+impl<E, Context> OnFailed<Context> for DownlinkEventSender<E>
+where
+    E: DownlinkStreamEvent,
+{
+    type OnFailedHandler<'a> = ForwardEvent<E>
+    where
+        Self: 'a;
+
+    fn on_failed(&self) -> Self::OnFailedHandler<'_> {
+        self.forward(E::failed())
+    }
+}
+
+impl<T, Context> OnSynced<T, Context> for DownlinkEventSender<ValueDownlinkEvent<T>>
+where
+    T: Clone + Send + 'static,
+{
+    type OnSyncedHandler<'a> = ForwardEvent<ValueDownlinkEvent<T>>
+    where
+        Self: 'a;
+
+    fn on_synced<'a>(&'a self, value: &T) -> Self::OnSyncedHandler<'a> {
+        self.forward(ValueDownlinkEvent::Synced(value.clone()))
+    }
+}
+
+impl<T, Context> OnDownlinkEvent<T, Context> for DownlinkEventSender<ValueDownlinkEvent<T>>
+where
+    T: Send + 'static,
+{
+    type OnEventHandler<'a> = crate::event_handler::UnitHandler
+    where
+        Self: 'a;
+
+    // Changes to the value are reported by `on_set`, which also provides the previous value.
+    fn on_event(&self, _value: &T) -> Self::OnEventHandler<'_> {
+        Default::default()
+    }
+}
+
+impl<T, Context> OnDownlinkSet<T, Context> for DownlinkEventSender<ValueDownlinkEvent<T>>
+where
+    T: Clone + Send + 'static,
+{
+    type OnSetHandler<'a> = ForwardEvent<ValueDownlinkEvent<T>>
+    where
+        Self: 'a;
+
+    fn on_set<'a>(&'a self, previous: Option<T>, new_value: &T) -> Self::OnSetHandler<'a> {
+        self.forward(ValueDownlinkEvent::Set {
+            previous,
+            value: new_value.clone(),
+        })
+    }
+}
+
+impl<T, Context> OnSynced<(), Context> for DownlinkEventSender<EventDownlinkEvent<T>>
+where
+    T: Send + 'static,
+{
+    type OnSyncedHandler<'a> = ForwardEvent<EventDownlinkEvent<T>>
+    where
+        Self: 'a;
+
+    fn on_synced<'a>(&'a self, _value: &()) -> Self::OnSyncedHandler<'a> {
+        self.forward(EventDownlinkEvent::Synced)
+    }
+}
+
+impl<T, Context> OnConsumeEvent<T, Context> for DownlinkEventSender<EventDownlinkEvent<T>>
+where
+    T: Send + 'static,
+{
+    type OnEventHandler<'a> = ForwardEvent<EventDownlinkEvent<T>>
+    where
+        Self: 'a;
+
+    fn on_event(&self, value: T) -> Self::OnEventHandler<'_> {
+        self.forward(EventDownlinkEvent::Event(value))
+    }
+}
+
+impl<K, V, Context> OnSynced<HashMap<K, V>, Context> for DownlinkEventSender<MapDownlinkEvent<K, V>>
+where
+    K: Eq + std::hash::Hash + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+{
+    type OnSyncedHandler<'a> = ForwardEvent<MapDownlinkEvent<K, V>>
+    where
+        Self: 'a;
+
+    fn on_synced<'a>(&'a self, value: &HashMap<K, V>) -> Self::OnSyncedHandler<'a> {
+        self.forward(MapDownlinkEvent::Synced(value.clone()))
+    }
+}
+
+impl<K, V, Context> OnDownlinkUpdate<K, V, Context> for DownlinkEventSender<MapDownlinkEvent<K, V>>
+where
+    K: Eq + std::hash::Hash + Send + 'static,
+    V: Clone + Send + 'static,
+{
+    type OnUpdateHandler<'a> = ForwardEvent<MapDownlinkEvent<K, V>>
+    where
+        Self: 'a;
+
+    fn on_update<'a>(
+        &'a self,
+        key: K,
+        _map: &HashMap<K, V>,
+        previous: Option<V>,
+        new_value: &V,
+    ) -> Self::OnUpdateHandler<'a> {
+        self.forward(MapDownlinkEvent::Updated {
+            key,
+            previous,
+            value: new_value.clone(),
+        })
+    }
+}
+
+impl<K, V, Context> OnDownlinkRemove<K, V, Context> for DownlinkEventSender<MapDownlinkEvent<K, V>>
+where
+    K: Eq + std::hash::Hash + Send + 'static,
+    V: Send + 'static,
+{
+    type OnRemoveHandler<'a> = ForwardEvent<MapDownlinkEvent<K, V>>
+    where
+        Self: 'a;
+
+    fn on_remove<'a>(
+        &'a self,
+        key: K,
+        _map: &HashMap<K, V>,
+        removed: V,
+    ) -> Self::OnRemoveHandler<'a> {
+        self.forward(MapDownlinkEvent::Removed {
+            key,
+            value: removed,
+        })
+    }
+}
+
+impl<K, V, Context> OnDownlinkClear<K, V, Context> for DownlinkEventSender<MapDownlinkEvent<K, V>>
+where
+    K: Eq + std::hash::Hash + Send + 'static,
+    V: Send + 'static,
+{
+    type OnClearHandler<'a> = ForwardEvent<MapDownlinkEvent<K, V>>
+    where
+        Self: 'a;
+
+    fn on_clear(&self, map: HashMap<K, V>) -> Self::OnClearHandler<'_> {
+        self.forward(MapDownlinkEvent::Cleared(map))
+    }
+}
@@ -14,10 +14,12 @@
 
 use swimos_api::address::Address;
 use swimos_form::write::StructuralWritable;
+use thiserror::Error;
+use tokio::sync::oneshot;
 
 use crate::meta::AgentMetadata;
 
-use super::{ActionContext, HandlerAction, StepResult};
+use super::{ActionContext, EventHandler, HandlerAction, StepResult};
 
 #[cfg(test)]
 mod tests;
@@ -46,6 +48,59 @@ impl<S, T> SendCommand<S, T> {
     }
 }
 
+/// Error type for the acknowledgement of a command that was sent with [`SendCommandWithAck`].
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandAckError {
+    /// The agent stopped before the command was passed to the runtime.
+    #[error("The command was not passed to the runtime before the agent stopped.")]
+    Failed,
+    /// The command was sent but acknowledgements cannot be provided in the context in which the
+    /// handler was executed.
+    #[error("Command acknowledgements are not supported in this context.")]
+    NotSupported,
+}
+
+/// Trait for contexts that track the commands, written into the ad hoc command buffer of an agent,
+/// for which an acknowledgement was requested.
+pub trait CommandAckTracker {
+    /// Register an acknowledgement for the command that was most recently written into the buffer.
+    /// The sender will be completed when the contents of the buffer have been passed to the runtime
+    /// (or dropped if that fails).
+    ///
+    /// # Arguments
+    /// * `ack` - The sender to complete when the command has been passed to the runtime.
+    fn track(&self, ack: oneshot::Sender<()>);
+}
+
+///  An [event handler](crate::event_handler::EventHandler) that will send a command to a remote lane
+/// and run another handler when the command has been passed to the runtime to be routed to the lane.
+/// Commands with acknowledgements are never overwritten by subsequent commands to the same lane.
+///
+/// The acknowledgement only indicates that the command has left the agent. The WARP protocol has no
+/// response to commands so it cannot indicate that the command was received by the lane.
+pub struct SendCommandWithAck<S, T, F> {
+    body: Option<(Body<S, T>, F)>,
+}
+
+impl<S, T, F> SendCommandWithAck<S, T, F> {
+    /// # Arguments
+    /// * `address` - The address of the remote lane.
+    /// * `command` - The body of the command.
+    /// * `on_ack` - Creates an event handler to run when the command has been passed to the runtime
+    ///   (or failed to be).
+    pub fn new(address: Address<S>, command: T, on_ack: F) -> Self {
+        SendCommandWithAck {
+            body: Some((
+                Body {
+                    address,
+                    value: command,
+                },
+                on_ack,
+            )),
+        }
+    }
+}
+
 struct Body<S, T> {
     address: Address<S>,
     value: T,
@@ -76,3 +131,29 @@ where
         }
     }
 }
+
+impl<Context, S, T, F, H> HandlerAction<Context> for SendCommandWithAck<S, T, F>
+where
+    Context: 'static,
+    S: AsRef<str>,
+    T: StructuralWritable,
+    F: FnOnce(Result<(), CommandAckError>) -> H + Send + 'static,
+    H: EventHandler<Context> + Send + 'static,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<Context>,
+        _meta: AgentMetadata,
+        _context: &Context,
+    ) -> StepResult<Self::Completion> {
+        let SendCommandWithAck { body } = self;
+        if let Some((Body { address, value }, on_ack)) = body.take() {
+            action_context.send_command_with_ack(address, value, on_ack);
+            StepResult::done(())
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
@@ -12,22 +12,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 use bytes::BytesMut;
+use futures::stream::FuturesUnordered;
 use swimos_agent_protocol::encoding::ad_hoc::AdHocCommandDecoder;
 use swimos_agent_protocol::AdHocCommand;
 use swimos_api::{address::Address, agent::AgentConfig};
 use swimos_utilities::{encoding::BytesStr, routing::RouteUri};
+use tokio::sync::oneshot;
 use tokio_util::codec::Decoder;
 
 use crate::{
-    event_handler::{EventHandlerError, HandlerAction, StepResult},
+    event_handler::{
+        ActionContext, EventHandlerError, HandlerAction, HandlerFuture, SideEffect, StepResult,
+    },
     meta::AgentMetadata,
-    test_context::dummy_context,
+    test_context::{dummy_context, no_downlink, DummyAgentContext},
 };
 
-use super::SendCommand;
+use super::{CommandAckError, CommandAckTracker, SendCommand, SendCommandWithAck};
 
 const HOST: &str = "localhost:8080";
 const NODE: &str = "/node";
@@ -88,3 +94,132 @@ fn decode_message(buffer: &mut BytesMut) -> AdHocCommand<BytesStr, i32> {
     assert!(buffer.is_empty());
     cmd
 }
+
+#[derive(Default)]
+struct Acks(RefCell<Vec<oneshot::Sender<()>>>);
+
+impl CommandAckTracker for Acks {
+    fn track(&self, ack: oneshot::Sender<()>) {
+        self.0.borrow_mut().push(ack);
+    }
+}
+
+type Outcome = Arc<Mutex<Option<Result<(), CommandAckError>>>>;
+type RecordOutcome = SideEffect<Box<dyn FnOnce() + Send>>;
+
+fn record(outcome: &Outcome) -> impl FnOnce(Result<(), CommandAckError>) -> RecordOutcome {
+    let outcome = outcome.clone();
+    move |result| {
+        let f: Box<dyn FnOnce() + Send> = Box::new(move || {
+            *outcome.lock().unwrap() = Some(result);
+        });
+        SideEffect::from(f)
+    }
+}
+
+// Run a handler that sends a command with an acknowledgement, returning the handler that was suspended.
+fn send_with_ack(
+    acks: Option<&Acks>,
+    ad_hoc_buffer: &mut BytesMut,
+    outcome: &Outcome,
+) -> HandlerFuture<FakeAgent> {
+    let mut join_lane_init = HashMap::new();
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let suspended: FuturesUnordered<HandlerFuture<FakeAgent>> = FuturesUnordered::new();
+
+    let address = Address::new(Some(HOST), NODE, LANE);
+    let mut handler = SendCommandWithAck::new(address, 23, record(outcome));
+    {
+        let mut action_context = ActionContext::new(
+            &suspended,
+            &DummyAgentContext,
+            &no_downlink,
+            &mut join_lane_init,
+            ad_hoc_buffer,
+        );
+        if let Some(acks) = acks {
+            action_context = action_context.with_command_acks(acks);
+        }
+        assert!(matches!(
+            handler.step(&mut action_context, meta, &FakeAgent),
+            StepResult::Complete {
+                modified_item: None,
+                ..
+            }
+        ));
+        let result = handler.step(&mut action_context, meta, &FakeAgent);
+        assert!(matches!(
+            result,
+            StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+        ));
+    }
+    let msg = decode_message(ad_hoc_buffer);
+    assert_eq!(msg, AdHocCommand::new(address, 23, false));
+
+    let mut suspended = suspended.into_iter();
+    let fut = suspended.next().expect("No handler suspended.");
+    assert!(suspended.next().is_none());
+    fut
+}
+
+// Run the handler that is produced when the acknowledgement completes.
+async fn run_ack_handler(fut: HandlerFuture<FakeAgent>) {
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+
+    let mut handler = fut.await;
+    let mut action_context = dummy_context(&mut join_lane_init, &mut ad_hoc_buffer);
+    assert!(matches!(
+        handler.step(&mut action_context, meta, &FakeAgent),
+        StepResult::Complete { .. }
+    ));
+}
+
+#[tokio::test]
+async fn command_acknowledged() {
+    let acks = Acks::default();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let outcome = Outcome::default();
+
+    let fut = send_with_ack(Some(&acks), &mut ad_hoc_buffer, &outcome);
+    let mut senders = acks.0.take();
+    assert_eq!(senders.len(), 1);
+    assert!(outcome.lock().unwrap().is_none());
+
+    senders.pop().unwrap().send(()).expect("Handler dropped.");
+    run_ack_handler(fut).await;
+    assert_eq!(*outcome.lock().unwrap(), Some(Ok(())));
+}
+
+#[tokio::test]
+async fn command_not_acknowledged() {
+    let acks = Acks::default();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let outcome = Outcome::default();
+
+    let fut = send_with_ack(Some(&acks), &mut ad_hoc_buffer, &outcome);
+    drop(acks);
+
+    run_ack_handler(fut).await;
+    assert_eq!(*outcome.lock().unwrap(), Some(Err(CommandAckError::Failed)));
+}
+
+#[tokio::test]
+async fn command_acks_not_supported() {
+    let mut ad_hoc_buffer = BytesMut::new();
+    let outcome = Outcome::default();
+
+    // The command is still sent when no acknowledgement can be provided.
+    let fut = send_with_ack(None, &mut ad_hoc_buffer, &outcome);
+
+    run_ack_handler(fut).await;
+    assert_eq!(
+        *outcome.lock().unwrap(),
+        Some(Err(CommandAckError::NotSupported))
+    );
+}
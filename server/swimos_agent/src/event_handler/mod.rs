@@ -23,25 +23,32 @@ use bytes::BytesMut;
 use frunk::{coproduct::CNil, Coproduct};
 use futures::FutureExt;
 use static_assertions::assert_obj_safe;
-use swimos_agent_protocol::{encoding::ad_hoc::AdHocCommandEncoder, AdHocCommand};
+use swimos_agent_protocol::{
+    encoding::ad_hoc::AdHocCommandEncoder, AdHocCommand, DecodePayload, PayloadDecodeError,
+};
 use swimos_api::{
     address::Address,
-    agent::{AgentContext, DownlinkKind},
+    agent::{AgentContext, DownlinkKind, LaneConfig, WarpLaneKind},
     error::{AgentRuntimeError, DownlinkRuntimeError},
+    limits::LimitExceeded,
 };
-use swimos_form::{read::RecognizerReadable, write::StructuralWritable};
+use swimos_form::write::StructuralWritable;
 use swimos_model::Text;
-use swimos_recon::parser::{AsyncParseError, RecognizerDecoder};
+use swimos_recon::parser::AsyncParseError;
 use swimos_utilities::{
     byte_channel::{ByteReader, ByteWriter},
+    future::RetryStrategy,
     never::Never,
     routing::RouteUri,
 };
 use thiserror::Error;
-use tokio_util::codec::{Decoder, Encoder};
+use tokio::sync::oneshot;
+use tokio_util::{codec::Encoder, sync::CancellationToken};
+use uuid::Uuid;
 
 use crate::{
     agent_model::downlink::{BoxDownlinkChannel, MapDownlinkHandle, ValueDownlinkHandle},
+    feature_flags::FeatureFlags,
     lanes::JoinLaneKind,
     meta::AgentMetadata,
 };
@@ -54,20 +61,28 @@ pub use futures::future::Either;
 pub(crate) mod check_step;
 mod command;
 mod handler_fn;
+mod open_lane;
+pub(crate) mod reentrancy;
 mod register_downlink;
 mod suspend;
 #[cfg(test)]
 mod tests;
 
-pub use suspend::{run_after, run_schedule, run_schedule_async, HandlerFuture, Spawner, Suspend};
+pub use suspend::{
+    run_after, run_in_batches, run_schedule, run_schedule_async, suspend_with_timeout,
+    CancellationHandle, HandlerFuture, RetryWithBackoff, Spawner, Suspend, SuspendCancellable,
+};
 
-pub use command::SendCommand;
+pub use command::{CommandAckError, CommandAckTracker, SendCommand, SendCommandWithAck};
 #[doc(hidden)]
 pub use handler_fn::{
     CueFn0, CueFn1, EventConsumeFn, EventFn, GetFn, HandlerFn0, MapRemoveFn, MapUpdateBorrowFn,
     MapUpdateFn, RequestFn0, RequestFn1, TakeFn, UpdateBorrowFn, UpdateFn,
 };
+pub use open_lane::{LaneSpawnError, LaneSpawner, OpenLane};
+pub use reentrancy::ReentrancyPolicy;
 
+use self::open_lane::RegisterLane;
 use self::register_downlink::RegisterHostedDownlink;
 
 /// Trait for contexts that can spawn a new task into the agent runtime to run the lifecycle for a downlink.
@@ -113,6 +128,11 @@ pub struct ActionContext<'a, Context> {
     downlink: &'a dyn DownlinkSpawner<Context>,
     join_lane_init: &'a mut HashMap<u64, BoxJoinLaneInit<'static, Context>>,
     ad_hoc_buffer: &'a mut BytesMut,
+    feature_flags: Option<&'a FeatureFlags>,
+    lane_spawner: Option<&'a dyn LaneSpawner>,
+    command_acks: Option<&'a dyn CommandAckTracker>,
+    stop_token: Option<&'a CancellationToken>,
+    reentrancy: ReentrancyPolicy,
 }
 
 impl<'a, Context> Spawner<Context> for ActionContext<'a, Context> {
@@ -144,9 +164,70 @@ impl<'a, Context> ActionContext<'a, Context> {
             downlink,
             join_lane_init,
             ad_hoc_buffer,
+            feature_flags: None,
+            lane_spawner: None,
+            command_acks: None,
+            stop_token: None,
+            reentrancy: ReentrancyPolicy::Panic,
         }
     }
 
+    /// Attach the feature flags cache of the agent to the context.
+    #[doc(hidden)]
+    pub(crate) fn with_feature_flags(mut self, feature_flags: &'a FeatureFlags) -> Self {
+        self.feature_flags = Some(feature_flags);
+        self
+    }
+
+    /// Get the feature flags cache of the agent, if one is available.
+    #[doc(hidden)]
+    pub(crate) fn feature_flags(&self) -> Option<&'a FeatureFlags> {
+        self.feature_flags
+    }
+
+    /// Attach a context that can register lanes that are opened after the agent has started.
+    #[doc(hidden)]
+    pub(crate) fn with_lane_spawner(mut self, lane_spawner: &'a dyn LaneSpawner) -> Self {
+        self.lane_spawner = Some(lane_spawner);
+        self
+    }
+
+    /// Attach a context that tracks the commands for which an acknowledgement was requested.
+    #[doc(hidden)]
+    pub(crate) fn with_command_acks(mut self, command_acks: &'a dyn CommandAckTracker) -> Self {
+        self.command_acks = Some(command_acks);
+        self
+    }
+
+    /// Attach a token that will be cancelled when the agent stops.
+    #[doc(hidden)]
+    pub(crate) fn with_stop_token(mut self, stop_token: &'a CancellationToken) -> Self {
+        self.stop_token = Some(stop_token);
+        self
+    }
+
+    /// Set how writes to items that are already borrowed are handled.
+    #[doc(hidden)]
+    pub(crate) fn with_reentrancy(mut self, reentrancy: ReentrancyPolicy) -> Self {
+        self.reentrancy = reentrancy;
+        self
+    }
+
+    /// Get the policy for writes to items that are already borrowed.
+    #[doc(hidden)]
+    pub(crate) fn reentrancy(&self) -> ReentrancyPolicy {
+        self.reentrancy
+    }
+
+    /// Create a token to cancel a suspended future. This will be cancelled when the agent stops if
+    /// a stop token is attached to the context.
+    #[doc(hidden)]
+    pub(crate) fn cancellation_token(&self) -> CancellationToken {
+        self.stop_token
+            .map(CancellationToken::child_token)
+            .unwrap_or_default()
+    }
+
     /// Get any join lane initializer that was registered using [`Self::register_join_lane_initializer`]. Typically,
     /// a join lane initializer will be during the `on_init` event of the agent and then retrieved each time a new
     /// downlink is opened for the lane.
@@ -214,6 +295,68 @@ impl<'a, Context> ActionContext<'a, Context> {
         self.spawn_suspend(fut);
     }
 
+    /// Request that the runtime open a new lane for the agent.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the lane.
+    /// * `kind` - The kind of the lane.
+    /// * `config` - Configuration parameters for the lane.
+    /// * `on_done` - A callback that will be executed when the lane has been registered with the agent
+    /// task (or failed to be registered).
+    #[doc(hidden)]
+    pub(crate) fn open_lane<OnDone, H>(
+        &self,
+        name: Text,
+        kind: WarpLaneKind,
+        config: LaneConfig,
+        on_done: OnDone,
+    ) where
+        Context: 'static,
+        OnDone: FnOnce(Result<u64, LaneSpawnError>) -> H + Send + 'static,
+        H: EventHandler<Context> + Send + 'static,
+    {
+        // The state of a lane is only restored from the store when the agent starts so lanes
+        // opened afterwards cannot be persistent.
+        let config = LaneConfig {
+            transient: true,
+            ..config
+        };
+        let reserved = self
+            .lane_spawner
+            .ok_or(LaneSpawnError::NotSupported)
+            .and_then(|spawner| spawner.reserve_lane(name.as_str()));
+        let fut = match reserved {
+            Ok(()) => self
+                .agent_context
+                .add_lane(name.as_str(), kind, config)
+                .map(move |result| {
+                    let register = RegisterLane::new(name, kind, result);
+                    Box::new(register.and_then(on_done)) as LocalBoxEventHandler<'static, Context>
+                })
+                .boxed(),
+            Err(err) => {
+                let handler = on_done(Err(err));
+                async move { handler.boxed_local() }.boxed()
+            }
+        };
+        self.spawn_suspend(fut);
+    }
+
+    /// Register the channels for a lane, that was opened by the runtime, with the agent task.
+    #[doc(hidden)]
+    pub(crate) fn spawn_lane(
+        &self,
+        name: Text,
+        kind: WarpLaneKind,
+        io: Result<(ByteWriter, ByteReader), AgentRuntimeError>,
+    ) -> Result<u64, LaneSpawnError> {
+        if let Some(spawner) = self.lane_spawner {
+            spawner.spawn_lane(name, kind, io)
+        } else {
+            Err(LaneSpawnError::NotSupported)
+        }
+    }
+
     /// Send an ad-hoc command message to a remote lane.
     ///
     /// # Arguments
@@ -238,6 +381,44 @@ impl<'a, Context> ActionContext<'a, Context> {
             .encode(cmd, ad_hoc_buffer)
             .expect("Encoding should be infallible.")
     }
+
+    /// Send an ad-hoc command message to a remote lane and run a callback when it has been passed to
+    /// the runtime. The command cannot be overwritten by subsequent commands to the same lane.
+    ///
+    /// # Arguments
+    /// * `address` - The address of the remote lane.
+    /// * `command` - The body of the command message.
+    /// * `on_ack` - A callback that will be executed when the command has been passed to the runtime
+    /// (or failed to be).
+    #[doc(hidden)]
+    pub(crate) fn send_command_with_ack<S, T, OnAck, H>(
+        &mut self,
+        address: Address<S>,
+        command: T,
+        on_ack: OnAck,
+    ) where
+        Context: 'static,
+        S: AsRef<str>,
+        T: StructuralWritable,
+        OnAck: FnOnce(Result<(), CommandAckError>) -> H + Send + 'static,
+        H: EventHandler<Context> + Send + 'static,
+    {
+        self.send_command(address, command, false);
+        let Some(command_acks) = self.command_acks else {
+            let handler = on_ack(Err(CommandAckError::NotSupported));
+            self.spawn_suspend(async move { handler.boxed_local() }.boxed());
+            return;
+        };
+        let (ack_tx, ack_rx) = oneshot::channel();
+        command_acks.track(ack_tx);
+        let fut = ack_rx
+            .map(move |result| {
+                let handler = on_ack(result.map_err(|_| CommandAckError::Failed));
+                handler.boxed_local()
+            })
+            .boxed();
+        self.spawn_suspend(fut);
+    }
 }
 
 struct ConstructDownlink<F> {
@@ -360,6 +541,13 @@ pub enum EventHandlerError {
     /// An incoming command message was incomplete and could not be deserialized.
     #[error("An incoming message was incomplete.")]
     IncompleteCommand,
+    /// The payload of an incoming command message was rejected by the custom
+    /// [`PayloadCodec`](swimos_agent_protocol::PayloadCodec) of the lane it was targetting.
+    #[error("Invalid incoming payload: {0}")]
+    BadPayload(Box<dyn std::error::Error + Send + Sync + 'static>),
+    /// An incoming command message violated one of the configured [`Limits`](swimos_api::limits::Limits).
+    #[error("An incoming message violated a limit: {0}")]
+    CommandLimitExceeded(LimitExceeded),
     /// An error occurred in the agent runtime which prevented this handler from producing its result.
     #[error("An error occurred in the agent runtime.")]
     RuntimeError(#[from] AgentRuntimeError),
@@ -376,6 +564,50 @@ pub enum EventHandlerError {
     /// The event handler has explicitly requested that the agent stop.
     #[error("The event handler has instructed the agent to stop.")]
     StopInstructed,
+    /// A command was sent to a rate limited command lane with a full backlog and the lane is configured
+    /// to treat this as an error.
+    #[error("The backlog of a rate limited command lane is full.")]
+    CommandBacklogFull,
+    /// An event handler attempted to access a lane, in a collection of lanes that were opened
+    /// after the agent started, that does not exist.
+    #[error("The agent has no dynamic lane named '{0}'.")]
+    NoSuchLane(Text),
+    /// An event handler failed with an error that was raised by user code (for example, using
+    /// the [`try_handler`](crate::try_handler) macro).
+    #[error("An event handler failed: {0}")]
+    HandlerFailed(Box<dyn std::error::Error + Send + Sync + 'static>),
+    /// An event handler attempted to write to a lane or store that was already borrowed (this is
+    /// only reported if diagnostics are enabled with a [`ReentrancyPolicy`]).
+    #[error("{}", describe_reentrant_access(*.item_id, .item_name.as_ref()))]
+    ReentrantAccess {
+        /// The ID of the item.
+        item_id: u64,
+        /// The name of the item, if it is known.
+        item_name: Option<Text>,
+    },
+}
+
+fn describe_reentrant_access(item_id: u64, item_name: Option<&Text>) -> String {
+    match item_name {
+        Some(name) => format!(
+            "Attempted to write to item '{}' while it was already borrowed.",
+            name
+        ),
+        None => format!(
+            "Attempted to write to the item with ID {} while it was already borrowed.",
+            item_id
+        ),
+    }
+}
+
+impl EventHandlerError {
+    /// Create an error for an event handler that failed due to an error raised by user code.
+    pub fn failed<E>(error: E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        EventHandlerError::HandlerFailed(Box::new(error))
+    }
 }
 
 bitflags! {
@@ -1010,6 +1242,58 @@ impl<Context> HandlerAction<Context> for GetAgentUri {
     }
 }
 
+/// An event handler that will get the ID of the remote that sent the command that is currently
+/// being handled (see [`AgentMetadata::command_origin`]).
+#[derive(Default, Debug)]
+pub struct GetCommandOrigin {
+    done: bool,
+}
+
+impl<Context> HandlerAction<Context> for GetCommandOrigin {
+    type Completion = Option<Uuid>;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<Context>,
+        meta: AgentMetadata,
+        _context: &Context,
+    ) -> StepResult<Self::Completion> {
+        let GetCommandOrigin { done } = self;
+        if *done {
+            StepResult::after_done()
+        } else {
+            *done = true;
+            StepResult::done(meta.command_origin())
+        }
+    }
+}
+
+/// An event handler that will get the correlation ID of the envelope that contained the command
+/// that is currently being handled (see [`AgentMetadata::correlation_id`]).
+#[derive(Default, Debug)]
+pub struct GetCorrelationId {
+    done: bool,
+}
+
+impl<Context> HandlerAction<Context> for GetCorrelationId {
+    type Completion = Option<u64>;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<Context>,
+        meta: AgentMetadata,
+        _context: &Context,
+    ) -> StepResult<Self::Completion> {
+        let GetCorrelationId { done } = self;
+        if *done {
+            StepResult::after_done()
+        } else {
+            *done = true;
+            StepResult::done(meta.correlation_id())
+        }
+    }
+}
+
 /// Get a parameter from the route URI of the running agent.
 pub struct GetParameter<S> {
     key: Option<S>,
@@ -1040,7 +1324,8 @@ impl<Context, S: AsRef<str>> HandlerAction<Context> for GetParameter<S> {
 }
 
 /// An event handler that will attempt to decode a [readable](`swimos_form::read::StructuralReadable`) type
-/// from a buffer, immediately returning the result or an error.
+/// (or a value with a custom [payload codec](swimos_agent_protocol::Encoded)) from a buffer, immediately
+/// returning the result or an error.
 pub struct Decode<T> {
     _target_type: PhantomData<fn() -> T>,
     buffer: BytesMut,
@@ -1057,13 +1342,13 @@ impl<T> Decode<T> {
     }
 }
 
-impl<Context, T: RecognizerReadable> HandlerAction<Context> for Decode<T> {
+impl<Context, T: DecodePayload> HandlerAction<Context> for Decode<T> {
     type Completion = T;
 
     fn step(
         &mut self,
         _action_context: &mut ActionContext<Context>,
-        _meta: AgentMetadata,
+        meta: AgentMetadata,
         _context: &Context,
     ) -> StepResult<Self::Completion> {
         let Decode {
@@ -1072,12 +1357,26 @@ impl<Context, T: RecognizerReadable> HandlerAction<Context> for Decode<T> {
         if *complete {
             StepResult::after_done()
         } else {
-            let mut decoder = RecognizerDecoder::new(T::make_recognizer());
             *complete = true;
-            match decoder.decode_eof(buffer) {
-                Ok(Some(value)) => StepResult::done(value),
-                Ok(_) => StepResult::Fail(EventHandlerError::IncompleteCommand),
-                Err(e) => StepResult::Fail(EventHandlerError::BadCommand(e)),
+            let limits = meta
+                .agent_configuration()
+                .default_lane_config
+                .unwrap_or_default()
+                .limits;
+            if let Err(err) = T::check_limits(buffer.as_ref(), &limits) {
+                return StepResult::Fail(EventHandlerError::CommandLimitExceeded(err));
+            }
+            match T::decode_payload(buffer) {
+                Ok(value) => StepResult::done(value),
+                Err(PayloadDecodeError::Incomplete) => {
+                    StepResult::Fail(EventHandlerError::IncompleteCommand)
+                }
+                Err(PayloadDecodeError::Recon(e)) => {
+                    StepResult::Fail(EventHandlerError::BadCommand(e))
+                }
+                Err(PayloadDecodeError::Codec(e)) => {
+                    StepResult::Fail(EventHandlerError::BadPayload(e))
+                }
             }
         }
     }
@@ -1148,6 +1447,52 @@ pub trait HandlerActionExt<Context>: HandlerAction<Context> {
         AndThenTry::new(self, f)
     }
 
+    /// Create a new handler that executes this handler and another in sequence and produces a
+    /// tuple of both of their results.
+    fn zip<H2>(self, other: H2) -> Join<Context, Self, H2>
+    where
+        Self: Sized,
+        H2: HandlerAction<Context>,
+    {
+        join(self, other)
+    }
+
+    /// Create a new handler that alternates between stepping this handler and another, completing
+    /// with the result of whichever completes first. The other handler is then discarded. If either
+    /// handler fails, the combined handler will fail.
+    fn race<H2>(self, other: H2) -> Race<Self, H2>
+    where
+        Self: Sized,
+        H2: HandlerAction<Context>,
+    {
+        Race::new(self, other)
+    }
+
+    /// Create a new handler that will run a fresh copy of this handler again, at most `n` times,
+    /// if it fails. Note that any changes that the failed attempts made to the agent will not be
+    /// rolled back. A handler that instructs the agent to stop will not be retried.
+    fn retry(self, n: usize) -> Retry<Self>
+    where
+        Self: Sized + Clone,
+    {
+        Retry::new(self, n)
+    }
+
+    /// Create a new handler that will run a fresh copy of this handler again, after a delay, if it
+    /// fails, for as long as the retry strategy permits. The retries are suspended into the agent
+    /// task so the agent will continue to handle other events while it waits. Note that any changes
+    /// that the failed attempts made to the agent will not be rolled back. A handler that instructs
+    /// the agent to stop will not be retried.
+    ///
+    /// # Arguments
+    /// * `strategy` - Determines the number of retries and the delays between them.
+    fn retry_with_backoff(self, strategy: RetryStrategy) -> RetryWithBackoff<Self>
+    where
+        Self: Sized + Clone,
+    {
+        RetryWithBackoff::new(self, strategy)
+    }
+
     /// Create a new handler that executes this handler and another in sequence.
     fn followed_by<H2>(self, after: H2) -> FollowedBy<Self, H2>
     where
@@ -1289,6 +1634,200 @@ impl<Context, H: HandlerAction<Context>> HandlerAction<Context> for Discard<H> {
     }
 }
 
+/// Type that is returned by the `race` method on the [`HandlerActionExt`] trait.
+#[derive(Debug, Default)]
+pub enum Race<H1, H2> {
+    Running {
+        first: H1,
+        second: H2,
+        first_next: bool,
+    },
+    #[default]
+    Done,
+}
+
+impl<H1, H2> Race<H1, H2> {
+    fn new(first: H1, second: H2) -> Self {
+        Race::Running {
+            first,
+            second,
+            first_next: true,
+        }
+    }
+}
+
+impl<Context, H1, H2> HandlerAction<Context> for Race<H1, H2>
+where
+    H1: HandlerAction<Context>,
+    H2: HandlerAction<Context>,
+{
+    type Completion = Either<H1::Completion, H2::Completion>;
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<Context>,
+        meta: AgentMetadata,
+        context: &Context,
+    ) -> StepResult<Self::Completion> {
+        match std::mem::take(self) {
+            Race::Running {
+                mut first,
+                mut second,
+                first_next,
+            } => {
+                let step_result = if first_next {
+                    first.step(action_context, meta, context).map(Either::Left)
+                } else {
+                    second
+                        .step(action_context, meta, context)
+                        .map(Either::Right)
+                };
+                if step_result.is_cont() {
+                    *self = Race::Running {
+                        first,
+                        second,
+                        first_next: !first_next,
+                    };
+                }
+                step_result
+            }
+            Race::Done => StepResult::after_done(),
+        }
+    }
+}
+
+/// Type that is returned by the `retry` method on the [`HandlerActionExt`] trait.
+#[derive(Debug)]
+pub struct Retry<H> {
+    template: H,
+    current: Option<H>,
+    remaining: usize,
+}
+
+impl<H: Clone> Retry<H> {
+    fn new(handler: H, n: usize) -> Self {
+        Retry {
+            current: Some(handler.clone()),
+            template: handler,
+            remaining: n,
+        }
+    }
+}
+
+impl<Context, H> HandlerAction<Context> for Retry<H>
+where
+    H: HandlerAction<Context> + Clone,
+{
+    type Completion = H::Completion;
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<Context>,
+        meta: AgentMetadata,
+        context: &Context,
+    ) -> StepResult<Self::Completion> {
+        let Retry {
+            template,
+            current,
+            remaining,
+        } = self;
+        let Some(handler) = current.as_mut() else {
+            return StepResult::after_done();
+        };
+        match handler.step(action_context, meta, context) {
+            StepResult::Continue { modified_item } => StepResult::Continue { modified_item },
+            StepResult::Fail(EventHandlerError::StopInstructed) => {
+                *current = None;
+                StepResult::Fail(EventHandlerError::StopInstructed)
+            }
+            StepResult::Fail(_) if *remaining > 0 => {
+                *remaining -= 1;
+                *current = Some(template.clone());
+                StepResult::Continue {
+                    modified_item: None,
+                }
+            }
+            result => {
+                *current = None;
+                result
+            }
+        }
+    }
+}
+
+/// An event handler that immediately fails with an error.
+pub struct Fail<T> {
+    error: Option<EventHandlerError>,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T> Fail<T> {
+    pub fn new(error: impl Into<EventHandlerError>) -> Self {
+        Fail {
+            error: Some(error.into()),
+            _type: PhantomData,
+        }
+    }
+}
+
+impl<T, Context> HandlerAction<Context> for Fail<T> {
+    type Completion = T;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<Context>,
+        _meta: AgentMetadata,
+        _context: &Context,
+    ) -> StepResult<Self::Completion> {
+        if let Some(error) = self.error.take() {
+            StepResult::Fail(error)
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+impl<'a, Context, T: 'a> From<Fail<T>> for LocalBoxHandlerAction<'a, Context, T> {
+    fn from(handler: Fail<T>) -> Self {
+        Box::new(handler)
+    }
+}
+
+impl<'a, Context, T: 'a> From<Fail<T>> for BoxHandlerAction<'a, Context, T> {
+    fn from(handler: Fail<T>) -> Self {
+        Box::new(handler)
+    }
+}
+
+/// Unwraps a [`Result`] in a function that returns a boxed [`HandlerAction`] (either a
+/// [`BoxHandlerAction`] or a [`LocalBoxHandlerAction`]). If the result is an error, the function
+/// returns early with a handler that will fail with that error. The error type must be convertible
+/// into an [`EventHandlerError`] (arbitrary errors can be wrapped with [`EventHandlerError::failed`]).
+///
+/// # Example
+/// ```
+/// use swimos_agent::event_handler::{
+///     ConstHandler, EventHandlerError, HandlerActionExt, LocalBoxHandlerAction,
+/// };
+/// use swimos_agent::try_handler;
+///
+/// fn parse_input<'a, Context>(input: &str) -> LocalBoxHandlerAction<'a, Context, i32> {
+///     let n: i32 = try_handler!(input.parse::<i32>().map_err(EventHandlerError::failed));
+///     ConstHandler::from(n).boxed_local()
+/// }
+/// ```
+#[macro_export]
+macro_rules! try_handler {
+    ($result:expr) => {
+        match $result {
+            ::core::result::Result::Ok(value) => value,
+            ::core::result::Result::Err(err) => {
+                return ::core::convert::From::from($crate::event_handler::Fail::new(err));
+            }
+        }
+    };
+}
+
 /// Shorthand for a handler action that will open a value downlink.
 pub trait OpenValueDownlink<Context, T>:
     HandlerAction<Context, Completion = ValueDownlinkHandle<T>>
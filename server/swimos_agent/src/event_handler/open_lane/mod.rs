@@ -0,0 +1,158 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use swimos_api::{
+    agent::{LaneConfig, WarpLaneKind},
+    error::AgentRuntimeError,
+};
+use swimos_model::Text;
+use swimos_utilities::byte_channel::{ByteReader, ByteWriter};
+use thiserror::Error;
+
+use crate::meta::AgentMetadata;
+
+use super::{ActionContext, EventHandler, HandlerAction, StepResult};
+
+#[cfg(test)]
+mod tests;
+
+/// Error type for the operation of opening a new lane after an agent has started.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LaneSpawnError {
+    /// The runtime failed to register the lane.
+    #[error(transparent)]
+    RuntimeError(#[from] AgentRuntimeError),
+    /// The agent already has a lane with the requested name.
+    #[error("The agent already has a lane named '{0}'.")]
+    DuplicateLane(Text),
+    /// Lanes cannot be opened in the context in which the handler was executed (for example,
+    /// while the agent is stopping).
+    #[error("Lanes cannot be opened in this context.")]
+    NotSupported,
+}
+
+/// Trait for contexts that can register lanes that are opened after the agent has started.
+pub trait LaneSpawner {
+    /// Reserve the name for a new lane. This will fail if the agent already has a lane with that
+    /// name (or a request to open one is in progress).
+    ///
+    /// # Arguments
+    /// * `name` - The name of the lane.
+    fn reserve_lane(&self, name: &str) -> Result<(), LaneSpawnError>;
+
+    /// Register the channels for a lane, that was opened by the runtime, with the agent task. If the
+    /// runtime failed to open the lane, the reserved name is released.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the lane.
+    /// * `kind` - The kind of the lane.
+    /// * `io` - The channels for the lane (or the error that occurred when it was opened).
+    fn spawn_lane(
+        &self,
+        name: Text,
+        kind: WarpLaneKind,
+        io: Result<(ByteWriter, ByteReader), AgentRuntimeError>,
+    ) -> Result<u64, LaneSpawnError>;
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will open a new lane for the agent.
+/// When the runtime has registered the lane, its channels are attached to the agent task and the
+/// `on_done` callback is called with the ID that was assigned to the lane. The agent specification
+/// must be able to dispatch to the lane by name, using that ID, for it to receive commands and
+/// produce events. Derived agents can do this by adding the lane to a
+/// [`DynamicValueLanes`](crate::lanes::DynamicValueLanes) or
+/// [`DynamicMapLanes`](crate::lanes::DynamicMapLanes) field.
+pub struct OpenLane<F> {
+    inner: Option<(Text, F)>,
+    kind: WarpLaneKind,
+    config: LaneConfig,
+}
+
+impl<F> OpenLane<F> {
+    /// # Arguments
+    /// * `name` - The name of the lane.
+    /// * `kind` - The kind of the lane.
+    /// * `config` - Configuration parameters for the lane.
+    /// * `on_done` - Creates an event handler to run when the lane has been opened (or failed to open).
+    pub fn new(name: Text, kind: WarpLaneKind, config: LaneConfig, on_done: F) -> Self {
+        OpenLane {
+            inner: Some((name, on_done)),
+            kind,
+            config,
+        }
+    }
+}
+
+impl<Context, F, H> HandlerAction<Context> for OpenLane<F>
+where
+    Context: 'static,
+    F: FnOnce(Result<u64, LaneSpawnError>) -> H + Send + 'static,
+    H: EventHandler<Context> + Send + 'static,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<Context>,
+        _meta: AgentMetadata,
+        _context: &Context,
+    ) -> StepResult<Self::Completion> {
+        let OpenLane {
+            inner,
+            kind,
+            config,
+        } = self;
+        if let Some((name, on_done)) = inner.take() {
+            action_context.open_lane(name, *kind, *config, on_done);
+            StepResult::done(())
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+type LaneIo = Result<(ByteWriter, ByteReader), AgentRuntimeError>;
+
+/// A [`HandlerAction`] that registers a lane, opened by the runtime, with the agent task.
+pub(crate) struct RegisterLane {
+    inner: Option<(Text, LaneIo)>,
+    kind: WarpLaneKind,
+}
+
+impl RegisterLane {
+    pub(crate) fn new(name: Text, kind: WarpLaneKind, io: LaneIo) -> Self {
+        RegisterLane {
+            inner: Some((name, io)),
+            kind,
+        }
+    }
+}
+
+impl<Context> HandlerAction<Context> for RegisterLane {
+    type Completion = Result<u64, LaneSpawnError>;
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<Context>,
+        _meta: AgentMetadata,
+        _context: &Context,
+    ) -> StepResult<Self::Completion> {
+        let RegisterLane { inner, kind } = self;
+        if let Some((name, io)) = inner.take() {
+            StepResult::done(action_context.spawn_lane(name, *kind, io))
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
@@ -0,0 +1,309 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use bytes::BytesMut;
+use futures::{
+    future::{ready, BoxFuture},
+    stream::FuturesUnordered,
+    FutureExt, StreamExt,
+};
+use swimos_api::{
+    agent::{
+        AgentConfig, AgentContext, DownlinkKind, HttpLaneRequestChannel, LaneConfig, StoreKind,
+        WarpLaneKind,
+    },
+    error::{AgentRuntimeError, DownlinkRuntimeError, OpenStoreError},
+};
+use swimos_model::Text;
+use swimos_utilities::{
+    byte_channel::{byte_channel, ByteReader, ByteWriter},
+    non_zero_usize,
+    routing::RouteUri,
+};
+
+use crate::{
+    event_handler::{
+        ActionContext, EventHandlerError, HandlerAction, HandlerActionExt, HandlerFuture,
+        SideEffect, StepResult,
+    },
+    meta::AgentMetadata,
+    test_context::no_downlink,
+};
+
+use super::{LaneSpawnError, LaneSpawner, OpenLane};
+
+const LANE: &str = "sensor";
+const LANE_ID: u64 = 12;
+
+struct FakeAgent;
+
+fn make_uri() -> RouteUri {
+    RouteUri::try_from("/self").expect("Bad URI.")
+}
+
+fn make_meta<'a>(
+    uri: &'a RouteUri,
+    route_params: &'a HashMap<String, String>,
+) -> AgentMetadata<'a> {
+    AgentMetadata::new(uri, route_params, &AgentConfig::DEFAULT)
+}
+
+/// Agent context that records the lanes that are requested.
+#[derive(Default)]
+struct LaneContext {
+    requested: Mutex<Vec<(String, WarpLaneKind, LaneConfig)>>,
+}
+
+impl AgentContext for LaneContext {
+    fn ad_hoc_commands(&self) -> BoxFuture<'static, Result<ByteWriter, DownlinkRuntimeError>> {
+        panic!("Unexpected ad hoc commands request.");
+    }
+
+    fn add_lane(
+        &self,
+        name: &str,
+        lane_kind: WarpLaneKind,
+        config: LaneConfig,
+    ) -> BoxFuture<'static, Result<(ByteWriter, ByteReader), AgentRuntimeError>> {
+        self.requested
+            .lock()
+            .unwrap()
+            .push((name.to_string(), lane_kind, config));
+        let (tx, _) = byte_channel(non_zero_usize!(64));
+        let (_, rx) = byte_channel(non_zero_usize!(64));
+        ready(Ok((tx, rx))).boxed()
+    }
+
+    fn add_http_lane(
+        &self,
+        _name: &str,
+    ) -> BoxFuture<'static, Result<HttpLaneRequestChannel, AgentRuntimeError>> {
+        panic!("Unexpected HTTP lane request.");
+    }
+
+    fn open_downlink(
+        &self,
+        _host: Option<&str>,
+        _node: &str,
+        _lane: &str,
+        _kind: DownlinkKind,
+    ) -> BoxFuture<'static, Result<(ByteWriter, ByteReader), DownlinkRuntimeError>> {
+        panic!("Unexpected downlink request.");
+    }
+
+    fn add_store(
+        &self,
+        _name: &str,
+        _kind: StoreKind,
+    ) -> BoxFuture<'static, Result<(ByteWriter, ByteReader), OpenStoreError>> {
+        panic!("Unexpected store request.");
+    }
+}
+
+/// Lane spawner that accepts a single lane.
+#[derive(Default)]
+struct SingleLane {
+    spawned: RefCell<Option<(Text, WarpLaneKind)>>,
+}
+
+impl LaneSpawner for SingleLane {
+    fn reserve_lane(&self, name: &str) -> Result<(), LaneSpawnError> {
+        if name == LANE {
+            Ok(())
+        } else {
+            Err(LaneSpawnError::DuplicateLane(Text::new(name)))
+        }
+    }
+
+    fn spawn_lane(
+        &self,
+        name: Text,
+        kind: WarpLaneKind,
+        io: Result<(ByteWriter, ByteReader), AgentRuntimeError>,
+    ) -> Result<u64, LaneSpawnError> {
+        io?;
+        self.spawned.replace(Some((name, kind)));
+        Ok(LANE_ID)
+    }
+}
+
+type Outcome = Arc<Mutex<Option<Result<u64, LaneSpawnError>>>>;
+type RecordOutcome = SideEffect<Box<dyn FnOnce() + Send>>;
+
+fn record(outcome: &Outcome) -> impl FnOnce(Result<u64, LaneSpawnError>) -> RecordOutcome {
+    let outcome = outcome.clone();
+    move |result| {
+        let f: Box<dyn FnOnce() + Send> = Box::new(move || {
+            *outcome.lock().unwrap() = Some(result);
+        });
+        SideEffect::from(f)
+    }
+}
+
+/// Run a handler to completion, followed by any handlers that it suspends.
+async fn run_handler<H>(
+    agent_context: &dyn AgentContext,
+    spawner: Option<&dyn LaneSpawner>,
+    handler: H,
+) where
+    H: HandlerAction<FakeAgent, Completion = ()> + 'static,
+{
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let mut suspended: FuturesUnordered<HandlerFuture<FakeAgent>> = FuturesUnordered::new();
+
+    let mut next = Some(handler.boxed_local());
+    loop {
+        if let Some(mut handler) = next.take() {
+            loop {
+                let mut action_context = ActionContext::new(
+                    &suspended,
+                    agent_context,
+                    &no_downlink,
+                    &mut join_lane_init,
+                    &mut ad_hoc_buffer,
+                );
+                if let Some(spawner) = spawner {
+                    action_context = action_context.with_lane_spawner(spawner);
+                }
+                match handler.step(&mut action_context, meta, &FakeAgent) {
+                    StepResult::Continue { .. } => {}
+                    StepResult::Fail(err) => panic!("Handler failed: {:?}", err),
+                    StepResult::Complete { .. } => break,
+                }
+            }
+        }
+        match suspended.next().await {
+            Some(h) => next = Some(h),
+            None => break,
+        }
+    }
+}
+
+#[tokio::test]
+async fn open_lane() {
+    let agent_context = LaneContext::default();
+    let spawner = SingleLane::default();
+    let outcome = Outcome::default();
+
+    let handler = OpenLane::new(
+        Text::new(LANE),
+        WarpLaneKind::Map,
+        LaneConfig::default(),
+        record(&outcome),
+    );
+    run_handler(&agent_context, Some(&spawner), handler).await;
+
+    assert_eq!(*outcome.lock().unwrap(), Some(Ok(LANE_ID)));
+    assert_eq!(
+        spawner.spawned.take(),
+        Some((Text::new(LANE), WarpLaneKind::Map))
+    );
+
+    let requested = agent_context.requested.lock().unwrap();
+    match requested.as_slice() {
+        [(name, kind, config)] => {
+            assert_eq!(name, LANE);
+            assert_eq!(*kind, WarpLaneKind::Map);
+            assert!(config.transient);
+        }
+        ow => panic!("Unexpected requests: {:?}", ow),
+    }
+}
+
+#[tokio::test]
+async fn open_duplicate_lane() {
+    let agent_context = LaneContext::default();
+    let spawner = SingleLane::default();
+    let outcome = Outcome::default();
+
+    let handler = OpenLane::new(
+        Text::new("other"),
+        WarpLaneKind::Value,
+        LaneConfig::default(),
+        record(&outcome),
+    );
+    run_handler(&agent_context, Some(&spawner), handler).await;
+
+    assert_eq!(
+        *outcome.lock().unwrap(),
+        Some(Err(LaneSpawnError::DuplicateLane(Text::new("other"))))
+    );
+    assert!(spawner.spawned.take().is_none());
+    assert!(agent_context.requested.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn open_lane_not_supported() {
+    let agent_context = LaneContext::default();
+    let outcome = Outcome::default();
+
+    let handler = OpenLane::new(
+        Text::new(LANE),
+        WarpLaneKind::Value,
+        LaneConfig::default(),
+        record(&outcome),
+    );
+    run_handler(&agent_context, None, handler).await;
+
+    assert_eq!(
+        *outcome.lock().unwrap(),
+        Some(Err(LaneSpawnError::NotSupported))
+    );
+    assert!(agent_context.requested.lock().unwrap().is_empty());
+}
+
+#[test]
+fn open_lane_stepped_after_complete() {
+    let agent_context = LaneContext::default();
+    let spawner = SingleLane::default();
+    let outcome = Outcome::default();
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let suspended: FuturesUnordered<HandlerFuture<FakeAgent>> = FuturesUnordered::new();
+
+    let mut handler = OpenLane::new(
+        Text::new(LANE),
+        WarpLaneKind::Value,
+        LaneConfig::default(),
+        record(&outcome),
+    );
+    let mut action_context = ActionContext::new(
+        &suspended,
+        &agent_context,
+        &no_downlink,
+        &mut join_lane_init,
+        &mut ad_hoc_buffer,
+    )
+    .with_lane_spawner(&spawner);
+    assert!(matches!(
+        handler.step(&mut action_context, meta, &FakeAgent),
+        StepResult::Complete { .. }
+    ));
+    assert!(matches!(
+        handler.step(&mut action_context, meta, &FakeAgent),
+        StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+    ));
+    assert_eq!(suspended.len(), 1);
+}
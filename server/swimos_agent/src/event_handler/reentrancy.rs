@@ -0,0 +1,159 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    cell::{Cell, RefCell, RefMut},
+    collections::VecDeque,
+};
+
+/// Determines how the event handler executor treats writes to a lane or store that is already
+/// borrowed (for example, setting a value lane from within a closure that is reading the same lane).
+/// By default, such writes panic with `already borrowed: BorrowMutError` which is difficult to
+/// diagnose from a backtrace.
+///
+/// This applies to value and map lanes and stores.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ReentrancyPolicy {
+    /// Reentrant writes will panic.
+    #[default]
+    Panic,
+    /// Reentrant writes are discarded and the event handler fails with
+    /// [`EventHandlerError::ReentrantAccess`](super::EventHandlerError::ReentrantAccess), naming
+    /// the item.
+    Diagnose,
+    /// Reentrant `set` operations (for value lanes and stores) and `update` operations (for map
+    /// lanes and stores) are queued and applied, in order, when the existing borrow is released.
+    /// Any other reentrant writes are treated as for [`ReentrancyPolicy::Diagnose`].
+    Queue,
+}
+
+thread_local! {
+    static POLICY: Cell<ReentrancyPolicy> = const { Cell::new(ReentrancyPolicy::Panic) };
+    static VIOLATION: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Guard that applies a policy on the current thread while event handlers are being executed. The
+/// previous policy is restored when it is dropped.
+#[must_use]
+pub(crate) struct PolicyScope {
+    previous: ReentrancyPolicy,
+}
+
+impl Drop for PolicyScope {
+    fn drop(&mut self) {
+        POLICY.with(|policy| policy.set(self.previous));
+    }
+}
+
+/// Apply a policy for the lifetime of the returned guard.
+pub(crate) fn enter(policy: ReentrancyPolicy) -> PolicyScope {
+    let previous = POLICY.with(|current| current.replace(policy));
+    PolicyScope { previous }
+}
+
+/// Take the ID of the first item that was written to reentrantly since the last call, if any.
+pub(crate) fn take_violation() -> Option<u64> {
+    VIOLATION.with(Cell::take)
+}
+
+fn report(item_id: u64) {
+    VIOLATION.with(|violation| {
+        if violation.get().is_none() {
+            violation.set(Some(item_id));
+        }
+    })
+}
+
+/// The outcome of attempting to borrow an item in order to write to it.
+pub(crate) enum Write<'a, T> {
+    /// The item was available.
+    Available(RefMut<'a, T>),
+    /// The item is already borrowed and the write should be queued.
+    Defer,
+    /// The item is already borrowed and the write should be discarded (the violation has been
+    /// recorded).
+    Rejected,
+}
+
+/// Attempt to borrow an item in order to write to it, respecting the policy for the current thread.
+///
+/// # Arguments
+/// * `item_id` - The ID of the item.
+/// * `cell` - The cell containing the state of the item.
+/// * `deferrable` - Whether the write can be queued if the item is already borrowed.
+pub(crate) fn write<T>(item_id: u64, cell: &RefCell<T>, deferrable: bool) -> Write<'_, T> {
+    match cell.try_borrow_mut() {
+        Ok(guard) => Write::Available(guard),
+        Err(_) => match POLICY.with(Cell::get) {
+            ReentrancyPolicy::Panic => Write::Available(cell.borrow_mut()),
+            ReentrancyPolicy::Queue if deferrable => Write::Defer,
+            _ => {
+                report(item_id);
+                Write::Rejected
+            }
+        },
+    }
+}
+
+/// Writes to an item that were queued, under [`ReentrancyPolicy::Queue`], while the item was
+/// already borrowed.
+#[derive(Debug)]
+pub(crate) struct Deferred<T> {
+    writes: RefCell<VecDeque<T>>,
+}
+
+impl<T> Default for Deferred<T> {
+    fn default() -> Self {
+        Self {
+            writes: Default::default(),
+        }
+    }
+}
+
+impl<T> Deferred<T> {
+    pub(crate) fn push(&self, write: T) {
+        self.writes.borrow_mut().push_back(write);
+    }
+
+    /// Apply the queued writes, in order, if the item is no longer borrowed.
+    ///
+    /// # Arguments
+    /// * `cell` - The cell containing the state of the item.
+    /// * `f` - Applies a single write to the state.
+    pub(crate) fn apply<S, F>(&self, cell: &RefCell<S>, f: F)
+    where
+        F: FnMut(&mut S, T),
+    {
+        if self.writes.borrow().is_empty() {
+            return;
+        }
+        if let Ok(mut guard) = cell.try_borrow_mut() {
+            self.apply_to(&mut *guard, f);
+        }
+    }
+
+    /// Apply the queued writes, in order, to the state of an item that is already borrowed.
+    pub(crate) fn apply_to<S, F>(&self, state: &mut S, mut f: F)
+    where
+        F: FnMut(&mut S, T),
+    {
+        loop {
+            let next = self.writes.borrow_mut().pop_front();
+            match next {
+                Some(write) => f(state, write),
+                None => break,
+            }
+        }
+    }
+}
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::time::Duration;
+use std::{iter::Peekable, num::NonZeroUsize, time::Duration};
 
 use futures::{
     future::{BoxFuture, Either},
@@ -20,12 +20,14 @@ use futures::{
     Future, FutureExt, Stream, StreamExt,
 };
 use static_assertions::assert_obj_safe;
+use swimos_utilities::future::RetryStrategy;
+use tokio_util::sync::CancellationToken;
 
 use crate::meta::AgentMetadata;
 
 use super::{
-    ActionContext, EventHandler, HandlerAction, HandlerActionExt, LocalBoxEventHandler, StepResult,
-    UnitHandler,
+    ActionContext, EventHandler, EventHandlerError, HandlerAction, HandlerActionExt,
+    LocalBoxEventHandler, Sequentially, StepResult, UnitHandler,
 };
 
 #[cfg(test)]
@@ -104,6 +106,84 @@ where
     }
 }
 
+/// A handle that can be used to cancel a future that was suspended with [`SuspendCancellable`].
+#[derive(Debug, Clone)]
+pub struct CancellationHandle {
+    token: CancellationToken,
+}
+
+impl CancellationHandle {
+    /// Cancel the future. If it has not already completed, it will be discarded and its
+    /// `on_cancelled` handler will be executed instead.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Whether the future has been cancelled (either explicitly or because the agent is stopping).
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+/// A handler action that will suspend a future into the agent task that can be cancelled. The
+/// future is cancelled automatically when the agent stops. The action completes with a
+/// [`CancellationHandle`] for the future.
+pub struct SuspendCancellable<Fut, H> {
+    inner: Option<(Fut, H)>,
+}
+
+impl<Fut, H> SuspendCancellable<Fut, H> {
+    /// # Arguments
+    /// * `future` - The future to be suspended.
+    /// * `on_cancelled` - The handler to run if the future is cancelled before it completes.
+    pub fn new(future: Fut, on_cancelled: H) -> Self {
+        SuspendCancellable {
+            inner: Some((future, on_cancelled)),
+        }
+    }
+}
+
+impl<Context, Fut, H, H2> HandlerAction<Context> for SuspendCancellable<Fut, H2>
+where
+    Fut: Future<Output = H> + Send + 'static,
+    H: EventHandler<Context> + 'static,
+    H2: EventHandler<Context> + Send + 'static,
+{
+    type Completion = CancellationHandle;
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<Context>,
+        _meta: AgentMetadata,
+        _context: &Context,
+    ) -> StepResult<Self::Completion> {
+        let SuspendCancellable { inner } = self;
+        if let Some((future, on_cancelled)) = inner.take() {
+            let token = action_context.cancellation_token();
+            let handle = CancellationHandle {
+                token: token.clone(),
+            };
+            let fut = async move {
+                tokio::select! {
+                    biased;
+                    _ = token.cancelled() => {
+                        let boxed: LocalBoxEventHandler<Context> = Box::new(on_cancelled);
+                        boxed
+                    }
+                    handler = future => {
+                        let boxed: LocalBoxEventHandler<Context> = Box::new(handler);
+                        boxed
+                    }
+                }
+            };
+            action_context.spawn_suspend(fut.boxed());
+            StepResult::done(handle)
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
 /// Suspend an [`EventHandler`] to be executed after a fixed duration.
 ///
 /// # Note
@@ -125,6 +205,106 @@ where
     Suspend::new(fut)
 }
 
+/// Suspend a future into the agent task, bounding the time that it may take to complete. If the
+/// future completes within the timeout, the handler that it results in will be executed. Otherwise,
+/// the future is discarded and the `on_timeout` handler is executed instead.
+///
+/// # Arguments
+/// * `timeout` - The maximum time to wait for the future.
+/// * `future` - The future to be suspended.
+/// * `on_timeout` - The handler to run if the future does not complete in time.
+pub fn suspend_with_timeout<Context, Fut, H, H2>(
+    timeout: Duration,
+    future: Fut,
+    on_timeout: H2,
+) -> impl EventHandler<Context> + Send + 'static
+where
+    Fut: Future<Output = H> + Send + 'static,
+    H: EventHandler<Context> + 'static,
+    H2: EventHandler<Context> + Send + 'static,
+{
+    let fut = tokio::time::timeout(timeout, future).map(move |result| match result {
+        Ok(handler) => Either::Left(handler),
+        Err(_) => Either::Right(on_timeout),
+    });
+    Suspend::new(fut)
+}
+
+/// Type that is returned by the `retry_with_backoff` method on the [`HandlerActionExt`] trait.
+///
+/// If the handler fails, a fresh copy of it is suspended into the agent task to be run after the
+/// delay determined by the retry strategy and this handler completes. Other events will be handled
+/// by the agent before the retry runs. If the strategy is exhausted, the error from the last attempt
+/// is returned.
+#[derive(Debug)]
+pub struct RetryWithBackoff<H> {
+    template: H,
+    current: Option<H>,
+    strategy: RetryStrategy,
+}
+
+impl<H: Clone> RetryWithBackoff<H> {
+    pub(crate) fn new(handler: H, strategy: RetryStrategy) -> Self {
+        RetryWithBackoff {
+            current: Some(handler.clone()),
+            template: handler,
+            strategy,
+        }
+    }
+}
+
+impl<Context, H> HandlerAction<Context> for RetryWithBackoff<H>
+where
+    H: EventHandler<Context> + Clone + Send + 'static,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<Context>,
+        meta: AgentMetadata,
+        context: &Context,
+    ) -> StepResult<Self::Completion> {
+        let RetryWithBackoff {
+            template,
+            current,
+            strategy,
+        } = self;
+        let Some(handler) = current.as_mut() else {
+            return StepResult::after_done();
+        };
+        match handler.step(action_context, meta, context) {
+            StepResult::Continue { modified_item } => StepResult::Continue { modified_item },
+            StepResult::Fail(EventHandlerError::StopInstructed) => {
+                *current = None;
+                StepResult::Fail(EventHandlerError::StopInstructed)
+            }
+            StepResult::Fail(err) => {
+                *current = None;
+                match strategy.next() {
+                    Some(delay) => {
+                        let retry = RetryWithBackoff::new(template.clone(), *strategy);
+                        let fut = async move {
+                            if let Some(delay) = delay {
+                                tokio::time::sleep(delay).await;
+                            }
+                            let boxed: LocalBoxEventHandler<Context> = Box::new(retry);
+                            boxed
+                        };
+                        action_context.spawn_suspend(fut.boxed());
+                        StepResult::done(())
+                    }
+                    None => StepResult::Fail(err),
+                }
+            }
+            result => {
+                *current = None;
+                result
+            }
+        }
+    }
+}
+
 /// Schedule a sequence of [`EventHandler`]s to run on a schedule. For each pair of a delay and and
 /// [`EventHandler`] returned by the provided iterator, the handler is scheduled to run after the delay.
 /// The handlers are scheduled sequentially, not simultaneously.
@@ -180,3 +360,50 @@ where
         }
     })
 }
+
+/// Run a (potentially very long) sequence of [`EventHandler`]s in batches. Each batch is executed
+/// without interruption but, between batches, the remainder of the sequence is suspended into the
+/// agent task. This allows the agent to process other events between the batches, rather than
+/// being blocked until the entire sequence has completed.
+///
+/// # Note
+///
+/// Both the iterator and the handlers must be [`Send`] as the task running the agent could be moved while
+/// they are still in use.
+///
+/// # Arguments
+/// * `handlers` - An iterator returning a sequence of handlers.
+/// * `batch_size` - The maximum number of handlers to run before yielding to the agent task.
+pub fn run_in_batches<Context, I, H>(
+    handlers: I,
+    batch_size: NonZeroUsize,
+) -> impl EventHandler<Context> + Send + 'static
+where
+    Context: 'static,
+    I: IntoIterator<Item = H>,
+    I::IntoIter: Send + 'static,
+    H: EventHandler<Context> + Send + 'static,
+{
+    next_batch(handlers.into_iter().peekable(), batch_size)
+}
+
+fn next_batch<Context, I, H>(
+    mut it: Peekable<I>,
+    batch_size: NonZeroUsize,
+) -> impl EventHandler<Context> + Send + 'static
+where
+    Context: 'static,
+    I: Iterator<Item = H> + Send + 'static,
+    H: EventHandler<Context> + Send + 'static,
+{
+    let batch = it.by_ref().take(batch_size.get()).collect::<Vec<_>>();
+    let handler = Sequentially::new(batch);
+    if it.peek().is_some() {
+        Either::Left(handler.followed_by(Suspend::new(async move {
+            let h: Box<dyn EventHandler<Context> + Send> = Box::new(next_batch(it, batch_size));
+            h
+        })))
+    } else {
+        Either::Right(handler)
+    }
+}
@@ -16,7 +16,8 @@ use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use crate::{
     event_handler::{
-        ActionContext, EventHandler, EventHandlerError, HandlerAction, SideEffect, StepResult,
+        ActionContext, EventHandler, EventHandlerError, HandlerAction, HandlerActionExt,
+        SideEffect, StepResult, UnitHandler,
     },
     meta::AgentMetadata,
     test_context::{no_downlink, DummyAgentContext},
@@ -25,10 +26,16 @@ use bytes::BytesMut;
 use futures::{stream::FuturesUnordered, StreamExt};
 use parking_lot::Mutex;
 use swimos_api::agent::AgentConfig;
-use swimos_utilities::{routing::RouteUri, trigger};
+use swimos_utilities::{
+    future::{Quantity, RetryStrategy},
+    non_zero_usize,
+    routing::RouteUri,
+    trigger,
+};
 use tokio::{sync::mpsc, time::Instant};
+use tokio_util::sync::CancellationToken;
 
-use super::{HandlerFuture, Suspend};
+use super::{CancellationHandle, HandlerFuture, Suspend, SuspendCancellable};
 
 const CONFIG: AgentConfig = AgentConfig::DEFAULT;
 const NODE_URI: &str = "/node";
@@ -268,3 +275,217 @@ async fn scheduled_handler_async() {
     let guard = events.lock();
     assert_eq!(*guard, vec![0, 1, 2]);
 }
+
+#[tokio::test]
+async fn handlers_in_batches() {
+    let events: Arc<Mutex<Vec<usize>>> = Default::default();
+    let handlers = (0..5).map(|n| set_n(events.clone(), n)).collect::<Vec<_>>();
+
+    let batched = super::run_in_batches(handlers, non_zero_usize!(2));
+
+    let mut spawner = FuturesUnordered::new();
+    run_handler(batched, &spawner);
+    assert_eq!(*events.lock(), vec![0, 1]);
+
+    let mut batches = 1;
+    while let Some(h) = spawner.next().await {
+        batches += 1;
+        run_handler(h, &spawner);
+    }
+
+    assert_eq!(batches, 3);
+    assert_eq!(*events.lock(), vec![0, 1, 2, 3, 4]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn suspend_with_timeout_completes() {
+    let events: Arc<Mutex<Vec<usize>>> = Default::default();
+
+    let handler = super::suspend_with_timeout(
+        DELAY * 2,
+        set_n_async(events.clone(), 0),
+        set_n(events.clone(), 1),
+    );
+
+    let before = Instant::now();
+    run_handler_with_futures(handler).await;
+    assert_eq!(before.elapsed(), DELAY);
+
+    assert_eq!(*events.lock(), vec![0]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn suspend_with_timeout_expires() {
+    let events: Arc<Mutex<Vec<usize>>> = Default::default();
+
+    let handler = super::suspend_with_timeout(
+        DELAY / 2,
+        set_n_async(events.clone(), 0),
+        set_n(events.clone(), 1),
+    );
+
+    let before = Instant::now();
+    run_handler_with_futures(handler).await;
+    assert_eq!(before.elapsed(), DELAY / 2);
+
+    assert_eq!(*events.lock(), vec![1]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn suspend_cancellable_completes() {
+    let events: Arc<Mutex<Vec<usize>>> = Default::default();
+
+    let handler =
+        SuspendCancellable::new(set_n_async(events.clone(), 0), set_n(events.clone(), 1)).discard();
+
+    let before = Instant::now();
+    run_handler_with_futures(handler).await;
+    assert_eq!(before.elapsed(), DELAY);
+
+    assert_eq!(*events.lock(), vec![0]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn suspend_cancellable_cancelled() {
+    let events: Arc<Mutex<Vec<usize>>> = Default::default();
+
+    let handler = SuspendCancellable::new(set_n_async(events.clone(), 0), set_n(events.clone(), 1))
+        .and_then(|handle: CancellationHandle| SideEffect::from(move || handle.cancel()));
+
+    let before = Instant::now();
+    run_handler_with_futures(handler).await;
+    assert_eq!(before.elapsed(), Duration::ZERO);
+
+    assert_eq!(*events.lock(), vec![1]);
+}
+
+#[tokio::test]
+async fn suspend_cancellable_cancelled_on_stop() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let events: Arc<Mutex<Vec<usize>>> = Default::default();
+    let stop_token = CancellationToken::new();
+
+    let mut handler = SuspendCancellable::new(
+        futures::future::pending::<UnitHandler>(),
+        set_n(events.clone(), 1),
+    );
+
+    let mut spawner = FuturesUnordered::new();
+
+    let handle = match handler.step(
+        &mut ActionContext::new(
+            &spawner,
+            &DummyAgentContext,
+            &no_downlink,
+            &mut join_lane_init,
+            &mut ad_hoc_buffer,
+        )
+        .with_stop_token(&stop_token),
+        meta,
+        &DummyAgent,
+    ) {
+        StepResult::Complete { result, .. } => result,
+        _ => panic!("Handler did not complete."),
+    };
+    assert!(!handle.is_cancelled());
+
+    stop_token.cancel();
+    assert!(handle.is_cancelled());
+
+    let on_cancelled = spawner.next().await.expect("No suspended future.");
+    run_handler(on_cancelled, &spawner);
+    assert!(spawner.is_empty());
+
+    assert_eq!(*events.lock(), vec![1]);
+}
+
+#[derive(Clone)]
+struct FailTimes {
+    attempts: Arc<Mutex<usize>>,
+    failures: usize,
+}
+
+impl HandlerAction<DummyAgent> for FailTimes {
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<DummyAgent>,
+        _meta: AgentMetadata,
+        _context: &DummyAgent,
+    ) -> StepResult<Self::Completion> {
+        let FailTimes { attempts, failures } = self;
+        let mut guard = attempts.lock();
+        *guard += 1;
+        if *guard <= *failures {
+            StepResult::Fail(EventHandlerError::IncompleteCommand)
+        } else {
+            StepResult::done(())
+        }
+    }
+}
+
+fn retry_strategy(retries: usize) -> RetryStrategy {
+    RetryStrategy::interval(
+        DELAY,
+        Quantity::Finite(retries.try_into().expect("Retries must be positive.")),
+    )
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_with_backoff_succeeds() {
+    let attempts: Arc<Mutex<usize>> = Default::default();
+    let handler = FailTimes {
+        attempts: attempts.clone(),
+        failures: 2,
+    }
+    .retry_with_backoff(retry_strategy(2));
+
+    let before = Instant::now();
+    run_handler_with_futures(handler).await;
+    assert_eq!(before.elapsed(), DELAY * 2);
+
+    assert_eq!(*attempts.lock(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_with_backoff_exhausted() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+
+    let attempts: Arc<Mutex<usize>> = Default::default();
+    let handler = FailTimes {
+        attempts: attempts.clone(),
+        failures: 3,
+    }
+    .retry_with_backoff(retry_strategy(1));
+
+    let mut spawner = FuturesUnordered::new();
+    run_handler(handler, &spawner);
+
+    let mut retry = spawner.next().await.expect("Retry was not suspended.");
+    let result = retry.step(
+        &mut ActionContext::new(
+            &spawner,
+            &DummyAgentContext,
+            &no_downlink,
+            &mut join_lane_init,
+            &mut ad_hoc_buffer,
+        ),
+        meta,
+        &DummyAgent,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::IncompleteCommand)
+    ));
+    assert!(spawner.is_empty());
+    assert_eq!(*attempts.lock(), 2);
+}
@@ -13,23 +13,34 @@
 // limitations under the License.
 
 use std::fmt::Write;
-use std::{cell::RefCell, collections::HashMap};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
 
 use bytes::BytesMut;
-use swimos_api::agent::AgentConfig;
+use swimos_api::{
+    agent::AgentConfig,
+    limits::{LimitExceeded, Limits},
+};
+use swimos_model::Value;
 use swimos_recon::parser::AsyncParseError;
 use swimos_utilities::routing::RouteUri;
+use uuid::Uuid;
 
 use crate::event_handler::check_step::{check_is_complete, check_is_continue};
 use crate::event_handler::{GetParameter, ModificationFlags};
 
 use crate::{
     event_handler::{
-        ConstHandler, EventHandlerError, GetAgentUri, HandlerActionExt, Sequentially, SideEffects,
+        ConstHandler, Either, EventHandlerError, Fail, GetAgentUri, GetCommandOrigin,
+        GetCorrelationId, HandlerActionExt, LocalBoxHandlerAction, Sequentially, SideEffects,
     },
     lanes::{value::ValueLaneSet, ValueLane},
     meta::AgentMetadata,
     test_context::dummy_context,
+    try_handler,
 };
 
 use super::{join, ActionContext, Decode, HandlerAction, Modification, SideEffect, StepResult};
@@ -179,6 +190,99 @@ fn constant_handler() {
     ));
 }
 
+#[test]
+fn get_correlation_id() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+
+    let mut handler = GetCorrelationId::default();
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Complete {
+            modified_item: None,
+            result: None
+        }
+    ));
+
+    let mut handler = GetCorrelationId::default();
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta.with_correlation_id(Some(6)),
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Complete {
+            modified_item: None,
+            result: Some(6)
+        }
+    ));
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+    ));
+}
+
+#[test]
+fn get_command_origin() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let origin = Uuid::from_u128(84);
+    let meta = make_meta(&uri, &route_params);
+
+    let mut handler = GetCommandOrigin::default();
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Complete {
+            modified_item: None,
+            result: None
+        }
+    ));
+
+    let mut handler = GetCommandOrigin::default();
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta.with_command_origin(Some(origin)),
+        &DUMMY,
+    );
+    if let StepResult::Complete {
+        modified_item: None,
+        result,
+    } = result
+    {
+        assert_eq!(result, Some(origin));
+    } else {
+        panic!("Expected completion.");
+    }
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+    ));
+}
+
 #[test]
 fn get_agent_uri() {
     let uri = make_uri();
@@ -550,6 +654,31 @@ fn decoding_handler_failure() {
     ));
 }
 
+#[test]
+fn decoding_handler_limit_exceeded() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+
+    let depth = Limits::DEFAULT.max_recon_depth.unwrap().get() + 1;
+    let mut buffer = BytesMut::new();
+    write!(buffer, "{}1{}", "{".repeat(depth), "}".repeat(depth)).expect("Write failed.");
+
+    let mut handler = Decode::<Value>::new(buffer);
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::CommandLimitExceeded(
+            LimitExceeded::TooDeep { .. }
+        ))
+    ));
+}
+
 struct FakeLaneWriter(Option<u64>);
 
 impl FakeLaneWriter {
@@ -965,3 +1094,248 @@ fn join3_handler_modify() {
     agent.lane2.read(|v| assert_eq!(*v, 3));
     agent.lane3.read(|v| assert_eq!(*v, 4));
 }
+
+#[test]
+fn zip_handler() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+
+    let mut handler = FakeLaneWriter::new(7).zip(ConstHandler::from(5));
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    check_is_continue(result, 7, ModificationFlags::all());
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Complete {
+            modified_item: None,
+            result: ((), 5)
+        }
+    ));
+}
+
+#[test]
+fn race_handler() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+
+    let mut handler = FakeLaneWriter::new(7)
+        .followed_by(ConstHandler::from(1))
+        .race(ConstHandler::from("second"));
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    check_is_continue(result, 7, ModificationFlags::all());
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Complete {
+            modified_item: None,
+            result: Either::Right("second")
+        }
+    ));
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+    ));
+}
+
+#[test]
+fn race_handler_failure() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+
+    let mut handler = HandlerActionExt::<DummyAgent>::race(
+        Fail::<i32>::new(EventHandlerError::IncompleteCommand),
+        ConstHandler::from("second"),
+    );
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::IncompleteCommand)
+    ));
+}
+
+/// A handler that fails until it has been attempted a fixed number of times.
+#[derive(Clone)]
+struct FailTimes {
+    attempts: Rc<Cell<usize>>,
+    failures: usize,
+}
+
+impl HandlerAction<DummyAgent> for FailTimes {
+    type Completion = usize;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<DummyAgent>,
+        _meta: AgentMetadata,
+        _context: &DummyAgent,
+    ) -> StepResult<Self::Completion> {
+        let FailTimes { attempts, failures } = self;
+        let n = attempts.get() + 1;
+        attempts.set(n);
+        if n <= *failures {
+            StepResult::Fail(EventHandlerError::IncompleteCommand)
+        } else {
+            StepResult::done(n)
+        }
+    }
+}
+
+#[test]
+fn retry_handler_succeeds() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+
+    let attempts = Rc::new(Cell::new(0));
+    let mut handler = FailTimes {
+        attempts: attempts.clone(),
+        failures: 2,
+    }
+    .retry(2);
+
+    for _ in 0..2 {
+        let result = handler.step(
+            &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+            meta,
+            &DUMMY,
+        );
+        assert!(matches!(
+            result,
+            StepResult::Continue {
+                modified_item: None
+            }
+        ));
+    }
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Complete {
+            modified_item: None,
+            result: 3
+        }
+    ));
+    assert_eq!(attempts.get(), 3);
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+    ));
+}
+
+#[test]
+fn retry_handler_exhausted() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+
+    let attempts = Rc::new(Cell::new(0));
+    let mut handler = FailTimes {
+        attempts: attempts.clone(),
+        failures: 3,
+    }
+    .retry(1);
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(result.is_cont());
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::IncompleteCommand)
+    ));
+    assert_eq!(attempts.get(), 2);
+}
+
+fn parse_handler<'a>(input: &str) -> LocalBoxHandlerAction<'a, DummyAgent, i32> {
+    let n: i32 = try_handler!(input.parse::<i32>().map_err(EventHandlerError::failed));
+    ConstHandler::from(n).boxed_local()
+}
+
+#[test]
+fn try_handler_macro() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+
+    let mut handler = parse_handler("12");
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Complete {
+            modified_item: None,
+            result: 12
+        }
+    ));
+
+    let mut handler = parse_handler("twelve");
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &DUMMY,
+    );
+    match result {
+        StepResult::Fail(err @ EventHandlerError::HandlerFailed(_)) => {
+            assert_eq!(
+                err.to_string(),
+                "An event handler failed: invalid digit found in string"
+            );
+        }
+        _ => panic!("Unexpected step result."),
+    }
+}
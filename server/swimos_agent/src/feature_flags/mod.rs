@@ -0,0 +1,230 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use swimos_api::{
+    address::Address,
+    agent::{FEATURE_FLAGS_LANE, FEATURE_FLAGS_NODE},
+};
+use swimos_form::Form;
+use swimos_model::{Text, Value};
+
+use crate::{
+    agent_model::downlink::{MapDownlinkHandle, OpenMapDownlinkAction},
+    config::MapDownlinkConfig,
+    downlink_lifecycle::{
+        OnDownlinkClear, OnDownlinkRemove, OnDownlinkUpdate, OnFailed, OnLinked, OnSynced,
+        OnUnlinked,
+    },
+    event_handler::{ActionContext, HandlerAction, StepResult, UnitHandler},
+    meta::AgentMetadata,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The cached state of the feature flags of the plane, shared by all of the event handlers of an
+/// agent instance. The cache is populated by a map downlink to the feature flags meta-agent that is
+/// opened the first time that a flag is requested.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct FeatureFlags {
+    inner: Arc<Mutex<FlagsState>>,
+}
+
+#[derive(Debug, Default)]
+struct FlagsState {
+    handle: Option<MapDownlinkHandle<Text, Value>>,
+    flags: HashMap<Text, Value>,
+}
+
+impl FeatureFlags {
+    /// Whether a downlink needs to be opened to populate the cache (either because it has never been
+    /// opened or because the previous downlink has stopped).
+    fn needs_downlink(&self) -> bool {
+        let guard = self.inner.lock().expect("Feature flags lock poisoned.");
+        guard
+            .handle
+            .as_ref()
+            .map(MapDownlinkHandle::is_stopped)
+            .unwrap_or(true)
+    }
+
+    fn set_handle(&self, handle: MapDownlinkHandle<Text, Value>) {
+        let mut guard = self.inner.lock().expect("Feature flags lock poisoned.");
+        guard.handle = Some(handle);
+    }
+
+    fn get(&self, name: &str) -> Option<Value> {
+        let guard = self.inner.lock().expect("Feature flags lock poisoned.");
+        guard.flags.get(name).cloned()
+    }
+
+    fn with_flags<F>(&self, f: F)
+    where
+        F: FnOnce(&mut HashMap<Text, Value>),
+    {
+        let mut guard = self.inner.lock().expect("Feature flags lock poisoned.");
+        f(&mut guard.flags)
+    }
+}
+
+/// Downlink lifecycle that keeps a [`FeatureFlags`] cache consistent with the remote lane.
+struct FlagsLifecycle(FeatureFlags);
+
+impl<Context> OnLinked<Context> for FlagsLifecycle {
+    type OnLinkedHandler<'a> = UnitHandler
+    where
+        Self: 'a;
+
+    fn on_linked(&self) -> Self::OnLinkedHandler<'_> {
+        UnitHandler::default()
+    }
+}
+
+impl<Context> OnSynced<HashMap<Text, Value>, Context> for FlagsLifecycle {
+    type OnSyncedHandler<'a> = UnitHandler
+    where
+        Self: 'a;
+
+    fn on_synced<'a>(&'a self, value: &HashMap<Text, Value>) -> Self::OnSyncedHandler<'a> {
+        self.0.with_flags(|flags| flags.clone_from(value));
+        UnitHandler::default()
+    }
+}
+
+impl<Context> OnDownlinkUpdate<Text, Value, Context> for FlagsLifecycle {
+    type OnUpdateHandler<'a> = UnitHandler
+    where
+        Self: 'a;
+
+    fn on_update<'a>(
+        &'a self,
+        key: Text,
+        _map: &HashMap<Text, Value>,
+        _previous: Option<Value>,
+        new_value: &Value,
+    ) -> Self::OnUpdateHandler<'a> {
+        self.0.with_flags(|flags| {
+            flags.insert(key, new_value.clone());
+        });
+        UnitHandler::default()
+    }
+}
+
+impl<Context> OnDownlinkRemove<Text, Value, Context> for FlagsLifecycle {
+    type OnRemoveHandler<'a> = UnitHandler
+    where
+        Self: 'a;
+
+    fn on_remove<'a>(
+        &'a self,
+        key: Text,
+        _map: &HashMap<Text, Value>,
+        _removed: Value,
+    ) -> Self::OnRemoveHandler<'a> {
+        self.0.with_flags(|flags| {
+            flags.remove(&key);
+        });
+        UnitHandler::default()
+    }
+}
+
+impl<Context> OnDownlinkClear<Text, Value, Context> for FlagsLifecycle {
+    type OnClearHandler<'a> = UnitHandler
+    where
+        Self: 'a;
+
+    fn on_clear(&self, _map: HashMap<Text, Value>) -> Self::OnClearHandler<'_> {
+        self.0.with_flags(HashMap::clear);
+        UnitHandler::default()
+    }
+}
+
+impl<Context> OnUnlinked<Context> for FlagsLifecycle {
+    type OnUnlinkedHandler<'a> = UnitHandler
+    where
+        Self: 'a;
+
+    fn on_unlinked(&self) -> Self::OnUnlinkedHandler<'_> {
+        UnitHandler::default()
+    }
+}
+
+impl<Context> OnFailed<Context> for FlagsLifecycle {
+    type OnFailedHandler<'a> = UnitHandler
+    where
+        Self: 'a;
+
+    fn on_failed(&self) -> Self::OnFailedHandler<'_> {
+        UnitHandler::default()
+    }
+}
+
+/// A [`HandlerAction`] that reads the current variant of a feature flag from the cache of the agent,
+/// opening a downlink to the feature flags meta-agent if the cache is not yet being maintained.
+pub struct GetFeatureFlag<T> {
+    _type: PhantomData<fn() -> T>,
+    name: Option<Text>,
+}
+
+impl<T> GetFeatureFlag<T> {
+    pub(crate) fn new(name: &str) -> Self {
+        GetFeatureFlag {
+            _type: PhantomData,
+            name: Some(Text::new(name)),
+        }
+    }
+}
+
+impl<T, Context> HandlerAction<Context> for GetFeatureFlag<T>
+where
+    Context: 'static,
+    T: Form,
+{
+    type Completion = Option<T>;
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<Context>,
+        meta: AgentMetadata,
+        context: &Context,
+    ) -> StepResult<Self::Completion> {
+        let Some(name) = self.name.take() else {
+            return StepResult::after_done();
+        };
+        let Some(flags) = action_context.feature_flags().cloned() else {
+            return StepResult::done(None);
+        };
+        if flags.needs_downlink() {
+            let address = Address::text(None, FEATURE_FLAGS_NODE, FEATURE_FLAGS_LANE);
+            let mut open = OpenMapDownlinkAction::<Text, Value, _>::new(
+                address,
+                FlagsLifecycle(flags.clone()),
+                MapDownlinkConfig::default(),
+            );
+            if let StepResult::Complete { result, .. } = open.step(action_context, meta, context) {
+                flags.set_handle(result);
+            }
+        }
+        let flag = flags
+            .get(name.as_str())
+            .and_then(|value| T::try_from_value(&value).ok());
+        StepResult::done(flag)
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    sync::{atomic::AtomicU8, Arc, Mutex},
+};
+
+use bytes::BytesMut;
+use futures::{
+    future::{pending, BoxFuture},
+    stream::FuturesUnordered,
+    FutureExt,
+};
+use swimos_api::{
+    address::Address,
+    agent::{
+        AgentConfig, AgentContext, DownlinkKind, HttpLaneRequestChannel, LaneConfig, StoreKind,
+        WarpLaneKind, FEATURE_FLAGS_LANE, FEATURE_FLAGS_NODE,
+    },
+    error::{AgentRuntimeError, DownlinkRuntimeError, OpenStoreError},
+};
+use swimos_model::{Text, Value};
+use swimos_utilities::{
+    byte_channel::{ByteReader, ByteWriter},
+    routing::RouteUri,
+    trigger,
+};
+use tokio::sync::mpsc;
+
+use crate::{
+    agent_model::downlink::MapDownlinkHandle,
+    downlink_lifecycle::{OnDownlinkClear, OnDownlinkRemove, OnDownlinkUpdate, OnSynced},
+    event_handler::{ActionContext, HandlerAction, HandlerFuture, StepResult},
+    meta::AgentMetadata,
+    test_context::{dummy_context, no_downlink},
+};
+
+use super::{FeatureFlags, FlagsLifecycle, GetFeatureFlag};
+
+struct FakeAgent;
+
+const CONFIG: AgentConfig = AgentConfig::DEFAULT;
+const NODE_URI: &str = "/node";
+
+fn make_uri() -> RouteUri {
+    RouteUri::try_from(NODE_URI).expect("Bad URI.")
+}
+
+fn make_meta<'a>(
+    uri: &'a RouteUri,
+    route_params: &'a HashMap<String, String>,
+) -> AgentMetadata<'a> {
+    AgentMetadata::new(uri, route_params, &CONFIG)
+}
+
+fn get_flag<T: swimos_form::Form>(
+    action_context: &mut ActionContext<'_, FakeAgent>,
+    name: &str,
+) -> Option<T> {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+
+    let mut handler = GetFeatureFlag::<T>::new(name);
+    match handler.step(action_context, meta, &FakeAgent) {
+        StepResult::Complete { result, .. } => result,
+        StepResult::Fail(err) => panic!("Handler failed: {}", err),
+        StepResult::Continue { .. } => panic!("Handler did not complete."),
+    }
+}
+
+/// Attach a handle to the cache that will never report that the downlink has stopped.
+fn with_live_handle(flags: &FeatureFlags) -> Arc<AtomicU8> {
+    let state = Arc::new(AtomicU8::new(0));
+    let (tx, _rx) = mpsc::unbounded_channel();
+    let (stop_tx, _stop_rx) = trigger::trigger();
+    let address = Address::text(None, FEATURE_FLAGS_NODE, FEATURE_FLAGS_LANE);
+    flags.set_handle(MapDownlinkHandle::new(address, tx, stop_tx, &state));
+    state
+}
+
+#[test]
+fn no_cache_resolves_to_none() {
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let mut action_context = dummy_context(&mut join_lane_init, &mut ad_hoc_buffer);
+
+    assert_eq!(get_flag::<bool>(&mut action_context, "dark_mode"), None);
+}
+
+#[test]
+fn lifecycle_maintains_cache() {
+    let flags = FeatureFlags::default();
+    let lifecycle = FlagsLifecycle(flags.clone());
+
+    let synced = HashMap::from([
+        (Text::new("dark_mode"), Value::from(true)),
+        (Text::new("layout"), Value::text("compact")),
+    ]);
+    let _ = OnSynced::<_, FakeAgent>::on_synced(&lifecycle, &synced);
+    assert_eq!(flags.get("dark_mode"), Some(Value::from(true)));
+    assert_eq!(flags.get("layout"), Some(Value::text("compact")));
+
+    let new_value = Value::text("wide");
+    let _ = OnDownlinkUpdate::<_, _, FakeAgent>::on_update(
+        &lifecycle,
+        Text::new("layout"),
+        &HashMap::new(),
+        None,
+        &new_value,
+    );
+    assert_eq!(flags.get("layout"), Some(Value::text("wide")));
+
+    let _ = OnDownlinkRemove::<_, _, FakeAgent>::on_remove(
+        &lifecycle,
+        Text::new("dark_mode"),
+        &HashMap::new(),
+        Value::from(true),
+    );
+    assert_eq!(flags.get("dark_mode"), None);
+
+    let _ = OnDownlinkClear::<_, _, FakeAgent>::on_clear(&lifecycle, HashMap::new());
+    assert_eq!(flags.get("layout"), None);
+}
+
+#[test]
+fn reads_typed_flags_from_cache() {
+    let flags = FeatureFlags::default();
+    let _state = with_live_handle(&flags);
+    flags.with_flags(|map| {
+        map.insert(Text::new("dark_mode"), Value::from(true));
+        map.insert(Text::new("rollout"), Value::from(25));
+    });
+
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let mut action_context =
+        dummy_context(&mut join_lane_init, &mut ad_hoc_buffer).with_feature_flags(&flags);
+
+    assert_eq!(
+        get_flag::<bool>(&mut action_context, "dark_mode"),
+        Some(true)
+    );
+    assert_eq!(get_flag::<i32>(&mut action_context, "rollout"), Some(25));
+    assert_eq!(get_flag::<bool>(&mut action_context, "rollout"), None);
+    assert_eq!(get_flag::<bool>(&mut action_context, "missing"), None);
+}
+
+type OpenedDownlink = (Option<String>, String, String, DownlinkKind);
+
+#[derive(Default)]
+struct OpenDownlinkContext {
+    opened: Mutex<Vec<OpenedDownlink>>,
+}
+
+impl AgentContext for OpenDownlinkContext {
+    fn ad_hoc_commands(&self) -> BoxFuture<'static, Result<ByteWriter, DownlinkRuntimeError>> {
+        panic!("Unexpected runtime interaction.");
+    }
+
+    fn add_lane(
+        &self,
+        _name: &str,
+        _lane_kind: WarpLaneKind,
+        _config: LaneConfig,
+    ) -> BoxFuture<'static, Result<(ByteWriter, ByteReader), AgentRuntimeError>> {
+        panic!("Unexpected runtime interaction.");
+    }
+
+    fn open_downlink(
+        &self,
+        host: Option<&str>,
+        node: &str,
+        lane: &str,
+        kind: DownlinkKind,
+    ) -> BoxFuture<'static, Result<(ByteWriter, ByteReader), DownlinkRuntimeError>> {
+        self.opened.lock().unwrap().push((
+            host.map(ToString::to_string),
+            node.to_string(),
+            lane.to_string(),
+            kind,
+        ));
+        pending().boxed()
+    }
+
+    fn add_store(
+        &self,
+        _name: &str,
+        _kind: StoreKind,
+    ) -> BoxFuture<'static, Result<(ByteWriter, ByteReader), OpenStoreError>> {
+        panic!("Unexpected runtime interaction.");
+    }
+
+    fn add_http_lane(
+        &self,
+        _name: &str,
+    ) -> BoxFuture<'static, Result<HttpLaneRequestChannel, AgentRuntimeError>> {
+        panic!("Unexpected runtime interaction.");
+    }
+}
+
+#[test]
+fn downlink_opened_on_first_access() {
+    let flags = FeatureFlags::default();
+    let agent_context = OpenDownlinkContext::default();
+    let suspended: FuturesUnordered<HandlerFuture<FakeAgent>> = FuturesUnordered::new();
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+
+    let mut action_context = ActionContext::new(
+        &suspended,
+        &agent_context,
+        &no_downlink,
+        &mut join_lane_init,
+        &mut ad_hoc_buffer,
+    )
+    .with_feature_flags(&flags);
+
+    assert_eq!(get_flag::<bool>(&mut action_context, "dark_mode"), None);
+    assert_eq!(get_flag::<bool>(&mut action_context, "dark_mode"), None);
+
+    let opened = agent_context.opened.lock().unwrap();
+    assert_eq!(
+        *opened,
+        vec![(
+            None,
+            FEATURE_FLAGS_NODE.to_string(),
+            FEATURE_FLAGS_LANE.to_string(),
+            DownlinkKind::Map
+        )]
+    );
+    assert_eq!(suspended.len(), 1);
+}
@@ -26,6 +26,15 @@ pub trait AgentItem {
     fn id(&self) -> u64;
 }
 
+/// Trait for agent items that keep a running count of the events that have modified their state.
+/// This is used to drive derived metrics (such as event rates) without the need to attach an event
+/// handler to every modification of the item.
+pub trait EventCount: AgentItem {
+    /// The total number of events that have modified the item since the agent started (restoring
+    /// the state of the item from persistence does not count as an event).
+    fn event_count(&self) -> u64;
+}
+
 pub trait ValueItem<T>: AgentItem {
     fn read_with_prev<F, R>(&self, f: F) -> R
     where
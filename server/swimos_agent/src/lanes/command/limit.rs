@@ -0,0 +1,204 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::VecDeque, num::NonZeroUsize, time::Duration};
+
+use tokio::time::Instant;
+
+/// The action to take when a command is received by a rate limited command lane and its backlog is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Discard the oldest command in the backlog to make room for the new command.
+    #[default]
+    DropOldest,
+    /// Discard the new command.
+    DropNewest,
+    /// Fail the event handler that attempted to send the command (this will cause the agent to stop).
+    Error,
+}
+
+/// Limits on the rate at which a command lane will invoke its `on_command` event handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandLaneLimits {
+    /// The minimum period between consecutive commands being delivered to the lane.
+    pub min_interval: Duration,
+    /// The maximum number of commands that will be held back while waiting to be delivered.
+    pub max_backlog: NonZeroUsize,
+    /// The action to take when the backlog is full.
+    pub overflow: OverflowPolicy,
+}
+
+impl CommandLaneLimits {
+    /// # Arguments
+    /// * `min_interval` - The minimum period between consecutive commands being delivered to the lane.
+    /// * `max_backlog` - The maximum number of commands that will be held back.
+    pub fn new(min_interval: Duration, max_backlog: NonZeroUsize) -> Self {
+        CommandLaneLimits {
+            min_interval,
+            max_backlog,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+
+    /// Set the action to take when the backlog is full.
+    pub fn with_overflow(self, overflow: OverflowPolicy) -> Self {
+        CommandLaneLimits { overflow, ..self }
+    }
+}
+
+const DEFAULT_MAX_BACKLOG: usize = 1024;
+
+impl Default for CommandLaneLimits {
+    /// No minimum interval between commands and a backlog of 1024 commands.
+    fn default() -> Self {
+        CommandLaneLimits::new(
+            Duration::ZERO,
+            NonZeroUsize::new(DEFAULT_MAX_BACKLOG).expect("Backlog size must be non-zero."),
+        )
+    }
+}
+
+/// The result of offering a command to a [`RateLimiter`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum Admission<T> {
+    /// The command can be delivered to the lane immediately.
+    Deliver(T),
+    /// The command has been added to the backlog. If a deadline is included, a timer must be
+    /// scheduled to release the backlog at that time.
+    Queued(Option<Instant>),
+    /// The command was discarded.
+    Dropped,
+    /// The backlog was full and the policy requires that this is treated as an error.
+    Overflow,
+}
+
+/// Combines all of the commands in a backlog into a single command.
+type Merge<T> = fn(&mut VecDeque<T>) -> Option<T>;
+
+/// Holds back commands that arrive at a command lane faster than its configured rate.
+#[derive(Debug)]
+pub struct RateLimiter<T> {
+    limits: CommandLaneLimits,
+    backlog: VecDeque<T>,
+    last_delivery: Option<Instant>,
+    timer_scheduled: bool,
+    merge: Option<Merge<T>>,
+}
+
+impl<T> RateLimiter<T> {
+    pub fn new(limits: CommandLaneLimits) -> Self {
+        RateLimiter {
+            limits,
+            backlog: VecDeque::new(),
+            last_delivery: None,
+            timer_scheduled: false,
+            merge: None,
+        }
+    }
+
+    /// Whether the commands in the backlog are delivered together.
+    pub fn is_batched(&self) -> bool {
+        self.merge.is_some()
+    }
+
+    pub fn limits(&self) -> &CommandLaneLimits {
+        &self.limits
+    }
+
+    fn next_slot(&self) -> Instant {
+        let RateLimiter {
+            limits,
+            last_delivery,
+            ..
+        } = self;
+        match last_delivery {
+            Some(t) => *t + limits.min_interval,
+            None => Instant::now(),
+        }
+    }
+
+    /// Offer a new command to the limiter. In batch mode, commands are never delivered immediately so that
+    /// any other commands that arrive before the timer fires are delivered with them.
+    pub fn admit(&mut self, command: T, now: Instant) -> Admission<T> {
+        if !self.is_batched() && self.backlog.is_empty() && now >= self.next_slot() {
+            self.last_delivery = Some(now);
+            return Admission::Deliver(command);
+        }
+        let RateLimiter {
+            limits,
+            backlog,
+            timer_scheduled,
+            ..
+        } = self;
+        if backlog.len() >= limits.max_backlog.get() {
+            match limits.overflow {
+                OverflowPolicy::DropOldest => {
+                    backlog.pop_front();
+                }
+                OverflowPolicy::DropNewest => return Admission::Dropped,
+                OverflowPolicy::Error => return Admission::Overflow,
+            }
+        }
+        backlog.push_back(command);
+        if *timer_scheduled {
+            Admission::Queued(None)
+        } else {
+            *timer_scheduled = true;
+            Admission::Queued(Some(self.next_slot()))
+        }
+    }
+
+    /// Called when a scheduled timer fires. This returns the next command to deliver (in batch mode, this
+    /// is the entire backlog) and, if there are still commands in the backlog, the time at which the next
+    /// timer should fire.
+    pub fn release(&mut self, now: Instant) -> (Option<T>, Option<Instant>) {
+        self.timer_scheduled = false;
+        let command = match self.merge {
+            Some(merge) => merge(&mut self.backlog),
+            None => self.backlog.pop_front(),
+        };
+        if command.is_some() {
+            self.last_delivery = Some(now);
+        }
+        if self.backlog.is_empty() {
+            (command, None)
+        } else {
+            self.timer_scheduled = true;
+            (command, Some(self.next_slot()))
+        }
+    }
+
+    /// The number of commands waiting to be delivered.
+    pub fn backlog_len(&self) -> usize {
+        self.backlog.len()
+    }
+}
+
+impl<T> RateLimiter<Vec<T>> {
+    /// Create a limiter that delivers its entire backlog as a single batch each time its timer fires.
+    pub fn batched(limits: CommandLaneLimits) -> Self {
+        RateLimiter {
+            merge: Some(merge_batch),
+            ..RateLimiter::new(limits)
+        }
+    }
+}
+
+fn merge_batch<T>(backlog: &mut VecDeque<Vec<T>>) -> Option<Vec<T>> {
+    if backlog.is_empty() {
+        None
+    } else {
+        Some(backlog.drain(..).flatten().collect())
+    }
+}
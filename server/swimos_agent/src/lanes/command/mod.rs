@@ -15,42 +15,60 @@
 use std::cell::{Cell, RefCell};
 
 use bytes::BytesMut;
-use futures::{Stream, StreamExt};
+use futures::{FutureExt, Stream, StreamExt};
 use static_assertions::assert_impl_all;
-use swimos_agent_protocol::{encoding::lane::ValueLaneResponseEncoder, LaneResponse};
+use swimos_agent_protocol::{
+    encoding::lane::ValueLaneResponseEncoder, DecodePayload, LaneResponse,
+};
 use swimos_api::error::FrameIoError;
-use swimos_form::{read::RecognizerReadable, write::StructuralWritable};
 use swimos_recon::parser::AsyncParseError;
+use tokio::time::Instant;
 use tokio_util::codec::Encoder;
 
 use crate::{
     agent_model::WriteResult,
     event_handler::{
-        ActionContext, AndThen, Decode, HandlerAction, HandlerActionExt, HandlerTrans,
-        Modification, StepResult,
+        ActionContext, AndThen, Decode, EventHandlerError, HandlerAction, HandlerActionExt,
+        HandlerTrans, LocalBoxEventHandler, Modification, Spawner, StepResult,
     },
-    item::AgentItem,
+    item::{AgentItem, EventCount},
     meta::AgentMetadata,
 };
 
 use super::{LaneItem, ProjTransform};
 
 pub mod lifecycle;
+mod limit;
 #[cfg(test)]
 mod tests;
 
+use limit::{Admission, RateLimiter};
+pub use limit::{CommandLaneLimits, OverflowPolicy};
+
 /// Model of a command lane. An event is triggered when a command is received (either externally or
 /// internally) but the lane does not maintain any record of its state.
+///
+/// A command lane can optionally be rate limited (see [`CommandLane::with_limits`]). Commands that arrive
+/// faster than the permitted rate are held in a bounded backlog and delivered, in order, by the agent task
+/// as the limit allows.
+///
+/// A [`BatchCommandLane`] (see [`CommandLane::batched`]) always holds commands in its backlog and, each time
+/// its limit allows, delivers the entire backlog to the `on_command` event handler as a single [`Vec`].
 #[derive(Debug)]
 pub struct CommandLane<T> {
     id: u64,
     prev_command: RefCell<Option<T>>,
     dirty: Cell<bool>,
+    events: Cell<u64>,
+    limiter: Option<RefCell<RateLimiter<T>>>,
     //sync_queue: RefCell<VecDeque<Uuid>>, TODO Is syncing reasonable?
 }
 
 assert_impl_all!(CommandLane<()>: Send);
 
+/// A command lane that delivers the commands that it receives in batches (see [`CommandLane::batched`]).
+pub type BatchCommandLane<T> = CommandLane<Vec<T>>;
+
 impl<T> CommandLane<T> {
     /// Create a command lane with the specified ID (this needs to be unique within an agent).
     pub fn new(id: u64) -> Self {
@@ -58,19 +76,57 @@ impl<T> CommandLane<T> {
             id,
             prev_command: Default::default(),
             dirty: Cell::new(false),
+            events: Cell::new(0),
+            limiter: None,
         }
     }
 
+    /// Create a rate limited command lane with the specified ID (this needs to be unique within an agent).
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the lane.
+    /// * `limits` - The maximum rate for the lane and how to deal with commands that exceed it.
+    pub fn with_limits(id: u64, limits: CommandLaneLimits) -> Self {
+        CommandLane::with_limiter(id, RateLimiter::new(limits))
+    }
+
+    fn with_limiter(id: u64, limiter: RateLimiter<T>) -> Self {
+        CommandLane {
+            id,
+            prev_command: Default::default(),
+            dirty: Cell::new(false),
+            events: Cell::new(0),
+            limiter: Some(RefCell::new(limiter)),
+        }
+    }
+
+    /// The rate limits for the lane, if it has any.
+    pub fn limits(&self) -> Option<CommandLaneLimits> {
+        self.limiter
+            .as_ref()
+            .map(|limiter| *limiter.borrow().limits())
+    }
+
+    /// The number of commands that are waiting to be delivered to a rate limited lane.
+    pub fn backlog_len(&self) -> usize {
+        self.limiter
+            .as_ref()
+            .map(|limiter| limiter.borrow().backlog_len())
+            .unwrap_or_default()
+    }
+
     /// Execute a command against the lane.
     pub(crate) fn command(&self, value: T) {
         let CommandLane {
             prev_command,
             dirty,
+            events,
             ..
         } = self;
         let mut guard = prev_command.borrow_mut();
         *guard = Some(value);
         dirty.set(true);
+        events.set(events.get() + 1);
     }
 
     /// Consume the previous command that was executed against the lane.
@@ -84,6 +140,28 @@ impl<T> CommandLane<T> {
     }
 }
 
+impl<T> CommandLane<Vec<T>> {
+    /// Create a command lane that delivers commands in batches. Commands are held in the backlog until the
+    /// rate limit allows them to be delivered and all commands in the backlog are then delivered together.
+    /// With the [default limits](`CommandLaneLimits::default`), this will deliver all of the commands that
+    /// are received before the agent next polls for timer events.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the lane.
+    /// * `limits` - The maximum rate for the lane and how to deal with commands that exceed the backlog.
+    pub fn batched(id: u64, limits: CommandLaneLimits) -> Self {
+        CommandLane::with_limiter(id, RateLimiter::batched(limits))
+    }
+
+    /// Whether the lane delivers commands in batches.
+    pub fn is_batched(&self) -> bool {
+        self.limiter
+            .as_ref()
+            .map(|limiter| limiter.borrow().is_batched())
+            .unwrap_or_default()
+    }
+}
+
 const INFALLIBLE_SER: &str = "Serializing a command to recon should be infallible.";
 
 ///  An [event handler](crate::event_handler::EventHandler) that feeds a command to the command lane.
@@ -104,12 +182,16 @@ impl<Context, T> DoCommand<Context, T> {
     }
 }
 
-impl<Context, T> HandlerAction<Context> for DoCommand<Context, T> {
+impl<Context, T> HandlerAction<Context> for DoCommand<Context, T>
+where
+    Context: 'static,
+    T: 'static,
+{
     type Completion = ();
 
     fn step(
         &mut self,
-        _action_context: &mut ActionContext<Context>,
+        action_context: &mut ActionContext<Context>,
         _meta: AgentMetadata,
         context: &Context,
     ) -> StepResult<Self::Completion> {
@@ -119,17 +201,108 @@ impl<Context, T> HandlerAction<Context> for DoCommand<Context, T> {
         } = self;
         if let Some(cmd) = command.take() {
             let lane = projection(context);
+            let admission = match &lane.limiter {
+                Some(limiter) => limiter.borrow_mut().admit(cmd, Instant::now()),
+                None => Admission::Deliver(cmd),
+            };
+            match admission {
+                Admission::Deliver(cmd) => {
+                    lane.command(cmd);
+                    StepResult::Complete {
+                        modified_item: Some(Modification::of(lane.id)),
+                        result: (),
+                    }
+                }
+                Admission::Queued(deadline) => {
+                    if let Some(deadline) = deadline {
+                        schedule_release(action_context, *projection, deadline);
+                    }
+                    StepResult::done(())
+                }
+                Admission::Dropped => StepResult::done(()),
+                Admission::Overflow => StepResult::Fail(EventHandlerError::CommandBacklogFull),
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+///  An [event handler](crate::event_handler::EventHandler) that delivers the next command from the backlog of
+/// a rate limited command lane.
+pub struct ReleaseCommand<Context, T> {
+    projection: fn(&Context) -> &CommandLane<T>,
+    done: bool,
+}
+
+impl<Context, T> ReleaseCommand<Context, T> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    pub fn new(projection: fn(&Context) -> &CommandLane<T>) -> Self {
+        ReleaseCommand {
+            projection,
+            done: false,
+        }
+    }
+}
+
+impl<Context, T> HandlerAction<Context> for ReleaseCommand<Context, T>
+where
+    Context: 'static,
+    T: 'static,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<Context>,
+        _meta: AgentMetadata,
+        context: &Context,
+    ) -> StepResult<Self::Completion> {
+        let ReleaseCommand { projection, done } = self;
+        if *done {
+            return StepResult::after_done();
+        }
+        *done = true;
+        let lane = projection(context);
+        let (command, next) = match &lane.limiter {
+            Some(limiter) => limiter.borrow_mut().release(Instant::now()),
+            None => (None, None),
+        };
+        if let Some(deadline) = next {
+            schedule_release(action_context, *projection, deadline);
+        }
+        if let Some(cmd) = command {
             lane.command(cmd);
             StepResult::Complete {
                 modified_item: Some(Modification::of(lane.id)),
                 result: (),
             }
         } else {
-            StepResult::after_done()
+            StepResult::done(())
         }
     }
 }
 
+/// Suspend a timer into the agent task that will deliver the next command from the backlog of the lane.
+fn schedule_release<Context, T>(
+    action_context: &mut ActionContext<Context>,
+    projection: fn(&Context) -> &CommandLane<T>,
+    deadline: Instant,
+) where
+    Context: 'static,
+    T: 'static,
+{
+    let fut = tokio::time::sleep_until(deadline)
+        .map(move |_| {
+            let handler: LocalBoxEventHandler<'static, Context> =
+                Box::new(ReleaseCommand::new(projection));
+            handler
+        })
+        .boxed();
+    action_context.spawn_suspend(fut);
+}
+
 impl<C, T> HandlerTrans<T> for ProjTransform<C, CommandLane<T>> {
     type Out = DoCommand<C, T>;
 
@@ -143,7 +316,7 @@ pub type DecodeAndCommand<C, T> =
     AndThen<Decode<T>, DoCommand<C, T>, ProjTransform<C, CommandLane<T>>>;
 
 /// Create an event handler that will decode an incoming command and apply it to a command lane.
-pub fn decode_and_command<C, T: RecognizerReadable>(
+pub fn decode_and_command<C: 'static, T: DecodePayload + 'static>(
     buffer: BytesMut,
     projection: fn(&C) -> &CommandLane<T>,
 ) -> DecodeAndCommand<C, T> {
@@ -151,7 +324,36 @@ pub fn decode_and_command<C, T: RecognizerReadable>(
     decode.and_then(ProjTransform::new(projection))
 }
 
-impl<T: StructuralWritable> LaneItem for CommandLane<T> {
+/// Transformation that adds a single command to the batch for a [`BatchCommandLane`].
+pub struct BatchTransform<C, T> {
+    projection: fn(&C) -> &BatchCommandLane<T>,
+}
+
+impl<C, T> HandlerTrans<T> for BatchTransform<C, T> {
+    type Out = DoCommand<C, Vec<T>>;
+
+    fn transform(self, input: T) -> Self::Out {
+        let BatchTransform { projection } = self;
+        DoCommand::new(projection, vec![input])
+    }
+}
+
+pub type DecodeAndBatch<C, T> = AndThen<Decode<T>, DoCommand<C, Vec<T>>, BatchTransform<C, T>>;
+
+/// Create an event handler that will decode a single incoming command and add it to the batch for a
+/// batch command lane.
+pub fn decode_and_batch<C: 'static, T: DecodePayload + 'static>(
+    buffer: BytesMut,
+    projection: fn(&C) -> &BatchCommandLane<T>,
+) -> DecodeAndBatch<C, T> {
+    let decode: Decode<T> = Decode::new(buffer);
+    decode.and_then(BatchTransform { projection })
+}
+
+impl<T> LaneItem for CommandLane<T>
+where
+    for<'a> ValueLaneResponseEncoder: Encoder<LaneResponse<&'a T>, Error = std::io::Error>,
+{
     fn write_to_buffer(&self, buffer: &mut BytesMut) -> WriteResult {
         let CommandLane {
             prev_command,
@@ -181,6 +383,12 @@ impl<T> AgentItem for CommandLane<T> {
     }
 }
 
+impl<T> EventCount for CommandLane<T> {
+    fn event_count(&self) -> u64 {
+        self.events.get()
+    }
+}
+
 pub async fn init_command_lane<T, In>(
     mut input: In,
 ) -> Result<impl FnOnce(&CommandLane<T>), FrameIoError>
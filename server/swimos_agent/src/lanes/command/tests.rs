@@ -12,26 +12,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::{collections::HashMap, num::NonZeroUsize, time::Duration};
 
 use bytes::BytesMut;
+use futures::{stream::FuturesUnordered, StreamExt};
 use swimos_agent_protocol::{encoding::lane::RawValueLaneResponseDecoder, LaneResponse};
 use swimos_api::agent::AgentConfig;
 use swimos_utilities::routing::RouteUri;
+use tokio::time::Instant;
 use tokio_util::codec::Decoder;
 
 use crate::{
     agent_model::WriteResult,
     event_handler::{
-        check_step::check_is_complete, EventHandlerError, HandlerAction, ModificationFlags,
-        StepResult,
+        check_step::check_is_complete, ActionContext, EventHandlerError, HandlerAction,
+        HandlerFuture, ModificationFlags, StepResult,
+    },
+    lanes::{
+        command::{decode_and_batch, DoCommand},
+        LaneItem,
     },
-    lanes::{command::DoCommand, LaneItem},
     meta::AgentMetadata,
-    test_context::dummy_context,
+    test_context::{dummy_context, no_downlink, DummyAgentContext},
 };
 
-use super::CommandLane;
+use super::{BatchCommandLane, CommandLane, CommandLaneLimits, OverflowPolicy};
 
 const LANE_ID: u64 = 38;
 
@@ -126,3 +131,273 @@ fn command_event_handler() {
         StepResult::Fail(EventHandlerError::SteppedAfterComplete)
     ));
 }
+
+const INTERVAL: Duration = Duration::from_secs(1);
+
+fn limits(max_backlog: usize, overflow: OverflowPolicy) -> CommandLaneLimits {
+    CommandLaneLimits::new(INTERVAL, NonZeroUsize::new(max_backlog).unwrap())
+        .with_overflow(overflow)
+}
+
+impl TestAgent {
+    fn with_limits(limits: CommandLaneLimits) -> Self {
+        Self {
+            lane: CommandLane::with_limits(LANE_ID, limits),
+        }
+    }
+}
+
+fn send(
+    agent: &TestAgent,
+    meta: AgentMetadata<'_>,
+    pending: &FuturesUnordered<HandlerFuture<TestAgent>>,
+    command: i32,
+) -> StepResult<()> {
+    let mut handler = DoCommand::new(TestAgent::LANE, command);
+    handler.step(
+        &mut ActionContext::new(
+            pending,
+            &DummyAgentContext,
+            &no_downlink,
+            &mut HashMap::new(),
+            &mut BytesMut::new(),
+        ),
+        meta,
+        agent,
+    )
+}
+
+async fn release<Agent>(
+    agent: &Agent,
+    meta: AgentMetadata<'_>,
+    pending: &mut FuturesUnordered<HandlerFuture<Agent>>,
+) -> StepResult<()> {
+    let mut handler = pending
+        .next()
+        .await
+        .expect("No release timer was scheduled.");
+    handler.step(
+        &mut ActionContext::new(
+            &*pending,
+            &DummyAgentContext,
+            &no_downlink,
+            &mut HashMap::new(),
+            &mut BytesMut::new(),
+        ),
+        meta,
+        agent,
+    )
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limited_commands_are_delayed() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_limits(limits(8, OverflowPolicy::DropOldest));
+
+    let mut pending = FuturesUnordered::new();
+    let result = send(&agent, meta, &pending, 1);
+    check_is_complete(result, LANE_ID, &(), ModificationFlags::all());
+    assert_eq!(agent.lane.with_prev(Clone::clone), Some(1));
+
+    for i in 2..5 {
+        assert!(matches!(
+            send(&agent, meta, &pending, i),
+            StepResult::Complete {
+                modified_item: None,
+                ..
+            }
+        ));
+    }
+    assert_eq!(agent.lane.backlog_len(), 3);
+    assert_eq!(pending.len(), 1);
+
+    let start = Instant::now();
+    for i in 2..5 {
+        let result = release(&agent, meta, &mut pending).await;
+        check_is_complete(result, LANE_ID, &(), ModificationFlags::all());
+        assert_eq!(agent.lane.with_prev(Clone::clone), Some(i));
+        assert_eq!(start.elapsed(), INTERVAL * (i as u32 - 1));
+    }
+    assert_eq!(agent.lane.backlog_len(), 0);
+    assert!(pending.is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limited_drop_oldest() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_limits(limits(2, OverflowPolicy::DropOldest));
+
+    let mut pending = FuturesUnordered::new();
+    for i in 1..5 {
+        assert!(matches!(
+            send(&agent, meta, &pending, i),
+            StepResult::Complete { .. }
+        ));
+    }
+    assert_eq!(agent.lane.backlog_len(), 2);
+
+    release(&agent, meta, &mut pending).await;
+    assert_eq!(agent.lane.with_prev(Clone::clone), Some(3));
+    release(&agent, meta, &mut pending).await;
+    assert_eq!(agent.lane.with_prev(Clone::clone), Some(4));
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limited_drop_newest() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_limits(limits(2, OverflowPolicy::DropNewest));
+
+    let mut pending = FuturesUnordered::new();
+    for i in 1..5 {
+        assert!(matches!(
+            send(&agent, meta, &pending, i),
+            StepResult::Complete { .. }
+        ));
+    }
+    assert_eq!(agent.lane.backlog_len(), 2);
+
+    release(&agent, meta, &mut pending).await;
+    assert_eq!(agent.lane.with_prev(Clone::clone), Some(2));
+    release(&agent, meta, &mut pending).await;
+    assert_eq!(agent.lane.with_prev(Clone::clone), Some(3));
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limited_overflow_error() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_limits(limits(1, OverflowPolicy::Error));
+
+    let pending = FuturesUnordered::new();
+    assert!(matches!(
+        send(&agent, meta, &pending, 1),
+        StepResult::Complete { .. }
+    ));
+    assert!(matches!(
+        send(&agent, meta, &pending, 2),
+        StepResult::Complete { .. }
+    ));
+    assert!(matches!(
+        send(&agent, meta, &pending, 3),
+        StepResult::Fail(EventHandlerError::CommandBacklogFull)
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limit_resets_after_interval() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_limits(limits(1, OverflowPolicy::DropOldest));
+
+    let pending = FuturesUnordered::new();
+    let result = send(&agent, meta, &pending, 1);
+    check_is_complete(result, LANE_ID, &(), ModificationFlags::all());
+
+    tokio::time::advance(INTERVAL).await;
+
+    let result = send(&agent, meta, &pending, 2);
+    check_is_complete(result, LANE_ID, &(), ModificationFlags::all());
+    assert_eq!(agent.lane.with_prev(Clone::clone), Some(2));
+    assert!(pending.is_empty());
+}
+
+struct BatchAgent {
+    lane: BatchCommandLane<i32>,
+}
+
+impl BatchAgent {
+    const LANE: fn(&BatchAgent) -> &BatchCommandLane<i32> = |agent| &agent.lane;
+
+    fn with_limits(limits: CommandLaneLimits) -> Self {
+        BatchAgent {
+            lane: CommandLane::batched(LANE_ID, limits),
+        }
+    }
+}
+
+fn send_to_batch(
+    agent: &BatchAgent,
+    meta: AgentMetadata<'_>,
+    pending: &FuturesUnordered<HandlerFuture<BatchAgent>>,
+    command: i32,
+) {
+    let mut handler = decode_and_batch(
+        BytesMut::from(command.to_string().as_str()),
+        BatchAgent::LANE,
+    );
+    loop {
+        let result = handler.step(
+            &mut ActionContext::new(
+                pending,
+                &DummyAgentContext,
+                &no_downlink,
+                &mut HashMap::new(),
+                &mut BytesMut::new(),
+            ),
+            meta,
+            agent,
+        );
+        match result {
+            StepResult::Continue { modified_item } => assert!(modified_item.is_none()),
+            StepResult::Fail(err) => panic!("Command failed: {}", err),
+            StepResult::Complete { modified_item, .. } => {
+                assert!(modified_item.is_none());
+                break;
+            }
+        }
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn batched_commands_are_delivered_together() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = BatchAgent::with_limits(CommandLaneLimits::default());
+    assert!(agent.lane.is_batched());
+
+    let mut pending = FuturesUnordered::new();
+    for i in 1..4 {
+        send_to_batch(&agent, meta, &pending, i);
+    }
+    assert_eq!(agent.lane.with_prev(Clone::clone), None);
+    assert_eq!(agent.lane.backlog_len(), 3);
+    assert_eq!(pending.len(), 1);
+
+    let result = release(&agent, meta, &mut pending).await;
+    check_is_complete(result, LANE_ID, &(), ModificationFlags::all());
+    assert_eq!(agent.lane.with_prev(Clone::clone), Some(vec![1, 2, 3]));
+    assert_eq!(agent.lane.backlog_len(), 0);
+    assert!(pending.is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limited_batches() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = BatchAgent::with_limits(limits(2, OverflowPolicy::DropOldest));
+
+    let mut pending = FuturesUnordered::new();
+    for i in 1..5 {
+        send_to_batch(&agent, meta, &pending, i);
+    }
+    assert_eq!(agent.lane.backlog_len(), 2);
+
+    let start = Instant::now();
+    release(&agent, meta, &mut pending).await;
+    assert_eq!(agent.lane.with_prev(Clone::clone), Some(vec![3, 4]));
+
+    send_to_batch(&agent, meta, &pending, 5);
+    release(&agent, meta, &mut pending).await;
+    assert_eq!(agent.lane.with_prev(Clone::clone), Some(vec![5]));
+    assert_eq!(start.elapsed(), INTERVAL);
+}
@@ -20,7 +20,6 @@ use std::{
 use bytes::BytesMut;
 use static_assertions::assert_impl_all;
 use swimos_agent_protocol::{encoding::lane::ValueLaneResponseEncoder, LaneResponse};
-use swimos_form::write::StructuralWritable;
 use tokio_util::codec::Encoder;
 use uuid::Uuid;
 
@@ -56,6 +55,10 @@ impl<T> Default for DemandLaneInner<T> {
 /// a persistent state that can be queried, a demand lane computes a value, on demand, that is
 /// sent on all uplinks attached to it.
 ///
+/// The values are serialized as Recon unless they are [`crate::lanes::Encoded`] values, in which
+/// case they are encoded with the associated codec (for example, `Encoded<RawBytes>` relays
+/// documents that have already been rendered exactly as they were computed).
+///
 /// A demand lane can be cued to produce a value by executing an instance of [`Cue`] (which can be
 /// constructed using the [handler context](`crate::agent_lifecycle::HandlerContext`)).
 #[derive(Debug)]
@@ -97,7 +100,7 @@ const INFALLIBLE_SER: &str = "Serializing a value to recon should be infallible.
 
 impl<T> LaneItem for DemandLane<T>
 where
-    T: StructuralWritable,
+    ValueLaneResponseEncoder: for<'a> Encoder<LaneResponse<&'a T>, Error = std::io::Error>,
 {
     fn write_to_buffer(&self, buffer: &mut BytesMut) -> WriteResult {
         let DemandLane { inner, cued, .. } = self;
@@ -109,7 +112,7 @@ where
         } = &mut *guard;
         if let Some(value) = computed_value {
             if let Some(id) = sync_queue.pop_front() {
-                let value_response = LaneResponse::sync_event(id, value);
+                let value_response = LaneResponse::sync_event(id, &*value);
                 encoder
                     .encode(value_response, buffer)
                     .expect(INFALLIBLE_SER);
@@ -124,7 +127,7 @@ where
                     WriteResult::Done
                 }
             } else if cued.get() {
-                let response = LaneResponse::event(value);
+                let response = LaneResponse::event(&*value);
                 encoder.encode(response, buffer).expect(INFALLIBLE_SER);
                 cued.set(false);
                 *computed_value = None;
@@ -14,7 +14,7 @@
 
 use std::collections::HashMap;
 
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use swimos_agent_protocol::{encoding::lane::RawValueLaneResponseDecoder, LaneResponse};
 use swimos_api::agent::AgentConfig;
 use swimos_utilities::routing::RouteUri;
@@ -30,7 +30,7 @@ use crate::{
     },
     lanes::{
         demand::{Cue, Demand, DemandLaneSync},
-        LaneItem,
+        Encoded, LaneItem, RawBytes,
     },
     meta::AgentMetadata,
     test_context::dummy_context,
@@ -297,6 +297,10 @@ fn read_buffer(buffer: &mut BytesMut) -> Vec<LaneResponse<i32>> {
                     .expect("Bad body."),
             ),
             LaneResponse::Synced(id) => LaneResponse::Synced(id),
+            LaneResponse::SyncedAt(id, version) => LaneResponse::SyncedAt(id, version),
+            LaneResponse::Correlated(id) => LaneResponse::Correlated(id),
+            LaneResponse::Originated(origin) => LaneResponse::Originated(origin),
+            LaneResponse::EventBatch(n) => LaneResponse::EventBatch(n),
         });
     }
     results
@@ -364,3 +368,28 @@ fn write_multiple() {
         ]
     )
 }
+
+#[test]
+fn write_encoded_sync() {
+    let lane = DemandLane::new(LANE_ID);
+    lane.sync(SYNC_ID);
+    lane.inner.borrow_mut().computed_value =
+        Some(Encoded::<RawBytes>::new(Bytes::from_static(b"@value(27)")));
+
+    let mut buffer = BytesMut::new();
+    assert_eq!(lane.write_to_buffer(&mut buffer), WriteResult::Done);
+
+    let mut decoder = RawValueLaneResponseDecoder::default();
+    let mut messages = vec![];
+    while let Some(msg) = decoder.decode(&mut buffer).expect("Decode failed.") {
+        messages.push(msg);
+    }
+
+    assert_eq!(
+        messages,
+        vec![
+            LaneResponse::SyncEvent(SYNC_ID, BytesMut::from(&b"@value(27)"[..])),
+            LaneResponse::Synced(SYNC_ID),
+        ]
+    );
+}
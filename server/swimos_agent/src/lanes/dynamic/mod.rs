@@ -0,0 +1,557 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(test)]
+mod tests;
+
+use std::{cell::RefCell, collections::HashMap, hash::Hash};
+
+use bytes::BytesMut;
+use static_assertions::assert_impl_all;
+use swimos_agent_protocol::{DecodePayload, MapMessage};
+use swimos_api::agent::SyncVersion;
+use swimos_form::read::RecognizerReadable;
+use swimos_model::Text;
+use uuid::Uuid;
+
+use crate::{
+    agent_model::WriteResult,
+    event_handler::{
+        ActionContext, AndThen, Decode, EventHandlerError, HandlerAction, HandlerActionExt,
+        HandlerTrans, Modification, StepResult,
+    },
+    item::AgentItem,
+    meta::AgentMetadata,
+};
+
+use super::{map::DecodeMapMessage, LaneItem, MapLane, ValueLane};
+
+/// A collection of lanes, of the same type, that are opened by event handlers after the agent has
+/// started (see [`HandlerContext::open_value_lane`](crate::agent_lifecycle::HandlerContext::open_value_lane)
+/// and [`HandlerContext::open_map_lane`](crate::agent_lifecycle::HandlerContext::open_map_lane)).
+/// When a field of this type is included in an agent that derives
+/// [`AgentLaneModel`](crate::AgentLaneModel), commands, sync requests and events for any lane in the
+/// collection are dispatched to it by name.
+#[derive(Debug)]
+pub struct DynamicLaneSet<L> {
+    lanes: RefCell<HashMap<Text, L>>,
+}
+
+/// A collection of value lanes that are opened after the agent has started.
+pub type DynamicValueLanes<T> = DynamicLaneSet<ValueLane<T>>;
+/// A collection of map lanes that are opened after the agent has started.
+pub type DynamicMapLanes<K, V> = DynamicLaneSet<MapLane<K, V>>;
+
+assert_impl_all!(DynamicValueLanes<()>: Send);
+assert_impl_all!(DynamicMapLanes<(), ()>: Send);
+
+impl<L> Default for DynamicLaneSet<L> {
+    fn default() -> Self {
+        DynamicLaneSet {
+            lanes: Default::default(),
+        }
+    }
+}
+
+impl<L> DynamicLaneSet<L> {
+    /// Determine whether the collection contains a lane with the specified name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.lanes.borrow().contains_key(name)
+    }
+
+    /// The names of all lanes in the collection.
+    pub fn names(&self) -> Vec<Text> {
+        self.lanes.borrow().keys().cloned().collect()
+    }
+
+    /// Apply a function to the lane with the specified name, if it exists.
+    pub fn with_lane<F, R>(&self, name: &str, f: F) -> Option<R>
+    where
+        F: FnOnce(&L) -> R,
+    {
+        self.lanes.borrow().get(name).map(f)
+    }
+
+    pub(crate) fn insert(&self, name: Text, lane: L) {
+        self.lanes.borrow_mut().insert(name, lane);
+    }
+
+    fn try_with_lane<F, R>(&self, name: &Text, f: F) -> Result<R, EventHandlerError>
+    where
+        F: FnOnce(&L) -> R,
+    {
+        self.with_lane(name.as_str(), f)
+            .ok_or_else(|| EventHandlerError::NoSuchLane(name.clone()))
+    }
+}
+
+impl<L: LaneItem> DynamicLaneSet<L> {
+    /// If the state of the named lane has changed, write an event into the buffer. There will be no
+    /// result if the collection does not contain the lane.
+    pub fn write_to_buffer(&self, name: &str, buffer: &mut BytesMut) -> Option<WriteResult> {
+        self.with_lane(name, |lane| lane.write_to_buffer(buffer))
+    }
+}
+
+impl<T> DynamicValueLanes<T> {
+    /// Read the state of the named value lane, if it exists.
+    pub fn read<F, R>(&self, name: &str, f: F) -> Option<R>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.with_lane(name, |lane| lane.read(f))
+    }
+}
+
+impl<K, V> DynamicMapLanes<K, V>
+where
+    K: Clone + Eq + Hash,
+{
+    /// Read the state of the named map lane, if it exists.
+    pub fn get_map<F, R>(&self, name: &str, f: F) -> Option<R>
+    where
+        F: FnOnce(&HashMap<K, V>) -> R,
+    {
+        self.with_lane(name, |lane| lane.get_map(f))
+    }
+}
+
+fn complete<T>(
+    result: Result<(u64, T), EventHandlerError>,
+    modified: fn(u64) -> Modification,
+) -> StepResult<T> {
+    match result {
+        Ok((id, result)) => StepResult::Complete {
+            modified_item: Some(modified(id)),
+            result,
+        },
+        Err(err) => StepResult::Fail(err),
+    }
+}
+
+/// A [`HandlerAction`] that adds a lane, that has been registered with the agent task, to a
+/// collection of dynamic lanes.
+pub(crate) struct AttachDynamicLane<C, L> {
+    projection: for<'a> fn(&'a C) -> &'a DynamicLaneSet<L>,
+    inner: Option<(Text, L)>,
+}
+
+impl<C, L> AttachDynamicLane<C, L> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the collection of lanes.
+    /// * `lane` - The name of the lane and the lane itself (if it was opened successfully).
+    pub(crate) fn new(
+        projection: for<'a> fn(&'a C) -> &'a DynamicLaneSet<L>,
+        lane: Option<(Text, L)>,
+    ) -> Self {
+        AttachDynamicLane {
+            projection,
+            inner: lane,
+        }
+    }
+}
+
+impl<C, L: AgentItem> HandlerAction<C> for AttachDynamicLane<C, L> {
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let AttachDynamicLane { projection, inner } = self;
+        match inner.take() {
+            Some((name, lane)) => {
+                let id = lane.id();
+                projection(context).insert(name, lane);
+                StepResult::Complete {
+                    modified_item: Some(Modification::no_trigger(id)),
+                    result: (),
+                }
+            }
+            None => StepResult::done(()),
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will get the value of a dynamic
+/// value lane.
+pub struct DynamicValueLaneGet<C, T> {
+    projection: for<'a> fn(&'a C) -> &'a DynamicValueLanes<T>,
+    name: Option<Text>,
+}
+
+impl<C, T> DynamicValueLaneGet<C, T> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the collection of lanes.
+    /// * `name` - The name of the lane.
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a DynamicValueLanes<T>, name: Text) -> Self {
+        DynamicValueLaneGet {
+            projection,
+            name: Some(name),
+        }
+    }
+}
+
+impl<C, T: Clone> HandlerAction<C> for DynamicValueLaneGet<C, T> {
+    type Completion = T;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let DynamicValueLaneGet { projection, name } = self;
+        if let Some(name) = name.take() {
+            match projection(context).try_with_lane(&name, |lane| lane.read(T::clone)) {
+                Ok(value) => StepResult::done(value),
+                Err(err) => StepResult::Fail(err),
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will set the value of a dynamic
+/// value lane.
+pub struct DynamicValueLaneSet<C, T> {
+    projection: for<'a> fn(&'a C) -> &'a DynamicValueLanes<T>,
+    inner: Option<(Text, T)>,
+}
+
+impl<C, T> DynamicValueLaneSet<C, T> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the collection of lanes.
+    /// * `name` - The name of the lane.
+    /// * `value` - The new value for the lane.
+    pub fn new(
+        projection: for<'a> fn(&'a C) -> &'a DynamicValueLanes<T>,
+        name: Text,
+        value: T,
+    ) -> Self {
+        DynamicValueLaneSet {
+            projection,
+            inner: Some((name, value)),
+        }
+    }
+}
+
+impl<C, T> HandlerAction<C> for DynamicValueLaneSet<C, T> {
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let DynamicValueLaneSet { projection, inner } = self;
+        if let Some((name, value)) = inner.take() {
+            let result = projection(context).try_with_lane(&name, |lane| {
+                lane.set(value);
+                (lane.id(), ())
+            });
+            complete(result, Modification::of)
+        } else {
+            StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will request a sync from a dynamic
+/// value lane.
+pub struct DynamicValueLaneSync<C, T> {
+    projection: for<'a> fn(&'a C) -> &'a DynamicValueLanes<T>,
+    inner: Option<(Text, Uuid)>,
+}
+
+impl<C, T> DynamicValueLaneSync<C, T> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the collection of lanes.
+    /// * `name` - The name of the lane.
+    /// * `id` - The ID of the remote that requested the sync.
+    pub fn new(
+        projection: for<'a> fn(&'a C) -> &'a DynamicValueLanes<T>,
+        name: Text,
+        id: Uuid,
+    ) -> Self {
+        DynamicValueLaneSync {
+            projection,
+            inner: Some((name, id)),
+        }
+    }
+}
+
+impl<C, T> HandlerAction<C> for DynamicValueLaneSync<C, T> {
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let DynamicValueLaneSync { projection, inner } = self;
+        if let Some((name, id)) = inner.take() {
+            let result = projection(context).try_with_lane(&name, |lane| {
+                lane.sync(id);
+                (lane.id(), ())
+            });
+            complete(result, Modification::no_trigger)
+        } else {
+            StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+        }
+    }
+}
+
+/// Wrapper to allow a projection to a collection of dynamic lanes, along with the name of one of
+/// the lanes, to be exposed as an event handler transform.
+pub struct DynamicProjTransform<C, L> {
+    projection: for<'a> fn(&'a C) -> &'a DynamicLaneSet<L>,
+    name: Text,
+}
+
+impl<C, L> DynamicProjTransform<C, L> {
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a DynamicLaneSet<L>, name: Text) -> Self {
+        DynamicProjTransform { projection, name }
+    }
+}
+
+impl<C, T> HandlerTrans<T> for DynamicProjTransform<C, ValueLane<T>> {
+    type Out = DynamicValueLaneSet<C, T>;
+
+    fn transform(self, input: T) -> Self::Out {
+        let DynamicProjTransform { projection, name } = self;
+        DynamicValueLaneSet::new(projection, name, input)
+    }
+}
+
+pub type DynamicDecodeAndSet<C, T> =
+    AndThen<Decode<T>, DynamicValueLaneSet<C, T>, DynamicProjTransform<C, ValueLane<T>>>;
+
+/// Create an event handler that will decode an incoming command and set the value into a dynamic
+/// value lane.
+pub fn decode_and_set<C, T: DecodePayload>(
+    buffer: BytesMut,
+    projection: fn(&C) -> &DynamicValueLanes<T>,
+    name: &str,
+) -> DynamicDecodeAndSet<C, T> {
+    let decode: Decode<T> = Decode::new(buffer);
+    decode.and_then(DynamicProjTransform::new(projection, Text::new(name)))
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will apply an update, removal or
+/// clear operation to a dynamic map lane.
+pub struct DynamicMapLaneApply<C, K, V> {
+    projection: for<'a> fn(&'a C) -> &'a DynamicMapLanes<K, V>,
+    inner: Option<(Text, MapMessage<K, V>)>,
+}
+
+impl<C, K, V> DynamicMapLaneApply<C, K, V> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the collection of lanes.
+    /// * `name` - The name of the lane.
+    /// * `message` - The operation to apply to the lane.
+    pub fn new(
+        projection: for<'a> fn(&'a C) -> &'a DynamicMapLanes<K, V>,
+        name: Text,
+        message: MapMessage<K, V>,
+    ) -> Self {
+        DynamicMapLaneApply {
+            projection,
+            inner: Some((name, message)),
+        }
+    }
+}
+
+impl<C, K, V> HandlerAction<C> for DynamicMapLaneApply<C, K, V>
+where
+    K: Clone + Eq + Hash,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let DynamicMapLaneApply { projection, inner } = self;
+        if let Some((name, message)) = inner.take() {
+            let result = projection(context).try_with_lane(&name, |lane| {
+                match message {
+                    MapMessage::Update { key, value } => lane.update(key, value),
+                    MapMessage::Remove { key } => lane.remove(&key),
+                    MapMessage::Clear => lane.clear(),
+                    _ => {}
+                }
+                (lane.id(), ())
+            });
+            complete(result, Modification::of)
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will get an entry from a dynamic
+/// map lane.
+pub struct DynamicMapLaneGet<C, K, V> {
+    projection: for<'a> fn(&'a C) -> &'a DynamicMapLanes<K, V>,
+    inner: Option<(Text, K)>,
+}
+
+impl<C, K, V> DynamicMapLaneGet<C, K, V> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the collection of lanes.
+    /// * `name` - The name of the lane.
+    /// * `key` - The key to fetch.
+    pub fn new(
+        projection: for<'a> fn(&'a C) -> &'a DynamicMapLanes<K, V>,
+        name: Text,
+        key: K,
+    ) -> Self {
+        DynamicMapLaneGet {
+            projection,
+            inner: Some((name, key)),
+        }
+    }
+}
+
+impl<C, K, V> HandlerAction<C> for DynamicMapLaneGet<C, K, V>
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+{
+    type Completion = Option<V>;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let DynamicMapLaneGet { projection, inner } = self;
+        if let Some((name, key)) = inner.take() {
+            match projection(context).try_with_lane(&name, |lane| lane.get(&key, |v| v.cloned())) {
+                Ok(value) => StepResult::done(value),
+                Err(err) => StepResult::Fail(err),
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will request a sync from a dynamic
+/// map lane.
+pub struct DynamicMapLaneSync<C, K, V> {
+    projection: for<'a> fn(&'a C) -> &'a DynamicMapLanes<K, V>,
+    inner: Option<(Text, Uuid, Option<SyncVersion>)>,
+}
+
+impl<C, K, V> DynamicMapLaneSync<C, K, V> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the collection of lanes.
+    /// * `name` - The name of the lane.
+    /// * `id` - The ID of the remote that requested the sync.
+    pub fn new(
+        projection: for<'a> fn(&'a C) -> &'a DynamicMapLanes<K, V>,
+        name: Text,
+        id: Uuid,
+    ) -> Self {
+        DynamicMapLaneSync {
+            projection,
+            inner: Some((name, id, None)),
+        }
+    }
+
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the collection of lanes.
+    /// * `name` - The name of the lane.
+    /// * `id` - The ID of the remote that requested the sync.
+    /// * `since` - The version of the state of the lane that the remote already holds.
+    pub fn since(
+        projection: for<'a> fn(&'a C) -> &'a DynamicMapLanes<K, V>,
+        name: Text,
+        id: Uuid,
+        since: SyncVersion,
+    ) -> Self {
+        DynamicMapLaneSync {
+            projection,
+            inner: Some((name, id, Some(since))),
+        }
+    }
+}
+
+impl<C, K, V> HandlerAction<C> for DynamicMapLaneSync<C, K, V>
+where
+    K: Clone + Eq + Hash,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let DynamicMapLaneSync { projection, inner } = self;
+        if let Some((name, id, since)) = inner.take() {
+            let result = projection(context).try_with_lane(&name, |lane| {
+                match since {
+                    Some(since) => lane.sync_since(id, since),
+                    None => lane.sync(id),
+                }
+                (lane.id(), ())
+            });
+            complete(result, Modification::no_trigger)
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+impl<C, K, V> HandlerTrans<MapMessage<K, V>> for DynamicProjTransform<C, MapLane<K, V>> {
+    type Out = DynamicMapLaneApply<C, K, V>;
+
+    fn transform(self, input: MapMessage<K, V>) -> Self::Out {
+        let DynamicProjTransform { projection, name } = self;
+        DynamicMapLaneApply::new(projection, name, input)
+    }
+}
+
+pub type DynamicDecodeAndApply<C, K, V> = AndThen<
+    DecodeMapMessage<K, V>,
+    DynamicMapLaneApply<C, K, V>,
+    DynamicProjTransform<C, MapLane<K, V>>,
+>;
+
+/// Create an event handler that will decode an incoming map message and apply the value into a
+/// dynamic map lane.
+pub fn decode_and_apply<C, K, V>(
+    message: MapMessage<BytesMut, BytesMut>,
+    projection: fn(&C) -> &DynamicMapLanes<K, V>,
+    name: &str,
+) -> DynamicDecodeAndApply<C, K, V>
+where
+    K: Clone + Eq + Hash + RecognizerReadable,
+    V: RecognizerReadable,
+{
+    let decode: DecodeMapMessage<K, V> = DecodeMapMessage::new(message);
+    decode.and_then(DynamicProjTransform::new(projection, Text::new(name)))
+}
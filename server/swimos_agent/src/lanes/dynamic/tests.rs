@@ -0,0 +1,176 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use bytes::BytesMut;
+use swimos_agent_derive::AgentLaneModel;
+use swimos_agent_protocol::MapMessage;
+use swimos_api::agent::AgentConfig;
+use swimos_model::Text;
+use swimos_utilities::routing::RouteUri;
+use uuid::Uuid;
+
+use crate::{
+    agent_model::{AgentSpec, WriteResult},
+    event_handler::{EventHandlerError, HandlerAction, StepResult},
+    lanes::{MapLane, ValueLane},
+    meta::AgentMetadata,
+    test_context::dummy_context,
+};
+
+use super::{AttachDynamicLane, DynamicMapLanes, DynamicValueLaneSet, DynamicValueLanes};
+
+const CONFIG: AgentConfig = AgentConfig::DEFAULT;
+const NODE_URI: &str = "/node";
+const SYNC_ID: Uuid = Uuid::from_u128(7374);
+
+const VALUE_ID: u64 = 10;
+const MAP_ID: u64 = 11;
+
+#[derive(AgentLaneModel)]
+#[agent(root(crate))]
+struct TestAgent {
+    lane: ValueLane<i32>,
+    values: DynamicValueLanes<i32>,
+    maps: DynamicMapLanes<i32, i32>,
+}
+
+fn run_handler<H>(agent: &TestAgent, mut handler: H) -> Result<H::Completion, EventHandlerError>
+where
+    H: HandlerAction<TestAgent>,
+{
+    let uri = RouteUri::try_from(NODE_URI).expect("Bad URI.");
+    let route_params = HashMap::new();
+    let meta = AgentMetadata::new(&uri, &route_params, &CONFIG);
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    loop {
+        match handler.step(
+            &mut dummy_context(&mut join_lane_init, &mut ad_hoc_buffer),
+            meta,
+            agent,
+        ) {
+            StepResult::Continue { .. } => {}
+            StepResult::Fail(err) => break Err(err),
+            StepResult::Complete { result, .. } => break Ok(result),
+        }
+    }
+}
+
+fn with_lanes() -> TestAgent {
+    let agent = TestAgent::default();
+    let attach_value = AttachDynamicLane::new(
+        |agent: &TestAgent| &agent.values,
+        Some((Text::new("dyn_value"), ValueLane::new(VALUE_ID, 0))),
+    );
+    let attach_map = AttachDynamicLane::new(
+        |agent: &TestAgent| &agent.maps,
+        Some((Text::new("dyn_map"), MapLane::new(MAP_ID, HashMap::new()))),
+    );
+    assert!(run_handler(&agent, attach_value).is_ok());
+    assert!(run_handler(&agent, attach_map).is_ok());
+    agent
+}
+
+#[test]
+fn dynamic_lanes_are_not_item_specs() {
+    let specs = TestAgent::item_specs();
+    assert_eq!(specs.len(), 1);
+    assert!(specs.contains_key("lane"));
+}
+
+#[test]
+fn dispatch_command_to_dynamic_value_lane() {
+    let agent = with_lanes();
+
+    assert!(agent
+        .on_value_command("missing", BytesMut::from("1"))
+        .is_none());
+    let handler = agent
+        .on_value_command("dyn_value", BytesMut::from("56"))
+        .expect("No handler.");
+    assert!(run_handler(&agent, handler).is_ok());
+
+    assert_eq!(agent.values.read("dyn_value", |n| *n), Some(56));
+    assert_eq!(agent.lane.read(|n| *n), 0);
+
+    let mut buffer = BytesMut::new();
+    assert_eq!(
+        agent.write_event("dyn_value", &mut buffer),
+        Some(WriteResult::Done)
+    );
+    assert!(!buffer.is_empty());
+}
+
+#[test]
+fn dispatch_command_to_dynamic_map_lane() {
+    let agent = with_lanes();
+
+    assert!(agent
+        .on_map_command("dyn_value", MapMessage::Clear)
+        .is_none());
+    let message = MapMessage::Update {
+        key: BytesMut::from("1"),
+        value: BytesMut::from("2"),
+    };
+    let handler = agent
+        .on_map_command("dyn_map", message)
+        .expect("No handler.");
+    assert!(run_handler(&agent, handler).is_ok());
+
+    let expected = HashMap::from([(1, 2)]);
+    assert_eq!(agent.maps.get_map("dyn_map", Clone::clone), Some(expected));
+}
+
+#[test]
+fn dispatch_sync_to_dynamic_lanes() {
+    let agent = with_lanes();
+
+    assert!(agent.on_sync("missing", SYNC_ID).is_none());
+    for name in ["dyn_value", "dyn_map"] {
+        let handler = agent.on_sync(name, SYNC_ID).expect("No handler.");
+        assert!(run_handler(&agent, handler).is_ok());
+
+        let mut buffer = BytesMut::new();
+        assert!(matches!(
+            agent.write_event(name, &mut buffer),
+            Some(WriteResult::Done | WriteResult::DataStillAvailable)
+        ));
+        assert!(!buffer.is_empty());
+    }
+}
+
+#[test]
+fn set_missing_dynamic_lane() {
+    let agent = with_lanes();
+
+    let handler =
+        DynamicValueLaneSet::new(|agent: &TestAgent| &agent.values, Text::new("missing"), 5);
+    match run_handler(&agent, handler) {
+        Err(EventHandlerError::NoSuchLane(name)) => assert_eq!(name, "missing"),
+        _ => panic!("Expected the handler to fail."),
+    }
+}
+
+#[test]
+fn attach_failed_lane() {
+    let agent = TestAgent::default();
+    let attach = AttachDynamicLane::<TestAgent, ValueLane<i32>>::new(
+        |agent: &TestAgent| &agent.values,
+        None,
+    );
+    assert!(run_handler(&agent, attach).is_ok());
+    assert!(agent.values.names().is_empty());
+}
@@ -0,0 +1,167 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{Arc, Mutex, MutexGuard},
+    task::Poll,
+    time::Duration,
+};
+
+use futures::{future::BoxFuture, FutureExt};
+use tokio::time::Instant;
+use tokio_util::time::{delay_queue, DelayQueue};
+
+use crate::event_handler::{ActionContext, EventHandler, LocalBoxEventHandler, Spawner};
+
+/// The timers for the entries of a lane that has been configured with a time-to-live. All entries
+/// share a single delay queue so that the agent task only ever needs to wait on one future for
+/// the lane (see [`ExpiryTimer::schedule`]), however many times the entries are written to.
+#[derive(Debug)]
+pub struct ExpiryTimer<K> {
+    ttl: Duration,
+    queue: Arc<Mutex<TimerQueue<K>>>,
+}
+
+#[derive(Debug)]
+struct TimerQueue<K> {
+    timers: DelayQueue<K>,
+    keys: HashMap<K, delay_queue::Key>,
+}
+
+impl<K> Default for TimerQueue<K> {
+    fn default() -> Self {
+        TimerQueue {
+            timers: Default::default(),
+            keys: Default::default(),
+        }
+    }
+}
+
+impl<K> ExpiryTimer<K> {
+    pub fn new(ttl: Duration) -> Self {
+        ExpiryTimer {
+            ttl,
+            queue: Default::default(),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Change the time-to-live for entries that are refreshed from now on. The timers that are
+    /// already running are unaffected (see [`ExpiryTimer::refresh_at`]).
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        self.ttl = ttl;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TimerQueue<K>> {
+        self.queue.lock().expect("Expiry timers poisoned.")
+    }
+}
+
+impl<K: Clone + Eq + Hash> ExpiryTimer<K> {
+    /// Record that an entry has been written, restarting its timer.
+    pub fn refresh(&self, key: K) {
+        let ttl = self.ttl;
+        let mut guard = self.lock();
+        let TimerQueue { timers, keys } = &mut *guard;
+        if let Some(timer_key) = keys.get(&key) {
+            timers.reset(timer_key, ttl);
+        } else {
+            let timer_key = timers.insert(key.clone(), ttl);
+            keys.insert(key, timer_key);
+        }
+    }
+
+    /// Restart the timer for an entry so that it expires at a specific time.
+    pub fn refresh_at(&self, key: K, deadline: Instant) {
+        let mut guard = self.lock();
+        let TimerQueue { timers, keys } = &mut *guard;
+        if let Some(timer_key) = keys.get(&key) {
+            timers.reset_at(timer_key, deadline);
+        } else {
+            let timer_key = timers.insert_at(key.clone(), deadline);
+            keys.insert(key, timer_key);
+        }
+    }
+
+    /// Stop the timer for an entry that has been removed.
+    pub fn remove(&self, key: &K) {
+        let mut guard = self.lock();
+        let TimerQueue { timers, keys } = &mut *guard;
+        if let Some(timer_key) = keys.remove(key) {
+            timers.remove(&timer_key);
+        }
+    }
+
+    /// Stop the timers for all entries.
+    pub fn clear(&self) {
+        let mut guard = self.lock();
+        let TimerQueue { timers, keys } = &mut *guard;
+        timers.clear();
+        keys.clear();
+    }
+
+    /// Determine whether an entry, that was reported as expired, has been written to again since.
+    pub fn is_running(&self, key: &K) -> bool {
+        self.lock().keys.contains_key(key)
+    }
+}
+
+impl<K> ExpiryTimer<K>
+where
+    K: Clone + Eq + Hash + Send + 'static,
+{
+    /// Suspend a future into the agent task that will wait for the next entries of the lane to
+    /// expire and then run the handler produced by `on_expired` for them. The handler is
+    /// responsible for scheduling the timer again.
+    pub fn schedule<C, F, H>(&self, action_context: &mut ActionContext<C>, on_expired: F)
+    where
+        C: 'static,
+        F: FnOnce(Vec<K>) -> H + Send + 'static,
+        H: EventHandler<C> + 'static,
+    {
+        let fut = self.expired().map(move |keys| {
+            let handler: LocalBoxEventHandler<'static, C> = Box::new(on_expired(keys));
+            handler
+        });
+        action_context.spawn_suspend(fut.boxed());
+    }
+
+    /// A future that completes with all entries that have expired, as soon as there is at least one.
+    fn expired(&self) -> BoxFuture<'static, Vec<K>> {
+        let queue = self.queue.clone();
+        futures::future::poll_fn(move |cx| {
+            let mut guard = queue.lock().expect("Expiry timers poisoned.");
+            let TimerQueue { timers, keys } = &mut *guard;
+            let mut expired = vec![];
+            // If the queue is empty, it retains the waker and will wake the task when a timer is
+            // next inserted.
+            while let Poll::Ready(Some(entry)) = timers.poll_expired(cx) {
+                let key = entry.into_inner();
+                keys.remove(&key);
+                expired.push(key);
+            }
+            if expired.is_empty() {
+                Poll::Pending
+            } else {
+                Poll::Ready(expired)
+            }
+        })
+        .boxed()
+    }
+}
@@ -0,0 +1,670 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    ops::{Bound, RangeBounds},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bytes::BytesMut;
+use static_assertions::assert_impl_all;
+use swimos_agent_protocol::{encoding::lane::MapLaneResponseEncoder, LaneResponse, MapOperation};
+use swimos_form::write::StructuralWritable;
+use tokio::time::Instant;
+use tokio_util::codec::Encoder;
+use uuid::Uuid;
+
+use crate::{
+    agent_model::WriteResult,
+    event_handler::{ActionContext, HandlerAction, Modification, StepResult},
+    item::{AgentItem, EventCount},
+    meta::AgentMetadata,
+};
+
+use super::{expiry::ExpiryTimer, LaneItem};
+
+#[cfg(test)]
+mod tests;
+
+/// The number of entries retained by a [`HistoryLane`] if no retention policy is specified.
+pub const DEFAULT_HISTORY_LENGTH: usize = 1024;
+
+/// Policy determining which entries are retained by a [`HistoryLane`]. An entry is evicted when
+/// either of the limits is exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryRetention {
+    max_len: Option<usize>,
+    max_age: Option<Duration>,
+}
+
+impl Default for HistoryRetention {
+    fn default() -> Self {
+        HistoryRetention::count(DEFAULT_HISTORY_LENGTH)
+    }
+}
+
+impl HistoryRetention {
+    /// Retain (at most) the most recent `n` entries.
+    pub fn count(n: usize) -> Self {
+        HistoryRetention {
+            max_len: Some(n),
+            max_age: None,
+        }
+    }
+
+    /// Retain the entries that were added within the specified duration.
+    pub fn duration(max_age: Duration) -> Self {
+        HistoryRetention {
+            max_len: None,
+            max_age: Some(max_age),
+        }
+    }
+
+    /// Additionally limit the number of entries that are retained.
+    pub fn with_max_len(self, n: usize) -> Self {
+        HistoryRetention {
+            max_len: Some(n),
+            ..self
+        }
+    }
+
+    /// Additionally limit the age of the entries that are retained.
+    pub fn with_max_age(self, max_age: Duration) -> Self {
+        HistoryRetention {
+            max_age: Some(max_age),
+            ..self
+        }
+    }
+
+    /// The maximum number of entries that will be retained.
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    /// The maximum age of an entry before it is evicted.
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+}
+
+#[derive(Debug)]
+struct HistoryEntry<T> {
+    timestamp: u64,
+    added: Instant,
+    value: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HistoryEvent {
+    Appended(u64),
+    Evicted(u64),
+}
+
+#[derive(Debug)]
+struct HistoryLaneInner<T> {
+    retention: HistoryRetention,
+    entries: VecDeque<HistoryEntry<T>>,
+    events: VecDeque<HistoryEvent>,
+    sync_queue: VecDeque<Uuid>,
+    event_count: u64,
+    // The timers for the entries, keyed by timestamp. This is created when the lane is first given
+    // a maximum age and is then retained, so that there is only ever one timer task for the lane.
+    expiry: Option<ExpiryTimer<u64>>,
+    pruning: bool,
+}
+
+impl<T> HistoryLaneInner<T> {
+    fn new(retention: HistoryRetention) -> Self {
+        HistoryLaneInner {
+            retention,
+            entries: Default::default(),
+            events: Default::default(),
+            sync_queue: Default::default(),
+            event_count: 0,
+            expiry: retention.max_age.map(ExpiryTimer::new),
+            pruning: false,
+        }
+    }
+
+    fn push(&mut self, timestamp: u64, value: T) -> u64 {
+        let HistoryLaneInner {
+            retention,
+            entries,
+            events,
+            event_count,
+            expiry,
+            ..
+        } = self;
+        // Keys must be unique so entries added within the same millisecond are nudged forward.
+        let timestamp = match entries.back() {
+            Some(last) if last.timestamp >= timestamp => last.timestamp.saturating_add(1),
+            _ => timestamp,
+        };
+        let added = Instant::now();
+        entries.push_back(HistoryEntry {
+            timestamp,
+            added,
+            value,
+        });
+        events.push_back(HistoryEvent::Appended(timestamp));
+        *event_count = event_count.wrapping_add(1);
+        if let (Some(age), Some(expiry)) = (retention.max_age, expiry.as_ref()) {
+            expiry.refresh_at(timestamp, added + age);
+        }
+        self.evict(added, None);
+        timestamp
+    }
+
+    /// Evict the entries that do not satisfy the retention policy. Entries with timestamps up to
+    /// `expired` are evicted unconditionally as their timers have already fired.
+    fn evict(&mut self, now: Instant, expired: Option<u64>) -> bool {
+        let HistoryLaneInner {
+            retention: HistoryRetention { max_len, max_age },
+            entries,
+            events,
+            expiry,
+            ..
+        } = self;
+        let mut evicted = false;
+        while let Some(entry) = entries.front() {
+            let too_many = max_len.is_some_and(|n| entries.len() > n);
+            let too_old = max_age.is_some_and(|age| entry.added + age <= now);
+            let timed_out = expired.is_some_and(|t| entry.timestamp <= t);
+            if too_many || too_old || timed_out {
+                if let Some(expiry) = expiry.as_ref() {
+                    expiry.remove(&entry.timestamp);
+                }
+                events.push_back(HistoryEvent::Evicted(entry.timestamp));
+                entries.pop_front();
+                evicted = true;
+            } else {
+                break;
+            }
+        }
+        evicted
+    }
+
+    fn set_retention(&mut self, retention: HistoryRetention) -> bool {
+        let HistoryLaneInner {
+            retention: current,
+            entries,
+            expiry,
+            ..
+        } = self;
+        if current.max_age != retention.max_age {
+            match (retention.max_age, expiry.as_mut()) {
+                (Some(age), Some(expiry)) => {
+                    expiry.set_ttl(age);
+                    for entry in entries.iter() {
+                        expiry.refresh_at(entry.timestamp, entry.added + age);
+                    }
+                }
+                (Some(age), None) => {
+                    let timer = ExpiryTimer::new(age);
+                    for entry in entries.iter() {
+                        timer.refresh_at(entry.timestamp, entry.added + age);
+                    }
+                    *expiry = Some(timer);
+                }
+                (None, Some(expiry)) => expiry.clear(),
+                (None, None) => {}
+            }
+        }
+        *current = retention;
+        self.evict(Instant::now(), None)
+    }
+
+    fn find(&self, timestamp: u64) -> Option<&HistoryEntry<T>> {
+        let HistoryLaneInner { entries, .. } = self;
+        entries
+            .binary_search_by_key(&timestamp, |entry| entry.timestamp)
+            .ok()
+            .and_then(|i| entries.get(i))
+    }
+}
+
+/// A lane that retains a bounded window of timestamped values. Each value that is added to the lane
+/// is keyed by the time (in milliseconds since the UNIX epoch) at which it was added and entries
+/// are evicted, oldest first, according to the [retention policy](`HistoryRetention`) of the lane.
+/// If several values are added within the same millisecond, the later timestamps are advanced so
+/// that every entry has a distinct key.
+///
+/// Remotely, a history lane appears to be a map lane from timestamps to values; synchronizing with
+/// the lane will deliver the current window and adding and evicting entries will generate update
+/// and remove events, respectively. History lanes are always transient and ignore any commands
+/// that they receive.
+///
+/// Values can be added to the lane by executing an instance of [`HistoryLanePush`] (which can be
+/// constructed using the [`crate::agent_lifecycle::HandlerContext`]). The context can also populate
+/// the lane by periodically sampling another lane or store.
+#[derive(Debug)]
+pub struct HistoryLane<T> {
+    id: u64,
+    inner: RefCell<HistoryLaneInner<T>>,
+}
+
+assert_impl_all!(HistoryLane<()>: Send);
+
+impl<T> HistoryLane<T> {
+    /// Create a history lane that retains the [default](`DEFAULT_HISTORY_LENGTH`) number of
+    /// entries.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the lane. This should be unique in an agent.
+    pub fn new(id: u64) -> Self {
+        Self::with_retention(id, HistoryRetention::default())
+    }
+
+    /// # Arguments
+    /// * `id` - The ID of the lane. This should be unique in an agent.
+    /// * `retention` - The policy determining which entries are retained.
+    pub fn with_retention(id: u64, retention: HistoryRetention) -> Self {
+        HistoryLane {
+            id,
+            inner: RefCell::new(HistoryLaneInner::new(retention)),
+        }
+    }
+
+    /// The retention policy of the lane.
+    pub fn retention(&self) -> HistoryRetention {
+        self.inner.borrow().retention
+    }
+
+    /// The number of entries currently held by the lane.
+    pub fn len(&self) -> usize {
+        self.inner.borrow().entries.len()
+    }
+
+    /// Whether the lane currently holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.inner.borrow().entries.is_empty()
+    }
+
+    /// Read the most recent entry in the lane.
+    pub fn latest<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(Option<(u64, &T)>) -> R,
+    {
+        let guard = self.inner.borrow();
+        f(guard
+            .entries
+            .back()
+            .map(|entry| (entry.timestamp, &entry.value)))
+    }
+
+    /// Get the entries with timestamps in the specified range, ordered from oldest to newest.
+    pub fn range<R>(&self, range: R) -> Vec<(u64, T)>
+    where
+        R: RangeBounds<u64>,
+        T: Clone,
+    {
+        let guard = self.inner.borrow();
+        let entries = &guard.entries;
+        let start = match range.start_bound() {
+            Bound::Included(t) => entries.partition_point(|entry| entry.timestamp < *t),
+            Bound::Excluded(t) => entries.partition_point(|entry| entry.timestamp <= *t),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(t) => entries.partition_point(|entry| entry.timestamp <= *t),
+            Bound::Excluded(t) => entries.partition_point(|entry| entry.timestamp < *t),
+            Bound::Unbounded => entries.len(),
+        };
+        entries
+            .range(start..end.max(start))
+            .map(|entry| (entry.timestamp, entry.value.clone()))
+            .collect()
+    }
+
+    /// Add a value to the lane, returning the timestamp that was assigned to it.
+    pub(crate) fn push(&self, value: T) -> u64 {
+        self.inner.borrow_mut().push(now_millis(), value)
+    }
+
+    /// Evict the entries with timers that have fired (and any others that have exceeded the
+    /// maximum age of the lane), returning whether any entries were removed.
+    pub(crate) fn prune(&self, expired: &[u64]) -> bool {
+        let mut guard = self.inner.borrow_mut();
+        guard.pruning = false;
+        guard.evict(Instant::now(), expired.iter().copied().max())
+    }
+
+    /// Replace the retention policy of the lane, evicting any entries that do not satisfy it and
+    /// restarting the timers of the remaining entries if the maximum age has changed.
+    pub(crate) fn set_retention(&self, retention: HistoryRetention) -> bool {
+        self.inner.borrow_mut().set_retention(retention)
+    }
+
+    pub(crate) fn sync(&self, id: Uuid) {
+        self.inner.borrow_mut().sync_queue.push_back(id);
+    }
+}
+
+impl<T: 'static> HistoryLane<T> {
+    /// If the lane has a maximum age, suspend a future into the agent task that will evict entries
+    /// as they expire. All entries share a single timer queue so there is, at most, one such
+    /// future for the lane at any time.
+    ///
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    /// * `action_context` - The context in which to suspend the timer.
+    pub(crate) fn start_pruning<C: 'static>(
+        &self,
+        projection: fn(&C) -> &Self,
+        action_context: &mut ActionContext<C>,
+    ) {
+        let mut guard = self.inner.borrow_mut();
+        let HistoryLaneInner {
+            retention,
+            expiry,
+            pruning,
+            ..
+        } = &mut *guard;
+        if let (Some(_), Some(expiry), false) = (retention.max_age, expiry.as_ref(), *pruning) {
+            expiry.schedule(action_context, move |expired| {
+                HistoryLanePrune::new(projection, expired)
+            });
+            *pruning = true;
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+        .unwrap_or_default()
+}
+
+impl<T> AgentItem for HistoryLane<T> {
+    fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<T> EventCount for HistoryLane<T> {
+    fn event_count(&self) -> u64 {
+        self.inner.borrow().event_count
+    }
+}
+
+const INFALLIBLE_SER: &str = "Serializing to recon should be infallible.";
+
+impl<T: StructuralWritable> LaneItem for HistoryLane<T> {
+    fn write_to_buffer(&self, buffer: &mut BytesMut) -> WriteResult {
+        let mut encoder = MapLaneResponseEncoder::default();
+        let mut guard = self.inner.borrow_mut();
+        let mut written = false;
+        while let Some(event) = guard.events.pop_front() {
+            match event {
+                HistoryEvent::Appended(timestamp) => {
+                    // If the entry has already been evicted, its removal will follow.
+                    if let Some(entry) = guard.find(timestamp) {
+                        let op = MapOperation::Update {
+                            key: timestamp,
+                            value: &entry.value,
+                        };
+                        encoder
+                            .encode(LaneResponse::StandardEvent(op), buffer)
+                            .expect(INFALLIBLE_SER);
+                        written = true;
+                        break;
+                    }
+                }
+                HistoryEvent::Evicted(timestamp) => {
+                    let op = MapOperation::<u64, &T>::Remove { key: timestamp };
+                    encoder
+                        .encode(LaneResponse::StandardEvent(op), buffer)
+                        .expect(INFALLIBLE_SER);
+                    written = true;
+                    break;
+                }
+            }
+        }
+        if !written {
+            if let Some(id) = guard.sync_queue.pop_front() {
+                for entry in &guard.entries {
+                    let op = MapOperation::Update {
+                        key: entry.timestamp,
+                        value: &entry.value,
+                    };
+                    encoder
+                        .encode(LaneResponse::SyncEvent(id, op), buffer)
+                        .expect(INFALLIBLE_SER);
+                }
+                encoder
+                    .encode(LaneResponse::<MapOperation<u64, &T>>::Synced(id), buffer)
+                    .expect(INFALLIBLE_SER);
+                written = true;
+            }
+        }
+        if !written {
+            WriteResult::NoData
+        } else if guard.events.is_empty() && guard.sync_queue.is_empty() {
+            WriteResult::Done
+        } else {
+            WriteResult::DataStillAvailable
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will add a value to a history
+/// lane, completing with the timestamp that was assigned to it.
+pub struct HistoryLanePush<C, T> {
+    projection: fn(&C) -> &HistoryLane<T>,
+    value: Option<T>,
+}
+
+impl<C, T> HistoryLanePush<C, T> {
+    pub fn new(projection: fn(&C) -> &HistoryLane<T>, value: T) -> Self {
+        HistoryLanePush {
+            projection,
+            value: Some(value),
+        }
+    }
+}
+
+impl<C: 'static, T: 'static> HandlerAction<C> for HistoryLanePush<C, T> {
+    type Completion = u64;
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let HistoryLanePush { projection, value } = self;
+        if let Some(value) = value.take() {
+            let lane = projection(context);
+            let timestamp = lane.push(value);
+            lane.start_pruning(*projection, action_context);
+            StepResult::Complete {
+                modified_item: Some(Modification::no_trigger(lane.id)),
+                result: timestamp,
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will evict the entries from a
+/// history lane that have exceeded its maximum age and then restart the timer for the lane.
+pub struct HistoryLanePrune<C, T> {
+    projection: fn(&C) -> &HistoryLane<T>,
+    expired: Option<Vec<u64>>,
+}
+
+impl<C, T> HistoryLanePrune<C, T> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    /// * `expired` - The timestamps of the entries with timers that have fired.
+    pub fn new(projection: fn(&C) -> &HistoryLane<T>, expired: Vec<u64>) -> Self {
+        HistoryLanePrune {
+            projection,
+            expired: Some(expired),
+        }
+    }
+}
+
+impl<C: 'static, T: 'static> HandlerAction<C> for HistoryLanePrune<C, T> {
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let HistoryLanePrune {
+            projection,
+            expired,
+        } = self;
+        if let Some(expired) = expired.take() {
+            let lane = projection(context);
+            let pruned = lane.prune(&expired);
+            lane.start_pruning(*projection, action_context);
+            if pruned {
+                StepResult::Complete {
+                    modified_item: Some(Modification::no_trigger(lane.id)),
+                    result: (),
+                }
+            } else {
+                StepResult::done(())
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will replace the retention
+/// policy of a history lane.
+pub struct HistoryLaneSetRetention<C, T> {
+    projection: fn(&C) -> &HistoryLane<T>,
+    retention: Option<HistoryRetention>,
+}
+
+impl<C, T> HistoryLaneSetRetention<C, T> {
+    pub fn new(projection: fn(&C) -> &HistoryLane<T>, retention: HistoryRetention) -> Self {
+        HistoryLaneSetRetention {
+            projection,
+            retention: Some(retention),
+        }
+    }
+}
+
+impl<C: 'static, T: 'static> HandlerAction<C> for HistoryLaneSetRetention<C, T> {
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let HistoryLaneSetRetention {
+            projection,
+            retention,
+        } = self;
+        if let Some(retention) = retention.take() {
+            let lane = projection(context);
+            let evicted = lane.set_retention(retention);
+            lane.start_pruning(*projection, action_context);
+            if evicted {
+                StepResult::Complete {
+                    modified_item: Some(Modification::no_trigger(lane.id)),
+                    result: (),
+                }
+            } else {
+                StepResult::done(())
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will get the entries of a
+/// history lane with timestamps in a range.
+pub struct HistoryLaneRange<C, T> {
+    projection: fn(&C) -> &HistoryLane<T>,
+    range: Option<(Bound<u64>, Bound<u64>)>,
+}
+
+impl<C, T> HistoryLaneRange<C, T> {
+    pub fn new<R: RangeBounds<u64>>(projection: fn(&C) -> &HistoryLane<T>, range: R) -> Self {
+        HistoryLaneRange {
+            projection,
+            range: Some((range.start_bound().cloned(), range.end_bound().cloned())),
+        }
+    }
+}
+
+impl<C, T: Clone> HandlerAction<C> for HistoryLaneRange<C, T> {
+    type Completion = Vec<(u64, T)>;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let HistoryLaneRange { projection, range } = self;
+        if let Some(range) = range.take() {
+            StepResult::done(projection(context).range(range))
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+pub struct HistoryLaneSync<C, T> {
+    projection: fn(&C) -> &HistoryLane<T>,
+    id: Option<Uuid>,
+}
+
+impl<C, T> HistoryLaneSync<C, T> {
+    pub fn new(projection: fn(&C) -> &HistoryLane<T>, id: Uuid) -> Self {
+        HistoryLaneSync {
+            projection,
+            id: Some(id),
+        }
+    }
+}
+
+impl<C, T> HandlerAction<C> for HistoryLaneSync<C, T> {
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let HistoryLaneSync { projection, id } = self;
+        if let Some(id) = id.take() {
+            let lane = projection(context);
+            lane.sync(id);
+            StepResult::Complete {
+                modified_item: Some(Modification::no_trigger(lane.id)),
+                result: (),
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
@@ -0,0 +1,465 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bytes::BytesMut;
+use futures::{stream::FuturesUnordered, StreamExt};
+use swimos_agent_protocol::{
+    encoding::lane::RawMapLaneResponseDecoder, MapLaneResponse, MapOperation,
+};
+use swimos_api::agent::AgentConfig;
+use swimos_utilities::routing::RouteUri;
+use tokio::time::Instant;
+use tokio_util::codec::Decoder;
+use uuid::Uuid;
+
+use crate::{
+    agent_model::WriteResult,
+    event_handler::{ActionContext, HandlerAction, HandlerFuture, Modification, StepResult},
+    item::EventCount,
+    lanes::LaneItem,
+    meta::AgentMetadata,
+    test_context::{dummy_context, no_downlink, DummyAgentContext},
+};
+
+use super::{
+    HistoryLane, HistoryLanePush, HistoryLaneRange, HistoryLaneSetRetention, HistoryLaneSync,
+    HistoryRetention, DEFAULT_HISTORY_LENGTH,
+};
+
+const CONFIG: AgentConfig = AgentConfig::DEFAULT;
+const NODE_URI: &str = "/node";
+const LANE_ID: u64 = 9;
+const SYNC_ID: Uuid = Uuid::from_u128(85883);
+const MAX_AGE: Duration = Duration::from_secs(60);
+
+fn make_uri() -> RouteUri {
+    RouteUri::try_from(NODE_URI).expect("Bad URI.")
+}
+
+fn make_meta<'a>(
+    uri: &'a RouteUri,
+    route_params: &'a HashMap<String, String>,
+) -> AgentMetadata<'a> {
+    AgentMetadata::new(uri, route_params, &CONFIG)
+}
+
+struct TestAgent {
+    lane: HistoryLane<i32>,
+}
+
+impl TestAgent {
+    const LANE: fn(&TestAgent) -> &HistoryLane<i32> = |agent| &agent.lane;
+
+    fn with_retention(retention: HistoryRetention) -> Self {
+        TestAgent {
+            lane: HistoryLane::with_retention(LANE_ID, retention),
+        }
+    }
+}
+
+fn parse<T: std::str::FromStr>(bytes: &BytesMut) -> T {
+    std::str::from_utf8(bytes.as_ref())
+        .expect("Bad utf.")
+        .parse::<T>()
+        .ok()
+        .expect("Bad body.")
+}
+
+fn interpret(op: MapOperation<BytesMut, BytesMut>) -> MapOperation<u64, i32> {
+    match op {
+        MapOperation::Update { key, value } => MapOperation::Update {
+            key: parse(&key),
+            value: parse(&value),
+        },
+        MapOperation::Remove { key } => MapOperation::Remove { key: parse(&key) },
+        MapOperation::Clear => MapOperation::Clear,
+    }
+}
+
+#[derive(Debug, Default)]
+struct Operations {
+    events: Vec<MapOperation<u64, i32>>,
+    sync: HashMap<Uuid, Vec<MapOperation<u64, i32>>>,
+}
+
+fn consume_events(lane: &HistoryLane<i32>) -> Operations {
+    let mut operations = Operations::default();
+    let mut sync_pending = HashMap::new();
+
+    let mut decoder = RawMapLaneResponseDecoder::default();
+    let mut buffer = BytesMut::new();
+
+    loop {
+        let result = lane.write_to_buffer(&mut buffer);
+
+        if matches!(result, WriteResult::NoData) {
+            break;
+        }
+
+        while !buffer.is_empty() {
+            let content = decoder
+                .decode(&mut buffer)
+                .expect("Invalid frame.")
+                .expect("Incomplete frame.");
+
+            match content {
+                MapLaneResponse::StandardEvent(operation) => {
+                    operations.events.push(interpret(operation));
+                }
+                MapLaneResponse::SyncEvent(id, operation) => {
+                    sync_pending
+                        .entry(id)
+                        .or_insert_with(Vec::new)
+                        .push(interpret(operation));
+                }
+                MapLaneResponse::Synced(id) => {
+                    let ops = sync_pending.remove(&id).unwrap_or_default();
+                    operations.sync.insert(id, ops);
+                }
+                ow => panic!("Unexpected response: {:?}", ow),
+            }
+        }
+
+        if matches!(result, WriteResult::Done) {
+            break;
+        }
+    }
+    assert!(sync_pending.is_empty());
+    operations
+}
+
+fn push(lane: &HistoryLane<i32>, value: i32) -> u64 {
+    lane.push(value)
+}
+
+#[test]
+fn default_retention() {
+    let lane = HistoryLane::<i32>::new(LANE_ID);
+    assert_eq!(lane.retention().max_len(), Some(DEFAULT_HISTORY_LENGTH));
+    assert!(lane.retention().max_age().is_none());
+    assert!(lane.is_empty());
+}
+
+#[test]
+fn timestamps_are_distinct() {
+    let lane = HistoryLane::new(LANE_ID);
+    let timestamps = (0..10).map(|i| push(&lane, i)).collect::<Vec<_>>();
+
+    assert!(timestamps.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(lane.len(), 10);
+    assert_eq!(lane.event_count(), 10);
+    lane.latest(|latest| assert_eq!(latest, Some((timestamps[9], &9))));
+}
+
+#[test]
+fn evict_by_count() {
+    let lane = HistoryLane::with_retention(LANE_ID, HistoryRetention::count(2));
+    let t1 = push(&lane, 1);
+    let t2 = push(&lane, 2);
+    let t3 = push(&lane, 3);
+
+    assert_eq!(lane.range(..), vec![(t2, 2), (t3, 3)]);
+
+    // The first entry was evicted before its addition was written.
+    let Operations { events, sync } = consume_events(&lane);
+    assert!(sync.is_empty());
+    assert_eq!(
+        events,
+        vec![
+            MapOperation::Update { key: t2, value: 2 },
+            MapOperation::Update { key: t3, value: 3 },
+            MapOperation::Remove { key: t1 },
+        ]
+    );
+}
+
+#[test]
+fn range_queries() {
+    let lane = HistoryLane::new(LANE_ID);
+    let timestamps = (0..5).map(|i| push(&lane, i)).collect::<Vec<_>>();
+
+    assert_eq!(lane.range(..).len(), 5);
+    assert_eq!(
+        lane.range(timestamps[1]..timestamps[3]),
+        vec![(timestamps[1], 1), (timestamps[2], 2)]
+    );
+    assert_eq!(
+        lane.range(timestamps[1]..=timestamps[3]),
+        vec![(timestamps[1], 1), (timestamps[2], 2), (timestamps[3], 3)]
+    );
+    assert_eq!(
+        lane.range(timestamps[3]..),
+        vec![(timestamps[3], 3), (timestamps[4], 4)]
+    );
+    assert!(lane.range(timestamps[4] + 1..).is_empty());
+    assert!(lane.range(timestamps[3]..timestamps[1]).is_empty());
+}
+
+#[test]
+fn sync_delivers_window() {
+    let lane = HistoryLane::with_retention(LANE_ID, HistoryRetention::count(2));
+    push(&lane, 1);
+    let t2 = push(&lane, 2);
+    let t3 = push(&lane, 3);
+    consume_events(&lane);
+
+    lane.sync(SYNC_ID);
+    let Operations { events, sync } = consume_events(&lane);
+    assert!(events.is_empty());
+    assert_eq!(
+        sync.get(&SYNC_ID),
+        Some(&vec![
+            MapOperation::Update { key: t2, value: 2 },
+            MapOperation::Update { key: t3, value: 3 },
+        ])
+    );
+}
+
+fn check_written<T: Eq + std::fmt::Debug>(result: StepResult<T>, written: bool) -> T {
+    let expected_mod = if written {
+        Some(Modification::no_trigger(LANE_ID))
+    } else {
+        None
+    };
+    match result {
+        StepResult::Complete {
+            modified_item,
+            result,
+        } => {
+            assert_eq!(modified_item, expected_mod);
+            result
+        }
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+}
+
+#[test]
+fn history_lane_push_event_handler() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_retention(HistoryRetention::count(4));
+
+    let mut handler = HistoryLanePush::new(TestAgent::LANE, 7);
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    let timestamp = check_written(result, true);
+    assert_eq!(agent.lane.range(..), vec![(timestamp, 7)]);
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    assert!(matches!(result, StepResult::Fail(_)));
+}
+
+fn run_push(
+    agent: &TestAgent,
+    meta: AgentMetadata<'_>,
+    pending: &FuturesUnordered<HandlerFuture<TestAgent>>,
+    value: i32,
+) -> u64 {
+    let mut handler = HistoryLanePush::new(TestAgent::LANE, value);
+    let result = handler.step(
+        &mut ActionContext::new(
+            pending,
+            &DummyAgentContext,
+            &no_downlink,
+            &mut HashMap::new(),
+            &mut BytesMut::new(),
+        ),
+        meta,
+        agent,
+    );
+    check_written(result, true)
+}
+
+async fn run_prune(
+    agent: &TestAgent,
+    meta: AgentMetadata<'_>,
+    pending: &mut FuturesUnordered<HandlerFuture<TestAgent>>,
+) -> StepResult<()> {
+    let mut handler = pending
+        .next()
+        .await
+        .expect("No eviction timer was scheduled.");
+    handler.step(
+        &mut ActionContext::new(
+            &*pending,
+            &DummyAgentContext,
+            &no_downlink,
+            &mut HashMap::new(),
+            &mut BytesMut::new(),
+        ),
+        meta,
+        agent,
+    )
+}
+
+fn run_set_retention(
+    agent: &TestAgent,
+    meta: AgentMetadata<'_>,
+    pending: &FuturesUnordered<HandlerFuture<TestAgent>>,
+    retention: HistoryRetention,
+) -> StepResult<()> {
+    let mut handler = HistoryLaneSetRetention::new(TestAgent::LANE, retention);
+    handler.step(
+        &mut ActionContext::new(
+            pending,
+            &DummyAgentContext,
+            &no_downlink,
+            &mut HashMap::new(),
+            &mut BytesMut::new(),
+        ),
+        meta,
+        agent,
+    )
+}
+
+#[tokio::test(start_paused = true)]
+async fn evict_by_age() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_retention(HistoryRetention::duration(MAX_AGE));
+
+    let mut pending = FuturesUnordered::new();
+    let t1 = run_push(&agent, meta, &pending, 1);
+    tokio::time::advance(MAX_AGE / 2).await;
+    let t2 = run_push(&agent, meta, &pending, 2);
+    // The entries share a single timer.
+    assert_eq!(pending.len(), 1);
+    consume_events(&agent.lane);
+
+    let start = Instant::now();
+    let result = run_prune(&agent, meta, &mut pending).await;
+    assert_eq!(start.elapsed(), MAX_AGE / 2);
+    check_written(result, true);
+    assert_eq!(agent.lane.range(..), vec![(t2, 2)]);
+
+    let Operations { events, .. } = consume_events(&agent.lane);
+    assert_eq!(events, vec![MapOperation::Remove { key: t1 }]);
+
+    let result = run_prune(&agent, meta, &mut pending).await;
+    check_written(result, true);
+    assert!(agent.lane.is_empty());
+    // The timer is restarted, waiting for more entries.
+    assert_eq!(pending.len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn history_lane_set_retention_event_handler() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_retention(HistoryRetention::count(4));
+    push(&agent.lane, 1);
+    let t2 = push(&agent.lane, 2);
+
+    let pending = FuturesUnordered::new();
+    let retention = HistoryRetention::count(1).with_max_age(MAX_AGE);
+    let result = run_set_retention(&agent, meta, &pending, retention);
+    check_written(result, true);
+    assert_eq!(agent.lane.retention(), retention);
+    assert_eq!(agent.lane.range(..), vec![(t2, 2)]);
+    assert_eq!(pending.len(), 1);
+
+    let result = run_set_retention(&agent, meta, &pending, retention);
+    check_written(result, false);
+    assert_eq!(pending.len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn shortening_retention_prunes_without_push() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_retention(HistoryRetention::duration(MAX_AGE * 4));
+
+    let mut pending = FuturesUnordered::new();
+    let t1 = run_push(&agent, meta, &pending, 1);
+    tokio::time::advance(MAX_AGE / 2).await;
+    let t2 = run_push(&agent, meta, &pending, 2);
+    tokio::time::advance(MAX_AGE / 4).await;
+    consume_events(&agent.lane);
+
+    // Nothing has expired yet so shortening the retention evicts nothing immediately.
+    let result = run_set_retention(&agent, meta, &pending, HistoryRetention::duration(MAX_AGE));
+    check_written(result, false);
+    assert_eq!(pending.len(), 1);
+
+    let start = Instant::now();
+    let result = run_prune(&agent, meta, &mut pending).await;
+    assert_eq!(start.elapsed(), MAX_AGE / 4);
+    check_written(result, true);
+    assert_eq!(agent.lane.range(..), vec![(t2, 2)]);
+    let Operations { events, .. } = consume_events(&agent.lane);
+    assert_eq!(events, vec![MapOperation::Remove { key: t1 }]);
+
+    let start = Instant::now();
+    let result = run_prune(&agent, meta, &mut pending).await;
+    assert_eq!(start.elapsed(), MAX_AGE / 2);
+    check_written(result, true);
+    assert!(agent.lane.is_empty());
+}
+
+#[test]
+fn history_lane_range_event_handler() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_retention(HistoryRetention::count(4));
+    let t1 = push(&agent.lane, 1);
+    let t2 = push(&agent.lane, 2);
+    push(&agent.lane, 3);
+
+    let mut handler = HistoryLaneRange::new(TestAgent::LANE, t1..=t2);
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    assert_eq!(check_written(result, false), vec![(t1, 1), (t2, 2)]);
+}
+
+#[test]
+fn history_lane_sync_event_handler() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_retention(HistoryRetention::count(4));
+    let t1 = push(&agent.lane, 1);
+    consume_events(&agent.lane);
+
+    let mut handler = HistoryLaneSync::new(TestAgent::LANE, SYNC_ID);
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    check_written(result, true);
+
+    let Operations { sync, .. } = consume_events(&agent.lane);
+    assert_eq!(
+        sync.get(&SYNC_ID),
+        Some(&vec![MapOperation::Update { key: t1, value: 1 }])
+    );
+}
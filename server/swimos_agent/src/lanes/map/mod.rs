@@ -15,8 +15,12 @@
 use bytes::BytesMut;
 use frunk::{Coprod, Coproduct};
 use static_assertions::assert_impl_all;
-use std::{borrow::Borrow, cell::RefCell, collections::HashMap, hash::Hash, marker::PhantomData};
-use swimos_agent_protocol::{encoding::lane::MapLaneResponseEncoder, MapMessage};
+use std::{
+    borrow::Borrow, cell::RefCell, collections::HashMap, hash::Hash, marker::PhantomData,
+    time::Duration,
+};
+use swimos_agent_protocol::{encoding::lane::MapLaneResponseEncoder, MapMessage, MapOperation};
+use swimos_api::agent::SyncVersion;
 use swimos_form::{read::RecognizerReadable, write::StructuralWritable};
 use swimos_recon::parser::RecognizerDecoder;
 use tokio_util::codec::{Decoder, Encoder};
@@ -24,6 +28,7 @@ use uuid::Uuid;
 
 mod event;
 pub mod lifecycle;
+mod transaction;
 
 #[cfg(test)]
 mod tests;
@@ -31,17 +36,21 @@ mod tests;
 use crate::{
     agent_model::WriteResult,
     event_handler::{
+        reentrancy::{self, Deferred, Write},
         ActionContext, AndThen, EventHandlerError, HandlerAction, HandlerActionExt, HandlerTrans,
         Modification, StepResult,
     },
-    item::{AgentItem, InspectableMapLikeItem, MapItem, MapLikeItem, MutableMapLikeItem},
+    item::{
+        AgentItem, EventCount, InspectableMapLikeItem, MapItem, MapLikeItem, MutableMapLikeItem,
+    },
     map_storage::{MapStoreInner, TransformEntryResult},
     meta::AgentMetadata,
 };
 
-use super::queues::WriteQueues;
+use super::{expiry::ExpiryTimer, queues::WriteQueues};
 
 pub use event::MapLaneEvent;
+pub use transaction::MapLaneTransaction;
 
 use super::{LaneItem, ProjTransform};
 
@@ -50,12 +59,19 @@ type Inner<K, V> = MapStoreInner<K, V, WriteQueues<K>>;
 /// Model of a value lane. This maintains a sate consisting of a hash-map from keys to values. It generates an
 /// event whenever the map is updated (updating the value for a key, removing a key or clearing the map).
 ///
+/// A map lane can optionally be given a time-to-live for its entries (see [`MapLane::with_ttl`]). An entry
+/// that is not written to for longer than this period will be removed by the agent task, generating a
+/// remove event, exactly as if it had been removed explicitly. The timers for all of the entries are held
+/// in a single queue for the lane.
+///
 /// TODO: This could be parameterized over the type of the hash (and potentially over the kind of the map,
 /// potentially allowing a choice between hash and ordered maps).
 #[derive(Debug)]
 pub struct MapLane<K, V> {
     id: u64,
     inner: RefCell<Inner<K, V>>,
+    expiry: Option<ExpiryTimer<K>>,
+    deferred: Deferred<(K, V)>,
 }
 
 assert_impl_all!(MapLane<(), ()>: Send);
@@ -68,6 +84,55 @@ impl<K, V> MapLane<K, V> {
         MapLane {
             id,
             inner: RefCell::new(Inner::new(init)),
+            expiry: None,
+            deferred: Default::default(),
+        }
+    }
+
+    /// Create a map lane where each entry will be removed if it is not updated within a fixed period.
+    /// Entries in the initial contents of the lane (or restored from persistence) will not expire until
+    /// they have been written to at least once.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the lane. This should be unique within an agent.
+    /// * `init` - The initial contents of the map.
+    /// * `ttl` - The time-to-live for each entry.
+    pub fn with_ttl(id: u64, init: HashMap<K, V>, ttl: Duration) -> Self {
+        MapLane {
+            id,
+            inner: RefCell::new(Inner::new(init)),
+            expiry: Some(ExpiryTimer::new(ttl)),
+            deferred: Default::default(),
+        }
+    }
+
+    /// The time-to-live for the entries of the lane, if one has been set.
+    pub fn ttl(&self) -> Option<Duration> {
+        self.expiry.as_ref().map(ExpiryTimer::ttl)
+    }
+}
+
+impl<K, V> MapLane<K, V>
+where
+    K: Clone + Eq + Hash + Send + 'static,
+    V: 'static,
+{
+    /// If the lane has a time-to-live, start the timer that removes its expired entries. This should
+    /// be called once, when the agent starts (see
+    /// [`AgentSpec::start_expiry_timers`](crate::agent_model::AgentSpec::start_expiry_timers)).
+    ///
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    /// * `action_context` - The context in which to suspend the timer.
+    pub fn start_expiry<C: 'static>(
+        &self,
+        projection: fn(&C) -> &Self,
+        action_context: &mut ActionContext<C>,
+    ) {
+        if let Some(expiry) = &self.expiry {
+            expiry.schedule(action_context, move |keys| {
+                MapLaneExpire::new(projection, keys)
+            });
         }
     }
 }
@@ -78,6 +143,12 @@ impl<K, V> AgentItem for MapLane<K, V> {
     }
 }
 
+impl<K, V> EventCount for MapLane<K, V> {
+    fn event_count(&self) -> u64 {
+        self.inner.borrow().event_count()
+    }
+}
+
 impl<K, V> MapItem<K, V> for MapLane<K, V>
 where
     K: Eq + Hash + Clone,
@@ -90,7 +161,9 @@ where
     where
         F: FnOnce(Option<MapLaneEvent<K, V>>, &HashMap<K, V>) -> R,
     {
-        self.inner.borrow_mut().read_with_prev(f)
+        let result = self.inner.borrow_mut().read_with_prev(f);
+        self.apply_deferred();
+        result
     }
 }
 
@@ -100,7 +173,23 @@ where
 {
     /// Update the value associated with a key.
     pub(crate) fn update(&self, key: K, value: V) {
-        self.inner.borrow_mut().update(key, value)
+        self.refresh_expiry(&key);
+        match reentrancy::write(self.id, &self.inner, true) {
+            Write::Available(mut guard) => {
+                let inner = &mut *guard;
+                self.deferred
+                    .apply_to(inner, |inner, (key, value)| inner.update(key, value));
+                inner.update(key, value);
+            }
+            Write::Defer => self.deferred.push((key, value)),
+            Write::Rejected => {}
+        }
+    }
+
+    /// Apply any updates that were queued while the lane was borrowed.
+    fn apply_deferred(&self) {
+        self.deferred
+            .apply(&self.inner, |guard, (key, value)| guard.update(key, value));
     }
 
     /// Transform the value associated with a key.
@@ -108,17 +197,83 @@ where
     where
         F: FnOnce(Option<&V>) -> Option<V>,
     {
-        self.inner.borrow_mut().transform_entry(key, f)
+        let result = match reentrancy::write(self.id, &self.inner, false) {
+            Write::Available(mut guard) => guard.transform_entry(key.clone(), f),
+            _ => TransformEntryResult::NoChange,
+        };
+        match result {
+            TransformEntryResult::Update => self.refresh_expiry(&key),
+            TransformEntryResult::Remove => self.clear_expiry(&key),
+            TransformEntryResult::NoChange => {}
+        }
+        result
     }
 
     /// Remove and entry from the map.
     pub(crate) fn remove(&self, key: &K) {
-        self.inner.borrow_mut().remove(key)
+        self.clear_expiry(key);
+        if let Write::Available(mut guard) = reentrancy::write(self.id, &self.inner, false) {
+            guard.remove(key);
+        }
     }
 
     /// Clear the map.
     pub(crate) fn clear(&self) {
-        self.inner.borrow_mut().clear()
+        if let Some(expiry) = &self.expiry {
+            expiry.clear();
+        }
+        if let Write::Available(mut guard) = reentrancy::write(self.id, &self.inner, false) {
+            guard.clear();
+        }
+    }
+
+    /// Apply a sequence of operations to the map, atomically, and report them as a single batch of
+    /// events.
+    pub(crate) fn transaction(&self, ops: Vec<MapOperation<K, V>>) {
+        for op in &ops {
+            match op {
+                MapOperation::Update { key, .. } => self.refresh_expiry(key),
+                MapOperation::Remove { key } => self.clear_expiry(key),
+                MapOperation::Clear => {
+                    if let Some(expiry) = &self.expiry {
+                        expiry.clear();
+                    }
+                }
+            }
+        }
+        if let Write::Available(mut guard) = reentrancy::write(self.id, &self.inner, false) {
+            guard.transaction(ops);
+        }
+    }
+
+    /// If the lane has a time-to-live, record that an entry has been written.
+    fn refresh_expiry(&self, key: &K) {
+        if let Some(expiry) = &self.expiry {
+            expiry.refresh(key.clone());
+        }
+    }
+
+    fn clear_expiry(&self, key: &K) {
+        if let Some(expiry) = &self.expiry {
+            expiry.remove(key);
+        }
+    }
+
+    /// Remove an entry from the map that has been reported as expired, unless it has been written
+    /// to again since. Returns whether the entry was removed.
+    fn expire(&self, key: &K) -> bool {
+        match &self.expiry {
+            Some(expiry) if !expiry.is_running(key) => {
+                match reentrancy::write(self.id, &self.inner, false) {
+                    Write::Available(mut guard) if guard.get_map(|map| map.contains_key(key)) => {
+                        guard.remove(key);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
     }
 
     /// Read a value from the map, if it exists.
@@ -128,7 +283,9 @@ where
         Q: Hash + Eq,
         F: FnOnce(Option<&V>) -> R,
     {
-        self.inner.borrow().with_entry(key, f)
+        let result = self.inner.borrow().with_entry(key, f);
+        self.apply_deferred();
+        result
     }
 
     /// Read the complete state of the map.
@@ -136,7 +293,9 @@ where
     where
         F: FnOnce(&HashMap<K, V>) -> R,
     {
-        self.inner.borrow().get_map(f)
+        let result = self.inner.borrow().get_map(f);
+        self.apply_deferred();
+        result
     }
 
     /// Start a sync operation from the lane to the specified remote.
@@ -144,6 +303,16 @@ where
         let keys = self.get_map(|content| content.keys().cloned().collect());
         self.inner.borrow_mut().queue().sync(id, keys);
     }
+
+    /// Start a sync operation from the lane to the specified remote which already holds the state
+    /// of the lane as of a previous version. If possible, only the changes since that version will be
+    /// sent. Otherwise, the remote will be told to discard its state and all entries will be sent.
+    pub(crate) fn sync_since(&self, id: Uuid, since: SyncVersion) {
+        if !self.inner.borrow_mut().queue().delta_sync(id, since) {
+            let keys = self.get_map(|content| content.keys().cloned().collect());
+            self.inner.borrow_mut().queue().versioned_sync(id, keys);
+        }
+    }
 }
 
 impl<K, V> MapLane<K, V>
@@ -172,6 +341,12 @@ where
         let mut guard = self.inner.borrow_mut();
         if let Some(op) = guard.pop_operation() {
             encoder.encode(op, buffer).expect(INFALLIBLE_SER);
+            // All events of a batch are written together so that the state cannot change part way through.
+            while guard.queue().in_batch() {
+                if let Some(op) = guard.pop_operation() {
+                    encoder.encode(op, buffer).expect(INFALLIBLE_SER);
+                }
+            }
             if guard.queue().is_empty() {
                 WriteResult::Done
             } else {
@@ -310,6 +485,60 @@ where
     }
 }
 
+///  An [event handler](crate::event_handler::EventHandler)`] that will remove entries from the map that
+/// have been reported as expired by the timer for the lane, and then schedule the timer again.
+pub struct MapLaneExpire<C, K, V> {
+    projection: for<'a> fn(&'a C) -> &'a MapLane<K, V>,
+    keys: Option<std::vec::IntoIter<K>>,
+}
+
+impl<C, K, V> MapLaneExpire<C, K, V> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    /// * `keys` - The keys of the entries that have expired.
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a MapLane<K, V>, keys: Vec<K>) -> Self {
+        MapLaneExpire {
+            projection,
+            keys: Some(keys.into_iter()),
+        }
+    }
+}
+
+impl<C, K, V> HandlerAction<C> for MapLaneExpire<C, K, V>
+where
+    C: 'static,
+    K: Clone + Eq + Hash + Send + 'static,
+    V: 'static,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let MapLaneExpire { projection, keys } = self;
+        if let Some(pending) = keys {
+            let lane = projection(context);
+            // Each removal is reported as a separate step so that the lifecycle of the lane
+            // observes every one of them.
+            for key in pending.by_ref() {
+                if lane.expire(&key) {
+                    return StepResult::Continue {
+                        modified_item: Some(Modification::of(lane.id)),
+                    };
+                }
+            }
+            *keys = None;
+            lane.start_expiry(*projection, action_context);
+            StepResult::done(())
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
 ///  An [event handler](crate::event_handler::EventHandler)`] that will get an entry from the map.
 pub struct MapLaneGet<C, K, V> {
     projection: for<'a> fn(&'a C) -> &'a MapLane<K, V>,
@@ -448,6 +677,7 @@ impl<C, K, V, F, B: ?Sized> MapLaneWithEntry<C, K, V, F, B> {
 pub struct MapLaneSync<C, K, V> {
     projection: for<'a> fn(&'a C) -> &'a MapLane<K, V>,
     id: Option<Uuid>,
+    since: Option<SyncVersion>,
 }
 
 impl<C, K, V> MapLaneSync<C, K, V> {
@@ -455,6 +685,20 @@ impl<C, K, V> MapLaneSync<C, K, V> {
         MapLaneSync {
             projection,
             id: Some(id),
+            since: None,
+        }
+    }
+
+    /// Request a sync for a remote that already holds the state of the lane as of a previous version.
+    pub fn since(
+        projection: for<'a> fn(&'a C) -> &'a MapLane<K, V>,
+        id: Uuid,
+        since: SyncVersion,
+    ) -> Self {
+        MapLaneSync {
+            projection,
+            id: Some(id),
+            since: Some(since),
         }
     }
 }
@@ -471,10 +715,17 @@ where
         _meta: AgentMetadata,
         context: &C,
     ) -> StepResult<Self::Completion> {
-        let MapLaneSync { projection, id } = self;
+        let MapLaneSync {
+            projection,
+            id,
+            since,
+        } = self;
         if let Some(id) = id.take() {
             let lane = projection(context);
-            lane.sync(id);
+            match since.take() {
+                Some(since) => lane.sync_since(id, since),
+                None => lane.sync(id),
+            }
             StepResult::Complete {
                 modified_item: Some(Modification::no_trigger(lane.id)),
                 result: (),
@@ -528,7 +779,9 @@ impl<K, V> DecodeMapMessage<K, V> {
 }
 
 //TODO: The decoders should be shifted elsewhere so they don't need constantly recreating.
-fn try_decode<T: RecognizerReadable>(mut buffer: BytesMut) -> Result<T, EventHandlerError> {
+pub(crate) fn try_decode<T: RecognizerReadable>(
+    mut buffer: BytesMut,
+) -> Result<T, EventHandlerError> {
     let mut decoder = RecognizerDecoder::new(T::make_recognizer());
     match decoder.decode_eof(&mut buffer) {
         Ok(Some(value)) => Ok(value),
@@ -670,7 +923,7 @@ where
     where
         Self: 'static,
         C: 'a,
-        B: ?Sized +'static,
+        B: ?Sized + 'static,
         V: Borrow<B>,
         F: FnOnce(Option<&B>) -> U + Send + 'a;
 
@@ -14,30 +14,36 @@
 
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::time::Duration;
 
 use bytes::BytesMut;
+use futures::{stream::FuturesUnordered, StreamExt};
 use swimos_agent_protocol::{
     encoding::lane::RawMapLaneResponseDecoder, MapLaneResponse, MapOperation,
 };
-use swimos_api::agent::AgentConfig;
+use swimos_api::agent::{AgentConfig, SyncVersion};
 use swimos_recon::parser::parse_recognize;
 use swimos_utilities::routing::RouteUri;
+use tokio::time::Instant;
 use tokio_util::codec::Decoder;
 use uuid::Uuid;
 
 use crate::{
     agent_model::WriteResult,
-    event_handler::{EventHandlerError, HandlerAction, Modification, StepResult},
-    item::MapItem,
+    event_handler::{
+        ActionContext, BoxJoinLaneInit, EventHandlerError, HandlerAction, HandlerFuture,
+        Modification, StepResult,
+    },
+    item::{EventCount, MapItem},
     lanes::{
         map::{
             MapLane, MapLaneClear, MapLaneEvent, MapLaneGet, MapLaneGetMap, MapLaneRemove,
-            MapLaneSync, MapLaneTransformEntry, MapLaneUpdate,
+            MapLaneSync, MapLaneTransaction, MapLaneTransformEntry, MapLaneUpdate,
         },
         LaneItem,
     },
     meta::AgentMetadata,
-    test_context::dummy_context,
+    test_context::{dummy_context, no_downlink, DummyAgentContext},
 };
 
 use super::MapLaneWithEntry;
@@ -228,12 +234,16 @@ fn write_to_buffer_clear() {
 struct Operations {
     events: Vec<MapOperation<i32, String>>,
     sync: HashMap<Uuid, Vec<MapOperation<i32, String>>>,
+    versions: HashMap<Uuid, SyncVersion>,
+    batches: Vec<u64>,
 }
 
 fn consume_events(lane: &MapLane<i32, String>) -> Operations {
     let mut events = vec![];
     let mut sync_pending = HashMap::new();
     let mut sync = HashMap::new();
+    let mut versions = HashMap::new();
+    let mut batches = vec![];
 
     let mut decoder = RawMapLaneResponseDecoder::default();
     let mut buffer = BytesMut::new();
@@ -245,27 +255,37 @@ fn consume_events(lane: &MapLane<i32, String>) -> Operations {
             break;
         }
 
-        let content = decoder
-            .decode(&mut buffer)
-            .expect("Invalid frame.")
-            .expect("Incomplete frame.");
-
-        match content {
-            MapLaneResponse::StandardEvent(operation) => {
-                events.push(interpret(operation));
-            }
-            MapLaneResponse::SyncEvent(id, operation) => {
-                sync_pending
-                    .entry(id)
-                    .or_insert_with(Vec::new)
-                    .push(interpret(operation));
-            }
-            MapLaneResponse::Synced(id) => {
-                assert!(!sync.contains_key(&id));
-                let ops = sync_pending.remove(&id).unwrap_or_default();
-                sync.insert(id, ops);
+        while !buffer.is_empty() {
+            let content = decoder
+                .decode(&mut buffer)
+                .expect("Invalid frame.")
+                .expect("Incomplete frame.");
+
+            match content {
+                MapLaneResponse::StandardEvent(operation) => {
+                    events.push(interpret(operation));
+                }
+                MapLaneResponse::SyncEvent(id, operation) => {
+                    sync_pending
+                        .entry(id)
+                        .or_insert_with(Vec::new)
+                        .push(interpret(operation));
+                }
+                MapLaneResponse::Synced(id) => {
+                    assert!(!sync.contains_key(&id));
+                    let ops = sync_pending.remove(&id).unwrap_or_default();
+                    sync.insert(id, ops);
+                }
+                MapLaneResponse::SyncedAt(id, version) => {
+                    assert!(!sync.contains_key(&id));
+                    let ops = sync_pending.remove(&id).unwrap_or_default();
+                    sync.insert(id, ops);
+                    versions.insert(id, version);
+                }
+                MapLaneResponse::EventBatch(n) => batches.push(n),
+                MapLaneResponse::Correlated(_) | MapLaneResponse::Originated(_) => {}
+                MapLaneResponse::Initialized => {}
             }
-            MapLaneResponse::Initialized => {}
         }
 
         if matches!(result, WriteResult::Done) {
@@ -274,7 +294,12 @@ fn consume_events(lane: &MapLane<i32, String>) -> Operations {
     }
     assert!(sync_pending.is_empty());
 
-    Operations { events, sync }
+    Operations {
+        events,
+        sync,
+        versions,
+        batches,
+    }
 }
 
 fn interpret(op: MapOperation<BytesMut, BytesMut>) -> MapOperation<i32, String> {
@@ -303,7 +328,7 @@ fn write_multiple_events_to_buffer() {
     lane.remove(&K1);
     lane.update(K3, "altered".to_owned());
 
-    let Operations { events, sync } = consume_events(&lane);
+    let Operations { events, sync, .. } = consume_events(&lane);
 
     assert!(sync.is_empty());
 
@@ -329,7 +354,7 @@ fn updates_to_one_key_overwrite() {
     lane.update(K3, "altered".to_string());
     lane.update(ABSENT, "changed".to_owned());
 
-    let Operations { events, sync } = consume_events(&lane);
+    let Operations { events, sync, .. } = consume_events(&lane);
 
     assert!(sync.is_empty());
 
@@ -355,7 +380,7 @@ fn clear_resets_event_queue() {
     lane.update(K3, "altered".to_owned());
     lane.clear();
 
-    let Operations { events, sync } = consume_events(&lane);
+    let Operations { events, sync, .. } = consume_events(&lane);
 
     assert!(sync.is_empty());
 
@@ -386,7 +411,7 @@ fn sync_lane_state() {
 
     lane.sync(SYNC_ID1);
 
-    let Operations { events, sync } = consume_events(&lane);
+    let Operations { events, sync, .. } = consume_events(&lane);
     assert!(events.is_empty());
     assert_eq!(sync.len(), 1);
 
@@ -406,7 +431,7 @@ fn sync_twice_lane_state() {
     lane.sync(SYNC_ID1);
     lane.sync(SYNC_ID2);
 
-    let Operations { events, sync } = consume_events(&lane);
+    let Operations { events, sync, .. } = consume_events(&lane);
     assert!(events.is_empty());
     assert_eq!(sync.len(), 2);
 
@@ -421,6 +446,126 @@ fn sync_twice_lane_state() {
     assert_eq!(sync_map2, expected);
 }
 
+fn op_key(op: &MapOperation<i32, String>) -> i32 {
+    match op {
+        MapOperation::Update { key, .. } | MapOperation::Remove { key } => *key,
+        MapOperation::Clear => i32::MIN,
+    }
+}
+
+#[test]
+fn sync_since_unknown_version() {
+    let lane = MapLane::new(ID, init());
+
+    lane.sync_since(SYNC_ID1, SyncVersion::new(0, 0));
+
+    let Operations {
+        events,
+        sync,
+        versions,
+        ..
+    } = consume_events(&lane);
+    assert!(events.is_empty());
+    assert_eq!(sync.len(), 1);
+    assert!(versions.contains_key(&SYNC_ID1));
+
+    let ops = sync.get(&SYNC_ID1).expect("Incorrect Sync ID.");
+    let (first, rest) = ops.split_first().expect("Expected operations.");
+    assert_eq!(first, &MapOperation::Clear);
+
+    let expected: HashMap<_, _> = [(K1, V1), (K2, V2), (K3, V3)]
+        .into_iter()
+        .map(|(k, v)| (k, v.to_owned()))
+        .collect();
+    assert_eq!(to_updates(rest), expected);
+}
+
+#[test]
+fn sync_since_previous_version() {
+    let lane = MapLane::new(ID, init());
+
+    lane.sync_since(SYNC_ID1, SyncVersion::new(0, 0));
+    let Operations { versions, .. } = consume_events(&lane);
+    let version = versions[&SYNC_ID1];
+
+    lane.update(K2, "altered".to_owned());
+    lane.remove(&K3);
+    lane.update(ABSENT, "added".to_owned());
+    let Operations { events, .. } = consume_events(&lane);
+    assert_eq!(events.len(), 3);
+
+    lane.sync_since(SYNC_ID2, version);
+
+    let Operations {
+        events,
+        sync,
+        versions,
+        ..
+    } = consume_events(&lane);
+    assert!(events.is_empty());
+
+    let mut ops = sync.get(&SYNC_ID2).expect("Incorrect Sync ID.").clone();
+    ops.sort_by_key(op_key);
+    let mut expected = vec![
+        MapOperation::Remove { key: K3 },
+        MapOperation::Update {
+            key: K2,
+            value: "altered".to_owned(),
+        },
+        MapOperation::Update {
+            key: ABSENT,
+            value: "added".to_owned(),
+        },
+    ];
+    expected.sort_by_key(op_key);
+    assert_eq!(ops, expected);
+
+    let new_version = versions[&SYNC_ID2];
+    assert_eq!(new_version.epoch, version.epoch);
+    assert_eq!(new_version.version, version.version + 3);
+}
+
+#[test]
+fn sync_since_version_before_clear() {
+    let lane = MapLane::new(ID, init());
+
+    lane.sync_since(SYNC_ID1, SyncVersion::new(0, 0));
+    let Operations { versions, .. } = consume_events(&lane);
+    let version = versions[&SYNC_ID1];
+
+    lane.clear();
+    lane.update(ABSENT, "added".to_owned());
+    consume_events(&lane);
+
+    lane.sync_since(SYNC_ID2, version);
+
+    let Operations { sync, .. } = consume_events(&lane);
+    let expected = vec![
+        MapOperation::Clear,
+        MapOperation::Update {
+            key: ABSENT,
+            value: "added".to_owned(),
+        },
+    ];
+    assert_eq!(sync.get(&SYNC_ID2), Some(&expected));
+}
+
+#[test]
+fn versioned_sync_waits_for_events() {
+    let lane = MapLane::new(ID, HashMap::new());
+
+    lane.sync_since(SYNC_ID1, SyncVersion::new(0, 0));
+    lane.update(K1, V1.to_owned());
+    lane.update(K2, V2.to_owned());
+
+    let Operations {
+        events, versions, ..
+    } = consume_events(&lane);
+    assert_eq!(events.len(), 2);
+    // The reported version must include all of the events that were sent before it.
+    assert_eq!(versions[&SYNC_ID1].version, 2);
+}
+
 #[test]
 fn sync_lane_state_and_event() {
     let lane = MapLane::new(ID, init());
@@ -428,7 +573,7 @@ fn sync_lane_state_and_event() {
     lane.sync(SYNC_ID1);
     lane.update(ABSENT, "added".to_owned());
 
-    let Operations { events, sync } = consume_events(&lane);
+    let Operations { events, sync, .. } = consume_events(&lane);
 
     let expected_events = vec![MapOperation::Update {
         key: ABSENT,
@@ -447,6 +592,78 @@ fn sync_lane_state_and_event() {
     assert_eq!(sync_map, expected_sync);
 }
 
+#[test]
+fn transaction_produces_single_batch() {
+    let lane = MapLane::new(ID, init());
+
+    lane.transaction(vec![
+        MapOperation::Update {
+            key: K1,
+            value: "altered".to_owned(),
+        },
+        MapOperation::Remove { key: K2 },
+        MapOperation::Update {
+            key: ABSENT,
+            value: "added".to_owned(),
+        },
+    ]);
+
+    let Operations {
+        events, batches, ..
+    } = consume_events(&lane);
+
+    let expected_events = vec![
+        MapOperation::Update {
+            key: K1,
+            value: "altered".to_owned(),
+        },
+        MapOperation::Remove { key: K2 },
+        MapOperation::Update {
+            key: ABSENT,
+            value: "added".to_owned(),
+        },
+    ];
+    assert_eq!(events, expected_events);
+    assert_eq!(batches, vec![3]);
+    assert_eq!(lane.event_count(), 3);
+}
+
+#[test]
+fn transaction_includes_pending_events() {
+    let lane = MapLane::new(ID, init());
+
+    lane.update(K3, "first".to_owned());
+    lane.transaction(vec![
+        MapOperation::Update {
+            key: K1,
+            value: "altered".to_owned(),
+        },
+        MapOperation::Remove { key: ABSENT },
+    ]);
+    lane.update(K2, "last".to_owned());
+
+    let Operations {
+        events, batches, ..
+    } = consume_events(&lane);
+
+    let expected_events = vec![
+        MapOperation::Update {
+            key: K3,
+            value: "first".to_owned(),
+        },
+        MapOperation::Update {
+            key: K1,
+            value: "altered".to_owned(),
+        },
+        MapOperation::Update {
+            key: K2,
+            value: "last".to_owned(),
+        },
+    ];
+    assert_eq!(events, expected_events);
+    assert_eq!(batches, vec![2]);
+}
+
 const CONFIG: AgentConfig = AgentConfig::DEFAULT;
 const NODE_URI: &str = "/node";
 
@@ -559,6 +776,69 @@ fn map_lane_update_event_handler() {
     ));
 }
 
+#[test]
+fn map_lane_transaction_event_handler() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_init();
+
+    let mut handler = MapLaneTransaction::new(TestAgent::LANE, |map: &HashMap<i32, String>| {
+        let value = map.get(&K1).cloned().unwrap_or_default();
+        Ok::<_, std::fmt::Error>(vec![
+            MapOperation::Remove { key: K1 },
+            MapOperation::Update { key: K2, value },
+        ])
+    });
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    check_result(result, true, false, Some(()));
+
+    agent.lane.get_map(|map| {
+        assert_eq!(map.len(), 2);
+        assert!(!map.contains_key(&K1));
+        assert_eq!(map.get(&K2).map(String::as_str), Some(V1));
+    });
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+    ));
+}
+
+#[test]
+fn failed_map_lane_transaction() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_init();
+
+    let mut handler = MapLaneTransaction::new(TestAgent::LANE, |_: &HashMap<i32, String>| {
+        Err::<Vec<MapOperation<i32, String>>, _>(std::fmt::Error)
+    });
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::HandlerFailed(_))
+    ));
+
+    agent.lane.get_map(|map| assert_eq!(map, &init()));
+}
+
 #[test]
 fn map_lane_remove_event_handler() {
     let uri = make_uri();
@@ -714,7 +994,7 @@ fn map_lane_sync_event_handler() {
         StepResult::Fail(EventHandlerError::SteppedAfterComplete)
     ));
 
-    let Operations { events, sync } = consume_events(&agent.lane);
+    let Operations { events, sync, .. } = consume_events(&agent.lane);
 
     assert!(events.is_empty());
     assert_eq!(sync.len(), 1);
@@ -840,3 +1120,174 @@ fn map_lane_with_entry_handler_present() {
     );
     check_result(result, false, false, Some(Some(V1.to_owned())));
 }
+
+const TTL: Duration = Duration::from_secs(30);
+
+impl TestAgent {
+    fn with_ttl() -> Self {
+        TestAgent {
+            lane: MapLane::with_ttl(LANE_ID, Default::default(), TTL),
+        }
+    }
+}
+
+fn expiry_context<'a>(
+    pending: &'a FuturesUnordered<HandlerFuture<TestAgent>>,
+    join_lane_init: &'a mut HashMap<u64, BoxJoinLaneInit<'static, TestAgent>>,
+    ad_hoc_buffer: &'a mut BytesMut,
+) -> ActionContext<'a, TestAgent> {
+    ActionContext::new(
+        pending,
+        &DummyAgentContext,
+        &no_downlink,
+        join_lane_init,
+        ad_hoc_buffer,
+    )
+}
+
+fn start_expiry(agent: &TestAgent, pending: &FuturesUnordered<HandlerFuture<TestAgent>>) {
+    agent.lane.start_expiry(
+        TestAgent::LANE,
+        &mut expiry_context(pending, &mut HashMap::new(), &mut BytesMut::new()),
+    );
+}
+
+/// Wait for the timer of the lane to fire and then run the handler that it produces to
+/// completion, returning the number of entries that were removed.
+async fn run_expiry(
+    agent: &TestAgent,
+    meta: AgentMetadata<'_>,
+    pending: &mut FuturesUnordered<HandlerFuture<TestAgent>>,
+) -> usize {
+    let mut handler = pending
+        .next()
+        .await
+        .expect("No expiry timer was scheduled.");
+    let mut removed = 0;
+    loop {
+        let result = handler.step(
+            &mut expiry_context(pending, &mut HashMap::new(), &mut BytesMut::new()),
+            meta,
+            agent,
+        );
+        match result {
+            StepResult::Continue { .. } => {
+                check_result(result, true, true, None);
+                removed += 1;
+            }
+            _ => {
+                check_result(result, false, false, Some(()));
+                break removed;
+            }
+        }
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn map_lane_entry_expires() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_ttl();
+    assert_eq!(agent.lane.ttl(), Some(TTL));
+
+    let mut pending = FuturesUnordered::new();
+    start_expiry(&agent, &pending);
+    let mut handler = MapLaneUpdate::new(TestAgent::LANE, K1, V1.to_owned());
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    check_result(result, true, true, Some(()));
+    agent.lane.read_with_prev(|_, _| ());
+
+    let start = Instant::now();
+    assert_eq!(run_expiry(&agent, meta, &mut pending).await, 1);
+    assert_eq!(start.elapsed(), TTL);
+
+    agent.lane.get_map(|map| assert!(map.is_empty()));
+    let event = agent.lane.read_with_prev(|event, _| event);
+    assert_eq!(event, Some(MapLaneEvent::Remove(K1, V1.to_owned())));
+
+    //The timer is scheduled again for the next entries.
+    assert_eq!(pending.len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn map_lane_entries_share_timer() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_ttl();
+
+    let mut pending = FuturesUnordered::new();
+    start_expiry(&agent, &pending);
+    agent.lane.update(K1, V1.to_owned());
+    agent.lane.update(K2, V2.to_owned());
+    agent.lane.transform_entry(K3, |_| Some(V3.to_owned()));
+    assert_eq!(pending.len(), 1);
+
+    assert_eq!(run_expiry(&agent, meta, &mut pending).await, 3);
+    agent.lane.get_map(|map| assert!(map.is_empty()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn map_lane_updated_entry_does_not_expire_early() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_ttl();
+
+    let mut pending = FuturesUnordered::new();
+    start_expiry(&agent, &pending);
+    let start = Instant::now();
+    agent.lane.update(K1, V1.to_owned());
+    tokio::time::advance(TTL / 2).await;
+    agent.lane.update(K1, V2.to_owned());
+
+    assert_eq!(run_expiry(&agent, meta, &mut pending).await, 1);
+    assert_eq!(start.elapsed(), TTL + TTL / 2);
+    agent.lane.get_map(|map| assert!(map.is_empty()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn map_lane_removed_entry_does_not_expire() {
+    let agent = TestAgent::with_ttl();
+
+    let mut pending = FuturesUnordered::new();
+    start_expiry(&agent, &pending);
+    agent.lane.update(K1, V1.to_owned());
+    agent.lane.remove(&K1);
+
+    let next = tokio::time::timeout(TTL * 2, pending.next()).await;
+    assert!(next.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn map_lane_entry_written_after_expiry_retained() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_ttl();
+
+    let mut pending = FuturesUnordered::new();
+    start_expiry(&agent, &pending);
+    agent.lane.update(K1, V1.to_owned());
+
+    let mut handler = pending
+        .next()
+        .await
+        .expect("No expiry timer was scheduled.");
+    //The entry is written to after the timer fires but before the handler runs.
+    agent.lane.update(K1, V2.to_owned());
+    let result = handler.step(
+        &mut expiry_context(&pending, &mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    check_result(result, false, false, Some(()));
+    agent
+        .lane
+        .get(&K1, |v| assert_eq!(v.map(String::as_str), Some(V2)));
+}
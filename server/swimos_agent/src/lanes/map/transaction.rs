@@ -0,0 +1,81 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, hash::Hash};
+
+use swimos_agent_protocol::MapOperation;
+
+use crate::{
+    event_handler::{ActionContext, EventHandlerError, HandlerAction, Modification, StepResult},
+    meta::AgentMetadata,
+};
+
+use super::MapLane;
+
+/// An [event handler](crate::event_handler::EventHandler) that applies several operations to a map
+/// lane as a single step. The operations are computed from the current state of the map and, if this
+/// succeeds, they are all applied before any other handler can observe the lane. Remotes that are
+/// linked to the lane receive the operations as a single batch, rather than one event at a time. The
+/// lifecycle event handlers of the lane are not run for the individual operations. If the computation
+/// fails, the lane is not modified.
+pub struct MapLaneTransaction<C, K, V, F> {
+    projection: for<'a> fn(&'a C) -> &'a MapLane<K, V>,
+    f: Option<F>,
+}
+
+impl<C, K, V, F> MapLaneTransaction<C, K, V, F> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    /// * `f` - Computes the operations to apply from the current state of the map.
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a MapLane<K, V>, f: F) -> Self {
+        MapLaneTransaction {
+            projection,
+            f: Some(f),
+        }
+    }
+}
+
+impl<C, K, V, F, E> HandlerAction<C> for MapLaneTransaction<C, K, V, F>
+where
+    K: Clone + Eq + Hash,
+    F: FnOnce(&HashMap<K, V>) -> Result<Vec<MapOperation<K, V>>, E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let MapLaneTransaction { projection, f } = self;
+        if let Some(f) = f.take() {
+            let lane = projection(context);
+            match lane.get_map(f) {
+                Ok(ops) if ops.is_empty() => StepResult::done(()),
+                Ok(ops) => {
+                    lane.transaction(ops);
+                    StepResult::Complete {
+                        modified_item: Some(Modification::no_trigger(lane.id)),
+                        result: (),
+                    }
+                }
+                Err(error) => StepResult::Fail(EventHandlerError::failed(error)),
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
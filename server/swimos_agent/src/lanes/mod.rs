@@ -19,12 +19,21 @@ pub mod demand;
 #[doc(hidden)]
 pub mod demand_map;
 #[doc(hidden)]
+pub mod dynamic;
+mod expiry;
+#[doc(hidden)]
+pub mod history;
+#[doc(hidden)]
 pub mod http;
 mod join;
 #[doc(hidden)]
 pub mod map;
+#[doc(hidden)]
+pub mod ordered_map;
 mod queues;
 #[doc(hidden)]
+pub mod stats;
+#[doc(hidden)]
 pub mod supply;
 #[doc(hidden)]
 pub mod value;
@@ -33,6 +42,7 @@ pub use join::map as join_map;
 #[doc(hidden)]
 pub use join::value as join_value;
 pub use join::LinkClosedResponse;
+pub use swimos_agent_protocol::{Encoded, PayloadCodec, RawBytes};
 
 use bytes::BytesMut;
 
@@ -40,16 +50,20 @@ use crate::{agent_model::WriteResult, item::AgentItem};
 
 #[doc(inline)]
 pub use self::{
-    command::CommandLane,
+    command::{BatchCommandLane, CommandLane, CommandLaneLimits, OverflowPolicy},
     demand::DemandLane,
     demand_map::DemandMapLane,
+    dynamic::{DynamicLaneSet, DynamicMapLanes, DynamicValueLanes},
+    history::{HistoryLane, HistoryRetention},
     http::{HttpLane, SimpleHttpLane},
     join::JoinLaneKind,
     join_map::JoinMapLane,
     join_value::JoinValueLane,
     map::MapLane,
+    ordered_map::OrderedMapLane,
+    stats::{Stats, StatsLane},
     supply::SupplyLane,
-    value::ValueLane,
+    value::{TransactionLanes, ValueLane},
 };
 
 /// Wrapper to allow projection function pointers to be exposed as event handler transforms
@@ -0,0 +1,856 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    borrow::Borrow,
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    hash::Hash,
+    marker::PhantomData,
+    ops::Bound,
+};
+
+use bytes::{Bytes, BytesMut};
+use frunk::{Coprod, Coproduct};
+use static_assertions::assert_impl_all;
+use swimos_agent_protocol::{encoding::lane::MapLaneResponseEncoder, MapMessage, MapOperation};
+use swimos_api::agent::KeyRange;
+use swimos_form::{read::RecognizerReadable, write::StructuralWritable};
+use tokio_util::codec::Encoder;
+use uuid::Uuid;
+
+use crate::{
+    agent_model::WriteResult,
+    event_handler::{
+        ActionContext, AndThen, EventHandlerError, HandlerAction, HandlerActionExt, HandlerTrans,
+        Modification, StepResult,
+    },
+    item::{AgentItem, EventCount, InspectableMapLikeItem, MutableMapLikeItem},
+    map_storage::{MapEventQueue, TransformEntryResult},
+    meta::AgentMetadata,
+};
+
+use super::{
+    map::{try_decode, DecodeMapMessage},
+    queues::WriteQueues,
+    LaneItem, ProjTransform,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Model of an ordered map lane. This is a variant of the [map lane](super::MapLane) that is backed by an
+/// ordered map. When a remote syncs with the lane, the entries are sent in the order of their keys and a
+/// sync may be restricted to the entries with keys in a range (with the `from` and `to` slots of the sync
+/// envelope). This is appropriate for large maps, keyed by time, where a client will page through ranges
+/// of the entries.
+///
+/// Ordered map lanes do not currently support lifecycle event handlers.
+#[derive(Debug)]
+pub struct OrderedMapLane<K, V> {
+    id: u64,
+    inner: RefCell<OrderedMapLaneInner<K, V>>,
+}
+
+assert_impl_all!(OrderedMapLane<(), ()>: Send);
+
+#[derive(Debug)]
+struct OrderedMapLaneInner<K, V> {
+    content: BTreeMap<K, V>,
+    queue: WriteQueues<K>,
+    event_count: u64,
+}
+
+impl<K, V> OrderedMapLaneInner<K, V>
+where
+    K: Clone + Ord + Hash,
+{
+    fn push(&mut self, action: MapOperation<K, ()>) {
+        self.event_count += 1;
+        self.queue.push_operation(action);
+    }
+}
+
+impl<K, V> OrderedMapLane<K, V> {
+    /// # Arguments
+    /// * `id` - The ID of the lane. This should be unique within an agent.
+    /// * `init` - The initial contents of the map.
+    pub fn new(id: u64, init: BTreeMap<K, V>) -> Self {
+        OrderedMapLane {
+            id,
+            inner: RefCell::new(OrderedMapLaneInner {
+                content: init,
+                queue: Default::default(),
+                event_count: 0,
+            }),
+        }
+    }
+}
+
+impl<K, V> AgentItem for OrderedMapLane<K, V> {
+    fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<K, V> EventCount for OrderedMapLane<K, V> {
+    fn event_count(&self) -> u64 {
+        self.inner.borrow().event_count
+    }
+}
+
+impl<K, V> OrderedMapLane<K, V>
+where
+    K: Clone + Ord + Hash,
+{
+    /// Replace the contents of the map (without generating any events).
+    pub(crate) fn init(&self, map: BTreeMap<K, V>) {
+        self.inner.borrow_mut().content = map;
+    }
+
+    /// Update the value associated with a key.
+    pub(crate) fn update(&self, key: K, value: V) {
+        let mut guard = self.inner.borrow_mut();
+        guard.content.insert(key.clone(), value);
+        guard.push(MapOperation::Update { key, value: () });
+    }
+
+    /// Remove an entry from the map.
+    pub(crate) fn remove(&self, key: &K) {
+        let mut guard = self.inner.borrow_mut();
+        if guard.content.remove(key).is_some() {
+            guard.push(MapOperation::Remove { key: key.clone() });
+        }
+    }
+
+    /// Clear the map.
+    pub(crate) fn clear(&self) {
+        let mut guard = self.inner.borrow_mut();
+        guard.content.clear();
+        guard.push(MapOperation::Clear);
+    }
+
+    /// Retain only the first `n` entries of the map (in the order of their keys).
+    pub(crate) fn take(&self, n: usize) {
+        let mut guard = self.inner.borrow_mut();
+        if n < guard.content.len() {
+            let split_key = guard.content.keys().nth(n).cloned();
+            if let Some(split_key) = split_key {
+                let removed = guard.content.split_off(&split_key);
+                for key in removed.into_keys() {
+                    guard.push(MapOperation::Remove { key });
+                }
+            }
+        }
+    }
+
+    /// Remove the first `n` entries of the map (in the order of their keys).
+    pub(crate) fn drop_first(&self, n: usize) {
+        let mut guard = self.inner.borrow_mut();
+        for _ in 0..n {
+            if let Some((key, _)) = guard.content.pop_first() {
+                guard.push(MapOperation::Remove { key });
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Transform the value associated with a key.
+    pub fn transform_entry<F>(&self, key: K, f: F) -> TransformEntryResult
+    where
+        F: FnOnce(Option<&V>) -> Option<V>,
+    {
+        let mut guard = self.inner.borrow_mut();
+        match f(guard.content.get(&key)) {
+            Some(value) => {
+                guard.content.insert(key.clone(), value);
+                guard.push(MapOperation::Update { key, value: () });
+                TransformEntryResult::Update
+            }
+            None if guard.content.remove(&key).is_some() => {
+                guard.push(MapOperation::Remove { key });
+                TransformEntryResult::Remove
+            }
+            None => TransformEntryResult::NoChange,
+        }
+    }
+
+    /// Read a value from the map, if it exists.
+    pub fn get<Q, F, R>(&self, key: &Q, f: F) -> R
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        F: FnOnce(Option<&V>) -> R,
+    {
+        f(self.inner.borrow().content.get(key))
+    }
+
+    /// Read the complete state of the map.
+    pub fn get_map<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&BTreeMap<K, V>) -> R,
+    {
+        f(&self.inner.borrow().content)
+    }
+
+    /// Start a sync operation from the lane to the specified remote, restricted to the entries with
+    /// keys in a range. The entries will be sent in the order of their keys.
+    pub(crate) fn sync_range(&self, id: Uuid, range: KeyRange<K>) {
+        let mut guard = self.inner.borrow_mut();
+        let KeyRange { from, to } = range;
+        let keys = match (&from, &to) {
+            (Some(from), Some(to)) if from >= to => VecDeque::new(),
+            _ => {
+                let lower = from.map(Bound::Included).unwrap_or(Bound::Unbounded);
+                let upper = to.map(Bound::Excluded).unwrap_or(Bound::Unbounded);
+                guard
+                    .content
+                    .range((lower, upper))
+                    .map(|(k, _)| k.clone())
+                    .collect()
+            }
+        };
+        guard.queue.sync(id, keys);
+    }
+
+    pub fn with_entry<F, B, U>(&self, key: &K, f: F) -> U
+    where
+        B: ?Sized,
+        V: Borrow<B>,
+        F: FnOnce(Option<&B>) -> U,
+    {
+        f(self.inner.borrow().content.get(key).map(Borrow::borrow))
+    }
+}
+
+const INFALLIBLE_SER: &str = "Serializing lane responses to recon should be infallible.";
+
+impl<K, V> LaneItem for OrderedMapLane<K, V>
+where
+    K: Clone + Ord + Hash + StructuralWritable,
+    V: StructuralWritable,
+{
+    fn write_to_buffer(&self, buffer: &mut BytesMut) -> WriteResult {
+        let mut encoder = MapLaneResponseEncoder::default();
+        let mut guard = self.inner.borrow_mut();
+        let OrderedMapLaneInner { content, queue, .. } = &mut *guard;
+        if let Some(op) = <WriteQueues<K> as MapEventQueue<K, V>>::pop(queue, content) {
+            encoder.encode(op, buffer).expect(INFALLIBLE_SER);
+            if queue.is_empty() {
+                WriteResult::Done
+            } else {
+                WriteResult::DataStillAvailable
+            }
+        } else {
+            WriteResult::NoData
+        }
+    }
+}
+
+///  An [event handler](crate::event_handler::EventHandler)`] that will update the value of an entry in the map.
+pub struct OrderedMapLaneUpdate<C, K, V> {
+    projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>,
+    key_value: Option<(K, V)>,
+}
+
+impl<C, K, V> OrderedMapLaneUpdate<C, K, V> {
+    pub fn new(
+        projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>,
+        key: K,
+        value: V,
+    ) -> Self {
+        OrderedMapLaneUpdate {
+            projection,
+            key_value: Some((key, value)),
+        }
+    }
+}
+
+impl<C, K, V> HandlerAction<C> for OrderedMapLaneUpdate<C, K, V>
+where
+    K: Clone + Ord + Hash,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let OrderedMapLaneUpdate {
+            projection,
+            key_value,
+        } = self;
+        if let Some((key, value)) = key_value.take() {
+            let lane = projection(context);
+            lane.update(key, value);
+            StepResult::Complete {
+                modified_item: Some(Modification::of(lane.id)),
+                result: (),
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+///  An [event handler](crate::event_handler::EventHandler)`] that will remove an entry from the map.
+pub struct OrderedMapLaneRemove<C, K, V> {
+    projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>,
+    key: Option<K>,
+}
+
+impl<C, K, V> OrderedMapLaneRemove<C, K, V> {
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>, key: K) -> Self {
+        OrderedMapLaneRemove {
+            projection,
+            key: Some(key),
+        }
+    }
+}
+
+impl<C, K, V> HandlerAction<C> for OrderedMapLaneRemove<C, K, V>
+where
+    K: Clone + Ord + Hash,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let OrderedMapLaneRemove { projection, key } = self;
+        if let Some(key) = key.take() {
+            let lane = projection(context);
+            lane.remove(&key);
+            StepResult::Complete {
+                modified_item: Some(Modification::of(lane.id)),
+                result: (),
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+///  An [event handler](crate::event_handler::EventHandler)`] that will clear the map.
+pub struct OrderedMapLaneClear<C, K, V> {
+    projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>,
+    done: bool,
+}
+
+impl<C, K, V> OrderedMapLaneClear<C, K, V> {
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>) -> Self {
+        OrderedMapLaneClear {
+            projection,
+            done: false,
+        }
+    }
+}
+
+impl<C, K, V> HandlerAction<C> for OrderedMapLaneClear<C, K, V>
+where
+    K: Clone + Ord + Hash,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let OrderedMapLaneClear { projection, done } = self;
+        if !*done {
+            *done = true;
+            let lane = projection(context);
+            lane.clear();
+            StepResult::Complete {
+                modified_item: Some(Modification::of(lane.id)),
+                result: (),
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+/// Whether to retain or remove the first entries of the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Truncate {
+    Take(usize),
+    Drop(usize),
+}
+
+///  An [event handler](crate::event_handler::EventHandler)`] that will either retain or remove the
+/// first `n` entries of the map (in the order of their keys).
+pub struct OrderedMapLaneTruncate<C, K, V> {
+    projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>,
+    truncate: Option<Truncate>,
+}
+
+impl<C, K, V> OrderedMapLaneTruncate<C, K, V> {
+    /// Retain only the first `n` entries of the map.
+    pub fn take(projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>, n: usize) -> Self {
+        OrderedMapLaneTruncate {
+            projection,
+            truncate: Some(Truncate::Take(n)),
+        }
+    }
+
+    /// Remove the first `n` entries of the map.
+    pub fn drop(projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>, n: usize) -> Self {
+        OrderedMapLaneTruncate {
+            projection,
+            truncate: Some(Truncate::Drop(n)),
+        }
+    }
+}
+
+impl<C, K, V> HandlerAction<C> for OrderedMapLaneTruncate<C, K, V>
+where
+    K: Clone + Ord + Hash,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let OrderedMapLaneTruncate {
+            projection,
+            truncate,
+        } = self;
+        if let Some(truncate) = truncate.take() {
+            let lane = projection(context);
+            match truncate {
+                Truncate::Take(n) => lane.take(n),
+                Truncate::Drop(n) => lane.drop_first(n),
+            }
+            StepResult::Complete {
+                modified_item: Some(Modification::of(lane.id)),
+                result: (),
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+///  An [event handler](crate::event_handler::EventHandler)`] that will get an entry from the map.
+pub struct OrderedMapLaneGet<C, K, V> {
+    projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>,
+    key: K,
+    done: bool,
+}
+
+impl<C, K, V> OrderedMapLaneGet<C, K, V> {
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>, key: K) -> Self {
+        OrderedMapLaneGet {
+            projection,
+            key,
+            done: false,
+        }
+    }
+}
+
+impl<C, K, V> HandlerAction<C> for OrderedMapLaneGet<C, K, V>
+where
+    K: Clone + Ord + Hash,
+    V: Clone,
+{
+    type Completion = Option<V>;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let OrderedMapLaneGet {
+            projection,
+            key,
+            done,
+        } = self;
+        if !*done {
+            *done = true;
+            let lane = projection(context);
+            StepResult::done(lane.get(key, |v| v.cloned()))
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+///  An [event handler](crate::event_handler::EventHandler)`] that will read the entire state of an ordered
+/// map lane.
+pub struct OrderedMapLaneGetMap<C, K, V> {
+    projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>,
+    done: bool,
+}
+
+impl<C, K, V> OrderedMapLaneGetMap<C, K, V> {
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>) -> Self {
+        OrderedMapLaneGetMap {
+            projection,
+            done: false,
+        }
+    }
+}
+
+impl<C, K, V> HandlerAction<C> for OrderedMapLaneGetMap<C, K, V>
+where
+    K: Clone + Ord + Hash,
+    V: Clone,
+{
+    type Completion = BTreeMap<K, V>;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let OrderedMapLaneGetMap { projection, done } = self;
+        if !*done {
+            *done = true;
+            let lane = projection(context);
+            StepResult::done(lane.get_map(Clone::clone))
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+///  An [event handler](crate::event_handler::EventHandler)`] that will alter an entry in the map.
+pub struct OrderedMapLaneWithEntry<C, K, V, F, B: ?Sized> {
+    projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>,
+    key_and_f: Option<(K, F)>,
+    _type: PhantomData<fn(&B)>,
+}
+
+impl<C, K, V, F, B: ?Sized> OrderedMapLaneWithEntry<C, K, V, F, B> {
+    /// #Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    /// * `key` - Key of the entry.
+    /// * `f` - The closure to apply to the entry.
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>, key: K, f: F) -> Self {
+        OrderedMapLaneWithEntry {
+            projection,
+            key_and_f: Some((key, f)),
+            _type: PhantomData,
+        }
+    }
+}
+
+impl<C, K, V, F, B, U> HandlerAction<C> for OrderedMapLaneWithEntry<C, K, V, F, B>
+where
+    K: Clone + Ord + Hash,
+    B: ?Sized,
+    V: Borrow<B>,
+    F: FnOnce(Option<&B>) -> U,
+{
+    type Completion = U;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let OrderedMapLaneWithEntry {
+            projection,
+            key_and_f,
+            ..
+        } = self;
+        if let Some((key, f)) = key_and_f.take() {
+            let lane = projection(context);
+            StepResult::done(lane.with_entry(&key, f))
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+/// An (event handler)[`crate::event_handler::EventHandler`] that will alter an entry in the map.
+pub struct OrderedMapLaneTransformEntry<C, K, V, F> {
+    projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>,
+    key_and_f: Option<(K, F)>,
+}
+
+impl<C, K, V, F> OrderedMapLaneTransformEntry<C, K, V, F> {
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>, key: K, f: F) -> Self {
+        OrderedMapLaneTransformEntry {
+            projection,
+            key_and_f: Some((key, f)),
+        }
+    }
+}
+
+impl<C, K, V, F> HandlerAction<C> for OrderedMapLaneTransformEntry<C, K, V, F>
+where
+    K: Clone + Ord + Hash,
+    F: FnOnce(Option<&V>) -> Option<V>,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let OrderedMapLaneTransformEntry {
+            projection,
+            key_and_f,
+        } = self;
+        if let Some((key, f)) = key_and_f.take() {
+            let lane = projection(context);
+            match lane.transform_entry(key, f) {
+                TransformEntryResult::NoChange => StepResult::done(()),
+                _ => StepResult::Complete {
+                    modified_item: Some(Modification::of(lane.id)),
+                    result: (),
+                },
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+///  An [event handler](crate::event_handler::EventHandler)`] that will request a sync from the lane.
+pub struct OrderedMapLaneSync<C, K, V> {
+    projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>,
+    id: Option<Uuid>,
+    range: KeyRange<Bytes>,
+}
+
+impl<C, K, V> OrderedMapLaneSync<C, K, V> {
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>, id: Uuid) -> Self {
+        OrderedMapLaneSync {
+            projection,
+            id: Some(id),
+            range: KeyRange::default(),
+        }
+    }
+
+    /// Request a sync that is restricted to the entries with keys in a range.
+    ///
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    /// * `id` - The ID of the remote that requested the sync.
+    /// * `range` - The Recon representations of the bounds of the range of keys.
+    pub fn range(
+        projection: for<'a> fn(&'a C) -> &'a OrderedMapLane<K, V>,
+        id: Uuid,
+        range: KeyRange<Bytes>,
+    ) -> Self {
+        OrderedMapLaneSync {
+            projection,
+            id: Some(id),
+            range,
+        }
+    }
+}
+
+fn decode_bound<K: RecognizerReadable>(
+    bound: Option<Bytes>,
+) -> Result<Option<K>, EventHandlerError> {
+    bound
+        .map(|bytes| try_decode::<K>(BytesMut::from(bytes.as_ref())))
+        .transpose()
+}
+
+impl<C, K, V> HandlerAction<C> for OrderedMapLaneSync<C, K, V>
+where
+    K: Clone + Ord + Hash + RecognizerReadable,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let OrderedMapLaneSync {
+            projection,
+            id,
+            range,
+        } = self;
+        if let Some(id) = id.take() {
+            let KeyRange { from, to } = std::mem::take(range);
+            let range = match (decode_bound::<K>(from), decode_bound::<K>(to)) {
+                (Ok(from), Ok(to)) => KeyRange::new(from, to),
+                (Err(e), _) | (_, Err(e)) => return StepResult::Fail(e),
+            };
+            let lane = projection(context);
+            lane.sync_range(id, range);
+            StepResult::Complete {
+                modified_item: Some(Modification::no_trigger(lane.id)),
+                result: (),
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+type OrderedMapLaneHandler<C, K, V> = Coprod!(
+    OrderedMapLaneUpdate<C, K, V>,
+    OrderedMapLaneRemove<C, K, V>,
+    OrderedMapLaneClear<C, K, V>,
+    OrderedMapLaneTruncate<C, K, V>,
+);
+
+impl<C, K, V> HandlerTrans<MapMessage<K, V>> for ProjTransform<C, OrderedMapLane<K, V>> {
+    type Out = OrderedMapLaneHandler<C, K, V>;
+
+    fn transform(self, input: MapMessage<K, V>) -> Self::Out {
+        let ProjTransform { projection } = self;
+        let to_usize = |n: u64| usize::try_from(n).unwrap_or(usize::MAX);
+        match input {
+            MapMessage::Update { key, value } => {
+                Coproduct::Inl(OrderedMapLaneUpdate::new(projection, key, value))
+            }
+            MapMessage::Remove { key } => {
+                Coproduct::Inr(Coproduct::Inl(OrderedMapLaneRemove::new(projection, key)))
+            }
+            MapMessage::Clear => Coproduct::Inr(Coproduct::Inr(Coproduct::Inl(
+                OrderedMapLaneClear::new(projection),
+            ))),
+            MapMessage::Take(n) => Coproduct::Inr(Coproduct::Inr(Coproduct::Inr(Coproduct::Inl(
+                OrderedMapLaneTruncate::take(projection, to_usize(n)),
+            )))),
+            MapMessage::Drop(n) => Coproduct::Inr(Coproduct::Inr(Coproduct::Inr(Coproduct::Inl(
+                OrderedMapLaneTruncate::drop(projection, to_usize(n)),
+            )))),
+        }
+    }
+}
+
+pub type DecodeAndApply<C, K, V> = AndThen<
+    DecodeMapMessage<K, V>,
+    OrderedMapLaneHandler<C, K, V>,
+    ProjTransform<C, OrderedMapLane<K, V>>,
+>;
+
+/// Create an event handler that will decode an incoming map message and apply the value into an
+/// ordered map lane.
+pub fn decode_and_apply<C, K, V>(
+    message: MapMessage<BytesMut, BytesMut>,
+    projection: fn(&C) -> &OrderedMapLane<K, V>,
+) -> DecodeAndApply<C, K, V>
+where
+    C: 'static,
+    K: Clone + Ord + Hash + RecognizerReadable + 'static,
+    V: RecognizerReadable + 'static,
+{
+    let decode: DecodeMapMessage<K, V> = DecodeMapMessage::new(message);
+    decode.and_then(ProjTransform::new(projection))
+}
+
+impl<K, V> InspectableMapLikeItem<K, V> for OrderedMapLane<K, V>
+where
+    K: Clone + Ord + Hash + Send + 'static,
+    V: 'static,
+{
+    type WithEntryHandler<'a, C, F, B, U>
+        = OrderedMapLaneWithEntry<C, K, V, F, B>
+    where
+        Self: 'static,
+        C: 'a,
+        B: ?Sized + 'static,
+        V: Borrow<B>,
+        F: FnOnce(Option<&B>) -> U + Send + 'a;
+
+    fn with_entry_handler<'a, C, F, B, U>(
+        projection: fn(&C) -> &Self,
+        key: K,
+        f: F,
+    ) -> Self::WithEntryHandler<'a, C, F, B, U>
+    where
+        Self: 'static,
+        C: 'a,
+        B: ?Sized + 'static,
+        V: Borrow<B>,
+        F: FnOnce(Option<&B>) -> U + Send + 'a,
+    {
+        OrderedMapLaneWithEntry::new(projection, key, f)
+    }
+}
+
+impl<K, V> MutableMapLikeItem<K, V> for OrderedMapLane<K, V>
+where
+    K: Clone + Ord + Hash + Send + 'static,
+    V: Send + 'static,
+{
+    type UpdateHandler<C>
+        = OrderedMapLaneUpdate<C, K, V>
+    where
+        C: 'static;
+
+    type RemoveHandler<C>
+        = OrderedMapLaneRemove<C, K, V>
+    where
+        C: 'static;
+
+    type ClearHandler<C>
+        = OrderedMapLaneClear<C, K, V>
+    where
+        C: 'static;
+
+    fn update_handler<C: 'static>(
+        projection: fn(&C) -> &Self,
+        key: K,
+        value: V,
+    ) -> Self::UpdateHandler<C> {
+        OrderedMapLaneUpdate::new(projection, key, value)
+    }
+
+    fn remove_handler<C: 'static>(projection: fn(&C) -> &Self, key: K) -> Self::RemoveHandler<C> {
+        OrderedMapLaneRemove::new(projection, key)
+    }
+
+    fn clear_handler<C: 'static>(projection: fn(&C) -> &Self) -> Self::ClearHandler<C> {
+        OrderedMapLaneClear::new(projection)
+    }
+
+    type TransformEntryHandler<'a, C, F>
+        = OrderedMapLaneTransformEntry<C, K, V, F>
+    where
+        Self: 'static,
+        C: 'a,
+        F: FnOnce(Option<&V>) -> Option<V> + Send + 'a;
+
+    fn transform_entry_handler<'a, C, F>(
+        projection: fn(&C) -> &Self,
+        key: K,
+        f: F,
+    ) -> Self::TransformEntryHandler<'a, C, F>
+    where
+        Self: 'static,
+        C: 'a,
+        F: FnOnce(Option<&V>) -> Option<V> + Send + 'a,
+    {
+        OrderedMapLaneTransformEntry::new(projection, key, f)
+    }
+}
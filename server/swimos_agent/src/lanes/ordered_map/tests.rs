@@ -0,0 +1,299 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bytes::{Bytes, BytesMut};
+use swimos_agent_protocol::{
+    encoding::lane::RawMapLaneResponseDecoder, MapLaneResponse, MapMessage, MapOperation,
+};
+use swimos_api::agent::{AgentConfig, KeyRange};
+use swimos_utilities::routing::RouteUri;
+use tokio_util::codec::Decoder;
+use uuid::Uuid;
+
+use crate::{
+    agent_model::WriteResult,
+    event_handler::{EventHandlerError, HandlerAction, Modification, StepResult},
+    lanes::LaneItem,
+    meta::AgentMetadata,
+    test_context::dummy_context,
+};
+
+use super::{decode_and_apply, OrderedMapLane, OrderedMapLaneSync};
+
+const CONFIG: AgentConfig = AgentConfig::DEFAULT;
+const NODE_URI: &str = "/node";
+const LANE_ID: u64 = 3;
+const SYNC_ID: Uuid = Uuid::from_u128(7727);
+
+fn make_uri() -> RouteUri {
+    RouteUri::try_from(NODE_URI).expect("Bad URI.")
+}
+
+fn make_meta<'a>(
+    uri: &'a RouteUri,
+    route_params: &'a HashMap<String, String>,
+) -> AgentMetadata<'a> {
+    AgentMetadata::new(uri, route_params, &CONFIG)
+}
+
+struct TestAgent {
+    lane: OrderedMapLane<i32, String>,
+}
+
+impl TestAgent {
+    const LANE: fn(&TestAgent) -> &OrderedMapLane<i32, String> = |agent| &agent.lane;
+
+    fn with_keys(keys: &[i32]) -> Self {
+        let init = keys
+            .iter()
+            .map(|k| (*k, k.to_string()))
+            .collect::<BTreeMap<_, _>>();
+        TestAgent {
+            lane: OrderedMapLane::new(LANE_ID, init),
+        }
+    }
+}
+
+fn interpret(op: MapOperation<BytesMut, BytesMut>) -> MapOperation<i32, String> {
+    let key_of = |bytes: &BytesMut| {
+        std::str::from_utf8(bytes.as_ref())
+            .expect("Bad utf.")
+            .parse::<i32>()
+            .expect("Bad key.")
+    };
+    match op {
+        MapOperation::Update { key, value } => MapOperation::Update {
+            key: key_of(&key),
+            value: std::str::from_utf8(value.as_ref())
+                .expect("Bad utf.")
+                .trim_matches('"')
+                .to_string(),
+        },
+        MapOperation::Remove { key } => MapOperation::Remove { key: key_of(&key) },
+        MapOperation::Clear => MapOperation::Clear,
+    }
+}
+
+#[derive(Debug, Default)]
+struct Operations {
+    events: Vec<MapOperation<i32, String>>,
+    sync: HashMap<Uuid, Vec<MapOperation<i32, String>>>,
+}
+
+fn consume_events(lane: &OrderedMapLane<i32, String>) -> Operations {
+    let mut operations = Operations::default();
+    let mut sync_pending = HashMap::new();
+
+    let mut decoder = RawMapLaneResponseDecoder::default();
+    let mut buffer = BytesMut::new();
+
+    loop {
+        let result = lane.write_to_buffer(&mut buffer);
+
+        if matches!(result, WriteResult::NoData) {
+            break;
+        }
+
+        while !buffer.is_empty() {
+            let content = decoder
+                .decode(&mut buffer)
+                .expect("Invalid frame.")
+                .expect("Incomplete frame.");
+
+            match content {
+                MapLaneResponse::StandardEvent(operation) => {
+                    operations.events.push(interpret(operation));
+                }
+                MapLaneResponse::SyncEvent(id, operation) => {
+                    sync_pending
+                        .entry(id)
+                        .or_insert_with(Vec::new)
+                        .push(interpret(operation));
+                }
+                MapLaneResponse::Synced(id) => {
+                    let ops = sync_pending.remove(&id).unwrap_or_default();
+                    operations.sync.insert(id, ops);
+                }
+                ow => panic!("Unexpected response: {:?}", ow),
+            }
+        }
+
+        if matches!(result, WriteResult::Done) {
+            break;
+        }
+    }
+    assert!(sync_pending.is_empty());
+    operations
+}
+
+fn updates(keys: &[i32]) -> Vec<MapOperation<i32, String>> {
+    keys.iter()
+        .map(|k| MapOperation::Update {
+            key: *k,
+            value: k.to_string(),
+        })
+        .collect()
+}
+
+fn run_sync(agent: &TestAgent, mut handler: OrderedMapLaneSync<TestAgent, i32, String>) {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        agent,
+    );
+    match result {
+        StepResult::Complete { modified_item, .. } => {
+            assert_eq!(modified_item, Some(Modification::no_trigger(LANE_ID)));
+        }
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+}
+
+fn range(from: Option<&'static str>, to: Option<&'static str>) -> KeyRange<Bytes> {
+    KeyRange::new(from, to).map(|bound| Bytes::from_static(bound.as_bytes()))
+}
+
+#[test]
+fn sync_in_key_order() {
+    let agent = TestAgent::with_keys(&[5, -3, 12, 0, 7]);
+    run_sync(&agent, OrderedMapLaneSync::new(TestAgent::LANE, SYNC_ID));
+
+    let Operations { events, sync } = consume_events(&agent.lane);
+    assert!(events.is_empty());
+    assert_eq!(sync.get(&SYNC_ID), Some(&updates(&[-3, 0, 5, 7, 12])));
+}
+
+#[test]
+fn sync_key_range() {
+    let cases = [
+        (range(Some("0"), Some("7")), vec![0, 5]),
+        (range(Some("1"), None), vec![5, 7, 12]),
+        (range(None, Some("5")), vec![-3, 0]),
+        (range(Some("7"), Some("7")), vec![]),
+        (range(Some("8"), Some("2")), vec![]),
+    ];
+    for (key_range, expected) in cases {
+        let agent = TestAgent::with_keys(&[5, -3, 12, 0, 7]);
+        run_sync(
+            &agent,
+            OrderedMapLaneSync::range(TestAgent::LANE, SYNC_ID, key_range),
+        );
+
+        let Operations { sync, .. } = consume_events(&agent.lane);
+        assert_eq!(sync.get(&SYNC_ID), Some(&updates(&expected)));
+    }
+}
+
+#[test]
+fn sync_with_invalid_bound() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_keys(&[1, 2]);
+
+    let mut handler =
+        OrderedMapLaneSync::range(TestAgent::LANE, SYNC_ID, range(Some("\"a\""), None));
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::BadCommand(_))
+    ));
+    assert!(consume_events(&agent.lane).sync.is_empty());
+}
+
+#[test]
+fn take_and_drop_entries() {
+    let agent = TestAgent::with_keys(&[1, 2, 3, 4, 5]);
+
+    agent.lane.take(3);
+    agent.lane.drop_first(1);
+    agent.lane.get_map(|map| {
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![2, 3]);
+    });
+
+    let Operations { events, .. } = consume_events(&agent.lane);
+    assert_eq!(
+        events,
+        vec![
+            MapOperation::Remove { key: 4 },
+            MapOperation::Remove { key: 5 },
+            MapOperation::Remove { key: 1 },
+        ]
+    );
+}
+
+fn run_command(agent: &TestAgent, message: MapMessage<BytesMut, BytesMut>) {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let mut handler = decode_and_apply(message, TestAgent::LANE);
+    loop {
+        match handler.step(
+            &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+            meta,
+            agent,
+        ) {
+            StepResult::Continue { .. } => {}
+            StepResult::Complete { .. } => break,
+            StepResult::Fail(err) => panic!("Handler failed: {}", err),
+        }
+    }
+}
+
+#[test]
+fn apply_commands() {
+    let agent = TestAgent::with_keys(&[]);
+
+    run_command(
+        &agent,
+        MapMessage::Update {
+            key: BytesMut::from("2"),
+            value: BytesMut::from("two"),
+        },
+    );
+    run_command(
+        &agent,
+        MapMessage::Update {
+            key: BytesMut::from("1"),
+            value: BytesMut::from("one"),
+        },
+    );
+    run_command(&agent, MapMessage::Drop(1));
+
+    agent.lane.get_map(|map| {
+        assert_eq!(map.get(&2).map(String::as_str), Some("two"));
+        assert_eq!(map.len(), 1);
+    });
+
+    let Operations { events, .. } = consume_events(&agent.lane);
+    assert_eq!(
+        events,
+        vec![
+            MapOperation::Update {
+                key: 2,
+                value: "two".to_string()
+            },
+            MapOperation::Remove { key: 1 },
+        ]
+    );
+}
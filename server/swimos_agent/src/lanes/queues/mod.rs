@@ -12,20 +12,42 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::{HashMap, VecDeque};
+use std::collections::VecDeque;
 use std::hash::Hash;
 
 use swimos_agent_protocol::{LaneResponse, MapOperation};
+use swimos_api::agent::SyncVersion;
 use uuid::Uuid;
 
 use crate::event_queue::{to_operation, EventQueue};
-use crate::map_storage::MapEventQueue;
+use crate::map_storage::{MapContent, MapEventQueue};
+
+mod versions;
+
+#[cfg(test)]
+mod tests;
+
+use versions::{Delta, EntryVersions};
 
 /// For a sync operation on a map lane, keeps track of which keys are synced for a given remote.
 #[derive(Debug)]
 pub struct SyncQueue<K> {
     id: Uuid,
+    // The remote holds state that must be discarded before the entries are sent.
+    clear: bool,
+    // Keys, held by the remote, that have since been removed from the map.
+    removed: VecDeque<K>,
     queue: VecDeque<K>,
+    // The remote should be told the version of the map when the sync completes.
+    versioned: bool,
+}
+
+/// The next item to send to a remote that is syncing with a map lane.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SyncItem<K> {
+    Clear,
+    Remove(K),
+    Update(K),
 }
 
 impl<K> SyncQueue<K>
@@ -33,22 +55,74 @@ where
     K: Clone + Eq + Hash,
 {
     pub fn new(id: Uuid, keys: VecDeque<K>) -> Self {
-        SyncQueue { id, queue: keys }
+        SyncQueue {
+            id,
+            clear: false,
+            removed: VecDeque::new(),
+            queue: keys,
+            versioned: false,
+        }
+    }
+
+    /// A full sync, for a remote that holds stale state, that reports the version of the map on completion.
+    pub fn versioned(id: Uuid, keys: VecDeque<K>) -> Self {
+        SyncQueue {
+            id,
+            clear: true,
+            removed: VecDeque::new(),
+            queue: keys,
+            versioned: true,
+        }
+    }
+
+    /// A sync that only sends the changes since the version that the remote already holds.
+    pub fn delta(id: Uuid, delta: Delta<K>) -> Self {
+        let Delta { updated, removed } = delta;
+        SyncQueue {
+            id,
+            clear: false,
+            removed,
+            queue: updated,
+            versioned: true,
+        }
     }
 
     pub fn remove(&mut self, key: &K) {
-        let SyncQueue { queue, .. } = self;
+        let SyncQueue { removed, queue, .. } = self;
         if let Some(i) = queue.iter().position(|k| k == key) {
             queue.remove(i);
         }
+        if let Some(i) = removed.iter().position(|k| k == key) {
+            removed.remove(i);
+        }
     }
 
     pub fn clear(&mut self) {
-        self.queue.clear();
+        let SyncQueue {
+            clear,
+            removed,
+            queue,
+            ..
+        } = self;
+        *clear = false;
+        removed.clear();
+        queue.clear();
     }
 
-    pub fn pop(&mut self) -> Option<K> {
-        self.queue.pop_front()
+    pub fn pop(&mut self) -> Option<SyncItem<K>> {
+        let SyncQueue {
+            clear,
+            removed,
+            queue,
+            ..
+        } = self;
+        if std::mem::take(clear) {
+            Some(SyncItem::Clear)
+        } else if let Some(k) = removed.pop_front() {
+            Some(SyncItem::Remove(k))
+        } else {
+            queue.pop_front().map(SyncItem::Update)
+        }
     }
 }
 
@@ -99,16 +173,24 @@ impl Default for NextWrite {
 #[derive(Debug)]
 pub struct WriteQueues<K> {
     event_queue: EventQueue<K, ()>,
+    // Batches of events that must be reported together, ahead of the event queue.
+    batches: VecDeque<Vec<Action<K>>>,
+    // The remaining events of a batch that has been announced but not yet fully written.
+    in_batch: VecDeque<Action<K>>,
     sync_queues: Vec<SyncQueue<K>>,
     next: NextWrite,
+    versions: EntryVersions<K>,
 }
 
 impl<K> Default for WriteQueues<K> {
     fn default() -> Self {
         Self {
             event_queue: Default::default(),
+            batches: Default::default(),
+            in_batch: Default::default(),
             sync_queues: Default::default(),
             next: Default::default(),
+            versions: Default::default(),
         }
     }
 }
@@ -118,8 +200,12 @@ pub type Action<K> = MapOperation<K, ()>;
 #[derive(Debug)]
 pub enum ToWrite<K> {
     Event(Action<K>),
+    EventBatch(Vec<Action<K>>),
     SyncEvent(Uuid, K),
+    SyncRemove(Uuid, K),
+    SyncClear(Uuid),
     Synced(Uuid),
+    SyncedAt(Uuid),
 }
 
 impl<K> WriteQueues<K>
@@ -130,43 +216,116 @@ where
         self.event_queue.push(op);
     }
 
+    /// Push a sequence of operations that must be reported as a single batch. Any events that are
+    /// already pending are included at the start of the batch so that the ordering of the events is
+    /// preserved.
+    pub fn push_batch(&mut self, ops: Vec<Action<K>>) {
+        let WriteQueues {
+            event_queue,
+            batches,
+            ..
+        } = self;
+        let mut batch = Vec::with_capacity(ops.len());
+        while let Some(op) = event_queue.pop() {
+            batch.push(op);
+        }
+        batch.extend(ops);
+        batches.push_back(batch);
+    }
+
     pub fn sync(&mut self, id: Uuid, keys: VecDeque<K>) {
         self.sync_queues.push(SyncQueue::new(id, keys));
     }
 
+    /// Attempt to start a sync that only sends the changes since a version that the remote already
+    /// holds. Returns false if this is not possible and a full sync (with
+    /// [`WriteQueues::versioned_sync`]) is required.
+    pub fn delta_sync(&mut self, id: Uuid, since: SyncVersion) -> bool {
+        if let Some(delta) = self.versions.delta(since) {
+            self.sync_queues.push(SyncQueue::delta(id, delta));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Start a full sync that replaces any state that the remote holds and reports the version of
+    /// the map on completion.
+    pub fn versioned_sync(&mut self, id: Uuid, keys: VecDeque<K>) {
+        self.sync_queues.push(SyncQueue::versioned(id, keys));
+    }
+
     pub fn pop(&mut self) -> Option<ToWrite<K>> {
         let WriteQueues {
             event_queue,
+            batches,
             sync_queues,
             next: NextWrite { sync_index, next },
+            ..
         } = self;
         let selection = next.flip();
-        if (selection.is_event() && !event_queue.is_empty()) || sync_queues.is_empty() {
-            if let Some(action) = event_queue.pop() {
-                update_sync_queues(sync_queues, &action);
-                Some(ToWrite::Event(action))
-            } else {
-                None
-            }
+        let no_events = event_queue.is_empty() && batches.is_empty();
+        if (selection.is_event() && !no_events) || sync_queues.is_empty() {
+            pop_event(event_queue, batches, sync_queues)
         } else if let Some(queue) = sync_queues.get_mut(*sync_index) {
-            Some(if let Some(k) = queue.pop() {
-                let id = queue.id;
+            let id = queue.id;
+            if let Some(item) = queue.pop() {
                 *sync_index = (*sync_index + 1) % sync_queues.len();
-                ToWrite::SyncEvent(id, k)
+                Some(match item {
+                    SyncItem::Clear => ToWrite::SyncClear(id),
+                    SyncItem::Remove(k) => ToWrite::SyncRemove(id, k),
+                    SyncItem::Update(k) => ToWrite::SyncEvent(id, k),
+                })
+            } else if queue.versioned && !no_events {
+                // The reported version must include any pending events so they are sent first.
+                pop_event(event_queue, batches, sync_queues)
             } else {
-                let id = sync_queues.remove(*sync_index).id;
+                let versioned = sync_queues.remove(*sync_index).versioned;
                 if *sync_index >= sync_queues.len() {
                     *sync_index = 0;
                 }
-                ToWrite::Synced(id)
-            })
+                Some(if versioned {
+                    ToWrite::SyncedAt(id)
+                } else {
+                    ToWrite::Synced(id)
+                })
+            }
         } else {
             None
         }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.event_queue.is_empty() && self.sync_queues.is_empty()
+        self.event_queue.is_empty()
+            && self.batches.is_empty()
+            && self.in_batch.is_empty()
+            && self.sync_queues.is_empty()
+    }
+
+    /// Whether a batch has been announced and its events have not all been written.
+    pub fn in_batch(&self) -> bool {
+        !self.in_batch.is_empty()
+    }
+}
+
+fn pop_event<K>(
+    event_queue: &mut EventQueue<K, ()>,
+    batches: &mut VecDeque<Vec<Action<K>>>,
+    sync_queues: &mut Vec<SyncQueue<K>>,
+) -> Option<ToWrite<K>>
+where
+    K: Clone + Eq + Hash,
+{
+    if let Some(batch) = batches.pop_front() {
+        for action in &batch {
+            update_sync_queues(sync_queues, action);
+        }
+        Some(ToWrite::EventBatch(batch))
+    } else {
+        event_queue.pop().map(|action| {
+            update_sync_queues(sync_queues, &action);
+            ToWrite::Event(action)
+        })
     }
 }
 
@@ -199,10 +358,36 @@ where
         Self: 'a;
 
     fn push(&mut self, action: MapOperation<K, ()>) {
+        self.versions.record(&action);
         self.push_operation(action)
     }
 
-    fn pop<'a>(&mut self, content: &'a HashMap<K, V>) -> Option<Self::Output<'a>> {
+    fn push_batch(&mut self, actions: Vec<MapOperation<K, ()>>) {
+        for action in &actions {
+            self.versions.record(action);
+        }
+        WriteQueues::push_batch(self, actions)
+    }
+
+    fn pop<'a, M>(&mut self, content: &'a M) -> Option<Self::Output<'a>>
+    where
+        K: 'a,
+        V: 'a,
+        M: MapContent<K, V>,
+    {
+        if let Some(action) = self.in_batch.pop_front() {
+            // The number of events in the batch has already been reported so, if an entry was
+            // removed after the batch started, its removal is reported in place of the update.
+            let op = match action {
+                MapOperation::Update { key, .. } => match content.get_entry(&key) {
+                    Some(value) => MapOperation::Update { key, value },
+                    None => MapOperation::Remove { key },
+                },
+                MapOperation::Remove { key } => MapOperation::Remove { key },
+                MapOperation::Clear => MapOperation::Clear,
+            };
+            return Some(LaneResponse::StandardEvent(op));
+        }
         loop {
             match WriteQueues::pop(self)? {
                 ToWrite::Event(action) => {
@@ -210,15 +395,48 @@ where
                         break Some(LaneResponse::StandardEvent(op));
                     }
                 }
+                ToWrite::EventBatch(batch) => {
+                    // Updates for entries that were subsequently removed are not reported.
+                    let mut actions = batch
+                        .into_iter()
+                        .filter(|action| match action {
+                            MapOperation::Update { key, .. } => content.has_entry(key),
+                            _ => true,
+                        })
+                        .collect::<VecDeque<_>>();
+                    match actions.len() {
+                        0 => {}
+                        1 => {
+                            if let Some(op) =
+                                actions.pop_front().and_then(|a| to_operation(content, a))
+                            {
+                                break Some(LaneResponse::StandardEvent(op));
+                            }
+                        }
+                        n => {
+                            self.in_batch = actions;
+                            break Some(LaneResponse::EventBatch(n as u64));
+                        }
+                    }
+                }
                 ToWrite::SyncEvent(id, key) => {
-                    if let Some(value) = content.get(&key) {
+                    if let Some(value) = content.get_entry(&key) {
                         break Some(LaneResponse::SyncEvent(
                             id,
                             MapOperation::Update { key, value },
                         ));
                     }
                 }
+                ToWrite::SyncRemove(id, key) => {
+                    break Some(LaneResponse::SyncEvent(id, MapOperation::Remove { key }));
+                }
+                ToWrite::SyncClear(id) => {
+                    break Some(LaneResponse::SyncEvent(id, MapOperation::Clear));
+                }
                 ToWrite::Synced(id) => break Some(LaneResponse::Synced(id)),
+                ToWrite::SyncedAt(id) => {
+                    break Some(LaneResponse::SyncedAt(id, self.versions.current()));
+                }
             }
         }
     }
@@ -0,0 +1,79 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use swimos_agent_protocol::MapOperation;
+use swimos_api::agent::SyncVersion;
+
+use super::versions::{Delta, EntryVersions};
+
+fn update(key: i32) -> MapOperation<i32, ()> {
+    MapOperation::Update { key, value: () }
+}
+
+fn sorted(delta: Delta<i32>) -> (Vec<i32>, Vec<i32>) {
+    let Delta { updated, removed } = delta;
+    let mut updated = Vec::from(updated);
+    let mut removed = Vec::from(removed);
+    updated.sort();
+    removed.sort();
+    (updated, removed)
+}
+
+#[test]
+fn delta_from_current_is_empty() {
+    let mut versions = EntryVersions::default();
+    versions.record(&update(1));
+    let delta = versions.delta(versions.current()).expect("Delta expected.");
+    assert_eq!(sorted(delta), (vec![], vec![]));
+}
+
+#[test]
+fn delta_contains_changes() {
+    let mut versions = EntryVersions::default();
+    versions.record(&update(1));
+    versions.record(&update(2));
+    let since = versions.current();
+    versions.record(&update(2));
+    versions.record(&update(3));
+    versions.record(&MapOperation::Remove { key: 1 });
+    versions.record(&MapOperation::Remove { key: 4 });
+    versions.record(&update(4));
+
+    let delta = versions.delta(since).expect("Delta expected.");
+    assert_eq!(sorted(delta), (vec![2, 3, 4], vec![1]));
+}
+
+#[test]
+fn no_delta_for_other_epoch() {
+    let mut versions = EntryVersions::<i32>::default();
+    versions.record(&update(1));
+    let SyncVersion { epoch, version } = versions.current();
+    assert!(versions
+        .delta(SyncVersion::new(epoch.wrapping_add(1), version))
+        .is_none());
+    assert!(versions.delta(SyncVersion::new(0, version)).is_none());
+    assert!(versions
+        .delta(SyncVersion::new(epoch, version + 1))
+        .is_none());
+}
+
+#[test]
+fn no_delta_across_clear() {
+    let mut versions = EntryVersions::default();
+    versions.record(&update(1));
+    let before = versions.current();
+    versions.record(&MapOperation::Clear);
+    versions.record(&update(2));
+    assert!(versions.delta(before).is_none());
+}
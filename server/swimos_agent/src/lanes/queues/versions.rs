@@ -0,0 +1,132 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{BuildHasher, Hash, Hasher};
+
+use swimos_agent_protocol::MapOperation;
+use swimos_api::agent::SyncVersion;
+
+use super::Action;
+
+/// The maximum number of removals that are remembered. If a remote presents a version that is older
+/// than the oldest remembered removal, it must perform a full sync.
+const MAX_REMOVALS: usize = 8192;
+
+/// The keys that must be sent to a remote to bring it up to date with the current state of a map lane.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Delta<K> {
+    pub updated: VecDeque<K>,
+    pub removed: VecDeque<K>,
+}
+
+/// Tracks the version at which each entry of a map lane was last modified so that a remote that has
+/// previously synced with the lane can be sent only the entries that have changed since then.
+#[derive(Debug)]
+pub struct EntryVersions<K> {
+    epoch: u64,
+    version: u64,
+    modified: HashMap<K, u64>,
+    removed: VecDeque<(u64, K)>,
+    // A delta cannot be computed for versions before this (as the map was cleared or the removals
+    // have been discarded).
+    horizon: u64,
+}
+
+/// Choose a (non-zero) epoch for a new instance of a lane. Remotes that have no prior state can use
+/// an epoch of 0 to request a full sync and be told the current version.
+fn new_epoch() -> u64 {
+    RandomState::new().build_hasher().finish().max(1)
+}
+
+impl<K> Default for EntryVersions<K> {
+    fn default() -> Self {
+        EntryVersions {
+            epoch: new_epoch(),
+            version: 0,
+            modified: HashMap::new(),
+            removed: VecDeque::new(),
+            horizon: 0,
+        }
+    }
+}
+
+impl<K> EntryVersions<K> {
+    /// The version of the current state of the map.
+    pub fn current(&self) -> SyncVersion {
+        SyncVersion::new(self.epoch, self.version)
+    }
+}
+
+impl<K: Clone + Eq + Hash> EntryVersions<K> {
+    /// Record a modification to the map, advancing the version.
+    pub fn record(&mut self, action: &Action<K>) {
+        let EntryVersions {
+            version,
+            modified,
+            removed,
+            horizon,
+            ..
+        } = self;
+        *version += 1;
+        match action {
+            MapOperation::Update { key, .. } => {
+                modified.insert(key.clone(), *version);
+            }
+            MapOperation::Remove { key } => {
+                modified.remove(key);
+                removed.push_back((*version, key.clone()));
+                if removed.len() > MAX_REMOVALS {
+                    if let Some((v, _)) = removed.pop_front() {
+                        *horizon = v;
+                    }
+                }
+            }
+            MapOperation::Clear => {
+                modified.clear();
+                removed.clear();
+                *horizon = *version;
+            }
+        }
+    }
+
+    /// Compute the changes since a previous version. If this is not possible (the version was
+    /// issued by another instance of the lane or is too old), [`None`] is returned and the remote
+    /// must perform a full sync.
+    pub fn delta(&self, since: SyncVersion) -> Option<Delta<K>> {
+        let EntryVersions {
+            epoch,
+            version,
+            modified,
+            removed,
+            horizon,
+        } = self;
+        if since.epoch != *epoch || since.version > *version || since.version < *horizon {
+            return None;
+        }
+        let updated = modified
+            .iter()
+            .filter(|(_, v)| **v > since.version)
+            .map(|(k, _)| k.clone())
+            .collect();
+        let mut seen = HashSet::new();
+        let removed = removed
+            .iter()
+            .filter(|(v, k)| *v > since.version && !modified.contains_key(k) && seen.insert(k))
+            .map(|(_, k)| k.clone())
+            .collect();
+        Some(Delta { updated, removed })
+    }
+}
@@ -0,0 +1,607 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{cell::RefCell, collections::VecDeque};
+
+use bytes::BytesMut;
+use static_assertions::assert_impl_all;
+use swimos_agent_protocol::{encoding::lane::ValueLaneResponseEncoder, LaneResponse};
+use swimos_form::Form;
+use tokio::time::Instant;
+use tokio_util::codec::Encoder;
+use uuid::Uuid;
+
+use crate::{
+    agent_model::WriteResult,
+    event_handler::{ActionContext, HandlerAction, Modification, StepResult},
+    item::AgentItem,
+    meta::AgentMetadata,
+};
+
+use super::{expiry::ExpiryTimer, history::HistoryRetention, LaneItem};
+
+#[cfg(test)]
+mod tests;
+
+/// Summary statistics for a window of samples. If the window is empty, all of the fields are 0.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Form)]
+pub struct Stats {
+    /// The number of samples in the window.
+    pub count: u64,
+    /// The arithmetic mean of the samples.
+    pub mean: f64,
+    /// The smallest sample.
+    pub min: f64,
+    /// The largest sample.
+    pub max: f64,
+    /// The (population) standard deviation of the samples.
+    pub stddev: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    seq: u64,
+    added: Instant,
+    value: f64,
+}
+
+/// Running aggregates over the samples in a window, updated as samples are added and evicted so
+/// that the statistics can be computed without visiting every sample.
+#[derive(Debug, Default)]
+struct Aggregates {
+    sum: f64,
+    sum_sq: f64,
+    // Monotonic queues of (sequence number, value) where the front is the current minimum
+    // (respectively, maximum) of the window.
+    min: VecDeque<(u64, f64)>,
+    max: VecDeque<(u64, f64)>,
+}
+
+impl Aggregates {
+    fn add(&mut self, sample: &Sample) {
+        let Aggregates {
+            sum,
+            sum_sq,
+            min,
+            max,
+        } = self;
+        let Sample { seq, value, .. } = *sample;
+        *sum += value;
+        *sum_sq += value * value;
+        while min.back().is_some_and(|(_, x)| *x >= value) {
+            min.pop_back();
+        }
+        min.push_back((seq, value));
+        while max.back().is_some_and(|(_, x)| *x <= value) {
+            max.pop_back();
+        }
+        max.push_back((seq, value));
+    }
+
+    fn remove(&mut self, sample: &Sample) {
+        let Aggregates {
+            sum,
+            sum_sq,
+            min,
+            max,
+        } = self;
+        let Sample { seq, value, .. } = *sample;
+        *sum -= value;
+        *sum_sq -= value * value;
+        if min.front().is_some_and(|(s, _)| *s == seq) {
+            min.pop_front();
+        }
+        if max.front().is_some_and(|(s, _)| *s == seq) {
+            max.pop_front();
+        }
+    }
+
+    fn stats(&self, count: usize) -> Stats {
+        let Aggregates {
+            sum,
+            sum_sq,
+            min,
+            max,
+        } = self;
+        match (min.front(), max.front()) {
+            (Some((_, min)), Some((_, max))) if count > 0 => {
+                let n = count as f64;
+                let mean = sum / n;
+                // Rounding errors can make the variance very slightly negative.
+                let variance = (sum_sq / n - mean * mean).max(0.0);
+                Stats {
+                    count: count as u64,
+                    mean,
+                    min: *min,
+                    max: *max,
+                    stddev: variance.sqrt(),
+                }
+            }
+            _ => Stats::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct StatsLaneInner {
+    window: HistoryRetention,
+    samples: VecDeque<Sample>,
+    aggregates: Aggregates,
+    next_seq: u64,
+    dirty: bool,
+    sync_queue: VecDeque<Uuid>,
+    // The timers for the samples, keyed by sequence number. This is created when the window is
+    // first given a maximum age and is then retained, so that there is only ever one timer task
+    // for the lane.
+    expiry: Option<ExpiryTimer<u64>>,
+    pruning: bool,
+}
+
+impl StatsLaneInner {
+    fn new(window: HistoryRetention) -> Self {
+        StatsLaneInner {
+            window,
+            samples: Default::default(),
+            aggregates: Default::default(),
+            next_seq: 0,
+            dirty: false,
+            sync_queue: Default::default(),
+            expiry: window.max_age().map(ExpiryTimer::new),
+            pruning: false,
+        }
+    }
+
+    fn stats(&self) -> Stats {
+        self.aggregates.stats(self.samples.len())
+    }
+
+    fn push(&mut self, value: f64) {
+        let StatsLaneInner {
+            window,
+            samples,
+            aggregates,
+            next_seq,
+            dirty,
+            expiry,
+            ..
+        } = self;
+        let added = Instant::now();
+        let sample = Sample {
+            seq: *next_seq,
+            added,
+            value,
+        };
+        *next_seq = next_seq.wrapping_add(1);
+        aggregates.add(&sample);
+        samples.push_back(sample);
+        if let (Some(age), Some(expiry)) = (window.max_age(), expiry.as_ref()) {
+            expiry.refresh_at(sample.seq, added + age);
+        }
+        *dirty = true;
+        self.evict(added, None);
+    }
+
+    /// Evict the samples that are no longer in the window. Samples with sequence numbers up to
+    /// `expired` are evicted unconditionally as their timers have already fired.
+    fn evict(&mut self, now: Instant, expired: Option<u64>) -> bool {
+        let StatsLaneInner {
+            window,
+            samples,
+            aggregates,
+            dirty,
+            expiry,
+            ..
+        } = self;
+        let mut evicted = false;
+        while let Some(sample) = samples.front() {
+            let too_many = window.max_len().is_some_and(|n| samples.len() > n);
+            let too_old = window
+                .max_age()
+                .is_some_and(|age| sample.added + age <= now);
+            let timed_out = expired.is_some_and(|seq| sample.seq <= seq);
+            if too_many || too_old || timed_out {
+                if let Some(expiry) = expiry.as_ref() {
+                    expiry.remove(&sample.seq);
+                }
+                aggregates.remove(sample);
+                samples.pop_front();
+                evicted = true;
+            } else {
+                break;
+            }
+        }
+        if evicted {
+            if samples.is_empty() {
+                // Discard any accumulated rounding errors.
+                *aggregates = Aggregates::default();
+            }
+            *dirty = true;
+        }
+        evicted
+    }
+
+    fn set_window(&mut self, window: HistoryRetention) -> bool {
+        let StatsLaneInner {
+            window: current,
+            samples,
+            expiry,
+            ..
+        } = self;
+        if current.max_age() != window.max_age() {
+            match (window.max_age(), expiry.as_mut()) {
+                (Some(age), Some(expiry)) => {
+                    expiry.set_ttl(age);
+                    for sample in samples.iter() {
+                        expiry.refresh_at(sample.seq, sample.added + age);
+                    }
+                }
+                (Some(age), None) => {
+                    let timer = ExpiryTimer::new(age);
+                    for sample in samples.iter() {
+                        timer.refresh_at(sample.seq, sample.added + age);
+                    }
+                    *expiry = Some(timer);
+                }
+                (None, Some(expiry)) => expiry.clear(),
+                (None, None) => {}
+            }
+        }
+        *current = window;
+        self.evict(Instant::now(), None)
+    }
+}
+
+/// A lane that maintains summary [statistics](`Stats`) (the count, mean, minimum, maximum and
+/// standard deviation) over a window of numeric samples. The window is described by a
+/// [`HistoryRetention`] (by default, the most recent 1024 samples are used).
+///
+/// Remotely, a stats lane appears to be a value lane containing a [`Stats`] record which is updated
+/// each time the window changes. Stats lanes are always transient and ignore any commands that they
+/// receive.
+///
+/// Samples can be added to the lane by executing an instance of [`StatsLanePush`] (which can be
+/// constructed using the [`crate::agent_lifecycle::HandlerContext`]).
+#[derive(Debug)]
+pub struct StatsLane {
+    id: u64,
+    inner: RefCell<StatsLaneInner>,
+}
+
+assert_impl_all!(StatsLane: Send);
+
+impl StatsLane {
+    /// Create a stats lane over the [default](`HistoryRetention::default`) window.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the lane. This should be unique in an agent.
+    pub fn new(id: u64) -> Self {
+        Self::with_window(id, HistoryRetention::default())
+    }
+
+    /// # Arguments
+    /// * `id` - The ID of the lane. This should be unique in an agent.
+    /// * `window` - The policy determining which samples are included in the statistics.
+    pub fn with_window(id: u64, window: HistoryRetention) -> Self {
+        StatsLane {
+            id,
+            inner: RefCell::new(StatsLaneInner::new(window)),
+        }
+    }
+
+    /// The window over which the statistics are computed.
+    pub fn window(&self) -> HistoryRetention {
+        self.inner.borrow().window
+    }
+
+    /// The statistics for the current window.
+    pub fn stats(&self) -> Stats {
+        self.inner.borrow().stats()
+    }
+
+    /// Add a sample to the window.
+    pub(crate) fn push(&self, sample: f64) {
+        self.inner.borrow_mut().push(sample)
+    }
+
+    /// Evict the samples with timers that have fired (and any others that have exceeded the
+    /// maximum age of the window), returning whether any samples were removed.
+    pub(crate) fn prune(&self, expired: &[u64]) -> bool {
+        let mut guard = self.inner.borrow_mut();
+        guard.pruning = false;
+        guard.evict(Instant::now(), expired.iter().copied().max())
+    }
+
+    /// Replace the window of the lane, evicting any samples that are no longer included and
+    /// restarting the timers of the remaining samples if the maximum age has changed.
+    pub(crate) fn set_window(&self, window: HistoryRetention) -> bool {
+        self.inner.borrow_mut().set_window(window)
+    }
+
+    /// If the window has a maximum age, suspend a future into the agent task that will evict
+    /// samples as they expire. All samples share a single timer queue so there is, at most, one
+    /// such future for the lane at any time.
+    ///
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    /// * `action_context` - The context in which to suspend the timer.
+    pub(crate) fn start_pruning<C: 'static>(
+        &self,
+        projection: fn(&C) -> &Self,
+        action_context: &mut ActionContext<C>,
+    ) {
+        let mut guard = self.inner.borrow_mut();
+        let StatsLaneInner {
+            window,
+            expiry,
+            pruning,
+            ..
+        } = &mut *guard;
+        if let (Some(_), Some(expiry), false) = (window.max_age(), expiry.as_ref(), *pruning) {
+            expiry.schedule(action_context, move |expired| {
+                StatsLanePrune::new(projection, expired)
+            });
+            *pruning = true;
+        }
+    }
+
+    pub(crate) fn sync(&self, id: Uuid) {
+        self.inner.borrow_mut().sync_queue.push_back(id);
+    }
+}
+
+impl AgentItem for StatsLane {
+    fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+const INFALLIBLE_SER: &str = "Serializing to recon should be infallible.";
+
+impl LaneItem for StatsLane {
+    fn write_to_buffer(&self, buffer: &mut BytesMut) -> WriteResult {
+        let mut encoder = ValueLaneResponseEncoder::default();
+        let mut guard = self.inner.borrow_mut();
+        let stats = guard.stats();
+        let StatsLaneInner {
+            dirty, sync_queue, ..
+        } = &mut *guard;
+        if let Some(id) = sync_queue.pop_front() {
+            encoder
+                .encode(LaneResponse::sync_event(id, stats), buffer)
+                .expect(INFALLIBLE_SER);
+            encoder
+                .encode(LaneResponse::<Stats>::synced(id), buffer)
+                .expect(INFALLIBLE_SER);
+            if sync_queue.is_empty() && !*dirty {
+                WriteResult::Done
+            } else {
+                WriteResult::DataStillAvailable
+            }
+        } else if *dirty {
+            *dirty = false;
+            encoder
+                .encode(LaneResponse::event(stats), buffer)
+                .expect(INFALLIBLE_SER);
+            WriteResult::Done
+        } else {
+            WriteResult::NoData
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will add a sample to a stats lane,
+/// completing with the updated statistics.
+pub struct StatsLanePush<C> {
+    projection: fn(&C) -> &StatsLane,
+    sample: Option<f64>,
+}
+
+impl<C> StatsLanePush<C> {
+    pub fn new(projection: fn(&C) -> &StatsLane, sample: f64) -> Self {
+        StatsLanePush {
+            projection,
+            sample: Some(sample),
+        }
+    }
+}
+
+impl<C: 'static> HandlerAction<C> for StatsLanePush<C> {
+    type Completion = Stats;
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let StatsLanePush { projection, sample } = self;
+        if let Some(sample) = sample.take() {
+            let lane = projection(context);
+            lane.push(sample);
+            lane.start_pruning(*projection, action_context);
+            StepResult::Complete {
+                modified_item: Some(Modification::no_trigger(lane.id)),
+                result: lane.stats(),
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will evict the samples from a stats
+/// lane that have exceeded the maximum age of its window and then restart the timer for the lane.
+pub struct StatsLanePrune<C> {
+    projection: fn(&C) -> &StatsLane,
+    expired: Option<Vec<u64>>,
+}
+
+impl<C> StatsLanePrune<C> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    /// * `expired` - The sequence numbers of the samples with timers that have fired.
+    pub fn new(projection: fn(&C) -> &StatsLane, expired: Vec<u64>) -> Self {
+        StatsLanePrune {
+            projection,
+            expired: Some(expired),
+        }
+    }
+}
+
+impl<C: 'static> HandlerAction<C> for StatsLanePrune<C> {
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let StatsLanePrune {
+            projection,
+            expired,
+        } = self;
+        if let Some(expired) = expired.take() {
+            let lane = projection(context);
+            let pruned = lane.prune(&expired);
+            lane.start_pruning(*projection, action_context);
+            if pruned {
+                StepResult::Complete {
+                    modified_item: Some(Modification::no_trigger(lane.id)),
+                    result: (),
+                }
+            } else {
+                StepResult::done(())
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will replace the window of a stats
+/// lane.
+pub struct StatsLaneSetWindow<C> {
+    projection: fn(&C) -> &StatsLane,
+    window: Option<HistoryRetention>,
+}
+
+impl<C> StatsLaneSetWindow<C> {
+    pub fn new(projection: fn(&C) -> &StatsLane, window: HistoryRetention) -> Self {
+        StatsLaneSetWindow {
+            projection,
+            window: Some(window),
+        }
+    }
+}
+
+impl<C: 'static> HandlerAction<C> for StatsLaneSetWindow<C> {
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let StatsLaneSetWindow { projection, window } = self;
+        if let Some(window) = window.take() {
+            let lane = projection(context);
+            let evicted = lane.set_window(window);
+            lane.start_pruning(*projection, action_context);
+            if evicted {
+                StepResult::Complete {
+                    modified_item: Some(Modification::no_trigger(lane.id)),
+                    result: (),
+                }
+            } else {
+                StepResult::done(())
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will get the statistics for the
+/// current window of a stats lane.
+pub struct StatsLaneGet<C> {
+    projection: fn(&C) -> &StatsLane,
+    done: bool,
+}
+
+impl<C> StatsLaneGet<C> {
+    pub fn new(projection: fn(&C) -> &StatsLane) -> Self {
+        StatsLaneGet {
+            projection,
+            done: false,
+        }
+    }
+}
+
+impl<C> HandlerAction<C> for StatsLaneGet<C> {
+    type Completion = Stats;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let StatsLaneGet { projection, done } = self;
+        if *done {
+            StepResult::after_done()
+        } else {
+            *done = true;
+            StepResult::done(projection(context).stats())
+        }
+    }
+}
+
+pub struct StatsLaneSync<C> {
+    projection: fn(&C) -> &StatsLane,
+    id: Option<Uuid>,
+}
+
+impl<C> StatsLaneSync<C> {
+    pub fn new(projection: fn(&C) -> &StatsLane, id: Uuid) -> Self {
+        StatsLaneSync {
+            projection,
+            id: Some(id),
+        }
+    }
+}
+
+impl<C> HandlerAction<C> for StatsLaneSync<C> {
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let StatsLaneSync { projection, id } = self;
+        if let Some(id) = id.take() {
+            let lane = projection(context);
+            lane.sync(id);
+            StepResult::Complete {
+                modified_item: Some(Modification::no_trigger(lane.id)),
+                result: (),
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
@@ -0,0 +1,431 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bytes::BytesMut;
+use futures::{stream::FuturesUnordered, StreamExt};
+use swimos_agent_protocol::{encoding::lane::RawValueLaneResponseDecoder, LaneResponse};
+use swimos_api::agent::AgentConfig;
+use swimos_recon::parser::parse_recognize;
+use swimos_utilities::routing::RouteUri;
+use tokio_util::codec::Decoder;
+use uuid::Uuid;
+
+use crate::{
+    agent_model::WriteResult,
+    event_handler::{ActionContext, HandlerAction, HandlerFuture, Modification, StepResult},
+    lanes::{history::HistoryRetention, LaneItem},
+    meta::AgentMetadata,
+    test_context::{dummy_context, no_downlink, DummyAgentContext},
+};
+
+use super::{Stats, StatsLane, StatsLaneGet, StatsLanePush, StatsLaneSetWindow, StatsLaneSync};
+
+const CONFIG: AgentConfig = AgentConfig::DEFAULT;
+const NODE_URI: &str = "/node";
+const LANE_ID: u64 = 3;
+const SYNC_ID: Uuid = Uuid::from_u128(2847);
+const MAX_AGE: Duration = Duration::from_secs(10);
+const EPSILON: f64 = 1e-9;
+
+fn make_uri() -> RouteUri {
+    RouteUri::try_from(NODE_URI).expect("Bad URI.")
+}
+
+fn make_meta<'a>(
+    uri: &'a RouteUri,
+    route_params: &'a HashMap<String, String>,
+) -> AgentMetadata<'a> {
+    AgentMetadata::new(uri, route_params, &CONFIG)
+}
+
+struct TestAgent {
+    lane: StatsLane,
+}
+
+impl TestAgent {
+    const LANE: fn(&TestAgent) -> &StatsLane = |agent| &agent.lane;
+
+    fn with_window(window: HistoryRetention) -> Self {
+        TestAgent {
+            lane: StatsLane::with_window(LANE_ID, window),
+        }
+    }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < EPSILON,
+        "{} is not close to {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn empty_stats() {
+    let lane = StatsLane::new(LANE_ID);
+    assert_eq!(lane.window(), HistoryRetention::default());
+    assert_eq!(lane.stats(), Stats::default());
+}
+
+#[test]
+fn compute_stats() {
+    let lane = StatsLane::new(LANE_ID);
+    for sample in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+        lane.push(sample);
+    }
+    let Stats {
+        count,
+        mean,
+        min,
+        max,
+        stddev,
+    } = lane.stats();
+    assert_eq!(count, 8);
+    assert_close(mean, 5.0);
+    assert_close(min, 2.0);
+    assert_close(max, 9.0);
+    assert_close(stddev, 2.0);
+}
+
+#[test]
+fn stats_over_count_window() {
+    let lane = StatsLane::with_window(LANE_ID, HistoryRetention::count(2));
+    lane.push(100.0);
+    lane.push(1.0);
+    lane.push(3.0);
+
+    let stats = lane.stats();
+    assert_eq!(stats.count, 2);
+    assert_close(stats.mean, 2.0);
+    assert_close(stats.min, 1.0);
+    assert_close(stats.max, 3.0);
+    assert_close(stats.stddev, 1.0);
+}
+
+fn read_buffer(buffer: &mut BytesMut) -> Vec<LaneResponse<Stats>> {
+    let mut decoder = RawValueLaneResponseDecoder::default();
+    let mut results = vec![];
+    let parse = |body: &BytesMut| {
+        let body = std::str::from_utf8(body.as_ref()).expect("Bad utf.");
+        parse_recognize::<Stats>(body, false).expect("Bad body.")
+    };
+    while !buffer.is_empty() {
+        let msg = decoder
+            .decode(buffer)
+            .expect("Decode failed.")
+            .expect("Incomplete record");
+        results.push(match msg {
+            LaneResponse::StandardEvent(body) => LaneResponse::StandardEvent(parse(&body)),
+            LaneResponse::SyncEvent(id, body) => LaneResponse::SyncEvent(id, parse(&body)),
+            LaneResponse::Synced(id) => LaneResponse::Synced(id),
+            ow => panic!("Unexpected response: {:?}", ow),
+        });
+    }
+    results
+}
+
+#[test]
+fn write_event_and_sync() {
+    let lane = StatsLane::new(LANE_ID);
+    let mut buffer = BytesMut::new();
+    assert_eq!(lane.write_to_buffer(&mut buffer), WriteResult::NoData);
+
+    lane.push(2.0);
+    let expected = lane.stats();
+    assert_eq!(lane.write_to_buffer(&mut buffer), WriteResult::Done);
+    assert_eq!(
+        read_buffer(&mut buffer),
+        vec![LaneResponse::StandardEvent(expected)]
+    );
+    assert_eq!(lane.write_to_buffer(&mut buffer), WriteResult::NoData);
+
+    lane.sync(SYNC_ID);
+    assert_eq!(lane.write_to_buffer(&mut buffer), WriteResult::Done);
+    assert_eq!(
+        read_buffer(&mut buffer),
+        vec![
+            LaneResponse::SyncEvent(SYNC_ID, expected),
+            LaneResponse::Synced(SYNC_ID)
+        ]
+    );
+}
+
+fn check_written<T: PartialEq + std::fmt::Debug>(result: StepResult<T>, written: bool) -> T {
+    let expected_mod = if written {
+        Some(Modification::no_trigger(LANE_ID))
+    } else {
+        None
+    };
+    match result {
+        StepResult::Complete {
+            modified_item,
+            result,
+        } => {
+            assert_eq!(modified_item, expected_mod);
+            result
+        }
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+}
+
+#[test]
+fn stats_lane_push_and_get_event_handlers() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_window(HistoryRetention::count(4));
+
+    let mut handler = StatsLanePush::new(TestAgent::LANE, 6.0);
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    let stats = check_written(result, true);
+    assert_eq!(stats.count, 1);
+    assert_close(stats.mean, 6.0);
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    assert!(matches!(result, StepResult::Fail(_)));
+
+    let mut handler = StatsLaneGet::new(TestAgent::LANE);
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    assert_eq!(check_written(result, false), stats);
+}
+
+#[test]
+fn stats_lane_set_window_event_handler() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_window(HistoryRetention::count(4));
+    agent.lane.push(1.0);
+    agent.lane.push(2.0);
+
+    let mut handler = StatsLaneSetWindow::new(TestAgent::LANE, HistoryRetention::count(1));
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    check_written(result, true);
+    assert_eq!(agent.lane.window(), HistoryRetention::count(1));
+    assert_eq!(agent.lane.stats().count, 1);
+    assert_close(agent.lane.stats().mean, 2.0);
+}
+
+#[test]
+fn stats_lane_sync_event_handler() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_window(HistoryRetention::count(4));
+
+    let mut handler = StatsLaneSync::new(TestAgent::LANE, SYNC_ID);
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    check_written(result, true);
+
+    let mut buffer = BytesMut::new();
+    assert_eq!(agent.lane.write_to_buffer(&mut buffer), WriteResult::Done);
+    assert_eq!(
+        read_buffer(&mut buffer),
+        vec![
+            LaneResponse::SyncEvent(SYNC_ID, Stats::default()),
+            LaneResponse::Synced(SYNC_ID)
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn samples_expire() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_window(HistoryRetention::duration(MAX_AGE));
+
+    let mut pending: FuturesUnordered<HandlerFuture<TestAgent>> = FuturesUnordered::new();
+    let mut handler = StatsLanePush::new(TestAgent::LANE, 5.0);
+    let result = handler.step(
+        &mut ActionContext::new(
+            &pending,
+            &DummyAgentContext,
+            &no_downlink,
+            &mut HashMap::new(),
+            &mut BytesMut::new(),
+        ),
+        meta,
+        &agent,
+    );
+    check_written(result, true);
+    assert_eq!(pending.len(), 1);
+
+    let result = run_prune(&agent, meta, &mut pending).await;
+    check_written(result, true);
+    assert_eq!(agent.lane.stats(), Stats::default());
+    // The timer is restarted, waiting for more samples.
+    assert_eq!(pending.len(), 1);
+}
+
+async fn run_prune(
+    agent: &TestAgent,
+    meta: AgentMetadata<'_>,
+    pending: &mut FuturesUnordered<HandlerFuture<TestAgent>>,
+) -> StepResult<()> {
+    let mut handler = pending
+        .next()
+        .await
+        .expect("No eviction timer was scheduled.");
+    handler.step(
+        &mut ActionContext::new(
+            &*pending,
+            &DummyAgentContext,
+            &no_downlink,
+            &mut HashMap::new(),
+            &mut BytesMut::new(),
+        ),
+        meta,
+        agent,
+    )
+}
+
+fn run_handler<H>(
+    agent: &TestAgent,
+    meta: AgentMetadata<'_>,
+    pending: &FuturesUnordered<HandlerFuture<TestAgent>>,
+    mut handler: H,
+) -> StepResult<H::Completion>
+where
+    H: HandlerAction<TestAgent>,
+{
+    handler.step(
+        &mut ActionContext::new(
+            pending,
+            &DummyAgentContext,
+            &no_downlink,
+            &mut HashMap::new(),
+            &mut BytesMut::new(),
+        ),
+        meta,
+        agent,
+    )
+}
+
+#[tokio::test(start_paused = true)]
+async fn samples_share_one_timer() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_window(HistoryRetention::duration(MAX_AGE));
+
+    let mut pending = FuturesUnordered::new();
+    for sample in [1.0, 2.0, 3.0] {
+        run_handler(
+            &agent,
+            meta,
+            &pending,
+            StatsLanePush::new(TestAgent::LANE, sample),
+        );
+        tokio::time::advance(MAX_AGE / 4).await;
+    }
+    assert_eq!(pending.len(), 1);
+
+    let result = run_prune(&agent, meta, &mut pending).await;
+    check_written(result, true);
+    let stats = agent.lane.stats();
+    assert_eq!(stats.count, 2);
+    assert_close(stats.min, 2.0);
+    assert_close(stats.max, 3.0);
+    assert_eq!(pending.len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn shortening_window_prunes_without_push() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::with_window(HistoryRetention::count(8));
+
+    let mut pending = FuturesUnordered::new();
+    run_handler(
+        &agent,
+        meta,
+        &pending,
+        StatsLanePush::new(TestAgent::LANE, 1.0),
+    );
+    tokio::time::advance(MAX_AGE / 2).await;
+    run_handler(
+        &agent,
+        meta,
+        &pending,
+        StatsLanePush::new(TestAgent::LANE, 2.0),
+    );
+    tokio::time::advance(MAX_AGE / 4).await;
+    // Without a maximum age, no timer is required.
+    assert!(pending.is_empty());
+
+    let window = HistoryRetention::duration(MAX_AGE);
+    let result = run_handler(
+        &agent,
+        meta,
+        &pending,
+        StatsLaneSetWindow::new(TestAgent::LANE, window),
+    );
+    check_written(result, false);
+    assert_eq!(pending.len(), 1);
+
+    let start = tokio::time::Instant::now();
+    let result = run_prune(&agent, meta, &mut pending).await;
+    assert_eq!(start.elapsed(), MAX_AGE / 4);
+    check_written(result, true);
+    assert_eq!(agent.lane.stats().count, 1);
+    assert_close(agent.lane.stats().mean, 2.0);
+
+    let result = run_prune(&agent, meta, &mut pending).await;
+    check_written(result, true);
+    assert_eq!(agent.lane.stats(), Stats::default());
+}
+
+#[test]
+fn running_min_and_max() {
+    let lane = StatsLane::with_window(LANE_ID, HistoryRetention::count(3));
+    let samples = [5.0, 1.0, 4.0, 2.0, 3.0, 8.0, 0.5, 6.0];
+    for (i, sample) in samples.iter().enumerate() {
+        lane.push(*sample);
+        let window = &samples[i.saturating_sub(2)..=i];
+        let expected_min = window.iter().copied().fold(f64::INFINITY, f64::min);
+        let expected_max = window.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let expected_mean = window.iter().sum::<f64>() / window.len() as f64;
+        let stats = lane.stats();
+        assert_eq!(stats.count, window.len() as u64);
+        assert_close(stats.min, expected_min);
+        assert_close(stats.max, expected_max);
+        assert_close(stats.mean, expected_mean);
+    }
+}
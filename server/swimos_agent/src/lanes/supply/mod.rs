@@ -21,7 +21,6 @@ use tokio_util::codec::Encoder;
 use uuid::Uuid;
 
 use swimos_agent_protocol::encoding::lane::ValueLaneResponseEncoder;
-use swimos_form::write::StructuralWritable;
 
 use crate::event_handler::EventHandlerError;
 use crate::{
@@ -53,6 +52,10 @@ impl<T> Default for SupplyLaneInner<T> {
 
 /// A stateless lane that pushes events received directly to all uplinks attached to it.
 ///
+/// The events are serialized as Recon unless they are [`crate::lanes::Encoded`] values, in which
+/// case they are encoded with the associated codec (for example, `Encoded<RawBytes>` relays
+/// documents that have already been rendered exactly as they were supplied).
+///
 /// A Supply lane can push a value by executing an instance of [`Supply`] (which can be constructed
 /// using the [`crate::agent_lifecycle::HandlerContext`]).
 #[derive(Debug)]
@@ -93,7 +96,7 @@ const INFALLIBLE_SER: &str = "Serializing a value to recon should be infallible.
 
 impl<T> LaneItem for SupplyLane<T>
 where
-    T: StructuralWritable,
+    ValueLaneResponseEncoder: Encoder<LaneResponse<T>, Error = std::io::Error>,
 {
     fn write_to_buffer(&self, buffer: &mut BytesMut) -> WriteResult {
         let SupplyLane { inner, .. } = self;
@@ -14,7 +14,7 @@
 
 use std::collections::HashMap;
 
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use swimos_agent_protocol::LaneResponse;
 use tokio_util::codec::Decoder;
 use uuid::Uuid;
@@ -27,7 +27,7 @@ use crate::lanes::supply::{Supply, SupplyLaneSync};
 use crate::{
     agent_model::WriteResult,
     event_handler::{EventHandlerError, HandlerAction, Modification, StepResult},
-    lanes::LaneItem,
+    lanes::{Encoded, LaneItem, RawBytes},
     meta::AgentMetadata,
     test_context::dummy_context,
 };
@@ -168,6 +168,10 @@ fn read_buffer(buffer: &mut BytesMut) -> Vec<LaneResponse<i32>> {
                     .expect("Bad body."),
             ),
             LaneResponse::Synced(id) => LaneResponse::Synced(id),
+            LaneResponse::SyncedAt(id, version) => LaneResponse::SyncedAt(id, version),
+            LaneResponse::Correlated(id) => LaneResponse::Correlated(id),
+            LaneResponse::Originated(origin) => LaneResponse::Originated(origin),
+            LaneResponse::EventBatch(n) => LaneResponse::EventBatch(n),
         });
     }
     results
@@ -241,3 +245,25 @@ fn read_empty() {
     lane.write_to_buffer(&mut buffer);
     assert!(read_buffer(&mut buffer).is_empty());
 }
+
+#[test]
+fn write_encoded() {
+    let lane = SupplyLane::new(LANE_ID);
+    lane.push(Encoded::<RawBytes>::new(Bytes::from_static(
+        br#"{"value":13}"#,
+    )));
+
+    let mut buffer = BytesMut::new();
+    assert_eq!(lane.write_to_buffer(&mut buffer), WriteResult::Done);
+
+    let mut decoder = RawValueLaneResponseDecoder::default();
+    let message = decoder
+        .decode(&mut buffer)
+        .expect("Decode failed.")
+        .expect("Incomplete record");
+    match message {
+        LaneResponse::StandardEvent(body) => assert_eq!(body.as_ref(), br#"{"value":13}"#),
+        ow => panic!("Unexpected response: {:?}", ow),
+    }
+    assert!(buffer.is_empty());
+}
@@ -13,16 +13,22 @@
 // limitations under the License.
 
 pub mod lifecycle;
+mod transaction;
 
 #[cfg(test)]
 mod tests;
 
-use std::{borrow::Borrow, cell::RefCell, collections::VecDeque, marker::PhantomData};
+pub use transaction::{TransactionLanes, ValueLaneTransaction};
+
+use std::{
+    borrow::Borrow, cell::RefCell, collections::VecDeque, marker::PhantomData, time::Duration,
+};
 
 use bytes::BytesMut;
 use static_assertions::assert_impl_all;
-use swimos_agent_protocol::{encoding::lane::ValueLaneResponseEncoder, LaneResponse};
-use swimos_form::{read::RecognizerReadable, write::StructuralWritable};
+use swimos_agent_protocol::{
+    encoding::lane::ValueLaneResponseEncoder, DecodePayload, LaneResponse,
+};
 use tokio_util::codec::Encoder;
 use uuid::Uuid;
 
@@ -32,19 +38,24 @@ use crate::{
         ActionContext, AndThen, Decode, EventHandlerError, HandlerAction, HandlerActionExt,
         HandlerTrans, Modification, StepResult,
     },
-    item::{AgentItem, MutableValueLikeItem, ValueItem, ValueLikeItem},
+    item::{AgentItem, EventCount, MutableValueLikeItem, ValueItem, ValueLikeItem},
     meta::AgentMetadata,
     stores::value::ValueStore,
 };
 
-use super::{LaneItem, ProjTransform};
+use super::{expiry::ExpiryTimer, LaneItem, ProjTransform};
 
 /// Model of a value lane. This maintains a state and triggers an event each time this state is updated.
 /// Updates may come from external commands or from an action performed by an event handler on the agent.
+///
+/// A value lane can optionally be given a time-to-live (see [`ValueLane::with_ttl`]). If the lane is not
+/// written to for longer than this period, the agent task will reset it to the default value of its type,
+/// generating an event exactly as if it had been set explicitly.
 #[derive(Debug)]
 pub struct ValueLane<T> {
     store: ValueStore<T>,
     sync_queue: RefCell<VecDeque<Uuid>>,
+    expiry: Option<ExpiryTimer<()>>,
 }
 
 assert_impl_all!(ValueLane<()>: Send);
@@ -57,9 +68,31 @@ impl<T> ValueLane<T> {
         ValueLane {
             store: ValueStore::new(id, init),
             sync_queue: Default::default(),
+            expiry: None,
+        }
+    }
+
+    /// Create a value lane that will be reset to the default value of its type if it is not written to
+    /// within a fixed period. The initial value of the lane (or a value restored from persistence) will
+    /// not expire until the lane has been written to at least once.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the lane. This should be unique in an agent.
+    /// * `init` - The initial value of the lane.
+    /// * `ttl` - The time-to-live for the value of the lane.
+    pub fn with_ttl(id: u64, init: T, ttl: Duration) -> Self {
+        ValueLane {
+            store: ValueStore::new(id, init),
+            sync_queue: Default::default(),
+            expiry: Some(ExpiryTimer::new(ttl)),
         }
     }
 
+    /// The time-to-live for the value of the lane, if one has been set.
+    pub fn ttl(&self) -> Option<Duration> {
+        self.expiry.as_ref().map(ExpiryTimer::ttl)
+    }
+
     /// Read the state of the lane.
     pub fn read<F, R>(&self, f: F) -> R
     where
@@ -70,6 +103,7 @@ impl<T> ValueLane<T> {
 
     /// Update the state of the lane.
     pub(crate) fn set(&self, value: T) {
+        self.refresh_expiry();
         self.store.set(value)
     }
 
@@ -83,9 +117,54 @@ impl<T> ValueLane<T> {
     where
         F: FnOnce(&T) -> T,
     {
+        self.refresh_expiry();
         self.store.replace(f);
     }
 
+    /// Set the state of the lane, if its current value is equal to an expected value. Returns
+    /// whether the lane was modified.
+    pub fn compare_and_set(&self, expected: &T, new: T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.modify(|current| (current == expected).then_some(new))
+    }
+
+    /// Replace the contents of the lane if the closure produces a new value, returning whether the
+    /// lane was modified.
+    pub(crate) fn modify<F>(&self, f: F) -> bool
+    where
+        F: FnOnce(&T) -> Option<T>,
+    {
+        let modified = self.store.replace_if(f);
+        if modified {
+            self.refresh_expiry();
+        }
+        modified
+    }
+
+    /// If the lane has a time-to-live, record that it has been written to.
+    fn refresh_expiry(&self) {
+        if let Some(expiry) = &self.expiry {
+            expiry.refresh(());
+        }
+    }
+
+    /// Reset the lane to the default value of its type, after it has been reported as expired, unless
+    /// it has been written to again since. Returns whether the lane was reset.
+    fn expire(&self) -> bool
+    where
+        T: Default,
+    {
+        match &self.expiry {
+            Some(expiry) if !expiry.is_running(&()) => {
+                self.store.set(T::default());
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub(crate) fn with<F, B, U>(&self, f: F) -> U
     where
         B: ?Sized,
@@ -96,15 +175,46 @@ impl<T> ValueLane<T> {
     }
 }
 
+impl<T> ValueLane<T>
+where
+    T: Default + 'static,
+{
+    /// If the lane has a time-to-live, start the timer that resets it when it expires. This should be
+    /// called once, when the agent starts (see
+    /// [`AgentSpec::start_expiry_timers`](crate::agent_model::AgentSpec::start_expiry_timers)).
+    ///
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    /// * `action_context` - The context in which to suspend the timer.
+    pub fn start_expiry<C: 'static>(
+        &self,
+        projection: fn(&C) -> &Self,
+        action_context: &mut ActionContext<C>,
+    ) {
+        if let Some(expiry) = &self.expiry {
+            expiry.schedule(action_context, move |_| ValueLaneExpire::new(projection));
+        }
+    }
+}
+
 impl<T> AgentItem for ValueLane<T> {
     fn id(&self) -> u64 {
         self.store.id()
     }
 }
 
+impl<T> EventCount for ValueLane<T> {
+    fn event_count(&self) -> u64 {
+        self.store.event_count()
+    }
+}
+
 const INFALLIBLE_SER: &str = "Serializing to recon should be infallible.";
 
-impl<T: StructuralWritable> LaneItem for ValueLane<T> {
+impl<T> LaneItem for ValueLane<T>
+where
+    for<'a> ValueLaneResponseEncoder: Encoder<LaneResponse<&'a T>, Error = std::io::Error>,
+{
     fn write_to_buffer(&self, buffer: &mut BytesMut) -> WriteResult {
         let ValueLane {
             store, sync_queue, ..
@@ -274,6 +384,155 @@ impl<C, T> HandlerAction<C> for ValueLaneSync<C, T> {
     }
 }
 
+///  An [event handler](crate::event_handler::EventHandler)`] that will reset a value lane that has been
+/// reported as expired by its timer, and then schedule the timer again.
+pub struct ValueLaneExpire<C, T> {
+    projection: for<'a> fn(&'a C) -> &'a ValueLane<T>,
+    done: bool,
+}
+
+impl<C, T> ValueLaneExpire<C, T> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a ValueLane<T>) -> Self {
+        ValueLaneExpire {
+            projection,
+            done: false,
+        }
+    }
+}
+
+impl<C, T> HandlerAction<C> for ValueLaneExpire<C, T>
+where
+    C: 'static,
+    T: Default + 'static,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let ValueLaneExpire { projection, done } = self;
+        if *done {
+            StepResult::after_done()
+        } else {
+            *done = true;
+            let lane = projection(context);
+            let expired = lane.expire();
+            lane.start_expiry(*projection, action_context);
+            if expired {
+                StepResult::Complete {
+                    modified_item: Some(Modification::of(lane.id())),
+                    result: (),
+                }
+            } else {
+                StepResult::done(())
+            }
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will set the value of a value lane if its
+/// current value is equal to an expected value. The handler completes with whether the lane was modified.
+pub struct ValueLaneCompareAndSet<C, T> {
+    projection: for<'a> fn(&'a C) -> &'a ValueLane<T>,
+    values: Option<(T, T)>,
+}
+
+impl<C, T> ValueLaneCompareAndSet<C, T> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    /// * `expected` - The value that the lane must have for it to be modified.
+    /// * `new` - The new value for the lane.
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a ValueLane<T>, expected: T, new: T) -> Self {
+        ValueLaneCompareAndSet {
+            projection,
+            values: Some((expected, new)),
+        }
+    }
+}
+
+impl<C, T: PartialEq> HandlerAction<C> for ValueLaneCompareAndSet<C, T> {
+    type Completion = bool;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let ValueLaneCompareAndSet { projection, values } = self;
+        if let Some((expected, new)) = values.take() {
+            let lane = projection(context);
+            if lane.compare_and_set(&expected, new) {
+                StepResult::Complete {
+                    modified_item: Some(Modification::of(lane.id())),
+                    result: true,
+                }
+            } else {
+                StepResult::done(false)
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
+/// An [event handler](crate::event_handler::EventHandler) that will compute a new value for a value lane
+/// from its current value in a single step, so that no other handler can modify the lane in between. If no
+/// new value is produced, the lane is not modified. The handler completes with the value of the lane.
+pub struct ValueLaneModify<C, T, F> {
+    projection: for<'a> fn(&'a C) -> &'a ValueLane<T>,
+    f: Option<F>,
+}
+
+impl<C, T, F> ValueLaneModify<C, T, F> {
+    /// # Arguments
+    /// * `projection` - Projection from the agent context to the lane.
+    /// * `f` - Closure that computes the new value (if any) from the current value of the lane.
+    pub fn new(projection: for<'a> fn(&'a C) -> &'a ValueLane<T>, f: F) -> Self {
+        ValueLaneModify {
+            projection,
+            f: Some(f),
+        }
+    }
+}
+
+impl<C, T, F> HandlerAction<C> for ValueLaneModify<C, T, F>
+where
+    T: Clone,
+    F: FnOnce(&T) -> Option<T>,
+{
+    type Completion = T;
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let ValueLaneModify { projection, f } = self;
+        if let Some(f) = f.take() {
+            let lane = projection(context);
+            let modified = lane.modify(f);
+            let value = lane.read(T::clone);
+            if modified {
+                StepResult::Complete {
+                    modified_item: Some(Modification::of(lane.id())),
+                    result: value,
+                }
+            } else {
+                StepResult::done(value)
+            }
+        } else {
+            StepResult::after_done()
+        }
+    }
+}
+
 /// An [`HandlerAction`] that will produce a value from a reference to the contents of the lane.
 pub struct ValueLaneWithValue<C, T, F, B: ?Sized> {
     projection: for<'a> fn(&'a C) -> &'a ValueLane<T>,
@@ -330,7 +589,7 @@ pub type DecodeAndSet<C, T> =
     AndThen<Decode<T>, ValueLaneSet<C, T>, ProjTransform<C, ValueLane<T>>>;
 
 /// Create an event handler that will decode an incoming command and set the value into a value lane.
-pub fn decode_and_set<C, T: RecognizerReadable>(
+pub fn decode_and_set<C, T: DecodePayload>(
     buffer: BytesMut,
     projection: fn(&C) -> &ValueLane<T>,
 ) -> DecodeAndSet<C, T> {
@@ -12,25 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashMap, fmt::Debug};
+use std::{collections::HashMap, fmt::Debug, time::Duration};
 
-use bytes::BytesMut;
-use swimos_agent_protocol::{encoding::lane::RawValueLaneResponseDecoder, LaneResponse};
+use bytes::{Bytes, BytesMut};
+use futures::{stream::FuturesUnordered, StreamExt};
+use swimos_agent_protocol::{
+    encoding::lane::RawValueLaneResponseDecoder, Encoded, LaneResponse, RawBytes,
+};
 use swimos_api::agent::AgentConfig;
 use swimos_utilities::routing::RouteUri;
+use tokio::time::Instant;
 use tokio_util::codec::Decoder;
 use uuid::Uuid;
 
 use crate::{
     agent_model::WriteResult,
-    event_handler::{EventHandlerError, HandlerAction, Modification, StepResult},
+    event_handler::{
+        ActionContext, EventHandlerError, HandlerAction, HandlerFuture, Modification, StepResult,
+    },
     item::ValueItem,
     lanes::{
-        value::{ValueLaneGet, ValueLaneSync, ValueLaneWithValue},
+        value::{
+            decode_and_set, ValueLaneCompareAndSet, ValueLaneGet, ValueLaneModify, ValueLaneSync,
+            ValueLaneTransaction, ValueLaneWithValue,
+        },
         LaneItem,
     },
     meta::AgentMetadata,
-    test_context::dummy_context,
+    test_context::{dummy_context, no_downlink, DummyAgentContext},
 };
 
 use super::{ValueLane, ValueLaneSet};
@@ -73,6 +82,19 @@ fn write_to_value_lane() {
     assert_eq!(lane.read_with_prev(|prev, n| (prev, *n)), (Some(123), 89));
 }
 
+#[test]
+fn compare_and_set_value_lane() {
+    let lane = ValueLane::new(ID, 123);
+
+    assert!(!lane.compare_and_set(&0, 89));
+    assert!(!lane.store.has_data_to_write());
+    assert_eq!(lane.read(|n| *n), 123);
+
+    assert!(lane.compare_and_set(&123, 89));
+    assert!(lane.store.has_data_to_write());
+    assert_eq!(lane.read_with_prev(|prev, n| (prev, *n)), (Some(123), 89));
+}
+
 #[test]
 fn write_to_buffer_not_dirty() {
     let lane = ValueLane::new(ID, 123);
@@ -467,3 +489,290 @@ fn value_lane_with_value_event_handler() {
         StepResult::Fail(EventHandlerError::SteppedAfterComplete)
     ));
 }
+
+#[test]
+fn value_lane_transaction_event_handler() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::default();
+
+    let mut handler = ValueLaneTransaction::new(
+        (TestAgent::LANE, TestAgent::STR_LANE),
+        |(n, s): (i32, String)| Ok::<_, std::fmt::Error>((n + 1, format!("{} world", s))),
+    );
+
+    let expected = [
+        Modification::no_trigger(LANE_ID),
+        Modification::no_trigger(STR_LANE_ID),
+        Modification::trigger_only(LANE_ID),
+        Modification::trigger_only(STR_LANE_ID),
+    ];
+
+    for (i, expected_mod) in expected.into_iter().enumerate() {
+        let result = handler.step(
+            &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+            meta,
+            &agent,
+        );
+        // Both lanes are set by the first step, before any event is triggered.
+        assert_eq!(agent.lane.read(|n| *n), 1);
+        assert_eq!(agent.str_lane.read(Clone::clone), "hello world");
+        match result {
+            StepResult::Continue { modified_item } if i < 3 => {
+                assert_eq!(modified_item, Some(expected_mod));
+            }
+            StepResult::Complete { modified_item, .. } if i == 3 => {
+                assert_eq!(modified_item, Some(expected_mod));
+            }
+            ow => panic!("Unexpected result: {:?}", ow),
+        }
+    }
+
+    assert!(agent.lane.store.has_data_to_write());
+    assert!(agent.str_lane.store.has_data_to_write());
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+    ));
+}
+
+#[test]
+fn failed_value_lane_transaction() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::default();
+
+    let mut handler = ValueLaneTransaction::new(
+        (TestAgent::LANE, TestAgent::STR_LANE),
+        |_: (i32, String)| Err(std::fmt::Error),
+    );
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::HandlerFailed(_))
+    ));
+
+    assert!(!agent.lane.store.has_data_to_write());
+    assert!(!agent.str_lane.store.has_data_to_write());
+    assert_eq!(agent.lane.read(|n| *n), 0);
+    assert_eq!(agent.str_lane.read(Clone::clone), "hello");
+}
+
+#[test]
+fn value_lane_compare_and_set_event_handler() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::default();
+
+    let mut handler = ValueLaneCompareAndSet::new(TestAgent::LANE, 1, 84);
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    check_result(result, false, false, Some(false));
+    assert!(!agent.lane.store.has_data_to_write());
+
+    let mut handler = ValueLaneCompareAndSet::new(TestAgent::LANE, 0, 84);
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    check_result(result, true, true, Some(true));
+    assert_eq!(agent.lane.read(|n| *n), 84);
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+    ));
+}
+
+#[test]
+fn value_lane_modify_event_handler() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent::default();
+
+    let mut handler = ValueLaneModify::new(TestAgent::LANE, |n: &i32| Some(*n + 5));
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    check_result(result, true, true, Some(5));
+
+    let mut buffer = BytesMut::new();
+    let result = agent.lane.write_to_buffer(&mut buffer);
+    assert_eq!(result, WriteResult::Done);
+
+    let mut decoder = RawValueLaneResponseDecoder::default();
+    match decoder.decode(&mut buffer) {
+        Ok(Some(LaneResponse::StandardEvent(body))) => assert_eq!(body.as_ref(), b"5"),
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+    assert!(buffer.is_empty());
+
+    // No event is emitted if no new value is produced.
+    let mut handler = ValueLaneModify::new(TestAgent::LANE, |n: &i32| (*n > 10).then_some(0));
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    check_result(result, false, false, Some(5));
+    assert!(!agent.lane.store.has_data_to_write());
+
+    let result = handler.step(
+        &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+        meta,
+        &agent,
+    );
+    assert!(matches!(
+        result,
+        StepResult::Fail(EventHandlerError::SteppedAfterComplete)
+    ));
+}
+
+struct EncodedAgent {
+    lane: ValueLane<Encoded<RawBytes>>,
+}
+
+impl EncodedAgent {
+    const LANE: fn(&EncodedAgent) -> &ValueLane<Encoded<RawBytes>> = |agent| &agent.lane;
+}
+
+#[test]
+fn write_encoded_to_buffer() {
+    let lane = ValueLane::new(ID, Encoded::<RawBytes>::new(Bytes::from_static(&[0, 1])));
+    lane.set(Encoded::new(Bytes::from_static(&[255, 0, 128])));
+    let mut buffer = BytesMut::new();
+
+    let result = lane.write_to_buffer(&mut buffer);
+    assert_eq!(result, WriteResult::Done);
+
+    let mut decoder = RawValueLaneResponseDecoder::default();
+    match decoder.decode(&mut buffer) {
+        Ok(Some(LaneResponse::StandardEvent(body))) => {
+            assert_eq!(body.as_ref(), &[255, 0, 128])
+        }
+        ow => panic!("Unexpected result: {:?}", ow),
+    }
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn decode_and_set_encoded() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = EncodedAgent {
+        lane: ValueLane::new(LANE_ID, Encoded::new(Bytes::new())),
+    };
+
+    let body = BytesMut::from(&[0xde, 0xad, 0xbe, 0xef][..]);
+    let mut handler = decode_and_set(body, EncodedAgent::LANE);
+
+    let modified = loop {
+        match handler.step(
+            &mut dummy_context(&mut HashMap::new(), &mut BytesMut::new()),
+            meta,
+            &agent,
+        ) {
+            StepResult::Continue { .. } => {}
+            StepResult::Complete { modified_item, .. } => break modified_item,
+            StepResult::Fail(err) => panic!("Handler failed: {}", err),
+        }
+    };
+    assert_eq!(modified, Some(Modification::of(LANE_ID)));
+    assert_eq!(
+        agent.lane.read(|value| value.get().clone()),
+        Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef])
+    );
+}
+
+const TTL: Duration = Duration::from_secs(30);
+
+#[tokio::test(start_paused = true)]
+async fn value_lane_expires() {
+    let uri = make_uri();
+    let route_params = HashMap::new();
+    let meta = make_meta(&uri, &route_params);
+    let agent = TestAgent {
+        lane: ValueLane::with_ttl(LANE_ID, 0, TTL),
+        ..Default::default()
+    };
+    assert_eq!(agent.lane.ttl(), Some(TTL));
+
+    let pending = FuturesUnordered::<HandlerFuture<TestAgent>>::new();
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let mut action_context = ActionContext::new(
+        &pending,
+        &DummyAgentContext,
+        &no_downlink,
+        &mut join_lane_init,
+        &mut ad_hoc_buffer,
+    );
+    agent
+        .lane
+        .start_expiry(TestAgent::LANE, &mut action_context);
+    assert_eq!(pending.len(), 1);
+
+    let start = Instant::now();
+    agent.lane.set(56);
+    tokio::time::advance(TTL / 2).await;
+    agent.lane.set(78);
+    agent.lane.read_with_prev(|_, _| ());
+
+    let mut pending = pending;
+    let mut handler = pending
+        .next()
+        .await
+        .expect("No expiry timer was scheduled.");
+    assert_eq!(start.elapsed(), TTL + TTL / 2);
+
+    let mut join_lane_init = HashMap::new();
+    let mut ad_hoc_buffer = BytesMut::new();
+    let result = handler.step(
+        &mut ActionContext::new(
+            &pending,
+            &DummyAgentContext,
+            &no_downlink,
+            &mut join_lane_init,
+            &mut ad_hoc_buffer,
+        ),
+        meta,
+        &agent,
+    );
+    check_result(result, true, true, Some(()));
+
+    //The lane is reset to the default value and the timer is scheduled again.
+    let event = agent.lane.read_with_prev(|prev, value| (prev, *value));
+    assert_eq!(event, (Some(78), 0));
+    assert_eq!(pending.len(), 1);
+}
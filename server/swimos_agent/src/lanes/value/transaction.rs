@@ -0,0 +1,153 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::marker::PhantomData;
+
+use crate::{
+    event_handler::{ActionContext, EventHandlerError, HandlerAction, Modification, StepResult},
+    item::AgentItem,
+    meta::AgentMetadata,
+};
+
+use super::ValueLane;
+
+/// A tuple of projections to value lanes of an agent that can be updated together by a
+/// [`ValueLaneTransaction`]. This is implemented for tuples of between two and four projections.
+pub trait TransactionLanes<C> {
+    /// A tuple of the types of the values of the lanes.
+    type Values;
+
+    /// The number of lanes.
+    const LEN: usize;
+
+    /// Clone the current values of the lanes.
+    fn read(&self, context: &C) -> Self::Values;
+
+    /// Set the values of the lanes.
+    fn write(&self, context: &C, values: Self::Values);
+
+    /// The ID of the lane at an index.
+    fn id(&self, context: &C, index: usize) -> u64;
+}
+
+macro_rules! transaction_lanes {
+    ($len:literal, $(($proj:ident, $val:ident, $index:tt)),+) => {
+        impl<C, $($proj, $val),+> TransactionLanes<C> for ($($proj,)+)
+        where
+            $(
+                $proj: for<'a> Fn(&'a C) -> &'a ValueLane<$val>,
+                $val: Clone,
+            )+
+        {
+            type Values = ($($val,)+);
+
+            const LEN: usize = $len;
+
+            fn read(&self, context: &C) -> Self::Values {
+                ($(self.$index(context).read($val::clone),)+)
+            }
+
+            fn write(&self, context: &C, values: Self::Values) {
+                $(self.$index(context).set(values.$index);)+
+            }
+
+            fn id(&self, context: &C, index: usize) -> u64 {
+                match index {
+                    $($index => self.$index(context).id(),)+
+                    _ => panic!("Lane index out of range."),
+                }
+            }
+        }
+    };
+}
+
+transaction_lanes!(2, (P0, T0, 0), (P1, T1, 1));
+transaction_lanes!(3, (P0, T0, 0), (P1, T1, 1), (P2, T2, 2));
+transaction_lanes!(4, (P0, T0, 0), (P1, T1, 1), (P2, T2, 2), (P3, T3, 3));
+
+/// An [event handler](crate::event_handler::EventHandler) that updates several value lanes of an
+/// agent as a single step. The function is applied to the current values of the lanes and, if it
+/// succeeds, all of the lanes are set before any of their lifecycle events are triggered. If the
+/// function fails, none of the lanes are modified.
+pub struct ValueLaneTransaction<C, L, F> {
+    _type: PhantomData<fn(&C)>,
+    lanes: L,
+    state: TransactionState<F>,
+}
+
+enum TransactionState<F> {
+    Pending(F),
+    // All of the lanes have been set and the modifications are being reported. The first pass
+    // marks each lane as dirty and the second triggers the lifecycle events.
+    Reporting(usize),
+    Done,
+}
+
+impl<C, L, F> ValueLaneTransaction<C, L, F> {
+    /// # Arguments
+    /// * `lanes` - Projections to the lanes.
+    /// * `f` - Computes the new values of the lanes from their current values.
+    pub fn new(lanes: L, f: F) -> Self {
+        ValueLaneTransaction {
+            _type: PhantomData,
+            lanes,
+            state: TransactionState::Pending(f),
+        }
+    }
+}
+
+impl<C, L, F, E> HandlerAction<C> for ValueLaneTransaction<C, L, F>
+where
+    L: TransactionLanes<C>,
+    F: FnOnce(L::Values) -> Result<L::Values, E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    type Completion = ();
+
+    fn step(
+        &mut self,
+        _action_context: &mut ActionContext<C>,
+        _meta: AgentMetadata,
+        context: &C,
+    ) -> StepResult<Self::Completion> {
+        let ValueLaneTransaction { lanes, state, .. } = self;
+        let n = match std::mem::replace(state, TransactionState::Done) {
+            TransactionState::Pending(f) => match f(lanes.read(context)) {
+                Ok(values) => {
+                    lanes.write(context, values);
+                    0
+                }
+                Err(error) => return StepResult::Fail(EventHandlerError::failed(error)),
+            },
+            TransactionState::Reporting(n) => n,
+            TransactionState::Done => return StepResult::after_done(),
+        };
+        let modification = if n < L::LEN {
+            Modification::no_trigger(lanes.id(context, n))
+        } else {
+            Modification::trigger_only(lanes.id(context, n - L::LEN))
+        };
+        if n + 1 < 2 * L::LEN {
+            *state = TransactionState::Reporting(n + 1);
+            StepResult::Continue {
+                modified_item: Some(modification),
+            }
+        } else {
+            StepResult::Complete {
+                modified_item: Some(modification),
+                result: (),
+            }
+        }
+    }
+}
@@ -41,6 +41,12 @@ pub mod agent_model;
 /// Configuration types for downlinks that are started from agent lifecycles.
 pub mod config;
 
+/// Moving averages, rate meters and change detection used to maintain lanes that are derived from the
+/// activity of other lanes and stores (see [`agent_lifecycle::HandlerContext::track_rate`],
+/// [`agent_lifecycle::HandlerContext::track_ewma`] and
+/// [`agent_lifecycle::HandlerContext::track_history`]).
+pub mod derived;
+
 /// Traits and builders for constructing downlink lifecycles for downlinks started from agent lifecycles.
 pub mod downlink_lifecycle;
 
@@ -48,6 +54,7 @@ pub mod downlink_lifecycle;
 pub mod event_handler;
 
 mod event_queue;
+mod feature_flags;
 mod item;
 
 /// Defines the lane types that can be included in agent specifications. Lanes are exposed externally by the runtime,
@@ -72,12 +79,12 @@ mod tests;
 #[cfg(test)]
 pub use agent_model::AgentSpec;
 
-pub use item::AgentItem;
+pub use item::{AgentItem, EventCount};
 
 #[doc(hidden)]
 pub mod model {
     pub use swimos_agent_protocol::{MapMessage, MapOperation};
-    pub use swimos_api::agent::HttpLaneRequest;
+    pub use swimos_api::agent::{HttpLaneRequest, KeyRange, SyncVersion};
     pub use swimos_model::Text;
 }
 
@@ -12,7 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
 
 use swimos_agent_protocol::MapOperation;
 
@@ -24,6 +28,7 @@ pub struct MapStoreInner<K, V, Q> {
     content: HashMap<K, V>,
     previous: Option<MapLaneEvent<K, V>>,
     queue: Q,
+    events: u64,
 }
 
 /// Both map stores and map lanes maintain a queue of events to be sent to the runtime. This
@@ -37,7 +42,51 @@ pub trait MapEventQueue<K, V>: Default {
 
     fn push(&mut self, action: MapOperation<K, ()>);
 
-    fn pop<'a>(&mut self, content: &'a HashMap<K, V>) -> Option<Self::Output<'a>>;
+    /// Push a sequence of actions that should be reported as a single batch. By default, the actions
+    /// are pushed individually.
+    fn push_batch(&mut self, actions: Vec<MapOperation<K, ()>>) {
+        for action in actions {
+            self.push(action);
+        }
+    }
+
+    fn pop<'a, M>(&mut self, content: &'a M) -> Option<Self::Output<'a>>
+    where
+        K: 'a,
+        V: 'a,
+        M: MapContent<K, V>;
+}
+
+/// The entries of a map, from which the values referred to by the events in a [`MapEventQueue`]
+/// are resolved. This allows the queues to be used for maps with different representations.
+pub trait MapContent<K, V> {
+    /// Get the value associated with a key, if it exists.
+    fn get_entry(&self, key: &K) -> Option<&V>;
+
+    /// Determine whether the map has an entry for a key.
+    fn has_entry(&self, key: &K) -> bool {
+        self.get_entry(key).is_some()
+    }
+}
+
+impl<K: Eq + Hash, V> MapContent<K, V> for HashMap<K, V> {
+    fn get_entry(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn has_entry(&self, key: &K) -> bool {
+        self.contains_key(key)
+    }
+}
+
+impl<K: Ord, V> MapContent<K, V> for BTreeMap<K, V> {
+    fn get_entry(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn has_entry(&self, key: &K) -> bool {
+        self.contains_key(key)
+    }
 }
 
 impl<K, V, Q: Default> MapStoreInner<K, V, Q> {
@@ -46,8 +95,14 @@ impl<K, V, Q: Default> MapStoreInner<K, V, Q> {
             content,
             previous: Default::default(),
             queue: Default::default(),
+            events: 0,
         }
     }
+
+    /// The number of modifications that have been made to the map (excluding initialization).
+    pub fn event_count(&self) -> u64 {
+        self.events
+    }
 }
 
 pub enum TransformEntryResult {
@@ -70,7 +125,9 @@ where
             content,
             previous,
             queue,
+            events,
         } = self;
+        *events += 1;
         let prev = content.insert(key.clone(), value);
         *previous = Some(MapLaneEvent::Update(key.clone(), prev));
         queue.push(MapOperation::Update { key, value: () });
@@ -84,16 +141,19 @@ where
             content,
             previous,
             queue,
+            events,
         } = self;
         match content.remove(&key) {
             Some(v) => match f(Some(&v)) {
                 Some(v2) => {
                     content.insert(key.clone(), v2);
+                    *events += 1;
                     *previous = Some(MapLaneEvent::Update(key.clone(), Some(v)));
                     queue.push(MapOperation::Update { key, value: () });
                     TransformEntryResult::Update
                 }
                 _ => {
+                    *events += 1;
                     *previous = Some(MapLaneEvent::Remove(key.clone(), v));
                     queue.push(MapOperation::Remove { key: key.clone() });
                     TransformEntryResult::Remove
@@ -102,6 +162,7 @@ where
             _ => match f(None) {
                 Some(v2) => {
                     content.insert(key.clone(), v2);
+                    *events += 1;
                     *previous = Some(MapLaneEvent::Update(key.clone(), None));
                     queue.push(MapOperation::Update { key, value: () });
                     TransformEntryResult::Update
@@ -116,9 +177,11 @@ where
             content,
             previous,
             queue,
+            events,
         } = self;
         let prev = content.remove(key);
         if let Some(prev) = prev {
+            *events += 1;
             *previous = Some(MapLaneEvent::Remove(key.clone(), prev));
             queue.push(MapOperation::Remove { key: key.clone() });
         }
@@ -129,11 +192,47 @@ where
             content,
             previous,
             queue,
+            events,
         } = self;
+        *events += 1;
         *previous = Some(MapLaneEvent::Clear(std::mem::take(content)));
         queue.push(MapOperation::Clear);
     }
 
+    /// Apply a sequence of operations to the map as a single batch. No lifecycle event will be
+    /// available for the changes and they will be reported to the queue together.
+    pub fn transaction(&mut self, ops: Vec<MapOperation<K, V>>) {
+        let MapStoreInner {
+            content,
+            previous,
+            queue,
+            events,
+        } = self;
+        let mut actions = Vec::with_capacity(ops.len());
+        for op in ops {
+            match op {
+                MapOperation::Update { key, value } => {
+                    content.insert(key.clone(), value);
+                    actions.push(MapOperation::Update { key, value: () });
+                }
+                MapOperation::Remove { key } => {
+                    if content.remove(&key).is_some() {
+                        actions.push(MapOperation::Remove { key });
+                    }
+                }
+                MapOperation::Clear => {
+                    content.clear();
+                    actions.push(MapOperation::Clear);
+                }
+            }
+        }
+        if !actions.is_empty() {
+            *events += actions.len() as u64;
+            *previous = None;
+            queue.push_batch(actions);
+        }
+    }
+
     pub fn get_map<F, R>(&self, f: F) -> R
     where
         F: FnOnce(&HashMap<K, V>) -> R,
@@ -16,6 +16,7 @@ use std::collections::HashMap;
 
 use swimos_api::agent::AgentConfig;
 use swimos_utilities::routing::RouteUri;
+use uuid::Uuid;
 
 /// Metadata to describe a running agent instance.
 #[derive(Clone, Copy, Debug)]
@@ -26,6 +27,10 @@ pub struct AgentMetadata<'a> {
     route_params: &'a HashMap<String, String>,
     // Specific configuration for the instance.
     configuration: &'a AgentConfig,
+    // The ID of the remote that sent the command that is being handled (if known).
+    command_origin: Option<Uuid>,
+    // The correlation ID of the envelope that contained the command that is being handled.
+    correlation_id: Option<u64>,
 }
 
 impl<'a> AgentMetadata<'a> {
@@ -38,6 +43,25 @@ impl<'a> AgentMetadata<'a> {
             path,
             route_params,
             configuration,
+            command_origin: None,
+            correlation_id: None,
+        }
+    }
+
+    /// Attach the ID of the remote that sent the command that is currently being handled.
+    pub fn with_command_origin(self, command_origin: Option<Uuid>) -> Self {
+        AgentMetadata {
+            command_origin,
+            ..self
+        }
+    }
+
+    /// Attach the correlation ID of the envelope that contained the command that is currently
+    /// being handled.
+    pub fn with_correlation_id(self, correlation_id: Option<u64>) -> Self {
+        AgentMetadata {
+            correlation_id,
+            ..self
         }
     }
 
@@ -52,4 +76,17 @@ impl<'a> AgentMetadata<'a> {
     pub fn agent_configuration(&self) -> &'a AgentConfig {
         self.configuration
     }
+
+    /// The ID of the remote that sent the command that is currently being handled. This is only
+    /// available for lanes that are configured to track the origins of commands.
+    pub fn command_origin(&self) -> Option<Uuid> {
+        self.command_origin
+    }
+
+    /// The correlation ID of the envelope that contained the command that is currently being
+    /// handled. This can be used to associate the log messages of a handler with those of the
+    /// server tasks that handled the envelope.
+    pub fn correlation_id(&self) -> Option<u64> {
+        self.correlation_id
+    }
 }
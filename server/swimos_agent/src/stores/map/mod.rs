@@ -24,9 +24,12 @@ use swimos_form::write::StructuralWritable;
 use tokio_util::codec::Encoder;
 
 use crate::agent_model::WriteResult;
+use crate::event_handler::reentrancy::{self, Deferred, Write};
 use crate::event_handler::{ActionContext, HandlerAction, Modification, StepResult};
 use crate::event_queue::EventQueue;
-use crate::item::{AgentItem, InspectableMapLikeItem, MapItem, MapLikeItem, MutableMapLikeItem};
+use crate::item::{
+    AgentItem, EventCount, InspectableMapLikeItem, MapItem, MapLikeItem, MutableMapLikeItem,
+};
 use crate::map_storage::{MapStoreInner, TransformEntryResult};
 use crate::meta::AgentMetadata;
 
@@ -44,6 +47,7 @@ type Inner<K, V> = MapStoreInner<K, V, EventQueue<K, ()>>;
 pub struct MapStore<K, V> {
     id: u64,
     inner: RefCell<Inner<K, V>>,
+    deferred: Deferred<(K, V)>,
 }
 
 assert_impl_all!(MapStore<(), ()>: Send);
@@ -56,6 +60,7 @@ impl<K, V> MapStore<K, V> {
         MapStore {
             id,
             inner: RefCell::new(Inner::new(init)),
+            deferred: Default::default(),
         }
     }
 }
@@ -66,6 +71,12 @@ impl<K, V> AgentItem for MapStore<K, V> {
     }
 }
 
+impl<K, V> EventCount for MapStore<K, V> {
+    fn event_count(&self) -> u64 {
+        self.inner.borrow().event_count()
+    }
+}
+
 impl<K, V> MapItem<K, V> for MapStore<K, V>
 where
     K: Eq + Hash + Clone,
@@ -78,7 +89,9 @@ where
     where
         F: FnOnce(Option<crate::lanes::map::MapLaneEvent<K, V>>, &HashMap<K, V>) -> R,
     {
-        self.inner.borrow_mut().read_with_prev(f)
+        let result = self.inner.borrow_mut().read_with_prev(f);
+        self.apply_deferred();
+        result
     }
 }
 
@@ -88,7 +101,22 @@ where
 {
     /// Update the value associated with a key.
     pub fn update(&self, key: K, value: V) {
-        self.inner.borrow_mut().update(key, value)
+        match reentrancy::write(self.id, &self.inner, true) {
+            Write::Available(mut guard) => {
+                let inner = &mut *guard;
+                self.deferred
+                    .apply_to(inner, |inner, (key, value)| inner.update(key, value));
+                inner.update(key, value);
+            }
+            Write::Defer => self.deferred.push((key, value)),
+            Write::Rejected => {}
+        }
+    }
+
+    /// Apply any updates that were queued while the store was borrowed.
+    fn apply_deferred(&self) {
+        self.deferred
+            .apply(&self.inner, |guard, (key, value)| guard.update(key, value));
     }
 
     /// Transform the value associated with a key.
@@ -96,17 +124,24 @@ where
     where
         F: FnOnce(Option<&V>) -> Option<V>,
     {
-        self.inner.borrow_mut().transform_entry(key, f)
+        match reentrancy::write(self.id, &self.inner, false) {
+            Write::Available(mut guard) => guard.transform_entry(key, f),
+            _ => TransformEntryResult::NoChange,
+        }
     }
 
     /// Remove an entry from the map.
     pub fn remove(&self, key: &K) {
-        self.inner.borrow_mut().remove(key)
+        if let Write::Available(mut guard) = reentrancy::write(self.id, &self.inner, false) {
+            guard.remove(key);
+        }
     }
 
     /// Clear the map.
     pub fn clear(&self) {
-        self.inner.borrow_mut().clear()
+        if let Write::Available(mut guard) = reentrancy::write(self.id, &self.inner, false) {
+            guard.clear();
+        }
     }
 
     /// Read a value from the map, if it exists.
@@ -116,7 +151,9 @@ where
         Q: Eq + Hash,
         F: FnOnce(Option<&V>) -> R,
     {
-        self.inner.borrow().with_entry(key, f)
+        let result = self.inner.borrow().with_entry(key, f);
+        self.apply_deferred();
+        result
     }
 
     /// Read the complete state of the map.
@@ -124,7 +161,9 @@ where
     where
         F: FnOnce(&HashMap<K, V>) -> R,
     {
-        self.inner.borrow().get_map(f)
+        let result = self.inner.borrow().get_map(f);
+        self.apply_deferred();
+        result
     }
 }
 
@@ -511,7 +550,7 @@ where
     where
         Self: 'static,
         C: 'a,
-        B: ?Sized +'static,
+        B: ?Sized + 'static,
         V: Borrow<B>,
         F: FnOnce(Option<&B>) -> U + Send + 'a;
 
@@ -27,7 +27,9 @@ use tokio_util::codec::Decoder;
 
 use crate::{
     agent_model::WriteResult,
-    event_handler::{EventHandlerError, HandlerAction, Modification, StepResult},
+    event_handler::{
+        reentrancy, EventHandlerError, HandlerAction, Modification, ReentrancyPolicy, StepResult,
+    },
     item::MapItem,
     lanes::map::MapLaneEvent,
     meta::AgentMetadata,
@@ -127,6 +129,25 @@ fn clear_map_store() {
     });
 }
 
+#[test]
+fn queue_reentrant_update_map_store() {
+    let store = MapStore::new(ID, init());
+
+    let _scope = reentrancy::enter(ReentrancyPolicy::Queue);
+    store.get(&K1, |v| {
+        store.update(K2, v.cloned().unwrap());
+        store.remove(&K3);
+    });
+
+    assert_eq!(reentrancy::take_violation(), Some(ID));
+    store.get_map(|m| {
+        assert_eq!(m.len(), 3);
+        assert_eq!(m.get(&K1), Some(&Text::new(V1)));
+        assert_eq!(m.get(&K2), Some(&Text::new(V1)));
+        assert_eq!(m.get(&K3), Some(&Text::new(V3)));
+    });
+}
+
 #[test]
 fn write_to_buffer_no_data() {
     let store = MapStore::new(ID, init());
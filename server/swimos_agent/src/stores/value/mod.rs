@@ -26,8 +26,11 @@ use tokio_util::codec::Encoder;
 
 use crate::{
     agent_model::WriteResult,
-    event_handler::{ActionContext, EventHandlerError, HandlerAction, Modification, StepResult},
-    item::{AgentItem, MutableValueLikeItem, ValueItem, ValueLikeItem},
+    event_handler::{
+        reentrancy::{self, Deferred, Write},
+        ActionContext, EventHandlerError, HandlerAction, Modification, StepResult,
+    },
+    item::{AgentItem, EventCount, MutableValueLikeItem, ValueItem, ValueLikeItem},
     meta::AgentMetadata,
 };
 
@@ -50,6 +53,8 @@ pub struct ValueStore<T> {
     id: u64,
     inner: RefCell<Inner<T>>,
     dirty: Cell<bool>,
+    events: Cell<u64>,
+    deferred: Deferred<T>,
 }
 
 assert_impl_all!(ValueStore<()>: Send);
@@ -66,6 +71,8 @@ impl<T> ValueStore<T> {
                 previous: None,
             }),
             dirty: Cell::new(false),
+            events: Cell::new(0),
+            deferred: Default::default(),
         }
     }
 
@@ -75,8 +82,12 @@ impl<T> ValueStore<T> {
         F: FnOnce(&T) -> R,
     {
         let ValueStore { inner, .. } = self;
-        let guard = inner.borrow();
-        f(&guard.content)
+        let result = {
+            let guard = inner.borrow();
+            f(&guard.content)
+        };
+        self.apply_deferred();
+        result
     }
 
     /// Read the state of the store, consuming the previous value (used when triggering the `on_set` event
@@ -86,20 +97,46 @@ impl<T> ValueStore<T> {
         F: FnOnce(Option<T>, &T) -> R,
     {
         let ValueStore { inner, .. } = self;
-        let mut guard = inner.borrow_mut();
-        let Inner { content, previous } = &mut *guard;
-        let prev = previous.take();
-        f(prev, content)
+        let result = {
+            let mut guard = inner.borrow_mut();
+            let Inner { content, previous } = &mut *guard;
+            let prev = previous.take();
+            f(prev, content)
+        };
+        self.apply_deferred();
+        result
     }
 
     /// Update the state of the store.
     pub fn set(&self, value: T) {
-        let ValueStore { inner, dirty, .. } = self;
-        let mut guard = inner.borrow_mut();
-        let Inner { content, previous } = &mut *guard;
+        let ValueStore {
+            id,
+            inner,
+            deferred,
+            ..
+        } = self;
+        match reentrancy::write(*id, inner, true) {
+            Write::Available(mut guard) => self.apply_set(&mut guard, value),
+            Write::Defer => deferred.push(value),
+            Write::Rejected => {}
+        }
+    }
+
+    fn apply_set(&self, inner: &mut Inner<T>, value: T) {
+        let ValueStore { dirty, events, .. } = self;
+        let Inner { content, previous } = inner;
         let prev = std::mem::replace(content, value);
         *previous = Some(prev);
         dirty.replace(true);
+        events.set(events.get() + 1);
+    }
+
+    /// Apply any calls to `set` that were queued while the store was borrowed.
+    fn apply_deferred(&self) {
+        let ValueStore {
+            inner, deferred, ..
+        } = self;
+        deferred.apply(inner, |guard, value| self.apply_set(guard, value));
     }
 
     pub(crate) fn init(&self, value: T) {
@@ -129,13 +166,27 @@ impl<T> ValueStore<T> {
     where
         F: FnOnce(&T) -> T,
     {
-        let ValueStore { inner, dirty, .. } = self;
-        let mut guard = inner.borrow_mut();
-        let Inner { content, previous } = &mut *guard;
-        let new_value = f(content);
-        let prev = std::mem::replace(content, new_value);
-        *previous = Some(prev);
-        dirty.replace(true);
+        let ValueStore { id, inner, .. } = self;
+        if let Write::Available(mut guard) = reentrancy::write(*id, inner, false) {
+            let new_value = f(&guard.content);
+            self.apply_set(&mut guard, new_value);
+        }
+    }
+
+    /// Replace the contents of the store if the closure produces a new value, returning whether
+    /// the store was modified.
+    pub(crate) fn replace_if<F>(&self, f: F) -> bool
+    where
+        F: FnOnce(&T) -> Option<T>,
+    {
+        let ValueStore { id, inner, .. } = self;
+        if let Write::Available(mut guard) = reentrancy::write(*id, inner, false) {
+            if let Some(new_value) = f(&guard.content) {
+                self.apply_set(&mut guard, new_value);
+                return true;
+            }
+        }
+        false
     }
 
     pub(crate) fn with<F, B, U>(&self, f: F) -> U
@@ -145,9 +196,13 @@ impl<T> ValueStore<T> {
         F: FnOnce(&B) -> U,
     {
         let ValueStore { inner, .. } = self;
-        let guard = inner.borrow();
-        let Inner { content, .. } = &*guard;
-        f(content.borrow())
+        let result = {
+            let guard = inner.borrow();
+            let Inner { content, .. } = &*guard;
+            f(content.borrow())
+        };
+        self.apply_deferred();
+        result
     }
 }
 
@@ -157,16 +212,18 @@ impl<T> AgentItem for ValueStore<T> {
     }
 }
 
+impl<T> EventCount for ValueStore<T> {
+    fn event_count(&self) -> u64 {
+        self.events.get()
+    }
+}
+
 impl<T> ValueItem<T> for ValueStore<T> {
     fn read_with_prev<F, R>(&self, f: F) -> R
     where
         F: FnOnce(Option<T>, &T) -> R,
     {
-        let ValueStore { inner, .. } = self;
-        let mut guard = inner.borrow_mut();
-        let Inner { content, previous } = &mut *guard;
-        let prev = previous.take();
-        f(prev, content)
+        ValueStore::read_with_prev(self, f)
     }
 
     fn init(&self, value: T) {
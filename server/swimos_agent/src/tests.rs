@@ -50,7 +50,9 @@ use swimos_agent_derive::{lifecycle, AgentLaneModel};
 use swimos_api::agent::DownlinkKind;
 use swimos_api::error::{DownlinkRuntimeError, OpenStoreError};
 use swimos_api::{
-    agent::{AgentConfig, HttpLaneRequest, HttpResponseReceiver, StoreKind, WarpLaneKind},
+    agent::{
+        AgentConfig, HttpLaneRequest, HttpResponseReceiver, StopReason, StoreKind, WarpLaneKind,
+    },
     http::{Header, HttpRequest, HttpResponse, Method, StandardHeaderName, StatusCode, Version},
 };
 use swimos_model::Text;
@@ -193,6 +195,7 @@ enum MapEvent {
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Event {
     StartOrStop,
+    Stopped(StopReason),
     Value(ValueEvent<i32>),
     Command(i32),
     Map(MapEvent),
@@ -337,7 +340,7 @@ fn on_stop_handler() {
 
     let lifecycle = template.clone().into_lifecycle();
 
-    let handler = lifecycle.on_stop();
+    let handler = lifecycle.on_stop(StopReason::PlaneShutdown);
     run_handler(&agent, handler);
 
     let events = template.0.take();
@@ -345,6 +348,38 @@ fn on_stop_handler() {
     assert_eq!(events, vec![Event::StartOrStop]);
 }
 
+#[test]
+fn on_stop_handler_with_reason() {
+    #[derive(Default, Clone)]
+    struct TestLifecycle(LifecycleInner);
+
+    #[lifecycle(TestAgent, agent_root(crate))]
+    impl TestLifecycle {
+        #[on_stop]
+        fn my_on_stop(
+            &self,
+            context: HandlerContext<TestAgent>,
+            reason: StopReason,
+        ) -> impl EventHandler<TestAgent> + '_ {
+            context.effect(move || {
+                self.0.push(Event::Stopped(reason));
+            })
+        }
+    }
+
+    let agent = TestAgent::default();
+    let template = TestLifecycle::default();
+
+    let lifecycle = template.clone().into_lifecycle();
+
+    let handler = lifecycle.on_stop(StopReason::InactivityTimeout);
+    run_handler(&agent, handler);
+
+    let events = template.0.take();
+
+    assert_eq!(events, vec![Event::Stopped(StopReason::InactivityTimeout)]);
+}
+
 #[test]
 fn on_start_and_stop_handler() {
     #[derive(Default, Clone)]
@@ -372,7 +407,7 @@ fn on_start_and_stop_handler() {
     let handler = lifecycle.on_start();
     run_handler(&agent, handler);
 
-    let handler = lifecycle.on_stop();
+    let handler = lifecycle.on_stop(StopReason::PlaneShutdown);
     run_handler(&agent, handler);
 
     let events = template.0.take();
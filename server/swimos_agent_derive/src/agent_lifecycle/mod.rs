@@ -22,7 +22,7 @@ use self::{
     model::{
         AgentLifecycleDescriptor, CommandLifecycleDescriptor, DemandLifecycleDescriptor,
         DemandMapLifecycleDescriptor, HttpLifecycleDescriptor, ItemLifecycle, JoinLaneInit,
-        MapLifecycleDescriptor, ValueLifecycleDescriptor,
+        MapLifecycleDescriptor, OnStopHandler, ValueLifecycleDescriptor,
     },
     tree::BinTree,
 };
@@ -277,9 +277,19 @@ impl<'a> ToTokens for ImplAgentLifecycle<'a> {
             };
         }
 
-        if let Some(on_stop) = on_stop {
-            lifecycle_builder = parse_quote! {
-                #root::agent_lifecycle::StatefulAgentLifecycle::on_stop(#lifecycle_builder, #lifecycle_type::#on_stop)
+        if let Some(OnStopHandler {
+            method,
+            with_reason,
+        }) = on_stop
+        {
+            lifecycle_builder = if with_reason {
+                parse_quote! {
+                    #root::agent_lifecycle::StatefulAgentLifecycle::on_stop_with_reason(#lifecycle_builder, #lifecycle_type::#method)
+                }
+            } else {
+                parse_quote! {
+                    #root::agent_lifecycle::StatefulAgentLifecycle::on_stop(#lifecycle_builder, #lifecycle_type::#method)
+                }
             };
         }
 
@@ -268,8 +268,8 @@ fn validate_method_as<'a>(
                 })
             }
             HandlerKind::Stop => {
-                Validation::join(acc, validate_no_type_sig(sig)).and_then(|(mut acc, _)| {
-                    if let Err(e) = acc.add_on_stop(&sig.ident) {
+                Validation::join(acc, validate_stop_sig(sig)).and_then(|(mut acc, with_reason)| {
+                    if let Err(e) = acc.add_on_stop(&sig.ident, with_reason) {
                         Validation::Validated(acc, Errors::of(e))
                     } else {
                         Validation::valid(acc)
@@ -282,7 +282,7 @@ fn validate_method_as<'a>(
                     if let Err(e) = acc.add_on_start(&sig.ident) {
                         errors.push(e);
                     }
-                    if let Err(e) = acc.add_on_stop(&sig.ident) {
+                    if let Err(e) = acc.add_on_stop(&sig.ident, false) {
                         errors.push(e);
                     }
                     Validation::Validated(acc, errors)
@@ -494,6 +494,25 @@ fn validate_no_type_sig(sig: &Signature) -> Validation<(), Errors<syn::Error>> {
         })
 }
 
+/// Check that a method has the correct shape for the on_stop handler. The handler may optionally
+/// take the reason that the agent is stopping, after the context.
+fn validate_stop_sig(sig: &Signature) -> Validation<bool, Errors<syn::Error>> {
+    let iter = sig.inputs.iter();
+    check_receiver(sig, iter)
+        .and_then(|mut iter| {
+            if iter.next().is_none() {
+                Validation::fail(syn::Error::new_spanned(sig, REQUIRED_CONTEXT))
+            } else {
+                Validation::valid(iter)
+            }
+        })
+        .and_then(|iter| match extract_types(iter).len() {
+            0 => Validation::valid(false),
+            1 => Validation::valid(true),
+            _ => Validation::fail(syn::Error::new_spanned(sig, BAD_SIGNATURE)),
+        })
+}
+
 fn validate_join_value_lifecycle_sig(
     sig: &Signature,
 ) -> Validation<(&Type, &Type), Errors<syn::Error>> {
@@ -1152,10 +1171,17 @@ pub struct AgentLifecycleDescriptor<'a> {
     pub lifecycle_type: &'a Type, //The type of the lifecycle (taken from the impl block).
     pub init_blocks: Vec<JoinLaneInit<'a>>,
     pub on_start: Option<&'a Ident>, //A handler attached to the on_start event.
-    pub on_stop: Option<&'a Ident>,  //A handler attached to the on_stop event.
+    pub on_stop: Option<OnStopHandler<'a>>, //A handler attached to the on_stop event.
     pub lane_lifecycles: BinTree<String, ItemLifecycle<'a>>, //Labelled tree of lane handlers.
 }
 
+/// Description of the handler attached to the on_stop event.
+#[derive(Clone, Copy)]
+pub struct OnStopHandler<'a> {
+    pub method: &'a Ident,
+    pub with_reason: bool, //Whether the handler takes the reason that the agent is stopping.
+}
+
 /// Builder type for constructing an [`AgentLifecycleDescriptor`].
 pub struct AgentLifecycleDescriptorBuilder<'a> {
     pub root: Path,
@@ -1163,7 +1189,7 @@ pub struct AgentLifecycleDescriptorBuilder<'a> {
     pub no_clone: bool,
     pub lifecycle_type: &'a Type,
     pub on_start: Option<&'a Ident>,
-    pub on_stop: Option<&'a Ident>,
+    pub on_stop: Option<OnStopHandler<'a>>,
     pub lane_lifecycles: BTreeMap<String, ItemLifecycle<'a>>,
 }
 
@@ -1224,12 +1250,15 @@ impl<'a> AgentLifecycleDescriptorBuilder<'a> {
         }
     }
 
-    pub fn add_on_stop(&mut self, method: &'a Ident) -> Result<(), syn::Error> {
+    pub fn add_on_stop(&mut self, method: &'a Ident, with_reason: bool) -> Result<(), syn::Error> {
         let AgentLifecycleDescriptorBuilder { on_stop, .. } = self;
         if on_stop.is_some() {
             Err(syn::Error::new_spanned(method, DUPLICATE_ON_STOP))
         } else {
-            *on_stop = Some(method);
+            *on_stop = Some(OnStopHandler {
+                method,
+                with_reason,
+            });
             Ok(())
         }
     }
@@ -25,21 +25,184 @@ use super::model::ItemFlags;
 const RENAME_TAG: &str = "name";
 const CONV_TAG: &str = "convention";
 const TRANSIENT_ATTR_NAME: &str = "transient";
+const TRACK_ORIGIN_ATTR_NAME: &str = "track_origin";
+const RENAMED_FROM_TAG: &str = "renamed_from";
+const ALIAS_TAG: &str = "alias";
 const ROOT_ATTR_NAME: &str = "root";
+const RATE_LIMIT_TAG: &str = "rate_limit";
+const MIN_INTERVAL_TAG: &str = "min_interval_ms";
+const MAX_BACKLOG_TAG: &str = "max_backlog";
+const OVERFLOW_TAG: &str = "overflow";
+const TTL_TAG: &str = "ttl_ms";
 const INVALID_FIELD_ATTR: &str = "Invalid field attribute.";
 const INVALID_AGENT_ROOT: &str = "Invalid agent root specifier.";
+const INVALID_PREVIOUS_NAME: &str = "The previous name of an item must be a non-empty string.";
+const INVALID_ALIAS: &str = "The alias of a lane must be a non-empty string.";
+const INVALID_RATE_LIMIT: &str = "Invalid rate limit. Expected `rate_limit(min_interval_ms = <integer>, max_backlog = <positive integer>, overflow = \"drop_oldest\" | \"drop_newest\" | \"error\")`.";
+const DUPLICATE_RATE_LIMIT: &str = "An item can only have one rate limit.";
+const INVALID_TTL: &str = "Invalid time-to-live. Expected `ttl_ms = <positive integer>`.";
+const DUPLICATE_TTL: &str = "An item can only have one time-to-live.";
 
 struct TransientFlag;
 
 /// Attribute consumer to recognize the transient flag for agent items.
 struct TransientFlagConsumer;
 
+struct TrackOriginFlag;
+
+/// Attribute consumer to recognize the origin tracking flag for lanes.
+struct TrackOriginFlagConsumer;
+
 /// Types of modification that can be applied to an item using attributes.
 pub enum ItemAttr {
     /// The should be transient.
     Transient,
+    /// The lane should track the origins of the commands that it receives.
+    TrackOrigin,
     /// The name of the item should be transformed.
     Transform(Transformation),
+    /// The item was previously known by another name.
+    RenamedFrom(String),
+    /// The item can still be addressed by a deprecated name.
+    Alias(String),
+    /// Limits on the rate at which a command lane invokes its event handler.
+    RateLimit(RateLimitSpec),
+    /// The time-to-live, in milliseconds, for the value or entries of a lane.
+    Ttl(u64),
+}
+
+/// The action to take when the backlog of a rate limited command lane is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowKind {
+    DropOldest,
+    DropNewest,
+    Error,
+}
+
+/// Limits on the rate at which a command lane invokes its event handler. Any unspecified fields will
+/// take their default values.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitSpec {
+    /// The minimum interval between commands, in milliseconds.
+    pub min_interval_ms: Option<u64>,
+    /// The maximum number of commands that will be held back.
+    pub max_backlog: Option<usize>,
+    /// The action to take when the backlog is full.
+    pub overflow: Option<OverflowKind>,
+}
+
+/// Attribute consumer to recognize the rate limits of command lanes.
+struct RateLimitConsumer;
+
+impl NestedMetaConsumer<RateLimitSpec> for RateLimitConsumer {
+    fn try_consume(&self, meta: &syn::NestedMeta) -> Result<Option<RateLimitSpec>, syn::Error> {
+        match meta {
+            syn::NestedMeta::Meta(syn::Meta::List(meta_list))
+                if meta_list.path.is_ident(RATE_LIMIT_TAG) =>
+            {
+                let bad_limit = || syn::Error::new_spanned(meta, INVALID_RATE_LIMIT);
+                let mut spec = RateLimitSpec::default();
+                for nested in &meta_list.nested {
+                    let name_value = match nested {
+                        NestedMeta::Meta(syn::Meta::NameValue(name_value)) => name_value,
+                        _ => return Err(bad_limit()),
+                    };
+                    match &name_value.lit {
+                        syn::Lit::Int(n)
+                            if name_value.path.is_ident(MIN_INTERVAL_TAG)
+                                && spec.min_interval_ms.is_none() =>
+                        {
+                            spec.min_interval_ms = Some(n.base10_parse()?);
+                        }
+                        syn::Lit::Int(n)
+                            if name_value.path.is_ident(MAX_BACKLOG_TAG)
+                                && spec.max_backlog.is_none() =>
+                        {
+                            match n.base10_parse()? {
+                                0 => return Err(bad_limit()),
+                                n => spec.max_backlog = Some(n),
+                            }
+                        }
+                        syn::Lit::Str(s)
+                            if name_value.path.is_ident(OVERFLOW_TAG)
+                                && spec.overflow.is_none() =>
+                        {
+                            let overflow = match s.value().as_str() {
+                                "drop_oldest" => OverflowKind::DropOldest,
+                                "drop_newest" => OverflowKind::DropNewest,
+                                "error" => OverflowKind::Error,
+                                _ => return Err(bad_limit()),
+                            };
+                            spec.overflow = Some(overflow);
+                        }
+                        _ => return Err(bad_limit()),
+                    }
+                }
+                Ok(Some(spec))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Attribute consumer to recognize the time-to-live of value and map lanes.
+struct TtlConsumer;
+
+impl NestedMetaConsumer<u64> for TtlConsumer {
+    fn try_consume(&self, meta: &syn::NestedMeta) -> Result<Option<u64>, syn::Error> {
+        match meta {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(name_value))
+                if name_value.path.is_ident(TTL_TAG) =>
+            {
+                match &name_value.lit {
+                    syn::Lit::Int(n) => match n.base10_parse()? {
+                        0 => Err(syn::Error::new_spanned(meta, INVALID_TTL)),
+                        n => Ok(Some(n)),
+                    },
+                    _ => Err(syn::Error::new_spanned(meta, INVALID_TTL)),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Attribute consumer to recognize the previous names of agent items.
+struct RenamedFromConsumer;
+
+impl NestedMetaConsumer<String> for RenamedFromConsumer {
+    fn try_consume(&self, meta: &syn::NestedMeta) -> Result<Option<String>, syn::Error> {
+        match meta {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(name_value))
+                if name_value.path.is_ident(RENAMED_FROM_TAG) =>
+            {
+                match &name_value.lit {
+                    syn::Lit::Str(s) if !s.value().is_empty() => Ok(Some(s.value())),
+                    _ => Err(syn::Error::new_spanned(meta, INVALID_PREVIOUS_NAME)),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Attribute consumer to recognize the deprecated aliases of lanes.
+struct AliasConsumer;
+
+impl NestedMetaConsumer<String> for AliasConsumer {
+    fn try_consume(&self, meta: &syn::NestedMeta) -> Result<Option<String>, syn::Error> {
+        match meta {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(name_value))
+                if name_value.path.is_ident(ALIAS_TAG) =>
+            {
+                match &name_value.lit {
+                    syn::Lit::Str(s) if !s.value().is_empty() => Ok(Some(s.value())),
+                    _ => Err(syn::Error::new_spanned(meta, INVALID_ALIAS)),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
 }
 
 impl NestedMetaConsumer<TransientFlag> for TransientFlagConsumer {
@@ -64,10 +227,37 @@ impl NestedMetaConsumer<TransientFlag> for TransientFlagConsumer {
     }
 }
 
+impl NestedMetaConsumer<TrackOriginFlag> for TrackOriginFlagConsumer {
+    fn try_consume(&self, meta: &syn::NestedMeta) -> Result<Option<TrackOriginFlag>, syn::Error> {
+        match meta {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) => match path.segments.first() {
+                Some(seg) => {
+                    if seg.ident == TRACK_ORIGIN_ATTR_NAME {
+                        if path.segments.len() == 1 && seg.arguments.is_empty() {
+                            Ok(Some(TrackOriginFlag))
+                        } else {
+                            Err(syn::Error::new_spanned(meta, INVALID_FIELD_ATTR))
+                        }
+                    } else {
+                        Ok(None)
+                    }
+                }
+                _ => Ok(None),
+            },
+            _ => Ok(None),
+        }
+    }
+}
+
 pub fn make_item_attr_consumer() -> impl NestedMetaConsumer<ItemAttr> {
     let trans_consumer = NameTransformConsumer::new(RENAME_TAG, CONV_TAG);
     hlist![
         TransientFlagConsumer.map(|_| ItemAttr::Transient),
+        TrackOriginFlagConsumer.map(|_| ItemAttr::TrackOrigin),
+        RenamedFromConsumer.map(ItemAttr::RenamedFrom),
+        AliasConsumer.map(ItemAttr::Alias),
+        RateLimitConsumer.map(ItemAttr::RateLimit),
+        TtlConsumer.map(ItemAttr::Ttl),
         trans_consumer.map(ItemAttr::Transform)
     ]
 }
@@ -79,6 +269,14 @@ pub struct ItemModifiers {
     pub transform: NameTransform,
     /// Flags that are set for the item.
     pub flags: ItemFlags,
+    /// Names that the item previously had.
+    pub previous_names: Vec<String>,
+    /// Deprecated names by which the item can still be addressed.
+    pub aliases: Vec<String>,
+    /// Limits on the rate at which a command lane invokes its event handler.
+    pub rate_limit: Option<RateLimitSpec>,
+    /// The time-to-live, in milliseconds, for the value or entries of a lane.
+    pub ttl_ms: Option<u64>,
 }
 
 /// Attempt to create an [`ItemModifiers`] from the [`ItemAttr`] records extracted from the attributes
@@ -94,13 +292,30 @@ pub fn combine_item_attrs(
             let mut errors = Errors::empty();
             match attr {
                 ItemAttr::Transient => modifiers.flags.insert(ItemFlags::TRANSIENT),
+                ItemAttr::TrackOrigin => modifiers.flags.insert(ItemFlags::TRACK_ORIGIN),
+                ItemAttr::RenamedFrom(name) => modifiers.previous_names.push(name),
+                ItemAttr::Alias(alias) => modifiers.aliases.push(alias),
+                ItemAttr::RateLimit(spec) => {
+                    if modifiers.rate_limit.is_some() {
+                        errors.push(syn::Error::new_spanned(field, DUPLICATE_RATE_LIMIT));
+                    } else {
+                        modifiers.rate_limit = Some(spec);
+                    }
+                }
+                ItemAttr::Ttl(ttl_ms) => {
+                    if modifiers.ttl_ms.is_some() {
+                        errors.push(syn::Error::new_spanned(field, DUPLICATE_TTL));
+                    } else {
+                        modifiers.ttl_ms = Some(ttl_ms);
+                    }
+                }
                 ItemAttr::Transform(t) => {
                     if let Err(e) = modifiers.transform.try_add(field, t) {
                         errors.push(e);
                     }
                 }
             }
-            Validation::valid(modifiers)
+            Validation::Validated(modifiers, errors)
         },
     )
 }
@@ -24,8 +24,8 @@ pub use attributes::{combine_agent_attrs, make_agent_attr_consumer};
 pub use model::{validate_input, ItemModel, ItemSpec, LanesModel};
 
 use self::{
-    attributes::AgentModifiers,
-    model::{HttpLaneModel, HttpLaneSpec, ItemKind, WarpLaneModel, WarpLaneSpec},
+    attributes::{AgentModifiers, OverflowKind, RateLimitSpec},
+    model::{HttpLaneModel, HttpLaneSpec, ItemFlags, ItemKind, WarpLaneModel, WarpLaneSpec},
 };
 
 pub struct DeriveAgentLaneModel<'a> {
@@ -57,16 +57,21 @@ impl<'a> ToTokens for DeriveAgentLaneModel<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let DeriveAgentLaneModel {
             ref root,
-            model: LanesModel {
-                agent_type,
-                ref lanes,
-            },
+            model:
+                LanesModel {
+                    agent_type: agent_name,
+                    generics,
+                    ref lanes,
+                },
         } = *self;
 
+        let (impl_gen, type_gen, where_clause) = generics.split_for_impl();
+        let agent_type: syn::Type = parse_quote!(#agent_name #type_gen);
+
         let item_models = lanes
             .iter()
             .zip(0u64..)
-            .map(|(model, i)| OrdinalItemModel::new(agent_type, i, model.clone()))
+            .map(|(model, i)| OrdinalItemModel::new(&agent_type, i, model.clone()))
             .collect::<Vec<_>>();
 
         let initializers = item_models
@@ -142,6 +147,7 @@ impl<'a> ToTokens for DeriveAgentLaneModel<'a> {
 
         let item_specs = item_models
             .iter()
+            .filter(|model| !model.model.kind.is_dynamic())
             .map(|model| LaneSpecInsert(model.ordinal, model.model.clone()))
             .map(|insert| insert.into_tokens(root));
 
@@ -165,9 +171,59 @@ impl<'a> ToTokens for DeriveAgentLaneModel<'a> {
         let sync_match_blocks = warp_lane_models
             .iter()
             .cloned()
-            .map(|model| SyncHandlerMatch::new(root, model))
+            .map(|model| SyncHandlerMatch::new(root, model, SyncMode::Full))
             .map(SyncHandlerMatch::into_tokens);
 
+        // Only map lanes support syncing from a previous version so the default implementation
+        // of `on_sync_since` is sufficient if there are none.
+        let sync_since_match_blocks = warp_lane_models
+            .iter()
+            .filter(|model| {
+                matches!(
+                    model.model.kind,
+                    WarpLaneSpec::Map(_, _) | WarpLaneSpec::DynamicMap(_, _)
+                )
+            })
+            .cloned()
+            .map(|model| SyncHandlerMatch::new(root, model, SyncMode::Since))
+            .map(SyncHandlerMatch::into_tokens)
+            .collect::<Vec<_>>();
+
+        let on_sync_since = if sync_since_match_blocks.is_empty() {
+            None
+        } else {
+            Some(quote! {
+                fn on_sync_since(&self, lane: &str, id: #root::reexport::uuid::Uuid, since: #root::model::SyncVersion) -> Option<Self::OnSyncHandler> {
+                    match lane {
+                        #(#sync_since_match_blocks,)*
+                        _ => self.on_sync(lane, id),
+                    }
+                }
+            })
+        };
+
+        // Likewise, only ordered map lanes support syncing a range of keys.
+        let sync_range_match_blocks = warp_lane_models
+            .iter()
+            .filter(|model| matches!(model.model.kind, WarpLaneSpec::OrderedMap(_, _)))
+            .cloned()
+            .map(|model| SyncHandlerMatch::new(root, model, SyncMode::Range))
+            .map(SyncHandlerMatch::into_tokens)
+            .collect::<Vec<_>>();
+
+        let on_sync_range = if sync_range_match_blocks.is_empty() {
+            None
+        } else {
+            Some(quote! {
+                fn on_sync_range(&self, lane: &str, id: #root::reexport::uuid::Uuid, range: #root::model::KeyRange<#root::reexport::bytes::Bytes>) -> Option<Self::OnSyncHandler> {
+                    match lane {
+                        #(#sync_range_match_blocks,)*
+                        _ => self.on_sync(lane, id),
+                    }
+                }
+            })
+        };
+
         let write_match_blocks = item_models
             .iter()
             .filter(|m| m.category() != ItemCategory::Http)
@@ -187,10 +243,38 @@ impl<'a> ToTokens for DeriveAgentLaneModel<'a> {
             .map(MapItemInitMatch::new)
             .map(|model| model.into_tokens(root));
 
+        // Only lanes with a time-to-live have timers to start so the default implementation of
+        // `start_expiry_timers` is sufficient if there are none.
+        let expiry_starts = item_models
+            .iter()
+            .filter(|model| model.model.ttl_ms.is_some())
+            .map(|model| {
+                let OrdinalItemModel {
+                    agent_type,
+                    model: ItemModel { name, .. },
+                    ..
+                } = model;
+                quote!(self.#name.start_expiry(|agent: &#agent_type| &agent.#name, action_context))
+            })
+            .collect::<Vec<_>>();
+
+        let start_expiry_timers = if expiry_starts.is_empty() {
+            None
+        } else {
+            Some(quote! {
+                fn start_expiry_timers(&self, action_context: &mut #root::event_handler::ActionContext<Self>)
+                where
+                    Self: 'static,
+                {
+                    #(#expiry_starts;)*
+                }
+            })
+        };
+
         tokens.append_all(quote! {
 
             #[automatically_derived]
-            impl ::core::default::Default for #agent_type {
+            impl #impl_gen ::core::default::Default for #agent_type #where_clause {
                 fn default() -> Self {
                     Self {
                         #(#initializers),*
@@ -199,7 +283,7 @@ impl<'a> ToTokens for DeriveAgentLaneModel<'a> {
             }
 
             #[automatically_derived]
-            impl #root::agent_model::AgentSpec for #agent_type {
+            impl #impl_gen #root::agent_model::AgentSpec for #agent_type #where_clause {
                 type ValCommandHandler = #value_handler;
 
                 type MapCommandHandler = #map_handler;
@@ -239,6 +323,12 @@ impl<'a> ToTokens for DeriveAgentLaneModel<'a> {
                     }
                 }
 
+                #on_sync_since
+
+                #on_sync_range
+
+                #start_expiry_timers
+
                 fn on_http_request(
                     &self,
                     lane: &str,
@@ -290,21 +380,21 @@ impl<'a> ToTokens for DeriveAgentLaneModel<'a> {
 
 #[derive(Clone)]
 struct OrdinalItemModel<'a> {
-    agent_name: &'a Ident,
+    agent_type: &'a syn::Type,
     ordinal: u64,
     model: ItemModel<'a>,
 }
 
 #[derive(Clone)]
 struct OrdinalWarpLaneModel<'a> {
-    agent_name: &'a Ident,
+    agent_type: &'a syn::Type,
     lane_ordinal: usize,
     model: WarpLaneModel<'a>,
 }
 
 #[derive(Clone)]
 struct OrdinalHttpLaneModel<'a> {
-    agent_name: &'a Ident,
+    agent_type: &'a syn::Type,
     lane_ordinal: usize,
     model: HttpLaneModel<'a>,
 }
@@ -316,12 +406,12 @@ impl<'a> OrdinalWarpLaneModel<'a> {
             .cloned()
             .filter_map(
                 |OrdinalItemModel {
-                     agent_name, model, ..
-                 }| model.lane().map(move |lane_model| (agent_name, lane_model)),
+                     agent_type, model, ..
+                 }| model.lane().map(move |lane_model| (agent_type, lane_model)),
             )
             .enumerate()
-            .map(|(lane_ordinal, (agent_name, model))| OrdinalWarpLaneModel {
-                agent_name,
+            .map(|(lane_ordinal, (agent_type, model))| OrdinalWarpLaneModel {
+                agent_type,
                 lane_ordinal,
                 model,
             })
@@ -332,9 +422,12 @@ impl<'a> OrdinalWarpLaneModel<'a> {
         matches!(
             &self.model.kind,
             WarpLaneSpec::Map(_, _)
+                | WarpLaneSpec::OrderedMap(_, _)
                 | WarpLaneSpec::DemandMap(_, _)
                 | WarpLaneSpec::JoinValue(_, _)
                 | WarpLaneSpec::JoinMap(_, _, _)
+                | WarpLaneSpec::History(_)
+                | WarpLaneSpec::DynamicMap(_, _)
         )
     }
 }
@@ -346,12 +439,12 @@ impl<'a> OrdinalHttpLaneModel<'a> {
             .cloned()
             .filter_map(
                 |OrdinalItemModel {
-                     agent_name, model, ..
-                 }| model.http().map(move |lane_model| (agent_name, lane_model)),
+                     agent_type, model, ..
+                 }| model.http().map(move |lane_model| (agent_type, lane_model)),
             )
             .enumerate()
-            .map(|(lane_ordinal, (agent_name, model))| OrdinalHttpLaneModel {
-                agent_name,
+            .map(|(lane_ordinal, (agent_type, model))| OrdinalHttpLaneModel {
+                agent_type,
                 lane_ordinal,
                 model,
             })
@@ -367,9 +460,9 @@ enum ItemCategory {
 }
 
 impl<'a> OrdinalItemModel<'a> {
-    fn new(agent_name: &'a Ident, ordinal: u64, model: ItemModel<'a>) -> Self {
+    fn new(agent_type: &'a syn::Type, ordinal: u64, model: ItemModel<'a>) -> Self {
         OrdinalItemModel {
-            agent_name,
+            agent_type,
             ordinal,
             model,
         }
@@ -378,9 +471,12 @@ impl<'a> OrdinalItemModel<'a> {
     fn category(&self) -> ItemCategory {
         match &self.model.kind {
             ItemSpec::Map(_, _, _)
+            | ItemSpec::OrderedMap(_, _)
             | ItemSpec::JoinValue(_, _)
             | ItemSpec::JoinMap(_, _, _)
-            | ItemSpec::DemandMap(_, _) => ItemCategory::MapLike,
+            | ItemSpec::DemandMap(_, _)
+            | ItemSpec::History(_)
+            | ItemSpec::DynamicMap(_, _) => ItemCategory::MapLike,
             ItemSpec::Http(_) => ItemCategory::Http,
             _ => ItemCategory::ValueLike,
         }
@@ -393,13 +489,28 @@ impl<'a> FieldInitializer<'a> {
     fn into_tokens(self, root: &syn::Path) -> impl ToTokens {
         let FieldInitializer(OrdinalItemModel {
             ordinal,
-            model: ItemModel { name, kind, .. },
+            model:
+                ItemModel {
+                    name,
+                    kind,
+                    rate_limit,
+                    ttl_ms,
+                    ..
+                },
             ..
         }) = self;
 
         match kind {
-            ItemSpec::Command(_) => {
-                quote!(#name: #root::lanes::CommandLane::new(#ordinal))
+            ItemSpec::Command(_) => match rate_limit {
+                Some(spec) => {
+                    let limits = rate_limit_tokens(root, &spec);
+                    quote!(#name: #root::lanes::CommandLane::with_limits(#ordinal, #limits))
+                }
+                None => quote!(#name: #root::lanes::CommandLane::new(#ordinal)),
+            },
+            ItemSpec::BatchCommand(_) => {
+                let limits = rate_limit_tokens(root, &rate_limit.unwrap_or_default());
+                quote!(#name: #root::lanes::CommandLane::batched(#ordinal, #limits))
             }
             ItemSpec::Demand(_) => {
                 quote!(#name: #root::lanes::DemandLane::new(#ordinal))
@@ -410,18 +521,37 @@ impl<'a> FieldInitializer<'a> {
             ItemSpec::Supply(_) => {
                 quote!(#name: #root::lanes::SupplyLane::new(#ordinal))
             }
-            ItemSpec::Value(ItemKind::Lane, _) => {
-                quote!(#name: #root::lanes::ValueLane::new(#ordinal, ::core::default::Default::default()))
+            ItemSpec::History(_) => {
+                quote!(#name: #root::lanes::HistoryLane::new(#ordinal))
+            }
+            ItemSpec::Stats => {
+                quote!(#name: #root::lanes::StatsLane::new(#ordinal))
             }
+            ItemSpec::Value(ItemKind::Lane, _) => match ttl_ms {
+                Some(ms) => {
+                    quote!(#name: #root::lanes::ValueLane::with_ttl(#ordinal, ::core::default::Default::default(), ::core::time::Duration::from_millis(#ms)))
+                }
+                None => {
+                    quote!(#name: #root::lanes::ValueLane::new(#ordinal, ::core::default::Default::default()))
+                }
+            },
             ItemSpec::Value(ItemKind::Store, _) => {
                 quote!(#name: #root::stores::ValueStore::new(#ordinal, ::core::default::Default::default()))
             }
-            ItemSpec::Map(ItemKind::Lane, _, _) => {
-                quote!(#name: #root::lanes::MapLane::new(#ordinal, ::core::default::Default::default()))
-            }
+            ItemSpec::Map(ItemKind::Lane, _, _) => match ttl_ms {
+                Some(ms) => {
+                    quote!(#name: #root::lanes::MapLane::with_ttl(#ordinal, ::core::default::Default::default(), ::core::time::Duration::from_millis(#ms)))
+                }
+                None => {
+                    quote!(#name: #root::lanes::MapLane::new(#ordinal, ::core::default::Default::default()))
+                }
+            },
             ItemSpec::Map(ItemKind::Store, _, _) => {
                 quote!(#name: #root::stores::MapStore::new(#ordinal, ::core::default::Default::default()))
             }
+            ItemSpec::OrderedMap(_, _) => {
+                quote!(#name: #root::lanes::OrderedMapLane::new(#ordinal, ::core::default::Default::default()))
+            }
             ItemSpec::JoinValue(_, _) => {
                 quote!(#name: #root::lanes::JoinValueLane::new(#ordinal))
             }
@@ -431,6 +561,45 @@ impl<'a> FieldInitializer<'a> {
             ItemSpec::Http { .. } => {
                 quote!(#name: #root::lanes::HttpLane::new(#ordinal))
             }
+            ItemSpec::DynamicValue(_) | ItemSpec::DynamicMap(_, _) => {
+                quote!(#name: ::core::default::Default::default())
+            }
+        }
+    }
+}
+
+/// Expression that constructs the limits for a command lane, using the defaults for any unspecified fields.
+fn rate_limit_tokens(root: &syn::Path, spec: &RateLimitSpec) -> TokenStream {
+    let RateLimitSpec {
+        min_interval_ms,
+        max_backlog,
+        overflow,
+    } = spec;
+    // A struct update is only required if some of the fields were not specified.
+    let defaults = if min_interval_ms.is_none() || max_backlog.is_none() || overflow.is_none() {
+        Some(quote!(..::core::default::Default::default()))
+    } else {
+        None
+    };
+    let min_interval =
+        min_interval_ms.map(|ms| quote!(min_interval: ::core::time::Duration::from_millis(#ms),));
+    let max_backlog = max_backlog.map(|n| {
+        quote!(max_backlog: ::core::num::NonZeroUsize::new(#n).expect("Validated by the macro."),)
+    });
+    let overflow = overflow.map(|kind| {
+        let variant = match kind {
+            OverflowKind::DropOldest => quote!(DropOldest),
+            OverflowKind::DropNewest => quote!(DropNewest),
+            OverflowKind::Error => quote!(Error),
+        };
+        quote!(overflow: #root::lanes::OverflowPolicy::#variant,)
+    });
+    quote! {
+        #root::lanes::CommandLaneLimits {
+            #min_interval
+            #max_backlog
+            #overflow
+            #defaults
         }
     }
 }
@@ -444,26 +613,40 @@ struct HttpHandlerType<'a>(OrdinalHttpLaneModel<'a>);
 impl<'a> HandlerType<'a> {
     fn into_tokens(self, root: &syn::Path) -> impl ToTokens {
         let HandlerType(OrdinalWarpLaneModel {
-            agent_name,
+            agent_type,
             model: WarpLaneModel { kind, .. },
             ..
         }) = self;
 
         match kind {
             WarpLaneSpec::Command(t) => {
-                quote!(#root::lanes::command::DecodeAndCommand<#agent_name, #t>)
+                quote!(#root::lanes::command::DecodeAndCommand<#agent_type, #t>)
+            }
+            WarpLaneSpec::BatchCommand(t) => {
+                quote!(#root::lanes::command::DecodeAndBatch<#agent_type, #t>)
             }
             WarpLaneSpec::Value(t) => {
-                quote!(#root::lanes::value::DecodeAndSet<#agent_name, #t>)
+                quote!(#root::lanes::value::DecodeAndSet<#agent_type, #t>)
             }
             WarpLaneSpec::Map(k, v) => {
-                quote!(#root::lanes::map::DecodeAndApply<#agent_name, #k, #v>)
+                quote!(#root::lanes::map::DecodeAndApply<#agent_type, #k, #v>)
+            }
+            WarpLaneSpec::OrderedMap(k, v) => {
+                quote!(#root::lanes::ordered_map::DecodeAndApply<#agent_type, #k, #v>)
+            }
+            WarpLaneSpec::DynamicValue(t) => {
+                quote!(#root::lanes::dynamic::DynamicDecodeAndSet<#agent_type, #t>)
+            }
+            WarpLaneSpec::DynamicMap(k, v) => {
+                quote!(#root::lanes::dynamic::DynamicDecodeAndApply<#agent_type, #k, #v>)
             }
             WarpLaneSpec::Demand(_)
             | WarpLaneSpec::DemandMap(_, _)
             | WarpLaneSpec::JoinValue(_, _)
             | WarpLaneSpec::JoinMap(_, _, _)
-            | WarpLaneSpec::Supply(_) => {
+            | WarpLaneSpec::Supply(_)
+            | WarpLaneSpec::History(_)
+            | WarpLaneSpec::Stats => {
                 quote!(#root::event_handler::UnitHandler)
             }
         }
@@ -473,33 +656,50 @@ impl<'a> HandlerType<'a> {
 impl<'a> SyncHandlerType<'a> {
     fn into_tokens(self, root: &syn::Path) -> impl ToTokens {
         let SyncHandlerType(OrdinalWarpLaneModel {
-            agent_name,
+            agent_type,
             model: WarpLaneModel { kind, .. },
             ..
         }) = self;
 
         match kind {
-            WarpLaneSpec::Command(_) => quote!(#root::event_handler::UnitHandler), //TODO Do this properly later.
+            WarpLaneSpec::Command(_) | WarpLaneSpec::BatchCommand(_) => {
+                quote!(#root::event_handler::UnitHandler)
+            } //TODO Do this properly later.
             WarpLaneSpec::Demand(t) => {
-                quote!(#root::lanes::demand::DemandLaneSync<#agent_name, #t>)
+                quote!(#root::lanes::demand::DemandLaneSync<#agent_type, #t>)
             }
             WarpLaneSpec::DemandMap(k, v) => {
-                quote!(#root::lanes::demand_map::DemandMapLaneSync<#agent_name, #k, #v>)
+                quote!(#root::lanes::demand_map::DemandMapLaneSync<#agent_type, #k, #v>)
             }
             WarpLaneSpec::Value(t) => {
-                quote!(#root::lanes::value::ValueLaneSync<#agent_name, #t>)
+                quote!(#root::lanes::value::ValueLaneSync<#agent_type, #t>)
             }
             WarpLaneSpec::Map(k, v) => {
-                quote!(#root::lanes::map::MapLaneSync<#agent_name, #k, #v>)
+                quote!(#root::lanes::map::MapLaneSync<#agent_type, #k, #v>)
+            }
+            WarpLaneSpec::OrderedMap(k, v) => {
+                quote!(#root::lanes::ordered_map::OrderedMapLaneSync<#agent_type, #k, #v>)
             }
             WarpLaneSpec::JoinValue(k, v) => {
-                quote!(#root::lanes::join_value::JoinValueLaneSync<#agent_name, #k, #v>)
+                quote!(#root::lanes::join_value::JoinValueLaneSync<#agent_type, #k, #v>)
             }
             WarpLaneSpec::JoinMap(l, k, v) => {
-                quote!(#root::lanes::join_map::JoinMapLaneSync<#agent_name, #l, #k, #v>)
+                quote!(#root::lanes::join_map::JoinMapLaneSync<#agent_type, #l, #k, #v>)
             }
             WarpLaneSpec::Supply(t) => {
-                quote!(#root::lanes::supply::SupplyLaneSync<#agent_name, #t>)
+                quote!(#root::lanes::supply::SupplyLaneSync<#agent_type, #t>)
+            }
+            WarpLaneSpec::History(t) => {
+                quote!(#root::lanes::history::HistoryLaneSync<#agent_type, #t>)
+            }
+            WarpLaneSpec::Stats => {
+                quote!(#root::lanes::stats::StatsLaneSync<#agent_type>)
+            }
+            WarpLaneSpec::DynamicValue(t) => {
+                quote!(#root::lanes::dynamic::DynamicValueLaneSync<#agent_type, #t>)
+            }
+            WarpLaneSpec::DynamicMap(k, v) => {
+                quote!(#root::lanes::dynamic::DynamicMapLaneSync<#agent_type, #k, #v>)
             }
         }
     }
@@ -508,7 +708,7 @@ impl<'a> SyncHandlerType<'a> {
 impl<'a> HttpHandlerType<'a> {
     fn into_tokens(self, root: &syn::Path) -> impl ToTokens {
         let HttpHandlerType(OrdinalHttpLaneModel {
-            agent_name,
+            agent_type,
             model: HttpLaneModel { kind, .. },
             ..
         }) = self;
@@ -519,7 +719,7 @@ impl<'a> HttpHandlerType<'a> {
             put,
             codec,
         } = kind;
-        quote!(#root::lanes::http::HttpLaneAccept<#agent_name, #get, #post, #put, #codec>)
+        quote!(#root::lanes::http::HttpLaneAccept<#agent_type, #get, #post, #put, #codec>)
     }
 }
 
@@ -540,33 +740,47 @@ impl<'a> WarpLaneHandlerMatch<'a> {
         let WarpLaneHandlerMatch {
             group_ordinal,
             model: OrdinalWarpLaneModel {
-                agent_name, model, ..
+                agent_type, model, ..
             },
         } = self;
-        let name_lit = model.literal();
+        let pattern = model.pattern();
         let WarpLaneModel { name, kind, .. } = model;
         let handler_base: syn::Expr = parse_quote!(handler);
         let coprod_con = coproduct_constructor(root, handler_base, group_ordinal);
         let lane_handler_expr = match kind {
             WarpLaneSpec::Command(ty) => {
-                quote!(#root::lanes::command::decode_and_command::<#agent_name, #ty>(body, |agent: &#agent_name| &agent.#name))
+                quote!(#root::lanes::command::decode_and_command::<#agent_type, #ty>(body, |agent: &#agent_type| &agent.#name))
+            }
+            WarpLaneSpec::BatchCommand(ty) => {
+                quote!(#root::lanes::command::decode_and_batch::<#agent_type, #ty>(body, |agent: &#agent_type| &agent.#name))
             }
             WarpLaneSpec::Value(ty) => {
-                quote!(#root::lanes::value::decode_and_set::<#agent_name, #ty>(body, |agent: &#agent_name| &agent.#name))
+                quote!(#root::lanes::value::decode_and_set::<#agent_type, #ty>(body, |agent: &#agent_type| &agent.#name))
             }
             WarpLaneSpec::Map(k, v) => {
-                quote!(#root::lanes::map::decode_and_apply::<#agent_name, #k, #v>(body, |agent: &#agent_name| &agent.#name))
+                quote!(#root::lanes::map::decode_and_apply::<#agent_type, #k, #v>(body, |agent: &#agent_type| &agent.#name))
+            }
+            WarpLaneSpec::OrderedMap(k, v) => {
+                quote!(#root::lanes::ordered_map::decode_and_apply::<#agent_type, #k, #v>(body, |agent: &#agent_type| &agent.#name))
+            }
+            WarpLaneSpec::DynamicValue(ty) => {
+                quote!(#root::lanes::dynamic::decode_and_set::<#agent_type, #ty>(body, |agent: &#agent_type| &agent.#name, lane))
+            }
+            WarpLaneSpec::DynamicMap(k, v) => {
+                quote!(#root::lanes::dynamic::decode_and_apply::<#agent_type, #k, #v>(body, |agent: &#agent_type| &agent.#name, lane))
             }
             WarpLaneSpec::Demand(_)
             | WarpLaneSpec::DemandMap(_, _)
             | WarpLaneSpec::JoinValue(_, _)
             | WarpLaneSpec::JoinMap(_, _, _)
-            | WarpLaneSpec::Supply(_) => {
+            | WarpLaneSpec::Supply(_)
+            | WarpLaneSpec::History(_)
+            | WarpLaneSpec::Stats => {
                 quote!(#root::event_handler::UnitHandler::default())
             }
         };
         quote! {
-            #name_lit => {
+            #pattern => {
                 let handler = #lane_handler_expr;
                 ::core::option::Option::Some(#coprod_con)
             }
@@ -595,7 +809,7 @@ impl<'a> HttpLaneHandlerMatch<'a> {
         let HttpLaneHandlerMatch {
             model:
                 OrdinalHttpLaneModel {
-                    agent_name,
+                    agent_type,
                     lane_ordinal,
                     model,
                 },
@@ -610,7 +824,7 @@ impl<'a> HttpLaneHandlerMatch<'a> {
             put,
             codec,
         } = kind;
-        let lane_handler_expr = quote!(#root::lanes::http::HttpLaneAccept::<#agent_name, #get, #post, #put, #codec>::new(|agent: &#agent_name| &agent.#name, request));
+        let lane_handler_expr = quote!(#root::lanes::http::HttpLaneAccept::<#agent_type, #get, #post, #put, #codec>::new(|agent: &#agent_type| &agent.#name, request));
         quote! {
             #name_lit => {
                 let handler = #lane_handler_expr;
@@ -620,14 +834,25 @@ impl<'a> HttpLaneHandlerMatch<'a> {
     }
 }
 
+/// The kinds of sync request that can be made to a lane.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyncMode {
+    Full,
+    // Sync from the version held by the remote (where the lane supports it).
+    Since,
+    // Sync the entries with keys in a range (where the lane supports it).
+    Range,
+}
+
 struct SyncHandlerMatch<'a> {
     root: &'a syn::Path,
     model: OrdinalWarpLaneModel<'a>,
+    mode: SyncMode,
 }
 
 impl<'a> SyncHandlerMatch<'a> {
-    fn new(root: &'a syn::Path, model: OrdinalWarpLaneModel<'a>) -> Self {
-        SyncHandlerMatch { root, model }
+    fn new(root: &'a syn::Path, model: OrdinalWarpLaneModel<'a>, mode: SyncMode) -> Self {
+        SyncHandlerMatch { root, model, mode }
     }
 }
 
@@ -637,42 +862,69 @@ impl<'a> SyncHandlerMatch<'a> {
             root,
             model:
                 OrdinalWarpLaneModel {
-                    agent_name,
+                    agent_type,
                     lane_ordinal: ord,
                     model,
                     ..
                 },
+            mode,
         } = self;
-        let name_lit = model.literal();
+        let pattern = model.pattern();
         let WarpLaneModel { name, kind, .. } = model;
         let handler_base: syn::Expr = parse_quote!(handler);
         let coprod_con = coproduct_constructor(root, handler_base, ord);
         let sync_handler_expr = match kind {
-            WarpLaneSpec::Command(_) => quote!(#root::event_handler::UnitHandler::default()),
+            WarpLaneSpec::Command(_) | WarpLaneSpec::BatchCommand(_) => {
+                quote!(#root::event_handler::UnitHandler::default())
+            }
             WarpLaneSpec::Demand(ty) => {
-                quote!(#root::lanes::demand::DemandLaneSync::<#agent_name, #ty>::new(|agent: &#agent_name| &agent.#name, id))
+                quote!(#root::lanes::demand::DemandLaneSync::<#agent_type, #ty>::new(|agent: &#agent_type| &agent.#name, id))
             }
             WarpLaneSpec::DemandMap(k, v) => {
-                quote!(#root::lanes::demand_map::DemandMapLaneSync::<#agent_name, #k, #v>::new(|agent: &#agent_name| &agent.#name, id))
+                quote!(#root::lanes::demand_map::DemandMapLaneSync::<#agent_type, #k, #v>::new(|agent: &#agent_type| &agent.#name, id))
             }
             WarpLaneSpec::Value(ty) => {
-                quote!(#root::lanes::value::ValueLaneSync::<#agent_name, #ty>::new(|agent: &#agent_name| &agent.#name, id))
+                quote!(#root::lanes::value::ValueLaneSync::<#agent_type, #ty>::new(|agent: &#agent_type| &agent.#name, id))
+            }
+            WarpLaneSpec::Map(k, v) if mode == SyncMode::Since => {
+                quote!(#root::lanes::map::MapLaneSync::<#agent_type, #k, #v>::since(|agent: &#agent_type| &agent.#name, id, since))
             }
             WarpLaneSpec::Map(k, v) => {
-                quote!(#root::lanes::map::MapLaneSync::<#agent_name, #k, #v>::new(|agent: &#agent_name| &agent.#name, id))
+                quote!(#root::lanes::map::MapLaneSync::<#agent_type, #k, #v>::new(|agent: &#agent_type| &agent.#name, id))
+            }
+            WarpLaneSpec::OrderedMap(k, v) if mode == SyncMode::Range => {
+                quote!(#root::lanes::ordered_map::OrderedMapLaneSync::<#agent_type, #k, #v>::range(|agent: &#agent_type| &agent.#name, id, range))
+            }
+            WarpLaneSpec::OrderedMap(k, v) => {
+                quote!(#root::lanes::ordered_map::OrderedMapLaneSync::<#agent_type, #k, #v>::new(|agent: &#agent_type| &agent.#name, id))
             }
             WarpLaneSpec::JoinValue(k, v) => {
-                quote!(#root::lanes::join_value::JoinValueLaneSync::<#agent_name, #k, #v>::new(|agent: &#agent_name| &agent.#name, id))
+                quote!(#root::lanes::join_value::JoinValueLaneSync::<#agent_type, #k, #v>::new(|agent: &#agent_type| &agent.#name, id))
             }
             WarpLaneSpec::JoinMap(l, k, v) => {
-                quote!(#root::lanes::join_map::JoinMapLaneSync::<#agent_name, #l, #k, #v>::new(|agent: &#agent_name| &agent.#name, id))
+                quote!(#root::lanes::join_map::JoinMapLaneSync::<#agent_type, #l, #k, #v>::new(|agent: &#agent_type| &agent.#name, id))
             }
             WarpLaneSpec::Supply(ty) => {
-                quote!(#root::lanes::supply::SupplyLaneSync::<#agent_name, #ty>::new(|agent: &#agent_name| &agent.#name, id))
+                quote!(#root::lanes::supply::SupplyLaneSync::<#agent_type, #ty>::new(|agent: &#agent_type| &agent.#name, id))
+            }
+            WarpLaneSpec::History(ty) => {
+                quote!(#root::lanes::history::HistoryLaneSync::<#agent_type, #ty>::new(|agent: &#agent_type| &agent.#name, id))
+            }
+            WarpLaneSpec::Stats => {
+                quote!(#root::lanes::stats::StatsLaneSync::<#agent_type>::new(|agent: &#agent_type| &agent.#name, id))
+            }
+            WarpLaneSpec::DynamicValue(ty) => {
+                quote!(#root::lanes::dynamic::DynamicValueLaneSync::<#agent_type, #ty>::new(|agent: &#agent_type| &agent.#name, #root::model::Text::new(lane), id))
+            }
+            WarpLaneSpec::DynamicMap(k, v) if mode == SyncMode::Since => {
+                quote!(#root::lanes::dynamic::DynamicMapLaneSync::<#agent_type, #k, #v>::since(|agent: &#agent_type| &agent.#name, #root::model::Text::new(lane), id, since))
+            }
+            WarpLaneSpec::DynamicMap(k, v) => {
+                quote!(#root::lanes::dynamic::DynamicMapLaneSync::<#agent_type, #k, #v>::new(|agent: &#agent_type| &agent.#name, #root::model::Text::new(lane), id))
             }
         };
         quote! {
-            #name_lit => {
+            #pattern => {
                 let handler = #sync_handler_expr;
                 ::core::option::Option::Some(#coprod_con)
             }
@@ -687,6 +939,10 @@ impl<'a> WriteToBufferMatch<'a> {
         let WriteToBufferMatch(model) = self;
         let name_lit = model.external_literal();
         let ItemModel { name, kind, .. } = model;
+        if kind.is_dynamic() {
+            let pattern = model.external_pattern();
+            return quote!(#pattern => self.#name.write_to_buffer(lane, buffer));
+        }
         match kind.item_kind() {
             ItemKind::Lane => {
                 quote!(#name_lit => ::core::option::Option::Some(#root::lanes::LaneItem::write_to_buffer(&self.#name, buffer)))
@@ -699,7 +955,7 @@ impl<'a> WriteToBufferMatch<'a> {
 }
 
 struct ValueItemInitMatch<'a> {
-    agent_name: &'a Ident,
+    agent_type: &'a syn::Type,
     name: &'a Ident,
     name_lit: proc_macro2::Literal,
     kind: ItemKind,
@@ -708,7 +964,7 @@ struct ValueItemInitMatch<'a> {
 impl<'a> ValueItemInitMatch<'a> {
     pub fn new(item: &OrdinalItemModel<'a>) -> Self {
         ValueItemInitMatch {
-            agent_name: item.agent_name,
+            agent_type: item.agent_type,
             name: item.model.name,
             name_lit: item.model.external_literal(),
             kind: item.model.item_kind(),
@@ -719,17 +975,17 @@ impl<'a> ValueItemInitMatch<'a> {
 impl<'a> ValueItemInitMatch<'a> {
     fn into_tokens(self, root: &syn::Path) -> impl ToTokens {
         let ValueItemInitMatch {
-            agent_name,
+            agent_type,
             name,
             name_lit,
             kind,
         } = self;
         match kind {
             ItemKind::Lane => {
-                quote!(#name_lit => ::core::option::Option::Some(::std::boxed::Box::new(#root::agent_model::ValueLaneInitializer::new(|agent: &#agent_name| &agent.#name))))
+                quote!(#name_lit => ::core::option::Option::Some(::std::boxed::Box::new(#root::agent_model::ValueLaneInitializer::new(|agent: &#agent_type| &agent.#name))))
             }
             ItemKind::Store => {
-                quote!(#name_lit => ::core::option::Option::Some(::std::boxed::Box::new(#root::agent_model::ValueStoreInitializer::new(|agent: &#agent_name| &agent.#name))))
+                quote!(#name_lit => ::core::option::Option::Some(::std::boxed::Box::new(#root::agent_model::ValueStoreInitializer::new(|agent: &#agent_type| &agent.#name))))
             }
         }
     }
@@ -738,10 +994,11 @@ impl<'a> ValueItemInitMatch<'a> {
 enum InitKind {
     MapLane,
     MapStore,
+    OrderedMapLane,
 }
 
 struct MapItemInitMatch<'a> {
-    agent_name: &'a Ident,
+    agent_type: &'a syn::Type,
     name: &'a Ident,
     name_lit: proc_macro2::Literal,
     init_kind: InitKind,
@@ -751,10 +1008,11 @@ impl<'a> MapItemInitMatch<'a> {
     pub fn new(item: &OrdinalItemModel<'a>) -> Self {
         let init_kind = match &item.model.kind {
             ItemSpec::Map(ItemKind::Lane, _, _) => InitKind::MapLane,
+            ItemSpec::OrderedMap(_, _) => InitKind::OrderedMapLane,
             _ => InitKind::MapStore,
         };
         MapItemInitMatch {
-            agent_name: item.agent_name,
+            agent_type: item.agent_type,
             name: item.model.name,
             name_lit: item.model.external_literal(),
             init_kind,
@@ -765,17 +1023,20 @@ impl<'a> MapItemInitMatch<'a> {
 impl<'a> MapItemInitMatch<'a> {
     fn into_tokens(self, root: &syn::Path) -> impl ToTokens {
         let MapItemInitMatch {
-            agent_name,
+            agent_type,
             name,
             name_lit,
             init_kind,
         } = self;
         match init_kind {
             InitKind::MapLane => {
-                quote!(#name_lit => ::core::option::Option::Some(::std::boxed::Box::new(#root::agent_model::MapLaneInitializer::new(|agent: &#agent_name| &agent.#name))))
+                quote!(#name_lit => ::core::option::Option::Some(::std::boxed::Box::new(#root::agent_model::MapLaneInitializer::new(|agent: &#agent_type| &agent.#name))))
             }
             InitKind::MapStore => {
-                quote!(#name_lit => ::core::option::Option::Some(::std::boxed::Box::new(#root::agent_model::MapStoreInitializer::new(|agent: &#agent_name| &agent.#name))))
+                quote!(#name_lit => ::core::option::Option::Some(::std::boxed::Box::new(#root::agent_model::MapStoreInitializer::new(|agent: &#agent_type| &agent.#name))))
+            }
+            InitKind::OrderedMapLane => {
+                quote!(#name_lit => ::core::option::Option::Some(::std::boxed::Box::new(#root::agent_model::OrderedMapLaneInitializer::new(|agent: &#agent_type| &agent.#name))))
             }
         }
     }
@@ -787,13 +1048,20 @@ impl<'a> LaneSpecInsert<'a> {
     fn into_tokens(self, root: &syn::Path) -> impl ToTokens {
         let LaneSpecInsert(ordinal, model) = self;
 
-        let flags = if model.is_stateful() {
-            quote!(#root::agent_model::ItemFlags::empty())
-        } else {
-            quote!(#root::agent_model::ItemFlags::TRANSIENT)
+        let flags = match (
+            model.is_stateful(),
+            model.flags.contains(ItemFlags::TRACK_ORIGIN),
+        ) {
+            (true, false) => quote!(#root::agent_model::ItemFlags::empty()),
+            (false, false) => quote!(#root::agent_model::ItemFlags::TRANSIENT),
+            (true, true) => quote!(#root::agent_model::ItemFlags::TRACK_ORIGIN),
+            (false, true) => quote!(
+                #root::agent_model::ItemFlags::TRANSIENT
+                    .union(#root::agent_model::ItemFlags::TRACK_ORIGIN)
+            ),
         };
         let descriptor = match model.kind {
-            ItemSpec::Command(_) => {
+            ItemSpec::Command(_) | ItemSpec::BatchCommand(_) => {
                 quote!(#root::agent_model::ItemDescriptor::WarpLane { kind: #root::agent_model::WarpLaneKind::Command, flags: #flags })
             }
             ItemSpec::Demand(_) => {
@@ -814,6 +1082,9 @@ impl<'a> LaneSpecInsert<'a> {
             ItemSpec::Map(ItemKind::Store, _, _) => {
                 quote!(#root::agent_model::ItemDescriptor::Store { kind: #root::agent_model::StoreKind::Map, flags: #flags })
             }
+            ItemSpec::OrderedMap(_, _) => {
+                quote!(#root::agent_model::ItemDescriptor::WarpLane { kind: #root::agent_model::WarpLaneKind::Map, flags: #flags })
+            }
             ItemSpec::JoinValue(_, _) => {
                 quote!(#root::agent_model::ItemDescriptor::WarpLane { kind: #root::agent_model::WarpLaneKind::JoinValue, flags: #flags })
             }
@@ -824,11 +1095,28 @@ impl<'a> LaneSpecInsert<'a> {
             ItemSpec::Supply(_) => {
                 quote!(#root::agent_model::ItemDescriptor::WarpLane { kind: #root::agent_model::WarpLaneKind::Supply, flags: #flags })
             }
+            ItemSpec::History(_) => {
+                quote!(#root::agent_model::ItemDescriptor::WarpLane { kind: #root::agent_model::WarpLaneKind::Map, flags: #flags })
+            }
+            ItemSpec::Stats | ItemSpec::DynamicValue(_) => {
+                quote!(#root::agent_model::ItemDescriptor::WarpLane { kind: #root::agent_model::WarpLaneKind::Value, flags: #flags })
+            }
+            ItemSpec::DynamicMap(_, _) => {
+                quote!(#root::agent_model::ItemDescriptor::WarpLane { kind: #root::agent_model::WarpLaneKind::Map, flags: #flags })
+            }
         };
         let external_lane_name = model.external_literal();
         let lifecycle_lane_name = model.lifecycle_literal();
-        let spec =
+        let previous_names = &model.previous_names;
+        let aliases = &model.aliases;
+        let mut spec =
             quote!(#root::agent_model::ItemSpec::new(#ordinal, #lifecycle_lane_name, #descriptor));
+        if !previous_names.is_empty() {
+            spec = quote!(#spec.with_previous_names(&[#(#previous_names),*]));
+        }
+        if !aliases.is_empty() {
+            spec = quote!(#spec.with_aliases(&[#(#aliases),*]));
+        }
         quote!(::std::collections::HashMap::insert(&mut lanes, #external_lane_name, #spec))
     }
 }
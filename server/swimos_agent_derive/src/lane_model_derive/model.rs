@@ -13,8 +13,9 @@
 // limitations under the License.
 
 use bitflags::bitflags;
-use proc_macro2::Literal;
-use std::{collections::HashSet, hash::Hash};
+use proc_macro2::{Literal, TokenStream};
+use quote::{quote, ToTokens};
+use std::{borrow::Cow, collections::HashSet, hash::Hash};
 use swimos_macro_utilities::{
     attributes::consume_attributes, NameTransform, TypeLevelNameTransform,
 };
@@ -23,25 +24,33 @@ use swimos_utilities::{
     format::comma_sep,
 };
 use syn::{
-    AngleBracketedGenericArguments, Data, DataStruct, DeriveInput, Field, GenericArgument, Ident,
-    PathArguments, PathSegment, Type, TypePath,
+    AngleBracketedGenericArguments, Data, DataStruct, DeriveInput, Field, GenericArgument,
+    Generics, Ident, PathArguments, PathSegment, Type, TypePath,
 };
 
-use super::attributes::{combine_item_attrs, make_item_attr_consumer, ItemModifiers};
+use super::attributes::{
+    combine_item_attrs, make_item_attr_consumer, ItemModifiers, RateLimitSpec,
+};
 
 /// Model of a struct type for the AgentLaneModel derivation macro.
 pub struct LanesModel<'a> {
     pub agent_type: &'a Ident,
+    pub generics: &'a Generics,
     pub lanes: Vec<ItemModel<'a>>,
 }
 
 impl<'a> LanesModel<'a> {
     /// # Arguments
     /// * `agent_type` - The name of the target of the derive macro.
+    /// * `generics` - The generic parameters of the target (for application to the generated impl blocks).
     /// * `lanes` - Description of each lane in the agent (the name of the corresponding field
     ///    and the lane kind with types).
-    fn new(agent_type: &'a Ident, lanes: Vec<ItemModel<'a>>) -> Self {
-        LanesModel { agent_type, lanes }
+    fn new(agent_type: &'a Ident, generics: &'a Generics, lanes: Vec<ItemModel<'a>>) -> Self {
+        LanesModel {
+            agent_type,
+            generics,
+            lanes,
+        }
     }
 
     /// Apply global transformations to all lanes.
@@ -68,7 +77,8 @@ impl<'a> LanesModel<'a> {
         let LanesModel { lanes, .. } = self;
         let mut names = HashSet::new();
         let mut duplicates = HashSet::new();
-        for lane in lanes.iter() {
+        // The names of dynamic lanes are only known at runtime.
+        for lane in lanes.iter().filter(|lane| !lane.kind.is_dynamic()) {
             let name = lane.transform.transform_cow(lane.name.to_string());
             if names.contains(&name) {
                 duplicates.insert(name);
@@ -76,13 +86,29 @@ impl<'a> LanesModel<'a> {
                 names.insert(name);
             }
         }
-        if duplicates.is_empty() {
-            Ok(())
-        } else {
+        if !duplicates.is_empty() {
             let message = format!(
                 "Agent item names must be unique. Duplicated names: [{}]",
                 comma_sep(&duplicates)
             );
+            return Err(syn::Error::new_spanned(src, message));
+        }
+        let mut clashes = HashSet::new();
+        for alias in lanes.iter().flat_map(|lane| lane.aliases.iter()) {
+            let alias = Cow::Borrowed(alias.as_str());
+            if names.contains(&alias) {
+                clashes.insert(alias);
+            } else {
+                names.insert(alias);
+            }
+        }
+        if clashes.is_empty() {
+            Ok(())
+        } else {
+            let message = format!(
+                "Lane aliases must not clash with the names of other items or aliases. Clashing aliases: [{}]",
+                comma_sep(&clashes)
+            );
             Err(syn::Error::new_spanned(src, message))
         }
     }
@@ -98,27 +124,39 @@ pub enum ItemKind {
 #[derive(Clone, Copy, Debug)]
 pub enum ItemSpec<'a> {
     Command(&'a Type),
+    BatchCommand(&'a Type),
     Demand(&'a Type),
     DemandMap(&'a Type, &'a Type),
     Value(ItemKind, &'a Type),
     Map(ItemKind, &'a Type, &'a Type),
+    OrderedMap(&'a Type, &'a Type),
     Supply(&'a Type),
+    History(&'a Type),
+    Stats,
     JoinValue(&'a Type, &'a Type),
     JoinMap(&'a Type, &'a Type, &'a Type),
     Http(HttpLaneSpec<'a>),
+    DynamicValue(&'a Type),
+    DynamicMap(&'a Type, &'a Type),
 }
 
 impl<'a> ItemSpec<'a> {
     pub fn lane(&self) -> Option<WarpLaneSpec<'a>> {
         match self {
             ItemSpec::Command(t) => Some(WarpLaneSpec::Command(t)),
+            ItemSpec::BatchCommand(t) => Some(WarpLaneSpec::BatchCommand(t)),
             ItemSpec::Demand(t) => Some(WarpLaneSpec::Demand(t)),
             ItemSpec::DemandMap(k, v) => Some(WarpLaneSpec::DemandMap(k, v)),
             ItemSpec::Value(ItemKind::Lane, t) => Some(WarpLaneSpec::Value(t)),
             ItemSpec::Map(ItemKind::Lane, k, v) => Some(WarpLaneSpec::Map(k, v)),
+            ItemSpec::OrderedMap(k, v) => Some(WarpLaneSpec::OrderedMap(k, v)),
             ItemSpec::JoinValue(k, v) => Some(WarpLaneSpec::JoinValue(k, v)),
             ItemSpec::JoinMap(l, k, v) => Some(WarpLaneSpec::JoinMap(l, k, v)),
             ItemSpec::Supply(t) => Some(WarpLaneSpec::Supply(t)),
+            ItemSpec::History(t) => Some(WarpLaneSpec::History(t)),
+            ItemSpec::Stats => Some(WarpLaneSpec::Stats),
+            ItemSpec::DynamicValue(t) => Some(WarpLaneSpec::DynamicValue(t)),
+            ItemSpec::DynamicMap(k, v) => Some(WarpLaneSpec::DynamicMap(k, v)),
             _ => None,
         }
     }
@@ -135,28 +173,45 @@ impl<'a> ItemSpec<'a> {
         match self {
             ItemSpec::Value(k, _) => *k,
             ItemSpec::Map(k, _, _) => *k,
+            ItemSpec::OrderedMap(_, _) => ItemKind::Lane,
             ItemSpec::Command(_) => ItemKind::Lane,
+            ItemSpec::BatchCommand(_) => ItemKind::Lane,
             ItemSpec::JoinValue(_, _) => ItemKind::Lane,
             ItemSpec::JoinMap(_, _, _) => ItemKind::Lane,
             ItemSpec::Demand(_) => ItemKind::Lane,
             ItemSpec::DemandMap(_, _) => ItemKind::Lane,
             ItemSpec::Http(_) => ItemKind::Lane,
             ItemSpec::Supply(_) => ItemKind::Lane,
+            ItemSpec::History(_) => ItemKind::Lane,
+            ItemSpec::Stats => ItemKind::Lane,
+            ItemSpec::DynamicValue(_) => ItemKind::Lane,
+            ItemSpec::DynamicMap(_, _) => ItemKind::Lane,
         }
     }
+
+    /// Whether the item is a collection of lanes that are opened after the agent has started.
+    pub fn is_dynamic(&self) -> bool {
+        matches!(self, ItemSpec::DynamicValue(_) | ItemSpec::DynamicMap(_, _))
+    }
 }
 
 /// The kinds of lane that can be inferred from the type of a field.
 #[derive(Clone, Copy, Debug)]
 pub enum WarpLaneSpec<'a> {
     Command(&'a Type),
+    BatchCommand(&'a Type),
     Demand(&'a Type),
     DemandMap(&'a Type, &'a Type),
     Value(&'a Type),
     Supply(&'a Type),
+    History(&'a Type),
+    Stats,
     Map(&'a Type, &'a Type),
+    OrderedMap(&'a Type, &'a Type),
     JoinValue(&'a Type, &'a Type),
     JoinMap(&'a Type, &'a Type, &'a Type),
+    DynamicValue(&'a Type),
+    DynamicMap(&'a Type, &'a Type),
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -171,7 +226,14 @@ impl<'a> ItemSpec<'a> {
     pub fn is_stateful(&self) -> bool {
         !matches!(
             self,
-            ItemSpec::Command(_) | ItemSpec::Demand(_) | ItemSpec::Supply(_)
+            ItemSpec::Command(_)
+                | ItemSpec::BatchCommand(_)
+                | ItemSpec::Demand(_)
+                | ItemSpec::Supply(_)
+                | ItemSpec::History(_)
+                | ItemSpec::Stats
+                | ItemSpec::DynamicValue(_)
+                | ItemSpec::DynamicMap(_, _)
         )
     }
 }
@@ -182,6 +244,8 @@ bitflags! {
     pub struct ItemFlags: u8 {
         /// The state of the lane should not be persisted.
         const TRANSIENT = 0b01;
+        /// The lane should track the origins of the commands that it receives.
+        const TRACK_ORIGIN = 0b10;
     }
 }
 
@@ -192,6 +256,10 @@ pub struct ItemModel<'a> {
     pub kind: ItemSpec<'a>,
     pub flags: ItemFlags,
     pub transform: NameTransform,
+    pub previous_names: Vec<String>,
+    pub aliases: Vec<String>,
+    pub rate_limit: Option<RateLimitSpec>,
+    pub ttl_ms: Option<u64>,
 }
 
 impl<'a> ItemModel<'a> {
@@ -246,9 +314,20 @@ pub struct HttpLaneModel<'a> {
 }
 
 impl<'a> WarpLaneModel<'a> {
-    /// The name of the lane as a string literal.
-    pub fn literal(&self) -> proc_macro2::Literal {
-        self.transform.transform(|| self.name.to_string())
+    /// The pattern that matches the name of the lane. For a collection of dynamic lanes, this
+    /// is a guard that checks whether the collection contains a lane with the name.
+    pub fn pattern(&self) -> TokenStream {
+        if matches!(
+            self.kind,
+            WarpLaneSpec::DynamicValue(_) | WarpLaneSpec::DynamicMap(_, _)
+        ) {
+            let name = self.name;
+            quote!(_ if self.#name.contains(lane))
+        } else {
+            self.transform
+                .transform(|| self.name.to_string())
+                .into_token_stream()
+        }
     }
 }
 
@@ -269,6 +348,21 @@ impl<'a> ItemModel<'a> {
             kind,
             flags,
             transform,
+            previous_names: vec![],
+            aliases: vec![],
+            rate_limit: None,
+            ttl_ms: None,
+        }
+    }
+
+    /// The pattern that matches the name of the item that is exposed to the runtime (see
+    /// [`WarpLaneModel::pattern`]).
+    pub fn external_pattern(&self) -> TokenStream {
+        if self.kind.is_dynamic() {
+            let name = self.name;
+            quote!(_ if self.#name.contains(lane))
+        } else {
+            self.external_literal().into_token_stream()
         }
     }
 
@@ -297,19 +391,23 @@ impl<'a> HttpLaneModel<'a> {
 
 const NO_LANES: &str = "An agent must have at least one lane.";
 const NOT_A_STRUCT: &str = "Type is not a struct type.";
-const NO_GENERICS: &str = "Generic agents are not yet supported.";
+const NO_LIFETIMES: &str = "Agent types cannot have lifetime parameters.";
 const NOT_LANE_TYPE: &str = "Field is not of a lane type.";
 const NO_TUPLES: &str = "Tuple structs are not supported.";
 const BAD_PARAMS: &str = "Lane generic parameters are invalid.";
+const ALIAS_NOT_LANE: &str = "Only WARP lanes can have aliases.";
+const RATE_LIMIT_NOT_COMMAND: &str = "Only command lanes can have rate limits.";
+const TTL_NOT_VALUE_OR_MAP: &str = "Only value and map lanes can have a time-to-live.";
+const TRACK_ORIGIN_NOT_LANE: &str = "Only WARP lanes can track the origins of commands.";
 
 /// Extract the model of the type from the type definition, collecting any
 /// errors.
 pub fn validate_input(value: &DeriveInput) -> Validation<LanesModel<'_>, Errors<syn::Error>> {
-    if !value.generics.params.is_empty() {
-        return Validation::fail(syn::Error::new_spanned(&value.ident, NO_GENERICS));
+    if let Some(lifetime) = value.generics.lifetimes().next() {
+        return Validation::fail(syn::Error::new_spanned(lifetime, NO_LIFETIMES));
     }
     if let Data::Struct(body) = &value.data {
-        try_from_struct(&value.ident, body)
+        try_from_struct(&value.ident, &value.generics, body)
     } else {
         Validation::fail(syn::Error::new_spanned(&value.ident, NOT_A_STRUCT))
     }
@@ -317,6 +415,7 @@ pub fn validate_input(value: &DeriveInput) -> Validation<LanesModel<'_>, Errors<
 
 fn try_from_struct<'a>(
     name: &'a Ident,
+    generics: &'a Generics,
     definition: &'a DataStruct,
 ) -> Validation<LanesModel<'a>, Errors<syn::Error>> {
     definition
@@ -338,20 +437,26 @@ fn try_from_struct<'a>(
                 Validation::valid(lanes)
             }
         })
-        .map(|lanes| LanesModel::new(name, lanes))
+        .map(|lanes| LanesModel::new(name, generics, lanes))
 }
 
 const COMMAND_LANE_NAME: &str = "CommandLane";
+const BATCH_COMMAND_LANE_NAME: &str = "BatchCommandLane";
 const DEMAND_LANE_NAME: &str = "DemandLane";
 const DEMAND_MAP_LANE_NAME: &str = "DemandMapLane";
 const VALUE_LANE_NAME: &str = "ValueLane";
 const VALUE_STORE_NAME: &str = "ValueStore";
 const MAP_LANE_NAME: &str = "MapLane";
 const MAP_STORE_NAME: &str = "MapStore";
+const ORDERED_MAP_LANE_NAME: &str = "OrderedMapLane";
 const JOIN_VALUE_LANE_NAME: &str = "JoinValueLane";
 const JOIN_MAP_LANE_NAME: &str = "JoinMapLane";
 const SUPPLY_LANE_NAME: &str = "SupplyLane";
+const HISTORY_LANE_NAME: &str = "HistoryLane";
+const STATS_LANE_NAME: &str = "StatsLane";
 const HTTP_LANE_NAME: &str = "HttpLane";
+const DYNAMIC_VALUE_LANES_NAME: &str = "DynamicValueLanes";
+const DYNAMIC_MAP_LANES_NAME: &str = "DynamicMapLanes";
 const SIMPLE_HTTP_LANE_NAME: &str = "SimpleHttpLane";
 
 const ITEM_TAG: &str = "item";
@@ -370,8 +475,12 @@ fn extract_lane_model(field: &Field) -> Validation<ItemModel<'_>, Errors<syn::Er
                 |ItemModifiers {
                      transform,
                      flags: lane_flags,
+                     previous_names,
+                     aliases,
+                     rate_limit,
+                     ttl_ms,
                  }| {
-                    match type_name.as_str() {
+                    let model = match type_name.as_str() {
                         COMMAND_LANE_NAME => {
                             match single_param(arguments) {
                                 Ok(param) => Validation::valid(ItemModel::new(
@@ -383,6 +492,17 @@ fn extract_lane_model(field: &Field) -> Validation<ItemModel<'_>, Errors<syn::Er
                                 Err(e) => Validation::fail(Errors::of(e)),
                             }
                         }
+                        BATCH_COMMAND_LANE_NAME => {
+                            match single_param(arguments) {
+                                Ok(param) => Validation::valid(ItemModel::new(
+                                    fld_name,
+                                    ItemSpec::BatchCommand(param),
+                                    ItemFlags::TRANSIENT, //Command lanes are always transient.
+                                    transform,
+                                )),
+                                Err(e) => Validation::fail(Errors::of(e)),
+                            }
+                        }
                         DEMAND_LANE_NAME => {
                             match single_param(arguments) {
                                 Ok(param) => Validation::valid(ItemModel::new(
@@ -432,6 +552,15 @@ fn extract_lane_model(field: &Field) -> Validation<ItemModel<'_>, Errors<syn::Er
                             )),
                             Err(e) => Validation::fail(Errors::of(e)),
                         },
+                        ORDERED_MAP_LANE_NAME => match two_params(arguments) {
+                            Ok((param1, param2)) => Validation::valid(ItemModel::new(
+                                fld_name,
+                                ItemSpec::OrderedMap(param1, param2),
+                                lane_flags,
+                                transform,
+                            )),
+                            Err(e) => Validation::fail(Errors::of(e)),
+                        },
                         MAP_STORE_NAME => match two_params(arguments) {
                             Ok((param1, param2)) => Validation::valid(ItemModel::new(
                                 fld_name,
@@ -470,6 +599,50 @@ fn extract_lane_model(field: &Field) -> Validation<ItemModel<'_>, Errors<syn::Er
                                 Err(e) => Validation::fail(Errors::of(e)),
                             }
                         }
+                        HISTORY_LANE_NAME => {
+                            match single_param(arguments) {
+                                Ok(param) => Validation::valid(ItemModel::new(
+                                    fld_name,
+                                    ItemSpec::History(param),
+                                    ItemFlags::TRANSIENT, //History lanes are always transient.
+                                    transform,
+                                )),
+                                Err(e) => Validation::fail(Errors::of(e)),
+                            }
+                        }
+                        STATS_LANE_NAME => {
+                            match no_params(arguments) {
+                                Ok(_) => Validation::valid(ItemModel::new(
+                                    fld_name,
+                                    ItemSpec::Stats,
+                                    ItemFlags::TRANSIENT, //Stats lanes are always transient.
+                                    transform,
+                                )),
+                                Err(e) => Validation::fail(Errors::of(e)),
+                            }
+                        }
+                        DYNAMIC_VALUE_LANES_NAME => {
+                            match single_param(arguments) {
+                                Ok(param) => Validation::valid(ItemModel::new(
+                                    fld_name,
+                                    ItemSpec::DynamicValue(param),
+                                    ItemFlags::TRANSIENT, //Dynamic lanes are always transient.
+                                    transform,
+                                )),
+                                Err(e) => Validation::fail(Errors::of(e)),
+                            }
+                        }
+                        DYNAMIC_MAP_LANES_NAME => {
+                            match two_params(arguments) {
+                                Ok((param1, param2)) => Validation::valid(ItemModel::new(
+                                    fld_name,
+                                    ItemSpec::DynamicMap(param1, param2),
+                                    ItemFlags::TRANSIENT, //Dynamic lanes are always transient.
+                                    transform,
+                                )),
+                                Err(e) => Validation::fail(Errors::of(e)),
+                            }
+                        }
                         name @ (HTTP_LANE_NAME | SIMPLE_HTTP_LANE_NAME) => {
                             match http_params(arguments, name == SIMPLE_HTTP_LANE_NAME) {
                                 Ok(spec) => Validation::valid(ItemModel::new(
@@ -485,7 +658,55 @@ fn extract_lane_model(field: &Field) -> Validation<ItemModel<'_>, Errors<syn::Er
                             &field.ty,
                             NOT_LANE_TYPE,
                         ))),
-                    }
+                    };
+                    model.and_then(|mut model| {
+                        if !aliases.is_empty()
+                            && (model.lane().is_none() || model.kind.is_dynamic())
+                        {
+                            return Validation::fail(Errors::of(syn::Error::new_spanned(
+                                field,
+                                ALIAS_NOT_LANE,
+                            )));
+                        }
+                        if lane_flags.contains(ItemFlags::TRACK_ORIGIN)
+                            && (model.lane().is_none() || model.kind.is_dynamic())
+                        {
+                            return Validation::fail(Errors::of(syn::Error::new_spanned(
+                                field,
+                                TRACK_ORIGIN_NOT_LANE,
+                            )));
+                        }
+                        if rate_limit.is_some()
+                            && !matches!(
+                                model.kind,
+                                ItemSpec::Command(_) | ItemSpec::BatchCommand(_)
+                            )
+                        {
+                            return Validation::fail(Errors::of(syn::Error::new_spanned(
+                                field,
+                                RATE_LIMIT_NOT_COMMAND,
+                            )));
+                        }
+                        if ttl_ms.is_some()
+                            && !matches!(
+                                model.kind,
+                                ItemSpec::Value(ItemKind::Lane, _)
+                                    | ItemSpec::Map(ItemKind::Lane, _, _)
+                            )
+                        {
+                            return Validation::fail(Errors::of(syn::Error::new_spanned(
+                                field,
+                                TTL_NOT_VALUE_OR_MAP,
+                            )));
+                        }
+                        // The flags of some kinds of lane are fixed so origin tracking is added separately.
+                        model.flags.insert(lane_flags & ItemFlags::TRACK_ORIGIN);
+                        model.previous_names = previous_names;
+                        model.aliases = aliases;
+                        model.rate_limit = rate_limit;
+                        model.ttl_ms = ttl_ms;
+                        Validation::valid(model)
+                    })
                 },
             )
         } else {
@@ -504,6 +725,14 @@ fn extract_lane_model(field: &Field) -> Validation<ItemModel<'_>, Errors<syn::Er
     }
 }
 
+fn no_params(args: &PathArguments) -> Result<(), syn::Error> {
+    if matches!(args, PathArguments::None) {
+        Ok(())
+    } else {
+        Err(syn::Error::new_spanned(args, BAD_PARAMS))
+    }
+}
+
 fn single_param(args: &PathArguments) -> Result<&Type, syn::Error> {
     if let PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) = args {
         let mut selected = None;
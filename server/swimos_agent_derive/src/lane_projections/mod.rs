@@ -50,7 +50,7 @@ impl<'a> ToTokens for ProjectionsImpl<'a> {
             #[automatically_derived]
             impl #impl_gen #agent_name #type_gen #where_clause {
 
-                #(pub const #defs;)*
+                #(#defs;)*
 
             }
         });
@@ -69,12 +69,14 @@ impl<'a> Projection<'a> {
     fn into_tokens(self) -> TokenStream {
         let Projection { field } = self;
         let proj_name = field.projection_name();
+        let cfg_attrs = field.cfg_attrs();
 
         let AgentField {
             field_name,
             field_type,
+            ..
         } = field;
 
-        quote!(#proj_name: for<'a> fn(&'a Self) -> &'a #field_type = |agent| &agent.#field_name)
+        quote!(#(#cfg_attrs)* pub const #proj_name: for<'a> fn(&'a Self) -> &'a #field_type = |agent| &agent.#field_name)
     }
 }
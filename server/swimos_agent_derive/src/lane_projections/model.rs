@@ -14,7 +14,7 @@
 
 use proc_macro2::{Span, TokenStream};
 use swimos_utilities::errors::{Errors, Validation, ValidationItExt};
-use syn::{Generics, Ident, Item, ItemStruct, Type};
+use syn::{Attribute, Generics, Ident, Item, ItemStruct, Type};
 
 /// Model of a the components of a struct type required to generate projection functions
 /// for each field.
@@ -39,21 +39,31 @@ impl<'a> AgentFields<'a> {
     }
 }
 
-/// Name and type of each field from a struct.
+/// Name, type and attributes of each field from a struct.
 #[derive(Clone, Copy)]
 pub struct AgentField<'a> {
     pub field_name: &'a Ident,
     pub field_type: &'a Type,
+    pub attrs: &'a [Attribute],
 }
 
 impl<'a> AgentField<'a> {
-    pub fn new(field_name: &'a Ident, field_type: &'a Type) -> Self {
+    pub fn new(field_name: &'a Ident, field_type: &'a Type, attrs: &'a [Attribute]) -> Self {
         AgentField {
             field_name,
             field_type,
+            attrs,
         }
     }
 
+    /// The `cfg` attributes of the field. These must also be applied to the projection as the
+    /// field may not exist.
+    pub fn cfg_attrs(&self) -> impl Iterator<Item = &'a Attribute> {
+        self.attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident(CFG_ATTR))
+    }
+
     /// Transform the name of the field to upper case to get the name of the projection function
     /// constant.
     pub fn projection_name(&self) -> syn::Ident {
@@ -78,6 +88,7 @@ pub fn validate_input<'a>(
     name.join(fields).map(|(_, fields)| fields)
 }
 
+const CFG_ATTR: &str = "cfg";
 const NO_PARAMS: &str = "The projections macro does not take any arguments.";
 const ONLY_STRUCTS: &str = "The projections macro can only be applied to struct definitions.";
 const NO_TUPLES: &str = "Projections cannot be generated for tuple structs.";
@@ -107,7 +118,7 @@ fn validate_from_struct(
             .iter()
             .append_fold(Validation::valid(vec![]), true, |mut acc, field| {
                 if let Some(name) = &field.ident {
-                    acc.push(AgentField::new(name, &field.ty));
+                    acc.push(AgentField::new(name, &field.ty, &field.attrs));
                     Validation::valid(acc)
                 } else {
                     Validation::Validated(acc, Some(syn::Error::new_spanned(field, NO_TUPLES)))
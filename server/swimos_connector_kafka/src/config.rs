@@ -0,0 +1,93 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, num::NonZeroUsize};
+
+use swimos_utilities::non_zero_usize;
+
+use crate::{format::DataFormat, selector::CommandMapping};
+
+/// The default bound on the number of messages that have been consumed but not yet forwarded.
+pub const DEFAULT_MAX_PENDING: NonZeroUsize = non_zero_usize!(64);
+
+/// Configuration for a Kafka ingress.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KafkaIngressConfiguration {
+    /// Properties that are passed to the Kafka consumer (for example `bootstrap.servers` and
+    /// `group.id`). The ingress stores the offsets of messages itself so `enable.auto.offset.store`
+    /// will always be disabled.
+    pub properties: HashMap<String, String>,
+    /// The topics to consume.
+    pub topics: Vec<String>,
+    /// The format of the keys of the messages.
+    pub key_format: DataFormat,
+    /// The format of the payloads of the messages.
+    pub payload_format: DataFormat,
+    /// Mappings from messages to commands.
+    pub mappings: Vec<CommandMapping>,
+    /// The host to which commands will be sent (for example `ws://localhost:8080`).
+    pub host: String,
+    /// The maximum number of messages that can have been consumed without yet having been
+    /// forwarded. When this is reached, the ingress will stop consuming until the server catches up.
+    pub max_pending: NonZeroUsize,
+}
+
+impl KafkaIngressConfiguration {
+    /// Create a configuration with no properties or mappings, text keys and payloads and the
+    /// default bound on pending messages.
+    ///
+    /// # Arguments
+    /// * `host` - The host to which commands will be sent.
+    /// * `topics` - The topics to consume.
+    pub fn new<I, S>(host: impl Into<String>, topics: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        KafkaIngressConfiguration {
+            properties: HashMap::new(),
+            topics: topics.into_iter().map(Into::into).collect(),
+            key_format: DataFormat::Text,
+            payload_format: DataFormat::Text,
+            mappings: vec![],
+            host: host.into(),
+            max_pending: DEFAULT_MAX_PENDING,
+        }
+    }
+
+    /// Set a property of the Kafka consumer.
+    pub fn with_property(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.properties.insert(name.into(), value.into());
+        self
+    }
+
+    /// Set the formats of the keys and payloads of the messages.
+    pub fn with_formats(mut self, key_format: DataFormat, payload_format: DataFormat) -> Self {
+        self.key_format = key_format;
+        self.payload_format = payload_format;
+        self
+    }
+
+    /// Add a mapping from messages to commands.
+    pub fn with_mapping(mut self, mapping: CommandMapping) -> Self {
+        self.mappings.push(mapping);
+        self
+    }
+
+    /// Set the maximum number of messages that may be pending.
+    pub fn with_max_pending(mut self, max_pending: NonZeroUsize) -> Self {
+        self.max_pending = max_pending;
+        self
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use avro_schema::schema::Schema;
+use swimos_model::{Blob, Item, Value};
+use thiserror::Error;
+
+/// Error type for failures to decode an Avro datum.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum AvroError {
+    /// The datum ended before a complete value was read.
+    #[error("The Avro datum was truncated.")]
+    Truncated,
+    /// A variable length integer was longer than permitted.
+    #[error("An Avro integer was too large.")]
+    IntegerOverflow,
+    /// A length was negative.
+    #[error("An Avro length was negative.")]
+    NegativeLength,
+    /// A string did not contain valid UTF-8.
+    #[error("An Avro string was not valid UTF-8.")]
+    BadUtf8,
+    /// An enumeration symbol or union branch did not exist in the schema.
+    #[error("An Avro enumeration or union index was out of range: {0}")]
+    BadIndex(i64),
+    /// Data remained after the datum was read.
+    #[error("{0} unexpected bytes after the Avro datum.")]
+    TrailingBytes(usize),
+}
+
+/// Decode a single Avro datum, in the binary encoding, into the Swim data model. Records are
+/// converted into records of slots, maps into records of slots with text keys, arrays into
+/// records of value items and enumerations into the text of the symbol.
+pub fn decode_avro(schema: &Schema, bytes: &[u8]) -> Result<Value, AvroError> {
+    let mut input = bytes;
+    let value = decode_datum(schema, &mut input)?;
+    if input.is_empty() {
+        Ok(value)
+    } else {
+        Err(AvroError::TrailingBytes(input.len()))
+    }
+}
+
+fn decode_datum(schema: &Schema, input: &mut &[u8]) -> Result<Value, AvroError> {
+    Ok(match schema {
+        Schema::Null => Value::Extant,
+        Schema::Boolean => Value::BooleanValue(take(input, 1)?[0] != 0),
+        Schema::Int(_) => {
+            let n = read_long(input)?;
+            Value::Int32Value(i32::try_from(n).map_err(|_| AvroError::IntegerOverflow)?)
+        }
+        Schema::Long(_) => Value::Int64Value(read_long(input)?),
+        Schema::Float => {
+            let bytes = take(input, 4)?;
+            let n = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            Value::Float64Value(n.into())
+        }
+        Schema::Double => {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(take(input, 8)?);
+            Value::Float64Value(f64::from_le_bytes(buf))
+        }
+        Schema::Bytes(_) => Value::Data(Blob::from_vec(read_bytes(input)?.to_vec())),
+        Schema::String(_) => Value::text(read_str(input)?),
+        Schema::Record(record) => {
+            let mut items = Vec::with_capacity(record.fields.len());
+            for field in &record.fields {
+                let value = decode_datum(&field.schema, input)?;
+                items.push(Item::slot(field.name.as_str(), value));
+            }
+            Value::record(items)
+        }
+        Schema::Enum(enumeration) => {
+            let index = read_long(input)?;
+            let symbol = usize::try_from(index)
+                .ok()
+                .and_then(|i| enumeration.symbols.get(i))
+                .ok_or(AvroError::BadIndex(index))?;
+            Value::text(symbol.as_str())
+        }
+        Schema::Array(item_schema) => {
+            let mut items = vec![];
+            read_blocks(input, |input| {
+                items.push(Item::ValueItem(decode_datum(item_schema, input)?));
+                Ok(())
+            })?;
+            Value::record(items)
+        }
+        Schema::Map(value_schema) => {
+            let mut items = vec![];
+            read_blocks(input, |input| {
+                let key = read_str(input)?;
+                let value = decode_datum(value_schema, input)?;
+                items.push(Item::slot(key, value));
+                Ok(())
+            })?;
+            Value::record(items)
+        }
+        Schema::Union(branches) => {
+            let index = read_long(input)?;
+            let branch = usize::try_from(index)
+                .ok()
+                .and_then(|i| branches.get(i))
+                .ok_or(AvroError::BadIndex(index))?;
+            decode_datum(branch, input)?
+        }
+        Schema::Fixed(fixed) => Value::Data(Blob::from_vec(take(input, fixed.size)?.to_vec())),
+    })
+}
+
+fn take<'a>(input: &mut &'a [u8], n: usize) -> Result<&'a [u8], AvroError> {
+    if input.len() < n {
+        Err(AvroError::Truncated)
+    } else {
+        let (head, tail) = input.split_at(n);
+        *input = tail;
+        Ok(head)
+    }
+}
+
+// Avro integers are zig-zag encoded variable length integers.
+fn read_long(input: &mut &[u8]) -> Result<i64, AvroError> {
+    let mut acc = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = take(input, 1)?[0];
+        if shift > 63 {
+            return Err(AvroError::IntegerOverflow);
+        }
+        acc |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    Ok(((acc >> 1) as i64) ^ -((acc & 1) as i64))
+}
+
+fn read_len(input: &mut &[u8]) -> Result<usize, AvroError> {
+    usize::try_from(read_long(input)?).map_err(|_| AvroError::NegativeLength)
+}
+
+fn read_bytes<'a>(input: &mut &'a [u8]) -> Result<&'a [u8], AvroError> {
+    let len = read_len(input)?;
+    take(input, len)
+}
+
+fn read_str<'a>(input: &mut &'a [u8]) -> Result<&'a str, AvroError> {
+    std::str::from_utf8(read_bytes(input)?).map_err(|_| AvroError::BadUtf8)
+}
+
+// Arrays and maps are written as a sequence of blocks, terminated by an empty block. A negative
+// block count is followed by the size of the block in bytes.
+fn read_blocks<F>(input: &mut &[u8], mut f: F) -> Result<(), AvroError>
+where
+    F: FnMut(&mut &[u8]) -> Result<(), AvroError>,
+{
+    loop {
+        let count = read_long(input)?;
+        if count == 0 {
+            break Ok(());
+        }
+        if count < 0 {
+            read_long(input)?;
+        }
+        for _ in 0..count.unsigned_abs() {
+            f(input)?;
+        }
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod avro;
+#[cfg(test)]
+mod tests;
+
+use std::str::Utf8Error;
+
+use avro_schema::schema::Schema;
+use swimos_model::Value;
+use swimos_recon::{json::json_to_value, parser::parse_recognize, parser::ParseError};
+use thiserror::Error;
+
+pub use avro::AvroError;
+
+/// The formats that can be used to interpret the keys and payloads of Kafka messages.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum DataFormat {
+    /// The bytes are interpreted as a UTF-8 string which is used as a text value.
+    #[default]
+    Text,
+    /// The bytes are interpreted as a UTF-8 string containing Recon.
+    Recon,
+    /// The bytes are interpreted as a UTF-8 string containing JSON. The JSON is converted into
+    /// the Swim model using [`json_to_value`].
+    Json,
+    /// The bytes are a single Avro datum, in the binary encoding, with the provided schema.
+    Avro(AvroSchema),
+}
+
+/// An Avro schema for the [`DataFormat::Avro`] format. Named references to other types are not
+/// supported so the schema must be fully expanded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvroSchema(Schema);
+
+/// Error type for an Avro schema that could not be parsed.
+#[derive(Debug, Error)]
+#[error("Invalid Avro schema: {0}")]
+pub struct InvalidAvroSchema(#[from] serde_json::Error);
+
+impl AvroSchema {
+    /// Parse a schema from its JSON representation.
+    pub fn parse(schema: &str) -> Result<Self, InvalidAvroSchema> {
+        Ok(AvroSchema(serde_json::from_str(schema)?))
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.0
+    }
+}
+
+impl From<Schema> for AvroSchema {
+    fn from(schema: Schema) -> Self {
+        AvroSchema(schema)
+    }
+}
+
+/// Error type for data that could not be interpreted according to a [`DataFormat`].
+#[derive(Debug, Error)]
+pub enum DeserializationError {
+    /// The data was required to be UTF-8 and was not.
+    #[error("The data was not valid UTF-8: {0}")]
+    Utf8(#[from] Utf8Error),
+    /// The data was not valid Recon.
+    #[error("The data was not valid Recon: {0}")]
+    Recon(#[from] ParseError),
+    /// The data was not valid JSON.
+    #[error("The data was not valid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The data was not a valid Avro datum for the schema.
+    #[error("The data was not a valid Avro datum: {0}")]
+    Avro(#[from] AvroError),
+}
+
+impl DataFormat {
+    /// Interpret bytes as a value in the Swim model.
+    pub fn deserialize(&self, bytes: &[u8]) -> Result<Value, DeserializationError> {
+        match self {
+            DataFormat::Text => Ok(Value::text(std::str::from_utf8(bytes)?)),
+            DataFormat::Recon => {
+                let recon = std::str::from_utf8(bytes)?;
+                Ok(parse_recognize::<Value>(recon, false)?)
+            }
+            DataFormat::Json => {
+                let json = serde_json::from_slice::<serde_json::Value>(bytes)?;
+                Ok(json_to_value(&json))
+            }
+            DataFormat::Avro(AvroSchema(schema)) => Ok(avro::decode_avro(schema, bytes)?),
+        }
+    }
+}
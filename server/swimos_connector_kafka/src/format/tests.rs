@@ -0,0 +1,142 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use swimos_model::{Item, Value};
+
+use super::{AvroError, AvroSchema, DataFormat, DeserializationError};
+
+const READING_SCHEMA: &str = r#"{
+    "type": "record",
+    "name": "Reading",
+    "fields": [
+        {"name": "id", "type": "string"},
+        {"name": "value", "type": "double"},
+        {"name": "count", "type": "int"},
+        {"name": "tags", "type": {"type": "array", "items": "string"}},
+        {"name": "status", "type": {"type": "enum", "name": "Status", "symbols": ["OK", "FAULT"]}},
+        {"name": "note", "type": ["null", "string"]}
+    ]
+}"#;
+
+fn avro(schema: &str) -> DataFormat {
+    DataFormat::Avro(AvroSchema::parse(schema).expect("Invalid schema."))
+}
+
+#[test]
+fn deserialize_text() {
+    let value = DataFormat::Text.deserialize(b"sensor").expect("Failed.");
+    assert_eq!(value, Value::text("sensor"));
+
+    let result = DataFormat::Text.deserialize(&[0xff, 0xfe]);
+    assert!(matches!(result, Err(DeserializationError::Utf8(_))));
+}
+
+#[test]
+fn deserialize_recon() {
+    let value = DataFormat::Recon
+        .deserialize(b"{id: a1, value: 2}")
+        .expect("Failed.");
+    assert_eq!(
+        value,
+        Value::record(vec![Item::slot("id", "a1"), Item::slot("value", 2)])
+    );
+
+    let result = DataFormat::Recon.deserialize(b"{id: ");
+    assert!(matches!(result, Err(DeserializationError::Recon(_))));
+}
+
+#[test]
+fn deserialize_json() {
+    let value = DataFormat::Json
+        .deserialize(br#"{"id": "a1", "values": [1, 2]}"#)
+        .expect("Failed.");
+    assert_eq!(
+        value,
+        Value::record(vec![
+            Item::slot("id", "a1"),
+            Item::slot("values", Value::from_vec(vec![1i64, 2i64]))
+        ])
+    );
+
+    let result = DataFormat::Json.deserialize(b"{\"id\": ");
+    assert!(matches!(result, Err(DeserializationError::Json(_))));
+}
+
+#[test]
+fn invalid_avro_schema() {
+    assert!(AvroSchema::parse(r#"{"type": "record"}"#).is_err());
+}
+
+#[test]
+fn deserialize_avro_record() {
+    let format = avro(READING_SCHEMA);
+    let mut bytes = vec![0x04, b'a', b'1'];
+    bytes.extend_from_slice(&1.5f64.to_le_bytes());
+    bytes.push(0x05);
+    bytes.extend_from_slice(&[0x04, 0x02, b'x', 0x02, b'y', 0x00]);
+    bytes.push(0x02);
+    bytes.push(0x00);
+
+    let value = format.deserialize(&bytes).expect("Failed.");
+    assert_eq!(
+        value,
+        Value::record(vec![
+            Item::slot("id", "a1"),
+            Item::slot("value", 1.5),
+            Item::slot("count", -3),
+            Item::slot("tags", Value::from_vec(vec!["x", "y"])),
+            Item::slot("status", "FAULT"),
+            Item::slot("note", Value::Extant),
+        ])
+    );
+}
+
+#[test]
+fn deserialize_avro_map_with_sized_blocks() {
+    let format = avro(r#"{"type": "map", "values": "long"}"#);
+    let bytes = [0x01, 0x08, 0x02, b'k', 0xd8, 0x04, 0x00];
+
+    let value = format.deserialize(&bytes).expect("Failed.");
+    assert_eq!(value, Value::record(vec![Item::slot("k", 300i64)]));
+}
+
+#[test]
+fn deserialize_avro_union() {
+    let format = avro(r#"["null", "long"]"#);
+    let value = format.deserialize(&[0x02, 0x07]).expect("Failed.");
+    assert_eq!(value, Value::Int64Value(-4));
+
+    let result = format.deserialize(&[0x04, 0x07]);
+    assert!(matches!(
+        result,
+        Err(DeserializationError::Avro(AvroError::BadIndex(2)))
+    ));
+}
+
+#[test]
+fn deserialize_bad_avro() {
+    let format = avro(r#""string""#);
+    let result = format.deserialize(&[0x04, b'a']);
+    assert!(matches!(
+        result,
+        Err(DeserializationError::Avro(AvroError::Truncated))
+    ));
+
+    let format = avro(r#""int""#);
+    let result = format.deserialize(&[0x02, 0x00]);
+    assert!(matches!(
+        result,
+        Err(DeserializationError::Avro(AvroError::TrailingBytes(1)))
+    ));
+}
@@ -0,0 +1,293 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(test)]
+mod tests;
+
+use futures::{future::BoxFuture, FutureExt};
+use rdkafka::{
+    consumer::{CommitMode, Consumer, StreamConsumer},
+    error::KafkaError,
+    ClientConfig, Message,
+};
+use swimos_client::{CommandError, Commander};
+use swimos_model::Value;
+use swimos_utilities::trigger;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    config::KafkaIngressConfiguration,
+    format::DataFormat,
+    selector::{Command, CommandRelay, InvalidSelector, MessageView},
+};
+
+const AUTO_OFFSET_STORE: &str = "enable.auto.offset.store";
+
+/// Error type for failures of a Kafka ingress.
+#[derive(Debug, Error)]
+pub enum KafkaIngressError {
+    /// One of the command mappings was invalid.
+    #[error("Invalid command mapping: {0}")]
+    Mapping(#[from] InvalidSelector),
+    /// The Kafka consumer failed.
+    #[error("The Kafka consumer failed: {0}")]
+    Kafka(#[from] KafkaError),
+    /// A command could not be sent to the server.
+    #[error("Failed to send a command: {0}")]
+    Command(#[from] CommandError),
+}
+
+/// An ingress that consumes messages from Kafka topics and forwards them, as commands, to the
+/// lanes of a Swim server.
+///
+/// Messages are consumed in order and each is forwarded to the lanes selected by the
+/// [command mappings](crate::CommandMapping) of the configuration. The offset of a message is only
+/// stored (to be committed to Kafka) after all of the commands for that message have been sent so,
+/// if the ingress fails, every message will be delivered at least once when it is restarted.
+/// Messages with keys or payloads that cannot be deserialized are skipped.
+#[derive(Debug)]
+pub struct KafkaIngress {
+    config: KafkaIngressConfiguration,
+    relays: Vec<CommandRelay>,
+}
+
+impl KafkaIngress {
+    /// Create an ingress, validating the command mappings.
+    pub fn new(config: KafkaIngressConfiguration) -> Result<Self, KafkaIngressError> {
+        let relays = config
+            .mappings
+            .iter()
+            .map(CommandRelay::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(KafkaIngress { config, relays })
+    }
+
+    /// Run the ingress until the stop signal is triggered. When stopped, the ingress will finish
+    /// forwarding any messages that it has already consumed and commit the offsets to Kafka.
+    pub async fn run(self, stop_signal: trigger::Receiver) -> Result<(), KafkaIngressError> {
+        let KafkaIngress { config, relays } = self;
+        let mut client_config = ClientConfig::new();
+        for (name, value) in &config.properties {
+            client_config.set(name, value);
+        }
+        client_config.set(AUTO_OFFSET_STORE, "false");
+        let consumer: StreamConsumer = client_config.create()?;
+        let topics = config.topics.iter().map(String::as_str).collect::<Vec<_>>();
+        consumer.subscribe(&topics)?;
+        info!(topics = ?config.topics, host = %config.host, "Kafka ingress started.");
+
+        let mut commander = Commander::default();
+        let result = run_ingress(&consumer, &mut commander, &config, &relays, stop_signal).await;
+        for err in commander.close().await {
+            warn!(error = %err, "Closing a connection to the server failed.");
+        }
+        result
+    }
+}
+
+/// A message consumed from Kafka.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KafkaMessage {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    pub key: Option<Vec<u8>>,
+    pub payload: Option<Vec<u8>>,
+}
+
+/// Abstraction over the Kafka consumer.
+pub(crate) trait MessageSource {
+    /// Wait for the next message.
+    fn next_message(&self) -> BoxFuture<'_, Result<KafkaMessage, KafkaError>>;
+
+    /// Mark a message as having been processed so that its offset will be committed.
+    fn acknowledge(&self, topic: &str, partition: i32, offset: i64) -> Result<(), KafkaError>;
+
+    /// Commit the offsets of all acknowledged messages.
+    fn commit(&self) -> Result<(), KafkaError>;
+}
+
+impl MessageSource for StreamConsumer {
+    fn next_message(&self) -> BoxFuture<'_, Result<KafkaMessage, KafkaError>> {
+        async move {
+            let message = self.recv().await?;
+            Ok(KafkaMessage {
+                topic: message.topic().to_string(),
+                partition: message.partition(),
+                offset: message.offset(),
+                key: message.key().map(ToOwned::to_owned),
+                payload: message.payload().map(ToOwned::to_owned),
+            })
+        }
+        .boxed()
+    }
+
+    fn acknowledge(&self, topic: &str, partition: i32, offset: i64) -> Result<(), KafkaError> {
+        self.store_offset(topic, partition, offset)
+    }
+
+    fn commit(&self) -> Result<(), KafkaError> {
+        match self.commit_consumer_state(CommitMode::Sync) {
+            // Nothing was stored so there is nothing to commit.
+            Err(KafkaError::ConsumerCommit(rdkafka::types::RDKafkaErrorCode::NoOffset)) => Ok(()),
+            ow => ow,
+        }
+    }
+}
+
+/// Abstraction over the sending of commands to the server.
+pub(crate) trait CommandSink {
+    fn send_command<'a>(
+        &'a mut self,
+        host: &'a str,
+        command: &'a Command,
+    ) -> BoxFuture<'a, Result<(), CommandError>>;
+}
+
+impl CommandSink for Commander {
+    fn send_command<'a>(
+        &'a mut self,
+        host: &'a str,
+        command: &'a Command,
+    ) -> BoxFuture<'a, Result<(), CommandError>> {
+        let Command { node, lane, body } = command;
+        Commander::send_command(self, host, node, lane, body).boxed()
+    }
+}
+
+/// The commands generated for a consumed message.
+struct Pending {
+    topic: String,
+    partition: i32,
+    offset: i64,
+    commands: Vec<Command>,
+}
+
+/// Consume messages from the source, forwarding them to the sink. Consumption and forwarding
+/// proceed concurrently, separated by a bounded queue, so that the consumer will wait when the
+/// server cannot keep up.
+pub(crate) async fn run_ingress<S, C>(
+    source: &S,
+    sink: &mut C,
+    config: &KafkaIngressConfiguration,
+    relays: &[CommandRelay],
+    mut stop_signal: trigger::Receiver,
+) -> Result<(), KafkaIngressError>
+where
+    S: MessageSource,
+    C: CommandSink,
+{
+    let (tx, mut rx) = mpsc::channel::<Pending>(config.max_pending.get());
+
+    let consume = async move {
+        loop {
+            let message = tokio::select! {
+                biased;
+                _ = &mut stop_signal => break,
+                result = source.next_message() => result?,
+            };
+            let commands =
+                generate_commands(&message, &config.key_format, &config.payload_format, relays);
+            let KafkaMessage {
+                topic,
+                partition,
+                offset,
+                ..
+            } = message;
+            let pending = Pending {
+                topic,
+                partition,
+                offset,
+                commands,
+            };
+            if tx.send(pending).await.is_err() {
+                break;
+            }
+        }
+        Ok::<_, KafkaIngressError>(())
+    };
+
+    let forward = async move {
+        while let Some(pending) = rx.recv().await {
+            let Pending {
+                topic,
+                partition,
+                offset,
+                commands,
+            } = pending;
+            for command in &commands {
+                sink.send_command(&config.host, command).await?;
+            }
+            source.acknowledge(&topic, partition, offset)?;
+        }
+        Ok::<_, KafkaIngressError>(())
+    };
+
+    let result = futures::future::try_join(consume, forward).await;
+    if let Err(err) = source.commit() {
+        error!(error = %err, "Failed to commit the offsets of the forwarded messages.");
+    }
+    result.map(|_| ())
+}
+
+fn deserialize(format: &DataFormat, bytes: Option<&[u8]>) -> Result<Value, String> {
+    match bytes {
+        Some(bytes) => format.deserialize(bytes).map_err(|err| err.to_string()),
+        None => Ok(Value::Extant),
+    }
+}
+
+fn generate_commands(
+    message: &KafkaMessage,
+    key_format: &DataFormat,
+    payload_format: &DataFormat,
+    relays: &[CommandRelay],
+) -> Vec<Command> {
+    let KafkaMessage {
+        topic,
+        partition,
+        offset,
+        key,
+        payload,
+    } = message;
+    let (key, payload) = match (
+        deserialize(key_format, key.as_deref()),
+        deserialize(payload_format, payload.as_deref()),
+    ) {
+        (Ok(key), Ok(payload)) => (key, payload),
+        (Err(err), _) | (_, Err(err)) => {
+            warn!(topic, partition, offset, error = %err, "Skipping a message that could not be deserialized.");
+            return vec![];
+        }
+    };
+    let view = MessageView {
+        topic,
+        partition: *partition,
+        key: &key,
+        payload: &payload,
+    };
+    let commands = relays
+        .iter()
+        .filter_map(|relay| relay.select(&view))
+        .collect::<Vec<_>>();
+    if commands.is_empty() {
+        debug!(
+            topic,
+            partition, offset, "No command mappings applied to a message."
+        );
+    }
+    commands
+}
@@ -0,0 +1,293 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::VecDeque,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use futures::{future::BoxFuture, FutureExt};
+use rdkafka::error::KafkaError;
+use swimos_client::{CommandError, Commander};
+use swimos_model::{Item, Value};
+use swimos_utilities::{non_zero_usize, trigger};
+
+use crate::{
+    config::KafkaIngressConfiguration,
+    format::DataFormat,
+    selector::{Command, CommandMapping, CommandRelay},
+};
+
+use super::{run_ingress, CommandSink, KafkaIngressError, KafkaMessage, MessageSource};
+
+const HOST: &str = "ws://localhost:8080";
+const TOPIC: &str = "sensors";
+const SEND_DELAY: Duration = Duration::from_secs(1);
+
+struct TestSource {
+    messages: Mutex<VecDeque<KafkaMessage>>,
+    consumed: AtomicUsize,
+    acknowledged: Mutex<Vec<(String, i32, i64)>>,
+    commits: AtomicUsize,
+    // Triggered when all of the messages have been consumed.
+    exhausted: Mutex<Option<trigger::Sender>>,
+}
+
+impl TestSource {
+    fn new(messages: Vec<KafkaMessage>, exhausted: Option<trigger::Sender>) -> Self {
+        TestSource {
+            messages: Mutex::new(messages.into()),
+            consumed: Default::default(),
+            acknowledged: Default::default(),
+            commits: Default::default(),
+            exhausted: Mutex::new(exhausted),
+        }
+    }
+
+    fn acknowledged_offsets(&self) -> Vec<i64> {
+        self.acknowledged
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, _, offset)| *offset)
+            .collect()
+    }
+}
+
+impl MessageSource for TestSource {
+    fn next_message(&self) -> BoxFuture<'_, Result<KafkaMessage, KafkaError>> {
+        let next = self.messages.lock().unwrap().pop_front();
+        match next {
+            Some(message) => {
+                self.consumed.fetch_add(1, Ordering::SeqCst);
+                futures::future::ready(Ok(message)).boxed()
+            }
+            None => {
+                if let Some(tx) = self.exhausted.lock().unwrap().take() {
+                    tx.trigger();
+                }
+                futures::future::pending().boxed()
+            }
+        }
+    }
+
+    fn acknowledge(&self, topic: &str, partition: i32, offset: i64) -> Result<(), KafkaError> {
+        self.acknowledged
+            .lock()
+            .unwrap()
+            .push((topic.to_string(), partition, offset));
+        Ok(())
+    }
+
+    fn commit(&self) -> Result<(), KafkaError> {
+        self.commits.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct TestSink {
+    sent: Vec<Command>,
+    fail_at: Option<usize>,
+    delay: Option<Duration>,
+}
+
+async fn command_error() -> CommandError {
+    Commander::default()
+        .send_command("not a host", "/node", "lane", &Value::Extant)
+        .await
+        .expect_err("Sending to an invalid host should fail.")
+}
+
+impl CommandSink for TestSink {
+    fn send_command<'a>(
+        &'a mut self,
+        host: &'a str,
+        command: &'a Command,
+    ) -> BoxFuture<'a, Result<(), CommandError>> {
+        async move {
+            assert_eq!(host, HOST);
+            if let Some(delay) = self.delay {
+                tokio::time::sleep(delay).await;
+            }
+            if self.fail_at == Some(self.sent.len()) {
+                return Err(command_error().await);
+            }
+            self.sent.push(command.clone());
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+fn message(offset: i64, key: &str, payload: &str) -> KafkaMessage {
+    KafkaMessage {
+        topic: TOPIC.to_string(),
+        partition: 0,
+        offset,
+        key: Some(key.as_bytes().to_vec()),
+        payload: Some(payload.as_bytes().to_vec()),
+    }
+}
+
+fn make_config(max_pending: NonZeroUsize) -> (KafkaIngressConfiguration, Vec<CommandRelay>) {
+    let config = KafkaIngressConfiguration::new(HOST, [TOPIC])
+        .with_formats(DataFormat::Text, DataFormat::Json)
+        .with_mapping(CommandMapping::new("/sensors/$key", "reading").with_body("$payload.value"))
+        .with_max_pending(max_pending);
+    let relays = config
+        .mappings
+        .iter()
+        .map(|mapping| CommandRelay::try_from(mapping).expect("Invalid mapping."))
+        .collect();
+    (config, relays)
+}
+
+fn reading(key: &str, value: i64) -> Command {
+    Command {
+        node: format!("/sensors/{}", key),
+        lane: "reading".to_string(),
+        body: Value::Int64Value(value),
+    }
+}
+
+#[tokio::test]
+async fn forward_messages_as_commands() {
+    let (config, relays) = make_config(non_zero_usize!(8));
+    let (stop_tx, stop_rx) = trigger::trigger();
+    let source = TestSource::new(
+        vec![
+            message(0, "a", r#"{"value": 1}"#),
+            message(1, "b", r#"{"value": 2}"#),
+        ],
+        Some(stop_tx),
+    );
+    let mut sink = TestSink::default();
+
+    let result = run_ingress(&source, &mut sink, &config, &relays, stop_rx).await;
+    assert!(result.is_ok());
+    assert_eq!(sink.sent, vec![reading("a", 1), reading("b", 2)]);
+    assert_eq!(
+        *source.acknowledged.lock().unwrap(),
+        vec![(TOPIC.to_string(), 0, 0), (TOPIC.to_string(), 0, 1)]
+    );
+    assert_eq!(source.commits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn skip_invalid_messages() {
+    let (config, relays) = make_config(non_zero_usize!(8));
+    let (stop_tx, stop_rx) = trigger::trigger();
+    let source = TestSource::new(
+        vec![
+            message(0, "a", "{\"value\": "),
+            message(1, "b", r#"{"other": 2}"#),
+            message(2, "c", r#"{"value": 3}"#),
+        ],
+        Some(stop_tx),
+    );
+    let mut sink = TestSink::default();
+
+    let result = run_ingress(&source, &mut sink, &config, &relays, stop_rx).await;
+    assert!(result.is_ok());
+    assert_eq!(sink.sent, vec![reading("c", 3)]);
+    assert_eq!(source.acknowledged_offsets(), vec![0, 1, 2]);
+}
+
+#[tokio::test]
+async fn multiple_mappings() {
+    let (mut config, mut relays) = make_config(non_zero_usize!(8));
+    let mapping = CommandMapping::new("/all", "$topic");
+    relays.push(CommandRelay::try_from(&mapping).expect("Invalid mapping."));
+    config.mappings.push(mapping);
+
+    let (stop_tx, stop_rx) = trigger::trigger();
+    let source = TestSource::new(vec![message(0, "a", r#"{"value": 1}"#)], Some(stop_tx));
+    let mut sink = TestSink::default();
+
+    let result = run_ingress(&source, &mut sink, &config, &relays, stop_rx).await;
+    assert!(result.is_ok());
+    assert_eq!(
+        sink.sent,
+        vec![
+            reading("a", 1),
+            Command {
+                node: "/all".to_string(),
+                lane: TOPIC.to_string(),
+                body: Value::record(vec![Item::slot("value", 1i64)]),
+            }
+        ]
+    );
+    assert_eq!(source.acknowledged_offsets(), vec![0]);
+}
+
+#[tokio::test]
+async fn unsent_messages_are_not_acknowledged() {
+    let (config, relays) = make_config(non_zero_usize!(8));
+    let (_stop_tx, stop_rx) = trigger::trigger();
+    let source = TestSource::new(
+        vec![
+            message(0, "a", r#"{"value": 1}"#),
+            message(1, "b", r#"{"value": 2}"#),
+            message(2, "c", r#"{"value": 3}"#),
+        ],
+        None,
+    );
+    let mut sink = TestSink {
+        fail_at: Some(1),
+        ..Default::default()
+    };
+
+    let result = run_ingress(&source, &mut sink, &config, &relays, stop_rx).await;
+    assert!(matches!(result, Err(KafkaIngressError::Command(_))));
+    assert_eq!(sink.sent, vec![reading("a", 1)]);
+    assert_eq!(source.acknowledged_offsets(), vec![0]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn consumption_is_bounded() {
+    let (config, relays) = make_config(non_zero_usize!(1));
+    let (stop_tx, stop_rx) = trigger::trigger();
+    let messages = (0..10)
+        .map(|i| message(i, "a", &format!("{{\"value\": {}}}", i)))
+        .collect();
+    let source = TestSource::new(messages, None);
+    let mut sink = TestSink {
+        delay: Some(SEND_DELAY),
+        ..Default::default()
+    };
+
+    let check = async {
+        tokio::time::sleep(SEND_DELAY / 2).await;
+        // One message is being sent, one is queued and one is waiting to be queued.
+        assert_eq!(source.consumed.load(Ordering::SeqCst), 3);
+        stop_tx.trigger();
+    };
+
+    let (result, _) = tokio::join!(
+        run_ingress(&source, &mut sink, &config, &relays, stop_rx),
+        check
+    );
+    assert!(result.is_ok());
+    assert_eq!(
+        sink.sent,
+        vec![reading("a", 0), reading("a", 1), reading("a", 2)]
+    );
+    assert_eq!(source.acknowledged_offsets(), vec![0, 1, 2]);
+}
@@ -0,0 +1,34 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Kafka Ingress Connector
+//!
+//! Consumes messages from Kafka topics and forwards them, as commands, to the lanes of a Swim
+//! server.
+//!
+//! - The [`KafkaIngressConfiguration`] describes the topics to consume, how the keys and payloads
+//!   of the messages should be deserialized (see [`DataFormat`]) and how messages are mapped to
+//!   commands (see [`CommandMapping`]).
+//! - The [`KafkaIngress`] runs the connector, with backpressure and at-least-once delivery of
+//!   messages to the server.
+
+mod config;
+mod format;
+mod ingress;
+mod selector;
+
+pub use config::{KafkaIngressConfiguration, DEFAULT_MAX_PENDING};
+pub use format::{AvroError, AvroSchema, DataFormat, DeserializationError, InvalidAvroSchema};
+pub use ingress::{KafkaIngress, KafkaIngressError};
+pub use selector::{CommandMapping, InvalidSelector};
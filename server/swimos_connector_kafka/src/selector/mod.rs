@@ -0,0 +1,272 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(test)]
+mod tests;
+
+use std::{borrow::Cow, str::FromStr};
+
+use swimos_model::{Item, Value};
+use thiserror::Error;
+
+const KEY: &str = "$key";
+const PAYLOAD: &str = "$payload";
+const TOPIC: &str = "$topic";
+const PARTITION: &str = "$partition";
+
+/// Specifies how each Kafka message is mapped to a command. A message will result in a command
+/// for each mapping that can be applied to it.
+///
+/// The node, lane and body are described using selectors that pick values out of the message:
+///
+/// - `$key` and `$payload` select the deserialized key and payload of the message. These can be
+///   followed by a path of field names and item indices, separated by `.`, to select a part of the
+///   value. For example, `$payload.readings.0` selects the first item of the `readings` field of
+///   the payload.
+/// - `$topic` and `$partition` select the topic and partition from which the message was consumed.
+///
+/// The node and lane are templates where any segment (separated by `/`) that starts with `$` is
+/// a selector. For example, `/sensors/$key` will route each message to the agent for the sensor
+/// named by its key. A selected value must be text, a number or a boolean to be used in a template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandMapping {
+    /// Template for the node URI of the target agent.
+    pub node: String,
+    /// Template for the name of the target lane.
+    pub lane: String,
+    /// Selector for the body of the command.
+    pub body: String,
+}
+
+impl CommandMapping {
+    /// Send the entire payload of each message to a lane.
+    ///
+    /// # Arguments
+    /// * `node` - Template for the node URI.
+    /// * `lane` - Template for the lane name.
+    pub fn new(node: impl Into<String>, lane: impl Into<String>) -> Self {
+        CommandMapping {
+            node: node.into(),
+            lane: lane.into(),
+            body: PAYLOAD.to_string(),
+        }
+    }
+
+    /// Select the body of the command from the message.
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+}
+
+/// Error type for a selector that could not be parsed.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum InvalidSelector {
+    /// The selector did not start with one of the permitted roots.
+    #[error("'{0}' is not a valid selector root.")]
+    BadRoot(String),
+    /// A path was applied to the topic or partition.
+    #[error("The selector '{0}' cannot have a path.")]
+    UnexpectedPath(String),
+    /// A segment of the path was empty.
+    #[error("The selector '{0}' contains an empty path segment.")]
+    EmptySegment(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Root {
+    Key,
+    Payload,
+    Topic,
+    Partition,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Field(String),
+    Index(usize),
+}
+
+/// Selects a value from a Kafka message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Selector {
+    root: Root,
+    path: Vec<Segment>,
+}
+
+/// A view of a Kafka message, with the key and payload already deserialized.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MessageView<'a> {
+    pub topic: &'a str,
+    pub partition: i32,
+    pub key: &'a Value,
+    pub payload: &'a Value,
+}
+
+impl FromStr for Selector {
+    type Err = InvalidSelector;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('.');
+        let root = match parts.next() {
+            Some(KEY) => Root::Key,
+            Some(PAYLOAD) => Root::Payload,
+            Some(TOPIC) => Root::Topic,
+            Some(PARTITION) => Root::Partition,
+            _ => return Err(InvalidSelector::BadRoot(s.to_string())),
+        };
+        let path = parts
+            .map(|part| {
+                if part.is_empty() {
+                    Err(InvalidSelector::EmptySegment(s.to_string()))
+                } else if let Ok(n) = part.parse::<usize>() {
+                    Ok(Segment::Index(n))
+                } else {
+                    Ok(Segment::Field(part.to_string()))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if !path.is_empty() && matches!(root, Root::Topic | Root::Partition) {
+            return Err(InvalidSelector::UnexpectedPath(s.to_string()));
+        }
+        Ok(Selector { root, path })
+    }
+}
+
+impl Selector {
+    /// Select a value from a message, if it exists.
+    pub fn select<'a>(&self, message: &MessageView<'a>) -> Option<Cow<'a, Value>> {
+        let Selector { root, path } = self;
+        let base = match root {
+            Root::Key => message.key,
+            Root::Payload => message.payload,
+            Root::Topic => return Some(Cow::Owned(Value::text(message.topic))),
+            Root::Partition => return Some(Cow::Owned(Value::Int32Value(message.partition))),
+        };
+        path.iter()
+            .try_fold(base, |value, segment| match (value, segment) {
+                (Value::Record(_, items), Segment::Field(name)) => {
+                    items.iter().find_map(|item| match item {
+                        Item::Slot(Value::Text(key), value) if key.as_str() == name => Some(value),
+                        _ => None,
+                    })
+                }
+                (Value::Record(_, items), Segment::Index(i)) => match items.get(*i) {
+                    Some(Item::ValueItem(value)) | Some(Item::Slot(_, value)) => Some(value),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .map(Cow::Borrowed)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Literal(String),
+    Selector(Selector),
+}
+
+/// A template for a node URI or lane name, consisting of literal segments and selectors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+impl FromStr for Template {
+    type Err = InvalidSelector;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .split('/')
+            .map(|segment| {
+                if segment.starts_with('$') {
+                    segment.parse().map(TemplatePart::Selector)
+                } else {
+                    Ok(TemplatePart::Literal(segment.to_string()))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Template { parts })
+    }
+}
+
+impl Template {
+    fn render(&self, message: &MessageView<'_>) -> Option<String> {
+        let mut rendered = String::new();
+        for (i, part) in self.parts.iter().enumerate() {
+            if i > 0 {
+                rendered.push('/');
+            }
+            match part {
+                TemplatePart::Literal(literal) => rendered.push_str(literal),
+                TemplatePart::Selector(selector) => match selector.select(message)?.as_ref() {
+                    Value::Text(text) => rendered.push_str(text.as_str()),
+                    Value::Int32Value(n) => rendered.push_str(&n.to_string()),
+                    Value::Int64Value(n) => rendered.push_str(&n.to_string()),
+                    Value::UInt32Value(n) => rendered.push_str(&n.to_string()),
+                    Value::UInt64Value(n) => rendered.push_str(&n.to_string()),
+                    Value::BigInt(n) => rendered.push_str(&n.to_string()),
+                    Value::BigUint(n) => rendered.push_str(&n.to_string()),
+                    Value::Float64Value(x) => rendered.push_str(&x.to_string()),
+                    Value::BooleanValue(p) => rendered.push_str(&p.to_string()),
+                    _ => return None,
+                },
+            }
+        }
+        Some(rendered)
+    }
+}
+
+/// A command to be sent to a lane.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Command {
+    pub node: String,
+    pub lane: String,
+    pub body: Value,
+}
+
+/// A [`CommandMapping`] where all of the selectors have been parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CommandRelay {
+    node: Template,
+    lane: Template,
+    body: Selector,
+}
+
+impl TryFrom<&CommandMapping> for CommandRelay {
+    type Error = InvalidSelector;
+
+    fn try_from(mapping: &CommandMapping) -> Result<Self, Self::Error> {
+        let CommandMapping { node, lane, body } = mapping;
+        Ok(CommandRelay {
+            node: node.parse()?,
+            lane: lane.parse()?,
+            body: body.parse()?,
+        })
+    }
+}
+
+impl CommandRelay {
+    /// Attempt to generate a command from a message. This will fail if any of the selectors do
+    /// not match.
+    pub fn select(&self, message: &MessageView<'_>) -> Option<Command> {
+        let CommandRelay { node, lane, body } = self;
+        Some(Command {
+            node: node.render(message)?,
+            lane: lane.render(message)?,
+            body: body.select(message)?.into_owned(),
+        })
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use swimos_model::{Item, Value};
+
+use super::{Command, CommandMapping, CommandRelay, InvalidSelector, MessageView, Selector};
+
+fn payload() -> Value {
+    Value::record(vec![
+        Item::slot("id", "a1"),
+        Item::slot("zone", 7),
+        Item::slot("readings", Value::from_vec(vec![1.5, 2.5])),
+        Item::slot("location", Value::record(vec![Item::slot("x", 1)])),
+    ])
+}
+
+fn view<'a>(key: &'a Value, payload: &'a Value) -> MessageView<'a> {
+    MessageView {
+        topic: "sensors",
+        partition: 3,
+        key,
+        payload,
+    }
+}
+
+fn select(selector: &str, message: &MessageView<'_>) -> Option<Value> {
+    let selector = selector.parse::<Selector>().expect("Invalid selector.");
+    selector.select(message).map(|v| v.into_owned())
+}
+
+#[test]
+fn parse_invalid_selectors() {
+    assert_eq!(
+        "payload".parse::<Selector>(),
+        Err(InvalidSelector::BadRoot("payload".to_string()))
+    );
+    assert_eq!(
+        "$value.id".parse::<Selector>(),
+        Err(InvalidSelector::BadRoot("$value.id".to_string()))
+    );
+    assert_eq!(
+        "$topic.name".parse::<Selector>(),
+        Err(InvalidSelector::UnexpectedPath("$topic.name".to_string()))
+    );
+    assert_eq!(
+        "$payload..id".parse::<Selector>(),
+        Err(InvalidSelector::EmptySegment("$payload..id".to_string()))
+    );
+}
+
+#[test]
+fn select_from_message() {
+    let key = Value::text("k");
+    let payload = payload();
+    let message = view(&key, &payload);
+
+    assert_eq!(select("$key", &message), Some(Value::text("k")));
+    assert_eq!(select("$payload", &message), Some(payload.clone()));
+    assert_eq!(select("$topic", &message), Some(Value::text("sensors")));
+    assert_eq!(select("$partition", &message), Some(Value::Int32Value(3)));
+    assert_eq!(select("$payload.id", &message), Some(Value::text("a1")));
+    assert_eq!(
+        select("$payload.readings.1", &message),
+        Some(Value::Float64Value(2.5))
+    );
+    assert_eq!(
+        select("$payload.location.x", &message),
+        Some(Value::Int32Value(1))
+    );
+    assert_eq!(select("$payload.0", &message), Some(Value::text("a1")));
+    assert_eq!(select("$payload.missing", &message), None);
+    assert_eq!(select("$payload.readings.5", &message), None);
+    assert_eq!(select("$key.id", &message), None);
+}
+
+#[test]
+fn relay_generates_command() {
+    let key = Value::text("k");
+    let payload = payload();
+    let message = view(&key, &payload);
+
+    let mapping = CommandMapping::new("/zones/$payload.zone/$payload.id", "readings")
+        .with_body("$payload.readings");
+    let relay = CommandRelay::try_from(&mapping).expect("Invalid mapping.");
+    assert_eq!(
+        relay.select(&message),
+        Some(Command {
+            node: "/zones/7/a1".to_string(),
+            lane: "readings".to_string(),
+            body: Value::from_vec(vec![1.5, 2.5]),
+        })
+    );
+
+    let mapping = CommandMapping::new("/sensors/$key", "$topic");
+    let relay = CommandRelay::try_from(&mapping).expect("Invalid mapping.");
+    assert_eq!(
+        relay.select(&message),
+        Some(Command {
+            node: "/sensors/k".to_string(),
+            lane: "sensors".to_string(),
+            body: payload.clone(),
+        })
+    );
+}
+
+#[test]
+fn relay_requires_simple_values_in_templates() {
+    let key = Value::text("k");
+    let payload = payload();
+    let message = view(&key, &payload);
+
+    let mapping = CommandMapping::new("/sensors/$payload.location", "lane");
+    let relay = CommandRelay::try_from(&mapping).expect("Invalid mapping.");
+    assert_eq!(relay.select(&message), None);
+
+    let mapping = CommandMapping::new("/sensors/$payload.missing", "lane");
+    let relay = CommandRelay::try_from(&mapping).expect("Invalid mapping.");
+    assert_eq!(relay.select(&message), None);
+}
+
+#[test]
+fn invalid_mapping() {
+    let mapping = CommandMapping::new("/sensors/$id", "lane");
+    assert_eq!(
+        CommandRelay::try_from(&mapping),
+        Err(InvalidSelector::BadRoot("$id".to_string()))
+    );
+}
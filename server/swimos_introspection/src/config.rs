@@ -26,8 +26,15 @@ pub struct IntrospectionConfig {
     pub node_pulse_interval: Duration,
     /// Frequency at which the lane meta agents will generate an uplink pulse.
     pub lane_pulse_interval: Duration,
+    /// Frequency at which the mesh meta agent will generate a pulse.
+    pub mesh_pulse_interval: Duration,
     /// Size of the buffer for registering new lanes with the introspection system.
     pub registration_channel_size: NonZeroUsize,
+    /// Allow the traffic of the server to be captured: this exposes the remote meta-agents (which
+    /// capture the raw frames exchanged with each remote) and the lanes of the node meta-agents
+    /// that record the envelopes of each agent. Any client that can reach the server could then
+    /// read the traffic of other clients so this is disabled by default.
+    pub capture_traffic: bool,
 }
 
 impl Default for IntrospectionConfig {
@@ -35,7 +42,9 @@ impl Default for IntrospectionConfig {
         Self {
             node_pulse_interval: DEFAULT_PULSE_INTERVAL,
             lane_pulse_interval: DEFAULT_PULSE_INTERVAL,
+            mesh_pulse_interval: DEFAULT_PULSE_INTERVAL,
             registration_channel_size: DEFAULT_REG_CHANNEL_SIZE,
+            capture_traffic: false,
         }
     }
 }
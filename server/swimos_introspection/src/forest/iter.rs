@@ -58,18 +58,22 @@ pub struct UriForestIterator<'l, D> {
     uri_stack: VecDeque<String>,
     /// A stack of searches that are being performed and a cursor signalling the depth.
     op_stack: VecDeque<usize>,
+    /// Whether the nodes should be visited in order.
+    ordered: bool,
 }
 
 impl<'l, D> UriForestIterator<'l, D> {
     pub(crate) fn new(
         prefix: String,
         nodes: &'l HashMap<SmolStr, TreeNode<D>>,
+        ordered: bool,
     ) -> UriForestIterator<'l, D> {
         UriForestIterator {
             prefix,
-            visit: VecDeque::from_iter(nodes),
+            visit: VecDeque::from(children(nodes, ordered)),
             uri_stack: VecDeque::default(),
             op_stack: VecDeque::new(),
+            ordered,
         }
     }
 }
@@ -85,6 +89,7 @@ impl<'l, D> Iterator for UriForestIterator<'l, D> {
             visit,
             uri_stack,
             op_stack,
+            ordered,
         } = self;
 
         loop {
@@ -102,7 +107,7 @@ impl<'l, D> Iterator for UriForestIterator<'l, D> {
                     (format!("{}/{}", prefix, suffix), data)
                 });
 
-                dfs(node, visit, uri_stack, op_stack);
+                dfs(node, visit, uri_stack, op_stack, *ordered);
 
                 if let Some(ret) = ret {
                     return Some(ret);
@@ -125,14 +130,20 @@ pub struct UriPartIterator<'l, D> {
     uri_stack: VecDeque<String>,
     /// A stack of searches that are being performed and a cursor signalling the depth.
     op_stack: VecDeque<usize>,
+    /// Whether the nodes should be visited in order.
+    ordered: bool,
 }
 
 impl<'l, D> UriPartIterator<'l, D> {
-    pub(crate) fn new(nodes: &'l HashMap<SmolStr, TreeNode<D>>) -> UriPartIterator<'l, D> {
+    pub(crate) fn new(
+        nodes: &'l HashMap<SmolStr, TreeNode<D>>,
+        ordered: bool,
+    ) -> UriPartIterator<'l, D> {
         UriPartIterator {
-            visit: VecDeque::from_iter(nodes),
+            visit: VecDeque::from(children(nodes, ordered)),
             uri_stack: Default::default(),
             op_stack: Default::default(),
+            ordered,
         }
     }
 }
@@ -145,6 +156,7 @@ impl<'l, D> Iterator for UriPartIterator<'l, D> {
             visit,
             uri_stack,
             op_stack,
+            ordered,
         } = self;
 
         loop {
@@ -175,7 +187,7 @@ impl<'l, D> Iterator for UriPartIterator<'l, D> {
                         path: make_uri(),
                         data,
                     });
-                    dfs(node, visit, uri_stack, op_stack);
+                    dfs(node, visit, uri_stack, op_stack, *ordered);
                     ret
                 };
 
@@ -187,6 +199,18 @@ impl<'l, D> Iterator for UriPartIterator<'l, D> {
     }
 }
 
+/// Collects the children of a node, sorted by their path segments if 'ordered' is set.
+fn children<D>(
+    nodes: &HashMap<SmolStr, TreeNode<D>>,
+    ordered: bool,
+) -> Vec<(&SmolStr, &TreeNode<D>)> {
+    let mut children = Vec::from_iter(nodes);
+    if ordered {
+        children.sort_unstable_by_key(|(segment, _)| *segment);
+    }
+    children
+}
+
 /// Performs a depth-first search from 'node'. Populating the visit stack with the next nodes to
 /// visit or if there are no reachable nodes from 'node', then drains the URI stack back up to the
 /// next node to visit.
@@ -195,10 +219,12 @@ fn dfs<'l, D>(
     visit_stack: &mut VecDeque<(&'l SmolStr, &'l TreeNode<D>)>,
     uri_stack: &mut VecDeque<String>,
     op_stack: &mut VecDeque<usize>,
+    ordered: bool,
 ) {
     if node.has_descendants() {
-        // Insert the next collection of nodes to search
-        for (key, descendant) in &node.descendants {
+        // Insert the next collection of nodes to search (in reverse, so that the first child will
+        // be visited first)
+        for (key, descendant) in children(&node.descendants, ordered).into_iter().rev() {
             visit_stack.push_front((key, descendant));
         }
     } else {
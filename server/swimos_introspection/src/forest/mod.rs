@@ -57,21 +57,35 @@ static_assertions::assert_impl_all!(UriForest<()>: Send, Sync);
 pub struct UriForest<D> {
     /// A collection of trees in this forest.
     trees: HashMap<SmolStr, TreeNode<D>>,
+    /// Whether the iterators should yield URIs in order.
+    ordered: bool,
 }
 
 impl<D> Default for UriForest<D> {
     fn default() -> Self {
         UriForest {
             trees: HashMap::default(),
+            ordered: false,
         }
     }
 }
 
 impl<D> UriForest<D> {
-    /// Constructs a new URI forest.
+    /// Constructs a new URI forest. The iterators over the forest will yield the URIs in an
+    /// arbitrary order.
     pub fn new() -> UriForest<D> {
         UriForest {
             trees: HashMap::new(),
+            ordered: false,
+        }
+    }
+
+    /// Constructs a new URI forest where the iterators over the forest will yield the URIs in
+    /// lexicographic order of their path segments.
+    pub fn ordered() -> UriForest<D> {
+        UriForest {
+            trees: HashMap::new(),
+            ordered: true,
         }
     }
 
@@ -82,7 +96,7 @@ impl<D> UriForest<D> {
 
     /// Inserts 'uri' into this forest and associates 'node_data' with it.
     pub fn insert(&mut self, uri: &str, node_data: D) {
-        let UriForest { trees } = self;
+        let UriForest { trees, .. } = self;
         let mut segment_iter = PathSegmentIterator::new(uri).peekable();
 
         if let Some(segment) = segment_iter.next() {
@@ -107,7 +121,7 @@ impl<D> UriForest<D> {
 
     /// Attempts to remove 'uri' from this forest, returning any associated data.
     pub fn remove(&mut self, uri: &str) -> Option<D> {
-        let UriForest { trees } = self;
+        let UriForest { trees, .. } = self;
         let mut segment_iter = PathSegmentIterator::new(uri).peekable();
 
         match segment_iter.next() {
@@ -144,7 +158,7 @@ impl<D> UriForest<D> {
 
     /// Returns an optional mutable reference to the data associated at 'uri'
     pub fn get_mut(&mut self, uri: &str) -> Option<&mut D> {
-        let UriForest { trees } = self;
+        let UriForest { trees, .. } = self;
         let mut segment_iter = PathSegmentIterator::new(uri).peekable();
 
         match segment_iter.next() {
@@ -177,9 +191,8 @@ impl<D> UriForest<D> {
     }
 
     /// Returns whether this URI forest contains 'uri'.
-    #[cfg(test)]
     pub fn contains_uri(&self, uri: &str) -> bool {
-        let UriForest { trees } = self;
+        let UriForest { trees, .. } = self;
         let mut segment_iter = PathSegmentIterator::new(uri).peekable();
 
         match segment_iter.next() {
@@ -223,15 +236,15 @@ impl<D> UriForest<D> {
 
     /// Returns an iterator that will yield every URI in the forest.
     pub fn uri_iter(&self) -> UriForestIterator<'_, D> {
-        let UriForest { trees } = self;
-        UriForestIterator::new("".to_string(), trees)
+        let UriForest { trees, ordered } = self;
+        UriForestIterator::new("".to_string(), trees, *ordered)
     }
 
     /// Returns an iterator that yields URI parts; either a leaf item containing node data or a
     /// junction item containing the number of descendants.
     pub fn part_iter(&self) -> UriPartIterator<'_, D> {
-        let UriForest { trees } = self;
-        UriPartIterator::new(trees)
+        let UriForest { trees, ordered } = self;
+        UriPartIterator::new(trees, *ordered)
     }
 }
 
@@ -391,7 +404,6 @@ impl<D> TreeNode<D> {
         self.descendants.get_mut(segment)
     }
 
-    #[cfg(test)]
     fn get_descendant(&self, segment: &str) -> Option<&TreeNode<D>> {
         self.descendants.get(segment)
     }
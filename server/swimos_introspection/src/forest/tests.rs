@@ -53,6 +53,33 @@ fn iters() {
     );
 }
 
+#[test]
+fn ordered_uri_iter() {
+    let mut forest = UriForest::ordered();
+
+    forest.insert("/unit/2/cnt/1", ());
+    forest.insert("/unit/1/cnt/s/1", ());
+    forest.insert("/listener/2", ());
+    forest.insert("/unit/1/cnt/2", ());
+    forest.insert("/unit/1", ());
+    forest.insert("/listener/1", ());
+    forest.insert("/unit/1/blah/", ());
+
+    let all_uris = forest.uri_iter().map(|(uri, _)| uri).collect::<Vec<_>>();
+    assert_eq!(
+        all_uris,
+        vec![
+            "/listener/1".to_string(),
+            "/listener/2".to_string(),
+            "/unit/1".to_string(),
+            "/unit/1/blah".to_string(),
+            "/unit/1/cnt/2".to_string(),
+            "/unit/1/cnt/s/1".to_string(),
+            "/unit/2/cnt/1".to_string(),
+        ]
+    );
+}
+
 #[test]
 fn contains() {
     let mut forest = UriForest::new();
@@ -280,3 +307,31 @@ fn uri_part_iter() {
 
     assert_eq!(actual, expected)
 }
+
+#[test]
+fn ordered_uri_part_iter() {
+    let mut forest = UriForest::ordered();
+
+    forest.insert("/unit/1/cnt/3", ());
+    forest.insert("/listener", ());
+    forest.insert("/agent/1", ());
+    forest.insert("/unit/2/cnt/3", ());
+
+    let actual = forest.part_iter().collect::<Vec<_>>();
+    let expected = vec![
+        UriPart::Junction {
+            path: "/agent".to_string(),
+            descendants: 1,
+        },
+        UriPart::Leaf {
+            path: "/listener".to_string(),
+            data: &(),
+        },
+        UriPart::Junction {
+            path: "/unit".to_string(),
+            descendants: 2,
+        },
+    ];
+
+    assert_eq!(actual, expected)
+}
@@ -16,17 +16,22 @@
 //!
 //! Adds support for introspection to a Swim server.
 //!
-//! - The [`register_introspection`] will add special meta-agents to export information about running agents.
+//! - The [`register_introspection`] will add special meta-agents to export information about running agents
+//!   and to capture the frames exchanged with connected remotes.
 //! - The [`IntrospectionResolver`] type is used by the server to register normal agents for introspection.
 
 mod config;
 mod forest;
 mod meta_agent;
 mod meta_mesh;
+mod meta_remote;
 mod model;
 mod route;
 mod task;
 
 pub use config::IntrospectionConfig;
-pub use route::{lane_pattern, mesh_pattern, node_pattern};
+pub use forest::UriForest;
+pub use meta_mesh::AgentRecovery;
+pub use meta_remote::NoSuchRemote;
+pub use route::{lane_pattern, mesh_pattern, node_pattern, remote_pattern};
 pub use task::{register_introspection, AgentRegistration, IntrospectionResolver};
@@ -64,7 +64,7 @@ impl Agent for LaneMetaAgent {
     ) -> BoxFuture<'static, AgentInitResult> {
         let LaneMetaAgent { config, resolver } = self;
         run_init(
-            config.node_pulse_interval,
+            config.lane_pulse_interval,
             resolver.clone(),
             route,
             route_params,
@@ -119,12 +119,17 @@ async fn run_task(
     pulse_io: Io,
 ) -> Result<(), AgentTaskError> {
     let (_shutdown_tx, shutdown_rx) = trigger::trigger();
+    let LaneView { report_reader, .. } = view;
+    let event_reader = report_reader.clone();
     run_pulse_lane(
         shutdown_rx,
         pulse_interval,
-        view.report_reader,
+        report_reader,
         pulse_io,
-        |uplink_pulse| LanePulse { uplink_pulse },
+        move |uplink_pulse| LanePulse {
+            uplink_pulse,
+            last_event: event_reader.last_event(),
+        },
     )
     .await
     .map_err(|error| AgentTaskError::BadFrame {
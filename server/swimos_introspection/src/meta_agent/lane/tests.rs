@@ -80,7 +80,9 @@ async fn run_lane_meta_agent() {
 
             let mut receiver = PulseLaneReader::new(rx);
             // The pulse lane should clear the events and commands when it starts to create a clean baseline.
-            receiver.expect_pulse(3, 0, 0).await;
+            // The time of the last event is retained.
+            let last_event = receiver.expect_pulse(3, 0, 0).await;
+            assert!(last_event.is_some());
             drop(receiver);
             drop(lanes);
         },
@@ -126,13 +128,17 @@ impl<'a> PulseLaneReader<'a> {
         }
     }
 
-    async fn expect_pulse(&mut self, links: u64, events: u64, commands: u64) {
+    async fn expect_pulse(&mut self, links: u64, events: u64, commands: u64) -> Option<u64> {
         let PulseLaneReader { inner } = self;
         match inner.next().await {
-            Some(Ok(LaneResponse::StandardEvent(LanePulse { uplink_pulse }))) => {
+            Some(Ok(LaneResponse::StandardEvent(LanePulse {
+                uplink_pulse,
+                last_event,
+            }))) => {
                 assert_eq!(uplink_pulse.link_count, links);
                 assert_eq!(uplink_pulse.event_count, events);
                 assert_eq!(uplink_pulse.command_count, commands);
+                last_event
             }
             ow => panic!("Unexpected response: {:?}", ow),
         }
@@ -79,7 +79,10 @@ where
     .await
 }
 
-fn sleep_stream(pulse_interval: Duration, sleep: Pin<&mut Sleep>) -> impl Stream<Item = ()> + '_ {
+pub(crate) fn sleep_stream(
+    pulse_interval: Duration,
+    sleep: Pin<&mut Sleep>,
+) -> impl Stream<Item = ()> + '_ {
     unfold(sleep, move |mut sleep| {
         let new_timeout = Instant::now()
             .checked_add(pulse_interval)
@@ -146,7 +149,11 @@ where
         };
 
         match result.transpose()? {
-            Some(LaneRequest::Sync(id)) => {
+            Some(
+                LaneRequest::Sync(id)
+                | LaneRequest::SyncSince(id, _)
+                | LaneRequest::SyncRange(id, _),
+            ) => {
                 let synced = LaneResponse::SyncEvent(id, &last_pulse);
                 output.send(synced).await?;
 
@@ -169,7 +176,7 @@ where
 
 #[derive(Debug, Error)]
 #[error("Invalid introspection URI: {route}. Missing parameter: {missing}")]
-struct MetaRouteError {
+pub(crate) struct MetaRouteError {
     route: RouteUri,
     missing: String,
 }
@@ -12,32 +12,48 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashMap, pin::pin, time::Duration};
+use std::{collections::HashMap, fmt::Write, pin::pin, time::Duration};
 
-use crate::{config::IntrospectionConfig, meta_agent::run_pulse_lane, route::NODE_PARAM};
+use crate::{
+    config::IntrospectionConfig,
+    meta_agent::{run_pulse_lane, sleep_stream},
+    meta_remote::{parse_bool, text_response},
+    route::NODE_PARAM,
+};
+use bytes::Bytes;
 use futures::{
-    future::{BoxFuture, Either},
-    FutureExt, SinkExt, StreamExt,
+    future::{join_all, select_all, BoxFuture},
+    FutureExt, SinkExt, StreamExt, TryFutureExt,
 };
 use swimos_agent_protocol::{
-    encoding::lane::{MapLaneResponseEncoder, RawValueLaneRequestDecoder},
+    encoding::lane::{
+        MapLaneResponseEncoder, RawValueLaneRequestDecoder, ValueLaneResponseEncoder,
+    },
     LaneRequest, LaneResponse, MapOperation,
 };
 use swimos_api::{
-    agent::{Agent, AgentConfig, AgentContext, AgentInitResult, WarpLaneKind},
+    agent::{
+        Agent, AgentConfig, AgentContext, AgentInitResult, HttpLaneRequestChannel, WarpLaneKind,
+    },
     error::{AgentInitError, AgentTaskError, FrameIoError},
+    http::{Method, StatusCode},
 };
-use swimos_meta::{LaneInfo, NodePulse};
+use swimos_meta::{LaneInfo, LogEntry, LogLevel, NodePulse, RemotePruned};
 use swimos_model::Text;
+use swimos_runtime::agent::{
+    recording::EnvelopeRecording,
+    reporting::{DeprecationEvent, DeprecationReportReader, PruneEvent, PruneReportReader},
+};
 use swimos_utilities::{
     byte_channel::{ByteReader, ByteWriter},
     routing::RouteUri,
     trigger,
 };
+use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::codec::{FramedRead, FramedWrite};
+use tracing::debug;
 
 use crate::{model::AgentIntrospectionHandle, task::IntrospectionResolver};
-use futures::future::select;
 
 use super::{MetaRouteError, PULSE_LANE};
 
@@ -45,9 +61,18 @@ use super::{MetaRouteError, PULSE_LANE};
 mod tests;
 
 const LANES_LANE: &str = "lanes";
+const PRUNED_LANE: &str = "pruned";
+const RECORDING_LANE: &str = "recording";
+const ENVELOPES_LANE: &str = "envelopes";
+const WARN_LOG_LANE: &str = "warnLog";
 
-/// A meta agent providing information on the lanes of an agent and aggregate statistics on
-/// the uplinks for all of its lanes. The meta agent extracts the target node URI from its own
+/// A meta agent providing information on the lanes of an agent, aggregate statistics on
+/// the uplinks for all of its lanes and the remotes that are pruned from the agent. Warnings, such
+/// as remotes addressing lanes by deprecated aliases, are emitted on the `warnLog` lane. If traffic
+/// capture is enabled in the [`IntrospectionConfig`], it also allows the envelopes received and
+/// sent by the agent to be recorded: recording is toggled by sending a boolean to the `recording`
+/// value lane and the recorded envelopes can be downloaded, one per line, with a GET request to the
+/// `envelopes` HTTP lane (a DELETE request to the same lane will discard them). The meta agent extracts the target node URI from its own
 /// node URI and then attempts to resolve the introspection handle during it's initialization
 /// phase. If the node cannot be resolved, the meta-agent will fail to start with an appropriate
 /// error.
@@ -73,6 +98,7 @@ impl Agent for NodeMetaAgent {
         let NodeMetaAgent { config, resolver } = self;
         run_init(
             config.node_pulse_interval,
+            config.capture_traffic,
             resolver.clone(),
             route,
             route_params,
@@ -85,6 +111,7 @@ impl Agent for NodeMetaAgent {
 
 async fn run_init(
     pulse_interval: Duration,
+    capture_traffic: bool,
     resolver: IntrospectionResolver,
     route: RouteUri,
     route_params: HashMap<String, String>,
@@ -99,7 +126,7 @@ async fn run_init(
         )));
     };
 
-    let handle = match resolver.resolve_agent(node_uri).await {
+    let handle = match resolver.resolve_agent(node_uri.clone()).await {
         Ok(handle) => handle,
         Err(e) => return Err(AgentInitError::UserCodeError(Box::new(e))),
     };
@@ -111,61 +138,147 @@ async fn run_init(
     let lanes_io = context
         .add_lane(LANES_LANE, WarpLaneKind::DemandMap, lane_config)
         .await?;
-    Ok(run_task(context, pulse_interval, handle, pulse_io, lanes_io).boxed())
+    let pruned_io = context
+        .add_lane(PRUNED_LANE, WarpLaneKind::Supply, lane_config)
+        .await?;
+    let warn_log_io = context
+        .add_lane(WARN_LOG_LANE, WarpLaneKind::Supply, lane_config)
+        .await?;
+    let recording = match handle.recording() {
+        Some(recording) if capture_traffic => {
+            let recording_io = context
+                .add_lane(RECORDING_LANE, WarpLaneKind::Value, lane_config)
+                .await?;
+            let envelopes_rx = context.add_http_lane(ENVELOPES_LANE).await?;
+            Some(RecordingLanesIo {
+                recording,
+                recording_io,
+                envelopes_rx,
+            })
+        }
+        _ => None,
+    };
+    Ok(run_task(
+        context,
+        pulse_interval,
+        handle,
+        node_uri,
+        NodeLanesIo {
+            pulse_io,
+            lanes_io,
+            pruned_io,
+            warn_log_io,
+            recording,
+        },
+    )
+    .boxed())
 }
 
 type Io = (ByteWriter, ByteReader);
 
+struct NodeLanesIo {
+    pulse_io: Io,
+    lanes_io: Io,
+    pruned_io: Io,
+    warn_log_io: Io,
+    recording: Option<RecordingLanesIo>,
+}
+
+/// The lanes that are only opened if traffic capture is enabled.
+struct RecordingLanesIo {
+    recording: EnvelopeRecording,
+    recording_io: Io,
+    envelopes_rx: HttpLaneRequestChannel,
+}
+
+fn bad_frame(lane: &str) -> impl FnOnce(FrameIoError) -> AgentTaskError + '_ {
+    move |error| AgentTaskError::BadFrame {
+        lane: Text::new(lane),
+        error,
+    }
+}
+
 async fn run_task(
     context: Box<dyn AgentContext + Send>,
     pulse_interval: Duration,
     handle: AgentIntrospectionHandle,
-    pulse_io: Io,
-    lanes_io: Io,
+    node_uri: Text,
+    io: NodeLanesIo,
 ) -> Result<(), AgentTaskError> {
     // deferred drop so the agent doesn't terminate early.
     let _context = context;
 
+    let NodeLanesIo {
+        pulse_io,
+        lanes_io,
+        pruned_io,
+        warn_log_io,
+        recording,
+    } = io;
     let report_reader = handle.aggregate_reader();
+    let prune_reader = handle.prune_reader();
+    let deprecation_reader = handle.deprecation_reader();
     let (shutdown_tx, shutdown_rx) = trigger::trigger();
-    let pulse_lane = pin!(run_pulse_lane(
+    let pulse_lane = run_pulse_lane(
         shutdown_rx.clone(),
         pulse_interval,
         report_reader,
         pulse_io,
         |uplinks| NodePulse { uplinks },
-    ));
-    let lanes_lane = pin!(run_lanes_descriptor_lane(shutdown_rx, handle, lanes_io));
-
-    match select(pulse_lane, lanes_lane).await {
-        Either::Left((result, fut)) => {
-            shutdown_tx.trigger();
-            let _ = fut.await;
-            result.map_err(|error| AgentTaskError::BadFrame {
-                lane: Text::new(PULSE_LANE),
-                error,
-            })
-        }
-        Either::Right((result, fut)) => {
-            shutdown_tx.trigger();
-            let _ = fut.await;
-            result.map_err(|error| AgentTaskError::BadFrame {
-                lane: Text::new(LANES_LANE),
-                error,
-            })
-        }
+    )
+    .map_err(bad_frame(PULSE_LANE));
+    let pruned_lane = run_pruned_lane(shutdown_rx.clone(), pulse_interval, prune_reader, pruned_io)
+        .map_err(bad_frame(PRUNED_LANE));
+    let warn_log_lane = run_warn_log_lane(
+        shutdown_rx.clone(),
+        pulse_interval,
+        deprecation_reader,
+        node_uri,
+        warn_log_io,
+    )
+    .map_err(bad_frame(WARN_LOG_LANE));
+    let mut lane_tasks = vec![
+        pulse_lane.boxed(),
+        pruned_lane.boxed(),
+        warn_log_lane.boxed(),
+    ];
+    if let Some(RecordingLanesIo {
+        recording,
+        recording_io,
+        envelopes_rx,
+    }) = recording
+    {
+        let recording_lane =
+            run_recording_lane(shutdown_rx.clone(), recording.clone(), recording_io)
+                .map_err(bad_frame(RECORDING_LANE));
+        let envelopes_lane =
+            run_envelopes_lane(shutdown_rx.clone(), recording, envelopes_rx).map(Ok);
+        lane_tasks.push(recording_lane.boxed());
+        lane_tasks.push(envelopes_lane.boxed());
     }
+    let lanes_lane = run_lanes_descriptor_lane(shutdown_rx, pulse_interval, handle, lanes_io)
+        .map_err(bad_frame(LANES_LANE));
+    lane_tasks.push(lanes_lane.boxed());
+
+    let (result, _, remaining) = select_all(lane_tasks).await;
+    shutdown_tx.trigger();
+    join_all(remaining).await;
+    result
 }
 
 /// A lane that will return information on all of the lanes of an agent, as a map, when a Sync
-/// request is sent to the lane.
+/// request is sent to the lane. The lanes of the agent are checked for changes on a fixed
+/// schedule (and before each sync) and an event is emitted for each lane that has been opened or
+/// closed since the last check.
 ///
 /// # Arguments
 /// * `shutdown_rx` - Shutdown signal for when the agent is stopping.
+/// * `interval` - Interval on which to check for changes to the lanes of the agent.
 /// * `handle` - Introspection handle used to refresh the view of the lanes.
 /// * lanes_io` - The input and output channels for the lane.
 async fn run_lanes_descriptor_lane(
     shutdown_rx: trigger::Receiver,
+    interval: Duration,
     mut handle: AgentIntrospectionHandle,
     lanes_io: Io,
 ) -> Result<(), FrameIoError> {
@@ -181,16 +294,54 @@ async fn run_lanes_descriptor_lane(
         return Ok(());
     };
 
-    while let Some(request) = input.next().await.transpose()? {
-        if let LaneRequest::Sync(id) = request {
-            if handle.changed() {
-                snapshot = if let Some(s) = handle.new_snapshot() {
-                    s
-                } else {
-                    return Ok(());
-                };
+    let sleep = pin!(tokio::time::sleep(interval));
+    let mut checks = pin!(sleep_stream(interval, sleep));
+
+    loop {
+        let sync_id = tokio::select! {
+            biased;
+            maybe_request = input.next() => match maybe_request.transpose()? {
+                Some(LaneRequest::Sync(id) | LaneRequest::SyncSince(id, _) | LaneRequest::SyncRange(id, _)) => Some(id),
+                Some(_) => None,
+                None => break Ok(()),
+            },
+            _ = checks.next() => None,
+        };
+
+        if handle.changed() {
+            let new_snapshot = if let Some(s) = handle.new_snapshot() {
+                s
+            } else {
+                return Ok(());
+            };
+            let previous: HashMap<_, _> = snapshot
+                .lane_info()
+                .map(|info| (info.lane_uri.clone(), info))
+                .collect();
+            let current: HashMap<_, _> = new_snapshot
+                .lane_info()
+                .map(|info| (info.lane_uri.clone(), info))
+                .collect();
+            for (name, lane_info) in &current {
+                if previous.get(name) != Some(lane_info) {
+                    let op = MapOperation::Update {
+                        key: name.as_str(),
+                        value: lane_info,
+                    };
+                    output.send(LaneResponse::StandardEvent(op)).await?;
+                }
             }
+            for name in previous.keys() {
+                if !current.contains_key(name) {
+                    let op: MapOperation<&str, &LaneInfo> =
+                        MapOperation::Remove { key: name.as_str() };
+                    output.send(LaneResponse::StandardEvent(op)).await?;
+                }
+            }
+            snapshot = new_snapshot;
+        }
 
+        if let Some(id) = sync_id {
             for lane_info in snapshot.lane_info() {
                 let op = MapOperation::Update {
                     key: lane_info.lane_uri.as_str(),
@@ -202,5 +353,192 @@ async fn run_lanes_descriptor_lane(
             output.send(synced).await?;
         }
     }
+}
+
+/// A lane that will emit an event for each remote that is pruned from the agent. The records of
+/// the pruned remotes are collected on a fixed schedule.
+///
+/// # Arguments
+/// * `shutdown_rx` - Shutdown signal for when the agent is stopping.
+/// * `interval` - Interval on which to collect the records of pruned remotes.
+/// * `prune_reader` - Reader for the records of the pruned remotes.
+/// * `pruned_io` - The input and output channels for the lane.
+async fn run_pruned_lane(
+    shutdown_rx: trigger::Receiver,
+    interval: Duration,
+    prune_reader: PruneReportReader,
+    pruned_io: Io,
+) -> Result<(), FrameIoError> {
+    let (tx, rx) = pruned_io;
+
+    let mut input =
+        FramedRead::new(rx, RawValueLaneRequestDecoder::default()).take_until(shutdown_rx);
+    let mut output = FramedWrite::new(tx, ValueLaneResponseEncoder::default());
+
+    let sleep = pin!(tokio::time::sleep(interval));
+    let mut collections = pin!(sleep_stream(interval, sleep));
+    // Once the agent stops, the lane continues to serve sync requests until it is shut down.
+    let mut active = true;
+
+    loop {
+        tokio::select! {
+            biased;
+            maybe_request = input.next() => match maybe_request.transpose()? {
+                Some(LaneRequest::Sync(id) | LaneRequest::SyncSince(id, _) | LaneRequest::SyncRange(id, _)) => {
+                    let synced: LaneResponse<RemotePruned> = LaneResponse::Synced(id);
+                    output.send(synced).await?;
+                }
+                Some(_) => {}
+                None => break Ok(()),
+            },
+            _ = collections.next(), if active => {
+                if let Some(events) = prune_reader.take_events() {
+                    for PruneEvent { remote_id, idle_for } in events {
+                        let record = RemotePruned {
+                            remote_id: Text::from(remote_id.to_string()),
+                            idle_time: u64::try_from(idle_for.as_millis()).unwrap_or(u64::MAX),
+                        };
+                        output.send(LaneResponse::StandardEvent(&record)).await?;
+                    }
+                } else {
+                    active = false;
+                }
+            }
+        }
+    }
+}
+
+/// A lane that will emit a warning log entry the first time that each remote addresses a lane of
+/// the agent by a deprecated alias. The records are collected on a fixed schedule.
+///
+/// # Arguments
+/// * `shutdown_rx` - Shutdown signal for when the agent is stopping.
+/// * `interval` - Interval on which to collect the records of deprecated aliases.
+/// * `deprecation_reader` - Reader for the records of deprecated aliases.
+/// * `node_uri` - The node URI of the agent.
+/// * `warn_log_io` - The input and output channels for the lane.
+async fn run_warn_log_lane(
+    shutdown_rx: trigger::Receiver,
+    interval: Duration,
+    deprecation_reader: DeprecationReportReader,
+    node_uri: Text,
+    warn_log_io: Io,
+) -> Result<(), FrameIoError> {
+    let (tx, rx) = warn_log_io;
+
+    let mut input =
+        FramedRead::new(rx, RawValueLaneRequestDecoder::default()).take_until(shutdown_rx);
+    let mut output = FramedWrite::new(tx, ValueLaneResponseEncoder::default());
+
+    let sleep = pin!(tokio::time::sleep(interval));
+    let mut collections = pin!(sleep_stream(interval, sleep));
+    // Once the agent stops, the lane continues to serve sync requests until it is shut down.
+    let mut active = true;
+
+    loop {
+        tokio::select! {
+            biased;
+            maybe_request = input.next() => match maybe_request.transpose()? {
+                Some(LaneRequest::Sync(id) | LaneRequest::SyncSince(id, _) | LaneRequest::SyncRange(id, _)) => {
+                    let synced: LaneResponse<LogEntry> = LaneResponse::Synced(id);
+                    output.send(synced).await?;
+                }
+                Some(_) => {}
+                None => break Ok(()),
+            },
+            _ = collections.next(), if active => {
+                if let Some(events) = deprecation_reader.take_events() {
+                    for DeprecationEvent { remote_id, alias, lane } in events {
+                        let message = format!(
+                            "Remote {} addressed lane '{}' by its deprecated alias '{}'.",
+                            remote_id, lane, alias
+                        );
+                        let entry = LogEntry::new(LogLevel::Warn, node_uri.clone(), lane, message);
+                        output.send(LaneResponse::StandardEvent(&entry)).await?;
+                    }
+                } else {
+                    active = false;
+                }
+            }
+        }
+    }
+}
+
+/// A value lane that reports whether the envelopes of the agent are being recorded. Commands sent
+/// to the lane start or stop the recording.
+///
+/// # Arguments
+/// * `shutdown_rx` - Shutdown signal for when the agent is stopping.
+/// * `recording` - The recording of the envelopes of the agent.
+/// * `recording_io` - The input and output channels for the lane.
+async fn run_recording_lane(
+    shutdown_rx: trigger::Receiver,
+    recording: EnvelopeRecording,
+    recording_io: Io,
+) -> Result<(), FrameIoError> {
+    let (tx, rx) = recording_io;
+
+    let mut input =
+        FramedRead::new(rx, RawValueLaneRequestDecoder::default()).take_until(shutdown_rx);
+    let mut output = FramedWrite::new(tx, ValueLaneResponseEncoder::default());
+
+    while let Some(request) = input.next().await.transpose()? {
+        match request {
+            LaneRequest::Command(body)
+            | LaneRequest::CommandFrom(_, body)
+            | LaneRequest::CorrelatedCommand(_, _, body) => {
+                let enabled = parse_bool(&body)?;
+                debug!(enabled, "Setting envelope recording for agent.");
+                recording.set_enabled(enabled);
+                output.send(LaneResponse::StandardEvent(enabled)).await?;
+            }
+            LaneRequest::Sync(id)
+            | LaneRequest::SyncSince(id, _)
+            | LaneRequest::SyncRange(id, _) => {
+                output
+                    .send(LaneResponse::SyncEvent(id, recording.is_enabled()))
+                    .await?;
+                output.send(LaneResponse::<bool>::Synced(id)).await?;
+            }
+            LaneRequest::InitComplete => {}
+        }
+    }
     Ok(())
 }
+
+/// An HTTP lane from which the recorded envelopes can be downloaded, as plain text with one
+/// envelope per line. The download can be replayed against an agent with
+/// [`swimos_runtime::agent::recording::EnvelopeReplay`].
+///
+/// # Arguments
+/// * `shutdown_rx` - Shutdown signal for when the agent is stopping.
+/// * `recording` - The recording of the envelopes of the agent.
+/// * `envelopes_rx` - Channel for the requests to the lane.
+async fn run_envelopes_lane(
+    shutdown_rx: trigger::Receiver,
+    recording: EnvelopeRecording,
+    envelopes_rx: HttpLaneRequestChannel,
+) {
+    let mut requests = ReceiverStream::new(envelopes_rx).take_until(shutdown_rx);
+    while let Some(request) = requests.next().await {
+        let (request, response_tx) = request.into_parts();
+        let response = match request.method {
+            Method::GET => {
+                let mut body = String::new();
+                for record in recording.envelopes() {
+                    writeln!(body, "{}", record)
+                        .expect("Writing to a string should be infallible.");
+                }
+                text_response(StatusCode::OK, Bytes::from(body))
+            }
+            Method::DELETE => {
+                recording.clear();
+                text_response(StatusCode::NO_CONTENT, Bytes::new())
+            }
+            _ => text_response(StatusCode::METHOD_NOT_ALLOWED, Bytes::new()),
+        };
+        if response_tx.send(response).is_err() {
+            debug!("HTTP request dropped before the response was sent.");
+        }
+    }
+}
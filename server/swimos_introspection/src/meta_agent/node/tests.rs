@@ -12,23 +12,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashMap, num::NonZeroUsize};
+use std::{collections::HashMap, num::NonZeroUsize, time::Duration};
 
-use futures::{future::join, Future, StreamExt};
+use bytes::Bytes;
+use futures::{future::join, Future, SinkExt, StreamExt};
 use swimos_agent_protocol::{
-    encoding::lane::{MapLaneResponseDecoder, ValueLaneResponseDecoder},
-    LaneResponse, MapOperation,
+    encoding::lane::{MapLaneResponseDecoder, ValueLaneRequestEncoder, ValueLaneResponseDecoder},
+    LaneRequest, LaneResponse, MapOperation,
 };
-use swimos_api::agent::{LaneConfig, LaneKind, WarpLaneKind};
-use swimos_meta::{LaneInfo, NodePulse};
+use swimos_api::{
+    address::RelativeAddress,
+    agent::{HttpLaneRequest, LaneConfig, LaneKind, RawHttpLaneResponse, WarpLaneKind},
+    http::{HttpRequest, Method, StatusCode, Uri},
+};
+use swimos_messages::protocol::RequestMessage;
+use swimos_meta::{LaneInfo, LogEntry, LogLevel, NodePulse, RemotePruned};
 use swimos_model::Text;
-use swimos_runtime::agent::reporting::UplinkReporter;
+use swimos_runtime::agent::{
+    recording::EnvelopeRecording,
+    reporting::{DeprecationEvent, DeprecationReporter, PruneEvent, PruneReporter, UplinkReporter},
+};
 use swimos_utilities::{
     byte_channel::{byte_channel, ByteReader, ByteWriter},
     non_zero_usize, trigger,
 };
 use tokio::sync::mpsc;
-use tokio_util::codec::FramedRead;
+use tokio_util::codec::{FramedRead, FramedWrite};
 use uuid::Uuid;
 
 use crate::{
@@ -42,7 +51,10 @@ use crate::{
     task::IntrospectionMessage,
 };
 
-use super::{run_lanes_descriptor_lane, NodeMetaAgent, LANES_LANE};
+use super::{
+    run_envelopes_lane, run_lanes_descriptor_lane, run_pruned_lane, run_recording_lane,
+    run_warn_log_lane, NodeMetaAgent, LANES_LANE, PRUNED_LANE, RECORDING_LANE, WARN_LOG_LANE,
+};
 use crate::meta_agent::tests::LaneSender;
 
 const BUFFER_SIZE: NonZeroUsize = non_zero_usize!(4096);
@@ -89,8 +101,23 @@ impl LaneReceiver {
             ow => panic!("Unexpected record: {:?}", ow),
         }
     }
+
+    async fn expect_event(&mut self) -> MapOperation<Text, LaneInfo> {
+        let record = self
+            .reader
+            .next()
+            .await
+            .expect("Expected a record.")
+            .expect("Bad response.");
+        match record {
+            LaneResponse::StandardEvent(op) => op,
+            ow => panic!("Unexpected record: {:?}", ow),
+        }
+    }
 }
 
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 async fn lane_descriptor_test<F, Fut>(test_case: F) -> Fut::Output
 where
     F: FnOnce(TestContext) -> Fut,
@@ -98,13 +125,18 @@ where
 {
     let (shutdown_tx, shutdown_rx) = trigger::trigger();
     let agg_reporter = UplinkReporter::default();
-    let updater = AgentIntrospectionUpdater::new(agg_reporter.reader());
+    let updater = AgentIntrospectionUpdater::new(
+        agg_reporter.reader(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    );
 
     let handle = updater.make_handle();
 
     let (in_tx, in_rx) = byte_channel(BUFFER_SIZE);
     let (out_tx, out_rx) = byte_channel(BUFFER_SIZE);
-    let lane_task = run_lanes_descriptor_lane(shutdown_rx, handle, (out_tx, in_rx));
+    let lane_task = run_lanes_descriptor_lane(shutdown_rx, CHECK_INTERVAL, handle, (out_tx, in_rx));
 
     let context = TestContext {
         shutdown_tx,
@@ -201,6 +233,15 @@ async fn resync_lane_descriptors() {
 
             sender.sync().await;
 
+            // Linked remotes are informed of the new lane before the sync is served.
+            match receiver.expect_event().await {
+                MapOperation::Update { key, value } => {
+                    assert_eq!(key, "third");
+                    assert_eq!(value, LaneInfo::new(Text::new("third"), LaneKind::Command));
+                }
+                ow => panic!("Unexpected event: {:?}", ow),
+            }
+
             let mut result_map2 = HashMap::new();
 
             while let Some((name, info)) = receiver.expect_sync_message().await {
@@ -228,6 +269,44 @@ async fn resync_lane_descriptors() {
     assert_eq!(result_map2, expected);
 }
 
+#[tokio::test(start_paused = true)]
+async fn new_lanes_are_pushed_to_linked_remotes() {
+    let first = UplinkReporter::default();
+    let second = UplinkReporter::default();
+
+    lane_descriptor_test(|context| {
+        let TestContext { updater, .. } = &context;
+        updater.add_lane(Text::new("first"), LaneKind::Value, first.reader());
+
+        async move {
+            let TestContext {
+                shutdown_tx,
+                updater,
+                mut sender,
+                mut receiver,
+                agg_reporter: _agg_reporter,
+            } = context;
+
+            sender.sync().await;
+            while receiver.expect_sync_message().await.is_some() {}
+
+            updater.add_lane(Text::new("second"), LaneKind::Map, second.reader());
+            tokio::time::sleep(2 * CHECK_INTERVAL).await;
+
+            match receiver.expect_event().await {
+                MapOperation::Update { key, value } => {
+                    assert_eq!(key, "second");
+                    assert_eq!(value, LaneInfo::new(Text::new("second"), LaneKind::Map));
+                }
+                ow => panic!("Unexpected event: {:?}", ow),
+            }
+
+            shutdown_tx.trigger();
+        }
+    })
+    .await;
+}
+
 #[tokio::test(start_paused = true)] //Auto-resume will ensure pulses trigger predictably.
 async fn node_meta_agent_pulse_lane() {
     let expected_lane_config = LaneConfig {
@@ -239,6 +318,8 @@ async fn node_meta_agent_pulse_lane() {
     let lanes = vec![
         (PULSE_LANE.to_string(), WarpLaneKind::Supply),
         (LANES_LANE.to_string(), WarpLaneKind::DemandMap),
+        (PRUNED_LANE.to_string(), WarpLaneKind::Supply),
+        (WARN_LOG_LANE.to_string(), WarpLaneKind::Supply),
     ];
 
     let route_params = [(NODE_PARAM.to_string(), "/node".to_string())]
@@ -295,6 +376,8 @@ async fn node_meta_agent_laneinfo_lane() {
     let lanes = vec![
         (PULSE_LANE.to_string(), WarpLaneKind::Supply),
         (LANES_LANE.to_string(), WarpLaneKind::DemandMap),
+        (PRUNED_LANE.to_string(), WarpLaneKind::Supply),
+        (WARN_LOG_LANE.to_string(), WarpLaneKind::Supply),
     ];
 
     let route_params = [(NODE_PARAM.to_string(), "/node".to_string())]
@@ -347,11 +430,93 @@ async fn node_meta_agent_laneinfo_lane() {
     .await;
 }
 
+#[tokio::test(start_paused = true)]
+async fn node_meta_agent_recording_lane() {
+    let expected_lane_config = LaneConfig {
+        transient: true,
+        ..Default::default()
+    };
+    let route = "swimos:meta:node/%2Fnode".parse().expect("Invalid route.");
+
+    let lanes = vec![
+        (PULSE_LANE.to_string(), WarpLaneKind::Supply),
+        (LANES_LANE.to_string(), WarpLaneKind::DemandMap),
+        (PRUNED_LANE.to_string(), WarpLaneKind::Supply),
+        (RECORDING_LANE.to_string(), WarpLaneKind::Value),
+        (WARN_LOG_LANE.to_string(), WarpLaneKind::Supply),
+    ];
+
+    let route_params = [(NODE_PARAM.to_string(), "/node".to_string())]
+        .into_iter()
+        .collect();
+
+    let config = IntrospectionConfig {
+        capture_traffic: true,
+        ..Default::default()
+    };
+
+    introspection_agent_test(
+        expected_lane_config,
+        lanes,
+        route,
+        route_params,
+        move |resolver| NodeMetaAgent::new(config, resolver),
+        |context| async move {
+            let IntrospectionTestContext {
+                mut lanes,
+                init_done,
+                mut queries_rx,
+                _reg_rx,
+            } = context;
+
+            let recording = EnvelopeRecording::default();
+
+            let _updaters = provide_node_with_recording(
+                &mut queries_rx,
+                "/node",
+                vec![("value", LaneKind::Value)],
+                Some(recording.clone()),
+                |_| {},
+            )
+            .await;
+
+            assert!(init_done.await.is_ok());
+
+            let (tx, rx) = lanes.remove(RECORDING_LANE).expect("Lane not defined.");
+            let mut requests = FramedWrite::new(tx, ValueLaneRequestEncoder::default());
+            let mut responses = FramedRead::new(rx, ValueLaneResponseDecoder::<bool>::default());
+
+            requests
+                .send(LaneRequest::Command(true))
+                .await
+                .expect("Lane stopped.");
+            assert_eq!(
+                responses.next().await.unwrap().unwrap(),
+                LaneResponse::StandardEvent(true)
+            );
+            assert!(recording.is_enabled());
+
+            drop(lanes);
+        },
+    )
+    .await;
+}
+
 async fn provide_node(
     queries_rx: &mut mpsc::UnboundedReceiver<IntrospectionMessage>,
     expected_node: &str,
     lanes: Vec<(&str, LaneKind)>,
     init: impl FnOnce(&UplinkReporter),
+) -> (AgentIntrospectionUpdater, Vec<UplinkReporter>) {
+    provide_node_with_recording(queries_rx, expected_node, lanes, None, init).await
+}
+
+async fn provide_node_with_recording(
+    queries_rx: &mut mpsc::UnboundedReceiver<IntrospectionMessage>,
+    expected_node: &str,
+    lanes: Vec<(&str, LaneKind)>,
+    recording: Option<EnvelopeRecording>,
+    init: impl FnOnce(&UplinkReporter),
 ) -> (AgentIntrospectionUpdater, Vec<UplinkReporter>) {
     if let Some(IntrospectionMessage::IntrospectAgent {
         node_uri,
@@ -361,7 +526,12 @@ async fn provide_node(
         assert_eq!(node_uri, expected_node);
         let agg_reporter = UplinkReporter::default();
         init(&agg_reporter);
-        let updater = AgentIntrospectionUpdater::new(agg_reporter.reader());
+        let updater = AgentIntrospectionUpdater::new(
+            agg_reporter.reader(),
+            Default::default(),
+            Default::default(),
+            recording,
+        );
         let mut reporters = vec![agg_reporter];
         for (name, kind) in lanes {
             let reporter = UplinkReporter::default();
@@ -412,3 +582,216 @@ async fn sync_lanes_meta(tx: ByteWriter, rx: ByteReader) -> HashMap<Text, LaneIn
     }
     result_map
 }
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(1);
+
+#[tokio::test(start_paused = true)]
+async fn pruned_lane_events() {
+    let (shutdown_tx, shutdown_rx) = trigger::trigger();
+    let reporter = PruneReporter::default();
+
+    let (in_tx, in_rx) = byte_channel(BUFFER_SIZE);
+    let (out_tx, out_rx) = byte_channel(BUFFER_SIZE);
+    let lane_task = run_pruned_lane(
+        shutdown_rx,
+        PRUNE_INTERVAL,
+        reporter.reader(),
+        (out_tx, in_rx),
+    );
+
+    let test_task = async move {
+        let mut sender = LaneSender::new(SYNC_ID, in_tx);
+        let mut receiver =
+            FramedRead::new(out_rx, ValueLaneResponseDecoder::<RemotePruned>::default());
+
+        sender.sync().await;
+        match receiver.next().await {
+            Some(Ok(LaneResponse::Synced(id))) => assert_eq!(id, SYNC_ID),
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+
+        let remote_id = Uuid::from_u128(77);
+        reporter.report_pruned(PruneEvent {
+            remote_id,
+            idle_for: Duration::from_millis(1500),
+        });
+
+        match receiver.next().await {
+            Some(Ok(LaneResponse::StandardEvent(record))) => {
+                assert_eq!(
+                    record,
+                    RemotePruned {
+                        remote_id: Text::from(remote_id.to_string()),
+                        idle_time: 1500,
+                    }
+                );
+            }
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+
+        // The lane continues to run after the agent has stopped.
+        drop(reporter);
+        tokio::time::sleep(PRUNE_INTERVAL * 2).await;
+        sender.sync().await;
+        match receiver.next().await {
+            Some(Ok(LaneResponse::Synced(id))) => assert_eq!(id, SYNC_ID),
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+
+        shutdown_tx.trigger();
+    };
+
+    let (result, _) = join(lane_task, test_task).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+async fn warn_log_lane_events() {
+    let (shutdown_tx, shutdown_rx) = trigger::trigger();
+    let reporter = DeprecationReporter::default();
+
+    let (in_tx, in_rx) = byte_channel(BUFFER_SIZE);
+    let (out_tx, out_rx) = byte_channel(BUFFER_SIZE);
+    let lane_task = run_warn_log_lane(
+        shutdown_rx,
+        PRUNE_INTERVAL,
+        reporter.reader(),
+        Text::new("/node"),
+        (out_tx, in_rx),
+    );
+
+    let test_task = async move {
+        let mut sender = LaneSender::new(SYNC_ID, in_tx);
+        let mut receiver = FramedRead::new(out_rx, ValueLaneResponseDecoder::<LogEntry>::default());
+
+        sender.sync().await;
+        match receiver.next().await {
+            Some(Ok(LaneResponse::Synced(id))) => assert_eq!(id, SYNC_ID),
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+
+        reporter.report_deprecated(DeprecationEvent {
+            remote_id: Uuid::from_u128(77),
+            alias: Text::new("oldLane"),
+            lane: Text::new("lane"),
+        });
+
+        match receiver.next().await {
+            Some(Ok(LaneResponse::StandardEvent(entry))) => {
+                assert_eq!(entry.level, LogLevel::Warn);
+                assert_eq!(entry.node, "/node");
+                assert_eq!(entry.lane, "lane");
+            }
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+
+        shutdown_tx.trigger();
+    };
+
+    let (result, _) = join(lane_task, test_task).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn recording_lane_toggles_recording() {
+    let (shutdown_tx, shutdown_rx) = trigger::trigger();
+    let recording = EnvelopeRecording::default();
+
+    let (in_tx, in_rx) = byte_channel(BUFFER_SIZE);
+    let (out_tx, out_rx) = byte_channel(BUFFER_SIZE);
+    let lane_task = run_recording_lane(shutdown_rx, recording.clone(), (out_tx, in_rx));
+
+    let test_task = async {
+        let mut requests = FramedWrite::new(in_tx, ValueLaneRequestEncoder::default());
+        let mut responses = FramedRead::new(out_rx, ValueLaneResponseDecoder::<bool>::default());
+
+        requests
+            .send(LaneRequest::<bool>::Sync(SYNC_ID))
+            .await
+            .expect("Lane stopped.");
+        assert_eq!(
+            responses.next().await.unwrap().unwrap(),
+            LaneResponse::SyncEvent(SYNC_ID, false)
+        );
+        assert_eq!(
+            responses.next().await.unwrap().unwrap(),
+            LaneResponse::Synced(SYNC_ID)
+        );
+
+        requests
+            .send(LaneRequest::Command(true))
+            .await
+            .expect("Lane stopped.");
+        assert_eq!(
+            responses.next().await.unwrap().unwrap(),
+            LaneResponse::StandardEvent(true)
+        );
+        assert!(recording.is_enabled());
+
+        requests
+            .send(LaneRequest::Command(false))
+            .await
+            .expect("Lane stopped.");
+        assert_eq!(
+            responses.next().await.unwrap().unwrap(),
+            LaneResponse::StandardEvent(false)
+        );
+        assert!(!recording.is_enabled());
+
+        shutdown_tx.trigger();
+    };
+
+    let (result, _) = join(lane_task, test_task).await;
+    assert!(result.is_ok());
+}
+
+async fn make_request(tx: &mpsc::Sender<HttpLaneRequest>, method: Method) -> RawHttpLaneResponse {
+    let request = HttpRequest {
+        method,
+        version: Default::default(),
+        uri: Uri::from_static("/swimos:meta:node/node?lane=envelopes"),
+        headers: vec![],
+        payload: Bytes::new(),
+    };
+    let (request, response_rx) = HttpLaneRequest::new(request);
+    tx.send(request).await.expect("Lane stopped.");
+    response_rx.await.expect("No response.")
+}
+
+#[tokio::test]
+async fn envelopes_lane_downloads_recording() {
+    let (shutdown_tx, shutdown_rx) = trigger::trigger();
+    let recording = EnvelopeRecording::default();
+    recording.set_enabled(true);
+    let message: RequestMessage<&str, &[u8]> = RequestMessage::command(
+        Uuid::from_u128(1),
+        RelativeAddress::new("/node", "lane"),
+        b"1",
+    );
+    recording.record_request(&message);
+
+    let (tx, rx) = mpsc::channel(8);
+    let lane_task = run_envelopes_lane(shutdown_rx, recording.clone(), rx);
+
+    let test_task = async move {
+        let response = make_request(&tx, Method::GET).await;
+        assert_eq!(response.status_code, StatusCode::OK);
+        let body = std::str::from_utf8(response.payload.as_ref()).expect("Invalid UTF8");
+        let lines = body.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with(
+            " in 00000000-0000-0000-0000-000000000001 @command(node:\"/node\",lane:lane) 1"
+        ));
+
+        let response = make_request(&tx, Method::POST).await;
+        assert_eq!(response.status_code, StatusCode::METHOD_NOT_ALLOWED);
+
+        let response = make_request(&tx, Method::DELETE).await;
+        assert_eq!(response.status_code, StatusCode::NO_CONTENT);
+        assert!(recording.envelopes().is_empty());
+
+        shutdown_tx.trigger();
+    };
+
+    join(lane_task, test_task).await;
+}
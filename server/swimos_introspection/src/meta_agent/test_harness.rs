@@ -21,8 +21,8 @@ use futures::{
 use parking_lot::Mutex;
 use swimos_api::{
     agent::{
-        Agent, AgentConfig, AgentContext, DownlinkKind, HttpLaneRequestChannel, LaneConfig,
-        StoreKind, WarpLaneKind,
+        Agent, AgentConfig, AgentContext, DownlinkKind, HttpLaneRequest, HttpLaneRequestChannel,
+        LaneConfig, StoreKind, WarpLaneKind,
     },
     error::{AgentRuntimeError, DownlinkRuntimeError, OpenStoreError},
 };
@@ -95,6 +95,9 @@ pub struct IntrospectionTestContext {
 
 struct ContextInner {
     expected_lanes: HashMap<String, FakeRuntimeLane>,
+    // The HTTP lanes are not exercised by the tests so the request senders are only held to keep
+    // the lanes open.
+    http_lanes: Vec<mpsc::Sender<HttpLaneRequest>>,
 }
 
 const BUFFER_SIZE: NonZeroUsize = non_zero_usize!(4096);
@@ -122,6 +125,7 @@ pub fn init(
     let fake_context = FakeContext {
         inner: Arc::new(Mutex::new(ContextInner {
             expected_lanes: expected,
+            http_lanes: vec![],
         })),
     };
     let test_context = IntrospectionTestContext {
@@ -152,7 +156,7 @@ impl AgentContext for FakeContext {
         let key = name.to_string();
         async move {
             let mut lock = inner.lock();
-            let ContextInner { expected_lanes } = &mut *lock;
+            let ContextInner { expected_lanes, .. } = &mut *lock;
             let FakeRuntimeLane {
                 kind,
                 expected_config,
@@ -187,6 +191,8 @@ impl AgentContext for FakeContext {
         &self,
         _name: &str,
     ) -> BoxFuture<'static, Result<HttpLaneRequestChannel, AgentRuntimeError>> {
-        panic!("Unexpected HTTP lane request.");
+        let (tx, rx) = mpsc::channel(8);
+        self.inner.lock().http_lanes.push(tx);
+        async move { Ok(rx) }.boxed()
     }
 }
@@ -17,36 +17,82 @@ mod tests;
 
 use crate::{
     forest::{UriForest, UriPart},
+    meta_agent::sleep_stream,
     task::AgentMeta,
 };
-use futures::future::{BoxFuture, Either};
+use futures::future::{try_join, try_join3, BoxFuture, Either};
 use futures::stream::select;
-use futures::{FutureExt, SinkExt, StreamExt, TryFutureExt};
+use futures::{FutureExt, SinkExt, Stream, StreamExt, TryFutureExt};
 use parking_lot::RwLock;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::pin::pin;
 use std::sync::Arc;
-use swimos_agent_protocol::encoding::lane::{MapLaneResponseEncoder, RawValueLaneRequestDecoder};
+use std::time::Duration;
+use swimos_agent_protocol::encoding::lane::{
+    MapLaneResponseEncoder, RawValueLaneRequestDecoder, ValueLaneRequestDecoder,
+    ValueLaneResponseEncoder,
+};
 use swimos_agent_protocol::{LaneRequest, LaneResponse, MapOperation};
 use swimos_api::agent::{Agent, AgentConfig, AgentContext, AgentInitResult, WarpLaneKind};
 use swimos_api::error::{AgentTaskError, FrameIoError};
 use swimos_form::read::{ReadError, ReadEvent, Recognizer, RecognizerReadable};
 use swimos_form::write::{StructuralWritable, StructuralWriter};
 use swimos_form::Form;
-use swimos_model::Text;
+use swimos_meta::{MeshPulse, NodeTopology};
+use swimos_model::{Text, Timestamp};
 use swimos_utilities::trigger;
 use swimos_utilities::{
     byte_channel::{ByteReader, ByteWriter},
     routing::RouteUri,
 };
+use tokio::sync::mpsc;
 use tokio_util::codec::{FramedRead, FramedWrite};
+use tracing::{debug, warn};
+
+/// The node URIs of the agents that have state in the server's store (loaded when the server
+/// starts) and a channel to request that the server start one of them. Agents that are
+/// recoverable, but not running, are listed on the `nodes` lane of the mesh meta-agent (with a
+/// creation time of 0 and no agents) and can be started by sending their node URI to its `start`
+/// lane.
+#[derive(Debug, Clone)]
+pub struct AgentRecovery {
+    node_uris: Arc<BTreeSet<Text>>,
+    start_tx: mpsc::Sender<RouteUri>,
+}
+
+impl AgentRecovery {
+    /// # Arguments
+    /// * `node_uris` - The node URIs of the agents that have persisted state.
+    /// * `start_tx` - Channel on which requests to start agents will be sent to the server.
+    pub fn new<I>(node_uris: I, start_tx: mpsc::Sender<RouteUri>) -> AgentRecovery
+    where
+        I: IntoIterator,
+        I::Item: Into<Text>,
+    {
+        AgentRecovery {
+            node_uris: Arc::new(node_uris.into_iter().map(Into::into).collect()),
+            start_tx,
+        }
+    }
+}
 
 pub struct MetaMeshAgent {
     agents: Arc<RwLock<UriForest<AgentMeta>>>,
+    pulse_interval: Duration,
+    recovery: Option<AgentRecovery>,
 }
 
 impl MetaMeshAgent {
-    pub fn new(agents: Arc<RwLock<UriForest<AgentMeta>>>) -> MetaMeshAgent {
-        MetaMeshAgent { agents }
+    pub fn new(
+        agents: Arc<RwLock<UriForest<AgentMeta>>>,
+        pulse_interval: Duration,
+        recovery: Option<AgentRecovery>,
+    ) -> MetaMeshAgent {
+        MetaMeshAgent {
+            agents,
+            pulse_interval,
+            recovery,
+        }
     }
 }
 
@@ -58,16 +104,32 @@ impl Agent for MetaMeshAgent {
         config: AgentConfig,
         context: Box<dyn AgentContext + Send>,
     ) -> BoxFuture<'static, AgentInitResult> {
-        let MetaMeshAgent { agents } = self;
-        run_init(agents.clone(), config, context).boxed()
+        let MetaMeshAgent {
+            agents,
+            pulse_interval,
+            recovery,
+        } = self;
+        run_init(
+            agents.clone(),
+            *pulse_interval,
+            recovery.clone(),
+            config,
+            context,
+        )
+        .boxed()
     }
 }
 
 const NODES_LANE: &str = "nodes";
 const NODES_COUNT_LANE: &str = "nodes#/";
+const START_LANE: &str = "start";
+const PULSE_LANE: &str = "pulse";
+const TOPOLOGY_LANE: &str = "topology";
 
 async fn run_init(
     agents: Arc<RwLock<UriForest<AgentMeta>>>,
+    pulse_interval: Duration,
+    recovery: Option<AgentRecovery>,
     config: AgentConfig,
     context: Box<dyn AgentContext + Send>,
 ) -> AgentInitResult {
@@ -79,14 +141,63 @@ async fn run_init(
     let nodes_count_io = context
         .add_lane(NODES_COUNT_LANE, WarpLaneKind::DemandMap, lane_config)
         .await?;
+    let pulse_io = context
+        .add_lane(PULSE_LANE, WarpLaneKind::Value, lane_config)
+        .await?;
+    let topology_io = context
+        .add_lane(TOPOLOGY_LANE, WarpLaneKind::DemandMap, lane_config)
+        .await?;
+    let start = if let Some(recovery) = recovery {
+        let start_io = context
+            .add_lane(START_LANE, WarpLaneKind::Command, lane_config)
+            .await?;
+        Some((recovery, start_io))
+    } else {
+        None
+    };
     Ok(Box::pin(async move {
         let (_shutdown_tx, shutdown_rx) = trigger::trigger();
-        run_task(shutdown_rx, agents, context, nodes_io, nodes_count_io)
-            .map_err(|error| AgentTaskError::BadFrame {
-                lane: Text::from("nodes"),
+        let recoverable = start
+            .as_ref()
+            .map(|(recovery, _)| recovery.node_uris.clone())
+            .unwrap_or_default();
+        let pulse_task =
+            run_pulse_lane(agents.clone(), pulse_interval, pulse_io).map_err(|error| {
+                AgentTaskError::BadFrame {
+                    lane: Text::from(PULSE_LANE),
+                    error,
+                }
+            });
+        let topology_task = run_topology_lane(agents.clone(), topology_io).map_err(|error| {
+            AgentTaskError::BadFrame {
+                lane: Text::from(TOPOLOGY_LANE),
                 error,
-            })
-            .await
+            }
+        });
+        let nodes_task = run_task(
+            shutdown_rx,
+            agents.clone(),
+            recoverable,
+            context,
+            nodes_io,
+            nodes_count_io,
+        )
+        .map_err(|error| AgentTaskError::BadFrame {
+            lane: Text::from(NODES_LANE),
+            error,
+        });
+        let lanes_task = try_join3(nodes_task, pulse_task, topology_task);
+        if let Some((recovery, start_io)) = start {
+            let start_task = run_start_lane(agents, recovery, start_io).map_err(|error| {
+                AgentTaskError::BadFrame {
+                    lane: Text::from(START_LANE),
+                    error,
+                }
+            });
+            try_join(lanes_task, start_task).await.map(|_| ())
+        } else {
+            lanes_task.await.map(|_| ())
+        }
     }))
 }
 
@@ -212,6 +323,7 @@ type Io = (ByteWriter, ByteReader);
 async fn run_task(
     shutdown_rx: trigger::Receiver,
     agents: Arc<RwLock<UriForest<AgentMeta>>>,
+    recoverable: Arc<BTreeSet<Text>>,
     context: Box<dyn AgentContext + Send>,
     nodes_io: Io,
     nodes_count_io: Io,
@@ -235,20 +347,30 @@ async fn run_task(
     while let Some(request) = request_stream.next().await {
         match request {
             Either::Left(request) => {
-                if let LaneRequest::Sync(id) = request? {
+                if let LaneRequest::Sync(id)
+                | LaneRequest::SyncSince(id, _)
+                | LaneRequest::SyncRange(id, _) = request?
+                {
                     // Done in two passes in to reduce the time that we hold the lock
                     let parts = {
                         let guard = agents.read();
                         let forest = &*guard;
 
-                        forest
-                            .uri_iter()
-                            .map(|(node_uri, meta)| NodeInfoList {
-                                node_uri,
-                                created: meta.created.millis(),
-                                agents: vec![meta.name.to_string()],
-                            })
-                            .collect::<Vec<_>>()
+                        let running = forest.uri_iter().map(|(node_uri, meta)| NodeInfoList {
+                            node_uri,
+                            created: meta.created.millis(),
+                            agents: vec![meta.name.to_string()],
+                        });
+                        // Agents that have persisted state but are not running.
+                        let dormant = recoverable
+                            .iter()
+                            .filter(|node_uri| !forest.contains_uri(node_uri.as_str()))
+                            .map(|node_uri| NodeInfoList {
+                                node_uri: node_uri.to_string(),
+                                created: 0,
+                                agents: vec![],
+                            });
+                        running.chain(dormant).collect::<Vec<_>>()
                     };
 
                     for info in parts {
@@ -266,7 +388,10 @@ async fn run_task(
                 }
             }
             Either::Right(request) => {
-                if let LaneRequest::Sync(id) = request? {
+                if let LaneRequest::Sync(id)
+                | LaneRequest::SyncSince(id, _)
+                | LaneRequest::SyncRange(id, _) = request?
+                {
                     // Done in two passes in to reduce the time that we hold the lock
                     let parts = {
                         let guard = agents.read();
@@ -318,3 +443,178 @@ async fn run_task(
 
     Ok(())
 }
+
+/// A command lane that requests that the server start a recoverable agent. The body of each
+/// command is the node URI of the agent. The node URIs of agents that are started are echoed as
+/// events on the lane. Commands for agents that are not recoverable, or that are already running,
+/// are ignored.
+async fn run_start_lane(
+    agents: Arc<RwLock<UriForest<AgentMeta>>>,
+    recovery: AgentRecovery,
+    start_io: Io,
+) -> Result<(), FrameIoError> {
+    let AgentRecovery {
+        node_uris,
+        start_tx,
+    } = recovery;
+    let (tx, rx) = start_io;
+
+    let mut input = FramedRead::new(rx, ValueLaneRequestDecoder::<Text>::default());
+    let mut output = FramedWrite::new(tx, ValueLaneResponseEncoder::default());
+
+    while let Some(request) = input.next().await.transpose()? {
+        match request {
+            LaneRequest::Command(node_uri)
+            | LaneRequest::CommandFrom(_, node_uri)
+            | LaneRequest::CorrelatedCommand(_, _, node_uri) => {
+                if !node_uris.contains(&node_uri) {
+                    debug!(node_uri = %node_uri, "Ignoring a request to start an agent that is not recoverable.");
+                    continue;
+                }
+                if agents.read().contains_uri(node_uri.as_str()) {
+                    debug!(node_uri = %node_uri, "Ignoring a request to start an agent that is already running.");
+                    continue;
+                }
+                match RouteUri::try_from(node_uri.as_str()) {
+                    Ok(route) => {
+                        if start_tx.send(route).await.is_err() {
+                            debug!(node_uri = %node_uri, "The server stopped before a recoverable agent could be started.");
+                        } else {
+                            output.send(LaneResponse::StandardEvent(&node_uri)).await?;
+                        }
+                    }
+                    Err(error) => {
+                        warn!(error = %error, "A recoverable agent has an invalid node URI.");
+                    }
+                }
+            }
+            LaneRequest::Sync(id)
+            | LaneRequest::SyncSince(id, _)
+            | LaneRequest::SyncRange(id, _) => {
+                output.send(LaneResponse::<&Text>::Synced(id)).await?;
+            }
+            LaneRequest::InitComplete => {}
+        }
+    }
+    Ok(())
+}
+
+/// A demand-map lane, keyed by node URI, that describes each of the agents that are running on
+/// the server. The entries are computed when a remote syncs with the lane.
+async fn run_topology_lane(
+    agents: Arc<RwLock<UriForest<AgentMeta>>>,
+    topology_io: Io,
+) -> Result<(), FrameIoError> {
+    let (tx, rx) = topology_io;
+
+    let mut input = FramedRead::new(rx, RawValueLaneRequestDecoder::default());
+    let mut output = FramedWrite::new(tx, MapLaneResponseEncoder::default());
+
+    while let Some(request) = input.next().await.transpose()? {
+        if let LaneRequest::Sync(id)
+        | LaneRequest::SyncSince(id, _)
+        | LaneRequest::SyncRange(id, _) = request
+        {
+            let entries = topology(&agents);
+            for entry in &entries {
+                let op = MapOperation::Update {
+                    key: entry.node_uri.as_str(),
+                    value: entry,
+                };
+                output.send(LaneResponse::SyncEvent(id, op)).await?;
+            }
+            let synced: LaneResponse<MapOperation<&str, &NodeTopology>> = LaneResponse::Synced(id);
+            output.send(synced).await?;
+        }
+    }
+    Ok(())
+}
+
+// As with the pulse, agents that have stopped, but have not yet been removed from the forest, are
+// not included.
+fn topology(agents: &RwLock<UriForest<AgentMeta>>) -> Vec<NodeTopology> {
+    let now = Timestamp::now().millis();
+    let guard = agents.read();
+    guard
+        .uri_iter()
+        .filter_map(|(node_uri, meta)| {
+            let link_count = meta.updater.link_count()?;
+            let lane_count = meta.updater.lane_count();
+            let created = meta.created.millis();
+            Some(NodeTopology {
+                node_uri: Text::from(node_uri),
+                route: meta.route.clone(),
+                created,
+                uptime: u64::try_from(now.saturating_sub(created)).unwrap_or_default(),
+                link_count,
+                lane_count: lane_count as u64,
+                memory: lane_count.saturating_mul(meta.lane_buffer_size) as u64,
+            })
+        })
+        .collect()
+}
+
+/// A value lane that emits a [`MeshPulse`], describing the agents that are running on the server,
+/// on a fixed schedule.
+async fn run_pulse_lane(
+    agents: Arc<RwLock<UriForest<AgentMeta>>>,
+    pulse_interval: Duration,
+    pulse_io: Io,
+) -> Result<(), FrameIoError> {
+    let sleep = pin!(tokio::time::sleep(pulse_interval));
+    run_pulse_lane_inner(agents, sleep_stream(pulse_interval, sleep), pulse_io).await
+}
+
+async fn run_pulse_lane_inner<S>(
+    agents: Arc<RwLock<UriForest<AgentMeta>>>,
+    pulses: S,
+    pulse_io: Io,
+) -> Result<(), FrameIoError>
+where
+    S: Stream<Item = ()>,
+{
+    let (tx, rx) = pulse_io;
+
+    let mut input = FramedRead::new(rx, RawValueLaneRequestDecoder::default());
+    let mut output = FramedWrite::new(tx, ValueLaneResponseEncoder::default());
+
+    let mut last_pulse = mesh_pulse(&agents);
+    let mut pulses = pin!(pulses);
+
+    loop {
+        tokio::select! {
+            biased;
+            maybe_request = input.next() => match maybe_request.transpose()? {
+                Some(LaneRequest::Sync(id) | LaneRequest::SyncSince(id, _) | LaneRequest::SyncRange(id, _)) => {
+                    output.send(LaneResponse::SyncEvent(id, &last_pulse)).await?;
+                    let synced: LaneResponse<&MeshPulse> = LaneResponse::Synced(id);
+                    output.send(synced).await?;
+                }
+                Some(_) => {}
+                None => break Ok(()),
+            },
+            maybe_pulse = pulses.next() => {
+                if maybe_pulse.is_none() {
+                    break Ok(());
+                }
+                let pulse = mesh_pulse(&agents);
+                output.send(LaneResponse::StandardEvent(&pulse)).await?;
+                last_pulse = pulse;
+            }
+        }
+    }
+}
+
+// Agents that have stopped, but have not yet been removed from the forest, are not counted.
+fn mesh_pulse(agents: &RwLock<UriForest<AgentMeta>>) -> MeshPulse {
+    let guard = agents.read();
+    guard
+        .uri_iter()
+        .fold(MeshPulse::default(), |mut pulse, (_, meta)| {
+            if let Some(link_count) = meta.updater.link_count() {
+                pulse.agent_count += 1;
+                pulse.link_count = pulse.link_count.saturating_add(link_count);
+            }
+            pulse
+        })
+}
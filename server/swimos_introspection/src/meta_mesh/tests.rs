@@ -13,27 +13,39 @@
 // limitations under the License.
 
 use crate::forest::UriForest;
-use crate::meta_mesh::{run_task, NodeInfo, NodeInfoCount, NodeInfoList};
+use crate::meta_mesh::{
+    run_pulse_lane_inner, run_start_lane, run_task, run_topology_lane, AgentRecovery, NodeInfo,
+    NodeInfoCount, NodeInfoList,
+};
 use crate::model::AgentIntrospectionUpdater;
 use crate::task::AgentMeta;
 use futures::future::{join, BoxFuture};
 use futures::{SinkExt, StreamExt};
 use parking_lot::RwLock;
+use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::future::Future;
 use std::num::NonZeroUsize;
 use std::sync::{Arc, OnceLock};
-use swimos_agent_protocol::encoding::lane::{MapLaneResponseDecoder, RawValueLaneRequestEncoder};
+use swimos_agent_protocol::encoding::lane::{
+    MapLaneResponseDecoder, RawValueLaneRequestEncoder, ValueLaneRequestEncoder,
+    ValueLaneResponseDecoder,
+};
 use swimos_agent_protocol::{LaneRequest, LaneResponse, MapOperation};
+use swimos_api::agent::LaneKind;
 use swimos_api::agent::{
     AgentContext, DownlinkKind, HttpLaneRequestChannel, LaneConfig, StoreKind, WarpLaneKind,
 };
 use swimos_api::error::{AgentRuntimeError, DownlinkRuntimeError, OpenStoreError};
 use swimos_form::read::RecognizerReadable;
+use swimos_meta::{MeshPulse, NodeTopology};
 use swimos_model::{Text, Timestamp};
 use swimos_runtime::agent::reporting::UplinkReporter;
 use swimos_utilities::byte_channel::{byte_channel, ByteReader, ByteWriter};
+use swimos_utilities::routing::RouteUri;
 use swimos_utilities::{non_zero_usize, trigger};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::codec::{FramedRead, FramedWrite};
 use uuid::Uuid;
 
@@ -149,6 +161,14 @@ struct Context {
 }
 
 async fn run_test<F, Fut>(test: F) -> Fut::Output
+where
+    F: FnOnce(Context) -> Fut,
+    Fut: Future,
+{
+    run_test_with_recoverable(&[], test).await
+}
+
+async fn run_test_with_recoverable<F, Fut>(recoverable: &[&str], test: F) -> Fut::Output
 where
     F: FnOnce(Context) -> Fut,
     Fut: Future,
@@ -167,6 +187,12 @@ where
     let task = run_task(
         shutdown_rx,
         forest.clone(),
+        Arc::new(
+            recoverable
+                .iter()
+                .map(|uri| Text::new(uri))
+                .collect::<BTreeSet<_>>(),
+        ),
         Box::new(MockAgentContext),
         (nodes_out_tx, nodes_in_rx),
         (nodes_count_out_tx, nodes_count_in_rx),
@@ -189,8 +215,15 @@ fn push_uri(forest: &mut UriForest<AgentMeta>, reporter: &UplinkReporter, path:
         path,
         AgentMeta {
             name: name.into(),
+            route: "/cnt/:id".into(),
+            lane_buffer_size: 64,
             created: *NOW.get().unwrap(),
-            updater: AgentIntrospectionUpdater::new(reporter.reader()),
+            updater: AgentIntrospectionUpdater::new(
+                reporter.reader(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+            ),
         },
     );
 }
@@ -363,3 +396,228 @@ async fn list_lanes() {
     })
     .await
 }
+
+#[tokio::test]
+async fn list_recoverable_lanes() {
+    run_test_with_recoverable(&["/cnt/1", "/cnt/2"], |ctx| async {
+        let Context {
+            shutdown_tx,
+            mut nodes_channel,
+            forest,
+            ..
+        } = ctx;
+
+        let reporter = UplinkReporter::default();
+
+        {
+            let forest = &mut *forest.write();
+            push_uri(forest, &reporter, "/cnt/1", "counter_1");
+        }
+
+        let mut expected = vec![
+            (
+                "/cnt/1".into(),
+                NodeInfoList {
+                    node_uri: "/cnt/1".to_string(),
+                    created: NOW.get().unwrap().clone().millis(),
+                    agents: vec!["counter_1".into()],
+                },
+            ),
+            (
+                "/cnt/2".into(),
+                NodeInfoList {
+                    node_uri: "/cnt/2".to_string(),
+                    created: 0,
+                    agents: vec![],
+                },
+            ),
+        ];
+
+        nodes_channel.send_sync().await;
+
+        let mut events = nodes_channel.expect_n_sync_events(expected.len()).await;
+
+        expected.sort();
+        events.sort();
+
+        assert_eq!(expected, events);
+
+        nodes_channel.recv_synced().await;
+        assert!(shutdown_tx.trigger());
+    })
+    .await
+}
+
+#[tokio::test]
+async fn start_recoverable_agent() {
+    let (in_tx, in_rx) = byte_channel(BUFFER_SIZE);
+    let (out_tx, out_rx) = byte_channel(BUFFER_SIZE);
+    let (start_tx, mut start_rx) = mpsc::channel(8);
+
+    let forest = Arc::new(RwLock::new(UriForest::new()));
+    let reporter = UplinkReporter::default();
+    push_uri(&mut forest.write(), &reporter, "/cnt/1", "counter_1");
+
+    let recovery = AgentRecovery::new(["/cnt/1", "/cnt/2"], start_tx);
+    let task = run_start_lane(forest, recovery, (out_tx, in_rx));
+
+    let test = async move {
+        let mut sender = FramedWrite::new(in_tx, ValueLaneRequestEncoder::default());
+        let mut receiver = FramedRead::new(out_rx, ValueLaneResponseDecoder::<Text>::default());
+
+        // Neither of these agents can be started.
+        for node_uri in ["/other", "/cnt/1"] {
+            let request = LaneRequest::Command(Text::new(node_uri));
+            assert!(sender.send(request).await.is_ok());
+        }
+
+        let request = LaneRequest::Command(Text::new("/cnt/2"));
+        assert!(sender.send(request).await.is_ok());
+
+        assert_eq!(
+            start_rx.recv().await,
+            Some(RouteUri::try_from("/cnt/2").unwrap())
+        );
+        match receiver.next().await {
+            Some(Ok(LaneResponse::StandardEvent(node_uri))) => assert_eq!(node_uri, "/cnt/2"),
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+
+        drop(sender);
+        assert!(start_rx.recv().await.is_none());
+    };
+
+    let (result, _) = join(task, test).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn mesh_pulse() {
+    let (in_tx, in_rx) = byte_channel(BUFFER_SIZE);
+    let (out_tx, out_rx) = byte_channel(BUFFER_SIZE);
+    let (pulse_tx, pulse_rx) = mpsc::channel(8);
+
+    let _r = NOW.set(Timestamp::now());
+    let forest = Arc::new(RwLock::new(UriForest::new()));
+    let first = UplinkReporter::default();
+    let second = UplinkReporter::default();
+    first.set_uplinks(2);
+    push_uri(&mut forest.write(), &first, "/cnt/1", "counter_1");
+
+    let task = run_pulse_lane_inner(
+        forest.clone(),
+        ReceiverStream::new(pulse_rx),
+        (out_tx, in_rx),
+    );
+
+    let test = async move {
+        let mut sender = FramedWrite::new(in_tx, RawValueLaneRequestEncoder::default());
+        let mut receiver =
+            FramedRead::new(out_rx, ValueLaneResponseDecoder::<MeshPulse>::default());
+
+        let id = Uuid::from_u128(7);
+        let req: LaneRequest<&[u8]> = LaneRequest::Sync(id);
+        assert!(sender.send(req).await.is_ok());
+
+        let expected = MeshPulse {
+            agent_count: 1,
+            link_count: 2,
+        };
+        match receiver.next().await {
+            Some(Ok(LaneResponse::SyncEvent(sync_id, pulse))) => {
+                assert_eq!(sync_id, id);
+                assert_eq!(pulse, expected);
+            }
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+        match receiver.next().await {
+            Some(Ok(LaneResponse::Synced(sync_id))) => assert_eq!(sync_id, id),
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+
+        second.set_uplinks(3);
+        push_uri(&mut forest.write(), &second, "/cnt/2", "counter_2");
+        assert!(pulse_tx.send(()).await.is_ok());
+
+        let expected = MeshPulse {
+            agent_count: 2,
+            link_count: 5,
+        };
+        match receiver.next().await {
+            Some(Ok(LaneResponse::StandardEvent(pulse))) => assert_eq!(pulse, expected),
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+
+        // Stopped agents are not counted.
+        drop(first);
+        assert!(pulse_tx.send(()).await.is_ok());
+
+        let expected = MeshPulse {
+            agent_count: 1,
+            link_count: 3,
+        };
+        match receiver.next().await {
+            Some(Ok(LaneResponse::StandardEvent(pulse))) => assert_eq!(pulse, expected),
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+    };
+
+    let (result, _) = join(task, test).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn topology_lane() {
+    let (in_tx, in_rx) = byte_channel(BUFFER_SIZE);
+    let (out_tx, out_rx) = byte_channel(BUFFER_SIZE);
+
+    let _r = NOW.set(Timestamp::now());
+    let forest = Arc::new(RwLock::new(UriForest::new()));
+    let running = UplinkReporter::default();
+    let stopped = UplinkReporter::default();
+    let lane = UplinkReporter::default();
+    running.set_uplinks(3);
+    {
+        let forest = &mut *forest.write();
+        push_uri(forest, &running, "/cnt/1", "counter_1");
+        push_uri(forest, &stopped, "/cnt/2", "counter_2");
+        forest
+            .get_mut("/cnt/1")
+            .expect("Agent missing.")
+            .updater
+            .add_lane(Text::new("lane"), LaneKind::Value, lane.reader());
+    }
+    drop(stopped);
+
+    let task = run_topology_lane(forest.clone(), (out_tx, in_rx));
+
+    let test = async move {
+        let mut channel = LaneChannel::<NodeTopology>::new(in_tx, out_rx);
+        channel.send_sync().await;
+
+        // Agents that have stopped are not included.
+        let events = channel.expect_n_sync_events(1).await;
+        channel.recv_synced().await;
+
+        let (key, entry) = &events[0];
+        assert_eq!(key, "/cnt/1");
+        let NodeTopology {
+            node_uri,
+            route,
+            created,
+            link_count,
+            lane_count,
+            memory,
+            ..
+        } = entry;
+        assert_eq!(node_uri, "/cnt/1");
+        assert_eq!(route, "/cnt/:id");
+        assert_eq!(*created, NOW.get().unwrap().millis());
+        assert_eq!(*link_count, 3);
+        assert_eq!(*lane_count, 1);
+        assert_eq!(*memory, 64);
+    };
+
+    let (result, _) = join(task, test).await;
+    assert!(result.is_ok());
+}
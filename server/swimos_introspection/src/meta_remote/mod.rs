@@ -0,0 +1,219 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, fmt::Write};
+
+use bytes::{Bytes, BytesMut};
+use futures::{future::BoxFuture, FutureExt, SinkExt, StreamExt};
+use swimos_agent_protocol::{
+    encoding::lane::{RawValueLaneRequestDecoder, ValueLaneResponseEncoder},
+    LaneRequest, LaneResponse,
+};
+use swimos_api::{
+    agent::{
+        Agent, AgentConfig, AgentContext, AgentInitResult, HttpLaneRequestChannel,
+        RawHttpLaneResponse, WarpLaneKind,
+    },
+    error::{AgentInitError, AgentTaskError, FrameIoError, InvalidFrame},
+    http::{Header, HttpResponse, Method, StandardHeaderName, StatusCode, Version},
+};
+use swimos_model::Text;
+use swimos_recon::parser::{parse_recognize, AsyncParseError};
+use swimos_remote::{FrameCapture, RemoteCaptures};
+use swimos_utilities::{
+    byte_channel::{ByteReader, ByteWriter},
+    routing::RouteUri,
+};
+use thiserror::Error;
+use tokio_util::codec::{FramedRead, FramedWrite};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::{meta_agent::MetaRouteError, route::REMOTE_PARAM};
+
+const DEBUG_LANE: &str = "debug";
+const FRAMES_LANE: &str = "frames";
+
+/// Error type indicating that a remote meta-agent was started for a remote that is not connected.
+#[derive(Debug, Error)]
+#[error("No remote with ID '{0}' is connected.")]
+pub struct NoSuchRemote(String);
+
+/// A meta agent that allows the raw frames sent to and received from a single remote to be
+/// captured, to diagnose protocol issues. Capturing is toggled by sending a boolean to the `debug`
+/// value lane and the captured frames can be downloaded with a GET request to the `frames` HTTP
+/// lane (a DELETE request to the same lane will discard them). If the remote ID in the node URI of
+/// the meta-agent is not a connected remote, the meta-agent will fail to start.
+pub struct RemoteMetaAgent {
+    remotes: RemoteCaptures,
+}
+
+impl RemoteMetaAgent {
+    pub fn new(remotes: RemoteCaptures) -> RemoteMetaAgent {
+        RemoteMetaAgent { remotes }
+    }
+}
+
+impl Agent for RemoteMetaAgent {
+    fn run(
+        &self,
+        route: RouteUri,
+        route_params: HashMap<String, String>,
+        config: AgentConfig,
+        context: Box<dyn AgentContext + Send>,
+    ) -> BoxFuture<'static, AgentInitResult> {
+        let RemoteMetaAgent { remotes } = self;
+        run_init(remotes.clone(), route, route_params, config, context).boxed()
+    }
+}
+
+async fn run_init(
+    remotes: RemoteCaptures,
+    route: RouteUri,
+    route_params: HashMap<String, String>,
+    config: AgentConfig,
+    context: Box<dyn AgentContext + Send>,
+) -> AgentInitResult {
+    let remote_id = if let Some(remote_id) = route_params.get(REMOTE_PARAM) {
+        remote_id
+    } else {
+        return Err(AgentInitError::UserCodeError(Box::new(
+            MetaRouteError::new(route, REMOTE_PARAM),
+        )));
+    };
+    let capture = match Uuid::parse_str(remote_id)
+        .ok()
+        .and_then(|id| remotes.get(id))
+    {
+        Some(remote) => remote.capture,
+        None => {
+            return Err(AgentInitError::UserCodeError(Box::new(NoSuchRemote(
+                remote_id.clone(),
+            ))))
+        }
+    };
+
+    let mut lane_config = config.default_lane_config.unwrap_or_default();
+    lane_config.transient = true;
+    let debug_io = context
+        .add_lane(DEBUG_LANE, WarpLaneKind::Value, lane_config)
+        .await?;
+    let frames_rx = context.add_http_lane(FRAMES_LANE).await?;
+    Ok(run_task(context, capture, debug_io, frames_rx).boxed())
+}
+
+type Io = (ByteWriter, ByteReader);
+
+async fn run_task(
+    context: Box<dyn AgentContext + Send>,
+    capture: FrameCapture,
+    debug_io: Io,
+    frames_rx: HttpLaneRequestChannel,
+) -> Result<(), AgentTaskError> {
+    // deferred drop so the agent doesn't terminate early.
+    let _context = context;
+
+    let debug_lane = run_debug_lane(capture.clone(), debug_io).map(|result| {
+        result.map_err(|error| AgentTaskError::BadFrame {
+            lane: Text::new(DEBUG_LANE),
+            error,
+        })
+    });
+    let frames_lane = run_frames_lane(capture, frames_rx).map(Ok);
+    futures::future::try_join(debug_lane, frames_lane)
+        .await
+        .map(|_| ())
+}
+
+/// A value lane that reports whether frames are being captured for the remote. Commands sent to
+/// the lane enable or disable the capture.
+async fn run_debug_lane(capture: FrameCapture, debug_io: Io) -> Result<(), FrameIoError> {
+    let (tx, rx) = debug_io;
+
+    let mut input = FramedRead::new(rx, RawValueLaneRequestDecoder::default());
+    let mut output = FramedWrite::new(tx, ValueLaneResponseEncoder::default());
+
+    while let Some(request) = input.next().await.transpose()? {
+        match request {
+            LaneRequest::Command(body)
+            | LaneRequest::CommandFrom(_, body)
+            | LaneRequest::CorrelatedCommand(_, _, body) => {
+                let enabled = parse_bool(&body)?;
+                debug!(enabled, "Setting frame capture for remote.");
+                capture.set_enabled(enabled);
+                output.send(LaneResponse::StandardEvent(enabled)).await?;
+            }
+            LaneRequest::Sync(id)
+            | LaneRequest::SyncSince(id, _)
+            | LaneRequest::SyncRange(id, _) => {
+                output
+                    .send(LaneResponse::SyncEvent(id, capture.is_enabled()))
+                    .await?;
+                output.send(LaneResponse::<bool>::Synced(id)).await?;
+            }
+            LaneRequest::InitComplete => {}
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn parse_bool(body: &BytesMut) -> Result<bool, FrameIoError> {
+    let bad_body = |error| FrameIoError::BadFrame(InvalidFrame::InvalidMessageBody(error));
+    let body_str = std::str::from_utf8(body.as_ref())
+        .map_err(|error| bad_body(AsyncParseError::BadUtf8(error)))?;
+    parse_recognize(body_str, false).map_err(|error| bad_body(AsyncParseError::Parser(error)))
+}
+
+/// An HTTP lane from which the captured frames can be downloaded, as plain text with one frame
+/// per line.
+async fn run_frames_lane(capture: FrameCapture, mut frames_rx: HttpLaneRequestChannel) {
+    while let Some(request) = frames_rx.recv().await {
+        let (request, response_tx) = request.into_parts();
+        let response = match request.method {
+            Method::GET => {
+                let mut body = String::new();
+                for frame in capture.frames() {
+                    let content = String::from_utf8_lossy(frame.frame.as_ref());
+                    writeln!(body, "{} {} {}", frame.timestamp, frame.direction, content)
+                        .expect("Writing to a string should be infallible.");
+                }
+                text_response(StatusCode::OK, Bytes::from(body))
+            }
+            Method::DELETE => {
+                capture.clear();
+                text_response(StatusCode::NO_CONTENT, Bytes::new())
+            }
+            _ => text_response(StatusCode::METHOD_NOT_ALLOWED, Bytes::new()),
+        };
+        if response_tx.send(response).is_err() {
+            debug!("HTTP request dropped before the response was sent.");
+        }
+    }
+}
+
+pub(crate) fn text_response(status_code: StatusCode, payload: Bytes) -> RawHttpLaneResponse {
+    let headers = vec![
+        Header::new(StandardHeaderName::ContentType, "text/plain"),
+        Header::new(StandardHeaderName::ContentLength, payload.len().to_string()),
+    ];
+    HttpResponse {
+        status_code,
+        version: Version::HTTP_1_1,
+        headers,
+        payload,
+    }
+}
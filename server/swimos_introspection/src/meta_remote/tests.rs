@@ -0,0 +1,207 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::num::NonZeroUsize;
+
+use bytes::Bytes;
+use futures::future::{join, BoxFuture};
+use futures::{SinkExt, StreamExt};
+use swimos_agent_protocol::encoding::lane::{ValueLaneRequestEncoder, ValueLaneResponseDecoder};
+use swimos_agent_protocol::{LaneRequest, LaneResponse};
+use swimos_api::agent::{
+    AgentConfig, AgentContext, DownlinkKind, HttpLaneRequest, HttpLaneRequestChannel, LaneConfig,
+    StoreKind, WarpLaneKind,
+};
+use swimos_api::error::{AgentInitError, AgentRuntimeError, DownlinkRuntimeError, OpenStoreError};
+use swimos_api::http::{HttpRequest, Method, StatusCode, Uri};
+use swimos_remote::{CaptureDirection, FrameCapture, RemoteCaptures};
+use swimos_utilities::byte_channel::{byte_channel, ByteReader, ByteWriter};
+use swimos_utilities::non_zero_usize;
+use swimos_utilities::routing::RouteUri;
+use tokio::sync::mpsc;
+use tokio_util::codec::{FramedRead, FramedWrite};
+use uuid::Uuid;
+
+use crate::route::REMOTE_PARAM;
+
+use super::{run_debug_lane, run_frames_lane, run_init};
+
+const BUFFER_SIZE: NonZeroUsize = non_zero_usize!(128);
+const CAPTURE_SIZE: NonZeroUsize = non_zero_usize!(8);
+const SYNC_ID: Uuid = Uuid::from_u128(7);
+
+#[tokio::test]
+async fn debug_lane_toggles_capture() {
+    let capture = FrameCapture::new(CAPTURE_SIZE);
+    let (in_tx, in_rx) = byte_channel(BUFFER_SIZE);
+    let (out_tx, out_rx) = byte_channel(BUFFER_SIZE);
+
+    let lane_task = run_debug_lane(capture.clone(), (out_tx, in_rx));
+
+    let test_task = async {
+        let mut requests = FramedWrite::new(in_tx, ValueLaneRequestEncoder::default());
+        let mut responses = FramedRead::new(out_rx, ValueLaneResponseDecoder::<bool>::default());
+
+        requests
+            .send(LaneRequest::<bool>::Sync(SYNC_ID))
+            .await
+            .expect("Lane stopped.");
+        assert_eq!(
+            responses.next().await.unwrap().unwrap(),
+            LaneResponse::SyncEvent(SYNC_ID, false)
+        );
+        assert_eq!(
+            responses.next().await.unwrap().unwrap(),
+            LaneResponse::Synced(SYNC_ID)
+        );
+
+        requests
+            .send(LaneRequest::Command(true))
+            .await
+            .expect("Lane stopped.");
+        assert_eq!(
+            responses.next().await.unwrap().unwrap(),
+            LaneResponse::StandardEvent(true)
+        );
+        assert!(capture.is_enabled());
+
+        requests
+            .send(LaneRequest::Command(false))
+            .await
+            .expect("Lane stopped.");
+        assert_eq!(
+            responses.next().await.unwrap().unwrap(),
+            LaneResponse::StandardEvent(false)
+        );
+        assert!(!capture.is_enabled());
+    };
+
+    let (result, _) = join(lane_task, test_task).await;
+    assert!(result.is_ok());
+}
+
+async fn make_request(
+    tx: &mpsc::Sender<HttpLaneRequest>,
+    method: Method,
+) -> swimos_api::agent::RawHttpLaneResponse {
+    let request = HttpRequest {
+        method,
+        version: Default::default(),
+        uri: Uri::from_static("/swimos:meta:remote/id?lane=frames"),
+        headers: vec![],
+        payload: Bytes::new(),
+    };
+    let (request, response_rx) = HttpLaneRequest::new(request);
+    tx.send(request).await.expect("Lane stopped.");
+    response_rx.await.expect("No response.")
+}
+
+#[tokio::test]
+async fn frames_lane_downloads_capture() {
+    let capture = FrameCapture::new(CAPTURE_SIZE);
+    capture.set_enabled(true);
+    capture.record(CaptureDirection::Incoming, b"@sync(node:a,lane:b)");
+    capture.record(CaptureDirection::Outgoing, b"@synced(node:a,lane:b)");
+
+    let (tx, rx) = mpsc::channel(8);
+    let lane_task = run_frames_lane(capture.clone(), rx);
+
+    let test_task = async move {
+        let response = make_request(&tx, Method::GET).await;
+        assert_eq!(response.status_code, StatusCode::OK);
+        let body = std::str::from_utf8(response.payload.as_ref()).expect("Invalid UTF8");
+        let lines = body.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" in @sync(node:a,lane:b)"));
+        assert!(lines[1].ends_with(" out @synced(node:a,lane:b)"));
+
+        let response = make_request(&tx, Method::POST).await;
+        assert_eq!(response.status_code, StatusCode::METHOD_NOT_ALLOWED);
+
+        let response = make_request(&tx, Method::DELETE).await;
+        assert_eq!(response.status_code, StatusCode::NO_CONTENT);
+        assert!(capture.frames().is_empty());
+    };
+
+    join(lane_task, test_task).await;
+}
+
+struct MockAgentContext;
+
+impl AgentContext for MockAgentContext {
+    fn add_lane(
+        &self,
+        _name: &str,
+        _lane_kind: WarpLaneKind,
+        _config: LaneConfig,
+    ) -> BoxFuture<'static, Result<(ByteWriter, ByteReader), AgentRuntimeError>> {
+        panic!("Unexpected add lane invocation")
+    }
+
+    fn open_downlink(
+        &self,
+        _host: Option<&str>,
+        _node: &str,
+        _lane: &str,
+        _kind: DownlinkKind,
+    ) -> BoxFuture<'static, Result<(ByteWriter, ByteReader), DownlinkRuntimeError>> {
+        panic!("Unexpected open downlink invocation")
+    }
+
+    fn add_store(
+        &self,
+        _name: &str,
+        _kind: StoreKind,
+    ) -> BoxFuture<'static, Result<(ByteWriter, ByteReader), OpenStoreError>> {
+        panic!("Unexpected add store invocation")
+    }
+
+    fn ad_hoc_commands(&self) -> BoxFuture<'static, Result<ByteWriter, DownlinkRuntimeError>> {
+        panic!("Unexpected ad hoc commands invocation")
+    }
+
+    fn add_http_lane(
+        &self,
+        _name: &str,
+    ) -> BoxFuture<'static, Result<HttpLaneRequestChannel, AgentRuntimeError>> {
+        panic!("Unexpected add HTTP lane invocation")
+    }
+}
+
+#[tokio::test]
+async fn remote_meta_agent_unknown_remote() {
+    let remotes = RemoteCaptures::new(CAPTURE_SIZE);
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8080);
+    remotes.register(Uuid::from_u128(1), addr);
+
+    let other = Uuid::from_u128(2).to_string();
+    let route = format!("swimos:meta:remote/{}", other)
+        .parse::<RouteUri>()
+        .unwrap();
+    let params = [(REMOTE_PARAM.to_string(), other)]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    let result = run_init(
+        remotes,
+        route,
+        params,
+        AgentConfig::default(),
+        Box::new(MockAgentContext),
+    )
+    .await;
+    assert!(matches!(result, Err(AgentInitError::UserCodeError(_))));
+}
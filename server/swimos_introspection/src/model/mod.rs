@@ -24,7 +24,10 @@ use parking_lot::Mutex;
 use swimos_api::agent::LaneKind;
 use swimos_meta::LaneInfo;
 use swimos_model::Text;
-use swimos_runtime::agent::reporting::UplinkReportReader;
+use swimos_runtime::agent::{
+    recording::EnvelopeRecording,
+    reporting::{DeprecationReportReader, PruneReportReader, UplinkReportReader},
+};
 
 #[cfg(test)]
 mod tests;
@@ -67,6 +70,9 @@ impl AgentSnapshot {
 #[derive(Debug)]
 struct Inner {
     aggregate_reporter: UplinkReportReader,
+    prune_reader: PruneReportReader,
+    deprecation_reader: DeprecationReportReader,
+    recording: Option<EnvelopeRecording>,
     lanes: Mutex<HashMap<Text, LaneView>>,
     epoch: AtomicU64,
 }
@@ -78,9 +84,24 @@ pub struct AgentIntrospectionUpdater {
 }
 
 impl AgentIntrospectionUpdater {
-    pub fn new(aggregate_reporter: UplinkReportReader) -> Self {
+    /// # Arguments
+    /// * `aggregate_reporter` - Reader for the aggregate uplink statistics of the agent.
+    /// * `prune_reader` - Reader for the records of the remotes that are pruned from the agent.
+    /// * `deprecation_reader` - Reader for the records of lanes that are addressed by deprecated
+    ///   aliases.
+    /// * `recording` - Recording of the envelopes received and sent by the agent, if recording is
+    ///   allowed.
+    pub fn new(
+        aggregate_reporter: UplinkReportReader,
+        prune_reader: PruneReportReader,
+        deprecation_reader: DeprecationReportReader,
+        recording: Option<EnvelopeRecording>,
+    ) -> Self {
         let inner = Arc::new(Inner {
             aggregate_reporter,
+            prune_reader,
+            deprecation_reader,
+            recording,
             lanes: Default::default(),
             epoch: AtomicU64::new(0),
         });
@@ -108,6 +129,21 @@ impl AgentIntrospectionUpdater {
         epoch.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// The number of active uplinks, across all lanes of the agent. If the agent has stopped this
+    /// will return nothing.
+    pub fn link_count(&self) -> Option<u64> {
+        self.inner.aggregate_reporter.link_count()
+    }
+
+    /// The number of lanes of the agent that are still running.
+    pub fn lane_count(&self) -> usize {
+        let guard = self.inner.lanes.lock();
+        guard
+            .values()
+            .filter(|view| view.report_reader.is_active())
+            .count()
+    }
+
     /// Create an introspection handle for a meta-agent.
     pub fn make_handle(&self) -> AgentIntrospectionHandle {
         AgentIntrospectionHandle {
@@ -136,6 +172,7 @@ impl AgentIntrospectionHandle {
             lanes,
             epoch,
             aggregate_reporter,
+            ..
         } = &**inner;
         if aggregate_reporter.is_active() {
             let mut guard = lanes.lock();
@@ -169,6 +206,21 @@ impl AgentIntrospectionHandle {
     pub fn aggregate_reader(&self) -> UplinkReportReader {
         self.inner.aggregate_reporter.clone()
     }
+
+    /// Create a reader for the records of the remotes that are pruned from the agent.
+    pub fn prune_reader(&self) -> PruneReportReader {
+        self.inner.prune_reader.clone()
+    }
+
+    /// Create a reader for the records of lanes that are addressed by deprecated aliases.
+    pub fn deprecation_reader(&self) -> DeprecationReportReader {
+        self.inner.deprecation_reader.clone()
+    }
+
+    /// The recording of the envelopes received and sent by the agent, if recording is allowed.
+    pub fn recording(&self) -> Option<EnvelopeRecording> {
+        self.inner.recording.clone()
+    }
 }
 
 // Clear any lanes that have stopped running when producing a new snapshot.
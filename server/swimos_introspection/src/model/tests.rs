@@ -23,7 +23,12 @@ use super::{AgentIntrospectionUpdater, AgentSnapshot};
 #[test]
 fn snapshot_from_handle() {
     let reporter = UplinkReporter::default();
-    let updater = AgentIntrospectionUpdater::new(reporter.reader());
+    let updater = AgentIntrospectionUpdater::new(
+        reporter.reader(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    );
 
     let lane_reporter = UplinkReporter::default();
     updater.add_lane(Text::new("lane"), LaneKind::Value, lane_reporter.reader());
@@ -73,7 +78,12 @@ fn snapshot_from_handle() {
 #[test]
 fn drop_reporter() {
     let reporter = UplinkReporter::default();
-    let updater = AgentIntrospectionUpdater::new(reporter.reader());
+    let updater = AgentIntrospectionUpdater::new(
+        reporter.reader(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    );
 
     let mut handle = updater.make_handle();
 
@@ -18,10 +18,13 @@ use swimos_utilities::routing::RoutePattern;
 pub const NODE_PARAM: &str = "node_uri";
 /// The name of the route parameter containing the encoded lane name.
 pub const LANE_PARAM: &str = "lane_name";
+/// The name of the route parameter containing the ID of a remote.
+pub const REMOTE_PARAM: &str = "remote_id";
 
 const NODE_PATTERN: &str = "swimos:meta:node/:node_uri";
 const LANE_PATTERN: &str = "swimos:meta:node/:node_uri/lane/:lane_name";
 const MESH_PATTERN: &str = "swimos:meta:mesh";
+const REMOTE_PATTERN: &str = "swimos:meta:remote/:remote_id";
 
 /// Create a route pattern for the mesh meta-agents.
 pub fn mesh_pattern() -> RoutePattern {
@@ -38,9 +41,14 @@ pub fn lane_pattern() -> RoutePattern {
     RoutePattern::parse_str(LANE_PATTERN).expect("Lane pattern should be valid.")
 }
 
+/// Create a route pattern for the remote meta-agents.
+pub fn remote_pattern() -> RoutePattern {
+    RoutePattern::parse_str(REMOTE_PATTERN).expect("Remote pattern should be valid.")
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::route::mesh_pattern;
+    use crate::route::{mesh_pattern, remote_pattern, REMOTE_PARAM};
     use swimos_utilities::routing::RouteUri;
 
     use super::{lane_pattern, node_pattern, LANE_PARAM, NODE_PARAM};
@@ -79,4 +87,20 @@ mod tests {
         assert_eq!(map.get(NODE_PARAM), Some(&"unit/foo".to_string()));
         assert_eq!(map.get(LANE_PARAM), Some(&"pulse".to_string()));
     }
+
+    #[test]
+    fn recognize_remote() {
+        let uri = "swimos:meta:remote/4c6f2a8e-8d0b-4d5c-9a43-3e5e6b2f1a7d"
+            .parse::<RouteUri>()
+            .unwrap();
+        let pattern = remote_pattern();
+        let params = pattern.unapply_route_uri(&uri);
+        assert!(params.is_ok());
+        let map = params.unwrap();
+        assert_eq!(map.len(), 1);
+        assert_eq!(
+            map.get(REMOTE_PARAM),
+            Some(&"4c6f2a8e-8d0b-4d5c-9a43-3e5e6b2f1a7d".to_string())
+        );
+    }
 }
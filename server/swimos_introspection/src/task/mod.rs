@@ -15,10 +15,11 @@
 use crate::forest::UriForest;
 use crate::meta_agent::lane::LaneMetaAgent;
 use crate::meta_agent::node::NodeMetaAgent;
-use crate::meta_mesh::MetaMeshAgent;
-use crate::route::{lane_pattern, mesh_pattern, node_pattern};
+use crate::meta_mesh::{AgentRecovery, MetaMeshAgent};
+use crate::meta_remote::RemoteMetaAgent;
+use crate::route::{lane_pattern, mesh_pattern, node_pattern, remote_pattern};
 use std::sync::Arc;
-use std::{collections::HashMap, num::NonZeroUsize};
+use std::{collections::HashMap, num::NonZeroUsize, time::Duration};
 
 use futures::StreamExt;
 use futures::{stream::select, Future};
@@ -26,8 +27,10 @@ use parking_lot::RwLock;
 use swimos_api::agent::{Agent, LaneKind};
 use swimos_api::error::{IntrospectionStopped, LaneIntrospectionError, NodeIntrospectionError};
 use swimos_model::{Text, Timestamp};
+use swimos_remote::RemoteCaptures;
 use swimos_runtime::agent::{
-    reporting::{UplinkReportReader, UplinkReporter},
+    recording::EnvelopeRecording,
+    reporting::{DeprecationReportReader, PruneReportReader, UplinkReportReader, UplinkReporter},
     NodeReporting, UplinkReporterRegistration,
 };
 use swimos_utilities::routing::RoutePattern;
@@ -47,7 +50,12 @@ pub enum IntrospectionMessage {
         agent_id: Uuid,
         node_uri: Text,
         name: Text,
+        route: Text,
+        lane_buffer_size: usize,
         aggregate_reader: UplinkReportReader,
+        prune_reader: PruneReportReader,
+        deprecation_reader: DeprecationReportReader,
+        recording: Option<EnvelopeRecording>,
     },
     // Register a lane for an already existing agent instance.
     AddLane {
@@ -99,20 +107,26 @@ impl From<UplinkReporterRegistration> for IntrospectionMessage {
 /// # Arguments
 /// * `stopping` - Signal that the server is stopping.
 /// * `channel_size` - Size of the channel use to register new lanes.
+/// * `pulse_interval` - Interval on which the mesh meta-agent will generate a pulse.
+/// * `capture_traffic` - Whether the envelopes of the agents can be recorded.
+/// * `recovery` - Agents with persisted state that can be started by the mesh meta-agent.
 fn init_introspection(
     stopping: trigger::Receiver,
     channel_size: NonZeroUsize,
+    pulse_interval: Duration,
+    capture_traffic: bool,
+    recovery: Option<AgentRecovery>,
 ) -> (
     IntrospectionResolver,
     MetaMeshAgent,
     impl Future<Output = ()> + Send + 'static,
 ) {
-    let agents = Arc::new(RwLock::new(UriForest::new()));
+    let agents = Arc::new(RwLock::new(UriForest::ordered()));
     let (msg_tx, msg_rx) = mpsc::unbounded_channel();
     let (reg_tx, reg_rx) = mpsc::channel(channel_size.get());
     let task = introspection_task(stopping, msg_rx, reg_rx, agents.clone());
-    let meta_agent = MetaMeshAgent::new(agents);
-    let resolver = IntrospectionResolver::new(msg_tx, reg_tx);
+    let meta_agent = MetaMeshAgent::new(agents, pulse_interval, recovery);
+    let resolver = IntrospectionResolver::new(msg_tx, reg_tx).with_capture_traffic(capture_traffic);
     (resolver, meta_agent, task)
 }
 
@@ -133,10 +147,17 @@ pub trait AgentRegistration {
 /// # Arguments
 /// * `stopping` - Signal that the server is stopping.
 /// * `config` - Configuration parameters for the introspection agents.
+/// * `remotes` - Registry of the frame captures for the remotes connected to the server. The remote
+///   meta-agents are only registered if this is provided and traffic capture is enabled in the
+///   configuration.
+/// * `recovery` - Agents with persisted state (if the server has a store) that can be listed and
+///   started by the mesh meta-agent.
 /// * `registration` - Registration context to register the introspection agent routes.
 pub fn register_introspection<R>(
     stopping: trigger::Receiver,
     config: IntrospectionConfig,
+    remotes: Option<RemoteCaptures>,
+    recovery: Option<AgentRecovery>,
     registration: &mut R,
 ) -> (
     IntrospectionResolver,
@@ -145,28 +166,47 @@ pub fn register_introspection<R>(
 where
     R: AgentRegistration,
 {
-    let (resolver, mesh_meta, task) =
-        init_introspection(stopping, config.registration_channel_size);
+    let (resolver, mesh_meta, task) = init_introspection(
+        stopping,
+        config.registration_channel_size,
+        config.mesh_pulse_interval,
+        config.capture_traffic,
+        recovery,
+    );
     let node_meta = NodeMetaAgent::new(config, resolver.clone());
     let lane_meta = LaneMetaAgent::new(config, resolver.clone());
 
     registration.register(mesh_pattern(), mesh_meta);
     registration.register(node_pattern(), node_meta);
     registration.register(lane_pattern(), lane_meta);
+    if let Some(remotes) = remotes.filter(|_| config.capture_traffic) {
+        registration.register(remote_pattern(), RemoteMetaAgent::new(remotes));
+    }
     (resolver, task)
 }
 
 #[derive(Debug)]
 pub struct AgentMeta {
     pub name: Text,
+    /// The route pattern that the node URI of the agent matched.
+    pub route: Text,
+    /// The combined size of the input and output buffers of each lane of the agent.
+    pub lane_buffer_size: usize,
     pub created: Timestamp,
     pub updater: AgentIntrospectionUpdater,
 }
 
 impl AgentMeta {
-    fn new(name: Text, updater: AgentIntrospectionUpdater) -> AgentMeta {
+    fn new(
+        name: Text,
+        route: Text,
+        lane_buffer_size: usize,
+        updater: AgentIntrospectionUpdater,
+    ) -> AgentMeta {
         AgentMeta {
             name,
+            route,
+            lane_buffer_size,
             created: Timestamp::now(),
             updater,
         }
@@ -260,14 +300,22 @@ pub async fn introspection_task(
                 agent_id,
                 node_uri,
                 name,
+                route,
+                lane_buffer_size,
                 aggregate_reader,
+                prune_reader,
+                deprecation_reader,
+                recording,
             } => {
                 if !is_meta_node(&node_uri) {
-                    agents.insert(
-                        agent_id,
-                        node_uri,
-                        AgentMeta::new(name, AgentIntrospectionUpdater::new(aggregate_reader)),
+                    let updater = AgentIntrospectionUpdater::new(
+                        aggregate_reader,
+                        prune_reader,
+                        deprecation_reader,
+                        recording,
                     );
+                    let meta = AgentMeta::new(name, route, lane_buffer_size, updater);
+                    agents.insert(agent_id, node_uri, meta);
                 }
             }
             IntrospectionMessage::AddLane {
@@ -338,6 +386,7 @@ fn is_meta_node(node_uri: &Text) -> bool {
 pub struct IntrospectionResolver {
     queries: mpsc::UnboundedSender<IntrospectionMessage>,
     registrations: mpsc::Sender<UplinkReporterRegistration>,
+    capture_traffic: bool,
 }
 
 impl IntrospectionResolver {
@@ -348,35 +397,57 @@ impl IntrospectionResolver {
         IntrospectionResolver {
             queries,
             registrations,
+            capture_traffic: false,
         }
     }
 
+    /// Allow the envelopes of the agents that are registered to be recorded.
+    pub(crate) fn with_capture_traffic(mut self, capture_traffic: bool) -> Self {
+        self.capture_traffic = capture_traffic;
+        self
+    }
+
     /// Register a new agent instance for introspection.
     ///
     /// # Arguments
     /// * `agent_id` - The unique ID of the agent.
     /// * `route_uri` - The node URI of the agent.
     /// * `name` - The name of the agent; usually the struct name.
+    /// * `route` - The route pattern that the node URI matched.
+    /// * `lane_buffer_size` - The combined size of the input and output buffers of each lane of
+    ///   the agent (used to estimate the memory used by the agent).
     pub fn register_agent(
         &self,
         agent_id: Uuid,
         node_uri: RouteUri,
         name: Text,
+        route: &RoutePattern,
+        lane_buffer_size: usize,
     ) -> Result<NodeReporting, IntrospectionStopped> {
         let node_uri = Text::new(node_uri.as_str());
         let IntrospectionResolver {
             queries,
             registrations,
+            capture_traffic,
         } = self;
         let reporter = UplinkReporter::default();
+        let aggregate_reader = reporter.reader();
+        let mut reporting = NodeReporting::new(agent_id, reporter, registrations.clone());
+        if *capture_traffic {
+            reporting = reporting.with_recording(EnvelopeRecording::default());
+        }
         let message = IntrospectionMessage::AddAgent {
             agent_id,
             node_uri,
             name,
-            aggregate_reader: reporter.reader(),
+            route: Text::from(route.to_string()),
+            lane_buffer_size,
+            aggregate_reader,
+            prune_reader: reporting.prune_reader(),
+            deprecation_reader: reporting.deprecation_reader(),
+            recording: reporting.recording(),
         };
         if queries.send(message).is_ok() {
-            let reporting = NodeReporting::new(agent_id, reporter, registrations.clone());
             Ok(reporting)
         } else {
             Err(IntrospectionStopped)
@@ -0,0 +1,130 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use futures::{future::BoxFuture, FutureExt, SinkExt, StreamExt, TryFutureExt};
+use swimos_agent_protocol::{
+    encoding::lane::{MapLaneResponseEncoder, RawValueLaneRequestDecoder},
+    LaneRequest, LaneResponse, MapOperation,
+};
+use swimos_api::{
+    agent::{Agent, AgentConfig, AgentContext, AgentInitResult, WarpLaneKind},
+    error::{AgentTaskError, FrameIoError},
+};
+use swimos_form::Form;
+use swimos_model::Text;
+use swimos_utilities::{
+    byte_channel::{ByteReader, ByteWriter},
+    routing::{RoutePattern, RouteUri},
+};
+use tokio_util::codec::{FramedRead, FramedWrite};
+
+use super::Cluster;
+
+const PARTITIONS_PATTERN: &str = "swimos:meta:partitions";
+const PARTITIONS_LANE: &str = "partitions";
+
+/// Create a route pattern for the meta-agent that describes the partitions of a cluster.
+pub fn partitions_pattern() -> RoutePattern {
+    RoutePattern::parse_str(PARTITIONS_PATTERN).expect("Partitions pattern should be valid.")
+}
+
+/// A meta-agent with a demand map lane, keyed by the token that ends each partition (as a decimal
+/// string), describing the members of the cluster that own it.
+pub struct PartitionMetaAgent {
+    cluster: Cluster,
+}
+
+impl PartitionMetaAgent {
+    pub fn new(cluster: Cluster) -> Self {
+        PartitionMetaAgent { cluster }
+    }
+}
+
+impl Agent for PartitionMetaAgent {
+    fn run(
+        &self,
+        _route: RouteUri,
+        _route_params: HashMap<String, String>,
+        config: AgentConfig,
+        context: Box<dyn AgentContext + Send>,
+    ) -> BoxFuture<'static, AgentInitResult> {
+        run_init(self.cluster.clone(), config, context).boxed()
+    }
+}
+
+async fn run_init(
+    cluster: Cluster,
+    config: AgentConfig,
+    context: Box<dyn AgentContext + Send>,
+) -> AgentInitResult {
+    let mut lane_config = config.default_lane_config.unwrap_or_default();
+    lane_config.transient = true;
+    let io = context
+        .add_lane(PARTITIONS_LANE, WarpLaneKind::DemandMap, lane_config)
+        .await?;
+    Ok(async move {
+        let result = run_task(cluster, io)
+            .map_err(|error| AgentTaskError::BadFrame {
+                lane: Text::new(PARTITIONS_LANE),
+                error,
+            })
+            .await;
+        // deferred drop so the agent doesn't terminate early.
+        drop(context);
+        result
+    }
+    .boxed())
+}
+
+#[derive(Form, Debug, Clone, PartialEq, Eq)]
+pub struct PartitionInfo {
+    pub start: u64,
+    pub end: u64,
+    pub owners: Vec<String>,
+}
+
+pub(super) async fn run_task(
+    cluster: Cluster,
+    io: (ByteWriter, ByteReader),
+) -> Result<(), FrameIoError> {
+    let (tx, rx) = io;
+    let mut input = FramedRead::new(rx, RawValueLaneRequestDecoder::default());
+    let mut output = FramedWrite::new(tx, MapLaneResponseEncoder::default());
+
+    while let Some(request) = input.next().await {
+        if let LaneRequest::Sync(id)
+        | LaneRequest::SyncSince(id, _)
+        | LaneRequest::SyncRange(id, _) = request?
+        {
+            for partition in cluster.partitions() {
+                let info = PartitionInfo {
+                    start: partition.start,
+                    end: partition.end,
+                    owners: partition.owners.iter().map(ToString::to_string).collect(),
+                };
+                let key = info.end.to_string();
+                let op = MapOperation::Update {
+                    key: key.as_str(),
+                    value: &info,
+                };
+                output.send(LaneResponse::SyncEvent(id, op)).await?;
+            }
+            let synced: LaneResponse<MapOperation<&str, &PartitionInfo>> = LaneResponse::Synced(id);
+            output.send(synced).await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,303 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::BTreeMap, hash::Hasher, num::NonZeroUsize, sync::Arc};
+
+use fnv::FnvHasher;
+use parking_lot::RwLock;
+use swimos_remote::{BadWarpUrl, SchemeHostPort};
+use swimos_utilities::non_zero_usize;
+use tokio::sync::watch;
+
+mod meta;
+mod pulse;
+
+pub use meta::partitions_pattern;
+pub(crate) use meta::PartitionMetaAgent;
+pub use pulse::cluster_pattern;
+pub(crate) use pulse::ClusterMetaAgent;
+
+#[cfg(test)]
+mod tests;
+
+const DEFAULT_REPLICATION_FACTOR: NonZeroUsize = non_zero_usize!(1);
+const DEFAULT_VIRTUAL_NODES: NonZeroUsize = non_zero_usize!(64);
+
+/// Configuration for the partitioning of nodes between the members of a cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionConfig {
+    /// The number of members that own each partition. The first owner of a partition hosts its
+    /// nodes and the remaining owners are the members that will take over, in order, if it leaves.
+    pub replication_factor: NonZeroUsize,
+    /// The number of tokens that each member places on the hash ring. More tokens spread the
+    /// nodes more evenly between the members.
+    pub virtual_nodes: NonZeroUsize,
+}
+
+impl Default for PartitionConfig {
+    fn default() -> Self {
+        Self {
+            replication_factor: DEFAULT_REPLICATION_FACTOR,
+            virtual_nodes: DEFAULT_VIRTUAL_NODES,
+        }
+    }
+}
+
+/// A range of the hash ring and the members of the cluster that own it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Partition {
+    /// The token that ends the previous partition (exclusive).
+    pub start: u64,
+    /// The token that ends this partition (inclusive).
+    pub end: u64,
+    /// The owners of the partition, starting with the member that hosts its nodes.
+    pub owners: Vec<SchemeHostPort>,
+}
+
+/// The members of a cluster of servers that share the nodes of a plane between them. Each node URI
+/// is assigned to members by consistent hashing so that, when a member joins or leaves, only the
+/// nodes in the partitions adjacent to its tokens change owner. When a member leaves, each of its
+/// partitions is taken over by the next of its owners.
+///
+/// Only nodes with URIs that are paths (such as `/example/1`) and that match one of the routes of
+/// the plane are partitioned. Envelopes for nodes that are owned by another member are proxied to
+/// it (and are never proxied a second time by that member) and HTTP requests are redirected to it.
+/// When the membership changes, running agents for nodes that are now owned by another member are
+/// stopped so that each node is only hosted by one member. Their state is not transferred so the
+/// members should share a persistence store if it must be kept.
+///
+/// The handle can be cloned and shared with a membership protocol that calls [`Cluster::join`] and
+/// [`Cluster::leave`] as members are discovered or lost.
+#[derive(Debug, Clone)]
+pub struct Cluster {
+    ring: Arc<RwLock<HashRing>>,
+    changes: Arc<watch::Sender<()>>,
+}
+
+impl Cluster {
+    /// # Arguments
+    /// * `local` - The URL of this server, as it is known to the other members of the cluster.
+    /// * `config` - Configuration for the partitioning.
+    pub fn new(local: &str, config: PartitionConfig) -> Result<Self, BadWarpUrl> {
+        let local = local.parse()?;
+        let (changes, _) = watch::channel(());
+        Ok(Cluster {
+            ring: Arc::new(RwLock::new(HashRing::new(local, config))),
+            changes: Arc::new(changes),
+        })
+    }
+
+    /// Add a member to the cluster. Returns false if it was already a member.
+    ///
+    /// # Arguments
+    /// * `member` - The URL of the member.
+    pub fn join(&self, member: &str) -> Result<bool, BadWarpUrl> {
+        let member = member.parse()?;
+        let joined = self.ring.write().join(member);
+        if joined {
+            self.changes.send_replace(());
+        }
+        Ok(joined)
+    }
+
+    /// Remove a member from the cluster. Returns false if it was not a member. The local server
+    /// cannot leave the cluster.
+    ///
+    /// # Arguments
+    /// * `member` - The URL of the member.
+    pub fn leave(&self, member: &str) -> Result<bool, BadWarpUrl> {
+        let member = member.parse()?;
+        let left = self.ring.write().leave(&member);
+        if left {
+            self.changes.send_replace(());
+        }
+        Ok(left)
+    }
+
+    /// The URLs of the current members of the cluster, including the local server.
+    pub fn members(&self) -> Vec<String> {
+        self.ring
+            .read()
+            .members
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// The URL of the local server.
+    pub(crate) fn local(&self) -> String {
+        self.ring.read().local.to_string()
+    }
+
+    /// The URLs of the members that own a node, starting with the member that hosts it.
+    ///
+    /// # Arguments
+    /// * `node` - The URI of the node.
+    pub fn owners(&self, node: &str) -> Vec<String> {
+        let ring = self.ring.read();
+        ring.owners(ring.token_for(node))
+            .map(|index| ring.members[index].to_string())
+            .collect()
+    }
+
+    /// The member that hosts a node, if it is not the local server.
+    pub(crate) fn remote_owner(&self, node: &str) -> Option<SchemeHostPort> {
+        let ring = self.ring.read();
+        let index = ring.owners(ring.token_for(node)).next()?;
+        let owner = &ring.members[index];
+        if *owner == ring.local {
+            None
+        } else {
+            Some(owner.clone())
+        }
+    }
+
+    /// Subscribe to changes to the membership of the cluster.
+    pub(crate) fn subscribe(&self) -> watch::Receiver<()> {
+        self.changes.subscribe()
+    }
+
+    /// A description of every partition of the ring, in token order.
+    pub(crate) fn partitions(&self) -> Vec<Partition> {
+        self.ring.read().partitions()
+    }
+}
+
+#[derive(Debug)]
+struct HashRing {
+    config: PartitionConfig,
+    local: SchemeHostPort,
+    // Kept sorted so that every member of the cluster builds an identical ring.
+    members: Vec<SchemeHostPort>,
+    tokens: BTreeMap<u64, usize>,
+}
+
+impl HashRing {
+    fn new(local: SchemeHostPort, config: PartitionConfig) -> Self {
+        let mut ring = HashRing {
+            config,
+            local: local.clone(),
+            members: vec![local],
+            tokens: BTreeMap::new(),
+        };
+        ring.rebuild();
+        ring
+    }
+
+    fn join(&mut self, member: SchemeHostPort) -> bool {
+        if self.members.contains(&member) {
+            false
+        } else {
+            self.members.push(member);
+            self.rebuild();
+            true
+        }
+    }
+
+    fn leave(&mut self, member: &SchemeHostPort) -> bool {
+        if *member == self.local {
+            return false;
+        }
+        let len = self.members.len();
+        self.members.retain(|m| m != member);
+        if self.members.len() == len {
+            false
+        } else {
+            self.rebuild();
+            true
+        }
+    }
+
+    fn rebuild(&mut self) {
+        let HashRing {
+            config,
+            members,
+            tokens,
+            ..
+        } = self;
+        members.sort_by_cached_key(ToString::to_string);
+        tokens.clear();
+        for (index, member) in members.iter().enumerate() {
+            let name = member.to_string();
+            for i in 0..config.virtual_nodes.get() {
+                tokens
+                    .entry(hash(format!("{}#{}", name, i).as_bytes()))
+                    .or_insert(index);
+            }
+        }
+    }
+
+    // The first token, clockwise, from the hash of the node.
+    fn token_for(&self, node: &str) -> u64 {
+        let h = hash(node.as_bytes());
+        self.tokens
+            .range(h..)
+            .chain(&self.tokens)
+            .next()
+            .map(|(token, _)| *token)
+            .unwrap_or_default()
+    }
+
+    // The distinct members that own the partition ending at a token, in the order in which they
+    // are found walking clockwise around the ring. When the first of these leaves, its tokens are
+    // removed and the next becomes the first owner of the partition.
+    fn owners(&self, token: u64) -> impl Iterator<Item = usize> + '_ {
+        let mut seen = Vec::with_capacity(self.config.replication_factor.get());
+        self.tokens
+            .range(token..)
+            .chain(self.tokens.range(..token))
+            .filter_map(move |(_, index)| {
+                if seen.contains(index) {
+                    None
+                } else {
+                    seen.push(*index);
+                    Some(*index)
+                }
+            })
+            .take(self.config.replication_factor.get())
+    }
+
+    fn partitions(&self) -> Vec<Partition> {
+        let mut start = self.tokens.keys().next_back().copied().unwrap_or_default();
+        self.tokens
+            .keys()
+            .map(|end| {
+                let partition = Partition {
+                    start,
+                    end: *end,
+                    owners: self
+                        .owners(*end)
+                        .map(|index| self.members[index].clone())
+                        .collect(),
+                };
+                start = *end;
+                partition
+            })
+            .collect()
+    }
+}
+
+// FNV-1a is stable across processes but distributes similar strings poorly so the result is
+// passed through the MurmurHash3 finalizer.
+fn hash(bytes: &[u8]) -> u64 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(bytes);
+    let mut h = hasher.finish();
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51afd7ed558ccd);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xc4ceb9fe1a85ec53);
+    h ^= h >> 33;
+    h
+}
@@ -0,0 +1,387 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    pin::pin,
+    time::Duration,
+};
+
+use futures::{
+    future::{ready, BoxFuture},
+    stream::{once, BoxStream, FuturesUnordered, SelectAll},
+    FutureExt, SinkExt, Stream, StreamExt, TryFutureExt,
+};
+use swimos_agent_protocol::{
+    encoding::{
+        downlink::ValueNotificationDecoder,
+        lane::{MapLaneResponseEncoder, RawValueLaneRequestDecoder, ValueLaneResponseEncoder},
+    },
+    DownlinkNotification, LaneRequest, LaneResponse, MapOperation,
+};
+use swimos_api::{
+    agent::{Agent, AgentConfig, AgentContext, AgentInitResult, DownlinkKind, WarpLaneKind},
+    error::{AgentTaskError, DownlinkRuntimeError, FrameIoError},
+};
+use swimos_form::Form;
+use swimos_meta::MeshPulse;
+use swimos_model::Text;
+use swimos_utilities::{
+    byte_channel::{ByteReader, ByteWriter},
+    routing::{RoutePattern, RouteUri},
+};
+use tokio::time::Instant;
+use tokio_stream::wrappers::IntervalStream;
+use tokio_util::codec::{FramedRead, FramedWrite};
+use tracing::debug;
+
+use super::Cluster;
+
+const CLUSTER_PATTERN: &str = "swimos:meta:cluster";
+const PULSE_LANE: &str = "pulse";
+const MEMBERS_LANE: &str = "members";
+
+const MESH_NODE: &str = "swimos:meta:mesh";
+const MESH_PULSE_LANE: &str = "pulse";
+
+// A member that has not reported a pulse for this many intervals is considered to be unhealthy.
+const STALE_INTERVALS: u32 = 3;
+
+/// Create a route pattern for the meta-agent that aggregates the pulses of the members of a cluster.
+pub fn cluster_pattern() -> RoutePattern {
+    RoutePattern::parse_str(CLUSTER_PATTERN).expect("Cluster pattern should be valid.")
+}
+
+/// A meta-agent that links to the pulse lane of the mesh meta-agent of every member of a cluster
+/// (including the local server) and rolls them up into cluster-wide statistics. This allows the
+/// whole cluster to be monitored through whichever member a client connects to. It has two lanes:
+///
+/// 1. `pulse` - A value lane with the totals for the cluster, updated on a fixed schedule.
+/// 2. `members` - A map lane, keyed by the URL of each member, describing its health.
+pub struct ClusterMetaAgent {
+    cluster: Cluster,
+    pulse_interval: Duration,
+}
+
+impl ClusterMetaAgent {
+    /// # Arguments
+    /// * `cluster` - The members of the cluster.
+    /// * `pulse_interval` - Interval on which the pulse of the cluster is generated. This should
+    ///   match the interval of the pulses of the mesh meta-agents of the members.
+    pub fn new(cluster: Cluster, pulse_interval: Duration) -> Self {
+        ClusterMetaAgent {
+            cluster,
+            pulse_interval,
+        }
+    }
+}
+
+impl Agent for ClusterMetaAgent {
+    fn run(
+        &self,
+        _route: RouteUri,
+        _route_params: HashMap<String, String>,
+        config: AgentConfig,
+        context: Box<dyn AgentContext + Send>,
+    ) -> BoxFuture<'static, AgentInitResult> {
+        run_init(self.cluster.clone(), self.pulse_interval, config, context).boxed()
+    }
+}
+
+async fn run_init(
+    cluster: Cluster,
+    pulse_interval: Duration,
+    config: AgentConfig,
+    context: Box<dyn AgentContext + Send>,
+) -> AgentInitResult {
+    let mut lane_config = config.default_lane_config.unwrap_or_default();
+    lane_config.transient = true;
+    let pulse_io = context
+        .add_lane(PULSE_LANE, WarpLaneKind::Value, lane_config)
+        .await?;
+    let members_io = context
+        .add_lane(MEMBERS_LANE, WarpLaneKind::Map, lane_config)
+        .await?;
+    Ok(async move {
+        let ticks = IntervalStream::new(tokio::time::interval(pulse_interval)).map(|_| ());
+        // The opener owns the context so the agent doesn't terminate early.
+        let open = move |host: Option<&str>| {
+            context.open_downlink(host, MESH_NODE, MESH_PULSE_LANE, DownlinkKind::Value)
+        };
+        run_task(
+            cluster,
+            pulse_interval * STALE_INTERVALS,
+            ticks,
+            open,
+            pulse_io,
+            members_io,
+        )
+        .map_err(|error| AgentTaskError::BadFrame {
+            lane: Text::new(PULSE_LANE),
+            error,
+        })
+        .await
+    }
+    .boxed())
+}
+
+/// Statistics for the whole cluster. Only the members that are healthy contribute to the
+/// counts of agents and links.
+#[derive(Form, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClusterPulse {
+    #[form(name = "memberCount")]
+    pub member_count: u64,
+    #[form(name = "healthyCount")]
+    pub healthy_count: u64,
+    #[form(name = "agentCount")]
+    pub agent_count: u64,
+    #[form(name = "linkCount")]
+    pub link_count: u64,
+}
+
+/// The health of a member of the cluster. A member is healthy if it is linked and has reported
+/// a pulse recently. The counts are taken from the last pulse that the member reported.
+#[derive(Form, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemberHealth {
+    pub healthy: bool,
+    #[form(name = "agentCount")]
+    pub agent_count: u64,
+    #[form(name = "linkCount")]
+    pub link_count: u64,
+}
+
+type Io = (ByteWriter, ByteReader);
+type Opened = (String, u64, Result<Io, DownlinkRuntimeError>);
+type Notification = Option<Result<DownlinkNotification<MeshPulse>, FrameIoError>>;
+
+enum Link {
+    Closed,
+    Opening,
+    Open {
+        // The writer is held to keep the downlink open.
+        _writer: ByteWriter,
+    },
+}
+
+struct MemberState {
+    // Distinguishes the downlinks that have been opened to the member, in case it leaves and
+    // rejoins the cluster before the old downlink stops.
+    epoch: u64,
+    link: Link,
+    linked: bool,
+    last_pulse: Option<(Instant, MeshPulse)>,
+    reported: Option<MemberHealth>,
+}
+
+impl MemberState {
+    fn new(epoch: u64) -> Self {
+        MemberState {
+            epoch,
+            link: Link::Closed,
+            linked: false,
+            last_pulse: None,
+            reported: None,
+        }
+    }
+
+    fn health(&self, now: Instant, stale_after: Duration) -> MemberHealth {
+        match self.last_pulse {
+            Some((received, pulse)) => MemberHealth {
+                healthy: self.linked && now.duration_since(received) <= stale_after,
+                agent_count: pulse.agent_count,
+                link_count: pulse.link_count,
+            },
+            None => MemberHealth::default(),
+        }
+    }
+}
+
+pub(super) async fn run_task<S, F>(
+    cluster: Cluster,
+    stale_after: Duration,
+    ticks: S,
+    open: F,
+    pulse_io: Io,
+    members_io: Io,
+) -> Result<(), FrameIoError>
+where
+    S: Stream<Item = ()>,
+    F: Fn(Option<&str>) -> BoxFuture<'static, Result<Io, DownlinkRuntimeError>>,
+{
+    let (pulse_tx, pulse_rx) = pulse_io;
+    let (members_tx, members_rx) = members_io;
+
+    let mut pulse_input = FramedRead::new(pulse_rx, RawValueLaneRequestDecoder::default());
+    let mut pulse_output = FramedWrite::new(pulse_tx, ValueLaneResponseEncoder::default());
+    let mut members_input = FramedRead::new(members_rx, RawValueLaneRequestDecoder::default());
+    let mut members_output = FramedWrite::new(members_tx, MapLaneResponseEncoder::default());
+
+    let local = cluster.local();
+    let mut members: BTreeMap<String, MemberState> = BTreeMap::new();
+    let mut next_epoch = 0;
+    let mut last_pulse = ClusterPulse::default();
+
+    let mut pending: FuturesUnordered<BoxFuture<'static, Opened>> = FuturesUnordered::new();
+    let mut downlinks: SelectAll<BoxStream<'static, (String, u64, Notification)>> =
+        SelectAll::new();
+    let mut ticks = pin!(ticks);
+
+    loop {
+        tokio::select! {
+            biased;
+            maybe_request = pulse_input.next() => match maybe_request.transpose()? {
+                Some(LaneRequest::Sync(id) | LaneRequest::SyncSince(id, _) | LaneRequest::SyncRange(id, _)) => {
+                    pulse_output.send(LaneResponse::SyncEvent(id, &last_pulse)).await?;
+                    let synced: LaneResponse<&ClusterPulse> = LaneResponse::Synced(id);
+                    pulse_output.send(synced).await?;
+                }
+                Some(_) => {}
+                None => break Ok(()),
+            },
+            maybe_request = members_input.next() => match maybe_request.transpose()? {
+                Some(LaneRequest::Sync(id) | LaneRequest::SyncSince(id, _) | LaneRequest::SyncRange(id, _)) => {
+                    for (member, state) in &members {
+                        if let Some(health) = &state.reported {
+                            let op = MapOperation::Update {
+                                key: member.as_str(),
+                                value: health,
+                            };
+                            members_output.send(LaneResponse::SyncEvent(id, op)).await?;
+                        }
+                    }
+                    let synced: LaneResponse<MapOperation<&str, &MemberHealth>> =
+                        LaneResponse::Synced(id);
+                    members_output.send(synced).await?;
+                }
+                // The lane is read only.
+                Some(_) => {}
+                None => break Ok(()),
+            },
+            Some((member, epoch, result)) = pending.next(), if !pending.is_empty() => {
+                let Some(state) = members.get_mut(&member).filter(|s| s.epoch == epoch) else {
+                    continue;
+                };
+                match result {
+                    Ok((writer, reader)) => {
+                        state.link = Link::Open { _writer: writer };
+                        let notifications = FramedRead::new(
+                            reader,
+                            ValueNotificationDecoder::<MeshPulse>::default(),
+                        )
+                        .map(Some)
+                        .chain(once(ready(None)))
+                        .map(move |notification| (member.clone(), epoch, notification));
+                        downlinks.push(notifications.boxed());
+                    }
+                    Err(error) => {
+                        debug!(member, error = %error, "Failed to link to the pulse of a member of the cluster.");
+                        state.link = Link::Closed;
+                    }
+                }
+            },
+            Some((member, epoch, notification)) = downlinks.next(), if !downlinks.is_empty() => {
+                let Some(state) = members.get_mut(&member).filter(|s| s.epoch == epoch) else {
+                    continue;
+                };
+                match notification {
+                    Some(Ok(DownlinkNotification::Linked)) => {
+                        state.linked = true;
+                    }
+                    Some(Ok(DownlinkNotification::Event { body })) => {
+                        state.last_pulse = Some((Instant::now(), body));
+                    }
+                    Some(Ok(DownlinkNotification::Unlinked)) => {
+                        state.linked = false;
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(error)) => {
+                        debug!(member, error = %error, "Invalid pulse from a member of the cluster.");
+                        state.link = Link::Closed;
+                        state.linked = false;
+                    }
+                    None => {
+                        state.link = Link::Closed;
+                        state.linked = false;
+                    }
+                }
+                let health = state.health(Instant::now(), stale_after);
+                if state.reported != Some(health) {
+                    state.reported = Some(health);
+                    let op = MapOperation::Update {
+                        key: member.as_str(),
+                        value: &health,
+                    };
+                    members_output.send(LaneResponse::StandardEvent(op)).await?;
+                }
+            },
+            maybe_tick = ticks.next() => {
+                if maybe_tick.is_none() {
+                    break Ok(());
+                }
+                let current = cluster.members();
+                let departed = members
+                    .keys()
+                    .filter(|member| !current.contains(*member))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for member in departed {
+                    if let Some(MemberState { reported: Some(_), .. }) = members.remove(&member) {
+                        let op: MapOperation<&str, &MemberHealth> = MapOperation::Remove {
+                            key: member.as_str(),
+                        };
+                        members_output.send(LaneResponse::StandardEvent(op)).await?;
+                    }
+                }
+                for member in current {
+                    members.entry(member).or_insert_with(|| {
+                        next_epoch += 1;
+                        MemberState::new(next_epoch)
+                    });
+                }
+
+                let now = Instant::now();
+                let mut pulse = ClusterPulse::default();
+                for (member, state) in &mut members {
+                    if matches!(state.link, Link::Closed) {
+                        state.link = Link::Opening;
+                        let host = if *member == local {
+                            None
+                        } else {
+                            Some(member.as_str())
+                        };
+                        let (key, epoch) = (member.clone(), state.epoch);
+                        pending.push(open(host).map(move |result| (key, epoch, result)).boxed());
+                    }
+                    let health = state.health(now, stale_after);
+                    if state.reported != Some(health) {
+                        state.reported = Some(health);
+                        let op = MapOperation::Update {
+                            key: member.as_str(),
+                            value: &health,
+                        };
+                        members_output.send(LaneResponse::StandardEvent(op)).await?;
+                    }
+                    pulse.member_count += 1;
+                    if health.healthy {
+                        pulse.healthy_count += 1;
+                        pulse.agent_count = pulse.agent_count.saturating_add(health.agent_count);
+                        pulse.link_count = pulse.link_count.saturating_add(health.link_count);
+                    }
+                }
+                pulse_output.send(LaneResponse::StandardEvent(&pulse)).await?;
+                last_pulse = pulse;
+            }
+        }
+    }
+}
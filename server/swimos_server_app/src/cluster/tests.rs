@@ -0,0 +1,515 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, num::NonZeroUsize, time::Duration};
+
+use futures::{
+    future::{join, ready},
+    FutureExt, SinkExt, StreamExt,
+};
+use swimos_agent_protocol::{
+    encoding::{
+        downlink::DownlinkNotificationEncoder,
+        lane::{MapLaneResponseDecoder, RawValueLaneRequestEncoder, ValueLaneResponseDecoder},
+    },
+    DownlinkNotification, LaneRequest, LaneResponse, MapOperation,
+};
+use swimos_meta::MeshPulse;
+use swimos_model::Text;
+use swimos_recon::print_recon_compact;
+use swimos_remote::BadWarpUrl;
+use swimos_utilities::{
+    byte_channel::{byte_channel, ByteReader, ByteWriter},
+    non_zero_usize,
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::codec::{FramedRead, FramedWrite};
+use uuid::Uuid;
+
+use super::{
+    meta::{run_task, PartitionInfo},
+    pulse::{self, ClusterPulse, MemberHealth},
+    Cluster, PartitionConfig,
+};
+
+const LOCAL: &str = "ws://local:8080";
+const PEER1: &str = "ws://peer1:8080";
+const PEER2: &str = "ws://peer2:8080";
+
+fn nodes() -> impl Iterator<Item = String> {
+    (0..200).map(|i| format!("/node/{}", i))
+}
+
+fn make_cluster(replication_factor: NonZeroUsize) -> Cluster {
+    let config = PartitionConfig {
+        replication_factor,
+        ..Default::default()
+    };
+    let cluster = Cluster::new(LOCAL, config).expect("Invalid host.");
+    assert!(cluster.join(PEER1).expect("Invalid host."));
+    assert!(cluster.join(PEER2).expect("Invalid host."));
+    cluster
+}
+
+#[test]
+fn single_member_owns_everything() {
+    let cluster = Cluster::new(LOCAL, Default::default()).expect("Invalid host.");
+    assert_eq!(cluster.members(), vec![LOCAL.to_string()]);
+    for node in nodes() {
+        assert_eq!(cluster.owners(&node), vec![LOCAL.to_string()]);
+        assert!(cluster.remote_owner(&node).is_none());
+    }
+}
+
+#[test]
+fn nodes_are_spread_between_members() {
+    let cluster = make_cluster(non_zero_usize!(1));
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for node in nodes() {
+        let owners = cluster.owners(&node);
+        assert_eq!(owners.len(), 1);
+        *counts.entry(owners[0].clone()).or_default() += 1;
+        let is_local = owners[0] == LOCAL;
+        assert_eq!(cluster.remote_owner(&node).is_none(), is_local);
+    }
+    assert_eq!(counts.len(), 3);
+}
+
+#[test]
+fn assignment_is_independent_of_join_order() {
+    let first = make_cluster(non_zero_usize!(2));
+    let second = Cluster::new(
+        PEER2,
+        PartitionConfig {
+            replication_factor: non_zero_usize!(2),
+            ..Default::default()
+        },
+    )
+    .expect("Invalid host.");
+    second.join(PEER1).expect("Invalid host.");
+    second.join(LOCAL).expect("Invalid host.");
+    for node in nodes() {
+        assert_eq!(first.owners(&node), second.owners(&node));
+    }
+}
+
+#[test]
+fn replicas_are_distinct_members() {
+    let cluster = make_cluster(non_zero_usize!(2));
+    for node in nodes() {
+        let owners = cluster.owners(&node);
+        assert_eq!(owners.len(), 2);
+        assert_ne!(owners[0], owners[1]);
+    }
+
+    let cluster = make_cluster(non_zero_usize!(5));
+    for node in nodes() {
+        assert_eq!(cluster.owners(&node).len(), 3);
+    }
+}
+
+#[test]
+fn leaving_only_moves_the_nodes_of_that_member() {
+    let cluster = make_cluster(non_zero_usize!(2));
+    let before = nodes()
+        .map(|node| (node.clone(), cluster.owners(&node)))
+        .collect::<Vec<_>>();
+
+    assert!(cluster.leave(PEER1).expect("Invalid host."));
+    assert!(!cluster.leave(PEER1).expect("Invalid host."));
+    assert_eq!(cluster.members().len(), 2);
+
+    for (node, owners) in before {
+        let primary = &owners[0];
+        let new_owners = cluster.owners(&node);
+        if primary == PEER1 {
+            // The first replica takes over.
+            assert_eq!(&new_owners[0], &owners[1]);
+        } else {
+            assert_eq!(&new_owners[0], primary);
+        }
+    }
+}
+
+#[test]
+fn partition_survives_loss_of_primary() {
+    let cluster = make_cluster(non_zero_usize!(2));
+    let lost = cluster
+        .partitions()
+        .into_iter()
+        .filter(|partition| partition.owners[0].to_string() == PEER1)
+        .collect::<Vec<_>>();
+    assert!(!lost.is_empty());
+
+    assert!(cluster.leave(PEER1).expect("Invalid host."));
+
+    let partitions = cluster.partitions();
+    for old in lost {
+        // The tokens of the member that left are removed so its range is now covered by the
+        // partition that follows it on the ring.
+        let successor = partitions
+            .iter()
+            .find(|partition| partition.end >= old.end)
+            .unwrap_or(&partitions[0]);
+        assert_eq!(successor.owners[0], old.owners[1]);
+        assert_eq!(successor.owners.len(), 2);
+        assert!(successor
+            .owners
+            .iter()
+            .all(|owner| owner.to_string() != PEER1));
+    }
+    for node in nodes() {
+        let owners = cluster.owners(&node);
+        assert_eq!(owners.len(), 2);
+        assert!(!owners.iter().any(|owner| owner == PEER1));
+        assert_eq!(cluster.remote_owner(&node).is_none(), owners[0] == LOCAL);
+    }
+}
+
+#[test]
+fn joining_only_moves_nodes_to_the_new_member() {
+    let cluster = Cluster::new(LOCAL, Default::default()).expect("Invalid host.");
+    cluster.join(PEER1).expect("Invalid host.");
+    let before = nodes()
+        .map(|node| (node.clone(), cluster.owners(&node)))
+        .collect::<Vec<_>>();
+
+    assert!(cluster.join(PEER2).expect("Invalid host."));
+    assert!(!cluster.join(PEER2).expect("Invalid host."));
+
+    let mut moved = 0;
+    for (node, owners) in before {
+        let new_owners = cluster.owners(&node);
+        if new_owners != owners {
+            assert_eq!(new_owners[0], PEER2);
+            moved += 1;
+        }
+    }
+    assert!(moved > 0);
+}
+
+#[test]
+fn membership_changes_are_notified() {
+    let cluster = Cluster::new(LOCAL, Default::default()).expect("Invalid host.");
+    let mut changes = cluster.subscribe();
+    assert!(!changes.has_changed().expect("Cluster dropped."));
+
+    assert!(cluster.join(PEER1).expect("Invalid host."));
+    assert!(changes.has_changed().expect("Cluster dropped."));
+    changes.mark_unchanged();
+
+    // Joining again does not change the membership.
+    assert!(!cluster.join(PEER1).expect("Invalid host."));
+    assert!(!changes.has_changed().expect("Cluster dropped."));
+
+    assert!(cluster.leave(PEER1).expect("Invalid host."));
+    assert!(changes.has_changed().expect("Cluster dropped."));
+}
+
+#[test]
+fn local_member_cannot_leave() {
+    let cluster = make_cluster(non_zero_usize!(1));
+    assert!(!cluster.leave(LOCAL).expect("Invalid host."));
+    assert_eq!(cluster.members().len(), 3);
+}
+
+#[test]
+fn invalid_member() {
+    assert!(matches!(
+        Cluster::new("http://local:8080", Default::default()),
+        Err(BadWarpUrl::BadScheme(_))
+    ));
+    let cluster = Cluster::new(LOCAL, Default::default()).expect("Invalid host.");
+    assert!(cluster.join("local").is_err());
+}
+
+#[test]
+fn partitions_cover_the_ring() {
+    let cluster = make_cluster(non_zero_usize!(2));
+    let partitions = cluster.partitions();
+    assert_eq!(
+        partitions.len(),
+        3 * PartitionConfig::default().virtual_nodes.get()
+    );
+    let last = partitions.last().expect("No partitions.");
+    assert_eq!(partitions[0].start, last.end);
+    for pair in partitions.windows(2) {
+        assert_eq!(pair[0].end, pair[1].start);
+        assert!(pair[0].end < pair[1].end);
+    }
+    let members = cluster.members();
+    for partition in &partitions {
+        assert_eq!(partition.owners.len(), 2);
+        assert!(partition
+            .owners
+            .iter()
+            .all(|owner| members.contains(&owner.to_string())));
+    }
+}
+
+const BUFFER_SIZE: NonZeroUsize = non_zero_usize!(4096);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[tokio::test]
+async fn meta_lane_syncs_partitions() {
+    let cluster = make_cluster(non_zero_usize!(2));
+    let expected = cluster
+        .partitions()
+        .into_iter()
+        .map(|partition| PartitionInfo {
+            start: partition.start,
+            end: partition.end,
+            owners: partition.owners.iter().map(ToString::to_string).collect(),
+        })
+        .collect::<Vec<_>>();
+
+    let (request_tx, request_rx) = byte_channel(BUFFER_SIZE);
+    let (response_tx, response_rx) = byte_channel(BUFFER_SIZE);
+
+    let task = run_task(cluster, (response_tx, request_rx));
+
+    let test_case = async move {
+        let mut requests = FramedWrite::new(request_tx, RawValueLaneRequestEncoder::default());
+        let mut responses = FramedRead::new(
+            response_rx,
+            MapLaneResponseDecoder::<String, PartitionInfo>::default(),
+        );
+
+        let id = Uuid::from_u128(1);
+        requests
+            .send(LaneRequest::<&[u8]>::Sync(id))
+            .await
+            .expect("Sending request failed.");
+
+        let mut received = vec![];
+        loop {
+            match responses
+                .next()
+                .await
+                .expect("Agent stopped.")
+                .expect("Invalid response.")
+            {
+                LaneResponse::SyncEvent(sync_id, MapOperation::Update { key, value }) => {
+                    assert_eq!(sync_id, id);
+                    assert_eq!(key, value.end.to_string());
+                    received.push(value);
+                }
+                LaneResponse::Synced(sync_id) => {
+                    assert_eq!(sync_id, id);
+                    break;
+                }
+                ow => panic!("Unexpected response: {:?}", ow),
+            }
+        }
+        assert_eq!(received, expected);
+    };
+
+    let (result, _) = tokio::time::timeout(TIMEOUT, join(task, test_case))
+        .await
+        .expect("Test timed out.");
+    assert!(result.is_ok());
+}
+
+type Notifications = FramedWrite<ByteWriter, DownlinkNotificationEncoder>;
+
+async fn send_notification(
+    notifications: &mut Notifications,
+    notification: DownlinkNotification<MeshPulse>,
+) {
+    let notification = match notification {
+        DownlinkNotification::Linked => DownlinkNotification::Linked,
+        DownlinkNotification::Synced => DownlinkNotification::Synced,
+        DownlinkNotification::Event { body } => DownlinkNotification::Event {
+            body: format!("{}", print_recon_compact(&body)).into_bytes(),
+        },
+        DownlinkNotification::Unlinked => DownlinkNotification::Unlinked,
+        DownlinkNotification::Advisory { advice } => DownlinkNotification::Advisory { advice },
+        DownlinkNotification::NodeStopped { notice } => {
+            DownlinkNotification::NodeStopped { notice }
+        }
+        DownlinkNotification::Failed { failure } => DownlinkNotification::Failed { failure },
+        DownlinkNotification::Version { version } => DownlinkNotification::Version { version },
+        DownlinkNotification::Origin { origin } => DownlinkNotification::Origin { origin },
+    };
+    notifications
+        .send(notification)
+        .await
+        .expect("Sending notification failed.");
+}
+
+async fn next_pulse(
+    pulses: &mut FramedRead<ByteReader, ValueLaneResponseDecoder<ClusterPulse>>,
+) -> ClusterPulse {
+    match pulses.next().await {
+        Some(Ok(LaneResponse::StandardEvent(pulse))) => pulse,
+        ow => panic!("Unexpected response: {:?}", ow),
+    }
+}
+
+fn healthy(agent_count: u64, link_count: u64) -> MemberHealth {
+    MemberHealth {
+        healthy: true,
+        agent_count,
+        link_count,
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn cluster_pulse_aggregates_members() {
+    let cluster = Cluster::new(LOCAL, Default::default()).expect("Invalid host.");
+    assert!(cluster.join(PEER1).expect("Invalid host."));
+
+    let (pulse_req_tx, pulse_req_rx) = byte_channel(BUFFER_SIZE);
+    let (pulse_resp_tx, pulse_resp_rx) = byte_channel(BUFFER_SIZE);
+    let (members_req_tx, members_req_rx) = byte_channel(BUFFER_SIZE);
+    let (members_resp_tx, members_resp_rx) = byte_channel(BUFFER_SIZE);
+    let (tick_tx, tick_rx) = mpsc::channel(8);
+    let (open_tx, mut open_rx) =
+        mpsc::unbounded_channel::<(Option<String>, ByteWriter, ByteReader)>();
+
+    let open = move |host: Option<&str>| {
+        let (notify_tx, notify_rx) = byte_channel(BUFFER_SIZE);
+        let (ops_tx, ops_rx) = byte_channel(BUFFER_SIZE);
+        assert!(open_tx
+            .send((host.map(ToString::to_string), notify_tx, ops_rx))
+            .is_ok());
+        ready(Ok((ops_tx, notify_rx))).boxed()
+    };
+
+    let task = pulse::run_task(
+        cluster.clone(),
+        Duration::from_secs(1),
+        ReceiverStream::new(tick_rx),
+        open,
+        (pulse_resp_tx, pulse_req_rx),
+        (members_resp_tx, members_req_rx),
+    );
+
+    let test_case = async move {
+        let mut pulses = FramedRead::new(
+            pulse_resp_rx,
+            ValueLaneResponseDecoder::<ClusterPulse>::default(),
+        );
+        let mut members = FramedRead::new(
+            members_resp_rx,
+            MapLaneResponseDecoder::<Text, MemberHealth>::default(),
+        );
+
+        assert!(tick_tx.send(()).await.is_ok());
+        for member in [LOCAL, PEER1] {
+            match members.next().await {
+                Some(Ok(LaneResponse::StandardEvent(MapOperation::Update { key, value }))) => {
+                    assert_eq!(key, member);
+                    assert_eq!(value, MemberHealth::default());
+                }
+                ow => panic!("Unexpected response: {:?}", ow),
+            }
+        }
+        assert_eq!(
+            next_pulse(&mut pulses).await,
+            ClusterPulse {
+                member_count: 2,
+                ..Default::default()
+            }
+        );
+
+        // The local mesh meta-agent is linked without a host.
+        let mut links = vec![];
+        for (expected_host, pulse) in [(None, (2, 3)), (Some(PEER1), (4, 5))] {
+            let (host, notify_tx, ops_rx) = open_rx.recv().await.expect("Downlink not opened.");
+            assert_eq!(host.as_deref(), expected_host);
+            let mut notifications = FramedWrite::new(notify_tx, DownlinkNotificationEncoder);
+            send_notification(&mut notifications, DownlinkNotification::Linked).await;
+            let (agent_count, link_count) = pulse;
+            let body = MeshPulse {
+                agent_count,
+                link_count,
+            };
+            send_notification(&mut notifications, DownlinkNotification::Event { body }).await;
+            match members.next().await {
+                Some(Ok(LaneResponse::StandardEvent(MapOperation::Update { key, value }))) => {
+                    assert_eq!(key, host.as_deref().unwrap_or(LOCAL));
+                    assert_eq!(value, healthy(agent_count, link_count));
+                }
+                ow => panic!("Unexpected response: {:?}", ow),
+            }
+            links.push((notifications, ops_rx));
+        }
+
+        assert!(tick_tx.send(()).await.is_ok());
+        assert_eq!(
+            next_pulse(&mut pulses).await,
+            ClusterPulse {
+                member_count: 2,
+                healthy_count: 2,
+                agent_count: 6,
+                link_count: 8,
+            }
+        );
+
+        // Members that leave the cluster are removed.
+        assert!(cluster.leave(PEER1).expect("Invalid host."));
+        assert!(tick_tx.send(()).await.is_ok());
+        match members.next().await {
+            Some(Ok(LaneResponse::StandardEvent(MapOperation::Remove { key }))) => {
+                assert_eq!(key, PEER1);
+            }
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+        assert_eq!(
+            next_pulse(&mut pulses).await,
+            ClusterPulse {
+                member_count: 1,
+                healthy_count: 1,
+                agent_count: 2,
+                link_count: 3,
+            }
+        );
+
+        // Members that stop reporting pulses become unhealthy.
+        tokio::time::advance(Duration::from_secs(2)).await;
+        assert!(tick_tx.send(()).await.is_ok());
+        match members.next().await {
+            Some(Ok(LaneResponse::StandardEvent(MapOperation::Update { key, value }))) => {
+                assert_eq!(key, LOCAL);
+                assert_eq!(
+                    value,
+                    MemberHealth {
+                        healthy: false,
+                        agent_count: 2,
+                        link_count: 3,
+                    }
+                );
+            }
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+        assert_eq!(
+            next_pulse(&mut pulses).await,
+            ClusterPulse {
+                member_count: 1,
+                ..Default::default()
+            }
+        );
+
+        drop(pulse_req_tx);
+        drop(members_req_tx);
+        links
+    };
+
+    let (result, _) = tokio::time::timeout(TIMEOUT, join(task, test_case))
+        .await
+        .expect("Test timed out.");
+    assert!(result.is_ok());
+}
@@ -15,11 +15,33 @@
 use std::{num::NonZeroUsize, time::Duration};
 
 use ratchet::WebSocketConfig;
-use swimos_api::agent::AgentConfig;
-use swimos_runtime::{agent::AgentRuntimeConfig, downlink::DownlinkRuntimeConfig};
-use swimos_utilities::non_zero_usize;
+use swimos_api::{agent::AgentConfig, limits::Limits};
+use swimos_remote::websocket::{WarpEncoding, WarpVersions};
+use swimos_runtime::{
+    agent::{AgentRuntimeConfig, StoreFailureAction},
+    config::{check_buffer_size, check_timeout, ConfigError},
+    downlink::DownlinkRuntimeConfig,
+};
+use swimos_utilities::{future::RetryStrategy, non_zero_usize};
 
-/// Configuration parameters for a Swim server.
+/// Configuration parameters for a Swim server. All of the parameters for the server form a
+/// single tree:
+///
+/// ```text
+/// SwimServerConfig
+/// ├── remote: RemoteConnectionsConfig      (remote socket management)
+/// ├── agent: AgentConfig                   (parameters passed to agents)
+/// ├── agent_runtime: AgentRuntimeConfig    (the agent runtime task)
+/// ├── http: HttpConfig                     (the HTTP server and websockets)
+/// ├── downlink_runtime: DownlinkRuntimeConfig
+/// ├── store_startup: StoreStartupPolicy
+/// └── channel and buffer sizes and timeouts for the server task
+/// ```
+///
+/// [`SwimServerConfig::builder`], [`HttpConfig::builder`], [`AgentRuntimeConfig::builder`] and
+/// [`DownlinkRuntimeConfig::builder`] check that the parameters are within their permitted
+/// ranges. The server builder also checks the complete configuration before the server is
+/// started.
 #[derive(Debug, Clone, Copy)]
 pub struct SwimServerConfig {
     /// Parameters for remote sockets.
@@ -46,8 +68,63 @@ pub struct SwimServerConfig {
     pub downlink_runtime: DownlinkRuntimeConfig,
     /// Budget for byte stream futures (causes streams with constantly available data to periodically yield).
     pub channel_coop_budget: Option<NonZeroUsize>,
+    /// Policy for when the persistence store cannot be opened as the server starts.
+    pub store_startup: StoreStartupPolicy,
 }
 
+/// Policy for handling a failure to open the persistence store when the server starts.
+#[derive(Debug, Clone, Copy)]
+pub struct StoreStartupPolicy {
+    /// Strategy for retrying to open the store.
+    pub retry: RetryStrategy,
+    /// What to do if the store still cannot be opened after all retries have been attempted.
+    pub on_failure: StoreFailureAction,
+}
+
+impl Default for StoreStartupPolicy {
+    fn default() -> Self {
+        Self::fail_fast()
+    }
+}
+
+impl StoreStartupPolicy {
+    /// Fail to start the server if the store cannot be opened (the default).
+    pub fn fail_fast() -> Self {
+        StoreStartupPolicy {
+            retry: RetryStrategy::none(),
+            on_failure: StoreFailureAction::Fail,
+        }
+    }
+
+    /// Start the server without persistence, logging a warning, if the store cannot be opened.
+    pub fn transient() -> Self {
+        StoreStartupPolicy {
+            retry: RetryStrategy::none(),
+            on_failure: StoreFailureAction::Transient,
+        }
+    }
+
+    /// Retry opening the store, failing to start the server if the retries are exhausted.
+    ///
+    /// # Arguments
+    /// * `retry` - Strategy for retrying to open the store.
+    pub fn retry(retry: RetryStrategy) -> Self {
+        StoreStartupPolicy {
+            retry,
+            on_failure: StoreFailureAction::Fail,
+        }
+    }
+
+    /// Run without persistence if the store cannot be opened after all retries.
+    pub fn or_transient(self) -> Self {
+        StoreStartupPolicy {
+            on_failure: StoreFailureAction::Transient,
+            ..self
+        }
+    }
+}
+
+/// Configuration parameters for the HTTP server.
 #[derive(Debug, Clone, Copy)]
 pub struct HttpConfig {
     /// Configuration for websocket connections.
@@ -56,8 +133,27 @@ pub struct HttpConfig {
     pub max_http_requests: NonZeroUsize,
     /// HTTP request timeout.
     pub http_request_timeout: Duration,
+    /// The time allowed for a client to send the headers of a request (including a websocket
+    /// upgrade request) after which the connection will be closed.
+    pub handshake_timeout: Duration,
     /// Period of inactivity after which the HTTP server will drop channels to agents.
     pub resolver_timeout: Duration,
+    /// The encoding of Warp envelopes to request when opening connections to other servers. If
+    /// the peer does not support it, the connection will fall back to Recon text. Incoming
+    /// connections may negotiate either encoding.
+    pub warp_encoding: WarpEncoding,
+    /// The versions of the Warp protocol that may be negotiated for websocket connections. For
+    /// incoming connections, the most preferred version offered by the client is selected.
+    pub warp_versions: WarpVersions,
+    /// Whether the lanes of agents can be accessed with plain HTTP requests to paths of the form
+    /// `/warp/<node>/<lane>`. A `GET` request returns the state of the lane and a `POST` request
+    /// sends its body to the lane as a command.
+    pub warp_bridge: bool,
+    /// Whether the events of lanes can be streamed, as server-sent events, with requests to paths
+    /// of the form `/stream/<node>/<lane>`.
+    pub event_streams: bool,
+    /// The interval after which a heartbeat is sent on an idle event stream.
+    pub event_stream_heartbeat: Duration,
 }
 
 /// Configuration for remote socket management.
@@ -67,21 +163,53 @@ pub struct RemoteConnectionsConfig {
     pub registration_buffer_size: NonZeroUsize,
     /// Time to wait for a websocket to close before giving up.
     pub close_timeout: Duration,
+    /// The maximum number of frames retained for a remote when its frames are being captured for
+    /// debugging (using the `swimos:meta:remote` meta-agent, if traffic capture is enabled in the
+    /// introspection configuration).
+    pub capture_buffer_size: NonZeroUsize,
+    /// The maximum number of incoming websocket connections. Further connections are refused
+    /// until some are closed.
+    pub max_connections: Option<NonZeroUsize>,
+    /// The maximum number of incoming websocket connections from a single remote IP address.
+    pub max_connections_per_ip: Option<NonZeroUsize>,
+    /// Limits the rate at which a single remote IP address may open connections.
+    pub handshake_rate_limit: Option<HandshakeRateLimit>,
+    /// Limits on the envelopes received from remote sockets. A socket that sends an envelope that
+    /// violates them is closed.
+    pub limits: Limits,
+}
+
+/// Limits the number of connections that a remote IP address may open in a period. Connections
+/// in excess of the limit are refused before the websocket handshake is attempted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandshakeRateLimit {
+    /// The maximum number of connections that may be opened in each period.
+    pub max_handshakes: NonZeroUsize,
+    /// The length of the period.
+    pub period: Duration,
 }
 
 const DEFAULT_CHANNEL_SIZE: NonZeroUsize = non_zero_usize!(16);
 const DEFAULT_BUFFER_SIZE: NonZeroUsize = non_zero_usize!(4096);
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
 const DEFAULT_HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 const DEFAULT_HTTP_RESOLVER_TIMEOUT: Duration = Duration::from_secs(60 * 5);
+const DEFAULT_EVENT_STREAM_HEARTBEAT: Duration = Duration::from_secs(15);
 const DEFAULT_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
 const DEFAULT_MAX_HTTP: NonZeroUsize = non_zero_usize!(1024);
+const DEFAULT_CAPTURE_BUFFER_SIZE: NonZeroUsize = non_zero_usize!(256);
 
 impl Default for RemoteConnectionsConfig {
     fn default() -> Self {
         Self {
             registration_buffer_size: DEFAULT_CHANNEL_SIZE,
             close_timeout: DEFAULT_CLOSE_TIMEOUT,
+            capture_buffer_size: DEFAULT_CAPTURE_BUFFER_SIZE,
+            max_connections: None,
+            max_connections_per_ip: None,
+            handshake_rate_limit: None,
+            limits: Limits::default(),
         }
     }
 }
@@ -107,6 +235,7 @@ impl Default for SwimServerConfig {
             },
             client_request_channel_size: DEFAULT_CHANNEL_SIZE,
             channel_coop_budget: None,
+            store_startup: StoreStartupPolicy::default(),
         }
     }
 }
@@ -117,7 +246,328 @@ impl Default for HttpConfig {
             websockets: Default::default(),
             max_http_requests: DEFAULT_MAX_HTTP,
             http_request_timeout: DEFAULT_HTTP_TIMEOUT,
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
             resolver_timeout: DEFAULT_HTTP_RESOLVER_TIMEOUT,
+            warp_encoding: WarpEncoding::Text,
+            warp_versions: WarpVersions::default(),
+            warp_bridge: false,
+            event_streams: false,
+            event_stream_heartbeat: DEFAULT_EVENT_STREAM_HEARTBEAT,
+        }
+    }
+}
+
+impl SwimServerConfig {
+    /// Create a builder, starting from the default configuration, that will check the values of
+    /// the parameters when the configuration is built.
+    pub fn builder() -> SwimServerConfigBuilder {
+        SwimServerConfigBuilder::default()
+    }
+
+    /// Check that all of the parameters, including those of the nested configurations, are
+    /// within their permitted ranges.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let SwimServerConfig {
+            remote,
+            agent_runtime,
+            agent_runtime_buffer_size,
+            attachment_timeout,
+            http,
+            downlink_runtime,
+            ..
+        } = self;
+        remote.validate()?;
+        agent_runtime.validate()?;
+        check_buffer_size(
+            "SwimServerConfig::agent_runtime_buffer_size",
+            agent_runtime_buffer_size.get(),
+        )?;
+        check_timeout("SwimServerConfig::attachment_timeout", *attachment_timeout)?;
+        http.validate()?;
+        downlink_runtime.validate()
+    }
+}
+
+impl HttpConfig {
+    /// Create a builder, starting from the default configuration, that will check the values of
+    /// the parameters when the configuration is built.
+    pub fn builder() -> HttpConfigBuilder {
+        HttpConfigBuilder::default()
+    }
+
+    /// Check that all of the parameters are within their permitted ranges.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let HttpConfig {
+            websockets,
+            http_request_timeout,
+            handshake_timeout,
+            resolver_timeout,
+            event_stream_heartbeat,
+            ..
+        } = self;
+        check_buffer_size(
+            "HttpConfig::websockets::max_message_size",
+            websockets.max_message_size,
+        )?;
+        check_timeout("HttpConfig::http_request_timeout", *http_request_timeout)?;
+        check_timeout("HttpConfig::handshake_timeout", *handshake_timeout)?;
+        check_timeout("HttpConfig::resolver_timeout", *resolver_timeout)?;
+        check_timeout(
+            "HttpConfig::event_stream_heartbeat",
+            *event_stream_heartbeat,
+        )
+    }
+}
+
+impl RemoteConnectionsConfig {
+    /// Check that all of the parameters are within their permitted ranges.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        check_timeout("RemoteConnectionsConfig::close_timeout", self.close_timeout)?;
+        if let Some(HandshakeRateLimit { period, .. }) = &self.handshake_rate_limit {
+            check_timeout(
+                "RemoteConnectionsConfig::handshake_rate_limit::period",
+                *period,
+            )?;
         }
+        Ok(())
+    }
+}
+
+/// Builder for [`SwimServerConfig`] that validates the parameters when the configuration is
+/// built. Any parameters that are not set will take their default values.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SwimServerConfigBuilder {
+    config: SwimServerConfig,
+}
+
+impl SwimServerConfigBuilder {
+    /// Set the parameters for remote sockets.
+    pub fn remote(mut self, remote: RemoteConnectionsConfig) -> Self {
+        self.config.remote = remote;
+        self
+    }
+
+    /// Set the parameters to be passed to agents.
+    pub fn agent(mut self, agent: AgentConfig) -> Self {
+        self.config.agent = agent;
+        self
+    }
+
+    /// Set the parameters for the agent runtime component.
+    pub fn agent_runtime(mut self, agent_runtime: AgentRuntimeConfig) -> Self {
+        self.config.agent_runtime = agent_runtime;
+        self
+    }
+
+    /// Set the size of the MPSC channel for requesting new downlinks from a remote.
+    pub fn client_attachment_buffer_size(mut self, size: NonZeroUsize) -> Self {
+        self.config.client_attachment_buffer_size = size;
+        self
+    }
+
+    /// Set the size of the MPSC channel for requesting new downlinks to the server.
+    pub fn client_request_channel_size(mut self, size: NonZeroUsize) -> Self {
+        self.config.client_request_channel_size = size;
+        self
+    }
+
+    /// Set the size of the MPSC channel for resolving agents.
+    pub fn find_route_channel_size(mut self, size: NonZeroUsize) -> Self {
+        self.config.find_route_channel_size = size;
+        self
+    }
+
+    /// Set the size of the MPSC channel for opening new downlinks.
+    pub fn open_downlink_channel_size(mut self, size: NonZeroUsize) -> Self {
+        self.config.open_downlink_channel_size = size;
+        self
+    }
+
+    /// Set the buffer size for communication between remote sockets and agents.
+    pub fn agent_runtime_buffer_size(mut self, size: NonZeroUsize) -> Self {
+        self.config.agent_runtime_buffer_size = size;
+        self
+    }
+
+    /// Set the timeout on attempting to connect a remote socket to an agent.
+    pub fn attachment_timeout(mut self, timeout: Duration) -> Self {
+        self.config.attachment_timeout = timeout;
+        self
+    }
+
+    /// Set the HTTP server parameters.
+    pub fn http(mut self, http: HttpConfig) -> Self {
+        self.config.http = http;
+        self
+    }
+
+    /// Set the parameters for the downlink runtime component.
+    pub fn downlink_runtime(mut self, downlink_runtime: DownlinkRuntimeConfig) -> Self {
+        self.config.downlink_runtime = downlink_runtime;
+        self
+    }
+
+    /// Set the budget for byte stream futures.
+    pub fn channel_coop_budget(mut self, budget: Option<NonZeroUsize>) -> Self {
+        self.config.channel_coop_budget = budget;
+        self
+    }
+
+    /// Set the policy for when the persistence store cannot be opened as the server starts.
+    pub fn store_startup(mut self, policy: StoreStartupPolicy) -> Self {
+        self.config.store_startup = policy;
+        self
+    }
+
+    /// Build the configuration, failing if any of the parameters are out of range.
+    pub fn build(self) -> Result<SwimServerConfig, ConfigError> {
+        let SwimServerConfigBuilder { config } = self;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Builder for [`HttpConfig`] that validates the parameters when the configuration is built.
+/// Any parameters that are not set will take their default values.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HttpConfigBuilder {
+    config: HttpConfig,
+}
+
+impl HttpConfigBuilder {
+    /// Set the configuration for websocket connections.
+    pub fn websockets(mut self, websockets: WebSocketConfig) -> Self {
+        self.config.websockets = websockets;
+        self
+    }
+
+    /// Set the maximum number of concurrent HTTP requests to serve.
+    pub fn max_http_requests(mut self, max: NonZeroUsize) -> Self {
+        self.config.max_http_requests = max;
+        self
+    }
+
+    /// Set the HTTP request timeout.
+    pub fn http_request_timeout(mut self, timeout: Duration) -> Self {
+        self.config.http_request_timeout = timeout;
+        self
+    }
+
+    /// Set the time allowed for a client to send the headers of a request.
+    pub fn handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.config.handshake_timeout = timeout;
+        self
+    }
+
+    /// Set the period of inactivity after which the HTTP server will drop channels to agents.
+    pub fn resolver_timeout(mut self, timeout: Duration) -> Self {
+        self.config.resolver_timeout = timeout;
+        self
+    }
+
+    /// Set the encoding of Warp envelopes to request when opening connections to other servers.
+    pub fn warp_encoding(mut self, encoding: WarpEncoding) -> Self {
+        self.config.warp_encoding = encoding;
+        self
+    }
+
+    /// Set the versions of the Warp protocol that may be negotiated for websocket connections.
+    pub fn warp_versions(mut self, versions: WarpVersions) -> Self {
+        self.config.warp_versions = versions;
+        self
+    }
+
+    /// Enable or disable HTTP access to lanes through paths of the form `/warp/<node>/<lane>`.
+    pub fn warp_bridge(mut self, enabled: bool) -> Self {
+        self.config.warp_bridge = enabled;
+        self
+    }
+
+    /// Enable or disable streaming the events of lanes through paths of the form
+    /// `/stream/<node>/<lane>`.
+    pub fn event_streams(mut self, enabled: bool) -> Self {
+        self.config.event_streams = enabled;
+        self
+    }
+
+    /// Set the interval after which a heartbeat is sent on an idle event stream.
+    pub fn event_stream_heartbeat(mut self, heartbeat: Duration) -> Self {
+        self.config.event_stream_heartbeat = heartbeat;
+        self
+    }
+
+    /// Build the configuration, failing if any of the parameters are out of range.
+    pub fn build(self) -> Result<HttpConfig, ConfigError> {
+        let HttpConfigBuilder { config } = self;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ratchet::WebSocketConfig;
+    use swimos_runtime::{agent::AgentRuntimeConfig, config::ConfigError};
+
+    use super::{HttpConfig, SwimServerConfig};
+
+    #[test]
+    fn default_server_config_is_valid() {
+        assert!(SwimServerConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn server_config_builder() {
+        let agent_runtime = AgentRuntimeConfig::builder()
+            .inactive_timeout(Duration::from_secs(60))
+            .build()
+            .expect("Valid config rejected.");
+        let config = SwimServerConfig::builder()
+            .agent_runtime(agent_runtime)
+            .attachment_timeout(Duration::from_secs(1))
+            .build()
+            .expect("Valid config rejected.");
+        assert_eq!(
+            config.agent_runtime.inactive_timeout,
+            Duration::from_secs(60)
+        );
+        assert_eq!(config.attachment_timeout, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn server_config_checks_nested_configs() {
+        let agent_runtime = AgentRuntimeConfig {
+            item_init_timeout: Duration::ZERO,
+            ..Default::default()
+        };
+        let result = SwimServerConfig::builder()
+            .agent_runtime(agent_runtime)
+            .build();
+        assert!(matches!(
+            result,
+            Err(ConfigError::TimeoutTooShort {
+                parameter: "AgentRuntimeConfig::item_init_timeout",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn http_config_small_websocket_messages() {
+        let result = HttpConfig::builder()
+            .websockets(WebSocketConfig {
+                max_message_size: 0,
+            })
+            .build();
+        assert!(matches!(
+            result,
+            Err(ConfigError::BufferTooSmall {
+                parameter: "HttpConfig::websockets::max_message_size",
+                value: 0,
+                ..
+            })
+        ));
     }
 }
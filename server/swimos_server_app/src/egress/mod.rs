@@ -0,0 +1,368 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashSet, error::Error, fmt::Display, sync::Arc};
+
+use futures::{
+    future::BoxFuture,
+    stream::{unfold, BoxStream, FuturesUnordered, SelectAll},
+    FutureExt, StreamExt,
+};
+use swimos_agent_protocol::{encoding::downlink::ValueNotificationDecoder, DownlinkNotification};
+use swimos_api::{
+    address::RelativeAddress,
+    agent::DownlinkKind,
+    error::{AgentRuntimeError, DownlinkRuntimeError},
+};
+use swimos_model::{Text, Value};
+use swimos_runtime::{
+    agent::{DownlinkRequest, LinkRequest},
+    downlink::DownlinkOptions,
+};
+use swimos_utilities::{
+    future::RetryStrategy,
+    routing::{RoutePattern, RouteUri},
+};
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::codec::FramedRead;
+use tracing::{debug, error, info, warn};
+
+use crate::Io;
+
+#[cfg(test)]
+mod tests;
+
+/// An event from a lane that is to be pushed to an external sink.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EgressEvent {
+    /// The node URI of the agent.
+    pub node: Text,
+    /// The name of the lane.
+    pub lane: Text,
+    /// The body of the event.
+    pub body: Value,
+}
+
+/// Error type for a failure to deliver an event to a sink.
+#[derive(Debug)]
+pub struct DeliveryError(Box<dyn Error + Send + Sync>);
+
+impl DeliveryError {
+    pub fn new<E>(error: E) -> Self
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        DeliveryError(error.into())
+    }
+}
+
+impl Display for DeliveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to deliver an event: {}", self.0)
+    }
+}
+
+impl Error for DeliveryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+/// An external system (for example, an HTTP webhook, a Kafka producer or a database) to which the
+/// events of lanes can be pushed by an [`EgressBridge`]. Events are delivered one at a time, in the
+/// order in which they were received by the bridge.
+pub trait EgressSink: Send + Sync + 'static {
+    /// Deliver an event to the sink. If this fails, the bridge will retry the delivery according
+    /// to its retry strategy.
+    fn deliver<'a>(&'a self, event: &'a EgressEvent) -> BoxFuture<'a, Result<(), DeliveryError>>;
+}
+
+/// Destination for events that could not be delivered to an [`EgressSink`] after all retries had
+/// been exhausted.
+pub trait DeadLetterSink: Send + Sync + 'static {
+    /// Handle an event that could not be delivered.
+    ///
+    /// # Arguments
+    /// * `event` - The event.
+    /// * `error` - The error from the final attempt to deliver the event.
+    fn dead_letter(&self, event: EgressEvent, error: DeliveryError) -> BoxFuture<'_, ()>;
+}
+
+impl EgressSink for mpsc::Sender<EgressEvent> {
+    fn deliver<'a>(&'a self, event: &'a EgressEvent) -> BoxFuture<'a, Result<(), DeliveryError>> {
+        async move {
+            self.send(event.clone())
+                .await
+                .map_err(|_| DeliveryError::new("The channel was closed."))
+        }
+        .boxed()
+    }
+}
+
+impl DeadLetterSink for mpsc::Sender<(EgressEvent, DeliveryError)> {
+    fn dead_letter(&self, event: EgressEvent, error: DeliveryError) -> BoxFuture<'_, ()> {
+        async move {
+            if self.send((event, error)).await.is_err() {
+                warn!("The dead letter channel was closed.");
+            }
+        }
+        .boxed()
+    }
+}
+
+/// The default [`DeadLetterSink`] that logs the events that could not be delivered.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogDeadLetters;
+
+impl DeadLetterSink for LogDeadLetters {
+    fn dead_letter(&self, event: EgressEvent, error: DeliveryError) -> BoxFuture<'_, ()> {
+        let EgressEvent { node, lane, body } = event;
+        error!(node = %node, lane = %lane, body = %body, error = %error, "Failed to deliver an event to an egress sink.");
+        futures::future::ready(()).boxed()
+    }
+}
+
+/// A plane-level component that links to every lane, with a given name, of the agents with node
+/// URIs matching a route pattern and pushes the events from those lanes to an [`EgressSink`].
+///
+/// The bridge links to the lanes of each matching agent when the agent is started (it will not
+/// cause agents to start). Failed deliveries are retried according to a [`RetryStrategy`] and
+/// events that cannot be delivered are passed to a [`DeadLetterSink`] (by default, they are
+/// logged). While the sink is failing, the bridge stops consuming events so the links will
+/// apply backpressure to the lanes.
+pub struct EgressBridge {
+    pub(crate) subscriptions: Vec<(RoutePattern, Text)>,
+    pub(crate) sink: Arc<dyn EgressSink>,
+    pub(crate) dead_letters: Arc<dyn DeadLetterSink>,
+    pub(crate) retry: RetryStrategy,
+}
+
+impl EgressBridge {
+    /// Create a bridge, with no subscriptions, that does not retry failed deliveries.
+    ///
+    /// # Arguments
+    /// * `sink` - The sink to which events will be pushed.
+    pub fn new<S: EgressSink>(sink: S) -> Self {
+        EgressBridge {
+            subscriptions: vec![],
+            sink: Arc::new(sink),
+            dead_letters: Arc::new(LogDeadLetters),
+            retry: RetryStrategy::none(),
+        }
+    }
+
+    /// Subscribe to a lane of each agent with a node URI matching a pattern.
+    ///
+    /// # Arguments
+    /// * `pattern` - The pattern for the node URIs.
+    /// * `lane` - The name of the lane.
+    pub fn subscribe(mut self, pattern: RoutePattern, lane: &str) -> Self {
+        self.subscriptions.push((pattern, Text::new(lane)));
+        self
+    }
+
+    /// Set the strategy for retrying failed deliveries.
+    pub fn with_retry(mut self, retry: RetryStrategy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the destination for events that could not be delivered.
+    pub fn with_dead_letters<D: DeadLetterSink>(mut self, dead_letters: D) -> Self {
+        self.dead_letters = Arc::new(dead_letters);
+        self
+    }
+
+    fn lanes_for<'a>(&'a self, node: &'a RouteUri) -> impl Iterator<Item = &'a Text> + 'a {
+        self.subscriptions
+            .iter()
+            .filter(move |(pattern, _)| pattern.unapply_route_uri(node).is_ok())
+            .map(|(_, lane)| lane)
+    }
+}
+
+/// Notifies the running egress bridges when agents are started.
+#[derive(Debug, Default)]
+pub(crate) struct AgentStarts {
+    senders: Vec<mpsc::UnboundedSender<RouteUri>>,
+}
+
+impl AgentStarts {
+    pub fn subscribe(&mut self) -> mpsc::UnboundedReceiver<RouteUri> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.senders.push(tx);
+        rx
+    }
+
+    pub fn notify(&mut self, node: &RouteUri) {
+        self.senders.retain(|tx| tx.send(node.clone()).is_ok());
+    }
+}
+
+enum LinkEvent {
+    Event(EgressEvent),
+    Closed(Text, Text),
+}
+
+type OpenResult = (Text, Text, Result<Io, DownlinkRuntimeError>);
+
+/// Run an egress bridge until the server stops notifying it of started agents.
+///
+/// # Arguments
+/// * `bridge` - The bridge.
+/// * `starts` - Notifications of the node URIs of agents as they are started.
+/// * `link_requests` - Channel used to open links to the lanes of the agents.
+pub(crate) async fn run_bridge(
+    bridge: EgressBridge,
+    mut starts: mpsc::UnboundedReceiver<RouteUri>,
+    link_requests: mpsc::Sender<LinkRequest>,
+) {
+    let mut linked: HashSet<(Text, Text)> = HashSet::new();
+    let mut opening = FuturesUnordered::<BoxFuture<'static, OpenResult>>::new();
+    let mut links = SelectAll::<BoxStream<'static, LinkEvent>>::new();
+
+    loop {
+        tokio::select! {
+            biased;
+            maybe_node = starts.recv() => {
+                let Some(node) = maybe_node else {
+                    break;
+                };
+                for lane in bridge.lanes_for(&node) {
+                    let key = (Text::new(node.as_str()), lane.clone());
+                    if linked.insert(key.clone()) {
+                        let (node, lane) = key;
+                        match open_link(&link_requests, node, lane).await {
+                            Some(fut) => opening.push(fut),
+                            None => return,
+                        }
+                    }
+                }
+            }
+            Some((node, lane, result)) = opening.next(), if !opening.is_empty() => {
+                match result {
+                    Ok(io) => {
+                        debug!(node = %node, lane = %lane, "Egress bridge linked to a lane.");
+                        links.push(link_events(node, lane, io));
+                    }
+                    Err(error) => {
+                        warn!(node = %node, lane = %lane, error = %error, "Egress bridge failed to link to a lane.");
+                        linked.remove(&(node, lane));
+                    }
+                }
+            }
+            Some(event) = links.next(), if !links.is_empty() => {
+                match event {
+                    LinkEvent::Event(event) => deliver(&bridge, event).await,
+                    LinkEvent::Closed(node, lane) => {
+                        debug!(node = %node, lane = %lane, "Egress bridge link closed.");
+                        linked.remove(&(node, lane));
+                    }
+                }
+            }
+        }
+    }
+    info!("Egress bridge stopped.");
+}
+
+async fn open_link(
+    link_requests: &mpsc::Sender<LinkRequest>,
+    node: Text,
+    lane: Text,
+) -> Option<BoxFuture<'static, OpenResult>> {
+    let (promise_tx, promise_rx) = oneshot::channel();
+    let request = DownlinkRequest::new(
+        None,
+        RelativeAddress::new(node.clone(), lane.clone()),
+        DownlinkKind::Event,
+        DownlinkOptions::empty(),
+        promise_tx,
+    );
+    link_requests
+        .send(LinkRequest::Downlink(request))
+        .await
+        .ok()?;
+    Some(
+        async move {
+            let result = promise_rx
+                .await
+                .unwrap_or(Err(AgentRuntimeError::Stopping.into()));
+            (node, lane, result)
+        }
+        .boxed(),
+    )
+}
+
+fn link_events(node: Text, lane: Text, io: Io) -> BoxStream<'static, LinkEvent> {
+    // The writer must be held for as long as the link is required.
+    let (writer, reader) = io;
+    let notifications = FramedRead::new(reader, ValueNotificationDecoder::<Value>::default());
+    unfold(
+        Some((writer, notifications, node, lane)),
+        |state| async move {
+            let (writer, mut notifications, node, lane) = state?;
+            loop {
+                match notifications.next().await {
+                    Some(Ok(DownlinkNotification::Event { body })) => {
+                        let event = EgressEvent {
+                            node: node.clone(),
+                            lane: lane.clone(),
+                            body,
+                        };
+                        break Some((
+                            LinkEvent::Event(event),
+                            Some((writer, notifications, node, lane)),
+                        ));
+                    }
+                    Some(Ok(DownlinkNotification::Unlinked)) | None => {
+                        break Some((LinkEvent::Closed(node, lane), None));
+                    }
+                    Some(Err(error)) => {
+                        warn!(node = %node, lane = %lane, error = %error, "Egress bridge link failed.");
+                        break Some((LinkEvent::Closed(node, lane), None));
+                    }
+                    _ => {}
+                }
+            }
+        },
+    )
+    .boxed()
+}
+
+async fn deliver(bridge: &EgressBridge, event: EgressEvent) {
+    let EgressBridge {
+        sink,
+        dead_letters,
+        retry,
+        ..
+    } = bridge;
+    let mut retry = *retry;
+    loop {
+        match sink.deliver(&event).await {
+            Ok(()) => break,
+            Err(error) => match retry.next() {
+                Some(delay) => {
+                    debug!(node = %event.node, lane = %event.lane, error = %error, "Delivery to an egress sink failed. Retrying.");
+                    if let Some(delay) = delay {
+                        tokio::time::sleep(delay).await;
+                    }
+                }
+                None => {
+                    dead_letters.dead_letter(event, error).await;
+                    break;
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use futures::{future::join, future::BoxFuture, FutureExt, SinkExt};
+use swimos_agent_protocol::{
+    encoding::downlink::DownlinkNotificationEncoder, DownlinkNotification,
+};
+use swimos_api::agent::DownlinkKind;
+use swimos_model::{Text, Value};
+use swimos_runtime::agent::{DownlinkRequest, LinkRequest};
+use swimos_utilities::{
+    byte_channel::{byte_channel, ByteReader, ByteWriter},
+    future::{Quantity, RetryStrategy},
+    non_zero_usize,
+    routing::{RoutePattern, RouteUri},
+};
+use tokio::sync::mpsc;
+use tokio_util::codec::FramedWrite;
+
+use super::{run_bridge, AgentStarts, DeliveryError, EgressBridge, EgressEvent, EgressSink};
+
+const BUFFER_SIZE: NonZeroUsize = non_zero_usize!(4096);
+const SENSOR_PATTERN: &str = "/sensors/:id";
+const LANE: &str = "reading";
+
+type Notifications = FramedWrite<ByteWriter, DownlinkNotificationEncoder>;
+
+fn route(node: &str) -> RouteUri {
+    node.parse().expect("Invalid route.")
+}
+
+fn sensor_bridge<S: EgressSink>(sink: S) -> EgressBridge {
+    EgressBridge::new(sink).subscribe(
+        RoutePattern::parse_str(SENSOR_PATTERN).expect("Invalid pattern."),
+        LANE,
+    )
+}
+
+fn event(node: &str, n: i32) -> EgressEvent {
+    EgressEvent {
+        node: Text::new(node),
+        lane: Text::new(LANE),
+        body: Value::Int32Value(n),
+    }
+}
+
+// Satisfy the next link request, checking that it is for the expected lane. The returned reader
+// must be held for the link to remain open.
+async fn accept_link(
+    link_rx: &mut mpsc::Receiver<LinkRequest>,
+    node: &str,
+) -> (Notifications, ByteReader) {
+    let Some(LinkRequest::Downlink(DownlinkRequest {
+        remote,
+        address,
+        kind,
+        promise,
+        ..
+    })) = link_rx.recv().await
+    else {
+        panic!("Expected a downlink request.");
+    };
+    assert!(remote.is_none());
+    assert_eq!(address.node.as_str(), node);
+    assert_eq!(address.lane.as_str(), LANE);
+    assert_eq!(kind, DownlinkKind::Event);
+
+    let (in_tx, in_rx) = byte_channel(BUFFER_SIZE);
+    let (out_tx, out_rx) = byte_channel(BUFFER_SIZE);
+    assert!(promise.send(Ok((in_tx, out_rx))).is_ok());
+    let mut notifications = FramedWrite::new(out_tx, DownlinkNotificationEncoder);
+    let linked: DownlinkNotification<String> = DownlinkNotification::Linked;
+    assert!(notifications.send(linked).await.is_ok());
+    (notifications, in_rx)
+}
+
+async fn send_event(notifications: &mut Notifications, n: i32) {
+    let notification = DownlinkNotification::Event {
+        body: n.to_string(),
+    };
+    assert!(notifications.send(notification).await.is_ok());
+}
+
+#[tokio::test]
+async fn forward_events_from_matching_lanes() {
+    let (event_tx, mut event_rx) = mpsc::channel(8);
+    let (link_tx, mut link_rx) = mpsc::channel(8);
+    let mut starts = AgentStarts::default();
+    let task = run_bridge(sensor_bridge(event_tx), starts.subscribe(), link_tx);
+
+    let test = async move {
+        starts.notify(&route("/other/1"));
+        starts.notify(&route("/sensors/1"));
+        let (mut notifications, _input) = accept_link(&mut link_rx, "/sensors/1").await;
+
+        send_event(&mut notifications, 5).await;
+        send_event(&mut notifications, 6).await;
+        assert_eq!(event_rx.recv().await, Some(event("/sensors/1", 5)));
+        assert_eq!(event_rx.recv().await, Some(event("/sensors/1", 6)));
+
+        starts.notify(&route("/sensors/2"));
+        let (mut notifications, _input) = accept_link(&mut link_rx, "/sensors/2").await;
+        send_event(&mut notifications, 7).await;
+        assert_eq!(event_rx.recv().await, Some(event("/sensors/2", 7)));
+    };
+
+    join(task, test).await;
+}
+
+#[tokio::test]
+async fn relink_when_agent_restarts() {
+    let (event_tx, mut event_rx) = mpsc::channel(8);
+    let (link_tx, mut link_rx) = mpsc::channel(8);
+    let mut starts = AgentStarts::default();
+    let task = run_bridge(sensor_bridge(event_tx), starts.subscribe(), link_tx);
+
+    let test = async move {
+        starts.notify(&route("/sensors/1"));
+        let (mut notifications, _input) = accept_link(&mut link_rx, "/sensors/1").await;
+        let unlinked: DownlinkNotification<String> = DownlinkNotification::Unlinked;
+        assert!(notifications.send(unlinked).await.is_ok());
+        drop(notifications);
+        // Give the bridge the chance to observe that the link has closed.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        starts.notify(&route("/sensors/1"));
+        let (mut notifications, _input) = accept_link(&mut link_rx, "/sensors/1").await;
+        send_event(&mut notifications, 1).await;
+        assert_eq!(event_rx.recv().await, Some(event("/sensors/1", 1)));
+    };
+
+    join(task, test).await;
+}
+
+struct FailingSink(Arc<AtomicUsize>);
+
+impl EgressSink for FailingSink {
+    fn deliver<'a>(&'a self, _event: &'a EgressEvent) -> BoxFuture<'a, Result<(), DeliveryError>> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        futures::future::ready(Err(DeliveryError::new("Unavailable."))).boxed()
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_then_dead_letter() {
+    let (dead_tx, mut dead_rx) = mpsc::channel(8);
+    let (link_tx, mut link_rx) = mpsc::channel(8);
+    let mut starts = AgentStarts::default();
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let bridge = sensor_bridge(FailingSink(attempts.clone()))
+        .with_retry(RetryStrategy::interval(
+            Duration::from_secs(1),
+            Quantity::Finite(non_zero_usize!(2)),
+        ))
+        .with_dead_letters(dead_tx);
+    let task = run_bridge(bridge, starts.subscribe(), link_tx);
+
+    let test = async move {
+        starts.notify(&route("/sensors/1"));
+        let (mut notifications, _input) = accept_link(&mut link_rx, "/sensors/1").await;
+        send_event(&mut notifications, 3).await;
+
+        let (dead_event, error) = dead_rx.recv().await.expect("No dead letter.");
+        assert_eq!(dead_event, event("/sensors/1", 3));
+        assert_eq!(
+            error.to_string(),
+            "Failed to deliver an event: Unavailable."
+        );
+        // The initial attempt and two retries.
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    };
+
+    join(task, test).await;
+}
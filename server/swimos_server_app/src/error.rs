@@ -20,6 +20,7 @@ use std::{
 use swimos_api::error::StoreError;
 use swimos_remote::tls::TlsError;
 use swimos_remote::ConnectionError;
+use swimos_runtime::config::ConfigError;
 use thiserror::Error;
 
 use swimos_utilities::{format::comma_sep, routing::RoutePattern};
@@ -99,6 +100,12 @@ pub enum ServerBuilderError {
     /// The server TLS configuration is invalid.
     #[error("Invalid TLS configuration/certificate: {0}")]
     Tls(#[from] TlsError),
+    /// A parameter of the server configuration is out of range.
+    #[error("Invalid server configuration: {0}")]
+    Config(#[from] ConfigError),
+    /// TLS was configured for a server that uses a local (in-process or Unix socket) transport.
+    #[error("TLS is not supported by local transports.")]
+    LocalTransportTls,
 }
 
 #[cfg(test)]
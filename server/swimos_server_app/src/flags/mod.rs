@@ -0,0 +1,185 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashMap};
+
+use futures::{future::BoxFuture, FutureExt, SinkExt, StreamExt, TryFutureExt};
+use swimos_agent_protocol::{
+    encoding::{
+        lane::{MapLaneRequestDecoder, MapLaneResponseEncoder},
+        store::{MapStoreInitDecoder, StoreInitializedCodec},
+    },
+    LaneRequest, LaneResponse, MapMessage, MapOperation, StoreInitMessage, StoreInitialized,
+};
+use swimos_api::{
+    agent::{
+        Agent, AgentConfig, AgentContext, AgentInitResult, WarpLaneKind, FEATURE_FLAGS_LANE,
+        FEATURE_FLAGS_NODE,
+    },
+    error::{AgentInitError, AgentTaskError, FrameIoError},
+};
+use swimos_model::{Text, Value};
+use swimos_utilities::{
+    byte_channel::{ByteReader, ByteWriter},
+    routing::{RoutePattern, RouteUri},
+};
+use tokio_util::codec::{FramedRead, FramedWrite};
+
+#[cfg(test)]
+mod tests;
+
+/// Create a route pattern for the meta-agent that holds the feature flags of the plane.
+pub fn feature_flags_pattern() -> RoutePattern {
+    RoutePattern::parse_str(FEATURE_FLAGS_NODE).expect("Feature flags pattern should be valid.")
+}
+
+/// A meta-agent with a single map lane, mapping the names of feature flags to their currently
+/// selected variants. The flags can be changed by sending commands to the lane and agents can
+/// observe them by opening a downlink to the lane.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FeatureFlagAgent;
+
+impl Agent for FeatureFlagAgent {
+    fn run(
+        &self,
+        _route: RouteUri,
+        _route_params: HashMap<String, String>,
+        config: AgentConfig,
+        context: Box<dyn AgentContext + Send>,
+    ) -> BoxFuture<'static, AgentInitResult> {
+        run_init(config, context).boxed()
+    }
+}
+
+async fn run_init(config: AgentConfig, context: Box<dyn AgentContext + Send>) -> AgentInitResult {
+    let lane_config = config.default_lane_config.unwrap_or_default();
+    let io = context
+        .add_lane(FEATURE_FLAGS_LANE, WarpLaneKind::Map, lane_config)
+        .await?;
+    let (flags, io) = if lane_config.transient {
+        (Flags::new(), io)
+    } else {
+        initialize(io)
+            .await
+            .map_err(AgentInitError::LaneInitializationFailure)?
+    };
+    Ok(async move {
+        let result = run_task(flags, io)
+            .map_err(|error| AgentTaskError::BadFrame {
+                lane: Text::new(FEATURE_FLAGS_LANE),
+                error,
+            })
+            .await;
+        // deferred drop so the agent doesn't terminate early.
+        drop(context);
+        result
+    }
+    .boxed())
+}
+
+pub(crate) type Flags = BTreeMap<Text, Value>;
+
+/// Restore the persisted state of the lane (if any) before the agent starts.
+async fn initialize(
+    io: (ByteWriter, ByteReader),
+) -> Result<(Flags, (ByteWriter, ByteReader)), FrameIoError> {
+    let (mut tx, rx) = io;
+    let mut flags = Flags::new();
+    let mut input = FramedRead::new(rx, MapStoreInitDecoder::<Text, Value>::default());
+    while let Some(message) = input.next().await {
+        match message? {
+            StoreInitMessage::Command(message) => {
+                apply(&mut flags, message);
+            }
+            StoreInitMessage::InitComplete => break,
+        }
+    }
+    FramedWrite::new(&mut tx, StoreInitializedCodec)
+        .send(StoreInitialized)
+        .await?;
+    Ok((flags, (tx, input.into_inner())))
+}
+
+/// Apply a command to the flags, returning the changes that were made.
+fn apply(flags: &mut Flags, message: MapMessage<Text, Value>) -> Vec<MapOperation<Text, Value>> {
+    match message {
+        MapMessage::Update { key, value } => {
+            flags.insert(key.clone(), value.clone());
+            vec![MapOperation::Update { key, value }]
+        }
+        MapMessage::Remove { key } => {
+            if flags.remove(&key).is_some() {
+                vec![MapOperation::Remove { key }]
+            } else {
+                vec![]
+            }
+        }
+        MapMessage::Clear => {
+            flags.clear();
+            vec![MapOperation::Clear]
+        }
+        MapMessage::Take(n) => {
+            let n = usize::try_from(n).unwrap_or(usize::MAX);
+            let removed = flags.keys().skip(n).cloned().collect::<Vec<_>>();
+            remove_all(flags, removed)
+        }
+        MapMessage::Drop(n) => {
+            let n = usize::try_from(n).unwrap_or(usize::MAX);
+            let removed = flags.keys().take(n).cloned().collect::<Vec<_>>();
+            remove_all(flags, removed)
+        }
+    }
+}
+
+fn remove_all(flags: &mut Flags, keys: Vec<Text>) -> Vec<MapOperation<Text, Value>> {
+    keys.into_iter()
+        .map(|key| {
+            flags.remove(&key);
+            MapOperation::Remove { key }
+        })
+        .collect()
+}
+
+pub(crate) async fn run_task(
+    mut flags: Flags,
+    io: (ByteWriter, ByteReader),
+) -> Result<(), FrameIoError> {
+    let (tx, rx) = io;
+    let mut input = FramedRead::new(rx, MapLaneRequestDecoder::<Text, Value>::default());
+    let mut output = FramedWrite::new(tx, MapLaneResponseEncoder::default());
+
+    while let Some(request) = input.next().await {
+        match request? {
+            LaneRequest::Command(message)
+            | LaneRequest::CommandFrom(_, message)
+            | LaneRequest::CorrelatedCommand(_, _, message) => {
+                for op in apply(&mut flags, message) {
+                    output.send(LaneResponse::StandardEvent(op)).await?;
+                }
+            }
+            LaneRequest::Sync(id)
+            | LaneRequest::SyncSince(id, _)
+            | LaneRequest::SyncRange(id, _) => {
+                for (key, value) in &flags {
+                    let op = MapOperation::Update { key, value };
+                    output.send(LaneResponse::SyncEvent(id, op)).await?;
+                }
+                let synced: LaneResponse<MapOperation<&Text, &Value>> = LaneResponse::Synced(id);
+                output.send(synced).await?;
+            }
+            LaneRequest::InitComplete => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,193 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{num::NonZeroUsize, time::Duration};
+
+use futures::{future::join, SinkExt, StreamExt};
+use swimos_agent_protocol::{
+    encoding::{
+        lane::{MapLaneRequestEncoder, MapLaneResponseDecoder},
+        store::{RawMapStoreInitEncoder, StoreInitializedCodec},
+    },
+    LaneRequest, LaneResponse, MapMessage, MapOperation, StoreInitMessage,
+};
+use swimos_model::{Text, Value};
+use swimos_utilities::{
+    byte_channel::{byte_channel, ByteReader, ByteWriter},
+    non_zero_usize,
+};
+use tokio_util::codec::{FramedRead, FramedWrite};
+use uuid::Uuid;
+
+use super::{initialize, run_task, Flags};
+
+const BUFFER_SIZE: NonZeroUsize = non_zero_usize!(4096);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+type Responses = FramedRead<ByteReader, MapLaneResponseDecoder<Text, Value>>;
+
+async fn next_response(responses: &mut Responses) -> LaneResponse<MapOperation<Text, Value>> {
+    responses
+        .next()
+        .await
+        .expect("Agent stopped.")
+        .expect("Invalid response.")
+}
+
+async fn sync(
+    requests: &mut FramedWrite<ByteWriter, MapLaneRequestEncoder>,
+    responses: &mut Responses,
+) -> Flags {
+    let id = Uuid::from_u128(1);
+    requests
+        .send(LaneRequest::<MapMessage<Text, Value>>::Sync(id))
+        .await
+        .expect("Sending request failed.");
+    let mut flags = Flags::new();
+    loop {
+        match next_response(responses).await {
+            LaneResponse::SyncEvent(sync_id, MapOperation::Update { key, value }) => {
+                assert_eq!(sync_id, id);
+                flags.insert(key, value);
+            }
+            LaneResponse::Synced(sync_id) => {
+                assert_eq!(sync_id, id);
+                break flags;
+            }
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+    }
+}
+
+#[tokio::test]
+async fn flags_lane_commands_and_sync() {
+    let (request_tx, request_rx) = byte_channel(BUFFER_SIZE);
+    let (response_tx, response_rx) = byte_channel(BUFFER_SIZE);
+
+    let task = run_task(Flags::new(), (response_tx, request_rx));
+
+    let test_case = async move {
+        let mut requests = FramedWrite::new(request_tx, MapLaneRequestEncoder::default());
+        let mut responses = FramedRead::new(response_rx, MapLaneResponseDecoder::default());
+
+        let commands = [
+            MapMessage::Update {
+                key: Text::new("dark_mode"),
+                value: Value::from(true),
+            },
+            MapMessage::Update {
+                key: Text::new("layout"),
+                value: Value::text("compact"),
+            },
+            MapMessage::Remove {
+                key: Text::new("missing"),
+            },
+            MapMessage::Remove {
+                key: Text::new("dark_mode"),
+            },
+        ];
+        for command in commands {
+            requests
+                .send(LaneRequest::Command(command))
+                .await
+                .expect("Sending request failed.");
+        }
+
+        let expected = [
+            MapOperation::Update {
+                key: Text::new("dark_mode"),
+                value: Value::from(true),
+            },
+            MapOperation::Update {
+                key: Text::new("layout"),
+                value: Value::text("compact"),
+            },
+            MapOperation::Remove {
+                key: Text::new("dark_mode"),
+            },
+        ];
+        for op in expected {
+            assert_eq!(
+                next_response(&mut responses).await,
+                LaneResponse::StandardEvent(op)
+            );
+        }
+
+        let flags = sync(&mut requests, &mut responses).await;
+        assert_eq!(
+            flags,
+            Flags::from([(Text::new("layout"), Value::text("compact"))])
+        );
+    };
+
+    let (result, _) = tokio::time::timeout(TIMEOUT, join(task, test_case))
+        .await
+        .expect("Test timed out.");
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn flags_lane_restores_persisted_state() {
+    let (request_tx, request_rx) = byte_channel(BUFFER_SIZE);
+    let (response_tx, response_rx) = byte_channel(BUFFER_SIZE);
+
+    let init = initialize((response_tx, request_rx));
+
+    let runtime = async move {
+        let mut init_requests = FramedWrite::new(request_tx, RawMapStoreInitEncoder::default());
+        init_requests
+            .send(StoreInitMessage::Command(MapMessage::Update {
+                key: "rollout",
+                value: "0.25",
+            }))
+            .await
+            .expect("Sending request failed.");
+        init_requests
+            .send(StoreInitMessage::<MapMessage<&str, &str>>::InitComplete)
+            .await
+            .expect("Sending request failed.");
+        let mut initialized = FramedRead::new(response_rx, StoreInitializedCodec);
+        assert!(matches!(initialized.next().await, Some(Ok(_))));
+        (init_requests.into_inner(), initialized.into_inner())
+    };
+
+    let (result, (request_tx, response_rx)) = tokio::time::timeout(TIMEOUT, join(init, runtime))
+        .await
+        .expect("Test timed out.");
+    let (flags, io) = result.expect("Initialization failed.");
+    assert_eq!(
+        flags,
+        Flags::from([(Text::new("rollout"), Value::from(0.25))])
+    );
+
+    let task = run_task(flags, io);
+    let test_case = async move {
+        let mut requests = FramedWrite::new(request_tx, MapLaneRequestEncoder::default());
+        let mut responses = FramedRead::new(response_rx, MapLaneResponseDecoder::default());
+        let flags = sync(&mut requests, &mut responses).await;
+        assert_eq!(
+            flags,
+            Flags::from([(Text::new("rollout"), Value::from(0.25))])
+        );
+    };
+
+    tokio::time::timeout(TIMEOUT, async move {
+        tokio::select! {
+            result = task => panic!("Task stopped early: {:?}", result),
+            _ = test_case => {},
+        }
+    })
+    .await
+    .expect("Test timed out.");
+}
@@ -110,6 +110,21 @@ impl PlanePersistence for InMemoryPlanePersistence {
             }
         }
     }
+
+    fn node_uris(&self) -> BoxFuture<'static, Result<Vec<String>, StoreError>> {
+        let InMemoryPlanePersistence(inner) = self;
+        let guard = inner.lock();
+        let uris = guard
+            .nodes
+            .iter()
+            .filter(|(_, entry)| match entry {
+                NodeEntry::Idle(state) => !state.is_empty(),
+                NodeEntry::InUse(_) => true,
+            })
+            .map(|(uri, _)| uri.clone())
+            .collect();
+        ready(Ok(uris)).boxed()
+    }
 }
 
 type MapIt<'a> = std::collections::btree_map::Iter<'a, Vec<u8>, Vec<u8>>;
@@ -152,6 +167,12 @@ struct NodeState {
     maps: HashMap<u64, BTreeMap<Vec<u8>, Vec<u8>>>,
 }
 
+impl NodeState {
+    fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.maps.values().all(BTreeMap::is_empty)
+    }
+}
+
 impl NodePersistence for InMemoryNodePersistence {
     type MapCon<'a> = InMemRangeConsumer<'a>;
 
@@ -363,3 +363,29 @@ async fn kind_collision() {
     assert!(store.clear_map(value_id).is_err());
     assert!(store.read_map(value_id).is_err());
 }
+
+#[tokio::test]
+async fn list_node_uris() {
+    let plane = InMemoryPlanePersistence::default();
+
+    let mut with_state = make_store(&plane).await;
+    let id = with_state.id_for("value").expect(SHOULD_NOT_FAIL);
+    with_state.put_value(id, &[1]).expect(SHOULD_NOT_FAIL);
+    drop(with_state);
+
+    let without_state = plane
+        .node_store("example2")
+        .await
+        .expect("Failed to open second node store.");
+    drop(without_state);
+
+    let running = plane
+        .node_store("example3")
+        .await
+        .expect("Failed to open third node store.");
+
+    let mut uris = plane.node_uris().await.expect(SHOULD_NOT_FAIL);
+    uris.sort();
+    assert_eq!(uris, vec!["example1".to_string(), "example3".to_string()]);
+    drop(running);
+}
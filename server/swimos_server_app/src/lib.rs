@@ -52,16 +52,38 @@
 //! ```
 //!
 
+mod cluster;
 mod config;
+mod egress;
 mod error;
+mod flags;
 mod in_memory_store;
 mod plane;
+mod replication;
 mod server;
+mod store_compression;
 mod util;
 
 pub use self::{
-    config::{RemoteConnectionsConfig, SwimServerConfig},
-    server::{BoxServer, Server, ServerBuilder, ServerHandle, UnresolvableRoute},
+    cluster::{cluster_pattern, partitions_pattern, Cluster, PartitionConfig},
+    config::{
+        HandshakeRateLimit, HttpConfig, HttpConfigBuilder, RemoteConnectionsConfig,
+        StoreStartupPolicy, SwimServerConfig, SwimServerConfigBuilder,
+    },
+    egress::{
+        DeadLetterSink, DeliveryError, EgressBridge, EgressEvent, EgressSink, LogDeadLetters,
+    },
+    flags::{feature_flags_pattern, FeatureFlagAgent},
+    plane::{AgentStartPolicy, PlanePeer},
+    replication::{replication_pattern, ReplicationConfig},
+    server::{
+        AddRouteError, AgentStatus, BoxServer, PromoteError, RunningAgent, Server, ServerBuilder,
+        ServerHandle, ServerStopped, StopAgentError, UnresolvableRoute, WatchLaneError,
+    },
+    store_compression::{
+        train_dictionary, CompressedPersistence, CompressionCodec, CompressionMetrics,
+        CompressionStats, StoreCompressionConfig, DEFAULT_COMPRESSION_THRESHOLD,
+    },
     util::AgentExt,
 };
 
@@ -71,6 +93,22 @@ pub use server::wait::{until_termination, RegistrationFailed};
 pub use error::{AmbiguousRoutes, ServerBuilderError, ServerError};
 pub use ratchet::deflate::{DeflateConfig, WindowBits};
 pub use swimos_introspection::IntrospectionConfig;
+pub use swimos_remote::websocket::DeflateSettings;
+pub use swimos_runtime::agent::{
+    intercept::{
+        InterceptAction, InterceptRule, InterceptRules, InvalidSelector, OutgoingInterceptor,
+        RemoteEndpoint, RemoteFilter, Selector,
+    },
+    prune::{AlwaysPrune, IdleRemote, KeepRecentlyLinked, PruneDecision, PrunePolicy},
+    recording::{
+        CaptureDirection, EnvelopeRecording, EnvelopeReplay, InvalidRecord, RecordedEnvelope,
+        ReplayError, ReplayTiming,
+    },
+    AgentRuntimeConfig, AgentRuntimeConfigBuilder, LinkAdvisoryConfig, QueueOverflow,
+    StateMigrationPolicy, StoreFailureAction, UplinkBackpressure,
+};
+pub use swimos_runtime::config::ConfigError;
+pub use swimos_runtime::downlink::{DownlinkRuntimeConfig, DownlinkRuntimeConfigBuilder};
 use swimos_utilities::byte_channel::{ByteReader, ByteWriter};
 
 type Io = (ByteWriter, ByteReader);
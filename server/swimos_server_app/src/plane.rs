@@ -12,20 +12,79 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::Arc};
 
 use swimos_api::agent::{Agent, BoxAgent};
 use swimos_introspection::{lane_pattern, node_pattern};
 use swimos_model::Text;
+use swimos_remote::{BadWarpUrl, SchemeHostPort};
+use swimos_runtime::agent::{intercept::OutgoingInterceptor, prune::PrunePolicy};
 use swimos_utilities::routing::RoutePattern;
 
-use crate::{error::AmbiguousRoutes, util::AgentExt};
+use crate::{
+    cluster::{partitions_pattern, Cluster},
+    egress::EgressBridge,
+    error::AmbiguousRoutes,
+    flags::feature_flags_pattern,
+    replication::{replication_pattern, ReplicationConfig},
+    util::AgentExt,
+};
 
 /// A plane is a collection of agents which are all served by a single TCP listener. This mode
 /// describes all of the kinds of agents that are defined in the lane and maps them to URI routes.
 pub struct PlaneModel {
     pub(crate) name: Text,
     pub(crate) routes: Vec<(RoutePattern, BoxAgent)>,
+    pub(crate) explicit_start: Vec<RoutePattern>,
+    pub(crate) peers: Vec<PlanePeer>,
+    pub(crate) cluster: Option<Cluster>,
+    pub(crate) feature_flags: bool,
+    pub(crate) interceptor: Option<Arc<dyn OutgoingInterceptor>>,
+    pub(crate) prune_policy: Option<Arc<dyn PrunePolicy>>,
+    pub(crate) replication: Option<ReplicationConfig>,
+    pub(crate) egress: Vec<EgressBridge>,
+}
+
+/// Controls when the agents for a route of a plane are started.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AgentStartPolicy {
+    /// Agents are started when the first envelope addressed to them arrives (or when they are
+    /// started through the server handle).
+    #[default]
+    OnEnvelope,
+    /// Agents are only started through [`crate::ServerHandle::start_agent`]. Envelopes addressed
+    /// to an agent that is not running are rejected with an `@unlinked` envelope.
+    Explicit,
+}
+
+/// A peer server that hosts agents which are not hosted by this server. Envelopes that are addressed
+/// to nodes that do not match any of the local routes, but do match one of the routes of a peer, will
+/// be proxied to that peer. If the routes of more than one peer match, the peer that was added to the
+/// plane first will be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanePeer {
+    pub(crate) host: SchemeHostPort,
+    pub(crate) routes: Vec<RoutePattern>,
+}
+
+impl PlanePeer {
+    /// # Arguments
+    /// * `host` - The URL of the peer server (for example `ws://peer.example.com:8080`).
+    pub fn new(host: &str) -> Result<Self, BadWarpUrl> {
+        Ok(PlanePeer {
+            host: host.parse()?,
+            routes: vec![],
+        })
+    }
+
+    /// Add a route for nodes that are hosted by the peer.
+    ///
+    /// # Arguments
+    /// * `pattern` - The route pattern for matching the node URI of incoming envelopes.
+    pub fn add_route(mut self, pattern: RoutePattern) -> Self {
+        self.routes.push(pattern);
+        self
+    }
 }
 
 impl PlaneModel {
@@ -58,6 +117,51 @@ impl PlaneModel {
             Err(AmbiguousRoutes::collision(meta, routes))
         }
     }
+
+    pub fn check_partition_collisions(&self) -> Result<(), AmbiguousRoutes> {
+        let partitions = partitions_pattern();
+        let routes = self
+            .routes
+            .iter()
+            .filter(|(pattern, _)| RoutePattern::are_ambiguous(&partitions, pattern))
+            .map(|(pattern, _)| pattern.clone())
+            .collect::<Vec<_>>();
+        if routes.is_empty() {
+            Ok(())
+        } else {
+            Err(AmbiguousRoutes::collision(vec![partitions], routes))
+        }
+    }
+
+    pub fn check_feature_flag_collisions(&self) -> Result<(), AmbiguousRoutes> {
+        let flags = feature_flags_pattern();
+        let routes = self
+            .routes
+            .iter()
+            .filter(|(pattern, _)| RoutePattern::are_ambiguous(&flags, pattern))
+            .map(|(pattern, _)| pattern.clone())
+            .collect::<Vec<_>>();
+        if routes.is_empty() {
+            Ok(())
+        } else {
+            Err(AmbiguousRoutes::collision(vec![flags], routes))
+        }
+    }
+
+    pub fn check_replication_collisions(&self) -> Result<(), AmbiguousRoutes> {
+        let replication = replication_pattern();
+        let routes = self
+            .routes
+            .iter()
+            .filter(|(pattern, _)| RoutePattern::are_ambiguous(&replication, pattern))
+            .map(|(pattern, _)| pattern.clone())
+            .collect::<Vec<_>>();
+        if routes.is_empty() {
+            Ok(())
+        } else {
+            Err(AmbiguousRoutes::collision(vec![replication], routes))
+        }
+    }
 }
 
 /// A builder that will construct a [`PlaneModel`]. The consistency of the routes that are supplied
@@ -72,6 +176,14 @@ impl PlaneBuilder {
             model: PlaneModel {
                 name: Text::new(name),
                 routes: Default::default(),
+                explicit_start: Default::default(),
+                peers: Default::default(),
+                cluster: None,
+                feature_flags: false,
+                interceptor: None,
+                prune_policy: None,
+                replication: None,
+                egress: vec![],
             },
         }
     }
@@ -80,7 +192,19 @@ impl PlaneBuilder {
     /// this will fail.
     pub fn build(self) -> Result<PlaneModel, AmbiguousRoutes> {
         let PlaneBuilder {
-            model: PlaneModel { name, routes },
+            model:
+                PlaneModel {
+                    name,
+                    routes,
+                    explicit_start,
+                    peers,
+                    cluster,
+                    feature_flags,
+                    interceptor,
+                    prune_policy,
+                    replication,
+                    egress,
+                },
         } = self;
         let template = routes.iter().map(|(r, _)| r).enumerate();
 
@@ -112,7 +236,18 @@ impl PlaneBuilder {
                 .collect();
             Err(AmbiguousRoutes::new(bad))
         } else {
-            Ok(PlaneModel { name, routes })
+            Ok(PlaneModel {
+                name,
+                routes,
+                explicit_start,
+                peers,
+                cluster,
+                feature_flags,
+                interceptor,
+                prune_policy,
+                replication,
+                egress,
+            })
         }
     }
 
@@ -123,8 +258,80 @@ impl PlaneBuilder {
     /// * `pattern` - The route pattern for matching the node URI of incoming envelopes.
     /// * `agent` - The agent type to be started each time the route matches.
     pub fn add_route<A: Agent + Send + 'static>(&mut self, pattern: RoutePattern, agent: A) {
+        self.add_route_with_policy(pattern, agent, AgentStartPolicy::OnEnvelope);
+    }
+
+    /// Add a new route to the builder, specifying when the agents for the route are started. This
+    /// does not check that the route is not ambiguous with respect to the already added routes.
+    ///
+    /// # Arguments
+    /// * `pattern` - The route pattern for matching the node URI of incoming envelopes.
+    /// * `agent` - The agent type to be started each time the route matches.
+    /// * `policy` - Whether agents are started by incoming envelopes or only explicitly.
+    pub fn add_route_with_policy<A: Agent + Send + 'static>(
+        &mut self,
+        pattern: RoutePattern,
+        agent: A,
+        policy: AgentStartPolicy,
+    ) {
+        if policy == AgentStartPolicy::Explicit {
+            self.model.explicit_start.push(pattern.clone());
+        }
         self.model.routes.push((pattern, agent.boxed()));
     }
+
+    /// Add a peer server to which envelopes for nodes that are not hosted locally can be proxied.
+    ///
+    /// # Arguments
+    /// * `peer` - The host and routes of the peer.
+    pub fn add_peer(&mut self, peer: PlanePeer) {
+        self.model.peers.push(peer);
+    }
+
+    /// Partition the nodes of the plane between the members of a cluster.
+    ///
+    /// # Arguments
+    /// * `cluster` - The members of the cluster.
+    pub fn set_cluster(&mut self, cluster: Cluster) {
+        self.model.cluster = Some(cluster);
+    }
+
+    /// Run the built-in feature flags agent (at `swimos:meta:flags`) in the plane.
+    pub fn enable_feature_flags(&mut self) {
+        self.model.feature_flags = true;
+    }
+
+    /// Transform the events sent by the agents of the plane before they are written to remotes.
+    ///
+    /// # Arguments
+    /// * `interceptor` - The interceptor to apply to the events.
+    pub fn set_interceptor(&mut self, interceptor: Arc<dyn OutgoingInterceptor>) {
+        self.model.interceptor = Some(interceptor);
+    }
+
+    /// Customize when remotes, with no links to the agents of the plane, are pruned.
+    ///
+    /// # Arguments
+    /// * `prune_policy` - The policy to apply to idle remotes.
+    pub fn set_prune_policy(&mut self, prune_policy: Arc<dyn PrunePolicy>) {
+        self.model.prune_policy = Some(prune_policy);
+    }
+
+    /// Run the plane as one of a primary/standby pair.
+    ///
+    /// # Arguments
+    /// * `config` - The role of the server and the replication parameters.
+    pub fn set_replication(&mut self, config: ReplicationConfig) {
+        self.model.replication = Some(config);
+    }
+
+    /// Push the events of lanes of the plane to an external sink.
+    ///
+    /// # Arguments
+    /// * `bridge` - The lanes to subscribe to and the sink for their events.
+    pub fn add_egress_bridge(&mut self, bridge: EgressBridge) {
+        self.model.egress.push(bridge);
+    }
 }
 
 #[cfg(test)]
@@ -135,9 +342,11 @@ mod tests {
     use swimos_api::agent::{Agent, AgentConfig, AgentContext, AgentInitResult};
     use swimos_utilities::routing::{RoutePattern, RouteUri};
 
-    use crate::error::AmbiguousRoutes;
+    use swimos_remote::BadWarpUrl;
+
+    use crate::{cluster::Cluster, error::AmbiguousRoutes, replication::ReplicationConfig};
 
-    use super::PlaneModel;
+    use super::{PlaneModel, PlanePeer};
 
     struct DummyAgent;
 
@@ -159,7 +368,7 @@ mod tests {
         let route = RoutePattern::parse_str("/node").expect("Bad route.");
         builder.add_route(route.clone(), DummyAgent);
 
-        let PlaneModel { name, routes } = builder.build().expect("Building plane failed.");
+        let PlaneModel { name, routes, .. } = builder.build().expect("Building plane failed.");
 
         assert_eq!(name, "plane");
         match routes.as_slice() {
@@ -178,7 +387,7 @@ mod tests {
         builder.add_route(route1.clone(), DummyAgent);
         builder.add_route(route2.clone(), DummyAgent);
 
-        let PlaneModel { name, routes } = builder.build().expect("Building plane failed.");
+        let PlaneModel { name, routes, .. } = builder.build().expect("Building plane failed.");
 
         assert_eq!(name, "plane");
         match routes.as_slice() {
@@ -240,4 +449,78 @@ mod tests {
             _ => panic!("Wrong number of routes."),
         }
     }
+
+    #[test]
+    fn plane_with_peer() {
+        let mut builder = super::PlaneBuilder::with_name("plane");
+        let route = RoutePattern::parse_str("/node").expect("Bad route.");
+        let peer_route = RoutePattern::parse_str("/remote/:id").expect("Bad route.");
+        builder.add_route(route, DummyAgent);
+        let peer = PlanePeer::new("ws://peer:8080")
+            .expect("Bad host.")
+            .add_route(peer_route.clone());
+        builder.add_peer(peer.clone());
+
+        let PlaneModel { peers, .. } = builder.build().expect("Building plane failed.");
+        assert_eq!(peers, vec![peer]);
+        let [PlanePeer { host, routes }] = peers.as_slice() else {
+            panic!("Wrong number of peers.");
+        };
+        assert_eq!(host.to_string(), "ws://peer:8080");
+        assert_eq!(routes, &vec![peer_route]);
+    }
+
+    #[test]
+    fn peer_with_bad_host() {
+        assert_eq!(
+            PlanePeer::new("http://peer:8080").err(),
+            Some(BadWarpUrl::BadScheme("http".to_string()))
+        );
+    }
+
+    #[test]
+    fn route_collides_with_partitions_agent() {
+        let mut builder = super::PlaneBuilder::with_name("plane");
+        let route = RoutePattern::parse_str("swimos:meta:partitions").expect("Bad route.");
+        builder.add_route(route.clone(), DummyAgent);
+        builder
+            .set_cluster(Cluster::new("ws://local:8080", Default::default()).expect("Bad host."));
+
+        let model = builder.build().expect("Building plane failed.");
+        assert!(model.cluster.is_some());
+        match model.check_partition_collisions() {
+            Err(AmbiguousRoutes::MetaCollision { routes, .. }) => assert_eq!(routes, vec![route]),
+            _ => panic!("Collision not detected."),
+        }
+    }
+
+    #[test]
+    fn route_collides_with_feature_flags_agent() {
+        let mut builder = super::PlaneBuilder::with_name("plane");
+        let route = RoutePattern::parse_str("swimos:meta:flags").expect("Bad route.");
+        builder.add_route(route.clone(), DummyAgent);
+        builder.enable_feature_flags();
+
+        let model = builder.build().expect("Building plane failed.");
+        assert!(model.feature_flags);
+        match model.check_feature_flag_collisions() {
+            Err(AmbiguousRoutes::MetaCollision { routes, .. }) => assert_eq!(routes, vec![route]),
+            _ => panic!("Collision not detected."),
+        }
+    }
+
+    #[test]
+    fn route_collides_with_replication_agent() {
+        let mut builder = super::PlaneBuilder::with_name("plane");
+        let route = RoutePattern::parse_str("swimos:meta:replication").expect("Bad route.");
+        builder.add_route(route.clone(), DummyAgent);
+        builder.set_replication(ReplicationConfig::primary("secret"));
+
+        let model = builder.build().expect("Building plane failed.");
+        assert!(model.replication.is_some());
+        match model.check_replication_collisions() {
+            Err(AmbiguousRoutes::MetaCollision { routes, .. }) => assert_eq!(routes, vec![route]),
+            _ => panic!("Collision not detected."),
+        }
+    }
 }
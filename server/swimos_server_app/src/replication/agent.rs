@@ -0,0 +1,276 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+
+use bytes::BytesMut;
+use futures::{future::BoxFuture, FutureExt, SinkExt, StreamExt};
+use swimos_agent_protocol::{
+    encoding::lane::{RawValueLaneRequestDecoder, ValueLaneResponseEncoder},
+    LaneRequest, LaneResponse,
+};
+use swimos_api::{
+    agent::{Agent, AgentConfig, AgentContext, AgentInitResult, WarpLaneKind},
+    error::{AgentTaskError, FrameIoError, StoreError},
+    persistence::PlanePersistence,
+};
+use swimos_model::Text;
+use swimos_recon::parser::parse_recognize;
+use swimos_utilities::{
+    byte_channel::{ByteReader, ByteWriter},
+    routing::{RoutePattern, RouteUri},
+};
+use thiserror::Error;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::codec::{FramedRead, FramedWrite};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use super::{
+    log::{ReplicationEvent, ReplicationLog},
+    store::read_node,
+};
+
+pub(super) const REPLICATION_NODE: &str = "swimos:meta:replication";
+pub(super) const MUTATIONS_LANE: &str = "mutations";
+
+/// Create a route pattern for the meta-agent that streams the persisted state of a server to its
+/// standby.
+pub fn replication_pattern() -> RoutePattern {
+    RoutePattern::parse_str(REPLICATION_NODE).expect("Replication pattern should be valid.")
+}
+
+// Compare the secrets in constant time so that the expected secret cannot be discovered from the
+// time taken to reject it.
+pub(super) fn secrets_match(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// A meta-agent with a supply lane that sends a snapshot of the persisted state of the server to
+/// each standby that links to it, followed by every mutation that is made to that state. A standby
+/// must first authenticate by sending the shared secret to the lane as a command. Nothing is sent
+/// to a remote until it has authenticated.
+pub struct ReplicationAgent<P> {
+    log: ReplicationLog,
+    secret: String,
+    plane_store: P,
+}
+
+impl<P> ReplicationAgent<P> {
+    /// # Arguments
+    /// * `log` - The log of the mutations to the persisted state of the server.
+    /// * `secret` - The secret that is shared with the standby.
+    /// * `plane_store` - The store from which the snapshots are read.
+    pub fn new(log: ReplicationLog, secret: &str, plane_store: P) -> Self {
+        ReplicationAgent {
+            log,
+            secret: secret.to_string(),
+            plane_store,
+        }
+    }
+}
+
+impl<P> Agent for ReplicationAgent<P>
+where
+    P: PlanePersistence + Clone + Send + Sync + 'static,
+{
+    fn run(
+        &self,
+        _route: RouteUri,
+        _route_params: HashMap<String, String>,
+        config: AgentConfig,
+        context: Box<dyn AgentContext + Send>,
+    ) -> BoxFuture<'static, AgentInitResult> {
+        run_init(
+            self.log.clone(),
+            self.secret.clone(),
+            self.plane_store.clone(),
+            config,
+            context,
+        )
+        .boxed()
+    }
+}
+
+async fn run_init<P>(
+    log: ReplicationLog,
+    secret: String,
+    plane_store: P,
+    config: AgentConfig,
+    context: Box<dyn AgentContext + Send>,
+) -> AgentInitResult
+where
+    P: PlanePersistence + Send + Sync + 'static,
+{
+    let mut lane_config = config.default_lane_config.unwrap_or_default();
+    lane_config.transient = true;
+    // The secret is attributed to the remote that sent it.
+    lane_config.track_origin = true;
+    let io = context
+        .add_lane(MUTATIONS_LANE, WarpLaneKind::Supply, lane_config)
+        .await?;
+    Ok(async move {
+        let result = run_task(log, &secret, plane_store, io)
+            .await
+            .map_err(|error| match error {
+                ReplicationTaskError::Io(error) => AgentTaskError::BadFrame {
+                    lane: Text::new(MUTATIONS_LANE),
+                    error,
+                },
+                ReplicationTaskError::Store(error) => {
+                    AgentTaskError::UserCodeError(Box::new(error))
+                }
+            });
+        // deferred drop so the agent doesn't terminate early.
+        drop(context);
+        result
+    }
+    .boxed())
+}
+
+/// Errors that can cause the replication lane to fail.
+#[derive(Debug, Error)]
+pub(super) enum ReplicationTaskError {
+    #[error(transparent)]
+    Io(#[from] FrameIoError),
+    #[error("Reading a snapshot from the store failed: {0}")]
+    Store(#[from] StoreError),
+}
+
+impl From<std::io::Error> for ReplicationTaskError {
+    fn from(err: std::io::Error) -> Self {
+        ReplicationTaskError::Io(err.into())
+    }
+}
+
+pub(super) async fn run_task<P>(
+    log: ReplicationLog,
+    secret: &str,
+    plane_store: P,
+    io: (ByteWriter, ByteReader),
+) -> Result<(), ReplicationTaskError>
+where
+    P: PlanePersistence,
+{
+    let (tx, rx) = io;
+    let mut input = FramedRead::new(rx, RawValueLaneRequestDecoder::default());
+    let mut output = FramedWrite::new(tx, ValueLaneResponseEncoder::default());
+    let mut mutations = log.subscribe();
+
+    // Remotes that have sent the shared secret.
+    let mut authenticated: HashSet<Uuid> = HashSet::new();
+    // Remotes that requested a sync before authenticating. The sync is completed when they do.
+    let mut awaiting_auth: HashSet<Uuid> = HashSet::new();
+
+    loop {
+        tokio::select! {
+            maybe_request = input.next() => match maybe_request {
+                Some(request) => match request? {
+                    LaneRequest::Sync(id) | LaneRequest::SyncSince(id, _) | LaneRequest::SyncRange(id, _) => {
+                        if authenticated.contains(&id) {
+                            send_snapshot(&log, &plane_store, &mut output, id).await?;
+                        } else {
+                            debug!(remote_id = %id, "Holding a sync with the replication lane until the remote authenticates.");
+                            awaiting_auth.insert(id);
+                        }
+                    }
+                    LaneRequest::CommandFrom(origin, body) | LaneRequest::CorrelatedCommand(_, Some(origin), body) => {
+                        if is_secret(&body, secret) {
+                            info!(remote_id = %origin, "A standby authenticated with the replication agent.");
+                            authenticated.insert(origin);
+                            if awaiting_auth.remove(&origin) {
+                                send_snapshot(&log, &plane_store, &mut output, origin).await?;
+                            }
+                        } else {
+                            warn!(remote_id = %origin, "Rejected an attempt to authenticate with the replication agent.");
+                        }
+                    }
+                    _ => {}
+                },
+                None => break,
+            },
+            result = mutations.recv() => match result {
+                Ok(event) => {
+                    // The mutations are only sent to the remotes that have authenticated so they
+                    // are never broadcast to every uplink of the lane.
+                    for id in &authenticated {
+                        output.feed(LaneResponse::SyncEvent(*id, event.as_ref())).await?;
+                    }
+                    SinkExt::<LaneResponse<&ReplicationEvent>>::flush(&mut output).await?;
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    // The standby will detect the gap in the sequence numbers and request a new
+                    // snapshot.
+                    warn!(missed, "The replication lane fell behind the mutations to the store.");
+                }
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+    Ok(())
+}
+
+fn is_secret(body: &BytesMut, secret: &str) -> bool {
+    std::str::from_utf8(body.as_ref())
+        .ok()
+        .and_then(|body| parse_recognize::<String>(body, false).ok())
+        .is_some_and(|provided| secrets_match(&provided, secret))
+}
+
+async fn send_snapshot<P>(
+    log: &ReplicationLog,
+    plane_store: &P,
+    output: &mut FramedWrite<ByteWriter, ValueLaneResponseEncoder>,
+    id: Uuid,
+) -> Result<(), ReplicationTaskError>
+where
+    P: PlanePersistence,
+{
+    // Every mutation up to this sequence number has already been written to the store so is
+    // included in the snapshot. Later mutations may also be included but, as the mutations replace
+    // state, applying them again after the snapshot leaves the standby in the same state.
+    let seq = log.seq();
+    output
+        .feed(LaneResponse::SyncEvent(
+            id,
+            &ReplicationEvent::SnapshotStart { seq },
+        ))
+        .await?;
+    // The snapshot is read from the store one node at a time.
+    for node in plane_store.node_uris().await? {
+        let node_store = plane_store.node_store(&node).await?;
+        for (lane, state) in read_node(&node_store)? {
+            for op in state.operations() {
+                let event = ReplicationEvent::Mutation {
+                    seq,
+                    node: node.clone(),
+                    lane: lane.clone(),
+                    op,
+                };
+                output.feed(LaneResponse::SyncEvent(id, &event)).await?;
+            }
+        }
+    }
+    output
+        .feed(LaneResponse::SyncEvent(id, &ReplicationEvent::SnapshotEnd))
+        .await?;
+    let synced: LaneResponse<()> = LaneResponse::Synced(id);
+    output.send(synced).await?;
+    Ok(())
+}
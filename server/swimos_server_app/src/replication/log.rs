@@ -0,0 +1,126 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::BTreeMap, num::NonZeroUsize, sync::Arc};
+
+use parking_lot::Mutex;
+use swimos_form::Form;
+use tokio::sync::broadcast;
+
+/// A change to the persisted state of a single lane (or store) of an agent.
+#[derive(Form, Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// The value of a value-like item was replaced.
+    Put { value: Vec<u8> },
+    /// The value of a value-like item was removed.
+    Delete,
+    /// An entry of a map-like item was inserted or replaced.
+    Update { key: Vec<u8>, value: Vec<u8> },
+    /// An entry of a map-like item was removed.
+    Remove { key: Vec<u8> },
+    /// All entries of a map-like item were removed.
+    Clear,
+}
+
+/// The events that are sent by the replication lane of a primary server.
+///
+/// A standby that links to the lane receives a snapshot of the entire persisted state of the primary
+/// (a `SnapshotStart`, a mutation for each value and map entry, and a `SnapshotEnd`) followed by every
+/// subsequent mutation. Mutations are numbered consecutively so that a standby can detect any that
+/// it has missed.
+#[derive(Form, Debug, Clone, PartialEq, Eq)]
+pub enum ReplicationEvent {
+    /// The start of a snapshot that includes all mutations up to (and including) `seq`.
+    SnapshotStart { seq: u64 },
+    /// The end of a snapshot.
+    SnapshotEnd,
+    /// A mutation to the persisted state of a lane.
+    Mutation {
+        seq: u64,
+        node: String,
+        lane: String,
+        op: Operation,
+    },
+}
+
+/// The state of a lane, as it was read from the store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum LaneState {
+    Value(Option<Vec<u8>>),
+    Map(BTreeMap<Vec<u8>, Vec<u8>>),
+}
+
+impl LaneState {
+    /// The operations that will restore the state of the lane into an empty store.
+    pub fn operations(&self) -> Vec<Operation> {
+        match self {
+            LaneState::Value(Some(value)) => vec![Operation::Put {
+                value: value.clone(),
+            }],
+            LaneState::Value(None) => vec![Operation::Delete],
+            LaneState::Map(entries) => std::iter::once(Operation::Clear)
+                .chain(entries.iter().map(|(key, value)| Operation::Update {
+                    key: key.clone(),
+                    value: value.clone(),
+                }))
+                .collect(),
+        }
+    }
+}
+
+/// A log of the mutations that are made to the persisted state of a plane. The log only numbers
+/// the mutations and retains the most recent of them, up to its capacity, for consumers that have
+/// fallen behind. Snapshots of the state are read from the store itself.
+#[derive(Debug, Clone)]
+pub(crate) struct ReplicationLog {
+    seq: Arc<Mutex<u64>>,
+    tx: broadcast::Sender<Arc<ReplicationEvent>>,
+}
+
+impl ReplicationLog {
+    /// # Arguments
+    /// * `capacity` - The number of mutations that are retained for consumers that are behind.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        let (tx, _) = broadcast::channel(capacity.get());
+        ReplicationLog {
+            seq: Arc::new(Mutex::new(0)),
+            tx,
+        }
+    }
+
+    /// Record a mutation that has been written into the store.
+    pub fn record(&self, node: &str, lane: &str, op: Operation) {
+        // The lock is held while the event is sent so that the events are sent in order.
+        let mut seq = self.seq.lock();
+        *seq += 1;
+        let event = ReplicationEvent::Mutation {
+            seq: *seq,
+            node: node.to_string(),
+            lane: lane.to_string(),
+            op,
+        };
+        // There may be no consumers, in which case the event is discarded.
+        let _ = self.tx.send(Arc::new(event));
+    }
+
+    /// The sequence number of the most recently recorded mutation.
+    pub fn seq(&self) -> u64 {
+        *self.seq.lock()
+    }
+
+    /// Subscribe to the mutations that are recorded after this is called.
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<ReplicationEvent>> {
+        self.tx.subscribe()
+    }
+}
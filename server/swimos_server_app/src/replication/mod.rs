@@ -0,0 +1,141 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fmt::{Debug, Formatter},
+    num::NonZeroUsize,
+    time::Duration,
+};
+
+use swimos_remote::{BadWarpUrl, SchemeHostPort};
+use swimos_utilities::non_zero_usize;
+
+mod agent;
+mod log;
+mod standby;
+mod store;
+
+pub use agent::replication_pattern;
+pub(crate) use agent::ReplicationAgent;
+pub(crate) use log::ReplicationLog;
+pub(crate) use standby::{run_standby, StandbyOutcome};
+pub(crate) use store::ReplicatedPersistence;
+
+#[cfg(test)]
+mod tests;
+
+const DEFAULT_LOG_CAPACITY: NonZeroUsize = non_zero_usize!(4096);
+const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The role of a server in a primary/standby pair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ReplicationRole {
+    Primary,
+    Standby { primary: SchemeHostPort },
+}
+
+/// Configuration for a warm standby deployment, where a standby server keeps a copy of the persisted
+/// state of a primary server so that it can take over if the primary fails.
+///
+/// Both servers expose the replication meta-agent (at `swimos:meta:replication`). After linking to
+/// the agent, the standby sends it the secret that is shared by the primary and the standby as a
+/// command. The agent sends nothing to a remote that has not sent the secret, so that the state of
+/// the primary cannot be read by other clients. As the secret is sent in a command, the standby
+/// should connect to the primary over TLS. Once authenticated, the standby receives a snapshot of
+/// the persisted state of the primary followed by a stream of every mutation that the agents of
+/// the primary make to their state, which are written into the store of the standby. The standby does not run the agents of the plane until it is promoted,
+/// either explicitly (with [`crate::ServerHandle::promote`]) or when the primary has been unreachable
+/// for longer than the failover timeout. After promotion, agents are started on demand and restore
+/// their state from the replicated store.
+///
+/// Replication is asynchronous: the mutations that the primary had made, but not yet sent to the
+/// standby, when it failed are lost. If the standby falls further behind than the capacity of the
+/// log of the primary it will request a new snapshot.
+///
+/// Snapshots are read from the store of the primary, one agent at a time, so the store must be able
+/// to list the agents that it holds state for. This has no effect if no store is enabled for the
+/// server.
+#[derive(Clone)]
+pub struct ReplicationConfig {
+    pub(crate) role: ReplicationRole,
+    pub(crate) secret: String,
+    pub(crate) log: ReplicationLog,
+    pub(crate) retry_interval: Duration,
+    pub(crate) failover_timeout: Option<Duration>,
+}
+
+// The secret is omitted so that it is not written into logs.
+impl Debug for ReplicationConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReplicationConfig")
+            .field("role", &self.role)
+            .field("log", &self.log)
+            .field("retry_interval", &self.retry_interval)
+            .field("failover_timeout", &self.failover_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReplicationConfig {
+    /// Configuration for a primary server.
+    ///
+    /// # Arguments
+    /// * `secret` - The secret that is shared with the standby.
+    pub fn primary(secret: &str) -> Self {
+        Self::with_role(ReplicationRole::Primary, secret)
+    }
+
+    /// Configuration for a standby server.
+    ///
+    /// # Arguments
+    /// * `primary` - The URL of the primary server (for example `wss://primary.example.com:8080`).
+    /// * `secret` - The secret that is shared with the primary.
+    pub fn standby(primary: &str, secret: &str) -> Result<Self, BadWarpUrl> {
+        Ok(Self::with_role(
+            ReplicationRole::Standby {
+                primary: primary.parse()?,
+            },
+            secret,
+        ))
+    }
+
+    fn with_role(role: ReplicationRole, secret: &str) -> Self {
+        ReplicationConfig {
+            role,
+            secret: secret.to_string(),
+            log: ReplicationLog::new(DEFAULT_LOG_CAPACITY),
+            retry_interval: DEFAULT_RETRY_INTERVAL,
+            failover_timeout: None,
+        }
+    }
+
+    /// Set the number of mutations that are retained for a standby that falls behind.
+    pub fn with_log_capacity(mut self, capacity: NonZeroUsize) -> Self {
+        self.log = ReplicationLog::new(capacity);
+        self
+    }
+
+    /// Set the interval between attempts of a standby to link to its primary.
+    pub fn with_retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    /// Promote a standby automatically when it has been unable to link to its primary for the
+    /// specified time. By default, a standby is only promoted explicitly.
+    pub fn with_failover_timeout(mut self, timeout: Duration) -> Self {
+        self.failover_timeout = Some(timeout);
+        self
+    }
+}
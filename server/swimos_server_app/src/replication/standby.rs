@@ -0,0 +1,250 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    time::Duration,
+};
+
+use futures::{SinkExt, StreamExt};
+use swimos_agent_protocol::{
+    encoding::downlink::{DownlinkOperationEncoder, ValueNotificationDecoder},
+    DownlinkNotification, DownlinkOperation,
+};
+use swimos_api::{
+    address::RelativeAddress,
+    agent::DownlinkKind,
+    error::{FrameIoError, StoreError},
+    persistence::{NodePersistence, PlanePersistence},
+};
+use swimos_model::Text;
+use swimos_remote::SchemeHostPort;
+use swimos_runtime::{
+    agent::{DownlinkRequest, LinkRequest},
+    downlink::DownlinkOptions,
+};
+use thiserror::Error;
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::Instant,
+};
+use tokio_util::codec::{FramedRead, FramedWrite};
+use tracing::{debug, info, warn};
+
+use crate::Io;
+
+use super::{
+    agent::{MUTATIONS_LANE, REPLICATION_NODE},
+    log::{Operation, ReplicationEvent},
+};
+
+/// Errors that can occur while a standby is following the replication lane of its primary.
+#[derive(Debug, Error)]
+pub(crate) enum ReplicationError {
+    #[error("The link to the primary failed: {0}")]
+    Link(#[from] FrameIoError),
+    #[error("Failed to apply a mutation to the store: {0}")]
+    Store(#[from] StoreError),
+    #[error("Mutations were missed (expected {expected} but received {received}).")]
+    Gap { expected: u64, received: u64 },
+}
+
+/// The reason that a standby stopped following its primary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StandbyOutcome {
+    /// The primary could not be reached for longer than the failover timeout.
+    Failover,
+    /// The server is stopping.
+    Stopped,
+}
+
+/// Follow the replication lane of a primary server, applying the mutations to the local store,
+/// until the primary has been unreachable for longer than the failover timeout (if there is one).
+///
+/// # Arguments
+/// * `primary` - The URL of the primary server.
+/// * `secret` - The secret that is shared with the primary.
+/// * `retry_interval` - The interval between attempts to link to the primary.
+/// * `failover_timeout` - The time for which the primary can be unreachable before the standby
+///   should be promoted.
+/// * `plane_store` - The store of the standby.
+/// * `link_requests` - Channel used to open downlinks from the standby.
+pub(crate) async fn run_standby<P: PlanePersistence>(
+    primary: SchemeHostPort,
+    secret: &str,
+    retry_interval: Duration,
+    failover_timeout: Option<Duration>,
+    plane_store: P,
+    link_requests: mpsc::Sender<LinkRequest>,
+) -> StandbyOutcome {
+    let mut replica = Replica::new(plane_store);
+    let mut disconnected_at = Instant::now();
+    loop {
+        let (promise_tx, promise_rx) = oneshot::channel();
+        let request = DownlinkRequest::new(
+            Some(primary.clone()),
+            RelativeAddress::new(Text::new(REPLICATION_NODE), Text::new(MUTATIONS_LANE)),
+            DownlinkKind::Event,
+            DownlinkOptions::SYNC,
+            promise_tx,
+        );
+        if link_requests
+            .send(LinkRequest::Downlink(request))
+            .await
+            .is_err()
+        {
+            return StandbyOutcome::Stopped;
+        }
+        match promise_rx.await {
+            Ok(Ok(io)) => {
+                info!(primary = %primary, "Linked to the replication lane of the primary.");
+                match replica.follow(io, secret).await {
+                    Ok(()) => info!(primary = %primary, "The link to the primary was closed."),
+                    Err(error) => {
+                        warn!(primary = %primary, error = %error, "Replication from the primary failed.")
+                    }
+                }
+                disconnected_at = Instant::now();
+            }
+            Ok(Err(error)) => {
+                debug!(primary = %primary, error = %error, "Failed to link to the primary.");
+            }
+            Err(_) => return StandbyOutcome::Stopped,
+        }
+        if let Some(timeout) = failover_timeout {
+            if disconnected_at.elapsed() >= timeout {
+                warn!(primary = %primary, "The primary has been unreachable for longer than the failover timeout.");
+                return StandbyOutcome::Failover;
+            }
+        }
+        tokio::time::sleep(retry_interval).await;
+    }
+}
+
+/// Tracks the progress of a standby through the events of the replication lane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Progress {
+    /// Events are ignored until the start of a snapshot.
+    AwaitingSnapshot,
+    /// Receiving a snapshot that includes all mutations up to the sequence number.
+    InSnapshot(u64),
+    /// Following the mutations after the sequence number.
+    Following(u64),
+}
+
+/// The local copy of the persisted state of the primary. The node stores are held open until the
+/// replica is dropped.
+pub(crate) struct Replica<P: PlanePersistence> {
+    plane_store: P,
+    nodes: HashMap<String, P::Node>,
+}
+
+impl<P: PlanePersistence> Replica<P> {
+    pub fn new(plane_store: P) -> Self {
+        Replica {
+            plane_store,
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Authenticate with the replication lane and apply its events until the link is closed.
+    async fn follow(&mut self, io: Io, secret: &str) -> Result<(), ReplicationError> {
+        let (writer, reader) = io;
+        // The primary will not send the snapshot until it has received the secret. The writer must
+        // be held for as long as the link is required.
+        let mut commands = FramedWrite::new(writer, DownlinkOperationEncoder::default());
+        commands
+            .send(DownlinkOperation::new(secret))
+            .await
+            .map_err(FrameIoError::from)?;
+        let mut notifications = FramedRead::new(
+            reader,
+            ValueNotificationDecoder::<ReplicationEvent>::default(),
+        );
+        let mut progress = Progress::AwaitingSnapshot;
+        while let Some(notification) = notifications.next().await {
+            match notification? {
+                DownlinkNotification::Event { body } => self.handle(&mut progress, body).await?,
+                DownlinkNotification::Unlinked => break,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) async fn handle(
+        &mut self,
+        progress: &mut Progress,
+        event: ReplicationEvent,
+    ) -> Result<(), ReplicationError> {
+        match (*progress, event) {
+            (_, ReplicationEvent::SnapshotStart { seq }) => {
+                *progress = Progress::InSnapshot(seq);
+            }
+            (Progress::InSnapshot(seq), ReplicationEvent::SnapshotEnd) => {
+                info!(seq, "Received a snapshot of the state of the primary.");
+                *progress = Progress::Following(seq);
+            }
+            (Progress::InSnapshot(_), ReplicationEvent::Mutation { node, lane, op, .. }) => {
+                self.apply(&node, &lane, op).await?;
+            }
+            (
+                Progress::Following(applied),
+                ReplicationEvent::Mutation {
+                    seq,
+                    node,
+                    lane,
+                    op,
+                },
+            ) => {
+                if seq == applied + 1 {
+                    self.apply(&node, &lane, op).await?;
+                    *progress = Progress::Following(seq);
+                } else if seq > applied + 1 {
+                    return Err(ReplicationError::Gap {
+                        expected: applied + 1,
+                        received: seq,
+                    });
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn apply(&mut self, node: &str, lane: &str, op: Operation) -> Result<(), StoreError> {
+        let Replica { plane_store, nodes } = self;
+        let store = match nodes.entry(node.to_string()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(plane_store.node_store(node).await?),
+        };
+        apply_operation(store, lane, op)
+    }
+}
+
+/// Apply a mutation, received from the primary, to the store for a node.
+pub(crate) fn apply_operation<N: NodePersistence>(
+    store: &mut N,
+    lane: &str,
+    op: Operation,
+) -> Result<(), StoreError> {
+    let id = store.id_for(lane)?;
+    match op {
+        Operation::Put { value } => store.put_value(id, &value),
+        Operation::Delete => store.delete_value(id),
+        Operation::Update { key, value } => store.update_map(id, &key, &value),
+        Operation::Remove { key } => store.remove_map(id, &key),
+        Operation::Clear => store.clear_map(id),
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bytes::BytesMut;
+use futures::{future::BoxFuture, FutureExt, TryFutureExt};
+use parking_lot::Mutex;
+use swimos_api::{
+    error::StoreError,
+    persistence::{NodePersistence, PlanePersistence, RangeConsumer, ServerPersistence},
+};
+use swimos_recon::parser::parse_recognize;
+use swimos_runtime::agent::{decode_item_kind, MANIFEST_NAME};
+
+use super::log::{LaneState, Operation, ReplicationLog};
+
+/// A [`ServerPersistence`] implementation that records the mutations made to another store in a
+/// [`ReplicationLog`].
+#[derive(Debug)]
+pub(crate) struct ReplicatedPersistence<S> {
+    inner: S,
+    log: ReplicationLog,
+}
+
+impl<S> ReplicatedPersistence<S> {
+    /// # Arguments
+    /// * `inner` - The underlying store.
+    /// * `log` - The log into which the mutations are recorded.
+    pub fn new(inner: S, log: ReplicationLog) -> Self {
+        ReplicatedPersistence { inner, log }
+    }
+}
+
+impl<S: ServerPersistence> ServerPersistence for ReplicatedPersistence<S> {
+    type PlaneStore = ReplicatedPlanePersistence<S::PlaneStore>;
+
+    fn open_plane(&self, name: &str) -> Result<Self::PlaneStore, StoreError> {
+        let ReplicatedPersistence { inner, log } = self;
+        Ok(ReplicatedPlanePersistence {
+            inner: inner.open_plane(name)?,
+            log: log.clone(),
+        })
+    }
+}
+
+/// A [`PlanePersistence`] implementation that records the mutations made to another store in a
+/// [`ReplicationLog`].
+#[derive(Debug, Clone)]
+pub(crate) struct ReplicatedPlanePersistence<P> {
+    inner: P,
+    log: ReplicationLog,
+}
+
+impl<P: PlanePersistence> PlanePersistence for ReplicatedPlanePersistence<P> {
+    type Node = ReplicatedNodePersistence<P::Node>;
+
+    fn node_store(&self, node_uri: &str) -> BoxFuture<'static, Result<Self::Node, StoreError>> {
+        let ReplicatedPlanePersistence { inner, log } = self;
+        let log = log.clone();
+        let node = node_uri.to_string();
+        inner
+            .node_store(node_uri)
+            .map_ok(move |inner| ReplicatedNodePersistence {
+                inner,
+                node,
+                log,
+                lanes: Default::default(),
+            })
+            .boxed()
+    }
+
+    fn node_uris(&self) -> BoxFuture<'static, Result<Vec<String>, StoreError>> {
+        self.inner.node_uris()
+    }
+}
+
+/// Read all of the state of a node. The names of the items of the agent are taken from its manifest.
+pub(crate) fn read_node<N: NodePersistence>(
+    node: &N,
+) -> Result<HashMap<String, LaneState>, StoreError> {
+    let mut buffer = BytesMut::new();
+    let manifest_id = node.id_for(MANIFEST_NAME)?;
+    if node.get_value(manifest_id, &mut buffer)?.is_none() {
+        return Ok(HashMap::new());
+    }
+    let body = std::str::from_utf8(buffer.as_ref())
+        .map_err(|err| StoreError::Decoding(err.to_string()))?;
+    let items = parse_recognize::<HashMap<String, String>>(body, false)
+        .map_err(|err| StoreError::Decoding(err.to_string()))?;
+
+    let mut lanes = HashMap::new();
+    lanes.insert(
+        MANIFEST_NAME.to_string(),
+        LaneState::Value(Some(buffer.to_vec())),
+    );
+    for (name, tag) in items {
+        let kind = decode_item_kind(&tag).ok_or_else(|| {
+            StoreError::Decoding(format!("Invalid kind '{}' for item '{}'.", tag, name))
+        })?;
+        let id = node.id_for(&name)?;
+        let state = if kind.map_like() {
+            let mut entries = BTreeMap::new();
+            let mut consumer = node.read_map(id)?;
+            while let Some((key, value)) = consumer.consume_next()? {
+                entries.insert(key.to_vec(), value.to_vec());
+            }
+            LaneState::Map(entries)
+        } else {
+            buffer.clear();
+            match node.get_value(id, &mut buffer)? {
+                Some(_) => LaneState::Value(Some(buffer.to_vec())),
+                None => continue,
+            }
+        };
+        lanes.insert(name, state);
+    }
+    Ok(lanes)
+}
+
+/// The ID of a lane in a [`ReplicatedNodePersistence`] store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ReplicatedLaneId<Id> {
+    id: Id,
+    slot: usize, //Index of the name of the lane.
+}
+
+/// A [`NodePersistence`] implementation that records the mutations made to another store in a
+/// [`ReplicationLog`].
+pub(crate) struct ReplicatedNodePersistence<N> {
+    inner: N,
+    node: String,
+    log: ReplicationLog,
+    lanes: Mutex<Vec<String>>,
+}
+
+impl<N> ReplicatedNodePersistence<N> {
+    fn record(&self, slot: usize, op: Operation) {
+        let ReplicatedNodePersistence {
+            node, log, lanes, ..
+        } = self;
+        if let Some(lane) = lanes.lock().get(slot) {
+            log.record(node, lane, op);
+        }
+    }
+}
+
+impl<N: NodePersistence> NodePersistence for ReplicatedNodePersistence<N> {
+    type MapCon<'a>
+        = N::MapCon<'a>
+    where
+        Self: 'a;
+
+    type LaneId = ReplicatedLaneId<N::LaneId>;
+
+    fn id_for(&self, name: &str) -> Result<Self::LaneId, StoreError> {
+        let id = self.inner.id_for(name)?;
+        let mut guard = self.lanes.lock();
+        let slot = match guard.iter().position(|lane| lane == name) {
+            Some(slot) => slot,
+            None => {
+                guard.push(name.to_string());
+                guard.len() - 1
+            }
+        };
+        Ok(ReplicatedLaneId { id, slot })
+    }
+
+    fn get_value(
+        &self,
+        id: Self::LaneId,
+        buffer: &mut BytesMut,
+    ) -> Result<Option<usize>, StoreError> {
+        self.inner.get_value(id.id, buffer)
+    }
+
+    fn put_value(&mut self, id: Self::LaneId, value: &[u8]) -> Result<(), StoreError> {
+        self.inner.put_value(id.id, value)?;
+        self.record(
+            id.slot,
+            Operation::Put {
+                value: value.to_vec(),
+            },
+        );
+        Ok(())
+    }
+
+    fn delete_value(&mut self, id: Self::LaneId) -> Result<(), StoreError> {
+        self.inner.delete_value(id.id)?;
+        self.record(id.slot, Operation::Delete);
+        Ok(())
+    }
+
+    fn update_map(&mut self, id: Self::LaneId, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        self.inner.update_map(id.id, key, value)?;
+        self.record(
+            id.slot,
+            Operation::Update {
+                key: key.to_vec(),
+                value: value.to_vec(),
+            },
+        );
+        Ok(())
+    }
+
+    fn remove_map(&mut self, id: Self::LaneId, key: &[u8]) -> Result<(), StoreError> {
+        self.inner.remove_map(id.id, key)?;
+        self.record(id.slot, Operation::Remove { key: key.to_vec() });
+        Ok(())
+    }
+
+    fn clear_map(&mut self, id: Self::LaneId) -> Result<(), StoreError> {
+        self.inner.clear_map(id.id)?;
+        self.record(id.slot, Operation::Clear);
+        Ok(())
+    }
+
+    fn read_map(&self, id: Self::LaneId) -> Result<Self::MapCon<'_>, StoreError> {
+        self.inner.read_map(id.id)
+    }
+}
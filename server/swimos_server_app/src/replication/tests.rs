@@ -0,0 +1,519 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use bytes::BytesMut;
+use futures::{future::join, SinkExt, StreamExt};
+use swimos_agent_protocol::{
+    encoding::lane::{RawValueLaneRequestEncoder, ValueLaneResponseDecoder},
+    LaneRequest, LaneResponse,
+};
+use swimos_api::{
+    error::StoreError,
+    persistence::{NodePersistence, PlanePersistence, RangeConsumer, ServerPersistence},
+};
+use swimos_recon::print_recon_compact;
+use swimos_runtime::agent::MANIFEST_NAME;
+use swimos_utilities::{byte_channel::byte_channel, non_zero_usize};
+use tokio_util::codec::{FramedRead, FramedWrite};
+use uuid::Uuid;
+
+use crate::in_memory_store::InMemoryPlanePersistence;
+
+use super::{
+    agent::{run_task, secrets_match},
+    log::{Operation, ReplicationEvent, ReplicationLog},
+    standby::{Progress, Replica, ReplicationError},
+    store::ReplicatedPersistence,
+};
+
+const NODE: &str = "/node";
+const VALUE_LANE: &str = "value";
+const MAP_LANE: &str = "entries";
+const TIMEOUT: Duration = Duration::from_secs(5);
+const SECRET: &str = "a secret/with?symbols";
+
+struct TestStore(InMemoryPlanePersistence);
+
+impl ServerPersistence for TestStore {
+    type PlaneStore = InMemoryPlanePersistence;
+
+    fn open_plane(&self, _name: &str) -> Result<Self::PlaneStore, StoreError> {
+        Ok(self.0.clone())
+    }
+}
+
+fn make_log() -> ReplicationLog {
+    ReplicationLog::new(non_zero_usize!(64))
+}
+
+fn replicated(
+    inner: &InMemoryPlanePersistence,
+    log: &ReplicationLog,
+) -> impl PlanePersistence + Clone {
+    ReplicatedPersistence::new(TestStore(inner.clone()), log.clone())
+        .open_plane("plane")
+        .expect("Opening the plane failed.")
+}
+
+fn mutation(seq: u64, lane: &str, op: Operation) -> ReplicationEvent {
+    ReplicationEvent::Mutation {
+        seq,
+        node: NODE.to_string(),
+        lane: lane.to_string(),
+        op,
+    }
+}
+
+fn put(value: &str) -> Operation {
+    Operation::Put {
+        value: value.as_bytes().to_vec(),
+    }
+}
+
+fn update(key: &str, value: &str) -> Operation {
+    Operation::Update {
+        key: key.as_bytes().to_vec(),
+        value: value.as_bytes().to_vec(),
+    }
+}
+
+// Write some state through a store, returning the expected contents of the map lane.
+fn write_state<N: NodePersistence>(node: &mut N) -> BTreeMap<Vec<u8>, Vec<u8>> {
+    let value_id = node.id_for(VALUE_LANE).expect("No ID.");
+    let map_id = node.id_for(MAP_LANE).expect("No ID.");
+    node.put_value(value_id, b"first").expect("Put failed.");
+    node.put_value(value_id, b"second").expect("Put failed.");
+    node.update_map(map_id, b"a", b"1").expect("Update failed.");
+    node.update_map(map_id, b"b", b"2").expect("Update failed.");
+    node.remove_map(map_id, b"a").expect("Remove failed.");
+    [(b"b".to_vec(), b"2".to_vec())].into_iter().collect()
+}
+
+fn read_value<N: NodePersistence>(node: &N, lane: &str) -> Option<Vec<u8>> {
+    let id = node.id_for(lane).expect("No ID.");
+    let mut buffer = BytesMut::new();
+    node.get_value(id, &mut buffer)
+        .expect("Read failed.")
+        .map(|_| buffer.to_vec())
+}
+
+fn read_map<N: NodePersistence>(node: &N, lane: &str) -> BTreeMap<Vec<u8>, Vec<u8>> {
+    let id = node.id_for(lane).expect("No ID.");
+    let mut consumer = node.read_map(id).expect("Read failed.");
+    let mut entries = BTreeMap::new();
+    while let Some((key, value)) = consumer.consume_next().expect("Read failed.") {
+        entries.insert(key.to_vec(), value.to_vec());
+    }
+    entries
+}
+
+#[tokio::test]
+async fn mutations_are_recorded() {
+    let inner = InMemoryPlanePersistence::default();
+    let log = make_log();
+    let mut events = log.subscribe();
+
+    let mut node = replicated(&inner, &log)
+        .node_store(NODE)
+        .await
+        .expect("Opening the node failed.");
+    let expected_map = write_state(&mut node);
+    drop(node);
+
+    let mut received = vec![];
+    while let Ok(event) = events.try_recv() {
+        received.push(event.as_ref().clone());
+    }
+    assert_eq!(
+        received,
+        vec![
+            mutation(1, VALUE_LANE, put("first")),
+            mutation(2, VALUE_LANE, put("second")),
+            mutation(3, MAP_LANE, update("a", "1")),
+            mutation(4, MAP_LANE, update("b", "2")),
+            mutation(5, MAP_LANE, Operation::Remove { key: b"a".to_vec() }),
+        ]
+    );
+
+    // The mutations are also written into the underlying store.
+    let node = inner
+        .node_store(NODE)
+        .await
+        .expect("Opening the node failed.");
+    assert_eq!(read_value(&node, VALUE_LANE), Some(b"second".to_vec()));
+    assert_eq!(read_map(&node, MAP_LANE), expected_map);
+}
+
+// Write the manifest that describes the lanes of the node.
+fn write_manifest<N: NodePersistence>(node: &mut N) -> String {
+    let manifest_id = node.id_for(MANIFEST_NAME).expect("No ID.");
+    let manifest = format!("{{{}:ValueLane,{}:MapLane}}", VALUE_LANE, MAP_LANE);
+    node.put_value(manifest_id, manifest.as_bytes())
+        .expect("Put failed.");
+    manifest
+}
+
+// The command that a remote sends to the replication lane to authenticate.
+fn auth_command(id: Uuid, secret: &str) -> LaneRequest<Vec<u8>> {
+    let body = format!("{}", print_recon_compact(&secret));
+    LaneRequest::CommandFrom(id, body.into_bytes())
+}
+
+// Request a snapshot from the replication lane.
+async fn read_snapshot(
+    log: ReplicationLog,
+    plane_store: InMemoryPlanePersistence,
+) -> Vec<ReplicationEvent> {
+    let (request_tx, request_rx) = byte_channel(non_zero_usize!(4096));
+    let (response_tx, response_rx) = byte_channel(non_zero_usize!(4096));
+
+    let task = run_task(log, SECRET, plane_store, (response_tx, request_rx));
+
+    let test_case = async move {
+        let mut requests = FramedWrite::new(request_tx, RawValueLaneRequestEncoder::default());
+        let mut responses = FramedRead::new(
+            response_rx,
+            ValueLaneResponseDecoder::<ReplicationEvent>::default(),
+        );
+        let id = Uuid::from_u128(1);
+        requests
+            .send(auth_command(id, SECRET))
+            .await
+            .expect("Sending request failed.");
+        requests
+            .send(LaneRequest::<&[u8]>::Sync(id))
+            .await
+            .expect("Sending request failed.");
+        let mut snapshot = vec![];
+        loop {
+            match responses
+                .next()
+                .await
+                .expect("Agent stopped.")
+                .expect("Invalid response.")
+            {
+                LaneResponse::SyncEvent(_, event) => snapshot.push(event),
+                LaneResponse::Synced(_) => break,
+                ow => panic!("Unexpected response: {:?}", ow),
+            }
+        }
+        snapshot
+    };
+
+    let (result, snapshot) = tokio::time::timeout(TIMEOUT, join(task, test_case))
+        .await
+        .expect("Test timed out.");
+    assert!(result.is_ok());
+    snapshot
+}
+
+async fn restore(standby: &InMemoryPlanePersistence, snapshot: Vec<ReplicationEvent>) -> Progress {
+    let mut replica = Replica::new(standby.clone());
+    let mut progress = Progress::AwaitingSnapshot;
+    for event in snapshot {
+        replica
+            .handle(&mut progress, event)
+            .await
+            .expect("Applying the snapshot failed.");
+    }
+    progress
+}
+
+#[tokio::test]
+async fn snapshot_restores_state() {
+    let inner = InMemoryPlanePersistence::default();
+    let log = make_log();
+    let mut primary = replicated(&inner, &log)
+        .node_store(NODE)
+        .await
+        .expect("Opening the node failed.");
+    write_manifest(&mut primary);
+    let expected_map = write_state(&mut primary);
+    drop(primary);
+
+    let snapshot = read_snapshot(log, inner).await;
+    assert_eq!(
+        snapshot.first(),
+        Some(&ReplicationEvent::SnapshotStart { seq: 6 })
+    );
+    assert_eq!(snapshot.last(), Some(&ReplicationEvent::SnapshotEnd));
+
+    // Stale state in the standby is replaced by the snapshot.
+    let standby = InMemoryPlanePersistence::default();
+    let mut node = standby
+        .node_store(NODE)
+        .await
+        .expect("Opening the node failed.");
+    let map_id = node.id_for(MAP_LANE).expect("No ID.");
+    node.update_map(map_id, b"stale", b"0")
+        .expect("Update failed.");
+    drop(node);
+
+    let progress = restore(&standby, snapshot).await;
+    assert_eq!(progress, Progress::Following(6));
+
+    let node = standby
+        .node_store(NODE)
+        .await
+        .expect("Opening the node failed.");
+    assert_eq!(read_value(&node, VALUE_LANE), Some(b"second".to_vec()));
+    assert_eq!(read_map(&node, MAP_LANE), expected_map);
+}
+
+#[tokio::test]
+async fn snapshot_includes_unreplicated_state() {
+    // State that was written before replication was enabled is only in the store.
+    let inner = InMemoryPlanePersistence::default();
+    let mut node = inner
+        .node_store(NODE)
+        .await
+        .expect("Opening the node failed.");
+    let manifest = write_manifest(&mut node);
+    let expected_map = write_state(&mut node);
+    drop(node);
+
+    let snapshot = read_snapshot(make_log(), inner).await;
+    assert_eq!(
+        snapshot.first(),
+        Some(&ReplicationEvent::SnapshotStart { seq: 0 })
+    );
+
+    let standby = InMemoryPlanePersistence::default();
+    let progress = restore(&standby, snapshot).await;
+    assert_eq!(progress, Progress::Following(0));
+
+    let node = standby
+        .node_store(NODE)
+        .await
+        .expect("Opening the node failed.");
+    assert_eq!(
+        read_value(&node, MANIFEST_NAME),
+        Some(manifest.as_bytes().to_vec())
+    );
+    assert_eq!(read_value(&node, VALUE_LANE), Some(b"second".to_vec()));
+    assert_eq!(read_map(&node, MAP_LANE), expected_map);
+}
+
+#[test]
+fn secrets_are_compared_exactly() {
+    assert!(secrets_match(SECRET, SECRET));
+    assert!(!secrets_match("a secret", SECRET));
+    assert!(!secrets_match("a secret/with?symbolz", SECRET));
+}
+
+#[tokio::test]
+async fn replica_follows_mutations() {
+    let standby = InMemoryPlanePersistence::default();
+    let mut replica = Replica::new(standby.clone());
+    let mut progress = Progress::AwaitingSnapshot;
+
+    let events = vec![
+        // Ignored as it precedes the snapshot.
+        mutation(1, VALUE_LANE, put("ignored")),
+        ReplicationEvent::SnapshotStart { seq: 2 },
+        mutation(2, VALUE_LANE, put("snapshot")),
+        ReplicationEvent::SnapshotEnd,
+        // Ignored as it is included in the snapshot.
+        mutation(2, VALUE_LANE, put("stale")),
+        mutation(3, MAP_LANE, update("a", "1")),
+    ];
+    for event in events {
+        replica
+            .handle(&mut progress, event)
+            .await
+            .expect("Applying the event failed.");
+    }
+    assert_eq!(progress, Progress::Following(3));
+
+    let result = replica
+        .handle(&mut progress, mutation(5, VALUE_LANE, put("missed")))
+        .await;
+    assert!(matches!(
+        result,
+        Err(ReplicationError::Gap {
+            expected: 4,
+            received: 5
+        })
+    ));
+    drop(replica);
+
+    let node = standby
+        .node_store(NODE)
+        .await
+        .expect("Opening the node failed.");
+    assert_eq!(read_value(&node, VALUE_LANE), Some(b"snapshot".to_vec()));
+    assert_eq!(
+        read_map(&node, MAP_LANE),
+        [(b"a".to_vec(), b"1".to_vec())].into_iter().collect()
+    );
+}
+
+#[tokio::test]
+async fn replication_lane_syncs_then_streams() {
+    let inner = InMemoryPlanePersistence::default();
+    let log = make_log();
+    let mut node = replicated(&inner, &log)
+        .node_store(NODE)
+        .await
+        .expect("Opening the node failed.");
+    write_manifest(&mut node);
+    let value_id = node.id_for(VALUE_LANE).expect("No ID.");
+    node.put_value(value_id, b"first").expect("Put failed.");
+    drop(node);
+
+    let (request_tx, request_rx) = byte_channel(non_zero_usize!(4096));
+    let (response_tx, response_rx) = byte_channel(non_zero_usize!(4096));
+
+    let task = run_task(log.clone(), SECRET, inner, (response_tx, request_rx));
+
+    let test_case = async move {
+        let mut requests = FramedWrite::new(request_tx, RawValueLaneRequestEncoder::default());
+        let mut responses = FramedRead::new(
+            response_rx,
+            ValueLaneResponseDecoder::<ReplicationEvent>::default(),
+        );
+
+        // The sync is held until the remote authenticates.
+        let id = Uuid::from_u128(1);
+        requests
+            .send(LaneRequest::<&[u8]>::Sync(id))
+            .await
+            .expect("Sending request failed.");
+        requests
+            .send(auth_command(id, SECRET))
+            .await
+            .expect("Sending request failed.");
+
+        let mut received = vec![];
+        loop {
+            match responses
+                .next()
+                .await
+                .expect("Agent stopped.")
+                .expect("Invalid response.")
+            {
+                LaneResponse::SyncEvent(sync_id, event) => {
+                    assert_eq!(sync_id, id);
+                    received.push(event);
+                }
+                LaneResponse::Synced(sync_id) => {
+                    assert_eq!(sync_id, id);
+                    break;
+                }
+                ow => panic!("Unexpected response: {:?}", ow),
+            }
+        }
+        assert_eq!(
+            received.first(),
+            Some(&ReplicationEvent::SnapshotStart { seq: 2 })
+        );
+        assert_eq!(received.last(), Some(&ReplicationEvent::SnapshotEnd));
+        assert!(received.contains(&mutation(2, VALUE_LANE, put("first"))));
+
+        log.record(NODE, VALUE_LANE, put("second"));
+        let event = match responses
+            .next()
+            .await
+            .expect("Agent stopped.")
+            .expect("Invalid response.")
+        {
+            LaneResponse::SyncEvent(target, event) => {
+                assert_eq!(target, id);
+                event
+            }
+            ow => panic!("Unexpected response: {:?}", ow),
+        };
+        assert_eq!(event, mutation(3, VALUE_LANE, put("second")));
+    };
+
+    let (result, _) = tokio::time::timeout(TIMEOUT, join(task, test_case))
+        .await
+        .expect("Test timed out.");
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn replication_lane_ignores_unauthenticated_remotes() {
+    let inner = InMemoryPlanePersistence::default();
+    let log = make_log();
+
+    let (request_tx, request_rx) = byte_channel(non_zero_usize!(4096));
+    let (response_tx, response_rx) = byte_channel(non_zero_usize!(4096));
+
+    let task = run_task(log.clone(), SECRET, inner, (response_tx, request_rx));
+
+    let test_case = async move {
+        let mut requests = FramedWrite::new(request_tx, RawValueLaneRequestEncoder::default());
+        let mut responses = FramedRead::new(
+            response_rx,
+            ValueLaneResponseDecoder::<ReplicationEvent>::default(),
+        );
+
+        let intruder = Uuid::from_u128(1);
+        let standby = Uuid::from_u128(2);
+        requests
+            .send(LaneRequest::<&[u8]>::Sync(intruder))
+            .await
+            .expect("Sending request failed.");
+        requests
+            .send(auth_command(intruder, "a secret"))
+            .await
+            .expect("Sending request failed.");
+        requests
+            .send(auth_command(standby, SECRET))
+            .await
+            .expect("Sending request failed.");
+        requests
+            .send(LaneRequest::<&[u8]>::Sync(standby))
+            .await
+            .expect("Sending request failed.");
+
+        // Only the standby receives the snapshot and the mutations.
+        loop {
+            match responses
+                .next()
+                .await
+                .expect("Agent stopped.")
+                .expect("Invalid response.")
+            {
+                LaneResponse::SyncEvent(target, _) => assert_eq!(target, standby),
+                LaneResponse::Synced(target) => {
+                    assert_eq!(target, standby);
+                    break;
+                }
+                ow => panic!("Unexpected response: {:?}", ow),
+            }
+        }
+        log.record(NODE, VALUE_LANE, put("value"));
+        match responses
+            .next()
+            .await
+            .expect("Agent stopped.")
+            .expect("Invalid response.")
+        {
+            LaneResponse::SyncEvent(target, event) => {
+                assert_eq!(target, standby);
+                assert_eq!(event, mutation(1, VALUE_LANE, put("value")));
+            }
+            ow => panic!("Unexpected response: {:?}", ow),
+        }
+    };
+
+    let (result, _) = tokio::time::timeout(TIMEOUT, join(task, test_case))
+        .await
+        .expect("Test timed out.");
+    assert!(result.is_ok());
+}
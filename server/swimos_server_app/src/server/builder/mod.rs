@@ -17,10 +17,7 @@ use std::{
     sync::Arc,
 };
 
-use ratchet::{
-    deflate::{DeflateConfig, DeflateExtProvider},
-    NoExtProvider, WebSocketStream,
-};
+use ratchet::{deflate::DeflateConfig, NoExtProvider, WebSocketStream};
 use rustls::crypto::CryptoProvider;
 
 use swimos_api::{
@@ -29,18 +26,27 @@ use swimos_api::{
     persistence::{ServerPersistence, StoreDisabled},
 };
 use swimos_remote::dns::Resolver;
+use swimos_remote::local::InProcessNetworking;
+#[cfg(unix)]
+use swimos_remote::local::UnixNetworking;
 use swimos_remote::plain::TokioPlainTextNetworking;
 use swimos_remote::tls::{
     ClientConfig, CryptoProviderConfig, RustlsClientNetworking, RustlsNetworking,
     RustlsServerNetworking, TlsConfig,
 };
+use swimos_remote::websocket::DeflateSettings;
 use swimos_remote::ExternalConnections;
+use swimos_runtime::agent::{intercept::OutgoingInterceptor, prune::PrunePolicy};
 use swimos_utilities::routing::RoutePattern;
 
 use crate::{
+    cluster::Cluster,
     config::SwimServerConfig,
+    egress::EgressBridge,
     error::ServerBuilderError,
-    plane::{PlaneBuilder, PlaneModel},
+    plane::{AgentStartPolicy, PlaneBuilder, PlaneModel, PlanePeer},
+    replication::{ReplicatedPersistence, ReplicationConfig},
+    store_compression::{CompressedPersistence, StoreCompressionConfig},
     IntrospectionConfig,
 };
 
@@ -56,11 +62,24 @@ pub struct ServerBuilder {
     bind_to: SocketAddr,
     plane: PlaneBuilder,
     tls_config: Option<TlsConfig>,
-    deflate: Option<DeflateConfig>,
+    deflate: Option<DeflateSettings>,
     config: SwimServerConfig,
     store_options: StoreConfig,
+    compression: Option<StoreCompressionConfig>,
     introspection: Option<IntrospectionConfig>,
     crypto_provider: CryptoProviderConfig,
+    transport: ServerTransport,
+}
+
+/// The transport over which the server accepts connections (and opens downlinks to other
+/// servers).
+#[derive(Default)]
+enum ServerTransport {
+    #[default]
+    Tcp,
+    InProcess(InProcessNetworking),
+    #[cfg(unix)]
+    Unix(UnixNetworking),
 }
 
 #[non_exhaustive]
@@ -87,8 +106,10 @@ impl ServerBuilder {
             deflate: Default::default(),
             config: Default::default(),
             store_options: Default::default(),
+            compression: None,
             introspection: Default::default(),
             crypto_provider: CryptoProviderConfig::default(),
+            transport: Default::default(),
         }
     }
 
@@ -111,6 +132,112 @@ impl ServerBuilder {
         self
     }
 
+    /// Add a new route to the plane that the server will run, specifying when its agents are
+    /// started. With [`AgentStartPolicy::Explicit`], the agents must be started with
+    /// [`crate::ServerHandle::start_agent`] and envelopes addressed to agents that are not running
+    /// will be rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The route pattern against which to match incoming envelopes.
+    /// * `agent` - The agent definition.
+    /// * `policy` - Whether agents are started by incoming envelopes or only explicitly.
+    pub fn add_route_with_policy<A: Agent + Send + 'static>(
+        mut self,
+        pattern: RoutePattern,
+        agent: A,
+        policy: AgentStartPolicy,
+    ) -> Self {
+        self.plane.add_route_with_policy(pattern, agent, policy);
+        self
+    }
+
+    /// Add a peer server to the plane. Envelopes addressed to nodes that do not match any of the
+    /// routes of this server, but do match a route of the peer, will be proxied to the peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The host and routes of the peer.
+    pub fn add_peer(mut self, peer: PlanePeer) -> Self {
+        self.plane.add_peer(peer);
+        self
+    }
+
+    /// Partition the nodes of the plane between the members of a cluster. Envelopes addressed to
+    /// nodes that are owned by another member will be proxied to that member. The partitions are
+    /// described by the lanes of a meta-agent at `swimos:meta:partitions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster` - The members of the cluster.
+    pub fn with_cluster(mut self, cluster: Cluster) -> Self {
+        self.plane.set_cluster(cluster);
+        self
+    }
+
+    /// Run the server as one of a primary/standby pair. The persisted state of the agents of a
+    /// primary is streamed, by a meta-agent at `swimos:meta:replication`, to its standby which will
+    /// only run the agents of the plane after it has been promoted (see [`ReplicationConfig`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The role of the server and the replication parameters.
+    pub fn with_replication(mut self, config: ReplicationConfig) -> Self {
+        self.plane.set_replication(config);
+        self
+    }
+
+    /// Run a meta-agent, at `swimos:meta:flags`, that holds the feature flags of the plane in a map
+    /// lane called `flags` (from the name of each flag to its selected variant). Operators can change
+    /// the flags, while the server is running, by sending commands to the lane and agents can read
+    /// them with `HandlerContext::feature_flag`.
+    pub fn enable_feature_flags(mut self) -> Self {
+        self.plane.enable_feature_flags();
+        self
+    }
+
+    /// Push the events of lanes to an external sink (for example, an HTTP webhook, a Kafka producer
+    /// or a database). The bridge links to the matching lanes of each agent as it is started, so
+    /// exporters do not need to be run as separate client processes.
+    ///
+    /// # Arguments
+    ///
+    /// * `bridge` - The lanes to subscribe to, the sink and the retry policy for failed deliveries.
+    pub fn add_egress_bridge(mut self, bridge: EgressBridge) -> Self {
+        self.plane.add_egress_bridge(bridge);
+        self
+    }
+
+    /// Transform the bodies of the events sent by the lanes of all agents before they are written
+    /// to remotes (for example, to redact fields for some remotes or to add fields to the events of
+    /// a lane).
+    ///
+    /// # Arguments
+    ///
+    /// * `interceptor` - The interceptor to apply to the events.
+    pub fn with_outgoing_interceptor<I>(mut self, interceptor: I) -> Self
+    where
+        I: OutgoingInterceptor + 'static,
+    {
+        self.plane.set_interceptor(Arc::new(interceptor));
+        self
+    }
+
+    /// Customize when remotes with no links to the lanes of an agent are pruned from it (for
+    /// example, to retain remotes that have recently attempted to link). By default, idle remotes
+    /// are pruned as soon as the prune delay of the agent runtime configuration has elapsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The policy to apply to idle remotes.
+    pub fn with_prune_policy<P>(mut self, policy: P) -> Self
+    where
+        P: PrunePolicy + 'static,
+    {
+        self.plane.set_prune_policy(Arc::new(policy));
+        self
+    }
+
     /// Enable TLS on the server.
     pub fn add_tls_support(mut self, config: TlsConfig) -> Self {
         self.tls_config = Some(config);
@@ -127,11 +254,32 @@ impl ServerBuilder {
     /// # Arguments
     /// * `config` - Configuration parameters for the compression.
     pub fn configure_deflate_support(mut self, config: DeflateConfig) -> Self {
-        self.deflate = Some(config);
+        self.deflate = Some(config.into());
+        self
+    }
+
+    /// Enable the deflate extension for websocket connections, specifying the size of the
+    /// compression window, the compression level and a threshold below which messages will not
+    /// be compressed (see [`DeflateSettings::new`]).
+    ///
+    /// # Arguments
+    /// * `settings` - Configuration parameters for the compression.
+    pub fn configure_compression(mut self, settings: DeflateSettings) -> Self {
+        self.deflate = Some(settings);
         self
     }
 
-    /// Alter the server configuration parameters.
+    /// Replace the server configuration parameters.
+    ///
+    /// # Arguments
+    /// * `config` - The configuration (see [`SwimServerConfig::builder`]).
+    pub fn with_config(mut self, config: SwimServerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Alter the server configuration parameters. The altered configuration will be checked
+    /// when the server is built.
     ///
     /// # Arguments
     /// * `f` - A function that can mutate the configuration.
@@ -164,6 +312,15 @@ impl ServerBuilder {
         self
     }
 
+    /// Compress the values that agents write into the persistence store (this has no effect if no
+    /// store is enabled).
+    /// # Arguments
+    /// * `config` - Determines how the values for each lane are compressed.
+    pub fn with_store_compression(mut self, config: StoreCompressionConfig) -> Self {
+        self.compression = Some(config);
+        self
+    }
+
     /// Uses the process-default [`CryptoProvider`] for any TLS connections.
     pub fn with_default_crypto_provider(mut self) -> Self {
         self.crypto_provider = CryptoProviderConfig::ProcessDefault;
@@ -176,8 +333,26 @@ impl ServerBuilder {
         self
     }
 
+    /// Serve connections over an in-process transport rather than TCP. Clients in the same
+    /// process can connect to the server using a clone of the same transport, bypassing the
+    /// network stack. Downlinks opened by the agents of the server will also use the transport.
+    /// TLS is not supported.
+    pub fn with_in_process_transport(mut self, networking: InProcessNetworking) -> Self {
+        self.transport = ServerTransport::InProcess(networking);
+        self
+    }
+
+    /// Serve connections over Unix domain sockets rather than TCP, for clients on the same host.
+    /// Downlinks opened by the agents of the server will also use the transport. TLS is not
+    /// supported.
+    #[cfg(unix)]
+    pub fn with_unix_sockets(mut self, networking: UnixNetworking) -> Self {
+        self.transport = ServerTransport::Unix(networking);
+        self
+    }
+
     /// Attempt to make a server instance. This will fail if the routes specified for the
-    /// agents are ambiguous.
+    /// agents are ambiguous or if any of the configuration parameters are out of range.
     pub async fn build(self) -> Result<BoxServer, ServerBuilderError> {
         let ServerBuilder {
             bind_to,
@@ -186,20 +361,47 @@ impl ServerBuilder {
             deflate,
             config,
             store_options,
+            compression,
             introspection,
             crypto_provider,
+            transport,
         } = self;
+        config.validate()?;
         let routes = plane.build()?;
         if introspection.is_some() {
             routes.check_meta_collisions()?;
         }
+        if routes.cluster.is_some() {
+            routes.check_partition_collisions()?;
+        }
+        if routes.feature_flags {
+            routes.check_feature_flag_collisions()?;
+        }
+        if routes.replication.is_some() {
+            routes.check_replication_collisions()?;
+        }
         let resolver = Arc::new(Resolver::new().await);
         let config = AppConfig {
             server: config,
             store: store_options,
+            compression,
             deflate,
             introspection,
         };
+        if tls_config.is_some() && !matches!(transport, ServerTransport::Tcp) {
+            return Err(ServerBuilderError::LocalTransportTls);
+        }
+        match transport {
+            ServerTransport::Tcp => {}
+            ServerTransport::InProcess(networking) => {
+                return Ok(with_store(bind_to, routes, networking, config).await?);
+            }
+            #[cfg(unix)]
+            ServerTransport::Unix(networking) => {
+                return Ok(with_store(bind_to, routes, networking, config).await?);
+            }
+        }
+
         let crypto_provider = crypto_provider.try_build()?;
 
         if let Some(tls_conf) = tls_config {
@@ -207,7 +409,7 @@ impl ServerBuilder {
                 RustlsClientNetworking::build(resolver, tls_conf.client, crypto_provider.clone())?;
             let server = RustlsServerNetworking::build(tls_conf.server, crypto_provider)?;
             let networking = RustlsNetworking::new_tls(client, server);
-            Ok(with_store(bind_to, routes, networking, config)?)
+            Ok(with_store(bind_to, routes, networking, config).await?)
         } else {
             let client = RustlsClientNetworking::build(
                 resolver.clone(),
@@ -216,7 +418,7 @@ impl ServerBuilder {
             )?;
             let server = TokioPlainTextNetworking::new(resolver);
             let networking = RustlsNetworking::new_plain_text(client, server);
-            Ok(with_store(bind_to, routes, networking, config)?)
+            Ok(with_store(bind_to, routes, networking, config).await?)
         }
     }
 }
@@ -224,11 +426,12 @@ impl ServerBuilder {
 struct AppConfig {
     server: SwimServerConfig,
     store: StoreConfig,
-    deflate: Option<DeflateConfig>,
+    compression: Option<StoreCompressionConfig>,
+    deflate: Option<DeflateSettings>,
     introspection: Option<IntrospectionConfig>,
 }
 
-fn with_store<N>(
+async fn with_store<N>(
     bind_to: SocketAddr,
     routes: PlaneModel,
     networking: N,
@@ -242,8 +445,15 @@ where
     match store_config {
         #[cfg(feature = "rocks_store")]
         StoreConfig::RockStore { path, options } => {
-            let store = swimos_rocks_store::open_rocks_store(path, options)?;
-            Ok(with_websockets(bind_to, routes, networking, config, store))
+            let policy = config.server.store_startup;
+            let store = super::store::open_store(policy, || {
+                swimos_rocks_store::open_rocks_store(path.clone(), options.clone())
+            })
+            .await?;
+            Ok(match store {
+                Some(store) => with_websockets(bind_to, routes, networking, config, store),
+                None => with_websockets(bind_to, routes, networking, config, StoreDisabled),
+            })
         }
         StoreConfig::InMemory => Ok(with_websockets(
             bind_to,
@@ -263,6 +473,48 @@ where
 }
 
 fn with_websockets<N, Store>(
+    bind_to: SocketAddr,
+    routes: PlaneModel,
+    networking: N,
+    mut config: AppConfig,
+    store: Store,
+) -> BoxServer
+where
+    N: ExternalConnections,
+    N::Socket: WebSocketStream,
+    Store: ServerPersistence + Send + Sync + 'static,
+{
+    if let Some(compression) = config.compression.take() {
+        let store = CompressedPersistence::new(store, compression);
+        with_replication(bind_to, routes, networking, config, store)
+    } else {
+        with_replication(bind_to, routes, networking, config, store)
+    }
+}
+
+fn with_replication<N, Store>(
+    bind_to: SocketAddr,
+    routes: PlaneModel,
+    networking: N,
+    config: AppConfig,
+    store: Store,
+) -> BoxServer
+where
+    N: ExternalConnections,
+    N::Socket: WebSocketStream,
+    Store: ServerPersistence + Send + Sync + 'static,
+{
+    // The mutations are recorded before they are compressed so that the standby can apply its own
+    // compression settings.
+    if let Some(log) = routes.replication.as_ref().map(|config| config.log.clone()) {
+        let store = ReplicatedPersistence::new(store, log);
+        with_transport(bind_to, routes, networking, config, store)
+    } else {
+        with_transport(bind_to, routes, networking, config, store)
+    }
+}
+
+fn with_transport<N, Store>(
     bind_to: SocketAddr,
     routes: PlaneModel,
     networking: N,
@@ -280,9 +532,9 @@ where
         introspection,
         ..
     } = config;
-    if let Some(deflate_config) = deflate {
+    if let Some(deflate_settings) = deflate {
         let websockets = HyperWebsockets::new(server_config.http);
-        let ext_provider = DeflateExtProvider::with_config(deflate_config);
+        let ext_provider = deflate_settings.provider();
         BoxServer(Box::new(SwimServer::new(
             routes,
             bind_to,
@@ -12,9 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use swimos_api::error::DownlinkRuntimeError;
 use swimos_utilities::routing::RouteUri;
 use thiserror::Error;
 
+use crate::error::AmbiguousRoutes;
+
 #[derive(Debug, Error)]
 pub enum UnresolvableRoute {
     #[error("No agent at route: {uri}")]
@@ -28,3 +31,46 @@ impl UnresolvableRoute {
         UnresolvableRoute::NoRoute { uri }
     }
 }
+
+/// Errors that can occur when attempting to stop an agent through a [`crate::ServerHandle`].
+#[derive(Debug, Error)]
+pub enum StopAgentError {
+    #[error("No agent is running at route: {uri}")]
+    NotRunning { uri: RouteUri },
+    #[error("Server is stopped or stopping.")]
+    Stopped,
+}
+
+/// The server is stopped or stopping and so cannot satisfy a request from a [`crate::ServerHandle`].
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[error("Server is stopped or stopping.")]
+pub struct ServerStopped;
+
+/// Errors that can occur when attempting to observe the value of a lane through a [`crate::ServerHandle`].
+#[derive(Debug, Error)]
+pub enum WatchLaneError {
+    #[error("Failed to link to the lane: {0}")]
+    Link(#[from] DownlinkRuntimeError),
+    #[error("Server is stopped or stopping.")]
+    Stopped,
+}
+
+/// Errors that can occur when attempting to add an agent route to a running server through a
+/// [`crate::ServerHandle`].
+#[derive(Debug, Error)]
+pub enum AddRouteError {
+    #[error("The route is ambiguous: {0}")]
+    Ambiguous(#[from] AmbiguousRoutes),
+    #[error("Server is stopped or stopping.")]
+    Stopped,
+}
+
+/// Errors that can occur when attempting to promote a standby server through a
+/// [`crate::ServerHandle`].
+#[derive(Debug, Error)]
+pub enum PromoteError {
+    #[error("The server is not a standby or has already been promoted.")]
+    NotStandby,
+    #[error("Server is stopped or stopping.")]
+    Stopped,
+}
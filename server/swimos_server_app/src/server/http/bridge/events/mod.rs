@@ -0,0 +1,224 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Streams of the events of lanes as [server-sent events](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+//!
+//! A `GET` request to `/stream/<node>/<lane>` opens an uplink to the lane, for the client, and
+//! streams its events until the client disconnects or the lane is unlinked. The stream consists of:
+//!
+//! - The events of the lane, each with an ID. The stream starts with the events of a sync so the
+//!   client always receives the complete state of the lane.
+//! - An event of type `synced` when the sync has completed.
+//! - An event of type `unlinked` if the lane is unlinked, after which the stream ends.
+//! - A heartbeat comment whenever the stream has been idle for the heartbeat interval.
+//!
+//! The data of the events is Recon, unless the request has the query parameter `format=json`.
+//! Lanes do not retain their history so, when a client reconnects with a `Last-Event-ID` header,
+//! the stream resumes with a new sync of the lane. The IDs of the events continue from the last ID
+//! so that they always increase for the client.
+
+use std::{pin::Pin, time::Duration};
+
+use bytes::Bytes;
+use futures::{stream::unfold, Stream, StreamExt};
+use http_body_util::{BodyExt, StreamBody};
+use hyper::{
+    body::{Frame, Incoming},
+    header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE},
+    Method, Request, Response, StatusCode,
+};
+use swimos_messages::{
+    protocol::{Notification, ResponseMessage},
+    remote_protocol::FindNode,
+};
+use swimos_utilities::byte_channel::ByteWriter;
+use tokio::{
+    sync::mpsc,
+    time::{sleep, Instant, Sleep},
+};
+use tracing::{debug, warn};
+
+use super::{
+    super::{bad_request, req_timeout},
+    boxed_response, link_and_sync, method_not_allowed, parse_lane_path, BodyFormat, ResponseBody,
+    Responses, STREAM_PREFIX,
+};
+
+#[cfg(test)]
+mod tests;
+
+const LAST_EVENT_ID: &str = "last-event-id";
+const EVENT_STREAM: &str = "text/event-stream";
+const HEARTBEAT: &[u8] = b": heartbeat\n\n";
+const SYNCED_EVENT: &str = "synced";
+const UNLINKED_EVENT: &str = "unlinked";
+
+/// Serve a request for an event stream.
+///
+/// # Arguments
+/// * `find` - Channel for resolving agents.
+/// * `request` - The HTTP request.
+/// * `timeout` - Timeout for the lane to respond to the link request.
+/// * `heartbeat` - The interval after which a heartbeat is sent on an idle stream.
+pub async fn serve_stream(
+    find: &mpsc::Sender<FindNode>,
+    request: Request<Incoming>,
+    timeout: Duration,
+    heartbeat: Duration,
+) -> Response<ResponseBody> {
+    if request.method() != Method::GET {
+        return boxed_response(method_not_allowed("GET"));
+    }
+    let address = match parse_lane_path(STREAM_PREFIX, request.uri().path()) {
+        Ok(address) => address,
+        Err(msg) => return boxed_response(bad_request(msg)),
+    };
+    let format = match stream_format(request.uri().query()) {
+        Ok(format) => format,
+        Err(msg) => return boxed_response(bad_request(msg)),
+    };
+    let next_id = request
+        .headers()
+        .get(LAST_EVENT_ID)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|id| id.trim().parse::<u64>().ok())
+        .map(|id| id.saturating_add(1))
+        .unwrap_or_default();
+
+    let (writer, responses) =
+        match tokio::time::timeout(timeout, link_and_sync(find, &address)).await {
+            Ok(Ok(uplink)) => uplink,
+            Ok(Err(response)) => return boxed_response(response),
+            Err(_) => return boxed_response(req_timeout()),
+        };
+    debug!(node = %address.node, lane = %address.lane, next_id, "Opened an event stream for a lane.");
+
+    let state = StreamState {
+        _writer: writer,
+        responses,
+        format,
+        next_id,
+        heartbeat,
+        idle: Box::pin(sleep(heartbeat)),
+        done: false,
+    };
+    let body = StreamBody::new(event_stream(state).map(|bytes| Ok(Frame::data(bytes))));
+    let mut response = Response::new(body.boxed_unsync());
+    *response.status_mut() = StatusCode::OK;
+    let headers = response.headers_mut();
+    headers.append(CONTENT_TYPE, HeaderValue::from_static(EVENT_STREAM));
+    headers.append(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    response
+}
+
+/// Select the format of the data of the events from the query of the request.
+fn stream_format(query: Option<&str>) -> Result<BodyFormat, String> {
+    let format = query
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .filter_map(|param| param.strip_prefix("format="))
+        .next_back();
+    match format {
+        None | Some("recon") => Ok(BodyFormat::Recon),
+        Some("json") => Ok(BodyFormat::Json),
+        Some(other) => Err(format!(
+            "Unsupported event format '{}'. Expected 'recon' or 'json'.",
+            other
+        )),
+    }
+}
+
+/// Encode an event in the server-sent events format.
+fn encode_event(id: Option<u64>, event: Option<&str>, data: &str) -> Bytes {
+    let mut encoded = String::new();
+    if let Some(id) = id {
+        encoded.push_str(&format!("id: {}\n", id));
+    }
+    if let Some(event) = event {
+        encoded.push_str(&format!("event: {}\n", event));
+    }
+    for line in data.split('\n') {
+        encoded.push_str("data: ");
+        encoded.push_str(line.strip_suffix('\r').unwrap_or(line));
+        encoded.push('\n');
+    }
+    encoded.push('\n');
+    Bytes::from(encoded)
+}
+
+struct StreamState {
+    // The uplink is held open until the stream ends.
+    _writer: ByteWriter,
+    responses: Responses,
+    format: BodyFormat,
+    next_id: u64,
+    heartbeat: Duration,
+    idle: Pin<Box<Sleep>>,
+    done: bool,
+}
+
+fn event_stream(state: StreamState) -> impl Stream<Item = Bytes> + Send + 'static {
+    unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+        let StreamState {
+            responses,
+            format,
+            next_id,
+            heartbeat,
+            idle,
+            done,
+            ..
+        } = &mut state;
+        let frame = loop {
+            tokio::select! {
+                biased;
+                maybe_response = responses.next() => {
+                    let Some(Ok(ResponseMessage { envelope, .. })) = maybe_response else {
+                        return None;
+                    };
+                    match envelope {
+                        Notification::Event(body) => match BodyFormat::Recon.parse(body.as_ref()) {
+                            Ok(value) => {
+                                let data = format.render(&value);
+                                let id = *next_id;
+                                *next_id += 1;
+                                break encode_event(
+                                    Some(id),
+                                    None,
+                                    &String::from_utf8_lossy(data.as_ref()),
+                                );
+                            }
+                            Err(msg) => {
+                                warn!(error = %msg, "A lane produced an invalid event.");
+                            }
+                        },
+                        Notification::Synced | Notification::SyncedWith(_) => {
+                            break encode_event(None, Some(SYNCED_EVENT), "");
+                        }
+                        Notification::Unlinked(_) => {
+                            *done = true;
+                            break encode_event(None, Some(UNLINKED_EVENT), "");
+                        }
+                        Notification::Linked | Notification::Advisory(_) => {}
+                    }
+                }
+                _ = idle.as_mut() => break Bytes::from_static(HEARTBEAT),
+            }
+        };
+        idle.as_mut().reset(Instant::now() + *heartbeat);
+        Some((frame, state))
+    })
+}
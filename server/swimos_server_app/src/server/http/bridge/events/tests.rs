@@ -0,0 +1,40 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{encode_event, stream_format, BodyFormat};
+
+#[test]
+fn select_stream_format() {
+    assert_eq!(stream_format(None), Ok(BodyFormat::Recon));
+    assert_eq!(stream_format(Some("other=1")), Ok(BodyFormat::Recon));
+    assert_eq!(stream_format(Some("format=recon")), Ok(BodyFormat::Recon));
+    assert_eq!(stream_format(Some("a=b&format=json")), Ok(BodyFormat::Json));
+    assert!(stream_format(Some("format=xml")).is_err());
+}
+
+#[test]
+fn encode_events() {
+    assert_eq!(
+        encode_event(Some(3), None, "{a:1}").as_ref(),
+        b"id: 3\ndata: {a:1}\n\n"
+    );
+    assert_eq!(
+        encode_event(None, Some("synced"), "").as_ref(),
+        b"event: synced\ndata: \n\n"
+    );
+    assert_eq!(
+        encode_event(Some(0), None, "first\r\nsecond").as_ref(),
+        b"id: 0\ndata: first\ndata: second\n\n"
+    );
+}
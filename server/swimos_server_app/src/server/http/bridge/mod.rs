@@ -0,0 +1,449 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{str::FromStr, time::Duration};
+
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::Incoming,
+    header::{HeaderValue, ACCEPT, ALLOW, CONTENT_LENGTH, CONTENT_TYPE},
+    HeaderMap, Method, Request, Response, StatusCode,
+};
+use mime::Mime;
+use swimos_api::address::RelativeAddress;
+use swimos_messages::{
+    protocol::{
+        Notification, RawRequestMessage, RawRequestMessageEncoder, RawResponseMessageDecoder,
+        RequestMessage, ResponseMessage,
+    },
+    remote_protocol::{
+        AgentHostedByPeer, AgentNotStarted, AgentResolutionError, FindNode, NoSuchAgent,
+        NodeConnectionRequest,
+    },
+};
+use swimos_model::{Text, Value};
+use swimos_recon::{
+    json::{parse_json_as, print_json},
+    parser::parse_recognize,
+    print_recon_compact,
+};
+use swimos_utilities::byte_channel::{ByteReader, ByteWriter};
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::codec::{FramedRead, FramedWrite};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::{config::HttpConfig, server::runtime::ids::next_bridge_id};
+
+use super::{
+    bad_request, boxed_response, error, not_found, req_timeout, unavailable, ResponseBody,
+};
+
+mod events;
+#[cfg(test)]
+mod tests;
+
+type Responses = FramedRead<ByteReader, RawResponseMessageDecoder>;
+
+const BRIDGE_PREFIX: &str = "/warp";
+const STREAM_PREFIX: &str = "/stream";
+const RECON_SUBTYPE: &str = "x-recon";
+const LANE_NOT_FOUND_BODY: &[u8] = b"@laneNotFound";
+
+/// Provides access to the lanes of agents with plain HTTP requests, bypassing the websocket
+/// protocol. A request to `/warp/<node>/<lane>` is routed to the lane `<lane>` of the agent
+/// at the node URI `/<node>` (the lane name is the final segment of the path).
+///
+/// - A `GET` request performs a one-shot sync with the lane and returns its state. If the sync
+///   consists of a single event (as it will for a value lane), the body of the response is that
+///   event. Otherwise, the body is a record containing all of the events.
+/// - A `POST` request sends its body to the lane as a command.
+///
+/// Bodies are Recon (`application/x-recon`) or JSON (`application/json`), selected with the
+/// `Content-Type` and `Accept` headers. Recon is used when the headers are absent.
+///
+/// Additionally, a `GET` request to `/stream/<node>/<lane>` opens a stream of the events of the
+/// lane as server-sent events.
+#[derive(Debug, Clone)]
+pub struct WarpBridge {
+    find: mpsc::Sender<FindNode>,
+    timeout: Duration,
+    warp_requests: bool,
+    event_streams: Option<Duration>,
+}
+
+impl WarpBridge {
+    /// Create a bridge if either WARP requests or event streams are enabled in the configuration.
+    ///
+    /// # Arguments
+    /// * `find` - Channel for resolving agents.
+    /// * `config` - Configuration for the HTTP server.
+    pub fn new(find: mpsc::Sender<FindNode>, config: &HttpConfig) -> Option<Self> {
+        let HttpConfig {
+            http_request_timeout,
+            warp_bridge,
+            event_streams,
+            event_stream_heartbeat,
+            ..
+        } = config;
+        if *warp_bridge || *event_streams {
+            Some(WarpBridge {
+                find,
+                timeout: *http_request_timeout,
+                warp_requests: *warp_bridge,
+                event_streams: event_streams.then_some(*event_stream_heartbeat),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Determine whether a request should be handled by the bridge.
+    pub fn handles(&self, request: &Request<Incoming>) -> bool {
+        let path = request.uri().path();
+        (self.warp_requests && has_prefix(path, BRIDGE_PREFIX))
+            || (self.event_streams.is_some() && has_prefix(path, STREAM_PREFIX))
+    }
+
+    /// Serve a request to the bridge.
+    pub async fn serve(self, request: Request<Incoming>) -> Response<ResponseBody> {
+        let WarpBridge {
+            find,
+            timeout,
+            event_streams,
+            ..
+        } = self;
+        let path = request.uri().path();
+        match event_streams {
+            Some(heartbeat) if has_prefix(path, STREAM_PREFIX) => {
+                events::serve_stream(&find, request, timeout, heartbeat).await
+            }
+            _ => boxed_response(serve_warp(&find, request, timeout).await),
+        }
+    }
+}
+
+async fn serve_warp(
+    find: &mpsc::Sender<FindNode>,
+    request: Request<Incoming>,
+    timeout: Duration,
+) -> Response<Full<Bytes>> {
+    let address = match parse_lane_path(BRIDGE_PREFIX, request.uri().path()) {
+        Ok(address) => address,
+        Err(msg) => return bad_request(msg),
+    };
+    match *request.method() {
+        Method::GET => {
+            let Some(format) = BodyFormat::negotiate(request.headers()) else {
+                return response(
+                    StatusCode::NOT_ACCEPTABLE,
+                    Bytes::from_static(b"Lanes can only be read as Recon or JSON."),
+                );
+            };
+            with_timeout(timeout, sync_lane(find, address, format)).await
+        }
+        Method::POST => {
+            let Some(format) = BodyFormat::for_content(request.headers()) else {
+                return response(
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    Bytes::from_static(b"Commands must be Recon or JSON."),
+                );
+            };
+            let body = match request.into_body().collect().await {
+                Ok(body) => body.to_bytes(),
+                Err(err) => return bad_request(err.to_string()),
+            };
+            let command = match format.parse(body.as_ref()) {
+                Ok(value) => value,
+                Err(msg) => return bad_request(msg),
+            };
+            with_timeout(timeout, send_command(find, address, command)).await
+        }
+        _ => method_not_allowed("GET, POST"),
+    }
+}
+
+fn has_prefix(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Extract the address of a lane from a path of the form `<prefix>/<node>/<lane>`. The node URI
+/// and the lane name are percent decoded after the path is split.
+fn parse_lane_path(prefix: &str, path: &str) -> Result<RelativeAddress<Text>, String> {
+    let invalid = || {
+        format!(
+            "'{}' does not identify a lane. Expected '{}/<node>/<lane>'.",
+            path, prefix
+        )
+    };
+    let rest = path.strip_prefix(prefix).ok_or_else(invalid)?;
+    let (node, lane) = rest.rsplit_once('/').ok_or_else(invalid)?;
+    if node.len() < 2 || lane.is_empty() {
+        return Err(invalid());
+    }
+    let decode = |s: &str| Text::new(&percent_encoding::percent_decode_str(s).decode_utf8_lossy());
+    Ok(RelativeAddress::new(decode(node), decode(lane)))
+}
+
+/// The formats supported for the bodies of requests and responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BodyFormat {
+    Recon,
+    Json,
+}
+
+impl BodyFormat {
+    fn content_type(&self) -> HeaderValue {
+        match self {
+            BodyFormat::Recon => HeaderValue::from_static("application/x-recon"),
+            BodyFormat::Json => HeaderValue::from_static("application/json"),
+        }
+    }
+
+    fn for_mime(mime: &Mime) -> Option<Self> {
+        match (mime.type_(), mime.subtype()) {
+            (mime::APPLICATION, mime::JSON) => Some(BodyFormat::Json),
+            (mime::APPLICATION, sub) if sub == RECON_SUBTYPE => Some(BodyFormat::Recon),
+            (mime::APPLICATION, mime::STAR) | (mime::STAR, mime::STAR) => Some(BodyFormat::Recon),
+            _ => None,
+        }
+    }
+
+    /// Select the format of the body of a request from its `Content-Type` header.
+    fn for_content(headers: &HeaderMap) -> Option<Self> {
+        match headers.get(CONTENT_TYPE) {
+            Some(value) => {
+                let mime = value.to_str().ok()?.parse::<Mime>().ok()?;
+                match (mime.type_(), mime.subtype()) {
+                    (mime::APPLICATION, mime::JSON) => Some(BodyFormat::Json),
+                    (mime::APPLICATION, sub) if sub == RECON_SUBTYPE => Some(BodyFormat::Recon),
+                    (mime::TEXT, mime::PLAIN) => Some(BodyFormat::Recon),
+                    _ => None,
+                }
+            }
+            None => Some(BodyFormat::Recon),
+        }
+    }
+
+    /// Select the format of the body of a response from the `Accept` headers of the request,
+    /// choosing the supported type with the highest quality.
+    fn negotiate(headers: &HeaderMap) -> Option<Self> {
+        let mut accepted = headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|media_range| Mime::from_str(media_range.trim()).ok())
+            .peekable();
+        if accepted.peek().is_none() {
+            return Some(BodyFormat::Recon);
+        }
+        let mut selected: Option<(Self, f32)> = None;
+        for mime in accepted {
+            let quality = mime
+                .get_param("q")
+                .and_then(|q| q.as_str().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if quality <= 0.0 {
+                continue;
+            }
+            if let Some(format) = BodyFormat::for_mime(&mime) {
+                if selected.map(|(_, q)| quality > q).unwrap_or(true) {
+                    selected = Some((format, quality));
+                }
+            }
+        }
+        selected.map(|(format, _)| format)
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<Value, String> {
+        let body = std::str::from_utf8(body).map_err(|err| err.to_string())?;
+        match self {
+            BodyFormat::Recon => parse_recognize(body, false).map_err(|err| err.to_string()),
+            BodyFormat::Json => parse_json_as(body).map_err(|err| err.to_string()),
+        }
+    }
+
+    fn render(&self, value: &Value) -> Bytes {
+        match self {
+            BodyFormat::Recon => Bytes::from(print_recon_compact(value).to_string()),
+            BodyFormat::Json => Bytes::from(print_json(value)),
+        }
+    }
+}
+
+fn response(status: StatusCode, payload: Bytes) -> Response<Full<Bytes>> {
+    let mut response = Response::default();
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .append(CONTENT_LENGTH, payload.len().into());
+    *response.body_mut() = payload.into();
+    response
+}
+
+fn method_not_allowed(allowed: &'static str) -> Response<Full<Bytes>> {
+    let mut response = response(
+        StatusCode::METHOD_NOT_ALLOWED,
+        Bytes::from(format!("Supported methods: {}.", allowed)),
+    );
+    response
+        .headers_mut()
+        .append(ALLOW, HeaderValue::from_static(allowed));
+    response
+}
+
+fn lane_not_found(address: &RelativeAddress<Text>) -> Response<Full<Bytes>> {
+    response(
+        StatusCode::NOT_FOUND,
+        Bytes::from(format!(
+            "No lane '{}' on agent '{}'",
+            address.lane, address.node
+        )),
+    )
+}
+
+async fn with_timeout<F>(timeout: Duration, fut: F) -> Response<Full<Bytes>>
+where
+    F: std::future::Future<Output = Response<Full<Bytes>>>,
+{
+    tokio::time::timeout(timeout, fut)
+        .await
+        .unwrap_or_else(|_| req_timeout())
+}
+
+/// Attach to the agent as if the bridge were a remote.
+async fn connect(
+    find: &mpsc::Sender<FindNode>,
+    source: Uuid,
+    address: &RelativeAddress<Text>,
+) -> Result<(ByteWriter, ByteReader), Response<Full<Bytes>>> {
+    let (promise_tx, promise_rx) = oneshot::channel();
+    let request = FindNode {
+        node: address.node.clone(),
+        lane: Some(address.lane.clone()),
+        request: NodeConnectionRequest::Warp {
+            source,
+            promise: promise_tx,
+        },
+    };
+    if find.send(request).await.is_err() {
+        return Err(unavailable());
+    }
+    match promise_rx.await {
+        Ok(Ok(io)) => Ok(io),
+        Ok(Err(AgentResolutionError::NotFound(NoSuchAgent { node, .. })))
+        | Ok(Err(AgentResolutionError::NotStarted(AgentNotStarted { node, .. })))
+        | Ok(Err(AgentResolutionError::HostedByPeer(AgentHostedByPeer { node, .. }))) => {
+            Err(not_found(node.as_str()))
+        }
+        Ok(Err(AgentResolutionError::PlaneStopping)) | Err(_) => Err(unavailable()),
+    }
+}
+
+/// Attach to a lane, as if the bridge were a remote, and request a sync. This waits until the lane
+/// has responded to the link request.
+async fn link_and_sync(
+    find: &mpsc::Sender<FindNode>,
+    address: &RelativeAddress<Text>,
+) -> Result<(ByteWriter, Responses), Response<Full<Bytes>>> {
+    let source = next_bridge_id();
+    let (writer, reader) = connect(find, source, address).await?;
+    let mut requests = FramedWrite::new(writer, RawRequestMessageEncoder);
+    let link: RawRequestMessage<'_, Text> = RequestMessage::link(source, address.clone());
+    let sync: RawRequestMessage<'_, Text> = RequestMessage::sync(source, address.clone());
+    if requests.send(link).await.is_err() || requests.send(sync).await.is_err() {
+        return Err(error("The agent failed to provide a response."));
+    }
+    let mut responses = FramedRead::new(reader, RawResponseMessageDecoder);
+    loop {
+        let Some(Ok(ResponseMessage { envelope, .. })) = responses.next().await else {
+            break Err(error("The agent failed to provide a response."));
+        };
+        match envelope {
+            Notification::Linked => break Ok((requests.into_inner(), responses)),
+            Notification::Unlinked(Some(body)) if body.as_ref() == LANE_NOT_FOUND_BODY => {
+                break Err(lane_not_found(address));
+            }
+            Notification::Unlinked(_) => {
+                break Err(error("The lane was unlinked before it synced."));
+            }
+            _ => {}
+        }
+    }
+}
+
+async fn sync_lane(
+    find: &mpsc::Sender<FindNode>,
+    address: RelativeAddress<Text>,
+    format: BodyFormat,
+) -> Response<Full<Bytes>> {
+    debug!(node = %address.node, lane = %address.lane, "Syncing with a lane for an HTTP request.");
+    let (_writer, mut responses) = match link_and_sync(find, &address).await {
+        Ok(uplink) => uplink,
+        Err(response) => return response,
+    };
+    let mut events = vec![];
+    loop {
+        let Some(Ok(ResponseMessage { envelope, .. })) = responses.next().await else {
+            return error("The agent failed to provide a response.");
+        };
+        match envelope {
+            Notification::Event(body) => match BodyFormat::Recon.parse(body.as_ref()) {
+                Ok(event) => events.push(event),
+                Err(_) => return error("Invalid response."),
+            },
+            Notification::Synced | Notification::SyncedWith(_) => break,
+            Notification::Unlinked(_) => {
+                return error("The lane was unlinked before it synced.");
+            }
+            Notification::Linked | Notification::Advisory(_) => {}
+        }
+    }
+
+    let state = if events.len() == 1 {
+        events.swap_remove(0)
+    } else {
+        Value::from_vec(events)
+    };
+    let mut response = response(StatusCode::OK, format.render(&state));
+    response
+        .headers_mut()
+        .append(CONTENT_TYPE, format.content_type());
+    response
+}
+
+async fn send_command(
+    find: &mpsc::Sender<FindNode>,
+    address: RelativeAddress<Text>,
+    command: Value,
+) -> Response<Full<Bytes>> {
+    let source = next_bridge_id();
+    let (writer, _reader) = match connect(find, source, &address).await {
+        Ok(io) => io,
+        Err(response) => return response,
+    };
+    debug!(node = %address.node, lane = %address.lane, "Sending a command for an HTTP request.");
+    let body = print_recon_compact(&command).to_string();
+    let mut requests = FramedWrite::new(writer, RawRequestMessageEncoder);
+    let message: RawRequestMessage<'_, Text> =
+        RequestMessage::command(source, address, body.as_bytes());
+    if requests.send(message).await.is_err() {
+        return error("The agent failed to receive the command.");
+    }
+    response(StatusCode::ACCEPTED, Bytes::new())
+}
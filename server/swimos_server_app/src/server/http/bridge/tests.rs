@@ -0,0 +1,156 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use hyper::{
+    header::{HeaderValue, ACCEPT, CONTENT_TYPE},
+    HeaderMap,
+};
+use swimos_api::address::RelativeAddress;
+use swimos_model::{Item, Text, Value};
+
+use super::{has_prefix, parse_lane_path, BodyFormat, BRIDGE_PREFIX, STREAM_PREFIX};
+
+fn headers(name: hyper::header::HeaderName, values: &[&'static str]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for value in values {
+        headers.append(name.clone(), HeaderValue::from_static(value));
+    }
+    headers
+}
+
+#[test]
+fn bridge_paths() {
+    assert!(has_prefix("/warp/node/lane", BRIDGE_PREFIX));
+    assert!(has_prefix("/warp/", BRIDGE_PREFIX));
+    assert!(!has_prefix("/warp", BRIDGE_PREFIX));
+    assert!(!has_prefix("/warped/node/lane", BRIDGE_PREFIX));
+    assert!(!has_prefix("/node/lane", BRIDGE_PREFIX));
+    assert!(has_prefix("/stream/node/lane", STREAM_PREFIX));
+    assert!(!has_prefix("/warp/node/lane", STREAM_PREFIX));
+}
+
+fn lane_path(prefix: &str, path: &str) -> Option<RelativeAddress<Text>> {
+    parse_lane_path(prefix, path).ok()
+}
+
+#[test]
+fn parse_lane_paths() {
+    assert_eq!(
+        lane_path(BRIDGE_PREFIX, "/warp/node/lane"),
+        Some(RelativeAddress::new(Text::new("/node"), Text::new("lane")))
+    );
+    assert_eq!(
+        lane_path(BRIDGE_PREFIX, "/warp/rooms/1/lights"),
+        Some(RelativeAddress::new(
+            Text::new("/rooms/1"),
+            Text::new("lights")
+        ))
+    );
+    assert_eq!(
+        lane_path(BRIDGE_PREFIX, "/warp/my%20node/my%2Flane"),
+        Some(RelativeAddress::new(
+            Text::new("/my node"),
+            Text::new("my/lane")
+        ))
+    );
+    assert_eq!(lane_path(BRIDGE_PREFIX, "/warp/lane"), None);
+    assert_eq!(lane_path(BRIDGE_PREFIX, "/warp/node/"), None);
+    assert_eq!(lane_path(BRIDGE_PREFIX, "/warp/"), None);
+    assert_eq!(
+        lane_path(STREAM_PREFIX, "/stream/node/lane"),
+        Some(RelativeAddress::new(Text::new("/node"), Text::new("lane")))
+    );
+}
+
+#[test]
+fn negotiate_response_format() {
+    assert_eq!(
+        BodyFormat::negotiate(&HeaderMap::new()),
+        Some(BodyFormat::Recon)
+    );
+    assert_eq!(
+        BodyFormat::negotiate(&headers(ACCEPT, &["application/json"])),
+        Some(BodyFormat::Json)
+    );
+    assert_eq!(
+        BodyFormat::negotiate(&headers(ACCEPT, &["application/x-recon"])),
+        Some(BodyFormat::Recon)
+    );
+    assert_eq!(
+        BodyFormat::negotiate(&headers(ACCEPT, &["*/*"])),
+        Some(BodyFormat::Recon)
+    );
+    assert_eq!(
+        BodyFormat::negotiate(&headers(
+            ACCEPT,
+            &["application/x-recon;q=0.5, application/json"]
+        )),
+        Some(BodyFormat::Json)
+    );
+    assert_eq!(
+        BodyFormat::negotiate(&headers(ACCEPT, &["text/html", "application/json;q=0.2"])),
+        Some(BodyFormat::Json)
+    );
+    assert_eq!(
+        BodyFormat::negotiate(&headers(ACCEPT, &["application/json;q=0"])),
+        None
+    );
+    assert_eq!(
+        BodyFormat::negotiate(&headers(ACCEPT, &["text/html"])),
+        None
+    );
+}
+
+#[test]
+fn request_body_format() {
+    assert_eq!(
+        BodyFormat::for_content(&HeaderMap::new()),
+        Some(BodyFormat::Recon)
+    );
+    assert_eq!(
+        BodyFormat::for_content(&headers(CONTENT_TYPE, &["application/json; charset=utf-8"])),
+        Some(BodyFormat::Json)
+    );
+    assert_eq!(
+        BodyFormat::for_content(&headers(CONTENT_TYPE, &["application/x-recon"])),
+        Some(BodyFormat::Recon)
+    );
+    assert_eq!(
+        BodyFormat::for_content(&headers(CONTENT_TYPE, &["text/plain"])),
+        Some(BodyFormat::Recon)
+    );
+    assert_eq!(
+        BodyFormat::for_content(&headers(CONTENT_TYPE, &["image/png"])),
+        None
+    );
+}
+
+#[test]
+fn parse_and_render_bodies() {
+    let value = Value::record(vec![Item::slot("a", 1), Item::slot("b", "x")]);
+
+    assert_eq!(BodyFormat::Recon.parse(b"{a:1,b:x}"), Ok(value.clone()));
+    assert_eq!(
+        BodyFormat::Json.parse(br#"{"a": 1, "b": "x"}"#),
+        Ok(value.clone())
+    );
+    assert!(BodyFormat::Recon.parse(b"{a:").is_err());
+    assert!(BodyFormat::Json.parse(b"{\"a\"").is_err());
+
+    assert_eq!(BodyFormat::Recon.render(&value).as_ref(), b"{a:1,b:x}");
+    assert_eq!(
+        BodyFormat::Json.render(&value).as_ref(),
+        br#"{"a":1,"b":"x"}"#
+    );
+}
@@ -20,37 +20,40 @@ use futures::{
     Future, FutureExt, Stream, StreamExt,
 };
 use http_body_util::BodyExt;
-use http_body_util::Full;
+use http_body_util::{combinators::UnsyncBoxBody, Full};
 use hyper::body::Incoming;
 use hyper::{
-    header::CONTENT_LENGTH,
+    header::{HeaderValue, CONTENT_LENGTH, LOCATION},
     server::conn::http1,
     service::Service,
     upgrade::{Parts, Upgraded},
     Request, Response, StatusCode,
 };
-use hyper_util::rt::TokioIo;
+use hyper_util::rt::{TokioIo, TokioTimer};
 use pin_project::pin_project;
-use ratchet::{
-    Extension, ExtensionProvider, ProtocolRegistry, WebSocket, WebSocketConfig, WebSocketStream,
-};
+use ratchet::{Extension, ExtensionProvider, WebSocket, WebSocketConfig, WebSocketStream};
 use std::{
-    collections::HashSet,
+    convert::Infallible,
     marker::PhantomData,
     net::SocketAddr,
     pin::Pin,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc, OnceLock,
+        Arc,
     },
     task::{Context, Poll},
     time::{Duration, Instant},
 };
 use swimos_api::{agent::HttpLaneRequest, http::HttpRequest};
 use swimos_http::{Negotiated, SockUnwrap, UpgradeError, UpgradeFuture};
-use swimos_messages::remote_protocol::{AgentResolutionError, FindNode, NoSuchAgent};
+use swimos_messages::remote_protocol::{
+    AgentHostedByPeer, AgentNotStarted, AgentResolutionError, FindNode, NoSuchAgent,
+};
 use swimos_remote::{
-    websocket::{RatchetError, WebsocketClient, WebsocketServer, WsOpenFuture, WARP},
+    websocket::{
+        RatchetError, WarpFeatures, WarpProtocol, WarpVersions, WebsocketClient, WebsocketServer,
+        WsOpenFuture,
+    },
     Listener, ListenerError, ListenerResult, Scheme,
 };
 use tokio::{
@@ -61,19 +64,27 @@ use tokio::{
 
 use crate::config::HttpConfig;
 
-use self::resolver::Resolver;
+use self::{bridge::WarpBridge, resolver::Resolver};
 
+mod bridge;
 mod resolver;
 #[cfg(test)]
 mod tests;
 
-pub type WsWithAddr<Ext, Sock> = (WebSocket<Sock, Ext>, Scheme, SocketAddr);
+/// The body of a response to an HTTP request. Most responses are sent in full but event streams
+/// are produced incrementally.
+type ResponseBody = UnsyncBoxBody<Bytes, Infallible>;
+
+pub type WsWithAddr<Ext, Sock> = (WebSocket<Sock, Ext>, Scheme, SocketAddr, WarpProtocol);
 pub type ListenResult<Ext, Sock> = Result<WsWithAddr<Ext, Sock>, ListenerError>;
 
 /// Hyper based web-server that will attempt to negotiate a server websocket over
 /// every incoming connection. If the connection is not a web-socket upgrade, it
 /// will attempt to forward to an HTTP lane on an agent, using the URL in the
-/// request to route the message.
+/// request to route the message. If the WARP bridge is enabled in the configuration,
+/// requests to paths starting with `/warp/` are instead served by syncing with, or
+/// sending commands to, ordinary lanes. Similarly, if event streams are enabled, requests
+/// to paths starting with `/stream/` receive the events of lanes as server-sent events.
 ///
 /// # Arguments
 /// * `listener` - Listener providing a stream of incoming connections.
@@ -92,18 +103,29 @@ where
     Ext: ExtensionProvider + Send + Sync + Unpin + 'static,
     Ext::Extension: Send + Unpin,
 {
+    let bridge = WarpBridge::new(find.clone(), &config);
     let resolver = Resolver::new(find, config.resolver_timeout);
+    let handshake_timeout = config.handshake_timeout;
     let state = HttpServerState::<L::AcceptStream, Sock, Ext, _, _>::new(
         listener.into_stream(),
         extension_provider,
         resolver,
+        bridge,
         config,
-        |sock, svc| async move {
+        move |sock, svc| async move {
             let result = http1::Builder::new()
+                .timer(TokioTimer::new())
+                .header_read_timeout(handshake_timeout)
                 .serve_connection(TokioIo::new(sock), &svc)
                 .with_upgrades()
                 .await;
             let did_upgrade = svc.did_upgrade.load(Ordering::Acquire);
+            // Clients of event streams end them by closing the connection, part way through the
+            // response, so this is not treated as a failure.
+            let result = match result {
+                Err(err) if err.is_incomplete_message() => Ok(()),
+                ow => ow,
+            };
             result.map(move |_| {
                 if did_upgrade {
                     ConnKind::Websocket
@@ -119,7 +141,7 @@ where
     })
 }
 
-type WebsocketParts<Sock, Ext> = (WebSocket<Sock, Ext>, Scheme, SocketAddr);
+type WebsocketParts<Sock, Ext> = (WebSocket<Sock, Ext>, Scheme, SocketAddr, WarpProtocol);
 
 enum ConnKind {
     NoUpgrade,
@@ -204,12 +226,14 @@ where
     /// * `listener_stream` - A listener that produced a stream of incoming connections.
     /// * `extension_provider` - Extension provider to use when negotiating websocket connections.
     /// * `resolver` - Agent resolver for forwarding requests to HTTP lanes.
+    /// * `bridge` - Bridge for requests to ordinary lanes (if enabled).
     /// * `config` - Configuration parameters for HTTP server.
     /// * `connect_fn` - Async function to handle an incoming HTTP connection.
     fn new(
         listener_stream: L,
         extension_provider: Ext,
         resolver: resolver::Resolver,
+        bridge: Option<WarpBridge>,
         config: HttpConfig,
         connect_fn: FC,
     ) -> Self {
@@ -223,7 +247,9 @@ where
             upgrader: Upgrader::new(
                 extension_provider,
                 resolver,
+                bridge,
                 config.websockets,
+                config.warp_versions,
                 config.http_request_timeout,
                 upgrade_tx,
             ),
@@ -376,6 +402,7 @@ type BytesHyperResult = Result<Response<Full<Bytes>>, hyper::Error>;
 fn perform_upgrade<Ext, Sock, Err>(
     request: Request<Incoming>,
     config: WebSocketConfig,
+    versions: WarpVersions,
     result: Result<Negotiated<'_, Ext>, UpgradeError<Err>>,
     scheme: Scheme,
     addr: SocketAddr,
@@ -387,6 +414,7 @@ where
 {
     match result {
         Ok(negotiated) => {
+            let protocol = versions.negotiated(negotiated.protocol);
             let (response, upgrade_fut) = swimos_http::upgrade(
                 request,
                 negotiated,
@@ -395,7 +423,12 @@ where
             );
             (
                 Ok(response),
-                Some(UpgradeFutureWithSock::new(upgrade_fut, scheme, addr)),
+                Some(UpgradeFutureWithSock::new(
+                    upgrade_fut,
+                    scheme,
+                    addr,
+                    protocol,
+                )),
             )
         }
         Err(err) => (Ok(swimos_http::fail_upgrade(err)), None),
@@ -407,7 +440,9 @@ where
 struct Upgrader<Ext: ExtensionProvider, Sock> {
     extension_provider: Arc<Ext>,
     resolver: resolver::Resolver,
+    bridge: Option<WarpBridge>,
     config: WebSocketConfig,
+    versions: WarpVersions,
     request_timeout: Duration,
     upgrade_tx: mpsc::Sender<UpgradeFutureWithSock<Ext::Extension, Sock>>,
 }
@@ -420,14 +455,18 @@ where
     fn new(
         extension_provider: Ext,
         resolver: resolver::Resolver,
+        bridge: Option<WarpBridge>,
         config: WebSocketConfig,
+        versions: WarpVersions,
         request_timeout: Duration,
         upgrade_tx: mpsc::Sender<UpgradeFutureWithSock<Ext::Extension, Sock>>,
     ) -> Self {
         Upgrader {
             extension_provider: Arc::new(extension_provider),
             resolver,
+            bridge,
             config,
+            versions,
             request_timeout,
             upgrade_tx,
         }
@@ -441,14 +480,18 @@ where
         let Upgrader {
             extension_provider,
             resolver,
+            bridge,
             config,
+            versions,
             request_timeout,
             upgrade_tx,
         } = self;
         UpgradeService::new(
             extension_provider.clone(),
             resolver.clone(),
+            bridge.clone(),
             *config,
+            *versions,
             scheme,
             addr,
             *request_timeout,
@@ -462,8 +505,10 @@ where
 struct UpgradeService<Ext: ExtensionProvider, Sock> {
     extension_provider: Arc<Ext>,
     resolver: resolver::Resolver,
+    bridge: Option<WarpBridge>,
     upgrade_tx: mpsc::Sender<UpgradeFutureWithSock<Ext::Extension, Sock>>,
     config: WebSocketConfig,
+    versions: WarpVersions,
     scheme: Scheme,
     addr: SocketAddr,
     request_timeout: Duration,
@@ -474,10 +519,13 @@ impl<Ext: ExtensionProvider, Sock> UpgradeService<Ext, Sock>
 where
     Sock: AsyncRead + AsyncWrite + Unpin + 'static,
 {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         extension_provider: Arc<Ext>,
         resolver: resolver::Resolver,
+        bridge: Option<WarpBridge>,
         config: WebSocketConfig,
+        versions: WarpVersions,
         scheme: Scheme,
         addr: SocketAddr,
         request_timeout: Duration,
@@ -486,8 +534,10 @@ where
         UpgradeService {
             extension_provider,
             resolver,
+            bridge,
             upgrade_tx,
             config,
+            versions,
             scheme,
             addr,
             request_timeout,
@@ -496,62 +546,60 @@ where
     }
 }
 
-static PROTOCOLS: OnceLock<HashSet<&'static str>> = OnceLock::new();
-
-fn warp_protocol() -> &'static HashSet<&'static str> {
-    PROTOCOLS.get_or_init(|| {
-        let mut s = HashSet::new();
-        s.insert(WARP);
-        s
-    })
-}
-
 impl<'a, Ext, Sock> Service<Request<Incoming>> for &'a UpgradeService<Ext, Sock>
 where
     Sock: AsyncRead + AsyncWrite + Unpin + Send + 'static,
     Ext: ExtensionProvider,
     Ext::Extension: Send + 'static,
 {
-    type Response = Response<Full<Bytes>>;
+    type Response = Response<ResponseBody>;
 
     type Error = hyper::Error;
 
-    type Future = BoxFuture<'static, Result<Response<Full<Bytes>>, hyper::Error>>;
+    type Future = BoxFuture<'static, Result<Response<ResponseBody>, hyper::Error>>;
 
     fn call(&self, request: Request<Incoming>) -> Self::Future {
         let UpgradeService {
             extension_provider,
             upgrade_tx,
             config,
+            versions,
             scheme,
             addr,
             resolver,
+            bridge,
             request_timeout,
             did_upgrade,
         } = *self;
-        let result =
-            swimos_http::negotiate_upgrade(&request, warp_protocol(), extension_provider.as_ref())
-                .transpose();
+        // The most preferred version of the Warp protocol that the client offered is selected.
+        let result = swimos_http::negotiate_upgrade_with(
+            &request,
+            |offered| versions.select(offered).map(|version| version.name),
+            extension_provider.as_ref(),
+        )
+        .transpose();
         // If the request in a websocket upgrade, perform the upgrade, otherwise attempt to delegate
-        // the request to an HTTP lane on an agent.
+        // the request to the WARP bridge or to an HTTP lane on an agent.
         if let Some(result) = result {
             let (upgrade_result, maybe_fut) =
-                perform_upgrade(request, *config, result, *scheme, *addr);
+                perform_upgrade(request, *config, *versions, result, *scheme, *addr);
             did_upgrade.store(true, Ordering::Release);
             if let Some(upgrade_fut) = maybe_fut {
                 let tx = upgrade_tx.clone();
                 async move {
                     // This can only fail if the server is no longer running, in which case it is irrelevant.
                     let _ = tx.send(upgrade_fut).await;
-                    upgrade_result
+                    upgrade_result.map(boxed_response)
                 }
                 .boxed()
             } else {
-                async move { upgrade_result }.boxed()
+                async move { upgrade_result.map(boxed_response) }.boxed()
             }
+        } else if let Some(bridge) = bridge.as_ref().filter(|b| b.handles(&request)) {
+            bridge.clone().serve(request).map(Ok).boxed()
         } else {
             serve_request(request, *request_timeout, resolver.clone())
-                .map(Ok)
+                .map(|response| Ok(boxed_response(response)))
                 .boxed()
         }
     }
@@ -581,11 +629,13 @@ where
     }
 }
 
-/// Associates a [`Scheme`] and [`SocketAddr`] with the future performing the websocket upgrade.
+/// Associates a [`Scheme`], [`SocketAddr`] and the negotiated [`WarpProtocol`] with the future
+/// performing the websocket upgrade.
 struct UpgradeFutureWithSock<Ext, Sock> {
     inner: UpgradeFuture<Ext, ReclaimSock<Sock>>,
     scheme: Scheme,
     addr: SocketAddr,
+    protocol: WarpProtocol,
 }
 
 impl<Ext, Sock> UpgradeFutureWithSock<Ext, Sock> {
@@ -593,11 +643,13 @@ impl<Ext, Sock> UpgradeFutureWithSock<Ext, Sock> {
         inner: UpgradeFuture<Ext, ReclaimSock<Sock>>,
         scheme: Scheme,
         addr: SocketAddr,
+        protocol: WarpProtocol,
     ) -> Self {
         UpgradeFutureWithSock {
             inner,
             scheme,
             addr,
+            protocol,
         }
     }
 }
@@ -607,16 +659,17 @@ where
     Sock: AsyncRead + AsyncWrite + Unpin + Send + 'static,
     Ext: Extension + Unpin,
 {
-    type Output = Result<(WebSocket<Sock, Ext>, Scheme, SocketAddr), hyper::Error>;
+    type Output = Result<WebsocketParts<Sock, Ext>, hyper::Error>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let UpgradeFutureWithSock {
             inner,
             scheme,
             addr,
+            protocol,
         } = self.get_mut();
         let ws = ready!(inner.poll_unpin(cx))?;
-        Poll::Ready(Ok((ws, *scheme, *addr)))
+        Poll::Ready(Ok((ws, *scheme, *addr, *protocol)))
     }
 }
 
@@ -637,7 +690,7 @@ impl HyperWebsockets {
 
 impl WebsocketServer for HyperWebsockets {
     type WsStream<Sock, Ext> =
-        BoxStream<'static, Result<(WebSocket<Sock, Ext>, SocketAddr), ListenerError>>;
+        BoxStream<'static, Result<(WebSocket<Sock, Ext>, SocketAddr, WarpProtocol), ListenerError>>;
 
     fn wrap_listener<Sock, L, Provider>(
         &self,
@@ -653,17 +706,18 @@ impl WebsocketServer for HyperWebsockets {
     {
         let HyperWebsockets { config } = self;
         hyper_http_server(listener, find, provider, *config)
-            .map(|r| r.map(|(ws, _, addr)| (ws, addr)))
+            .map(|r| r.map(|(ws, _, addr, protocol)| (ws, addr, protocol)))
             .boxed()
     }
 }
 
-impl WebsocketClient for HyperWebsockets {
-    fn open_connection<'a, Sock, Provider>(
+impl HyperWebsockets {
+    fn open_with<'a, Sock, Provider>(
         &self,
         socket: Sock,
         provider: &'a Provider,
         addr: String,
+        features: WarpFeatures,
     ) -> WsOpenFuture<'a, Sock, Provider::Extension, RatchetError>
     where
         Sock: WebSocketStream + Send,
@@ -674,16 +728,52 @@ impl WebsocketClient for HyperWebsockets {
 
         let config = *config;
         Box::pin(async move {
-            let subprotocols = ProtocolRegistry::new([WARP])?;
-            let socket =
+            let versions = config.warp_versions;
+            let subprotocols =
+                versions.client_protocols(config.warp_encoding.features() | features)?;
+            let upgraded =
                 ratchet::subscribe_with(config.websockets, socket, addr, provider, subprotocols)
-                    .await?
-                    .into_websocket();
-            Ok(socket)
+                    .await?;
+            let protocol = versions.negotiated(upgraded.subprotocol.as_deref());
+            Ok((upgraded.into_websocket(), protocol))
         })
     }
 }
 
+impl WebsocketClient for HyperWebsockets {
+    fn open_connection<'a, Sock, Provider>(
+        &self,
+        socket: Sock,
+        provider: &'a Provider,
+        addr: String,
+    ) -> WsOpenFuture<'a, Sock, Provider::Extension, RatchetError>
+    where
+        Sock: WebSocketStream + Send,
+        Provider: ExtensionProvider + Send + Sync + 'static,
+        Provider::Extension: Send + Sync + 'static,
+    {
+        self.open_with(socket, provider, addr, WarpFeatures::empty())
+    }
+
+    fn open_forwarding_connection<'a, Sock, Provider>(
+        &self,
+        socket: Sock,
+        provider: &'a Provider,
+        addr: String,
+    ) -> WsOpenFuture<'a, Sock, Provider::Extension, RatchetError>
+    where
+        Sock: WebSocketStream + Send,
+        Provider: ExtensionProvider + Send + Sync + 'static,
+        Provider::Extension: Send + Sync + 'static,
+    {
+        self.open_with(socket, provider, addr, WarpFeatures::FORWARDED)
+    }
+}
+
+fn boxed_response(response: Response<Full<Bytes>>) -> Response<ResponseBody> {
+    response.map(BodyExt::boxed_unsync)
+}
+
 /// Produce a bad request response for an request that we cannot route correctly.
 fn bad_request(msg: String) -> Response<Full<Bytes>> {
     let mut response = Response::default();
@@ -733,6 +823,20 @@ fn not_found(node: &str) -> Response<Full<Bytes>> {
     response
 }
 
+/// Produce a redirect response for the case where an agent is hosted by another server.
+fn redirect(location: &str) -> Response<Full<Bytes>> {
+    let mut response = Response::default();
+    *response.status_mut() = StatusCode::TEMPORARY_REDIRECT;
+    match HeaderValue::from_str(location) {
+        Ok(location) => {
+            response.headers_mut().append(LOCATION, location);
+        }
+        Err(_) => return error("Invalid redirect location."),
+    }
+    response.headers_mut().append(CONTENT_LENGTH, 0.into());
+    response
+}
+
 /// Produce a response to send if the server is already stopping.
 fn unavailable() -> Response<Full<Bytes>> {
     let mut response = Response::default();
@@ -767,12 +871,22 @@ async fn serve_request(
         Err(err) => return bad_request(err.to_string()),
     };
 
+    let path_and_query = bytes_request
+        .uri
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or("/")
+        .to_string();
     let (message, response_rx) = HttpLaneRequest::new(bytes_request);
     if let Err(err) = resolver.send(message).await {
         match err {
-            AgentResolutionError::NotFound(NoSuchAgent { node, .. }) => {
+            AgentResolutionError::NotFound(NoSuchAgent { node, .. })
+            | AgentResolutionError::NotStarted(AgentNotStarted { node, .. }) => {
                 return not_found(node.as_str())
             }
+            AgentResolutionError::HostedByPeer(AgentHostedByPeer { host, .. }) => {
+                return redirect(&format!("{}{}", host, path_and_query))
+            }
             AgentResolutionError::PlaneStopping => return unavailable(),
         }
     }
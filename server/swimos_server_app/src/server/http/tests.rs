@@ -24,25 +24,46 @@ use bytes::{Bytes, BytesMut};
 use futures::{
     future::{join, join3, Either},
     stream::{BoxStream, FuturesUnordered, SelectAll},
-    Future, StreamExt,
+    Future, SinkExt, StreamExt,
 };
 use http_body_util::{BodyExt, Full};
 use hyper::body::Incoming;
 use hyper::{client::conn::http1, header::HeaderValue, Request, Response, Uri};
 use hyper_util::rt::TokioIo;
-use ratchet::{CloseReason, Message, NoExt, NoExtProvider, WebSocket, WebSocketConfig};
+use ratchet::{
+    CloseReason, Message, NoExt, NoExtProvider, ProtocolRegistry, WebSocket, WebSocketConfig,
+};
 use swimos_api::{
+    address::RelativeAddress,
     agent::{HttpLaneRequest, RawHttpLaneResponse},
     http::{StatusCode, Version},
 };
-use swimos_messages::remote_protocol::{
-    AgentResolutionError, FindNode, NoSuchAgent, NodeConnectionRequest,
+use swimos_messages::{
+    protocol::{
+        Operation, RawRequestMessageDecoder, RawResponseMessageEncoder, RequestMessage,
+        ResponseMessage,
+    },
+    remote_protocol::{
+        AgentHostedByPeer, AgentResolutionError, FindNode, NoSuchAgent, NodeConnectionRequest,
+    },
 };
 use swimos_model::Text;
-use swimos_remote::{Listener, ListenerResult, Scheme};
-use swimos_utilities::non_zero_usize;
-use tokio::{io::DuplexStream, sync::mpsc};
+use swimos_remote::{
+    websocket::{
+        WarpEncoding, WarpFeatures, WarpProtocol, WarpVersion, WarpVersions, WARP, WARP_BINARY,
+    },
+    Listener, ListenerResult, Scheme,
+};
+use swimos_utilities::{
+    byte_channel::{byte_channel, ByteReader, ByteWriter},
+    non_zero_usize,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, DuplexStream},
+    sync::mpsc,
+};
 use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::codec::{FramedRead, FramedWrite};
 
 use crate::config::HttpConfig;
 
@@ -50,6 +71,7 @@ const BUFFER_SIZE: usize = 4096;
 const MAX_ACTIVE: NonZeroUsize = non_zero_usize!(2);
 const REQ_TIMEOUT: Duration = Duration::from_millis(100);
 const CHANNEL_SIZE: NonZeroUsize = non_zero_usize!(8);
+const CHANNEL_BUFFER_SIZE: NonZeroUsize = non_zero_usize!(4096);
 
 async fn client(tx: &mpsc::Sender<DuplexStream>, tag: &str) {
     let (client, server) = tokio::io::duplex(BUFFER_SIZE);
@@ -120,13 +142,20 @@ impl Listener<DuplexStream> for TestListener {
 }
 
 async fn run_server(rx: mpsc::Receiver<DuplexStream>, find_tx: mpsc::Sender<FindNode>) {
-    let listener = TestListener { rx };
-
     let config = HttpConfig {
         max_http_requests: MAX_ACTIVE,
         http_request_timeout: REQ_TIMEOUT,
         ..Default::default()
     };
+    run_server_with_config(rx, find_tx, config).await
+}
+
+async fn run_server_with_config(
+    rx: mpsc::Receiver<DuplexStream>,
+    find_tx: mpsc::Sender<FindNode>,
+    config: HttpConfig,
+) {
+    let listener = TestListener { rx };
 
     let mut stream = pin!(super::hyper_http_server(
         listener,
@@ -137,7 +166,7 @@ async fn run_server(rx: mpsc::Receiver<DuplexStream>, find_tx: mpsc::Sender<Find
 
     let handles = FuturesUnordered::new();
     while let Some(result) = stream.next().await {
-        let (websocket, _, _) = result.expect("Server handshake failed.");
+        let (websocket, _, _, _) = result.expect("Server handshake failed.");
         handles.push(tokio::spawn(handle_connection(websocket)));
     }
 
@@ -234,6 +263,105 @@ async fn many_clients() {
     join(server, clients).await;
 }
 
+// Open a websocket connection, offering the specified subprotocols, and return the subprotocol
+// selected in the response along with the protocol reported by the server.
+async fn negotiate_version(
+    config: HttpConfig,
+    offered: &[&'static str],
+) -> (Option<String>, WarpProtocol) {
+    let (tx, rx) = mpsc::channel(8);
+    let (find_tx, _find_rx) = mpsc::channel(CHANNEL_SIZE.get());
+    let mut server = pin!(super::hyper_http_server(
+        TestListener { rx },
+        find_tx,
+        NoExtProvider,
+        config,
+    ));
+
+    let (client, server_sock) = tokio::io::duplex(BUFFER_SIZE);
+    tx.send(server_sock).await.expect("Failed to open channel.");
+    let subprotocols = ProtocolRegistry::new(offered.iter().copied()).expect("Invalid protocols.");
+    let client = ratchet::subscribe_with(
+        WebSocketConfig::default(),
+        client,
+        "ws://localhost:8080",
+        &NoExtProvider,
+        subprotocols,
+    );
+
+    let (client_result, server_result) = join(client, server.next()).await;
+    let upgraded = client_result.expect("Client handshake failed.");
+    let (_, _, _, protocol) = server_result
+        .expect("Server stopped.")
+        .expect("Server handshake failed.");
+    (upgraded.subprotocol, protocol)
+}
+
+#[tokio::test]
+async fn negotiate_preferred_warp_version() {
+    let (client_protocol, protocol) =
+        negotiate_version(HttpConfig::default(), &[WARP, WARP_BINARY]).await;
+    assert_eq!(client_protocol.as_deref(), Some(WARP_BINARY));
+    assert_eq!(protocol.version(), Some(WARP_BINARY));
+    assert_eq!(protocol.features(), WarpFeatures::BINARY_ENVELOPES);
+
+    let (client_protocol, protocol) = negotiate_version(HttpConfig::default(), &[WARP]).await;
+    assert_eq!(client_protocol.as_deref(), Some(WARP));
+    assert_eq!(protocol.version(), Some(WARP));
+    assert_eq!(protocol.features(), WarpFeatures::empty());
+}
+
+#[tokio::test]
+async fn negotiate_restricted_warp_versions() {
+    const TEXT_ONLY: &[WarpVersion] = &[WarpVersion::new(WARP, WarpFeatures::empty())];
+    let config = HttpConfig {
+        warp_versions: WarpVersions::new(TEXT_ONLY),
+        ..Default::default()
+    };
+    let (client_protocol, protocol) = negotiate_version(config, &[WARP, WARP_BINARY]).await;
+    assert_eq!(client_protocol.as_deref(), Some(WARP));
+    assert_eq!(protocol.version(), Some(WARP));
+    assert_eq!(protocol.encoding(), WarpEncoding::Text);
+}
+
+#[tokio::test]
+async fn negotiate_without_warp_version() {
+    let (client_protocol, protocol) = negotiate_version(HttpConfig::default(), &["other"]).await;
+    assert_eq!(client_protocol, None);
+    assert_eq!(protocol, WarpProtocol::default());
+}
+
+#[tokio::test]
+async fn close_stalled_handshake() {
+    let (tx, rx) = mpsc::channel(8);
+    let (find_tx, _find_rx) = mpsc::channel(CHANNEL_SIZE.get());
+    let config = HttpConfig {
+        handshake_timeout: Duration::from_millis(50),
+        ..Default::default()
+    };
+    let mut server = pin!(super::hyper_http_server(
+        TestListener { rx },
+        find_tx,
+        NoExtProvider,
+        config,
+    ));
+
+    let (mut client, server_sock) = tokio::io::duplex(BUFFER_SIZE);
+    tx.send(server_sock).await.expect("Failed to open channel.");
+    // Send an incomplete request and then stall.
+    client
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n")
+        .await
+        .expect("Write failed.");
+
+    let client = async move {
+        let mut buffer = vec![];
+        client.read_to_end(&mut buffer).await.expect("Read failed.");
+    };
+    let (_, result) = with_timeout(join(client, server.next())).await;
+    assert!(matches!(result, Some(Err(_))));
+}
+
 #[derive(Debug, Clone, Copy)]
 enum FindResponse {
     Ok,
@@ -241,6 +369,7 @@ enum FindResponse {
     NotFound,
     Stopping,
     Timeout,
+    Peer,
 }
 
 #[derive(Clone, Copy)]
@@ -298,6 +427,15 @@ async fn fake_plane(responses: HashMap<Text, FindResponse>, mut find_rx: mpsc::R
                             .send(Err(AgentResolutionError::PlaneStopping))
                             .expect("Request dropped.");
                     }
+                    Some(FindResponse::Peer) => {
+                        promise
+                            .send(Err(AgentHostedByPeer {
+                                node,
+                                host: Text::new("http://peer:9001"),
+                            }
+                            .into()))
+                            .expect("Request dropped.");
+                    }
                     Some(FindResponse::Timeout) => {
                         let (tx, rx) = mpsc::channel(CHANNEL_SIZE.get());
                         promise.send(Ok(tx)).expect("Request dropped.");
@@ -351,6 +489,7 @@ fn setup_responses() -> HashMap<Text, FindResponse> {
         (Text::new("/not_found"), FindResponse::NotFound),
         (Text::new("/stopping"), FindResponse::Stopping),
         (Text::new("/timeout"), FindResponse::Timeout),
+        (Text::new("/peer"), FindResponse::Peer),
     ]
     .into_iter()
     .collect()
@@ -428,6 +567,26 @@ async fn not_found_http_request() {
     .await
 }
 
+#[tokio::test]
+async fn peer_hosted_http_request() {
+    with_timeout(async move {
+        let (tx, rx) = mpsc::channel(8);
+        let (find_tx, find_rx) = mpsc::channel(CHANNEL_SIZE.get());
+        let server = run_server(rx, find_tx);
+        let responses = setup_responses();
+        let agent = fake_plane(responses, find_rx);
+        let client = http_client(tx, "peer", "name");
+        let (_, response, _) = join3(server, client, agent).await;
+        assert_eq!(response.status(), hyper::StatusCode::TEMPORARY_REDIRECT);
+        let location = response
+            .headers()
+            .get(hyper::header::LOCATION)
+            .expect("No location header.");
+        assert_eq!(location, "http://peer:9001/peer?lane=name");
+    })
+    .await
+}
+
 #[tokio::test]
 async fn server_stopping_http_request() {
     with_timeout(async move {
@@ -472,3 +631,376 @@ async fn http_request_timeout() {
     })
     .await
 }
+
+const BRIDGE_AGENT: &str = "/node";
+const VALUE_LANE: &str = "value";
+const MAP_LANE: &str = "map";
+const MISSING_LANE: &str = "missing";
+// A lane that is unlinked as soon as it has synced.
+const TRANSIENT_LANE: &str = "transient";
+const HEARTBEAT: Duration = Duration::from_millis(50);
+const LANE_NOT_FOUND: &[u8] = b"@laneNotFound";
+
+type AgentResponse<'a> = ResponseMessage<Text, &'a [u8], &'a [u8]>;
+
+async fn run_bridge_server(rx: mpsc::Receiver<DuplexStream>, find_tx: mpsc::Sender<FindNode>) {
+    let config = HttpConfig {
+        max_http_requests: MAX_ACTIVE,
+        http_request_timeout: REQ_TIMEOUT,
+        warp_bridge: true,
+        event_streams: true,
+        event_stream_heartbeat: HEARTBEAT,
+        ..Default::default()
+    };
+    run_server_with_config(rx, find_tx, config).await
+}
+
+// Attaches a fake agent to every bridge request for `BRIDGE_AGENT`. Commands received by the agent
+// are reported on the provided channel.
+async fn fake_bridge_plane(
+    mut find_rx: mpsc::Receiver<FindNode>,
+    commands_tx: mpsc::Sender<(Text, String)>,
+) {
+    while let Some(FindNode {
+        node,
+        lane,
+        request,
+    }) = find_rx.recv().await
+    {
+        let NodeConnectionRequest::Warp { promise, .. } = request else {
+            panic!("Unexpected HTTP resolution request.");
+        };
+        if node.as_str() == BRIDGE_AGENT {
+            let (request_tx, request_rx) = byte_channel(CHANNEL_BUFFER_SIZE);
+            let (response_tx, response_rx) = byte_channel(CHANNEL_BUFFER_SIZE);
+            promise
+                .send(Ok((request_tx, response_rx)))
+                .expect("Request dropped.");
+            tokio::spawn(fake_agent(request_rx, response_tx, commands_tx.clone()));
+        } else {
+            promise
+                .send(Err(AgentResolutionError::NotFound(NoSuchAgent {
+                    node,
+                    lane,
+                })))
+                .expect("Request dropped.");
+        }
+    }
+}
+
+async fn fake_agent(
+    request_rx: ByteReader,
+    response_tx: ByteWriter,
+    commands_tx: mpsc::Sender<(Text, String)>,
+) {
+    let mut requests = FramedRead::new(request_rx, RawRequestMessageDecoder);
+    let mut responses = FramedWrite::new(response_tx, RawResponseMessageEncoder);
+    while let Some(Ok(RequestMessage {
+        origin,
+        path,
+        envelope,
+        ..
+    })) = requests.next().await
+    {
+        let path =
+            RelativeAddress::new(Text::new(path.node.as_str()), Text::new(path.lane.as_str()));
+        let lane = path.lane.clone();
+        match envelope {
+            Operation::Link(_) if lane.as_str() == MISSING_LANE => {
+                send_response(
+                    &mut responses,
+                    ResponseMessage::unlinked(origin, path, Some(LANE_NOT_FOUND)),
+                )
+                .await
+                .expect("Send failed.");
+            }
+            Operation::Link(_) => {
+                send_response(&mut responses, ResponseMessage::linked(origin, path))
+                    .await
+                    .expect("Send failed.");
+            }
+            Operation::Sync(_) => {
+                let events: &[&'static [u8]] = match lane.as_str() {
+                    VALUE_LANE => &[b"{a:1,b:x}"],
+                    MAP_LANE => &[b"1", b"2"],
+                    TRANSIENT_LANE => &[b"1"],
+                    _ => &[],
+                };
+                for event in events {
+                    send_response(
+                        &mut responses,
+                        ResponseMessage::event(origin, path.clone(), *event),
+                    )
+                    .await
+                    .expect("Send failed.");
+                }
+                send_response(
+                    &mut responses,
+                    ResponseMessage::synced(origin, path.clone()),
+                )
+                .await
+                .expect("Send failed.");
+                if lane.as_str() == TRANSIENT_LANE {
+                    send_response(
+                        &mut responses,
+                        ResponseMessage::unlinked(origin, path, None),
+                    )
+                    .await
+                    .expect("Send failed.");
+                }
+            }
+            Operation::Command(body) => {
+                let body = std::str::from_utf8(body.as_ref()).expect("Bad UTF8 in command.");
+                commands_tx
+                    .send((lane, body.to_string()))
+                    .await
+                    .expect("Channel dropped.");
+            }
+            _ => {}
+        }
+    }
+}
+
+async fn send_response(
+    responses: &mut FramedWrite<ByteWriter, RawResponseMessageEncoder>,
+    response: AgentResponse<'static>,
+) -> Result<(), std::io::Error> {
+    responses.send(response).await
+}
+
+async fn bridge_client(
+    tx: mpsc::Sender<DuplexStream>,
+    method: hyper::Method,
+    path: &str,
+    header: Option<(hyper::header::HeaderName, &'static str)>,
+    body: &'static str,
+) -> (hyper::StatusCode, Option<HeaderValue>, Bytes) {
+    let (client, server) = tokio::io::duplex(BUFFER_SIZE);
+    tx.send(server).await.expect("Failed to open channel.");
+    let (mut sender, connection) = http1::handshake(TokioIo::new(client))
+        .await
+        .expect("HTTP handshake failed.");
+
+    let send = async move {
+        let mut request = Request::new(Full::new(Bytes::from_static(body.as_bytes())));
+        let uri = Uri::try_from(format!("http://example:8080{}", path)).expect("Bad URI.");
+        *request.method_mut() = method;
+        request.headers_mut().append(
+            hyper::header::HOST,
+            HeaderValue::from_str(uri.authority().unwrap().as_str()).unwrap(),
+        );
+        if let Some((name, value)) = header {
+            request
+                .headers_mut()
+                .append(name, HeaderValue::from_static(value));
+        }
+        *request.uri_mut() = uri;
+        let response = sender.send_request(request).await.expect("Sending");
+        let status = response.status();
+        let content_type = response.headers().get(hyper::header::CONTENT_TYPE).cloned();
+        let body = response
+            .collect()
+            .await
+            .expect("Failed to read body.")
+            .to_bytes();
+        (status, content_type, body)
+    };
+
+    let (conn_result, response) = join(connection, send).await;
+    assert!(conn_result.is_ok());
+    response
+}
+
+async fn bridge_test_case(
+    method: hyper::Method,
+    path: &str,
+    header: Option<(hyper::header::HeaderName, &'static str)>,
+    body: &'static str,
+) -> (
+    (hyper::StatusCode, Option<HeaderValue>, Bytes),
+    Vec<(Text, String)>,
+) {
+    with_timeout(async move {
+        let (tx, rx) = mpsc::channel(8);
+        let (find_tx, find_rx) = mpsc::channel(CHANNEL_SIZE.get());
+        let (commands_tx, mut commands_rx) = mpsc::channel(8);
+        let server = run_bridge_server(rx, find_tx);
+        let plane = fake_bridge_plane(find_rx, commands_tx);
+        let client = bridge_client(tx, method, path, header, body);
+        let (_, response, _) = join3(server, client, plane).await;
+        let mut commands = vec![];
+        while let Some(command) = commands_rx.recv().await {
+            commands.push(command);
+        }
+        (response, commands)
+    })
+    .await
+}
+
+#[tokio::test]
+async fn bridge_get_value_lane() {
+    let ((status, content_type, body), _) =
+        bridge_test_case(hyper::Method::GET, "/warp/node/value", None, "").await;
+    assert_eq!(status, hyper::StatusCode::OK);
+    assert_eq!(
+        content_type,
+        Some(HeaderValue::from_static("application/x-recon"))
+    );
+    assert_eq!(body.as_ref(), b"{a:1,b:x}");
+}
+
+#[tokio::test]
+async fn bridge_get_lane_as_json() {
+    let accept = Some((hyper::header::ACCEPT, "application/json"));
+    let ((status, content_type, body), _) =
+        bridge_test_case(hyper::Method::GET, "/warp/node/value", accept.clone(), "").await;
+    assert_eq!(status, hyper::StatusCode::OK);
+    assert_eq!(
+        content_type,
+        Some(HeaderValue::from_static("application/json"))
+    );
+    assert_eq!(body.as_ref(), br#"{"a":1,"b":"x"}"#);
+
+    let ((status, _, body), _) =
+        bridge_test_case(hyper::Method::GET, "/warp/node/map", accept, "").await;
+    assert_eq!(status, hyper::StatusCode::OK);
+    assert_eq!(body.as_ref(), b"[1,2]");
+}
+
+#[tokio::test]
+async fn bridge_get_missing_lane() {
+    let ((status, _, _), _) =
+        bridge_test_case(hyper::Method::GET, "/warp/node/missing", None, "").await;
+    assert_eq!(status, hyper::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn bridge_get_missing_agent() {
+    let ((status, _, _), _) =
+        bridge_test_case(hyper::Method::GET, "/warp/other/value", None, "").await;
+    assert_eq!(status, hyper::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn bridge_post_command() {
+    let content_type = Some((hyper::header::CONTENT_TYPE, "application/json"));
+    let ((status, _, _), commands) = bridge_test_case(
+        hyper::Method::POST,
+        "/warp/node/value",
+        content_type,
+        r#"{"a": 2}"#,
+    )
+    .await;
+    assert_eq!(status, hyper::StatusCode::ACCEPTED);
+    assert_eq!(commands, vec![(Text::new(VALUE_LANE), "{a:2}".to_string())]);
+}
+
+#[tokio::test]
+async fn bridge_post_invalid_command() {
+    let ((status, _, _), commands) =
+        bridge_test_case(hyper::Method::POST, "/warp/node/value", None, "{a:").await;
+    assert_eq!(status, hyper::StatusCode::BAD_REQUEST);
+    assert!(commands.is_empty());
+}
+
+#[tokio::test]
+async fn bridge_unsupported_method() {
+    let ((status, _, _), _) =
+        bridge_test_case(hyper::Method::DELETE, "/warp/node/value", None, "").await;
+    assert_eq!(status, hyper::StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[tokio::test]
+async fn bridge_stream_until_unlinked() {
+    let ((status, content_type, body), _) =
+        bridge_test_case(hyper::Method::GET, "/stream/node/transient", None, "").await;
+    assert_eq!(status, hyper::StatusCode::OK);
+    assert_eq!(
+        content_type,
+        Some(HeaderValue::from_static("text/event-stream"))
+    );
+    assert_eq!(
+        body.as_ref(),
+        b"id: 0\ndata: 1\n\nevent: synced\ndata: \n\nevent: unlinked\ndata: \n\n"
+    );
+}
+
+#[tokio::test]
+async fn bridge_stream_resume_from_last_event() {
+    let last_event_id = Some((hyper::header::HeaderName::from_static("last-event-id"), "4"));
+    let ((status, _, body), _) = bridge_test_case(
+        hyper::Method::GET,
+        "/stream/node/transient",
+        last_event_id,
+        "",
+    )
+    .await;
+    assert_eq!(status, hyper::StatusCode::OK);
+    assert!(body.starts_with(b"id: 5\ndata: 1\n\n"));
+}
+
+#[tokio::test]
+async fn bridge_stream_missing_lane() {
+    let ((status, _, _), _) =
+        bridge_test_case(hyper::Method::GET, "/stream/node/missing", None, "").await;
+    assert_eq!(status, hyper::StatusCode::NOT_FOUND);
+}
+
+// Read an event stream until a heartbeat is received.
+async fn stream_client(tx: mpsc::Sender<DuplexStream>, path: &str) -> String {
+    let (client, server) = tokio::io::duplex(BUFFER_SIZE);
+    tx.send(server).await.expect("Failed to open channel.");
+    let (mut sender, connection) = http1::handshake(TokioIo::new(client))
+        .await
+        .expect("HTTP handshake failed.");
+
+    let read = async move {
+        let mut request = Request::new(Full::new(Bytes::new()));
+        let uri = Uri::try_from(format!("http://example:8080{}", path)).expect("Bad URI.");
+        request.headers_mut().append(
+            hyper::header::HOST,
+            HeaderValue::from_str(uri.authority().unwrap().as_str()).unwrap(),
+        );
+        *request.uri_mut() = uri;
+        let mut body = sender
+            .send_request(request)
+            .await
+            .expect("Sending")
+            .into_body();
+        let mut received = String::new();
+        while !received.ends_with(": heartbeat\n\n") {
+            let frame = body
+                .frame()
+                .await
+                .expect("Stream ended early.")
+                .expect("Failed to read body.");
+            if let Ok(data) = frame.into_data() {
+                received.push_str(std::str::from_utf8(data.as_ref()).expect("Bad UTF8."));
+            }
+        }
+        received
+    };
+
+    tokio::select! {
+        received = read => received,
+        _ = connection => panic!("Connection closed early."),
+    }
+}
+
+#[tokio::test]
+async fn bridge_stream_heartbeat() {
+    with_timeout(async move {
+        let (tx, rx) = mpsc::channel(8);
+        let (find_tx, find_rx) = mpsc::channel(CHANNEL_SIZE.get());
+        let (commands_tx, _commands_rx) = mpsc::channel(8);
+        let server = run_bridge_server(rx, find_tx);
+        let plane = fake_bridge_plane(find_rx, commands_tx);
+        let client = stream_client(tx, "/stream/node/value?format=json");
+        let (_, received, _) = join3(server, client, plane).await;
+        assert_eq!(
+            received,
+            "id: 0\ndata: {\"a\":1,\"b\":\"x\"}\n\nevent: synced\ndata: \n\n: heartbeat\n\n"
+        );
+    })
+    .await
+}
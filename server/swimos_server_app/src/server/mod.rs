@@ -15,29 +15,71 @@
 use std::net::SocketAddr;
 
 use futures::future::BoxFuture;
-use swimos_utilities::{routing::RouteUri, trigger};
+use swimos_api::{
+    address::RelativeAddress,
+    agent::{Agent, DownlinkKind},
+};
+use swimos_form::read::RecognizerReadable;
+use swimos_model::Text;
+use swimos_runtime::{
+    agent::{DownlinkRequest, LinkRequest},
+    downlink::DownlinkOptions,
+};
+use swimos_utilities::{
+    routing::{RoutePattern, RouteUri},
+    trigger,
+};
 
 mod builder;
 mod error;
 mod http;
 mod runtime;
 mod store;
+mod watch;
 
 pub use builder::ServerBuilder;
-pub use error::UnresolvableRoute;
+pub use error::{
+    AddRouteError, PromoteError, ServerStopped, StopAgentError, UnresolvableRoute, WatchLaneError,
+};
 use tokio::sync::{mpsc, oneshot};
 
-use crate::error::ServerError;
+use crate::{error::ServerError, plane::PlanePeer};
 
-use self::runtime::StartAgentRequest;
+use self::runtime::{
+    AddRouteRequest, AgentRequest, ListAgentsRequest, PromoteRequest, SetPeersRequest,
+    StartAgentRequest, StopAgentRequest,
+};
+
+/// The status of an agent that is running in the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentStatus {
+    /// The agent is running normally.
+    Running,
+    /// The agent has been stopped but has not yet completed.
+    Stopping,
+}
+
+/// Description of an agent instance that is running in the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunningAgent {
+    /// The node URI of the agent.
+    pub route: RouteUri,
+    /// The status of the agent.
+    pub status: AgentStatus,
+}
 
 /// A handle used to interact with a running Swim server instance. This can be used to find the interface
-/// on which the server is listening, instruct the server to stop and explicitly start agents.
+/// on which the server is listening, instruct the server to stop, explicitly start agents and observe
+/// the values of lanes from within the same process.
 pub struct ServerHandle {
     stop_trigger: Option<trigger::Sender>,
     addr: Option<SocketAddr>,
     addr_rx: Option<oneshot::Receiver<SocketAddr>>,
-    start_agent_tx: mpsc::Sender<StartAgentRequest>,
+    agent_tx: mpsc::Sender<AgentRequest>,
+    add_route_tx: mpsc::Sender<AddRouteRequest>,
+    promote_tx: mpsc::Sender<PromoteRequest>,
+    peers_tx: mpsc::Sender<SetPeersRequest>,
+    link_requests_tx: mpsc::Sender<LinkRequest>,
 }
 
 /// Allows the server to be stopped externally.
@@ -45,13 +87,21 @@ impl ServerHandle {
     fn new(
         tx: trigger::Sender,
         addr_rx: oneshot::Receiver<SocketAddr>,
-        start_agent_tx: mpsc::Sender<StartAgentRequest>,
+        agent_tx: mpsc::Sender<AgentRequest>,
+        add_route_tx: mpsc::Sender<AddRouteRequest>,
+        promote_tx: mpsc::Sender<PromoteRequest>,
+        peers_tx: mpsc::Sender<SetPeersRequest>,
+        link_requests_tx: mpsc::Sender<LinkRequest>,
     ) -> Self {
         ServerHandle {
             stop_trigger: Some(tx),
             addr: None,
             addr_rx: Some(addr_rx),
-            start_agent_tx,
+            agent_tx,
+            add_route_tx,
+            promote_tx,
+            peers_tx,
+            link_requests_tx,
         }
     }
 
@@ -79,9 +129,10 @@ impl ServerHandle {
     /// * `route` - The node URI of the agent.
     pub async fn start_agent(&self, route: RouteUri) -> Result<(), UnresolvableRoute> {
         let (response_tx, response_rx) = oneshot::channel();
+        let request = StartAgentRequest::new(route, response_tx);
         if self
-            .start_agent_tx
-            .send(StartAgentRequest::new(route, response_tx))
+            .agent_tx
+            .send(AgentRequest::Start(request))
             .await
             .is_err()
         {
@@ -93,6 +144,190 @@ impl ServerHandle {
         }
     }
 
+    /// Attempt to start many agent instances in the server. All of the requests are submitted
+    /// before waiting for any of them to complete so this is much faster than calling
+    /// [`ServerHandle::start_agent`] for each route in turn. If any of the agents cannot be
+    /// started, the first failure is returned (the remaining agents will still be started).
+    ///
+    /// # Arguments
+    /// * `routes` - The node URIs of the agents.
+    pub async fn start_agents<I>(&self, routes: I) -> Result<(), UnresolvableRoute>
+    where
+        I: IntoIterator<Item = RouteUri>,
+    {
+        let mut responses = vec![];
+        for route in routes {
+            let (response_tx, response_rx) = oneshot::channel();
+            let request = StartAgentRequest::new(route, response_tx);
+            if self
+                .agent_tx
+                .send(AgentRequest::Start(request))
+                .await
+                .is_err()
+            {
+                return Err(UnresolvableRoute::Stopped);
+            }
+            responses.push(response_rx);
+        }
+        let mut first_error = None;
+        for response_rx in responses {
+            let result = response_rx.await.unwrap_or(Err(UnresolvableRoute::Stopped));
+            if let (Err(error), None) = (result, &first_error) {
+                first_error = Some(error);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Stop an agent instance that is running in the server. The agent will no longer receive
+    /// envelopes and will stop once any pending operations have completed. If an envelope is
+    /// subsequently addressed to the agent, a new instance may be started.
+    ///
+    /// # Arguments
+    /// * `route` - The node URI of the agent.
+    pub async fn stop_agent(&self, route: RouteUri) -> Result<(), StopAgentError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        let request = StopAgentRequest::new(route, response_tx);
+        if self
+            .agent_tx
+            .send(AgentRequest::Stop(request))
+            .await
+            .is_err()
+        {
+            Err(StopAgentError::Stopped)
+        } else if let Ok(result) = response_rx.await {
+            result
+        } else {
+            Err(StopAgentError::Stopped)
+        }
+    }
+
+    /// List the agent instances that are currently running in the server (including the
+    /// meta-agents), with their status.
+    pub async fn list_agents(&self) -> Result<Vec<RunningAgent>, ServerStopped> {
+        let (response_tx, response_rx) = oneshot::channel();
+        let request = ListAgentsRequest::new(response_tx);
+        if self
+            .agent_tx
+            .send(AgentRequest::List(request))
+            .await
+            .is_err()
+        {
+            Err(ServerStopped)
+        } else {
+            response_rx.await.map_err(|_| ServerStopped)
+        }
+    }
+
+    /// Register a new kind of agent with the running server. Once this completes, envelopes
+    /// addressed to nodes that match the route will start instances of the agent. The route
+    /// will be rejected if it is ambiguous with any of the routes that the server already has
+    /// (including those of the meta-agents).
+    ///
+    /// # Arguments
+    /// * `pattern` - The route pattern for the node URIs of the agent.
+    /// * `agent` - The agent definition (for example, an `AgentModel`).
+    pub async fn add_route<A>(&self, pattern: RoutePattern, agent: A) -> Result<(), AddRouteError>
+    where
+        A: Agent + Send + 'static,
+    {
+        let (response_tx, response_rx) = oneshot::channel();
+        if self
+            .add_route_tx
+            .send(AddRouteRequest::new(pattern, Box::new(agent), response_tx))
+            .await
+            .is_err()
+        {
+            Err(AddRouteError::Stopped)
+        } else if let Ok(result) = response_rx.await {
+            Ok(result?)
+        } else {
+            Err(AddRouteError::Stopped)
+        }
+    }
+
+    /// Promote a standby server (see [`crate::ReplicationConfig`]). The server will stop following
+    /// its primary and start to run the agents of the plane, which will restore the state that was
+    /// replicated from the primary.
+    pub async fn promote(&self) -> Result<(), PromoteError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        if self
+            .promote_tx
+            .send(PromoteRequest::new(response_tx))
+            .await
+            .is_err()
+        {
+            Err(PromoteError::Stopped)
+        } else if let Ok(result) = response_rx.await {
+            result
+        } else {
+            Err(PromoteError::Stopped)
+        }
+    }
+
+    /// Replace the peer servers that host the nodes that are not hosted by this server (see
+    /// [`crate::PlanePeer`]). Nodes that were previously resolved to a peer will be resolved again,
+    /// against the new peers, when they are next addressed.
+    ///
+    /// # Arguments
+    /// * `peers` - The new peer servers.
+    pub async fn set_peers(&self, peers: Vec<PlanePeer>) -> Result<(), ServerStopped> {
+        let (response_tx, response_rx) = oneshot::channel();
+        if self
+            .peers_tx
+            .send(SetPeersRequest::new(peers, response_tx))
+            .await
+            .is_err()
+        {
+            Err(ServerStopped)
+        } else {
+            response_rx.await.map_err(|_| ServerStopped)
+        }
+    }
+
+    /// Observe the value of a value-like lane (for example, a value lane or a value store exposed as a
+    /// lane) from within the same process. The returned receiver will hold the most recent value of
+    /// the lane and is kept up to date by the server runtime without the need for a WARP connection.
+    /// The receiver will contain nothing until the lane has been synchronized. If the agent is not
+    /// already running it will be started.
+    ///
+    /// # Arguments
+    /// * `node` - The node URI of the agent.
+    /// * `lane` - The name of the lane.
+    pub async fn watch_value_lane<T>(
+        &self,
+        node: impl Into<Text>,
+        lane: impl Into<Text>,
+    ) -> Result<tokio::sync::watch::Receiver<Option<T>>, WatchLaneError>
+    where
+        T: RecognizerReadable + Send + Sync + 'static,
+        T::Rec: Send,
+    {
+        let (promise_tx, promise_rx) = oneshot::channel();
+        let request = DownlinkRequest::new(
+            None,
+            RelativeAddress::new(node.into(), lane.into()),
+            DownlinkKind::Value,
+            DownlinkOptions::DEFAULT,
+            promise_tx,
+        );
+        if self
+            .link_requests_tx
+            .send(LinkRequest::Downlink(request))
+            .await
+            .is_err()
+        {
+            return Err(WatchLaneError::Stopped);
+        }
+        let io = match promise_rx.await {
+            Ok(result) => result?,
+            Err(_) => return Err(WatchLaneError::Stopped),
+        };
+        let (tx, rx) = tokio::sync::watch::channel(None);
+        tokio::spawn(watch::mirror_value_lane(io, tx));
+        Ok(rx)
+    }
+
     /// After this is called, the associated task will begin to stop.
     pub fn stop(&mut self) {
         if let Some(tx) = self.stop_trigger.take() {
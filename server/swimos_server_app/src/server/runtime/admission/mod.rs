@@ -0,0 +1,207 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    net::{IpAddr, SocketAddr},
+    num::NonZeroUsize,
+    sync::Arc,
+    time::Instant,
+};
+
+use futures::{future::ready, stream::BoxStream, StreamExt};
+use parking_lot::Mutex;
+use swimos_remote::{Listener, ListenerResult, Scheme};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::config::{HandshakeRateLimit, RemoteConnectionsConfig};
+
+#[cfg(test)]
+mod tests;
+
+/// Reasons for which an incoming connection can be refused.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum Refusal {
+    #[error("The maximum number of connections has been reached.")]
+    TooManyConnections,
+    #[error("The maximum number of connections from the remote address has been reached.")]
+    TooManyFromAddress,
+    #[error("The remote address is opening connections too quickly.")]
+    RateLimited,
+}
+
+/// Counts the handshakes started by a remote host in the current period.
+#[derive(Debug)]
+struct HandshakeWindow {
+    start: Instant,
+    count: usize,
+}
+
+#[derive(Debug)]
+struct AdmissionState {
+    open: HashSet<SocketAddr>,
+    per_ip: HashMap<IpAddr, usize>,
+    handshakes: HashMap<IpAddr, HandshakeWindow>,
+    last_pruned: Instant,
+}
+
+/// Admission control for incoming connections. A connection is refused, when it is accepted,
+/// if admitting it would exceed the global limit on the number of connections, the limit on the
+/// number of connections from a single remote host or the rate at which that host may open
+/// connections. The connection limits apply to established websocket connections (the number
+/// of connections that are still negotiating is bounded by the HTTP server).
+#[derive(Debug, Clone)]
+pub struct Admission {
+    max_connections: Option<NonZeroUsize>,
+    max_per_ip: Option<NonZeroUsize>,
+    rate_limit: Option<HandshakeRateLimit>,
+    state: Arc<Mutex<AdmissionState>>,
+}
+
+impl Admission {
+    pub fn new(config: &RemoteConnectionsConfig) -> Self {
+        Admission {
+            max_connections: config.max_connections,
+            max_per_ip: config.max_connections_per_ip,
+            rate_limit: config.handshake_rate_limit,
+            state: Arc::new(Mutex::new(AdmissionState {
+                open: HashSet::new(),
+                per_ip: HashMap::new(),
+                handshakes: HashMap::new(),
+                last_pruned: Instant::now(),
+            })),
+        }
+    }
+
+    /// Determine whether a new connection from a remote host should be accepted.
+    pub fn admit(&self, host: IpAddr, now: Instant) -> Result<(), Refusal> {
+        let Admission {
+            max_connections,
+            max_per_ip,
+            rate_limit,
+            state,
+        } = self;
+        let mut guard = state.lock();
+        let AdmissionState {
+            open,
+            per_ip,
+            handshakes,
+            last_pruned,
+        } = &mut *guard;
+        if matches!(max_connections, Some(max) if open.len() >= max.get()) {
+            return Err(Refusal::TooManyConnections);
+        }
+        let from_host = per_ip.get(&host).copied().unwrap_or_default();
+        if matches!(max_per_ip, Some(max) if from_host >= max.get()) {
+            return Err(Refusal::TooManyFromAddress);
+        }
+        if let Some(HandshakeRateLimit {
+            max_handshakes,
+            period,
+        }) = rate_limit
+        {
+            // Discard the windows that have expired so that hosts that have stopped connecting
+            // are not retained indefinitely.
+            if now.duration_since(*last_pruned) >= *period {
+                handshakes.retain(|_, window| now.duration_since(window.start) < *period);
+                *last_pruned = now;
+            }
+            let window = handshakes.entry(host).or_insert(HandshakeWindow {
+                start: now,
+                count: 0,
+            });
+            if now.duration_since(window.start) >= *period {
+                window.start = now;
+                window.count = 0;
+            }
+            if window.count >= max_handshakes.get() {
+                return Err(Refusal::RateLimited);
+            }
+            window.count += 1;
+        }
+        Ok(())
+    }
+
+    /// Record that a websocket connection has been established with a remote.
+    pub fn opened(&self, addr: SocketAddr) {
+        let mut guard = self.state.lock();
+        let AdmissionState { open, per_ip, .. } = &mut *guard;
+        if open.insert(addr) {
+            *per_ip.entry(addr.ip()).or_default() += 1;
+        }
+    }
+
+    /// Record that a connection with a remote has closed. Addresses that were not registered with
+    /// [`Admission::opened`] (for example, outgoing connections) are ignored.
+    pub fn closed(&self, addr: SocketAddr) {
+        let mut guard = self.state.lock();
+        let AdmissionState { open, per_ip, .. } = &mut *guard;
+        if open.remove(&addr) {
+            if let Some(count) = per_ip.get_mut(&addr.ip()) {
+                *count -= 1;
+                if *count == 0 {
+                    per_ip.remove(&addr.ip());
+                }
+            }
+        }
+    }
+
+    /// Wrap a listener so that the connections that it produces are subject to admission
+    /// control. Refused connections are dropped immediately.
+    pub fn wrap_listener<L>(&self, listener: L) -> AdmissionListener<L> {
+        AdmissionListener {
+            listener,
+            admission: self.clone(),
+        }
+    }
+}
+
+/// A [`Listener`] that drops any connections that are refused by its [`Admission`].
+pub struct AdmissionListener<L> {
+    listener: L,
+    admission: Admission,
+}
+
+impl<Sock, L> Listener<Sock> for AdmissionListener<L>
+where
+    Sock: Unpin + Send + Sync + 'static,
+    L: Listener<Sock>,
+    L::AcceptStream: 'static,
+{
+    type AcceptStream = BoxStream<'static, ListenerResult<(Sock, Scheme, SocketAddr)>>;
+
+    fn into_stream(self) -> Self::AcceptStream {
+        let AdmissionListener {
+            listener,
+            admission,
+        } = self;
+        listener
+            .into_stream()
+            .filter(move |result| {
+                let admitted = match result {
+                    Ok((_, _, addr)) => match admission.admit(addr.ip(), Instant::now()) {
+                        Ok(()) => true,
+                        Err(reason) => {
+                            warn!(peer = %addr, reason = %reason, "Refusing incoming connection.");
+                            false
+                        }
+                    },
+                    Err(_) => true,
+                };
+                ready(admitted)
+            })
+            .boxed()
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    num::NonZeroUsize,
+    time::{Duration, Instant},
+};
+
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use swimos_remote::{Listener, ListenerResult, Scheme};
+use swimos_utilities::non_zero_usize;
+
+use crate::config::{HandshakeRateLimit, RemoteConnectionsConfig};
+
+use super::{Admission, Refusal};
+
+const HOST_A: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+const HOST_B: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 2));
+const PERIOD: Duration = Duration::from_secs(1);
+
+fn admission(
+    max_connections: Option<NonZeroUsize>,
+    max_connections_per_ip: Option<NonZeroUsize>,
+    handshake_rate_limit: Option<HandshakeRateLimit>,
+) -> Admission {
+    Admission::new(&RemoteConnectionsConfig {
+        max_connections,
+        max_connections_per_ip,
+        handshake_rate_limit,
+        ..Default::default()
+    })
+}
+
+fn addr(host: IpAddr, port: u16) -> SocketAddr {
+    SocketAddr::new(host, port)
+}
+
+#[test]
+fn unlimited_by_default() {
+    let admission = Admission::new(&RemoteConnectionsConfig::default());
+    let now = Instant::now();
+    for port in 0..100 {
+        assert_eq!(admission.admit(HOST_A, now), Ok(()));
+        admission.opened(addr(HOST_A, port));
+    }
+}
+
+#[test]
+fn global_connection_limit() {
+    let admission = admission(Some(non_zero_usize!(2)), None, None);
+    let now = Instant::now();
+
+    admission.opened(addr(HOST_A, 1));
+    assert_eq!(admission.admit(HOST_B, now), Ok(()));
+    admission.opened(addr(HOST_B, 1));
+    assert_eq!(
+        admission.admit(HOST_B, now),
+        Err(Refusal::TooManyConnections)
+    );
+
+    admission.closed(addr(HOST_A, 1));
+    assert_eq!(admission.admit(HOST_B, now), Ok(()));
+}
+
+#[test]
+fn per_address_connection_limit() {
+    let admission = admission(None, Some(non_zero_usize!(2)), None);
+    let now = Instant::now();
+
+    admission.opened(addr(HOST_A, 1));
+    admission.opened(addr(HOST_A, 2));
+    assert_eq!(
+        admission.admit(HOST_A, now),
+        Err(Refusal::TooManyFromAddress)
+    );
+    assert_eq!(admission.admit(HOST_B, now), Ok(()));
+
+    // Connections that were never registered (such as outgoing connections) are ignored.
+    admission.closed(addr(HOST_A, 3));
+    assert_eq!(
+        admission.admit(HOST_A, now),
+        Err(Refusal::TooManyFromAddress)
+    );
+
+    admission.closed(addr(HOST_A, 2));
+    assert_eq!(admission.admit(HOST_A, now), Ok(()));
+}
+
+#[test]
+fn handshake_rate_limit() {
+    let limit = HandshakeRateLimit {
+        max_handshakes: non_zero_usize!(2),
+        period: PERIOD,
+    };
+    let admission = admission(None, None, Some(limit));
+    let start = Instant::now();
+
+    assert_eq!(admission.admit(HOST_A, start), Ok(()));
+    assert_eq!(admission.admit(HOST_A, start), Ok(()));
+    assert_eq!(admission.admit(HOST_A, start), Err(Refusal::RateLimited));
+    assert_eq!(admission.admit(HOST_B, start), Ok(()));
+
+    let later = start + PERIOD / 2;
+    assert_eq!(admission.admit(HOST_A, later), Err(Refusal::RateLimited));
+
+    let next_period = start + PERIOD;
+    assert_eq!(admission.admit(HOST_A, next_period), Ok(()));
+}
+
+struct TestListener(Vec<SocketAddr>);
+
+impl Listener<u16> for TestListener {
+    type AcceptStream = BoxStream<'static, ListenerResult<(u16, Scheme, SocketAddr)>>;
+
+    fn into_stream(self) -> Self::AcceptStream {
+        stream::iter(self.0)
+            .map(|addr| Ok((addr.port(), Scheme::Ws, addr)))
+            .boxed()
+    }
+}
+
+#[tokio::test]
+async fn listener_drops_refused_connections() {
+    let admission = admission(None, Some(non_zero_usize!(1)), None);
+    admission.opened(addr(HOST_A, 1));
+
+    let listener = TestListener(vec![addr(HOST_A, 2), addr(HOST_B, 3), addr(HOST_A, 4)]);
+    let accepted = admission
+        .wrap_listener(listener)
+        .into_stream()
+        .map(|result| result.expect("Listener failed.").0)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(accepted, vec![3]);
+}
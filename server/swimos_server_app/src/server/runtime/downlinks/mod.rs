@@ -851,6 +851,12 @@ impl DownlinkRuntime {
                         },
                         config,
                     );
+                    // Event downlinks must see every event so are never conflated.
+                    let runtime = if matches!(kind, DownlinkKind::Value) {
+                        runtime.conflating()
+                    } else {
+                        runtime
+                    };
                     runtime.run().await;
                 }
                 DownlinkKind::MapEvent => {
@@ -17,7 +17,10 @@ use std::{
     io::ErrorKind,
     net::{IpAddr, Ipv4Addr, SocketAddr},
     num::NonZeroUsize,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 
@@ -142,6 +145,7 @@ struct FakeServerTask {
     connector: ServerConnector,
     stop: trigger::Receiver,
     endpoints: Arc<Mutex<HashMap<Uuid, Endpoint>>>,
+    registrations: Arc<AtomicUsize>,
 }
 
 impl FakeServerTask {
@@ -154,6 +158,7 @@ impl FakeServerTask {
                 connector,
                 stop: rx,
                 endpoints: Default::default(),
+                registrations: Default::default(),
             },
         )
     }
@@ -229,12 +234,17 @@ impl FakeServerTask {
         }
     }
 
+    fn registrations(&self) -> Arc<AtomicUsize> {
+        self.registrations.clone()
+    }
+
     async fn run(self) -> ServerConnector {
         let FakeServerTask {
             mut connector,
             mut stop,
             port,
             endpoints,
+            registrations,
         } = self;
 
         let addr = addr(port);
@@ -267,6 +277,7 @@ impl FakeServerTask {
                     ..
                 }))) => {
                     check_hosts(host.as_str(), URL);
+                    registrations.fetch_add(1, Ordering::Relaxed);
                     let result = if sock_addrs.iter().any(|a| a == &addr) {
                         Ok(EstablishedClient {
                             tx: attach_tx.clone(),
@@ -468,6 +479,58 @@ async fn open_remote_downlink() {
     .await;
 }
 
+#[tokio::test]
+async fn remote_downlinks_share_connection() {
+    run_downlinks_test(CONFIG, |context| async move {
+        let TestContext { connector } = context;
+
+        let requests = connector.link_requests();
+        let (stop_server, server_task) = FakeServerTask::new(PORT, connector);
+
+        let endpoints = server_task.endpoints();
+        let registrations = server_task.registrations();
+
+        let (value_tx, value_rx) = oneshot::channel();
+        let value_request = request_remote(DownlinkKind::Value, value_tx);
+        let (map_tx, map_rx) = oneshot::channel();
+        let map_request = request_remote(DownlinkKind::Map, map_tx);
+
+        let test = async move {
+            assert!(requests
+                .send(LinkRequest::Downlink(value_request))
+                .await
+                .is_ok());
+
+            let value_io = value_rx
+                .await
+                .expect("Stopped prematurely.")
+                .expect("Connection failed.");
+
+            // Hold the remote end so that the first downlink runtime remains active.
+            let (_dl_id, _rem_io) = endpoints.take_two_way_endpoint(false, REM_NODE);
+
+            assert!(requests
+                .send(LinkRequest::Downlink(map_request))
+                .await
+                .is_ok());
+
+            let _map_io = map_rx
+                .await
+                .expect("Stopped prematurely.")
+                .expect("Connection failed.");
+
+            assert_eq!(registrations.load(Ordering::Relaxed), 1);
+
+            assert!(stop_server.trigger());
+
+            expect_unlinked_value(value_io).await;
+        };
+
+        join(server_task.run(), test).await
+    })
+    .await;
+}
+
 async fn expect_unlinked_value(io: Io) {
     let (_writer, reader) = io;
     let mut read = FramedRead::new(reader, ValueNotificationDecoder::<i32>::default());
@@ -504,13 +567,14 @@ async fn verify_link_value_dl(id: Uuid, downlink: Io, socket: Io, node: &str) ->
         origin,
         path,
         envelope,
+        ..
     } = env;
     assert_eq!(origin, id);
     assert_eq!(path.node.as_str(), node);
     assert_eq!(path.lane.as_str(), LANE);
 
     match envelope {
-        Operation::Link => {}
+        Operation::Link(_) => {}
         ow => panic!("Unexpected envelope: {:?}", ow),
     }
 
@@ -546,6 +610,7 @@ async fn verify_link_value_dl(id: Uuid, downlink: Io, socket: Io, node: &str) ->
         origin,
         path,
         envelope,
+        ..
     } = env;
     assert_eq!(origin, id);
     assert_eq!(path.node.as_str(), node);
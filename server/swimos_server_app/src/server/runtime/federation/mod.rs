@@ -0,0 +1,458 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    future::Future,
+    num::NonZeroUsize,
+    str::FromStr,
+    sync::Arc,
+};
+
+use bytes::Bytes;
+use futures::{stream::SelectAll, SinkExt, StreamExt};
+use parking_lot::Mutex;
+use swimos_api::address::RelativeAddress;
+use swimos_introspection::UriForest;
+use swimos_messages::{
+    protocol::{
+        Notification, Operation, RawRequestMessage, RawRequestMessageDecoder,
+        RawRequestMessageEncoder, RawResponseMessageDecoder, RawResponseMessageEncoder,
+        RequestMessage, ResponseMessage,
+    },
+    remote_protocol::{AttachClient, LinkError},
+};
+use swimos_model::Text;
+use swimos_remote::SchemeHostPort;
+use swimos_utilities::{
+    byte_channel::{byte_channel, ByteReader, ByteWriter},
+    non_zero_usize,
+    routing::RouteUri,
+    trigger,
+};
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::codec::{FramedRead, FramedWrite};
+use tracing::{debug, trace};
+use uuid::Uuid;
+
+use crate::plane::PlanePeer;
+
+#[cfg(test)]
+mod tests;
+
+/// The default maximum number of nodes for which the peer that hosts them is cached.
+const DEFAULT_RESOLVED_CAPACITY: NonZeroUsize = non_zero_usize!(4096);
+
+/// Resolves the peer servers that host nodes which are not hosted locally. The route patterns of the
+/// peers are only checked the first time a node is resolved, after which the result is cached. The
+/// cache is bounded (the oldest entries are evicted when it is full) and is cleared when the peers
+/// change.
+#[derive(Debug)]
+pub struct Federation {
+    peers: Vec<PlanePeer>,
+    resolved: UriForest<usize>,
+    // The nodes in the cache, in the order in which they were resolved, so that the oldest can be
+    // evicted when the cache is full.
+    resolved_order: VecDeque<Text>,
+    capacity: NonZeroUsize,
+    links: HashMap<SchemeHostPort, PeerLinks>,
+}
+
+impl Federation {
+    pub fn new(peers: Vec<PlanePeer>) -> Self {
+        Self::with_capacity(peers, DEFAULT_RESOLVED_CAPACITY)
+    }
+
+    /// # Arguments
+    /// * `peers` - The peer servers.
+    /// * `capacity` - The maximum number of nodes for which the peer that hosts them is cached.
+    pub fn with_capacity(peers: Vec<PlanePeer>, capacity: NonZeroUsize) -> Self {
+        Federation {
+            peers,
+            resolved: UriForest::new(),
+            resolved_order: VecDeque::new(),
+            capacity,
+            links: HashMap::new(),
+        }
+    }
+
+    /// Replace the peer servers. All cached resolutions are discarded as they may refer to peers
+    /// that have been removed.
+    ///
+    /// # Arguments
+    /// * `peers` - The new peer servers.
+    pub fn set_peers(&mut self, peers: Vec<PlanePeer>) {
+        let Federation {
+            peers: current,
+            resolved,
+            resolved_order,
+            links,
+            ..
+        } = self;
+        *current = peers;
+        *resolved = UriForest::new();
+        resolved_order.clear();
+        links.retain(|host, _| current.iter().any(|peer| &peer.host == host));
+    }
+
+    /// Get the table of the lanes that the remotes have linked to through the connection to a peer.
+    ///
+    /// # Arguments
+    /// * `peer` - The host of the peer.
+    pub fn peer_links(&mut self, peer: &SchemeHostPort) -> PeerLinks {
+        self.links.entry(peer.clone()).or_default().clone()
+    }
+
+    /// Find the peer that hosts a node, if there is one.
+    ///
+    /// # Arguments
+    /// * `node` - The URI of the node.
+    pub fn resolve(&mut self, node: &str) -> Option<&SchemeHostPort> {
+        let Federation {
+            peers,
+            resolved,
+            resolved_order,
+            capacity,
+            ..
+        } = self;
+        if peers.is_empty() {
+            return None;
+        }
+        let index = if let Some(index) = resolved.get_mut(node) {
+            *index
+        } else {
+            let route = RouteUri::from_str(node).ok()?;
+            let index = peers.iter().position(|peer| {
+                peer.routes
+                    .iter()
+                    .any(|pattern| pattern.unapply_route_uri(&route).is_ok())
+            })?;
+            if resolved_order.len() >= capacity.get() {
+                if let Some(oldest) = resolved_order.pop_front() {
+                    resolved.remove(oldest.as_str());
+                }
+            }
+            resolved.insert(node, index);
+            resolved_order.push_back(Text::new(node));
+            index
+        };
+        peers.get(index).map(|peer| &peer.host)
+    }
+}
+
+/// Reasons that a proxy to a peer can fail.
+#[derive(Debug, Error)]
+pub enum PeerProxyError {
+    /// A connection could not be established with the peer.
+    #[error("Failed to connect to the peer.")]
+    ConnectionFailed,
+    /// The connection to the peer refused to attach a lane.
+    #[error("Failed to attach a lane to the peer connection: {0}")]
+    Link(#[from] LinkError),
+    /// Forwarding a message between the remote and the peer failed.
+    #[error("Forwarding a message failed: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// The remotes that have linked to each lane that is proxied to a peer. All remotes share the same
+/// connection to a peer so a lane must stay linked, on that connection, until the last remote that
+/// linked to it has unlinked.
+#[derive(Debug, Default, Clone)]
+pub struct PeerLinks {
+    inner: Arc<Mutex<HashMap<RelativeAddress<Text>, HashSet<Uuid>>>>,
+}
+
+impl PeerLinks {
+    fn link(&self, path: &RelativeAddress<Text>, remote: Uuid) {
+        self.inner
+            .lock()
+            .entry(path.clone())
+            .or_default()
+            .insert(remote);
+    }
+
+    // Returns whether there are no remotes still linked to the lane.
+    fn unlink(&self, path: &RelativeAddress<Text>, remote: Uuid) -> bool {
+        let mut guard = self.inner.lock();
+        if let Some(remotes) = guard.get_mut(path) {
+            remotes.remove(&remote);
+            if remotes.is_empty() {
+                guard.remove(path);
+                true
+            } else {
+                false
+            }
+        } else {
+            true
+        }
+    }
+}
+
+/// The state of the link from a single remote to a lane that is proxied to a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LinkState {
+    /// The remote has requested a link that the peer has not yet confirmed.
+    Linking,
+    Linked,
+    /// The remote was the last to unlink from the lane so the unlink was forwarded to the peer.
+    Unlinking,
+}
+
+/// The links from a single remote to the lanes of a node that is proxied to a peer. The peer sends
+/// the responses for a lane to every remote that has attached to it so these are used to discard
+/// the responses that are not meant for the remote.
+struct RemoteLinks {
+    source: Uuid,
+    node: Text,
+    states: HashMap<Text, LinkState>,
+    shared: PeerLinks,
+}
+
+impl RemoteLinks {
+    fn new(source: Uuid, node: Text, shared: PeerLinks) -> Self {
+        RemoteLinks {
+            source,
+            node,
+            states: HashMap::new(),
+            shared,
+        }
+    }
+
+    fn path(&self, lane: &Text) -> RelativeAddress<Text> {
+        RelativeAddress::new(self.node.clone(), lane.clone())
+    }
+
+    // Returns whether the request should be forwarded to the peer.
+    fn on_request(&mut self, lane: &Text, operation: &Operation<Bytes>) -> bool {
+        match operation {
+            Operation::Link(_) | Operation::Sync(_) | Operation::SyncRange(..) => {
+                let state = self
+                    .states
+                    .entry(lane.clone())
+                    .or_insert(LinkState::Linking);
+                if *state == LinkState::Unlinking {
+                    *state = LinkState::Linking;
+                }
+                self.shared.link(&self.path(lane), self.source);
+                true
+            }
+            Operation::Unlink => match self.states.get(lane).copied() {
+                Some(LinkState::Linking | LinkState::Linked) => {
+                    if self.shared.unlink(&self.path(lane), self.source) {
+                        self.states.insert(lane.clone(), LinkState::Unlinking);
+                        true
+                    } else {
+                        self.states.remove(lane);
+                        false
+                    }
+                }
+                Some(LinkState::Unlinking) => true,
+                None => false,
+            },
+            Operation::Ack | Operation::Command(_) => true,
+        }
+    }
+
+    // Returns whether the response should be forwarded to the remote.
+    fn on_response(&mut self, lane: &Text, notification: &Notification<Bytes, Bytes>) -> bool {
+        let state = self.states.get(lane).copied();
+        match notification {
+            Notification::Linked => {
+                if state == Some(LinkState::Linking) {
+                    self.states.insert(lane.clone(), LinkState::Linked);
+                    true
+                } else {
+                    false
+                }
+            }
+            Notification::Unlinked(_) => {
+                if self.states.remove(lane).is_some() {
+                    self.shared.unlink(&self.path(lane), self.source);
+                    true
+                } else {
+                    false
+                }
+            }
+            _ => state == Some(LinkState::Linked),
+        }
+    }
+
+    // Release the links of the remote, returning the lanes that no remote is still linked to.
+    fn release(self) -> Vec<Text> {
+        let RemoteLinks {
+            source,
+            node,
+            states,
+            shared,
+        } = self;
+        states
+            .into_iter()
+            .filter(|(_, state)| *state != LinkState::Unlinking)
+            .filter_map(|(lane, _)| {
+                let path = RelativeAddress::new(node.clone(), lane);
+                if shared.unlink(&path, source) {
+                    Some(path.lane)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+type RequestWriter = FramedWrite<ByteWriter, RawRequestMessageEncoder>;
+type ResponseReader = FramedRead<ByteReader, RawResponseMessageDecoder>;
+
+/// Proxy the messages between a remote and a node that is hosted by a peer. The requests that the
+/// remote sends to each lane of the node are forwarded over a connection to the peer. As all
+/// remotes share the same connection to the peer, the responses from the peer are only sent back
+/// to the remote for the lanes that it has linked to itself. A lane is only unlinked from the peer
+/// when the last remote that linked to it unlinks (or stops).
+///
+/// # Arguments
+/// * `source` - The ID of the remote.
+/// * `node` - The URI of the node.
+/// * `io` - Channels to send responses to, and receive requests from, the remote.
+/// * `connect` - Completes with a channel to attach lanes to the connection to the peer.
+/// * `links` - The lanes that the remotes have linked to through the connection to the peer.
+/// * `buffer_size` - The size of the buffers for the channels to the peer connection.
+/// * `stop_signal` - Signal to stop the proxy.
+pub async fn proxy_node<F>(
+    source: Uuid,
+    node: Text,
+    io: (ByteWriter, ByteReader),
+    connect: F,
+    links: PeerLinks,
+    buffer_size: NonZeroUsize,
+    mut stop_signal: trigger::Receiver,
+) -> Result<(), PeerProxyError>
+where
+    F: Future<Output = Result<mpsc::Sender<AttachClient>, PeerProxyError>>,
+{
+    let (output, input) = io;
+    let attach_tx = tokio::select! {
+        biased;
+        _ = &mut stop_signal => return Ok(()),
+        result = connect => result?,
+    };
+    debug!(node = %node, "Connected to the peer hosting a node.");
+
+    let mut requests = FramedRead::new(input, RawRequestMessageDecoder);
+    let mut responses = FramedWrite::new(output, RawResponseMessageEncoder);
+
+    let mut lanes: HashMap<Text, RequestWriter> = HashMap::new();
+    let mut peer_responses: SelectAll<ResponseReader> = SelectAll::new();
+    let mut remote_links = RemoteLinks::new(source, node.clone(), links);
+
+    let result = loop {
+        tokio::select! {
+            biased;
+            _ = &mut stop_signal => break Ok(()),
+            Some(result) = peer_responses.next(), if !peer_responses.is_empty() => {
+                let response = match result {
+                    Ok(response) => response,
+                    Err(err) => break Err(err.into()),
+                };
+                let lane = Text::new(response.path.lane.as_str());
+                if remote_links.on_response(&lane, &response.envelope) {
+                    trace!(response = ?response, "Forwarding a response from the peer.");
+                    if let Err(err) = responses.send(response).await {
+                        break Err(err.into());
+                    }
+                } else {
+                    trace!(response = ?response, "Discarding a response for a lane that the remote has not linked to.");
+                }
+            }
+            maybe_request = requests.next() => {
+                let Some(result) = maybe_request else {
+                    debug!(node = %node, "The remote stopped sending requests to a proxied node.");
+                    break Ok(());
+                };
+                let request = match result {
+                    Ok(request) => request,
+                    Err(err) => break Err(err.into()),
+                };
+                let lane = Text::new(request.path.lane.as_str());
+                if !remote_links.on_request(&lane, &request.envelope) {
+                    // Either the remote is not linked to the lane or other remotes are still linked to
+                    // it so the unlink is answered without involving the peer.
+                    trace!(request = ?request, "Unlinking a remote from a lane without unlinking it from the peer.");
+                    let unlinked = ResponseMessage::<_, Bytes, Bytes>::unlinked(source, request.path, None);
+                    if let Err(err) = responses.send(unlinked).await {
+                        break Err(err.into());
+                    }
+                    continue;
+                }
+                trace!(request = ?request, "Forwarding a request to the peer.");
+                let writer = if let Some(writer) = lanes.get_mut(&lane) {
+                    writer
+                } else {
+                    let path = RelativeAddress::new(node.clone(), lane.clone());
+                    match attach_lane(source, path, &attach_tx, buffer_size).await {
+                        Ok((writer, reader)) => {
+                            peer_responses.push(reader);
+                            lanes.entry(lane).or_insert(writer)
+                        }
+                        Err(err) => break Err(err),
+                    }
+                };
+                if let Err(err) = writer.send(request).await {
+                    break Err(err.into());
+                }
+            }
+        }
+    };
+
+    // Unlink the lanes, from the peer, that no other remote is linked to.
+    for lane in remote_links.release() {
+        if let Some(writer) = lanes.get_mut(&lane) {
+            let path = RelativeAddress::new(node.as_str(), lane.as_str());
+            let unlink: RawRequestMessage<'_, &str> = RequestMessage::unlink(source, path);
+            if writer.send(unlink).await.is_err() {
+                debug!(node = %node, lane = %lane, "Failed to unlink a proxied lane from the peer.");
+            }
+        }
+    }
+    result
+}
+
+async fn attach_lane(
+    source: Uuid,
+    path: RelativeAddress<Text>,
+    attach_tx: &mpsc::Sender<AttachClient>,
+    buffer_size: NonZeroUsize,
+) -> Result<(RequestWriter, ResponseReader), PeerProxyError> {
+    debug!(path = %path, "Attaching a proxied lane to the peer connection.");
+    let (request_tx, request_rx) = byte_channel(buffer_size);
+    let (response_tx, response_rx) = byte_channel(buffer_size);
+    let (done_tx, done_rx) = oneshot::channel();
+    attach_tx
+        .send(AttachClient::AttachDownlink {
+            downlink_id: source,
+            path,
+            sender: response_tx,
+            receiver: request_rx,
+            done: done_tx,
+        })
+        .await
+        .map_err(|_| PeerProxyError::ConnectionFailed)?;
+    done_rx
+        .await
+        .map_err(|_| PeerProxyError::ConnectionFailed)??;
+    Ok((
+        FramedWrite::new(request_tx, RawRequestMessageEncoder),
+        FramedRead::new(response_rx, RawResponseMessageDecoder),
+    ))
+}
@@ -0,0 +1,503 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::{future::ready, SinkExt, StreamExt};
+use swimos_api::address::RelativeAddress;
+use swimos_messages::{
+    protocol::{
+        Notification, Operation, RawRequestMessageDecoder, RawRequestMessageEncoder,
+        RawResponseMessageDecoder, RawResponseMessageEncoder, RequestMessage, ResponseMessage,
+    },
+    remote_protocol::AttachClient,
+};
+use swimos_model::Text;
+use swimos_utilities::{
+    byte_channel::{byte_channel, ByteReader, ByteWriter},
+    non_zero_usize,
+    routing::RoutePattern,
+    trigger,
+};
+use tokio::sync::mpsc;
+use tokio_util::codec::{FramedRead, FramedWrite};
+use uuid::Uuid;
+
+use crate::plane::PlanePeer;
+
+use super::{proxy_node, Federation, PeerLinks};
+
+const PEER1: &str = "ws://peer1:8080";
+const PEER2: &str = "ws://peer2:8080";
+
+fn make_peer(host: &str, routes: &[&str]) -> PlanePeer {
+    routes.iter().fold(
+        PlanePeer::new(host).expect("Invalid host."),
+        |peer, route| peer.add_route(RoutePattern::parse_str(route).expect("Invalid route.")),
+    )
+}
+
+#[test]
+fn resolve_peer_for_node() {
+    let mut federation = Federation::new(vec![
+        make_peer(PEER1, &["/first/:id"]),
+        make_peer(PEER2, &["/second/:id", "/other"]),
+    ]);
+
+    let peer = federation.resolve("/first/a").expect("No peer.");
+    assert_eq!(peer.to_string(), PEER1);
+
+    let peer = federation.resolve("/second/a").expect("No peer.");
+    assert_eq!(peer.to_string(), PEER2);
+
+    let peer = federation.resolve("/other").expect("No peer.");
+    assert_eq!(peer.to_string(), PEER2);
+
+    assert!(federation.resolve("/third/a").is_none());
+    assert!(federation.resolve("/first").is_none());
+}
+
+#[test]
+fn resolution_is_cached() {
+    let mut federation = Federation::new(vec![make_peer(PEER1, &["/node/:id"])]);
+
+    assert!(federation.resolve("/node/a").is_some());
+    assert_eq!(federation.resolved.get_mut("/node/a"), Some(&mut 0));
+    assert!(federation.resolved.get_mut("/node/b").is_none());
+
+    let peer = federation.resolve("/node/a").expect("No peer.");
+    assert_eq!(peer.to_string(), PEER1);
+}
+
+#[test]
+fn resolution_cache_is_bounded() {
+    let mut federation =
+        Federation::with_capacity(vec![make_peer(PEER1, &["/node/:id"])], non_zero_usize!(2));
+
+    assert!(federation.resolve("/node/a").is_some());
+    assert!(federation.resolve("/node/b").is_some());
+    assert!(federation.resolve("/node/c").is_some());
+
+    assert!(federation.resolved.get_mut("/node/a").is_none());
+    assert_eq!(federation.resolved.get_mut("/node/b"), Some(&mut 0));
+    assert_eq!(federation.resolved.get_mut("/node/c"), Some(&mut 0));
+    assert_eq!(federation.resolved_order.len(), 2);
+}
+
+#[test]
+fn changing_peers_clears_cache() {
+    let mut federation = Federation::new(vec![
+        make_peer(PEER1, &["/node/:id"]),
+        make_peer(PEER2, &["/other/:id"]),
+    ]);
+
+    let peer = federation.resolve("/node/a").expect("No peer.");
+    assert_eq!(peer.to_string(), PEER1);
+
+    federation.set_peers(vec![make_peer(PEER2, &["/node/:id"])]);
+    assert!(federation.resolved.get_mut("/node/a").is_none());
+
+    let peer = federation.resolve("/node/a").expect("No peer.");
+    assert_eq!(peer.to_string(), PEER2);
+    assert!(federation.resolve("/other/a").is_none());
+}
+
+#[test]
+fn first_matching_peer_wins() {
+    let mut federation = Federation::new(vec![
+        make_peer(PEER1, &["/node/:id"]),
+        make_peer(PEER2, &["/node/:name"]),
+    ]);
+
+    let peer = federation.resolve("/node/a").expect("No peer.");
+    assert_eq!(peer.to_string(), PEER1);
+}
+
+const NODE: &str = "/node";
+const LANE: &str = "lane";
+const BUFFER_SIZE: std::num::NonZeroUsize = non_zero_usize!(4096);
+const TIMEOUT: Duration = Duration::from_secs(5);
+const OTHER_LANE: &str = "other";
+
+#[tokio::test]
+async fn proxy_forwards_messages() {
+    let source = Uuid::from_u128(1);
+    let peer_id = Uuid::from_u128(2);
+
+    let (request_tx, request_rx) = byte_channel(BUFFER_SIZE);
+    let (response_tx, response_rx) = byte_channel(BUFFER_SIZE);
+    let (attach_tx, mut attach_rx) = mpsc::channel(8);
+    let (stop_tx, stop_rx) = trigger::trigger();
+
+    let proxy = proxy_node(
+        source,
+        Text::new(NODE),
+        (response_tx, request_rx),
+        ready(Ok(attach_tx)),
+        PeerLinks::default(),
+        BUFFER_SIZE,
+        stop_rx,
+    );
+
+    let test_case = async move {
+        let mut requests = FramedWrite::new(request_tx, RawRequestMessageEncoder);
+        let mut responses = FramedRead::new(response_rx, RawResponseMessageDecoder);
+
+        let path = RelativeAddress::new(NODE, LANE);
+        requests
+            .send(RequestMessage::<_, Bytes>::link(source, path))
+            .await
+            .expect("Sending request failed.");
+
+        let Some(AttachClient::AttachDownlink {
+            downlink_id,
+            path,
+            sender,
+            receiver,
+            done,
+        }) = attach_rx.recv().await
+        else {
+            panic!("Expected a downlink attachment.");
+        };
+        assert_eq!(downlink_id, source);
+        assert_eq!(path, RelativeAddress::new(Text::new(NODE), Text::new(LANE)));
+        done.send(Ok(())).expect("Proxy stopped.");
+
+        let mut peer_requests = FramedRead::new(receiver, RawRequestMessageDecoder);
+        let mut peer_responses = FramedWrite::new(sender, RawResponseMessageEncoder);
+
+        let forwarded = peer_requests
+            .next()
+            .await
+            .expect("Proxy stopped.")
+            .expect("Invalid request.");
+        assert_eq!(forwarded.path.node.as_str(), NODE);
+        assert_eq!(forwarded.path.lane.as_str(), LANE);
+        assert!(matches!(forwarded.envelope, Operation::Link(_)));
+
+        requests
+            .send(RequestMessage::command(
+                source,
+                RelativeAddress::new(NODE, LANE),
+                Bytes::from_static(b"5"),
+            ))
+            .await
+            .expect("Sending request failed.");
+
+        let forwarded = peer_requests
+            .next()
+            .await
+            .expect("Proxy stopped.")
+            .expect("Invalid request.");
+        match forwarded.envelope {
+            Operation::Command(body) => assert_eq!(body.as_ref(), b"5"),
+            ow => panic!("Unexpected envelope: {:?}", ow),
+        }
+        assert!(
+            attach_rx.try_recv().is_err(),
+            "Lane attached more than once."
+        );
+
+        peer_responses
+            .send(ResponseMessage::<_, Bytes, Bytes>::linked(
+                peer_id,
+                RelativeAddress::new(NODE, LANE),
+            ))
+            .await
+            .expect("Sending response failed.");
+
+        let response = responses
+            .next()
+            .await
+            .expect("Proxy stopped.")
+            .expect("Invalid response.");
+        assert_eq!(response.path.node.as_str(), NODE);
+        assert_eq!(response.path.lane.as_str(), LANE);
+        assert!(matches!(response.envelope, Notification::Linked));
+
+        stop_tx.trigger();
+    };
+
+    let (result, _) = tokio::time::timeout(TIMEOUT, futures::future::join(proxy, test_case))
+        .await
+        .expect("Test timed out.");
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn proxy_stops_when_remote_stops() {
+    let (request_tx, request_rx) = byte_channel(BUFFER_SIZE);
+    let (response_tx, _response_rx) = byte_channel(BUFFER_SIZE);
+    let (attach_tx, _attach_rx) = mpsc::channel(8);
+    let (_stop_tx, stop_rx) = trigger::trigger();
+
+    drop(request_tx);
+    let result = tokio::time::timeout(
+        TIMEOUT,
+        proxy_node(
+            Uuid::from_u128(1),
+            Text::new(NODE),
+            (response_tx, request_rx),
+            ready(Ok(attach_tx)),
+            PeerLinks::default(),
+            BUFFER_SIZE,
+            stop_rx,
+        ),
+    )
+    .await
+    .expect("Test timed out.");
+    assert!(result.is_ok());
+}
+
+type PeerRequests = FramedRead<ByteReader, RawRequestMessageDecoder>;
+type PeerResponses = FramedWrite<ByteWriter, RawResponseMessageEncoder>;
+
+async fn attach_to_peer(
+    attach_rx: &mut mpsc::Receiver<AttachClient>,
+    expected_lane: &str,
+) -> (PeerRequests, PeerResponses) {
+    let Some(AttachClient::AttachDownlink {
+        path,
+        sender,
+        receiver,
+        done,
+        ..
+    }) = attach_rx.recv().await
+    else {
+        panic!("Expected a downlink attachment.");
+    };
+    assert_eq!(path.lane.as_str(), expected_lane);
+    done.send(Ok(())).expect("Proxy stopped.");
+    (
+        FramedRead::new(receiver, RawRequestMessageDecoder),
+        FramedWrite::new(sender, RawResponseMessageEncoder),
+    )
+}
+
+fn event(lane: &'static str, body: &'static [u8]) -> ResponseMessage<&'static str, Bytes, Bytes> {
+    ResponseMessage::event(
+        Uuid::from_u128(2),
+        RelativeAddress::new(NODE, lane),
+        Bytes::from_static(body),
+    )
+}
+
+#[tokio::test]
+async fn proxy_only_forwards_responses_for_linked_lanes() {
+    let source = Uuid::from_u128(1);
+
+    let (request_tx, request_rx) = byte_channel(BUFFER_SIZE);
+    let (response_tx, response_rx) = byte_channel(BUFFER_SIZE);
+    let (attach_tx, mut attach_rx) = mpsc::channel(8);
+    let (stop_tx, stop_rx) = trigger::trigger();
+
+    let proxy = proxy_node(
+        source,
+        Text::new(NODE),
+        (response_tx, request_rx),
+        ready(Ok(attach_tx)),
+        PeerLinks::default(),
+        BUFFER_SIZE,
+        stop_rx,
+    );
+
+    let test_case = async move {
+        let mut requests = FramedWrite::new(request_tx, RawRequestMessageEncoder);
+        let mut responses = FramedRead::new(response_rx, RawResponseMessageDecoder);
+
+        // The remote only sends a command to the first lane so must not receive its events.
+        requests
+            .send(RequestMessage::command(
+                source,
+                RelativeAddress::new(NODE, OTHER_LANE),
+                Bytes::from_static(b"1"),
+            ))
+            .await
+            .expect("Sending request failed.");
+        let (mut other_requests, mut other_responses) =
+            attach_to_peer(&mut attach_rx, OTHER_LANE).await;
+        assert!(matches!(
+            other_requests.next().await,
+            Some(Ok(RequestMessage {
+                envelope: Operation::Command(_),
+                ..
+            }))
+        ));
+
+        requests
+            .send(RequestMessage::<_, Bytes>::link(
+                source,
+                RelativeAddress::new(NODE, LANE),
+            ))
+            .await
+            .expect("Sending request failed.");
+        let (mut peer_requests, mut peer_responses) = attach_to_peer(&mut attach_rx, LANE).await;
+        assert!(matches!(
+            peer_requests.next().await,
+            Some(Ok(RequestMessage {
+                envelope: Operation::Link(_),
+                ..
+            }))
+        ));
+
+        // Events for a lane that another remote linked to, through the same peer connection.
+        other_responses
+            .send(ResponseMessage::<_, Bytes, Bytes>::linked(
+                Uuid::from_u128(2),
+                RelativeAddress::new(NODE, OTHER_LANE),
+            ))
+            .await
+            .expect("Sending response failed.");
+        other_responses
+            .send(event(OTHER_LANE, b"secret"))
+            .await
+            .expect("Sending response failed.");
+
+        peer_responses
+            .send(ResponseMessage::<_, Bytes, Bytes>::linked(
+                Uuid::from_u128(2),
+                RelativeAddress::new(NODE, LANE),
+            ))
+            .await
+            .expect("Sending response failed.");
+        peer_responses
+            .send(event(LANE, b"2"))
+            .await
+            .expect("Sending response failed.");
+
+        let response = responses
+            .next()
+            .await
+            .expect("Proxy stopped.")
+            .expect("Invalid response.");
+        assert_eq!(response.path.lane.as_str(), LANE);
+        assert!(matches!(response.envelope, Notification::Linked));
+
+        let response = responses
+            .next()
+            .await
+            .expect("Proxy stopped.")
+            .expect("Invalid response.");
+        assert_eq!(response.path.lane.as_str(), LANE);
+        match response.envelope {
+            Notification::Event(body) => assert_eq!(body.as_ref(), b"2"),
+            ow => panic!("Unexpected envelope: {:?}", ow),
+        }
+
+        stop_tx.trigger();
+        drop(responses);
+    };
+
+    let (result, _) = tokio::time::timeout(TIMEOUT, futures::future::join(proxy, test_case))
+        .await
+        .expect("Test timed out.");
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn lane_stays_linked_while_other_remotes_are_linked() {
+    let source = Uuid::from_u128(1);
+    let other = Uuid::from_u128(3);
+    let links = PeerLinks::default();
+    links.link(
+        &RelativeAddress::new(Text::new(NODE), Text::new(LANE)),
+        other,
+    );
+
+    let (request_tx, request_rx) = byte_channel(BUFFER_SIZE);
+    let (response_tx, response_rx) = byte_channel(BUFFER_SIZE);
+    let (attach_tx, mut attach_rx) = mpsc::channel(8);
+    let (stop_tx, stop_rx) = trigger::trigger();
+
+    let proxy = proxy_node(
+        source,
+        Text::new(NODE),
+        (response_tx, request_rx),
+        ready(Ok(attach_tx)),
+        links.clone(),
+        BUFFER_SIZE,
+        stop_rx,
+    );
+
+    let test_case = async move {
+        let mut requests = FramedWrite::new(request_tx, RawRequestMessageEncoder);
+        let mut responses = FramedRead::new(response_rx, RawResponseMessageDecoder);
+
+        requests
+            .send(RequestMessage::<_, Bytes>::link(
+                source,
+                RelativeAddress::new(NODE, LANE),
+            ))
+            .await
+            .expect("Sending request failed.");
+        let (mut peer_requests, mut peer_responses) = attach_to_peer(&mut attach_rx, LANE).await;
+        assert!(matches!(
+            peer_requests.next().await,
+            Some(Ok(RequestMessage {
+                envelope: Operation::Link(_),
+                ..
+            }))
+        ));
+        peer_responses
+            .send(ResponseMessage::<_, Bytes, Bytes>::linked(
+                Uuid::from_u128(2),
+                RelativeAddress::new(NODE, LANE),
+            ))
+            .await
+            .expect("Sending response failed.");
+        let response = responses
+            .next()
+            .await
+            .expect("Proxy stopped.")
+            .expect("Invalid response.");
+        assert!(matches!(response.envelope, Notification::Linked));
+
+        // The other remote is still linked so the unlink is answered by the proxy.
+        requests
+            .send(RequestMessage::<_, Bytes>::unlink(
+                source,
+                RelativeAddress::new(NODE, LANE),
+            ))
+            .await
+            .expect("Sending request failed.");
+        let response = responses
+            .next()
+            .await
+            .expect("Proxy stopped.")
+            .expect("Invalid response.");
+        assert_eq!(response.path.lane.as_str(), LANE);
+        assert!(matches!(response.envelope, Notification::Unlinked(None)));
+
+        // Events for the other remote are no longer forwarded.
+        peer_responses
+            .send(event(LANE, b"3"))
+            .await
+            .expect("Sending response failed.");
+
+        stop_tx.trigger();
+        assert!(responses.next().await.is_none());
+        // The proxy stops without unlinking the lane from the peer.
+        assert!(peer_requests.next().await.is_none());
+    };
+
+    let (result, _) = tokio::time::timeout(TIMEOUT, futures::future::join(proxy, test_case))
+        .await
+        .expect("Test timed out.");
+    assert!(result.is_ok());
+    assert!(!links.unlink(
+        &RelativeAddress::new(Text::new(NODE), Text::new(LANE)),
+        source
+    ));
+}
@@ -12,11 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use uuid::Uuid;
 
 const REMOTE: u8 = 0;
 const PLANE: u8 = 1;
 const CLIENT: u8 = 2;
+const BRIDGE: u8 = 3;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IdKind {
@@ -54,6 +57,14 @@ impl IdIssuer {
     }
 }
 
+static BRIDGE_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Issue an ID for a connection opened by the HTTP WARP bridge. These are issued concurrently by
+/// the services handling HTTP requests so are drawn from a shared counter.
+pub fn next_bridge_id() -> Uuid {
+    make_id(BRIDGE, BRIDGE_COUNT.fetch_add(1, Ordering::Relaxed))
+}
+
 const fn make_id(tag: u8, count: u64) -> Uuid {
     let mut uuid_as_int = count as u128;
     uuid_as_int |= (tag as u128) << 120;
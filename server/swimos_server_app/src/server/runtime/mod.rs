@@ -13,11 +13,11 @@
 // limitations under the License.
 
 use futures::future::{join, Either};
-use futures::stream::{unfold, FuturesUnordered};
+use futures::stream::{self, select, unfold, FuturesUnordered};
 use futures::{FutureExt, Stream, StreamExt};
 use ratchet::{ExtensionProvider, SplittableExtension, WebSocket, WebSocketStream};
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
@@ -31,20 +31,26 @@ use swimos_api::error::{
 };
 use swimos_api::persistence::ServerPersistence;
 use swimos_api::{address::RelativeAddress, persistence::PlanePersistence};
-use swimos_introspection::IntrospectionConfig;
 use swimos_introspection::{register_introspection, AgentRegistration, IntrospectionResolver};
+use swimos_introspection::{AgentRecovery, IntrospectionConfig};
 use swimos_messages::remote_protocol::{
-    AgentResolutionError, AttachClient, FindNode, LinkError, NoSuchAgent, NodeConnectionRequest,
+    AgentHostedByPeer, AgentNotStarted, AgentResolutionError, AttachClient, FindNode, LinkError,
+    NoSuchAgent, NodeConnectionRequest,
 };
 use swimos_model::Text;
-use swimos_remote::{BadWarpUrl, RemoteTask, Scheme};
+use swimos_remote::dns::DnsResolver;
+use swimos_remote::{
+    race_connections, BadWarpUrl, RemoteCaptures, RemoteTask, Scheme, SchemeHostPort,
+    CONNECTION_ATTEMPT_DELAY,
+};
 use swimos_runtime::agent::{
-    AgentAttachmentRequest, AgentExecError, AgentRouteChannels, AgentRouteDescriptor,
-    AgentRouteTask, CombinedAgentConfig, DisconnectionReason, LinkRequest,
+    intercept::OutgoingInterceptor, prune::PrunePolicy, AgentAttachmentRequest, AgentExecError,
+    AgentRouteChannels, AgentRouteDescriptor, AgentRouteTask, CombinedAgentConfig,
+    DisconnectionReason, LinkRequest,
 };
 use swimos_utilities::routing::RouteUri;
 
-use swimos_remote::websocket::{RatchetError, Websockets};
+use swimos_remote::websocket::{RatchetError, WarpFeatures, WarpProtocol, Websockets};
 use swimos_remote::{ConnectionError, ExternalConnections, ListenerError};
 use swimos_utilities::byte_channel::{byte_channel, BudgetedFutureExt, ByteReader, ByteWriter};
 use swimos_utilities::routing::RoutePattern;
@@ -52,23 +58,36 @@ use swimos_utilities::trigger::{self, promise};
 use thiserror::Error;
 use tokio::sync::{mpsc, oneshot};
 use tokio::task::JoinError;
+use tokio_stream::wrappers::WatchStream;
 use tracing::{debug, error, info, info_span, warn, Instrument};
 use uuid::Uuid;
 
+use crate::cluster::{cluster_pattern, partitions_pattern, ClusterMetaAgent, PartitionMetaAgent};
 use crate::config::SwimServerConfig;
-use crate::plane::PlaneModel;
+use crate::egress::{run_bridge, AgentStarts};
+use crate::error::AmbiguousRoutes;
+use crate::flags::{feature_flags_pattern, FeatureFlagAgent};
+use crate::plane::{AgentStartPolicy, PlaneModel, PlanePeer};
+use crate::replication::{
+    replication_pattern, run_standby, ReplicationAgent, ReplicationConfig, ReplicationRole,
+    StandbyOutcome,
+};
 use crate::server::runtime::downlinks::DlTaskRequest;
-use crate::server::ServerHandle;
+use crate::server::{AgentStatus, RunningAgent, ServerHandle};
 use crate::Io;
 
+use self::admission::Admission;
 use self::downlinks::{DownlinkConnectionTask, ServerConnector};
+use self::federation::{proxy_node, Federation, PeerProxyError};
 use self::ids::{IdIssuer, IdKind};
 
-use super::error::UnresolvableRoute;
+use super::error::{PromoteError, StopAgentError, UnresolvableRoute};
 use super::{Server, ServerError};
 
+mod admission;
 mod downlinks;
-mod ids;
+mod federation;
+pub(super) mod ids;
 #[cfg(test)]
 mod tests;
 
@@ -103,12 +122,98 @@ impl<Net, Ws, Provider> Transport<Net, Ws, Provider> {
 
 pub struct StartAgentRequest {
     route: RouteUri,
-    response: oneshot::Sender<Result<(), UnresolvableRoute>>,
+    response: Option<oneshot::Sender<Result<(), UnresolvableRoute>>>,
 }
 
 impl StartAgentRequest {
     pub fn new(route: RouteUri, response: oneshot::Sender<Result<(), UnresolvableRoute>>) -> Self {
-        StartAgentRequest { route, response }
+        StartAgentRequest {
+            route,
+            response: Some(response),
+        }
+    }
+
+    /// A request, from the introspection system, to start an agent that has persisted state. No
+    /// response is sent for these requests.
+    fn recover(route: RouteUri) -> Self {
+        StartAgentRequest {
+            route,
+            response: None,
+        }
+    }
+}
+
+/// A request to stop a running agent.
+pub struct StopAgentRequest {
+    route: RouteUri,
+    response: oneshot::Sender<Result<(), StopAgentError>>,
+}
+
+impl StopAgentRequest {
+    pub fn new(route: RouteUri, response: oneshot::Sender<Result<(), StopAgentError>>) -> Self {
+        StopAgentRequest { route, response }
+    }
+}
+
+/// A request for the agents that are running in the server.
+pub struct ListAgentsRequest {
+    response: oneshot::Sender<Vec<RunningAgent>>,
+}
+
+impl ListAgentsRequest {
+    pub fn new(response: oneshot::Sender<Vec<RunningAgent>>) -> Self {
+        ListAgentsRequest { response }
+    }
+}
+
+/// Requests, from a [`ServerHandle`], to control the agents that are running in the server.
+pub enum AgentRequest {
+    Start(StartAgentRequest),
+    Stop(StopAgentRequest),
+    List(ListAgentsRequest),
+}
+
+/// A request to add a new agent route to a running server.
+pub struct AddRouteRequest {
+    pattern: RoutePattern,
+    agent: BoxAgent,
+    response: oneshot::Sender<Result<(), AmbiguousRoutes>>,
+}
+
+impl AddRouteRequest {
+    pub fn new(
+        pattern: RoutePattern,
+        agent: BoxAgent,
+        response: oneshot::Sender<Result<(), AmbiguousRoutes>>,
+    ) -> Self {
+        AddRouteRequest {
+            pattern,
+            agent,
+            response,
+        }
+    }
+}
+
+/// A request to promote a standby server.
+pub struct PromoteRequest {
+    response: oneshot::Sender<Result<(), PromoteError>>,
+}
+
+impl PromoteRequest {
+    pub fn new(response: oneshot::Sender<Result<(), PromoteError>>) -> Self {
+        PromoteRequest { response }
+    }
+}
+
+/// A request to replace the peers of a running server.
+pub struct SetPeersRequest {
+    peers: Vec<PlanePeer>,
+    response: oneshot::Sender<()>,
+}
+
+impl SetPeersRequest {
+    pub fn new(peers: Vec<PlanePeer>, response: oneshot::Sender<()>) -> Self {
+        SetPeersRequest { peers, response }
     }
 }
 
@@ -116,7 +221,7 @@ type ClientPromiseTx = oneshot::Sender<Result<EstablishedClient, NewClientError>
 type ClientPromiseRx = oneshot::Receiver<Result<EstablishedClient, NewClientError>>;
 
 enum ServerEvent<Sock, Ext> {
-    NewConnection(Result<(WebSocket<Sock, Ext>, SocketAddr), ListenerError>),
+    NewConnection(Result<(WebSocket<Sock, Ext>, SocketAddr, WarpProtocol), ListenerError>),
     FindRoute(FindNode),
     FailRoute(FindNode),
     RemoteStopped(SocketAddr, Result<(), JoinError>),
@@ -125,11 +230,20 @@ enum ServerEvent<Sock, Ext> {
     CmdChannelResult(Result<(), CmdLinkTimeout>),
     RemoteClientRequest(ClientRegistration),
     NewClient(
-        Result<(SocketAddr, WebSocket<Sock, Ext>), NewClientError>,
+        Result<(SocketAddr, WebSocket<Sock, Ext>, WarpProtocol), NewClientError>,
+        bool,
         ClientPromiseTx,
     ),
     LocalClient(AttachClient),
     StartAgent(StartAgentRequest),
+    StopAgent(StopAgentRequest),
+    ListAgents(ListAgentsRequest),
+    AddRoute(AddRouteRequest),
+    Promote(PromoteRequest),
+    SetPeers(SetPeersRequest),
+    StandbyStopped(StandbyOutcome),
+    ProxyStopped(Text, Result<(), PeerProxyError>),
+    MembershipChanged,
 }
 
 /// Response type, sent by the server, after receiving a [`ClientRegistration`].
@@ -155,18 +269,40 @@ pub struct ClientRegistration {
     scheme: Scheme,
     /// Addresses to try to connect to the remote.
     sock_addrs: Vec<SocketAddr>,
+    /// Whether the connection will carry requests forwarded to a peer.
+    forwarding: bool,
     /// Reply channel for the server task.
     responder: ClientPromiseTx,
 }
 
 impl ClientRegistration {
     fn new(scheme: Scheme, host: Text, sock_addrs: Vec<SocketAddr>) -> (Self, ClientPromiseRx) {
+        Self::with_forwarding(scheme, host, sock_addrs, false)
+    }
+
+    /// Request a connection to a peer that will carry forwarded requests. The connection is
+    /// negotiated such that the peer will not forward the requests again.
+    fn forwarded(
+        scheme: Scheme,
+        host: Text,
+        sock_addrs: Vec<SocketAddr>,
+    ) -> (Self, ClientPromiseRx) {
+        Self::with_forwarding(scheme, host, sock_addrs, true)
+    }
+
+    fn with_forwarding(
+        scheme: Scheme,
+        host: Text,
+        sock_addrs: Vec<SocketAddr>,
+        forwarding: bool,
+    ) -> (Self, ClientPromiseRx) {
         let (tx, rx) = oneshot::channel();
         (
             ClientRegistration {
                 scheme,
                 host,
                 sock_addrs,
+                forwarding,
                 responder: tx,
             },
             rx,
@@ -271,16 +407,28 @@ where
     }
 }
 
-fn start_req_stream(
-    maybe_rx: Option<mpsc::Receiver<StartAgentRequest>>,
-) -> impl Stream<Item = StartAgentRequest> + Send {
-    unfold(maybe_rx, |state| async move {
+fn agent_req_stream(
+    maybe_rx: Option<mpsc::Receiver<AgentRequest>>,
+    maybe_recovery_rx: Option<mpsc::Receiver<RouteUri>>,
+) -> impl Stream<Item = AgentRequest> + Send {
+    let requests = unfold(maybe_rx, |state| async move {
         if let Some(mut rx) = state {
             rx.recv().await.map(move |req| (req, Some(rx)))
         } else {
             None
         }
-    })
+    });
+    let recoveries = unfold(maybe_recovery_rx, |state| async move {
+        if let Some(mut rx) = state {
+            rx.recv().await.map(move |route| {
+                let request = AgentRequest::Start(StartAgentRequest::recover(route));
+                (request, Some(rx))
+            })
+        } else {
+            None
+        }
+    });
+    select(requests, recoveries)
 }
 
 impl<Net, Ws, Provider, Store> SwimServer<Net, Ws, Provider, Store>
@@ -301,16 +449,43 @@ where
     ) {
         let (tx, rx) = trigger::trigger();
         let (addr_tx, addr_rx) = oneshot::channel();
-        let (req_tx, req_rx) = mpsc::channel(8);
-        let fut = self.run_inner(rx, addr_tx, Some(req_rx), server_conn);
-        (fut, ServerHandle::new(tx, addr_rx, req_tx))
+        let (agent_tx, agent_rx) = mpsc::channel(8);
+        let (route_tx, route_rx) = mpsc::channel(8);
+        let (promote_tx, promote_rx) = mpsc::channel(8);
+        let (peers_tx, peers_rx) = mpsc::channel(8);
+        let link_requests_tx = server_conn.link_requests();
+        let fut = self.run_inner(
+            rx,
+            addr_tx,
+            Some(agent_rx),
+            route_rx,
+            promote_rx,
+            peers_rx,
+            server_conn,
+        );
+        (
+            fut,
+            ServerHandle::new(
+                tx,
+                addr_rx,
+                agent_tx,
+                route_tx,
+                promote_tx,
+                peers_tx,
+                link_requests_tx,
+            ),
+        )
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn run_inner(
         self,
         stop_signal: trigger::Receiver,
         addr_tx: oneshot::Sender<SocketAddr>,
-        start_requests_rx: Option<mpsc::Receiver<StartAgentRequest>>,
+        agent_requests_rx: Option<mpsc::Receiver<AgentRequest>>,
+        mut add_route_rx: mpsc::Receiver<AddRouteRequest>,
+        mut promote_rx: mpsc::Receiver<PromoteRequest>,
+        mut peers_rx: mpsc::Receiver<SetPeersRequest>,
         mut server_conn: ServerConnector,
     ) -> Result<(), ServerError> {
         let SwimServer {
@@ -328,20 +503,33 @@ where
         let websockets = Arc::new(websockets);
 
         let plane_store = store.open_plane(plane.name.as_str())?;
+        let mut federation = Federation::new(plane.peers);
+        let dns = networking.dns_resolver();
 
         let (bound_addr, listener) = networking.bind(addr).await?;
         info!(bound_addr = %bound_addr, "TCP listener bound.");
+        let admission = Admission::new(&config.remote);
+        let listener = admission.wrap_listener(listener);
         let _ = addr_tx.send(bound_addr);
         let mut remote_issuer = IdIssuer::new(IdKind::Remote);
 
         let (find_tx, mut find_rx) = mpsc::channel(config.find_route_channel_size.get());
+        let (peer_reg_tx, mut peer_reg_rx) =
+            mpsc::channel(config.client_request_channel_size.get());
         let mut remote_channels = HashMap::new();
+        // Connections to peers that carry forwarded requests are kept separate from those used
+        // by downlinks as they are negotiated differently.
+        let mut peer_channels = HashMap::new();
+        let mut remote_addrs = HashMap::new();
+        // Remotes that forward requests from a peer. These must never be forwarded again.
+        let mut forwarded_remotes = HashSet::new();
 
         let mut remote_tasks = FuturesUnordered::new();
         let mut agent_tasks = FuturesUnordered::new();
         let mut connection_tasks = FuturesUnordered::new();
         let mut cmd_connection_tasks = FuturesUnordered::new();
         let mut client_tasks = FuturesUnordered::new();
+        let mut proxy_tasks = FuturesUnordered::new();
 
         let mut web_server = websockets
             .wrap_listener(listener, ext_provider.clone(), find_tx.clone())
@@ -352,18 +540,99 @@ where
         let (remote_stop_tx, remote_stop_rx) = trigger::trigger();
         let mut remote_stop = Some(remote_stop_tx);
 
-        let mut routes = plane.routes.into_iter().collect();
-
-        let mut start_reqs = pin!(start_req_stream(start_requests_rx));
+        // A standby does not run the agents of the plane until it is promoted.
+        let (plane_routes, mut standby_routes) = match &plane.replication {
+            Some(ReplicationConfig {
+                role: ReplicationRole::Standby { .. },
+                ..
+            }) => (vec![], Some(plane.routes)),
+            _ => (plane.routes, None),
+        };
+        let explicit_start = plane.explicit_start;
+        let mut routes: Routes = plane_routes.into_iter().collect();
+        routes.require_explicit_start(&explicit_start);
+        if let Some(cluster) = &plane.cluster {
+            routes.append(
+                partitions_pattern(),
+                PartitionMetaAgent::new(cluster.clone()),
+            );
+            // The pulses of the members are only available if introspection is enabled.
+            if let Some(intro_config) = &introspection {
+                routes.append(
+                    cluster_pattern(),
+                    ClusterMetaAgent::new(cluster.clone(), intro_config.mesh_pulse_interval),
+                );
+            }
+        }
+        if plane.feature_flags {
+            routes.append(feature_flags_pattern(), FeatureFlagAgent);
+        }
+        let mut standby_tasks = FuturesUnordered::new();
+        if let Some(replication) = &plane.replication {
+            routes.append(
+                replication_pattern(),
+                ReplicationAgent::new(
+                    replication.log.clone(),
+                    &replication.secret,
+                    plane_store.clone(),
+                ),
+            );
+            if let ReplicationRole::Standby { primary } = &replication.role {
+                info!(primary = %primary, "Starting as a standby server.");
+                standby_tasks.push(run_standby(
+                    primary.clone(),
+                    &replication.secret,
+                    replication.retry_interval,
+                    replication.failover_timeout,
+                    plane_store.clone(),
+                    server_conn.link_requests(),
+                ));
+            }
+        }
 
-        let introspection_resolver = introspection.map(|intro_config| {
-            start_introspection(
-                intro_config,
-                config.channel_coop_budget,
-                remote_stop_rx.clone(),
-                &mut routes,
-            )
-        });
+        let (introspection_resolver, remote_captures, recovery_rx) = match introspection {
+            Some(intro_config) => {
+                let captures = if intro_config.capture_traffic {
+                    Some(RemoteCaptures::new(config.remote.capture_buffer_size))
+                } else {
+                    None
+                };
+                let node_uris = match plane_store.node_uris().await {
+                    Ok(node_uris) => node_uris,
+                    Err(error) => {
+                        warn!(error = %error, "Failed to list the agents with persisted state.");
+                        vec![]
+                    }
+                };
+                let (recovery_tx, recovery_rx) =
+                    mpsc::channel(config.find_route_channel_size.get());
+                let resolver = start_introspection(
+                    intro_config,
+                    config.channel_coop_budget,
+                    remote_stop_rx.clone(),
+                    captures.clone(),
+                    AgentRecovery::new(node_uris, recovery_tx),
+                    &mut routes,
+                );
+                (Some(resolver), captures, Some(recovery_rx))
+            }
+            None => (None, None, None),
+        };
+
+        let mut agent_reqs = pin!(agent_req_stream(agent_requests_rx, recovery_rx));
+
+        let mut agent_starts = AgentStarts::default();
+        let mut egress_tasks = plane
+            .egress
+            .into_iter()
+            .map(|bridge| {
+                run_bridge(
+                    bridge,
+                    agent_starts.subscribe(),
+                    server_conn.link_requests(),
+                )
+            })
+            .collect::<FuturesUnordered<_>>();
 
         let mut agents = Agents::new(
             routes,
@@ -374,7 +643,17 @@ where
             agent_stop_rx,
             server_conn.link_requests(),
             introspection_resolver,
-        );
+            plane.interceptor,
+            agent_starts,
+        )
+        .with_prune_policy(plane.prune_policy);
+
+        // When the membership of the cluster changes, agents for nodes that are now owned by
+        // another member must be stopped.
+        let mut membership_changes = match &plane.cluster {
+            Some(cluster) => WatchStream::from_changes(cluster.subscribe()).boxed(),
+            None => stream::pending().boxed(),
+        };
 
         let mut state = TaskState::Running;
 
@@ -387,13 +666,29 @@ where
                         Some((id, result)) = agent_tasks.next(), if !agent_tasks.is_empty() => ServerEvent::AgentStopped(id, result),
                         Some(reason) = connection_tasks.next(), if !connection_tasks.is_empty() => ServerEvent::ConnectionStopped(reason),
                         Some(result) = cmd_connection_tasks.next(), if !cmd_connection_tasks.is_empty() => ServerEvent::CmdChannelResult(result),
+                        Some((node, result)) = proxy_tasks.next(), if !proxy_tasks.is_empty() => ServerEvent::ProxyStopped(node, result),
                         Some(event) = client_tasks.next(), if !client_tasks.is_empty() => event,
-                        Some(req) = start_reqs.next() => ServerEvent::StartAgent(req),
+                        Some(req) = agent_reqs.next() => match req {
+                            AgentRequest::Start(req) => ServerEvent::StartAgent(req),
+                            AgentRequest::Stop(req) => ServerEvent::StopAgent(req),
+                            AgentRequest::List(req) => ServerEvent::ListAgents(req),
+                        },
+                        Some(req) = add_route_rx.recv() => ServerEvent::AddRoute(req),
+                        Some(req) = promote_rx.recv() => ServerEvent::Promote(req),
+                        Some(req) = peers_rx.recv() => ServerEvent::SetPeers(req),
+                        Some(_) = membership_changes.next() => ServerEvent::MembershipChanged,
+                        Some(outcome) = standby_tasks.next(), if !standby_tasks.is_empty() => ServerEvent::StandbyStopped(outcome),
+                        Some(_) = egress_tasks.next(), if !egress_tasks.is_empty() => continue,
+                        Some(reg) = peer_reg_rx.recv() => ServerEvent::RemoteClientRequest(reg),
                         maybe_result = web_server.next() => {
                             if let Some(result) = maybe_result {
                                 ServerEvent::NewConnection(result)
                             } else {
                                 info!("Server task moving to stopping downlinks.");
+                                // The links of a standby to its primary, and of the egress bridges, must be
+                                // released for the downlinks to stop.
+                                standby_tasks.clear();
+                                egress_tasks.clear();
                                 server_conn.stop();
                                 state = TaskState::StoppingDownlinks;
                                 continue;
@@ -406,6 +701,8 @@ where
                                 Some(DlTaskRequest::Local(local)) => ServerEvent::LocalClient(local),
                                 _ => {
                                     //The downlink task has failed unexpectedly so go straight to stopping agents.
+                                    standby_tasks.clear();
+                                    egress_tasks.clear();
                                     server_conn.stop();
                                     if let Some(stop) = agent_stop.take() {
                                         stop.trigger();
@@ -436,6 +733,7 @@ where
                         Some((id, result)) = agent_tasks.next(), if !agent_tasks.is_empty() => ServerEvent::AgentStopped(id, result),
                         Some(reason) = connection_tasks.next(), if !connection_tasks.is_empty() => ServerEvent::ConnectionStopped(reason),
                         Some(result) = cmd_connection_tasks.next(), if !cmd_connection_tasks.is_empty() => ServerEvent::CmdChannelResult(result),
+                        Some((node, result)) = proxy_tasks.next(), if !proxy_tasks.is_empty() => ServerEvent::ProxyStopped(node, result),
                         Some(find_route) = find_rx.recv() => ServerEvent::FindRoute(find_route),
                         else => continue,
                     }
@@ -458,6 +756,7 @@ where
                         },
                         Some(reason) = connection_tasks.next(), if !connection_tasks.is_empty() => ServerEvent::ConnectionStopped(reason),
                         Some(result) = cmd_connection_tasks.next(), if !cmd_connection_tasks.is_empty() => ServerEvent::CmdChannelResult(result),
+                        Some((node, result)) = proxy_tasks.next(), if !proxy_tasks.is_empty() => ServerEvent::ProxyStopped(node, result),
                         Some(find_route) = find_rx.recv() => ServerEvent::FailRoute(find_route),
                         else => continue,
                     }
@@ -475,6 +774,7 @@ where
                         },
                         Some(reason) = connection_tasks.next(), if !connection_tasks.is_empty() => ServerEvent::ConnectionStopped(reason),
                         Some(result) = cmd_connection_tasks.next(), if !cmd_connection_tasks.is_empty() => ServerEvent::CmdChannelResult(result),
+                        Some((node, result)) = proxy_tasks.next(), if !proxy_tasks.is_empty() => ServerEvent::ProxyStopped(node, result),
                         Some(find_route) = find_rx.recv() => ServerEvent::FailRoute(find_route),
                         else => continue,
                     }
@@ -482,18 +782,25 @@ where
             };
 
             match event {
-                ServerEvent::NewConnection(Ok((websocket, sock_addr))) => {
-                    info!(peer = %addr, "Accepting new client connection.");
+                ServerEvent::NewConnection(Ok((websocket, sock_addr, protocol))) => {
                     let id = remote_issuer.next_id();
+                    let protocol_features = protocol.features();
+                    info!(peer = %addr, remote_id = %id, "Accepting new client connection.");
                     let (attach_tx, task) = register_remote(
                         id,
                         sock_addr,
                         remote_stop_rx.clone(),
                         &config,
-                        websocket,
+                        (websocket, protocol),
                         find_tx.clone(),
+                        remote_captures.as_ref(),
                     );
+                    admission.opened(sock_addr);
+                    if protocol_features.contains(WarpFeatures::FORWARDED) {
+                        forwarded_remotes.insert(id);
+                    }
                     remote_channels.insert(sock_addr, attach_tx);
+                    remote_addrs.insert(id, sock_addr);
                     remote_tasks.push(task);
                 }
                 ServerEvent::NewConnection(Err(ListenerError::ListenerFailed(error))) => {
@@ -506,7 +813,17 @@ where
                     warn!(error = %error, "Accepting incoming connection failed.");
                 }
                 ServerEvent::RemoteStopped(id, result) => {
+                    admission.closed(id);
                     remote_channels.remove(&id);
+                    peer_channels.remove(&id);
+                    remote_addrs.retain(|remote_id, addr| {
+                        if *addr == id {
+                            forwarded_remotes.remove(remote_id);
+                            false
+                        } else {
+                            true
+                        }
+                    });
                     if let Err(error) = result {
                         error!(error = %error, remote_id = %id, "Remote connection task panicked.");
                     }
@@ -540,6 +857,14 @@ where
                         }
                     }
                 }
+                ServerEvent::ProxyStopped(node, result) => match result {
+                    Ok(_) => {
+                        debug!(node = %node, "A proxy to a peer stopped.");
+                    }
+                    Err(error) => {
+                        warn!(error = %error, node = %node, "A proxy to a peer failed.");
+                    }
+                },
                 ServerEvent::CmdChannelResult(result) => {
                     if let Err(err) = result {
                         error!(error = ?err, "Connecting a local command channel failed.");
@@ -564,12 +889,46 @@ where
                     lane,
                     request,
                 }) => {
-                    let node_store_fut = plane_store.node_store(node.as_str());
-                    let agent_tasks_ref = &agent_tasks;
-                    let result = agents.resolve_agent(node.clone(), move |name, route_task| {
-                        let task = route_task.run_agent_with_store(node_store_fut);
-                        agent_tasks_ref.push(attach_node(name, config.channel_coop_budget, task));
-                    });
+                    // Requests that have already been forwarded by a peer must be served locally.
+                    let forwarded = match &request {
+                        NodeConnectionRequest::Warp { source, .. } => {
+                            forwarded_remotes.contains(source)
+                        }
+                        NodeConnectionRequest::Http { .. } => false,
+                    };
+                    let partition_owner = match &plane.cluster {
+                        Some(cluster) if !forwarded && agents.is_partitioned(node.as_str()) => {
+                            cluster.remote_owner(node.as_str())
+                        }
+                        _ => None,
+                    };
+                    let result = if let Some(owner) = partition_owner {
+                        Err((node.clone(), Some(owner), false))
+                    } else {
+                        let node_store_fut = plane_store.node_store(node.as_str());
+                        let agent_tasks_ref = &agent_tasks;
+                        agents
+                            .resolve_agent(node.clone(), false, move |name, route_task| {
+                                let task = route_task.run_agent_with_store(node_store_fut);
+                                agent_tasks_ref.push(attach_node(
+                                    name,
+                                    config.channel_coop_budget,
+                                    task,
+                                ));
+                            })
+                            .map_err(|unresolved| match unresolved {
+                                Unresolved::NoRoute(node) => {
+                                    let peer = if forwarded {
+                                        None
+                                    } else {
+                                        federation.resolve(node.as_str()).cloned()
+                                    };
+                                    (node, peer, false)
+                                }
+                                // The node is hosted locally so must not be proxied to a peer.
+                                Unresolved::NotStarted(node) => (node, None, true),
+                            })
+                    };
                     match result {
                         Ok(AgentChannel {
                             id,
@@ -580,6 +939,7 @@ where
                                 info!(source = %source, node = %node, "Attempting to connect an agent to a remote.");
                                 let connect_task = attach_agent(
                                         source,
+                                        remote_addrs.get(&source).copied(),
                                         *id,
                                         attachment_tx.clone(),
                                         config.agent_runtime_buffer_size,
@@ -595,26 +955,82 @@ where
                                 }
                             }
                         },
-                        Err(node) => {
-                            debug!(node = %node, "Requested agent does not exist.");
-                            if let Err(AgentResolutionError::NotFound(NoSuchAgent {
-                                node, ..
-                            })) = request.fail(NoSuchAgent { node, lane }.into())
-                            {
-                                debug!(route = %node, "A remote stopped while a connection from it to an agent was pending.");
+                        Err((node, peer, not_started)) => match (request, peer) {
+                            (NodeConnectionRequest::Warp { promise, source }, Some(peer)) => {
+                                info!(source = %source, node = %node, peer = %peer, "Proxying a connection to an agent hosted by a peer.");
+                                let (request_tx, request_rx) =
+                                    byte_channel(config.agent_runtime_buffer_size);
+                                let (response_tx, response_rx) =
+                                    byte_channel(config.agent_runtime_buffer_size);
+                                if promise.send(Ok((request_tx, response_rx))).is_err() {
+                                    debug!(route = %node, "A remote stopped while a connection from it to a peer was pending.");
+                                } else {
+                                    let links = federation.peer_links(&peer);
+                                    let connect = connect_to_peer(peer, &dns, peer_reg_tx.clone());
+                                    let proxy_task = proxy_node(
+                                        source,
+                                        node.clone(),
+                                        (response_tx, request_rx),
+                                        connect,
+                                        links,
+                                        config.agent_runtime_buffer_size,
+                                        remote_stop_rx.clone(),
+                                    )
+                                    .map(move |result| (node, result))
+                                    .instrument(
+                                        info_span!("Peer proxy task.", remote_id = %source),
+                                    );
+                                    proxy_tasks.push(proxy_task);
+                                }
                             }
-                        }
+                            (NodeConnectionRequest::Http { promise }, Some(peer)) => {
+                                debug!(node = %node, peer = %peer, "Redirecting an HTTP request to the peer that hosts the agent.");
+                                let host = http_url(&peer);
+                                if promise
+                                    .send(Err(AgentHostedByPeer { node, host }.into()))
+                                    .is_err()
+                                {
+                                    debug!("A remote stopped while a HTTP request from it was pending.");
+                                }
+                            }
+                            (request, _) if not_started => {
+                                debug!(node = %node, "Requested agent has not been started.");
+                                if let Err(AgentResolutionError::NotStarted(AgentNotStarted {
+                                    node,
+                                    ..
+                                })) = request.fail(AgentNotStarted { node, lane }.into())
+                                {
+                                    debug!(route = %node, "A remote stopped while a connection from it to an agent was pending.");
+                                }
+                            }
+                            (request, _) => {
+                                debug!(node = %node, "Requested agent does not exist.");
+                                if let Err(AgentResolutionError::NotFound(NoSuchAgent {
+                                    node,
+                                    ..
+                                })) = request.fail(NoSuchAgent { node, lane }.into())
+                                {
+                                    debug!(route = %node, "A remote stopped while a connection from it to an agent was pending.");
+                                }
+                            }
+                        },
                     }
                 }
                 ServerEvent::RemoteClientRequest(ClientRegistration {
                     host,
                     scheme,
                     sock_addrs,
+                    forwarding,
                     responder,
                 }) => {
+                    let channels = if forwarding {
+                        &peer_channels
+                    } else {
+                        &remote_channels
+                    };
                     if let Some((sock_addr, attach_tx)) = sock_addrs
                         .iter()
-                        .find_map(|addr| remote_channels.get(addr).map(|tx| (*addr, tx.clone())))
+                        .find_map(|addr| channels.get(addr).map(|tx| (*addr, tx.clone())))
                     {
                         if responder
                             .send(Ok(EstablishedClient::new(attach_tx, sock_addr)))
@@ -627,23 +1043,35 @@ where
                         let ws = websockets.clone();
                         let provider = ext_provider.clone();
                         client_tasks.push(async move {
-                            let result =
-                                open_client(scheme, host, sock_addrs, net, ws, provider).await;
-                            ServerEvent::NewClient(result, responder)
+                            let result = open_client(
+                                scheme, host, sock_addrs, forwarding, net, ws, provider,
+                            )
+                            .await;
+                            ServerEvent::NewClient(result, forwarding, responder)
                         });
                     }
                 }
-                ServerEvent::NewClient(Ok((sock_addr, websocket)), responder) => {
+                ServerEvent::NewClient(
+                    Ok((sock_addr, websocket, protocol)),
+                    forwarding,
+                    responder,
+                ) => {
                     let id = remote_issuer.next_id();
                     let (attach_tx, task) = register_remote(
                         id,
                         sock_addr,
                         remote_stop_rx.clone(),
                         &config,
-                        websocket,
+                        (websocket, protocol),
                         find_tx.clone(),
+                        remote_captures.as_ref(),
                     );
-                    remote_channels.insert(sock_addr, attach_tx.clone());
+                    if forwarding {
+                        peer_channels.insert(sock_addr, attach_tx.clone());
+                    } else {
+                        remote_channels.insert(sock_addr, attach_tx.clone());
+                    }
+                    remote_addrs.insert(id, sock_addr);
                     remote_tasks.push(task);
                     if responder
                         .send(Ok(EstablishedClient::new(attach_tx, sock_addr)))
@@ -652,7 +1080,7 @@ where
                         info!("Request for client connection dropped before it was completed.");
                     }
                 }
-                ServerEvent::NewClient(Err(e), responder) => {
+                ServerEvent::NewClient(Err(e), _, responder) => {
                     if responder.send(Err(e)).is_err() {
                         info!(
                             "Request for client connection dropped before it failed to complete."
@@ -669,7 +1097,7 @@ where
                     let RelativeAddress { node, .. } = &path;
                     info!(source = %downlink_id, node = %node, "Attempting to connect a downlink to an agent.");
                     let node_store_fut = plane_store.node_store(node.as_str());
-                    let result = agents.resolve_agent(node.clone(), |name, route_task| {
+                    let result = agents.resolve_agent(node.clone(), false, |name, route_task| {
                         let task = route_task.run_agent_with_store(node_store_fut);
                         agent_tasks.push(attach_node(name, config.channel_coop_budget, task));
                     });
@@ -687,8 +1115,9 @@ where
                             );
                             connection_tasks.push(Either::Right(task));
                         }
-                        Err(node) => {
-                            warn!(node = %node, "Requested agent does not exist.");
+                        Err(unresolved) => {
+                            let node = unresolved.node();
+                            warn!(node = %node, reason = %unresolved, "Requested agent could not be resolved.");
                             if done.send(Err(LinkError::NoEndpoint(path))).is_err() {
                                 info!(node = %node, "Downlink request dropped before it was satisfied.");
                             }
@@ -705,10 +1134,15 @@ where
                         let RelativeAddress { node, .. } = &path;
                         info!(source = %agent_id, node = %node, "Attempting to connect a downlink to an agent.");
                         let node_store_fut = plane_store.node_store(node.as_str());
-                        let result = agents.resolve_agent(node.clone(), |name, route_task| {
-                            let task = route_task.run_agent_with_store(node_store_fut);
-                            agent_tasks.push(attach_node(name, config.channel_coop_budget, task));
-                        });
+                        let result =
+                            agents.resolve_agent(node.clone(), false, |name, route_task| {
+                                let task = route_task.run_agent_with_store(node_store_fut);
+                                agent_tasks.push(attach_node(
+                                    name,
+                                    config.channel_coop_budget,
+                                    task,
+                                ));
+                            });
                         match result {
                             Ok(AgentChannel {
                                 id, attachment_tx, ..
@@ -723,8 +1157,9 @@ where
                                 );
                                 cmd_connection_tasks.push(task);
                             }
-                            Err(node) => {
-                                warn!(node = %node, "Requested agent does not exist.");
+                            Err(unresolved) => {
+                                let node = unresolved.node();
+                                warn!(node = %node, reason = %unresolved, "Requested agent could not be resolved.");
                                 if done.send(Err(LinkError::NoEndpoint(path))).is_err() {
                                     info!(node = %node, "Command channel request dropped before it was satisfied.");
                                 }
@@ -737,7 +1172,7 @@ where
                     let node = Text::new(route.as_str());
                     let node_store_fut = plane_store.node_store(node.as_str());
                     let agent_tasks_ref = &agent_tasks;
-                    let result = agents.resolve_agent(node, move |name, route_task| {
+                    let result = agents.resolve_agent(node, true, move |name, route_task| {
                         let task = route_task.run_agent_with_store(node_store_fut);
                         agent_tasks_ref.push(attach_node(name, config.channel_coop_budget, task));
                     });
@@ -746,10 +1181,97 @@ where
                     } else {
                         Err(UnresolvableRoute::new(route))
                     };
-                    if response.send(resp_result).is_err() {
-                        info!("Agent start request dropped before it was satisfied.");
+                    match response {
+                        Some(response) => {
+                            if response.send(resp_result).is_err() {
+                                info!("Agent start request dropped before it was satisfied.");
+                            }
+                        }
+                        None => {
+                            if let Err(error) = resp_result {
+                                warn!(error = %error, "Failed to start a recoverable agent.");
+                            }
+                        }
                     }
                 }
+                ServerEvent::StopAgent(StopAgentRequest { route, response }) => {
+                    info!(route = %route, "Attempting to stop an agent instance.");
+                    let result = match agents.stop_agent(route.as_str()) {
+                        Ok(true) => Ok(()),
+                        Ok(false) => Err(StopAgentError::NotRunning { uri: route }),
+                        Err(_) => {
+                            warn!("Attempted to deregister an agent from metadata reporting but the reporting system had stopped.");
+                            Ok(())
+                        }
+                    };
+                    if response.send(result).is_err() {
+                        info!("Agent stop request dropped before it was satisfied.");
+                    }
+                }
+                ServerEvent::MembershipChanged => {
+                    if let Some(cluster) = &plane.cluster {
+                        for node in agents.partitioned_agents() {
+                            if let Some(owner) = cluster.remote_owner(node.as_str()) {
+                                info!(node = %node, owner = %owner, "Stopping an agent that is now owned by another member of the cluster.");
+                                if agents.stop_agent(node.as_str()).is_err() {
+                                    warn!("Attempted to deregister an agent from metadata reporting but the reporting system had stopped.");
+                                }
+                            }
+                        }
+                    }
+                }
+                ServerEvent::ListAgents(ListAgentsRequest { response }) => {
+                    if response.send(agents.list_agents()).is_err() {
+                        info!("Agent list request dropped before it was satisfied.");
+                    }
+                }
+                ServerEvent::AddRoute(AddRouteRequest {
+                    pattern,
+                    agent,
+                    response,
+                }) => {
+                    let result = agents.add_route(pattern, agent);
+                    match &result {
+                        Ok(_) => info!("Added a new agent route to the server."),
+                        Err(error) => warn!(error = %error, "Rejected an ambiguous agent route."),
+                    }
+                    if response.send(result).is_err() {
+                        info!("Add route request dropped before it was satisfied.");
+                    }
+                }
+                ServerEvent::Promote(PromoteRequest { response }) => {
+                    let result = if let Some(routes) = standby_routes.take() {
+                        // Dropping the task releases the stores that it holds for the agents.
+                        standby_tasks.clear();
+                        info!("Promoting the standby server.");
+                        agents.add_plane_routes(routes, &explicit_start);
+                        Ok(())
+                    } else {
+                        Err(PromoteError::NotStandby)
+                    };
+                    if response.send(result).is_err() {
+                        info!("Promote request dropped before it was satisfied.");
+                    }
+                }
+                ServerEvent::SetPeers(SetPeersRequest { peers, response }) => {
+                    info!(
+                        num_peers = peers.len(),
+                        "Replacing the peers of the server."
+                    );
+                    federation.set_peers(peers);
+                    if response.send(()).is_err() {
+                        info!("Set peers request dropped before it was satisfied.");
+                    }
+                }
+                ServerEvent::StandbyStopped(StandbyOutcome::Failover) => {
+                    if let Some(routes) = standby_routes.take() {
+                        info!("Promoting the standby server as the primary is unreachable.");
+                        agents.add_plane_routes(routes, &explicit_start);
+                    }
+                }
+                ServerEvent::StandbyStopped(StandbyOutcome::Stopped) => {
+                    warn!("The standby server stopped following its primary.");
+                }
             }
         }
 
@@ -757,6 +1279,47 @@ where
     }
 }
 
+// The base URL at which the HTTP lanes of a peer are served.
+fn http_url(peer: &SchemeHostPort) -> Text {
+    let scheme = match peer.scheme() {
+        Scheme::Ws => "http",
+        Scheme::Wss => "https",
+    };
+    Text::from(format!("{}://{}:{}", scheme, peer.host(), peer.port()))
+}
+
+// Resolve the address of a peer and register a connection to it with the server task.
+fn connect_to_peer<Dns>(
+    peer: SchemeHostPort,
+    dns: &Dns,
+    registrations: mpsc::Sender<ClientRegistration>,
+) -> impl Future<Output = Result<mpsc::Sender<AttachClient>, PeerProxyError>> + Send + 'static
+where
+    Dns: DnsResolver,
+{
+    let resolve = dns.resolve(peer.host().clone(), peer.port());
+    async move {
+        let sock_addrs = resolve.await.map_err(|error| {
+            warn!(error = %error, peer = %peer, "Failed to resolve the address of a peer.");
+            PeerProxyError::ConnectionFailed
+        })?;
+        let (registration, established) =
+            ClientRegistration::forwarded(*peer.scheme(), Text::from(peer.to_string()), sock_addrs);
+        registrations
+            .send(registration)
+            .await
+            .map_err(|_| PeerProxyError::ConnectionFailed)?;
+        match established.await {
+            Ok(Ok(EstablishedClient { tx, .. })) => Ok(tx),
+            Ok(Err(error)) => {
+                warn!(error = %error, peer = %peer, "Failed to open a connection to a peer.");
+                Err(PeerProxyError::ConnectionFailed)
+            }
+            Err(_) => Err(PeerProxyError::ConnectionFailed),
+        }
+    }
+}
+
 async fn attach_node<F>(
     node: Text,
     budget: Option<NonZeroUsize>,
@@ -774,8 +1337,9 @@ fn register_remote<S, E>(
     sock_addr: SocketAddr,
     stop: trigger::Receiver,
     config: &SwimServerConfig,
-    websocket: WebSocket<S, E>,
+    (websocket, protocol): (WebSocket<S, E>, WarpProtocol),
     find_tx: mpsc::Sender<FindNode>,
+    captures: Option<&RemoteCaptures>,
 ) -> (
     mpsc::Sender<AttachClient>,
     impl Future<Output = (SocketAddr, Result<(), JoinError>)>,
@@ -786,7 +1350,7 @@ where
 {
     let (attach_tx, attach_rx) = mpsc::channel(config.client_attachment_buffer_size.get());
 
-    let task = RemoteTask::new(
+    let mut task = RemoteTask::new(
         id,
         stop,
         websocket,
@@ -794,16 +1358,25 @@ where
         Some(find_tx),
         config.remote.registration_buffer_size,
         config.remote.close_timeout,
-    );
+    )
+    .with_protocol(protocol)
+    .with_limits(config.remote.limits);
+    if let Some(captures) = captures {
+        task = task.with_capture(captures.register(id, sock_addr));
+    }
+    let captures = captures.cloned();
+    let remote_task = async move {
+        task.run().await;
+        if let Some(captures) = captures {
+            captures.unregister(id);
+        }
+    };
 
     (
         attach_tx,
         with_sock_addr(
             sock_addr,
-            tokio::spawn(
-                task.run()
-                    .with_budget_or_default(config.channel_coop_budget),
-            ),
+            tokio::spawn(remote_task.with_budget_or_default(config.channel_coop_budget)),
         ),
     )
 }
@@ -822,6 +1395,12 @@ struct Agents {
     agent_stop_rx: trigger::Receiver,
     open_link_tx: mpsc::Sender<LinkRequest>,
     introspection_resolver: Option<IntrospectionResolver>,
+    interceptor: Option<Arc<dyn OutgoingInterceptor>>,
+    prune_policy: Option<Arc<dyn PrunePolicy>>,
+    agent_starts: AgentStarts,
+    // Agents that were stopped explicitly but whose tasks have not yet completed (with the number
+    // of instances of each).
+    stopping: HashMap<Text, usize>,
 }
 
 impl Agents {
@@ -831,6 +1410,8 @@ impl Agents {
         agent_stop_rx: trigger::Receiver,
         open_link_tx: mpsc::Sender<LinkRequest>,
         introspection_resolver: Option<IntrospectionResolver>,
+        interceptor: Option<Arc<dyn OutgoingInterceptor>>,
+        agent_starts: AgentStarts,
     ) -> Self {
         Agents {
             plane_issuer: IdIssuer::new(IdKind::Plane),
@@ -840,14 +1421,31 @@ impl Agents {
             agent_stop_rx,
             open_link_tx,
             introspection_resolver,
+            interceptor,
+            prune_policy: None,
+            agent_starts,
+            stopping: Default::default(),
         }
     }
 
+    fn with_prune_policy(mut self, prune_policy: Option<Arc<dyn PrunePolicy>>) -> Self {
+        self.prune_policy = prune_policy;
+        self
+    }
+
+    /// Find the running agent for a node, starting it if necessary.
+    ///
+    /// # Arguments
+    /// * `node` - The node URI of the agent.
+    /// * `explicit` - Whether this is an explicit request to start the agent. Agents for routes
+    ///   with the [`AgentStartPolicy::Explicit`] policy will only be started by such requests.
+    /// * `spawn_task` - Called to spawn the task for the agent, if it is started.
     fn resolve_agent<'a, F>(
         &'a mut self,
         node: Text,
+        explicit: bool,
         spawn_task: F,
-    ) -> Result<&'a AgentChannel, Text>
+    ) -> Result<&'a AgentChannel, Unresolved>
     where
         F: for<'b> FnOnce(Text, AgentRouteTask<'b, BoxAgent>),
     {
@@ -859,6 +1457,10 @@ impl Agents {
             agent_stop_rx,
             open_link_tx,
             introspection_resolver,
+            interceptor,
+            prune_policy,
+            agent_starts,
+            ..
         } = self;
         match agent_channels.entry(node) {
             Entry::Occupied(entry) => {
@@ -877,6 +1479,9 @@ impl Agents {
                                 .map(move |route| (route_uri, route))
                         })
                 {
+                    if route.start_policy == AgentStartPolicy::Explicit && !explicit {
+                        return Err(Unresolved::NotStarted(entry.into_key()));
+                    }
                     let id = plane_issuer.next_id();
                     let (attachment_tx, attachment_rx) =
                         mpsc::channel(config.runtime_config.attachment_queue_size.get());
@@ -884,6 +1489,7 @@ impl Agents {
                         mpsc::channel(config.runtime_config.agent_http_request_channel_size.get());
 
                     let Route {
+                        pattern,
                         agent,
                         disable_introspection,
                         ..
@@ -893,7 +1499,17 @@ impl Agents {
                     let node_reporting = if *disable_introspection {
                         None
                     } else if let Some(resolver) = introspection_resolver {
-                        match resolver.register_agent(id, route_uri.clone(), name.clone()) {
+                        let lane_config =
+                            config.agent_config.default_lane_config.unwrap_or_default();
+                        let lane_buffer_size = lane_config.input_buffer_size.get()
+                            + lane_config.output_buffer_size.get();
+                        match resolver.register_agent(
+                            id,
+                            route_uri.clone(),
+                            name.clone(),
+                            pattern,
+                            lane_buffer_size,
+                        ) {
                             Ok(reporting) => Some(reporting),
                             Err(_) => {
                                 *introspection_resolver = None;
@@ -904,6 +1520,7 @@ impl Agents {
                         None
                     };
 
+                    agent_starts.notify(&route_uri);
                     let route_task = AgentRouteTask::new(
                         agent,
                         AgentRouteDescriptor {
@@ -915,7 +1532,9 @@ impl Agents {
                         agent_stop_rx.clone(),
                         *config,
                         node_reporting,
-                    );
+                    )
+                    .with_interceptor(interceptor.clone())
+                    .with_prune_policy(prune_policy.clone());
                     spawn_task(name, route_task);
                     let channel = entry.insert(AgentChannel {
                         id,
@@ -924,19 +1543,61 @@ impl Agents {
                     });
                     Ok(channel)
                 } else {
-                    Err(entry.into_key())
+                    Err(Unresolved::NoRoute(entry.into_key()))
                 }
             }
         }
     }
 
+    fn is_partitioned(&self, node: &str) -> bool {
+        self.routes.is_partitioned(node)
+    }
+
+    /// The node URIs of the running agents that are partitioned between the members of a cluster.
+    fn partitioned_agents(&self) -> Vec<Text> {
+        self.agent_channels
+            .keys()
+            .filter(|node| self.routes.is_partitioned(node.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    fn add_route(&mut self, pattern: RoutePattern, agent: BoxAgent) -> Result<(), AmbiguousRoutes> {
+        self.routes.insert(pattern, agent)
+    }
+
+    // Add the routes of the plane that were held back while the server was a standby.
+    fn add_plane_routes(
+        &mut self,
+        routes: Vec<(RoutePattern, BoxAgent)>,
+        explicit_start: &[RoutePattern],
+    ) {
+        for (pattern, agent) in routes {
+            if let Err(error) = self.add_route(pattern, agent) {
+                error!(error = %error, "Failed to add an agent route to the promoted server.");
+            }
+        }
+        self.routes.require_explicit_start(explicit_start);
+    }
+
     fn remove_agent(&mut self, route: &str) -> Result<(), IntrospectionStopped> {
         let Agents {
             agent_channels,
             introspection_resolver,
+            stopping,
             ..
         } = self;
 
+        // An agent that was stopped explicitly was removed when it was stopped (and the route may
+        // since have been used to start a new instance).
+        if let Some(count) = stopping.get_mut(route) {
+            *count -= 1;
+            if *count == 0 {
+                stopping.remove(route);
+            }
+            return Ok(());
+        }
+
         if let Some(AgentChannel { id, .. }) = agent_channels.remove(route) {
             if let Some(resolver) = introspection_resolver {
                 resolver.close_agent(id)?;
@@ -944,6 +1605,64 @@ impl Agents {
         }
         Ok(())
     }
+
+    /// Stop a running agent by releasing it. The agent will stop once any pending attachments
+    /// have completed. Returns whether the agent was running.
+    fn stop_agent(&mut self, route: &str) -> Result<bool, IntrospectionStopped> {
+        let Agents {
+            agent_channels,
+            introspection_resolver,
+            stopping,
+            ..
+        } = self;
+
+        if let Some((name, AgentChannel { id, .. })) = agent_channels.remove_entry(route) {
+            *stopping.entry(name).or_default() += 1;
+            if let Some(resolver) = introspection_resolver {
+                resolver.close_agent(id)?;
+            }
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn list_agents(&self) -> Vec<RunningAgent> {
+        let Agents {
+            agent_channels,
+            stopping,
+            ..
+        } = self;
+        let running = agent_channels
+            .keys()
+            .map(|node| (node, AgentStatus::Running));
+        let stopping = stopping.keys().map(|node| (node, AgentStatus::Stopping));
+        running
+            .chain(stopping)
+            .filter_map(|(node, status)| {
+                RouteUri::from_str(node.as_str())
+                    .ok()
+                    .map(|route| RunningAgent { route, status })
+            })
+            .collect()
+    }
+}
+
+/// The reason that an agent could not be resolved for a node.
+#[derive(Debug, Error)]
+enum Unresolved {
+    #[error("No route matches the node.")]
+    NoRoute(Text),
+    #[error("The agent must be started explicitly.")]
+    NotStarted(Text),
+}
+
+impl Unresolved {
+    fn node(&self) -> &Text {
+        match self {
+            Unresolved::NoRoute(node) | Unresolved::NotStarted(node) => node,
+        }
+    }
 }
 
 #[derive(Default)]
@@ -953,14 +1672,24 @@ struct Route {
     pattern: RoutePattern,
     agent: BoxAgent,
     disable_introspection: bool,
+    // Whether the nodes of the route are shared between the members of a cluster.
+    partitioned: bool,
+    start_policy: AgentStartPolicy,
 }
 
 impl Route {
-    fn new(pattern: RoutePattern, agent: BoxAgent, disable_introspection: bool) -> Self {
+    fn new(
+        pattern: RoutePattern,
+        agent: BoxAgent,
+        disable_introspection: bool,
+        partitioned: bool,
+    ) -> Self {
         Route {
             pattern,
             agent,
             disable_introspection,
+            partitioned,
+            start_policy: AgentStartPolicy::default(),
         }
     }
 }
@@ -969,7 +1698,7 @@ impl FromIterator<(RoutePattern, BoxAgent)> for Routes {
     fn from_iter<T: IntoIterator<Item = (RoutePattern, BoxAgent)>>(iter: T) -> Self {
         Routes(
             iter.into_iter()
-                .map(|(pattern, agent)| Route::new(pattern, agent, false))
+                .map(|(pattern, agent)| Route::new(pattern, agent, false, true))
                 .collect(),
         )
     }
@@ -981,7 +1710,58 @@ impl Routes {
         A: Agent + Send + 'static,
     {
         let Routes(routes) = self;
-        routes.push(Route::new(route_pattern, Box::new(agent), false));
+        routes.push(Route::new(route_pattern, Box::new(agent), false, false));
+    }
+
+    /// Add a new plane route, after the server has started. The route is rejected if it is
+    /// ambiguous with any existing route (including the routes of the meta-agents). Agents that
+    /// are already running are not affected.
+    fn insert(&mut self, pattern: RoutePattern, agent: BoxAgent) -> Result<(), AmbiguousRoutes> {
+        let Routes(routes) = self;
+        // Only the routes of the plane are partitioned. The others were registered by the server
+        // itself (for the meta-agents, partitions and feature flags).
+        let (meta, plane): (Vec<_>, Vec<_>) = routes
+            .iter()
+            .filter(|route| RoutePattern::are_ambiguous(&route.pattern, &pattern))
+            .partition(|route| !route.partitioned);
+        if !meta.is_empty() {
+            Err(AmbiguousRoutes::collision(
+                meta.into_iter()
+                    .map(|route| route.pattern.clone())
+                    .collect(),
+                vec![pattern],
+            ))
+        } else if !plane.is_empty() {
+            let mut ambiguous = plane
+                .into_iter()
+                .map(|route| route.pattern.clone())
+                .collect::<Vec<_>>();
+            ambiguous.push(pattern);
+            Err(AmbiguousRoutes::new(ambiguous))
+        } else {
+            routes.push(Route::new(pattern, agent, false, true));
+            Ok(())
+        }
+    }
+
+    // Agents for these routes will only be started by explicit requests.
+    fn require_explicit_start(&mut self, patterns: &[RoutePattern]) {
+        let Routes(routes) = self;
+        for route in routes
+            .iter_mut()
+            .filter(|route| patterns.contains(&route.pattern))
+        {
+            route.start_policy = AgentStartPolicy::Explicit;
+        }
+    }
+
+    // Only path nodes that match the plane routes are partitioned; meta-agents are always local.
+    fn is_partitioned(&self, node: &str) -> bool {
+        node.starts_with('/')
+            && RouteUri::from_str(node)
+                .ok()
+                .and_then(|route_uri| self.find_route(&route_uri))
+                .is_some_and(|(route, _)| route.partitioned)
     }
 
     fn find_route<'a>(&'a self, node: &RouteUri) -> Option<(&'a Route, HashMap<String, String>)> {
@@ -1012,6 +1792,7 @@ struct ConnectionTerminated {
 //is established this task will continue to wait until the connection is terminated.
 async fn attach_agent(
     remote_id: Uuid,
+    remote_addr: Option<SocketAddr>,
     agent_id: Uuid,
     tx: mpsc::Sender<AgentAttachmentRequest>,
     buffer_size: NonZeroUsize,
@@ -1029,7 +1810,8 @@ async fn attach_agent(
         (out_tx, in_rx),
         disconnect_tx,
         connnected_tx,
-    );
+    )
+    .with_remote_addr(remote_addr);
 
     let reason = match tokio::time::timeout(connect_timeout, async move {
         tx.send(req).await.is_ok() && connected_rx.await.is_ok()
@@ -1199,10 +1981,18 @@ async fn open_client<Net, Ws, Provider>(
     scheme: Scheme,
     host: Text,
     addrs: Vec<SocketAddr>,
+    forwarding: bool,
     networking: Arc<Net>,
     websockets: Arc<Ws>,
     provider: Provider,
-) -> Result<(SocketAddr, WebSocket<Net::Socket, Provider::Extension>), NewClientError>
+) -> Result<
+    (
+        SocketAddr,
+        WebSocket<Net::Socket, Provider::Extension>,
+        WarpProtocol,
+    ),
+    NewClientError,
+>
 where
     Net: ExternalConnections,
     Net::Socket: WebSocketStream,
@@ -1210,30 +2000,19 @@ where
     Provider: ExtensionProvider + Send + Sync + Clone + Unpin + 'static,
     Provider::Extension: SplittableExtension + Send + Sync + Unpin + 'static,
 {
-    let mut conn_failures = vec![];
-    let mut sock = None;
-    for addr in addrs {
-        match networking.try_open(scheme, Some(host.as_str()), addr).await {
-            Ok(socket) => {
-                sock = Some((addr, socket));
-                break;
-            }
-            Err(e) => {
-                conn_failures.push((addr, e));
-            }
-        }
-    }
-    let (addr, socket) = if let Some((addr, socket)) = sock {
-        (addr, socket)
+    let (addr, socket) = race_connections(addrs, CONNECTION_ATTEMPT_DELAY, |addr| {
+        networking.try_open(scheme, Some(host.as_str()), addr)
+    })
+    .await
+    .map_err(|errors| NewClientError::OpeningSocketFailed { errors })?;
+    let negotiate = if forwarding {
+        websockets.open_forwarding_connection(socket, &provider, host.to_string())
     } else {
-        return Err(NewClientError::OpeningSocketFailed {
-            errors: conn_failures,
-        });
+        websockets.open_connection(socket, &provider, host.to_string())
     };
-    websockets
-        .open_connection(socket, &provider, host.to_string())
+    negotiate
         .await
-        .map(move |ws| (addr, ws))
+        .map(move |(ws, protocol)| (addr, ws, protocol))
         .map_err(|e| NewClientError::WsNegotationFailed { error: e })
 }
 
@@ -1241,9 +2020,12 @@ fn start_introspection(
     config: IntrospectionConfig,
     coop_budget: Option<NonZeroUsize>,
     stopping: trigger::Receiver,
+    captures: Option<RemoteCaptures>,
+    recovery: AgentRecovery,
     routes: &mut Routes,
 ) -> IntrospectionResolver {
-    let (resolver, task) = register_introspection(stopping, config, routes);
+    let (resolver, task) =
+        register_introspection(stopping, config, captures, Some(recovery), routes);
     tokio::spawn(task.with_budget_or_default(coop_budget));
     resolver
 }
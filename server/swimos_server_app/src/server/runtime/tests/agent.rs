@@ -145,7 +145,11 @@ async fn run_agent(
 
     while let Some(result) = input.next().await {
         match result {
-            Ok(LaneRequest::Sync(id)) => {
+            Ok(
+                LaneRequest::Sync(id)
+                | LaneRequest::SyncSince(id, _)
+                | LaneRequest::SyncRange(id, _),
+            ) => {
                 output
                     .send(LaneResponse::sync_event(id, state))
                     .await
@@ -155,11 +159,19 @@ async fn run_agent(
                     .await
                     .expect("Channel stopped.");
             }
-            Ok(LaneRequest::Command(TestMessage::SetAndReport(n))) => {
+            Ok(
+                LaneRequest::Command(TestMessage::SetAndReport(n))
+                | LaneRequest::CommandFrom(_, TestMessage::SetAndReport(n))
+                | LaneRequest::CorrelatedCommand(_, _, TestMessage::SetAndReport(n)),
+            ) => {
                 state = n;
                 reporter.send(n).expect("Reporter closed.");
             }
-            Ok(LaneRequest::Command(TestMessage::Event)) => {
+            Ok(
+                LaneRequest::Command(TestMessage::Event)
+                | LaneRequest::CommandFrom(_, TestMessage::Event)
+                | LaneRequest::CorrelatedCommand(_, _, TestMessage::Event),
+            ) => {
                 output
                     .send(LaneResponse::event(state))
                     .await
@@ -26,7 +26,9 @@ use ratchet::{
 };
 use swimos_messages::remote_protocol::FindNode;
 use swimos_remote::dns::{DnsFut, DnsResolver};
-use swimos_remote::websocket::{RatchetError, WebsocketClient, WebsocketServer, WsOpenFuture};
+use swimos_remote::websocket::{
+    RatchetError, WarpProtocol, WebsocketClient, WebsocketServer, WsOpenFuture,
+};
 use swimos_remote::{
     ConnectionError, ExternalConnections, Listener, ListenerError, ListenerResult, Scheme,
 };
@@ -75,12 +77,15 @@ impl WebsocketClient for TestWs {
         Provider: ExtensionProvider + Send + Sync + 'static,
         Provider::Extension: Send + Sync + 'static,
     {
-        ready(Ok(WebSocket::from_upgraded(
-            self.config,
-            socket,
-            NegotiatedExtension::from(None),
-            BytesMut::new(),
-            Role::Client,
+        ready(Ok((
+            WebSocket::from_upgraded(
+                self.config,
+                socket,
+                NegotiatedExtension::from(None),
+                BytesMut::new(),
+                Role::Client,
+            ),
+            WarpProtocol::default(),
         )))
         .boxed()
     }
@@ -88,7 +93,7 @@ impl WebsocketClient for TestWs {
 
 impl WebsocketServer for TestWs {
     type WsStream<Sock, Ext> =
-        BoxStream<'static, Result<(WebSocket<Sock, Ext>, SocketAddr), ListenerError>>;
+        BoxStream<'static, Result<(WebSocket<Sock, Ext>, SocketAddr, WarpProtocol), ListenerError>>;
 
     fn wrap_listener<Sock, L, Provider>(
         &self,
@@ -116,6 +121,7 @@ impl WebsocketServer for TestWs {
                             Role::Server,
                         ),
                         addr,
+                        WarpProtocol::default(),
                     )
                 })
             })
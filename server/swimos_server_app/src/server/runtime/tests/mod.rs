@@ -31,7 +31,11 @@ use swimos_api::{address::RelativeAddress, persistence::StoreDisabled};
 use swimos_form::write::StructuralWritable;
 use swimos_recon::print_recon_compact;
 use swimos_remote::{Scheme, SchemeHostPort};
-use swimos_utilities::{byte_channel::byte_channel, non_zero_usize, routing::RoutePattern};
+use swimos_utilities::{
+    byte_channel::byte_channel,
+    non_zero_usize,
+    routing::{RoutePattern, RouteUri},
+};
 
 use swimos_messages::{
     remote_protocol::{AttachClient, LinkError},
@@ -47,12 +51,14 @@ use tokio::{
 use uuid::Uuid;
 
 use crate::{
-    plane::PlaneBuilder,
+    error::AmbiguousRoutes,
+    plane::{AgentStartPolicy, PlaneBuilder},
     server::{
         runtime::{ClientRegistration, NewClientError},
         ServerError,
     },
-    ServerHandle, SwimServerConfig,
+    AddRouteError, AgentStatus, RunningAgent, ServerHandle, StopAgentError, SwimServerConfig,
+    UnresolvableRoute,
 };
 
 use self::{
@@ -102,6 +108,19 @@ async fn run_server_with_config_and_dl<F, Fut>(
     remotes: HashMap<SocketAddr, DuplexStream>,
     test_case: F,
 ) -> (Result<(), ServerError>, Fut::Output)
+where
+    F: FnOnce(DlTestContext) -> Fut,
+    Fut: Future,
+{
+    run_server_with_policy_and_dl(config, AgentStartPolicy::OnEnvelope, remotes, test_case).await
+}
+
+async fn run_server_with_policy_and_dl<F, Fut>(
+    config: SwimServerConfig,
+    policy: AgentStartPolicy,
+    remotes: HashMap<SocketAddr, DuplexStream>,
+    test_case: F,
+) -> (Result<(), ServerError>, Fut::Output)
 where
     F: FnOnce(DlTestContext) -> Fut,
     Fut: Future,
@@ -112,11 +131,12 @@ where
     let (event_tx, event_rx) = mpsc::unbounded_channel();
     let (report_tx, report_rx) = mpsc::unbounded_channel();
 
-    plane_builder.add_route(
+    plane_builder.add_route_with_policy(
         pattern,
         TestAgent::new(report_tx, event_tx, |uri, _route_params, _conf| {
             assert_eq!(uri, "/node");
         }),
+        policy,
     );
 
     let plane = plane_builder.build().expect("Invalid plane definition.");
@@ -172,7 +192,19 @@ where
     F: FnOnce(TestContext) -> Fut,
     Fut: Future,
 {
-    run_server_with_config_and_dl(config, HashMap::default(), |context| async move {
+    run_server_with_policy(config, AgentStartPolicy::OnEnvelope, test_case).await
+}
+
+async fn run_server_with_policy<F, Fut>(
+    config: SwimServerConfig,
+    policy: AgentStartPolicy,
+    test_case: F,
+) -> (Result<(), ServerError>, Fut::Output)
+where
+    F: FnOnce(TestContext) -> Fut,
+    Fut: Future,
+{
+    run_server_with_policy_and_dl(config, policy, HashMap::default(), |context| async move {
         let DlTestContext {
             test_context,
             downlink_connector,
@@ -349,6 +381,52 @@ async fn message_for_nonexistent_agent() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn message_for_agent_not_started() {
+    let config = SwimServerConfig::default();
+    let (result, _) = run_server_with_policy(
+        config,
+        AgentStartPolicy::Explicit,
+        |mut context| async move {
+            let TestContext {
+                incoming_tx,
+                report_rx,
+                handle,
+                ..
+            } = &mut context;
+
+            let (client_sock, server_sock) = duplex(BUFFER_SIZE.get());
+
+            incoming_tx
+                .send((remote_addr(1), server_sock))
+                .expect("Listener closed.");
+
+            let mut client = TestClient::new(client_sock);
+
+            client.link(NODE, LANE).await;
+            client.expect_unlinked(NODE, LANE, "@nodeNotStarted").await;
+
+            let route = RouteUri::try_from(NODE).expect("Bad route.");
+            handle
+                .start_agent(route)
+                .await
+                .expect("Starting the agent failed.");
+
+            client
+                .command(NODE, LANE, TestMessage::SetAndReport(12))
+                .await;
+
+            assert_eq!(report_rx.recv().await.expect("Agent stopped."), 12);
+
+            context.handle.stop();
+            client.expect_close().await;
+            context
+        },
+    )
+    .await;
+    assert!(result.is_ok());
+}
+
 #[tokio::test]
 async fn command_to_agent() {
     let (result, _) = run_server(|mut context| async move {
@@ -431,7 +509,9 @@ async fn link_to_agent_lane() {
 
         context.handle.stop();
 
-        client.expect_unlinked(NODE, LANE, "").await;
+        client
+            .expect_unlinked(NODE, LANE, "@nodeStopped(resume:false)")
+            .await;
         client.expect_close().await;
 
         context
@@ -461,7 +541,9 @@ async fn sync_with_agent_lane() {
 
         context.handle.stop();
 
-        client.expect_unlinked(NODE, LANE, "").await;
+        client
+            .expect_unlinked(NODE, LANE, "@nodeStopped(resume:false)")
+            .await;
         client.expect_close().await;
         context
     })
@@ -492,7 +574,9 @@ async fn trigger_event() {
 
         context.handle.stop();
 
-        client.expect_unlinked(NODE, LANE, "").await;
+        client
+            .expect_unlinked(NODE, LANE, "@nodeStopped(resume:false)")
+            .await;
         client.expect_close().await;
 
         context
@@ -565,7 +649,9 @@ async fn broadcast_events() {
 
         context.handle.stop();
 
-        client1.expect_unlinked(NODE, LANE, "").await;
+        client1
+            .expect_unlinked(NODE, LANE, "@nodeStopped(resume:false)")
+            .await;
         join(client1.expect_close(), client2.expect_close()).await;
 
         context
@@ -839,3 +925,155 @@ async fn downlink_to_local_nonexistent() {
         .await;
     assert!(result.is_ok());
 }
+
+#[tokio::test]
+async fn start_stop_and_list_agents() {
+    let (result, _) = run_server(|mut context| async move {
+        let TestContext {
+            event_rx, handle, ..
+        } = &mut context;
+
+        let route = RouteUri::try_from(NODE).expect("Bad route.");
+        handle
+            .start_agents([route.clone()])
+            .await
+            .expect("Starting the agents failed.");
+        assert!(matches!(event_rx.recv().await, Some(AgentEvent::Started)));
+
+        let agents = handle.list_agents().await.expect("Server stopped.");
+        assert_eq!(
+            agents,
+            vec![RunningAgent {
+                route: route.clone(),
+                status: AgentStatus::Running,
+            }]
+        );
+
+        handle
+            .stop_agent(route.clone())
+            .await
+            .expect("Stopping the agent failed.");
+        assert!(matches!(
+            handle.stop_agent(route.clone()).await,
+            Err(StopAgentError::NotRunning { uri }) if uri == route
+        ));
+        assert!(matches!(event_rx.recv().await, Some(AgentEvent::Stopped)));
+
+        let other = RouteUri::try_from("/other").expect("Bad route.");
+        let error = handle
+            .start_agents([route.clone(), other.clone()])
+            .await
+            .expect_err("Started an agent with no route.");
+        assert!(matches!(error, UnresolvableRoute::NoRoute { uri } if uri == other));
+        assert!(matches!(event_rx.recv().await, Some(AgentEvent::Started)));
+
+        let agents = handle.list_agents().await.expect("Server stopped.");
+        assert!(agents.contains(&RunningAgent {
+            route,
+            status: AgentStatus::Running,
+        }));
+
+        context.handle.stop();
+        context
+    })
+    .await;
+    assert!(result.is_ok());
+}
+
+const ADDED_NODE: &str = "/added/:id";
+
+#[tokio::test]
+async fn add_route_to_running_server() {
+    let (result, _) = run_server(|mut context| async move {
+        let TestContext {
+            incoming_tx,
+            handle,
+            ..
+        } = &mut context;
+
+        let (event_tx, _event_rx) = mpsc::unbounded_channel();
+        let (report_tx, mut report_rx) = mpsc::unbounded_channel();
+        let pattern = RoutePattern::parse_str(ADDED_NODE).expect("Invalid route.");
+        let agent = TestAgent::new(report_tx, event_tx, |uri, route_params, _conf| {
+            assert_eq!(uri, "/added/1");
+            assert_eq!(route_params.get("id").map(String::as_str), Some("1"));
+        });
+        handle
+            .add_route(pattern, agent)
+            .await
+            .expect("Adding the route failed.");
+
+        let (client_sock, server_sock) = duplex(BUFFER_SIZE.get());
+
+        incoming_tx
+            .send((remote_addr(1), server_sock))
+            .expect("Listener closed.");
+
+        let mut client = TestClient::new(client_sock);
+
+        client
+            .command("/added/1", LANE, TestMessage::SetAndReport(7))
+            .await;
+
+        assert_eq!(report_rx.recv().await.expect("Agent stopped."), 7);
+
+        context.handle.stop();
+        client.expect_close().await;
+        context
+    })
+    .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn add_ambiguous_route() {
+    let (result, _) = run_server(|mut context| async move {
+        let (event_tx, _event_rx) = mpsc::unbounded_channel();
+        let (report_tx, _report_rx) = mpsc::unbounded_channel();
+        let pattern = RoutePattern::parse_str("/:name").expect("Invalid route.");
+        let agent = TestAgent::new(report_tx, event_tx, |_uri, _route_params, _conf| {
+            panic!("Agent should not be started.")
+        });
+
+        let error = context
+            .handle
+            .add_route(pattern, agent)
+            .await
+            .expect_err("Ambiguous route was accepted.");
+        match error {
+            AddRouteError::Ambiguous(AmbiguousRoutes::Overlapping { routes }) => {
+                let routes = routes.iter().map(ToString::to_string).collect::<Vec<_>>();
+                assert_eq!(routes, vec![NODE.to_string(), "/:name".to_string()]);
+            }
+            ow => panic!("Unexpected error: {:?}", ow),
+        }
+
+        context.handle.stop();
+        context
+    })
+    .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn add_route_after_stop() {
+    let (result, context) = run_server(|mut context| async move {
+        context.handle.stop();
+        context
+    })
+    .await;
+    assert!(result.is_ok());
+
+    let (event_tx, _event_rx) = mpsc::unbounded_channel();
+    let (report_tx, _report_rx) = mpsc::unbounded_channel();
+    let pattern = RoutePattern::parse_str(ADDED_NODE).expect("Invalid route.");
+    let agent = TestAgent::new(report_tx, event_tx, |_uri, _route_params, _conf| {
+        panic!("Agent should not be started.")
+    });
+    let error = context
+        .handle
+        .add_route(pattern, agent)
+        .await
+        .expect_err("Route added to a stopped server.");
+    assert!(matches!(error, AddRouteError::Stopped));
+}
@@ -12,6 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(any(test, feature = "rocks_store"))]
+mod startup;
+#[cfg(test)]
+mod tests;
+
+#[cfg(any(test, feature = "rocks_store"))]
+pub use startup::open_store;
+
 pub mod in_memory {
     use std::collections::{hash_map::Entry, HashMap};
 
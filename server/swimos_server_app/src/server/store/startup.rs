@@ -0,0 +1,60 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use swimos_api::error::StoreError;
+use swimos_runtime::agent::StoreFailureAction;
+use tracing::warn;
+
+use crate::config::StoreStartupPolicy;
+
+/// Attempt to open a store, retrying according to the provided policy. If the store still cannot
+/// be opened and the policy allows it, this will resolve to [`None`] and the server should run
+/// without persistence.
+///
+/// # Arguments
+/// * `policy` - Policy for handling failures to open the store.
+/// * `open` - Attempts to open the store.
+pub async fn open_store<S, F>(
+    policy: StoreStartupPolicy,
+    mut open: F,
+) -> Result<Option<S>, StoreError>
+where
+    F: FnMut() -> Result<S, StoreError>,
+{
+    let StoreStartupPolicy {
+        mut retry,
+        on_failure,
+    } = policy;
+    loop {
+        let error = match open() {
+            Ok(store) => break Ok(Some(store)),
+            Err(error) => error,
+        };
+        match retry.next() {
+            Some(delay) => {
+                warn!(error = %error, "Failed to open the store. Retrying.");
+                if let Some(delay) = delay {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+            None => match on_failure {
+                StoreFailureAction::Fail => break Err(error),
+                StoreFailureAction::Transient => {
+                    warn!(error = %error, "Failed to open the store. The server will run without persistence.");
+                    break Ok(None);
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use swimos_api::error::StoreError;
+use swimos_utilities::{
+    future::{Quantity, RetryStrategy},
+    non_zero_usize,
+};
+
+use crate::config::StoreStartupPolicy;
+
+use super::open_store;
+
+fn fails_times(n: usize) -> impl FnMut() -> Result<i32, StoreError> {
+    let mut attempts = 0;
+    move || {
+        attempts += 1;
+        if attempts > n {
+            Ok(attempts as i32)
+        } else {
+            Err(StoreError::NoStoreAvailable)
+        }
+    }
+}
+
+#[tokio::test]
+async fn opens_store() {
+    let result = open_store(StoreStartupPolicy::fail_fast(), fails_times(0)).await;
+    assert!(matches!(result, Ok(Some(1))));
+}
+
+#[tokio::test]
+async fn fail_fast() {
+    let result = open_store(StoreStartupPolicy::fail_fast(), fails_times(1)).await;
+    assert!(matches!(result, Err(StoreError::NoStoreAvailable)));
+}
+
+#[tokio::test]
+async fn transient_on_failure() {
+    let result = open_store(StoreStartupPolicy::transient(), fails_times(1)).await;
+    assert!(matches!(result, Ok(None)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_until_open() {
+    let retry =
+        RetryStrategy::interval(Duration::from_secs(1), Quantity::Finite(non_zero_usize!(3)));
+    let result = open_store(StoreStartupPolicy::retry(retry), fails_times(3)).await;
+    assert!(matches!(result, Ok(Some(4))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn retries_exhausted() {
+    let retry =
+        RetryStrategy::interval(Duration::from_secs(1), Quantity::Finite(non_zero_usize!(2)));
+    let result = open_store(StoreStartupPolicy::retry(retry), fails_times(3)).await;
+    assert!(matches!(result, Err(StoreError::NoStoreAvailable)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn retries_exhausted_transient() {
+    let retry =
+        RetryStrategy::interval(Duration::from_secs(1), Quantity::Finite(non_zero_usize!(2)));
+    let policy = StoreStartupPolicy::retry(retry).or_transient();
+    let result = open_store(policy, fails_times(3)).await;
+    assert!(matches!(result, Ok(None)));
+}
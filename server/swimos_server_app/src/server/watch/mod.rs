@@ -0,0 +1,65 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use futures::StreamExt;
+use swimos_agent_protocol::{encoding::downlink::ValueNotificationDecoder, DownlinkNotification};
+use swimos_form::read::RecognizerReadable;
+use tokio::sync::watch;
+use tokio_util::codec::FramedRead;
+use tracing::debug;
+
+use crate::Io;
+
+#[cfg(test)]
+mod tests;
+
+/// Consume the notifications from a value downlink, publishing each new value of the lane
+/// into a watch channel. The task will stop when the downlink terminates or when all of the
+/// receivers for the channel have been dropped.
+///
+/// # Arguments
+/// * `io` - The channels to the downlink runtime.
+/// * `tx` - Sender for the watch channel that mirrors the state of the lane.
+pub async fn mirror_value_lane<T>(io: Io, tx: watch::Sender<Option<T>>)
+where
+    T: RecognizerReadable,
+{
+    // The writer must be held for as long as the link is required.
+    let (_writer, reader) = io;
+    let mut notifications = FramedRead::new(reader, ValueNotificationDecoder::<T>::default());
+    loop {
+        let notification = tokio::select! {
+            biased;
+            _ = tx.closed() => {
+                debug!("All receivers for the lane value were dropped.");
+                break;
+            }
+            maybe_notification = notifications.next() => maybe_notification,
+        };
+        match notification {
+            Some(Ok(DownlinkNotification::Event { body })) => {
+                tx.send_replace(Some(body));
+            }
+            Some(Ok(DownlinkNotification::Unlinked)) => {
+                debug!("The link to the lane was closed.");
+            }
+            Some(Ok(_)) => {}
+            Some(Err(error)) => {
+                debug!(error = %error, "The link to the lane failed.");
+                break;
+            }
+            None => break,
+        }
+    }
+}
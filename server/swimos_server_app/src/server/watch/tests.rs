@@ -0,0 +1,75 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::num::NonZeroUsize;
+
+use futures::{future::join, SinkExt};
+use swimos_agent_protocol::{
+    encoding::downlink::DownlinkNotificationEncoder, DownlinkNotification,
+};
+use swimos_utilities::{byte_channel::byte_channel, non_zero_usize};
+use tokio::sync::watch;
+use tokio_util::codec::FramedWrite;
+
+use super::mirror_value_lane;
+
+const BUFFER_SIZE: NonZeroUsize = non_zero_usize!(4096);
+
+type Notification = DownlinkNotification<String>;
+
+fn event(n: i32) -> Notification {
+    DownlinkNotification::Event {
+        body: n.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn mirror_lane_values() {
+    let (in_tx, _in_rx) = byte_channel(BUFFER_SIZE);
+    let (out_tx, out_rx) = byte_channel(BUFFER_SIZE);
+    let (tx, mut rx) = watch::channel::<Option<i32>>(None);
+
+    let task = mirror_value_lane((in_tx, out_rx), tx);
+
+    let test = async move {
+        let mut writer = FramedWrite::new(out_tx, DownlinkNotificationEncoder);
+        assert!(writer.send(Notification::Linked).await.is_ok());
+        assert!(writer.send(event(1)).await.is_ok());
+        assert!(writer.send(Notification::Synced).await.is_ok());
+
+        assert!(rx.wait_for(|v| *v == Some(1)).await.is_ok());
+
+        assert!(writer.send(event(2)).await.is_ok());
+        assert!(rx.wait_for(|v| *v == Some(2)).await.is_ok());
+
+        assert!(writer.send(Notification::Unlinked).await.is_ok());
+        drop(writer);
+
+        // The last value is retained after the link closes.
+        assert!(rx.changed().await.is_err());
+        assert_eq!(*rx.borrow(), Some(2));
+    };
+
+    join(task, test).await;
+}
+
+#[tokio::test]
+async fn mirror_stops_when_receivers_dropped() {
+    let (in_tx, _in_rx) = byte_channel(BUFFER_SIZE);
+    let (_out_tx, out_rx) = byte_channel(BUFFER_SIZE);
+    let (tx, rx) = watch::channel::<Option<i32>>(None);
+
+    drop(rx);
+    mirror_value_lane((in_tx, out_rx), tx).await;
+}
@@ -0,0 +1,552 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use bytes::{BufMut, BytesMut};
+use futures::{future::BoxFuture, FutureExt};
+use parking_lot::Mutex;
+use swimos_api::{
+    error::StoreError,
+    persistence::{KeyValue, NodePersistence, PlanePersistence, RangeConsumer, ServerPersistence},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Compression algorithms that can be applied to the values persisted for a lane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionCodec {
+    /// LZ4 block compression. This is very fast but achieves a lower compression ratio.
+    Lz4,
+    /// Zstandard compression at the specified level.
+    Zstd { level: i32 },
+}
+
+impl CompressionCodec {
+    /// Zstandard compression at its default level.
+    pub const ZSTD_DEFAULT: CompressionCodec = CompressionCodec::Zstd {
+        level: zstd::DEFAULT_COMPRESSION_LEVEL,
+    };
+}
+
+/// The default size (in bytes) below which values are stored without compression.
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 64;
+
+/// Configuration for the compression of the values persisted by agents. Compression is selected per
+/// lane (by name) with a default that applies to any lane that is not configured explicitly. Only
+/// lane values and the values of map entries are compressed (the keys of map entries are stored
+/// as they are so that they remain ordered).
+///
+/// Values that were written without compression remain readable after compression is enabled.
+#[derive(Debug, Clone)]
+pub struct StoreCompressionConfig {
+    default_codec: Option<CompressionCodec>,
+    lane_codecs: HashMap<String, Option<CompressionCodec>>,
+    dictionary: Option<Arc<[u8]>>,
+    threshold: usize,
+    metrics: CompressionMetrics,
+}
+
+impl Default for StoreCompressionConfig {
+    fn default() -> Self {
+        StoreCompressionConfig {
+            default_codec: None,
+            lane_codecs: Default::default(),
+            dictionary: None,
+            threshold: DEFAULT_COMPRESSION_THRESHOLD,
+            metrics: Default::default(),
+        }
+    }
+}
+
+impl StoreCompressionConfig {
+    /// Compress the values for all lanes that are not configured explicitly with the specified codec.
+    pub fn with_default_codec(mut self, codec: CompressionCodec) -> Self {
+        self.default_codec = Some(codec);
+        self
+    }
+
+    /// Set the compression for all lanes with a specific name.
+    ///
+    /// # Arguments
+    /// * `lane` - The name of the lane.
+    /// * `codec` - The codec to use for the lane (or none to store its values uncompressed).
+    pub fn with_lane_codec(mut self, lane: &str, codec: Option<CompressionCodec>) -> Self {
+        self.lane_codecs.insert(lane.to_string(), codec);
+        self
+    }
+
+    /// Provide a dictionary that is shared by all compressed values. A dictionary significantly
+    /// improves the compression of small values that have a common structure (such as the Recon
+    /// records of a single lane). See [`train_dictionary`].
+    ///
+    /// The same dictionary must be provided when the store is reopened or values that were written
+    /// with it will fail to be read.
+    pub fn with_dictionary(mut self, dictionary: impl Into<Vec<u8>>) -> Self {
+        let dictionary: Vec<u8> = dictionary.into();
+        self.dictionary = Some(dictionary.into());
+        self
+    }
+
+    /// Set the size (in bytes) below which values are stored without compression.
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// A handle to the compression metrics for the store. This can be used to observe the
+    /// compression ratios achieved for each lane after the configuration is passed to the server.
+    pub fn metrics(&self) -> CompressionMetrics {
+        self.metrics.clone()
+    }
+
+    fn codec_for(&self, lane: &str) -> Option<CompressionCodec> {
+        self.lane_codecs
+            .get(lane)
+            .copied()
+            .unwrap_or(self.default_codec)
+    }
+}
+
+/// Train a dictionary, for use with [`StoreCompressionConfig::with_dictionary`], from a collection
+/// of sample values.
+///
+/// # Arguments
+/// * `samples` - Representative values for the lanes that will be compressed.
+/// * `max_size` - The maximum size of the dictionary in bytes.
+pub fn train_dictionary<S: AsRef<[u8]>>(
+    samples: &[S],
+    max_size: usize,
+) -> Result<Vec<u8>, std::io::Error> {
+    zstd::dict::from_samples(samples, max_size)
+}
+
+/// Cumulative counts of the bytes written to a store for a lane (or set of lanes).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompressionStats {
+    /// The total size of the values before compression.
+    pub uncompressed_bytes: u64,
+    /// The total size of the values written into the store.
+    pub stored_bytes: u64,
+}
+
+impl CompressionStats {
+    /// The ratio of the uncompressed size to the stored size (if anything has been stored).
+    pub fn ratio(&self) -> Option<f64> {
+        if self.stored_bytes == 0 {
+            None
+        } else {
+            Some(self.uncompressed_bytes as f64 / self.stored_bytes as f64)
+        }
+    }
+}
+
+impl std::ops::Add for CompressionStats {
+    type Output = CompressionStats;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        CompressionStats {
+            uncompressed_bytes: self.uncompressed_bytes + rhs.uncompressed_bytes,
+            stored_bytes: self.stored_bytes + rhs.stored_bytes,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CompressionCounters {
+    uncompressed_bytes: AtomicU64,
+    stored_bytes: AtomicU64,
+}
+
+impl CompressionCounters {
+    fn record(&self, uncompressed: usize, stored: usize) {
+        self.uncompressed_bytes
+            .fetch_add(uncompressed as u64, Ordering::Relaxed);
+        self.stored_bytes
+            .fetch_add(stored as u64, Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> CompressionStats {
+        CompressionStats {
+            uncompressed_bytes: self.uncompressed_bytes.load(Ordering::Relaxed),
+            stored_bytes: self.stored_bytes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Compression metrics for a store, keyed by lane name (aggregated over all agents).
+#[derive(Debug, Clone, Default)]
+pub struct CompressionMetrics {
+    lanes: Arc<Mutex<HashMap<String, Arc<CompressionCounters>>>>,
+}
+
+impl CompressionMetrics {
+    fn counters_for(&self, lane: &str) -> Arc<CompressionCounters> {
+        let mut guard = self.lanes.lock();
+        if let Some(counters) = guard.get(lane) {
+            counters.clone()
+        } else {
+            let counters = Arc::new(CompressionCounters::default());
+            guard.insert(lane.to_string(), counters.clone());
+            counters
+        }
+    }
+
+    /// The statistics for all lanes with a specific name.
+    pub fn lane(&self, lane: &str) -> Option<CompressionStats> {
+        self.lanes.lock().get(lane).map(|counters| counters.stats())
+    }
+
+    /// The statistics for each lane name.
+    pub fn lanes(&self) -> HashMap<String, CompressionStats> {
+        self.lanes
+            .lock()
+            .iter()
+            .map(|(name, counters)| (name.clone(), counters.stats()))
+            .collect()
+    }
+
+    /// The statistics for the entire store.
+    pub fn total(&self) -> CompressionStats {
+        self.lanes
+            .lock()
+            .values()
+            .map(|counters| counters.stats())
+            .fold(CompressionStats::default(), |acc, stats| acc + stats)
+    }
+}
+
+// Compressed values are prefixed with a header consisting of a marker byte, a tag byte (identifying
+// the codec) and the length of the uncompressed value. The marker byte never occurs at the start of a
+// UTF-8 string (and so of a Recon value) so values without the header can be read as they are.
+const MARKER: u8 = 0xff;
+const TAG_RAW: u8 = 0;
+const TAG_LZ4: u8 = 1;
+const TAG_ZSTD: u8 = 2;
+const DICT_FLAG: u8 = 0x80;
+const HEADER_LEN: usize = 6;
+
+impl StoreCompressionConfig {
+    /// Write a value, compressed with the specified codec, into a buffer.
+    fn encode(
+        &self,
+        codec: Option<CompressionCodec>,
+        value: &[u8],
+        dst: &mut Vec<u8>,
+    ) -> Result<(), StoreError> {
+        let StoreCompressionConfig {
+            dictionary,
+            threshold,
+            ..
+        } = self;
+        dst.clear();
+        if let Some(codec) = codec.filter(|_| value.len() >= *threshold) {
+            let len = u32::try_from(value.len())
+                .map_err(|_| StoreError::Encoding("Value too large to compress.".to_string()))?;
+            let dict = dictionary.as_deref();
+            let (tag, compressed) = match codec {
+                CompressionCodec::Lz4 => {
+                    let compressed = match dict {
+                        Some(dict) => lz4_flex::block::compress_with_dict(value, dict),
+                        None => lz4_flex::block::compress(value),
+                    };
+                    (TAG_LZ4, compressed)
+                }
+                CompressionCodec::Zstd { level } => {
+                    let compressed = match dict {
+                        Some(dict) => zstd::bulk::Compressor::with_dictionary(level, dict)
+                            .and_then(|mut compressor| compressor.compress(value)),
+                        None => zstd::bulk::compress(value, level),
+                    }?;
+                    (TAG_ZSTD, compressed)
+                }
+            };
+            if compressed.len() + HEADER_LEN < value.len() {
+                let tag = if dict.is_some() { tag | DICT_FLAG } else { tag };
+                dst.extend_from_slice(&[MARKER, tag]);
+                dst.extend_from_slice(&len.to_be_bytes());
+                dst.extend_from_slice(&compressed);
+                return Ok(());
+            }
+        }
+        if value.first() == Some(&MARKER) {
+            dst.extend_from_slice(&[MARKER, TAG_RAW]);
+        }
+        dst.extend_from_slice(value);
+        Ok(())
+    }
+
+    /// Write the original form of a stored value into a buffer.
+    fn decode<B: BufMut>(&self, stored: &[u8], dst: &mut B) -> Result<usize, StoreError> {
+        let body = match stored {
+            [MARKER, TAG_RAW, body @ ..] => body,
+            [MARKER, tag, l0, l1, l2, l3, body @ ..] => {
+                let len = u32::from_be_bytes([*l0, *l1, *l2, *l3]) as usize;
+                let dict = if tag & DICT_FLAG != 0 {
+                    Some(self.dictionary.as_deref().ok_or_else(|| {
+                        StoreError::Decoding(
+                            "A compression dictionary is required to read the value.".to_string(),
+                        )
+                    })?)
+                } else {
+                    None
+                };
+                let decompressed = match tag & !DICT_FLAG {
+                    TAG_LZ4 => match dict {
+                        Some(dict) => lz4_flex::block::decompress_with_dict(body, len, dict),
+                        None => lz4_flex::block::decompress(body, len),
+                    }
+                    .map_err(|err| StoreError::Decoding(err.to_string()))?,
+                    TAG_ZSTD => match dict {
+                        Some(dict) => zstd::bulk::Decompressor::with_dictionary(dict)
+                            .and_then(|mut decompressor| decompressor.decompress(body, len)),
+                        None => zstd::bulk::decompress(body, len),
+                    }
+                    .map_err(|err| StoreError::Decoding(err.to_string()))?,
+                    _ => {
+                        return Err(StoreError::Decoding(format!(
+                            "Unknown compression tag: {}.",
+                            tag
+                        )))
+                    }
+                };
+                dst.put_slice(&decompressed);
+                return Ok(decompressed.len());
+            }
+            [MARKER, ..] => {
+                return Err(StoreError::Decoding(
+                    "Compressed value is truncated.".to_string(),
+                ))
+            }
+            _ => stored,
+        };
+        dst.put_slice(body);
+        Ok(body.len())
+    }
+}
+
+/// A [`ServerPersistence`] implementation that compresses the values written into another store.
+#[derive(Debug)]
+pub struct CompressedPersistence<S> {
+    inner: S,
+    settings: Arc<StoreCompressionConfig>,
+}
+
+impl<S> CompressedPersistence<S> {
+    /// # Arguments
+    /// * `inner` - The underlying store.
+    /// * `config` - Determines how the values for each lane are compressed.
+    pub fn new(inner: S, config: StoreCompressionConfig) -> Self {
+        CompressedPersistence {
+            inner,
+            settings: Arc::new(config),
+        }
+    }
+}
+
+impl<S: ServerPersistence> ServerPersistence for CompressedPersistence<S> {
+    type PlaneStore = CompressedPlanePersistence<S::PlaneStore>;
+
+    fn open_plane(&self, name: &str) -> Result<Self::PlaneStore, StoreError> {
+        let CompressedPersistence { inner, settings } = self;
+        Ok(CompressedPlanePersistence {
+            inner: inner.open_plane(name)?,
+            settings: settings.clone(),
+        })
+    }
+}
+
+/// A [`PlanePersistence`] implementation that compresses the values written into another store.
+#[derive(Debug, Clone)]
+pub struct CompressedPlanePersistence<P> {
+    inner: P,
+    settings: Arc<StoreCompressionConfig>,
+}
+
+impl<P: PlanePersistence> PlanePersistence for CompressedPlanePersistence<P> {
+    type Node = CompressedNodePersistence<P::Node>;
+
+    fn node_store(&self, node_uri: &str) -> BoxFuture<'static, Result<Self::Node, StoreError>> {
+        let CompressedPlanePersistence { inner, settings } = self;
+        let settings = settings.clone();
+        inner
+            .node_store(node_uri)
+            .map(move |result| {
+                result.map(|inner| CompressedNodePersistence {
+                    inner,
+                    settings,
+                    lanes: Default::default(),
+                    buffer: Default::default(),
+                })
+            })
+            .boxed()
+    }
+
+    fn node_uris(&self) -> BoxFuture<'static, Result<Vec<String>, StoreError>> {
+        self.inner.node_uris()
+    }
+}
+
+/// The ID of a lane in a [`CompressedNodePersistence`] store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressedLaneId<Id> {
+    id: Id,
+    codec: Option<CompressionCodec>,
+    slot: usize, //Index of the metrics for the lane.
+}
+
+/// A [`NodePersistence`] implementation that compresses the values written into another store.
+pub struct CompressedNodePersistence<N> {
+    inner: N,
+    settings: Arc<StoreCompressionConfig>,
+    lanes: Mutex<Vec<Arc<CompressionCounters>>>,
+    buffer: Vec<u8>,
+}
+
+impl<N> CompressedNodePersistence<N> {
+    fn record(&self, slot: usize, uncompressed: usize, stored: usize) {
+        if let Some(counters) = self.lanes.lock().get(slot) {
+            counters.record(uncompressed, stored);
+        }
+    }
+}
+
+impl<N: NodePersistence> NodePersistence for CompressedNodePersistence<N> {
+    type MapCon<'a>
+        = CompressedRangeConsumer<'a, N::MapCon<'a>>
+    where
+        Self: 'a;
+
+    type LaneId = CompressedLaneId<N::LaneId>;
+
+    fn id_for(&self, name: &str) -> Result<Self::LaneId, StoreError> {
+        let CompressedNodePersistence {
+            inner,
+            settings,
+            lanes,
+            ..
+        } = self;
+        let id = inner.id_for(name)?;
+        let codec = settings.codec_for(name);
+        let counters = settings.metrics.counters_for(name);
+        let mut guard = lanes.lock();
+        let slot = match guard.iter().position(|c| Arc::ptr_eq(c, &counters)) {
+            Some(slot) => slot,
+            None => {
+                guard.push(counters);
+                guard.len() - 1
+            }
+        };
+        Ok(CompressedLaneId { id, codec, slot })
+    }
+
+    fn get_value(
+        &self,
+        id: Self::LaneId,
+        buffer: &mut BytesMut,
+    ) -> Result<Option<usize>, StoreError> {
+        let CompressedNodePersistence {
+            inner, settings, ..
+        } = self;
+        let mut stored = BytesMut::new();
+        if inner.get_value(id.id, &mut stored)?.is_some() {
+            settings.decode(&stored, buffer).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn put_value(&mut self, id: Self::LaneId, value: &[u8]) -> Result<(), StoreError> {
+        let CompressedNodePersistence {
+            inner,
+            settings,
+            buffer,
+            ..
+        } = self;
+        settings.encode(id.codec, value, buffer)?;
+        inner.put_value(id.id, buffer)?;
+        let stored = buffer.len();
+        self.record(id.slot, value.len(), stored);
+        Ok(())
+    }
+
+    fn delete_value(&mut self, id: Self::LaneId) -> Result<(), StoreError> {
+        self.inner.delete_value(id.id)
+    }
+
+    fn update_map(&mut self, id: Self::LaneId, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        let CompressedNodePersistence {
+            inner,
+            settings,
+            buffer,
+            ..
+        } = self;
+        settings.encode(id.codec, value, buffer)?;
+        inner.update_map(id.id, key, buffer)?;
+        let stored = key.len() + buffer.len();
+        self.record(id.slot, key.len() + value.len(), stored);
+        Ok(())
+    }
+
+    fn remove_map(&mut self, id: Self::LaneId, key: &[u8]) -> Result<(), StoreError> {
+        self.inner.remove_map(id.id, key)
+    }
+
+    fn clear_map(&mut self, id: Self::LaneId) -> Result<(), StoreError> {
+        self.inner.clear_map(id.id)
+    }
+
+    fn read_map(&self, id: Self::LaneId) -> Result<Self::MapCon<'_>, StoreError> {
+        let CompressedNodePersistence {
+            inner, settings, ..
+        } = self;
+        Ok(CompressedRangeConsumer {
+            inner: inner.read_map(id.id)?,
+            settings,
+            buffer: Default::default(),
+        })
+    }
+}
+
+/// [`RangeConsumer`] that decompresses the values of the entries produced by another consumer.
+pub struct CompressedRangeConsumer<'a, C> {
+    inner: C,
+    settings: &'a StoreCompressionConfig,
+    buffer: Vec<u8>,
+}
+
+impl<'a, C: RangeConsumer> RangeConsumer for CompressedRangeConsumer<'a, C> {
+    fn consume_next(&mut self) -> Result<Option<KeyValue<'_>>, StoreError> {
+        let CompressedRangeConsumer {
+            inner,
+            settings,
+            buffer,
+        } = self;
+        match inner.consume_next()? {
+            Some((key, value)) => {
+                buffer.clear();
+                settings.decode(value, buffer)?;
+                Ok(Some((key, buffer.as_slice())))
+            }
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use bytes::BytesMut;
+use swimos_api::{
+    error::StoreError,
+    persistence::{NodePersistence, PlanePersistence, RangeConsumer},
+};
+
+use crate::in_memory_store::InMemoryPlanePersistence;
+
+use super::{
+    CompressedPlanePersistence, CompressionCodec, StoreCompressionConfig, MARKER, TAG_RAW,
+};
+
+const NODE: &str = "/node";
+const LANE: &str = "lane";
+const OTHER_LANE: &str = "other";
+
+fn record(n: usize) -> Vec<u8> {
+    format!(
+        "@record {{ id: {}, name: sensor, status: active, reading: 0.5 }}",
+        n
+    )
+    .into_bytes()
+}
+
+fn large_value() -> Vec<u8> {
+    (0..16).flat_map(record).collect()
+}
+
+fn open_plane(
+    inner: &InMemoryPlanePersistence,
+    config: StoreCompressionConfig,
+) -> CompressedPlanePersistence<InMemoryPlanePersistence> {
+    CompressedPlanePersistence {
+        inner: inner.clone(),
+        settings: Arc::new(config),
+    }
+}
+
+/// Read the value for a lane directly from the underlying store.
+async fn read_raw(inner: &InMemoryPlanePersistence, lane: &str) -> Option<BytesMut> {
+    let node = inner.node_store(NODE).await.expect("Failed to open node.");
+    let id = node.id_for(lane).expect("No ID.");
+    let mut buffer = BytesMut::new();
+    node.get_value(id, &mut buffer)
+        .expect("Read failed.")
+        .map(|_| buffer)
+}
+
+async fn round_trip_value(codec: CompressionCodec, dictionary: Option<Vec<u8>>) {
+    let inner = InMemoryPlanePersistence::default();
+    let mut config = StoreCompressionConfig::default().with_default_codec(codec);
+    if let Some(dictionary) = dictionary {
+        config = config.with_dictionary(dictionary);
+    }
+    let metrics = config.metrics();
+    let plane = open_plane(&inner, config);
+    let value = large_value();
+
+    {
+        let mut node = plane.node_store(NODE).await.expect("Failed to open node.");
+        let id = node.id_for(LANE).expect("No ID.");
+        node.put_value(id, &value).expect("Write failed.");
+
+        let mut buffer = BytesMut::new();
+        let len = node.get_value(id, &mut buffer).expect("Read failed.");
+        assert_eq!(len, Some(value.len()));
+        assert_eq!(buffer.as_ref(), value.as_slice());
+    }
+
+    let stored = read_raw(&inner, LANE).await.expect("Value not stored.");
+    assert_eq!(stored[0], MARKER);
+    assert!(stored.len() < value.len());
+
+    let stats = metrics.lane(LANE).expect("No metrics for lane.");
+    assert_eq!(stats.uncompressed_bytes, value.len() as u64);
+    assert_eq!(stats.stored_bytes, stored.len() as u64);
+    assert!(stats.ratio().expect("No ratio.") > 1.0);
+    assert_eq!(metrics.total(), stats);
+}
+
+#[tokio::test]
+async fn lz4_value_round_trip() {
+    round_trip_value(CompressionCodec::Lz4, None).await;
+}
+
+#[tokio::test]
+async fn zstd_value_round_trip() {
+    round_trip_value(CompressionCodec::ZSTD_DEFAULT, None).await;
+}
+
+fn dictionary() -> Vec<u8> {
+    (0..64).flat_map(record).collect()
+}
+
+#[tokio::test]
+async fn lz4_value_round_trip_with_dictionary() {
+    round_trip_value(CompressionCodec::Lz4, Some(dictionary())).await;
+}
+
+#[tokio::test]
+async fn zstd_value_round_trip_with_dictionary() {
+    round_trip_value(CompressionCodec::ZSTD_DEFAULT, Some(dictionary())).await;
+}
+
+#[tokio::test]
+async fn map_values_compressed() {
+    let inner = InMemoryPlanePersistence::default();
+    let config = StoreCompressionConfig::default().with_default_codec(CompressionCodec::Lz4);
+    let plane = open_plane(&inner, config);
+    let value = large_value();
+
+    let mut node = plane.node_store(NODE).await.expect("Failed to open node.");
+    let id = node.id_for(LANE).expect("No ID.");
+    node.update_map(id, b"a", &value).expect("Write failed.");
+    node.update_map(id, b"b", b"small").expect("Write failed.");
+
+    let mut consumer = node.read_map(id).expect("Read failed.");
+    let mut entries = vec![];
+    while let Some((key, value)) = consumer.consume_next().expect("Read failed.") {
+        entries.push((key.to_vec(), value.to_vec()));
+    }
+    assert_eq!(
+        entries,
+        vec![(b"a".to_vec(), value), (b"b".to_vec(), b"small".to_vec())]
+    );
+}
+
+#[tokio::test]
+async fn lane_codec_overrides_default() {
+    let inner = InMemoryPlanePersistence::default();
+    let config = StoreCompressionConfig::default()
+        .with_default_codec(CompressionCodec::Lz4)
+        .with_lane_codec(OTHER_LANE, None);
+    let plane = open_plane(&inner, config);
+    let value = large_value();
+
+    {
+        let mut node = plane.node_store(NODE).await.expect("Failed to open node.");
+        let id = node.id_for(LANE).expect("No ID.");
+        let other_id = node.id_for(OTHER_LANE).expect("No ID.");
+        node.put_value(id, &value).expect("Write failed.");
+        node.put_value(other_id, &value).expect("Write failed.");
+    }
+
+    let stored = read_raw(&inner, LANE).await.expect("Value not stored.");
+    assert!(stored.len() < value.len());
+    let other_stored = read_raw(&inner, OTHER_LANE)
+        .await
+        .expect("Value not stored.");
+    assert_eq!(other_stored.as_ref(), value.as_slice());
+}
+
+#[tokio::test]
+async fn small_values_not_compressed() {
+    let inner = InMemoryPlanePersistence::default();
+    let config = StoreCompressionConfig::default().with_default_codec(CompressionCodec::Lz4);
+    let plane = open_plane(&inner, config);
+    let value = record(0);
+    assert!(value.len() < super::DEFAULT_COMPRESSION_THRESHOLD);
+
+    {
+        let mut node = plane.node_store(NODE).await.expect("Failed to open node.");
+        let id = node.id_for(LANE).expect("No ID.");
+        node.put_value(id, &value).expect("Write failed.");
+    }
+
+    let stored = read_raw(&inner, LANE).await.expect("Value not stored.");
+    assert_eq!(stored.as_ref(), value.as_slice());
+}
+
+#[tokio::test]
+async fn values_beginning_with_marker_are_escaped() {
+    let inner = InMemoryPlanePersistence::default();
+    let plane = open_plane(&inner, StoreCompressionConfig::default());
+    let value = [MARKER, 1, 2, 3];
+
+    {
+        let mut node = plane.node_store(NODE).await.expect("Failed to open node.");
+        let id = node.id_for(LANE).expect("No ID.");
+        node.put_value(id, &value).expect("Write failed.");
+
+        let mut buffer = BytesMut::new();
+        node.get_value(id, &mut buffer).expect("Read failed.");
+        assert_eq!(buffer.as_ref(), value.as_slice());
+    }
+
+    let stored = read_raw(&inner, LANE).await.expect("Value not stored.");
+    assert_eq!(stored.as_ref(), &[MARKER, TAG_RAW, MARKER, 1, 2, 3]);
+}
+
+#[tokio::test]
+async fn uncompressed_values_readable() {
+    let inner = InMemoryPlanePersistence::default();
+    let value = large_value();
+    {
+        let mut node = inner.node_store(NODE).await.expect("Failed to open node.");
+        let id = node.id_for(LANE).expect("No ID.");
+        node.put_value(id, &value).expect("Write failed.");
+    }
+
+    let config = StoreCompressionConfig::default().with_default_codec(CompressionCodec::Lz4);
+    let plane = open_plane(&inner, config);
+    let node = plane.node_store(NODE).await.expect("Failed to open node.");
+    let id = node.id_for(LANE).expect("No ID.");
+    let mut buffer = BytesMut::new();
+    node.get_value(id, &mut buffer).expect("Read failed.");
+    assert_eq!(buffer.as_ref(), value.as_slice());
+}
+
+#[tokio::test]
+async fn missing_dictionary_fails_read() {
+    let inner = InMemoryPlanePersistence::default();
+    let value = large_value();
+    {
+        let config = StoreCompressionConfig::default()
+            .with_default_codec(CompressionCodec::ZSTD_DEFAULT)
+            .with_dictionary(dictionary());
+        let plane = open_plane(&inner, config);
+        let mut node = plane.node_store(NODE).await.expect("Failed to open node.");
+        let id = node.id_for(LANE).expect("No ID.");
+        node.put_value(id, &value).expect("Write failed.");
+    }
+
+    let config = StoreCompressionConfig::default().with_default_codec(CompressionCodec::Lz4);
+    let plane = open_plane(&inner, config);
+    let node = plane.node_store(NODE).await.expect("Failed to open node.");
+    let id = node.id_for(LANE).expect("No ID.");
+    let mut buffer = BytesMut::new();
+    let result = node.get_value(id, &mut buffer);
+    assert!(matches!(result, Err(StoreError::Decoding(_))));
+}
@@ -107,6 +107,7 @@ pub use swimos_agent_derive::lifecycle;
 /// This will generate constants called `VALUE_LANE` and `MAP_LANE` with types
 /// `fn(&ExampleAgent) -> &ValueLane<i32>` and `fn(&ExampleAgent) -> &MapLane<String, i32>`,
 /// respectively.
+/// The projection for a field with `cfg` attributes is only generated when the field is included.
 ///
 /// As an example, consider the following `on_start` handler for the above `ExampleAgent`:
 /// ```ignore
@@ -138,23 +139,45 @@ pub use swimos_agent_derive::AgentLaneModel;
 /// The supported lane types are:
 ///
 /// 1. [Value Lanes](`lanes::ValueLane`)
-/// 2. [Command Lanes](`lanes::CommandLane`)
+/// 2. [Command Lanes](`lanes::CommandLane`) (or [Batch Command Lanes](`lanes::BatchCommandLane`))
 /// 3. [Map Lanes](`lanes::MapLane`)
 /// 4. [Join-Value Lanes](`lanes::JoinValueLane`)
 /// 5. [Join-Map Lanes](`lanes::JoinMapLane`)
 /// 6. [Demand Lanes](`lanes::DemandLane`)
 /// 7. [Demand-Map Lanes](`lanes::DemandMapLane`)
 /// 8. [Supply Lanes](`lanes::SupplyLane`)
-/// 9. [HTTP Lanes](`lanes::HttpLane`) (or [Simple HTTP Lanes](`lanes::SimpleHttpLane`))
+/// 9. [History Lanes](`lanes::HistoryLane`)
+/// 10. [Stats Lanes](`lanes::StatsLane`)
+/// 11. [HTTP Lanes](`lanes::HttpLane`) (or [Simple HTTP Lanes](`lanes::SimpleHttpLane`))
+/// 12. [Ordered Map Lanes](`lanes::OrderedMapLane`)
 ///
 /// For [Value Lanes](`lanes::ValueLane`), [Command Lanes](`lanes::CommandLane`), [Demand Lanes](`lanes::DemandLane`) and
 /// [Supply Lanes](`lanes::SupplyLane`), the type parameter must implement the [`swimos_form::Form`] trait (used for serialization
 ///  and deserialization). For [Map Lanes](`lanes::MapLane`), [Demand-Map Lanes](`lanes::MapLane`),
 ///  [Join-Value Lanes](`lanes::JoinValueLane`) and [Join-Map Lanes](`lanes::JoinMapLane`), both parameters must implement
 /// [`swimos_form::Form`] and additionally, the key type `K` must additionally satisfy `K: Hash + Eq + Ord + Clone`.
+/// [Ordered Map Lanes](`lanes::OrderedMapLane`) have the same requirements but keep their entries sorted by key, so
+/// synchronization reports them in key order and may be limited to a range of keys.
+///
+/// For [History Lanes](`lanes::HistoryLane`), the type parameter must implement [`swimos_form::Form`]. History lanes
+/// are always transient and retain the most recent 1024 entries unless another
+/// [retention policy](`lanes::HistoryRetention`) is set when the agent starts. [Stats Lanes](`lanes::StatsLane`) have
+/// no type parameters; they compute [statistics](`lanes::Stats`) over numeric samples and are also always transient.
 ///
 /// Additionally, for [Join-Map Lanes](`lanes::JoinMapLane`), the link key type `L` must satisfy`L: Hash + Eq + Clone`.
 ///
+/// An agent can also include collections of [Value Lanes](`lanes::DynamicValueLanes`) and
+/// [Map Lanes](`lanes::DynamicMapLanes`) that are opened, by name, after the agent has started (using
+/// [`agent_lifecycle::HandlerContext::open_value_lane`] and [`agent_lifecycle::HandlerContext::open_map_lane`]). Commands, sync requests and events
+/// for these lanes are dispatched to the collection and the lanes are always transient.
+///
+/// [Value Lanes](`lanes::ValueLane`), [Command Lanes](`lanes::CommandLane`), [Demand Lanes](`lanes::DemandLane`) and
+/// [Supply Lanes](`lanes::SupplyLane`) can instead use a custom payload codec by using [`lanes::Encoded`] as the type
+/// parameter (for example, `ValueLane<Encoded<RawBytes>>` for binary payloads or `SupplyLane<Encoded<RawBytes>>` to
+/// relay documents that are already encoded, such as those from a connector). Any type implementing
+/// [`lanes::PayloadCodec`] can be used and the runtime treats the payloads as opaque bytes, so remote clients must use
+/// the same codec. If such a lane is persistent, its state is stored in its encoded form.
+///
 /// The supported store types are:
 ///
 /// 1. [Value Stores](`stores::ValueStore`)
@@ -219,6 +242,156 @@ pub use swimos_agent_derive::AgentLaneModel;
 ///     value_lane: ValueLane<i32>,
 /// }
 /// ```
+///
+/// If a persistent item is renamed, its previous name may be recorded with an attribute so that its state
+/// will be moved to the new name when the agent starts:
+///
+/// ```no_run
+/// use swimos::agent::AgentLaneModel;
+/// use swimos::agent::lanes::ValueLane;
+///
+/// #[derive(AgentLaneModel)]
+/// struct RenamedAgent {
+///     #[item(renamed_from = "value_lane")]
+///     counter: ValueLane<i32>,
+/// }
+/// ```
+///
+/// To allow remotes to keep using the previous name of a lane, it may also be given an alias. Links and commands
+/// addressed to the alias are routed to the lane and a warning is emitted on the `warnLog` lane of the node
+/// meta-agent the first time that each remote uses it:
+///
+/// ```no_run
+/// use swimos::agent::AgentLaneModel;
+/// use swimos::agent::lanes::ValueLane;
+///
+/// #[derive(AgentLaneModel)]
+/// struct AliasedAgent {
+///     #[item(renamed_from = "value_lane", alias = "value_lane")]
+///     counter: ValueLane<i32>,
+/// }
+/// ```
+///
+/// Items may be included conditionally with `cfg` attributes (and their attributes may be set with
+/// `cfg_attr`) so that the same agent type can have different sets of lanes in different builds. For example,
+/// the following agent only has a diagnostics lane in debug builds:
+///
+/// ```no_run
+/// use swimos::agent::AgentLaneModel;
+/// use swimos::agent::lanes::ValueLane;
+///
+/// #[derive(AgentLaneModel)]
+/// struct ConditionalAgent {
+///     value_lane: ValueLane<i32>,
+///     #[cfg(debug_assertions)]
+///     #[item(transient)]
+///     diagnostics: ValueLane<String>,
+/// }
+/// ```
+///
+/// The rate at which a [Command Lane](`lanes::CommandLane`) invokes its `on_command` event handler can be limited
+/// with an attribute. Commands that arrive faster than the limit are held in a backlog of at most `max_backlog`
+/// commands and the `overflow` policy (one of `"drop_oldest"`, `"drop_newest"` or `"error"`) determines what
+/// happens when it is full. Any of the parameters may be omitted to use the [defaults](`lanes::CommandLaneLimits`).
+/// A [Batch Command Lane](`lanes::BatchCommandLane`) accepts the same attribute but delivers the entire backlog
+/// to its `on_command` event handler as a single [`Vec`]:
+///
+/// ```no_run
+/// use swimos::agent::{AgentLaneModel, lifecycle};
+/// use swimos::agent::agent_lifecycle::HandlerContext;
+/// use swimos::agent::event_handler::EventHandler;
+/// use swimos::agent::lanes::{BatchCommandLane, CommandLane};
+///
+/// #[derive(AgentLaneModel)]
+/// struct RateLimitedAgent {
+///     #[item(rate_limit(min_interval_ms = 100, max_backlog = 16, overflow = "drop_newest"))]
+///     limited: CommandLane<i32>,
+///     #[item(rate_limit(min_interval_ms = 1000))]
+///     batched: BatchCommandLane<i32>,
+/// }
+///
+/// #[derive(Clone, Copy)]
+/// struct RateLimitedLifecycle;
+///
+/// #[lifecycle(RateLimitedAgent)]
+/// impl RateLimitedLifecycle {
+///     #[on_command(batched)]
+///     fn on_batch(
+///         &self,
+///         context: HandlerContext<RateLimitedAgent>,
+///         commands: &Vec<i32>,
+///     ) -> impl EventHandler<RateLimitedAgent> {
+///         let n = commands.len();
+///         context.effect(move || println!("Received {} commands.", n))
+///     }
+/// }
+/// ```
+///
+/// [Value Lanes](`lanes::ValueLane`) and [Map Lanes](`lanes::MapLane`) can be given a time-to-live with an
+/// attribute. The entries of a map lane that are not written to within this period are removed (generating
+/// remove events) and a value lane that is not written to is reset to the default value of its type:
+///
+/// ```no_run
+/// use swimos::agent::AgentLaneModel;
+/// use swimos::agent::lanes::{MapLane, ValueLane};
+///
+/// #[derive(AgentLaneModel)]
+/// struct ExpiringAgent {
+///     #[item(ttl_ms = 30000)]
+///     latest: ValueLane<Option<String>>,
+///     #[item(ttl_ms = 60000)]
+///     sessions: MapLane<String, u64>,
+/// }
+/// ```
+///
+/// A lane can be marked to track the origins of the commands that it receives (overriding the
+/// `track_origin` setting of the default lane configuration). The origin of a command is then available
+/// to the event handlers that it triggers (see `HandlerContext::command_origin`) and is attached to the
+/// events that the lane sends to its uplinks, so that downlinks to the lane can observe it:
+///
+/// ```no_run
+/// use swimos::agent::AgentLaneModel;
+/// use swimos::agent::lanes::{CommandLane, ValueLane};
+///
+/// #[derive(AgentLaneModel)]
+/// struct TrackingAgent {
+///     #[item(track_origin)]
+///     value_lane: ValueLane<i32>,
+///     #[item(transient, track_origin)]
+///     other: ValueLane<i32>,
+///     command_lane: CommandLane<i32>,
+/// }
+/// ```
+///
+/// The macro can also be applied to generic types (with type or const parameters but not lifetimes). The
+/// type parameters must be bounded, in the definition of the type, such that the type of each item satisfies
+/// the requirements above. Additionally, the type parameters of value-like items must be `Send + 'static`
+/// and, for map-like items, the type parameters and their recognizers must be `Send`. A lifecycle can then be
+/// attached to an instance of the type by giving it a name with a type alias:
+///
+/// ```no_run
+/// use swimos::agent::{AgentLaneModel, lifecycle};
+/// use swimos::agent::lanes::{MapLane, ValueLane};
+/// use swimos::form::Form;
+///
+/// #[derive(AgentLaneModel)]
+/// struct GenericAgent<T>
+/// where
+///     T: Form + Default + Send + 'static,
+///     T::Rec: Send,
+/// {
+///     value_lane: ValueLane<T>,
+///     map_lane: MapLane<i32, T>,
+/// }
+///
+/// type IntAgent = GenericAgent<i32>;
+///
+/// #[derive(Clone, Copy)]
+/// struct IntLifecycle;
+///
+/// #[lifecycle(IntAgent)]
+/// impl IntLifecycle {}
+/// ```
 pub trait AgentLaneModel: agent_model::AgentSpec {}
 
 impl<A> AgentLaneModel for A where A: agent_model::AgentSpec {}
@@ -227,31 +400,36 @@ pub use swimos_agent::agent_lifecycle;
 pub use swimos_agent::downlink_lifecycle;
 pub use swimos_agent::event_handler;
 pub use swimos_agent::reexport;
+pub use swimos_agent::try_handler;
 
 /// Configuration types for downlinks that are started from agent lifecycles.
 pub mod config {
-    pub use swimos_agent::config::{MapDownlinkConfig, SimpleDownlinkConfig};
+    pub use swimos_agent::config::{
+        CommandDownlinkConfig, MapDownlinkConfig, SimpleDownlinkConfig,
+    };
 }
 
 /// Special model types required from some agent event handlers.
 pub mod model {
-    pub use swimos_agent::model::{HttpLaneRequest, MapMessage, Text};
+    pub use swimos_agent::model::{HttpLaneRequest, KeyRange, MapMessage, SyncVersion, Text};
 }
 
 /// Defines the [agent specification](`agent_model::AgentSpec`) trait used to specify the structure of an agent it terms of lanes
 /// and stores.
 pub mod agent_model {
     pub use swimos_agent::agent_model::{
-        AgentModel, AgentSpec, ItemDescriptor, ItemFlags, ItemInitializer, ItemKind, ItemSpec,
-        MapLaneInitializer, MapStoreInitializer, ValueLaneInitializer, ValueStoreInitializer,
-        WriteResult,
+        AgentModel, AgentSpec, ExternalInitializer, ItemDescriptor, ItemFlags, ItemInitializer,
+        ItemKind, ItemSpec, LaneInitError, LaneInitializer, MapLaneInitializer,
+        MapStoreInitializer, OrderedMapLaneInitializer, ValueLaneInitializer,
+        ValueStoreInitializer, WriteResult,
     };
     pub use swimos_api::agent::{LaneKind, StoreKind, WarpLaneKind};
 
     /// Support for executing downlink lifecycles within agents.
     pub mod downlink {
         pub use swimos_agent::agent_model::downlink::{
-            EventDownlinkHandle, MapDownlinkHandle, ValueDownlinkHandle,
+            CommandDownlinkError, CommandDownlinkHandle, EventDownlinkHandle, MapDownlinkHandle,
+            ValueDownlinkHandle,
         };
     }
 }
@@ -264,14 +442,19 @@ pub use swimos_agent::AgentItem;
 pub mod lanes {
 
     pub use swimos_agent::lanes::{
-        CommandLane, DemandLane, DemandMapLane, HttpLane, JoinMapLane, JoinValueLane, LaneItem,
-        LinkClosedResponse, MapLane, SimpleHttpLane, SupplyLane, ValueLane,
+        BatchCommandLane, CommandLane, CommandLaneLimits, DemandLane, DemandMapLane,
+        DynamicLaneSet, DynamicMapLanes, DynamicValueLanes, Encoded, HistoryLane, HistoryRetention,
+        HttpLane, JoinMapLane, JoinValueLane, LaneItem, LinkClosedResponse, MapLane,
+        OrderedMapLane, OverflowPolicy, PayloadCodec, RawBytes, SimpleHttpLane, Stats, StatsLane,
+        SupplyLane, TransactionLanes, ValueLane,
     };
 
     #[doc(hidden)]
     pub mod command {
 
-        pub use swimos_agent::lanes::command::{decode_and_command, DecodeAndCommand};
+        pub use swimos_agent::lanes::command::{
+            decode_and_batch, decode_and_command, DecodeAndBatch, DecodeAndCommand,
+        };
         pub mod lifecycle {
             pub use swimos_agent::lanes::command::lifecycle::StatefulCommandLaneLifecycle;
         }
@@ -295,6 +478,14 @@ pub mod lanes {
         }
     }
 
+    #[doc(hidden)]
+    pub mod dynamic {
+        pub use swimos_agent::lanes::dynamic::{
+            decode_and_apply, decode_and_set, DynamicDecodeAndApply, DynamicDecodeAndSet,
+            DynamicMapLaneSync, DynamicValueLaneSync,
+        };
+    }
+
     #[doc(hidden)]
     pub mod value {
         pub use swimos_agent::lanes::value::{decode_and_set, DecodeAndSet, ValueLaneSync};
@@ -311,6 +502,13 @@ pub mod lanes {
         }
     }
 
+    #[doc(hidden)]
+    pub mod ordered_map {
+        pub use swimos_agent::lanes::ordered_map::{
+            decode_and_apply, DecodeAndApply, OrderedMapLaneSync,
+        };
+    }
+
     #[doc(hidden)]
     pub mod join_map {
         pub use swimos_agent::lanes::join_map::JoinMapLaneSync;
@@ -350,6 +548,16 @@ pub mod lanes {
     pub mod supply {
         pub use swimos_agent::lanes::supply::SupplyLaneSync;
     }
+
+    #[doc(hidden)]
+    pub mod history {
+        pub use swimos_agent::lanes::history::HistoryLaneSync;
+    }
+
+    #[doc(hidden)]
+    pub mod stats {
+        pub use swimos_agent::lanes::stats::StatsLaneSync;
+    }
 }
 
 /// Utility types for building stateful agent lifecycles.
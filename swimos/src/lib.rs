@@ -24,11 +24,22 @@
 //!
 //! 1. `agent` - The API for defining your own agents.
 //! 2. `server` - The SwimOS server, necessary for running a SwimOS application.
-//! 3. `json` - Enables JSON serialization support for HTTP lanes.
+//! 3. `client` - A client for opening downlinks to the lanes of SwimOS applications.
+//! 4. `json` - Enables JSON serialization support for HTTP lanes.
+//!
+//! ## Stability
+//! The items that are exported from this crate make up the supported API of SwimOS and changes to
+//! them follow semantic versioning. The `swimos_*` crates that this crate depends on are
+//! implementation details and may change in any release so should not be imported directly.
 
 #[doc(inline)]
 pub use swimos_model as model;
 
+/// Serialization of Rust types to and from the SwimOS data model. To use the derive macros
+/// without depending on `swimos_form` directly, add `#[form_root(::swimos::form)]` to the type.
+#[doc(inline)]
+pub use swimos_form as form;
+
 /// Defines the low level API for implementing Swim applications.
 pub mod api {
     pub use swimos_api::agent::{Agent, DownlinkKind, UplinkKind};
@@ -58,6 +69,11 @@ pub mod api {
         #[cfg(feature = "server")]
         pub use swimos_server_app::AgentExt;
     }
+
+    /// Limits enforced on the messages received from peers and lanes.
+    pub mod limits {
+        pub use swimos_api::limits::{LimitExceeded, LimitKind, LimitMetrics, Limits};
+    }
 }
 
 /// Retry strategies for processes that could fail.
@@ -94,10 +110,61 @@ pub mod io {
 #[cfg(feature = "server")]
 pub mod server {
     pub use swimos_server_app::{
-        until_termination, BoxServer, DeflateConfig, IntrospectionConfig, RemoteConnectionsConfig,
-        Server, ServerBuilder, ServerHandle, WindowBits,
+        until_termination, AgentStartPolicy, AgentStatus, BoxServer, Cluster, DeflateConfig,
+        DeflateSettings, IntrospectionConfig, PartitionConfig, PlanePeer, RemoteConnectionsConfig,
+        ReplicationConfig, RunningAgent, Server, ServerBuilder, ServerHandle, StateMigrationPolicy,
+        StoreFailureAction, StoreStartupPolicy, WindowBits,
     };
 
+    /// Configuration parameters for the server and its runtime components. The root of the
+    /// configuration is [`config::SwimServerConfig`].
+    pub mod config {
+        pub use swimos_server_app::{
+            AgentRuntimeConfig, AgentRuntimeConfigBuilder, DownlinkRuntimeConfig,
+            DownlinkRuntimeConfigBuilder, HandshakeRateLimit, HttpConfig, HttpConfigBuilder,
+            LinkAdvisoryConfig, QueueOverflow, RemoteConnectionsConfig, StoreStartupPolicy,
+            SwimServerConfig, SwimServerConfigBuilder, UplinkBackpressure,
+        };
+    }
+
+    /// Transports for serving clients on the same host, or in the same process, as the server.
+    pub mod local {
+        pub use swimos_remote::local::InProcessNetworking;
+        #[cfg(unix)]
+        pub use swimos_remote::local::UnixNetworking;
+    }
+
+    /// Transformation of the events sent by agents before they are written to remotes.
+    pub mod intercept {
+        pub use swimos_server_app::{
+            InterceptAction, InterceptRule, InterceptRules, InvalidSelector, OutgoingInterceptor,
+            RemoteEndpoint, RemoteFilter, Selector,
+        };
+    }
+
+    /// Policies for pruning remotes that have no links to the lanes of an agent.
+    pub mod prune {
+        pub use swimos_server_app::{
+            AlwaysPrune, IdleRemote, KeepRecentlyLinked, PruneDecision, PrunePolicy,
+        };
+    }
+
+    /// Recording of the envelopes received and sent by agents and replaying of the recordings.
+    pub mod recording {
+        pub use swimos_server_app::{
+            CaptureDirection, EnvelopeRecording, EnvelopeReplay, InvalidRecord, RecordedEnvelope,
+            ReplayError, ReplayTiming,
+        };
+    }
+
+    /// Compression of the state that agents persist in the store.
+    pub mod compression {
+        pub use swimos_server_app::{
+            train_dictionary, CompressionCodec, CompressionMetrics, CompressionStats,
+            StoreCompressionConfig, DEFAULT_COMPRESSION_THRESHOLD,
+        };
+    }
+
     /// Configuration for TLS support in the server.
     pub mod tls {
         pub use swimos_remote::tls::{
@@ -109,10 +176,58 @@ pub mod server {
     /// Error types that can be produced when initializing and executing the server.
     pub mod errors {
         pub use swimos_remote::tls::TlsError;
-        pub use swimos_remote::ConnectionError;
+        pub use swimos_remote::{BadWarpUrl, ConnectionError};
         pub use swimos_server_app::{
-            AmbiguousRoutes, RegistrationFailed, ServerBuilderError, ServerError, UnresolvableRoute,
+            AddRouteError, AmbiguousRoutes, ConfigError, PromoteError, RegistrationFailed,
+            ServerBuilderError, ServerError, ServerStopped, StopAgentError, UnresolvableRoute,
+            WatchLaneError,
+        };
+    }
+}
+
+/// A client for connecting to SwimOS applications and opening downlinks to their lanes.
+#[cfg(feature = "client")]
+pub mod client {
+    pub use swimos_client::{
+        BasicEventDownlinkLifecycle, BasicMapDownlinkLifecycle, BasicValueDownlinkLifecycle,
+        ClientConfig, ClientHandle, Commander, DownlinkConfig, DownlinkEventSender,
+        DownlinkEventStream, DownlinkOperationResult, Encoded, EventDownlinkBuilder,
+        EventDownlinkEvent, EventDownlinkLifecycle, EventDownlinkView, KeepAlive,
+        MapDownlinkBuilder, MapDownlinkEvent, MapDownlinkLifecycle, MapDownlinkView, PayloadCodec,
+        PoolGauge, RawBytes, RemotePath, RuntimeGauges, SwimClient, SwimClientBuilder,
+        SwimClientTlsBuilder, Url, ValueDownlinkBuilder, ValueDownlinkEvent,
+        ValueDownlinkLifecycle, ValueDownlinkView, WebSocketConfig,
+    };
+
+    /// Configuration for TLS support in the client.
+    pub mod tls {
+        pub use swimos_remote::tls::{CertChain, CertFormat, CertificateFile, ClientConfig};
+    }
+
+    /// DNS resolution for the client.
+    pub mod dns {
+        pub use swimos_remote::dns::{DnsResolver, Resolver, StaticResolver};
+    }
+
+    /// Proxy support for the connections opened by the client.
+    pub mod proxy {
+        pub use swimos_remote::proxy::{ProxyConfig, ProxyCredentials, ProxyKind};
+    }
+
+    /// Transports for connecting to servers on the same host, or in the same process, as the client.
+    pub mod local {
+        pub use swimos_remote::local::InProcessNetworking;
+        #[cfg(unix)]
+        pub use swimos_remote::local::UnixNetworking;
+    }
+
+    /// Error types that can be produced by the client and its downlinks.
+    pub mod errors {
+        pub use swimos_client::{
+            ClientError, CommandError, DownlinkErrorKind, DownlinkRuntimeError,
+            ValueDownlinkOperationError,
         };
+        pub use swimos_remote::tls::TlsError;
     }
 }
 
@@ -11,3 +11,13 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use swimos::agent::lanes::ValueLane;
+use swimos::agent::AgentLaneModel;
+
+#[derive(AgentLaneModel)]
+pub struct WithLifetime<'a> {
+    lane: ValueLane<&'a str>,
+}
+
+fn main() {}
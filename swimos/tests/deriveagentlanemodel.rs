@@ -14,18 +14,25 @@
 
 use std::collections::HashMap;
 use std::fmt::Write;
+use std::num::NonZeroUsize;
+use std::time::Duration;
 
 use swimos::agent::agent_model::ItemFlags;
-use swimos::agent::lanes::{CommandLane, MapLane, ValueLane};
+use swimos::agent::lanes::{
+    BatchCommandLane, CommandLane, CommandLaneLimits, DynamicMapLanes, DynamicValueLanes, Encoded,
+    MapLane, OverflowPolicy, RawBytes, ValueLane,
+};
 use swimos::agent::model::MapMessage;
 use swimos::agent::model::Text;
 use swimos::agent::reexport::bytes::BytesMut;
 use swimos::agent::reexport::uuid::Uuid;
 use swimos::agent::AgentLaneModel;
+use swimos::form::Form;
 use swimos_agent::agent_model::{ItemDescriptor, ItemSpec};
 use swimos_agent::lanes::http::Recon;
 use swimos_agent::lanes::{
-    DemandLane, DemandMapLane, HttpLane, JoinMapLane, JoinValueLane, SimpleHttpLane, SupplyLane,
+    DemandLane, DemandMapLane, HistoryLane, HttpLane, JoinMapLane, JoinValueLane, OrderedMapLane,
+    SimpleHttpLane, StatsLane, SupplyLane,
 };
 use swimos_agent::reexport::bytes::Bytes;
 use swimos_agent::stores::{MapStore, ValueStore};
@@ -189,6 +196,16 @@ fn single_map_lane() {
     check_agent::<SingleMapLane>(vec![persistent_lane(0, "lane", WarpLaneKind::Map)]);
 }
 
+#[test]
+fn single_ordered_map_lane() {
+    #[derive(AgentLaneModel)]
+    struct SingleOrderedMapLane {
+        lane: OrderedMapLane<i32, i32>,
+    }
+
+    check_agent::<SingleOrderedMapLane>(vec![persistent_lane(0, "lane", WarpLaneKind::Map)]);
+}
+
 #[test]
 fn single_map_store() {
     #[derive(AgentLaneModel)]
@@ -199,6 +216,67 @@ fn single_map_store() {
     check_agent::<SingleMapStore>(vec![persistent_store(0, "store", StoreKind::Map)]);
 }
 
+#[test]
+fn encoded_value_lane() {
+    #[derive(AgentLaneModel)]
+    struct EncodedValueLane {
+        lane: ValueLane<Encoded<RawBytes>>,
+    }
+
+    check_agent::<EncodedValueLane>(vec![persistent_lane(0, "lane", WarpLaneKind::Value)]);
+}
+
+#[test]
+fn conditional_lanes() {
+    #[derive(AgentLaneModel)]
+    struct ConditionalLanes {
+        first: ValueLane<i32>,
+        #[cfg(not(test))]
+        excluded: ValueLane<NotAType>,
+        #[cfg(test)]
+        included: MapLane<i32, i32>,
+        #[cfg_attr(test, item(transient))]
+        third: ValueLane<i32>,
+    }
+
+    check_agent::<ConditionalLanes>(vec![
+        persistent_lane(0, "first", WarpLaneKind::Value),
+        persistent_lane(1, "included", WarpLaneKind::Map),
+        transient_lane(2, "third", WarpLaneKind::Value),
+    ]);
+}
+
+#[test]
+fn generic_agent() {
+    #[derive(AgentLaneModel)]
+    struct GenericAgent<S, T>
+    where
+        S: Form + Default + Send + 'static,
+        T: Form + Send + 'static,
+        T::Rec: Send,
+    {
+        value: ValueLane<S>,
+        map: MapLane<i32, T>,
+        command: CommandLane<T>,
+    }
+
+    check_agent::<GenericAgent<i32, Text>>(vec![
+        persistent_lane(0, "value", WarpLaneKind::Value),
+        persistent_lane(1, "map", WarpLaneKind::Map),
+        transient_lane(2, "command", WarpLaneKind::Command),
+    ]);
+}
+
+#[test]
+fn const_generic_agent() {
+    #[derive(AgentLaneModel)]
+    struct ConstGenericAgent<const N: usize> {
+        lane: ValueLane<i32>,
+    }
+
+    check_agent::<ConstGenericAgent<3>>(vec![persistent_lane(0, "lane", WarpLaneKind::Value)]);
+}
+
 #[test]
 fn single_command_lane() {
     #[derive(AgentLaneModel)]
@@ -209,6 +287,16 @@ fn single_command_lane() {
     check_agent::<SingleCommandLane>(vec![transient_lane(0, "lane", WarpLaneKind::Command)]);
 }
 
+#[test]
+fn encoded_command_lane() {
+    #[derive(AgentLaneModel)]
+    struct EncodedCommandLane {
+        lane: CommandLane<Encoded<RawBytes>>,
+    }
+
+    check_agent::<EncodedCommandLane>(vec![transient_lane(0, "lane", WarpLaneKind::Command)]);
+}
+
 #[test]
 fn single_demand_lane() {
     #[derive(AgentLaneModel)]
@@ -239,6 +327,26 @@ fn single_supply_lane() {
     check_agent::<SingleSupplyLane>(vec![transient_lane(0, "lane", WarpLaneKind::Supply)]);
 }
 
+#[test]
+fn single_history_lane() {
+    #[derive(AgentLaneModel)]
+    struct SingleHistoryLane {
+        lane: HistoryLane<i32>,
+    }
+
+    check_agent::<SingleHistoryLane>(vec![transient_lane(0, "lane", WarpLaneKind::Map)]);
+}
+
+#[test]
+fn single_stats_lane() {
+    #[derive(AgentLaneModel)]
+    struct SingleStatsLane {
+        lane: StatsLane,
+    }
+
+    check_agent::<SingleStatsLane>(vec![transient_lane(0, "lane", WarpLaneKind::Value)]);
+}
+
 #[test]
 fn two_value_lanes() {
     #[derive(AgentLaneModel)]
@@ -444,6 +552,53 @@ fn value_lane_tagged_transient() {
     ]);
 }
 
+#[test]
+fn items_with_previous_names() {
+    #[derive(AgentLaneModel)]
+    struct RenamedItems {
+        #[item(renamed_from = "old_first")]
+        first: ValueLane<i32>,
+        #[item(renamed_from = "older_second", renamed_from = "old_second")]
+        second: MapStore<i32, i32>,
+    }
+
+    let (first_name, first) = persistent_lane(0, "first", WarpLaneKind::Value);
+    let (second_name, second) = persistent_store(1, "second", StoreKind::Map);
+    check_agent::<RenamedItems>(vec![
+        (first_name, first.with_previous_names(&["old_first"])),
+        (
+            second_name,
+            second.with_previous_names(&["older_second", "old_second"]),
+        ),
+    ]);
+}
+
+#[test]
+fn lanes_with_aliases() {
+    #[derive(AgentLaneModel)]
+    struct AliasedLanes {
+        #[item(renamed_from = "old_first", alias = "old_first")]
+        first: ValueLane<i32>,
+        #[item(alias = "older_second", alias = "old_second")]
+        second: CommandLane<i32>,
+    }
+
+    let (first_name, first) = persistent_lane(0, "first", WarpLaneKind::Value);
+    let (second_name, second) = transient_lane(1, "second", WarpLaneKind::Command);
+    check_agent::<AliasedLanes>(vec![
+        (
+            first_name,
+            first
+                .with_previous_names(&["old_first"])
+                .with_aliases(&["old_first"]),
+        ),
+        (
+            second_name,
+            second.with_aliases(&["older_second", "old_second"]),
+        ),
+    ]);
+}
+
 #[test]
 fn value_store_tagged_transient() {
     #[derive(AgentLaneModel)]
@@ -842,3 +997,125 @@ fn agent_level_transient_flag() {
         transient_store(3, "fourth", StoreKind::Value),
     ]);
 }
+
+#[test]
+fn dynamic_lanes_are_not_items() {
+    #[derive(AgentLaneModel)]
+    struct WithDynamicLanes {
+        first: ValueLane<i32>,
+        values: DynamicValueLanes<i32>,
+        maps: DynamicMapLanes<i32, i32>,
+    }
+
+    check_agent::<WithDynamicLanes>(vec![persistent_lane(0, "first", WarpLaneKind::Value)]);
+
+    fn check_not_lanes<A: AgentLaneModel>(agent: &A) {
+        assert!(agent
+            .on_value_command("values", get_i32_buffer(4))
+            .is_none());
+        assert!(agent.on_map_command("maps", MapMessage::Clear).is_none());
+        assert!(agent.on_sync("values", SYNC_ID).is_none());
+    }
+
+    let agent = WithDynamicLanes::default();
+    assert!(agent.values.names().is_empty());
+    assert!(agent.maps.names().is_empty());
+    check_not_lanes(&agent);
+}
+
+#[test]
+fn rate_limited_command_lanes() {
+    #[derive(AgentLaneModel)]
+    struct RateLimitedLanes {
+        #[item(rate_limit(min_interval_ms = 100, max_backlog = 8, overflow = "drop_newest"))]
+        first: CommandLane<i32>,
+        #[item(rate_limit(max_backlog = 4))]
+        second: BatchCommandLane<i32>,
+        third: BatchCommandLane<i32>,
+    }
+
+    check_agent::<RateLimitedLanes>(vec![
+        transient_lane(0, "first", WarpLaneKind::Command),
+        transient_lane(1, "second", WarpLaneKind::Command),
+        transient_lane(2, "third", WarpLaneKind::Command),
+    ]);
+
+    let agent = RateLimitedLanes::default();
+    let expected_first =
+        CommandLaneLimits::new(Duration::from_millis(100), NonZeroUsize::new(8).unwrap())
+            .with_overflow(OverflowPolicy::DropNewest);
+    assert_eq!(agent.first.limits(), Some(expected_first));
+    let expected_second = CommandLaneLimits {
+        max_backlog: NonZeroUsize::new(4).unwrap(),
+        ..Default::default()
+    };
+    assert_eq!(agent.second.limits(), Some(expected_second));
+    assert!(agent.second.is_batched());
+    assert_eq!(agent.third.limits(), Some(CommandLaneLimits::default()));
+    assert!(agent.third.is_batched());
+
+    fn check_command<A: AgentLaneModel>(agent: &A, lane: &str) {
+        assert!(agent.on_value_command(lane, get_i32_buffer(4)).is_some());
+    }
+
+    check_command(&agent, "first");
+    check_command(&agent, "second");
+}
+
+#[test]
+fn lanes_with_ttl() {
+    #[derive(AgentLaneModel)]
+    struct ExpiringLanes {
+        #[item(ttl_ms = 500)]
+        first: ValueLane<i32>,
+        #[item(ttl_ms = 30000)]
+        second: MapLane<i32, i32>,
+        third: MapLane<i32, i32>,
+    }
+
+    check_agent::<ExpiringLanes>(vec![
+        persistent_lane(0, "first", WarpLaneKind::Value),
+        persistent_lane(1, "second", WarpLaneKind::Map),
+        persistent_lane(2, "third", WarpLaneKind::Map),
+    ]);
+
+    let agent = ExpiringLanes::default();
+    assert_eq!(agent.first.ttl(), Some(Duration::from_millis(500)));
+    assert_eq!(agent.second.ttl(), Some(Duration::from_secs(30)));
+    assert_eq!(agent.third.ttl(), None);
+}
+
+#[test]
+fn lanes_tracking_origins() {
+    #[derive(AgentLaneModel)]
+    struct TrackingLanes {
+        #[item(track_origin)]
+        first: ValueLane<i32>,
+        #[item(transient, track_origin)]
+        second: MapLane<i32, i32>,
+        #[item(track_origin)]
+        third: CommandLane<i32>,
+        fourth: ValueLane<i32>,
+    }
+
+    let tracking_lane = |id: u64, name: &'static str, kind: WarpLaneKind, flags: ItemFlags| {
+        (
+            name,
+            ItemSpec::new(
+                id,
+                name,
+                ItemDescriptor::WarpLane {
+                    kind,
+                    flags: flags | ItemFlags::TRACK_ORIGIN,
+                },
+            ),
+        )
+    };
+
+    check_agent::<TrackingLanes>(vec![
+        tracking_lane(0, "first", WarpLaneKind::Value, ItemFlags::empty()),
+        tracking_lane(1, "second", WarpLaneKind::Map, ItemFlags::TRANSIENT),
+        tracking_lane(2, "third", WarpLaneKind::Command, ItemFlags::TRANSIENT),
+        persistent_lane(3, "fourth", WarpLaneKind::Value),
+    ]);
+}
@@ -0,0 +1,47 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use swimos::form::Form;
+use swimos::model::{Attr, Item, Value};
+
+// The derivation must only refer to the facade crate.
+#[derive(Debug, PartialEq, Form)]
+#[form_root(::swimos::form)]
+struct Reading {
+    sensor: String,
+    level: i32,
+}
+
+#[test]
+fn derive_form_through_facade() {
+    let reading = Reading {
+        sensor: "a".to_string(),
+        level: 3,
+    };
+
+    let value = reading.as_value();
+    assert_eq!(
+        value,
+        Value::Record(
+            vec![Attr::of("Reading")],
+            vec![
+                Item::Slot(Value::text("sensor"), Value::text("a")),
+                Item::Slot(Value::text("level"), Value::from(3)),
+            ]
+        )
+    );
+
+    let restored = Reading::try_from_value(&value).expect("Reading the value failed.");
+    assert_eq!(restored, reading);
+}
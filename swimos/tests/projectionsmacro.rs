@@ -105,3 +105,19 @@ fn projections_generic() {
         Text::new("hello")
     );
 }
+
+#[test]
+fn projections_conditional_lane() {
+    #[projections]
+    struct ConditionalLane {
+        first: ValueLane<i32>,
+        #[cfg(not(test))]
+        second: ValueLane<NotAType>,
+    }
+
+    let agent = ConditionalLane {
+        first: ValueLane::new(0, 12),
+    };
+
+    assert_eq!(ConditionalLane::FIRST(&agent).read(|n| *n), 12);
+}
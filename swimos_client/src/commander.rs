@@ -13,18 +13,25 @@
 // limitations under the License.
 
 use std::collections::{hash_map::Entry, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use bytes::BytesMut;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
 use ratchet::{
-    CloseCode, CloseReason, NoExt, NoExtProvider, ProtocolRegistry, WebSocket, WebSocketConfig,
+    CloseCode, CloseReason, NoExt, NoExtDecoder, NoExtEncoder, NoExtProvider, ProtocolRegistry,
+    Receiver, Sender, WebSocket, WebSocketConfig,
 };
+use swimos_agent_protocol::{Encoded, PayloadCodec};
 use swimos_form::write::StructuralWritable;
+use swimos_messages::warp::{peel_envelope_header_str, RawEnvelope};
 use swimos_recon::print_recon_compact;
 use swimos_remote::{BadWarpUrl, SchemeHostPort};
 use thiserror::Error;
 use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
 
 #[derive(Debug, Error)]
 pub enum CommandError {
@@ -32,6 +39,14 @@ pub enum CommandError {
     Ratchet(#[from] ratchet::Error),
     #[error("Invalid URL: {0}")]
     Url(#[from] BadWarpUrl),
+    #[error("The server could not dispatch the command.")]
+    NotDispatched,
+    #[error("The connection was closed before the command was dispatched.")]
+    ConnectionClosed,
+    #[error("The encoded payload of the command was not valid UTF-8.")]
+    InvalidPayload,
+    #[error("The command was not acknowledged within {0:?}.")]
+    TimedOut(Duration),
 }
 
 impl From<std::io::Error> for CommandError {
@@ -40,12 +55,156 @@ impl From<std::io::Error> for CommandError {
     }
 }
 
-#[derive(Debug, Default)]
+/// The default period to wait for a command to be acknowledged by the server.
+pub const DEFAULT_ACK_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
 pub struct Commander {
-    websockets: HashMap<SchemeHostPort, (BytesMut, WebSocket<TcpStream, NoExt>)>,
+    websockets: HashMap<SchemeHostPort, Connection>,
+    next_id: u64,
+    ack_timeout: Option<Duration>,
+}
+
+impl Default for Commander {
+    fn default() -> Self {
+        Commander::with_ack_timeout(Some(DEFAULT_ACK_TIMEOUT))
+    }
+}
+
+/// A connection to a host. The connection is read by a separate task that completes the
+/// acknowledgements of the commands that are waiting on it.
+#[derive(Debug)]
+struct Connection {
+    sender: Sender<TcpStream, NoExtEncoder>,
+    pending: Arc<PendingAcks>,
+    reader: JoinHandle<()>,
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+type AckResult = Result<(), CommandError>;
+
+/// The commands, sent over a connection, that are waiting to be acknowledged.
+#[derive(Debug, Default)]
+struct PendingAcks {
+    inner: Mutex<PendingState>,
+}
+
+#[derive(Debug, Default)]
+struct PendingState {
+    closed: bool,
+    waiters: HashMap<u64, AckWaiter>,
+}
+
+#[derive(Debug)]
+struct AckWaiter {
+    node: String,
+    lane: String,
+    tx: oneshot::Sender<AckResult>,
+}
+
+impl PendingAcks {
+    fn register(&self, id: u64, node: &str, lane: &str) -> oneshot::Receiver<AckResult> {
+        let (tx, rx) = oneshot::channel();
+        let mut guard = self.inner.lock().unwrap();
+        if guard.closed {
+            let _ = tx.send(Err(CommandError::ConnectionClosed));
+        } else {
+            guard.waiters.insert(
+                id,
+                AckWaiter {
+                    node: node.to_string(),
+                    lane: lane.to_string(),
+                    tx,
+                },
+            );
+        }
+        rx
+    }
+
+    fn remove(&self, id: u64) {
+        self.inner.lock().unwrap().waiters.remove(&id);
+    }
+
+    fn dispatched(&self, id: u64) {
+        if let Some(AckWaiter { tx, .. }) = self.inner.lock().unwrap().waiters.remove(&id) {
+            let _ = tx.send(Ok(()));
+        }
+    }
+
+    fn unlinked(&self, node: &str, lane: &str) {
+        let mut guard = self.inner.lock().unwrap();
+        let ids = guard
+            .waiters
+            .iter()
+            .filter(|(_, waiter)| waiter.node == node && waiter.lane == lane)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in ids {
+            if let Some(AckWaiter { tx, .. }) = guard.waiters.remove(&id) {
+                let _ = tx.send(Err(CommandError::NotDispatched));
+            }
+        }
+    }
+
+    fn close(&self) {
+        let mut guard = self.inner.lock().unwrap();
+        guard.closed = true;
+        for (_, AckWaiter { tx, .. }) in guard.waiters.drain() {
+            let _ = tx.send(Err(CommandError::ConnectionClosed));
+        }
+    }
+}
+
+/// A command that has been sent and that is waiting to be acknowledged by the server.
+#[must_use]
+#[derive(Debug)]
+pub struct PendingAck {
+    id: u64,
+    rx: oneshot::Receiver<AckResult>,
+    pending: Arc<PendingAcks>,
+    ack_timeout: Option<Duration>,
+}
+
+impl PendingAck {
+    /// Wait for the command to be dispatched. If it is not acknowledged within the timeout of the
+    /// commander that sent it, this will fail with [`CommandError::TimedOut`].
+    pub async fn acknowledged(self) -> Result<(), CommandError> {
+        let PendingAck {
+            id,
+            rx,
+            pending,
+            ack_timeout,
+        } = self;
+        let result = match ack_timeout {
+            Some(period) => match tokio::time::timeout(period, rx).await {
+                Ok(result) => result,
+                Err(_) => {
+                    pending.remove(id);
+                    return Err(CommandError::TimedOut(period));
+                }
+            },
+            None => rx.await,
+        };
+        result.unwrap_or(Err(CommandError::ConnectionClosed))
+    }
 }
 
 impl Commander {
+    /// Create a commander that will wait, at most, for the specified period for an acknowledged
+    /// command to be dispatched. If no timeout is specified, it will wait indefinitely.
+    pub fn with_ack_timeout(ack_timeout: Option<Duration>) -> Self {
+        Commander {
+            websockets: HashMap::new(),
+            next_id: 0,
+            ack_timeout,
+        }
+    }
+
     pub async fn send_command(
         &mut self,
         host: impl AsRef<str>,
@@ -53,36 +212,9 @@ impl Commander {
         lane: impl AsRef<str>,
         body: &impl StructuralWritable,
     ) -> Result<(), CommandError> {
-        let Commander { websockets } = self;
+        let Commander { websockets, .. } = self;
         let shp = host.as_ref().parse::<SchemeHostPort>()?;
-        let (_, ws) = match websockets.entry(shp.clone()) {
-            Entry::Occupied(mut entry) => {
-                let (buffer, ws) = entry.get_mut();
-                ws.write_ping(b"Heartbeat").await?;
-                let is_good = loop {
-                    match ws.read(buffer).await? {
-                        ratchet::Message::Pong(_) => break true,
-                        ratchet::Message::Close(_) => break false,
-                        _ => {}
-                    }
-                };
-                if is_good {
-                    entry.into_mut()
-                } else {
-                    match open_connection(shp).await {
-                        Ok(replacement) => {
-                            *ws = replacement;
-                            entry.into_mut()
-                        }
-                        Err(e) => {
-                            entry.remove();
-                            return Err(e);
-                        }
-                    }
-                }
-            }
-            Entry::Vacant(entry) => entry.insert((BytesMut::new(), open_connection(shp).await?)),
-        };
+        let Connection { sender, .. } = connection(websockets, shp).await?;
         let envelope = format!(
             "@command(node: \"{}\", lane: \"{}\") {}",
             node.as_ref(),
@@ -90,17 +222,128 @@ impl Commander {
             print_recon_compact(body)
         );
 
-        ws.write_text(envelope).await?;
+        sender.write_text(envelope).await?;
+        Ok(())
+    }
+
+    /// Send a command to a lane that uses a custom [`PayloadCodec`] (rather than Recon) for its
+    /// values. The payload is sent, as it is, in a text frame and so the codec must produce valid
+    /// UTF-8.
+    ///
+    /// # Arguments
+    /// * `host` - The host to which the command should be sent.
+    /// * `node` - The node URI of the agent.
+    /// * `lane` - The URI of the lane.
+    /// * `body` - The body of the command.
+    pub async fn send_encoded_command<C: PayloadCodec>(
+        &mut self,
+        host: impl AsRef<str>,
+        node: impl AsRef<str>,
+        lane: impl AsRef<str>,
+        body: &Encoded<C>,
+    ) -> Result<(), CommandError> {
+        let Commander { websockets, .. } = self;
+        let shp = host.as_ref().parse::<SchemeHostPort>()?;
+        let mut payload = BytesMut::new();
+        body.encode(&mut payload);
+        let payload =
+            std::str::from_utf8(payload.as_ref()).map_err(|_| CommandError::InvalidPayload)?;
+        let Connection { sender, .. } = connection(websockets, shp).await?;
+        let envelope = format!(
+            "@command(node: \"{}\", lane: \"{}\") {}",
+            node.as_ref(),
+            lane.as_ref(),
+            payload
+        );
+
+        sender.write_text(envelope).await?;
         Ok(())
     }
 
+    /// Send a command to a lane and wait until the server has dispatched it to the agent. Commands
+    /// that are sent to the same host, through the same commander, will be dispatched in the order
+    /// in which they were sent. If the command is not acknowledged within the timeout of the
+    /// commander, this will fail with [`CommandError::TimedOut`].
+    ///
+    /// # Arguments
+    /// * `host` - The host to which the command should be sent.
+    /// * `node` - The node URI of the agent.
+    /// * `lane` - The URI of the lane.
+    /// * `body` - The body of the command.
+    pub async fn send_command_acked(
+        &mut self,
+        host: impl AsRef<str>,
+        node: impl AsRef<str>,
+        lane: impl AsRef<str>,
+        body: &impl StructuralWritable,
+    ) -> Result<(), CommandError> {
+        self.dispatch_command_acked(host, node, lane, body)
+            .await?
+            .acknowledged()
+            .await
+    }
+
+    /// Send a command to a lane, returning a [`PendingAck`] that can be used to wait until the
+    /// server has dispatched it to the agent. The commander is not required to wait for the
+    /// acknowledgement so other commands may be sent in the meantime. Commands that are sent to the
+    /// same host, through the same commander, will be dispatched in the order in which they were
+    /// sent.
+    ///
+    /// # Arguments
+    /// * `host` - The host to which the command should be sent.
+    /// * `node` - The node URI of the agent.
+    /// * `lane` - The URI of the lane.
+    /// * `body` - The body of the command.
+    pub async fn dispatch_command_acked(
+        &mut self,
+        host: impl AsRef<str>,
+        node: impl AsRef<str>,
+        lane: impl AsRef<str>,
+        body: &impl StructuralWritable,
+    ) -> Result<PendingAck, CommandError> {
+        let Commander {
+            websockets,
+            next_id,
+            ack_timeout,
+        } = self;
+        let shp = host.as_ref().parse::<SchemeHostPort>()?;
+        let Connection {
+            sender, pending, ..
+        } = connection(websockets, shp).await?;
+        let node = node.as_ref();
+        let lane = lane.as_ref();
+        let id = *next_id;
+        *next_id = next_id.wrapping_add(1);
+        let envelope = format!(
+            "@command(node: \"{}\", lane: \"{}\", id: {}) {}",
+            node,
+            lane,
+            id,
+            print_recon_compact(body)
+        );
+
+        let rx = pending.register(id, node, lane);
+        if let Err(err) = sender.write_text(envelope).await {
+            pending.remove(id);
+            return Err(err.into());
+        }
+        Ok(PendingAck {
+            id,
+            rx,
+            pending: pending.clone(),
+            ack_timeout: *ack_timeout,
+        })
+    }
+
     pub async fn close(&mut self) -> Vec<CommandError> {
-        let Commander { websockets } = self;
+        let Commander { websockets, .. } = self;
         let mut closes = std::mem::take(websockets)
-            .into_iter()
-            .map(|(_, (_, mut ws))| {
+            .into_values()
+            .map(|mut connection| {
                 Box::pin(async move {
-                    ws.close(CloseReason::new(CloseCode::Normal, None))
+                    connection
+                        .sender
+                        .close(CloseReason::new(CloseCode::Normal, None))
                         .await
                         .err()
                 })
@@ -116,7 +359,61 @@ impl Commander {
     }
 }
 
-async fn open_connection(shp: SchemeHostPort) -> Result<WebSocket<TcpStream, NoExt>, CommandError> {
+/// Read the frames that are received on a connection, completing the acknowledgements of the
+/// commands that are waiting on it.
+async fn read_acks(mut receiver: Receiver<TcpStream, NoExtDecoder>, pending: Arc<PendingAcks>) {
+    let mut buffer = BytesMut::new();
+    loop {
+        buffer.clear();
+        match receiver.read(&mut buffer).await {
+            Ok(ratchet::Message::Text) => {
+                let Ok(text) = std::str::from_utf8(buffer.as_ref()) else {
+                    continue;
+                };
+                match peel_envelope_header_str(text) {
+                    Ok(RawEnvelope::Dispatched { id, .. }) => pending.dispatched(id),
+                    Ok(RawEnvelope::Unlinked {
+                        node_uri, lane_uri, ..
+                    }) => pending.unlinked(&node_uri, &lane_uri),
+                    _ => {}
+                }
+            }
+            Ok(ratchet::Message::Close(_)) | Err(_) => break,
+            _ => {}
+        }
+    }
+    pending.close();
+}
+
+async fn connection(
+    websockets: &mut HashMap<SchemeHostPort, Connection>,
+    shp: SchemeHostPort,
+) -> Result<&mut Connection, CommandError> {
+    match websockets.entry(shp.clone()) {
+        Entry::Occupied(mut entry) => {
+            let connection = entry.get_mut();
+            let is_good = !connection.reader.is_finished()
+                && connection.sender.write_ping(b"Heartbeat").await.is_ok();
+            if is_good {
+                Ok(entry.into_mut())
+            } else {
+                match open_connection(shp).await {
+                    Ok(replacement) => {
+                        *connection = replacement;
+                        Ok(entry.into_mut())
+                    }
+                    Err(e) => {
+                        entry.remove();
+                        Err(e)
+                    }
+                }
+            }
+        }
+        Entry::Vacant(entry) => Ok(entry.insert(open_connection(shp).await?)),
+    }
+}
+
+async fn open_connection(shp: SchemeHostPort) -> Result<Connection, CommandError> {
     let SchemeHostPort(scheme, host, port) = shp;
     let remote = format!("{}:{}", host, port);
     let url = format!("{}://{}:{}", scheme, host, port);
@@ -131,5 +428,16 @@ async fn open_connection(shp: SchemeHostPort) -> Result<WebSocket<TcpStream, NoE
     )
     .await?
     .into_websocket();
-    Ok(ws)
+    Ok(start_connection(ws)?)
+}
+
+fn start_connection(ws: WebSocket<TcpStream, NoExt>) -> Result<Connection, ratchet::Error> {
+    let (sender, receiver) = ws.split()?;
+    let pending = Arc::new(PendingAcks::default());
+    let reader = tokio::spawn(read_acks(receiver, pending.clone()));
+    Ok(Connection {
+        sender,
+        pending,
+        reader,
+    })
 }
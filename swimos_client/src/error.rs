@@ -17,7 +17,11 @@ use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 use std::time::Duration;
-use swimos_api::error::DownlinkTaskError;
+use swimos_api::{
+    agent::LinkFailure,
+    error::{DownlinkFailureReason, DownlinkTaskError},
+};
+use swimos_remote::ConnectionError;
 use tokio::sync::{mpsc, oneshot, watch};
 use tokio::task::JoinError;
 
@@ -32,16 +36,38 @@ impl Display for TimeoutElapsed {
 
 impl Error for TimeoutElapsed {}
 
+/// The kinds of failure that can be reported by the client. The kind of an error can be used to
+/// decide how to respond to it (for example, whether it is worth retrying the operation).
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum DownlinkErrorKind {
+    /// A connection to the remote host could not be established.
     Connection,
+    /// The host could not be resolved (for example, a DNS lookup failed).
     Unresolvable,
+    /// A TLS connection could not be negotiated with the remote host.
+    Tls,
+    /// The remote host rejected the web-socket handshake.
     WebsocketNegotiationFailed,
+    /// The remote host has no agent at the requested node.
+    NodeNotFound,
+    /// The agent at the requested node has no such lane.
+    LaneNotFound,
+    /// The remote host sent a message that was invalid.
+    ProtocolViolation,
+    /// The remote host stopped.
     RemoteStopped,
+    /// The operation did not complete within its timeout.
     Timeout,
+    /// The client runtime has terminated.
     Terminated,
+    /// The maximum number of connections has been reached.
+    ConnectionLimit,
 }
 
+/// Type alias for the errors that can be produced by the operations of the client. Errors can be
+/// branched on using their [`DownlinkErrorKind`].
+pub type ClientError = DownlinkRuntimeError;
+
 impl Display for DownlinkErrorKind {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -51,9 +77,21 @@ impl Display for DownlinkErrorKind {
             DownlinkErrorKind::Unresolvable => {
                 write!(f, "Host unresolvable")
             }
+            DownlinkErrorKind::Tls => {
+                write!(f, "TLS negotiation failed")
+            }
             DownlinkErrorKind::WebsocketNegotiationFailed => {
                 write!(f, "WebSocket negotiation failed")
             }
+            DownlinkErrorKind::NodeNotFound => {
+                write!(f, "Node not found")
+            }
+            DownlinkErrorKind::LaneNotFound => {
+                write!(f, "Lane not found")
+            }
+            DownlinkErrorKind::ProtocolViolation => {
+                write!(f, "Protocol violation")
+            }
             DownlinkErrorKind::RemoteStopped => {
                 write!(f, "Peer stopped")
             }
@@ -63,6 +101,9 @@ impl Display for DownlinkErrorKind {
             DownlinkErrorKind::Terminated => {
                 write!(f, "Terminated")
             }
+            DownlinkErrorKind::ConnectionLimit => {
+                write!(f, "Connection limit reached")
+            }
         }
     }
 }
@@ -103,8 +144,8 @@ impl DownlinkRuntimeError {
         self.kind
     }
 
-    pub fn is(&self, _kind: DownlinkErrorKind) -> bool {
-        matches!(&self.kind, _kind)
+    pub fn is(&self, kind: DownlinkErrorKind) -> bool {
+        self.kind == kind
     }
 
     pub fn downcast_ref<T: Any + Error>(&self) -> Option<&T> {
@@ -169,7 +210,52 @@ impl From<oneshot::error::RecvError> for DownlinkRuntimeError {
 
 impl From<DownlinkTaskError> for DownlinkRuntimeError {
     fn from(e: DownlinkTaskError) -> Self {
-        DownlinkRuntimeError::with_cause(DownlinkErrorKind::Terminated, e)
+        let kind = match &e {
+            DownlinkTaskError::SyncedWithNoValue
+            | DownlinkTaskError::BadFrame(_)
+            | DownlinkTaskError::DeserializationFailed(_) => DownlinkErrorKind::ProtocolViolation,
+            DownlinkTaskError::LinkFailed(LinkFailure::NodeNotFound) => {
+                DownlinkErrorKind::NodeNotFound
+            }
+            DownlinkTaskError::LinkFailed(LinkFailure::LaneNotFound) => {
+                DownlinkErrorKind::LaneNotFound
+            }
+            DownlinkTaskError::FailedToStart | DownlinkTaskError::Custom(_) => {
+                DownlinkErrorKind::Terminated
+            }
+        };
+        DownlinkRuntimeError::with_cause(kind, e)
+    }
+}
+
+impl From<DownlinkFailureReason> for DownlinkRuntimeError {
+    fn from(e: DownlinkFailureReason) -> Self {
+        let kind = match &e {
+            DownlinkFailureReason::InvalidUrl | DownlinkFailureReason::UnresolvableRemote(_) => {
+                DownlinkErrorKind::Unresolvable
+            }
+            DownlinkFailureReason::UnresolvableLocal(_) => DownlinkErrorKind::NodeNotFound,
+            DownlinkFailureReason::ConnectionFailed(_) => DownlinkErrorKind::Connection,
+            DownlinkFailureReason::TlsConnectionFailed { .. } => DownlinkErrorKind::Tls,
+            DownlinkFailureReason::WebsocketNegotiationFailed(_) => {
+                DownlinkErrorKind::WebsocketNegotiationFailed
+            }
+            DownlinkFailureReason::RemoteStopped => DownlinkErrorKind::RemoteStopped,
+            DownlinkFailureReason::DownlinkStopped => DownlinkErrorKind::Terminated,
+        };
+        DownlinkRuntimeError::with_cause(kind, e)
+    }
+}
+
+impl From<ConnectionError> for DownlinkRuntimeError {
+    fn from(e: ConnectionError) -> Self {
+        let kind = match &e {
+            ConnectionError::ConnectionFailed(_) | ConnectionError::BadParameter(_) => {
+                DownlinkErrorKind::Connection
+            }
+            ConnectionError::NegotiationFailed(_) => DownlinkErrorKind::Tls,
+        };
+        DownlinkRuntimeError::with_cause(kind, e)
     }
 }
 
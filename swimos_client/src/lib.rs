@@ -13,41 +13,65 @@
 // limitations under the License.
 
 use std::time::Duration;
-use std::{marker::PhantomData, num::NonZeroUsize, sync::Arc};
+use std::{future::Future, marker::PhantomData, num::NonZeroUsize, sync::Arc};
 
 use futures_util::future::BoxFuture;
 use ratchet::WebSocketStream;
 use rustls::crypto::CryptoProvider;
 
-pub use commander::{CommandError, Commander};
+pub use commander::{CommandError, Commander, PendingAck, DEFAULT_ACK_TIMEOUT};
+pub use swimos_agent_protocol::{Encoded, PayloadCodec, RawBytes};
+pub use swimos_api::limits::{LimitExceeded, LimitKind, LimitMetrics, Limits};
 pub use swimos_client_api::DownlinkConfig;
+use swimos_downlink::{
+    downlink_event_stream, ChannelError, DownlinkTask, EventDownlinkModel, MapDownlinkHandle,
+    MapDownlinkModel, MapKey, MapValue, NotYetSyncedError, ValueDownlinkModel, ValueDownlinkSet,
+    ValueDownlinkState,
+};
 pub use swimos_downlink::{
     lifecycle::BasicEventDownlinkLifecycle, lifecycle::BasicMapDownlinkLifecycle,
     lifecycle::BasicValueDownlinkLifecycle, lifecycle::EventDownlinkLifecycle,
-    lifecycle::MapDownlinkLifecycle, lifecycle::ValueDownlinkLifecycle,
+    lifecycle::MapDownlinkLifecycle, lifecycle::StatefulEventDownlinkLifecycle,
+    lifecycle::StatefulMapDownlinkLifecycle, lifecycle::StatefulValueDownlinkLifecycle,
+    lifecycle::StatelessEventDownlinkLifecycle, lifecycle::StatelessMapDownlinkLifecycle,
+    lifecycle::StatelessValueDownlinkLifecycle, lifecycle::ValueDownlinkLifecycle,
 };
-use swimos_downlink::{
-    ChannelError, DownlinkTask, EventDownlinkModel, MapDownlinkHandle, MapDownlinkModel, MapKey,
-    MapValue, NotYetSyncedError, ValueDownlinkModel, ValueDownlinkSet,
+pub use swimos_downlink::{
+    DownlinkEventSender, DownlinkEventStream, EventDownlinkEvent, MapDownlinkEvent,
+    ValueDownlinkEvent,
 };
-use swimos_form::Form;
+use swimos_form::{write::StructuralWritable, Form};
+pub use swimos_remote::local::InProcessNetworking;
+#[cfg(unix)]
+pub use swimos_remote::local::UnixNetworking;
 use swimos_remote::{
     dns::Resolver,
     plain::TokioPlainTextNetworking,
+    proxy::ProxyConfig,
     tls::CryptoProviderConfig,
     tls::{ClientConfig as TlsConfig, RustlsClientNetworking, TlsError},
     websocket::RatchetClient,
     ClientConnections,
 };
 use swimos_runtime::downlink::{DownlinkOptions, DownlinkRuntimeConfig};
+pub use swimos_utilities::future::{Quantity, RetryStrategy};
 use swimos_utilities::{non_zero_usize, trigger, trigger::promise};
-use tokio::{sync::mpsc, sync::mpsc::error::SendError, sync::oneshot::error::RecvError};
+use tokio::{
+    sync::mpsc, sync::mpsc::error::SendError, sync::oneshot::error::RecvError, sync::watch,
+};
 pub use url::Url;
 
+pub use crate::error::{ClientError, DownlinkErrorKind, DownlinkRuntimeError};
 pub use crate::models::RemotePath;
+pub use crate::pool::{PoolGauge, RuntimeGauges};
 use crate::{
-    error::DownlinkRuntimeError, runtime::start_runtime, runtime::RawHandle, transport::Transport,
+    runtime::{start_runtime, RawHandle, RuntimeLimits},
+    transport::{ConnectionConfig, Transport},
 };
+use swimos_model::Value;
+#[cfg(feature = "deflate")]
+pub use swimos_remote::websocket::DeflateSettings;
+pub use swimos_remote::{websocket::WarpEncoding, KeepAlive};
 
 #[cfg(test)]
 mod tests;
@@ -56,6 +80,7 @@ mod commander;
 mod error;
 mod models;
 mod pending;
+mod pool;
 mod runtime;
 mod transport;
 
@@ -63,12 +88,22 @@ pub type DownlinkOperationResult<T> = Result<T, DownlinkRuntimeError>;
 
 const DEFAULT_BUFFER_SIZE: NonZeroUsize = non_zero_usize!(32);
 const DEFAULT_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_MAX_PENDING_CONNECTIONS: NonZeroUsize = non_zero_usize!(32);
+const DEFAULT_MAX_PENDING_ATTACHMENTS: NonZeroUsize = non_zero_usize!(64);
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_KEEP_ALIVE: KeepAlive = KeepAlive {
+    interval: Duration::from_secs(30),
+    timeout: Duration::from_secs(90),
+};
 
 #[derive(Debug)]
 pub struct WebSocketConfig {
     pub max_message_size: usize,
     #[cfg(feature = "deflate")]
     pub deflate_config: Option<ratchet::deflate::DeflateConfig>,
+    /// Messages with payloads smaller than this (in bytes) will not be compressed.
+    #[cfg(feature = "deflate")]
+    pub deflate_threshold: usize,
 }
 
 impl Default for WebSocketConfig {
@@ -77,6 +112,8 @@ impl Default for WebSocketConfig {
             max_message_size: 64 << 20,
             #[cfg(feature = "deflate")]
             deflate_config: None,
+            #[cfg(feature = "deflate")]
+            deflate_threshold: 0,
         }
     }
 }
@@ -89,6 +126,20 @@ pub struct ClientConfig {
     pub registration_buffer_size: NonZeroUsize,
     pub close_timeout: Duration,
     pub interpret_frame_data: bool,
+    pub max_pending_connections: NonZeroUsize,
+    pub max_pending_attachments: NonZeroUsize,
+    pub max_connections: Option<NonZeroUsize>,
+    pub idle_timeout: Option<Duration>,
+    pub keep_alive: Option<KeepAlive>,
+    /// The encoding of Warp envelopes to request when opening connections. If a server does not
+    /// support it, the connection will fall back to Recon text.
+    pub warp_encoding: WarpEncoding,
+    /// Limits on the envelopes received from remote hosts. A connection that receives an envelope
+    /// that violates them is closed.
+    pub limits: Limits,
+    /// The period to wait for an acknowledged command to be dispatched. If this is not set,
+    /// sending an acknowledged command will wait indefinitely.
+    pub command_ack_timeout: Option<Duration>,
 }
 
 impl Default for ClientConfig {
@@ -100,6 +151,14 @@ impl Default for ClientConfig {
             registration_buffer_size: DEFAULT_BUFFER_SIZE,
             close_timeout: DEFAULT_CLOSE_TIMEOUT,
             interpret_frame_data: true,
+            max_pending_connections: DEFAULT_MAX_PENDING_CONNECTIONS,
+            max_pending_attachments: DEFAULT_MAX_PENDING_ATTACHMENTS,
+            max_connections: None,
+            idle_timeout: Some(DEFAULT_IDLE_TIMEOUT),
+            keep_alive: Some(DEFAULT_KEEP_ALIVE),
+            warp_encoding: WarpEncoding::Text,
+            limits: Limits::default(),
+            command_ack_timeout: Some(DEFAULT_ACK_TIMEOUT),
         }
     }
 }
@@ -107,11 +166,32 @@ impl Default for ClientConfig {
 #[derive(Debug, Default)]
 pub struct SwimClientBuilder {
     client_config: ClientConfig,
+    resolver: Option<Resolver>,
+    proxy: Option<ProxyConfig>,
 }
 
 impl SwimClientBuilder {
     pub fn new(client_config: ClientConfig) -> SwimClientBuilder {
-        SwimClientBuilder { client_config }
+        SwimClientBuilder {
+            client_config,
+            resolver: None,
+            proxy: None,
+        }
+    }
+
+    /// Sets the DNS resolver that is used to find the addresses of remote hosts. If this is not
+    /// set, the default resolver is used. A resolver backed by a custom implementation (for
+    /// example, a static table of hosts) can be created with [`Resolver::custom`].
+    pub fn set_dns_resolver(mut self, to: Resolver) -> SwimClientBuilder {
+        self.resolver = Some(to);
+        self
+    }
+
+    /// Sets a proxy through which all outgoing connections are opened. For secure connections,
+    /// the TLS session is negotiated through the tunnel provided by the proxy.
+    pub fn set_proxy(mut self, to: ProxyConfig) -> SwimClientBuilder {
+        self.proxy = Some(to);
+        self
     }
 
     /// Sets the websocket configuration.
@@ -132,6 +212,56 @@ impl SwimClientBuilder {
         self
     }
 
+    /// Sets the maximum number of DNS lookups and connection attempts that will run concurrently.
+    /// Any further requests are queued until one of these completes.
+    pub fn set_max_pending_connections(mut self, to: NonZeroUsize) -> SwimClientBuilder {
+        self.client_config.max_pending_connections = to;
+        self
+    }
+
+    /// Sets the maximum number of downlinks that will be attached to their runtimes (starting the
+    /// runtimes if required) concurrently. Any further requests are queued until one of these
+    /// completes.
+    pub fn set_max_pending_attachments(mut self, to: NonZeroUsize) -> SwimClientBuilder {
+        self.client_config.max_pending_attachments = to;
+        self
+    }
+
+    /// Sets the maximum number of connections to remote hosts that may be open at once. If this
+    /// is reached, unused connections are closed to make room and, if there are none, opening
+    /// a downlink to a new host will fail.
+    pub fn set_max_connections(mut self, to: Option<NonZeroUsize>) -> SwimClientBuilder {
+        self.client_config.max_connections = to;
+        self
+    }
+
+    /// Sets the period after which connections that are not being used by any downlinks are
+    /// closed. If this is not set, such connections remain open.
+    pub fn set_idle_timeout(mut self, to: Option<Duration>) -> SwimClientBuilder {
+        self.client_config.idle_timeout = to;
+        self
+    }
+
+    /// Sets the keep-alive configuration for connections to remote hosts. If this is not set,
+    /// pings will not be sent and unresponsive peers will not be detected.
+    pub fn set_keep_alive(mut self, to: Option<KeepAlive>) -> SwimClientBuilder {
+        self.client_config.keep_alive = to;
+        self
+    }
+
+    /// Sets the limits on the envelopes received from remote hosts.
+    pub fn set_limits(mut self, to: Limits) -> SwimClientBuilder {
+        self.client_config.limits = to;
+        self
+    }
+
+    /// Sets the period to wait for an acknowledged command to be dispatched. If this is not set,
+    /// sending an acknowledged command will wait indefinitely.
+    pub fn set_command_ack_timeout(mut self, to: Option<Duration>) -> SwimClientBuilder {
+        self.client_config.command_ack_timeout = to;
+        self
+    }
+
     /// Sets the deflate extension configuration for WebSocket connections.
     #[cfg(feature = "deflate")]
     pub fn set_deflate_config(mut self, to: ratchet::deflate::DeflateConfig) -> SwimClientBuilder {
@@ -139,28 +269,61 @@ impl SwimClientBuilder {
         self
     }
 
+    /// Sets the deflate extension configuration for WebSocket connections, including the size
+    /// below which messages will not be compressed.
+    #[cfg(feature = "deflate")]
+    pub fn set_deflate_settings(mut self, to: DeflateSettings) -> SwimClientBuilder {
+        let DeflateSettings { config, threshold } = to;
+        self.client_config.websocket.deflate_config = Some(config);
+        self.client_config.websocket.deflate_threshold = threshold;
+        self
+    }
+
     /// Enables TLS support.
     pub fn set_tls_config(self, tls_config: TlsConfig) -> SwimClientTlsBuilder {
         SwimClientTlsBuilder {
             client_config: self.client_config,
+            resolver: self.resolver,
+            proxy: self.proxy,
             tls_config,
             crypto_provider: Default::default(),
         }
     }
 
+    /// Builds the client using an alternative networking implementation (for example, an
+    /// [`InProcessNetworking`] transport that is shared with a server in the same process). The
+    /// DNS resolver of the networking implementation is used, rather than any resolver set on
+    /// the builder.
+    pub async fn build_with_networking<Net>(
+        self,
+        networking: Net,
+    ) -> (SwimClient, BoxFuture<'static, ()>)
+    where
+        Net: ClientConnections,
+        Net::ClientSocket: WebSocketStream,
+    {
+        open_client(self.client_config, networking).await
+    }
+
     /// Builds the client.
     pub async fn build(self) -> (SwimClient, BoxFuture<'static, ()>) {
-        let SwimClientBuilder { client_config } = self;
-        open_client(
+        let SwimClientBuilder {
             client_config,
-            TokioPlainTextNetworking::new(Arc::new(Resolver::new().await)),
-        )
-        .await
+            resolver,
+            proxy,
+        } = self;
+        let mut networking = TokioPlainTextNetworking::new(Arc::new(resolve_with(resolver).await));
+        if let Some(proxy) = proxy {
+            networking = networking.with_proxy(proxy);
+        }
+        open_client(client_config, networking).await
     }
 }
 
 pub struct SwimClientTlsBuilder {
     client_config: ClientConfig,
+    resolver: Option<Resolver>,
+    proxy: Option<ProxyConfig>,
     tls_config: TlsConfig,
     crypto_provider: CryptoProviderConfig,
 }
@@ -184,18 +347,27 @@ impl SwimClientTlsBuilder {
     pub async fn build(self) -> Result<(SwimClient, BoxFuture<'static, ()>), TlsError> {
         let SwimClientTlsBuilder {
             client_config,
+            resolver,
+            proxy,
             tls_config,
             crypto_provider,
         } = self;
-        Ok(open_client(
-            client_config,
-            RustlsClientNetworking::build(
-                Arc::new(Resolver::new().await),
-                tls_config,
-                crypto_provider.try_build()?,
-            )?,
-        )
-        .await)
+        let mut networking = RustlsClientNetworking::build(
+            Arc::new(resolve_with(resolver).await),
+            tls_config,
+            crypto_provider.try_build()?,
+        )?;
+        if let Some(proxy) = proxy {
+            networking = networking.with_proxy(proxy);
+        }
+        Ok(open_client(client_config, networking).await)
+    }
+}
+
+async fn resolve_with(resolver: Option<Resolver>) -> Resolver {
+    match resolver {
+        Some(resolver) => resolver,
+        None => Resolver::new().await,
     }
 }
 
@@ -214,8 +386,25 @@ where
         registration_buffer_size,
         close_timeout,
         interpret_frame_data,
-        ..
+        max_pending_connections,
+        max_pending_attachments,
+        max_connections,
+        idle_timeout,
+        keep_alive,
+        warp_encoding,
+        limits: envelope_limits,
+        command_ack_timeout,
     } = config;
+    let connections = ConnectionConfig {
+        max_connections,
+        idle_timeout,
+        keep_alive,
+        limits: envelope_limits,
+    };
+    let limits = RuntimeLimits {
+        max_pending_connections,
+        max_pending_attachments,
+    };
 
     let (stop_tx, stop_rx) = trigger::trigger();
 
@@ -224,11 +413,14 @@ where
         {
             let websockets = RatchetClient::from(ratchet::WebSocketConfig {
                 max_message_size: websocket.max_message_size,
-            });
+            })
+            .with_encoding(warp_encoding);
 
-            let provider = ratchet::deflate::DeflateExtProvider::with_config(
-                websocket.deflate_config.unwrap_or_default(),
-            );
+            let provider = DeflateSettings {
+                config: websocket.deflate_config.unwrap_or_default(),
+                threshold: websocket.deflate_threshold,
+            }
+            .provider();
 
             start_runtime(
                 registration_buffer_size,
@@ -239,16 +431,19 @@ where
                     provider,
                     remote_buffer_size,
                     close_timeout,
+                    connections,
                 ),
                 transport_buffer_size,
                 interpret_frame_data,
+                limits,
             )
         }
         #[cfg(not(feature = "deflate"))]
         {
             let websockets = RatchetClient::from(ratchet::WebSocketConfig {
                 max_message_size: websocket.max_message_size,
-            });
+            })
+            .with_encoding(warp_encoding);
 
             start_runtime(
                 registration_buffer_size,
@@ -259,18 +454,18 @@ where
                     ratchet::NoExtProvider,
                     remote_buffer_size,
                     close_timeout,
+                    connections,
                 ),
                 transport_buffer_size,
                 interpret_frame_data,
+                limits,
             )
         }
     };
 
     let client = SwimClient {
         stop_tx,
-        handle: ClientHandle {
-            inner: Arc::new(handle),
-        },
+        handle: ClientHandle::new(handle, command_ack_timeout),
     };
     (client, task)
 }
@@ -287,6 +482,12 @@ impl SwimClient {
         self.handle.clone()
     }
 
+    /// Returns the gauges reporting the number of connection and attachment tasks that are running,
+    /// or queued, in the runtime.
+    pub fn gauges(&self) -> &RuntimeGauges {
+        self.handle.inner.gauges()
+    }
+
     /// Triggers the runtime to shutdown and awaits its competition.
     pub async fn shutdown(self) {
         self.stop_tx.trigger();
@@ -298,18 +499,54 @@ impl SwimClient {
 #[derive(Debug, Clone)]
 pub struct ClientHandle {
     inner: Arc<RawHandle>,
+    commander: Arc<tokio::sync::Mutex<Commander>>,
 }
 
 impl ClientHandle {
+    fn new(handle: RawHandle, command_ack_timeout: Option<Duration>) -> Self {
+        ClientHandle {
+            inner: Arc::new(handle),
+            commander: Arc::new(tokio::sync::Mutex::new(Commander::with_ack_timeout(
+                command_ack_timeout,
+            ))),
+        }
+    }
+
     /// Returns a future that completes when the runtime has shutdown.
     pub async fn completed(&self) {
         self.inner.completed().await;
     }
 
+    /// Send a command to a lane, completing when the server has dispatched the command to the
+    /// lane. Commands sent through (clones of) the same handle are dispatched in the order in which
+    /// they were sent. If the command is not acknowledged within the configured timeout, this will
+    /// fail with [`CommandError::TimedOut`].
+    ///
+    /// # Arguments
+    /// * `host` - The host to which the command should be sent.
+    /// * `node` - The node URI of the agent.
+    /// * `lane` - The URI of the lane.
+    /// * `body` - The body of the command.
+    pub async fn send_command_acked<T: StructuralWritable>(
+        &self,
+        host: impl AsRef<str>,
+        node: impl AsRef<str>,
+        lane: impl AsRef<str>,
+        body: &T,
+    ) -> Result<(), CommandError> {
+        let pending = {
+            let mut commander = self.commander.lock().await;
+            commander
+                .dispatch_command_acked(host, node, lane, body)
+                .await?
+        };
+        pending.acknowledged().await
+    }
+
     /// Returns a value downlink builder initialised with the default options.
     ///
     /// # Arguments
-    /// * `path` - The path of the downlink top open.
+    /// * `path` - The path of the downlink to open.
     pub fn value_downlink<T>(
         &self,
         path: RemotePath,
@@ -321,13 +558,16 @@ impl ClientHandle {
             options: DownlinkOptions::SYNC,
             runtime_config: Default::default(),
             downlink_config: Default::default(),
+            timeout: None,
         }
     }
 
-    /// Returns an event downlink builder initialised with the default options.
+    /// Returns an event downlink builder initialised with the default options. Event downlinks
+    /// receive the events of the lane without maintaining any local state so, by default, they
+    /// do not request that the lane is synchronized.
     ///
     /// # Arguments
-    /// * `path` - The path of the downlink top open.
+    /// * `path` - The path of the downlink to open.
     pub fn event_downlink<T>(
         &self,
         path: RemotePath,
@@ -336,16 +576,17 @@ impl ClientHandle {
             handle: self,
             lifecycle: BasicEventDownlinkLifecycle::default(),
             path,
-            options: DownlinkOptions::SYNC,
+            options: DownlinkOptions::empty(),
             runtime_config: Default::default(),
             downlink_config: Default::default(),
+            timeout: None,
         }
     }
 
     /// Returns a map downlink builder initialised with the default options.
     ///
     /// # Arguments
-    /// * `path` - The path of the downlink top open.
+    /// * `path` - The path of the downlink to open.
     pub fn map_downlink<K, V>(
         &self,
         path: RemotePath,
@@ -357,8 +598,35 @@ impl ClientHandle {
             options: DownlinkOptions::SYNC,
             runtime_config: Default::default(),
             downlink_config: Default::default(),
+            timeout: None,
+            max_entries: None,
         }
     }
+
+    /// Returns a value downlink builder, initialised with the default options, for a lane with a
+    /// schema that is not known in advance. The state of the lane is delivered as [`Value`]s.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the downlink to open.
+    pub fn untyped_value_downlink(
+        &self,
+        path: RemotePath,
+    ) -> ValueDownlinkBuilder<'_, BasicValueDownlinkLifecycle<Value>> {
+        self.value_downlink(path)
+    }
+
+    /// Returns a map downlink builder, initialised with the default options, for a lane with a
+    /// schema that is not known in advance. The keys and values of the entries of the lane are
+    /// delivered as [`Value`]s.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the downlink to open.
+    pub fn untyped_map_downlink(
+        &self,
+        path: RemotePath,
+    ) -> MapDownlinkBuilder<'_, BasicMapDownlinkLifecycle<Value, Value>> {
+        self.map_downlink(path)
+    }
 }
 
 /// A builder for value downlinks.
@@ -369,6 +637,7 @@ pub struct ValueDownlinkBuilder<'h, L> {
     options: DownlinkOptions,
     runtime_config: DownlinkRuntimeConfig,
     downlink_config: DownlinkConfig,
+    timeout: Option<Duration>,
 }
 
 impl<'h, L> ValueDownlinkBuilder<'h, L> {
@@ -384,6 +653,7 @@ impl<'h, L> ValueDownlinkBuilder<'h, L> {
             options,
             runtime_config,
             downlink_config,
+            timeout,
             ..
         } = self;
         ValueDownlinkBuilder {
@@ -393,6 +663,7 @@ impl<'h, L> ValueDownlinkBuilder<'h, L> {
             options,
             runtime_config,
             downlink_config,
+            timeout,
         }
     }
 
@@ -414,6 +685,14 @@ impl<'h, L> ValueDownlinkBuilder<'h, L> {
         self
     }
 
+    /// Sets a timeout for opening the downlink. If the downlink has not been attached to its
+    /// runtime within the timeout, opening it will fail with a [`DownlinkErrorKind::Timeout`]
+    /// error.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     /// Attempts to open the downlink.
     pub async fn open<T>(self) -> Result<ValueDownlinkView<T>, Arc<DownlinkRuntimeError>>
     where
@@ -428,21 +707,70 @@ impl<'h, L> ValueDownlinkBuilder<'h, L> {
             options,
             runtime_config,
             downlink_config,
+            timeout,
         } = self;
         let (handle_tx, handle_rx) = mpsc::channel(downlink_config.buffer_size.get());
-        let task = DownlinkTask::new(ValueDownlinkModel::new(handle_rx, lifecycle));
-        let stop_rx = handle
-            .inner
-            .run_downlink(path, runtime_config, downlink_config, options, task)
-            .await?;
+        let (state_tx, state_rx) = watch::channel(ValueDownlinkState::default());
+        let model = ValueDownlinkModel::new(handle_rx, lifecycle).with_state_watch(state_tx);
+        let task = DownlinkTask::new(model);
+        let stop_rx = with_timeout(
+            timeout,
+            handle
+                .inner
+                .run_downlink(path, runtime_config, downlink_config, options, task),
+        )
+        .await?;
 
         Ok(ValueDownlinkView {
             tx: handle_tx,
+            state_rx,
             stop_rx,
         })
     }
 }
 
+impl<'h, T> ValueDownlinkBuilder<'h, BasicValueDownlinkLifecycle<T>> {
+    /// Attempts to open the downlink, delivering its events as a stream rather than to a
+    /// lifecycle. If the consumer of the stream falls behind, the downlink will stop reading
+    /// from the lane until it catches up.
+    ///
+    /// # Arguments
+    /// * `buffer_size` - The number of events that can be buffered by the stream.
+    pub async fn open_stream(
+        self,
+        buffer_size: NonZeroUsize,
+    ) -> Result<
+        (
+            ValueDownlinkView<T>,
+            DownlinkEventStream<ValueDownlinkEvent<T>>,
+        ),
+        Arc<DownlinkRuntimeError>,
+    >
+    where
+        T: Send + Sync + Form + Clone + 'static,
+        T::Rec: Send,
+    {
+        let (sender, events) = downlink_event_stream(buffer_size);
+        let view = self.lifecycle(sender).open().await?;
+        Ok((view, events))
+    }
+}
+
+async fn with_timeout<F, T>(
+    timeout: Option<Duration>,
+    fut: F,
+) -> Result<T, Arc<DownlinkRuntimeError>>
+where
+    F: Future<Output = Result<T, Arc<DownlinkRuntimeError>>>,
+{
+    match timeout {
+        Some(period) => tokio::time::timeout(period, fut)
+            .await
+            .unwrap_or_else(|_| Err(DownlinkRuntimeError::timed_out(period).shared())),
+        None => fut.await,
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ValueDownlinkOperationError {
     #[error("Downlink has not yet synced")]
@@ -473,6 +801,7 @@ impl From<NotYetSyncedError> for ValueDownlinkOperationError {
 #[derive(Debug, Clone)]
 pub struct ValueDownlinkView<T> {
     tx: mpsc::Sender<ValueDownlinkSet<T>>,
+    state_rx: watch::Receiver<ValueDownlinkState<T>>,
     stop_rx: promise::Receiver<Result<(), Arc<DownlinkRuntimeError>>>,
 }
 
@@ -483,6 +812,41 @@ impl<T> ValueDownlinkView<T> {
         Ok(())
     }
 
+    /// Gets the last value that the downlink received after it synced with the lane.
+    pub fn get(&self) -> Result<T, ValueDownlinkOperationError>
+    where
+        T: Clone,
+    {
+        match &self.state_rx.borrow().value {
+            Some(value) => Ok(value.clone()),
+            None if self.state_rx.has_changed().is_err() => {
+                Err(ValueDownlinkOperationError::DownlinkStopped)
+            }
+            None => Err(NotYetSyncedError.into()),
+        }
+    }
+
+    /// Waits until the downlink is linked to the lane. This fails if the downlink stops before
+    /// it links.
+    pub async fn await_linked(&self) -> Result<(), ValueDownlinkOperationError> {
+        self.await_state(|state| state.linked).await
+    }
+
+    /// Waits until the downlink has synced with the lane, after which [`Self::get`] will
+    /// return a value. This fails if the downlink stops before it syncs.
+    pub async fn await_synced(&self) -> Result<(), ValueDownlinkOperationError> {
+        self.await_state(|state| state.synced).await
+    }
+
+    async fn await_state<F>(&self, f: F) -> Result<(), ValueDownlinkOperationError>
+    where
+        F: Fn(&ValueDownlinkState<T>) -> bool,
+    {
+        let mut rx = self.state_rx.clone();
+        let result = rx.wait_for(f).await.map(|_| ());
+        result.map_err(|_| ValueDownlinkOperationError::DownlinkStopped)
+    }
+
     /// Returns a receiver that completes with the result of downlink's internal task.
     pub fn stop_notification(&self) -> promise::Receiver<Result<(), Arc<DownlinkRuntimeError>>> {
         self.stop_rx.clone()
@@ -497,6 +861,8 @@ pub struct MapDownlinkBuilder<'h, L> {
     options: DownlinkOptions,
     runtime_config: DownlinkRuntimeConfig,
     downlink_config: DownlinkConfig,
+    timeout: Option<Duration>,
+    max_entries: Option<NonZeroUsize>,
 }
 
 impl<'h, L> MapDownlinkBuilder<'h, L> {
@@ -512,6 +878,8 @@ impl<'h, L> MapDownlinkBuilder<'h, L> {
             options,
             runtime_config,
             downlink_config,
+            timeout,
+            max_entries,
             ..
         } = self;
         MapDownlinkBuilder {
@@ -521,6 +889,8 @@ impl<'h, L> MapDownlinkBuilder<'h, L> {
             options,
             runtime_config,
             downlink_config,
+            timeout,
+            max_entries,
         }
     }
 
@@ -542,6 +912,22 @@ impl<'h, L> MapDownlinkBuilder<'h, L> {
         self
     }
 
+    /// Sets a timeout for opening the downlink. If the downlink has not been attached to its
+    /// runtime within the timeout, opening it will fail with a [`DownlinkErrorKind::Timeout`]
+    /// error.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum number of entries that the downlink will hold locally. When this is
+    /// exceeded, the entries with the lowest keys are evicted and the `on_evicted` handler of the
+    /// lifecycle is called for each of them.
+    pub fn max_entries(mut self, max_entries: NonZeroUsize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
     /// Attempts to open the downlink.
     pub async fn open<K, V>(self) -> Result<MapDownlinkView<K, V>, Arc<DownlinkRuntimeError>>
     where
@@ -560,14 +946,21 @@ impl<'h, L> MapDownlinkBuilder<'h, L> {
             options,
             runtime_config,
             downlink_config,
+            timeout,
+            max_entries,
         } = self;
 
         let (tx, rx) = mpsc::channel(downlink_config.buffer_size.get());
-        let task = DownlinkTask::new(MapDownlinkModel::new(rx, lifecycle));
-        let stop_rx = handle
-            .inner
-            .run_downlink(path, runtime_config, downlink_config, options, task)
-            .await?;
+        let mut model = MapDownlinkModel::new(rx, lifecycle);
+        model.max_entries = max_entries;
+        let task = DownlinkTask::new(model);
+        let stop_rx = with_timeout(
+            timeout,
+            handle
+                .inner
+                .run_downlink(path, runtime_config, downlink_config, options, task),
+        )
+        .await?;
 
         Ok(MapDownlinkView {
             inner: MapDownlinkHandle::new(tx),
@@ -576,6 +969,37 @@ impl<'h, L> MapDownlinkBuilder<'h, L> {
     }
 }
 
+impl<'h, K, V> MapDownlinkBuilder<'h, BasicMapDownlinkLifecycle<K, V>> {
+    /// Attempts to open the downlink, delivering its events as a stream rather than to a
+    /// lifecycle. If the consumer of the stream falls behind, the downlink will stop reading
+    /// from the lane until it catches up.
+    ///
+    /// # Arguments
+    /// * `buffer_size` - The number of events that can be buffered by the stream.
+    pub async fn open_stream(
+        self,
+        buffer_size: NonZeroUsize,
+    ) -> Result<
+        (
+            MapDownlinkView<K, V>,
+            DownlinkEventStream<MapDownlinkEvent<K, V>>,
+        ),
+        Arc<DownlinkRuntimeError>,
+    >
+    where
+        K: MapKey,
+        V: MapValue,
+        K::Rec: Send,
+        V::Rec: Send,
+        K::BodyRec: Send,
+        V::BodyRec: Send,
+    {
+        let (sender, events) = downlink_event_stream(buffer_size);
+        let view = self.lifecycle(sender).open().await?;
+        Ok((view, events))
+    }
+}
+
 /// A view over a map downlink.
 #[derive(Debug, Clone)]
 pub struct MapDownlinkView<K, V> {
@@ -605,7 +1029,7 @@ impl<K, V> MapDownlinkView<K, V> {
     }
 }
 
-/// A builder for value downlinks.
+/// A builder for event downlinks.
 pub struct EventDownlinkBuilder<'h, L> {
     handle: &'h ClientHandle,
     lifecycle: L,
@@ -613,6 +1037,7 @@ pub struct EventDownlinkBuilder<'h, L> {
     options: DownlinkOptions,
     runtime_config: DownlinkRuntimeConfig,
     downlink_config: DownlinkConfig,
+    timeout: Option<Duration>,
 }
 
 impl<'h, L> EventDownlinkBuilder<'h, L> {
@@ -628,6 +1053,7 @@ impl<'h, L> EventDownlinkBuilder<'h, L> {
             options,
             runtime_config,
             downlink_config,
+            timeout,
             ..
         } = self;
         EventDownlinkBuilder {
@@ -637,6 +1063,7 @@ impl<'h, L> EventDownlinkBuilder<'h, L> {
             options,
             runtime_config,
             downlink_config,
+            timeout,
         }
     }
 
@@ -658,6 +1085,14 @@ impl<'h, L> EventDownlinkBuilder<'h, L> {
         self
     }
 
+    /// Sets a timeout for opening the downlink. If the downlink has not been attached to its
+    /// runtime within the timeout, opening it will fail with a [`DownlinkErrorKind::Timeout`]
+    /// error.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     /// Attempts to open the downlink.
     pub async fn open<T>(self) -> Result<EventDownlinkView<T>, Arc<DownlinkRuntimeError>>
     where
@@ -672,12 +1107,16 @@ impl<'h, L> EventDownlinkBuilder<'h, L> {
             options,
             runtime_config,
             downlink_config,
+            timeout,
         } = self;
         let task = DownlinkTask::new(EventDownlinkModel::new(lifecycle));
-        let stop_rx = handle
-            .inner
-            .run_downlink(path, runtime_config, downlink_config, options, task)
-            .await?;
+        let stop_rx = with_timeout(
+            timeout,
+            handle
+                .inner
+                .run_downlink(path, runtime_config, downlink_config, options, task),
+        )
+        .await?;
 
         Ok(EventDownlinkView {
             _type: Default::default(),
@@ -686,6 +1125,33 @@ impl<'h, L> EventDownlinkBuilder<'h, L> {
     }
 }
 
+impl<'h, T> EventDownlinkBuilder<'h, BasicEventDownlinkLifecycle<T>> {
+    /// Attempts to open the downlink, delivering its events as a stream rather than to a
+    /// lifecycle. If the consumer of the stream falls behind, the downlink will stop reading
+    /// from the lane until it catches up.
+    ///
+    /// # Arguments
+    /// * `buffer_size` - The number of events that can be buffered by the stream.
+    pub async fn open_stream(
+        self,
+        buffer_size: NonZeroUsize,
+    ) -> Result<
+        (
+            EventDownlinkView<T>,
+            DownlinkEventStream<EventDownlinkEvent<T>>,
+        ),
+        Arc<DownlinkRuntimeError>,
+    >
+    where
+        T: Send + Sync + Form + Clone + 'static,
+        T::Rec: Send,
+    {
+        let (sender, events) = downlink_event_stream(buffer_size);
+        let view = self.lifecycle(sender).open().await?;
+        Ok((view, events))
+    }
+}
+
 /// An event downlink handle which provides the functionality to await the downlink terminating.
 #[derive(Debug, Clone)]
 pub struct EventDownlinkView<T> {
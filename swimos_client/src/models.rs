@@ -162,6 +162,13 @@ impl DownlinkRuntime {
                         },
                         config,
                     );
+                    // Only the latest state of a value lane is relevant so lagging consumers
+                    // may skip events. Every event is delivered for event downlinks.
+                    let runtime = if matches!(kind, DownlinkKind::Value) {
+                        runtime.conflating()
+                    } else {
+                        runtime
+                    };
                     runtime.run().await;
                 }
                 DownlinkKind::Map | DownlinkKind::MapEvent => {
@@ -14,21 +14,24 @@
 
 use crate::error::DownlinkRuntimeError;
 use crate::models::{Key, RemotePath};
-use crate::runtime::{BoxedDownlink, DownlinkCallback};
+use crate::pool::{PoolGauge, TaskPool};
+use crate::runtime::{BoxedDownlink, ByteChannel, DownlinkCallback, RuntimeRelink};
 use fnv::FnvHashMap;
 use futures::Stream;
 use futures_util::future::{BoxFuture, Either};
-use futures_util::stream::FuturesUnordered;
 use std::net::SocketAddr;
+use std::num::NonZeroUsize;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use swimos_api::address::RelativeAddress;
+use swimos_api::agent::{DownlinkKind, SyncVersion};
 use swimos_client_api::DownlinkConfig;
 use swimos_messages::remote_protocol::AttachClient;
 use swimos_model::Text;
 use swimos_remote::Scheme;
 use swimos_runtime::downlink::{DownlinkOptions, DownlinkRuntimeConfig};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 
 type PendingDns = (Scheme, Text, Result<Vec<SocketAddr>, DownlinkRuntimeError>);
 type PendingHandshake = (
@@ -36,13 +39,51 @@ type PendingHandshake = (
     Result<(SocketAddr, mpsc::Sender<AttachClient>), DownlinkRuntimeError>,
 );
 
+pub type RelinkCallback = oneshot::Sender<Result<ByteChannel, Arc<DownlinkRuntimeError>>>;
+
 pub struct PendingDownlink {
-    pub callback: DownlinkCallback,
-    pub downlink: BoxedDownlink,
+    pub target: PendingTarget,
+    pub kind: DownlinkKind,
     pub address: RemotePath,
     pub runtime_config: DownlinkRuntimeConfig,
     pub downlink_config: DownlinkConfig,
     pub options: DownlinkOptions,
+    /// The version of the state of the remote lane held by a relinking downlink.
+    pub since: Option<SyncVersion>,
+}
+
+/// What to do with a downlink once it has been attached to its runtime.
+pub enum PendingTarget {
+    /// Start a new downlink task.
+    Start {
+        callback: DownlinkCallback,
+        downlink: BoxedDownlink,
+        relink: Box<RuntimeRelink>,
+    },
+    /// Provide new channels to a running downlink task that is relinking.
+    Relink(RelinkCallback),
+}
+
+impl PendingDownlink {
+    /// Report that the downlink could not be attached to its runtime. If the requester has already
+    /// gone away, the address and kind of the downlink are returned.
+    pub fn fail(self, error: Arc<DownlinkRuntimeError>) -> Option<(RemotePath, DownlinkKind)> {
+        let PendingDownlink {
+            target,
+            kind,
+            address,
+            ..
+        } = self;
+        let sent = match target {
+            PendingTarget::Start { callback, .. } => callback.send(Err(error)).is_ok(),
+            PendingTarget::Relink(callback) => callback.send(Err(error)).is_ok(),
+        };
+        if sent {
+            None
+        } else {
+            Some((address, kind))
+        }
+    }
 }
 
 #[derive(Eq, PartialEq, Hash, Debug)]
@@ -51,10 +92,9 @@ enum WaiterKey {
     Runtime(SocketAddr),
 }
 
-#[derive(Default)]
 pub struct PendingConnections<'f> {
     waiters: FnvHashMap<WaiterKey, FnvHashMap<Key, Vec<PendingDownlink>>>,
-    tasks: FuturesUnordered<BoxFuture<'f, Either<PendingDns, PendingHandshake>>>,
+    tasks: TaskPool<BoxFuture<'f, Either<PendingDns, PendingHandshake>>>,
 }
 
 pub enum Waiting {
@@ -69,17 +109,25 @@ pub enum Waiting {
 }
 
 impl<'f> PendingConnections<'f> {
+    /// # Arguments
+    /// * `limit` - The maximum number of DNS lookups and connection attempts to run concurrently.
+    /// * `gauge` - Gauge to report the number of running and queued connection tasks.
+    pub fn new(limit: NonZeroUsize, gauge: Arc<PoolGauge>) -> Self {
+        PendingConnections {
+            waiters: Default::default(),
+            tasks: TaskPool::new(limit, gauge),
+        }
+    }
+
     fn key(of: &PendingDownlink) -> Key {
-        let PendingDownlink {
-            downlink, address, ..
-        } = of;
+        let PendingDownlink { kind, address, .. } = of;
         (
             RelativeAddress::new(address.node.clone(), address.lane.clone()),
-            downlink.kind(),
+            *kind,
         )
     }
 
-    pub fn feed_task(&self, task: BoxFuture<'f, Either<PendingDns, PendingHandshake>>) {
+    pub fn feed_task(&mut self, task: BoxFuture<'f, Either<PendingDns, PendingHandshake>>) {
         self.tasks.push(task)
     }
 
@@ -95,7 +143,9 @@ impl<'f> PendingConnections<'f> {
         };
         entry
             .or_default()
-            .insert(Self::key(&downlink), vec![downlink]);
+            .entry(Self::key(&downlink))
+            .or_default()
+            .push(downlink);
     }
 
     pub fn drain_connection_queue(
@@ -0,0 +1,193 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use futures::Stream;
+use futures_util::stream::FuturesUnordered;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Gauges reporting the occupancy of one of the task pools of the client runtime.
+#[derive(Debug, Default)]
+pub struct PoolGauge {
+    in_flight: AtomicUsize,
+    queued: AtomicUsize,
+}
+
+impl PoolGauge {
+    /// The number of tasks that are currently running.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// The number of tasks that are waiting for a running task to complete before they can start.
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    fn update(&self, in_flight: usize, queued: usize) {
+        self.in_flight.store(in_flight, Ordering::Relaxed);
+        self.queued.store(queued, Ordering::Relaxed);
+    }
+}
+
+/// Gauges for the task pools of the client runtime.
+#[derive(Debug, Default, Clone)]
+pub struct RuntimeGauges {
+    connections: Arc<PoolGauge>,
+    attachments: Arc<PoolGauge>,
+}
+
+impl RuntimeGauges {
+    /// DNS lookups and connection attempts to remote hosts.
+    pub fn connections(&self) -> &PoolGauge {
+        &self.connections
+    }
+
+    /// Tasks starting downlink runtimes and attaching downlinks to them.
+    pub fn attachments(&self) -> &PoolGauge {
+        &self.attachments
+    }
+
+    pub(crate) fn connections_gauge(&self) -> Arc<PoolGauge> {
+        self.connections.clone()
+    }
+
+    pub(crate) fn attachments_gauge(&self) -> Arc<PoolGauge> {
+        self.attachments.clone()
+    }
+}
+
+/// A set of futures that are polled concurrently (as with [`FuturesUnordered`]) but where at most
+/// `limit` of them may be running at any one time. Any further futures are queued and started, in
+/// order, as the running futures complete.
+pub struct TaskPool<F> {
+    limit: NonZeroUsize,
+    running: FuturesUnordered<F>,
+    queue: VecDeque<F>,
+    gauge: Arc<PoolGauge>,
+}
+
+impl<F> TaskPool<F> {
+    /// # Arguments
+    /// * `limit` - The maximum number of futures that will be running at any one time.
+    /// * `gauge` - Gauge to report the number of running and queued futures.
+    pub fn new(limit: NonZeroUsize, gauge: Arc<PoolGauge>) -> Self {
+        TaskPool {
+            limit,
+            running: FuturesUnordered::new(),
+            queue: VecDeque::new(),
+            gauge,
+        }
+    }
+
+    /// Adds a future to the pool, queueing it if the pool is at capacity.
+    pub fn push(&mut self, task: F) {
+        if self.running.len() < self.limit.get() {
+            self.running.push(task);
+        } else {
+            self.queue.push_back(task);
+        }
+        self.report();
+    }
+
+    /// Whether there are no running (and so also no queued) futures.
+    pub fn is_empty(&self) -> bool {
+        self.running.is_empty()
+    }
+
+    fn report(&self) {
+        self.gauge.update(self.running.len(), self.queue.len());
+    }
+}
+
+impl<F: Future + Unpin> Stream for TaskPool<F> {
+    type Item = F::Output;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let result = Pin::new(&mut this.running).poll_next(cx);
+        if let Poll::Ready(Some(_)) = &result {
+            if let Some(task) = this.queue.pop_front() {
+                this.running.push(task);
+            }
+            this.report();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PoolGauge, TaskPool};
+    use futures::StreamExt;
+    use std::sync::Arc;
+    use swimos_utilities::non_zero_usize;
+    use tokio::sync::oneshot;
+
+    #[tokio::test]
+    async fn pool_queues_beyond_limit() {
+        let gauge = Arc::new(PoolGauge::default());
+        let mut pool = TaskPool::new(non_zero_usize!(2), gauge.clone());
+
+        let mut senders = vec![];
+        for i in 0..4 {
+            let (tx, rx) = oneshot::channel::<()>();
+            senders.push(tx);
+            pool.push(Box::pin(async move {
+                let _ = rx.await;
+                i
+            }));
+        }
+        assert_eq!(gauge.in_flight(), 2);
+        assert_eq!(gauge.queued(), 2);
+
+        for tx in senders.drain(..) {
+            let _ = tx.send(());
+        }
+
+        let mut completed = vec![];
+        while let Some(i) = pool.next().await {
+            completed.push(i);
+        }
+        completed.sort();
+        assert_eq!(completed, vec![0, 1, 2, 3]);
+        assert!(pool.is_empty());
+        assert_eq!(gauge.in_flight(), 0);
+        assert_eq!(gauge.queued(), 0);
+    }
+
+    #[tokio::test]
+    async fn queued_tasks_start_in_order() {
+        let gauge = Arc::new(PoolGauge::default());
+        let mut pool = TaskPool::new(non_zero_usize!(1), gauge.clone());
+
+        for i in 0..3 {
+            pool.push(Box::pin(async move { i }));
+        }
+        assert_eq!(gauge.in_flight(), 1);
+        assert_eq!(gauge.queued(), 2);
+
+        assert_eq!(pool.next().await, Some(0));
+        assert_eq!(gauge.in_flight(), 1);
+        assert_eq!(gauge.queued(), 1);
+        assert_eq!(pool.next().await, Some(1));
+        assert_eq!(pool.next().await, Some(2));
+        assert_eq!(pool.next().await, None);
+    }
+}
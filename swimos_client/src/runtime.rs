@@ -33,10 +33,15 @@ use uuid::Uuid;
 
 use crate::error::{DownlinkErrorKind, DownlinkRuntimeError};
 use crate::models::{DownlinkRuntime, IdIssuer, Key, Peer, RemotePath};
-use crate::pending::{PendingConnections, PendingDownlink, Waiting};
+use crate::pending::{PendingConnections, PendingDownlink, PendingTarget, Waiting};
+use crate::pool::{RuntimeGauges, TaskPool};
 use crate::transport::{Transport, TransportHandle};
-use swimos_api::{address::Address, agent::DownlinkKind, error::DownlinkFailureReason};
-use swimos_client_api::{Downlink, DownlinkConfig};
+use swimos_api::{
+    address::Address,
+    agent::{DownlinkKind, SyncVersion},
+    error::DownlinkFailureReason,
+};
+use swimos_client_api::{Downlink, DownlinkConfig, Relink, RelinkError};
 use swimos_model::Text;
 use swimos_remote::ClientConnections;
 use swimos_runtime::downlink::{AttachAction, DownlinkOptions, DownlinkRuntimeConfig};
@@ -45,7 +50,7 @@ use swimos_utilities::trigger;
 use swimos_utilities::trigger::promise;
 
 pub type BoxedDownlink = Box<dyn Downlink + Send + Sync + 'static>;
-type ByteChannel = (ByteWriter, ByteReader);
+pub type ByteChannel = (ByteWriter, ByteReader);
 type CallbackResult =
     Result<promise::Receiver<Result<(), Arc<DownlinkRuntimeError>>>, Arc<DownlinkRuntimeError>>;
 pub type DownlinkCallback = oneshot::Sender<CallbackResult>;
@@ -85,6 +90,7 @@ impl Debug for DownlinkRegistrationRequest {
 pub struct RawHandle {
     dispatch: mpsc::Sender<DownlinkRegistrationRequest>,
     completed: Arc<Notify>,
+    gauges: RuntimeGauges,
 }
 
 impl RawHandle {
@@ -92,6 +98,10 @@ impl RawHandle {
         self.completed.notified().await;
     }
 
+    pub fn gauges(&self) -> &RuntimeGauges {
+        &self.gauges
+    }
+
     pub async fn run_downlink<D>(
         &self,
         path: RemotePath,
@@ -139,10 +149,60 @@ pub struct DownlinkRegistrationRequest {
     pub downlink_config: DownlinkConfig,
 }
 
+/// Handle provided to a running downlink task so that it can request to be attached to its remote
+/// lane again, by the runtime that started it, after its connection is lost.
+pub struct RuntimeRelink {
+    requests: mpsc::Sender<PendingDownlink>,
+    path: RemotePath,
+    kind: DownlinkKind,
+    runtime_config: DownlinkRuntimeConfig,
+    downlink_config: DownlinkConfig,
+    options: DownlinkOptions,
+}
+
+impl Relink for RuntimeRelink {
+    fn relink(
+        &mut self,
+        since: Option<SyncVersion>,
+    ) -> BoxFuture<'_, Result<(ByteReader, ByteWriter), RelinkError>> {
+        let RuntimeRelink {
+            requests,
+            path,
+            kind,
+            runtime_config,
+            downlink_config,
+            options,
+        } = self;
+        async move {
+            let (callback_tx, callback_rx) = oneshot::channel();
+            let request = PendingDownlink {
+                target: PendingTarget::Relink(callback_tx),
+                kind: *kind,
+                address: path.clone(),
+                runtime_config: *runtime_config,
+                downlink_config: *downlink_config,
+                options: *options,
+                since,
+            };
+            if requests.send(request).await.is_err() {
+                return Err(RelinkError::Stopped);
+            }
+            match callback_rx.await {
+                Ok(Ok((output, input))) => Ok((input, output)),
+                Ok(Err(e)) => Err(RelinkError::Failed(Box::new(e))),
+                Err(_) => Err(RelinkError::Stopped),
+            }
+        }
+        .boxed()
+    }
+}
+
 enum RuntimeEvent {
     /// A request to start a downlink; opening a connection to the peer if required and starting the
     /// downlink type's runtime.
     StartDownlink(DownlinkRegistrationRequest),
+    /// A request from a running downlink to be attached to its remote lane again.
+    RelinkDownlink(PendingDownlink),
     /// A DNS resolution task completed.
     Resolved {
         scheme: Scheme,
@@ -189,6 +249,9 @@ impl Debug for RuntimeEvent {
             RuntimeEvent::StartDownlink(_) => {
                 write!(f, "RuntimeEvent::StartDownlink")
             }
+            RuntimeEvent::RelinkDownlink(_) => {
+                write!(f, "RuntimeEvent::RelinkDownlink")
+            }
             RuntimeEvent::Resolved { .. } => {
                 write!(f, "RuntimeEvent::Resolved")
             }
@@ -214,6 +277,16 @@ impl Debug for RuntimeEvent {
     }
 }
 
+/// Limits on the number of tasks that the runtime will run concurrently to establish links. Any
+/// further tasks are queued until one of the running tasks completes.
+#[derive(Debug, Clone, Copy)]
+pub struct RuntimeLimits {
+    /// The maximum number of DNS lookups and connection attempts to run concurrently.
+    pub max_pending_connections: NonZeroUsize,
+    /// The maximum number of downlink runtimes to start, or downlinks to attach, concurrently.
+    pub max_pending_attachments: NonZeroUsize,
+}
+
 /// Spawns a runtime task that uses the provided transport task and returns a handle that can be
 /// used to dispatch downlink registration requests.
 pub fn start_runtime<Net, Ws, Provider>(
@@ -222,6 +295,7 @@ pub fn start_runtime<Net, Ws, Provider>(
     transport: Transport<Net, Ws, Provider>,
     transport_buffer_size: NonZeroUsize,
     interpret_frame_data: bool,
+    limits: RuntimeLimits,
 ) -> (RawHandle, BoxFuture<'static, ()>)
 where
     Net: ClientConnections,
@@ -233,6 +307,8 @@ where
     let (requests_tx, requests_rx) = mpsc::channel(registration_buffer_size.get());
     let notified = Arc::new(Notify::new());
     let completed = notified.clone();
+    let gauges = RuntimeGauges::default();
+    let task_gauges = gauges.clone();
     let task = async move {
         runtime_task(
             transport,
@@ -240,6 +316,8 @@ where
             stop_rx,
             requests_rx,
             interpret_frame_data,
+            limits,
+            task_gauges,
         )
         .await;
         completed.notify_waiters();
@@ -249,6 +327,7 @@ where
         RawHandle {
             dispatch: requests_tx,
             completed: notified,
+            gauges,
         },
         task.boxed(),
     )
@@ -260,6 +339,8 @@ async fn runtime_task<Net, Ws, Provider>(
     mut remote_stop_rx: trigger::Receiver,
     mut requests_rx: mpsc::Receiver<DownlinkRegistrationRequest>,
     interpret_frame_data: bool,
+    limits: RuntimeLimits,
+    gauges: RuntimeGauges,
 ) where
     Net: ClientConnections,
     Net::ClientSocket: WebSocketStream,
@@ -270,9 +351,14 @@ async fn runtime_task<Net, Ws, Provider>(
     let (transport_tx, transport_rx) = mpsc::channel(transport_buffer_size.get());
     let transport_handle = TransportHandle::new(transport_tx);
     let mut peers: FnvHashMap<SocketAddr, Peer> = FnvHashMap::default();
-    let mut pending = PendingConnections::default();
+    let RuntimeLimits {
+        max_pending_connections,
+        max_pending_attachments,
+    } = limits;
+    let mut pending = PendingConnections::new(max_pending_connections, gauges.connections_gauge());
     let mut downlinks = FuturesUnordered::default();
-    let mut attachment_tasks = FuturesUnordered::default();
+    let mut attachment_tasks = TaskPool::new(max_pending_attachments, gauges.attachments_gauge());
+    let (relink_tx, mut relink_rx) = mpsc::channel(requests_rx.max_capacity());
 
     let mut transport_task: Fuse<JoinHandle<()>> = tokio::spawn(transport.run(transport_rx)).fuse();
     let mut runtime_id_issuer = IdIssuer::new();
@@ -290,6 +376,7 @@ async fn runtime_task<Net, Ws, Provider>(
                         None => break,
                     }
                 },
+                Some(request) = relink_rx.recv() => RuntimeEvent::RelinkDownlink(request),
                 join_result = &mut transport_task => {
                     if let Err(ref e) = join_result {
                         panic!("Transport task completed unexpectedly: {:?}", e);
@@ -320,43 +407,33 @@ async fn runtime_task<Net, Ws, Provider>(
                 } = request;
                 trace!(%path, "Received downlink registration request");
 
-                let RemotePath { host, node, lane } = path;
-                let shp = match host.as_str().parse::<SchemeHostPort>() {
-                    Ok(shp) => shp,
-                    Err(e) => {
-                        let _r = callback.send(Err(DownlinkRuntimeError::with_cause(
-                            DownlinkErrorKind::Unresolvable,
-                            e,
-                        )
-                        .shared()));
-                        continue;
-                    }
+                let kind = downlink.kind();
+                let relink = RuntimeRelink {
+                    requests: relink_tx.clone(),
+                    path: path.clone(),
+                    kind,
+                    runtime_config,
+                    downlink_config,
+                    options,
                 };
-
-                let task_shp = shp.clone();
-                let host = Text::from(shp.host().to_string());
-                let address = RemotePath::new(host.clone(), node, lane);
-
-                pending.feed_waiter(Waiting::Connection {
-                    host: host.clone(),
-                    downlink: PendingDownlink {
+                let pending_downlink = PendingDownlink {
+                    target: PendingTarget::Start {
                         callback,
                         downlink,
-                        address,
-                        runtime_config,
-                        downlink_config,
-                        options,
+                        relink: Box::new(relink),
                     },
-                });
-
-                let handle_ref = &transport_handle;
-
-                pending.feed_task(
-                    async move {
-                        Either::Left((*task_shp.scheme(), host, handle_ref.resolve(task_shp).await))
-                    }
-                    .boxed(),
-                );
+                    kind,
+                    address: path,
+                    runtime_config,
+                    downlink_config,
+                    options,
+                    since: None,
+                };
+                request_connection(&mut pending, &transport_handle, pending_downlink);
+            }
+            RuntimeEvent::RelinkDownlink(pending_downlink) => {
+                trace!(path = %pending_downlink.address, "Received downlink relink request");
+                request_connection(&mut pending, &transport_handle, pending_downlink);
             }
             RuntimeEvent::Resolved {
                 scheme,
@@ -430,15 +507,7 @@ async fn runtime_task<Net, Ws, Provider>(
 
                 let error = e.shared();
                 for (_key, downlink) in pending.drain_connection_queue(host) {
-                    let PendingDownlink {
-                        callback,
-                        address,
-                        downlink,
-                        ..
-                    } = downlink;
-
-                    if callback.send(Err(error.clone())).is_err() {
-                        let kind = downlink.kind();
+                    if let Some((address, kind)) = downlink.fail(error.clone()) {
                         info!(address = %address, kind = ?kind, "A request for a downlink was dropped before it was completed.");
                     }
                 }
@@ -489,14 +558,7 @@ async fn runtime_task<Net, Ws, Provider>(
                     .map(|(_key, waiters)| waiters);
                 let error = e.shared();
                 for pending_downlink in waiters {
-                    let PendingDownlink {
-                        callback,
-                        address,
-                        downlink,
-                        ..
-                    } = pending_downlink;
-                    if callback.send(Err(error.clone())).is_err() {
-                        let kind = downlink.kind();
+                    if let Some((address, kind)) = pending_downlink.fail(error.clone()) {
                         info!(address = %address, kind = ?kind, "A request for a downlink was dropped before it was completed.");
                     }
                 }
@@ -530,16 +592,9 @@ async fn runtime_task<Net, Ws, Provider>(
                 }
                 None => {
                     let error =
-                        Err(DownlinkRuntimeError::new(DownlinkErrorKind::RemoteStopped).shared());
+                        DownlinkRuntimeError::new(DownlinkErrorKind::RemoteStopped).shared();
                     for pending_downlink in pending.drain_runtime_queue(sock, &key) {
-                        let PendingDownlink {
-                            callback,
-                            address,
-                            downlink,
-                            ..
-                        } = pending_downlink;
-                        if callback.send(error.clone()).is_err() {
-                            let kind = downlink.kind();
+                        if let Some((address, kind)) = pending_downlink.fail(error.clone()) {
                             trace!(address = %address, kind = ?kind, "A request for a downlink was dropped before it was completed.");
                         }
                     }
@@ -552,20 +607,9 @@ async fn runtime_task<Net, Ws, Provider>(
             } => {
                 error!(error = %cause, host = %host, "Failed to start a downlink runtime to host: ");
 
-                let error = Err(DownlinkRuntimeError::with_cause(
-                    DownlinkErrorKind::Connection,
-                    cause,
-                )
-                .shared());
+                let error = DownlinkRuntimeError::from(cause).shared();
                 for pending_downlink in pending.drain_runtime_queue(sock, &key) {
-                    let PendingDownlink {
-                        callback,
-                        address,
-                        downlink,
-                        ..
-                    } = pending_downlink;
-                    if callback.send(error.clone()).is_err() {
-                        let kind = downlink.kind();
+                    if let Some((address, kind)) = pending_downlink.fail(error.clone()) {
                         trace!(address = %address, kind = ?kind, "A request for a downlink was dropped before it was completed.");
                     }
                 }
@@ -575,59 +619,64 @@ async fn runtime_task<Net, Ws, Provider>(
                 result: Ok((io_in, io_out)),
             } => {
                 let PendingDownlink {
-                    callback,
-                    downlink,
+                    target,
+                    kind,
                     address,
                     downlink_config,
                     ..
                 } = pending;
                 trace!(?address, "Downlink runtime attached");
-                let kind = downlink.kind();
-                let (promise_tx, promise_rx) = promise::promise();
-                let task = tokio::spawn(downlink.run_boxed(
-                    Address::new(
-                        Some(address.host.clone()),
-                        address.node.clone(),
-                        address.lane.clone(),
-                    ),
-                    downlink_config,
-                    io_out,
-                    io_in,
-                ))
-                .map(move |result| {
-                    let result = match result {
-                        Ok(Ok(())) => Ok(()),
-                        Ok(Err(e)) => Err(e.into()),
-                        Err(e) => Err(e.into()),
-                    };
-
-                    RuntimeEvent::DownlinkTaskComplete {
-                        kind,
-                        address,
-                        result,
-                        tx: promise_tx,
-                    }
-                });
+                match target {
+                    PendingTarget::Start {
+                        callback,
+                        downlink,
+                        relink,
+                    } => {
+                        let (promise_tx, promise_rx) = promise::promise();
+                        let task = tokio::spawn(downlink.run_relinking(
+                            Address::new(
+                                Some(address.host.clone()),
+                                address.node.clone(),
+                                address.lane.clone(),
+                            ),
+                            downlink_config,
+                            io_out,
+                            io_in,
+                            relink,
+                        ))
+                        .map(move |result| {
+                            let result = match result {
+                                Ok(Ok(())) => Ok(()),
+                                Ok(Err(e)) => Err(e.into()),
+                                Err(e) => Err(e.into()),
+                            };
+
+                            RuntimeEvent::DownlinkTaskComplete {
+                                kind,
+                                address,
+                                result,
+                                tx: promise_tx,
+                            }
+                        });
 
-                downlinks.push(task.boxed());
-                let _r = callback.send(Ok(promise_rx));
+                        downlinks.push(task.boxed());
+                        let _r = callback.send(Ok(promise_rx));
+                    }
+                    PendingTarget::Relink(callback) => {
+                        if callback.send(Ok((io_in, io_out))).is_err() {
+                            trace!(address = %address, kind = ?kind, "A downlink stopped before it was relinked.");
+                        }
+                    }
+                }
             }
             RuntimeEvent::DownlinkRuntimeAttached {
                 pending: pending_dl,
                 result: Err(cause),
                 ..
             } => {
-                let PendingDownlink {
-                    callback,
-                    address,
-                    downlink,
-                    ..
-                } = pending_dl;
-                let kind = downlink.kind();
+                error!(error = %cause, address = %pending_dl.address, kind = ?pending_dl.kind, "Failed to attach a downlink to runtime: ");
 
-                error!(error = %cause, address = %address, kind = ?kind, "Failed to attach a downlink to runtime: ");
-
-                if callback.send(Err(cause.shared())).is_err() {
+                if let Some((address, kind)) = pending_dl.fail(cause.shared()) {
                     trace!(address = %address, kind = ?kind, "A request for a downlink was dropped before it was completed.");
                 }
             }
@@ -636,6 +685,7 @@ async fn runtime_task<Net, Ws, Provider>(
                     let handle = entry.get_mut();
                     if handle.remove(&key) {
                         entry.remove();
+                        transport_handle.release(addr).await;
                     }
                 }
                 if let Err(err) = result {
@@ -682,6 +732,43 @@ async fn runtime_task<Net, Ws, Provider>(
     debug!("Runtime task completed");
 }
 
+/// Parse the host of the downlink's path and queue the downlink to wait for a connection to it,
+/// starting a task to resolve the host.
+fn request_connection<'f>(
+    pending: &mut PendingConnections<'f>,
+    transport_handle: &'f TransportHandle,
+    mut pending_downlink: PendingDownlink,
+) {
+    let shp = match pending_downlink
+        .address
+        .host
+        .as_str()
+        .parse::<SchemeHostPort>()
+    {
+        Ok(shp) => shp,
+        Err(e) => {
+            let error = DownlinkRuntimeError::with_cause(DownlinkErrorKind::Unresolvable, e);
+            if let Some((address, kind)) = pending_downlink.fail(error.shared()) {
+                trace!(address = %address, kind = ?kind, "A request for a downlink was dropped before it was completed.");
+            }
+            return;
+        }
+    };
+
+    let host = Text::from(shp.host().to_string());
+    pending_downlink.address.host = host.clone();
+
+    pending.feed_waiter(Waiting::Connection {
+        host: host.clone(),
+        downlink: pending_downlink,
+    });
+
+    pending.feed_task(
+        async move { Either::Left((*shp.scheme(), host, transport_handle.resolve(shp).await)) }
+            .boxed(),
+    );
+}
+
 async fn start_downlink_runtime(
     identity: Uuid,
     remote_addr: SocketAddr,
@@ -740,7 +827,7 @@ async fn attach_downlink(
     let (in_tx, in_rx) = byte_channel(pending.runtime_config.downlink_buffer_size);
     let (out_tx, out_rx) = byte_channel(pending.runtime_config.downlink_buffer_size);
     let result = attach
-        .send(AttachAction::new((in_tx, out_rx), pending.options))
+        .send(AttachAction::new((in_tx, out_rx), pending.options).with_since(pending.since))
         .await
         .map(move |_| (out_tx, in_rx))
         .map_err(|_| DownlinkRuntimeError::new(DownlinkErrorKind::Terminated));
@@ -32,458 +32,48 @@ use uuid::Uuid;
 
 use crate::error::{DownlinkErrorKind, DownlinkRuntimeError};
 use crate::models::RemotePath;
-use crate::runtime::{start_runtime, RawHandle};
-use crate::transport::{Transport, TransportHandle};
+use crate::runtime::{start_runtime, RawHandle, RuntimeLimits};
+use crate::transport::{ConnectionConfig, Transport, TransportHandle};
+use crate::{
+    with_timeout, ClientHandle, CommandError, Commander, KeepAlive, ValueDownlinkOperationError,
+};
 use bytes::BytesMut;
-use futures_util::future::{ready, BoxFuture};
-use futures_util::stream::BoxStream;
+use futures_util::future::BoxFuture;
 use futures_util::{FutureExt, StreamExt};
 use ratchet::{
-    ExtensionProvider, Message, NegotiatedExtension, NoExt, PayloadType, Role, WebSocket,
-    WebSocketConfig, WebSocketStream,
+    Message, NegotiatedExtension, NoExt, ProtocolRegistry, Role, WebSocket, WebSocketConfig,
 };
-use std::borrow::BorrowMut;
-use std::collections::HashMap;
-use std::io;
-use std::io::ErrorKind;
 use std::net::SocketAddr;
-use std::ops::DerefMut;
 use std::sync::Arc;
 use swimos_api::{
     address::{Address, RelativeAddress},
-    agent::DownlinkKind,
-    error::DownlinkTaskError,
+    agent::{DownlinkKind, LinkFailure},
+    error::{DownlinkFailureReason, DownlinkTaskError},
 };
 use swimos_client_api::{Downlink, DownlinkConfig};
 use swimos_downlink::lifecycle::{
-    BasicMapDownlinkLifecycle, BasicValueDownlinkLifecycle, MapDownlinkLifecycle,
-    ValueDownlinkLifecycle,
+    BasicEventDownlinkLifecycle, BasicMapDownlinkLifecycle, BasicValueDownlinkLifecycle,
+    EventDownlinkLifecycle, MapDownlinkLifecycle, ValueDownlinkLifecycle,
 };
 use swimos_downlink::{
-    DownlinkTask, MapDownlinkHandle, MapDownlinkModel, ValueDownlinkModel, ValueDownlinkSet,
+    DownlinkTask, MapDownlinkEvent, MapDownlinkHandle, MapDownlinkModel, ValueDownlinkEvent,
+    ValueDownlinkModel, ValueDownlinkSet,
 };
 use swimos_form::Form;
 use swimos_messages::protocol::{RawRequestMessageEncoder, RequestMessage};
-use swimos_messages::remote_protocol::FindNode;
 use swimos_model::{Text, Value};
-use swimos_recon::parser::parse_recognize;
-use swimos_recon::print_recon;
-use swimos_remote::dns::{BoxDnsResolver, DnsResolver};
-use swimos_remote::websocket::{RatchetError, WebsocketClient, WebsocketServer, WsOpenFuture};
-use swimos_remote::{
-    ClientConnections, ConnectionError, ConnectionResult, Listener, ListenerError, Scheme,
-};
+use swimos_remote::websocket::RatchetError;
+use swimos_remote::Scheme;
 use swimos_runtime::downlink::{DownlinkOptions, DownlinkRuntimeConfig};
+use swimos_testkit::{Envelope, MockClientConnections, MockServer, MockWs, WsAction};
 use swimos_utilities::byte_channel::{byte_channel, ByteReader, ByteWriter};
+use swimos_utilities::future::{Quantity, RetryStrategy};
 use swimos_utilities::trigger::{promise, trigger};
 use swimos_utilities::{non_zero_usize, trigger};
-use tokio::io::{AsyncRead, AsyncWrite, DuplexStream};
+use tokio::io::DuplexStream;
 use tokio::sync::mpsc;
-use tokio::sync::Mutex;
-
-#[derive(Debug)]
-struct Inner {
-    addrs: HashMap<(String, u16), SocketAddr>,
-    sockets: HashMap<SocketAddr, DuplexStream>,
-}
-
-impl Inner {
-    fn new<R, S>(resolver: R, sockets: S) -> Inner
-    where
-        R: IntoIterator<Item = ((String, u16), SocketAddr)>,
-        S: IntoIterator<Item = (SocketAddr, DuplexStream)>,
-    {
-        Inner {
-            addrs: HashMap::from_iter(resolver),
-            sockets: HashMap::from_iter(sockets),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct MockClientConnections {
-    inner: Arc<Mutex<Inner>>,
-}
-
-impl MockClientConnections {
-    pub fn new<R, S>(resolver: R, sockets: S) -> MockClientConnections
-    where
-        R: IntoIterator<Item = ((String, u16), SocketAddr)>,
-        S: IntoIterator<Item = (SocketAddr, DuplexStream)>,
-    {
-        MockClientConnections {
-            inner: Arc::new(Mutex::new(Inner::new(resolver, sockets))),
-        }
-    }
-}
-
-impl ClientConnections for MockClientConnections {
-    type ClientSocket = DuplexStream;
-
-    fn try_open(
-        &self,
-        _scheme: Scheme,
-        _host: Option<&str>,
-        addr: SocketAddr,
-    ) -> BoxFuture<'_, ConnectionResult<Self::ClientSocket>> {
-        async move {
-            self.inner
-                .lock()
-                .await
-                .sockets
-                .remove(&addr)
-                .ok_or_else(|| ConnectionError::ConnectionFailed(ErrorKind::NotFound.into()))
-        }
-        .boxed()
-    }
-
-    fn dns_resolver(&self) -> BoxDnsResolver {
-        Box::new(self.clone())
-    }
-
-    fn lookup(
-        &self,
-        host: String,
-        port: u16,
-    ) -> BoxFuture<'static, std::io::Result<Vec<SocketAddr>>> {
-        self.resolve(host, port).boxed()
-    }
-}
-
-impl DnsResolver for MockClientConnections {
-    type ResolveFuture = BoxFuture<'static, io::Result<Vec<SocketAddr>>>;
-
-    fn resolve(&self, host: String, port: u16) -> Self::ResolveFuture {
-        let inner = self.inner.clone();
-        async move {
-            match inner.lock().await.addrs.get(&(host, port)) {
-                Some(sock) => Ok(vec![*sock]),
-                None => Err(io::ErrorKind::NotFound.into()),
-            }
-        }
-        .boxed()
-    }
-}
-
-pub enum WsAction {
-    Open,
-    Fail(Box<dyn Fn() -> RatchetError + Send + Sync + 'static>),
-}
-
-impl WsAction {
-    pub fn fail<F>(with: F) -> WsAction
-    where
-        F: Fn() -> RatchetError + Send + Sync + 'static,
-    {
-        WsAction::Fail(Box::new(with))
-    }
-}
-
-pub struct MockWs {
-    states: HashMap<String, WsAction>,
-}
-
-impl MockWs {
-    pub fn new<S>(states: S) -> MockWs
-    where
-        S: IntoIterator<Item = (String, WsAction)>,
-    {
-        MockWs {
-            states: HashMap::from_iter(states),
-        }
-    }
-}
-
-impl WebsocketClient for MockWs {
-    fn open_connection<'a, Sock, Provider>(
-        &self,
-        socket: Sock,
-        _provider: &'a Provider,
-        addr: String,
-    ) -> WsOpenFuture<'a, Sock, Provider::Extension, RatchetError>
-    where
-        Sock: WebSocketStream + Send,
-        Provider: ExtensionProvider + Send + Sync + 'static,
-        Provider::Extension: Send + Sync + 'static,
-    {
-        let result = match self.states.get(&addr) {
-            Some(WsAction::Open) => Ok(WebSocket::from_upgraded(
-                WebSocketConfig::default(),
-                socket,
-                NegotiatedExtension::from(None),
-                BytesMut::default(),
-                Role::Client,
-            )),
-            Some(WsAction::Fail(e)) => Err(e()),
-            None => Err(ratchet::Error::new(ratchet::ErrorKind::Http).into()),
-        };
-        ready(result).boxed()
-    }
-}
-
-impl WebsocketServer for MockWs {
-    type WsStream<Sock, Ext> =
-        BoxStream<'static, Result<(WebSocket<Sock, Ext>, SocketAddr), ListenerError>>;
-
-    fn wrap_listener<Sock, L, Provider>(
-        &self,
-        _listener: L,
-        _provider: Provider,
-        _find: mpsc::Sender<FindNode>,
-    ) -> Self::WsStream<Sock, Provider::Extension>
-    where
-        Sock: AsyncRead + AsyncWrite + Unpin + Send + Sync + 'static,
-        L: Listener<Sock> + Send + 'static,
-        Provider: ExtensionProvider + Send + Sync + Unpin + 'static,
-        Provider::Extension: Send + Sync + Unpin + 'static,
-    {
-        futures::stream::pending().boxed()
-    }
-}
-
-#[derive(Clone, Debug, PartialEq, Form)]
-
-pub enum Envelope {
-    #[form(tag = "link")]
-    Link {
-        #[form(name = "node")]
-        node_uri: Text,
-        #[form(name = "lane")]
-        lane_uri: Text,
-        rate: Option<f64>,
-        prio: Option<f64>,
-        #[form(body)]
-        body: Option<Value>,
-    },
-    #[form(tag = "sync")]
-    Sync {
-        #[form(name = "node")]
-        node_uri: Text,
-        #[form(name = "lane")]
-        lane_uri: Text,
-        rate: Option<f64>,
-        prio: Option<f64>,
-        #[form(body)]
-        body: Option<Value>,
-    },
-    #[form(tag = "unlink")]
-    Unlink {
-        #[form(name = "node")]
-        node_uri: Text,
-        #[form(name = "lane")]
-        lane_uri: Text,
-        #[form(body)]
-        body: Option<Value>,
-    },
-    #[form(tag = "command")]
-    Command {
-        #[form(name = "node")]
-        node_uri: Text,
-        #[form(name = "lane")]
-        lane_uri: Text,
-        #[form(body)]
-        body: Option<Value>,
-    },
-    #[form(tag = "linked")]
-    Linked {
-        #[form(name = "node")]
-        node_uri: Text,
-        #[form(name = "lane")]
-        lane_uri: Text,
-        rate: Option<f64>,
-        prio: Option<f64>,
-        #[form(body)]
-        body: Option<Value>,
-    },
-    #[form(tag = "synced")]
-    Synced {
-        #[form(name = "node")]
-        node_uri: Text,
-        #[form(name = "lane")]
-        lane_uri: Text,
-        #[form(body)]
-        body: Option<Value>,
-    },
-    #[form(tag = "unlinked")]
-    Unlinked {
-        #[form(name = "node")]
-        node_uri: Text,
-        #[form(name = "lane")]
-        lane_uri: Text,
-        #[form(body)]
-        body: Option<Value>,
-    },
-    #[form(tag = "event")]
-    Event {
-        #[form(name = "node")]
-        node_uri: Text,
-        #[form(name = "lane")]
-        lane_uri: Text,
-        #[form(body)]
-        body: Option<Value>,
-    },
-}
-
-pub struct Lane {
-    node: String,
-    lane: String,
-    server: Arc<Mutex<Server>>,
-}
-
-impl Lane {
-    pub async fn read(&mut self) -> Envelope {
-        let Lane { server, .. } = self;
-        let mut guard = server.lock().await;
-        let Server { buf, transport } = &mut guard.deref_mut();
-
-        match transport.read(buf).await.unwrap() {
-            Message::Text => {}
-            m => panic!("Unexpected message type: {:?}", m),
-        }
-        let read = String::from_utf8(buf.to_vec()).unwrap();
-        buf.clear();
-
-        parse_recognize::<Envelope>(read.as_str(), false).unwrap()
-    }
-
-    pub async fn write(&mut self, env: Envelope) {
-        let Lane { server, .. } = self;
-        let mut guard = server.lock().await;
-        let Server { transport, .. } = &mut guard.deref_mut();
-
-        let response = print_recon(&env);
-        transport
-            .write(format!("{}", response), PayloadType::Text)
-            .await
-            .unwrap();
-    }
-
-    pub async fn await_link(&mut self) {
-        match self.read().await {
-            Envelope::Link {
-                node_uri, lane_uri, ..
-            } => {
-                assert_eq!(node_uri, self.node);
-                assert_eq!(lane_uri, self.lane);
-                self.write(Envelope::Linked {
-                    node_uri: node_uri.clone(),
-                    lane_uri: lane_uri.clone(),
-                    rate: None,
-                    prio: None,
-                    body: None,
-                })
-                .await;
-            }
-            e => panic!("Unexpected envelope {:?}", e),
-        }
-    }
 
-    pub async fn await_sync<V: Form>(&mut self, val: Vec<V>) {
-        match self.read().await {
-            Envelope::Sync {
-                node_uri, lane_uri, ..
-            } => {
-                assert_eq!(node_uri, self.node);
-                assert_eq!(lane_uri, self.lane);
-
-                for v in val {
-                    self.write(Envelope::Event {
-                        node_uri: node_uri.clone(),
-                        lane_uri: lane_uri.clone(),
-                        body: Some(v.as_value()),
-                    })
-                    .await;
-                }
-
-                self.write(Envelope::Synced {
-                    node_uri: node_uri.clone(),
-                    lane_uri: lane_uri.clone(),
-                    body: None,
-                })
-                .await;
-            }
-            e => panic!("Unexpected envelope {:?}", e),
-        }
-    }
-
-    pub async fn await_command(&mut self, expected: i32) {
-        match self.read().await {
-            Envelope::Command {
-                node_uri,
-                lane_uri,
-                body: Some(val),
-            } => {
-                assert_eq!(node_uri, self.node);
-                assert_eq!(lane_uri, self.lane);
-                assert_eq!(val, Value::Int32Value(expected));
-            }
-            e => panic!("Unexpected envelope {:?}", e),
-        }
-    }
-
-    pub async fn send_unlinked(&mut self) {
-        self.write(Envelope::Unlinked {
-            node_uri: self.node.clone().into(),
-            lane_uri: self.lane.clone().into(),
-            body: None,
-        })
-        .await;
-    }
-
-    pub async fn send_event<V: Form>(&mut self, val: V) {
-        self.write(Envelope::Event {
-            node_uri: self.node.clone().into(),
-            lane_uri: self.lane.clone().into(),
-            body: Some(val.as_value()),
-        })
-        .await;
-    }
-
-    pub async fn await_closed(&mut self) {
-        let Lane { server, .. } = self;
-        let mut guard = server.lock().await;
-        let Server { buf, transport } = &mut guard.deref_mut();
-
-        match transport.borrow_mut().read(buf).await.unwrap() {
-            Message::Close(_) => {}
-            m => panic!("Unexpected message type: {:?}", m),
-        }
-    }
-}
-
-pub struct Server {
-    pub buf: BytesMut,
-    pub transport: WebSocket<DuplexStream, NoExt>,
-}
-
-impl Server {
-    pub fn lane_for<N, L>(server: Arc<Mutex<Self>>, node: N, lane: L) -> Lane
-    where
-        N: ToString,
-        L: ToString,
-    {
-        Lane {
-            node: node.to_string(),
-            lane: lane.to_string(),
-            server,
-        }
-    }
-}
-
-impl Server {
-    pub fn new(transport: DuplexStream) -> Server {
-        Server {
-            buf: BytesMut::new(),
-            transport: WebSocket::from_upgraded(
-                WebSocketConfig::default(),
-                transport,
-                NegotiatedExtension::from(NoExt),
-                BytesMut::default(),
-                Role::Server,
-            ),
-        }
-    }
-}
+const TEST_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[tokio::test]
 async fn transport_opens_connection_ok() {
@@ -498,6 +88,7 @@ async fn transport_opens_connection_ok() {
         NoExtProvider,
         non_zero_usize!(128),
         Duration::from_secs(5),
+        Default::default(),
     );
 
     let (transport_tx, transport_rx) = mpsc::channel(128);
@@ -568,6 +159,165 @@ async fn transport_opens_connection_ok() {
     assert!(attach.same_channel(&attach_2));
 }
 
+struct TransportFixture {
+    handle: TransportHandle,
+    socks: Vec<SocketAddr>,
+    servers: Vec<WebSocket<DuplexStream, NoExt>>,
+    _task: JoinHandle<()>,
+}
+
+fn start_transport(hosts: &[&str], config: ConnectionConfig) -> TransportFixture {
+    let mut resolver = vec![];
+    let mut sockets = vec![];
+    let mut states = vec![];
+    let mut socks = vec![];
+    let mut servers = vec![];
+    for host in hosts {
+        let sock: SocketAddr = format!("{}:80", host).parse().unwrap();
+        let (client, server) = duplex(128);
+        resolver.push(((host.to_string(), 80), sock));
+        sockets.push((sock, client));
+        states.push((host.to_string(), WsAction::Open));
+        socks.push(sock);
+        servers.push(WebSocket::from_upgraded(
+            WebSocketConfig::default(),
+            server,
+            NegotiatedExtension::from(NoExt),
+            BytesMut::new(),
+            Role::Server,
+        ));
+    }
+    let transport = Transport::new(
+        MockClientConnections::new(resolver, sockets),
+        MockWs::new(states),
+        NoExtProvider,
+        non_zero_usize!(128),
+        Duration::from_secs(5),
+        config,
+    );
+
+    let (transport_tx, transport_rx) = mpsc::channel(128);
+    TransportFixture {
+        handle: TransportHandle::new(transport_tx),
+        socks,
+        servers,
+        _task: tokio::spawn(transport.run(transport_rx)),
+    }
+}
+
+async fn expect_closed(server: &mut WebSocket<DuplexStream, NoExt>) {
+    let mut buf = BytesMut::new();
+    loop {
+        match server.read(&mut buf).await {
+            Ok(Message::Close(_)) | Err(_) => break,
+            Ok(Message::Ping(_)) | Ok(Message::Pong(_)) => continue,
+            Ok(ow) => panic!("Unexpected message: {:?}", ow),
+        }
+    }
+}
+
+#[tokio::test]
+async fn transport_connection_limit() {
+    let TransportFixture {
+        handle,
+        socks,
+        mut servers,
+        _task,
+    } = start_transport(
+        &["127.0.0.1", "127.0.0.2"],
+        ConnectionConfig {
+            max_connections: Some(non_zero_usize!(1)),
+            idle_timeout: Some(Duration::from_secs(60)),
+            keep_alive: None,
+            ..Default::default()
+        },
+    );
+
+    handle
+        .connection_for(Scheme::Ws, "127.0.0.1".to_string(), vec![socks[0]])
+        .await
+        .expect("Failed to open connection");
+
+    let err = handle
+        .connection_for(Scheme::Ws, "127.0.0.2".to_string(), vec![socks[1]])
+        .await
+        .expect_err("Connection limit ignored.");
+    assert_eq!(err.kind(), DownlinkErrorKind::ConnectionLimit);
+
+    // Once the first connection is unused, it is evicted to make room.
+    handle.release(socks[0]).await;
+    let (opened_sock, _attach) = handle
+        .connection_for(Scheme::Ws, "127.0.0.2".to_string(), vec![socks[1]])
+        .await
+        .expect("Failed to open connection");
+    assert_eq!(opened_sock, socks[1]);
+
+    assert!(timeout(TEST_TIMEOUT, expect_closed(&mut servers[0]))
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn transport_closes_idle_connection() {
+    let TransportFixture {
+        handle,
+        socks,
+        mut servers,
+        _task,
+    } = start_transport(
+        &["127.0.0.1"],
+        ConnectionConfig {
+            max_connections: None,
+            idle_timeout: Some(Duration::from_millis(50)),
+            keep_alive: None,
+            ..Default::default()
+        },
+    );
+
+    handle
+        .connection_for(Scheme::Ws, "127.0.0.1".to_string(), vec![socks[0]])
+        .await
+        .expect("Failed to open connection");
+    handle.release(socks[0]).await;
+
+    assert!(timeout(TEST_TIMEOUT, expect_closed(&mut servers[0]))
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn transport_sends_keep_alive_pings() {
+    let TransportFixture {
+        handle,
+        socks,
+        mut servers,
+        _task,
+    } = start_transport(
+        &["127.0.0.1"],
+        ConnectionConfig {
+            max_connections: None,
+            idle_timeout: None,
+            keep_alive: Some(KeepAlive {
+                interval: Duration::from_millis(10),
+                timeout: Duration::from_secs(5),
+            }),
+            ..Default::default()
+        },
+    );
+
+    let (_sock, _attach) = handle
+        .connection_for(Scheme::Ws, "127.0.0.1".to_string(), vec![socks[0]])
+        .await
+        .expect("Failed to open connection");
+
+    let mut buf = BytesMut::new();
+    let message = timeout(TEST_TIMEOUT, servers[0].read(&mut buf))
+        .await
+        .expect("Timed out.")
+        .expect("Read failed.");
+    assert!(matches!(message, Message::Ping(_)));
+}
+
 #[tokio::test]
 async fn transport_opens_connection_err() {
     let peer = SchemeHostPort::new(Scheme::Ws, "127.0.0.1".to_string(), 80);
@@ -584,6 +334,7 @@ async fn transport_opens_connection_err() {
         NoExtProvider,
         non_zero_usize!(128),
         Duration::from_secs(5),
+        Default::default(),
     );
 
     let (transport_tx, transport_rx) = mpsc::channel(128);
@@ -598,7 +349,7 @@ async fn transport_opens_connection_err() {
         .connection_for(Scheme::Ws, "127.0.0.1".to_string(), vec![sock])
         .await
         .expect_err("Expected connection to fail");
-    assert!(actual_err.is(DownlinkErrorKind::Connection));
+    assert!(actual_err.is(DownlinkErrorKind::WebsocketNegotiationFailed));
     assert!(actual_err.downcast_ref::<RatchetError>().is_some());
 }
 
@@ -729,6 +480,8 @@ enum ValueTestMessage<T> {
     Event(T),
     Set(Option<T>, T),
     Unlinked,
+    Reconnecting,
+    Resynced(T),
 }
 
 fn value_lifecycle<T>(
@@ -756,6 +509,12 @@ where
         .on_unlinked_blocking(|tx| {
             assert!(tx.send(ValueTestMessage::Unlinked).is_ok());
         })
+        .on_reconnecting_blocking(|tx| {
+            assert!(tx.send(ValueTestMessage::Reconnecting).is_ok());
+        })
+        .on_resynced_blocking(|tx, v| {
+            assert!(tx.send(ValueTestMessage::Resynced(v.clone())).is_ok());
+        })
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -800,12 +559,38 @@ where
         })
 }
 
+#[derive(Debug, PartialEq, Eq)]
+enum EventTestMessage<T> {
+    Linked,
+    Event(T),
+    Unlinked,
+}
+
+fn event_lifecycle<T>(
+    tx: mpsc::UnboundedSender<EventTestMessage<T>>,
+) -> impl EventDownlinkLifecycle<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    BasicEventDownlinkLifecycle::<T>::default()
+        .with(tx)
+        .on_linked_blocking(|tx| {
+            assert!(tx.send(EventTestMessage::Linked).is_ok());
+        })
+        .on_event_blocking(|tx, v| {
+            assert!(tx.send(EventTestMessage::Event(v.clone())).is_ok());
+        })
+        .on_unlinked_blocking(|tx| {
+            assert!(tx.send(EventTestMessage::Unlinked).is_ok());
+        })
+}
+
 struct ValueDownlinkContext {
     handle: RawHandle,
     spawned: Arc<Notify>,
     stopped: Arc<Notify>,
     handle_tx: mpsc::Sender<ValueDownlinkSet<i32>>,
-    server: Server,
+    server: MockServer,
     promise: promise::Receiver<Result<(), Arc<DownlinkRuntimeError>>>,
     stop_tx: trigger::Sender,
 }
@@ -813,10 +598,15 @@ struct ValueDownlinkContext {
 struct Fixture {
     handle: RawHandle,
     stop_tx: trigger::Sender,
-    server: Server,
+    server: MockServer,
     _jh: JoinHandle<()>,
 }
 
+const LIMITS: RuntimeLimits = RuntimeLimits {
+    max_pending_connections: non_zero_usize!(32),
+    max_pending_attachments: non_zero_usize!(64),
+};
+
 fn start() -> Fixture {
     let sock: SocketAddr = "127.0.0.1:80".parse().unwrap();
     let (client, server) = duplex(128);
@@ -834,15 +624,17 @@ fn start() -> Fixture {
             NoExtProvider,
             non_zero_usize!(128),
             Duration::from_secs(5),
+            Default::default(),
         ),
         non_zero_usize!(32),
         true,
+        LIMITS,
     );
 
     Fixture {
         handle,
         stop_tx,
-        server: Server::new(server),
+        server: MockServer::new(server),
         _jh: tokio::spawn(task),
     }
 }
@@ -887,6 +679,21 @@ async fn spawns_downlink() {
     .await;
 }
 
+#[tokio::test]
+async fn pools_drained_once_spawned() {
+    let (msg_tx, _msg_rx) = unbounded_channel();
+    run_value_downlink(value_lifecycle(msg_tx), |ctx| async move {
+        ctx.spawned.notified().await;
+
+        let gauges = ctx.handle.gauges();
+        assert_eq!(gauges.connections().in_flight(), 0);
+        assert_eq!(gauges.connections().queued(), 0);
+        assert_eq!(gauges.attachments().in_flight(), 0);
+        assert_eq!(gauges.attachments().queued(), 0);
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn stops_on_disconnect() {
     let (msg_tx, _msg_rx) = unbounded_channel();
@@ -924,7 +731,7 @@ async fn test_value_lifecycle() {
         } = ctx;
         spawned.notified().await;
 
-        let mut lane = Server::lane_for(Arc::new(Mutex::new(server)), "node", "value_lane");
+        let mut lane = server.lane("node", "value_lane");
 
         lane.await_link().await;
         assert_eq!(msg_rx.recv().await.unwrap(), ValueTestMessage::Linked);
@@ -949,6 +756,214 @@ async fn test_value_lifecycle() {
     .await;
 }
 
+#[tokio::test]
+async fn value_downlink_event_stream() {
+    let Fixture {
+        handle,
+        stop_tx,
+        server,
+        _jh,
+    } = start();
+    let handle = ClientHandle::new(handle, None);
+
+    let test = async move {
+        let (view, mut events) = handle
+            .value_downlink::<i32>(RemotePath::new("ws://127.0.0.1", "node", "value_lane"))
+            .open_stream(non_zero_usize!(8))
+            .await
+            .expect("Failed to open downlink.");
+
+        let mut lane = server.lane("node", "value_lane");
+
+        lane.await_link().await;
+        assert_eq!(events.next().await, Some(ValueDownlinkEvent::Linked));
+
+        lane.await_sync(vec![7]).await;
+        assert_eq!(events.next().await, Some(ValueDownlinkEvent::Synced(7)));
+
+        lane.send_event(8).await;
+        assert_eq!(
+            events.next().await,
+            Some(ValueDownlinkEvent::Set {
+                previous: Some(7),
+                value: 8
+            })
+        );
+
+        view.set(13).await.unwrap();
+        lane.await_command(13).await;
+
+        lane.send_unlinked().await;
+        assert_eq!(events.next().await, Some(ValueDownlinkEvent::Unlinked));
+
+        assert!(stop_tx.trigger());
+        lane.await_closed().await;
+        assert!(view.stop_notification().await.unwrap().is_ok());
+        assert_eq!(events.next().await, None);
+    };
+    assert!(timeout(TEST_TIMEOUT, test).await.is_ok());
+}
+
+#[tokio::test]
+async fn lane_not_found_reported_to_caller() {
+    let Fixture {
+        handle,
+        stop_tx,
+        server,
+        _jh,
+    } = start();
+    let handle = ClientHandle::new(handle, None);
+
+    let test = async move {
+        let (view, mut events) = handle
+            .value_downlink::<i32>(RemotePath::new("ws://127.0.0.1", "node", "missing"))
+            .open_stream(non_zero_usize!(8))
+            .await
+            .expect("Failed to open downlink.");
+
+        let mut lane = server.lane("node", "missing");
+
+        match lane.read().await {
+            Envelope::Link {
+                node_uri, lane_uri, ..
+            } => {
+                lane.write(Envelope::Unlinked {
+                    node_uri,
+                    lane_uri,
+                    body: Some(Value::of_attr("laneNotFound")),
+                })
+                .await;
+            }
+            e => panic!("Unexpected envelope {:?}", e),
+        }
+
+        assert_eq!(
+            events.next().await,
+            Some(ValueDownlinkEvent::Failed(LinkFailure::LaneNotFound))
+        );
+        assert_eq!(events.next().await, Some(ValueDownlinkEvent::Unlinked));
+
+        let err = view
+            .stop_notification()
+            .await
+            .unwrap()
+            .expect_err("Expected the downlink to fail.");
+        assert!(err.is(DownlinkErrorKind::LaneNotFound));
+        assert_eq!(events.next().await, None);
+        assert!(stop_tx.trigger());
+    };
+    assert!(timeout(TEST_TIMEOUT, test).await.is_ok());
+}
+
+#[tokio::test]
+async fn value_downlink_get_and_await_synced() {
+    let Fixture {
+        handle,
+        stop_tx,
+        server,
+        _jh,
+    } = start();
+    let handle = ClientHandle::new(handle, None);
+
+    let test = async move {
+        let (view, mut events) = handle
+            .value_downlink::<i32>(RemotePath::new("ws://127.0.0.1", "node", "value_lane"))
+            .open_stream(non_zero_usize!(8))
+            .await
+            .expect("Failed to open downlink.");
+
+        assert!(matches!(
+            view.get(),
+            Err(ValueDownlinkOperationError::NotYetSynced)
+        ));
+
+        let mut lane = server.lane("node", "value_lane");
+
+        lane.await_link().await;
+        view.await_linked().await.expect("Downlink failed to link.");
+        assert!(matches!(
+            view.get(),
+            Err(ValueDownlinkOperationError::NotYetSynced)
+        ));
+
+        lane.await_sync(vec![7]).await;
+        view.await_synced().await.expect("Downlink failed to sync.");
+        assert_eq!(view.get().unwrap(), 7);
+
+        lane.send_event(8).await;
+        loop {
+            if let Some(ValueDownlinkEvent::Set { .. }) = events.next().await {
+                break;
+            }
+        }
+        assert_eq!(view.get().unwrap(), 8);
+
+        lane.send_unlinked().await;
+        assert_eq!(events.next().await, Some(ValueDownlinkEvent::Unlinked));
+
+        assert!(stop_tx.trigger());
+        lane.await_closed().await;
+        assert!(view.stop_notification().await.unwrap().is_ok());
+
+        assert!(matches!(
+            view.await_synced().await,
+            Err(ValueDownlinkOperationError::DownlinkStopped)
+        ));
+        assert_eq!(view.get().unwrap(), 8);
+    };
+    assert!(timeout(TEST_TIMEOUT, test).await.is_ok());
+}
+
+#[tokio::test]
+async fn map_downlink_event_stream() {
+    let Fixture {
+        handle,
+        stop_tx: _stop_tx,
+        server,
+        _jh,
+    } = start();
+    let handle = ClientHandle::new(handle, None);
+
+    let test = async move {
+        let (_view, mut events) = handle
+            .map_downlink::<i32, i32>(RemotePath::new("ws://127.0.0.1", "node", "map_lane"))
+            .open_stream(non_zero_usize!(8))
+            .await
+            .expect("Failed to open downlink.");
+
+        let mut lane = server.lane("node", "map_lane");
+
+        lane.await_link().await;
+        assert_eq!(events.next().await, Some(MapDownlinkEvent::Linked));
+
+        lane.await_sync(vec![MapMessage::Update { key: 1, value: 1 }])
+            .await;
+        assert_eq!(
+            events.next().await,
+            Some(MapDownlinkEvent::Synced(BTreeMap::from([(1, 1)])))
+        );
+
+        lane.send_event(MapMessage::Update { key: 1, value: 2 })
+            .await;
+        assert_eq!(
+            events.next().await,
+            Some(MapDownlinkEvent::Updated {
+                key: 1,
+                previous: Some(1),
+                value: 2
+            })
+        );
+
+        lane.send_event(MapMessage::<i32, i32>::Remove { key: 1 })
+            .await;
+        assert_eq!(
+            events.next().await,
+            Some(MapDownlinkEvent::Removed { key: 1, value: 2 })
+        );
+    };
+    assert!(timeout(TEST_TIMEOUT, test).await.is_ok());
+}
+
 async fn tracking_value_downlink<LC>(
     handle: &RawHandle,
     lifecycle: LC,
@@ -1061,9 +1076,11 @@ async fn failed_handshake() {
             NoExtProvider,
             non_zero_usize!(128),
             Duration::from_secs(5),
+            Default::default(),
         ),
         non_zero_usize!(32),
         true,
+        LIMITS,
     );
 
     let _task = spawn(task);
@@ -1138,9 +1155,8 @@ async fn disjoint_lanes() {
     )
     .await;
 
-    let server = Arc::new(Mutex::new(server));
-    let mut value_lane = Server::lane_for(server.clone(), "node", "value_lane");
-    let mut map_lane = Server::lane_for(server, "node", "map_lane");
+    let mut value_lane = server.lane("node", "value_lane");
+    let mut map_lane = server.lane("node", "map_lane");
 
     {
         value_spawned.notified().await;
@@ -1183,6 +1199,7 @@ async fn disjoint_lanes() {
 
     drop(value_lane);
     drop(map_lane);
+    drop(server);
 
     value_stopped.notified().await;
     map_stopped.notified().await;
@@ -1195,3 +1212,380 @@ async fn disjoint_lanes() {
     assert!(map_result.is_ok());
     assert!(map_result.unwrap().is_ok());
 }
+
+#[tokio::test]
+async fn event_downlink_without_sync() {
+    let Fixture {
+        handle,
+        stop_tx,
+        server,
+        _jh,
+    } = start();
+    let handle = ClientHandle::new(handle, None);
+    let (msg_tx, mut msg_rx) = unbounded_channel();
+
+    let test = async move {
+        let builder =
+            handle.event_downlink::<i32>(RemotePath::new("ws://127.0.0.1", "node", "event_lane"));
+        assert!(builder.options.is_empty());
+
+        let view = builder
+            .lifecycle(event_lifecycle::<i32>(msg_tx))
+            .open()
+            .await
+            .expect("Failed to open downlink.");
+
+        let mut lane = server.lane("node", "event_lane");
+
+        lane.await_link().await;
+        expect_event(&mut msg_rx, EventTestMessage::Linked).await;
+
+        lane.send_event(5).await;
+        expect_event(&mut msg_rx, EventTestMessage::Event(5)).await;
+        lane.send_event(6).await;
+        expect_event(&mut msg_rx, EventTestMessage::Event(6)).await;
+
+        lane.send_unlinked().await;
+        expect_event(&mut msg_rx, EventTestMessage::Unlinked).await;
+
+        assert!(stop_tx.trigger());
+        // If the downlink had requested a sync, a text frame would be received before the close frame.
+        lane.await_closed().await;
+        assert!(view.stop_notification().await.unwrap().is_ok());
+    };
+    assert!(timeout(Duration::from_secs(5), test).await.is_ok());
+}
+
+#[tokio::test]
+async fn relinks_after_disconnect() {
+    let sock: SocketAddr = "127.0.0.1:80".parse().unwrap();
+    let (client, server) = duplex(128);
+    let ext = MockClientConnections::new([(("127.0.0.1".to_string(), 80), sock)], [(sock, client)]);
+    let ws = MockWs::new([("127.0.0.1".to_string(), WsAction::Open)]);
+    let (stop_tx, stop_rx) = trigger();
+
+    let (handle, task) = start_runtime(
+        non_zero_usize!(32),
+        stop_rx,
+        Transport::new(
+            ext.clone(),
+            ws,
+            NoExtProvider,
+            non_zero_usize!(128),
+            Duration::from_secs(5),
+            Default::default(),
+        ),
+        non_zero_usize!(32),
+        true,
+        LIMITS,
+    );
+    let _jh = tokio::spawn(task);
+
+    let (msg_tx, mut msg_rx) = unbounded_channel();
+    let (_set_tx, set_rx) = mpsc::channel(8);
+    let downlink_config = DownlinkConfig {
+        relink: RetryStrategy::interval(
+            Duration::from_millis(50),
+            Quantity::Finite(non_zero_usize!(10)),
+        ),
+        ..Default::default()
+    };
+
+    let _promise = handle
+        .run_downlink(
+            RemotePath::new("ws://127.0.0.1", "node", "value_lane"),
+            DownlinkRuntimeConfig::default(),
+            downlink_config,
+            DownlinkOptions::SYNC,
+            DownlinkTask::new(ValueDownlinkModel::new(
+                set_rx,
+                value_lifecycle::<i32>(msg_tx),
+            )),
+        )
+        .await
+        .expect("Failed to spawn downlink open request");
+
+    let test = async move {
+        let mut lane = MockServer::new(server).lane("node", "value_lane");
+        lane.await_link().await;
+        expect_event(&mut msg_rx, ValueTestMessage::Linked).await;
+        lane.await_sync(vec![1]).await;
+        expect_event(&mut msg_rx, ValueTestMessage::Synced(1)).await;
+
+        // Make a new connection available and then drop the existing one.
+        let (client, server) = duplex(128);
+        ext.add_socket(sock, client).await;
+        drop(lane);
+
+        let mut msg = msg_rx.recv().await.unwrap();
+        if msg == ValueTestMessage::Unlinked {
+            msg = msg_rx.recv().await.unwrap();
+        }
+        assert_eq!(msg, ValueTestMessage::Reconnecting);
+
+        let mut lane = MockServer::new(server).lane("node", "value_lane");
+        lane.await_link().await;
+        expect_event(&mut msg_rx, ValueTestMessage::Linked).await;
+        lane.await_sync(vec![2]).await;
+        expect_event(&mut msg_rx, ValueTestMessage::Synced(2)).await;
+        expect_event(&mut msg_rx, ValueTestMessage::Resynced(2)).await;
+
+        lane.send_unlinked().await;
+        expect_event(&mut msg_rx, ValueTestMessage::Unlinked).await;
+        expect_event(&mut msg_rx, ValueTestMessage::Reconnecting).await;
+
+        assert!(stop_tx.trigger());
+        lane.await_closed().await;
+    };
+    assert!(timeout(Duration::from_secs(5), test).await.is_ok());
+}
+
+#[tokio::test]
+async fn untyped_downlinks() {
+    let Fixture {
+        handle,
+        stop_tx: _stop,
+        server,
+        _jh,
+    } = start();
+
+    let (value_msg_tx, mut value_msg_rx) = unbounded_channel();
+    let (_set_tx, set_rx) = mpsc::channel(128);
+    let _value_promise = handle
+        .run_downlink(
+            RemotePath::new("ws://127.0.0.1", "node", "value_lane"),
+            DownlinkRuntimeConfig::default(),
+            Default::default(),
+            DownlinkOptions::SYNC,
+            DownlinkTask::new(ValueDownlinkModel::new(
+                set_rx,
+                value_lifecycle::<Value>(value_msg_tx),
+            )),
+        )
+        .await
+        .expect("Failed to spawn downlink open request");
+
+    let (map_msg_tx, mut map_msg_rx) = unbounded_channel();
+    let (_map_tx, map_rx) = mpsc::channel(128);
+    let _map_promise = handle
+        .run_downlink(
+            RemotePath::new("ws://127.0.0.1", "node", "map_lane"),
+            DownlinkRuntimeConfig::default(),
+            Default::default(),
+            DownlinkOptions::SYNC,
+            DownlinkTask::new(MapDownlinkModel::new(
+                map_rx,
+                map_lifecycle::<Value, Value>(map_msg_tx),
+            )),
+        )
+        .await
+        .expect("Failed to spawn downlink open request");
+
+    let test = async move {
+        let mut value_lane = server.lane("node", "value_lane");
+        let mut map_lane = server.lane("node", "map_lane");
+
+        value_lane.await_link().await;
+        expect_event(&mut value_msg_rx, ValueTestMessage::Linked).await;
+        value_lane.await_sync(vec!["text".to_string()]).await;
+        expect_event(
+            &mut value_msg_rx,
+            ValueTestMessage::Synced(Value::text("text")),
+        )
+        .await;
+        value_lane.send_event(4.5f64).await;
+        expect_event(
+            &mut value_msg_rx,
+            ValueTestMessage::Event(Value::Float64Value(4.5)),
+        )
+        .await;
+
+        map_lane.await_link().await;
+        expect_event(&mut map_msg_rx, MapTestMessage::Linked).await;
+        map_lane
+            .await_sync(vec![MapMessage::Update {
+                key: 1,
+                value: "a".to_string(),
+            }])
+            .await;
+        expect_event(
+            &mut map_msg_rx,
+            MapTestMessage::Synced(BTreeMap::from([(Value::Int32Value(1), Value::text("a"))])),
+        )
+        .await;
+    };
+    assert!(timeout(Duration::from_secs(5), test).await.is_ok());
+}
+
+#[tokio::test]
+async fn operation_timeout_elapses() {
+    let result = with_timeout(
+        Some(Duration::from_millis(10)),
+        futures::future::pending::<Result<(), Arc<DownlinkRuntimeError>>>(),
+    )
+    .await;
+    let err = result.expect_err("Expected the operation to time out.");
+    assert!(err.is(DownlinkErrorKind::Timeout));
+
+    let result = with_timeout(Some(Duration::from_secs(5)), async { Ok(7) }).await;
+    assert_eq!(result.expect("Expected the operation to complete."), 7);
+}
+
+#[test]
+fn classify_failure_reasons() {
+    let cases = [
+        (
+            DownlinkFailureReason::TlsConnectionFailed {
+                message: "Bad certificate.".to_string(),
+                recoverable: false,
+            },
+            DownlinkErrorKind::Tls,
+        ),
+        (
+            DownlinkFailureReason::UnresolvableLocal(RelativeAddress::text("/node", "lane")),
+            DownlinkErrorKind::NodeNotFound,
+        ),
+        (
+            DownlinkFailureReason::WebsocketNegotiationFailed("Rejected.".to_string()),
+            DownlinkErrorKind::WebsocketNegotiationFailed,
+        ),
+        (
+            DownlinkFailureReason::RemoteStopped,
+            DownlinkErrorKind::RemoteStopped,
+        ),
+    ];
+    for (reason, kind) in cases {
+        let err = DownlinkRuntimeError::from(reason);
+        assert_eq!(err.kind(), kind);
+        assert!(err.downcast_ref::<DownlinkFailureReason>().is_some());
+    }
+
+    let err = DownlinkRuntimeError::from(DownlinkTaskError::SyncedWithNoValue);
+    assert_eq!(err.kind(), DownlinkErrorKind::ProtocolViolation);
+
+    let err = DownlinkRuntimeError::from(DownlinkTaskError::LinkFailed(LinkFailure::NodeNotFound));
+    assert_eq!(err.kind(), DownlinkErrorKind::NodeNotFound);
+}
+
+async fn bind_command_listener() -> (tokio::net::TcpListener, u16) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind to port.");
+    let port = listener.local_addr().unwrap().port();
+    (listener, port)
+}
+
+async fn accept_command_connection(
+    listener: tokio::net::TcpListener,
+) -> WebSocket<tokio::net::TcpStream, NoExt> {
+    let (socket, _) = listener.accept().await.expect("Failed to accept.");
+    let upgrader = ratchet::accept_with(
+        socket,
+        WebSocketConfig::default(),
+        NoExtProvider,
+        ProtocolRegistry::new(["warp0"]).unwrap(),
+    )
+    .await
+    .expect("Handshake failed.");
+    upgrader.upgrade().await.expect("Upgrade failed.").websocket
+}
+
+async fn expect_command(ws: &mut WebSocket<tokio::net::TcpStream, NoExt>, id: u64) {
+    let mut buffer = BytesMut::new();
+    loop {
+        match ws.read(&mut buffer).await {
+            Ok(Message::Text) => break,
+            Ok(Message::Ping(_) | Message::Pong(_)) => buffer.clear(),
+            ow => panic!("Unexpected message: {:?}", ow),
+        }
+    }
+    let frame = std::str::from_utf8(buffer.as_ref()).expect("Invalid UTF-8.");
+    assert!(
+        frame.contains(&format!("id: {}", id)),
+        "Unexpected frame: {}",
+        frame
+    );
+}
+
+#[tokio::test]
+async fn acked_command_times_out() {
+    let ack_timeout = Duration::from_millis(100);
+    let (listener, port) = bind_command_listener().await;
+    let (stop_tx, stop_rx) = oneshot::channel::<()>();
+    let server = spawn(async move {
+        let mut ws = accept_command_connection(listener).await;
+        let mut buffer = BytesMut::new();
+        // The command is received but is never acknowledged.
+        assert!(matches!(ws.read(&mut buffer).await, Ok(Message::Text)));
+        let _ = stop_rx.await;
+    });
+
+    let mut commander = Commander::with_ack_timeout(Some(ack_timeout));
+    let result = timeout(
+        TEST_TIMEOUT,
+        commander.send_command_acked(format!("ws://127.0.0.1:{}", port), "/node", "lane", &13),
+    )
+    .await
+    .expect("Test timed out.");
+    assert!(matches!(result, Err(CommandError::TimedOut(period)) if period == ack_timeout));
+
+    stop_tx.send(()).expect("Server stopped.");
+    server.await.expect("Server failed.");
+}
+
+#[tokio::test]
+async fn acked_commands_do_not_wait_for_earlier_acks() {
+    let (listener, port) = bind_command_listener().await;
+    let Fixture {
+        handle,
+        stop_tx,
+        _jh,
+        ..
+    } = start();
+    let handle = ClientHandle::new(handle, None);
+    let host = format!("ws://127.0.0.1:{}", port);
+
+    let (first_tx, first_rx) = oneshot::channel::<()>();
+    let (second_tx, second_rx) = oneshot::channel::<()>();
+    let server = spawn(async move {
+        let mut ws = accept_command_connection(listener).await;
+        expect_command(&mut ws, 0).await;
+        first_tx.send(()).expect("Test stopped.");
+        // The second command is received while the first is still waiting for its ack.
+        expect_command(&mut ws, 1).await;
+        ws.write_text("@dispatched(node: \"/node\", lane: lane, id: 1)")
+            .await
+            .expect("Write failed.");
+        second_rx.await.expect("Second command failed.");
+        ws.write_text("@dispatched(node: \"/node\", lane: lane, id: 0)")
+            .await
+            .expect("Write failed.");
+        let mut buffer = BytesMut::new();
+        while ws.read(&mut buffer).await.is_ok() {
+            buffer.clear();
+        }
+    });
+
+    let first_handle = handle.clone();
+    let first_host = host.clone();
+    let first = spawn(async move {
+        first_handle
+            .send_command_acked(first_host, "/node", "lane", &1)
+            .await
+    });
+    let test = async move {
+        // Wait for the first command to be received before sending the second.
+        first_rx.await.expect("Server stopped.");
+        assert!(handle
+            .send_command_acked(host, "/node", "lane", &2)
+            .await
+            .is_ok());
+        second_tx.send(()).expect("Server stopped.");
+        assert!(first.await.expect("First command panicked.").is_ok());
+        handle.commander.lock().await.close().await;
+    };
+    timeout(TEST_TIMEOUT, test).await.expect("Test timed out.");
+
+    server.await.expect("Server failed.");
+    stop_tx.trigger();
+}
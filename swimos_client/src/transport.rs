@@ -20,19 +20,24 @@ use futures_util::future::BoxFuture;
 use futures_util::stream::FuturesUnordered;
 use futures_util::FutureExt;
 use ratchet::{ExtensionProvider, SplittableExtension, WebSocket, WebSocketStream};
+use std::collections::hash_map::Entry;
 use std::io;
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 use std::time::Duration;
+use swimos_api::limits::Limits;
 use swimos_messages::remote_protocol::AttachClient;
-use swimos_remote::websocket::WebsocketClient;
-use swimos_remote::RemoteTask;
-use swimos_remote::{ClientConnections, Scheme, SchemeHostPort};
+use swimos_remote::websocket::{WarpProtocol, WebsocketClient};
+use swimos_remote::{
+    race_connections, ClientConnections, Scheme, SchemeHostPort, CONNECTION_ATTEMPT_DELAY,
+};
+use swimos_remote::{KeepAlive, RemoteTask};
 use swimos_utilities::trigger;
 use tokio::select;
 use tokio::sync::{mpsc, oneshot};
 use tokio::task::JoinError;
-use tracing::{debug, error};
+use tracing::{debug, error, info};
+use uuid::Uuid;
 
 type AttachCallback =
     oneshot::Sender<Result<(SocketAddr, mpsc::Sender<AttachClient>), DownlinkRuntimeError>>;
@@ -45,6 +50,31 @@ pub enum TransportRequest {
         addrs: Vec<SocketAddr>,
         callback: AttachCallback,
     },
+    /// The connection to the peer is no longer being used by any downlinks.
+    Release(SocketAddr),
+}
+
+/// Configuration for the connections that are managed by the transport task.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConnectionConfig {
+    /// The maximum number of open (or opening) connections. If this is reached, connections
+    /// that are not being used will be closed to make room. If there are none, the request for a
+    /// new connection will fail.
+    pub max_connections: Option<NonZeroUsize>,
+    /// Connections that are not being used by any downlinks will be closed after this period.
+    pub idle_timeout: Option<Duration>,
+    /// Keep-alive configuration for the connections.
+    pub keep_alive: Option<KeepAlive>,
+    /// Limits on the envelopes received over the connections.
+    pub limits: Limits,
+}
+
+/// An open connection to a peer.
+struct PeerConnection {
+    id: Uuid,
+    attach: mpsc::Sender<AttachClient>,
+    // Set when the connection is not being used by any downlinks.
+    idle_since: Option<u64>,
 }
 
 enum TransportEvent<Sock, Ext> {
@@ -60,12 +90,19 @@ enum TransportEvent<Sock, Ext> {
         host: String,
         callback: AttachCallback,
         websocket: WebSocket<Sock, Ext>,
+        protocol: WarpProtocol,
     },
+    ConnectionFailed,
     PeerStopped {
+        id: Uuid,
         addr: SocketAddr,
         host: String,
         result: Result<(), JoinError>,
     },
+    IdleTimeout {
+        addr: SocketAddr,
+        epoch: u64,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -121,6 +158,11 @@ impl TransportHandle {
         })
         .await
     }
+
+    /// Notify the transport that the connection to a peer is no longer being used.
+    pub async fn release(&self, addr: SocketAddr) {
+        let _r = self.tx.send(TransportRequest::Release(addr)).await;
+    }
 }
 
 pub struct Transport<Net, Ws, Provider> {
@@ -129,6 +171,7 @@ pub struct Transport<Net, Ws, Provider> {
     ext_provider: Provider,
     buffer_size: NonZeroUsize,
     close_timeout: Duration,
+    connections: ConnectionConfig,
 }
 
 impl<Net, Ws, Provider> Transport<Net, Ws, Provider>
@@ -145,6 +188,7 @@ where
         ext_provider: Provider,
         buffer_size: NonZeroUsize,
         close_timeout: Duration,
+        connections: ConnectionConfig,
     ) -> Transport<Net, Ws, Provider> {
         Transport {
             networking,
@@ -152,6 +196,7 @@ where
             ext_provider,
             buffer_size,
             close_timeout,
+            connections,
         }
     }
 
@@ -162,12 +207,23 @@ where
             ext_provider,
             buffer_size,
             close_timeout,
+            connections:
+                ConnectionConfig {
+                    max_connections,
+                    idle_timeout,
+                    keep_alive,
+                    limits,
+                },
         } = self;
 
-        let (stop_tx, stop_rx) = trigger::trigger();
-        let mut peers: FnvHashMap<SocketAddr, mpsc::Sender<AttachClient>> = FnvHashMap::default();
+        let mut peers: FnvHashMap<SocketAddr, PeerConnection> = FnvHashMap::default();
+        // Stop signals for the running connection tasks.
+        let mut stop_signals: FnvHashMap<Uuid, trigger::Sender> = FnvHashMap::default();
         let mut events: FuturesUnordered<BoxFuture<Option<_>>> = FuturesUnordered::default();
         let mut remote_issuer = IdIssuer::new();
+        // The number of connections that are being opened.
+        let mut connecting = 0usize;
+        let mut idle_epoch = 0u64;
 
         debug!("Transport task started");
 
@@ -180,7 +236,9 @@ where
                     match request {
                         Some(request) => TransportEvent::Request(request),
                         None => {
-                            stop_tx.trigger();
+                            for (_, stop_tx) in stop_signals.drain() {
+                                stop_tx.trigger();
+                            }
                             break
                         },
                     }
@@ -205,22 +263,39 @@ where
                     addrs,
                     callback,
                 }) => {
-                    let peer = addrs
-                        .iter()
-                        .find_map(|sock| peers.get(sock).map(|tx| (sock, tx)));
-                    match peer {
+                    let peer = addrs.iter().find(|sock| peers.contains_key(sock)).copied();
+                    match peer.and_then(|sock| peers.get_mut(&sock).map(|peer| (sock, peer))) {
                         Some((sock, peer)) => {
-                            let _r = callback.send(Ok((*sock, peer.clone())));
+                            peer.idle_since = None;
+                            let _r = callback.send(Ok((sock, peer.attach.clone())));
                         }
                         None => {
+                            if let Some(max) = max_connections {
+                                if peers.len() + connecting >= max.get()
+                                    && !evict_idle(&mut peers, &mut stop_signals)
+                                {
+                                    info!(host = %host, "Connection limit reached.");
+                                    let _r = callback.send(Err(DownlinkRuntimeError::new(
+                                        DownlinkErrorKind::ConnectionLimit,
+                                    )));
+                                    continue;
+                                }
+                            }
+                            connecting += 1;
                             let shared_networking = &networking;
                             events.push(
                                 async move {
-                                    for addr in addrs {
-                                        if let Ok(socket) = shared_networking
-                                            .try_open(scheme, Some(host.as_str()), addr)
-                                            .await
-                                        {
+                                    let result =
+                                        race_connections(addrs, CONNECTION_ATTEMPT_DELAY, |addr| {
+                                            shared_networking.try_open(
+                                                scheme,
+                                                Some(host.as_str()),
+                                                addr,
+                                            )
+                                        })
+                                        .await;
+                                    let mut errors = match result {
+                                        Ok((addr, socket)) => {
                                             return Some(TransportEvent::Open {
                                                 host,
                                                 callback,
@@ -228,12 +303,19 @@ where
                                                 addr,
                                             });
                                         }
-                                    }
+                                        Err(errors) => errors,
+                                    };
 
-                                    let _r = callback.send(Err(DownlinkRuntimeError::new(
-                                        DownlinkErrorKind::Unresolvable,
-                                    )));
-                                    None
+                                    // If no address could be connected to, the error for the last
+                                    // address is reported.
+                                    let error = match errors.pop() {
+                                        Some((_, e)) => DownlinkRuntimeError::from(e),
+                                        None => DownlinkRuntimeError::new(
+                                            DownlinkErrorKind::Unresolvable,
+                                        ),
+                                    };
+                                    let _r = callback.send(Err(error));
+                                    Some(TransportEvent::ConnectionFailed)
                                 }
                                 .boxed(),
                             );
@@ -253,18 +335,19 @@ where
                             .open_connection(socket, provider, host.clone())
                             .await
                         {
-                            Ok(websocket) => Some(TransportEvent::HandshakeComplete {
+                            Ok((websocket, protocol)) => Some(TransportEvent::HandshakeComplete {
                                 addr,
                                 host,
                                 callback,
                                 websocket,
+                                protocol,
                             }),
                             Err(e) => {
                                 let _r = callback.send(Err(DownlinkRuntimeError::with_cause(
                                     DownlinkErrorKind::WebsocketNegotiationFailed,
                                     e,
                                 )));
-                                None
+                                Some(TransportEvent::ConnectionFailed)
                             }
                         }
                     };
@@ -275,20 +358,30 @@ where
                     host,
                     callback,
                     websocket,
+                    protocol,
                 } => {
+                    connecting -= 1;
+                    let id = remote_issuer.next_id();
                     let (attach_tx, attach_rx) = mpsc::channel(buffer_size.get());
-                    let remote = RemoteTask::new(
-                        remote_issuer.next_id(),
-                        stop_rx.clone(),
+                    let (stop_tx, stop_rx) = trigger::trigger();
+                    let mut remote = RemoteTask::new(
+                        id,
+                        stop_rx,
                         websocket,
                         attach_rx,
                         None,
                         buffer_size,
                         close_timeout,
-                    );
+                    )
+                    .with_protocol(protocol)
+                    .with_limits(limits);
+                    if let Some(keep_alive) = keep_alive {
+                        remote = remote.with_keep_alive(keep_alive);
+                    }
                     events.push(
                         async move {
                             Some(TransportEvent::PeerStopped {
+                                id,
                                 addr,
                                 result: tokio::spawn(remote.run()).await,
                                 host,
@@ -296,10 +389,48 @@ where
                         }
                         .boxed(),
                     );
-                    peers.insert(addr, attach_tx.clone());
+                    let peer = PeerConnection {
+                        id,
+                        attach: attach_tx.clone(),
+                        idle_since: None,
+                    };
+                    stop_signals.insert(id, stop_tx);
+                    peers.insert(addr, peer);
                     let _r = callback.send(Ok((addr, attach_tx)));
                 }
-                TransportEvent::PeerStopped { addr, host, result } => {
+                TransportEvent::ConnectionFailed => {
+                    connecting -= 1;
+                }
+                TransportEvent::Request(TransportRequest::Release(addr)) => {
+                    if let (Some(peer), Some(timeout)) = (peers.get_mut(&addr), idle_timeout) {
+                        idle_epoch += 1;
+                        let epoch = idle_epoch;
+                        peer.idle_since = Some(epoch);
+                        events.push(
+                            async move {
+                                tokio::time::sleep(timeout).await;
+                                Some(TransportEvent::IdleTimeout { addr, epoch })
+                            }
+                            .boxed(),
+                        );
+                    }
+                }
+                TransportEvent::IdleTimeout { addr, epoch } => {
+                    if let Entry::Occupied(entry) = peers.entry(addr) {
+                        if entry.get().idle_since == Some(epoch) {
+                            debug!(address = %addr, "Closing idle connection.");
+                            if let Some(stop_tx) = stop_signals.remove(&entry.remove().id) {
+                                stop_tx.trigger();
+                            }
+                        }
+                    }
+                }
+                TransportEvent::PeerStopped {
+                    id,
+                    addr,
+                    host,
+                    result,
+                } => {
                     // We don't need to propagate the closure of the peer as any runtime and
                     // downlink tasks will be immediately notified due to their streams closing.
                     // Following this, the peer will be removed from the collection in the IO task.
@@ -310,10 +441,13 @@ where
                             "Connection task failure"
                         );
                     }
-                    debug_assert!(
-                        peers.remove(&addr).is_some(),
-                        "Attempted to remove a peer that didn't exist"
-                    );
+                    stop_signals.remove(&id);
+                    // The peer may already have been removed if it was evicted.
+                    if let Entry::Occupied(entry) = peers.entry(addr) {
+                        if entry.get().id == id {
+                            entry.remove();
+                        }
+                    }
                 }
             }
         }
@@ -321,3 +455,26 @@ where
         debug!("Transport task completed");
     }
 }
+
+// Closes one of the connections that is not being used by any downlinks, if there are any.
+fn evict_idle(
+    peers: &mut FnvHashMap<SocketAddr, PeerConnection>,
+    stop_signals: &mut FnvHashMap<Uuid, trigger::Sender>,
+) -> bool {
+    let idle = peers
+        .iter()
+        .filter_map(|(addr, peer)| peer.idle_since.map(|epoch| (epoch, *addr)))
+        .min();
+    if let Some((_, addr)) = idle {
+        if let Some(stop_tx) = peers
+            .remove(&addr)
+            .and_then(|peer| stop_signals.remove(&peer.id))
+        {
+            debug!(address = %addr, "Closing idle connection to make room for a new connection.");
+            stop_tx.trigger();
+        }
+        true
+    } else {
+        false
+    }
+}
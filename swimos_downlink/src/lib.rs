@@ -12,10 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub use model::stream::{
+    downlink_event_stream, DownlinkEventSender, DownlinkEventStream, DownlinkStreamEvent,
+    EventDownlinkEvent, MapDownlinkEvent, ValueDownlinkEvent,
+};
 pub use model::{
     event_downlink, map_downlink, value_downlink, ChannelError, DefaultEventDownlinkModel,
     DefaultMapDownlinkModel, DefaultValueDownlinkModel, EventDownlinkModel, MapDownlinkHandle,
-    MapDownlinkModel, NotYetSyncedError, ValueDownlinkModel, ValueDownlinkSet,
+    MapDownlinkModel, NotYetSyncedError, ValueDownlinkModel, ValueDownlinkSet, ValueDownlinkState,
 };
 pub use task::{DownlinkTask, MapKey, MapValue};
 
@@ -25,7 +29,11 @@ mod task;
 pub mod lifecycle {
     pub use crate::model::lifecycle::{
         BasicEventDownlinkLifecycle, BasicMapDownlinkLifecycle, BasicValueDownlinkLifecycle,
-        EventDownlinkLifecycle, MapDownlinkLifecycle, StatefulEventDownlinkLifecycle,
-        StatefulMapDownlinkLifecycle, StatefulValueDownlinkLifecycle, ValueDownlinkLifecycle,
+        EventDownlinkLifecycle, LinkAdvice, LinkFailure, MapDownlinkLifecycle,
+        MapLifecycleHandlers, NodeStopped, StatefulEventDownlinkLifecycle,
+        StatefulMapDownlinkLifecycle, StatefulValueDownlinkLifecycle,
+        StatelessEventDownlinkLifecycle, StatelessMapDownlinkLifecycle,
+        StatelessValueDownlinkLifecycle, ValueDownlinkLifecycle, ValueLifecycleHandlers,
+        WithSharedState,
     };
 }
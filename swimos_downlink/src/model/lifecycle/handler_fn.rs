@@ -35,6 +35,25 @@ where
     }
 }
 
+pub trait SharedHandlerFn1<'a, Shared, A> {
+    type Fut: Future<Output = ()> + Send + 'a;
+
+    fn apply(&'a mut self, shared: &'a mut Shared, arg: A) -> Self::Fut;
+}
+
+impl<'a, Shared, A, F, Fut> SharedHandlerFn1<'a, Shared, A> for F
+where
+    Shared: 'a,
+    F: FnMut(&'a mut Shared, A) -> Fut,
+    Fut: Future<Output = ()> + Send + 'a,
+{
+    type Fut = Fut;
+
+    fn apply(&'a mut self, shared: &'a mut Shared, arg: A) -> Self::Fut {
+        self(shared, arg)
+    }
+}
+
 pub trait EventFn<'a, T: ?Sized> {
     type Fut: Future<Output = ()> + Send + 'a;
 
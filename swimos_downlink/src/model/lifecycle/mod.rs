@@ -15,22 +15,35 @@
 use std::collections::BTreeMap;
 use std::marker::PhantomData;
 
-use crate::model::lifecycle::on_remove::{OnRemove, OnRemoveShared};
+pub use crate::model::lifecycle::on_evict::{OnEvict, OnEvictShared};
+pub use crate::model::lifecycle::on_remove::{OnRemove, OnRemoveShared};
 pub use handler_fn::*;
+pub use on_advisory::{OnAdvisory, OnAdvisoryShared};
 pub use on_clear::{OnClear, OnClearShared};
 pub use on_event::{OnEvent, OnEventShared};
+pub use on_failed::{OnFailed, OnFailedShared};
 pub use on_linked::{OnLinked, OnLinkedShared};
+pub use on_node_stopped::{OnNodeStopped, OnNodeStoppedShared};
+pub use on_reconnecting::{OnReconnecting, OnReconnectingShared};
+pub use on_resynced::{OnResynced, OnResyncedShared};
 pub use on_set::{OnSet, OnSetShared};
 pub use on_synced::{OnSynced, OnSyncedShared};
 pub use on_unlinked::{OnUnlinked, OnUnlinkedShared};
 pub use on_update::{OnUpdate, OnUpdateShared};
+pub use swimos_api::agent::{LinkAdvice, LinkFailure, NodeStopped};
 use swimos_utilities::handlers::{BlockingHandler, FnMutHandler, NoHandler, WithShared};
 
 mod handler_fn;
+mod on_advisory;
 mod on_clear;
 mod on_event;
+mod on_evict;
+mod on_failed;
 mod on_linked;
+mod on_node_stopped;
+mod on_reconnecting;
 mod on_remove;
+mod on_resynced;
 mod on_set;
 mod on_synced;
 mod on_unlinked;
@@ -38,7 +51,18 @@ mod on_update;
 
 /// Description of a lifecycle for a map downlink.
 pub trait MapDownlinkLifecycle<K, V>:
-    OnLinked + OnSynced<BTreeMap<K, V>> + OnUpdate<K, V> + OnRemove<K, V> + OnClear<K, V> + OnUnlinked
+    OnLinked
+    + OnSynced<BTreeMap<K, V>>
+    + OnUpdate<K, V>
+    + OnRemove<K, V>
+    + OnClear<K, V>
+    + OnEvict<K, V>
+    + OnUnlinked
+    + OnReconnecting
+    + OnResynced<BTreeMap<K, V>>
+    + OnAdvisory
+    + OnNodeStopped
+    + OnFailed
 {
 }
 
@@ -48,25 +72,56 @@ impl<K, V, L> MapDownlinkLifecycle<K, V> for L where
         + OnUpdate<K, V>
         + OnRemove<K, V>
         + OnClear<K, V>
+        + OnEvict<K, V>
         + OnUnlinked
+        + OnReconnecting
+        + OnResynced<BTreeMap<K, V>>
+        + OnAdvisory
+        + OnNodeStopped
+        + OnFailed
 {
 }
 
 /// Description of a lifecycle for a value downlink.
 pub trait ValueDownlinkLifecycle<T>:
-    OnLinked + OnSynced<T> + OnEvent<T> + OnSet<T> + OnUnlinked
+    OnLinked
+    + OnSynced<T>
+    + OnEvent<T>
+    + OnSet<T>
+    + OnUnlinked
+    + OnReconnecting
+    + OnResynced<T>
+    + OnAdvisory
+    + OnNodeStopped
+    + OnFailed
 {
 }
 
 /// Description of a lifecycle for an event downlink.
-pub trait EventDownlinkLifecycle<T>: OnLinked + OnEvent<T> + OnUnlinked {}
+pub trait EventDownlinkLifecycle<T>:
+    OnLinked + OnEvent<T> + OnUnlinked + OnReconnecting + OnAdvisory + OnNodeStopped + OnFailed
+{
+}
 
 impl<T, L> ValueDownlinkLifecycle<T> for L where
-    L: OnLinked + OnSynced<T> + OnEvent<T> + OnSet<T> + OnUnlinked
+    L: OnLinked
+        + OnSynced<T>
+        + OnEvent<T>
+        + OnSet<T>
+        + OnUnlinked
+        + OnReconnecting
+        + OnResynced<T>
+        + OnAdvisory
+        + OnNodeStopped
+        + OnFailed
+{
+}
+
+impl<T, L> EventDownlinkLifecycle<T> for L where
+    L: OnLinked + OnEvent<T> + OnUnlinked + OnReconnecting + OnAdvisory + OnNodeStopped + OnFailed
 {
 }
 
-impl<T, L> EventDownlinkLifecycle<T> for L where L: OnLinked + OnEvent<T> + OnUnlinked {}
 /// A basic lifecycle for a value downlink where the event handlers do not share any state.
 pub struct BasicValueDownlinkLifecycle<
     T,
@@ -75,6 +130,11 @@ pub struct BasicValueDownlinkLifecycle<
     FEv = NoHandler,
     FSet = NoHandler,
     FUnlink = NoHandler,
+    FReconnecting = NoHandler,
+    FResynced = NoHandler,
+    FAdvisory = NoHandler,
+    FNodeStopped = NoHandler,
+    FFailed = NoHandler,
 > {
     _value_type: PhantomData<fn(T)>,
     on_linked: FLink,
@@ -82,8 +142,41 @@ pub struct BasicValueDownlinkLifecycle<
     on_event: FEv,
     on_set: FSet,
     on_unlinked: FUnlink,
+    on_reconnecting: FReconnecting,
+    on_advisory: FAdvisory,
+    on_node_stopped: FNodeStopped,
+    on_failed: FFailed,
+    on_resynced: FResynced,
 }
 
+/// A lifecycle for a value downlink where the event handlers do not share state (named for parity
+/// with the lifecycles of downlinks that are hosted by agents).
+pub type StatelessValueDownlinkLifecycle<
+    T,
+    FLink = NoHandler,
+    FSync = NoHandler,
+    FEv = NoHandler,
+    FSet = NoHandler,
+    FUnlink = NoHandler,
+    FReconnecting = NoHandler,
+    FResynced = NoHandler,
+    FAdvisory = NoHandler,
+    FNodeStopped = NoHandler,
+    FFailed = NoHandler,
+> = BasicValueDownlinkLifecycle<
+    T,
+    FLink,
+    FSync,
+    FEv,
+    FSet,
+    FUnlink,
+    FReconnecting,
+    FResynced,
+    FAdvisory,
+    FNodeStopped,
+    FFailed,
+>;
+
 impl<T> Default for BasicValueDownlinkLifecycle<T> {
     fn default() -> Self {
         Self {
@@ -91,6 +184,11 @@ impl<T> Default for BasicValueDownlinkLifecycle<T> {
             on_linked: Default::default(),
             on_event: Default::default(),
             on_unlinked: Default::default(),
+            on_reconnecting: Default::default(),
+            on_advisory: Default::default(),
+            on_node_stopped: Default::default(),
+            on_failed: Default::default(),
+            on_resynced: Default::default(),
             on_set: Default::default(),
             on_synced: Default::default(),
         }
@@ -104,387 +202,5442 @@ impl<T> Default for BasicEventDownlinkLifecycle<T> {
             on_linked: Default::default(),
             on_event: Default::default(),
             on_unlinked: Default::default(),
+            on_reconnecting: Default::default(),
+            on_advisory: Default::default(),
+            on_node_stopped: Default::default(),
+            on_failed: Default::default(),
         }
     }
 }
 
-type WithSharedValueDownlinkLifecycle<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked> =
-    StatefulValueDownlinkLifecycle<
+type WithSharedValueDownlinkLifecycle<
+    T,
+    Shared,
+    FLinked,
+    FSynced,
+    FEv,
+    FSet,
+    FUnlinked,
+    FReconnecting,
+    FResynced,
+    FAdvisory,
+    FNodeStopped,
+    FFailed,
+> = StatefulValueDownlinkLifecycle<
+    T,
+    Shared,
+    WithShared<FLinked>,
+    WithShared<FSynced>,
+    WithShared<FEv>,
+    WithShared<FSet>,
+    WithShared<FUnlinked>,
+    WithShared<FReconnecting>,
+    WithShared<FResynced>,
+    WithShared<FAdvisory>,
+    WithShared<FNodeStopped>,
+    WithShared<FFailed>,
+>;
+
+type WithSharedEventDownlinkLifecycle<
+    T,
+    Shared,
+    FLinked,
+    FEv,
+    FUnlinked,
+    FReconnecting,
+    FAdvisory,
+    FNodeStopped,
+    FFailed,
+> = StatefulEventDownlinkLifecycle<
+    T,
+    Shared,
+    WithShared<FLinked>,
+    WithShared<FEv>,
+    WithShared<FUnlinked>,
+    WithShared<FReconnecting>,
+    WithShared<FAdvisory>,
+    WithShared<FNodeStopped>,
+    WithShared<FFailed>,
+>;
+
+/// The types of the value downlink lifecycles that result from replacing each of their event
+/// handlers with a handler of type `H`.
+pub trait ValueLifecycleHandlers {
+    /// The lifecycle with the `on_linked` handler replaced.
+    type WithOnLinked<H>;
+    /// The lifecycle with the `on_synced` handler replaced.
+    type WithOnSynced<H>;
+    /// The lifecycle with the `on_event` handler replaced.
+    type WithOnEvent<H>;
+    /// The lifecycle with the `on_set` handler replaced.
+    type WithOnSet<H>;
+    /// The lifecycle with the `on_unlinked` handler replaced.
+    type WithOnUnlinked<H>;
+    /// The lifecycle with the `on_reconnecting` handler replaced.
+    type WithOnReconnecting<H>;
+    /// The lifecycle with the `on_resynced` handler replaced.
+    type WithOnResynced<H>;
+    /// The lifecycle with the `on_advisory` handler replaced.
+    type WithOnAdvisory<H>;
+    /// The lifecycle with the `on_node_stopped` handler replaced.
+    type WithOnNodeStopped<H>;
+    /// The lifecycle with the `on_failed` handler replaced.
+    type WithOnFailed<H>;
+}
+
+/// The type of a lifecycle once shared state, of type `Shared`, has been added to its handlers.
+pub trait WithSharedState {
+    type Lifecycle<Shared>;
+}
+
+/// The types of the map downlink lifecycles that result from replacing each of their event
+/// handlers with a handler of type `H`.
+pub trait MapLifecycleHandlers {
+    /// The lifecycle with the `on_linked` handler replaced.
+    type WithOnLinked<H>;
+    /// The lifecycle with the `on_synced` handler replaced.
+    type WithOnSynced<H>;
+    /// The lifecycle with the `on_update` handler replaced.
+    type WithOnUpdate<H>;
+    /// The lifecycle with the `on_removed` handler replaced.
+    type WithOnRemoved<H>;
+    /// The lifecycle with the `on_clear` handler replaced.
+    type WithOnClear<H>;
+    /// The lifecycle with the `on_unlink` handler replaced.
+    type WithOnUnlink<H>;
+    /// The lifecycle with the `on_evicted` handler replaced.
+    type WithOnEvicted<H>;
+    /// The lifecycle with the `on_reconnecting` handler replaced.
+    type WithOnReconnecting<H>;
+    /// The lifecycle with the `on_resynced` handler replaced.
+    type WithOnResynced<H>;
+    /// The lifecycle with the `on_advisory` handler replaced.
+    type WithOnAdvisory<H>;
+    /// The lifecycle with the `on_node_stopped` handler replaced.
+    type WithOnNodeStopped<H>;
+    /// The lifecycle with the `on_failed` handler replaced.
+    type WithOnFailed<H>;
+}
+
+impl<
         T,
-        Shared,
-        WithShared<FLinked>,
-        WithShared<FSynced>,
-        WithShared<FEv>,
-        WithShared<FSet>,
-        WithShared<FUnlinked>,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > ValueLifecycleHandlers
+    for BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+{
+    type WithOnLinked<H> = BasicValueDownlinkLifecycle<
+        T,
+        H,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnSynced<H> = BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        H,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnEvent<H> = BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        H,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnSet<H> = BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        H,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnUnlinked<H> = BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        H,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnReconnecting<H> = BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        H,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnResynced<H> = BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        H,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnAdvisory<H> = BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        H,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnNodeStopped<H> = BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        H,
+        FFailed,
+    >;
+    type WithOnFailed<H> = BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        H,
     >;
+}
 
-type WithSharedEventDownlinkLifecycle<T, Shared, FLinked, FEv, FUnlinked> =
-    StatefulEventDownlinkLifecycle<
+impl<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > ValueLifecycleHandlers
+    for StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+{
+    type WithOnLinked<H> = StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        H,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnSynced<H> = StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        H,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnEvent<H> = StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        H,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnSet<H> = StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        H,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnUnlinked<H> = StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        H,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnReconnecting<H> = StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        H,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnResynced<H> = StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        H,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnAdvisory<H> = StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        H,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnNodeStopped<H> = StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        H,
+        FFailed,
+    >;
+    type WithOnFailed<H> = StatefulValueDownlinkLifecycle<
         T,
         Shared,
-        WithShared<FLinked>,
-        WithShared<FEv>,
-        WithShared<FUnlinked>,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        H,
     >;
+}
 
-impl<T, FLinked, FSynced, FEv, FSet, FUnlinked>
-    BasicValueDownlinkLifecycle<T, FLinked, FSynced, FEv, FSet, FUnlinked>
-where
-    T: Send + Sync + 'static,
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > MapLifecycleHandlers
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
 {
-    /// Replace the handler that is called when the downlink connects.
-    pub fn on_linked<F>(
-        self,
-        f: F,
-    ) -> BasicValueDownlinkLifecycle<T, FnMutHandler<F>, FSynced, FEv, FSet, FUnlinked>
-    where
-        FnMutHandler<F>: OnLinked,
-    {
-        BasicValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            on_linked: FnMutHandler(f),
-            on_synced: self.on_synced,
-            on_event: self.on_event,
-            on_set: self.on_set,
-            on_unlinked: self.on_unlinked,
-        }
-    }
-
-    /// Replace the handler that is called when the downlink connects with the specified synchronous
-    /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_linked_blocking<F>(
-        self,
-        f: F,
-    ) -> BasicValueDownlinkLifecycle<T, BlockingHandler<F>, FSynced, FEv, FSet, FUnlinked>
-    where
-        F: FnMut() + Send,
-    {
-        BasicValueDownlinkLifecycle {
+    type WithOnLinked<H> = BasicMapDownlinkLifecycle<
+        K,
+        V,
+        H,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnSynced<H> = BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        H,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnUpdate<H> = BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        H,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnRemoved<H> = BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        H,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnClear<H> = BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        H,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnUnlink<H> = BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        H,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnEvicted<H> = BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        H,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnReconnecting<H> = BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        H,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnResynced<H> = BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        H,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnAdvisory<H> = BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        H,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnNodeStopped<H> = BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        H,
+        FFailed,
+    >;
+    type WithOnFailed<H> = BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        H,
+    >;
+}
+
+impl<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > MapLifecycleHandlers
+    for StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+{
+    type WithOnLinked<H> = StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        H,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnSynced<H> = StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        H,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnUpdate<H> = StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        H,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnRemoved<H> = StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        H,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnClear<H> = StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        H,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnUnlink<H> = StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        H,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnEvicted<H> = StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        H,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnReconnecting<H> = StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        H,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnResynced<H> = StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        H,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnAdvisory<H> = StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        H,
+        FNodeStopped,
+        FFailed,
+    >;
+    type WithOnNodeStopped<H> = StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        H,
+        FFailed,
+    >;
+    type WithOnFailed<H> = StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        H,
+    >;
+}
+
+impl<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+{
+    /// Replace the handler that is called when the downlink connects.
+    pub fn on_linked<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnLinked<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnLinked,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: FnMutHandler(f),
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink connects with the specified synchronous
+    /// closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_linked_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnLinked<BlockingHandler<F>>
+    where
+        F: FnMut() + Send,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: BlockingHandler(f),
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink synchronizes.
+    pub fn on_synced<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnSynced<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: for<'a> OnSynced<T>,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: FnMutHandler(f),
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink synchronizes with the specified synchronous
+    /// closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_synced_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnSynced<BlockingHandler<F>>
+    where
+        F: FnMut(&T) + Send,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: BlockingHandler(f),
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink receives an event.
+    pub fn on_event<F>(self, f: F) -> <Self as ValueLifecycleHandlers>::WithOnEvent<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnEvent<T>,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: FnMutHandler(f),
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink receives and event with the specified
+    /// synchronous closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_event_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnEvent<BlockingHandler<F>>
+    where
+        F: FnMut(&T) + Send,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: BlockingHandler(f),
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink value changes.
+    pub fn on_set<F>(self, f: F) -> <Self as ValueLifecycleHandlers>::WithOnSet<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnSet<T>,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: FnMutHandler(f),
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink value changes with the specified synchronous
+    /// closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_set_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnSet<BlockingHandler<F>>
+    where
+        F: FnMut(Option<&T>, &T) + Send,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: BlockingHandler(f),
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink disconnects.
+    pub fn on_unlinked<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnUnlinked<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnUnlinked,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: FnMutHandler(f),
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink disconnects with the specified synchronous
+    /// closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_unlinked_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnUnlinked<BlockingHandler<F>>
+    where
+        F: FnMut() + Send,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: BlockingHandler(f),
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called before the downlink attempts to relink.
+    pub fn on_reconnecting<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnReconnecting<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnReconnecting,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: FnMutHandler(f),
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called before the downlink attempts to relink with the specified
+    /// synchronous closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_reconnecting_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnReconnecting<BlockingHandler<F>>
+    where
+        F: FnMut() + Send,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: BlockingHandler(f),
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the remote lane advises the downlink to back off.
+    pub fn on_advisory<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnAdvisory<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnAdvisory,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: FnMutHandler(f),
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the remote lane advises the downlink to back off with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_advisory_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnAdvisory<BlockingHandler<F>>
+    where
+        F: FnMut(LinkAdvice) + Send,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: BlockingHandler(f),
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the remote agent stops and unlinks the downlink.
+    pub fn on_node_stopped<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnNodeStopped<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnNodeStopped,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: FnMutHandler(f),
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the remote agent stops and unlinks the downlink with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_node_stopped_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnNodeStopped<BlockingHandler<F>>
+    where
+        F: FnMut(NodeStopped) + Send,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: BlockingHandler(f),
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the remote refuses to link the downlink because its
+    /// node or lane does not exist.
+    pub fn on_failed<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnFailed<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnFailed,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: FnMutHandler(f),
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the remote refuses to link the downlink because its
+    /// node or lane does not exist with the specified synchronous closure. Running this closure
+    /// will block the task so it should complete quickly.
+    pub fn on_failed_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnFailed<BlockingHandler<F>>
+    where
+        F: FnMut(LinkFailure) + Send,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: BlockingHandler(f),
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink synchronizes again after relinking.
+    pub fn on_resynced<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnResynced<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: for<'a> OnResynced<T>,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: FnMutHandler(f),
+        }
+    }
+
+    /// Replace the handler that is called when the downlink synchronizes again after relinking with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_resynced_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnResynced<BlockingHandler<F>>
+    where
+        F: FnMut(&T) + Send,
+    {
+        BasicValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: BlockingHandler(f),
+        }
+    }
+
+    /// Adds shared state this is accessible to all handlers for this downlink.
+    pub fn with<Shared>(
+        self,
+        shared_state: Shared,
+    ) -> WithSharedValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: shared_state,
+            on_linked: WithShared::new(self.on_linked),
+            on_synced: WithShared::new(self.on_synced),
+            on_event: WithShared::new(self.on_event),
+            on_set: WithShared::new(self.on_set),
+            on_unlinked: WithShared::new(self.on_unlinked),
+            on_reconnecting: WithShared::new(self.on_reconnecting),
+            on_advisory: WithShared::new(self.on_advisory),
+            on_node_stopped: WithShared::new(self.on_node_stopped),
+            on_failed: WithShared::new(self.on_failed),
+            on_resynced: WithShared::new(self.on_resynced),
+        }
+    }
+
+    /// Adds shared state this is accessible to all handlers for this downlink (equivalent to
+    /// [`BasicValueDownlinkLifecycle::with`]).
+    pub fn with_shared_state<Shared>(
+        self,
+        shared_state: Shared,
+    ) -> WithSharedValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > {
+        self.with(shared_state)
+    }
+}
+
+impl<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnLinked
+    for BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: OnLinked,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnLinkedFut<'a>
+        = FLinked::OnLinkedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_linked(&mut self) -> Self::OnLinkedFut<'_> {
+        self.on_linked.on_linked()
+    }
+}
+
+impl<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnSynced<T>
+    for BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: OnSynced<T>,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnSyncedFut<'a>
+        = FSynced::OnSyncedFut<'a>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn on_synced<'a>(&'a mut self, value: &'a T) -> Self::OnSyncedFut<'a> {
+        self.on_synced.on_synced(value)
+    }
+}
+
+impl<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnEvent<T>
+    for BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: OnEvent<T>,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnEventFut<'a>
+        = FEv::OnEventFut<'a>
+    where
+        Self: 'a;
+
+    fn on_event<'a>(&'a mut self, value: &'a T) -> Self::OnEventFut<'a> {
+        self.on_event.on_event(value)
+    }
+}
+
+impl<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnSet<T>
+    for BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: OnSet<T>,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnSetFut<'a>
+        = FSet::OnSetFut<'a>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn on_set<'a>(&'a mut self, existing: Option<&'a T>, new_value: &'a T) -> Self::OnSetFut<'a> {
+        self.on_set.on_set(existing, new_value)
+    }
+}
+
+impl<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnUnlinked
+    for BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: OnUnlinked,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnUnlinkedFut<'a>
+        = FUnlinked::OnUnlinkedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_unlinked(&mut self) -> Self::OnUnlinkedFut<'_> {
+        self.on_unlinked.on_unlinked()
+    }
+}
+
+impl<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnReconnecting
+    for BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: OnReconnecting,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnReconnectingFut<'a>
+        = FReconnecting::OnReconnectingFut<'a>
+    where
+        Self: 'a;
+
+    fn on_reconnecting(&mut self) -> Self::OnReconnectingFut<'_> {
+        self.on_reconnecting.on_reconnecting()
+    }
+}
+
+impl<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnAdvisory
+    for BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: OnAdvisory,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnAdvisoryFut<'a>
+        = FAdvisory::OnAdvisoryFut<'a>
+    where
+        Self: 'a;
+
+    fn on_advisory(&mut self, advice: LinkAdvice) -> Self::OnAdvisoryFut<'_> {
+        self.on_advisory.on_advisory(advice)
+    }
+}
+
+impl<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnNodeStopped
+    for BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: OnNodeStopped,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnNodeStoppedFut<'a>
+        = FNodeStopped::OnNodeStoppedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_node_stopped(&mut self, notice: NodeStopped) -> Self::OnNodeStoppedFut<'_> {
+        self.on_node_stopped.on_node_stopped(notice)
+    }
+}
+
+impl<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnFailed
+    for BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: OnFailed,
+    FResynced: Send,
+{
+    type OnFailedFut<'a>
+        = FFailed::OnFailedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_failed(&mut self, failure: LinkFailure) -> Self::OnFailedFut<'_> {
+        self.on_failed.on_failed(failure)
+    }
+}
+
+impl<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnResynced<T>
+    for BasicValueDownlinkLifecycle<
+        T,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: OnResynced<T>,
+{
+    type OnResyncedFut<'a>
+        = FResynced::OnResyncedFut<'a>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn on_resynced<'a>(&'a mut self, value: &'a T) -> Self::OnResyncedFut<'a> {
+        self.on_resynced.on_resynced(value)
+    }
+}
+
+/// A lifecycle for a value downlink where the handlers for each event share state.
+pub struct StatefulValueDownlinkLifecycle<
+    T,
+    Shared,
+    FLink = NoHandler,
+    FSync = NoHandler,
+    FEv = NoHandler,
+    FSet = NoHandler,
+    FUnlink = NoHandler,
+    FReconnecting = NoHandler,
+    FResynced = NoHandler,
+    FAdvisory = NoHandler,
+    FNodeStopped = NoHandler,
+    FFailed = NoHandler,
+> {
+    _value_type: PhantomData<fn(T)>,
+    shared: Shared,
+    on_linked: FLink,
+    on_synced: FSync,
+    on_event: FEv,
+    on_set: FSet,
+    on_unlinked: FUnlink,
+    on_reconnecting: FReconnecting,
+    on_advisory: FAdvisory,
+    on_node_stopped: FNodeStopped,
+    on_failed: FFailed,
+    on_resynced: FResynced,
+}
+
+impl<T, Shared> StatefulEventDownlinkLifecycle<T, Shared> {
+    pub fn new(shared: Shared) -> Self {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared,
+            on_linked: Default::default(),
+            on_event: Default::default(),
+            on_unlinked: Default::default(),
+            on_reconnecting: Default::default(),
+            on_advisory: Default::default(),
+            on_node_stopped: Default::default(),
+            on_failed: Default::default(),
+        }
+    }
+}
+
+impl<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+{
+    /// Replace the handler that is called when the downlink connects.
+    pub fn on_linked<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnLinked<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnLinkedShared<Shared>,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: FnMutHandler(f),
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink connects with the specified synchronous
+    /// closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_linked_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnLinked<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared) + Send,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: BlockingHandler(f),
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink synchronizes.
+    pub fn on_synced<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnSynced<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnSyncedShared<T, Shared>,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: FnMutHandler(f),
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink synchronizes with the specified synchronous
+    /// closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_synced_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnSynced<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared, &T),
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: BlockingHandler(f),
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink receives a new event.
+    pub fn on_event<F>(self, f: F) -> <Self as ValueLifecycleHandlers>::WithOnEvent<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnEventShared<T, Shared>,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: FnMutHandler(f),
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink receives a new event with the specified
+    /// synchronous closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_event_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnEvent<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared, &T),
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: BlockingHandler(f),
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink value changes.
+    pub fn on_set<F>(self, f: F) -> <Self as ValueLifecycleHandlers>::WithOnSet<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnSetShared<T, Shared>,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: FnMutHandler(f),
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink value changes with the specified synchronous
+    /// closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_set_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnSet<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared, Option<&T>, &T),
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: BlockingHandler(f),
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink disconnects.
+    pub fn on_unlinked<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnUnlinked<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnUnlinkedShared<Shared>,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: FnMutHandler(f),
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink disconnects with the specified synchronous
+    /// closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_unlinked_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnUnlinked<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared),
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: BlockingHandler(f),
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called before the downlink attempts to relink.
+    pub fn on_reconnecting<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnReconnecting<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnReconnectingShared<Shared>,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: FnMutHandler(f),
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called before the downlink attempts to relink with the specified
+    /// synchronous closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_reconnecting_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnReconnecting<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared) + Send,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: BlockingHandler(f),
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the remote lane advises the downlink to back off.
+    pub fn on_advisory<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnAdvisory<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnAdvisoryShared<Shared>,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: FnMutHandler(f),
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the remote lane advises the downlink to back off with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_advisory_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnAdvisory<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared, LinkAdvice) + Send,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: BlockingHandler(f),
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the remote agent stops and unlinks the downlink.
+    pub fn on_node_stopped<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnNodeStopped<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnNodeStoppedShared<Shared>,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: FnMutHandler(f),
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the remote agent stops and unlinks the downlink with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_node_stopped_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnNodeStopped<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared, NodeStopped) + Send,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: BlockingHandler(f),
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the remote refuses to link the downlink because its
+    /// node or lane does not exist.
+    pub fn on_failed<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnFailed<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnFailedShared<Shared>,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: FnMutHandler(f),
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the remote refuses to link the downlink because its
+    /// node or lane does not exist with the specified synchronous closure. Running this closure
+    /// will block the task so it should complete quickly.
+    pub fn on_failed_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnFailed<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared, LinkFailure) + Send,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: BlockingHandler(f),
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink synchronizes again after relinking.
+    pub fn on_resynced<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnResynced<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnResyncedShared<T, Shared>,
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: FnMutHandler(f),
+        }
+    }
+
+    /// Replace the handler that is called when the downlink synchronizes again after relinking with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_resynced_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as ValueLifecycleHandlers>::WithOnResynced<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared, &T),
+    {
+        StatefulValueDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_event: self.on_event,
+            on_set: self.on_set,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: BlockingHandler(f),
+        }
+    }
+}
+
+impl<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnLinked
+    for StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: OnLinkedShared<Shared>,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnLinkedFut<'a>
+        = FLinked::OnLinkedFut<'a>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_linked(&mut self) -> Self::OnLinkedFut<'_> {
+        let StatefulValueDownlinkLifecycle {
+            shared, on_linked, ..
+        } = self;
+        on_linked.on_linked(shared)
+    }
+}
+
+impl<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnSynced<T>
+    for StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: OnSyncedShared<T, Shared>,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnSyncedFut<'a>
+        = FSynced::OnSyncedFut<'a>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn on_synced<'a>(&'a mut self, value: &'a T) -> Self::OnSyncedFut<'a> {
+        let StatefulValueDownlinkLifecycle {
+            shared, on_synced, ..
+        } = self;
+        on_synced.on_synced(shared, value)
+    }
+}
+
+impl<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnEvent<T>
+    for StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: OnEventShared<T, Shared>,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnEventFut<'a>
+        = FEv::OnEventFut<'a>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_event<'a>(&'a mut self, value: &'a T) -> Self::OnEventFut<'a> {
+        let StatefulValueDownlinkLifecycle {
+            shared, on_event, ..
+        } = self;
+        on_event.on_event(shared, value)
+    }
+}
+
+impl<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnSet<T>
+    for StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: OnSetShared<T, Shared>,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnSetFut<'a>
+        = FSet::OnSetFut<'a>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn on_set<'a>(&'a mut self, existing: Option<&'a T>, new_value: &'a T) -> Self::OnSetFut<'a> {
+        let StatefulValueDownlinkLifecycle { shared, on_set, .. } = self;
+        on_set.on_set(shared, existing, new_value)
+    }
+}
+
+impl<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnUnlinked
+    for StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: OnUnlinkedShared<Shared>,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnUnlinkedFut<'a>
+        = FUnlinked::OnUnlinkedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_unlinked(&mut self) -> Self::OnUnlinkedFut<'_> {
+        let StatefulValueDownlinkLifecycle {
+            shared,
+            on_unlinked,
+            ..
+        } = self;
+        on_unlinked.on_unlinked(shared)
+    }
+}
+
+impl<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnReconnecting
+    for StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: OnReconnectingShared<Shared>,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnReconnectingFut<'a>
+        = FReconnecting::OnReconnectingFut<'a>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_reconnecting(&mut self) -> Self::OnReconnectingFut<'_> {
+        let StatefulValueDownlinkLifecycle {
+            shared,
+            on_reconnecting,
+            ..
+        } = self;
+        on_reconnecting.on_reconnecting(shared)
+    }
+}
+
+impl<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnAdvisory
+    for StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: OnAdvisoryShared<Shared>,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnAdvisoryFut<'a>
+        = FAdvisory::OnAdvisoryFut<'a>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_advisory(&mut self, advice: LinkAdvice) -> Self::OnAdvisoryFut<'_> {
+        let StatefulValueDownlinkLifecycle {
+            shared,
+            on_advisory,
+            ..
+        } = self;
+        on_advisory.on_advisory(shared, advice)
+    }
+}
+
+impl<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnNodeStopped
+    for StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: OnNodeStoppedShared<Shared>,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnNodeStoppedFut<'a>
+        = FNodeStopped::OnNodeStoppedFut<'a>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_node_stopped(&mut self, notice: NodeStopped) -> Self::OnNodeStoppedFut<'_> {
+        let StatefulValueDownlinkLifecycle {
+            shared,
+            on_node_stopped,
+            ..
+        } = self;
+        on_node_stopped.on_node_stopped(shared, notice)
+    }
+}
+
+impl<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnFailed
+    for StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: OnFailedShared<Shared>,
+    FResynced: Send,
+{
+    type OnFailedFut<'a>
+        = FFailed::OnFailedFut<'a>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_failed(&mut self, failure: LinkFailure) -> Self::OnFailedFut<'_> {
+        let StatefulValueDownlinkLifecycle {
+            shared, on_failed, ..
+        } = self;
+        on_failed.on_failed(shared, failure)
+    }
+}
+
+impl<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnResynced<T>
+    for StatefulValueDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FSynced,
+        FEv,
+        FSet,
+        FUnlinked,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FEv: Send,
+    FSet: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: OnResyncedShared<T, Shared>,
+{
+    type OnResyncedFut<'a>
+        = FResynced::OnResyncedFut<'a>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn on_resynced<'a>(&'a mut self, value: &'a T) -> Self::OnResyncedFut<'a> {
+        let StatefulValueDownlinkLifecycle {
+            shared,
+            on_resynced,
+            ..
+        } = self;
+        on_resynced.on_resynced(shared, value)
+    }
+}
+
+/// A basic lifecycle for an event downlink where the event handlers do not share any state.
+pub struct BasicEventDownlinkLifecycle<
+    T,
+    FLink = NoHandler,
+    FEv = NoHandler,
+    FUnlink = NoHandler,
+    FReconnecting = NoHandler,
+    FAdvisory = NoHandler,
+    FNodeStopped = NoHandler,
+    FFailed = NoHandler,
+> {
+    _value_type: PhantomData<fn(T)>,
+    on_linked: FLink,
+    on_event: FEv,
+    on_unlinked: FUnlink,
+    on_reconnecting: FReconnecting,
+    on_advisory: FAdvisory,
+    on_node_stopped: FNodeStopped,
+    on_failed: FFailed,
+}
+
+/// A lifecycle for an event downlink where the event handlers do not share state (named for parity
+/// with the lifecycles of downlinks that are hosted by agents).
+pub type StatelessEventDownlinkLifecycle<
+    T,
+    FLink = NoHandler,
+    FEv = NoHandler,
+    FUnlink = NoHandler,
+    FReconnecting = NoHandler,
+    FAdvisory = NoHandler,
+    FNodeStopped = NoHandler,
+    FFailed = NoHandler,
+> = BasicEventDownlinkLifecycle<
+    T,
+    FLink,
+    FEv,
+    FUnlink,
+    FReconnecting,
+    FAdvisory,
+    FNodeStopped,
+    FFailed,
+>;
+
+/// A lifecycle for an event downlink where the handlers for each event share state.
+pub struct StatefulEventDownlinkLifecycle<
+    T,
+    Shared,
+    FLink = NoHandler,
+    FEv = NoHandler,
+    FUnlink = NoHandler,
+    FReconnecting = NoHandler,
+    FAdvisory = NoHandler,
+    FNodeStopped = NoHandler,
+    FFailed = NoHandler,
+> {
+    _value_type: PhantomData<fn(T)>,
+    shared: Shared,
+    on_linked: FLink,
+    on_event: FEv,
+    on_unlinked: FUnlink,
+    on_reconnecting: FReconnecting,
+    on_advisory: FAdvisory,
+    on_node_stopped: FNodeStopped,
+    on_failed: FFailed,
+}
+
+impl<T, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed>
+    BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+{
+    /// Replace the handler that is called when the downlink connects.
+    pub fn on_linked<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        FnMutHandler<F>,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        FnMutHandler<F>: OnLinked,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: FnMutHandler(f),
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink connects with the specified synchronous
+    /// closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_linked_blocking<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        BlockingHandler<F>,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        F: FnMut() + Send,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: BlockingHandler(f),
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink receives a new event.
+    pub fn on_event<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FnMutHandler<F>,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        FnMutHandler<F>: OnEvent<T>,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_event: FnMutHandler(f),
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink receives a new event with the specified
+    /// synchronous closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_event_blocking<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        BlockingHandler<F>,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        F: FnMut(&T) + Send,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_event: BlockingHandler(f),
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink disconnects.
+    pub fn on_unlinked<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FnMutHandler<F>,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        FnMutHandler<F>: OnUnlinked,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: FnMutHandler(f),
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink disconnects with the specified synchronous
+    /// closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_unlinked_blocking<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        BlockingHandler<F>,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        F: FnMut() + Send,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: BlockingHandler(f),
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called before the downlink attempts to relink.
+    pub fn on_reconnecting<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FnMutHandler<F>,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        FnMutHandler<F>: OnReconnecting,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: FnMutHandler(f),
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called before the downlink attempts to relink with the specified
+    /// synchronous closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_reconnecting_blocking<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        BlockingHandler<F>,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        F: FnMut() + Send,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: BlockingHandler(f),
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the remote lane advises the downlink to back off.
+    pub fn on_advisory<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FnMutHandler<F>,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        FnMutHandler<F>: OnAdvisory,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: FnMutHandler(f),
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the remote lane advises the downlink to back off with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_advisory_blocking<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        BlockingHandler<F>,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        F: FnMut(LinkAdvice) + Send,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: BlockingHandler(f),
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the remote agent stops and unlinks the downlink.
+    pub fn on_node_stopped<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FnMutHandler<F>,
+        FFailed,
+    >
+    where
+        FnMutHandler<F>: OnNodeStopped,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: FnMutHandler(f),
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the remote agent stops and unlinks the downlink with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_node_stopped_blocking<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        BlockingHandler<F>,
+        FFailed,
+    >
+    where
+        F: FnMut(NodeStopped) + Send,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: BlockingHandler(f),
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the remote refuses to link the downlink because its
+    /// node or lane does not exist.
+    pub fn on_failed<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FnMutHandler<F>,
+    >
+    where
+        FnMutHandler<F>: OnFailed,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: FnMutHandler(f),
+        }
+    }
+
+    /// Replace the handler that is called when the remote refuses to link the downlink because its
+    /// node or lane does not exist with the specified synchronous closure. Running this closure
+    /// will block the task so it should complete quickly.
+    pub fn on_failed_blocking<F>(
+        self,
+        f: F,
+    ) -> BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        BlockingHandler<F>,
+    >
+    where
+        F: FnMut(LinkFailure) + Send,
+    {
+        BasicEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: BlockingHandler(f),
+        }
+    }
+
+    /// Adds shared state this is accessible to all handlers for this downlink.
+    pub fn with<Shared>(
+        self,
+        shared_state: Shared,
+    ) -> WithSharedEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: shared_state,
+            on_linked: WithShared::new(self.on_linked),
+            on_event: WithShared::new(self.on_event),
+            on_unlinked: WithShared::new(self.on_unlinked),
+            on_reconnecting: WithShared::new(self.on_reconnecting),
+            on_advisory: WithShared::new(self.on_advisory),
+            on_node_stopped: WithShared::new(self.on_node_stopped),
+            on_failed: WithShared::new(self.on_failed),
+        }
+    }
+
+    /// Adds shared state this is accessible to all handlers for this downlink (equivalent to
+    /// [`BasicEventDownlinkLifecycle::with`]).
+    pub fn with_shared_state<Shared>(
+        self,
+        shared_state: Shared,
+    ) -> WithSharedEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > {
+        self.with(shared_state)
+    }
+}
+
+impl<T, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed> OnLinked
+    for BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: OnLinked,
+    FEv: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+{
+    type OnLinkedFut<'a>
+        = FLinked::OnLinkedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_linked(&mut self) -> Self::OnLinkedFut<'_> {
+        self.on_linked.on_linked()
+    }
+}
+
+impl<T, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed> OnEvent<T>
+    for BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FEv: OnEvent<T>,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+{
+    type OnEventFut<'a>
+        = FEv::OnEventFut<'a>
+    where
+        Self: 'a;
+
+    fn on_event<'a>(&'a mut self, value: &'a T) -> Self::OnEventFut<'a> {
+        self.on_event.on_event(value)
+    }
+}
+
+impl<T, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed> OnUnlinked
+    for BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FEv: Send,
+    FUnlinked: OnUnlinked,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+{
+    type OnUnlinkedFut<'a>
+        = FUnlinked::OnUnlinkedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_unlinked(&mut self) -> Self::OnUnlinkedFut<'_> {
+        self.on_unlinked.on_unlinked()
+    }
+}
+
+impl<T, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed> OnReconnecting
+    for BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FEv: Send,
+    FUnlinked: Send,
+    FReconnecting: OnReconnecting,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+{
+    type OnReconnectingFut<'a>
+        = FReconnecting::OnReconnectingFut<'a>
+    where
+        Self: 'a;
+
+    fn on_reconnecting(&mut self) -> Self::OnReconnectingFut<'_> {
+        self.on_reconnecting.on_reconnecting()
+    }
+}
+
+impl<T, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed> OnAdvisory
+    for BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FEv: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: OnAdvisory,
+    FNodeStopped: Send,
+    FFailed: Send,
+{
+    type OnAdvisoryFut<'a>
+        = FAdvisory::OnAdvisoryFut<'a>
+    where
+        Self: 'a;
+
+    fn on_advisory(&mut self, advice: LinkAdvice) -> Self::OnAdvisoryFut<'_> {
+        self.on_advisory.on_advisory(advice)
+    }
+}
+
+impl<T, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed> OnNodeStopped
+    for BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FEv: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: OnNodeStopped,
+    FFailed: Send,
+{
+    type OnNodeStoppedFut<'a>
+        = FNodeStopped::OnNodeStoppedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_node_stopped(&mut self, notice: NodeStopped) -> Self::OnNodeStoppedFut<'_> {
+        self.on_node_stopped.on_node_stopped(notice)
+    }
+}
+
+impl<T, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed> OnFailed
+    for BasicEventDownlinkLifecycle<
+        T,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    FLinked: Send,
+    FEv: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: OnFailed,
+{
+    type OnFailedFut<'a>
+        = FFailed::OnFailedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_failed(&mut self, failure: LinkFailure) -> Self::OnFailedFut<'_> {
+        self.on_failed.on_failed(failure)
+    }
+}
+
+impl<T, Shared, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed>
+    StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+{
+    /// Replace the handler that is called when the downlink connects.
+    pub fn on_linked<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FnMutHandler<F>,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        FnMutHandler<F>: OnLinkedShared<Shared>,
+    {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: FnMutHandler(f),
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink connects. Running this closure
+    /// will block the task so it should complete quickly.
+    pub fn on_linked_blocking<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        BlockingHandler<F>,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        F: FnMut(&mut Shared) + Send,
+    {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: BlockingHandler(f),
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink receives a new event.
+    pub fn on_event<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FnMutHandler<F>,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        FnMutHandler<F>: OnEventShared<T, Shared>,
+    {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_event: FnMutHandler(f),
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+    /// Replace the handler that is called when the downlink receives a new event. Running this closure
+    /// will block the task so it should complete quickly.
+    pub fn on_event_blocking<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        BlockingHandler<F>,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        F: FnMut(&mut Shared, &T),
+    {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_event: BlockingHandler(f),
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink disconnects.
+    pub fn on_unlinked<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FnMutHandler<F>,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        FnMutHandler<F>: OnUnlinkedShared<Shared>,
+    {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: FnMutHandler(f),
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink disconnects. Running this closure
+    /// will block the task so it should complete quickly.
+    pub fn on_unlinked_blocking<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        BlockingHandler<F>,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        F: FnMut(&mut Shared),
+    {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: BlockingHandler(f),
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called before the downlink attempts to relink.
+    pub fn on_reconnecting<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FnMutHandler<F>,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        FnMutHandler<F>: OnReconnectingShared<Shared>,
+    {
+        StatefulEventDownlinkLifecycle {
             _value_type: PhantomData,
-            on_linked: BlockingHandler(f),
-            on_synced: self.on_synced,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: FnMutHandler(f),
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called before the downlink attempts to relink with the specified
+    /// synchronous closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_reconnecting_blocking<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        BlockingHandler<F>,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        F: FnMut(&mut Shared) + Send,
+    {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: BlockingHandler(f),
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the remote lane advises the downlink to back off.
+    pub fn on_advisory<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FnMutHandler<F>,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        FnMutHandler<F>: OnAdvisoryShared<Shared>,
+    {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: FnMutHandler(f),
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the remote lane advises the downlink to back off with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_advisory_blocking<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        BlockingHandler<F>,
+        FNodeStopped,
+        FFailed,
+    >
+    where
+        F: FnMut(&mut Shared, LinkAdvice) + Send,
+    {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: BlockingHandler(f),
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the remote agent stops and unlinks the downlink.
+    pub fn on_node_stopped<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FnMutHandler<F>,
+        FFailed,
+    >
+    where
+        FnMutHandler<F>: OnNodeStoppedShared<Shared>,
+    {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: FnMutHandler(f),
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the remote agent stops and unlinks the downlink with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_node_stopped_blocking<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        BlockingHandler<F>,
+        FFailed,
+    >
+    where
+        F: FnMut(&mut Shared, NodeStopped) + Send,
+    {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: BlockingHandler(f),
+            on_failed: self.on_failed,
+        }
+    }
+
+    /// Replace the handler that is called when the remote refuses to link the downlink because its
+    /// node or lane does not exist.
+    pub fn on_failed<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FnMutHandler<F>,
+    >
+    where
+        FnMutHandler<F>: OnFailedShared<Shared>,
+    {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
+            on_event: self.on_event,
+            on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: FnMutHandler(f),
+        }
+    }
+
+    /// Replace the handler that is called when the remote refuses to link the downlink because its
+    /// node or lane does not exist with the specified synchronous closure. Running this closure
+    /// will block the task so it should complete quickly.
+    pub fn on_failed_blocking<F>(
+        self,
+        f: F,
+    ) -> StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        BlockingHandler<F>,
+    >
+    where
+        F: FnMut(&mut Shared, LinkFailure) + Send,
+    {
+        StatefulEventDownlinkLifecycle {
+            _value_type: PhantomData,
+            shared: self.shared,
+            on_linked: self.on_linked,
             on_event: self.on_event,
-            on_set: self.on_set,
             on_unlinked: self.on_unlinked,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: BlockingHandler(f),
         }
     }
+}
+
+impl<T, Shared, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed> OnLinked
+    for StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: OnLinkedShared<Shared>,
+    FEv: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+{
+    type OnLinkedFut<'a>
+        = FLinked::OnLinkedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_linked(&mut self) -> Self::OnLinkedFut<'_> {
+        let StatefulEventDownlinkLifecycle {
+            shared, on_linked, ..
+        } = self;
+        on_linked.on_linked(shared)
+    }
+}
+
+impl<T, Shared, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed> OnEvent<T>
+    for StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync,
+    FLinked: Send,
+    FEv: OnEventShared<T, Shared>,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+{
+    type OnEventFut<'a>
+        = FEv::OnEventFut<'a>
+    where
+        Self: 'a;
+
+    fn on_event<'a>(&'a mut self, value: &'a T) -> Self::OnEventFut<'a> {
+        let StatefulEventDownlinkLifecycle {
+            shared, on_event, ..
+        } = self;
+        on_event.on_event(shared, value)
+    }
+}
+
+impl<T, Shared, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed> OnUnlinked
+    for StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: Send,
+    FEv: Send,
+    FUnlinked: OnUnlinkedShared<Shared>,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+{
+    type OnUnlinkedFut<'a>
+        = FUnlinked::OnUnlinkedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_unlinked(&mut self) -> Self::OnUnlinkedFut<'_> {
+        let StatefulEventDownlinkLifecycle {
+            shared,
+            on_unlinked,
+            ..
+        } = self;
+        on_unlinked.on_unlinked(shared)
+    }
+}
+
+impl<T, Shared, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed>
+    OnReconnecting
+    for StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: Send,
+    FEv: Send,
+    FUnlinked: Send,
+    FReconnecting: OnReconnectingShared<Shared>,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+{
+    type OnReconnectingFut<'a>
+        = FReconnecting::OnReconnectingFut<'a>
+    where
+        Self: 'a;
+
+    fn on_reconnecting(&mut self) -> Self::OnReconnectingFut<'_> {
+        let StatefulEventDownlinkLifecycle {
+            shared,
+            on_reconnecting,
+            ..
+        } = self;
+        on_reconnecting.on_reconnecting(shared)
+    }
+}
 
-    /// Replace the handler that is called when the downlink synchronizes.
-    pub fn on_synced<F>(
-        self,
-        f: F,
-    ) -> BasicValueDownlinkLifecycle<T, FLinked, FnMutHandler<F>, FEv, FSet, FUnlinked>
+impl<T, Shared, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed> OnAdvisory
+    for StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: Send,
+    FEv: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: OnAdvisoryShared<Shared>,
+    FNodeStopped: Send,
+    FFailed: Send,
+{
+    type OnAdvisoryFut<'a>
+        = FAdvisory::OnAdvisoryFut<'a>
     where
-        FnMutHandler<F>: for<'a> OnSynced<T>,
-    {
-        BasicValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: FnMutHandler(f),
-            on_event: self.on_event,
-            on_set: self.on_set,
-            on_unlinked: self.on_unlinked,
-        }
+        Self: 'a;
+
+    fn on_advisory(&mut self, advice: LinkAdvice) -> Self::OnAdvisoryFut<'_> {
+        let StatefulEventDownlinkLifecycle {
+            shared,
+            on_advisory,
+            ..
+        } = self;
+        on_advisory.on_advisory(shared, advice)
     }
+}
 
-    /// Replace the handler that is called when the downlink synchronizes with the specified synchronous
-    /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_synced_blocking<F>(
-        self,
-        f: F,
-    ) -> BasicValueDownlinkLifecycle<T, FLinked, BlockingHandler<F>, FEv, FSet, FUnlinked>
+impl<T, Shared, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed>
+    OnNodeStopped
+    for StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: Send,
+    FEv: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: OnNodeStoppedShared<Shared>,
+    FFailed: Send,
+{
+    type OnNodeStoppedFut<'a>
+        = FNodeStopped::OnNodeStoppedFut<'a>
     where
-        F: FnMut(&T) + Send,
-    {
-        BasicValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: BlockingHandler(f),
-            on_event: self.on_event,
-            on_set: self.on_set,
-            on_unlinked: self.on_unlinked,
-        }
+        Self: 'a;
+
+    fn on_node_stopped(&mut self, notice: NodeStopped) -> Self::OnNodeStoppedFut<'_> {
+        let StatefulEventDownlinkLifecycle {
+            shared,
+            on_node_stopped,
+            ..
+        } = self;
+        on_node_stopped.on_node_stopped(shared, notice)
     }
+}
 
-    /// Replace the handler that is called when the downlink receives an event.
-    pub fn on_event<F>(
-        self,
-        f: F,
-    ) -> BasicValueDownlinkLifecycle<T, FLinked, FSynced, FnMutHandler<F>, FSet, FUnlinked>
+impl<T, Shared, FLinked, FEv, FUnlinked, FReconnecting, FAdvisory, FNodeStopped, FFailed> OnFailed
+    for StatefulEventDownlinkLifecycle<
+        T,
+        Shared,
+        FLinked,
+        FEv,
+        FUnlinked,
+        FReconnecting,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    T: Send + Sync + 'static,
+    Shared: Send + Sync + 'static,
+    FLinked: Send,
+    FEv: Send,
+    FUnlinked: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: OnFailedShared<Shared>,
+{
+    type OnFailedFut<'a>
+        = FFailed::OnFailedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_failed(&mut self, failure: LinkFailure) -> Self::OnFailedFut<'_> {
+        let StatefulEventDownlinkLifecycle {
+            shared, on_failed, ..
+        } = self;
+        on_failed.on_failed(shared, failure)
+    }
+}
+
+/// A basic lifecycle for a map downlink where the event handlers do not share any state.
+pub struct BasicMapDownlinkLifecycle<
+    K,
+    V,
+    FLinked = NoHandler,
+    FSynced = NoHandler,
+    FUpdated = NoHandler,
+    FRemoved = NoHandler,
+    FClear = NoHandler,
+    FUnlink = NoHandler,
+    FEvicted = NoHandler,
+    FReconnecting = NoHandler,
+    FResynced = NoHandler,
+    FAdvisory = NoHandler,
+    FNodeStopped = NoHandler,
+    FFailed = NoHandler,
+> {
+    _type: PhantomData<fn(K, V)>,
+    on_linked: FLinked,
+    on_synced: FSynced,
+    on_update: FUpdated,
+    on_removed: FRemoved,
+    on_clear: FClear,
+    on_unlink: FUnlink,
+    on_evicted: FEvicted,
+    on_reconnecting: FReconnecting,
+    on_advisory: FAdvisory,
+    on_node_stopped: FNodeStopped,
+    on_failed: FFailed,
+    on_resynced: FResynced,
+}
+
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnLinked
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    FLinked: OnLinked,
+    FSynced: Send,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnLinkedFut<'a>
+        = FLinked::OnLinkedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_linked(&mut self) -> Self::OnLinkedFut<'_> {
+        self.on_linked.on_linked()
+    }
+}
+
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnSynced<BTreeMap<K, V>>
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: OnSynced<BTreeMap<K, V>>,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnSyncedFut<'a>
+        = FSynced::OnSyncedFut<'a>
+    where
+        Self: 'a;
+
+    fn on_synced<'a>(&'a mut self, value: &'a BTreeMap<K, V>) -> Self::OnSyncedFut<'a> {
+        self.on_synced.on_synced(value)
+    }
+}
+
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnUpdate<K, V>
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FUpdated: OnUpdate<K, V>,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnUpdateFut<'a>
+        = FUpdated::OnUpdateFut<'a>
+    where
+        Self: 'a;
+
+    fn on_update<'a>(
+        &'a mut self,
+        key: K,
+        map: &'a BTreeMap<K, V>,
+        previous: Option<V>,
+        new_value: &'a V,
+    ) -> Self::OnUpdateFut<'a> {
+        self.on_update.on_update(key, map, previous, new_value)
+    }
+}
+
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnRemove<K, V>
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FUpdated: Send,
+    FRemoved: OnRemove<K, V>,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnRemoveFut<'a>
+        = FRemoved::OnRemoveFut<'a>
     where
-        FnMutHandler<F>: OnEvent<T>,
-    {
-        BasicValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_event: FnMutHandler(f),
-            on_set: self.on_set,
-            on_unlinked: self.on_unlinked,
-        }
-    }
+        Self: 'a;
 
-    /// Replace the handler that is called when the downlink receives and event with the specified
-    /// synchronous closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_event_blocking<F>(
-        self,
-        f: F,
-    ) -> BasicValueDownlinkLifecycle<T, FLinked, FSynced, BlockingHandler<F>, FSet, FUnlinked>
-    where
-        F: FnMut(&T) + Send,
-    {
-        BasicValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_event: BlockingHandler(f),
-            on_set: self.on_set,
-            on_unlinked: self.on_unlinked,
-        }
+    fn on_remove<'a>(
+        &'a mut self,
+        key: K,
+        map: &'a BTreeMap<K, V>,
+        removed: V,
+    ) -> Self::OnRemoveFut<'a> {
+        self.on_removed.on_remove(key, map, removed)
     }
+}
 
-    /// Replace the handler that is called when the downlink value changes.
-    pub fn on_set<F>(
-        self,
-        f: F,
-    ) -> BasicValueDownlinkLifecycle<T, FLinked, FSynced, FEv, FnMutHandler<F>, FUnlinked>
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnClear<K, V>
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: OnClear<K, V>,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnClearFut<'a>
+        = FClear::OnClearFut<'a>
     where
-        FnMutHandler<F>: OnSet<T>,
-    {
-        BasicValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_event: self.on_event,
-            on_set: FnMutHandler(f),
-            on_unlinked: self.on_unlinked,
-        }
-    }
+        Self: 'a;
 
-    /// Replace the handler that is called when the downlink value changes with the specified synchronous
-    /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_set_blocking<F>(
-        self,
-        f: F,
-    ) -> BasicValueDownlinkLifecycle<T, FLinked, FSynced, FEv, BlockingHandler<F>, FUnlinked>
+    fn on_clear<'a>(&'a mut self, map: BTreeMap<K, V>) -> Self::OnClearFut<'a>
     where
-        F: FnMut(Option<&T>, &T) + Send,
+        K: 'a,
+        V: 'a,
     {
-        BasicValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_event: self.on_event,
-            on_set: BlockingHandler(f),
-            on_unlinked: self.on_unlinked,
-        }
+        self.on_clear.on_clear(map)
     }
+}
 
-    /// Replace the handler that is called when the downlink disconnects.
-    pub fn on_unlinked<F>(
-        self,
-        f: F,
-    ) -> BasicValueDownlinkLifecycle<T, FLinked, FSynced, FEv, FSet, FnMutHandler<F>>
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnEvict<K, V>
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: OnEvict<K, V>,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnEvictFut<'a>
+        = FEvicted::OnEvictFut<'a>
     where
-        FnMutHandler<F>: OnUnlinked,
-    {
-        BasicValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_event: self.on_event,
-            on_set: self.on_set,
-            on_unlinked: FnMutHandler(f),
-        }
+        Self: 'a;
+
+    fn on_evict<'a>(
+        &'a mut self,
+        key: K,
+        map: &'a BTreeMap<K, V>,
+        evicted: V,
+    ) -> Self::OnEvictFut<'a> {
+        self.on_evicted.on_evict(key, map, evicted)
     }
+}
 
-    /// Replace the handler that is called when the downlink disconnects with the specified synchronous
-    /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_unlinked_blocking<F>(
-        self,
-        f: F,
-    ) -> BasicValueDownlinkLifecycle<T, FLinked, FSynced, FEv, FSet, BlockingHandler<F>>
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnUnlinked
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: OnUnlinked,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnUnlinkedFut<'a>
+        = FUnlink::OnUnlinkedFut<'a>
     where
-        F: FnMut() + Send,
-    {
-        BasicValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_event: self.on_event,
-            on_set: self.on_set,
-            on_unlinked: BlockingHandler(f),
-        }
+        Self: 'a;
+
+    fn on_unlinked(&mut self) -> Self::OnUnlinkedFut<'_> {
+        self.on_unlink.on_unlinked()
     }
+}
 
-    /// Adds shared state this is accessible to all handlers for this downlink.
-    pub fn with<Shared>(
-        self,
-        shared_state: Shared,
-    ) -> WithSharedValueDownlinkLifecycle<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked> {
-        StatefulValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: shared_state,
-            on_linked: WithShared::new(self.on_linked),
-            on_synced: WithShared::new(self.on_synced),
-            on_event: WithShared::new(self.on_event),
-            on_set: WithShared::new(self.on_set),
-            on_unlinked: WithShared::new(self.on_unlinked),
+/// A lifecycle for a map downlink where the event handlers do not share state (named for parity
+/// with the lifecycles of downlinks that are hosted by agents).
+pub type StatelessMapDownlinkLifecycle<
+    K,
+    V,
+    FLinked = NoHandler,
+    FSynced = NoHandler,
+    FUpdated = NoHandler,
+    FRemoved = NoHandler,
+    FClear = NoHandler,
+    FUnlink = NoHandler,
+    FEvicted = NoHandler,
+    FReconnecting = NoHandler,
+    FResynced = NoHandler,
+    FAdvisory = NoHandler,
+    FNodeStopped = NoHandler,
+    FFailed = NoHandler,
+> = BasicMapDownlinkLifecycle<
+    K,
+    V,
+    FLinked,
+    FSynced,
+    FUpdated,
+    FRemoved,
+    FClear,
+    FUnlink,
+    FEvicted,
+    FReconnecting,
+    FResynced,
+    FAdvisory,
+    FNodeStopped,
+    FFailed,
+>;
+
+impl<K, V> Default for BasicMapDownlinkLifecycle<K, V> {
+    fn default() -> Self {
+        BasicMapDownlinkLifecycle {
+            _type: Default::default(),
+            on_linked: Default::default(),
+            on_synced: Default::default(),
+            on_update: Default::default(),
+            on_removed: Default::default(),
+            on_clear: Default::default(),
+            on_unlink: Default::default(),
+            on_evicted: Default::default(),
+            on_reconnecting: Default::default(),
+            on_advisory: Default::default(),
+            on_node_stopped: Default::default(),
+            on_failed: Default::default(),
+            on_resynced: Default::default(),
         }
     }
 }
 
-impl<T, FLinked, FSynced, FEv, FSet, FUnlinked> OnLinked
-    for BasicValueDownlinkLifecycle<T, FLinked, FSynced, FEv, FSet, FUnlinked>
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnReconnecting
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
 where
-    T: Send + Sync + 'static,
-    FLinked: OnLinked,
-    FSynced: Send,
-    FEv: Send,
-    FSet: Send,
-    FUnlinked: Send,
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    FLinked: Send,
+    FSynced: Send,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: OnReconnecting,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
 {
-    type OnLinkedFut<'a> = FLinked::OnLinkedFut<'a>
+    type OnReconnectingFut<'a>
+        = FReconnecting::OnReconnectingFut<'a>
     where
         Self: 'a;
 
-    fn on_linked(&mut self) -> Self::OnLinkedFut<'_> {
-        self.on_linked.on_linked()
+    fn on_reconnecting(&mut self) -> Self::OnReconnectingFut<'_> {
+        self.on_reconnecting.on_reconnecting()
     }
 }
 
-impl<T, FLinked, FSynced, FEv, FSet, FUnlinked> OnSynced<T>
-    for BasicValueDownlinkLifecycle<T, FLinked, FSynced, FEv, FSet, FUnlinked>
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnAdvisory
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
 where
-    T: Send + Sync + 'static,
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
     FLinked: Send,
-    FSynced: OnSynced<T>,
-    FEv: Send,
-    FSet: Send,
-    FUnlinked: Send,
+    FSynced: Send,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: OnAdvisory,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
 {
-    type OnSyncedFut<'a> = FSynced::OnSyncedFut<'a>
+    type OnAdvisoryFut<'a>
+        = FAdvisory::OnAdvisoryFut<'a>
     where
-        Self: 'a,
-        T: 'a;
+        Self: 'a;
 
-    fn on_synced<'a>(&'a mut self, value: &'a T) -> Self::OnSyncedFut<'a> {
-        self.on_synced.on_synced(value)
+    fn on_advisory(&mut self, advice: LinkAdvice) -> Self::OnAdvisoryFut<'_> {
+        self.on_advisory.on_advisory(advice)
     }
 }
 
-impl<T, FLinked, FSynced, FEv, FSet, FUnlinked> OnEvent<T>
-    for BasicValueDownlinkLifecycle<T, FLinked, FSynced, FEv, FSet, FUnlinked>
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnNodeStopped
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
 where
-    T: Send + Sync + 'static,
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
     FLinked: Send,
     FSynced: Send,
-    FEv: OnEvent<T>,
-    FSet: Send,
-    FUnlinked: Send,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: OnNodeStopped,
+    FFailed: Send,
+    FResynced: Send,
 {
-    type OnEventFut<'a> = FEv::OnEventFut<'a>
+    type OnNodeStoppedFut<'a>
+        = FNodeStopped::OnNodeStoppedFut<'a>
     where
         Self: 'a;
 
-    fn on_event<'a>(&'a mut self, value: &'a T) -> Self::OnEventFut<'a> {
-        self.on_event.on_event(value)
+    fn on_node_stopped(&mut self, notice: NodeStopped) -> Self::OnNodeStoppedFut<'_> {
+        self.on_node_stopped.on_node_stopped(notice)
     }
 }
 
-impl<T, FLinked, FSynced, FEv, FSet, FUnlinked> OnSet<T>
-    for BasicValueDownlinkLifecycle<T, FLinked, FSynced, FEv, FSet, FUnlinked>
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnFailed
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
 where
-    T: Send + Sync,
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
     FLinked: Send,
     FSynced: Send,
-    FEv: Send,
-    FSet: OnSet<T>,
-    FUnlinked: Send,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: OnFailed,
+    FResynced: Send,
 {
-    type OnSetFut<'a> = FSet::OnSetFut<'a>
+    type OnFailedFut<'a>
+        = FFailed::OnFailedFut<'a>
     where
-        Self: 'a,
-        T: 'a;
+        Self: 'a;
 
-    fn on_set<'a>(&'a mut self, existing: Option<&'a T>, new_value: &'a T) -> Self::OnSetFut<'a> {
-        self.on_set.on_set(existing, new_value)
+    fn on_failed(&mut self, failure: LinkFailure) -> Self::OnFailedFut<'_> {
+        self.on_failed.on_failed(failure)
     }
 }
 
-impl<T, FLinked, FSynced, FEv, FSet, FUnlinked> OnUnlinked
-    for BasicValueDownlinkLifecycle<T, FLinked, FSynced, FEv, FSet, FUnlinked>
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnResynced<BTreeMap<K, V>>
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
 where
-    T: Send + Sync + 'static,
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
     FLinked: Send,
     FSynced: Send,
-    FEv: Send,
-    FSet: Send,
-    FUnlinked: OnUnlinked,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: OnResynced<BTreeMap<K, V>>,
 {
-    type OnUnlinkedFut<'a> = FUnlinked::OnUnlinkedFut<'a>
+    type OnResyncedFut<'a>
+        = FResynced::OnResyncedFut<'a>
     where
         Self: 'a;
 
-    fn on_unlinked(&mut self) -> Self::OnUnlinkedFut<'_> {
-        self.on_unlinked.on_unlinked()
-    }
-}
-
-/// A lifecycle for a value downlink where the handlers for each event share state.
-pub struct StatefulValueDownlinkLifecycle<
-    T,
-    Shared,
-    FLink = NoHandler,
-    FSync = NoHandler,
-    FEv = NoHandler,
-    FSet = NoHandler,
-    FUnlink = NoHandler,
-> {
-    _value_type: PhantomData<fn(T)>,
-    shared: Shared,
-    on_linked: FLink,
-    on_synced: FSync,
-    on_event: FEv,
-    on_set: FSet,
-    on_unlinked: FUnlink,
-}
-
-impl<T, Shared> StatefulEventDownlinkLifecycle<T, Shared> {
-    pub fn new(shared: Shared) -> Self {
-        StatefulEventDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared,
-            on_linked: Default::default(),
-            on_event: Default::default(),
-            on_unlinked: Default::default(),
-        }
+    fn on_resynced<'a>(&'a mut self, value: &'a BTreeMap<K, V>) -> Self::OnResyncedFut<'a> {
+        self.on_resynced.on_resynced(value)
     }
 }
 
-impl<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked>
-    StatefulValueDownlinkLifecycle<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked>
-where
-    T: Send + Sync + 'static,
-    Shared: Send + Sync + 'static,
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
 {
     /// Replace the handler that is called when the downlink connects.
-    pub fn on_linked<F>(
-        self,
-        f: F,
-    ) -> StatefulValueDownlinkLifecycle<T, Shared, FnMutHandler<F>, FSynced, FEv, FSet, FUnlinked>
+    pub fn on_linked<F>(self, f: F) -> <Self as MapLifecycleHandlers>::WithOnLinked<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnLinkedShared<Shared>,
+        FnMutHandler<F>: OnLinked,
     {
-        StatefulValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: FnMutHandler(f),
             on_synced: self.on_synced,
-            on_event: self.on_event,
-            on_set: self.on_set,
-            on_unlinked: self.on_unlinked,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
@@ -493,1178 +5646,1268 @@ where
     pub fn on_linked_blocking<F>(
         self,
         f: F,
-    ) -> StatefulValueDownlinkLifecycle<T, Shared, BlockingHandler<F>, FSynced, FEv, FSet, FUnlinked>
+    ) -> <Self as MapLifecycleHandlers>::WithOnLinked<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared) + Send,
+        F: FnMut() + Send,
     {
-        StatefulValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: BlockingHandler(f),
             on_synced: self.on_synced,
-            on_event: self.on_event,
-            on_set: self.on_set,
-            on_unlinked: self.on_unlinked,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
     /// Replace the handler that is called when the downlink synchronizes.
-    pub fn on_synced<F>(
-        self,
-        f: F,
-    ) -> StatefulValueDownlinkLifecycle<T, Shared, FLinked, FnMutHandler<F>, FEv, FSet, FUnlinked>
+    pub fn on_synced<F>(self, f: F) -> <Self as MapLifecycleHandlers>::WithOnSynced<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnSyncedShared<T, Shared>,
+        FnMutHandler<F>: for<'a> OnSynced<BTreeMap<K, V>>,
     {
-        StatefulValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
             on_synced: FnMutHandler(f),
-            on_event: self.on_event,
-            on_set: self.on_set,
-            on_unlinked: self.on_unlinked,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink synchronizes with the specified synchronous
-    /// closure. Running this closure will block the task so it should complete quickly.
+    /// Replace the handler that is called when the downlink synchronizes with the specified
+    /// synchronous closure. Running this closure will block the task so it should complete quickly.
     pub fn on_synced_blocking<F>(
         self,
         f: F,
-    ) -> StatefulValueDownlinkLifecycle<T, Shared, FLinked, BlockingHandler<F>, FEv, FSet, FUnlinked>
+    ) -> <Self as MapLifecycleHandlers>::WithOnSynced<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared, &T),
+        F: FnMut(&BTreeMap<K, V>) + Send,
     {
-        StatefulValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
             on_synced: BlockingHandler(f),
-            on_event: self.on_event,
-            on_set: self.on_set,
-            on_unlinked: self.on_unlinked,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink receives a new event.
-    pub fn on_event<F>(
-        self,
-        f: F,
-    ) -> StatefulValueDownlinkLifecycle<T, Shared, FLinked, FSynced, FnMutHandler<F>, FSet, FUnlinked>
+    /// Replace the handler that is called when the downlink updates a value.
+    pub fn on_update<F>(self, f: F) -> <Self as MapLifecycleHandlers>::WithOnUpdate<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnEventShared<T, Shared>,
+        FnMutHandler<F>: OnUpdate<K, V>,
     {
-        StatefulValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
             on_synced: self.on_synced,
-            on_event: FnMutHandler(f),
-            on_set: self.on_set,
-            on_unlinked: self.on_unlinked,
+            on_update: FnMutHandler(f),
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink receives a new event with the specified
+    /// Replace the handler that is called when the downlink updates a value with the specified
     /// synchronous closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_event_blocking<F>(
+    pub fn on_update_blocking<F>(
         self,
         f: F,
-    ) -> StatefulValueDownlinkLifecycle<
-        T,
-        Shared,
-        FLinked,
-        FSynced,
-        BlockingHandler<F>,
-        FSet,
-        FUnlinked,
-    >
+    ) -> <Self as MapLifecycleHandlers>::WithOnUpdate<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared, &T),
+        F: FnMut(K, &BTreeMap<K, V>, Option<V>, &V) + Send,
     {
-        StatefulValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
             on_synced: self.on_synced,
-            on_event: BlockingHandler(f),
-            on_set: self.on_set,
-            on_unlinked: self.on_unlinked,
+            on_update: BlockingHandler(f),
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink value changes.
-    pub fn on_set<F>(
+    /// Replace the handler that is called when the downlink removes a key.
+    pub fn on_removed<F>(
         self,
         f: F,
-    ) -> StatefulValueDownlinkLifecycle<T, Shared, FLinked, FSynced, FEv, FnMutHandler<F>, FUnlinked>
+    ) -> <Self as MapLifecycleHandlers>::WithOnRemoved<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnSetShared<T, Shared>,
+        FnMutHandler<F>: OnRemove<K, V>,
     {
-        StatefulValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
             on_synced: self.on_synced,
-            on_event: self.on_event,
-            on_set: FnMutHandler(f),
-            on_unlinked: self.on_unlinked,
+            on_update: self.on_update,
+            on_removed: FnMutHandler(f),
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink value changes with the specified synchronous
-    /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_set_blocking<F>(
+    /// Replace the handler that is called when the downlink removes a key with the specified
+    /// synchronous closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_removed_blocking<F>(
         self,
         f: F,
-    ) -> StatefulValueDownlinkLifecycle<
-        T,
-        Shared,
-        FLinked,
-        FSynced,
-        FEv,
-        BlockingHandler<F>,
-        FUnlinked,
-    >
+    ) -> <Self as MapLifecycleHandlers>::WithOnRemoved<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared, Option<&T>, &T),
+        F: FnMut(K, &BTreeMap<K, V>, V) + Send,
     {
-        StatefulValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
             on_synced: self.on_synced,
-            on_event: self.on_event,
-            on_set: BlockingHandler(f),
-            on_unlinked: self.on_unlinked,
+            on_update: self.on_update,
+            on_removed: BlockingHandler(f),
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink disconnects.
-    pub fn on_unlinked<F>(
-        self,
-        f: F,
-    ) -> StatefulValueDownlinkLifecycle<T, Shared, FLinked, FSynced, FEv, FSet, FnMutHandler<F>>
+    /// Replace the handler that is called when the downlink clears.
+    pub fn on_clear<F>(self, f: F) -> <Self as MapLifecycleHandlers>::WithOnClear<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnUnlinkedShared<Shared>,
+        FnMutHandler<F>: OnRemove<K, V>,
     {
-        StatefulValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
             on_synced: self.on_synced,
-            on_event: self.on_event,
-            on_set: self.on_set,
-            on_unlinked: FnMutHandler(f),
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: FnMutHandler(f),
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink disconnects with the specified synchronous
+    /// Replace the handler that is called when the downlink clears with the specified synchronous
     /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_unlinked_blocking<F>(
+    pub fn on_clear_blocking<F>(
         self,
         f: F,
-    ) -> StatefulValueDownlinkLifecycle<T, Shared, FLinked, FSynced, FEv, FSet, BlockingHandler<F>>
+    ) -> <Self as MapLifecycleHandlers>::WithOnClear<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared),
+        F: FnMut(BTreeMap<K, V>) + Send,
     {
-        StatefulValueDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
             on_synced: self.on_synced,
-            on_event: self.on_event,
-            on_set: self.on_set,
-            on_unlinked: BlockingHandler(f),
-        }
-    }
-}
-
-impl<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked> OnLinked
-    for StatefulValueDownlinkLifecycle<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked>
-where
-    T: Send + Sync + 'static,
-    Shared: Send + Sync + 'static,
-    FLinked: OnLinkedShared<Shared>,
-    FSynced: Send,
-    FEv: Send,
-    FSet: Send,
-    FUnlinked: Send,
-{
-    type OnLinkedFut<'a> = FLinked::OnLinkedFut<'a>
-    where
-        Self: 'a,
-        Shared: 'a;
-
-    fn on_linked(&mut self) -> Self::OnLinkedFut<'_> {
-        let StatefulValueDownlinkLifecycle {
-            shared, on_linked, ..
-        } = self;
-        on_linked.on_linked(shared)
-    }
-}
-
-impl<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked> OnSynced<T>
-    for StatefulValueDownlinkLifecycle<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked>
-where
-    T: Send + Sync + 'static,
-    Shared: Send + Sync + 'static,
-    FLinked: Send,
-    FSynced: OnSyncedShared<T, Shared>,
-    FEv: Send,
-    FSet: Send,
-    FUnlinked: Send,
-{
-    type OnSyncedFut<'a> = FSynced::OnSyncedFut<'a>
-    where
-        Self: 'a,
-        T: 'a;
-
-    fn on_synced<'a>(&'a mut self, value: &'a T) -> Self::OnSyncedFut<'a> {
-        let StatefulValueDownlinkLifecycle {
-            shared, on_synced, ..
-        } = self;
-        on_synced.on_synced(shared, value)
-    }
-}
-
-impl<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked> OnEvent<T>
-    for StatefulValueDownlinkLifecycle<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked>
-where
-    T: Send + Sync + 'static,
-    Shared: Send + Sync,
-    FLinked: Send,
-    FSynced: Send,
-    FEv: OnEventShared<T, Shared>,
-    FSet: Send,
-    FUnlinked: Send,
-{
-    type OnEventFut<'a> = FEv::OnEventFut<'a>
-    where
-        Self: 'a,
-        Shared: 'a;
-
-    fn on_event<'a>(&'a mut self, value: &'a T) -> Self::OnEventFut<'a> {
-        let StatefulValueDownlinkLifecycle {
-            shared, on_event, ..
-        } = self;
-        on_event.on_event(shared, value)
-    }
-}
-
-impl<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked> OnSet<T>
-    for StatefulValueDownlinkLifecycle<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked>
-where
-    T: Send + Sync + 'static,
-    Shared: Send + Sync + 'static,
-    FLinked: Send,
-    FSynced: Send,
-    FEv: Send,
-    FSet: OnSetShared<T, Shared>,
-    FUnlinked: Send,
-{
-    type OnSetFut<'a> = FSet::OnSetFut<'a>
-    where
-        Self: 'a,
-        T: 'a;
-
-    fn on_set<'a>(&'a mut self, existing: Option<&'a T>, new_value: &'a T) -> Self::OnSetFut<'a> {
-        let StatefulValueDownlinkLifecycle { shared, on_set, .. } = self;
-        on_set.on_set(shared, existing, new_value)
-    }
-}
-
-impl<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked> OnUnlinked
-    for StatefulValueDownlinkLifecycle<T, Shared, FLinked, FSynced, FEv, FSet, FUnlinked>
-where
-    T: Send + Sync + 'static,
-    Shared: Send + Sync + 'static,
-    FLinked: Send,
-    FSynced: Send,
-    FEv: Send,
-    FSet: Send,
-    FUnlinked: OnUnlinkedShared<Shared>,
-{
-    type OnUnlinkedFut<'a> = FUnlinked::OnUnlinkedFut<'a>
-    where
-        Self: 'a;
-
-    fn on_unlinked(&mut self) -> Self::OnUnlinkedFut<'_> {
-        let StatefulValueDownlinkLifecycle {
-            shared,
-            on_unlinked,
-            ..
-        } = self;
-        on_unlinked.on_unlinked(shared)
-    }
-}
-
-/// A basic lifecycle for an event downlink where the event handlers do not share any state.
-pub struct BasicEventDownlinkLifecycle<T, FLink = NoHandler, FEv = NoHandler, FUnlink = NoHandler> {
-    _value_type: PhantomData<fn(T)>,
-    on_linked: FLink,
-    on_event: FEv,
-    on_unlinked: FUnlink,
-}
-
-/// A lifecycle for an event downlink where the handlers for each event share state.
-pub struct StatefulEventDownlinkLifecycle<
-    T,
-    Shared,
-    FLink = NoHandler,
-    FEv = NoHandler,
-    FUnlink = NoHandler,
-> {
-    _value_type: PhantomData<fn(T)>,
-    shared: Shared,
-    on_linked: FLink,
-    on_event: FEv,
-    on_unlinked: FUnlink,
-}
-
-impl<T, FLinked, FEv, FUnlinked> BasicEventDownlinkLifecycle<T, FLinked, FEv, FUnlinked>
-where
-    T: Send + Sync + 'static,
-{
-    /// Replace the handler that is called when the downlink connects.
-    pub fn on_linked<F>(
-        self,
-        f: F,
-    ) -> BasicEventDownlinkLifecycle<T, FnMutHandler<F>, FEv, FUnlinked>
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: BlockingHandler(f),
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink unlinks.
+    pub fn on_unlink<F>(self, f: F) -> <Self as MapLifecycleHandlers>::WithOnUnlink<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnLinked,
+        FnMutHandler<F>: OnUnlinked,
     {
-        BasicEventDownlinkLifecycle {
-            _value_type: PhantomData,
-            on_linked: FnMutHandler(f),
-            on_event: self.on_event,
-            on_unlinked: self.on_unlinked,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: FnMutHandler(f),
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink connects with the specified synchronous
+    /// Replace the handler that is called when the downlink unlinks with the specified synchronous
     /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_linked_blocking<F>(
+    pub fn on_unlink_blocking<F>(
         self,
         f: F,
-    ) -> BasicEventDownlinkLifecycle<T, BlockingHandler<F>, FEv, FUnlinked>
+    ) -> <Self as MapLifecycleHandlers>::WithOnUnlink<BlockingHandler<F>>
     where
         F: FnMut() + Send,
     {
-        BasicEventDownlinkLifecycle {
-            _value_type: PhantomData,
-            on_linked: BlockingHandler(f),
-            on_event: self.on_event,
-            on_unlinked: self.on_unlinked,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: BlockingHandler(f),
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink receives a new event.
-    pub fn on_event<F>(
+    /// Replace the handler that is called when the downlink evicts an entry to stay within its
+    /// maximum number of entries.
+    pub fn on_evicted<F>(
         self,
         f: F,
-    ) -> BasicEventDownlinkLifecycle<T, FLinked, FnMutHandler<F>, FUnlinked>
+    ) -> <Self as MapLifecycleHandlers>::WithOnEvicted<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnEvent<T>,
+        FnMutHandler<F>: OnEvict<K, V>,
     {
-        BasicEventDownlinkLifecycle {
-            _value_type: PhantomData,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
-            on_event: FnMutHandler(f),
-            on_unlinked: self.on_unlinked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: FnMutHandler(f),
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink receives a new event with the specified
-    /// synchronous closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_event_blocking<F>(
+    /// Replace the handler that is called when the downlink evicts an entry to stay within its
+    /// maximum number of entries with the specified synchronous closure. Running this closure will
+    /// block the task so it should complete quickly.
+    pub fn on_evicted_blocking<F>(
         self,
         f: F,
-    ) -> BasicEventDownlinkLifecycle<T, FLinked, BlockingHandler<F>, FUnlinked>
+    ) -> <Self as MapLifecycleHandlers>::WithOnEvicted<BlockingHandler<F>>
     where
-        F: FnMut(&T) + Send,
+        F: FnMut(K, &BTreeMap<K, V>, V) + Send,
     {
-        BasicEventDownlinkLifecycle {
-            _value_type: PhantomData,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
-            on_event: BlockingHandler(f),
-            on_unlinked: self.on_unlinked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: BlockingHandler(f),
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink disconnects.
-    pub fn on_unlinked<F>(
+    /// Replace the handler that is called before the downlink attempts to relink.
+    pub fn on_reconnecting<F>(
         self,
         f: F,
-    ) -> BasicEventDownlinkLifecycle<T, FLinked, FEv, FnMutHandler<F>>
+    ) -> <Self as MapLifecycleHandlers>::WithOnReconnecting<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnUnlinked,
+        FnMutHandler<F>: OnReconnecting,
     {
-        BasicEventDownlinkLifecycle {
-            _value_type: PhantomData,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
-            on_event: self.on_event,
-            on_unlinked: FnMutHandler(f),
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: FnMutHandler(f),
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink disconnects with the specified synchronous
-    /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_unlinked_blocking<F>(
+    /// Replace the handler that is called before the downlink attempts to relink with the specified
+    /// synchronous closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_reconnecting_blocking<F>(
         self,
         f: F,
-    ) -> BasicEventDownlinkLifecycle<T, FLinked, FEv, BlockingHandler<F>>
+    ) -> <Self as MapLifecycleHandlers>::WithOnReconnecting<BlockingHandler<F>>
     where
         F: FnMut() + Send,
     {
-        BasicEventDownlinkLifecycle {
-            _value_type: PhantomData,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
-            on_event: self.on_event,
-            on_unlinked: BlockingHandler(f),
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: BlockingHandler(f),
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Adds shared state this is accessible to all handlers for this downlink.
-    pub fn with<Shared>(
+    /// Replace the handler that is called when the remote lane advises the downlink to back off.
+    pub fn on_advisory<F>(
         self,
-        shared_state: Shared,
-    ) -> WithSharedEventDownlinkLifecycle<T, Shared, FLinked, FEv, FUnlinked> {
-        StatefulEventDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: shared_state,
-            on_linked: WithShared::new(self.on_linked),
-            on_event: WithShared::new(self.on_event),
-            on_unlinked: WithShared::new(self.on_unlinked),
-        }
-    }
-}
-
-impl<T, FLinked, FEv, FUnlinked> OnLinked
-    for BasicEventDownlinkLifecycle<T, FLinked, FEv, FUnlinked>
-where
-    T: Send + Sync + 'static,
-    FLinked: OnLinked,
-    FEv: Send,
-    FUnlinked: Send,
-{
-    type OnLinkedFut<'a> = FLinked::OnLinkedFut<'a>
-    where
-        Self: 'a;
-
-    fn on_linked(&mut self) -> Self::OnLinkedFut<'_> {
-        self.on_linked.on_linked()
-    }
-}
-
-impl<T, FLinked, FEv, FUnlinked> OnEvent<T>
-    for BasicEventDownlinkLifecycle<T, FLinked, FEv, FUnlinked>
-where
-    T: Send + Sync + 'static,
-    FLinked: Send,
-    FEv: OnEvent<T>,
-    FUnlinked: Send,
-{
-    type OnEventFut<'a> = FEv::OnEventFut<'a>
+        f: F,
+    ) -> <Self as MapLifecycleHandlers>::WithOnAdvisory<FnMutHandler<F>>
     where
-        Self: 'a;
-
-    fn on_event<'a>(&'a mut self, value: &'a T) -> Self::OnEventFut<'a> {
-        self.on_event.on_event(value)
+        FnMutHandler<F>: OnAdvisory,
+    {
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: FnMutHandler(f),
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
     }
-}
 
-impl<T, FLinked, FEv, FUnlinked> OnUnlinked
-    for BasicEventDownlinkLifecycle<T, FLinked, FEv, FUnlinked>
-where
-    T: Send + Sync + 'static,
-    FLinked: Send,
-    FEv: Send,
-    FUnlinked: OnUnlinked,
-{
-    type OnUnlinkedFut<'a> = FUnlinked::OnUnlinkedFut<'a>
+    /// Replace the handler that is called when the remote lane advises the downlink to back off with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_advisory_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as MapLifecycleHandlers>::WithOnAdvisory<BlockingHandler<F>>
     where
-        Self: 'a;
-
-    fn on_unlinked(&mut self) -> Self::OnUnlinkedFut<'_> {
-        self.on_unlinked.on_unlinked()
+        F: FnMut(LinkAdvice) + Send,
+    {
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: BlockingHandler(f),
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
     }
-}
 
-impl<T, Shared, FLinked, FEv, FUnlinked>
-    StatefulEventDownlinkLifecycle<T, Shared, FLinked, FEv, FUnlinked>
-where
-    T: Send + Sync + 'static,
-    Shared: Send + Sync + 'static,
-{
-    /// Replace the handler that is called when the downlink connects.
-    pub fn on_linked<F>(
+    /// Replace the handler that is called when the remote agent stops and unlinks the downlink.
+    pub fn on_node_stopped<F>(
         self,
         f: F,
-    ) -> StatefulEventDownlinkLifecycle<T, Shared, FnMutHandler<F>, FEv, FUnlinked>
+    ) -> <Self as MapLifecycleHandlers>::WithOnNodeStopped<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnLinkedShared<Shared>,
+        FnMutHandler<F>: OnNodeStopped,
     {
-        StatefulEventDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
-            on_linked: FnMutHandler(f),
-            on_event: self.on_event,
-            on_unlinked: self.on_unlinked,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: FnMutHandler(f),
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink connects. Running this closure
-    /// will block the task so it should complete quickly.
-    pub fn on_linked_blocking<F>(
+    /// Replace the handler that is called when the remote agent stops and unlinks the downlink with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_node_stopped_blocking<F>(
         self,
         f: F,
-    ) -> StatefulEventDownlinkLifecycle<T, Shared, BlockingHandler<F>, FEv, FUnlinked>
+    ) -> <Self as MapLifecycleHandlers>::WithOnNodeStopped<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared) + Send,
+        F: FnMut(NodeStopped) + Send,
     {
-        StatefulEventDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
-            on_linked: BlockingHandler(f),
-            on_event: self.on_event,
-            on_unlinked: self.on_unlinked,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: BlockingHandler(f),
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink receives a new event.
-    pub fn on_event<F>(
-        self,
-        f: F,
-    ) -> StatefulEventDownlinkLifecycle<T, Shared, FLinked, FnMutHandler<F>, FUnlinked>
+    /// Replace the handler that is called when the remote refuses to link the downlink because its
+    /// node or lane does not exist.
+    pub fn on_failed<F>(self, f: F) -> <Self as MapLifecycleHandlers>::WithOnFailed<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnEventShared<T, Shared>,
+        FnMutHandler<F>: OnFailed,
     {
-        StatefulEventDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
-            on_event: FnMutHandler(f),
-            on_unlinked: self.on_unlinked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: FnMutHandler(f),
+            on_resynced: self.on_resynced,
         }
     }
-    /// Replace the handler that is called when the downlink receives a new event. Running this closure
+
+    /// Replace the handler that is called when the remote refuses to link the downlink because its
+    /// node or lane does not exist with the specified synchronous closure. Running this closure
     /// will block the task so it should complete quickly.
-    pub fn on_event_blocking<F>(
+    pub fn on_failed_blocking<F>(
         self,
         f: F,
-    ) -> StatefulEventDownlinkLifecycle<T, Shared, FLinked, BlockingHandler<F>, FUnlinked>
+    ) -> <Self as MapLifecycleHandlers>::WithOnFailed<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared, &T),
+        F: FnMut(LinkFailure) + Send,
     {
-        StatefulEventDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
-            on_event: BlockingHandler(f),
-            on_unlinked: self.on_unlinked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: BlockingHandler(f),
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink disconnects.
-    pub fn on_unlinked<F>(
+    /// Replace the handler that is called when the downlink synchronizes again after relinking.
+    pub fn on_resynced<F>(
         self,
         f: F,
-    ) -> StatefulEventDownlinkLifecycle<T, Shared, FLinked, FEv, FnMutHandler<F>>
+    ) -> <Self as MapLifecycleHandlers>::WithOnResynced<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnUnlinkedShared<Shared>,
+        FnMutHandler<F>: for<'a> OnResynced<BTreeMap<K, V>>,
     {
-        StatefulEventDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
-            on_event: self.on_event,
-            on_unlinked: FnMutHandler(f),
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: FnMutHandler(f),
         }
     }
 
-    /// Replace the handler that is called when the downlink disconnects. Running this closure
-    /// will block the task so it should complete quickly.
-    pub fn on_unlinked_blocking<F>(
+    /// Replace the handler that is called when the downlink synchronizes again after relinking with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_resynced_blocking<F>(
         self,
         f: F,
-    ) -> StatefulEventDownlinkLifecycle<T, Shared, FLinked, FEv, BlockingHandler<F>>
+    ) -> <Self as MapLifecycleHandlers>::WithOnResynced<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared),
+        F: FnMut(&BTreeMap<K, V>) + Send,
     {
-        StatefulEventDownlinkLifecycle {
-            _value_type: PhantomData,
-            shared: self.shared,
+        BasicMapDownlinkLifecycle {
+            _type: PhantomData,
             on_linked: self.on_linked,
-            on_event: self.on_event,
-            on_unlinked: BlockingHandler(f),
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: BlockingHandler(f),
         }
     }
-}
-
-impl<T, Shared, FLinked, FEv, FUnlinked> OnLinked
-    for StatefulEventDownlinkLifecycle<T, Shared, FLinked, FEv, FUnlinked>
-where
-    T: Send + Sync + 'static,
-    Shared: Send + Sync + 'static,
-    FLinked: OnLinkedShared<Shared>,
-    FEv: Send,
-    FUnlinked: Send,
-{
-    type OnLinkedFut<'a> = FLinked::OnLinkedFut<'a>
-    where
-        Self: 'a;
 
-    fn on_linked(&mut self) -> Self::OnLinkedFut<'_> {
-        let StatefulEventDownlinkLifecycle {
-            shared, on_linked, ..
-        } = self;
-        on_linked.on_linked(shared)
+    /// Adds shared state this is accessible to all handlers for this downlink.
+    pub fn with<Shared>(
+        self,
+        shared_state: Shared,
+    ) -> <Self as WithSharedState>::Lifecycle<Shared> {
+        StatefulMapDownlinkLifecycle {
+            _type: PhantomData,
+            state: shared_state,
+            on_linked: WithShared::new(self.on_linked),
+            on_synced: WithShared::new(self.on_synced),
+            on_update: WithShared::new(self.on_update),
+            on_removed: WithShared::new(self.on_removed),
+            on_clear: WithShared::new(self.on_clear),
+            on_unlink: WithShared::new(self.on_unlink),
+            on_evicted: WithShared::new(self.on_evicted),
+            on_reconnecting: WithShared::new(self.on_reconnecting),
+            on_advisory: WithShared::new(self.on_advisory),
+            on_node_stopped: WithShared::new(self.on_node_stopped),
+            on_failed: WithShared::new(self.on_failed),
+            on_resynced: WithShared::new(self.on_resynced),
+        }
     }
-}
 
-impl<T, Shared, FLinked, FEv, FUnlinked> OnEvent<T>
-    for StatefulEventDownlinkLifecycle<T, Shared, FLinked, FEv, FUnlinked>
-where
-    T: Send + Sync + 'static,
-    Shared: Send + Sync,
-    FLinked: Send,
-    FEv: OnEventShared<T, Shared>,
-    FUnlinked: Send,
-{
-    type OnEventFut<'a> = FEv::OnEventFut<'a>
-    where
-        Self: 'a;
-
-    fn on_event<'a>(&'a mut self, value: &'a T) -> Self::OnEventFut<'a> {
-        let StatefulEventDownlinkLifecycle {
-            shared, on_event, ..
-        } = self;
-        on_event.on_event(shared, value)
+    /// Adds shared state this is accessible to all handlers for this downlink (equivalent to
+    /// [`BasicMapDownlinkLifecycle::with`]).
+    pub fn with_shared_state<Shared>(
+        self,
+        shared_state: Shared,
+    ) -> <Self as WithSharedState>::Lifecycle<Shared> {
+        self.with(shared_state)
     }
 }
 
-impl<T, Shared, FLinked, FEv, FUnlinked> OnUnlinked
-    for StatefulEventDownlinkLifecycle<T, Shared, FLinked, FEv, FUnlinked>
-where
-    T: Send + Sync + 'static,
-    Shared: Send + Sync + 'static,
-    FLinked: Send,
-    FEv: Send,
-    FUnlinked: OnUnlinkedShared<Shared>,
-{
-    type OnUnlinkedFut<'a> = FUnlinked::OnUnlinkedFut<'a>
-    where
-        Self: 'a;
+type WithSharedMapDownlinkLifecycle<
+    K,
+    V,
+    Shared,
+    FLinked,
+    FSynced,
+    FUpdated,
+    FRemoved,
+    FClear,
+    FUnlink,
+    FEvicted,
+    FReconnecting,
+    FResynced,
+    FAdvisory,
+    FNodeStopped,
+    FFailed,
+> = StatefulMapDownlinkLifecycle<
+    K,
+    V,
+    Shared,
+    WithShared<FLinked>,
+    WithShared<FSynced>,
+    WithShared<FUpdated>,
+    WithShared<FRemoved>,
+    WithShared<FClear>,
+    WithShared<FUnlink>,
+    WithShared<FEvicted>,
+    WithShared<FReconnecting>,
+    WithShared<FResynced>,
+    WithShared<FAdvisory>,
+    WithShared<FNodeStopped>,
+    WithShared<FFailed>,
+>;
 
-    fn on_unlinked(&mut self) -> Self::OnUnlinkedFut<'_> {
-        let StatefulEventDownlinkLifecycle {
-            shared,
-            on_unlinked,
-            ..
-        } = self;
-        on_unlinked.on_unlinked(shared)
-    }
+impl<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > WithSharedState
+    for BasicMapDownlinkLifecycle<
+        K,
+        V,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+{
+    type Lifecycle<Shared> = WithSharedMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >;
 }
 
-/// A basic lifecycle for a map downlink where the event handlers do not share any state.
-pub struct BasicMapDownlinkLifecycle<
+/// A lifecycle for a map downlink where the handlers for each event share state.
+pub struct StatefulMapDownlinkLifecycle<
     K,
     V,
+    Shared,
     FLinked = NoHandler,
     FSynced = NoHandler,
     FUpdated = NoHandler,
     FRemoved = NoHandler,
     FClear = NoHandler,
     FUnlink = NoHandler,
+    FEvicted = NoHandler,
+    FReconnecting = NoHandler,
+    FResynced = NoHandler,
+    FAdvisory = NoHandler,
+    FNodeStopped = NoHandler,
+    FFailed = NoHandler,
 > {
     _type: PhantomData<fn(K, V)>,
+    state: Shared,
     on_linked: FLinked,
     on_synced: FSynced,
     on_update: FUpdated,
     on_removed: FRemoved,
     on_clear: FClear,
     on_unlink: FUnlink,
+    on_evicted: FEvicted,
+    on_reconnecting: FReconnecting,
+    on_advisory: FAdvisory,
+    on_node_stopped: FNodeStopped,
+    on_failed: FFailed,
+    on_resynced: FResynced,
 }
 
-impl<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnLinked
-    for BasicMapDownlinkLifecycle<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
+impl<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnLinked
+    for StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
 where
     K: Send + Sync + 'static,
     V: Send + Sync + 'static,
-    FLinked: OnLinked,
+    Shared: Send,
+    FLinked: OnLinkedShared<Shared>,
     FSynced: Send,
     FUpdated: Send,
     FRemoved: Send,
     FClear: Send,
     FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
 {
-    type OnLinkedFut<'a> = FLinked::OnLinkedFut<'a>
+    type OnLinkedFut<'a>
+        = FLinked::OnLinkedFut<'a>
     where
-    Self: 'a;
+        Self: 'a;
 
     fn on_linked(&mut self) -> Self::OnLinkedFut<'_> {
-        self.on_linked.on_linked()
-    }
-}
-
-impl<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnSynced<BTreeMap<K, V>>
-    for BasicMapDownlinkLifecycle<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
-where
-    K: Send + Sync + 'static,
-    V: Send + Sync + 'static,
-    FLinked: Send,
-    FSynced: OnSynced<BTreeMap<K, V>>,
-    FUpdated: Send,
-    FRemoved: Send,
-    FClear: Send,
-    FUnlink: Send,
-{
-    type OnSyncedFut<'a> = FSynced::OnSyncedFut<'a> where Self:'a;
-
-    fn on_synced<'a>(&'a mut self, value: &'a BTreeMap<K, V>) -> Self::OnSyncedFut<'a> {
-        self.on_synced.on_synced(value)
-    }
-}
-
-impl<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnUpdate<K, V>
-    for BasicMapDownlinkLifecycle<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
-where
-    K: Send + Sync + 'static,
-    V: Send + Sync + 'static,
-    FLinked: Send,
-    FSynced: Send,
-    FUpdated: OnUpdate<K, V>,
-    FRemoved: Send,
-    FClear: Send,
-    FUnlink: Send,
-{
-    type OnUpdateFut<'a> = FUpdated::OnUpdateFut<'a> where Self: 'a;
-
-    fn on_update<'a>(
-        &'a mut self,
-        key: K,
-        map: &'a BTreeMap<K, V>,
-        previous: Option<V>,
-        new_value: &'a V,
-    ) -> Self::OnUpdateFut<'a> {
-        self.on_update.on_update(key, map, previous, new_value)
-    }
-}
-
-impl<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnRemove<K, V>
-    for BasicMapDownlinkLifecycle<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
-where
-    K: Send + Sync + 'static,
-    V: Send + Sync + 'static,
-    FLinked: Send,
-    FSynced: Send,
-    FUpdated: Send,
-    FRemoved: OnRemove<K, V>,
-    FClear: Send,
-    FUnlink: Send,
-{
-    type OnRemoveFut<'a> = FRemoved::OnRemoveFut<'a> where Self:'a;
-
-    fn on_remove<'a>(
-        &'a mut self,
-        key: K,
-        map: &'a BTreeMap<K, V>,
-        removed: V,
-    ) -> Self::OnRemoveFut<'a> {
-        self.on_removed.on_remove(key, map, removed)
-    }
-}
-
-impl<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnClear<K, V>
-    for BasicMapDownlinkLifecycle<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
-where
-    K: Send + Sync + 'static,
-    V: Send + Sync + 'static,
-    FLinked: Send,
-    FSynced: Send,
-    FUpdated: Send,
-    FRemoved: Send,
-    FClear: OnClear<K, V>,
-    FUnlink: Send,
-{
-    type OnClearFut<'a> = FClear::OnClearFut<'a> where Self:'a;
-
-    fn on_clear<'a>(&'a mut self, map: BTreeMap<K, V>) -> Self::OnClearFut<'a>
-    where
-        K: 'a,
-        V: 'a,
-    {
-        self.on_clear.on_clear(map)
+        let StatefulMapDownlinkLifecycle {
+            state, on_linked, ..
+        } = self;
+        on_linked.on_linked(state)
     }
 }
 
-impl<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnUnlinked
-    for BasicMapDownlinkLifecycle<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
+impl<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnSynced<BTreeMap<K, V>>
+    for StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
 where
     K: Send + Sync + 'static,
     V: Send + Sync + 'static,
+    Shared: Send,
     FLinked: Send,
-    FSynced: Send,
+    FSynced: OnSyncedShared<BTreeMap<K, V>, Shared>,
     FUpdated: Send,
     FRemoved: Send,
     FClear: Send,
-    FUnlink: OnUnlinked,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
 {
-    type OnUnlinkedFut<'a> = FUnlink::OnUnlinkedFut<'a> where Self:'a;
-
-    fn on_unlinked(&mut self) -> Self::OnUnlinkedFut<'_> {
-        self.on_unlink.on_unlinked()
-    }
-}
+    type OnSyncedFut<'a>
+        = FSynced::OnSyncedFut<'a>
+    where
+        Self: 'a;
 
-impl<K, V> Default for BasicMapDownlinkLifecycle<K, V> {
-    fn default() -> Self {
-        BasicMapDownlinkLifecycle {
-            _type: Default::default(),
-            on_linked: Default::default(),
-            on_synced: Default::default(),
-            on_update: Default::default(),
-            on_removed: Default::default(),
-            on_clear: Default::default(),
-            on_unlink: Default::default(),
-        }
+    fn on_synced<'a>(&'a mut self, value: &'a BTreeMap<K, V>) -> Self::OnSyncedFut<'a> {
+        let StatefulMapDownlinkLifecycle {
+            state, on_synced, ..
+        } = self;
+        on_synced.on_synced(state, value)
     }
 }
 
-impl<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
-    BasicMapDownlinkLifecycle<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
-{
-    /// Replace the handler that is called when the downlink connects.
-    pub fn on_linked<F>(
-        self,
-        f: F,
-    ) -> BasicMapDownlinkLifecycle<
+impl<
         K,
         V,
-        FnMutHandler<F>,
+        Shared,
+        FLinked,
         FSynced,
         FUpdated,
         FRemoved,
         FClear,
         FUnlink,
-    >
-    where
-        FnMutHandler<F>: OnLinked,
-    {
-        BasicMapDownlinkLifecycle {
-            _type: PhantomData,
-            on_linked: FnMutHandler(f),
-            on_synced: self.on_synced,
-            on_update: self.on_update,
-            on_removed: self.on_removed,
-            on_clear: self.on_clear,
-            on_unlink: self.on_unlink,
-        }
-    }
-
-    /// Replace the handler that is called when the downlink connects with the specified synchronous
-    /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_linked_blocking<F>(
-        self,
-        f: F,
-    ) -> BasicMapDownlinkLifecycle<
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnUpdate<K, V>
+    for StatefulMapDownlinkLifecycle<
         K,
         V,
-        BlockingHandler<F>,
+        Shared,
+        FLinked,
         FSynced,
         FUpdated,
         FRemoved,
         FClear,
         FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
     >
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    Shared: Send,
+    FLinked: Send,
+    FSynced: Send,
+    FUpdated: OnUpdateShared<K, V, Shared>,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnUpdateFut<'a>
+        = FUpdated::OnUpdateFut<'a>
     where
-        F: FnMut() + Send,
-    {
-        BasicMapDownlinkLifecycle {
-            _type: PhantomData,
-            on_linked: BlockingHandler(f),
-            on_synced: self.on_synced,
-            on_update: self.on_update,
-            on_removed: self.on_removed,
-            on_clear: self.on_clear,
-            on_unlink: self.on_unlink,
-        }
+        Self: 'a;
+
+    fn on_update<'a>(
+        &'a mut self,
+        key: K,
+        map: &'a BTreeMap<K, V>,
+        previous: Option<V>,
+        new_value: &'a V,
+    ) -> Self::OnUpdateFut<'a> {
+        let StatefulMapDownlinkLifecycle {
+            state, on_update, ..
+        } = self;
+        on_update.on_update(state, key, map, previous, new_value)
     }
+}
 
-    /// Replace the handler that is called when the downlink synchronizes.
-    pub fn on_synced<F>(
-        self,
-        f: F,
-    ) -> BasicMapDownlinkLifecycle<
+impl<
         K,
         V,
+        Shared,
         FLinked,
-        FnMutHandler<F>,
+        FSynced,
         FUpdated,
         FRemoved,
         FClear,
         FUnlink,
-    >
-    where
-        FnMutHandler<F>: for<'a> OnSynced<BTreeMap<K, V>>,
-    {
-        BasicMapDownlinkLifecycle {
-            _type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: FnMutHandler(f),
-            on_update: self.on_update,
-            on_removed: self.on_removed,
-            on_clear: self.on_clear,
-            on_unlink: self.on_unlink,
-        }
-    }
-
-    /// Replace the handler that is called when the downlink synchronizes with the specified
-    /// synchronous closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_synced_blocking<F>(
-        self,
-        f: F,
-    ) -> BasicMapDownlinkLifecycle<
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnRemove<K, V>
+    for StatefulMapDownlinkLifecycle<
         K,
         V,
+        Shared,
         FLinked,
-        BlockingHandler<F>,
+        FSynced,
         FUpdated,
         FRemoved,
         FClear,
         FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
     >
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    Shared: Send,
+    FLinked: Send,
+    FSynced: Send,
+    FUpdated: Send,
+    FRemoved: OnRemoveShared<K, V, Shared>,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnRemoveFut<'a>
+        = FRemoved::OnRemoveFut<'a>
     where
-        F: FnMut(&BTreeMap<K, V>) + Send,
-    {
-        BasicMapDownlinkLifecycle {
-            _type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: BlockingHandler(f),
-            on_update: self.on_update,
-            on_removed: self.on_removed,
-            on_clear: self.on_clear,
-            on_unlink: self.on_unlink,
-        }
-    }
+        Self: 'a;
 
-    /// Replace the handler that is called when the downlink updates a value.
-    pub fn on_update<F>(
-        self,
-        f: F,
-    ) -> BasicMapDownlinkLifecycle<K, V, FLinked, FSynced, FnMutHandler<F>, FRemoved, FClear, FUnlink>
-    where
-        FnMutHandler<F>: OnUpdate<K, V>,
-    {
-        BasicMapDownlinkLifecycle {
-            _type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_update: FnMutHandler(f),
-            on_removed: self.on_removed,
-            on_clear: self.on_clear,
-            on_unlink: self.on_unlink,
-        }
+    fn on_remove<'a>(
+        &'a mut self,
+        key: K,
+        map: &'a BTreeMap<K, V>,
+        removed: V,
+    ) -> Self::OnRemoveFut<'a> {
+        let StatefulMapDownlinkLifecycle {
+            state, on_removed, ..
+        } = self;
+        on_removed.on_remove(state, key, map, removed)
     }
+}
 
-    /// Replace the handler that is called when the downlink updates a value with the specified
-    /// synchronous closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_update_blocking<F>(
-        self,
-        f: F,
-    ) -> BasicMapDownlinkLifecycle<
+impl<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnClear<K, V>
+    for StatefulMapDownlinkLifecycle<
         K,
         V,
+        Shared,
         FLinked,
         FSynced,
-        BlockingHandler<F>,
+        FUpdated,
         FRemoved,
         FClear,
         FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
     >
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    Shared: Send,
+    FLinked: Send,
+    FSynced: Send,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: OnClearShared<K, V, Shared>,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnClearFut<'a>
+        = FClear::OnClearFut<'a>
     where
-        F: FnMut(K, &BTreeMap<K, V>, Option<V>, &V) + Send,
-    {
-        BasicMapDownlinkLifecycle {
-            _type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_update: BlockingHandler(f),
-            on_removed: self.on_removed,
-            on_clear: self.on_clear,
-            on_unlink: self.on_unlink,
-        }
-    }
+        Self: 'a;
 
-    /// Replace the handler that is called when the downlink removes a key.
-    pub fn on_removed<F>(
-        self,
-        f: F,
-    ) -> BasicMapDownlinkLifecycle<K, V, FLinked, FSynced, FUpdated, FnMutHandler<F>, FClear, FUnlink>
+    fn on_clear<'a>(&'a mut self, map: BTreeMap<K, V>) -> Self::OnClearFut<'a>
     where
-        FnMutHandler<F>: OnRemove<K, V>,
+        K: 'a,
+        V: 'a,
     {
-        BasicMapDownlinkLifecycle {
-            _type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_update: self.on_update,
-            on_removed: FnMutHandler(f),
-            on_clear: self.on_clear,
-            on_unlink: self.on_unlink,
-        }
-    }
-
-    /// Replace the handler that is called when the downlink removes a key with the specified
-    /// synchronous closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_removed_blocking<F>(
-        self,
-        f: F,
-    ) -> BasicMapDownlinkLifecycle<
+        let StatefulMapDownlinkLifecycle {
+            state, on_clear, ..
+        } = self;
+        on_clear.on_clear(state, map)
+    }
+}
+
+impl<
         K,
         V,
+        Shared,
         FLinked,
         FSynced,
         FUpdated,
-        BlockingHandler<F>,
+        FRemoved,
         FClear,
         FUnlink,
-    >
-    where
-        F: FnMut(K, &BTreeMap<K, V>, V) + Send,
-    {
-        BasicMapDownlinkLifecycle {
-            _type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_update: self.on_update,
-            on_removed: BlockingHandler(f),
-            on_clear: self.on_clear,
-            on_unlink: self.on_unlink,
-        }
-    }
-
-    /// Replace the handler that is called when the downlink clears.
-    pub fn on_clear<F>(
-        self,
-        f: F,
-    ) -> BasicMapDownlinkLifecycle<
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnEvict<K, V>
+    for StatefulMapDownlinkLifecycle<
         K,
         V,
+        Shared,
         FLinked,
         FSynced,
         FUpdated,
         FRemoved,
-        FnMutHandler<F>,
+        FClear,
         FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
     >
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    Shared: Send,
+    FLinked: Send,
+    FSynced: Send,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: OnEvictShared<K, V, Shared>,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnEvictFut<'a>
+        = FEvicted::OnEvictFut<'a>
     where
-        FnMutHandler<F>: OnRemove<K, V>,
-    {
-        BasicMapDownlinkLifecycle {
-            _type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_update: self.on_update,
-            on_removed: self.on_removed,
-            on_clear: FnMutHandler(f),
-            on_unlink: self.on_unlink,
-        }
+        Self: 'a;
+
+    fn on_evict<'a>(
+        &'a mut self,
+        key: K,
+        map: &'a BTreeMap<K, V>,
+        evicted: V,
+    ) -> Self::OnEvictFut<'a> {
+        let StatefulMapDownlinkLifecycle {
+            state, on_evicted, ..
+        } = self;
+        on_evicted.on_evict(state, key, map, evicted)
     }
+}
 
-    /// Replace the handler that is called when the downlink clears with the specified synchronous
-    /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_clear_blocking<F>(
-        self,
-        f: F,
-    ) -> BasicMapDownlinkLifecycle<
+impl<
         K,
         V,
+        Shared,
         FLinked,
         FSynced,
         FUpdated,
         FRemoved,
-        BlockingHandler<F>,
+        FClear,
         FUnlink,
-    >
-    where
-        F: FnMut(BTreeMap<K, V>) + Send,
-    {
-        BasicMapDownlinkLifecycle {
-            _type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_update: self.on_update,
-            on_removed: self.on_removed,
-            on_clear: BlockingHandler(f),
-            on_unlink: self.on_unlink,
-        }
-    }
-
-    /// Replace the handler that is called when the downlink unlinks.
-    pub fn on_unlink<F>(
-        self,
-        f: F,
-    ) -> BasicMapDownlinkLifecycle<
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnUnlinked
+    for StatefulMapDownlinkLifecycle<
         K,
         V,
+        Shared,
         FLinked,
         FSynced,
         FUpdated,
         FRemoved,
         FClear,
-        FnMutHandler<F>,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
     >
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    Shared: Send,
+    FLinked: Send,
+    FSynced: Send,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: OnUnlinkedShared<Shared>,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnUnlinkedFut<'a>
+        = FUnlink::OnUnlinkedFut<'a>
     where
-        FnMutHandler<F>: OnUnlinked,
-    {
-        BasicMapDownlinkLifecycle {
-            _type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_update: self.on_update,
-            on_removed: self.on_removed,
-            on_clear: self.on_clear,
-            on_unlink: FnMutHandler(f),
-        }
+        Self: 'a;
+
+    fn on_unlinked(&mut self) -> Self::OnUnlinkedFut<'_> {
+        let StatefulMapDownlinkLifecycle {
+            state, on_unlink, ..
+        } = self;
+        on_unlink.on_unlinked(state)
     }
+}
 
-    /// Replace the handler that is called when the downlink unlinks with the specified synchronous
-    /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_unlink_blocking<F>(
-        self,
-        f: F,
-    ) -> BasicMapDownlinkLifecycle<
+impl<
         K,
         V,
+        Shared,
         FLinked,
         FSynced,
         FUpdated,
         FRemoved,
         FClear,
-        BlockingHandler<F>,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnReconnecting
+    for StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
     >
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    Shared: Send,
+    FLinked: Send,
+    FSynced: Send,
+    FUpdated: Send,
+    FRemoved: Send,
+    FClear: Send,
+    FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: OnReconnectingShared<Shared>,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
+{
+    type OnReconnectingFut<'a>
+        = FReconnecting::OnReconnectingFut<'a>
     where
-        F: FnMut() + Send,
-    {
-        BasicMapDownlinkLifecycle {
-            _type: PhantomData,
-            on_linked: self.on_linked,
-            on_synced: self.on_synced,
-            on_update: self.on_update,
-            on_removed: self.on_removed,
-            on_clear: self.on_clear,
-            on_unlink: BlockingHandler(f),
-        }
+        Self: 'a;
+
+    fn on_reconnecting(&mut self) -> Self::OnReconnectingFut<'_> {
+        let StatefulMapDownlinkLifecycle {
+            state,
+            on_reconnecting,
+            ..
+        } = self;
+        on_reconnecting.on_reconnecting(state)
     }
+}
 
-    /// Adds shared state this is accessible to all handlers for this downlink.
-    pub fn with<Shared>(
-        self,
-        shared_state: Shared,
-    ) -> WithSharedMapDownlinkLifecycle<
+impl<
         K,
         V,
         Shared,
@@ -1674,65 +6917,13 @@ impl<K, V, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
         FRemoved,
         FClear,
         FUnlink,
-    > {
-        StatefulMapDownlinkLifecycle {
-            _type: PhantomData,
-            state: shared_state,
-            on_linked: WithShared::new(self.on_linked),
-            on_synced: WithShared::new(self.on_synced),
-            on_update: WithShared::new(self.on_update),
-            on_removed: WithShared::new(self.on_removed),
-            on_clear: WithShared::new(self.on_clear),
-            on_unlink: WithShared::new(self.on_unlink),
-        }
-    }
-}
-
-type WithSharedMapDownlinkLifecycle<
-    K,
-    V,
-    Shared,
-    FLinked,
-    FSynced,
-    FUpdated,
-    FRemoved,
-    FClear,
-    FUnlink,
-> = StatefulMapDownlinkLifecycle<
-    K,
-    V,
-    Shared,
-    WithShared<FLinked>,
-    WithShared<FSynced>,
-    WithShared<FUpdated>,
-    WithShared<FRemoved>,
-    WithShared<FClear>,
-    WithShared<FUnlink>,
->;
-
-/// A lifecycle for a map downlink where the handlers for each event share state.
-pub struct StatefulMapDownlinkLifecycle<
-    K,
-    V,
-    Shared,
-    FLinked = NoHandler,
-    FSynced = NoHandler,
-    FUpdated = NoHandler,
-    FRemoved = NoHandler,
-    FClear = NoHandler,
-    FUnlink = NoHandler,
-> {
-    _type: PhantomData<fn(K, V)>,
-    state: Shared,
-    on_linked: FLinked,
-    on_synced: FSynced,
-    on_update: FUpdated,
-    on_removed: FRemoved,
-    on_clear: FClear,
-    on_unlink: FUnlink,
-}
-
-impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnLinked
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnAdvisory
     for StatefulMapDownlinkLifecycle<
         K,
         V,
@@ -1743,29 +6934,60 @@ impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnLink
         FRemoved,
         FClear,
         FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
     >
 where
     K: Send + Sync + 'static,
     V: Send + Sync + 'static,
     Shared: Send,
-    FLinked: OnLinkedShared<Shared>,
+    FLinked: Send,
     FSynced: Send,
     FUpdated: Send,
     FRemoved: Send,
     FClear: Send,
     FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: OnAdvisoryShared<Shared>,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: Send,
 {
-    type OnLinkedFut<'a> = FLinked::OnLinkedFut<'a> where Self:'a;
+    type OnAdvisoryFut<'a>
+        = FAdvisory::OnAdvisoryFut<'a>
+    where
+        Self: 'a;
 
-    fn on_linked(&mut self) -> Self::OnLinkedFut<'_> {
+    fn on_advisory(&mut self, advice: LinkAdvice) -> Self::OnAdvisoryFut<'_> {
         let StatefulMapDownlinkLifecycle {
-            state, on_linked, ..
+            state, on_advisory, ..
         } = self;
-        on_linked.on_linked(state)
+        on_advisory.on_advisory(state, advice)
     }
 }
 
-impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnSynced<BTreeMap<K, V>>
+impl<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnNodeStopped
     for StatefulMapDownlinkLifecycle<
         K,
         V,
@@ -1776,29 +6998,62 @@ impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnSync
         FRemoved,
         FClear,
         FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
     >
 where
     K: Send + Sync + 'static,
     V: Send + Sync + 'static,
     Shared: Send,
     FLinked: Send,
-    FSynced: OnSyncedShared<BTreeMap<K, V>, Shared>,
+    FSynced: Send,
     FUpdated: Send,
     FRemoved: Send,
     FClear: Send,
     FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: OnNodeStoppedShared<Shared>,
+    FFailed: Send,
+    FResynced: Send,
 {
-    type OnSyncedFut<'a> = FSynced::OnSyncedFut<'a> where Self:'a;
+    type OnNodeStoppedFut<'a>
+        = FNodeStopped::OnNodeStoppedFut<'a>
+    where
+        Self: 'a;
 
-    fn on_synced<'a>(&'a mut self, value: &'a BTreeMap<K, V>) -> Self::OnSyncedFut<'a> {
+    fn on_node_stopped(&mut self, notice: NodeStopped) -> Self::OnNodeStoppedFut<'_> {
         let StatefulMapDownlinkLifecycle {
-            state, on_synced, ..
+            state,
+            on_node_stopped,
+            ..
         } = self;
-        on_synced.on_synced(state, value)
+        on_node_stopped.on_node_stopped(state, notice)
     }
 }
 
-impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnUpdate<K, V>
+impl<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnFailed
     for StatefulMapDownlinkLifecycle<
         K,
         V,
@@ -1809,6 +7064,12 @@ impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnUpda
         FRemoved,
         FClear,
         FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
     >
 where
     K: Send + Sync + 'static,
@@ -1816,28 +7077,47 @@ where
     Shared: Send,
     FLinked: Send,
     FSynced: Send,
-    FUpdated: OnUpdateShared<K, V, Shared>,
+    FUpdated: Send,
     FRemoved: Send,
     FClear: Send,
     FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: OnFailedShared<Shared>,
+    FResynced: Send,
 {
-    type OnUpdateFut<'a> = FUpdated::OnUpdateFut<'a> where Self: 'a;
+    type OnFailedFut<'a>
+        = FFailed::OnFailedFut<'a>
+    where
+        Self: 'a;
 
-    fn on_update<'a>(
-        &'a mut self,
-        key: K,
-        map: &'a BTreeMap<K, V>,
-        previous: Option<V>,
-        new_value: &'a V,
-    ) -> Self::OnUpdateFut<'a> {
+    fn on_failed(&mut self, failure: LinkFailure) -> Self::OnFailedFut<'_> {
         let StatefulMapDownlinkLifecycle {
-            state, on_update, ..
+            state, on_failed, ..
         } = self;
-        on_update.on_update(state, key, map, previous, new_value)
+        on_failed.on_failed(state, failure)
     }
 }
 
-impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnRemove<K, V>
+impl<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    > OnResynced<BTreeMap<K, V>>
     for StatefulMapDownlinkLifecycle<
         K,
         V,
@@ -1848,6 +7128,12 @@ impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnRemo
         FRemoved,
         FClear,
         FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
     >
 where
     K: Send + Sync + 'static,
@@ -1856,27 +7142,30 @@ where
     FLinked: Send,
     FSynced: Send,
     FUpdated: Send,
-    FRemoved: OnRemoveShared<K, V, Shared>,
+    FRemoved: Send,
     FClear: Send,
     FUnlink: Send,
+    FEvicted: Send,
+    FReconnecting: Send,
+    FAdvisory: Send,
+    FNodeStopped: Send,
+    FFailed: Send,
+    FResynced: OnResyncedShared<BTreeMap<K, V>, Shared>,
 {
-    type OnRemoveFut<'a> = FRemoved::OnRemoveFut<'a> where Self:'a;
+    type OnResyncedFut<'a>
+        = FResynced::OnResyncedFut<'a>
+    where
+        Self: 'a;
 
-    fn on_remove<'a>(
-        &'a mut self,
-        key: K,
-        map: &'a BTreeMap<K, V>,
-        removed: V,
-    ) -> Self::OnRemoveFut<'a> {
+    fn on_resynced<'a>(&'a mut self, value: &'a BTreeMap<K, V>) -> Self::OnResyncedFut<'a> {
         let StatefulMapDownlinkLifecycle {
-            state, on_removed, ..
+            state, on_resynced, ..
         } = self;
-        on_removed.on_remove(state, key, map, removed)
+        on_resynced.on_resynced(state, value)
     }
 }
 
-impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnClear<K, V>
-    for StatefulMapDownlinkLifecycle<
+impl<
         K,
         V,
         Shared,
@@ -1886,278 +7175,503 @@ impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnClea
         FRemoved,
         FClear,
         FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
+    >
+    StatefulMapDownlinkLifecycle<
+        K,
+        V,
+        Shared,
+        FLinked,
+        FSynced,
+        FUpdated,
+        FRemoved,
+        FClear,
+        FUnlink,
+        FEvicted,
+        FReconnecting,
+        FResynced,
+        FAdvisory,
+        FNodeStopped,
+        FFailed,
     >
-where
-    K: Send + Sync + 'static,
-    V: Send + Sync + 'static,
-    Shared: Send,
-    FLinked: Send,
-    FSynced: Send,
-    FUpdated: Send,
-    FRemoved: Send,
-    FClear: OnClearShared<K, V, Shared>,
-    FUnlink: Send,
 {
-    type OnClearFut<'a> = FClear::OnClearFut<'a> where Self:'a;
+    /// Replace the handler that is called when the downlink connects.
+    pub fn on_linked<F>(self, f: F) -> <Self as MapLifecycleHandlers>::WithOnLinked<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnLinked,
+    {
+        StatefulMapDownlinkLifecycle {
+            _type: PhantomData,
+            state: self.state,
+            on_linked: FnMutHandler(f),
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink connects with the specified synchronous
+    /// closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_linked_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as MapLifecycleHandlers>::WithOnLinked<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared) + Send,
+    {
+        StatefulMapDownlinkLifecycle {
+            _type: PhantomData,
+            state: self.state,
+            on_linked: BlockingHandler(f),
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink synchronizes.
+    pub fn on_synced<F>(self, f: F) -> <Self as MapLifecycleHandlers>::WithOnSynced<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: for<'a> OnSynced<BTreeMap<K, V>>,
+    {
+        StatefulMapDownlinkLifecycle {
+            _type: PhantomData,
+            state: self.state,
+            on_linked: self.on_linked,
+            on_synced: FnMutHandler(f),
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink synchronizes with the specified
+    /// synchronous closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_synced_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as MapLifecycleHandlers>::WithOnSynced<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared, &BTreeMap<K, V>) + Send,
+    {
+        StatefulMapDownlinkLifecycle {
+            _type: PhantomData,
+            state: self.state,
+            on_linked: self.on_linked,
+            on_synced: BlockingHandler(f),
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink updates a value.
+    pub fn on_update<F>(self, f: F) -> <Self as MapLifecycleHandlers>::WithOnUpdate<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnUpdate<K, V>,
+    {
+        StatefulMapDownlinkLifecycle {
+            _type: PhantomData,
+            state: self.state,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: FnMutHandler(f),
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink updates a value with the specified
+    /// synchronous closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_update_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as MapLifecycleHandlers>::WithOnUpdate<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared, K, &BTreeMap<K, V>, Option<V>, &V) + Send,
+    {
+        StatefulMapDownlinkLifecycle {
+            _type: PhantomData,
+            state: self.state,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: BlockingHandler(f),
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink removes a key.
+    pub fn on_removed<F>(
+        self,
+        f: F,
+    ) -> <Self as MapLifecycleHandlers>::WithOnRemoved<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnRemove<K, V>,
+    {
+        StatefulMapDownlinkLifecycle {
+            _type: PhantomData,
+            state: self.state,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: FnMutHandler(f),
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink removes a key with the specified
+    /// synchronous closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_removed_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as MapLifecycleHandlers>::WithOnRemoved<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared, K, &BTreeMap<K, V>, V) + Send,
+    {
+        StatefulMapDownlinkLifecycle {
+            _type: PhantomData,
+            state: self.state,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: BlockingHandler(f),
+            on_clear: self.on_clear,
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink clears.
+    pub fn on_clear<F>(self, f: F) -> <Self as MapLifecycleHandlers>::WithOnClear<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnRemove<K, V>,
+    {
+        StatefulMapDownlinkLifecycle {
+            _type: PhantomData,
+            state: self.state,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: FnMutHandler(f),
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink clears with the specified synchronous
+    /// closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_clear_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as MapLifecycleHandlers>::WithOnClear<BlockingHandler<F>>
+    where
+        F: FnMut(&mut Shared, BTreeMap<K, V>) + Send,
+    {
+        StatefulMapDownlinkLifecycle {
+            _type: PhantomData,
+            state: self.state,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: BlockingHandler(f),
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
+
+    /// Replace the handler that is called when the downlink unlinks.
+    pub fn on_unlink<F>(self, f: F) -> <Self as MapLifecycleHandlers>::WithOnUnlink<FnMutHandler<F>>
+    where
+        FnMutHandler<F>: OnUnlinked,
+    {
+        StatefulMapDownlinkLifecycle {
+            _type: PhantomData,
+            state: self.state,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: FnMutHandler(f),
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
+    }
 
-    fn on_clear<'a>(&'a mut self, map: BTreeMap<K, V>) -> Self::OnClearFut<'a>
+    /// Replace the handler that is called when the downlink unlinks with the specified synchronous
+    /// closure. Running this closure will block the task so it should complete quickly.
+    pub fn on_unlink_blocking<F>(
+        self,
+        f: F,
+    ) -> <Self as MapLifecycleHandlers>::WithOnUnlink<BlockingHandler<F>>
     where
-        K: 'a,
-        V: 'a,
+        F: FnMut(&mut Shared) + Send,
     {
-        let StatefulMapDownlinkLifecycle {
-            state, on_clear, ..
-        } = self;
-        on_clear.on_clear(state, map)
-    }
-}
-
-impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink> OnUnlinked
-    for StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        FLinked,
-        FSynced,
-        FUpdated,
-        FRemoved,
-        FClear,
-        FUnlink,
-    >
-where
-    K: Send + Sync + 'static,
-    V: Send + Sync + 'static,
-    Shared: Send,
-    FLinked: Send,
-    FSynced: Send,
-    FUpdated: Send,
-    FRemoved: Send,
-    FClear: Send,
-    FUnlink: OnUnlinkedShared<Shared>,
-{
-    type OnUnlinkedFut<'a> = FUnlink::OnUnlinkedFut<'a> where Self:'a;
-
-    fn on_unlinked(&mut self) -> Self::OnUnlinkedFut<'_> {
-        let StatefulMapDownlinkLifecycle {
-            state, on_unlink, ..
-        } = self;
-        on_unlink.on_unlinked(state)
+        StatefulMapDownlinkLifecycle {
+            _type: PhantomData,
+            state: self.state,
+            on_linked: self.on_linked,
+            on_synced: self.on_synced,
+            on_update: self.on_update,
+            on_removed: self.on_removed,
+            on_clear: self.on_clear,
+            on_unlink: BlockingHandler(f),
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
+        }
     }
-}
 
-impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
-    StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        FLinked,
-        FSynced,
-        FUpdated,
-        FRemoved,
-        FClear,
-        FUnlink,
-    >
-{
-    /// Replace the handler that is called when the downlink connects.
-    pub fn on_linked<F>(
+    /// Replace the handler that is called when the downlink evicts an entry to stay within its
+    /// maximum number of entries.
+    pub fn on_evicted<F>(
         self,
         f: F,
-    ) -> StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        FnMutHandler<F>,
-        FSynced,
-        FUpdated,
-        FRemoved,
-        FClear,
-        FUnlink,
-    >
+    ) -> <Self as MapLifecycleHandlers>::WithOnEvicted<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnLinked,
+        FnMutHandler<F>: OnEvictShared<K, V, Shared>,
     {
         StatefulMapDownlinkLifecycle {
             _type: PhantomData,
             state: self.state,
-            on_linked: FnMutHandler(f),
+            on_linked: self.on_linked,
             on_synced: self.on_synced,
             on_update: self.on_update,
             on_removed: self.on_removed,
             on_clear: self.on_clear,
             on_unlink: self.on_unlink,
+            on_evicted: FnMutHandler(f),
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink connects with the specified synchronous
-    /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_linked_blocking<F>(
+    /// Replace the handler that is called when the downlink evicts an entry to stay within its
+    /// maximum number of entries with the specified synchronous closure. Running this closure will
+    /// block the task so it should complete quickly.
+    pub fn on_evicted_blocking<F>(
         self,
         f: F,
-    ) -> StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        BlockingHandler<F>,
-        FSynced,
-        FUpdated,
-        FRemoved,
-        FClear,
-        FUnlink,
-    >
+    ) -> <Self as MapLifecycleHandlers>::WithOnEvicted<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared) + Send,
+        F: FnMut(&mut Shared, K, &BTreeMap<K, V>, V) + Send,
     {
         StatefulMapDownlinkLifecycle {
             _type: PhantomData,
             state: self.state,
-            on_linked: BlockingHandler(f),
+            on_linked: self.on_linked,
             on_synced: self.on_synced,
             on_update: self.on_update,
             on_removed: self.on_removed,
             on_clear: self.on_clear,
             on_unlink: self.on_unlink,
+            on_evicted: BlockingHandler(f),
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink synchronizes.
-    pub fn on_synced<F>(
+    /// Replace the handler that is called before the downlink attempts to relink.
+    pub fn on_reconnecting<F>(
         self,
         f: F,
-    ) -> StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        FLinked,
-        FnMutHandler<F>,
-        FUpdated,
-        FRemoved,
-        FClear,
-        FUnlink,
-    >
+    ) -> <Self as MapLifecycleHandlers>::WithOnReconnecting<FnMutHandler<F>>
     where
-        FnMutHandler<F>: for<'a> OnSynced<BTreeMap<K, V>>,
+        FnMutHandler<F>: OnReconnecting,
     {
         StatefulMapDownlinkLifecycle {
             _type: PhantomData,
             state: self.state,
             on_linked: self.on_linked,
-            on_synced: FnMutHandler(f),
+            on_synced: self.on_synced,
             on_update: self.on_update,
             on_removed: self.on_removed,
             on_clear: self.on_clear,
             on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: FnMutHandler(f),
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink synchronizes with the specified
+    /// Replace the handler that is called before the downlink attempts to relink with the specified
     /// synchronous closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_synced_blocking<F>(
+    pub fn on_reconnecting_blocking<F>(
         self,
         f: F,
-    ) -> StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        FLinked,
-        BlockingHandler<F>,
-        FUpdated,
-        FRemoved,
-        FClear,
-        FUnlink,
-    >
+    ) -> <Self as MapLifecycleHandlers>::WithOnReconnecting<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared, &BTreeMap<K, V>) + Send,
+        F: FnMut(&mut Shared) + Send,
     {
         StatefulMapDownlinkLifecycle {
             _type: PhantomData,
             state: self.state,
             on_linked: self.on_linked,
-            on_synced: BlockingHandler(f),
+            on_synced: self.on_synced,
             on_update: self.on_update,
             on_removed: self.on_removed,
             on_clear: self.on_clear,
             on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: BlockingHandler(f),
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink updates a value.
-    pub fn on_update<F>(
+    /// Replace the handler that is called when the remote lane advises the downlink to back off.
+    pub fn on_advisory<F>(
         self,
         f: F,
-    ) -> StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        FLinked,
-        FSynced,
-        FnMutHandler<F>,
-        FRemoved,
-        FClear,
-        FUnlink,
-    >
+    ) -> <Self as MapLifecycleHandlers>::WithOnAdvisory<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnUpdate<K, V>,
+        FnMutHandler<F>: OnAdvisory,
     {
         StatefulMapDownlinkLifecycle {
             _type: PhantomData,
             state: self.state,
             on_linked: self.on_linked,
             on_synced: self.on_synced,
-            on_update: FnMutHandler(f),
+            on_update: self.on_update,
             on_removed: self.on_removed,
             on_clear: self.on_clear,
             on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: FnMutHandler(f),
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink updates a value with the specified
-    /// synchronous closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_update_blocking<F>(
+    /// Replace the handler that is called when the remote lane advises the downlink to back off with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_advisory_blocking<F>(
         self,
         f: F,
-    ) -> StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        FLinked,
-        FSynced,
-        BlockingHandler<F>,
-        FRemoved,
-        FClear,
-        FUnlink,
-    >
+    ) -> <Self as MapLifecycleHandlers>::WithOnAdvisory<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared, K, &BTreeMap<K, V>, Option<V>, &V) + Send,
+        F: FnMut(&mut Shared, LinkAdvice) + Send,
     {
         StatefulMapDownlinkLifecycle {
             _type: PhantomData,
             state: self.state,
             on_linked: self.on_linked,
             on_synced: self.on_synced,
-            on_update: BlockingHandler(f),
+            on_update: self.on_update,
             on_removed: self.on_removed,
             on_clear: self.on_clear,
             on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: BlockingHandler(f),
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink removes a key.
-    pub fn on_removed<F>(
+    /// Replace the handler that is called when the remote agent stops and unlinks the downlink.
+    pub fn on_node_stopped<F>(
         self,
         f: F,
-    ) -> StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        FLinked,
-        FSynced,
-        FUpdated,
-        FnMutHandler<F>,
-        FClear,
-        FUnlink,
-    >
+    ) -> <Self as MapLifecycleHandlers>::WithOnNodeStopped<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnRemove<K, V>,
+        FnMutHandler<F>: OnNodeStopped,
     {
         StatefulMapDownlinkLifecycle {
             _type: PhantomData,
@@ -2165,30 +7679,27 @@ impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
             on_linked: self.on_linked,
             on_synced: self.on_synced,
             on_update: self.on_update,
-            on_removed: FnMutHandler(f),
+            on_removed: self.on_removed,
             on_clear: self.on_clear,
             on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: FnMutHandler(f),
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink removes a key with the specified
-    /// synchronous closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_removed_blocking<F>(
+    /// Replace the handler that is called when the remote agent stops and unlinks the downlink with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_node_stopped_blocking<F>(
         self,
         f: F,
-    ) -> StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        FLinked,
-        FSynced,
-        FUpdated,
-        BlockingHandler<F>,
-        FClear,
-        FUnlink,
-    >
+    ) -> <Self as MapLifecycleHandlers>::WithOnNodeStopped<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared, K, &BTreeMap<K, V>, V) + Send,
+        F: FnMut(&mut Shared, NodeStopped) + Send,
     {
         StatefulMapDownlinkLifecycle {
             _type: PhantomData,
@@ -2196,29 +7707,23 @@ impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
             on_linked: self.on_linked,
             on_synced: self.on_synced,
             on_update: self.on_update,
-            on_removed: BlockingHandler(f),
+            on_removed: self.on_removed,
             on_clear: self.on_clear,
             on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: BlockingHandler(f),
+            on_failed: self.on_failed,
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink clears.
-    pub fn on_clear<F>(
-        self,
-        f: F,
-    ) -> StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        FLinked,
-        FSynced,
-        FUpdated,
-        FRemoved,
-        FnMutHandler<F>,
-        FUnlink,
-    >
+    /// Replace the handler that is called when the remote refuses to link the downlink because its
+    /// node or lane does not exist.
+    pub fn on_failed<F>(self, f: F) -> <Self as MapLifecycleHandlers>::WithOnFailed<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnRemove<K, V>,
+        FnMutHandler<F>: OnFailedShared<Shared>,
     {
         StatefulMapDownlinkLifecycle {
             _type: PhantomData,
@@ -2227,29 +7732,26 @@ impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
             on_synced: self.on_synced,
             on_update: self.on_update,
             on_removed: self.on_removed,
-            on_clear: FnMutHandler(f),
+            on_clear: self.on_clear,
             on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: FnMutHandler(f),
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink clears with the specified synchronous
-    /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_clear_blocking<F>(
+    /// Replace the handler that is called when the remote refuses to link the downlink because its
+    /// node or lane does not exist with the specified synchronous closure. Running this closure
+    /// will block the task so it should complete quickly.
+    pub fn on_failed_blocking<F>(
         self,
         f: F,
-    ) -> StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        FLinked,
-        FSynced,
-        FUpdated,
-        FRemoved,
-        BlockingHandler<F>,
-        FUnlink,
-    >
+    ) -> <Self as MapLifecycleHandlers>::WithOnFailed<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared, BTreeMap<K, V>) + Send,
+        F: FnMut(&mut Shared, LinkFailure) + Send,
     {
         StatefulMapDownlinkLifecycle {
             _type: PhantomData,
@@ -2258,28 +7760,24 @@ impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
             on_synced: self.on_synced,
             on_update: self.on_update,
             on_removed: self.on_removed,
-            on_clear: BlockingHandler(f),
+            on_clear: self.on_clear,
             on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: BlockingHandler(f),
+            on_resynced: self.on_resynced,
         }
     }
 
-    /// Replace the handler that is called when the downlink unlinks.
-    pub fn on_unlink<F>(
+    /// Replace the handler that is called when the downlink synchronizes again after relinking.
+    pub fn on_resynced<F>(
         self,
         f: F,
-    ) -> StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        FLinked,
-        FSynced,
-        FUpdated,
-        FRemoved,
-        FClear,
-        FnMutHandler<F>,
-    >
+    ) -> <Self as MapLifecycleHandlers>::WithOnResynced<FnMutHandler<F>>
     where
-        FnMutHandler<F>: OnUnlinked,
+        FnMutHandler<F>: for<'a> OnResynced<BTreeMap<K, V>>,
     {
         StatefulMapDownlinkLifecycle {
             _type: PhantomData,
@@ -2289,28 +7787,25 @@ impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
             on_update: self.on_update,
             on_removed: self.on_removed,
             on_clear: self.on_clear,
-            on_unlink: FnMutHandler(f),
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: FnMutHandler(f),
         }
     }
 
-    /// Replace the handler that is called when the downlink unlinks with the specified synchronous
-    /// closure. Running this closure will block the task so it should complete quickly.
-    pub fn on_unlink_blocking<F>(
+    /// Replace the handler that is called when the downlink synchronizes again after relinking with
+    /// the specified synchronous closure. Running this closure will block the task so it should
+    /// complete quickly.
+    pub fn on_resynced_blocking<F>(
         self,
         f: F,
-    ) -> StatefulMapDownlinkLifecycle<
-        K,
-        V,
-        Shared,
-        FLinked,
-        FSynced,
-        FUpdated,
-        FRemoved,
-        FClear,
-        BlockingHandler<F>,
-    >
+    ) -> <Self as MapLifecycleHandlers>::WithOnResynced<BlockingHandler<F>>
     where
-        F: FnMut(&mut Shared) + Send,
+        F: FnMut(&mut Shared, &BTreeMap<K, V>) + Send,
     {
         StatefulMapDownlinkLifecycle {
             _type: PhantomData,
@@ -2320,7 +7815,13 @@ impl<K, V, Shared, FLinked, FSynced, FUpdated, FRemoved, FClear, FUnlink>
             on_update: self.on_update,
             on_removed: self.on_removed,
             on_clear: self.on_clear,
-            on_unlink: BlockingHandler(f),
+            on_unlink: self.on_unlink,
+            on_evicted: self.on_evicted,
+            on_reconnecting: self.on_reconnecting,
+            on_advisory: self.on_advisory,
+            on_node_stopped: self.on_node_stopped,
+            on_failed: self.on_failed,
+            on_resynced: BlockingHandler(f),
         }
     }
 }
@@ -0,0 +1,174 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use futures::future::{ready, Ready};
+use std::future::Future;
+use swimos_api::agent::LinkAdvice;
+use swimos_utilities::handlers::{BlockingHandler, FnMutHandler, NoHandler, WithShared};
+
+use super::handler_fn::SharedHandlerFn1;
+
+/// Trait for event handlers to be called when the remote lane advises a downlink to back off, as
+/// its server is approaching its limits.
+pub trait OnAdvisory: Send {
+    type OnAdvisoryFut<'a>: Future<Output = ()> + Send + 'a
+    where
+        Self: 'a;
+
+    fn on_advisory(&mut self, advice: LinkAdvice) -> Self::OnAdvisoryFut<'_>;
+}
+
+/// Trait for event handlers, that share state with other handlers, called when the remote lane
+/// advises a downlink to back off, as its server is approaching its limits.
+pub trait OnAdvisoryShared<Shared>: Send {
+    type OnAdvisoryFut<'a>: Future<Output = ()> + Send + 'a
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_advisory<'a>(
+        &'a mut self,
+        shared: &'a mut Shared,
+        advice: LinkAdvice,
+    ) -> Self::OnAdvisoryFut<'a>;
+}
+
+impl OnAdvisory for NoHandler {
+    type OnAdvisoryFut<'a>
+        = Ready<()>
+    where
+        Self: 'a;
+
+    fn on_advisory(&mut self, _advice: LinkAdvice) -> Self::OnAdvisoryFut<'_> {
+        ready(())
+    }
+}
+
+impl<F, Fut> OnAdvisory for FnMutHandler<F>
+where
+    F: FnMut(LinkAdvice) -> Fut + Send,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    type OnAdvisoryFut<'a>
+        = Fut
+    where
+        Self: 'a;
+
+    fn on_advisory(&mut self, advice: LinkAdvice) -> Self::OnAdvisoryFut<'_> {
+        let FnMutHandler(f) = self;
+        f(advice)
+    }
+}
+
+impl<Shared> OnAdvisoryShared<Shared> for NoHandler {
+    type OnAdvisoryFut<'a>
+        = Ready<()>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_advisory<'a>(
+        &'a mut self,
+        _shared: &'a mut Shared,
+        _advice: LinkAdvice,
+    ) -> Self::OnAdvisoryFut<'a> {
+        ready(())
+    }
+}
+
+impl<F, Shared> OnAdvisoryShared<Shared> for FnMutHandler<F>
+where
+    F: for<'a> SharedHandlerFn1<'a, Shared, LinkAdvice> + Send,
+{
+    type OnAdvisoryFut<'a>
+        = <F as SharedHandlerFn1<'a, Shared, LinkAdvice>>::Fut
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_advisory<'a>(
+        &'a mut self,
+        shared: &'a mut Shared,
+        advice: LinkAdvice,
+    ) -> Self::OnAdvisoryFut<'a> {
+        let FnMutHandler(f) = self;
+        f.apply(shared, advice)
+    }
+}
+
+impl<H, Shared> OnAdvisoryShared<Shared> for WithShared<H>
+where
+    H: OnAdvisory,
+{
+    type OnAdvisoryFut<'a>
+        = H::OnAdvisoryFut<'a>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_advisory<'a>(
+        &'a mut self,
+        _shared: &'a mut Shared,
+        advice: LinkAdvice,
+    ) -> Self::OnAdvisoryFut<'a> {
+        self.0.on_advisory(advice)
+    }
+}
+
+impl<F> OnAdvisory for BlockingHandler<F>
+where
+    F: FnMut(LinkAdvice) + Send,
+{
+    type OnAdvisoryFut<'a>
+        = Ready<()>
+    where
+        Self: 'a;
+
+    fn on_advisory(&mut self, advice: LinkAdvice) -> Self::OnAdvisoryFut<'_> {
+        let BlockingHandler(f) = self;
+        f(advice);
+        ready(())
+    }
+}
+
+impl<Shared, F> OnAdvisoryShared<Shared> for BlockingHandler<F>
+where
+    F: for<'a> FnMut(&'a mut Shared, LinkAdvice) + Send,
+{
+    type OnAdvisoryFut<'a>
+        = Ready<()>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_advisory<'a>(
+        &'a mut self,
+        shared: &'a mut Shared,
+        advice: LinkAdvice,
+    ) -> Self::OnAdvisoryFut<'a> {
+        let BlockingHandler(f) = self;
+        f(shared, advice);
+        ready(())
+    }
+}
+
+#[macro_export]
+macro_rules! on_advisory_handler {
+    ($s:ty, |$shared:ident, $advice:ident| $body:expr) => {{
+        async fn handler($shared: &mut $s, $advice: $crate::lifecycle::LinkAdvice) {
+            $body
+        }
+        handler
+    }};
+}
@@ -0,0 +1,175 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use futures::future::{ready, Ready};
+use std::future::Future;
+use swimos_api::agent::LinkFailure;
+use swimos_utilities::handlers::{BlockingHandler, FnMutHandler, NoHandler, WithShared};
+
+use super::handler_fn::SharedHandlerFn1;
+
+/// Trait for event handlers to be called when the remote refuses to link a downlink because its
+/// node or lane does not exist. This is always followed by a call to the `on_unlinked` handler.
+pub trait OnFailed: Send {
+    type OnFailedFut<'a>: Future<Output = ()> + Send + 'a
+    where
+        Self: 'a;
+
+    fn on_failed(&mut self, failure: LinkFailure) -> Self::OnFailedFut<'_>;
+}
+
+/// Trait for event handlers, that share state with other handlers, called when the remote refuses
+/// to link a downlink because its node or lane does not exist. This is always followed by a call to
+/// the `on_unlinked` handler.
+pub trait OnFailedShared<Shared>: Send {
+    type OnFailedFut<'a>: Future<Output = ()> + Send + 'a
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_failed<'a>(
+        &'a mut self,
+        shared: &'a mut Shared,
+        failure: LinkFailure,
+    ) -> Self::OnFailedFut<'a>;
+}
+
+impl OnFailed for NoHandler {
+    type OnFailedFut<'a>
+        = Ready<()>
+    where
+        Self: 'a;
+
+    fn on_failed(&mut self, _failure: LinkFailure) -> Self::OnFailedFut<'_> {
+        ready(())
+    }
+}
+
+impl<F, Fut> OnFailed for FnMutHandler<F>
+where
+    F: FnMut(LinkFailure) -> Fut + Send,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    type OnFailedFut<'a>
+        = Fut
+    where
+        Self: 'a;
+
+    fn on_failed(&mut self, failure: LinkFailure) -> Self::OnFailedFut<'_> {
+        let FnMutHandler(f) = self;
+        f(failure)
+    }
+}
+
+impl<Shared> OnFailedShared<Shared> for NoHandler {
+    type OnFailedFut<'a>
+        = Ready<()>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_failed<'a>(
+        &'a mut self,
+        _shared: &'a mut Shared,
+        _failure: LinkFailure,
+    ) -> Self::OnFailedFut<'a> {
+        ready(())
+    }
+}
+
+impl<F, Shared> OnFailedShared<Shared> for FnMutHandler<F>
+where
+    F: for<'a> SharedHandlerFn1<'a, Shared, LinkFailure> + Send,
+{
+    type OnFailedFut<'a>
+        = <F as SharedHandlerFn1<'a, Shared, LinkFailure>>::Fut
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_failed<'a>(
+        &'a mut self,
+        shared: &'a mut Shared,
+        failure: LinkFailure,
+    ) -> Self::OnFailedFut<'a> {
+        let FnMutHandler(f) = self;
+        f.apply(shared, failure)
+    }
+}
+
+impl<H, Shared> OnFailedShared<Shared> for WithShared<H>
+where
+    H: OnFailed,
+{
+    type OnFailedFut<'a>
+        = H::OnFailedFut<'a>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_failed<'a>(
+        &'a mut self,
+        _shared: &'a mut Shared,
+        failure: LinkFailure,
+    ) -> Self::OnFailedFut<'a> {
+        self.0.on_failed(failure)
+    }
+}
+
+impl<F> OnFailed for BlockingHandler<F>
+where
+    F: FnMut(LinkFailure) + Send,
+{
+    type OnFailedFut<'a>
+        = Ready<()>
+    where
+        Self: 'a;
+
+    fn on_failed(&mut self, failure: LinkFailure) -> Self::OnFailedFut<'_> {
+        let BlockingHandler(f) = self;
+        f(failure);
+        ready(())
+    }
+}
+
+impl<Shared, F> OnFailedShared<Shared> for BlockingHandler<F>
+where
+    F: for<'a> FnMut(&'a mut Shared, LinkFailure) + Send,
+{
+    type OnFailedFut<'a>
+        = Ready<()>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_failed<'a>(
+        &'a mut self,
+        shared: &'a mut Shared,
+        failure: LinkFailure,
+    ) -> Self::OnFailedFut<'a> {
+        let BlockingHandler(f) = self;
+        f(shared, failure);
+        ready(())
+    }
+}
+
+#[macro_export]
+macro_rules! on_failed_handler {
+    ($s:ty, |$shared:ident, $failure:ident| $body:expr) => {{
+        async fn handler($shared: &mut $s, $failure: $crate::lifecycle::LinkFailure) {
+            $body
+        }
+        handler
+    }};
+}
@@ -0,0 +1,174 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use futures::future::{ready, Ready};
+use std::future::Future;
+use swimos_api::agent::NodeStopped;
+use swimos_utilities::handlers::{BlockingHandler, FnMutHandler, NoHandler, WithShared};
+
+use super::handler_fn::SharedHandlerFn1;
+
+/// Trait for event handlers to be called when the remote agent stops and unlinks a downlink. This
+/// is always followed by a call to the `on_unlinked` handler.
+pub trait OnNodeStopped: Send {
+    type OnNodeStoppedFut<'a>: Future<Output = ()> + Send + 'a
+    where
+        Self: 'a;
+
+    fn on_node_stopped(&mut self, notice: NodeStopped) -> Self::OnNodeStoppedFut<'_>;
+}
+
+/// Trait for event handlers, that share state with other handlers, called when the remote agent
+/// stops and unlinks a downlink. This is always followed by a call to the `on_unlinked` handler.
+pub trait OnNodeStoppedShared<Shared>: Send {
+    type OnNodeStoppedFut<'a>: Future<Output = ()> + Send + 'a
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_node_stopped<'a>(
+        &'a mut self,
+        shared: &'a mut Shared,
+        notice: NodeStopped,
+    ) -> Self::OnNodeStoppedFut<'a>;
+}
+
+impl OnNodeStopped for NoHandler {
+    type OnNodeStoppedFut<'a>
+        = Ready<()>
+    where
+        Self: 'a;
+
+    fn on_node_stopped(&mut self, _notice: NodeStopped) -> Self::OnNodeStoppedFut<'_> {
+        ready(())
+    }
+}
+
+impl<F, Fut> OnNodeStopped for FnMutHandler<F>
+where
+    F: FnMut(NodeStopped) -> Fut + Send,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    type OnNodeStoppedFut<'a>
+        = Fut
+    where
+        Self: 'a;
+
+    fn on_node_stopped(&mut self, notice: NodeStopped) -> Self::OnNodeStoppedFut<'_> {
+        let FnMutHandler(f) = self;
+        f(notice)
+    }
+}
+
+impl<Shared> OnNodeStoppedShared<Shared> for NoHandler {
+    type OnNodeStoppedFut<'a>
+        = Ready<()>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_node_stopped<'a>(
+        &'a mut self,
+        _shared: &'a mut Shared,
+        _notice: NodeStopped,
+    ) -> Self::OnNodeStoppedFut<'a> {
+        ready(())
+    }
+}
+
+impl<F, Shared> OnNodeStoppedShared<Shared> for FnMutHandler<F>
+where
+    F: for<'a> SharedHandlerFn1<'a, Shared, NodeStopped> + Send,
+{
+    type OnNodeStoppedFut<'a>
+        = <F as SharedHandlerFn1<'a, Shared, NodeStopped>>::Fut
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_node_stopped<'a>(
+        &'a mut self,
+        shared: &'a mut Shared,
+        notice: NodeStopped,
+    ) -> Self::OnNodeStoppedFut<'a> {
+        let FnMutHandler(f) = self;
+        f.apply(shared, notice)
+    }
+}
+
+impl<H, Shared> OnNodeStoppedShared<Shared> for WithShared<H>
+where
+    H: OnNodeStopped,
+{
+    type OnNodeStoppedFut<'a>
+        = H::OnNodeStoppedFut<'a>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_node_stopped<'a>(
+        &'a mut self,
+        _shared: &'a mut Shared,
+        notice: NodeStopped,
+    ) -> Self::OnNodeStoppedFut<'a> {
+        self.0.on_node_stopped(notice)
+    }
+}
+
+impl<F> OnNodeStopped for BlockingHandler<F>
+where
+    F: FnMut(NodeStopped) + Send,
+{
+    type OnNodeStoppedFut<'a>
+        = Ready<()>
+    where
+        Self: 'a;
+
+    fn on_node_stopped(&mut self, notice: NodeStopped) -> Self::OnNodeStoppedFut<'_> {
+        let BlockingHandler(f) = self;
+        f(notice);
+        ready(())
+    }
+}
+
+impl<Shared, F> OnNodeStoppedShared<Shared> for BlockingHandler<F>
+where
+    F: for<'a> FnMut(&'a mut Shared, NodeStopped) + Send,
+{
+    type OnNodeStoppedFut<'a>
+        = Ready<()>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_node_stopped<'a>(
+        &'a mut self,
+        shared: &'a mut Shared,
+        notice: NodeStopped,
+    ) -> Self::OnNodeStoppedFut<'a> {
+        let BlockingHandler(f) = self;
+        f(shared, notice);
+        ready(())
+    }
+}
+
+#[macro_export]
+macro_rules! on_node_stopped_handler {
+    ($s:ty, |$shared:ident, $notice:ident| $body:expr) => {{
+        async fn handler($shared: &mut $s, $notice: $crate::lifecycle::NodeStopped) {
+            $body
+        }
+        handler
+    }};
+}
@@ -0,0 +1,152 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use futures::future::{ready, Ready};
+use std::future::Future;
+use swimos_utilities::handlers::{BlockingHandler, FnMutHandler, NoHandler, WithShared};
+
+use super::handler_fn::SharedHandlerFn0;
+
+/// Trait for event handlers to be called before a downlink attempts to relink.
+pub trait OnReconnecting: Send {
+    type OnReconnectingFut<'a>: Future<Output = ()> + Send + 'a
+    where
+        Self: 'a;
+
+    fn on_reconnecting(&mut self) -> Self::OnReconnectingFut<'_>;
+}
+
+/// Trait for event handlers, that share state with other handlers, called before a downlink
+/// attempts to relink.
+pub trait OnReconnectingShared<Shared>: Send {
+    type OnReconnectingFut<'a>: Future<Output = ()> + Send + 'a
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_reconnecting<'a>(&'a mut self, shared: &'a mut Shared) -> Self::OnReconnectingFut<'a>;
+}
+
+impl OnReconnecting for NoHandler {
+    type OnReconnectingFut<'a>
+        = Ready<()>
+    where
+        Self: 'a;
+
+    fn on_reconnecting(&mut self) -> Self::OnReconnectingFut<'_> {
+        ready(())
+    }
+}
+
+impl<F, Fut> OnReconnecting for FnMutHandler<F>
+where
+    F: FnMut() -> Fut + Send,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    type OnReconnectingFut<'a>
+        = Fut
+    where
+        Self: 'a;
+
+    fn on_reconnecting(&mut self) -> Self::OnReconnectingFut<'_> {
+        let FnMutHandler(f) = self;
+        f()
+    }
+}
+
+impl<Shared> OnReconnectingShared<Shared> for NoHandler {
+    type OnReconnectingFut<'a>
+        = Ready<()>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_reconnecting<'a>(&'a mut self, _shared: &'a mut Shared) -> Self::OnReconnectingFut<'a> {
+        ready(())
+    }
+}
+
+impl<F, Shared> OnReconnectingShared<Shared> for FnMutHandler<F>
+where
+    F: for<'a> SharedHandlerFn0<'a, Shared> + Send,
+{
+    type OnReconnectingFut<'a>
+        = <F as SharedHandlerFn0<'a, Shared>>::Fut
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_reconnecting<'a>(&'a mut self, shared: &'a mut Shared) -> Self::OnReconnectingFut<'a> {
+        let FnMutHandler(f) = self;
+        f.apply(shared)
+    }
+}
+
+impl<H, Shared> OnReconnectingShared<Shared> for WithShared<H>
+where
+    H: OnReconnecting,
+{
+    type OnReconnectingFut<'a>
+        = H::OnReconnectingFut<'a>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_reconnecting<'a>(&'a mut self, _state: &'a mut Shared) -> Self::OnReconnectingFut<'a> {
+        self.0.on_reconnecting()
+    }
+}
+
+impl<F> OnReconnecting for BlockingHandler<F>
+where
+    F: FnMut() + Send,
+{
+    type OnReconnectingFut<'a>
+        = Ready<()>
+    where
+        Self: 'a;
+
+    fn on_reconnecting(&mut self) -> Self::OnReconnectingFut<'_> {
+        let BlockingHandler(f) = self;
+        f();
+        ready(())
+    }
+}
+
+impl<Shared, F> OnReconnectingShared<Shared> for BlockingHandler<F>
+where
+    F: for<'a> FnMut(&'a mut Shared) + Send,
+{
+    type OnReconnectingFut<'a>
+        = Ready<()>
+    where
+        Self: 'a,
+        Shared: 'a;
+
+    fn on_reconnecting<'a>(&'a mut self, shared: &'a mut Shared) -> Self::OnReconnectingFut<'a> {
+        let BlockingHandler(f) = self;
+        f(shared);
+        ready(())
+    }
+}
+
+#[macro_export]
+macro_rules! on_reconnecting_handler {
+    ($s:ty, |$shared:ident| $body:expr) => {{
+        async fn handler($shared: &mut $s) {
+            $body
+        }
+        handler
+    }};
+}
@@ -15,9 +15,11 @@
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::marker::PhantomData;
+use std::num::NonZeroUsize;
 
 use swimos_agent_protocol::MapOperation;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{mpsc, oneshot, watch};
+use uuid::Uuid;
 
 use crate::model::lifecycle::{BasicMapDownlinkLifecycle, MapDownlinkLifecycle};
 use lifecycle::{
@@ -26,6 +28,7 @@ use lifecycle::{
 };
 
 pub mod lifecycle;
+pub mod stream;
 
 #[derive(Debug, thiserror::Error, Copy, Clone, Eq, PartialEq)]
 #[error("Downlink not yet synced")]
@@ -39,6 +42,32 @@ pub struct ValueDownlinkSet<T> {
 pub struct ValueDownlinkModel<T, LC> {
     pub handle: mpsc::Receiver<ValueDownlinkSet<T>>,
     pub lifecycle: LC,
+    /// If present, the state of the downlink will be published to this channel as it changes.
+    pub state: Option<watch::Sender<ValueDownlinkState<T>>>,
+}
+
+/// The state of a value downlink, as published by the downlink task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueDownlinkState<T> {
+    /// Whether the downlink is currently linked to the lane.
+    pub linked: bool,
+    /// Whether the downlink has synced with the lane since it was last linked.
+    pub synced: bool,
+    /// The last value that the downlink received after it synced with the lane.
+    pub value: Option<T>,
+    /// The origin of the command that caused the last value, if the lane tracks the origins of commands.
+    pub origin: Option<Uuid>,
+}
+
+impl<T> Default for ValueDownlinkState<T> {
+    fn default() -> Self {
+        ValueDownlinkState {
+            linked: false,
+            synced: false,
+            value: None,
+            origin: None,
+        }
+    }
 }
 
 pub struct EventDownlinkModel<T, LC> {
@@ -48,7 +77,19 @@ pub struct EventDownlinkModel<T, LC> {
 
 impl<T, LC> ValueDownlinkModel<T, LC> {
     pub fn new(handle: mpsc::Receiver<ValueDownlinkSet<T>>, lifecycle: LC) -> Self {
-        ValueDownlinkModel { handle, lifecycle }
+        ValueDownlinkModel {
+            handle,
+            lifecycle,
+            state: None,
+        }
+    }
+
+    /// Publish the state of the downlink to a watch channel as it changes.
+    pub fn with_state_watch(self, state: watch::Sender<ValueDownlinkState<T>>) -> Self {
+        ValueDownlinkModel {
+            state: Some(state),
+            ..self
+        }
     }
 }
 
@@ -64,6 +105,9 @@ impl<T, LC> EventDownlinkModel<T, LC> {
 pub struct MapDownlinkModel<K, V, LC> {
     pub actions: mpsc::Receiver<MapOperation<K, V>>,
     pub lifecycle: LC,
+    /// The maximum number of entries that the downlink will hold locally. When this is exceeded,
+    /// the entries with the lowest keys will be evicted.
+    pub max_entries: Option<NonZeroUsize>,
 }
 
 impl<K, V, LC> MapDownlinkModel<K, V, LC> {
@@ -71,7 +115,19 @@ impl<K, V, LC> MapDownlinkModel<K, V, LC> {
         actions: mpsc::Receiver<MapOperation<K, V>>,
         lifecycle: LC,
     ) -> MapDownlinkModel<K, V, LC> {
-        MapDownlinkModel { actions, lifecycle }
+        MapDownlinkModel {
+            actions,
+            lifecycle,
+            max_entries: None,
+        }
+    }
+
+    /// Bound the number of entries that the downlink will hold locally.
+    pub fn with_max_entries(self, max_entries: NonZeroUsize) -> Self {
+        MapDownlinkModel {
+            max_entries: Some(max_entries),
+            ..self
+        }
     }
 }
 
@@ -84,10 +140,7 @@ pub type DefaultMapDownlinkModel<K, V> = MapDownlinkModel<K, V, BasicMapDownlink
 pub fn value_downlink<T>(
     handle: mpsc::Receiver<ValueDownlinkSet<T>>,
 ) -> DefaultValueDownlinkModel<T> {
-    ValueDownlinkModel {
-        handle,
-        lifecycle: Default::default(),
-    }
+    ValueDownlinkModel::new(handle, Default::default())
 }
 
 pub fn event_downlink<T>() -> DefaultEventDownlinkModel<T> {
@@ -168,10 +221,15 @@ where
         F: Fn(LC) -> LC2,
         LC2: ValueDownlinkLifecycle<T>,
     {
-        let ValueDownlinkModel { handle, lifecycle } = self;
+        let ValueDownlinkModel {
+            handle,
+            lifecycle,
+            state,
+        } = self;
         ValueDownlinkModel {
             handle,
             lifecycle: f(lifecycle),
+            state,
         }
     }
 }
@@ -203,11 +261,16 @@ where
         F: Fn(LC) -> LC2,
         LC2: MapDownlinkLifecycle<K, V>,
     {
-        let MapDownlinkModel { actions, lifecycle } = self;
+        let MapDownlinkModel {
+            actions,
+            lifecycle,
+            max_entries,
+        } = self;
 
         MapDownlinkModel {
             actions,
             lifecycle: f(lifecycle),
+            max_entries,
         }
     }
 }
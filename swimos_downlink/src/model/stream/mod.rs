@@ -0,0 +1,432 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::future::BoxFuture;
+use futures::{FutureExt, Stream};
+use tokio::sync::mpsc;
+
+use crate::model::lifecycle::{
+    LinkAdvice, LinkFailure, NodeStopped, OnAdvisory, OnClear, OnEvent, OnEvict, OnFailed,
+    OnLinked, OnNodeStopped, OnReconnecting, OnRemove, OnResynced, OnSet, OnSynced, OnUnlinked,
+    OnUpdate,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The events of a value downlink, as delivered by a [`DownlinkEventStream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueDownlinkEvent<T> {
+    Linked,
+    Synced(T),
+    /// The value of the downlink has changed.
+    Set {
+        previous: Option<T>,
+        value: T,
+    },
+    Unlinked,
+    Reconnecting,
+    Resynced(T),
+    Advisory(LinkAdvice),
+    NodeStopped(NodeStopped),
+    Failed(LinkFailure),
+}
+
+/// The events of a map downlink, as delivered by a [`DownlinkEventStream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapDownlinkEvent<K, V> {
+    Linked,
+    Synced(BTreeMap<K, V>),
+    Updated {
+        key: K,
+        previous: Option<V>,
+        value: V,
+    },
+    Removed {
+        key: K,
+        value: V,
+    },
+    Cleared(BTreeMap<K, V>),
+    /// An entry was evicted as the downlink exceeded its maximum number of entries.
+    Evicted {
+        key: K,
+        value: V,
+    },
+    Unlinked,
+    Reconnecting,
+    Resynced(BTreeMap<K, V>),
+    Advisory(LinkAdvice),
+    NodeStopped(NodeStopped),
+    Failed(LinkFailure),
+}
+
+/// The events of an event downlink, as delivered by a [`DownlinkEventStream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventDownlinkEvent<T> {
+    Linked,
+    Event(T),
+    Unlinked,
+    Reconnecting,
+    Advisory(LinkAdvice),
+    NodeStopped(NodeStopped),
+    Failed(LinkFailure),
+}
+
+/// The events that are common to all kinds of downlink.
+pub trait DownlinkStreamEvent: Send + 'static {
+    fn linked() -> Self;
+
+    fn unlinked() -> Self;
+
+    fn reconnecting() -> Self;
+
+    fn advisory(advice: LinkAdvice) -> Self;
+
+    fn node_stopped(notice: NodeStopped) -> Self;
+
+    fn failed(failure: LinkFailure) -> Self;
+}
+
+macro_rules! stream_event {
+    ($name:ident<$($param:ident),*>) => {
+        impl<$($param: Send + 'static),*> DownlinkStreamEvent for $name<$($param),*> {
+            fn linked() -> Self {
+                $name::Linked
+            }
+
+            fn unlinked() -> Self {
+                $name::Unlinked
+            }
+
+            fn reconnecting() -> Self {
+                $name::Reconnecting
+            }
+
+            fn advisory(advice: LinkAdvice) -> Self {
+                $name::Advisory(advice)
+            }
+
+            fn node_stopped(notice: NodeStopped) -> Self {
+                $name::NodeStopped(notice)
+            }
+
+            fn failed(failure: LinkFailure) -> Self {
+                $name::Failed(failure)
+            }
+        }
+    };
+}
+
+stream_event!(ValueDownlinkEvent<T>);
+stream_event!(MapDownlinkEvent<K, V>);
+stream_event!(EventDownlinkEvent<T>);
+
+/// Create a downlink lifecycle that forwards the events of the downlink to a stream, rather than
+/// handling them with callbacks.
+///
+/// The lifecycle waits for space in the buffer before forwarding each event so, if the consumer
+/// of the stream falls behind, the downlink will stop reading from its connection until the
+/// consumer catches up. If the stream is dropped, any subsequent events are discarded.
+///
+/// # Arguments
+/// * `buffer_size` - The number of events that can be buffered before the downlink is blocked.
+pub fn downlink_event_stream<E>(
+    buffer_size: NonZeroUsize,
+) -> (DownlinkEventSender<E>, DownlinkEventStream<E>) {
+    let (tx, rx) = mpsc::channel(buffer_size.get());
+    (DownlinkEventSender { tx }, DownlinkEventStream { rx })
+}
+
+/// A downlink lifecycle that forwards the events of the downlink to a [`DownlinkEventStream`].
+/// See [`downlink_event_stream`].
+#[derive(Debug)]
+pub struct DownlinkEventSender<E> {
+    tx: mpsc::Sender<E>,
+}
+
+impl<E: Send + 'static> DownlinkEventSender<E> {
+    fn forward(&self, event: E) -> BoxFuture<'static, ()> {
+        let tx = self.tx.clone();
+        async move {
+            // If the stream has been dropped there is nobody to observe the event.
+            let _ = tx.send(event).await;
+        }
+        .boxed()
+    }
+}
+
+/// A stream of the events of a downlink. See [`downlink_event_stream`].
+#[derive(Debug)]
+pub struct DownlinkEventStream<E> {
+    rx: mpsc::Receiver<E>,
+}
+
+impl<E> Stream for DownlinkEventStream<E> {
+    type Item = E;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().rx.poll_recv(cx)
+    }
+}
+
+impl<E: DownlinkStreamEvent> OnLinked for DownlinkEventSender<E> {
+    type OnLinkedFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a;
+
+    fn on_linked(&mut self) -> Self::OnLinkedFut<'_> {
+        self.forward(E::linked())
+    }
+}
+
+impl<E: DownlinkStreamEvent> OnUnlinked for DownlinkEventSender<E> {
+    type OnUnlinkedFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a;
+
+    fn on_unlinked(&mut self) -> Self::OnUnlinkedFut<'_> {
+        self.forward(E::unlinked())
+    }
+}
+
+impl<E: DownlinkStreamEvent> OnReconnecting for DownlinkEventSender<E> {
+    type OnReconnectingFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a;
+
+    fn on_reconnecting(&mut self) -> Self::OnReconnectingFut<'_> {
+        self.forward(E::reconnecting())
+    }
+}
+
+impl<E: DownlinkStreamEvent> OnAdvisory for DownlinkEventSender<E> {
+    type OnAdvisoryFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a;
+
+    fn on_advisory(&mut self, advice: LinkAdvice) -> Self::OnAdvisoryFut<'_> {
+        self.forward(E::advisory(advice))
+    }
+}
+
+impl<E: DownlinkStreamEvent> OnNodeStopped for DownlinkEventSender<E> {
+    type OnNodeStoppedFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a;
+
+    fn on_node_stopped(&mut self, notice: NodeStopped) -> Self::OnNodeStoppedFut<'_> {
+        self.forward(E::node_stopped(notice))
+    }
+}
+
+impl<E: DownlinkStreamEvent> OnFailed for DownlinkEventSender<E> {
+    type OnFailedFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a;
+
+    fn on_failed(&mut self, failure: LinkFailure) -> Self::OnFailedFut<'_> {
+        self.forward(E::failed(failure))
+    }
+}
+
+impl<T: Clone + Send + 'static> OnSynced<T> for DownlinkEventSender<ValueDownlinkEvent<T>> {
+    type OnSyncedFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn on_synced<'a>(&'a mut self, value: &'a T) -> Self::OnSyncedFut<'a> {
+        self.forward(ValueDownlinkEvent::Synced(value.clone()))
+    }
+}
+
+impl<T: Send + 'static> OnEvent<T> for DownlinkEventSender<ValueDownlinkEvent<T>> {
+    type OnEventFut<'a> = futures::future::Ready<()>
+    where
+        Self: 'a,
+        T: 'a;
+
+    // Changes to the value are reported by `on_set`, which also provides the previous value.
+    fn on_event<'a>(&'a mut self, _value: &'a T) -> Self::OnEventFut<'a> {
+        futures::future::ready(())
+    }
+}
+
+impl<T: Clone + Send + 'static> OnSet<T> for DownlinkEventSender<ValueDownlinkEvent<T>> {
+    type OnSetFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn on_set<'a>(&'a mut self, existing: Option<&'a T>, new_value: &'a T) -> Self::OnSetFut<'a> {
+        self.forward(ValueDownlinkEvent::Set {
+            previous: existing.cloned(),
+            value: new_value.clone(),
+        })
+    }
+}
+
+impl<T: Clone + Send + 'static> OnResynced<T> for DownlinkEventSender<ValueDownlinkEvent<T>> {
+    type OnResyncedFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn on_resynced<'a>(&'a mut self, value: &'a T) -> Self::OnResyncedFut<'a> {
+        self.forward(ValueDownlinkEvent::Resynced(value.clone()))
+    }
+}
+
+impl<T: Send + 'static> OnEvent<T> for DownlinkEventSender<EventDownlinkEvent<T>>
+where
+    T: Clone,
+{
+    type OnEventFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn on_event<'a>(&'a mut self, value: &'a T) -> Self::OnEventFut<'a> {
+        self.forward(EventDownlinkEvent::Event(value.clone()))
+    }
+}
+
+impl<K, V> OnSynced<BTreeMap<K, V>> for DownlinkEventSender<MapDownlinkEvent<K, V>>
+where
+    K: Clone + Send + 'static,
+    V: Clone + Send + 'static,
+{
+    type OnSyncedFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a,
+        BTreeMap<K, V>: 'a;
+
+    fn on_synced<'a>(&'a mut self, value: &'a BTreeMap<K, V>) -> Self::OnSyncedFut<'a> {
+        self.forward(MapDownlinkEvent::Synced(value.clone()))
+    }
+}
+
+impl<K, V> OnResynced<BTreeMap<K, V>> for DownlinkEventSender<MapDownlinkEvent<K, V>>
+where
+    K: Clone + Send + 'static,
+    V: Clone + Send + 'static,
+{
+    type OnResyncedFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a,
+        BTreeMap<K, V>: 'a;
+
+    fn on_resynced<'a>(&'a mut self, value: &'a BTreeMap<K, V>) -> Self::OnResyncedFut<'a> {
+        self.forward(MapDownlinkEvent::Resynced(value.clone()))
+    }
+}
+
+impl<K, V> OnUpdate<K, V> for DownlinkEventSender<MapDownlinkEvent<K, V>>
+where
+    K: Send + 'static,
+    V: Clone + Send + 'static,
+{
+    type OnUpdateFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a,
+        K: 'a,
+        V: 'a;
+
+    fn on_update<'a>(
+        &'a mut self,
+        key: K,
+        _map: &'a BTreeMap<K, V>,
+        previous: Option<V>,
+        new_value: &'a V,
+    ) -> Self::OnUpdateFut<'a> {
+        self.forward(MapDownlinkEvent::Updated {
+            key,
+            previous,
+            value: new_value.clone(),
+        })
+    }
+}
+
+impl<K, V> OnRemove<K, V> for DownlinkEventSender<MapDownlinkEvent<K, V>>
+where
+    K: Send + 'static,
+    V: Send + 'static,
+{
+    type OnRemoveFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a,
+        K: 'a,
+        V: 'a;
+
+    fn on_remove<'a>(
+        &'a mut self,
+        key: K,
+        _map: &'a BTreeMap<K, V>,
+        removed: V,
+    ) -> Self::OnRemoveFut<'a> {
+        self.forward(MapDownlinkEvent::Removed {
+            key,
+            value: removed,
+        })
+    }
+}
+
+impl<K, V> OnEvict<K, V> for DownlinkEventSender<MapDownlinkEvent<K, V>>
+where
+    K: Send + 'static,
+    V: Send + 'static,
+{
+    type OnEvictFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a,
+        K: 'a,
+        V: 'a;
+
+    fn on_evict<'a>(
+        &'a mut self,
+        key: K,
+        _map: &'a BTreeMap<K, V>,
+        evicted: V,
+    ) -> Self::OnEvictFut<'a> {
+        self.forward(MapDownlinkEvent::Evicted {
+            key,
+            value: evicted,
+        })
+    }
+}
+
+impl<K, V> OnClear<K, V> for DownlinkEventSender<MapDownlinkEvent<K, V>>
+where
+    K: Send + 'static,
+    V: Send + 'static,
+{
+    type OnClearFut<'a> = BoxFuture<'static, ()>
+    where
+        Self: 'a,
+        K: 'a,
+        V: 'a;
+
+    fn on_clear<'a>(&'a mut self, map: BTreeMap<K, V>) -> Self::OnClearFut<'a>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.forward(MapDownlinkEvent::Cleared(map))
+    }
+}
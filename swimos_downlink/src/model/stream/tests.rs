@@ -0,0 +1,134 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use futures::{FutureExt, StreamExt};
+use swimos_utilities::non_zero_usize;
+use tokio::time::timeout;
+
+use crate::model::lifecycle::{
+    EventDownlinkLifecycle, LinkAdvice, MapDownlinkLifecycle, OnAdvisory, OnClear, OnEvent,
+    OnLinked, OnRemove, OnSet, OnSynced, OnUnlinked, OnUpdate, ValueDownlinkLifecycle,
+};
+
+use super::{downlink_event_stream, EventDownlinkEvent, MapDownlinkEvent, ValueDownlinkEvent};
+
+fn assert_value_lifecycle<T, L: ValueDownlinkLifecycle<T>>(_lifecycle: &L) {}
+fn assert_map_lifecycle<K, V, L: MapDownlinkLifecycle<K, V>>(_lifecycle: &L) {}
+fn assert_event_lifecycle<T, L: EventDownlinkLifecycle<T>>(_lifecycle: &L) {}
+
+#[tokio::test]
+async fn value_downlink_events() {
+    let (mut sender, stream) = downlink_event_stream::<ValueDownlinkEvent<i32>>(non_zero_usize!(8));
+    assert_value_lifecycle::<i32, _>(&sender);
+
+    sender.on_linked().await;
+    sender.on_synced(&1).await;
+    sender.on_event(&2).await;
+    sender.on_set(Some(&1), &2).await;
+    sender.on_advisory(LinkAdvice::ReduceRate).await;
+    sender.on_unlinked().await;
+    drop(sender);
+
+    let events = stream.collect::<Vec<_>>().await;
+    assert_eq!(
+        events,
+        vec![
+            ValueDownlinkEvent::Linked,
+            ValueDownlinkEvent::Synced(1),
+            ValueDownlinkEvent::Set {
+                previous: Some(1),
+                value: 2
+            },
+            ValueDownlinkEvent::Advisory(LinkAdvice::ReduceRate),
+            ValueDownlinkEvent::Unlinked,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn map_downlink_events() {
+    let (mut sender, stream) =
+        downlink_event_stream::<MapDownlinkEvent<i32, String>>(non_zero_usize!(8));
+    assert_map_lifecycle::<i32, String, _>(&sender);
+
+    let mut map = BTreeMap::from([(1, "a".to_string())]);
+    sender.on_synced(&map).await;
+    map.insert(2, "b".to_string());
+    sender.on_update(2, &map, None, &"b".to_string()).await;
+    map.remove(&1);
+    sender.on_remove(1, &map, "a".to_string()).await;
+    sender.on_clear(map).await;
+    drop(sender);
+
+    let events = stream.collect::<Vec<_>>().await;
+    assert_eq!(
+        events,
+        vec![
+            MapDownlinkEvent::Synced(BTreeMap::from([(1, "a".to_string())])),
+            MapDownlinkEvent::Updated {
+                key: 2,
+                previous: None,
+                value: "b".to_string()
+            },
+            MapDownlinkEvent::Removed {
+                key: 1,
+                value: "a".to_string()
+            },
+            MapDownlinkEvent::Cleared(BTreeMap::from([(2, "b".to_string())])),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn event_downlink_events() {
+    let (mut sender, mut stream) =
+        downlink_event_stream::<EventDownlinkEvent<i32>>(non_zero_usize!(8));
+    assert_event_lifecycle::<i32, _>(&sender);
+
+    sender.on_linked().await;
+    sender.on_event(&5).await;
+    assert_eq!(stream.next().await, Some(EventDownlinkEvent::Linked));
+    assert_eq!(stream.next().await, Some(EventDownlinkEvent::Event(5)));
+}
+
+#[tokio::test]
+async fn full_buffer_applies_backpressure() {
+    let (mut sender, mut stream) =
+        downlink_event_stream::<EventDownlinkEvent<i32>>(non_zero_usize!(1));
+
+    sender.on_event(&1).await;
+    let blocked = timeout(Duration::from_millis(50), sender.on_event(&2)).await;
+    assert!(blocked.is_err());
+
+    assert_eq!(stream.next().await, Some(EventDownlinkEvent::Event(1)));
+    sender.on_event(&3).await;
+    assert_eq!(stream.next().await, Some(EventDownlinkEvent::Event(3)));
+    assert!(stream.next().now_or_never().is_none());
+}
+
+#[tokio::test]
+async fn dropped_stream_discards_events() {
+    let (mut sender, stream) = downlink_event_stream::<EventDownlinkEvent<i32>>(non_zero_usize!(1));
+    drop(stream);
+
+    let result = timeout(Duration::from_secs(5), async {
+        sender.on_event(&1).await;
+        sender.on_event(&2).await;
+    })
+    .await;
+    assert!(result.is_ok());
+}
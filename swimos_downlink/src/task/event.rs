@@ -18,7 +18,7 @@ use crate::model::lifecycle::EventDownlinkLifecycle;
 use futures::StreamExt;
 use swimos_agent_protocol::{encoding::downlink::ValueNotificationDecoder, DownlinkNotification};
 use swimos_api::{address::Address, error::DownlinkTaskError};
-use swimos_client_api::DownlinkConfig;
+use swimos_client_api::{BoxRelink, DownlinkConfig};
 use swimos_form::Form;
 use swimos_model::Text;
 use swimos_recon::print_recon;
@@ -28,6 +28,8 @@ use tracing::{info_span, trace, Instrument};
 
 use crate::EventDownlinkModel;
 
+use super::Relinker;
+
 /// Task to drive an event downlink, calling lifecyle events at appropriate points.
 ///
 /// # Arguments
@@ -36,23 +38,34 @@ use crate::EventDownlinkModel;
 /// * `path` - The path of the lane to which the downlink is attached.
 /// * `config` - Configuration parameters to the downlink.
 /// * `input` - Input stream for messages to the downlink from the runtime.
-/// * `_output` - Output stream for messages from the downlink to the runtime.
+/// * `output` - Output stream for messages from the downlink to the runtime.
+/// * `relink` - Used to attach the downlink to the lane again if its connection is lost.
 pub async fn event_downlink_task<T, LC>(
     model: EventDownlinkModel<T, LC>,
     path: Address<Text>,
     config: DownlinkConfig,
     input: ByteReader,
-    _output: ByteWriter,
+    output: ByteWriter,
+    relink: Option<BoxRelink>,
 ) -> Result<(), DownlinkTaskError>
 where
     T: Form + Send + Sync + 'static,
     LC: EventDownlinkLifecycle<T>,
 {
-    let EventDownlinkModel { lifecycle, .. } = model;
-
-    read_task(config, input, lifecycle)
-        .instrument(info_span!("Downlink read task.", %path))
-        .await
+    let EventDownlinkModel { mut lifecycle, .. } = model;
+    let mut relinker = Relinker::new(&config, relink);
+    let mut io = (input, output);
+    loop {
+        // The output channel is never written to but must be held open while the downlink runs.
+        let (input, _output) = io;
+        read_task(&config, input, &mut lifecycle)
+            .instrument(info_span!("Downlink read task.", %path))
+            .await?;
+        match relinker.relink(&mut lifecycle, None).await {
+            Some(new_io) => io = new_io,
+            None => break Ok(()),
+        }
+    }
 }
 
 enum State {
@@ -70,9 +83,9 @@ impl Display for State {
 }
 
 async fn read_task<T, LC>(
-    config: DownlinkConfig,
+    config: &DownlinkConfig,
     input: ByteReader,
-    mut lifecycle: LC,
+    lifecycle: &mut LC,
 ) -> Result<(), DownlinkTaskError>
 where
     T: Form + Send + Sync + 'static,
@@ -81,12 +94,13 @@ where
     let DownlinkConfig {
         terminate_on_unlinked,
         ..
-    } = config;
+    } = *config;
     let mut state = State::Unlinked;
     let mut framed_read = FramedRead::new(input, ValueNotificationDecoder::default());
 
     while let Some(result) = framed_read.next().await {
         match result? {
+            DownlinkNotification::Version { .. } | DownlinkNotification::Origin { .. } => {}
             DownlinkNotification::Linked | DownlinkNotification::Synced => {
                 trace!("Received Linked or Synced in state {state}", state = &state);
                 if matches!(&state, State::Unlinked) {
@@ -104,6 +118,29 @@ where
                     lifecycle.on_event(&body).await;
                 }
             }
+            DownlinkNotification::Advisory { advice } => {
+                trace!(
+                    "Received Advisory '{advice}' in state {state}",
+                    state = &state
+                );
+                lifecycle.on_advisory(advice).await;
+            }
+            DownlinkNotification::NodeStopped { notice } => {
+                trace!(
+                    "Received NodeStopped '{notice}' in state {state}",
+                    state = &state
+                );
+                lifecycle.on_node_stopped(notice).await;
+            }
+            DownlinkNotification::Failed { failure } => {
+                trace!(
+                    "Received Failed '{failure}' in state {state}",
+                    state = &state
+                );
+                lifecycle.on_failed(failure).await;
+                lifecycle.on_unlinked().await;
+                return Err(DownlinkTaskError::LinkFailed(failure));
+            }
             DownlinkNotification::Unlinked => {
                 trace!("Received Unlinked in state {state}", state = &state);
                 lifecycle.on_unlinked().await;
@@ -14,24 +14,28 @@
 
 use crate::model::lifecycle::MapDownlinkLifecycle;
 use crate::model::MapDownlinkModel;
-use crate::task::{MapKey, MapValue};
+use crate::task::{MapKey, MapValue, Relinker};
 use futures::{FutureExt, Sink, SinkExt, StreamExt};
 use std::collections::BTreeMap;
 use std::fmt::{Debug, Display};
 use std::mem;
+use std::num::NonZeroUsize;
 use swimos_agent_protocol::encoding::downlink::MapNotificationDecoder;
 use swimos_agent_protocol::encoding::map::MapOperationEncoder;
 use swimos_agent_protocol::DownlinkNotification;
 use swimos_agent_protocol::{MapMessage, MapOperation};
-use swimos_api::{address::Address, error::DownlinkTaskError};
-use swimos_client_api::DownlinkConfig;
+use swimos_api::{
+    address::Address,
+    agent::{LinkFailure, SyncVersion},
+    error::DownlinkTaskError,
+};
+use swimos_client_api::{BoxRelink, DownlinkConfig};
 use swimos_form::write::StructuralWritable;
 use swimos_model::Text;
 use swimos_recon::print_recon;
 use swimos_utilities::byte_channel::{ByteReader, ByteWriter};
 use swimos_utilities::future::immediate_or_join;
 use tokio::select;
-use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::codec::{Decoder, FramedRead, FramedWrite};
 use tracing::{error, info_span, trace, Instrument};
@@ -45,12 +49,14 @@ use tracing::{error, info_span, trace, Instrument};
 /// * `config` - Configuration parameters to the downlink.
 /// * `input` - Input stream for messages to the downlink from the runtime.
 /// * `output` - Output stream for messages from the downlink to the runtime.
+/// * `relink` - Used to attach the downlink to the lane again if its connection is lost.
 pub async fn map_downlink_task<K, V, LC>(
     model: MapDownlinkModel<K, V, LC>,
     path: Address<Text>,
     config: DownlinkConfig,
     input: ByteReader,
     output: ByteWriter,
+    relink: Option<BoxRelink>,
 ) -> Result<(), DownlinkTaskError>
 where
     K: MapKey,
@@ -58,18 +64,38 @@ where
     V::Rec: Send,
     LC: MapDownlinkLifecycle<K, V>,
 {
-    let MapDownlinkModel { actions, lifecycle } = model;
-
-    run_io(
-        config,
-        input,
-        lifecycle,
-        FramedWrite::new(output, MapOperationEncoder),
+    let MapDownlinkModel {
         actions,
-        MapNotificationDecoder::default(),
-    )
-    .instrument(info_span!("Downlink IO task.", %path))
-    .await
+        mut lifecycle,
+        max_entries,
+    } = model;
+    let mut set_stream = ReceiverStream::new(actions);
+    let mut relinker = Relinker::new(&config, relink);
+    let mut io = (input, output);
+    let mut relinked = false;
+    let mut resume = Resume::default();
+    loop {
+        let (input, output) = io;
+        run_io(
+            config,
+            max_entries,
+            FramedRead::new(input, MapNotificationDecoder::default()),
+            &mut lifecycle,
+            FramedWrite::new(output, MapOperationEncoder),
+            &mut set_stream,
+            relinked,
+            &mut resume,
+        )
+        .instrument(info_span!("Downlink IO task.", %path))
+        .await?;
+        match relinker.relink(&mut lifecycle, resume.since()).await {
+            Some(new_io) => {
+                io = new_io;
+                relinked = true;
+            }
+            None => break Ok(()),
+        }
+    }
 }
 
 /// The current state of the downlink.
@@ -79,6 +105,46 @@ enum State<K, V> {
     Synced(BTreeMap<K, V>),
 }
 
+/// Tracks the version of the state of the remote lane so that, when the downlink is relinked, it
+/// can keep its state and the lane need only send the entries that have changed since.
+struct Resume<K, V> {
+    /// The version reported by the lane when the current link was synced.
+    reported: Option<SyncVersion>,
+    /// The state of the map when the previous link was lost and the version that it corresponds to.
+    retained: Option<(SyncVersion, BTreeMap<K, V>)>,
+}
+
+impl<K, V> Default for Resume<K, V> {
+    fn default() -> Self {
+        Resume {
+            reported: None,
+            retained: None,
+        }
+    }
+}
+
+impl<K, V> Resume<K, V> {
+    /// The version to present to the lane when the downlink is relinked.
+    fn since(&self) -> Option<SyncVersion> {
+        self.retained.as_ref().map(|(version, _)| *version)
+    }
+
+    /// The link has been lost. If the downlink was synced with a known version of the lane, its
+    /// state is retained for the next link.
+    fn retain(&mut self, state: State<K, V>) {
+        if let (State::Synced(map), Some(version)) = (state, self.reported.take()) {
+            self.retained = Some((version, map));
+        }
+    }
+
+    /// A new link has been established. If the downlink presented a version of the lane, it starts
+    /// from the retained state.
+    fn resume(&mut self) -> BTreeMap<K, V> {
+        self.reported = None;
+        self.retained.take().map(|(_, map)| map).unwrap_or_default()
+    }
+}
+
 struct ShowState<'a, K, V>(&'a State<K, V>);
 
 impl<'a, K, V> Display for ShowState<'a, K, V>
@@ -108,13 +174,16 @@ enum Mode {
     Read,
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_io<K, V, LC, Snk, D, E>(
     config: DownlinkConfig,
-    input: ByteReader,
-    mut lifecycle: LC,
+    max_entries: Option<NonZeroUsize>,
+    mut framed_read: FramedRead<ByteReader, D>,
+    lifecycle: &mut LC,
     mut framed: Snk,
-    actions: mpsc::Receiver<MapOperation<K, V>>,
-    decoder: D,
+    set_stream: &mut ReceiverStream<MapOperation<K, V>>,
+    relinked: bool,
+    resume: &mut Resume<K, V>,
 ) -> Result<(), DownlinkTaskError>
 where
     K: MapKey,
@@ -129,8 +198,6 @@ where
 {
     let mut state: State<K, V> = State::Unlinked;
     let mut mode = Mode::ReadWrite;
-    let mut framed_read = FramedRead::new(input, decoder);
-    let mut set_stream = ReceiverStream::new(actions);
 
     loop {
         match mode {
@@ -142,7 +209,10 @@ where
                     read_event = framed_read.next() => match read_event {
                         Some(Ok(notification)) => IoEvent::Read(notification),
                         Some(Err(e)) => break Err(e.into()),
-                        None => break Ok(()),
+                        None => {
+                            resume.retain(state);
+                            break Ok(());
+                        }
                     }
                 };
 
@@ -150,17 +220,22 @@ where
                     IoEvent::Write(Some(message)) => {
                         trace!("Sending command '{cmd}'.", cmd = print_recon(&message));
 
-                        match &mut state {
-                            State::Synced(map) | State::Linked(map) => match &message {
+                        let (map, dispatch) = match &mut state {
+                            State::Synced(map) => (Some(map), true),
+                            State::Linked(map) => (Some(map), config.events_when_not_synced),
+                            State::Unlinked => (None, false),
+                        };
+                        if let Some(map) = map {
+                            match &message {
                                 MapOperation::Update { key, value } => {
                                     map.insert(K::clone(key), V::clone(value));
+                                    evict(map, lifecycle, max_entries, dispatch).await;
                                 }
                                 MapOperation::Remove { key } => {
                                     map.remove(key);
                                 }
                                 MapOperation::Clear => map.clear(),
-                            },
-                            State::Unlinked => {}
+                            }
                         }
 
                         if let Err(e) = framed.feed(message).await {
@@ -169,24 +244,55 @@ where
                     }
                     IoEvent::Write(None) => mode = Mode::Read,
                     IoEvent::Read(notification) => {
-                        match on_read(state, &mut lifecycle, notification, config).await {
+                        match on_read(
+                            state,
+                            lifecycle,
+                            notification,
+                            config,
+                            max_entries,
+                            relinked,
+                            resume,
+                        )
+                        .await
+                        {
                             Step::Cont(new_state) => {
                                 state = new_state;
                             }
-                            Step::Terminate => break Ok(()),
+                            Step::Terminate(final_state) => {
+                                resume.retain(final_state);
+                                break Ok(());
+                            }
+                            Step::Fail(failure) => {
+                                break Err(DownlinkTaskError::LinkFailed(failure))
+                            }
                         }
                     }
                 }
             }
             Mode::Read => {
                 while let Some(result) = framed_read.next().await {
-                    match on_read(state, &mut lifecycle, result?, config).await {
+                    match on_read(
+                        state,
+                        lifecycle,
+                        result?,
+                        config,
+                        max_entries,
+                        relinked,
+                        resume,
+                    )
+                    .await
+                    {
                         Step::Cont(new_state) => {
                             state = new_state;
                         }
-                        Step::Terminate => break,
+                        Step::Terminate(final_state) => {
+                            resume.retain(final_state);
+                            return Ok(());
+                        }
+                        Step::Fail(failure) => return Err(DownlinkTaskError::LinkFailed(failure)),
                     }
                 }
+                resume.retain(state);
                 break Ok(());
             }
         }
@@ -198,6 +304,9 @@ async fn on_read<K, V, LC>(
     lifecycle: &mut LC,
     notification: DownlinkNotification<MapMessage<K, V>>,
     config: DownlinkConfig,
+    max_entries: Option<NonZeroUsize>,
+    relinked: bool,
+    resume: &mut Resume<K, V>,
 ) -> Step<K, V>
 where
     K: MapKey,
@@ -219,7 +328,7 @@ where
             );
             if matches!(&state, State::Unlinked) {
                 lifecycle.on_linked().await;
-                state = State::Linked(BTreeMap::new());
+                state = State::Linked(resume.resume());
             }
         }
         DownlinkNotification::Synced => {
@@ -230,6 +339,9 @@ where
 
             if let State::Linked(value) = state {
                 lifecycle.on_synced(&value).await;
+                if relinked {
+                    lifecycle.on_resynced(&value).await;
+                }
                 state = State::Synced(value);
             }
         }
@@ -242,10 +354,44 @@ where
 
             match &mut state {
                 State::Unlinked => {}
-                State::Linked(map) => on_event(map, lifecycle, body, events_when_not_synced).await,
-                State::Synced(map) => on_event(map, lifecycle, body, true).await,
+                State::Linked(map) => {
+                    on_event(map, lifecycle, body, events_when_not_synced, max_entries).await
+                }
+                State::Synced(map) => on_event(map, lifecycle, body, true, max_entries).await,
             }
         }
+        DownlinkNotification::Version { version } => {
+            trace!(
+                "Received Version '{version:?}' in state {state}",
+                state = ShowState(&state)
+            );
+            resume.reported = Some(version);
+        }
+        // Map downlinks do not publish their state so there is nowhere to report origins.
+        DownlinkNotification::Origin { .. } => {}
+        DownlinkNotification::Advisory { advice } => {
+            trace!(
+                "Received Advisory '{advice}' in state {state}",
+                state = ShowState(&state)
+            );
+            lifecycle.on_advisory(advice).await;
+        }
+        DownlinkNotification::NodeStopped { notice } => {
+            trace!(
+                "Received NodeStopped '{notice}' in state {state}",
+                state = ShowState(&state)
+            );
+            lifecycle.on_node_stopped(notice).await;
+        }
+        DownlinkNotification::Failed { failure } => {
+            trace!(
+                "Received Failed '{failure}' in state {state}",
+                state = ShowState(&state)
+            );
+            lifecycle.on_failed(failure).await;
+            lifecycle.on_unlinked().await;
+            return Step::Fail(failure);
+        }
         DownlinkNotification::Unlinked => {
             trace!(
                 "Received Unlinked in state {state}",
@@ -254,7 +400,7 @@ where
             lifecycle.on_unlinked().await;
             if terminate_on_unlinked {
                 trace!("Terminating on Unlinked.");
-                return Step::Terminate;
+                return Step::Terminate(state);
             } else {
                 state = State::Unlinked;
             }
@@ -268,8 +414,10 @@ where
 enum Step<K, V> {
     /// The IO loop should continue and update its state.
     Cont(State<K, V>),
-    /// The IO loop should terminate.
-    Terminate,
+    /// The IO loop should terminate (with the final state of the downlink).
+    Terminate(State<K, V>),
+    /// The remote refused the link and the IO loop should fail.
+    Fail(LinkFailure),
 }
 
 async fn on_event<K, V, LC>(
@@ -277,6 +425,7 @@ async fn on_event<K, V, LC>(
     lifecycle: &mut LC,
     event: MapMessage<K, V>,
     dispatch: bool,
+    max_entries: Option<NonZeroUsize>,
 ) where
     LC: MapDownlinkLifecycle<K, V>,
     K: Clone + Ord,
@@ -288,6 +437,7 @@ async fn on_event<K, V, LC>(
             if dispatch {
                 lifecycle.on_update(key, map, old, &value).await;
             }
+            evict(map, lifecycle, max_entries, dispatch).await;
         }
         MapMessage::Remove { key } => {
             if let Some(value) = map.remove(&key) {
@@ -297,29 +447,66 @@ async fn on_event<K, V, LC>(
             }
         }
         MapMessage::Clear => {
+            let old_map = mem::take(map);
             if dispatch {
-                let old_map = mem::take(map);
                 lifecycle.on_clear(old_map).await;
             }
         }
         MapMessage::Take(cnt) => {
-            let mut it = mem::take(map).into_iter();
-
-            for (key, value) in (&mut it).take(cnt as usize) {
-                map.insert(key, value);
-            }
-            for (key, value) in it {
-                lifecycle.on_remove(key, map, value).await;
-            }
+            // Decompose the take into a sequence of removals.
+            let to_remove = map.keys().skip(cnt as usize).cloned().collect::<Vec<_>>();
+            remove_all(map, lifecycle, to_remove, dispatch).await;
         }
         MapMessage::Drop(cnt) => {
-            let mut it = mem::take(map).into_iter();
+            let cnt = cnt as usize;
+            if cnt >= map.len() {
+                let old_map = mem::take(map);
+                if dispatch {
+                    lifecycle.on_clear(old_map).await;
+                }
+            } else {
+                // Decompose the drop into a sequence of removals.
+                let to_remove = map.keys().take(cnt).cloned().collect::<Vec<_>>();
+                remove_all(map, lifecycle, to_remove, dispatch).await;
+            }
+        }
+    }
+}
 
-            for (key, value) in (&mut it).take(cnt as usize) {
+async fn remove_all<K, V, LC>(
+    map: &mut BTreeMap<K, V>,
+    lifecycle: &mut LC,
+    keys: Vec<K>,
+    dispatch: bool,
+) where
+    LC: MapDownlinkLifecycle<K, V>,
+    K: Ord,
+{
+    for key in keys {
+        if let Some(value) = map.remove(&key) {
+            if dispatch {
                 lifecycle.on_remove(key, map, value).await;
             }
-            for (key, value) in it {
-                map.insert(key, value);
+        }
+    }
+}
+
+/// Evict the entries with the lowest keys until the map is within its maximum size.
+async fn evict<K, V, LC>(
+    map: &mut BTreeMap<K, V>,
+    lifecycle: &mut LC,
+    max_entries: Option<NonZeroUsize>,
+    dispatch: bool,
+) where
+    LC: MapDownlinkLifecycle<K, V>,
+    K: Ord,
+{
+    if let Some(max) = max_entries {
+        while map.len() > max.get() {
+            if let Some((key, value)) = map.pop_first() {
+                if dispatch {
+                    lifecycle.on_evict(key, map, value).await;
+                }
             }
         }
     }
@@ -13,21 +13,26 @@
 // limitations under the License.
 
 use crate::model::lifecycle::{
-    EventDownlinkLifecycle, MapDownlinkLifecycle, ValueDownlinkLifecycle,
+    EventDownlinkLifecycle, MapDownlinkLifecycle, OnReconnecting, ValueDownlinkLifecycle,
 };
 use futures::future::BoxFuture;
 use futures::FutureExt;
 use std::hash::Hash;
-use swimos_api::{address::Address, agent::DownlinkKind, error::DownlinkTaskError};
-use swimos_client_api::{Downlink, DownlinkConfig};
+use swimos_api::{
+    address::Address,
+    agent::{DownlinkKind, SyncVersion},
+    error::DownlinkTaskError,
+};
+use swimos_client_api::{BoxRelink, Downlink, DownlinkConfig, RelinkError};
 
 use swimos_form::read::RecognizerReadable;
 use swimos_form::Form;
 use swimos_model::Text;
 
 use swimos_utilities::byte_channel::{ByteReader, ByteWriter};
+use swimos_utilities::future::RetryStrategy;
 
-use tracing::{info_span, Instrument};
+use tracing::{debug, info_span, Instrument};
 
 use crate::model::MapDownlinkModel;
 use crate::{EventDownlinkModel, ValueDownlinkModel};
@@ -38,6 +43,62 @@ mod map;
 mod tests;
 mod value;
 
+/// Makes attempts to relink a downlink after its connection to the remote lane is lost.
+struct Relinker {
+    initial: RetryStrategy,
+    strategy: RetryStrategy,
+    relink: Option<BoxRelink>,
+}
+
+impl Relinker {
+    fn new(config: &DownlinkConfig, relink: Option<BoxRelink>) -> Self {
+        Relinker {
+            initial: config.relink,
+            strategy: config.relink,
+            relink,
+        }
+    }
+
+    /// Attempt to relink the downlink, calling the `on_reconnecting` handler of the lifecycle
+    /// before each attempt. Returns [`None`] if the downlink should stop.
+    ///
+    /// # Arguments
+    /// * `lifecycle` - The lifecycle of the downlink.
+    /// * `since` - The version of the state of the remote lane held by the downlink.
+    async fn relink<LC>(
+        &mut self,
+        lifecycle: &mut LC,
+        since: Option<SyncVersion>,
+    ) -> Option<(ByteReader, ByteWriter)>
+    where
+        LC: OnReconnecting,
+    {
+        let Relinker {
+            initial,
+            strategy,
+            relink,
+        } = self;
+        let relink = relink.as_mut()?;
+        while let Some(delay) = strategy.next() {
+            lifecycle.on_reconnecting().await;
+            if let Some(delay) = delay {
+                tokio::time::sleep(delay).await;
+            }
+            match relink.relink(since).await {
+                Ok(io) => {
+                    *strategy = *initial;
+                    return Some(io);
+                }
+                Err(RelinkError::Stopped) => return None,
+                Err(RelinkError::Failed(error)) => {
+                    debug!(error = %error, "Attempting to relink the downlink failed.");
+                }
+            }
+        }
+        None
+    }
+}
+
 /// Wrapper for downlink models to allow them to serve as a [`Downlink`].
 pub struct DownlinkTask<Model>(Model);
 
@@ -65,7 +126,7 @@ where
         output: ByteWriter,
     ) -> BoxFuture<'static, Result<(), DownlinkTaskError>> {
         let DownlinkTask(model) = self;
-        value::value_downlink_task(model, path, config, input, output)
+        value::value_downlink_task(model, path, config, input, output, None)
             .instrument(info_span!("Downlink task.", kind = ?DownlinkKind::Value))
             .boxed()
     }
@@ -79,6 +140,20 @@ where
     ) -> BoxFuture<'static, Result<(), DownlinkTaskError>> {
         (*self).run(path, config, input, output)
     }
+
+    fn run_relinking(
+        self: Box<Self>,
+        path: Address<Text>,
+        config: DownlinkConfig,
+        input: ByteReader,
+        output: ByteWriter,
+        relink: BoxRelink,
+    ) -> BoxFuture<'static, Result<(), DownlinkTaskError>> {
+        let DownlinkTask(model) = *self;
+        value::value_downlink_task(model, path, config, input, output, Some(relink))
+            .instrument(info_span!("Downlink task.", kind = ?DownlinkKind::Value))
+            .boxed()
+    }
 }
 
 impl<T, LC> Downlink for DownlinkTask<EventDownlinkModel<T, LC>>
@@ -99,7 +174,7 @@ where
         output: ByteWriter,
     ) -> BoxFuture<'static, Result<(), DownlinkTaskError>> {
         let DownlinkTask(model) = self;
-        event::event_downlink_task(model, path, config, input, output)
+        event::event_downlink_task(model, path, config, input, output, None)
             .instrument(info_span!("Downlink task.", kind = ?DownlinkKind::Event))
             .boxed()
     }
@@ -113,6 +188,20 @@ where
     ) -> BoxFuture<'static, Result<(), DownlinkTaskError>> {
         (*self).run(path, config, input, output)
     }
+
+    fn run_relinking(
+        self: Box<Self>,
+        path: Address<Text>,
+        config: DownlinkConfig,
+        input: ByteReader,
+        output: ByteWriter,
+        relink: BoxRelink,
+    ) -> BoxFuture<'static, Result<(), DownlinkTaskError>> {
+        let DownlinkTask(model) = *self;
+        event::event_downlink_task(model, path, config, input, output, Some(relink))
+            .instrument(info_span!("Downlink task.", kind = ?DownlinkKind::Event))
+            .boxed()
+    }
 }
 
 pub trait MapKey:
@@ -150,7 +239,7 @@ where
         output: ByteWriter,
     ) -> BoxFuture<'static, Result<(), DownlinkTaskError>> {
         let DownlinkTask(model) = self;
-        map::map_downlink_task(model, path, config, input, output)
+        map::map_downlink_task(model, path, config, input, output, None)
             .instrument(info_span!("Downlink task.", kind = ?DownlinkKind::Map))
             .boxed()
     }
@@ -164,4 +253,18 @@ where
     ) -> BoxFuture<'static, Result<(), DownlinkTaskError>> {
         (*self).run(path, config, input, output)
     }
+
+    fn run_relinking(
+        self: Box<Self>,
+        path: Address<Text>,
+        config: DownlinkConfig,
+        input: ByteReader,
+        output: ByteWriter,
+        relink: BoxRelink,
+    ) -> BoxFuture<'static, Result<(), DownlinkTaskError>> {
+        let DownlinkTask(model) = *self;
+        map::map_downlink_task(model, path, config, input, output, Some(relink))
+            .instrument(info_span!("Downlink task.", kind = ?DownlinkKind::Map))
+            .boxed()
+    }
 }
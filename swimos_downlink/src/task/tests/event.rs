@@ -15,10 +15,11 @@
 use std::num::NonZeroUsize;
 
 use swimos_agent_protocol::DownlinkNotification;
+use swimos_api::agent::{LinkAdvice, LinkFailure, NodeStopped};
 use swimos_api::error::{DownlinkTaskError, FrameIoError, InvalidFrame};
 
 use swimos_client_api::DownlinkConfig;
-use swimos_utilities::non_zero_usize;
+use swimos_utilities::{future::RetryStrategy, non_zero_usize};
 use tokio::sync::mpsc;
 
 use super::run_value_downlink_task;
@@ -30,6 +31,9 @@ enum TestMessage<T> {
     Linked,
     Event(T),
     Unlinked,
+    Advisory(LinkAdvice),
+    NodeStopped(NodeStopped),
+    Failed(LinkFailure),
 }
 
 fn make_lifecycle<T>(tx: mpsc::UnboundedSender<TestMessage<T>>) -> impl EventDownlinkLifecycle<T>
@@ -47,6 +51,15 @@ where
         .on_unlinked_blocking(|tx| {
             assert!(tx.send(TestMessage::Unlinked).is_ok());
         })
+        .on_advisory_blocking(|tx, advice| {
+            assert!(tx.send(TestMessage::Advisory(advice)).is_ok());
+        })
+        .on_node_stopped_blocking(|tx, notice| {
+            assert!(tx.send(TestMessage::NodeStopped(notice)).is_ok());
+        })
+        .on_failed_blocking(|tx, failure| {
+            assert!(tx.send(TestMessage::Failed(failure)).is_ok());
+        })
 }
 
 async fn expect_event<T: Eq + std::fmt::Debug>(
@@ -68,6 +81,7 @@ async fn link_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -95,6 +109,7 @@ async fn message_before_linked() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -125,6 +140,7 @@ async fn message_after_linked() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -146,6 +162,40 @@ async fn message_after_linked() {
     assert!(result.unwrap().recv().await.is_none());
 }
 
+#[tokio::test]
+async fn advisory_after_linked() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32>>();
+    let lifecycle = make_lifecycle(event_tx);
+    let model = EventDownlinkModel::<i32, _>::new(lifecycle);
+
+    let config = DownlinkConfig {
+        events_when_not_synced: false,
+        terminate_on_unlinked: true,
+        buffer_size: DEEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
+    };
+
+    let result = run_value_downlink_task(
+        DownlinkTask::new(model),
+        config,
+        |mut writer, reader| async move {
+            let _reader = reader;
+            writer.send_value::<i32>(DownlinkNotification::Linked).await;
+            writer
+                .send_value::<i32>(DownlinkNotification::Advisory {
+                    advice: LinkAdvice::ReduceRate,
+                })
+                .await;
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+            expect_event(&mut event_rx, TestMessage::Advisory(LinkAdvice::ReduceRate)).await;
+            event_rx
+        },
+    )
+    .await;
+    assert!(result.is_ok());
+    assert!(result.unwrap().recv().await.is_none());
+}
+
 #[tokio::test]
 async fn terminate_after_unlinked() {
     let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32>>();
@@ -156,6 +206,7 @@ async fn terminate_after_unlinked() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -186,6 +237,84 @@ async fn terminate_after_unlinked() {
     }
 }
 
+#[tokio::test]
+async fn node_stopped_before_unlinked() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32>>();
+    let lifecycle = make_lifecycle(event_tx);
+    let model = EventDownlinkModel::<i32, _>::new(lifecycle);
+
+    let config = DownlinkConfig {
+        events_when_not_synced: false,
+        terminate_on_unlinked: true,
+        buffer_size: DEEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
+    };
+
+    let notice = NodeStopped { resume: true };
+    let result = run_value_downlink_task(
+        DownlinkTask::new(model),
+        config,
+        |mut writer, reader| async move {
+            writer.send_value::<i32>(DownlinkNotification::Linked).await;
+            writer
+                .send_value::<i32>(DownlinkNotification::NodeStopped { notice })
+                .await;
+            writer
+                .send_value::<i32>(DownlinkNotification::Unlinked)
+                .await;
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+            expect_event(&mut event_rx, TestMessage::NodeStopped(notice)).await;
+            expect_event(&mut event_rx, TestMessage::Unlinked).await;
+            (writer, reader, event_rx)
+        },
+    )
+    .await;
+    match result {
+        Ok((_writer, _reader, mut events)) => {
+            assert!(events.recv().await.is_none());
+        }
+        Err(e) => {
+            panic!("Task failed: {}", e)
+        }
+    }
+}
+
+#[tokio::test]
+async fn fail_after_link_failure() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32>>();
+    let lifecycle = make_lifecycle(event_tx);
+    let model = EventDownlinkModel::<i32, _>::new(lifecycle);
+
+    let config = DownlinkConfig {
+        events_when_not_synced: false,
+        terminate_on_unlinked: false,
+        buffer_size: DEEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
+    };
+
+    let failure = LinkFailure::NodeNotFound;
+    let result = run_value_downlink_task(
+        DownlinkTask::new(model),
+        config,
+        |mut writer, reader| async move {
+            writer
+                .send_value::<i32>(DownlinkNotification::Failed { failure })
+                .await;
+            writer
+                .send_value::<i32>(DownlinkNotification::Unlinked)
+                .await;
+            expect_event(&mut event_rx, TestMessage::Failed(failure)).await;
+            expect_event(&mut event_rx, TestMessage::Unlinked).await;
+            (writer, reader, event_rx)
+        },
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(DownlinkTaskError::LinkFailed(LinkFailure::NodeNotFound))
+    ));
+}
+
 #[tokio::test]
 async fn terminate_after_corrupt_frame() {
     let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32>>();
@@ -196,6 +325,7 @@ async fn terminate_after_corrupt_frame() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -227,6 +357,7 @@ async fn relink_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: false,
         buffer_size: DEEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -24,15 +24,16 @@ use swimos_client_api::{Downlink, DownlinkConfig};
 use swimos_agent_protocol::encoding::downlink::DownlinkNotificationEncoder;
 use swimos_agent_protocol::encoding::map::{MapMessageEncoder, MapOperationDecoder};
 use swimos_agent_protocol::DownlinkNotification;
+use swimos_api::agent::SyncVersion;
 use swimos_api::error::{DownlinkTaskError, FrameIoError, InvalidFrame};
 use swimos_form::write::StructuralWritable;
 use swimos_form::Form;
 use swimos_utilities::byte_channel::ByteWriter;
-use swimos_utilities::non_zero_usize;
+use swimos_utilities::{future::RetryStrategy, non_zero_usize};
 use tokio::sync::mpsc;
 use tokio_util::codec::{Encoder, FramedWrite};
 
-use super::{run_downlink_task, TestReader};
+use super::{run_downlink_task, run_relinking_downlink_task, TestReader};
 use crate::lifecycle::BasicMapDownlinkLifecycle;
 use crate::model::lifecycle::MapDownlinkLifecycle;
 use crate::model::MapDownlinkModel;
@@ -68,6 +69,13 @@ impl TestMapWriter {
             DownlinkNotification::Linked => DownlinkNotification::Linked,
             DownlinkNotification::Synced => DownlinkNotification::Synced,
             DownlinkNotification::Unlinked => DownlinkNotification::Unlinked,
+            DownlinkNotification::Advisory { advice } => DownlinkNotification::Advisory { advice },
+            DownlinkNotification::NodeStopped { notice } => {
+                DownlinkNotification::NodeStopped { notice }
+            }
+            DownlinkNotification::Failed { failure } => DownlinkNotification::Failed { failure },
+            DownlinkNotification::Version { version } => DownlinkNotification::Version { version },
+            DownlinkNotification::Origin { origin } => DownlinkNotification::Origin { origin },
             DownlinkNotification::Event { body } => {
                 let mut encoder = MapMessageEncoder::default();
                 let mut buf = BytesMut::new();
@@ -98,7 +106,10 @@ enum TestMessage<K, V> {
     Linked,
     Synced(BTreeMap<K, V>),
     Event(MapMessage<K, V>),
+    Evicted(K),
     Unlinked,
+    Reconnecting,
+    Resynced(BTreeMap<K, V>),
 }
 
 fn make_lifecycle<K, V>(
@@ -130,9 +141,18 @@ where
         .on_clear_blocking(|tx, _| {
             assert!(tx.send(TestMessage::Event(MapMessage::Clear)).is_ok());
         })
+        .on_evicted_blocking(|tx, key, _, _| {
+            assert!(tx.send(TestMessage::Evicted(key)).is_ok());
+        })
         .on_unlink_blocking(|tx| {
             assert!(tx.send(TestMessage::Unlinked).is_ok());
         })
+        .on_reconnecting_blocking(|tx| {
+            assert!(tx.send(TestMessage::Reconnecting).is_ok());
+        })
+        .on_resynced_blocking(|tx, map| {
+            assert!(tx.send(TestMessage::Resynced(map.clone())).is_ok());
+        })
 }
 
 async fn expect_event<K, V>(
@@ -159,6 +179,7 @@ async fn link_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -189,6 +210,7 @@ async fn invalid_sync_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -221,6 +243,7 @@ async fn sync_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -263,6 +286,7 @@ async fn report_events_before_sync() {
         events_when_not_synced: true,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -314,6 +338,7 @@ async fn report_events_after_sync() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -364,6 +389,7 @@ async fn terminate_after_unlinked() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -412,6 +438,7 @@ async fn terminate_after_corrupt_frame() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -446,6 +473,7 @@ async fn unlink_discards_value() {
         events_when_not_synced: false,
         terminate_on_unlinked: false,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -494,6 +522,7 @@ async fn relink_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: false,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -539,6 +568,155 @@ async fn relink_downlink() {
     assert!(result.unwrap().recv().await.is_none());
 }
 
+#[tokio::test]
+async fn relink_after_connection_lost() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32, i32>>();
+    let (_set_tx, set_rx) = mpsc::channel(16);
+    let lifecycle = make_lifecycle(event_tx);
+    let model = MapDownlinkModel::new(set_rx, lifecycle);
+
+    let config = DownlinkConfig {
+        events_when_not_synced: false,
+        terminate_on_unlinked: true,
+        buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::immediate(non_zero_usize!(2)),
+    };
+
+    let result = run_relinking_downlink_task(
+        DownlinkTask::new(model),
+        config,
+        |mut writer: TestMapWriter, reader, relink| async move {
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Linked)
+                .await;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Event {
+                    body: MapMessage::Update { key: 1, value: 1 },
+                })
+                .await;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Synced)
+                .await;
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+            expect_event(&mut event_rx, TestMessage::Synced(BTreeMap::from([(1, 1)]))).await;
+
+            // The first attempt fails and the downlink tries again.
+            relink.fail();
+            let (mut writer, _reader) = {
+                let new_io = relink.push(TestMapWriter::new);
+                drop(writer);
+                drop(reader);
+                new_io
+            };
+            expect_event(&mut event_rx, TestMessage::Reconnecting).await;
+            expect_event(&mut event_rx, TestMessage::Reconnecting).await;
+
+            // The state of the map is rebuilt from the new sync.
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Linked)
+                .await;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Event {
+                    body: MapMessage::Update { key: 2, value: 2 },
+                })
+                .await;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Synced)
+                .await;
+            let expected = BTreeMap::from([(2, 2)]);
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+            expect_event(&mut event_rx, TestMessage::Synced(expected.clone())).await;
+            expect_event(&mut event_rx, TestMessage::Resynced(expected)).await;
+
+            drop(relink);
+            drop(writer);
+            expect_event(&mut event_rx, TestMessage::Reconnecting).await;
+            event_rx
+        },
+        TestMapWriter::new,
+    )
+    .await;
+    assert!(result.is_ok());
+    assert!(result.unwrap().recv().await.is_none());
+}
+
+#[tokio::test]
+async fn relink_with_sync_version() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32, i32>>();
+    let (_set_tx, set_rx) = mpsc::channel(16);
+    let lifecycle = make_lifecycle(event_tx);
+    let model = MapDownlinkModel::new(set_rx, lifecycle);
+
+    let config = DownlinkConfig {
+        events_when_not_synced: false,
+        terminate_on_unlinked: true,
+        buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::immediate(non_zero_usize!(2)),
+    };
+
+    let version = SyncVersion::new(1, 4);
+
+    let result = run_relinking_downlink_task(
+        DownlinkTask::new(model),
+        config,
+        |mut writer: TestMapWriter, reader, mut relink| async move {
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Linked)
+                .await;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Event {
+                    body: MapMessage::Update { key: 1, value: 1 },
+                })
+                .await;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Version { version })
+                .await;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Synced)
+                .await;
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+            expect_event(&mut event_rx, TestMessage::Synced(BTreeMap::from([(1, 1)]))).await;
+
+            let (mut writer, _reader) = {
+                let new_io = relink.push(TestMapWriter::new);
+                drop(writer);
+                drop(reader);
+                new_io
+            };
+            expect_event(&mut event_rx, TestMessage::Reconnecting).await;
+            // The downlink presents the version of the state that it holds.
+            assert_eq!(relink.presented().await, Some(version));
+
+            // Only the changes are sent and are applied to the retained state.
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Linked)
+                .await;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Event {
+                    body: MapMessage::Update { key: 2, value: 2 },
+                })
+                .await;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Synced)
+                .await;
+            let expected = BTreeMap::from([(1, 1), (2, 2)]);
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+            expect_event(&mut event_rx, TestMessage::Synced(expected.clone())).await;
+            expect_event(&mut event_rx, TestMessage::Resynced(expected)).await;
+
+            // No version was reported for the second link so the next attempt requests a full sync.
+            drop(writer);
+            expect_event(&mut event_rx, TestMessage::Reconnecting).await;
+            assert_eq!(relink.presented().await, None);
+            drop(relink);
+            event_rx
+        },
+        TestMapWriter::new,
+    )
+    .await;
+    assert!(result.is_ok());
+}
+
 #[tokio::test]
 async fn send_on_downlink() {
     let (event_tx, _event_rx) = mpsc::unbounded_channel::<TestMessage<i32, i32>>();
@@ -550,6 +728,7 @@ async fn send_on_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -582,6 +761,7 @@ async fn clear_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -627,6 +807,7 @@ async fn empty_sync_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -661,6 +842,7 @@ async fn rx_take_elem_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -729,6 +911,7 @@ async fn rx_drop_elem_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -792,6 +975,7 @@ async fn remove_elem_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_map_downlink_task(
@@ -843,3 +1027,168 @@ async fn remove_elem_downlink() {
     assert!(result.is_ok());
     assert!(result.unwrap().recv().await.is_none());
 }
+
+#[tokio::test]
+async fn rx_drop_all_clears_downlink() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32, i32>>();
+    let (_set_tx, set_rx) = mpsc::channel(16);
+    let lifecycle = make_lifecycle(event_tx);
+    let model = MapDownlinkModel::new(set_rx, lifecycle);
+
+    let config = DownlinkConfig {
+        events_when_not_synced: false,
+        terminate_on_unlinked: true,
+        buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
+    };
+
+    let result = run_map_downlink_task(
+        DownlinkTask::new(model),
+        config,
+        |mut writer, reader| async move {
+            let _reader = reader;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Linked)
+                .await;
+
+            for i in 0..3 {
+                writer
+                    .send_message::<i32, i32>(DownlinkNotification::Event {
+                        body: MapMessage::Update { key: i, value: i },
+                    })
+                    .await;
+            }
+
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Synced)
+                .await;
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+
+            let state = (0..3).map(|i| (i, i)).collect::<BTreeMap<i32, i32>>();
+            expect_event(&mut event_rx, TestMessage::Synced(state)).await;
+
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Event {
+                    body: MapMessage::Drop(5),
+                })
+                .await;
+
+            expect_event(&mut event_rx, TestMessage::Event(MapMessage::Clear)).await;
+
+            event_rx
+        },
+    )
+    .await;
+    assert!(result.is_ok());
+    assert!(result.unwrap().recv().await.is_none());
+}
+
+#[tokio::test]
+async fn clear_before_sync_discards_entries() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32, i32>>();
+    let (_set_tx, set_rx) = mpsc::channel(16);
+    let lifecycle = make_lifecycle(event_tx);
+    let model = MapDownlinkModel::new(set_rx, lifecycle);
+
+    let config = DownlinkConfig {
+        events_when_not_synced: false,
+        terminate_on_unlinked: true,
+        buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
+    };
+
+    let result = run_map_downlink_task(
+        DownlinkTask::new(model),
+        config,
+        |mut writer, reader| async move {
+            let _reader = reader;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Linked)
+                .await;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Event {
+                    body: MapMessage::Update { key: 1, value: 1 },
+                })
+                .await;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Event {
+                    body: MapMessage::Clear,
+                })
+                .await;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Event {
+                    body: MapMessage::Update { key: 2, value: 2 },
+                })
+                .await;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Synced)
+                .await;
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+            expect_event(&mut event_rx, TestMessage::Synced(BTreeMap::from([(2, 2)]))).await;
+            event_rx
+        },
+    )
+    .await;
+    assert!(result.is_ok());
+    assert!(result.unwrap().recv().await.is_none());
+}
+
+#[tokio::test]
+async fn evict_entries_over_limit() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32, i32>>();
+    let (_set_tx, set_rx) = mpsc::channel(16);
+    let lifecycle = make_lifecycle(event_tx);
+    let model = MapDownlinkModel::new(set_rx, lifecycle).with_max_entries(non_zero_usize!(3));
+
+    let config = DownlinkConfig {
+        events_when_not_synced: false,
+        terminate_on_unlinked: true,
+        buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
+    };
+
+    let result = run_map_downlink_task(
+        DownlinkTask::new(model),
+        config,
+        |mut writer, reader| async move {
+            let _reader = reader;
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Linked)
+                .await;
+
+            for i in 0..5 {
+                writer
+                    .send_message::<i32, i32>(DownlinkNotification::Event {
+                        body: MapMessage::Update { key: i, value: i },
+                    })
+                    .await;
+            }
+
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Synced)
+                .await;
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+
+            let state = (2..5).map(|i| (i, i)).collect::<BTreeMap<i32, i32>>();
+            expect_event(&mut event_rx, TestMessage::Synced(state)).await;
+
+            writer
+                .send_message::<i32, i32>(DownlinkNotification::Event {
+                    body: MapMessage::Update { key: 5, value: 5 },
+                })
+                .await;
+
+            expect_event(
+                &mut event_rx,
+                TestMessage::Event(MapMessage::Update { key: 5, value: 5 }),
+            )
+            .await;
+            expect_event(&mut event_rx, TestMessage::Evicted(2)).await;
+
+            event_rx
+        },
+    )
+    .await;
+    assert!(result.is_ok());
+    assert!(result.unwrap().recv().await.is_none());
+}
@@ -15,15 +15,16 @@
 use std::future::Future;
 use std::num::NonZeroUsize;
 
-use futures::future::join;
-use futures::{SinkExt, StreamExt};
+use futures::future::{join, BoxFuture};
+use futures::{FutureExt, SinkExt, StreamExt};
+use tokio::sync::mpsc;
 use tokio::time::{timeout, Duration};
 use tokio_util::codec::{Decoder, FramedRead, FramedWrite};
 
 use swimos_agent_protocol::encoding::downlink::DownlinkNotificationEncoder;
 use swimos_agent_protocol::DownlinkNotification;
-use swimos_api::{address::Address, error::DownlinkTaskError};
-use swimos_client_api::{Downlink, DownlinkConfig};
+use swimos_api::{address::Address, agent::SyncVersion, error::DownlinkTaskError};
+use swimos_client_api::{Downlink, DownlinkConfig, Relink, RelinkError};
 use swimos_form::write::StructuralWritable;
 use swimos_recon::print_recon_compact;
 use swimos_utilities::{
@@ -93,6 +94,13 @@ impl TestValueWriter {
             DownlinkNotification::Linked => DownlinkNotification::Linked,
             DownlinkNotification::Synced => DownlinkNotification::Synced,
             DownlinkNotification::Unlinked => DownlinkNotification::Unlinked,
+            DownlinkNotification::Advisory { advice } => DownlinkNotification::Advisory { advice },
+            DownlinkNotification::NodeStopped { notice } => {
+                DownlinkNotification::NodeStopped { notice }
+            }
+            DownlinkNotification::Failed { failure } => DownlinkNotification::Failed { failure },
+            DownlinkNotification::Version { version } => DownlinkNotification::Version { version },
+            DownlinkNotification::Origin { origin } => DownlinkNotification::Origin { origin },
             DownlinkNotification::Event { body } => {
                 let body_bytes = format!("{}", print_recon_compact(&body)).into_bytes();
                 DownlinkNotification::Event { body: body_bytes }
@@ -145,3 +153,101 @@ where
         .expect("Test timed out.");
     result.map(|_| out)
 }
+
+/// Hands out the channels queued by a test to a downlink task each time that it relinks. A missing
+/// entry in the queue causes the attempt to fail. The versions presented by the downlink are
+/// passed back to the test.
+struct TestRelink(
+    mpsc::UnboundedReceiver<Option<(ByteReader, ByteWriter)>>,
+    mpsc::UnboundedSender<Option<SyncVersion>>,
+);
+
+impl Relink for TestRelink {
+    fn relink(
+        &mut self,
+        since: Option<SyncVersion>,
+    ) -> BoxFuture<'_, Result<(ByteReader, ByteWriter), RelinkError>> {
+        let TestRelink(rx, since_tx) = self;
+        let _ = since_tx.send(since);
+        async move {
+            match rx.recv().await {
+                Some(Some(io)) => Ok(io),
+                Some(None) => Err(RelinkError::Failed(Box::new(std::io::Error::from(
+                    std::io::ErrorKind::ConnectionRefused,
+                )))),
+                None => Err(RelinkError::Stopped),
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Queues channels for a downlink task to use the next time that it relinks.
+struct RelinkQueue(
+    mpsc::UnboundedSender<Option<(ByteReader, ByteWriter)>>,
+    mpsc::UnboundedReceiver<Option<SyncVersion>>,
+);
+
+impl RelinkQueue {
+    fn fail(&self) {
+        let RelinkQueue(tx, _) = self;
+        assert!(tx.send(None).is_ok());
+    }
+
+    fn push<W, Fac>(&self, make_writer: Fac) -> (W, TestReader)
+    where
+        Fac: FnOnce(ByteWriter) -> W,
+    {
+        let RelinkQueue(tx, _) = self;
+        let (in_tx, in_rx) = byte_channel(CHANNEL_SIZE);
+        let (out_tx, out_rx) = byte_channel(CHANNEL_SIZE);
+        assert!(tx.send(Some((in_rx, out_tx))).is_ok());
+        (make_writer(in_tx), TestReader::new(out_rx))
+    }
+
+    /// The version presented by the downlink in its next attempt to relink.
+    async fn presented(&mut self) -> Option<SyncVersion> {
+        let RelinkQueue(_, since_rx) = self;
+        since_rx
+            .recv()
+            .await
+            .expect("The downlink did not attempt to relink.")
+    }
+}
+
+async fn run_relinking_downlink_task<D, F, Fut, Fac, W>(
+    task: D,
+    config: DownlinkConfig,
+    test_block: F,
+    make_writer: Fac,
+) -> Result<Fut::Output, DownlinkTaskError>
+where
+    D: Downlink + 'static,
+    F: FnOnce(W, TestReader, RelinkQueue) -> Fut,
+    Fut: Future,
+    Fac: FnOnce(ByteWriter) -> W,
+{
+    let path = Address::text(None, "node", "lane");
+
+    let (in_tx, in_rx) = byte_channel(CHANNEL_SIZE);
+    let (out_tx, out_rx) = byte_channel(CHANNEL_SIZE);
+    let (relink_tx, relink_rx) = mpsc::unbounded_channel();
+    let (since_tx, since_rx) = mpsc::unbounded_channel();
+
+    let dl_task = Box::new(task).run_relinking(
+        path,
+        config,
+        in_rx,
+        out_tx,
+        Box::new(TestRelink(relink_rx, since_tx)),
+    );
+    let test_body = test_block(
+        make_writer(in_tx),
+        TestReader::new(out_rx),
+        RelinkQueue(relink_tx, since_rx),
+    );
+    let (result, out) = timeout(TEST_TIMEOUT, join(dl_task, test_body))
+        .await
+        .expect("Test timed out.");
+    result.map(|_| out)
+}
@@ -17,8 +17,9 @@ use std::marker::PhantomData;
 use std::num::NonZeroUsize;
 
 use bytes::BytesMut;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 use tokio_util::codec::Decoder;
+use uuid::Uuid;
 
 use swimos_agent_protocol::encoding::downlink::DownlinkOperationDecoder;
 use swimos_agent_protocol::DownlinkNotification;
@@ -26,13 +27,15 @@ use swimos_api::error::{DownlinkTaskError, FrameIoError, InvalidFrame};
 use swimos_client_api::DownlinkConfig;
 use swimos_form::read::RecognizerReadable;
 use swimos_recon::parser::parse_recognize;
-use swimos_utilities::non_zero_usize;
+use swimos_utilities::{future::RetryStrategy, non_zero_usize};
 
-use crate::model::lifecycle::{BasicValueDownlinkLifecycle, ValueDownlinkLifecycle};
-use crate::model::ValueDownlinkSet;
+use crate::model::lifecycle::{
+    BasicValueDownlinkLifecycle, StatelessValueDownlinkLifecycle, ValueDownlinkLifecycle,
+};
+use crate::model::{ValueDownlinkSet, ValueDownlinkState};
 use crate::{DownlinkTask, ValueDownlinkModel};
 
-use super::run_value_downlink_task;
+use super::{run_relinking_downlink_task, run_value_downlink_task, TestValueWriter};
 
 #[derive(Debug, PartialEq, Eq)]
 enum TestMessage<T> {
@@ -41,6 +44,8 @@ enum TestMessage<T> {
     Event(T),
     Set(Option<T>, T),
     Unlinked,
+    Reconnecting,
+    Resynced(T),
 }
 
 fn make_lifecycle<T>(tx: mpsc::UnboundedSender<TestMessage<T>>) -> impl ValueDownlinkLifecycle<T>
@@ -66,6 +71,12 @@ where
         .on_unlinked_blocking(|tx| {
             assert!(tx.send(TestMessage::Unlinked).is_ok());
         })
+        .on_reconnecting_blocking(|tx| {
+            assert!(tx.send(TestMessage::Reconnecting).is_ok());
+        })
+        .on_resynced_blocking(|tx, v| {
+            assert!(tx.send(TestMessage::Resynced(v.clone())).is_ok());
+        })
 }
 
 async fn expect_event<T: Eq + Debug>(
@@ -88,6 +99,7 @@ async fn link_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -117,6 +129,7 @@ async fn invalid_sync_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -145,6 +158,51 @@ async fn sync_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
+    };
+
+    let result = run_value_downlink_task(
+        DownlinkTask::new(model),
+        config,
+        |mut writer, reader| async move {
+            let _reader = reader;
+
+            writer.send_value::<i32>(DownlinkNotification::Linked).await;
+            writer
+                .send_value::<i32>(DownlinkNotification::Event { body: 5 })
+                .await;
+            writer.send_value::<i32>(DownlinkNotification::Synced).await;
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+            expect_event(&mut event_rx, TestMessage::Synced(5)).await;
+            event_rx
+        },
+    )
+    .await;
+    assert!(result.is_ok());
+    assert!(result.unwrap().recv().await.is_none());
+}
+
+#[tokio::test]
+async fn sync_downlink_with_shared_state() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32>>();
+    let linked_tx = event_tx.clone();
+    let lifecycle = StatelessValueDownlinkLifecycle::<i32>::default()
+        .on_linked_blocking(move || {
+            assert!(linked_tx.send(TestMessage::Linked).is_ok());
+        })
+        .with_shared_state(event_tx)
+        .on_synced_blocking(|tx, v| {
+            assert!(tx.send(TestMessage::Synced(*v)).is_ok());
+        });
+    let (_handle_tx, handle_rx) = mpsc::channel(8);
+
+    let model = ValueDownlinkModel::new(handle_rx, lifecycle);
+
+    let config = DownlinkConfig {
+        events_when_not_synced: false,
+        terminate_on_unlinked: true,
+        buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -179,6 +237,7 @@ async fn report_events_before_sync() {
         events_when_not_synced: true,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -217,6 +276,7 @@ async fn report_events_after_sync() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -255,6 +315,7 @@ async fn terminate_after_unlinked() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -297,6 +358,7 @@ async fn terminate_after_corrupt_frame() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -329,6 +391,7 @@ async fn unlink_discards_value() {
         events_when_not_synced: false,
         terminate_on_unlinked: false,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -367,6 +430,7 @@ async fn relink_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: false,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -400,6 +464,101 @@ async fn relink_downlink() {
     assert!(result.unwrap().recv().await.is_none());
 }
 
+#[tokio::test]
+async fn relink_after_connection_lost() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32>>();
+    let lifecycle = make_lifecycle(event_tx);
+    let (_handle_tx, handle_rx) = mpsc::channel(8);
+    let model = ValueDownlinkModel::new(handle_rx, lifecycle);
+
+    let config = DownlinkConfig {
+        events_when_not_synced: false,
+        terminate_on_unlinked: true,
+        buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::immediate(non_zero_usize!(2)),
+    };
+
+    let result = run_relinking_downlink_task(
+        DownlinkTask::new(model),
+        config,
+        |mut writer, reader, relink| async move {
+            writer.send_value::<i32>(DownlinkNotification::Linked).await;
+            writer
+                .send_value::<i32>(DownlinkNotification::Event { body: 5 })
+                .await;
+            writer.send_value::<i32>(DownlinkNotification::Synced).await;
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+            expect_event(&mut event_rx, TestMessage::Synced(5)).await;
+
+            let (new_writer, _new_reader) = relink.push(TestValueWriter::new);
+            drop(writer);
+            drop(reader);
+            expect_event(&mut event_rx, TestMessage::Reconnecting).await;
+
+            let mut writer = new_writer;
+
+            writer.send_value::<i32>(DownlinkNotification::Linked).await;
+            writer
+                .send_value::<i32>(DownlinkNotification::Event { body: 7 })
+                .await;
+            writer.send_value::<i32>(DownlinkNotification::Synced).await;
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+            expect_event(&mut event_rx, TestMessage::Synced(7)).await;
+            expect_event(&mut event_rx, TestMessage::Resynced(7)).await;
+
+            writer
+                .send_value::<i32>(DownlinkNotification::Unlinked)
+                .await;
+            expect_event(&mut event_rx, TestMessage::Unlinked).await;
+            // The relink attempt fails as no more channels are provided.
+            drop(relink);
+            expect_event(&mut event_rx, TestMessage::Reconnecting).await;
+            event_rx
+        },
+        TestValueWriter::new,
+    )
+    .await;
+    assert!(result.is_ok());
+    assert!(result.unwrap().recv().await.is_none());
+}
+
+#[tokio::test]
+async fn stop_when_relinking_exhausted() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32>>();
+    let lifecycle = make_lifecycle(event_tx);
+    let (_handle_tx, handle_rx) = mpsc::channel(8);
+    let model = ValueDownlinkModel::new(handle_rx, lifecycle);
+
+    let config = DownlinkConfig {
+        events_when_not_synced: false,
+        terminate_on_unlinked: true,
+        buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::immediate(non_zero_usize!(1)),
+    };
+
+    let result = run_relinking_downlink_task(
+        DownlinkTask::new(model),
+        config,
+        |mut writer, _reader, relink| async move {
+            relink.fail();
+            relink.fail();
+            writer.send_value::<i32>(DownlinkNotification::Linked).await;
+            writer
+                .send_value::<i32>(DownlinkNotification::Unlinked)
+                .await;
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+            expect_event(&mut event_rx, TestMessage::Unlinked).await;
+            expect_event(&mut event_rx, TestMessage::Reconnecting).await;
+            (event_rx, relink)
+        },
+        TestValueWriter::new,
+    )
+    .await;
+    assert!(result.is_ok());
+    let (mut event_rx, _relink) = result.unwrap();
+    assert!(event_rx.recv().await.is_none());
+}
+
 struct StringDecoder<T>(PhantomData<T>);
 
 impl<T> Default for StringDecoder<T> {
@@ -441,6 +600,7 @@ async fn send_on_downlink() {
         events_when_not_synced: false,
         terminate_on_unlinked: true,
         buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
     };
 
     let result = run_value_downlink_task(
@@ -455,3 +615,128 @@ async fn send_on_downlink() {
     .await;
     assert!(result.is_ok());
 }
+
+#[tokio::test]
+async fn publish_downlink_state() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32>>();
+    let lifecycle = make_lifecycle(event_tx);
+    let (_handle_tx, handle_rx) = mpsc::channel(8);
+    let (state_tx, state_rx) = watch::channel(ValueDownlinkState::default());
+
+    let model = ValueDownlinkModel::new(handle_rx, lifecycle).with_state_watch(state_tx);
+
+    let config = DownlinkConfig {
+        events_when_not_synced: false,
+        terminate_on_unlinked: true,
+        buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
+    };
+
+    let expected = |linked, synced, value| ValueDownlinkState {
+        linked,
+        synced,
+        value,
+        origin: None,
+    };
+
+    let result = run_value_downlink_task(
+        DownlinkTask::new(model),
+        config,
+        |mut writer, reader| async move {
+            let _reader = reader;
+
+            writer.send_value::<i32>(DownlinkNotification::Linked).await;
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+            assert_eq!(*state_rx.borrow(), expected(true, false, None));
+
+            writer
+                .send_value::<i32>(DownlinkNotification::Event { body: 5 })
+                .await;
+            writer.send_value::<i32>(DownlinkNotification::Synced).await;
+            expect_event(&mut event_rx, TestMessage::Synced(5)).await;
+            assert_eq!(*state_rx.borrow(), expected(true, true, Some(5)));
+
+            writer
+                .send_value::<i32>(DownlinkNotification::Event { body: 6 })
+                .await;
+            expect_event(&mut event_rx, TestMessage::Event(6)).await;
+            expect_event(&mut event_rx, TestMessage::Set(Some(5), 6)).await;
+            assert_eq!(*state_rx.borrow(), expected(true, true, Some(6)));
+
+            writer
+                .send_value::<i32>(DownlinkNotification::Unlinked)
+                .await;
+            expect_event(&mut event_rx, TestMessage::Unlinked).await;
+            assert_eq!(*state_rx.borrow(), expected(false, false, Some(6)));
+            state_rx
+        },
+    )
+    .await;
+    assert!(result.is_ok());
+    assert!(result.unwrap().has_changed().is_err());
+}
+
+#[tokio::test]
+async fn publish_event_origins() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<TestMessage<i32>>();
+    let lifecycle = make_lifecycle(event_tx);
+    let (_handle_tx, handle_rx) = mpsc::channel(8);
+    let (state_tx, state_rx) = watch::channel(ValueDownlinkState::default());
+
+    let model = ValueDownlinkModel::new(handle_rx, lifecycle).with_state_watch(state_tx);
+
+    let config = DownlinkConfig {
+        events_when_not_synced: false,
+        terminate_on_unlinked: true,
+        buffer_size: DEFAULT_BUFFER_SIZE,
+        relink: RetryStrategy::none(),
+    };
+
+    let origin = Uuid::from_u128(8383);
+
+    let result = run_value_downlink_task(
+        DownlinkTask::new(model),
+        config,
+        |mut writer, reader| async move {
+            let _reader = reader;
+
+            writer.send_value::<i32>(DownlinkNotification::Linked).await;
+            expect_event(&mut event_rx, TestMessage::Linked).await;
+            writer
+                .send_value::<i32>(DownlinkNotification::Event { body: 5 })
+                .await;
+            writer.send_value::<i32>(DownlinkNotification::Synced).await;
+            expect_event(&mut event_rx, TestMessage::Synced(5)).await;
+            assert_eq!(state_rx.borrow().origin, None);
+
+            writer
+                .send_value::<i32>(DownlinkNotification::Origin { origin })
+                .await;
+            writer
+                .send_value::<i32>(DownlinkNotification::Event { body: 6 })
+                .await;
+            expect_event(&mut event_rx, TestMessage::Event(6)).await;
+            expect_event(&mut event_rx, TestMessage::Set(Some(5), 6)).await;
+            {
+                let state = state_rx.borrow();
+                assert_eq!(state.value, Some(6));
+                assert_eq!(state.origin, Some(origin));
+            }
+
+            //An event with no origin clears the previous origin.
+            writer
+                .send_value::<i32>(DownlinkNotification::Event { body: 7 })
+                .await;
+            expect_event(&mut event_rx, TestMessage::Event(7)).await;
+            expect_event(&mut event_rx, TestMessage::Set(Some(6), 7)).await;
+            {
+                let state = state_rx.borrow();
+                assert_eq!(state.value, Some(7));
+                assert_eq!(state.origin, None);
+            }
+            state_rx
+        },
+    )
+    .await;
+    assert!(result.is_ok());
+}
@@ -14,13 +14,14 @@
 
 use either::Either;
 use std::fmt::Display;
-use swimos_client_api::DownlinkConfig;
+use swimos_client_api::{BoxRelink, DownlinkConfig};
 
 use futures::{Sink, SinkExt, StreamExt};
-use tokio::sync::mpsc;
+use tokio::sync::watch;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::codec::{FramedRead, FramedWrite};
 use tracing::{info_span, trace, Instrument};
+use uuid::Uuid;
 
 use swimos_agent_protocol::encoding::downlink::{
     DownlinkOperationEncoder, ValueNotificationDecoder,
@@ -35,9 +36,11 @@ use swimos_utilities::byte_channel::{ByteReader, ByteWriter};
 use swimos_utilities::future::{immediate_or_join, race};
 
 use crate::model::lifecycle::ValueDownlinkLifecycle;
-use crate::model::ValueDownlinkSet;
+use crate::model::{ValueDownlinkSet, ValueDownlinkState};
 use crate::ValueDownlinkModel;
 
+use super::Relinker;
+
 /// Task to drive a value downlink, calling lifecyle events at appropriate points.
 ///
 /// # Arguments
@@ -47,27 +50,68 @@ use crate::ValueDownlinkModel;
 /// * `config` - Configuration parameters to the downlink.
 /// * `input` - Input stream for messages to the downlink from the runtime.
 /// * `output` - Output stream for messages from the downlink to the runtime.
+/// * `relink` - Used to attach the downlink to the lane again if its connection is lost.
 pub async fn value_downlink_task<T, LC>(
     model: ValueDownlinkModel<T, LC>,
     path: Address<Text>,
     config: DownlinkConfig,
     input: ByteReader,
     output: ByteWriter,
+    relink: Option<BoxRelink>,
 ) -> Result<(), DownlinkTaskError>
 where
     T: Form + Send + Sync + Clone + 'static,
     LC: ValueDownlinkLifecycle<T>,
 {
-    let ValueDownlinkModel { handle, lifecycle } = model;
-    run_io(
-        config,
-        input,
-        lifecycle,
+    let ValueDownlinkModel {
         handle,
-        FramedWrite::new(output, DownlinkOperationEncoder::default()),
-    )
-    .instrument(info_span!("Downlink io task.", %path))
-    .await
+        mut lifecycle,
+        state,
+    } = model;
+    let publisher = StatePublisher(state);
+    let mut set_stream = ReceiverStream::new(handle);
+    let mut relinker = Relinker::new(&config, relink);
+    let mut io = (input, output);
+    let mut relinked = false;
+    loop {
+        let (input, output) = io;
+        run_io(
+            &config,
+            input,
+            &mut lifecycle,
+            &mut set_stream,
+            FramedWrite::new(output, DownlinkOperationEncoder::default()),
+            relinked,
+            &publisher,
+        )
+        .instrument(info_span!("Downlink io task.", %path))
+        .await?;
+        publisher.update(|state| {
+            state.linked = false;
+            state.synced = false;
+        });
+        match relinker.relink(&mut lifecycle, None).await {
+            Some(new_io) => {
+                io = new_io;
+                relinked = true;
+            }
+            None => break Ok(()),
+        }
+    }
+}
+
+/// Publishes changes in the state of the downlink, if anything is observing it.
+struct StatePublisher<T>(Option<watch::Sender<ValueDownlinkState<T>>>);
+
+impl<T> StatePublisher<T> {
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut ValueDownlinkState<T>),
+    {
+        if let StatePublisher(Some(tx)) = self {
+            tx.send_modify(f);
+        }
+    }
 }
 
 enum State<T> {
@@ -96,11 +140,13 @@ enum Mode {
 }
 
 async fn run_io<T, LC, S>(
-    config: DownlinkConfig,
+    config: &DownlinkConfig,
     input: ByteReader,
-    mut lifecycle: LC,
-    handle_rx: mpsc::Receiver<ValueDownlinkSet<T>>,
+    lifecycle: &mut LC,
+    set_stream: &mut ReceiverStream<ValueDownlinkSet<T>>,
     mut framed: S,
+    relinked: bool,
+    publisher: &StatePublisher<T>,
 ) -> Result<(), DownlinkTaskError>
 where
     LC: ValueDownlinkLifecycle<T>,
@@ -109,14 +155,14 @@ where
 {
     let mut mode = Mode::ReadWrite;
     let mut state: State<T> = State::Unlinked;
+    let mut origin = None;
     let mut framed_read = FramedRead::new(input, ValueNotificationDecoder::default());
-    let mut set_stream = ReceiverStream::new(handle_rx);
 
     let DownlinkConfig {
         events_when_not_synced,
         terminate_on_unlinked,
         ..
-    } = config;
+    } = *config;
 
     loop {
         match mode {
@@ -136,10 +182,13 @@ where
                     Either::Right(Some(Ok(frame))) => {
                         match on_read(
                             state,
-                            &mut lifecycle,
+                            lifecycle,
                             frame,
                             events_when_not_synced,
                             terminate_on_unlinked,
+                            relinked,
+                            publisher,
+                            &mut origin,
                         )
                         .await
                         {
@@ -156,10 +205,13 @@ where
                 while let Some(result) = framed_read.next().await {
                     match on_read(
                         state,
-                        &mut lifecycle,
+                        lifecycle,
                         result?,
                         events_when_not_synced,
                         terminate_on_unlinked,
+                        relinked,
+                        publisher,
+                        &mut origin,
                     )
                     .await
                     {
@@ -188,15 +240,19 @@ where
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn on_read<T, LC>(
     mut state: State<T>,
     lifecycle: &mut LC,
     notification: DownlinkNotification<T>,
     events_when_not_synced: bool,
     terminate_on_unlinked: bool,
+    relinked: bool,
+    publisher: &StatePublisher<T>,
+    origin: &mut Option<Uuid>,
 ) -> Result<Option<State<T>>, DownlinkTaskError>
 where
-    T: 'static + Form + Send + Sync,
+    T: 'static + Form + Send + Sync + Clone,
     LC: ValueDownlinkLifecycle<T>,
 {
     match notification {
@@ -207,6 +263,7 @@ where
             );
             if matches!(&state, State::Unlinked) {
                 lifecycle.on_linked().await;
+                publisher.update(|state| state.linked = true);
                 state = State::Linked(None);
             }
         }
@@ -217,18 +274,33 @@ where
             );
             return match state {
                 State::Linked(Some(value)) => {
+                    publisher.update(|state| {
+                        state.synced = true;
+                        state.value = Some(value.clone());
+                    });
                     lifecycle.on_synced(&value).await;
+                    if relinked {
+                        lifecycle.on_resynced(&value).await;
+                    }
                     Ok(Some(State::Synced(value)))
                 }
                 _ => Err(DownlinkTaskError::SyncedWithNoValue),
             };
         }
+        DownlinkNotification::Origin { origin: id } => {
+            trace!(
+                "Received Origin '{id}' in state {state}",
+                state = ShowState(&state)
+            );
+            *origin = Some(id);
+        }
         DownlinkNotification::Event { body } => {
             trace!(
                 "Received Event with body '{body}' in state {state}",
                 body = print_recon(&body),
                 state = ShowState(&state)
             );
+            let origin = origin.take();
             match state {
                 State::Linked(value) => {
                     if events_when_not_synced {
@@ -238,6 +310,10 @@ where
                     return Ok(Some(State::Linked(Some(body))));
                 }
                 State::Synced(value) => {
+                    publisher.update(|state| {
+                        state.value = Some(body.clone());
+                        state.origin = origin;
+                    });
                     lifecycle.on_event(&body).await;
                     lifecycle.on_set(Some(&value), &body).await;
                     return Ok(Some(State::Synced(body)));
@@ -245,11 +321,43 @@ where
                 _ => {}
             }
         }
+        DownlinkNotification::Version { .. } => {}
+        DownlinkNotification::Advisory { advice } => {
+            trace!(
+                "Received Advisory '{advice}' in state {state}",
+                state = ShowState(&state)
+            );
+            lifecycle.on_advisory(advice).await;
+        }
+        DownlinkNotification::NodeStopped { notice } => {
+            trace!(
+                "Received NodeStopped '{notice}' in state {state}",
+                state = ShowState(&state)
+            );
+            lifecycle.on_node_stopped(notice).await;
+        }
+        DownlinkNotification::Failed { failure } => {
+            trace!(
+                "Received Failed '{failure}' in state {state}",
+                state = ShowState(&state)
+            );
+            publisher.update(|state| {
+                state.linked = false;
+                state.synced = false;
+            });
+            lifecycle.on_failed(failure).await;
+            lifecycle.on_unlinked().await;
+            return Err(DownlinkTaskError::LinkFailed(failure));
+        }
         DownlinkNotification::Unlinked => {
             trace!(
                 "Received Unlinked in state {state}",
                 state = ShowState(&state)
             );
+            publisher.update(|state| {
+                state.linked = false;
+                state.synced = false;
+            });
             lifecycle.on_unlinked().await;
             if terminate_on_unlinked {
                 trace!("Terminating on Unlinked.");
@@ -26,6 +26,7 @@ pub mod attr_names {
     pub const CONV_NAME: &str = "convention";
     pub const TAG_NAME: &str = "tag";
     pub const FIELDS_NAME: &str = "fields_convention";
+    pub const RENAME_ALL_NAME: &str = "rename_all";
     pub const SCHEMA_NAME: &str = "schema";
 
     pub const FORM_PATH: Symbol = Symbol(FORM_NAME);
@@ -36,8 +37,10 @@ pub mod attr_names {
     pub const HEADER_BODY_PATH: Symbol = Symbol("header_body");
     pub const TAG_PATH: Symbol = Symbol(TAG_NAME);
     pub const SKIP_PATH: Symbol = Symbol("skip");
+    pub const DEFAULT_PATH: Symbol = Symbol("default");
     pub const SCHEMA_PATH: Symbol = Symbol(SCHEMA_NAME);
     pub const NEWTYPE_PATH: Symbol = Symbol("newtype");
+    pub const UNTAGGED_PATH: Symbol = Symbol("untagged");
 }
 
 /// An enumeration representing the contents of an input.
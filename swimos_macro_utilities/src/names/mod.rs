@@ -42,12 +42,12 @@ impl FromStr for CaseConvention {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "camel" | "camel-lower" => Ok(CaseConvention::CamelLower),
-            "camel-upper" => Ok(CaseConvention::CamelUpper),
-            "snake" | "snake-lower" => Ok(CaseConvention::SnakeLower),
-            "snake-upper" => Ok(CaseConvention::SnakeUpper),
-            "kebab" | "kebab-lower" => Ok(CaseConvention::KebabLower),
-            "kebab-upper" => Ok(CaseConvention::KebabUpper),
+            "camel" | "camel-lower" | "camelCase" => Ok(CaseConvention::CamelLower),
+            "camel-upper" | "PascalCase" => Ok(CaseConvention::CamelUpper),
+            "snake" | "snake-lower" | "snake_case" => Ok(CaseConvention::SnakeLower),
+            "snake-upper" | "SCREAMING_SNAKE_CASE" => Ok(CaseConvention::SnakeUpper),
+            "kebab" | "kebab-lower" | "kebab-case" => Ok(CaseConvention::KebabLower),
+            "kebab-upper" | "SCREAMING-KEBAB-CASE" => Ok(CaseConvention::KebabUpper),
             ow => Err(InvalidCaseConvention(ow.to_string())),
         }
     }
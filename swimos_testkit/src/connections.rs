@@ -0,0 +1,218 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, io, net::SocketAddr, sync::Arc};
+
+use bytes::BytesMut;
+use futures::{
+    future::{ready, BoxFuture},
+    stream::BoxStream,
+    FutureExt, StreamExt,
+};
+use ratchet::{
+    ExtensionProvider, NegotiatedExtension, Role, WebSocket, WebSocketConfig, WebSocketStream,
+};
+use swimos_messages::remote_protocol::FindNode;
+use swimos_remote::{
+    dns::{BoxDnsResolver, DnsResolver},
+    websocket::{RatchetError, WarpProtocol, WebsocketClient, WebsocketServer, WsOpenFuture},
+    ClientConnections, ConnectionError, ConnectionResult, Listener, ListenerError, Scheme,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, DuplexStream},
+    sync::{mpsc, Mutex},
+};
+
+#[derive(Debug)]
+struct Inner {
+    addrs: HashMap<(String, u16), SocketAddr>,
+    sockets: HashMap<SocketAddr, DuplexStream>,
+}
+
+impl Inner {
+    fn new<R, S>(resolver: R, sockets: S) -> Inner
+    where
+        R: IntoIterator<Item = ((String, u16), SocketAddr)>,
+        S: IntoIterator<Item = (SocketAddr, DuplexStream)>,
+    {
+        Inner {
+            addrs: HashMap::from_iter(resolver),
+            sockets: HashMap::from_iter(sockets),
+        }
+    }
+}
+
+/// Client networking that resolves a fixed set of hosts and connects to in-memory sockets. Each
+/// socket can only be opened once.
+#[derive(Debug, Clone)]
+pub struct MockClientConnections {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl MockClientConnections {
+    /// # Arguments
+    /// * `resolver` - The addresses to which each host and port resolve.
+    /// * `sockets` - The sockets to open for each address.
+    pub fn new<R, S>(resolver: R, sockets: S) -> MockClientConnections
+    where
+        R: IntoIterator<Item = ((String, u16), SocketAddr)>,
+        S: IntoIterator<Item = (SocketAddr, DuplexStream)>,
+    {
+        MockClientConnections {
+            inner: Arc::new(Mutex::new(Inner::new(resolver, sockets))),
+        }
+    }
+
+    /// Make a new socket available for an address (for example, so that a client can reconnect
+    /// after its previous connection was dropped).
+    pub async fn add_socket(&self, addr: SocketAddr, socket: DuplexStream) {
+        self.inner.lock().await.sockets.insert(addr, socket);
+    }
+}
+
+impl ClientConnections for MockClientConnections {
+    type ClientSocket = DuplexStream;
+
+    fn try_open(
+        &self,
+        _scheme: Scheme,
+        _host: Option<&str>,
+        addr: SocketAddr,
+    ) -> BoxFuture<'_, ConnectionResult<Self::ClientSocket>> {
+        async move {
+            self.inner
+                .lock()
+                .await
+                .sockets
+                .remove(&addr)
+                .ok_or_else(|| ConnectionError::ConnectionFailed(io::ErrorKind::NotFound.into()))
+        }
+        .boxed()
+    }
+
+    fn dns_resolver(&self) -> BoxDnsResolver {
+        Box::new(self.clone())
+    }
+
+    fn lookup(
+        &self,
+        host: String,
+        port: u16,
+    ) -> BoxFuture<'static, std::io::Result<Vec<SocketAddr>>> {
+        self.resolve(host, port).boxed()
+    }
+}
+
+impl DnsResolver for MockClientConnections {
+    type ResolveFuture = BoxFuture<'static, io::Result<Vec<SocketAddr>>>;
+
+    fn resolve(&self, host: String, port: u16) -> Self::ResolveFuture {
+        let inner = self.inner.clone();
+        async move {
+            match inner.lock().await.addrs.get(&(host, port)) {
+                Some(sock) => Ok(vec![*sock]),
+                None => Err(io::ErrorKind::NotFound.into()),
+            }
+        }
+        .boxed()
+    }
+}
+
+/// The outcome of opening a web-socket connection with a [`MockWs`].
+pub enum WsAction {
+    /// The connection is opened (without performing a handshake).
+    Open,
+    /// Opening the connection fails with an error.
+    Fail(Box<dyn Fn() -> RatchetError + Send + Sync + 'static>),
+}
+
+impl WsAction {
+    /// Fail to open the connection with the error produced by a function.
+    pub fn fail<F>(with: F) -> WsAction
+    where
+        F: Fn() -> RatchetError + Send + Sync + 'static,
+    {
+        WsAction::Fail(Box::new(with))
+    }
+}
+
+/// Web-socket implementation that opens connections without a handshake, or fails to open them,
+/// according to the address of the remote. Connections that are opened should be attached to a
+/// [`crate::MockServer`] with [`crate::MockServer::new`].
+pub struct MockWs {
+    states: HashMap<String, WsAction>,
+}
+
+impl MockWs {
+    /// # Arguments
+    /// * `states` - The outcome of opening a connection for each address.
+    pub fn new<S>(states: S) -> MockWs
+    where
+        S: IntoIterator<Item = (String, WsAction)>,
+    {
+        MockWs {
+            states: HashMap::from_iter(states),
+        }
+    }
+}
+
+impl WebsocketClient for MockWs {
+    fn open_connection<'a, Sock, Provider>(
+        &self,
+        socket: Sock,
+        _provider: &'a Provider,
+        addr: String,
+    ) -> WsOpenFuture<'a, Sock, Provider::Extension, RatchetError>
+    where
+        Sock: WebSocketStream + Send,
+        Provider: ExtensionProvider + Send + Sync + 'static,
+        Provider::Extension: Send + Sync + 'static,
+    {
+        let result = match self.states.get(&addr) {
+            Some(WsAction::Open) => Ok((
+                WebSocket::from_upgraded(
+                    WebSocketConfig::default(),
+                    socket,
+                    NegotiatedExtension::from(None),
+                    BytesMut::default(),
+                    Role::Client,
+                ),
+                WarpProtocol::default(),
+            )),
+            Some(WsAction::Fail(e)) => Err(e()),
+            None => Err(ratchet::Error::new(ratchet::ErrorKind::Http).into()),
+        };
+        ready(result).boxed()
+    }
+}
+
+impl WebsocketServer for MockWs {
+    type WsStream<Sock, Ext> =
+        BoxStream<'static, Result<(WebSocket<Sock, Ext>, SocketAddr, WarpProtocol), ListenerError>>;
+
+    fn wrap_listener<Sock, L, Provider>(
+        &self,
+        _listener: L,
+        _provider: Provider,
+        _find: mpsc::Sender<FindNode>,
+    ) -> Self::WsStream<Sock, Provider::Extension>
+    where
+        Sock: AsyncRead + AsyncWrite + Unpin + Send + Sync + 'static,
+        L: Listener<Sock> + Send + 'static,
+        Provider: ExtensionProvider + Send + Sync + Unpin + 'static,
+        Provider::Extension: Send + Sync + Unpin + 'static,
+    {
+        futures::stream::pending().boxed()
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use swimos_form::Form;
+use swimos_model::{Text, Value};
+
+/// Warp envelopes, in a form that is convenient to construct and match on in tests.
+#[derive(Clone, Debug, PartialEq, Form)]
+pub enum Envelope {
+    #[form(tag = "link")]
+    Link {
+        #[form(name = "node")]
+        node_uri: Text,
+        #[form(name = "lane")]
+        lane_uri: Text,
+        rate: Option<f64>,
+        prio: Option<f64>,
+        #[form(body)]
+        body: Option<Value>,
+    },
+    #[form(tag = "sync")]
+    Sync {
+        #[form(name = "node")]
+        node_uri: Text,
+        #[form(name = "lane")]
+        lane_uri: Text,
+        rate: Option<f64>,
+        prio: Option<f64>,
+        #[form(body)]
+        body: Option<Value>,
+    },
+    #[form(tag = "unlink")]
+    Unlink {
+        #[form(name = "node")]
+        node_uri: Text,
+        #[form(name = "lane")]
+        lane_uri: Text,
+        #[form(body)]
+        body: Option<Value>,
+    },
+    #[form(tag = "command")]
+    Command {
+        #[form(name = "node")]
+        node_uri: Text,
+        #[form(name = "lane")]
+        lane_uri: Text,
+        #[form(body)]
+        body: Option<Value>,
+    },
+    #[form(tag = "linked")]
+    Linked {
+        #[form(name = "node")]
+        node_uri: Text,
+        #[form(name = "lane")]
+        lane_uri: Text,
+        rate: Option<f64>,
+        prio: Option<f64>,
+        #[form(body)]
+        body: Option<Value>,
+    },
+    #[form(tag = "synced")]
+    Synced {
+        #[form(name = "node")]
+        node_uri: Text,
+        #[form(name = "lane")]
+        lane_uri: Text,
+        #[form(body)]
+        body: Option<Value>,
+    },
+    #[form(tag = "unlinked")]
+    Unlinked {
+        #[form(name = "node")]
+        node_uri: Text,
+        #[form(name = "lane")]
+        lane_uri: Text,
+        #[form(body)]
+        body: Option<Value>,
+    },
+    #[form(tag = "event")]
+    Event {
+        #[form(name = "node")]
+        node_uri: Text,
+        #[form(name = "lane")]
+        lane_uri: Text,
+        #[form(body)]
+        body: Option<Value>,
+    },
+}
+
+impl Envelope {
+    /// The node and lane to which the envelope is addressed.
+    pub fn path(&self) -> (&Text, &Text) {
+        match self {
+            Envelope::Link {
+                node_uri, lane_uri, ..
+            }
+            | Envelope::Sync {
+                node_uri, lane_uri, ..
+            }
+            | Envelope::Unlink {
+                node_uri, lane_uri, ..
+            }
+            | Envelope::Command {
+                node_uri, lane_uri, ..
+            }
+            | Envelope::Linked {
+                node_uri, lane_uri, ..
+            }
+            | Envelope::Synced {
+                node_uri, lane_uri, ..
+            }
+            | Envelope::Unlinked {
+                node_uri, lane_uri, ..
+            }
+            | Envelope::Event {
+                node_uri, lane_uri, ..
+            } => (node_uri, lane_uri),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # SwimOS Test Kit
+//!
+//! Fixtures for testing Warp clients against a mock remote, without running a server.
+//!
+//! - A [`MockServer`] plays the part of the remote end of a web-socket connection. Any number
+//!   of [`Lane`]s can be taken from it and driven concurrently to respond to the envelopes sent
+//!   by the client for that lane.
+//! - A [`Scenario`] scripts the exchange for a lane as a sequence of [`Step`]s.
+//! - A [`Fault`] can be injected into the envelopes sent by the server to simulate a badly behaved
+//!   remote (dropped frames, delays and connections closed part way through a sync).
+//! - [`MockClientConnections`] and [`MockWs`] replace the networking of a client so that it
+//!   connects to an in-memory socket.
+//!
+//! A mock server can either be attached to a socket on which the web-socket handshake has already
+//! been performed (for use with [`MockWs`]), or it can perform the handshake itself with
+//! [`MockServer::accept`]. In the second case, the socket can be a TLS stream (for example, from
+//! the listener of a `RustlsServerNetworking`) to test clients connecting with `wss`.
+
+mod connections;
+mod envelope;
+mod scenario;
+mod server;
+
+#[cfg(test)]
+mod tests;
+
+pub use connections::{MockClientConnections, MockWs, WsAction};
+pub use envelope::Envelope;
+pub use scenario::{Scenario, Step};
+pub use server::{Fault, Lane, MockServer};
@@ -0,0 +1,100 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use swimos_form::Form;
+use swimos_model::Value;
+
+use crate::Fault;
+
+/// A step in a [`Scenario`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// Wait for a link request and respond to it.
+    AwaitLink,
+    /// Wait for a sync request and respond to it with an event for each of the values.
+    AwaitSync(Vec<Value>),
+    /// Wait for a command with the specified body.
+    AwaitCommand(Value),
+    /// Wait for an unlink request and respond to it.
+    AwaitUnlink,
+    /// Send an event with the specified body.
+    SendEvent(Value),
+    /// Send an unlinked envelope.
+    SendUnlinked,
+    /// Inject a fault into the envelopes that are sent by the server.
+    Inject(Fault),
+    /// Wait for the specified duration.
+    Wait(Duration),
+    /// Wait for the connection to be closed.
+    AwaitClosed,
+}
+
+/// A scripted exchange between a client and a lane of a [`crate::MockServer`], to be run with
+/// [`crate::Lane::run`]. Each step is completed before the next step is started.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Scenario {
+    steps: Vec<Step>,
+}
+
+impl Scenario {
+    /// Add a step to the end of the scenario.
+    pub fn then(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn await_link(self) -> Self {
+        self.then(Step::AwaitLink)
+    }
+
+    pub fn await_sync<V: Form>(self, values: Vec<V>) -> Self {
+        let values = values.iter().map(Form::as_value).collect();
+        self.then(Step::AwaitSync(values))
+    }
+
+    pub fn await_command<V: Form>(self, expected: V) -> Self {
+        self.then(Step::AwaitCommand(expected.as_value()))
+    }
+
+    pub fn await_unlink(self) -> Self {
+        self.then(Step::AwaitUnlink)
+    }
+
+    pub fn send_event<V: Form>(self, value: V) -> Self {
+        self.then(Step::SendEvent(value.as_value()))
+    }
+
+    pub fn send_unlinked(self) -> Self {
+        self.then(Step::SendUnlinked)
+    }
+
+    pub fn inject(self, fault: Fault) -> Self {
+        self.then(Step::Inject(fault))
+    }
+
+    pub fn wait(self, duration: Duration) -> Self {
+        self.then(Step::Wait(duration))
+    }
+
+    pub fn await_closed(self) -> Self {
+        self.then(Step::AwaitClosed)
+    }
+
+    /// The steps of the scenario, in order.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
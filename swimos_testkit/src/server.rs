@@ -0,0 +1,451 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use bytes::BytesMut;
+use parking_lot::Mutex;
+use ratchet::{
+    CloseCode, CloseReason, Message, NegotiatedExtension, NoExt, NoExtProvider, PayloadType,
+    ProtocolRegistry, Role, WebSocket, WebSocketConfig, WebSocketStream,
+};
+use swimos_form::Form;
+use swimos_model::{Text, Value};
+use swimos_recon::{parser::parse_recognize, print_recon};
+use swimos_remote::websocket::WARP;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{Envelope, Scenario, Step};
+
+/// Faults that can be injected into the envelopes that are sent by a [`MockServer`]. A fault
+/// applies to the envelopes sent on all lanes after it was injected, replacing any fault that
+/// was injected previously.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Discard the next `n` envelopes without sending them.
+    DropFrames(usize),
+    /// Wait for the specified duration before sending each envelope.
+    Delay(Duration),
+    /// Send the next `n` envelopes and then close the connection. This can be used to close the
+    /// connection part way through the sync of a lane.
+    CloseAfter(usize),
+}
+
+#[derive(Debug)]
+enum Outgoing {
+    Envelope(Envelope, oneshot::Sender<()>),
+    Fault(Option<Fault>),
+    Close,
+}
+
+#[derive(Debug)]
+enum Inbound {
+    Envelope(Envelope),
+    Closed,
+}
+
+type LaneKey = (Text, Text);
+
+/// Routes the envelopes received from the client to the lanes to which they are addressed.
+/// Envelopes for lanes that have not been taken from the server yet are held until they are.
+#[derive(Debug, Default)]
+struct Router {
+    lanes: HashMap<LaneKey, mpsc::UnboundedSender<Inbound>>,
+    pending: HashMap<LaneKey, VecDeque<Envelope>>,
+    invalid: Vec<String>,
+    closed: bool,
+}
+
+impl Router {
+    fn register(&mut self, key: LaneKey) -> mpsc::UnboundedReceiver<Inbound> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        if let Some(envelopes) = self.pending.remove(&key) {
+            for envelope in envelopes {
+                let _ = tx.send(Inbound::Envelope(envelope));
+            }
+        }
+        if self.closed {
+            let _ = tx.send(Inbound::Closed);
+        } else {
+            self.lanes.insert(key, tx);
+        }
+        rx
+    }
+
+    fn route(&mut self, envelope: Envelope) {
+        let (node, lane) = envelope.path();
+        let key = (node.clone(), lane.clone());
+        match self.lanes.get(&key) {
+            Some(tx) => {
+                let _ = tx.send(Inbound::Envelope(envelope));
+            }
+            None => self.pending.entry(key).or_default().push_back(envelope),
+        }
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        for (_, tx) in self.lanes.drain() {
+            let _ = tx.send(Inbound::Closed);
+        }
+    }
+}
+
+/// A mock of the remote end of a Warp connection. Envelopes that are received from the client are
+/// routed to the [`Lane`] to which they are addressed so that any number of lanes can be driven
+/// concurrently (for example, from separate tasks). The connection is closed when the server,
+/// and all of the lanes taken from it, have been dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    router: Arc<Mutex<Router>>,
+    outgoing: mpsc::UnboundedSender<Outgoing>,
+}
+
+impl MockServer {
+    /// Attach a server to a socket on which the web-socket handshake has already been performed
+    /// (for example, by a [`crate::MockWs`]).
+    pub fn new<S>(socket: S) -> MockServer
+    where
+        S: WebSocketStream,
+    {
+        let websocket = WebSocket::from_upgraded(
+            WebSocketConfig::default(),
+            socket,
+            NegotiatedExtension::from(NoExt),
+            BytesMut::default(),
+            Role::Server,
+        );
+        MockServer::start(websocket)
+    }
+
+    /// Perform the server side of the web-socket handshake on a socket and attach a server to it.
+    /// The Warp subprotocol is accepted if the client offers it. If the socket is a TLS stream,
+    /// this can be used to test clients that connect with `wss`.
+    pub async fn accept<S>(socket: S) -> Result<MockServer, ratchet::Error>
+    where
+        S: WebSocketStream,
+    {
+        let upgrader = ratchet::accept_with(
+            socket,
+            WebSocketConfig::default(),
+            NoExtProvider,
+            ProtocolRegistry::new([WARP])?,
+        )
+        .await?;
+        let upgraded = upgrader.upgrade().await?;
+        Ok(MockServer::start(upgraded.websocket))
+    }
+
+    fn start<S>(websocket: WebSocket<S, NoExt>) -> MockServer
+    where
+        S: WebSocketStream,
+    {
+        let (mut sender, mut receiver) = websocket
+            .split()
+            .expect("A newly opened web-socket cannot be closed.");
+        let router = Arc::new(Mutex::new(Router::default()));
+        let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded_channel();
+
+        let read_router = router.clone();
+        tokio::spawn(async move {
+            let mut buffer = BytesMut::new();
+            loop {
+                match receiver.read(&mut buffer).await {
+                    Ok(Message::Text) => {
+                        let frame = String::from_utf8_lossy(buffer.as_ref()).into_owned();
+                        buffer.clear();
+                        let mut guard = read_router.lock();
+                        match parse_recognize::<Envelope>(frame.as_str(), false) {
+                            Ok(envelope) => guard.route(envelope),
+                            Err(_) => guard.invalid.push(frame),
+                        }
+                    }
+                    Ok(Message::Close(_)) | Err(_) => break,
+                    Ok(_) => buffer.clear(),
+                }
+            }
+            read_router.lock().close();
+        });
+
+        tokio::spawn(async move {
+            let mut fault = None;
+            while let Some(message) = outgoing_rx.recv().await {
+                match message {
+                    Outgoing::Envelope(envelope, written) => {
+                        match &mut fault {
+                            Some(Fault::DropFrames(n)) if *n > 0 => {
+                                *n -= 1;
+                                continue;
+                            }
+                            Some(Fault::Delay(delay)) => tokio::time::sleep(*delay).await,
+                            Some(Fault::CloseAfter(n)) => *n -= 1,
+                            _ => {}
+                        }
+                        let frame = format!("{}", print_recon(&envelope));
+                        if sender.write(frame, PayloadType::Text).await.is_err() {
+                            break;
+                        }
+                        let _ = written.send(());
+                    }
+                    Outgoing::Fault(new_fault) => {
+                        if new_fault == Some(Fault::CloseAfter(0)) {
+                            break;
+                        }
+                        fault = new_fault;
+                    }
+                    Outgoing::Close => break,
+                }
+                if fault == Some(Fault::CloseAfter(0)) {
+                    break;
+                }
+            }
+            let _ = sender
+                .close(CloseReason::new(CloseCode::Normal, None))
+                .await;
+        });
+
+        MockServer {
+            router,
+            outgoing: outgoing_tx,
+        }
+    }
+
+    /// Take the mock for a lane. Any envelopes that have already been received for the lane will
+    /// be delivered to it.
+    pub fn lane(&self, node: impl Into<Text>, lane: impl Into<Text>) -> Lane {
+        let node = node.into();
+        let lane = lane.into();
+        let inbox = self.router.lock().register((node.clone(), lane.clone()));
+        Lane {
+            node,
+            lane,
+            inbox,
+            outgoing: self.outgoing.clone(),
+        }
+    }
+
+    /// Inject a fault into the envelopes that are sent after this call.
+    pub fn inject(&self, fault: Fault) {
+        let _ = self.outgoing.send(Outgoing::Fault(Some(fault)));
+    }
+
+    /// Remove any fault that was injected previously.
+    pub fn clear_faults(&self) {
+        let _ = self.outgoing.send(Outgoing::Fault(None));
+    }
+
+    /// Close the connection, after sending any envelopes that are already queued.
+    pub fn close(&self) {
+        let _ = self.outgoing.send(Outgoing::Close);
+    }
+
+    /// The frames received from the client that could not be parsed as envelopes.
+    pub fn invalid_frames(&self) -> Vec<String> {
+        self.router.lock().invalid.clone()
+    }
+}
+
+/// The mock of a single lane of a [`MockServer`].
+#[derive(Debug)]
+pub struct Lane {
+    node: Text,
+    lane: Text,
+    inbox: mpsc::UnboundedReceiver<Inbound>,
+    outgoing: mpsc::UnboundedSender<Outgoing>,
+}
+
+impl Lane {
+    /// The node URI of the lane.
+    pub fn node(&self) -> &str {
+        self.node.as_str()
+    }
+
+    /// The URI of the lane.
+    pub fn lane(&self) -> &str {
+        self.lane.as_str()
+    }
+
+    /// Inject a fault into the envelopes that are sent by the server after this call (see
+    /// [`MockServer::inject`]).
+    pub fn inject(&self, fault: Fault) {
+        let _ = self.outgoing.send(Outgoing::Fault(Some(fault)));
+    }
+
+    /// Wait for the next envelope from the client for this lane.
+    ///
+    /// # Panics
+    /// If the connection is closed.
+    pub async fn read(&mut self) -> Envelope {
+        match self.inbox.recv().await {
+            Some(Inbound::Envelope(envelope)) => envelope,
+            _ => panic!("The connection was closed unexpectedly."),
+        }
+    }
+
+    /// Send an envelope to the client, completing when it has been written. If the connection
+    /// has been closed (or a fault prevents the envelope from being sent), it is discarded.
+    pub async fn write(&mut self, env: Envelope) {
+        let (tx, rx) = oneshot::channel();
+        if self.outgoing.send(Outgoing::Envelope(env, tx)).is_ok() {
+            let _ = rx.await;
+        }
+    }
+
+    fn check_path(&self, node_uri: &Text, lane_uri: &Text) {
+        assert_eq!(node_uri, &self.node);
+        assert_eq!(lane_uri, &self.lane);
+    }
+
+    /// Wait for a link request and respond to it.
+    pub async fn await_link(&mut self) {
+        match self.read().await {
+            Envelope::Link {
+                node_uri, lane_uri, ..
+            } => {
+                self.check_path(&node_uri, &lane_uri);
+                self.write(Envelope::Linked {
+                    node_uri,
+                    lane_uri,
+                    rate: None,
+                    prio: None,
+                    body: None,
+                })
+                .await;
+            }
+            e => panic!("Unexpected envelope {:?}", e),
+        }
+    }
+
+    /// Wait for a sync request and respond to it with an event for each of the values, followed
+    /// by a synced envelope.
+    pub async fn await_sync<V: Form>(&mut self, val: Vec<V>) {
+        self.await_sync_values(val.iter().map(Form::as_value)).await
+    }
+
+    async fn await_sync_values<I>(&mut self, values: I)
+    where
+        I: IntoIterator<Item = Value>,
+    {
+        match self.read().await {
+            Envelope::Sync {
+                node_uri, lane_uri, ..
+            } => {
+                self.check_path(&node_uri, &lane_uri);
+                for value in values {
+                    self.write(Envelope::Event {
+                        node_uri: node_uri.clone(),
+                        lane_uri: lane_uri.clone(),
+                        body: Some(value),
+                    })
+                    .await;
+                }
+                self.write(Envelope::Synced {
+                    node_uri,
+                    lane_uri,
+                    body: None,
+                })
+                .await;
+            }
+            e => panic!("Unexpected envelope {:?}", e),
+        }
+    }
+
+    /// Wait for a command and check its body.
+    pub async fn await_command<V: Form>(&mut self, expected: V) {
+        self.await_command_value(expected.as_value()).await
+    }
+
+    async fn await_command_value(&mut self, expected: Value) {
+        match self.read().await {
+            Envelope::Command {
+                node_uri,
+                lane_uri,
+                body: Some(val),
+            } => {
+                self.check_path(&node_uri, &lane_uri);
+                assert_eq!(val, expected);
+            }
+            e => panic!("Unexpected envelope {:?}", e),
+        }
+    }
+
+    /// Wait for an unlink request and respond to it.
+    pub async fn await_unlink(&mut self) {
+        match self.read().await {
+            Envelope::Unlink {
+                node_uri, lane_uri, ..
+            } => {
+                self.check_path(&node_uri, &lane_uri);
+                self.send_unlinked().await;
+            }
+            e => panic!("Unexpected envelope {:?}", e),
+        }
+    }
+
+    /// Send an unlinked envelope to the client.
+    pub async fn send_unlinked(&mut self) {
+        self.write(Envelope::Unlinked {
+            node_uri: self.node.clone(),
+            lane_uri: self.lane.clone(),
+            body: None,
+        })
+        .await;
+    }
+
+    /// Send an event to the client.
+    pub async fn send_event<V: Form>(&mut self, val: V) {
+        self.send_event_value(val.as_value()).await
+    }
+
+    async fn send_event_value(&mut self, value: Value) {
+        self.write(Envelope::Event {
+            node_uri: self.node.clone(),
+            lane_uri: self.lane.clone(),
+            body: Some(value),
+        })
+        .await;
+    }
+
+    /// Wait for the connection to be closed.
+    ///
+    /// # Panics
+    /// If another envelope is received for the lane first.
+    pub async fn await_closed(&mut self) {
+        match self.inbox.recv().await {
+            Some(Inbound::Closed) | None => {}
+            Some(Inbound::Envelope(e)) => panic!("Unexpected envelope {:?}", e),
+        }
+    }
+
+    /// Run the steps of a scenario, in order.
+    pub async fn run(&mut self, scenario: &Scenario) {
+        for step in scenario.steps() {
+            match step {
+                Step::AwaitLink => self.await_link().await,
+                Step::AwaitSync(values) => self.await_sync_values(values.iter().cloned()).await,
+                Step::AwaitCommand(expected) => self.await_command_value(expected.clone()).await,
+                Step::AwaitUnlink => self.await_unlink().await,
+                Step::SendEvent(value) => self.send_event_value(value.clone()).await,
+                Step::SendUnlinked => self.send_unlinked().await,
+                Step::Inject(fault) => self.inject(*fault),
+                Step::Wait(duration) => tokio::time::sleep(*duration).await,
+                Step::AwaitClosed => self.await_closed().await,
+            }
+        }
+    }
+}
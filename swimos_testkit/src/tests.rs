@@ -0,0 +1,481 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{future::Future, path::PathBuf, sync::Arc, time::Duration};
+
+use bytes::BytesMut;
+use futures::{future::join, StreamExt};
+use ratchet::{
+    Message, NegotiatedExtension, NoExt, NoExtProvider, PayloadType, ProtocolRegistry, Role,
+    WebSocket, WebSocketConfig, WebSocketStream,
+};
+use rustls::crypto::aws_lc_rs;
+use swimos_model::{Text, Value};
+use swimos_recon::{parser::parse_recognize, print_recon};
+use swimos_remote::{
+    dns::Resolver,
+    tls::{
+        CertChain, CertificateFile, ClientConfig, PrivateKey, RustlsClientNetworking,
+        RustlsServerNetworking, ServerConfig,
+    },
+    websocket::WARP,
+    ClientConnections, Listener, Scheme,
+};
+use tokio::io::duplex;
+
+use crate::{Envelope, Fault, MockServer, Scenario};
+
+const TEST_TIMEOUT: Duration = Duration::from_secs(5);
+const NODE: &str = "/node";
+const LANE: &str = "lane";
+const OTHER_LANE: &str = "other";
+
+async fn with_timeout<F: Future>(f: F) -> F::Output {
+    tokio::time::timeout(TEST_TIMEOUT, f)
+        .await
+        .expect("Test timed out.")
+}
+
+/// The client end of a connection to a mock server.
+struct TestClient<S> {
+    websocket: WebSocket<S, NoExt>,
+    buffer: BytesMut,
+}
+
+impl<S: WebSocketStream> TestClient<S> {
+    fn new(websocket: WebSocket<S, NoExt>) -> Self {
+        TestClient {
+            websocket,
+            buffer: BytesMut::new(),
+        }
+    }
+
+    async fn send(&mut self, envelope: Envelope) {
+        let frame = format!("{}", print_recon(&envelope));
+        self.websocket
+            .write(frame, PayloadType::Text)
+            .await
+            .expect("Write failed.");
+    }
+
+    async fn send_text(&mut self, frame: &str) {
+        self.websocket
+            .write(frame, PayloadType::Text)
+            .await
+            .expect("Write failed.");
+    }
+
+    /// Read the next envelope, returning nothing if the connection is closed.
+    async fn recv(&mut self) -> Option<Envelope> {
+        let TestClient { websocket, buffer } = self;
+        buffer.clear();
+        match websocket.read(buffer).await {
+            Ok(Message::Text) => {
+                let frame = std::str::from_utf8(buffer.as_ref()).expect("Invalid UTF8.");
+                Some(parse_recognize::<Envelope>(frame, false).expect("Invalid envelope."))
+            }
+            Ok(Message::Close(_)) | Err(_) => None,
+            Ok(ow) => panic!("Unexpected message: {:?}", ow),
+        }
+    }
+
+    async fn expect(&mut self, expected: Envelope) {
+        assert_eq!(self.recv().await, Some(expected));
+    }
+}
+
+fn connect() -> (TestClient<tokio::io::DuplexStream>, MockServer) {
+    let (client, server) = duplex(4096);
+    let websocket = WebSocket::from_upgraded(
+        WebSocketConfig::default(),
+        client,
+        NegotiatedExtension::from(NoExt),
+        BytesMut::default(),
+        Role::Client,
+    );
+    (TestClient::new(websocket), MockServer::new(server))
+}
+
+fn link(lane: &str) -> Envelope {
+    Envelope::Link {
+        node_uri: Text::new(NODE),
+        lane_uri: Text::new(lane),
+        rate: None,
+        prio: None,
+        body: None,
+    }
+}
+
+fn sync(lane: &str) -> Envelope {
+    Envelope::Sync {
+        node_uri: Text::new(NODE),
+        lane_uri: Text::new(lane),
+        rate: None,
+        prio: None,
+        body: None,
+    }
+}
+
+fn command(lane: &str, value: i32) -> Envelope {
+    Envelope::Command {
+        node_uri: Text::new(NODE),
+        lane_uri: Text::new(lane),
+        body: Some(Value::from(value)),
+    }
+}
+
+fn linked(lane: &str) -> Envelope {
+    Envelope::Linked {
+        node_uri: Text::new(NODE),
+        lane_uri: Text::new(lane),
+        rate: None,
+        prio: None,
+        body: None,
+    }
+}
+
+fn synced(lane: &str) -> Envelope {
+    Envelope::Synced {
+        node_uri: Text::new(NODE),
+        lane_uri: Text::new(lane),
+        body: None,
+    }
+}
+
+fn event(lane: &str, value: i32) -> Envelope {
+    Envelope::Event {
+        node_uri: Text::new(NODE),
+        lane_uri: Text::new(lane),
+        body: Some(Value::from(value)),
+    }
+}
+
+#[tokio::test]
+async fn link_sync_and_command() {
+    let (mut client, server) = connect();
+    let mut lane = server.lane(NODE, LANE);
+
+    let server_task = async move {
+        lane.await_link().await;
+        lane.await_sync(vec![1, 2]).await;
+        lane.await_command(3).await;
+        lane.send_event(4).await;
+    };
+
+    let client_task = async move {
+        client.send(link(LANE)).await;
+        client.expect(linked(LANE)).await;
+        client.send(sync(LANE)).await;
+        client.expect(event(LANE, 1)).await;
+        client.expect(event(LANE, 2)).await;
+        client.expect(synced(LANE)).await;
+        client.send(command(LANE, 3)).await;
+        client.expect(event(LANE, 4)).await;
+    };
+
+    with_timeout(join(server_task, client_task)).await;
+}
+
+#[tokio::test]
+async fn concurrent_lanes() {
+    let (mut client, server) = connect();
+    let mut first = server.lane(NODE, LANE);
+    let mut second = server.lane(NODE, OTHER_LANE);
+
+    let first_task = tokio::spawn(async move {
+        first.await_link().await;
+        first.await_command(1).await;
+    });
+    let second_task = tokio::spawn(async move {
+        second.await_link().await;
+        second.await_command(2).await;
+    });
+
+    let client_task = async move {
+        client.send(link(OTHER_LANE)).await;
+        client.send(link(LANE)).await;
+        let mut responses = vec![client.recv().await, client.recv().await];
+        responses.sort_by_key(|env| env.as_ref().map(|env| env.path().1.clone()));
+        assert_eq!(
+            responses,
+            vec![Some(linked(LANE)), Some(linked(OTHER_LANE))]
+        );
+        client.send(command(OTHER_LANE, 2)).await;
+        client.send(command(LANE, 1)).await;
+    };
+
+    let (_, (first_result, second_result)) =
+        with_timeout(join(client_task, join(first_task, second_task))).await;
+    assert!(first_result.is_ok());
+    assert!(second_result.is_ok());
+}
+
+#[tokio::test]
+async fn envelopes_held_until_lane_taken() {
+    let (mut client, server) = connect();
+
+    client.send(link(LANE)).await;
+    // Give the server time to receive the envelope before the lane is taken.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let mut lane = server.lane(NODE, LANE);
+    with_timeout(join(lane.await_link(), client.expect(linked(LANE)))).await;
+}
+
+#[tokio::test]
+async fn records_invalid_frames() {
+    let (mut client, server) = connect();
+    let mut lane = server.lane(NODE, LANE);
+
+    client.send_text("@nonsense").await;
+    client.send(link(LANE)).await;
+    with_timeout(join(lane.await_link(), client.expect(linked(LANE)))).await;
+
+    assert_eq!(server.invalid_frames(), vec!["@nonsense".to_string()]);
+}
+
+#[tokio::test]
+async fn drop_frames() {
+    let (mut client, server) = connect();
+    let mut lane = server.lane(NODE, LANE);
+
+    server.inject(Fault::DropFrames(2));
+    let server_task = async move {
+        lane.await_link().await;
+        lane.send_event(1).await;
+        lane.send_event(2).await;
+    };
+    let client_task = async move {
+        client.send(link(LANE)).await;
+        client.expect(event(LANE, 2)).await;
+    };
+    with_timeout(join(server_task, client_task)).await;
+}
+
+#[tokio::test]
+async fn delay_frames() {
+    let (mut client, server) = connect();
+    let mut lane = server.lane(NODE, LANE);
+
+    let delay = Duration::from_millis(100);
+    lane.inject(Fault::Delay(delay));
+    let server_task = async move {
+        lane.await_link().await;
+    };
+    let client_task = async move {
+        client.send(link(LANE)).await;
+        let start = tokio::time::Instant::now();
+        client.expect(linked(LANE)).await;
+        assert!(start.elapsed() >= delay);
+    };
+    with_timeout(join(server_task, client_task)).await;
+}
+
+#[tokio::test]
+async fn close_mid_sync() {
+    let (mut client, server) = connect();
+    let mut lane = server.lane(NODE, LANE);
+
+    let server_task = async move {
+        lane.await_link().await;
+        lane.inject(Fault::CloseAfter(1));
+        lane.await_sync(vec![1, 2, 3]).await;
+        lane.await_closed().await;
+    };
+    let client_task = async move {
+        client.send(link(LANE)).await;
+        client.expect(linked(LANE)).await;
+        client.send(sync(LANE)).await;
+        client.expect(event(LANE, 1)).await;
+        assert!(client.recv().await.is_none());
+    };
+    with_timeout(join(server_task, client_task)).await;
+}
+
+#[tokio::test]
+async fn clear_faults() {
+    let (mut client, server) = connect();
+    let mut lane = server.lane(NODE, LANE);
+
+    server.inject(Fault::DropFrames(10));
+    server.clear_faults();
+    with_timeout(join(lane.await_link(), async {
+        client.send(link(LANE)).await;
+        client.expect(linked(LANE)).await;
+    }))
+    .await;
+}
+
+#[tokio::test]
+async fn close_connection() {
+    let (mut client, server) = connect();
+    let mut lane = server.lane(NODE, LANE);
+
+    server.close();
+    with_timeout(join(lane.await_closed(), async {
+        assert!(client.recv().await.is_none());
+    }))
+    .await;
+}
+
+#[tokio::test]
+async fn dropping_server_closes_connection() {
+    let (mut client, server) = connect();
+    drop(server);
+    assert!(with_timeout(client.recv()).await.is_none());
+}
+
+#[tokio::test]
+async fn lane_observes_client_close() {
+    let (client, server) = connect();
+    let mut lane = server.lane(NODE, LANE);
+    drop(client);
+    with_timeout(lane.await_closed()).await;
+}
+
+#[tokio::test]
+async fn run_scenario() {
+    let (mut client, server) = connect();
+    let mut lane = server.lane(NODE, LANE);
+
+    let scenario = Scenario::default()
+        .await_link()
+        .await_sync(vec![1])
+        .await_command(2)
+        .send_event(3)
+        .await_unlink();
+
+    let server_task = async move {
+        lane.run(&scenario).await;
+    };
+    let client_task = async move {
+        client.send(link(LANE)).await;
+        client.expect(linked(LANE)).await;
+        client.send(sync(LANE)).await;
+        client.expect(event(LANE, 1)).await;
+        client.expect(synced(LANE)).await;
+        client.send(command(LANE, 2)).await;
+        client.expect(event(LANE, 3)).await;
+        client
+            .send(Envelope::Unlink {
+                node_uri: Text::new(NODE),
+                lane_uri: Text::new(LANE),
+                body: None,
+            })
+            .await;
+        client
+            .expect(Envelope::Unlinked {
+                node_uri: Text::new(NODE),
+                lane_uri: Text::new(LANE),
+                body: None,
+            })
+            .await;
+    };
+    with_timeout(join(server_task, client_task)).await;
+}
+
+async fn open_client<S: WebSocketStream>(socket: S, url: &str) -> TestClient<S> {
+    let upgraded = ratchet::subscribe_with(
+        WebSocketConfig::default(),
+        socket,
+        url,
+        NoExtProvider,
+        ProtocolRegistry::new([WARP]).expect("Invalid protocol."),
+    )
+    .await
+    .expect("Handshake failed.");
+    assert_eq!(upgraded.subprotocol.as_deref(), Some(WARP));
+    TestClient::new(upgraded.websocket)
+}
+
+async fn link_with_handshake<S: WebSocketStream>(
+    client: impl Future<Output = TestClient<S>>,
+    server: impl Future<Output = MockServer>,
+) {
+    let server_task = async move {
+        let server = server.await;
+        let mut lane = server.lane(NODE, LANE);
+        lane.await_link().await;
+    };
+    let client_task = async move {
+        let mut client = client.await;
+        client.send(link(LANE)).await;
+        client.expect(linked(LANE)).await;
+    };
+    with_timeout(join(server_task, client_task)).await;
+}
+
+#[tokio::test]
+async fn accept_handshake() {
+    let (client, server) = duplex(4096);
+    link_with_handshake(open_client(client, "ws://localhost/"), async move {
+        MockServer::accept(server).await.expect("Handshake failed.")
+    })
+    .await;
+}
+
+const CERTS_PATH: &str = "../runtime/swimos_remote/test-data/certs";
+
+fn read_cert(file_name: &str) -> Vec<u8> {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push(CERTS_PATH);
+    path.push(file_name);
+    std::fs::read(path).expect("Failed to load certificate.")
+}
+
+#[tokio::test]
+async fn accept_tls_handshake() {
+    let provider = Arc::new(aws_lc_rs::default_provider());
+    let server_config = ServerConfig {
+        chain: CertChain(vec![
+            CertificateFile::der(read_cert("server-cert.der")),
+            CertificateFile::der(read_cert("ca-cert.der")),
+        ]),
+        key: PrivateKey::der(read_cert("server-key.der")),
+        enable_log_file: false,
+    };
+    let client_config = ClientConfig {
+        use_webpki_roots: true,
+        custom_roots: vec![CertificateFile::der(read_cert("ca-cert.der"))],
+    };
+    let server_net = RustlsServerNetworking::build(server_config, provider.clone())
+        .expect("Invalid server config.");
+    let client_net =
+        RustlsClientNetworking::build(Arc::new(Resolver::new().await), client_config, provider)
+            .expect("Invalid client config.");
+
+    let (bound_to, listener) = server_net
+        .make_listener("127.0.0.1:0".parse().unwrap())
+        .await
+        .expect("Failed to bind to port.");
+
+    let client = async move {
+        let socket = client_net
+            .try_open(Scheme::Wss, Some("localhost"), bound_to)
+            .await
+            .expect("Failed to connect.");
+        open_client(socket, "wss://localhost/").await
+    };
+    let server = async move {
+        let (socket, scheme, _) = listener
+            .into_stream()
+            .next()
+            .await
+            .expect("Listener stopped.")
+            .expect("Failed to accept connection.");
+        assert_eq!(scheme, Scheme::Wss);
+        MockServer::accept(socket).await.expect("Handshake failed.")
+    };
+    link_with_handshake(client, server).await;
+}
@@ -15,10 +15,12 @@
 //! Futures Combinators
 //!
 //! Additional combinators for [`std::future::Future`]s that express transformations that are not
-//! available in the [`futures`] crate.
+//! available in the [`futures`] crate, along with composable transformations for streams of
+//! events (deduplication, windowing and splitting) in [`StreamTransformsExt`].
 
 mod combinators;
 mod retry_strategy;
+mod transforms;
 mod union;
 
 pub use combinators::{
@@ -26,4 +28,7 @@ pub use combinators::{
     NotifyOnBlocked, Race2, SecondaryResult, StopAfterError,
 };
 pub use retry_strategy::{ExponentialStrategy, IntervalStrategy, Quantity, RetryStrategy};
+pub use transforms::{
+    Dedupe, DedupeByKey, SlidingWindow, SplitHalf, StreamTransformsExt, TimeWindow,
+};
 pub use union::{UnionFuture3, UnionFuture4};
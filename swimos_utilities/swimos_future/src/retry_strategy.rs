@@ -200,7 +200,9 @@ impl Iterator for RetryStrategy {
 
                 // Thread local RNG is used as it will live for the duration of the retry strategy
                 let wait = rand::thread_rng().gen_range(0..1000);
-                let duration = Duration::from_millis((2 ^ strategy.retry_no) + wait);
+                let exponent = u32::try_from(strategy.retry_no).unwrap_or(u32::MAX);
+                let backoff = 2u64.saturating_pow(exponent).saturating_add(wait);
+                let duration = Duration::from_millis(backoff);
                 let sleep_time = std::cmp::min(duration, strategy.max_interval);
 
                 Some(Some(sleep_time))
@@ -243,6 +245,19 @@ mod tests {
         assert!(duration >= max_backoff && duration <= max_backoff + max_interval);
     }
 
+    #[test]
+    fn exponential_backoff_grows() {
+        let max_interval = Duration::from_secs(3600);
+        let mut strategy = RetryStrategy::exponential(max_interval, Quantity::Infinite);
+
+        for retry_no in 1..=16u32 {
+            let duration = strategy.next().flatten().expect("Expected a delay.");
+            let backoff = Duration::from_millis(2u64.pow(retry_no));
+            assert!(duration >= backoff);
+            assert!(duration < backoff + Duration::from_secs(1));
+        }
+    }
+
     #[tokio::test]
     async fn test_immediate() {
         let retries = 5;
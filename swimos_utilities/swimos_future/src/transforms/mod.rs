@@ -0,0 +1,423 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use futures::{Future, Stream};
+use pin_project::pin_project;
+use tokio::time::{sleep, Sleep};
+
+/// Extension trait providing composable transformations for streams of events (for example, the
+/// events produced by a downlink lifecycle or a lane). These complement the combinators that are
+/// available in the [`futures`] crate (such as [`futures::StreamExt::scan`] and
+/// [`futures::StreamExt::chunks`]).
+pub trait StreamTransformsExt: Stream + Sized {
+    /// Discard any item that is equal to the item immediately preceding it.
+    fn dedupe(self) -> Dedupe<Self>
+    where
+        Self::Item: PartialEq + Clone,
+    {
+        Dedupe::new(self)
+    }
+
+    /// Discard any item that has the same key as the item immediately preceding it.
+    ///
+    /// # Arguments
+    /// * `key` - Computes the key for each item.
+    fn dedupe_by_key<K, F>(self, key: F) -> DedupeByKey<Self, K, F>
+    where
+        K: PartialEq,
+        F: FnMut(&Self::Item) -> K,
+    {
+        DedupeByKey::new(self, key)
+    }
+
+    /// Produce a sliding window over the last `size` items of the stream. A window is emitted for
+    /// each item, once at least `size` items have been received.
+    fn sliding_window(self, size: NonZeroUsize) -> SlidingWindow<Self>
+    where
+        Self::Item: Clone,
+    {
+        SlidingWindow::new(self, size)
+    }
+
+    /// Group the items of the stream into windows. A window is opened by the first item to arrive
+    /// after the previous window was emitted and is emitted when either `period` has elapsed
+    /// or it contains `max_items` items, whichever happens first. The resulting stream must be
+    /// polled from within a Tokio runtime.
+    fn time_window(self, period: Duration, max_items: NonZeroUsize) -> TimeWindow<Self> {
+        TimeWindow::new(self, period, max_items)
+    }
+
+    /// Split the stream into two halves, using a predicate. Items for which the predicate returns
+    /// `true` are produced by the first half and all others by the second. Each half will buffer
+    /// at most `buffer_size` items that are waiting to be consumed before applying backpressure
+    /// to the other half. If either half is dropped, any items that would have been produced by
+    /// it are discarded.
+    fn split_by<F>(
+        self,
+        predicate: F,
+        buffer_size: NonZeroUsize,
+    ) -> (SplitHalf<Self, F>, SplitHalf<Self, F>)
+    where
+        F: FnMut(&Self::Item) -> bool,
+    {
+        split_by(self, predicate, buffer_size)
+    }
+}
+
+impl<S: Stream> StreamTransformsExt for S {}
+
+/// A stream that discards consecutive duplicate items. See [`StreamTransformsExt::dedupe`].
+#[pin_project]
+#[derive(Debug)]
+pub struct Dedupe<S: Stream> {
+    #[pin]
+    stream: S,
+    previous: Option<S::Item>,
+}
+
+impl<S: Stream> Dedupe<S> {
+    pub fn new(stream: S) -> Self {
+        Dedupe {
+            stream,
+            previous: None,
+        }
+    }
+}
+
+impl<S> Stream for Dedupe<S>
+where
+    S: Stream,
+    S::Item: PartialEq + Clone,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut projected = self.project();
+        loop {
+            match projected.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    if projected.previous.as_ref() != Some(&item) {
+                        *projected.previous = Some(item.clone());
+                        break Poll::Ready(Some(item));
+                    }
+                }
+                ow => break ow,
+            }
+        }
+    }
+}
+
+/// A stream that discards consecutive items with the same key. See
+/// [`StreamTransformsExt::dedupe_by_key`].
+#[pin_project]
+#[derive(Debug)]
+pub struct DedupeByKey<S, K, F> {
+    #[pin]
+    stream: S,
+    key: F,
+    previous: Option<K>,
+}
+
+impl<S, K, F> DedupeByKey<S, K, F> {
+    pub fn new(stream: S, key: F) -> Self {
+        DedupeByKey {
+            stream,
+            key,
+            previous: None,
+        }
+    }
+}
+
+impl<S, K, F> Stream for DedupeByKey<S, K, F>
+where
+    S: Stream,
+    K: PartialEq,
+    F: FnMut(&S::Item) -> K,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut projected = self.project();
+        loop {
+            match projected.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    let key = (projected.key)(&item);
+                    if projected.previous.as_ref() != Some(&key) {
+                        *projected.previous = Some(key);
+                        break Poll::Ready(Some(item));
+                    }
+                }
+                ow => break ow,
+            }
+        }
+    }
+}
+
+/// A stream of sliding windows over another stream. See [`StreamTransformsExt::sliding_window`].
+#[pin_project]
+#[derive(Debug)]
+pub struct SlidingWindow<S: Stream> {
+    #[pin]
+    stream: S,
+    size: NonZeroUsize,
+    window: VecDeque<S::Item>,
+}
+
+impl<S: Stream> SlidingWindow<S> {
+    pub fn new(stream: S, size: NonZeroUsize) -> Self {
+        SlidingWindow {
+            stream,
+            size,
+            window: VecDeque::with_capacity(size.get()),
+        }
+    }
+}
+
+impl<S> Stream for SlidingWindow<S>
+where
+    S: Stream,
+    S::Item: Clone,
+{
+    type Item = Vec<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut projected = self.project();
+        let size = projected.size.get();
+        loop {
+            match projected.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    if projected.window.len() == size {
+                        projected.window.pop_front();
+                    }
+                    projected.window.push_back(item);
+                    if projected.window.len() == size {
+                        break Poll::Ready(Some(projected.window.iter().cloned().collect()));
+                    }
+                }
+                Poll::Ready(None) => break Poll::Ready(None),
+                Poll::Pending => break Poll::Pending,
+            }
+        }
+    }
+}
+
+/// A stream that groups the items of another stream into windows, bounded by time and size. See
+/// [`StreamTransformsExt::time_window`].
+#[pin_project]
+#[derive(Debug)]
+pub struct TimeWindow<S: Stream> {
+    #[pin]
+    stream: S,
+    period: Duration,
+    max_items: NonZeroUsize,
+    timer: Option<Pin<Box<Sleep>>>,
+    window: Vec<S::Item>,
+    done: bool,
+}
+
+impl<S: Stream> TimeWindow<S> {
+    pub fn new(stream: S, period: Duration, max_items: NonZeroUsize) -> Self {
+        TimeWindow {
+            stream,
+            period,
+            max_items,
+            timer: None,
+            window: vec![],
+            done: false,
+        }
+    }
+}
+
+impl<S: Stream> Stream for TimeWindow<S> {
+    type Item = Vec<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut projected = self.project();
+        loop {
+            if *projected.done {
+                *projected.timer = None;
+                break if projected.window.is_empty() {
+                    Poll::Ready(None)
+                } else {
+                    Poll::Ready(Some(std::mem::take(projected.window)))
+                };
+            }
+            if let Some(timer) = projected.timer.as_mut() {
+                if timer.as_mut().poll(cx).is_ready() {
+                    *projected.timer = None;
+                    break Poll::Ready(Some(std::mem::take(projected.window)));
+                }
+            }
+            match projected.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    if projected.window.is_empty() {
+                        *projected.timer = Some(Box::pin(sleep(*projected.period)));
+                    }
+                    projected.window.push(item);
+                    if projected.window.len() >= projected.max_items.get() {
+                        *projected.timer = None;
+                        break Poll::Ready(Some(std::mem::take(projected.window)));
+                    }
+                }
+                Poll::Ready(None) => {
+                    *projected.done = true;
+                }
+                Poll::Pending => break Poll::Pending,
+            }
+        }
+    }
+}
+
+struct SplitSide<T> {
+    buffer: VecDeque<T>,
+    waker: Option<Waker>,
+    dropped: bool,
+}
+
+impl<T> SplitSide<T> {
+    fn new(capacity: usize) -> Self {
+        SplitSide {
+            buffer: VecDeque::with_capacity(capacity),
+            waker: None,
+            dropped: false,
+        }
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+struct SplitInner<S: Stream, F> {
+    stream: Pin<Box<S>>,
+    predicate: F,
+    buffer_size: usize,
+    sides: [SplitSide<S::Item>; 2],
+    done: bool,
+}
+
+/// One half of a stream that has been split in two. See [`StreamTransformsExt::split_by`].
+pub struct SplitHalf<S: Stream, F> {
+    index: usize,
+    inner: Arc<Mutex<SplitInner<S, F>>>,
+}
+
+fn split_by<S, F>(
+    stream: S,
+    predicate: F,
+    buffer_size: NonZeroUsize,
+) -> (SplitHalf<S, F>, SplitHalf<S, F>)
+where
+    S: Stream,
+    F: FnMut(&S::Item) -> bool,
+{
+    let inner = Arc::new(Mutex::new(SplitInner {
+        stream: Box::pin(stream),
+        predicate,
+        buffer_size: buffer_size.get(),
+        sides: [
+            SplitSide::new(buffer_size.get()),
+            SplitSide::new(buffer_size.get()),
+        ],
+        done: false,
+    }));
+    (
+        SplitHalf {
+            index: 0,
+            inner: inner.clone(),
+        },
+        SplitHalf { index: 1, inner },
+    )
+}
+
+impl<S, F> Stream for SplitHalf<S, F>
+where
+    S: Stream,
+    F: FnMut(&S::Item) -> bool,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let index = self.index;
+        let other = 1 - index;
+        let mut guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let SplitInner {
+            stream,
+            predicate,
+            buffer_size,
+            sides,
+            done,
+        } = &mut *guard;
+
+        if let Some(item) = sides[index].buffer.pop_front() {
+            // Space has been freed in the buffer so the other half may now be able to proceed.
+            sides[other].wake();
+            return Poll::Ready(Some(item));
+        }
+        loop {
+            if *done {
+                break Poll::Ready(None);
+            }
+            if sides[other].buffer.len() >= *buffer_size {
+                sides[index].waker = Some(cx.waker().clone());
+                break Poll::Pending;
+            }
+            match stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    let target = if predicate(&item) { 0 } else { 1 };
+                    if target == index {
+                        break Poll::Ready(Some(item));
+                    } else if !sides[other].dropped {
+                        sides[other].buffer.push_back(item);
+                        sides[other].wake();
+                    }
+                }
+                Poll::Ready(None) => {
+                    *done = true;
+                    sides[other].wake();
+                }
+                Poll::Pending => {
+                    sides[index].waker = Some(cx.waker().clone());
+                    break Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+impl<S: Stream, F> Drop for SplitHalf<S, F> {
+    fn drop(&mut self) {
+        let mut guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let SplitInner { sides, .. } = &mut *guard;
+        let side = &mut sides[self.index];
+        side.dropped = true;
+        side.buffer.clear();
+        side.waker = None;
+        // The underlying stream may have registered the waker of this half so the other half must
+        // poll it again.
+        sides[1 - self.index].wake();
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2015-2024 Swim Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use futures::executor::block_on;
+use futures::future::join;
+use futures::stream::iter;
+use futures::{SinkExt, StreamExt};
+use swimos_num::non_zero_usize;
+use tokio::time::{sleep, timeout};
+
+use crate::StreamTransformsExt;
+
+#[test]
+fn dedupe_stream() {
+    let inputs = iter(vec![1, 1, 2, 3, 3, 3, 1, 2, 2]);
+    let outputs = block_on(inputs.dedupe().collect::<Vec<_>>());
+    assert_eq!(outputs, vec![1, 2, 3, 1, 2]);
+}
+
+#[test]
+fn dedupe_stream_by_key() {
+    let inputs = iter(vec![(1, "a"), (1, "b"), (2, "c"), (2, "d"), (1, "e")]);
+    let outputs = block_on(inputs.dedupe_by_key(|(k, _)| *k).collect::<Vec<_>>());
+    assert_eq!(outputs, vec![(1, "a"), (2, "c"), (1, "e")]);
+}
+
+#[test]
+fn sliding_window_stream() {
+    let inputs = iter(vec![1, 2, 3, 4, 5]);
+    let outputs = block_on(
+        inputs
+            .sliding_window(non_zero_usize!(3))
+            .collect::<Vec<_>>(),
+    );
+    assert_eq!(outputs, vec![vec![1, 2, 3], vec![2, 3, 4], vec![3, 4, 5]]);
+}
+
+#[test]
+fn sliding_window_too_short() {
+    let inputs = iter(vec![1, 2]);
+    let outputs = block_on(
+        inputs
+            .sliding_window(non_zero_usize!(3))
+            .collect::<Vec<_>>(),
+    );
+    assert!(outputs.is_empty());
+}
+
+#[tokio::test]
+async fn time_window_max_items() {
+    let inputs = iter(vec![1, 2, 3, 4, 5]);
+    let outputs = inputs
+        .time_window(Duration::from_secs(60), non_zero_usize!(2))
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(outputs, vec![vec![1, 2], vec![3, 4], vec![5]]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn time_window_period() {
+    let (mut tx, rx) = futures::channel::mpsc::channel::<i32>(8);
+
+    let producer = async move {
+        tx.send(1).await.unwrap();
+        tx.send(2).await.unwrap();
+        sleep(Duration::from_secs(2)).await;
+        tx.send(3).await.unwrap();
+        sleep(Duration::from_secs(2)).await;
+        tx.send(4).await.unwrap();
+        tx.send(5).await.unwrap();
+    };
+
+    let consumer = rx
+        .time_window(Duration::from_secs(1), non_zero_usize!(8))
+        .collect::<Vec<_>>();
+
+    let (_, outputs) = join(producer, consumer).await;
+    assert_eq!(outputs, vec![vec![1, 2], vec![3], vec![4, 5]]);
+}
+
+#[test]
+fn split_stream_by_predicate() {
+    let inputs = iter(0..10);
+    let (evens, odds) = inputs.split_by(|n| n % 2 == 0, non_zero_usize!(2));
+    let (evens, odds) = block_on(join(evens.collect::<Vec<_>>(), odds.collect::<Vec<_>>()));
+    assert_eq!(evens, vec![0, 2, 4, 6, 8]);
+    assert_eq!(odds, vec![1, 3, 5, 7, 9]);
+}
+
+#[tokio::test]
+async fn split_stream_backpressure() {
+    let inputs = iter(0..10);
+    let (mut small, large) = inputs.split_by(|n| *n < 5, non_zero_usize!(2));
+
+    // Consuming only the first half, the second half fills its buffer and blocks.
+    assert_eq!(small.next().await, Some(0));
+    assert_eq!(small.next().await, Some(1));
+    assert_eq!(small.next().await, Some(2));
+    assert_eq!(small.next().await, Some(3));
+    assert_eq!(small.next().await, Some(4));
+    let blocked = timeout(Duration::from_millis(50), small.next()).await;
+    assert!(blocked.is_err());
+
+    // Draining the second half releases the first.
+    let (small_rest, large) = join(small.collect::<Vec<_>>(), large.collect::<Vec<_>>()).await;
+    assert!(small_rest.is_empty());
+    assert_eq!(large, vec![5, 6, 7, 8, 9]);
+}
+
+#[test]
+fn split_stream_dropped_half() {
+    let inputs = iter(0..10);
+    let (evens, odds) = inputs.split_by(|n| n % 2 == 0, non_zero_usize!(1));
+    drop(odds);
+    let evens = block_on(evens.collect::<Vec<_>>());
+    assert_eq!(evens, vec![0, 2, 4, 6, 8]);
+}
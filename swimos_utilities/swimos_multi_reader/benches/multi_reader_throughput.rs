@@ -193,7 +193,8 @@ async fn write(mut writers: Vec<Writer>, message_count: usize, writers_order: Wr
                                 format!("node_{}", i),
                                 format!("lane_{}", i),
                             ),
-                            envelope: Operation::Link,
+                            envelope: Operation::Link(Default::default()),
+                            correlation_id: None,
                         };
                         writer.send(msg).await.unwrap();
                     }
@@ -209,7 +210,8 @@ async fn write(mut writers: Vec<Writer>, message_count: usize, writers_order: Wr
             let msg: RequestMessage<String, Bytes> = RequestMessage {
                 origin: Uuid::from_u128(i as u128),
                 path: RelativeAddress::new(format!("node_{}", i), format!("lane_{}", i)),
-                envelope: Operation::Link,
+                envelope: Operation::Link(Default::default()),
+                correlation_id: None,
             };
             writer.send(msg).await.unwrap();
         }
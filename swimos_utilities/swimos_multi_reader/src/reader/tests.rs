@@ -46,7 +46,7 @@ async fn test_single_message_single_stream() {
     let write = async move {
         let mut first_framed = FramedWrite::new(first_writer, RawRequestMessageEncoder);
 
-        let envelope: Operation<Bytes> = Operation::Link;
+        let envelope: Operation<Bytes> = Operation::Link(Default::default());
         first_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(1),
@@ -55,6 +55,7 @@ async fn test_single_message_single_stream() {
                     Text::new("lane_1").to_string(),
                 ),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
@@ -69,7 +70,8 @@ async fn test_single_message_single_stream() {
             RequestMessage {
                 origin: Uuid::from_u128(1),
                 path: RelativeAddress::new(Text::new("node_1"), Text::new("lane_1")),
-                envelope: Operation::Link,
+                envelope: Operation::Link(Default::default()),
+                correlation_id: None,
             }
         );
 
@@ -99,32 +101,35 @@ async fn test_multiple_messages_single_stream() {
     let write = async move {
         let mut first_framed = FramedWrite::new(first_writer, RawRequestMessageEncoder);
 
-        let envelope: Operation<Bytes> = Operation::Link;
+        let envelope: Operation<Bytes> = Operation::Link(Default::default());
         first_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(1),
                 path: RelativeAddress::new(Text::new("node_1"), Text::new("lane_1")),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
 
-        let envelope: Operation<Bytes> = Operation::Link;
+        let envelope: Operation<Bytes> = Operation::Link(Default::default());
         first_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(2),
                 path: RelativeAddress::new(Text::new("node_2"), Text::new("lane_2")),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
 
-        let envelope: Operation<Bytes> = Operation::Link;
+        let envelope: Operation<Bytes> = Operation::Link(Default::default());
         first_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(3),
                 path: RelativeAddress::new(Text::new("node_3"), Text::new("lane_3")),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
@@ -139,7 +144,8 @@ async fn test_multiple_messages_single_stream() {
             RequestMessage {
                 origin: Uuid::from_u128(1),
                 path: RelativeAddress::new(Text::new("node_1"), Text::new("lane_1")),
-                envelope: Operation::Link,
+                envelope: Operation::Link(Default::default()),
+                correlation_id: None,
             }
         );
         let message = multi_reader.next().await.unwrap().unwrap();
@@ -148,7 +154,8 @@ async fn test_multiple_messages_single_stream() {
             RequestMessage {
                 origin: Uuid::from_u128(2),
                 path: RelativeAddress::new(Text::new("node_2"), Text::new("lane_2")),
-                envelope: Operation::Link,
+                envelope: Operation::Link(Default::default()),
+                correlation_id: None,
             }
         );
         let message = multi_reader.next().await.unwrap().unwrap();
@@ -157,7 +164,8 @@ async fn test_multiple_messages_single_stream() {
             RequestMessage {
                 origin: Uuid::from_u128(3),
                 path: RelativeAddress::new(Text::new("node_3"), Text::new("lane_3")),
-                envelope: Operation::Link,
+                envelope: Operation::Link(Default::default()),
+                correlation_id: None,
             }
         );
 
@@ -193,23 +201,25 @@ async fn test_single_message_multiple_streams() {
         let mut first_framed = FramedWrite::new(first_writer, RawRequestMessageEncoder);
         let mut second_framed = FramedWrite::new(second_writer, RawRequestMessageEncoder);
 
-        let envelope: Operation<Bytes> = Operation::Sync;
+        let envelope: Operation<Bytes> = Operation::Sync(Default::default());
         first_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(1),
                 path: RelativeAddress::new(Text::new("node_1"), Text::new("lane_1")),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
 
-        let envelope: Operation<Bytes> = Operation::Sync;
+        let envelope: Operation<Bytes> = Operation::Sync(Default::default());
 
         second_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(2),
                 path: RelativeAddress::new(Text::new("node_2"), Text::new("lane_2")),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
@@ -225,7 +235,8 @@ async fn test_single_message_multiple_streams() {
             RequestMessage {
                 origin: Uuid::from_u128(1),
                 path: RelativeAddress::new(Text::new("node_1"), Text::new("lane_1")),
-                envelope: Operation::Sync,
+                envelope: Operation::Sync(Default::default()),
+                correlation_id: None,
             }
         );
         let message = multi_reader.next().await.unwrap().unwrap();
@@ -234,7 +245,8 @@ async fn test_single_message_multiple_streams() {
             RequestMessage {
                 origin: Uuid::from_u128(2),
                 path: RelativeAddress::new(Text::new("node_2"), Text::new("lane_2")),
-                envelope: Operation::Sync,
+                envelope: Operation::Sync(Default::default()),
+                correlation_id: None,
             }
         );
 
@@ -276,43 +288,47 @@ async fn test_multiple_messages_multiple_streams() {
         let mut second_framed = FramedWrite::new(second_writer, RawRequestMessageEncoder);
         let third_framed = FramedWrite::new(third_writer, RawRequestMessageEncoder);
 
-        let envelope: Operation<Bytes> = Operation::Link;
+        let envelope: Operation<Bytes> = Operation::Link(Default::default());
         first_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(1),
                 path: RelativeAddress::new(Text::new("node_1"), Text::new("lane_1")),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
 
-        let envelope: Operation<Bytes> = Operation::Link;
+        let envelope: Operation<Bytes> = Operation::Link(Default::default());
 
         second_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(2),
                 path: RelativeAddress::new(Text::new("node_2"), Text::new("lane_2")),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
 
-        let envelope: Operation<Bytes> = Operation::Link;
+        let envelope: Operation<Bytes> = Operation::Link(Default::default());
         first_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(3),
                 path: RelativeAddress::new(Text::new("node_3"), Text::new("lane_3")),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
 
-        let envelope: Operation<Bytes> = Operation::Link;
+        let envelope: Operation<Bytes> = Operation::Link(Default::default());
         second_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(4),
                 path: RelativeAddress::text("node_4", "lane_4"),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
@@ -329,7 +345,8 @@ async fn test_multiple_messages_multiple_streams() {
             RequestMessage {
                 origin: Uuid::from_u128(1),
                 path: RelativeAddress::new(Text::new("node_1"), Text::new("lane_1")),
-                envelope: Operation::Link,
+                envelope: Operation::Link(Default::default()),
+                correlation_id: None,
             }
         );
         let message = multi_reader.next().await.unwrap().unwrap();
@@ -338,7 +355,8 @@ async fn test_multiple_messages_multiple_streams() {
             RequestMessage {
                 origin: Uuid::from_u128(2),
                 path: RelativeAddress::new(Text::new("node_2"), Text::new("lane_2")),
-                envelope: Operation::Link,
+                envelope: Operation::Link(Default::default()),
+                correlation_id: None,
             }
         );
         let message = multi_reader.next().await.unwrap().unwrap();
@@ -347,17 +365,19 @@ async fn test_multiple_messages_multiple_streams() {
             RequestMessage {
                 origin: Uuid::from_u128(3),
                 path: RelativeAddress::new(Text::new("node_3"), Text::new("lane_3")),
-                envelope: Operation::Link,
+                envelope: Operation::Link(Default::default()),
+                correlation_id: None,
             }
         );
         let message = multi_reader.next().await.unwrap().unwrap();
-        let envelope: Operation<Value> = Operation::Link;
+        let envelope: Operation<Value> = Operation::Link(Default::default());
         assert_eq!(
             message,
             RequestMessage {
                 origin: Uuid::from_u128(4),
                 path: RelativeAddress::text("node_4", "lane_4"),
                 envelope,
+                correlation_id: None,
             }
         );
         let message = multi_reader.next().await;
@@ -394,44 +414,48 @@ async fn test_replace_stream() {
         let mut second_framed = FramedWrite::new(second_writer, RawRequestMessageEncoder);
         let mut third_framed = FramedWrite::new(third_writer, RawRequestMessageEncoder);
 
-        let envelope: Operation<Bytes> = Operation::Link;
+        let envelope: Operation<Bytes> = Operation::Link(Default::default());
         first_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(1),
                 path: RelativeAddress::new(Text::new("node_1"), Text::new("lane_1")),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
 
-        let envelope: Operation<Bytes> = Operation::Link;
+        let envelope: Operation<Bytes> = Operation::Link(Default::default());
         second_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(2),
                 path: RelativeAddress::new(Text::new("node_2"), Text::new("lane_2")),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
 
         drop(first_framed);
 
-        let envelope: Operation<Bytes> = Operation::Link;
+        let envelope: Operation<Bytes> = Operation::Link(Default::default());
         third_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(3),
                 path: RelativeAddress::new(Text::new("node_3"), Text::new("lane_3")),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
 
-        let envelope: Operation<Bytes> = Operation::Link;
+        let envelope: Operation<Bytes> = Operation::Link(Default::default());
         second_framed
             .send(RequestMessage {
                 origin: Uuid::from_u128(4),
                 path: RelativeAddress::text("node_4", "lane_4"),
                 envelope,
+                correlation_id: None,
             })
             .await
             .unwrap();
@@ -447,7 +471,8 @@ async fn test_replace_stream() {
             RequestMessage {
                 origin: Uuid::from_u128(1),
                 path: RelativeAddress::new(Text::new("node_1"), Text::new("lane_1")),
-                envelope: Operation::Link,
+                envelope: Operation::Link(Default::default()),
+                correlation_id: None,
             }
         );
         let message = multi_reader.next().await.unwrap().unwrap();
@@ -456,7 +481,8 @@ async fn test_replace_stream() {
             RequestMessage {
                 origin: Uuid::from_u128(2),
                 path: RelativeAddress::new(Text::new("node_2"), Text::new("lane_2")),
-                envelope: Operation::Link,
+                envelope: Operation::Link(Default::default()),
+                correlation_id: None,
             }
         );
 
@@ -471,7 +497,8 @@ async fn test_replace_stream() {
             RequestMessage {
                 origin: Uuid::from_u128(3),
                 path: RelativeAddress::new(Text::new("node_3"), Text::new("lane_3")),
-                envelope: Operation::Link,
+                envelope: Operation::Link(Default::default()),
+                correlation_id: None,
             }
         );
         let message = multi_reader.next().await.unwrap().unwrap();
@@ -480,7 +507,8 @@ async fn test_replace_stream() {
             RequestMessage {
                 origin: Uuid::from_u128(4),
                 path: RelativeAddress::text("node_4", "lane_4"),
-                envelope: Operation::Link,
+                envelope: Operation::Link(Default::default()),
+                correlation_id: None,
             }
         );
         let message = multi_reader.next().await;
@@ -512,12 +540,13 @@ async fn test_512_streams() {
 
     let write = async move {
         for (idx, writer) in writers.iter_mut().enumerate() {
-            let envelope: Operation<Bytes> = Operation::Link;
+            let envelope: Operation<Bytes> = Operation::Link(Default::default());
             writer
                 .send(RequestMessage {
                     origin: Uuid::from_u128(idx as u128),
                     path: RelativeAddress::new(format!("node_{}", idx), format!("lane_{}", idx)),
                     envelope,
+                    correlation_id: None,
                 })
                 .await
                 .unwrap();
@@ -535,7 +564,8 @@ async fn test_512_streams() {
                         format!("node_{}", idx).into(),
                         format!("lane_{}", idx).into()
                     ),
-                    envelope: Operation::Link,
+                    envelope: Operation::Link(Default::default()),
+                    correlation_id: None,
                 }
             );
 
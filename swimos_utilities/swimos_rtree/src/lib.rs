@@ -14,7 +14,8 @@
 
 //! # Immutable R-tree implementation
 //!
-//! The module provides traits for implementing custom 2D and 3D objects that can be stored in the R-tree.
+//! The module provides traits for implementing custom 2D, 3D and higher dimensional objects that can be
+//! stored in the R-tree.
 
 #[macro_use]
 mod rectangles;
@@ -23,4 +24,6 @@ mod tree;
 
 pub use crate::rectangles::*;
 pub use crate::tree::strategies::*;
-pub use tree::{ChildrenSizeError, DuplicateLabelError, RTree, RTreeError, RTreeIter};
+pub use tree::{
+    ChildrenSizeError, DuplicateLabelError, RTree, RTreeError, RTreeIter, RTreeNearest, RTreeSearch,
+};
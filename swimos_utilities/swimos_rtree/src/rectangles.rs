@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use num::Float;
+use num::{Float, Zero};
 use std::cmp::Ordering;
 use std::fmt::Debug;
 use std::hash::Hash;
@@ -120,6 +120,30 @@ where
         self.low <= other_mbb.low && self.high >= other_mbb.high
     }
 
+    /// Calculates the square of the minimum distance from a point to the bounding box. If the point is
+    /// inside the box, the distance is zero.
+    pub(crate) fn min_distance_sq(&self, point: &P) -> P::Type {
+        let zero = P::Type::zero();
+        (0..P::get_coord_type().dimensions()).fold(zero, |acc, n| {
+            let (low, high, coord) = match (
+                self.low.get_nth_coord(n),
+                self.high.get_nth_coord(n),
+                point.get_nth_coord(n),
+            ) {
+                (Some(low), Some(high), Some(coord)) => (low, high, coord),
+                _ => return acc,
+            };
+            let delta = if coord < low {
+                low - coord
+            } else if coord > high {
+                coord - high
+            } else {
+                zero
+            };
+            acc + delta * delta
+        })
+    }
+
     /// Checks if two bounding boxes are intersecting.
     pub(crate) fn is_intersecting<B: BoxBounded<Point = <Self as BoxBounded>::Point>>(
         &self,
@@ -359,10 +383,23 @@ impl<T: Float + Debug> Point for Point3D<T> {
 }
 
 /// The type of the coordinates of the entries in the RTree.
-/// The RTree can currently work with 2D and 3D objects only.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum CoordType {
-    TwoDimensional = 2,
-    ThreeDimensional = 3,
+    TwoDimensional,
+    ThreeDimensional,
+    /// Points with an arbitrary (non-zero) number of dimensions.
+    MultiDimensional(usize),
+}
+
+impl CoordType {
+    /// Returns the number of dimensions of the points.
+    pub fn dimensions(&self) -> usize {
+        match self {
+            CoordType::TwoDimensional => 2,
+            CoordType::ThreeDimensional => 3,
+            CoordType::MultiDimensional(n) => *n,
+        }
+    }
 }
 
 /// A trait for implementing a custom point.
@@ -411,7 +448,8 @@ pub trait BoxBounded: Clone + Debug {
         Self::Point::get_coord_type()
     }
 
-    /// Calculates the area for 2D objects and volume for 3D objects.
+    /// Calculates the area for 2D objects, volume for 3D objects and hypervolume for objects with more
+    /// dimensions.
     fn measure(&self) -> <Self::Point as Point>::Type;
 }
 
@@ -14,8 +14,9 @@
 
 use num::traits::Pow;
 use std::borrow::Borrow;
+use std::cmp::Ordering;
 use std::collections::hash_map;
-use std::collections::HashMap;
+use std::collections::{BinaryHeap, HashMap};
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
@@ -61,7 +62,7 @@ where
     /// If a node has less elements that the minimum capacity after removal, the remaining elements
     /// in the node are merged back with the rest of the tree.
     ///
-    /// The R-tree currently supports only 2 and 3 dimensional items.
+    /// The dimensionality of the items is determined by the [`CoordType`] of their points.
     ///
     /// # Example:
     /// ```
@@ -152,7 +153,106 @@ where
     /// assert_eq!(maybe_found.unwrap(), vec![&first_item, &second_item]);
     /// ```
     pub fn search(&self, area: &Rect<B::Point>) -> Option<Vec<&B>> {
-        self.root.search(area)
+        let found = self.search_iter(area).collect::<Vec<_>>();
+        if found.is_empty() {
+            None
+        } else {
+            Some(found)
+        }
+    }
+
+    /// An iterator over all elements that are enclosed completely by the given area. The elements
+    /// are produced in the same order as by [`RTree::search`] but the tree is only traversed as
+    /// far as is required to produce each element.
+    ///
+    /// # Example:
+    /// ```
+    /// use swimos_num::non_zero_usize;
+    /// use swimos_rtree::{Point2D, Rect, RTree, SplitStrategy, rect};
+    ///
+    /// let mut rtree = RTree::new(non_zero_usize!(2), non_zero_usize!(5), SplitStrategy::Linear).unwrap();
+    ///
+    /// let first_item = rect!((0.0, 0.0), (1.0, 1.0));
+    /// let second_item = rect!((0.0, 0.0), (2.0, 2.0));
+    ///
+    /// rtree.insert("First".to_string(), first_item.clone()).unwrap();
+    /// rtree.insert("Second".to_string(), second_item.clone()).unwrap();
+    ///
+    /// let mut found = rtree.search_iter(&rect!((0.0, 0.0), (3.0, 3.0)));
+    /// assert_eq!(found.next(), Some(&first_item));
+    /// assert_eq!(found.next(), Some(&second_item));
+    /// assert_eq!(found.next(), None);
+    /// ```
+    pub fn search_iter<'a>(&'a self, area: &Rect<B::Point>) -> RTreeSearch<'a, L, B> {
+        RTreeSearch {
+            area: *area,
+            stack: self
+                .root
+                .entries
+                .iter()
+                .rev()
+                .map(|entry| &**entry)
+                .collect(),
+        }
+    }
+
+    /// Returns the `k` elements that are nearest to the given point, ordered by their distance
+    /// from it. The distance to an element is the minimum distance to its bounding box (so it is
+    /// zero for any element that contains the point). If there are fewer than `k` elements in the
+    /// tree, all of them are returned.
+    ///
+    /// # Example:
+    /// ```
+    /// use swimos_num::non_zero_usize;
+    /// use swimos_rtree::{Point2D, Rect, RTree, SplitStrategy, rect};
+    ///
+    /// let mut rtree = RTree::new(non_zero_usize!(2), non_zero_usize!(5), SplitStrategy::Linear).unwrap();
+    ///
+    /// let first_item = rect!((0.0, 0.0), (1.0, 1.0));
+    /// let second_item = rect!((5.0, 5.0), (6.0, 6.0));
+    /// let third_item = rect!((10.0, 10.0), (11.0, 11.0));
+    ///
+    /// rtree.insert("First".to_string(), first_item.clone()).unwrap();
+    /// rtree.insert("Second".to_string(), second_item.clone()).unwrap();
+    /// rtree.insert("Third".to_string(), third_item.clone()).unwrap();
+    ///
+    /// let nearest = rtree.nearest(&Point2D::new(9.0, 9.0), 2);
+    /// assert_eq!(
+    ///     nearest,
+    ///     vec![(&"Third".to_string(), &third_item), (&"Second".to_string(), &second_item)]
+    /// );
+    /// ```
+    pub fn nearest(&self, point: &B::Point, k: usize) -> Vec<(&L, &B)> {
+        self.nearest_iter(point).take(k).collect()
+    }
+
+    /// An iterator over all elements of the tree, in order of their distance from the given point
+    /// (as defined for [`RTree::nearest`]). The tree is searched incrementally so only the nodes
+    /// that are required to produce each element are visited.
+    ///
+    /// # Example:
+    /// ```
+    /// use swimos_num::non_zero_usize;
+    /// use swimos_rtree::{Point2D, Rect, RTree, SplitStrategy, rect};
+    ///
+    /// let mut rtree = RTree::new(non_zero_usize!(2), non_zero_usize!(5), SplitStrategy::Linear).unwrap();
+    ///
+    /// rtree.insert("First".to_string(), rect!((0.0, 0.0), (1.0, 1.0))).unwrap();
+    /// rtree.insert("Second".to_string(), rect!((5.0, 5.0), (6.0, 6.0))).unwrap();
+    ///
+    /// let labels = rtree
+    ///     .nearest_iter(&Point2D::new(0.5, 0.5))
+    ///     .map(|(label, _)| label.as_str())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(labels, vec!["First", "Second"]);
+    /// ```
+    pub fn nearest_iter<'a>(&'a self, point: &B::Point) -> RTreeNearest<'a, L, B> {
+        let mut nearest = RTreeNearest {
+            point: *point,
+            queue: BinaryHeap::new(),
+        };
+        nearest.push_entries(&self.root.entries);
+        nearest
     }
 
     /// Inserts a new item in the tree. Each item must have a unique label.
@@ -176,20 +276,115 @@ where
             return Err(DuplicateLabelError(label));
         }
 
-        let item = Arc::new(Entry::Leaf { label, item });
+        self.insert_entry(Arc::new(Entry::Leaf { label, item }));
+        Ok(())
+    }
 
-        let label_raw_ptr: *const L = match &*item {
-            Entry::Leaf { label, .. } => label,
-            Entry::Branch { .. } => {
-                unreachable!()
+    /// Replaces the item with the given label, returning the previous item. If no such item is
+    /// found, `None` is returned and the tree is unchanged.
+    ///
+    /// If the bounding box of the new item is still covered by the node that holds the previous
+    /// item, it is replaced in place. Otherwise, the entry is relocated with a remove and insert.
+    /// This makes frequent small movements of items much cheaper than removing and re-inserting
+    /// them.
+    ///
+    /// # Example:
+    /// ```
+    /// use swimos_num::non_zero_usize;
+    /// use swimos_rtree::{Point2D, Rect, RTree, SplitStrategy, rect};
+    ///
+    /// let mut rtree = RTree::new(non_zero_usize!(2), non_zero_usize!(5), SplitStrategy::Linear).unwrap();
+    ///
+    /// let first_item = rect!((0.0, 0.0), (1.0, 1.0));
+    /// let moved_item = rect!((0.5, 0.5), (1.5, 1.5));
+    ///
+    /// rtree.insert("First".to_string(), first_item.clone()).unwrap();
+    ///
+    /// let maybe_previous = rtree.update(&"First".to_string(), moved_item.clone());
+    /// assert_eq!(maybe_previous.unwrap(), first_item);
+    /// assert_eq!(rtree.search(&rect!((0.0, 0.0), (2.0, 2.0))).unwrap(), vec![&moved_item]);
+    ///
+    /// let maybe_previous = rtree.update(&"Second".to_string(), first_item);
+    /// assert!(maybe_previous.is_none());
+    /// assert_eq!(rtree.len(), 1);
+    /// ```
+    pub fn update(&mut self, label: &L, item: B) -> Option<B> {
+        let previous_mbb = *self.lookup_map.get(label)?.get_mbb();
+        let entry = Arc::new(Entry::Leaf {
+            label: label.clone(),
+            item,
+        });
+
+        if self.root.replace(&previous_mbb, label, &entry, None) {
+            let previous = self.lookup_map.remove(label)?;
+            self.insert_entry_key(entry);
+
+            let previous = if Arc::strong_count(&previous) == 1 {
+                Arc::try_unwrap(previous).unwrap()
+            } else {
+                (*previous).clone()
+            };
+
+            match previous {
+                Entry::Leaf { item, .. } => Some(item),
+                Entry::Branch { .. } => unreachable!(),
             }
-        };
+        } else {
+            let previous = self.remove(label)?;
+            self.insert_entry(entry);
+            Some(previous)
+        }
+    }
 
-        self.lookup_map
-            .insert(RTreeKey(label_raw_ptr), item.clone());
+    /// Retains only the items for which the predicate returns `true`, removing all others.
+    ///
+    /// If more than half of the items are removed, the tree is rebuilt from the remaining items
+    /// using the Sort-Tile-Recursive (STR) algorithm (as with [`RTree::bulk_load`]) rather than
+    /// removing the items one at a time.
+    ///
+    /// # Example:
+    /// ```
+    /// use swimos_num::non_zero_usize;
+    /// use swimos_rtree::{Point2D, Rect, RTree, SplitStrategy, rect};
+    ///
+    /// let mut rtree = RTree::new(non_zero_usize!(2), non_zero_usize!(5), SplitStrategy::Linear).unwrap();
+    ///
+    /// rtree.insert("First".to_string(), rect!((0.0, 0.0), (1.0, 1.0))).unwrap();
+    /// rtree.insert("Second".to_string(), rect!((0.0, 0.0), (2.0, 2.0))).unwrap();
+    ///
+    /// rtree.retain(|label, _| label != "Second");
+    /// assert_eq!(rtree.len(), 1);
+    /// assert!(rtree.remove(&"Second".to_string()).is_none());
+    /// ```
+    pub fn retain<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(&L, &B) -> bool,
+    {
+        let expired = self
+            .iter()
+            .filter(|(label, item)| !predicate(label, item))
+            .map(|(label, _)| label.clone())
+            .collect::<Vec<_>>();
+
+        if expired.len() > self.len() / 2 {
+            for label in &expired {
+                self.lookup_map.remove(label);
+            }
 
-        self.internal_insert(item, 0);
-        Ok(())
+            let Node {
+                min_children,
+                max_children,
+                split_strat,
+                ..
+            } = self.root;
+            let entries = self.lookup_map.values().cloned().collect();
+            self.root =
+                RTree::internal_bulk_load(min_children, max_children, split_strat, entries, 0);
+        } else {
+            for label in &expired {
+                self.remove(label);
+            }
+        }
     }
 
     /// Removes and returns an item from the tree given its label.
@@ -224,9 +419,11 @@ where
     /// assert_eq!(rtree.len(), 0);
     /// ```
     pub fn remove(&mut self, label: &L) -> Option<B> {
-        let item = self.lookup_map.remove(label)?;
+        // Release the lookup entry before removing from the tree so that the item can be moved
+        // out of the tree without being cloned.
+        let bounding_box = *self.lookup_map.remove(label)?.get_mbb();
 
-        let (removed, maybe_orphan_nodes) = self.root.remove(item.get_mbb(), label).unwrap();
+        let (removed, maybe_orphan_nodes) = self.root.remove(&bounding_box, label).unwrap();
 
         if self.root.num_entries() == 1 && !self.root.is_leaf() {
             let entry_ptr = self.root.entries.pop().unwrap();
@@ -375,6 +572,22 @@ where
         }
     }
 
+    fn insert_entry(&mut self, entry: EntryPtr<L, B>) {
+        self.insert_entry_key(entry.clone());
+        self.internal_insert(entry, 0);
+    }
+
+    fn insert_entry_key(&mut self, entry: EntryPtr<L, B>) {
+        let label_raw_ptr: *const L = match &*entry {
+            Entry::Leaf { label, .. } => label,
+            Entry::Branch { .. } => {
+                unreachable!()
+            }
+        };
+
+        self.lookup_map.insert(RTreeKey(label_raw_ptr), entry);
+    }
+
     fn internal_insert(&mut self, item: EntryPtr<L, B>, level: usize) {
         if let Some((first_entry, second_entry)) = self.root.insert(item, level) {
             self.root = Node {
@@ -399,7 +612,7 @@ where
         while entries_count > max_children {
             // We choose to fill the nodes halfway between the min and max capacity to avoid splits and merges after a single insert/remove
             let node_capacity = (max_children + min_children) / 2;
-            let coord_count = B::Point::get_coord_type() as usize;
+            let coord_count = B::Point::get_coord_type().dimensions();
 
             // Sort all by the first dimension
             entries.sort_by(|first, second| {
@@ -596,6 +809,144 @@ where
     }
 }
 
+/// An iterator over the entries of an [`RTree`] that are enclosed by an area.
+///
+/// This `struct` is created by the [`search_iter`] method on [`RTree`].
+///
+/// [`search_iter`]: RTree::search_iter
+pub struct RTreeSearch<'a, L, B>
+where
+    L: Label,
+    B: BoxBounded,
+{
+    area: Rect<B::Point>,
+    stack: Vec<&'a Entry<L, B>>,
+}
+
+impl<'a, L, B> Iterator for RTreeSearch<'a, L, B>
+where
+    L: Label,
+    B: BoxBounded,
+{
+    type Item = &'a B;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let RTreeSearch { area, stack } = self;
+        while let Some(entry) = stack.pop() {
+            match entry {
+                Entry::Leaf { item, .. } if area.is_covering(item.get_mbb()) => {
+                    return Some(item);
+                }
+                Entry::Branch { mbb, child } if area.is_intersecting(mbb) => {
+                    // Reversed so that the entries are visited in the order that they are stored.
+                    stack.extend(child.entries.iter().rev().map(|entry| &**entry));
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// An iterator over the entries of an [`RTree`] in order of their distance from a point.
+///
+/// This `struct` is created by the [`nearest_iter`] method on [`RTree`].
+///
+/// [`nearest_iter`]: RTree::nearest_iter
+pub struct RTreeNearest<'a, L, B>
+where
+    L: Label,
+    B: BoxBounded,
+{
+    point: B::Point,
+    queue: BinaryHeap<NearestCandidate<'a, L, B>>,
+}
+
+impl<'a, L, B> RTreeNearest<'a, L, B>
+where
+    L: Label,
+    B: BoxBounded,
+{
+    fn push_entries(&mut self, entries: &'a [EntryPtr<L, B>]) {
+        let RTreeNearest { point, queue } = self;
+        queue.extend(entries.iter().map(|entry| NearestCandidate {
+            distance_sq: entry.get_mbb().min_distance_sq(point),
+            entry,
+        }));
+    }
+}
+
+impl<'a, L, B> Iterator for RTreeNearest<'a, L, B>
+where
+    L: Label,
+    B: BoxBounded,
+{
+    type Item = (&'a L, &'a B);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The distance to a branch is a lower bound for the distances to all of its entries so,
+        // when a leaf is at the head of the queue, no closer entry remains.
+        while let Some(NearestCandidate { entry, .. }) = self.queue.pop() {
+            match entry {
+                Entry::Leaf { label, item } => return Some((label, item)),
+                Entry::Branch { child, .. } => self.push_entries(&child.entries),
+            }
+        }
+        None
+    }
+}
+
+/// An entry in the priority queue for a nearest neighbour search. The ordering is reversed so
+/// that the entry that is closest to the point is at the head of the queue.
+struct NearestCandidate<'a, L, B>
+where
+    L: Label,
+    B: BoxBounded,
+{
+    distance_sq: <B::Point as Point>::Type,
+    entry: &'a Entry<L, B>,
+}
+
+impl<L, B> PartialEq for NearestCandidate<'_, L, B>
+where
+    L: Label,
+    B: BoxBounded,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<L, B> Eq for NearestCandidate<'_, L, B>
+where
+    L: Label,
+    B: BoxBounded,
+{
+}
+
+impl<L, B> PartialOrd for NearestCandidate<'_, L, B>
+where
+    L: Label,
+    B: BoxBounded,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<L, B> Ord for NearestCandidate<'_, L, B>
+where
+    L: Label,
+    B: BoxBounded,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance_sq
+            .partial_cmp(&self.distance_sq)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
 #[derive(Debug, Clone, Eq)]
 struct RTreeKey<L>(*const L);
 
@@ -640,13 +991,10 @@ where
 fn calculate_chunk_size(node_capacity: usize, coord_count: usize, entries_count: usize) -> usize {
     let leaf_pages = (entries_count as f64 / node_capacity as f64).ceil();
 
-    let vertical_chunks = if coord_count == 2 {
-        leaf_pages.sqrt()
-    } else if coord_count == 3 {
-        leaf_pages.cbrt()
-    } else {
-        // Only 2D and 3D data types are currently supported.
-        unreachable!()
+    let vertical_chunks = match coord_count {
+        2 => leaf_pages.sqrt(),
+        3 => leaf_pages.cbrt(),
+        n => leaf_pages.powf((n as f64).recip()),
     };
 
     node_capacity * (vertical_chunks.pow((coord_count - 1) as f64) as usize)
@@ -688,38 +1036,6 @@ where
         self.level == 0
     }
 
-    fn search(&self, area: &Rect<B::Point>) -> Option<Vec<&B>> {
-        let mut found = vec![];
-
-        if self.is_leaf() {
-            for entry in &self.entries {
-                match **entry {
-                    Entry::Leaf {
-                        item: ref entry, ..
-                    } if area.is_covering(entry.get_mbb()) => {
-                        found.push(entry);
-                    }
-                    _ => (),
-                }
-            }
-        } else {
-            for entry in &self.entries {
-                if area.is_intersecting(entry.get_mbb()) {
-                    match entry.search(area) {
-                        None => {}
-                        Some(matching) => found.extend(matching),
-                    }
-                }
-            }
-        }
-
-        if found.is_empty() {
-            None
-        } else {
-            Some(found)
-        }
-    }
-
     fn insert(&mut self, item: EntryPtr<L, B>, level: usize) -> MaybeSplit<L, B> {
         match *item {
             //If we have a branch and we are at the right level -> insert
@@ -852,6 +1168,48 @@ where
         }
     }
 
+    /// Replaces a leaf entry in place, if its new bounding box is still covered by the bounding box
+    /// of the node that contains it (`bound`, which is absent for the root). Returns whether the
+    /// entry was replaced.
+    fn replace(
+        &mut self,
+        bounding_box: &Rect<B::Point>,
+        label: &L,
+        entry: &EntryPtr<L, B>,
+        bound: Option<&Rect<B::Point>>,
+    ) -> bool {
+        if self.is_leaf() {
+            let entry_index = self.entries.iter().position(|current| {
+                matches!(
+                    **current,
+                    Entry::Leaf {
+                        label: ref current_label,
+                        item: ref current,
+                    } if current.get_mbb() == bounding_box && current_label == label
+                )
+            });
+
+            match entry_index {
+                Some(idx) if bound.is_none_or(|bound| bound.is_covering(entry.get_mbb())) => {
+                    self.entries[idx] = entry.clone();
+                    true
+                }
+                _ => false,
+            }
+        } else {
+            for current in self.entries.iter_mut() {
+                if current.get_mbb().is_covering(bounding_box) {
+                    if let Entry::Branch { mbb, child } = Arc::make_mut(current) {
+                        if child.replace(bounding_box, label, entry, Some(mbb)) {
+                            return true;
+                        }
+                    }
+                }
+            }
+            false
+        }
+    }
+
     fn split(&mut self) -> (EntryPtr<L, B>, EntryPtr<L, B>) {
         let ((first_group, first_mbb), (second_group, second_mbb)) =
             split(&mut self.entries, self.min_children, self.split_strat);
@@ -984,13 +1342,6 @@ where
         }
     }
 
-    fn search(&self, area: &Rect<B::Point>) -> Option<Vec<&B>> {
-        match self {
-            Entry::Branch { child, .. } => child.search(area),
-            Entry::Leaf { .. } => unreachable!(),
-        }
-    }
-
     fn get_mbb(&self) -> &Rect<B::Point> {
         match self {
             Entry::Leaf { item, .. } => item.get_mbb(),
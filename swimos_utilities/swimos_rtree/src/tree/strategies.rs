@@ -168,7 +168,7 @@ where
         for (i, item) in entries.iter().enumerate() {
             let mbb = item.get_mbb();
 
-            for dim in 0..B::get_coord_type() as usize {
+            for dim in 0..B::get_coord_type().dimensions() {
                 let low_dim = mbb.low.get_nth_coord(dim).unwrap();
                 let high_dim = mbb.high.get_nth_coord(dim).unwrap();
 
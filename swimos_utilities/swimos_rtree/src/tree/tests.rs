@@ -16,7 +16,8 @@ use swimos_num::non_zero_usize;
 
 use crate::rectangles::{Point2D, Point3D};
 use crate::tree::ChildrenSizeError;
-use crate::{BoxBounded, Label, Rect, SplitStrategy};
+use crate::{BoxBounded, CoordType, Label, Point, Rect, SplitStrategy};
+use std::cmp::Ordering;
 use std::fs;
 use std::ops::Sub;
 use std::sync::{Arc, Mutex};
@@ -556,6 +557,243 @@ fn search_multiple_results_3d_test() {
     assert_eq!(found.len(), 12);
 }
 
+#[test]
+fn search_iter_matches_search_test() {
+    let tree = build_2d_search_tree();
+    for area in [
+        rect!((6.0, 11.0), (7.0, 13.0)),
+        rect!((6.0, 1.0), (9.0, 6.0)),
+        rect!((7.0, 0.0), (14.0, 15.0)),
+        rect!((0.0, 0.0), (20.0, 20.0)),
+    ] {
+        let found = tree.search_iter(&area).collect::<Vec<_>>();
+        assert_eq!(tree.search(&area).unwrap_or_default(), found);
+    }
+
+    let tree = build_3d_search_tree();
+    let area = rect!((0.0, 0.0, 0.0), (20.0, 20.0, 20.0));
+    let mut found = tree.search_iter(&area);
+    assert_eq!(found.by_ref().take(5).count(), 5);
+    assert_eq!(found.count(), 7);
+}
+
+#[test]
+fn nearest_2d_test() {
+    let tree = build_2d_search_tree();
+    let nearest = tree
+        .nearest(&Point2D::new(6.0, 2.0), 3)
+        .into_iter()
+        .map(|(label, _)| label.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(nearest, vec!["First", "Twelfth", "Fifth"]);
+
+    assert!(tree.nearest(&Point2D::new(6.0, 2.0), 0).is_empty());
+    assert_eq!(tree.nearest(&Point2D::new(6.0, 2.0), 20).len(), 12);
+}
+
+#[test]
+fn nearest_3d_test() {
+    let tree = build_3d_search_tree();
+    let nearest = tree.nearest(&Point3D::new(5.0, 5.0, 18.0), 2);
+    assert_eq!(nearest.len(), 2);
+    let (label, item) = nearest[0];
+    assert_eq!(label, "Ninth");
+    assert_eq!(item, &rect!((2.0, 0.0, 13.0), (4.0, 10.0, 16.0)));
+    let (label, _) = nearest[1];
+    assert_eq!(label, "Third");
+}
+
+#[test]
+fn nearest_iter_ordered_by_distance_test() {
+    let tree = build_2d_search_tree();
+    let point = Point2D::new(11.0, 17.0);
+    let distances = tree
+        .nearest_iter(&point)
+        .map(|(_, item)| item.min_distance_sq(&point))
+        .collect::<Vec<_>>();
+    assert_eq!(distances.len(), 12);
+    assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+
+    let mut expected = tree
+        .iter()
+        .map(|(_, item)| item.min_distance_sq(&point))
+        .collect::<Vec<_>>();
+    expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(distances, expected);
+}
+
+#[test]
+fn nearest_empty_tree_test() {
+    let tree: RTree<String, Rect<Point2D<f64>>> = RTree::new(
+        non_zero_usize!(2),
+        non_zero_usize!(4),
+        SplitStrategy::Linear,
+    )
+    .unwrap();
+    assert!(tree.nearest(&Point2D::new(0.0, 0.0), 3).is_empty());
+    assert!(tree
+        .search_iter(&rect!((0.0, 0.0), (1.0, 1.0)))
+        .next()
+        .is_none());
+}
+
+#[test]
+fn bulk_load_4d_test() {
+    let items = (0..40)
+        .map(|i| {
+            let offset = f64::from(i);
+            let low = TestPoint4D([offset, 40.0 - offset, offset % 7.0, offset % 5.0]);
+            let high = TestPoint4D([
+                offset + 1.0,
+                41.0 - offset,
+                offset % 7.0 + 2.0,
+                offset % 5.0 + 3.0,
+            ]);
+            (i, Rect::new(low, high))
+        })
+        .collect::<Vec<_>>();
+
+    let tree = RTree::bulk_load(
+        non_zero_usize!(2),
+        non_zero_usize!(4),
+        SplitStrategy::Quadratic,
+        items,
+    )
+    .unwrap();
+    assert_eq!(tree.len(), 40);
+
+    let everything = Rect::new(
+        TestPoint4D([-1.0, -1.0, -1.0, -1.0]),
+        TestPoint4D([50.0, 50.0, 50.0, 50.0]),
+    );
+    assert_eq!(tree.search_iter(&everything).count(), 40);
+
+    let nearest = tree
+        .nearest(&TestPoint4D([10.5, 30.0, 3.5, 1.0]), 1)
+        .into_iter()
+        .map(|(label, _)| *label)
+        .collect::<Vec<_>>();
+    assert_eq!(nearest, vec![10]);
+}
+
+fn check_search_matches_items(tree: &RTree<String, Rect<Point2D<f64>>>) {
+    for area in [
+        rect!((0.0, 0.0), (10.0, 10.0)),
+        rect!((5.0, 5.0), (15.0, 15.0)),
+        rect!((-20.0, -20.0), (40.0, 40.0)),
+    ] {
+        let mut found = tree.search_iter(&area).copied().collect::<Vec<_>>();
+        let mut expected = tree
+            .iter()
+            .filter(|(_, item)| area.is_covering(*item))
+            .map(|(_, item)| *item)
+            .collect::<Vec<_>>();
+        let key = |rect: &Rect<Point2D<f64>>| format!("{:?}", rect);
+        found.sort_by_key(key);
+        expected.sort_by_key(key);
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn update_small_move_test() {
+    let mut tree = build_2d_search_tree();
+
+    let previous = tree.update(&"Tenth".to_string(), rect!((2.5, 2.5), (3.5, 3.5)));
+    assert_eq!(previous, Some(rect!((2.0, 2.0), (3.0, 3.0))));
+    assert_eq!(tree.len(), 12);
+
+    let found = tree.search(&rect!((2.4, 2.4), (3.6, 3.6))).unwrap();
+    assert_eq!(found, vec![&rect!((2.5, 2.5), (3.5, 3.5))]);
+    check_search_matches_items(&tree);
+}
+
+#[test]
+fn update_relocate_test() {
+    let mut tree = build_2d_search_tree();
+
+    let previous = tree.update(&"Tenth".to_string(), rect!((30.0, 30.0), (31.0, 31.0)));
+    assert_eq!(previous, Some(rect!((2.0, 2.0), (3.0, 3.0))));
+    assert_eq!(tree.len(), 12);
+
+    assert!(tree.search(&rect!((1.0, 1.0), (3.5, 3.5))).is_none());
+    let found = tree.search(&rect!((29.0, 29.0), (32.0, 32.0))).unwrap();
+    assert_eq!(found, vec![&rect!((30.0, 30.0), (31.0, 31.0))]);
+    check_search_matches_items(&tree);
+}
+
+#[test]
+fn update_missing_label_test() {
+    let mut tree = build_2d_search_tree();
+    let previous = tree.update(&"Thirteenth".to_string(), rect!((0.0, 0.0), (1.0, 1.0)));
+    assert!(previous.is_none());
+    assert_eq!(tree.len(), 12);
+    assert!(tree.remove(&"Thirteenth".to_string()).is_none());
+}
+
+#[test]
+fn repeated_updates_test() {
+    let mut tree = build_2d_search_tree();
+    let labels = tree
+        .iter()
+        .map(|(label, _)| label.clone())
+        .collect::<Vec<_>>();
+
+    for step in 1..=20 {
+        for (i, label) in labels.iter().enumerate() {
+            let offset = f64::from(step) * if i % 2 == 0 { 0.5 } else { -0.25 };
+            let base = f64::from(u32::try_from(i).unwrap());
+            let item = rect!(
+                (base + offset, base - offset),
+                (base + offset + 1.0, base - offset + 1.0)
+            );
+            assert!(tree.update(label, item).is_some());
+        }
+        assert_eq!(tree.len(), labels.len());
+        check_search_matches_items(&tree);
+    }
+
+    for label in &labels {
+        assert!(tree.remove(label).is_some());
+    }
+    assert!(tree.is_empty());
+}
+
+#[test]
+fn retain_few_test() {
+    let mut tree = build_2d_search_tree();
+    tree.retain(|label, _| label != "First" && label != "Second");
+
+    assert_eq!(tree.len(), 10);
+    assert!(tree.remove(&"First".to_string()).is_none());
+    assert!(tree.remove(&"Second".to_string()).is_none());
+    check_search_matches_items(&tree);
+}
+
+#[test]
+fn retain_most_test() {
+    let mut tree = build_2d_search_tree();
+    tree.retain(|label, _| label == "Fifth" || label == "Eighth");
+
+    assert_eq!(tree.len(), 2);
+    let mut labels = tree
+        .iter()
+        .map(|(label, _)| label.as_str())
+        .collect::<Vec<_>>();
+    labels.sort();
+    assert_eq!(labels, vec!["Eighth", "Fifth"]);
+    check_search_matches_items(&tree);
+
+    tree.insert("Thirteenth".to_string(), rect!((1.0, 1.0), (2.0, 2.0)))
+        .unwrap();
+    assert_eq!(tree.len(), 3);
+    check_search_matches_items(&tree);
+
+    tree.retain(|_, _| false);
+    assert!(tree.is_empty());
+    assert!(tree.search(&rect!((-20.0, -20.0), (40.0, 40.0))).is_none());
+}
+
 #[test]
 fn tree_iterator_test() {
     let items = vec![
@@ -1156,6 +1394,45 @@ fn search_single_no_clone() {
     assert_eq!(found.len(), 1);
 }
 
+#[test]
+fn update_no_clone() {
+    let clone_count = CloneCount::new();
+
+    let items = (0..12)
+        .map(|i| {
+            let offset = f64::from(i) * 2.0;
+            (
+                i,
+                CloneTracker::new(
+                    rect!((offset, offset), (offset + 1.0, offset + 1.0)),
+                    clone_count.clone(),
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut rtree = RTree::bulk_load(
+        non_zero_usize!(2),
+        non_zero_usize!(4),
+        SplitStrategy::Linear,
+        items,
+    )
+    .unwrap();
+
+    // Small move, within the same node.
+    let moved = CloneTracker::new(rect!((0.5, 0.5), (1.5, 1.5)), clone_count.clone());
+    assert!(rtree.update(&0, moved).is_some());
+    // Large move, to another node.
+    let moved = CloneTracker::new(rect!((40.0, 40.0), (41.0, 41.0)), clone_count.clone());
+    assert!(rtree.update(&1, moved).is_some());
+
+    assert_eq!(clone_count.get(), 0);
+    assert_eq!(rtree.len(), 12);
+
+    assert!(rtree.remove(&2).is_some());
+    assert_eq!(clone_count.get(), 0);
+}
+
 #[test]
 fn search_multiple_no_clone() {
     let clone_count = CloneCount::new();
@@ -1298,13 +1575,69 @@ impl CloneCount {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, PartialOrd, Debug)]
-struct TestPoint4D {}
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct TestPoint4D([f64; 4]);
+
+impl TestPoint4D {
+    fn zip_with(&self, other: &Self, f: impl Fn(f64, f64) -> f64) -> Self {
+        let TestPoint4D(left) = self;
+        let TestPoint4D(right) = other;
+        TestPoint4D(std::array::from_fn(|i| f(left[i], right[i])))
+    }
+}
+
+impl PartialOrd for TestPoint4D {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let TestPoint4D(left) = self;
+        let TestPoint4D(right) = other;
+        if left == right {
+            Some(Ordering::Equal)
+        } else if left.iter().zip(right).all(|(l, r)| l >= r) {
+            Some(Ordering::Greater)
+        } else if left.iter().zip(right).all(|(l, r)| l <= r) {
+            Some(Ordering::Less)
+        } else {
+            None
+        }
+    }
+}
 
 impl Sub for TestPoint4D {
     type Output = TestPoint4D;
 
-    fn sub(self, _rhs: Self) -> Self::Output {
-        unimplemented!()
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip_with(&rhs, |l, r| l - r)
+    }
+}
+
+impl Point for TestPoint4D {
+    type Type = f64;
+
+    fn get_coord_type() -> CoordType {
+        CoordType::MultiDimensional(4)
+    }
+
+    fn get_nth_coord(&self, n: usize) -> Option<f64> {
+        self.0.get(n).copied()
+    }
+
+    fn mean(&self, other: &Self) -> Self {
+        self.zip_with(other, |l, r| (l + r) / 2.0)
+    }
+
+    fn multiply_coord(&self) -> f64 {
+        self.0.iter().product()
+    }
+
+    fn has_any_matching_coords(&self, other: &Self) -> bool {
+        self.0.iter().zip(&other.0).any(|(l, r)| l == r)
+    }
+
+    fn get_lowest(&self, other: &Self) -> Self {
+        self.zip_with(other, f64::min)
+    }
+
+    fn get_highest(&self, other: &Self) -> Self {
+        self.zip_with(other, f64::max)
     }
 }